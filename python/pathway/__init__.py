@@ -5,6 +5,7 @@ from __future__ import annotations
 import pathway._engine_finder  # noqa: F401  # isort: split
 from pathway.internals import warnings  # noqa: F401  # isort: split
 
+import pathway.errors as errors
 import pathway.reducers as reducers
 import pathway.universes as universes
 from pathway import debug, demo, io, udfs
@@ -21,10 +22,12 @@ from pathway.internals import (
     JoinMode,
     JoinResult,
     Json,
+    LicenseInfo,
     LiveTable,
     MonitoringLevel,
     Pointer,
     PyObjectWrapper,
+    RunHandle,
     Schema,
     SchemaProperties,
     Table,
@@ -41,6 +44,7 @@ from pathway.internals import (
     declare_type,
     enable_interactive_mode,
     fill_error,
+    format_str,
     global_error_log,
     groupby,
     if_else,
@@ -52,16 +56,24 @@ from pathway.internals import (
     join_outer,
     join_right,
     left,
+    license_info,
     load_yaml,
     local_error_log,
     make_tuple,
+    native_apply,
+    pause_connector,
+    request_shutdown,
     require,
+    resume_connector,
     right,
     run,
     run_all,
+    run_in_background,
+    runtime_metrics,
     schema_from_csv,
     schema_from_dict,
     schema_from_types,
+    set_async_runtime_config,
     set_license_key,
     set_monitoring_config,
     sql,
@@ -69,6 +81,7 @@ from pathway.internals import (
     this,
     udf,
     unwrap,
+    wasm_apply,
     wrap_py_object,
 )
 from pathway.internals.api import PathwayType as Type, PersistenceMode
@@ -77,8 +90,10 @@ from pathway.schema import schema_builder
 from pathway.stdlib import (
     graphs,
     indexing,
+    lineage,
     ml,
     ordered,
+    quality,
     stateful,
     statistical,
     temporal,
@@ -89,6 +104,7 @@ from pathway.stdlib.utils.async_transformer import AsyncTransformer
 from pathway.stdlib.utils.pandas_transformer import pandas_transformer
 
 import pathway.persistence as persistence  # isort: skip
+import pathway.secrets as secrets  # isort: skip
 
 __all__ = [
     "asynchronous",
@@ -113,6 +129,7 @@ __all__ = [
     "pandas_transformer",
     "AsyncTransformer",
     "reducers",
+    "errors",
     "schema_from_types",
     "Table",
     "TableLike",
@@ -134,8 +151,16 @@ __all__ = [
     "sql",
     "run",
     "run_all",
+    "request_shutdown",
+    "pause_connector",
+    "resume_connector",
+    "run_in_background",
+    "RunHandle",
     "if_else",
     "make_tuple",
+    "format_str",
+    "native_apply",
+    "wasm_apply",
     "Type",
     "__version__",
     "io",
@@ -163,6 +188,8 @@ __all__ = [
     "table_transformer",
     "BaseCustomAccumulator",
     "stateful",
+    "quality",
+    "lineage",
     "viz",
     "PersistenceMode",
     "join",
@@ -174,8 +201,13 @@ __all__ = [
     "enable_interactive_mode",
     "LiveTable",
     "persistence",
+    "secrets",
+    "set_async_runtime_config",
     "set_license_key",
+    "license_info",
+    "LicenseInfo",
     "set_monitoring_config",
+    "runtime_metrics",
     "global_error_log",
     "local_error_log",
     "load_yaml",
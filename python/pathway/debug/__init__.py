@@ -26,11 +26,16 @@ from os import PathLike
 from warnings import warn
 
 import pandas as pd
+import pyarrow as pa
 
 from pathway import persistence
 from pathway.internals import Json, api, parse_graph
 from pathway.internals.config import get_pathway_config
-from pathway.internals.datasource import DataSourceOptions, PandasDataSource
+from pathway.internals.datasource import (
+    ArrowDataSource,
+    DataSourceOptions,
+    PandasDataSource,
+)
 from pathway.internals.fingerprints import fingerprint
 from pathway.internals.graph_runner import GraphRunner
 from pathway.internals.monitoring import MonitoringLevel
@@ -298,6 +303,61 @@ def table_to_pandas(table: Table, *, include_id: bool = True):
     return res
 
 
+@check_arg_types
+@trace_user_frame
+def table_to_arrow(table: Table, *, include_id: bool = True) -> pa.RecordBatch:
+    """Runs the computations needed to get the contents of the table and returns them
+    as a ``pyarrow.RecordBatch``. The batch is built in the engine, without creating a
+    Python object for each cell, so it's cheaper than :py:func:`table_to_pandas` for
+    big tables.
+
+    Args:
+        table: the table to be converted.
+        include_id: whether to include the ``id`` column with row ids (as strings).
+
+    Example:
+
+    >>> import pathway as pw
+    >>> t = pw.debug.table_from_markdown('''
+    ... pet | age
+    ... dog | 10
+    ... cat | 8
+    ... ''')
+    >>> batch = pw.debug.table_to_arrow(t, include_id=False)
+    >>> sorted(batch.column("age").to_pylist())
+    [8, 10]
+    """
+    captured = _compute_tables(table)[0]
+    column_names = list(table._columns.keys())
+    dtypes = table.schema._dtypes()
+    batch = pa.record_batch(
+        api.captured_table_to_arrow(
+            captured, column_names, [dtypes[name].to_engine() for name in column_names]
+        )
+    )
+    if not include_id:
+        batch = pa.RecordBatch.from_arrays(
+            [batch.column(name) for name in column_names], names=column_names
+        )
+    return batch
+
+
+def table_to_polars(table: Table, *, include_id: bool = True):
+    """Runs the computations needed to get the contents of the table and returns them
+    as a ``polars.DataFrame``, built from :py:func:`table_to_arrow` without copying.
+    Requires ``polars`` to be installed.
+
+    Args:
+        table: the table to be converted.
+        include_id: whether to include the ``id`` column with row ids (as strings).
+    """
+    try:
+        import polars as pl
+    except ImportError as e:
+        raise ImportError("table_to_polars requires polars to be installed") from e
+    return pl.from_arrow(table_to_arrow(table, include_id=include_id))
+
+
 def _validate_dataframe(df: pd.DataFrame, stacklevel: int = 1) -> None:
     for pseudocolumn in api.PANDAS_PSEUDOCOLUMNS:
         if pseudocolumn in df.columns:
@@ -419,6 +479,65 @@ def table_from_pandas(
     return ret
 
 
+@check_arg_types
+@trace_user_frame
+def table_from_arrow(
+    batch: Any,
+    *,
+    schema: type[Schema] | None = None,
+    id_from: list[str] | None = None,
+) -> Table:
+    """A function for creating a table from an Arrow record batch, e.g. a
+    ``pyarrow.RecordBatch`` or any other object implementing ``__arrow_c_array__``.
+    The batch is passed to the engine through the Arrow C data interface, without
+    creating a Python object for each cell. Like in :py:func:`table_from_pandas`, special columns
+    ``__time__`` and ``__diff__`` can be used to set times and event types of the rows.
+
+    Args:
+        batch: the Arrow record batch.
+        schema: schema of the table. If not given, it is inferred from the Arrow schema.
+        id_from: columns used to compute ids of the rows. If not given, primary key
+            columns of the schema are used or, if there are none, the row positions, as
+            in :py:func:`table_from_pandas` with the default index.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> import pyarrow as pa
+    >>> batch = pa.record_batch({"pet": ["dog", "cat"], "age": [10, 8]})
+    >>> t = pw.debug.table_from_arrow(batch)
+    >>> pw.debug.compute_and_print(t, include_id=False)
+    pet | age
+    cat | 8
+    dog | 10
+    """
+    if id_from is not None and schema is not None:
+        raise ValueError("parameters `schema` and `id_from` are mutually exclusive")
+    if not isinstance(batch, pa.RecordBatch):
+        batch = pa.record_batch(batch)
+
+    ordinary_columns_names = [
+        column
+        for column in batch.schema.names
+        if column not in api.PANDAS_PSEUDOCOLUMNS
+    ]
+    if schema is None:
+        schema = schema_from_pandas(
+            batch.slice(0, 0).to_pandas(),
+            id_from=id_from,
+            exclude_columns=api.PANDAS_PSEUDOCOLUMNS,
+        )
+    elif set(ordinary_columns_names) != set(schema.column_names()):
+        raise ValueError("schema does not match given record batch")
+
+    if id_from is None:
+        id_from = schema.primary_key_columns()
+
+    return table_from_datasource(
+        ArrowDataSource(schema=schema, data=batch, id_from=id_from)
+    )
+
+
 def _markdown_to_pandas(table_def: str, split_on_whitespace: bool = True):
     table_def = table_def.lstrip("\n")
     if split_on_whitespace:
@@ -153,6 +153,50 @@ class DataRow:
         dtypes: list[PathwayType],
     ) -> None: ...
 
+class ArrowBatch:
+    """Arrow record batch produced by the engine, see ``__arrow_c_array__``."""
+
+    @property
+    def num_rows(self) -> int: ...
+    @property
+    def column_names(self) -> list[str]: ...
+    def __arrow_c_array__(self, requested_schema: Any = None) -> tuple[Any, Any]: ...
+    def __len__(self) -> int: ...
+
+class OperatorStats:
+    time: int | None
+    lag: int | None
+    done: bool
+
+class CountStats:
+    total_rows: int
+    current_rows: int
+
+class ConnectorStats:
+    num_messages_from_start: int
+    num_messages_in_last_minute: int
+    num_messages_recently_committed: int
+    finished: bool
+    time: int | None
+    epoch_duration: int | None
+    backfill: BackfillProgress | None
+
+class BackfillProgress:
+    objects_processed: int
+    objects_discovered: int
+    bytes_processed: int
+    bytes_discovered: int
+    finished: bool
+    eta: int | None
+
+class ProberStats:
+    input_stats: OperatorStats
+    output_stats: OperatorStats
+    operators_stats: dict[int, OperatorStats]
+    connector_stats: list[tuple[str, ConnectorStats]]
+    output_connector_stats: list[tuple[str, OperatorStats]]
+    row_counts: dict[int, CountStats]
+
 class MissingValueError(BaseException):
     "Marker class to indicate missing attributes"
 
@@ -163,6 +207,20 @@ class EngineErrorWithTrace(Exception):
     "Marker class to indicate engine error with trace"
     args: tuple[Exception, Trace | None]
 
+class ParseError(EngineError, ValueError):
+    "Raised when the data can't be parsed into values of the declared types."
+
+class ConnectorError(EngineError):
+    "Raised when an input or output connector fails."
+    connector_name: str | None
+
+class PersistenceError(EngineError):
+    "Raised when the persisted state can't be read or written."
+
+class LicenseError(EngineError, RuntimeError):
+    "Raised when the license doesn't allow using a feature."
+    entitlements: tuple[str, ...]
+
 class OtherWorkerError(Exception):
     "Marker class to indicate engine error resulting from other worker failure"
 
@@ -244,8 +302,28 @@ class Expression:
         /,
         *args: Expression,
         dtype: PathwayType,
+        arg_dtypes: list[PathwayType] | None = None,
         propagate_none: bool = False,
         max_batch_size: int | None = None,
+        vectorized: bool = False,
+        cache: UdfCache | None = None,
+        error_policy: UdfErrorPolicy | None = None,
+    ) -> Expression: ...
+    @staticmethod
+    def native_apply(
+        library_path: str,
+        function_name: str,
+        *args: Expression,
+        propagate_none: bool = False,
+    ) -> Expression: ...
+    @staticmethod
+    def wasm_apply(
+        module_path: str,
+        function_name: str,
+        *args: Expression,
+        propagate_none: bool = False,
+        max_memory_bytes: int | None = None,
+        timeout: datetime.timedelta | None = None,
     ) -> Expression: ...
     @staticmethod
     def async_apply(
@@ -255,6 +333,12 @@ class Expression:
         *args: Expression,
         dtype: PathwayType,
         propagate_none: bool = False,
+        max_in_flight: int | None = None,
+        timeout: datetime.timedelta | None = None,
+        timeout_policy: AsyncTimeoutPolicy = AsyncTimeoutPolicy.FAIL,
+        deduplicate: bool = False,
+        cache: UdfCache | None = None,
+        error_policy: UdfErrorPolicy | None = None,
     ) -> Expression: ...
     @staticmethod
     def is_none(expr: Expression) -> Expression: ...
@@ -410,6 +494,8 @@ class Expression:
     @staticmethod
     def make_tuple(*args: Expression) -> Expression: ...
     @staticmethod
+    def format(template: str, *args: Expression) -> Expression: ...
+    @staticmethod
     def sequence_get_item_checked(
         expr: Expression, index: Expression, default: Expression
     ) -> Expression: ...
@@ -428,8 +514,58 @@ class Expression:
     @staticmethod
     def to_string(expr: Expression) -> Expression: ...
     @staticmethod
+    def encode_base64(expr: Expression) -> Expression: ...
+    @staticmethod
+    def decode_base64(expr: Expression) -> Expression: ...
+    @staticmethod
+    def encode_hex(expr: Expression) -> Expression: ...
+    @staticmethod
+    def decode_hex(expr: Expression) -> Expression: ...
+    @staticmethod
+    def to_utf8(expr: Expression) -> Expression: ...
+    @staticmethod
+    def from_utf8_lossy(expr: Expression) -> Expression: ...
+    @staticmethod
     def fill_error(expr: Expression, replacement: Expression) -> Expression: ...
 
+class AsyncTimeoutPolicy(Enum):
+    FAIL: AsyncTimeoutPolicy
+    RETURN_NONE: AsyncTimeoutPolicy
+
+class CancellationToken:
+    def __init__(self) -> None: ...
+    def cancel(self) -> None: ...
+    @property
+    def cancelled(self) -> bool: ...
+
+class UdfCache:
+    def __init__(
+        self,
+        name: str,
+        *,
+        max_entries: int | None = None,
+        ttl: datetime.timedelta | None = None,
+        storage: DataStorage | None = None,
+    ) -> None: ...
+
+class UdfErrorAction(Enum):
+    PROPAGATE: UdfErrorAction
+    RETURN_DEFAULT: UdfErrorAction
+    SEND_TO_ERROR_LOG: UdfErrorAction
+    FAIL: UdfErrorAction
+
+class UdfErrorPolicy:
+    def __init__(
+        self,
+        *,
+        on_error: UdfErrorAction = UdfErrorAction.PROPAGATE,
+        default_value: Any = None,
+        max_retries: int = 0,
+        retry_delay: datetime.timedelta | None = None,
+        backoff_factor: float | None = None,
+        jitter: datetime.timedelta | None = None,
+    ) -> None: ...
+
 class MonitoringLevel(Enum):
     NONE = 0
     IN_OUT = 1
@@ -505,6 +641,14 @@ class Scope:
         rows: Iterable[DataRow],
         dt: DType,
     ) -> Table: ...
+    def static_table_from_arrow(
+        self,
+        batch: Any,
+        column_names: list[str],
+        properties: ConnectorProperties,
+        *,
+        id_from: list[str] | None = None,
+    ) -> Table: ...
     def map_column(
         self,
         table: LegacyTable,
@@ -546,9 +690,22 @@ class Scope:
         upper_column: ColumnPath,
         table_properties: TableProperties,
     ) -> Table: ...
+    def keyed_mat_mul(
+        self,
+        table: Table,
+        group_column: ColumnPath,
+        operand_column: ColumnPath,
+        matrix_table: Table,
+        matrix_group_column: ColumnPath,
+        matrix_column: ColumnPath,
+        table_properties: TableProperties,
+    ) -> Table: ...
     def filter_table(
         self, table: Table, path: ColumnPath, table_properties: TableProperties
     ) -> Table: ...
+    def sql_query(
+        self, query: str, tables: dict[str, tuple[Table, list[tuple[str, PathwayType]]]]
+    ) -> tuple[Table, list[str]]: ...
     def forget(
         self,
         table: Table,
@@ -636,6 +793,14 @@ class Scope:
         unique_name: str | None,
         table_properties: TableProperties,
     ) -> Table: ...
+    def drop_duplicates(
+        self,
+        table: Table,
+        grouping_columns: list[ColumnPath],
+        reduced_columns: list[ColumnPath],
+        unique_name: str | None,
+        table_properties: TableProperties,
+    ) -> Table: ...
     def ix_table(
         self,
         to_ix_table: Table,
@@ -714,6 +879,19 @@ class Scope:
         unique_name: str | None = None,
         sort_by_indices: Iterable[int] | None = None,
     ): ...
+    def subscribe_table_arrow(
+        self,
+        table: Table,
+        column_paths: Iterable[ColumnPath],
+        column_names: list[str],
+        dtypes: list[PathwayType],
+        on_batch: Callable[[ArrowBatch, int], None],
+        on_end: Callable[[], None],
+        *,
+        skip_persisted_batch: bool = False,
+        skip_errors: bool = False,
+        unique_name: str | None = None,
+    ): ...
     def output_table(
         self,
         table: Table,
@@ -742,6 +920,21 @@ class Scope:
         table: Table,
         table_properties: TableProperties,
     ) -> Table: ...
+    def sample_table(
+        self,
+        table: Table,
+        fraction: float,
+        seed: int,
+        table_properties: TableProperties,
+    ) -> Table: ...
+    def reservoir_sample_table(
+        self,
+        table: Table,
+        instance_column_path: ColumnPath,
+        size: int,
+        seed: int,
+        table_properties: TableProperties,
+    ) -> Table: ...
     def async_transformer(
         self,
         table: Table,
@@ -817,6 +1010,21 @@ def run_with_new_graph(
     run_id: str | None = None,
     terminate_on_error: bool = True,
     max_expression_batch_size: int,
+    prometheus_port: int | None = None,
+    resource_attributes: dict[str, str] = {},
+    metric_attributes: dict[str, str] = {},
+    trace_sample_ratio: float = 1.0,
+    metric_allowlist: list[str] = [],
+    metric_denylist: list[str] = [],
+    telemetry_export_dir: str | None = None,
+    usage_telemetry_enabled: bool = True,
+    metrics_enabled: bool = True,
+    traces_enabled: bool = True,
+    async_worker_threads_per_runtime: int | None = None,
+    async_max_blocking_threads: int | None = None,
+    async_thread_name_prefix: str | None = None,
+    async_runtime_groups: dict[str, list[str]] = {},
+    cancellation_token: CancellationToken | None = None,
 ) -> list[CapturedStream]: ...
 def unsafe_make_pointer(arg) -> Pointer: ...
 
@@ -858,6 +1066,136 @@ class DeltaOptimizerRule:
 class MqttSettings:
     def __init__(self, qos: int, retain: bool): ...
 
+class SubprocessSettings:
+    def __init__(
+        self,
+        command: list[str],
+        *,
+        env: dict[str, str] | None = None,
+        working_directory: str | None = None,
+        restart_policy: str = "never",
+        max_restarts: int | None = None,
+        restart_delay: datetime.timedelta = datetime.timedelta(seconds=1),
+    ): ...
+
+class CustomConnectorSettings:
+    def __init__(
+        self,
+        *,
+        read: Callable[[bytes | None], Iterable[tuple[bytes, bytes | None]]]
+        | None = None,
+        write: Callable[[bytes, int, int], None] | None = None,
+        flush: Callable[[], None] | None = None,
+        finish: Callable[[], None] | None = None,
+        max_queue_size: int = 1024,
+    ): ...
+
+class SqlPollingSettings:
+    def __init__(
+        self,
+        query: str,
+        cursor_column: str,
+        start_from: Value,
+        *,
+        poll_interval: datetime.timedelta = datetime.timedelta(seconds=1),
+    ): ...
+
+class OracleSettings:
+    def __init__(self, user: str, password: str, connect_string: str): ...
+
+class ColumnMasking:
+    def __init__(
+        self, column_name: str, method: str, *, key: bytes | None = None
+    ): ...
+
+class SinkPredicate:
+    @staticmethod
+    def compare(column_index: int, op: str, value: Value) -> SinkPredicate: ...
+    @staticmethod
+    def is_none(column_index: int) -> SinkPredicate: ...
+    @staticmethod
+    def all_of(predicates: list[SinkPredicate]) -> SinkPredicate: ...
+    @staticmethod
+    def any_of(predicates: list[SinkPredicate]) -> SinkPredicate: ...
+    @staticmethod
+    def negation(predicate: SinkPredicate) -> SinkPredicate: ...
+
+class ImapSettings:
+    def __init__(
+        self,
+        host: str,
+        username: str,
+        password: str,
+        *,
+        port: int = 993,
+        mailbox: str = "INBOX",
+        refresh_interval: datetime.timedelta = datetime.timedelta(seconds=30),
+    ): ...
+
+class PrometheusSettings:
+    def __init__(
+        self,
+        metric_fields: list[tuple[str, int]],
+        label_fields: list[tuple[str, int]],
+        *,
+        timestamp_field_index: int | None = None,
+        headers: dict[str, str] | None = None,
+    ): ...
+
+class FileRotationSettings:
+    def __init__(
+        self,
+        table_name: str,
+        extension: str,
+        *,
+        name_template: str = "{table}-{date}-{part}.{ext}",
+        max_file_size: int | None = None,
+        rotation_interval: datetime.timedelta | None = None,
+        write_success_markers: bool = False,
+        repeat_header: bool = False,
+    ): ...
+
+class KafkaLagThrottlingSettings:
+    def __init__(
+        self,
+        *,
+        check_interval: datetime.timedelta = datetime.timedelta(seconds=5),
+        min_lag: int = 1000,
+    ): ...
+
+class IcebergCatalogSettings:
+    def __init__(
+        self,
+        catalog_type: str,
+        *,
+        uri: str | None = None,
+        catalog_id: str | None = None,
+        properties: dict[str, str] | None = None,
+    ): ...
+
+class BackfillThenStreamSettings:
+    def __init__(
+        self,
+        backfill_storage: DataStorage,
+        backfill_format: DataFormat,
+        stream_storage: DataStorage,
+        stream_format: DataFormat,
+        *,
+        seam_column: str,
+        seam_value: Value,
+    ): ...
+
+class ConnectorRetryPolicy:
+    def __init__(
+        self,
+        *,
+        max_retries: int | None = None,
+        initial_delay: datetime.timedelta | None = None,
+        backoff_factor: float | None = None,
+        jitter: datetime.timedelta | None = None,
+        max_elapsed_time: datetime.timedelta | None = None,
+    ) -> None: ...
+
 class TableWriterInitMode(Enum):
     DEFAULT: TableWriterInitMode
     CREATE_IF_NOT_EXISTS: TableWriterInitMode
@@ -901,8 +1239,25 @@ class DataStorage:
         mqtt_settings: MqttSettings | None = None,
         only_provide_metadata: bool = False,
         sort_key_index: int | None = None,
+        retry_policy: ConnectorRetryPolicy | None = None,
+        request_timeout: datetime.timedelta | None = None,
+        subprocess_settings: SubprocessSettings | None = None,
+        backfill_then_stream_settings: BackfillThenStreamSettings | None = None,
+        iceberg_catalog_settings: IcebergCatalogSettings | None = None,
+        read_change_data_feed: bool = False,
+        partition_field_index: int | None = None,
+        imap_settings: ImapSettings | None = None,
+        prometheus_settings: PrometheusSettings | None = None,
+        file_rotation_settings: FileRotationSettings | None = None,
+        kafka_lag_throttling_settings: KafkaLagThrottlingSettings | None = None,
+        sql_polling_settings: SqlPollingSettings | None = None,
+        oracle_settings: OracleSettings | None = None,
+        parquet_row_group_size: int | None = None,
+        custom_connector_settings: CustomConnectorSettings | None = None,
     ) -> None: ...
     def delta_s3_storage_options(self, *args, **kwargs): ...
+    def iceberg_list_namespaces(self) -> list[list[str]]: ...
+    def iceberg_list_tables(self) -> list[str]: ...
 
 class CsvParserSettings:
     def __init__(self, *args, **kwargs): ...
@@ -981,6 +1336,10 @@ class TelemetryConfig:
     service_instance_id: str | None
     run_id: str
     license_key: str | None
+    resource_attributes: dict[str, str]
+    metric_attributes: dict[str, str]
+    trace_sample_ratio: float
+    export_dir: str | None
     @staticmethod
     def create(
         *,
@@ -988,6 +1347,13 @@ class TelemetryConfig:
         license_key: str | None = None,
         monitoring_server: str | None = None,
         metrics_reader_interval_secs: int | None = None,
+        resource_attributes: dict[str, str] = {},
+        metric_attributes: dict[str, str] = {},
+        trace_sample_ratio: float = 1.0,
+        export_dir: str | None = None,
+        usage_telemetry_enabled: bool = True,
+        metrics_enabled: bool = True,
+        traces_enabled: bool = True,
     ) -> TelemetryConfig: ...
 
 class ExternalIndexFactory:
@@ -1000,20 +1366,79 @@ class ExternalIndexFactory:
         connectivity: int,
         expansion_add: int,
         expansion_search: int,
+        snapshot_storage: DataStorage | None = None,
+        snapshot_name: str = "usearch",
+        snapshot_interval: int = 100_000,
     ) -> ExternalIndexFactory: ...
     @staticmethod
     def tantivy_factory(
         *,
         ram_budget: int,
         in_memory_index: bool,
+        fields: list[tuple[str, float]] | None = None,
+        tokenizer: str = "simple",
+        ngram_min: int = 2,
+        ngram_max: int = 3,
+        ngram_prefix_only: bool = False,
+        lowercase: bool = True,
+        stemming_language: str | None = None,
+        fuzzy_distance: int | None = None,
+        fuzzy_prefix: bool = False,
+        fuzzy_transpose_cost_one: bool = True,
+    ) -> ExternalIndexFactory: ...
+    @staticmethod
+    def hybrid_factory(
+        lexical_factory: ExternalIndexFactory,
+        vector_factory: ExternalIndexFactory,
+        *,
+        fusion: str = "rrf",
+        rrf_k: float = 60.0,
+        lexical_weight: float = 0.5,
+        vector_weight: float = 0.5,
+        candidates_multiplier: int = 2,
+    ) -> ExternalIndexFactory: ...
+    @staticmethod
+    def named_vectors_factory(
+        spaces: dict[str, ExternalIndexFactory],
+        *,
+        candidates_multiplier: int = 2,
+    ) -> ExternalIndexFactory: ...
+    @staticmethod
+    def faiss_knn_factory(
+        dimensions: int,
+        *,
+        kind: str = "ivf_flat",
+        metric: str = "l2sq",
+        nlist: int = 1024,
+        nprobe: int = 16,
+        pq_subquantizers: int = 8,
+        pq_bits: int = 8,
+        training_sample_size: int | None = None,
+        retrain_growth_factor: float | None = None,
+    ) -> ExternalIndexFactory: ...
+    def with_maintenance(
+        self,
+        *,
+        max_removed_fraction: float = 0.3,
+        min_removals: int = 1000,
+        rebuild_batch_size: int = 1000,
     ) -> ExternalIndexFactory: ...
     @staticmethod
+    def geo_factory() -> ExternalIndexFactory: ...
+    @staticmethod
     def brute_force_knn_factory(
         *,
         dimensions: int,
         reserved_space: int,
         auxiliary_space: int,
         metric: BruteForceKnnMetricKind,
+        parallel_chunk_size: int | None = None,
+        quantization: str | None = None,
+        pq_subquantizers: int = 8,
+        pq_training_sample_size: int = 10_000,
+        rerank: bool = True,
+        rerank_multiplier: int = 4,
+        gpu_device: int | None = None,
     ) -> ExternalIndexFactory: ...
 
 @dataclasses.dataclass(frozen=True)
@@ -1049,8 +1474,46 @@ def check_entitlements(
     license_key: str | None,
     entitlements: list[str],
 ): ...
+
+class LicenseInfo:
+    is_offline: bool
+    policy: str | None
+    entitlements: list[str] | None
+    expiration_date: str | None
+    file_expiration_date: str | None
+
+def license_info(*, license_key: str | None) -> LicenseInfo: ...
 def deserialize(data: bytes) -> Value: ...
 def serialize(value: Value) -> bytes: ...
+def runtime_stats() -> ProberStats | None: ...
+def request_shutdown() -> None: ...
+def set_connector_paused_state(name: str, paused: bool) -> None: ...
+def export_table_snapshot(
+    name: str,
+    path: str,
+    format: str,
+    time: int | None = None,
+    timeout: datetime.timedelta | None = None,
+) -> tuple[int, int | None]: ...
+def register_schema(
+    name: str,
+    value_fields: list[ValueField],
+    key_field_names: list[str] | None = None,
+) -> None: ...
+def is_shutdown_requested() -> bool: ...
+def current_log_context() -> tuple[int | None, str | None]: ...
+def env_var_or_config(name: str) -> str | None: ...
+def enable_schema_drift_reporting() -> None: ...
+def take_schema_drift_events() -> list[tuple[str, str, str, str]]: ...
+def record_constraint_violations(check: str, constraint: str, diff: int) -> None: ...
+def constraint_violations() -> list[tuple[str, str, int, int]]: ...
+def captured_table_to_arrow(
+    captured: list[DataRow],
+    column_names: list[str],
+    dtypes: list[PathwayType],
+    *,
+    squash: bool = True,
+) -> ArrowBatch: ...
 
 T = TypeVar("T")
 
@@ -0,0 +1,42 @@
+# Copyright © 2024 Pathway
+"""Exceptions raised by the Pathway engine.
+
+All of them derive from :py:class:`EngineError`, so that failures of the computation
+can be told apart from errors in the user code. The more specific classes carry
+structured information about the failure:
+
+- :py:class:`ParseError` - the data can't be parsed into values of the declared types,
+- :py:class:`ConnectorError` - an input or output connector has failed, the name of
+  the connector is available as ``connector_name``,
+- :py:class:`PersistenceError` - the persisted state can't be read or written,
+- :py:class:`LicenseError` - the license doesn't allow using a feature, the missing
+  entitlements are available as ``entitlements``.
+
+Exceptions raised by Python code called by the engine, for example by a UDF or by
+a :py:class:`~pathway.io.python.ConnectorSubject`, are propagated with their
+original types.
+
+Typical use:
+
+>>> import pathway as pw
+>>> try:
+...     pw.run()
+... except pw.errors.ConnectorError as e:
+...     print(f"connector {e.connector_name} has failed")
+"""
+
+from pathway.internals.api import (
+    ConnectorError,
+    EngineError,
+    LicenseError,
+    ParseError,
+    PersistenceError,
+)
+
+__all__ = [
+    "EngineError",
+    "ParseError",
+    "ConnectorError",
+    "PersistenceError",
+    "LicenseError",
+]
@@ -18,14 +18,23 @@ from pathway.internals.common import (
     coalesce,
     declare_type,
     fill_error,
+    format_str,
     if_else,
     iterate,
     make_tuple,
+    native_apply,
     require,
     table_transformer,
     unwrap,
+    wasm_apply,
+)
+from pathway.internals.config import (
+    LicenseInfo,
+    license_info,
+    set_async_runtime_config,
+    set_license_key,
+    set_monitoring_config,
 )
-from pathway.internals.config import set_license_key, set_monitoring_config
 from pathway.internals.custom_reducers import BaseCustomAccumulator
 from pathway.internals.datetime_types import DateTimeNaive, DateTimeUtc, Duration
 from pathway.internals.decorators import (
@@ -55,10 +64,18 @@ from pathway.internals.joins import (
     join_right,
 )
 from pathway.internals.json import Json
-from pathway.internals.monitoring import MonitoringLevel
+from pathway.internals.monitoring import MonitoringLevel, runtime_metrics
 from pathway.internals.operator import iterate_universe
 from pathway.internals.row_transformer import ClassArg
-from pathway.internals.run import run, run_all
+from pathway.internals.run import (
+    RunHandle,
+    pause_connector,
+    request_shutdown,
+    resume_connector,
+    run,
+    run_all,
+    run_in_background,
+)
 from pathway.internals.schema import (
     ColumnDefinition,
     Schema,
@@ -121,9 +138,17 @@ __all__ = [
     "require",
     "if_else",
     "make_tuple",
+    "format_str",
+    "native_apply",
+    "wasm_apply",
     "sql",
     "run",
     "run_all",
+    "request_shutdown",
+    "pause_connector",
+    "resume_connector",
+    "run_in_background",
+    "RunHandle",
     "__version__",
     "universes",
     "udfs",
@@ -151,8 +176,12 @@ __all__ = [
     "groupby",
     "enable_interactive_mode",
     "LiveTable",
+    "set_async_runtime_config",
     "set_license_key",
+    "license_info",
+    "LicenseInfo",
     "set_monitoring_config",
+    "runtime_metrics",
     "global_error_log",
     "local_error_log",
     "ColumnDefinition",
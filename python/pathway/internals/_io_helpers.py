@@ -5,11 +5,13 @@ from __future__ import annotations
 import dataclasses
 import datetime
 import json
+from collections.abc import Iterable
 
 import boto3
 import boto3.session
 
-from pathway.internals import api, schema
+from pathway import secrets
+from pathway.internals import api, dtype as dt, schema
 from pathway.internals.table import Table
 from pathway.internals.trace import trace_user_frame
 
@@ -25,8 +27,10 @@ class AwsS3Settings:
 
     Args:
         bucket_name: Name of S3 bucket.
-        access_key: Access key for the bucket.
-        secret_access_key: Secret access key for the bucket.
+        access_key: Access key for the bucket. Can be a
+            :py:class:`~pathway.secrets.Secret`, fetched when the connector is created.
+        secret_access_key: Secret access key for the bucket. Can be a
+            :py:class:`~pathway.secrets.Secret`.
         with_path_style: Whether to use path-style requests.
         region: Region of the bucket.
         endpoint: Custom endpoint in case of self-hosted storage.
@@ -57,12 +61,12 @@ class AwsS3Settings:
     def settings(self) -> api.AwsS3Settings:
         return api.AwsS3Settings(
             self._bucket_name,
-            self._access_key,
-            self._secret_access_key,
+            secrets.resolve(self._access_key),
+            secrets.resolve(self._secret_access_key),
             self._with_path_style,
             self._region,
             self._endpoint,
-            self._session_token,
+            secrets.resolve(self._session_token),
         )
 
     @classmethod
@@ -189,12 +193,25 @@ def is_s3_path(path: str) -> bool:
     return False
 
 
-def _format_output_value_fields(table: Table) -> list[api.ValueField]:
+def _format_output_value_fields(
+    table: Table,
+    masked_columns: Iterable[str] = (),
+    columns: Iterable[str] | None = None,
+) -> list[api.ValueField]:
+    masked_columns = set(masked_columns)
+    schema_columns = table.schema.columns()
+    if columns is None:
+        columns = schema_columns.keys()
     value_fields = []
-    for column_name, column_data in table.schema.columns().items():
+    for column_name in columns:
+        column_data = schema_columns[column_name]
+        dtype = column_data.dtype
+        if column_name in masked_columns:
+            # The masked values are strings, while the missing values are kept
+            dtype = dt.Optional(dt.STR) if isinstance(dtype, dt.Optional) else dt.STR
         value_field = api.ValueField(
             column_name,
-            column_data.dtype.to_engine(),
+            dtype.to_engine(),
         )
         value_field.set_metadata(
             json.dumps(column_data.to_json_serializable_dict(), sort_keys=True)
@@ -566,7 +566,8 @@ class GroupedContext(Context):
 class DeduplicateContext(Context):
     value: ColumnWithExpression
     instance: tuple[ColumnWithExpression, ...]
-    acceptor: Callable[[Any, Any], bool]
+    # If there is no acceptor, the first rows are kept, natively in the engine
+    acceptor: Callable[[Any, Any], bool] | None
     orig_id_column: IdColumn
     unique_name: str | None
 
@@ -613,6 +614,56 @@ class FilterContext(
         return self.id_column_to_filter.universe.subset()
 
 
+@dataclass(eq=False, frozen=True)
+class SampleContext(
+    Context, column_properties_evaluator=cp.PreserveDependenciesPropsEvaluator
+):
+    """Context of `table.sample() operation."""
+
+    id_column_to_filter: IdColumn
+    fraction: float
+    seed: int
+
+    def column_dependencies_external(self) -> Iterable[Column]:
+        return [self.id_column_to_filter]
+
+    def input_universe(self) -> Universe:
+        return self.id_column_to_filter.universe
+
+    def id_column_type(self) -> dt.DType:
+        return self.id_column_to_filter.dtype
+
+    @cached_property
+    def universe(self) -> Universe:
+        return self.id_column_to_filter.universe.subset()
+
+
+@dataclass(eq=False, frozen=True)
+class ReservoirSampleContext(Context):
+    """Context of `table.reservoir_sample() operation."""
+
+    orig_id_column: IdColumn
+    instance_column: ColumnWithExpression
+    size: int
+    seed: int
+
+    def column_dependencies_internal(self) -> Iterable[Column]:
+        return [self.instance_column]
+
+    def column_dependencies_external(self) -> Iterable[Column]:
+        return [self.orig_id_column]
+
+    def input_universe(self) -> Universe:
+        return self.orig_id_column.universe
+
+    def id_column_type(self) -> dt.DType:
+        return self.orig_id_column.dtype
+
+    @cached_property
+    def universe(self) -> Universe:
+        return self.orig_id_column.universe.subset()
+
+
 @dataclass(eq=False, frozen=True)
 class TimeColumnContext(Context):
     """Context of operations that use time columns."""
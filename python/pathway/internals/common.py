@@ -2,7 +2,9 @@
 
 from __future__ import annotations
 
+import datetime
 import inspect
+import os
 from collections import defaultdict
 from collections.abc import Callable, Mapping
 from functools import wraps
@@ -18,6 +20,7 @@ from typing import (
 from warnings import warn
 
 from pathway.internals import (
+    api,
     dtype as dt,
     expression as expr,
     operator as op,
@@ -207,6 +210,145 @@ def apply_async(
     return udf(fun, executor=async_executor())(*args, **kwargs)
 
 
+@check_arg_types
+@trace_user_frame
+def native_apply(
+    library_path: str | os.PathLike,
+    function_name: str,
+    *args: expr.ColumnExpression | Value,
+    return_type: Any,
+    propagate_none: bool = False,
+) -> expr.ColumnExpression:
+    """Applies a function from a native plugin library to column expressions, row-wise.
+
+    The plugin is a dynamic library exporting the ``pathway_udf_plugin`` function that
+    describes the functions it provides. The arguments and results can be None, bools,
+    ints, floats, strings and bytes. The library is loaded once per process and is
+    never unloaded. Unlike Python UDFs, the function is called without holding the GIL.
+
+    Args:
+        library_path: path to the plugin library
+        function_name: name of the function in the plugin
+        args: the expressions whose values are passed to the function
+        return_type: the type of the values returned by the function
+        propagate_none: if True, the function isn't called on rows with a None
+            argument and the result is None for them
+
+    Example:
+
+    >>> import pathway as pw
+    >>> table = pw.debug.table_from_markdown(
+    ...     '''
+    ... a | b
+    ... 1 | 2
+    ... 3 | 4
+    ... '''
+    ... )
+    >>> result = table.select(
+    ...     c=pw.native_apply(
+    ...         "./libplugin.so", "add", pw.this.a, pw.this.b, return_type=int
+    ...     )
+    ... )  # doctest: +SKIP
+    >>> pw.debug.compute_and_print(result, include_id=False)  # doctest: +SKIP
+    c
+    3
+    7
+    """
+    if not args:
+        raise ValueError("native_apply() requires at least one expression")
+    result_dtype = dt.wrap(return_type)
+    if propagate_none:
+        result_dtype = dt.Optional(result_dtype)
+    path = os.fspath(library_path)
+    return expr.MethodCallExpression(
+        (
+            (
+                tuple(dt.ANY for _ in args),
+                result_dtype,
+                lambda *engine_args: api.Expression.native_apply(
+                    path, function_name, *engine_args, propagate_none=propagate_none
+                ),
+            ),
+        ),
+        "native_apply",
+        *args,
+    )
+
+
+@check_arg_types
+@trace_user_frame
+def wasm_apply(
+    module_path: str | os.PathLike,
+    function_name: str,
+    *args: expr.ColumnExpression | Value,
+    return_type: Any,
+    propagate_none: bool = False,
+    max_memory_bytes: int | None = None,
+    timeout: datetime.timedelta | None = None,
+) -> expr.ColumnExpression:
+    """Applies a function from a WebAssembly module to column expressions, row-wise.
+
+    The module has to export its memory as ``memory``, an allocator
+    ``alloc(len: i32) -> i32`` and the function itself, taking the arguments as a JSON
+    array and returning a JSON value. The arguments and results can be None, bools,
+    ints, floats, strings, tuples and Json. Each call runs in a fresh instance of the
+    module, bounded by a memory limit and a time limit. The module is compiled once per
+    process. Like native functions, it is called without holding the GIL.
+
+    Args:
+        module_path: path to the WebAssembly module, in binary or text format
+        function_name: name of the function exported by the module
+        args: the expressions whose values are passed to the function
+        return_type: the type of the values returned by the function
+        propagate_none: if True, the function isn't called on rows with a None
+            argument and the result is None for them
+        max_memory_bytes: maximum size of the memory of a single call, 64MiB by default
+        timeout: maximum duration of a single call, one second by default
+
+    Example:
+
+    >>> import pathway as pw
+    >>> table = pw.debug.table_from_markdown(
+    ...     '''
+    ... a | b
+    ... 1 | 2
+    ... 3 | 4
+    ... '''
+    ... )
+    >>> result = table.select(
+    ...     c=pw.wasm_apply("./udf.wasm", "add", pw.this.a, pw.this.b, return_type=int)
+    ... )  # doctest: +SKIP
+    >>> pw.debug.compute_and_print(result, include_id=False)  # doctest: +SKIP
+    c
+    3
+    7
+    """
+    if not args:
+        raise ValueError("wasm_apply() requires at least one expression")
+    result_dtype = dt.wrap(return_type)
+    if propagate_none:
+        result_dtype = dt.Optional(result_dtype)
+    path = os.fspath(module_path)
+    return expr.MethodCallExpression(
+        (
+            (
+                tuple(dt.ANY for _ in args),
+                result_dtype,
+                lambda *engine_args: api.Expression.wasm_apply(
+                    path,
+                    function_name,
+                    *engine_args,
+                    propagate_none=propagate_none,
+                    max_memory_bytes=max_memory_bytes,
+                    timeout=timeout,
+                ),
+            ),
+        ),
+        "wasm_apply",
+        *args,
+    )
+
+
 # declare_type used to demand that target_type is of type 'type'
 # however, it should also accept Optional (something like type | Optional[anytype])
 # best we can do at the moment is to set the type of target_type to Any
@@ -396,6 +538,58 @@ def make_tuple(*args: expr.ColumnExpression | Value) -> expr.ColumnExpression:
     return expr.MakeTupleExpression(*args)
 
 
+@check_arg_types
+@trace_user_frame
+def format_str(
+    template: str, *args: expr.ColumnExpression | Value
+) -> expr.ColumnExpression:
+    """Creates a string from the values of the expressions according to the template,
+    in a similar way to ``str.format``.
+
+    The placeholders have the form
+    ``{[index][:[[fill]align][width][.precision][type]]}``, where ``align`` is one of
+    ``<``, ``>``, ``^`` and ``type`` is one of ``s`` (string), ``d`` (int), ``f``
+    (float), ``x`` and ``X`` (hexadecimal int). The indices can be given either for all
+    placeholders or for none of them. Literal braces are written as ``{{`` and ``}}``.
+    If a value doesn't match the type of its placeholder, the result is an error.
+
+    Args:
+        template: the format string
+        args: the expressions whose values are put in place of the placeholders
+
+    Example:
+
+    >>> import pathway as pw
+    >>> table = pw.debug.table_from_markdown(
+    ...     '''
+    ... name  | score
+    ... Alice | 7.456
+    ... Bob   | 12
+    ... '''
+    ... )
+    >>> result = table.select(
+    ...     line=pw.format_str("{:<5}: {:>6.2f}", pw.this.name, pw.this.score)
+    ... )
+    >>> pw.debug.compute_and_print(result, include_id=False)
+    line
+    Alice:   7.46
+    Bob  :  12.00
+    """
+    if not args:
+        raise ValueError("format_str() requires at least one expression")
+    return expr.MethodCallExpression(
+        (
+            (
+                tuple(dt.ANY for _ in args),
+                dt.STR,
+                lambda *engine_args: api.Expression.format(template, *engine_args),
+            ),
+        ),
+        "format_str",
+        *args,
+    )
+
+
 def unwrap(col: expr.ColumnExpression | Value) -> expr.ColumnExpression:
     """Changes the type of the column from Optional[T] to T. If there is any None in the
     column this operation will raise an exception.
@@ -1,4 +1,4 @@
-import os
+import datetime
 from contextlib import contextmanager
 from contextvars import ContextVar
 from dataclasses import dataclass, field
@@ -7,6 +7,13 @@ from pathway import persistence
 from pathway.internals import api
 
 
+def _env_value(name: str, default=None):
+    """Returns the value of the environment variable, falling back to the corresponding
+    key of the configuration file pointed to by ``PATHWAY_CONFIG_FILE``."""
+    value = api.env_var_or_config(name)
+    return default if value is None else value
+
+
 def _env_field(
     name: str,
     default: str | int | float | None = None,
@@ -14,7 +21,7 @@ def _env_field(
     _type=str,
 ):
     def factory():
-        value = os.environ.get(name, default)
+        value = _env_value(name, default)
         if default_if_empty and value == "":
             value = default
         if value is not None:
@@ -26,7 +33,7 @@ def _env_field(
 
 def _env_bool_field(name: str, *, default: str = "false"):
     def factory():
-        value = os.environ.get(name, default).lower()
+        value = _env_value(name, default).lower()
         if value in ("1", "true", "yes"):
             return True
         elif value in ("0", "false", "no"):
@@ -39,8 +46,38 @@ def _env_bool_field(name: str, *, default: str = "false"):
     return field(default_factory=factory)
 
 
+def _env_attributes_field(name: str):
+    """Parses comma-separated ``key=value`` pairs, like ``OTEL_RESOURCE_ATTRIBUTES``."""
+
+    def factory():
+        attributes = {}
+        for pair in _env_value(name, "").split(","):
+            if not pair.strip():
+                continue
+            key, separator, value = pair.partition("=")
+            if not separator or not key.strip():
+                raise ValueError(
+                    f"Unexpected value for {name!r} environment variable: {pair!r}"
+                    + " is not a key=value pair"
+                )
+            attributes[key.strip()] = value.strip()
+        return attributes
+
+    return field(default_factory=factory)
+
+
+def _env_list_field(name: str):
+    """Parses a comma-separated list."""
+
+    def factory():
+        items = _env_value(name, "").split(",")
+        return [item.strip() for item in items if item.strip()]
+
+    return field(default_factory=factory)
+
+
 def _snapshot_access() -> api.SnapshotAccess | None:
-    match os.environ.get("PATHWAY_SNAPSHOT_ACCESS", "").lower():
+    match _env_value("PATHWAY_SNAPSHOT_ACCESS", "").lower():
         case "record":
             return api.SnapshotAccess.RECORD
         case "replay":
@@ -50,8 +87,8 @@ def _snapshot_access() -> api.SnapshotAccess | None:
 
 
 def _persistence_mode() -> api.PersistenceMode:
-    match os.environ.get(
-        "PATHWAY_PERSISTENCE_MODE", os.environ.get("PATHWAY_REPLAY_MODE", "")
+    match _env_value(
+        "PATHWAY_PERSISTENCE_MODE", _env_value("PATHWAY_REPLAY_MODE", "")
     ).lower():
         case "speedrun":
             return api.PersistenceMode.SPEEDRUN_REPLAY
@@ -86,6 +123,52 @@ class PathwayConfig:
         default_if_empty=True,
         _type=int,
     )
+    log_format: str = _env_field("PATHWAY_LOG_FORMAT", default="text")
+    prometheus_port: int | None = _env_field(
+        "PATHWAY_PROMETHEUS_PORT", default=None, default_if_empty=True, _type=int
+    )
+    resource_attributes: dict[str, str] = _env_attributes_field(
+        "PATHWAY_RESOURCE_ATTRIBUTES"
+    )
+    metric_attributes: dict[str, str] = _env_attributes_field(
+        "PATHWAY_METRIC_ATTRIBUTES"
+    )
+    trace_sample_ratio: float = _env_field(
+        "PATHWAY_TRACE_SAMPLE_RATIO", default=1.0, default_if_empty=True, _type=float
+    )
+    metric_allowlist: list[str] = _env_list_field("PATHWAY_METRIC_ALLOWLIST")
+    metric_denylist: list[str] = _env_list_field("PATHWAY_METRIC_DENYLIST")
+    telemetry_export_dir: str | None = _env_field(
+        "PATHWAY_TELEMETRY_EXPORT_DIR", default_if_empty=True
+    )
+    usage_telemetry_enabled: bool = _env_bool_field(
+        "PATHWAY_USAGE_TELEMETRY_ENABLED", default="true"
+    )
+    metrics_enabled: bool = _env_bool_field("PATHWAY_METRICS_ENABLED", default="true")
+    traces_enabled: bool = _env_bool_field("PATHWAY_TRACES_ENABLED", default="true")
+    async_worker_threads_per_runtime: int | None = _env_field(
+        "PATHWAY_ASYNC_WORKER_THREADS_PER_RUNTIME",
+        default=None,
+        default_if_empty=True,
+        _type=int,
+    )
+    async_max_blocking_threads: int | None = _env_field(
+        "PATHWAY_ASYNC_MAX_BLOCKING_THREADS",
+        default=None,
+        default_if_empty=True,
+        _type=int,
+    )
+    async_thread_name_prefix: str | None = _env_field(
+        "PATHWAY_ASYNC_THREAD_NAME_PREFIX", default_if_empty=True
+    )
+    async_runtime_groups: dict[str, list[str]] = field(default_factory=dict)
+
+    def __post_init__(self) -> None:
+        if not 0.0 <= self.trace_sample_ratio <= 1.0:
+            raise ValueError(
+                "Unexpected value for 'PATHWAY_TRACE_SAMPLE_RATIO' environment"
+                + f" variable: {self.trace_sample_ratio!r} is not between 0 and 1"
+            )
 
     @property
     def replay_config(
@@ -157,7 +240,70 @@ def set_license_key(key: str | None) -> None:
     get_pathway_config().license_key = key
 
 
-def set_monitoring_config(*, server_endpoint: str | None) -> None:
+@dataclass(frozen=True)
+class LicenseInfo:
+    """What the Pathway license grants, as returned by :py:func:`license_info`.
+
+    Attributes:
+        is_offline: Whether the license is validated without contacting the license
+            server, which is the case for the signed license files.
+        policy: The name of the license policy, if it is known.
+        entitlements: The features enabled by the license. None for the license keys,
+            whose entitlements are known only to the license server.
+        expiration_date: When the license expires, if it does.
+        file_expiration_date: When the license file has to be replaced with a newer
+            one, if it does.
+    """
+
+    is_offline: bool
+    policy: str | None
+    entitlements: list[str] | None
+    expiration_date: datetime.datetime | None
+    file_expiration_date: datetime.datetime | None
+
+
+def license_info() -> LicenseInfo:
+    """Describes the license set with :py:func:`set_license_key` or the
+    ``PATHWAY_LICENSE_KEY`` environment variable.
+
+    A signed license file is validated and described without contacting the license
+    server, so it can be used in the deployments without internet access.
+
+    Returns:
+        LicenseInfo: The description of the license.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> info = pw.license_info()
+    """
+    info = api.license_info(license_key=get_pathway_config().license_key)
+
+    def parse_date(date: str | None) -> datetime.datetime | None:
+        return datetime.datetime.fromisoformat(date) if date is not None else None
+
+    return LicenseInfo(
+        is_offline=info.is_offline,
+        policy=info.policy,
+        entitlements=info.entitlements,
+        expiration_date=parse_date(info.expiration_date),
+        file_expiration_date=parse_date(info.file_expiration_date),
+    )
+
+
+def set_monitoring_config(
+    *,
+    server_endpoint: str | None,
+    resource_attributes: dict[str, str] | None = None,
+    metric_attributes: dict[str, str] | None = None,
+    trace_sample_ratio: float | None = None,
+    metric_allowlist: list[str] | None = None,
+    metric_denylist: list[str] | None = None,
+    export_dir: str | None = None,
+    usage_telemetry_enabled: bool | None = None,
+    metrics_enabled: bool | None = None,
+    traces_enabled: bool | None = None,
+) -> None:
     """Sets the monitoring server endpoint.
     Requires a valid Pathway Scale license key.
 
@@ -167,6 +313,41 @@ def set_monitoring_config(*, server_endpoint: str | None) -> None:
             The endpoint should be
             `OTLP <https://opentelemetry.io/docs/specs/otlp/>`_ compatible
             and support gRPC protocol.
+        resource_attributes: Additional attributes of the OpenTelemetry resource
+            describing the run, e.g. the team or the environment. They can't
+            override the built-in ``service.*`` attributes. If None, the attributes
+            from the ``PATHWAY_RESOURCE_ATTRIBUTES`` environment variable, given as
+            comma-separated ``key=value`` pairs, are kept.
+        metric_attributes: Attributes attached to every metric reported by the run,
+            also exposed as labels by the Prometheus endpoint. If None, the attributes
+            from the ``PATHWAY_METRIC_ATTRIBUTES`` environment variable are kept.
+        trace_sample_ratio: The fraction of the runs whose traces are exported, between
+            0 and 1. If None, the value of the ``PATHWAY_TRACE_SAMPLE_RATIO``
+            environment variable is kept, by default all the traces are exported.
+        metric_allowlist: If not empty, only the metrics with names matching one of
+            the patterns are exported. A pattern ending with ``*`` matches all the
+            names starting with the rest of the pattern, e.g. ``process.*``.
+            If None, the ``PATHWAY_METRIC_ALLOWLIST`` environment variable, given as
+            a comma-separated list, is used.
+        metric_denylist: The metrics with names matching one of the patterns are not
+            exported, even if they are allowlisted. If None, the
+            ``PATHWAY_METRIC_DENYLIST`` environment variable is used.
+        export_dir: A local directory to which metrics and spans are written as
+            JSON Lines files, for deployments where no OTLP endpoint is reachable.
+            The files are rotated, and can be uploaded later. Doesn't require a
+            license key. If None, the ``PATHWAY_TELEMETRY_EXPORT_DIR`` environment
+            variable is used.
+        usage_telemetry_enabled: Whether the usage telemetry is sent to Pathway.
+            If None, the ``PATHWAY_USAGE_TELEMETRY_ENABLED`` environment variable is
+            used, by default it is sent if required by the license.
+        metrics_enabled: Whether the metrics are pushed to the monitoring server,
+            the telemetry server and the export directory. The Prometheus endpoint
+            is not affected. If None, the ``PATHWAY_METRICS_ENABLED`` environment
+            variable is used, by default the metrics are exported.
+        traces_enabled: Whether the spans are exported to the monitoring server,
+            the telemetry server and the export directory. If None, the
+            ``PATHWAY_TRACES_ENABLED`` environment variable is used, by default the
+            spans are exported.
 
     Returns:
         None
@@ -176,14 +357,118 @@ def set_monitoring_config(*, server_endpoint: str | None) -> None:
     >>> import pathway as pw
     >>> pw.set_license_key("YOUR_LICENSE_KEY")
     >>> pw.set_monitoring_config(server_endpoint="https://example.com:4317")
+    >>> pw.set_monitoring_config(
+    ...     server_endpoint="https://example.com:4317",
+    ...     resource_attributes={"team": "search", "environment": "staging"},
+    ...     metric_attributes={"pipeline": "indexer"},
+    ... )
+    >>> pw.set_monitoring_config(
+    ...     server_endpoint="https://example.com:4317",
+    ...     traces_enabled=False,
+    ... )
+    """
+    config = get_pathway_config()
+    config.monitoring_server = server_endpoint
+    if resource_attributes is not None:
+        config.resource_attributes = dict(resource_attributes)
+    if metric_attributes is not None:
+        config.metric_attributes = dict(metric_attributes)
+    if trace_sample_ratio is not None:
+        if not 0.0 <= trace_sample_ratio <= 1.0:
+            raise ValueError(
+                "trace_sample_ratio should be between 0 and 1,"
+                + f" got {trace_sample_ratio}"
+            )
+        config.trace_sample_ratio = trace_sample_ratio
+    if metric_allowlist is not None:
+        config.metric_allowlist = list(metric_allowlist)
+    if metric_denylist is not None:
+        config.metric_denylist = list(metric_denylist)
+    if export_dir is not None:
+        config.telemetry_export_dir = export_dir
+    if usage_telemetry_enabled is not None:
+        config.usage_telemetry_enabled = usage_telemetry_enabled
+    if metrics_enabled is not None:
+        config.metrics_enabled = metrics_enabled
+    if traces_enabled is not None:
+        config.traces_enabled = traces_enabled
+
+
+def set_async_runtime_config(
+    *,
+    worker_threads_per_runtime: int | None = None,
+    max_blocking_threads: int | None = None,
+    thread_name_prefix: str | None = None,
+    runtime_groups: dict[str, list[str]] | None = None,
+) -> None:
+    """Sets the parameters of the async runtimes used by the connectors. Each connector
+    gets a dedicated runtime with these parameters, so a connector stalled on an
+    external service doesn't affect the others. The limits apply to each runtime
+    separately, so the total number of threads grows with the number of connectors.
+    The connectors of a runtime group share one runtime instead.
+
+    Args:
+        worker_threads_per_runtime: The number of worker threads of each runtime. If
+            None, the ``PATHWAY_ASYNC_WORKER_THREADS_PER_RUNTIME`` environment
+            variable is used. If it is not set either, the tasks are run on the thread
+            waiting for their results.
+        max_blocking_threads: The maximum number of threads each runtime spawns for
+            blocking operations. If None, the ``PATHWAY_ASYNC_MAX_BLOCKING_THREADS``
+            environment variable is used, by default the limit of tokio applies.
+        thread_name_prefix: The prefix of the names of the runtime threads, followed
+            by a sequence number. If None, the ``PATHWAY_ASYNC_THREAD_NAME_PREFIX``
+            environment variable is used.
+        runtime_groups: The names of the connectors sharing a runtime, by the name of
+            the group. A connector is identified by its ``name`` parameter and belongs
+            to at most one group. The runtime of a group always has worker threads,
+            one if ``worker_threads_per_runtime`` is not set, and its threads are named
+            after the group.
+
+    Returns:
+        None
+
+    Example:
+
+    >>> import pathway as pw
+    >>> pw.set_async_runtime_config(
+    ...     worker_threads_per_runtime=2,
+    ...     runtime_groups={"enrichment": ["http-input", "http-output"]},
+    ... )
     """
-    get_pathway_config().monitoring_server = server_endpoint
+    for name, value in [
+        ("worker_threads_per_runtime", worker_threads_per_runtime),
+        ("max_blocking_threads", max_blocking_threads),
+    ]:
+        if value is not None and value <= 0:
+            raise ValueError(f"{name} should be positive, got {value}")
+    if runtime_groups is not None:
+        connector_groups: dict[str, str] = {}
+        for group, connectors in runtime_groups.items():
+            for connector in connectors:
+                other_group = connector_groups.setdefault(connector, group)
+                if other_group != group:
+                    raise ValueError(
+                        f"connector {connector!r} is assigned to two runtime groups:"
+                        + f" {other_group!r} and {group!r}"
+                    )
+    config = get_pathway_config()
+    if worker_threads_per_runtime is not None:
+        config.async_worker_threads_per_runtime = worker_threads_per_runtime
+    if max_blocking_threads is not None:
+        config.async_max_blocking_threads = max_blocking_threads
+    if thread_name_prefix is not None:
+        config.async_thread_name_prefix = thread_name_prefix
+    if runtime_groups is not None:
+        config.async_runtime_groups = {
+            group: list(connectors) for group, connectors in runtime_groups.items()
+        }
 
 
 __all__ = [
     "PathwayConfig",
     "get_pathway_config",
     "local_pathway_config",
+    "set_async_runtime_config",
     "set_license_key",
     "set_monitoring_config",
 ]
@@ -43,13 +43,13 @@ class GenericDataSink(DataSink):
     def name(self) -> str:
         return self.datasink_name
 
-    @property
-    def sort_by_indices(self):
+    def sort_by_indices(self, table: Table):
+        # The rows of the whole table are sorted, before the sink selects its columns
         if self.sort_by is None:
             return None
         column_index: dict[str, int] = {}
-        for index, column in enumerate(self.dataformat.value_fields):
-            column_index[column.name] = index
+        for index, column in enumerate(table._columns):
+            column_index[column] = index
         return [column_index[column.name] for column in self.sort_by]
 
 
@@ -62,6 +62,7 @@ class CallbackDataSink(DataSink):
     skip_errors: bool
     unique_name: str | None
     sort_by: Iterable[ColumnReference] | None = None
+    on_subscribe: Callable[[], None] | None = None
 
     def sort_by_indices(self, table: Table):
         if self.sort_by is None:
@@ -72,6 +73,15 @@ class CallbackDataSink(DataSink):
         return [column_index[column.name] for column in self.sort_by]
 
 
+@dataclass(frozen=True)
+class ArrowCallbackDataSink(DataSink):
+    on_batch: Callable[[api.ArrowBatch, int], None]
+    on_end: Callable[[], None]
+    skip_persisted_batch: bool
+    skip_errors: bool
+    unique_name: str | None
+
+
 @dataclass(frozen=True)
 class ExportDataSink(DataSink):
     callback: Callable[[api.Scope, api.ExportedTable], None]
@@ -86,6 +86,17 @@ class PandasDataSource(StaticDataSource):
         )
 
 
+@dataclass(frozen=True)
+class ArrowDataSource(StaticDataSource):
+    data: Any
+    id_from: list[str] | None = None
+
+    def is_append_only(self) -> bool:
+        return api.DIFF_PSEUDOCOLUMN not in self.data.schema.names or all(
+            diff == 1 for diff in self.data.column(api.DIFF_PSEUDOCOLUMN).to_pylist()
+        )
+
+
 @dataclass(frozen=True)
 class GenericDataSource(DataSource):
     datastorage: api.DataStorage
@@ -23,6 +23,7 @@ if TYPE_CHECKING:
     )
     from pathway.internals.reducers import Reducer
     from pathway.internals.table import Table
+    from pathway.internals.udfs.error_policies import ErrorPolicy
 
 
 @dataclasses.dataclass(frozen=True)
@@ -801,6 +802,9 @@ class ApplyExpression(ColumnExpression):
     _deterministic: bool
     _check_for_disallowed_types: bool
     _max_batch_size: int | None
+    _vectorized: bool
+    _cache: api.UdfCache | None
+    _error_policy: ErrorPolicy | None
     _args: tuple[ColumnExpression, ...]
     _kwargs: dict[str, ColumnExpression]
     _fun: Callable
@@ -815,6 +819,9 @@ class ApplyExpression(ColumnExpression):
         kwargs: Mapping[str, ColumnExpression | Value],
         _check_for_disallowed_types: bool = True,
         max_batch_size: int | None = None,
+        vectorized: bool = False,
+        cache: api.UdfCache | None = None,
+        error_policy: ErrorPolicy | None = None,
     ):
         super().__init__()
         self._fun = fun
@@ -823,6 +830,9 @@ class ApplyExpression(ColumnExpression):
         self._deterministic = deterministic
         self._check_for_disallowed_types = _check_for_disallowed_types
         self._max_batch_size = max_batch_size
+        self._vectorized = vectorized
+        self._cache = cache
+        self._error_policy = error_policy
 
         self._args = tuple(ColumnExpression._wrap(arg) for arg in args)
 
@@ -842,20 +852,85 @@ class ApplyExpression(ColumnExpression):
             self._deterministic,
             self._check_for_disallowed_types,
             self._max_batch_size,
+            self._vectorized,
+            self._cache,
+            self._error_policy,
             *self._args,
             **self._kwargs,
         )
 
     @property
     def _maybe_optional_return_type(self) -> dt.DType:
-        if self._propagate_none:
+        if self._propagate_none or (
+            self._error_policy is not None and self._error_policy._returns_none
+        ):
             return dt.Optional(self._return_type)
         else:
             return self._return_type
 
 
 class AsyncApplyExpression(ApplyExpression):
-    pass
+    _max_in_flight: int | None
+    _timeout: float | None
+    _timeout_policy: str
+    _deduplicate: bool
+
+    def __init__(
+        self,
+        fun: Callable,
+        return_type: Any,
+        propagate_none: bool,
+        deterministic: bool,
+        args: tuple[ColumnExpression | Value, ...],
+        kwargs: Mapping[str, ColumnExpression | Value],
+        max_batch_size: int | None = None,
+        vectorized: bool = False,
+        cache: api.UdfCache | None = None,
+        error_policy: ErrorPolicy | None = None,
+        max_in_flight: int | None = None,
+        timeout: float | None = None,
+        timeout_policy: str = "fail",
+        deduplicate: bool = False,
+    ):
+        super().__init__(
+            fun,
+            return_type,
+            propagate_none,
+            deterministic,
+            args,
+            kwargs,
+            max_batch_size=max_batch_size,
+            vectorized=vectorized,
+            cache=cache,
+            error_policy=error_policy,
+        )
+        self._max_in_flight = max_in_flight
+        self._timeout = timeout
+        self._timeout_policy = timeout_policy
+        self._deduplicate = deduplicate
+
+    def _to_internal(self) -> InternalColExpr:
+        return InternalColExpr.build(
+            type(self),
+            self._fun,
+            self._return_type,
+            self._propagate_none,
+            self._deterministic,
+            self._cache,
+            self._error_policy,
+            self._max_in_flight,
+            self._timeout,
+            self._timeout_policy,
+            self._deduplicate,
+            *self._args,
+            **self._kwargs,
+        )
+
+    @property
+    def _maybe_optional_return_type(self) -> dt.DType:
+        if self._timeout is not None and self._timeout_policy == "return_none":
+            return dt.Optional(self._return_type)
+        return super()._maybe_optional_return_type
 
 
 class FullyAsyncApplyExpression(ApplyExpression):
@@ -871,6 +946,9 @@ class FullyAsyncApplyExpression(ApplyExpression):
         args: tuple[ColumnExpression | Value, ...],
         kwargs: Mapping[str, ColumnExpression | Value],
         max_batch_size: int | None = None,
+        vectorized: bool = False,
+        cache: api.UdfCache | None = None,
+        error_policy: ErrorPolicy | None = None,
     ):
         super().__init__(fun, return_type, propagate_none, deterministic, args, kwargs)
         self.autocommit_duration_ms = autocommit_duration_ms
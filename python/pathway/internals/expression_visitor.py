@@ -179,6 +179,9 @@ class IdentityTransform(ExpressionVisitor):
             kwargs=expr_kwargs,
             _check_for_disallowed_types=expression._check_for_disallowed_types,
             max_batch_size=expression._max_batch_size,
+            vectorized=expression._vectorized,
+            cache=expression._cache,
+            error_policy=expression._error_policy,
         )
 
     def eval_async_apply(
@@ -197,6 +200,13 @@ class IdentityTransform(ExpressionVisitor):
             args=tuple(expr_args),
             kwargs=expr_kwargs,
             max_batch_size=expression._max_batch_size,
+            vectorized=expression._vectorized,
+            cache=expression._cache,
+            error_policy=expression._error_policy,
+            max_in_flight=expression._max_in_flight,
+            timeout=expression._timeout,
+            timeout_policy=expression._timeout_policy,
+            deduplicate=expression._deduplicate,
         )
 
     def eval_fully_async_apply(
@@ -216,6 +226,9 @@ class IdentityTransform(ExpressionVisitor):
             args=tuple(expr_args),
             kwargs=expr_kwargs,
             max_batch_size=expression._max_batch_size,
+            vectorized=expression._vectorized,
+            cache=expression._cache,
+            error_policy=expression._error_policy,
         )
 
     def eval_pointer(
@@ -929,3 +929,198 @@ class StringNamespace:
             "str.parse_bool",
             self._expression,
         )
+
+    def encode_base64(self) -> expr.ColumnExpression:
+        """Encodes bytes, or the UTF-8 representation of a string, in base64.
+
+        Returns:
+            Base64-encoded string
+
+        Example:
+
+        >>> import pathway as pw
+        >>> table = pw.debug.table_from_markdown(
+        ...     '''
+        ...      | name
+        ...    1 | Alice
+        ...    2 | Bob
+        ... '''
+        ... )
+        >>> table += table.select(encoded=table.name.str.encode_base64())
+        >>> pw.debug.compute_and_print(table, include_id=False)
+        name  | encoded
+        Alice | QWxpY2U=
+        Bob   | Qm9i
+        """
+
+        return expr.MethodCallExpression(
+            (
+                (dt.BYTES, dt.STR, api.Expression.encode_base64),
+                (
+                    dt.STR,
+                    dt.STR,
+                    lambda x: api.Expression.encode_base64(api.Expression.to_utf8(x)),
+                ),
+            ),
+            "str.encode_base64",
+            self._expression,
+        )
+
+    def decode_base64(self) -> expr.ColumnExpression:
+        """Decodes a base64-encoded string into bytes. The leading and trailing
+        whitespace is ignored.
+
+        Returns:
+            Decoded bytes
+
+        Example:
+
+        >>> import pathway as pw
+        >>> table = pw.debug.table_from_markdown(
+        ...     '''
+        ...      | encoded
+        ...    1 | QWxpY2U=
+        ...    2 | Qm9i
+        ... '''
+        ... )
+        >>> table += table.select(decoded=table.encoded.str.decode_base64())
+        >>> pw.debug.compute_and_print(table, include_id=False)
+        encoded  | decoded
+        QWxpY2U= | b'Alice'
+        Qm9i     | b'Bob'
+        """
+
+        return expr.MethodCallExpression(
+            ((dt.STR, dt.BYTES, api.Expression.decode_base64),),
+            "str.decode_base64",
+            self._expression,
+        )
+
+    def encode_hex(self) -> expr.ColumnExpression:
+        """Encodes bytes, or the UTF-8 representation of a string, as lowercase
+        hexadecimal digits.
+
+        Returns:
+            Hex-encoded string
+
+        Example:
+
+        >>> import pathway as pw
+        >>> table = pw.debug.table_from_markdown(
+        ...     '''
+        ...      | name
+        ...    1 | Alice
+        ...    2 | Bob
+        ... '''
+        ... )
+        >>> table += table.select(encoded=table.name.str.encode_hex())
+        >>> pw.debug.compute_and_print(table, include_id=False)
+        name  | encoded
+        Alice | 416c696365
+        Bob   | 426f62
+        """
+
+        return expr.MethodCallExpression(
+            (
+                (dt.BYTES, dt.STR, api.Expression.encode_hex),
+                (
+                    dt.STR,
+                    dt.STR,
+                    lambda x: api.Expression.encode_hex(api.Expression.to_utf8(x)),
+                ),
+            ),
+            "str.encode_hex",
+            self._expression,
+        )
+
+    def decode_hex(self) -> expr.ColumnExpression:
+        """Decodes a string of hexadecimal digits into bytes. Both lowercase and
+        uppercase digits are accepted, and the leading and trailing whitespace is
+        ignored.
+
+        Returns:
+            Decoded bytes
+
+        Example:
+
+        >>> import pathway as pw
+        >>> table = pw.debug.table_from_markdown(
+        ...     '''
+        ...      | encoded
+        ...    1 | 416C696365
+        ...    2 | 426f62
+        ... '''
+        ... )
+        >>> table += table.select(decoded=table.encoded.str.decode_hex())
+        >>> pw.debug.compute_and_print(table, include_id=False)
+        encoded    | decoded
+        416C696365 | b'Alice'
+        426f62     | b'Bob'
+        """
+
+        return expr.MethodCallExpression(
+            ((dt.STR, dt.BYTES, api.Expression.decode_hex),),
+            "str.decode_hex",
+            self._expression,
+        )
+
+    def to_utf8(self) -> expr.ColumnExpression:
+        """Returns the UTF-8 representation of a string as bytes.
+
+        Returns:
+            UTF-8 bytes
+
+        Example:
+
+        >>> import pathway as pw
+        >>> table = pw.debug.table_from_markdown(
+        ...     '''
+        ...      | name
+        ...    1 | Alice
+        ...    2 | Bob
+        ... '''
+        ... )
+        >>> table += table.select(data=table.name.str.to_utf8())
+        >>> pw.debug.compute_and_print(table, include_id=False)
+        name  | data
+        Alice | b'Alice'
+        Bob   | b'Bob'
+        """
+
+        return expr.MethodCallExpression(
+            ((dt.STR, dt.BYTES, api.Expression.to_utf8),),
+            "str.to_utf8",
+            self._expression,
+        )
+
+    def from_utf8_lossy(self) -> expr.ColumnExpression:
+        """Decodes bytes as UTF-8. The invalid sequences are replaced with the
+        replacement character ``U+FFFD``, so the decoding never fails.
+
+        Returns:
+            Decoded string
+
+        Example:
+
+        >>> import pathway as pw
+        >>> table = pw.debug.table_from_markdown(
+        ...     '''
+        ...      | encoded
+        ...    1 | 416c696365ff
+        ...    2 | 426f62
+        ... '''
+        ... )
+        >>> table = table.select(
+        ...     name=table.encoded.str.decode_hex().str.from_utf8_lossy()
+        ... )
+        >>> pw.debug.compute_and_print(table, include_id=False)
+        name
+        Alice�
+        Bob
+        """
+
+        return expr.MethodCallExpression(
+            ((dt.BYTES, dt.STR, api.Expression.from_utf8_lossy),),
+            "str.from_utf8_lossy",
+            self._expression,
+        )
@@ -57,6 +57,7 @@ class GraphRunner:
         runtime_typechecking: bool | None = None,
         terminate_on_error: bool | None = None,
         max_expression_batch_size: int = 1024,
+        cancellation_token: api.CancellationToken | None = None,
         _stacklevel: int = 1,
     ) -> None:
         pathway_config = get_pathway_config()
@@ -78,6 +79,7 @@ class GraphRunner:
             terminate_on_error = pathway_config.terminate_on_error
         self.terminate_on_error = terminate_on_error
         self.max_expression_batch_size = max_expression_batch_size
+        self.cancellation_token = cancellation_token
         if not self.terminate_on_error:
             warnings.warn(
                 "terminate_on_error=False mode is experimental",
@@ -144,6 +146,12 @@ class GraphRunner:
             license_key=self.license_key,
             monitoring_server=pathway_config.monitoring_server,
             metrics_reader_interval_secs=pathway_config.metrics_reader_interval_secs,
+            resource_attributes=pathway_config.resource_attributes,
+            metric_attributes=pathway_config.metric_attributes,
+            trace_sample_ratio=pathway_config.trace_sample_ratio,
+            export_dir=pathway_config.telemetry_export_dir,
+            usage_telemetry_enabled=pathway_config.usage_telemetry_enabled,
+            traces_enabled=pathway_config.traces_enabled,
         )
         with otel.tracer.start_as_current_span("graph_runner.run"):
             trace_context, trace_parent = telemetry.get_current_context()
@@ -195,6 +203,8 @@ class GraphRunner:
                     node_names,
                     default_logging=self.default_logging,
                     process_id=pathway_config.process_id,
+                    run_id=run_id,
+                    log_format=pathway_config.log_format,
                 ) as stats_monitor,
                 otel.with_logging_handler(),
                 get_persistence_engine_config(
@@ -217,6 +227,27 @@ class GraphRunner:
                         run_id=run_id,
                         terminate_on_error=self.terminate_on_error,
                         max_expression_batch_size=self.max_expression_batch_size,
+                        prometheus_port=pathway_config.prometheus_port,
+                        resource_attributes=pathway_config.resource_attributes,
+                        metric_attributes=pathway_config.metric_attributes,
+                        trace_sample_ratio=pathway_config.trace_sample_ratio,
+                        metric_allowlist=pathway_config.metric_allowlist,
+                        metric_denylist=pathway_config.metric_denylist,
+                        telemetry_export_dir=pathway_config.telemetry_export_dir,
+                        usage_telemetry_enabled=pathway_config.usage_telemetry_enabled,
+                        metrics_enabled=pathway_config.metrics_enabled,
+                        traces_enabled=pathway_config.traces_enabled,
+                        async_worker_threads_per_runtime=(
+                            pathway_config.async_worker_threads_per_runtime
+                        ),
+                        async_max_blocking_threads=(
+                            pathway_config.async_max_blocking_threads
+                        ),
+                        async_thread_name_prefix=(
+                            pathway_config.async_thread_name_prefix
+                        ),
+                        async_runtime_groups=pathway_config.async_runtime_groups,
+                        cancellation_token=self.cancellation_token,
                     )
                 except api.EngineErrorWithTrace as e:
                     error, frame = e.args
@@ -2,6 +2,7 @@
 
 from __future__ import annotations
 
+import datetime
 from abc import ABC, abstractmethod
 from collections.abc import Callable, Iterable
 from dataclasses import dataclass
@@ -448,7 +449,15 @@ class RowwiseEvaluator(
             *(self.eval_expression(arg, eval_state=eval_state) for arg in args),
             propagate_none=expression._propagate_none,
             dtype=expression._dtype.to_engine(),
+            arg_dtypes=[arg._dtype.to_engine() for arg in args],
             max_batch_size=expression._max_batch_size,
+            vectorized=expression._vectorized,
+            cache=expression._cache,
+            error_policy=(
+                None
+                if expression._error_policy is None
+                else expression._error_policy._to_engine()
+            ),
         )
 
     def eval_async_apply(
@@ -468,6 +477,24 @@ class RowwiseEvaluator(
             *(self.eval_expression(arg, eval_state=eval_state) for arg in args),
             propagate_none=expression._propagate_none,
             dtype=expression._dtype.to_engine(),
+            max_in_flight=expression._max_in_flight,
+            timeout=(
+                None
+                if expression._timeout is None
+                else datetime.timedelta(seconds=expression._timeout)
+            ),
+            timeout_policy=(
+                api.AsyncTimeoutPolicy.RETURN_NONE
+                if expression._timeout_policy == "return_none"
+                else api.AsyncTimeoutPolicy.FAIL
+            ),
+            deduplicate=expression._deduplicate,
+            cache=expression._cache,
+            error_policy=(
+                None
+                if expression._error_policy is None
+                else expression._error_policy._to_engine()
+            ),
         )
 
     def eval_fully_async_apply(
@@ -1319,6 +1346,16 @@ class DeduplicateEvaluator(ExpressionEvaluator, context_type=clmn.DeduplicateCon
 
         properties = self._table_properties(output_storage)
 
+        acceptor = self.context.acceptor
+        if acceptor is None:
+            return self.scope.drop_duplicates(
+                self.state.get_table(input_storage._universe),
+                instance_paths,
+                reduced_columns_paths,
+                self.context.unique_name,
+                properties,
+            )
+
         def is_different_with_state(
             state: tuple[api.Value, ...] | None,
             rows: Iterable[tuple[list[api.Value], int]],
@@ -1327,7 +1364,7 @@ class DeduplicateEvaluator(ExpressionEvaluator, context_type=clmn.DeduplicateCon
                 if difference <= 0 or col is api.ERROR:
                     continue
                 state_val = state[0] if state is not None else None
-                if state_val is None or self.context.acceptor(col, state_val):
+                if state_val is None or acceptor(col, state_val):
                     state = (col, *cols)
             return state
 
@@ -1465,6 +1502,38 @@ class RemoveRetractionsEvaluator(
         )
 
 
+class SampleEvaluator(ExpressionEvaluator, context_type=clmn.SampleContext):
+    context: clmn.SampleContext
+
+    def run(self, output_storage: Storage) -> api.Table:
+        input_storage = self.state.get_storage(self.context.input_universe())
+        properties = self._table_properties(output_storage)
+        return self.scope.sample_table(
+            self.state.get_table(input_storage._universe),
+            self.context.fraction,
+            self.context.seed,
+            properties,
+        )
+
+
+class ReservoirSampleEvaluator(
+    ExpressionEvaluator, context_type=clmn.ReservoirSampleContext
+):
+    context: clmn.ReservoirSampleContext
+
+    def run(self, output_storage: Storage) -> api.Table:
+        input_storage = self.state.get_storage(self.context.input_universe())
+        instance_column_path = input_storage.get_path(self.context.instance_column)
+        properties = self._table_properties(output_storage)
+        return self.scope.reservoir_sample_table(
+            self.state.get_table(input_storage._universe),
+            instance_column_path,
+            self.context.size,
+            self.context.seed,
+            properties,
+        )
+
+
 class AsyncTransformerEvaluator(
     ExpressionEvaluator, context_type=clmn.AsyncTransformerContext
 ):
@@ -12,8 +12,14 @@ from abc import ABC, abstractmethod
 from typing import TYPE_CHECKING, ClassVar, Generic, TypeVar
 
 from pathway.internals import api, trace
-from pathway.internals.datasink import CallbackDataSink, ExportDataSink, GenericDataSink
+from pathway.internals.datasink import (
+    ArrowCallbackDataSink,
+    CallbackDataSink,
+    ExportDataSink,
+    GenericDataSink,
+)
 from pathway.internals.datasource import (
+    ArrowDataSource,
     EmptyDataSource,
     ErrorLogDataSource,
     GenericDataSource,
@@ -132,6 +138,16 @@ class InputOperatorHandler(OperatorHandler[InputOperator], operator_type=InputOp
                     schema=datasource.schema,
                 )
                 self.state.set_table(output_storages[table], materialized_table)
+        elif isinstance(datasource, ArrowDataSource):
+            for table in operator.output_tables:
+                assert table.schema is not None
+                materialized_table = self.scope.static_table_from_arrow(
+                    datasource.data,
+                    datasource.schema.column_names(),
+                    datasource.connector_properties,
+                    id_from=datasource.id_from,
+                )
+                self.state.set_table(output_storages[table], materialized_table)
         elif isinstance(datasource, GenericDataSource):
             for table in operator.output_tables:
                 assert table.schema is not None
@@ -188,9 +204,11 @@ class OutputOperatorHandler(
                 data_sink=datasink.datastorage,
                 data_format=datasink.dataformat,
                 unique_name=datasink.unique_name,
-                sort_by_indices=datasink.sort_by_indices,
+                sort_by_indices=datasink.sort_by_indices(table),
             )
         elif isinstance(datasink, CallbackDataSink):
+            if datasink.on_subscribe is not None:
+                datasink.on_subscribe()
             self.scope.subscribe_table(
                 table=engine_table,
                 column_paths=column_paths,
@@ -202,6 +220,19 @@ class OutputOperatorHandler(
                 unique_name=datasink.unique_name,
                 sort_by_indices=datasink.sort_by_indices(table),
             )
+        elif isinstance(datasink, ArrowCallbackDataSink):
+            dtypes = table.schema._dtypes()
+            self.scope.subscribe_table_arrow(
+                table=engine_table,
+                column_paths=column_paths,
+                column_names=list(table._columns.keys()),
+                dtypes=[dtypes[name].to_engine() for name in table._columns.keys()],
+                on_batch=datasink.on_batch,
+                on_end=datasink.on_end,
+                skip_persisted_batch=datasink.skip_persisted_batch,
+                skip_errors=datasink.skip_errors,
+                unique_name=datasink.unique_name,
+            )
         elif isinstance(datasink, ExportDataSink):
             exported_table = self.scope.export_table(
                 table=engine_table, column_paths=column_paths
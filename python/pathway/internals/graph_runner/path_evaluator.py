@@ -303,6 +303,8 @@ NoNewColumnsContext = (
     | clmn.SetSchemaContext
     | clmn.RemoveRetractionsContext
     | clmn.StreamToTableContext
+    | clmn.SampleContext
+    | clmn.ReservoirSampleContext
 )
 
 
@@ -319,6 +321,8 @@ class NoNewColumnsPathEvaluator(
         clmn.SetSchemaContext,
         clmn.RemoveRetractionsContext,
         clmn.StreamToTableContext,
+        clmn.SampleContext,
+        clmn.ReservoirSampleContext,
     ],
 ):
     context: NoNewColumnsContext
@@ -11,6 +11,8 @@ Logs are not included in the telemetry data sent to the telemetry server.
 Additionally, the module permits the configuration of an extra OpenTelemetry Collector endpoint
 or another endpoint compatible with the OTLP (via gRPC), supporting logs, metrics, and tracing.
 Data forwarded to the monitoring server will include both logs and telemetry data.
+Metrics and spans can also be written to JSON Lines files in a local directory, for
+deployments without access to any endpoint.
 
 By default, both telemetry and monitoring are turned OFF.
 """
@@ -18,6 +20,7 @@ By default, both telemetry and monitoring are turned OFF.
 from __future__ import annotations
 
 import logging
+import os
 import sys
 from contextlib import contextmanager
 from functools import cached_property
@@ -36,10 +39,13 @@ from opentelemetry.sdk.resources import (
     Resource,
 )
 from opentelemetry.sdk.trace import TracerProvider
-from opentelemetry.sdk.trace.export import BatchSpanProcessor
+from opentelemetry.sdk.trace.export import BatchSpanProcessor, ConsoleSpanExporter
+from opentelemetry.sdk.trace.sampling import ParentBased, TraceIdRatioBased
 from opentelemetry.trace.propagation.tracecontext import TraceContextTextMapPropagator
 
 from pathway.internals import api
+from pathway.internals.config import get_pathway_config
+from pathway.internals.structured_logging import LogContextFilter
 from pathway.internals.trace import trace_user_frame
 
 propagator = TraceContextTextMapPropagator()
@@ -61,6 +67,9 @@ class Telemetry:
     def _resource(self) -> Resource:
         return Resource(
             attributes={
+                # the custom attributes go first, so that they can't override the
+                # built-in ones
+                **self.config.resource_attributes,
                 SERVICE_NAME: self.config.service_name or "",
                 SERVICE_VERSION: self.config.service_version or "",
                 SERVICE_NAMESPACE: self.config.service_namespace or "",
@@ -79,12 +88,24 @@ class Telemetry:
         license_key: str | None = None,
         monitoring_server: str | None = None,
         metrics_reader_interval_secs: int | None = None,
+        resource_attributes: dict[str, str] | None = None,
+        metric_attributes: dict[str, str] | None = None,
+        trace_sample_ratio: float = 1.0,
+        export_dir: str | None = None,
+        usage_telemetry_enabled: bool = True,
+        traces_enabled: bool = True,
     ) -> Telemetry:
         config = api.TelemetryConfig.create(
             run_id=run_id,
             license_key=license_key,
             monitoring_server=monitoring_server,
             metrics_reader_interval_secs=metrics_reader_interval_secs,
+            resource_attributes=resource_attributes or {},
+            metric_attributes=metric_attributes or {},
+            trace_sample_ratio=trace_sample_ratio,
+            export_dir=export_dir,
+            usage_telemetry_enabled=usage_telemetry_enabled,
+            traces_enabled=traces_enabled,
         )
         return cls(config)
 
@@ -107,21 +128,45 @@ class Telemetry:
                 logger_provider.add_log_record_processor(
                     BatchLogRecordProcessor(exporter)
                 )
-            return LoggingHandler(level=logging.NOTSET, logger_provider=logger_provider)
+            handler = LoggingHandler(
+                level=logging.NOTSET, logger_provider=logger_provider
+            )
+            # the context fields are exported as attributes of the log records
+            handler.addFilter(
+                LogContextFilter(self.config.run_id, get_pathway_config().process_id)
+            )
+            return handler
         else:
             return logging.NullHandler()
 
     def _init_tracer(self) -> trace.Tracer:
-        if len(self.config.tracing_servers) > 0:
-            trace_provider = TracerProvider(resource=self._resource)
+        if len(self.config.tracing_servers) > 0 or self.config.export_dir is not None:
+            trace_provider = TracerProvider(
+                resource=self._resource,
+                sampler=ParentBased(TraceIdRatioBased(self.config.trace_sample_ratio)),
+            )
             for endpoint in self.config.tracing_servers:
                 exporter = OTLPSpanExporter(endpoint=endpoint)
                 trace_provider.add_span_processor(BatchSpanProcessor(exporter))
+            if self.config.export_dir is not None:
+                trace_provider.add_span_processor(
+                    BatchSpanProcessor(self._file_span_exporter(self.config.export_dir))
+                )
             return trace_provider.get_tracer("pathway-tracer")
         else:
             return trace.NoOpTracer()
 
 
+    def _file_span_exporter(self, export_dir: str) -> ConsoleSpanExporter:
+        # one file per run, next to the files with the spans of the engine
+        os.makedirs(export_dir, exist_ok=True)
+        path = os.path.join(export_dir, f"spans-python-{self.config.run_id}.jsonl")
+        return ConsoleSpanExporter(
+            out=open(path, "a"),
+            formatter=lambda span: span.to_json(indent=None) + "\n",
+        )
+
+
 def get_current_context() -> tuple[Context, str | None]:
     carrier: dict[str, str | list[str]] = {}
     propagator.inject(carrier)
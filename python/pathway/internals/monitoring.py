@@ -3,6 +3,8 @@
 import contextlib
 import logging
 import os
+import time
+from dataclasses import dataclass
 from enum import Enum
 from typing import Any
 
@@ -17,6 +19,7 @@ from rich.segment import Segment
 from rich.table import Table
 
 from pathway.internals import api
+from pathway.internals.structured_logging import LOG_FORMATS, json_logging_handler
 
 
 class ConsolePrintingToBuffer(Console):
@@ -100,6 +103,7 @@ class MonitoringOutput:
         table.add_column("no. messages in the last minibatch", justify="right")
         table.add_column("in the last minute", justify="right")
         table.add_column("since start", justify="right")
+        table.add_column("backfill", justify="right")
 
         for name, entry in self.data.connector_stats:
             table.add_row(
@@ -111,9 +115,24 @@ class MonitoringOutput:
                 ),
                 f"{entry.num_messages_in_last_minute}",
                 f"{entry.num_messages_from_start}",
+                self.get_backfill_description(entry.backfill),
             )
         return table
 
+    @staticmethod
+    def get_backfill_description(backfill: Any) -> str:
+        metrics = _backfill_metrics(backfill)
+        if metrics is None:
+            return ""
+        if metrics.finished:
+            return "done"
+        description = (
+            f"{metrics.fraction_done:.0%} of {metrics.objects_discovered} object(s)"
+        )
+        if metrics.eta_ms is not None:
+            description += f", ETA {metrics.eta_ms // 1000} s"
+        return description
+
     def get_operators_table(self, max_height) -> Table:
         if len(self.node_names) == 0:
             caption = (
@@ -214,8 +233,14 @@ def monitor_stats(
     *,
     default_logging: bool,
     process_id: str,
+    run_id: str = "",
+    log_format: str = "text",
     refresh_per_second: int = 4,
 ):
+    if log_format not in LOG_FORMATS:
+        raise ValueError(
+            f"unsupported log format {log_format!r}, expected one of {LOG_FORMATS}"
+        )
     if monitoring_level != api.MonitoringLevel.ALL:
         node_names = []
     if monitoring_level != api.MonitoringLevel.NONE:
@@ -237,7 +262,11 @@ def monitor_stats(
                 ):
                     yield None
     else:
-        if default_logging:
+        if default_logging and log_format == "json":
+            logging.basicConfig(
+                level=logging.INFO, handlers=[json_logging_handler(run_id, process_id)]
+            )
+        elif default_logging:
             logging.basicConfig(
                 level=logging.INFO,
                 format="[%(asctime)s]:%(levelname)s:%(message)s",
@@ -292,3 +321,134 @@ class MonitoringLevel(Enum):
 def _disable_monitoring_when_auto() -> bool:
     console = Console()
     return not (console.is_interactive or console.is_jupyter)
+
+
+@dataclass(frozen=True)
+class OperatorMetrics:
+    """Progress of a single operator, or of the inputs or outputs of the computation."""
+
+    time: int | None
+    """Time (in milliseconds since the epoch) of the data processed most recently."""
+    latency_ms: int | None
+    """Difference between the current time and ``time``."""
+    lag_ms: int | None
+    """How much the operator lags behind the input, not set for the input itself."""
+    done: bool
+    """Whether the operator has processed all the data."""
+    total_rows: int | None = None
+    """Number of rows that went through the operator, only set for operators."""
+    current_rows: int | None = None
+    """Number of rows currently present in the output of the operator, only set for
+    operators."""
+
+
+@dataclass(frozen=True)
+class BackfillMetrics:
+    """Progress of reading the objects that were present in the source when the
+    connector started, e.g. the files in an S3 bucket. The objects are discovered
+    gradually, so the totals only cover the objects listed so far."""
+
+    objects_processed: int
+    objects_discovered: int
+    bytes_processed: int
+    bytes_discovered: int
+    finished: bool
+    eta_ms: int | None
+    """Estimated time until the backfill is finished, based on the rate at which
+    the bytes have been processed so far."""
+
+    @property
+    def fraction_done(self) -> float:
+        """Share of the discovered bytes that have been processed."""
+        if self.finished:
+            return 1.0
+        if self.bytes_discovered == 0:
+            return 0.0
+        return self.bytes_processed / self.bytes_discovered
+
+
+@dataclass(frozen=True)
+class ConnectorMetrics:
+    """Number of messages read by an input connector."""
+
+    messages_since_start: int
+    messages_in_last_minute: int
+    messages_in_last_minibatch: int
+    finished: bool
+    backfill: BackfillMetrics | None = None
+    """Backfill progress, only set for the connectors reporting it, such as the
+    filesystem and S3 ones."""
+
+
+def _backfill_metrics(progress: api.BackfillProgress | None) -> BackfillMetrics | None:
+    if progress is None:
+        return None
+    return BackfillMetrics(
+        objects_processed=progress.objects_processed,
+        objects_discovered=progress.objects_discovered,
+        bytes_processed=progress.bytes_processed,
+        bytes_discovered=progress.bytes_discovered,
+        finished=progress.finished,
+        eta_ms=progress.eta,
+    )
+
+
+@dataclass(frozen=True)
+class RuntimeMetrics:
+    """Snapshot of runtime metrics of the running computation, returned by
+    :py:func:`~pathway.runtime_metrics`."""
+
+    input: OperatorMetrics
+    output: OperatorMetrics
+    operators: dict[int, OperatorMetrics]
+    """Metrics of the operators by their ids, only collected with
+    ``monitoring_level=pw.MonitoringLevel.ALL``."""
+    connectors: dict[str, ConnectorMetrics]
+
+
+def _operator_metrics(
+    stats: api.OperatorStats, now: int, counts: api.CountStats | None = None
+) -> OperatorMetrics:
+    return OperatorMetrics(
+        time=stats.time,
+        latency_ms=None if stats.time is None else max(0, now - stats.time),
+        lag_ms=stats.lag,
+        done=stats.done,
+        total_rows=None if counts is None else counts.total_rows,
+        current_rows=None if counts is None else counts.current_rows,
+    )
+
+
+def runtime_metrics() -> RuntimeMetrics | None:
+    """Returns a snapshot of the metrics of the computation running in this process:
+    latencies of the inputs and outputs, numbers of messages read by the connectors and,
+    with ``monitoring_level=pw.MonitoringLevel.ALL``, the progress of each operator.
+    It can be called from any thread, e.g. to implement a custom health check.
+    Returns ``None`` if no metrics have been collected yet. After the computation
+    finishes, the last snapshot stays available until the next one is started.
+    With several processes, the metrics are only available in the first one.
+    """
+    stats = api.runtime_stats()
+    if stats is None:
+        return None
+    now = int(time.time() * 1000)
+    return RuntimeMetrics(
+        input=_operator_metrics(stats.input_stats, now),
+        output=_operator_metrics(stats.output_stats, now),
+        operators={
+            operator_id: _operator_metrics(
+                operator_stats, now, stats.row_counts.get(operator_id)
+            )
+            for operator_id, operator_stats in stats.operators_stats.items()
+        },
+        connectors={
+            name: ConnectorMetrics(
+                messages_since_start=entry.num_messages_from_start,
+                messages_in_last_minute=entry.num_messages_in_last_minute,
+                messages_in_last_minibatch=entry.num_messages_recently_committed,
+                finished=entry.finished,
+                backfill=_backfill_metrics(entry.backfill),
+            )
+            for name, entry in stats.connector_stats
+        },
+    )
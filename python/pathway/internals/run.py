@@ -1,7 +1,12 @@
 # Copyright © 2024 Pathway
 
+from __future__ import annotations
 
-from pathway.internals import parse_graph
+import threading
+from types import TracebackType
+from typing import Callable
+
+from pathway.internals import api, parse_graph
 from pathway.internals.graph_runner import GraphRunner
 from pathway.internals.monitoring import MonitoringLevel
 from pathway.internals.runtime_type_check import check_arg_types
@@ -100,3 +105,195 @@ def run_all(
         max_expression_batch_size=max_expression_batch_size,
         _stacklevel=4,
     ).run_all()
+
+
+def request_shutdown() -> None:
+    """Requests graceful termination of the computation running in this process.
+
+    Input connectors stop reading new data and commit what they have already read.
+    The data that is already in the computation is processed to the end, output
+    connectors are flushed and, if persistence is enabled, the final state of the
+    inputs is saved, so that the next run starts where this one has stopped.
+    Then :py:func:`~pathway.run` returns, as if all the sources were finite.
+
+    The function returns immediately and can be called from any thread, for example
+    from a signal handler or from a callback of :py:func:`~pathway.io.subscribe`.
+    With several processes, it has to be called in each of them.
+
+    Readers waiting for new data are not interrupted. In particular, the ``run``
+    method of a :py:class:`~pathway.io.python.ConnectorSubject` keeps running after
+    the shutdown, so it should check
+    :py:attr:`~pathway.io.python.ConnectorSubject.stop_requested` and return once
+    it is set.
+    """
+    api.request_shutdown()
+
+
+def pause_connector(name: str) -> None:
+    """Pauses the connector with the given name in the computation running in this
+    process, without stopping the computation.
+
+    A paused input connector stops reading new data, while the data it has already read
+    is processed and committed as usual. A paused output connector finishes writing the
+    changes of the current time and holds the further changes in memory until it is
+    resumed with :py:func:`~pathway.resume_connector`. It is useful for stopping the
+    ingestion or the output while the external systems are under maintenance.
+
+    The connectors are identified by the ``name`` they were created with. The function
+    can be called from any thread, and with several processes, it has to be called in
+    each of them. The connectors can also be paused with a ``POST`` request to
+    ``http://localhost:<port>/connectors/<name>/pause`` of the monitoring HTTP server,
+    and the states of the running connectors are listed at
+    ``http://localhost:<port>/connectors``.
+
+    A paused output connector doesn't let the computation finish until it is resumed
+    or :py:func:`~pathway.request_shutdown` is called.
+
+    Raises:
+        ValueError: if there is no running connector with the given name.
+    """
+    api.set_connector_paused_state(name, True)
+
+
+def resume_connector(name: str) -> None:
+    """Resumes the connector paused with :py:func:`~pathway.pause_connector`. The
+    connectors can also be resumed with a ``POST`` request to
+    ``http://localhost:<port>/connectors/<name>/resume`` of the monitoring HTTP server.
+
+    Raises:
+        ValueError: if there is no running connector with the given name.
+    """
+    api.set_connector_paused_state(name, False)
+
+
+class RunHandle:
+    """A handle to a computation started with :py:func:`~pathway.run_in_background`.
+
+    Used as a context manager, it cancels the computation and waits for it to finish
+    on exit, which makes it convenient for starting and stopping a computation from
+    a notebook cell.
+    """
+
+    def __init__(
+        self, target: Callable[[], None], cancellation_token: api.CancellationToken
+    ) -> None:
+        self._exception: BaseException | None = None
+        self._cancellation_token = cancellation_token
+        self._thread = threading.Thread(
+            target=self._run, args=(target,), name="pathway:run", daemon=True
+        )
+
+    def _run(self, target: Callable[[], None]) -> None:
+        try:
+            target()
+        except BaseException as e:
+            self._exception = e
+
+    def _start(self) -> None:
+        self._thread.start()
+
+    def cancel(self) -> None:
+        """Requests a cooperative stop of the computation and returns without waiting
+        for it. The stop proceeds as in :py:func:`~pathway.request_shutdown`: input
+        connectors stop reading, the data already read is processed to the end and
+        outputs are flushed. Calling it on a finished computation has no effect, and
+        it doesn't affect the computations started later.
+        """
+        self._cancellation_token.cancel()
+
+    @property
+    def cancelled(self) -> bool:
+        """Whether :py:meth:`cancel` has been called."""
+        return self._cancellation_token.cancelled
+
+    def done(self) -> bool:
+        """Whether the computation has finished."""
+        return not self._thread.is_alive()
+
+    def wait(self, timeout: float | None = None) -> bool:
+        """Waits for the computation to finish, for at most ``timeout`` seconds
+        if given. Returns whether it has finished. If the computation has failed,
+        its exception is raised.
+        """
+        self._thread.join(timeout=timeout)
+        if self._thread.is_alive():
+            return False
+        if self._exception is not None:
+            raise self._exception
+        return True
+
+    def __enter__(self) -> RunHandle:
+        return self
+
+    def __exit__(
+        self,
+        exc_type: type[BaseException] | None,
+        exc_value: BaseException | None,
+        traceback: TracebackType | None,
+    ) -> None:
+        self.cancel()
+        self._thread.join()
+        if exc_type is None and self._exception is not None:
+            raise self._exception
+
+
+@check_arg_types
+def run_in_background(
+    *,
+    debug: bool = False,
+    monitoring_level: MonitoringLevel = MonitoringLevel.NONE,
+    default_logging: bool = True,
+    persistence_config: PersistenceConfig | None = None,
+    runtime_typechecking: bool | None = None,
+    terminate_on_error: bool | None = None,
+    max_expression_batch_size: int = 1024,
+) -> RunHandle:
+    """Runs the computation graph in a background thread and returns a handle
+    to it, which can be used to wait for the computation or to cancel it.
+    Only one computation can be running in a process at a time.
+
+    Args:
+        debug: enable output out of table.debug() operators
+        monitoring_level: the verbosity of stats monitoring mechanism. One of
+            pathway.MonitoringLevel.NONE, pathway.MonitoringLevel.IN_OUT,
+            pathway.MonitoringLevel.ALL. The dashboard is disabled by default,
+            as it would take over the terminal.
+        default_logging: whether to allow pathway to set its own logging handler. Set
+            it to False if you want to set your own logging handler.
+        persistence_config: the config for persisting the state in case this
+            persistence is required.
+        runtime_typechecking: enables additional strict type checking at runtime
+        terminate_on_error: whether to terminate the computation if the data/user-logic
+            error occurs
+        max_expression_batch_size: the maximal number of rows for which the expressions
+            are computed at once.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> table = pw.debug.table_from_markdown('''
+    ... a
+    ... 1
+    ... 2
+    ... ''')
+    >>> pw.io.null.write(table)
+    >>> with pw.run_in_background() as handle:
+    ...     handle.wait()
+    True
+    """
+    cancellation_token = api.CancellationToken()
+    runner = GraphRunner(
+        parse_graph.G,
+        debug=debug,
+        monitoring_level=monitoring_level,
+        default_logging=default_logging,
+        persistence_config=persistence_config,
+        runtime_typechecking=runtime_typechecking,
+        terminate_on_error=terminate_on_error,
+        max_expression_batch_size=max_expression_batch_size,
+        cancellation_token=cancellation_token,
+        _stacklevel=4,
+    )
+    handle = RunHandle(runner.run_outputs, cancellation_token)
+    handle._start()
+    return handle
@@ -0,0 +1,68 @@
+# Copyright © 2024 Pathway
+
+"""Structured logging. With ``PATHWAY_LOG_FORMAT=json``, the default logging handler
+emits one JSON object per line instead of plain text. The records, also the ones
+exported via OTLP, are annotated with the run ID, the process ID and, for records
+coming from the engine, the worker ID and the name of the connector."""
+
+from __future__ import annotations
+
+import datetime
+import json
+import logging
+
+from pathway.internals import api
+
+LOG_FORMATS = ("text", "json")
+
+CONTEXT_FIELDS = ("run_id", "process_id", "worker_id", "connector_name")
+
+
+class LogContextFilter(logging.Filter):
+    """Adds the context of the computation to the log records. Fields that are
+    unknown for a given record are not set, as OTLP attributes can't be empty."""
+
+    def __init__(self, run_id: str, process_id: str) -> None:
+        super().__init__()
+        self.run_id = run_id
+        self.process_id = process_id
+
+    def filter(self, record: logging.LogRecord) -> bool:
+        worker_id, connector_name = api.current_log_context()
+        fields = {
+            "run_id": self.run_id,
+            "process_id": self.process_id,
+            "worker_id": worker_id,
+            "connector_name": connector_name,
+        }
+        for name, value in fields.items():
+            # values passed explicitly with `extra` take precedence
+            if value is not None and not hasattr(record, name):
+                setattr(record, name, value)
+        return True
+
+
+class JsonFormatter(logging.Formatter):
+    """Formats log records as single-line JSON objects."""
+
+    def format(self, record: logging.LogRecord) -> str:
+        entry = {
+            "timestamp": datetime.datetime.fromtimestamp(
+                record.created, tz=datetime.timezone.utc
+            ).isoformat(),
+            "level": record.levelname,
+            "logger": record.name,
+            "message": record.getMessage(),
+        }
+        for name in CONTEXT_FIELDS:
+            entry[name] = getattr(record, name, None)
+        if record.exc_info:
+            entry["exception"] = self.formatException(record.exc_info)
+        return json.dumps(entry, default=str)
+
+
+def json_logging_handler(run_id: str, process_id: str) -> logging.Handler:
+    handler = logging.StreamHandler()
+    handler.setFormatter(JsonFormatter())
+    handler.addFilter(LogContextFilter(run_id, process_id))
+    return handler
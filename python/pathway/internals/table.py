@@ -2,6 +2,7 @@
 
 from __future__ import annotations
 
+import datetime
 import functools
 import warnings
 from collections.abc import Callable, Mapping
@@ -583,6 +584,116 @@ id_type=<class 'pathway.engine.Pointer'>>
 
         return self._table_with_context(context)
 
+    @trace_user_frame
+    @check_arg_types
+    @contextualized_operator
+    def sample(self, fraction: float, *, seed: int = 0) -> Table[TSchema]:
+        """Keeps a random subset of the rows of the table, each row with probability
+        ``fraction``.
+
+        The choice is made in the engine from a hash of the id of the row salted with
+        the ``seed``, so it is deterministic: a row is either always kept or always
+        dropped, in particular its updates and deletion are kept if and only if its
+        insertion was, and the same rows are kept in every run with the same ``seed``.
+        It makes the operator suitable for building cheap debug taps off high-volume
+        streams.
+
+        Args:
+            fraction: the probability of keeping a row, between 0 and 1.
+            seed: the seed of the hash. The samples with different seeds are
+                independent.
+
+        Returns:
+            Table: Result has the same schema as ``self`` and its ids are a subset of
+            ``self.id``.
+
+        Example:
+
+        >>> import pathway as pw
+        >>> t = pw.debug.table_from_markdown('''
+        ... value
+        ... 1
+        ... 2
+        ... 3
+        ... ''')
+        >>> pw.debug.compute_and_print(t.sample(1.0), include_id=False)
+        value
+        1
+        2
+        3
+        >>> pw.debug.compute_and_print(t.sample(0.0), include_id=False)
+        value
+        """
+        if not 0.0 <= fraction <= 1.0:
+            raise ValueError(f"fraction has to be between 0 and 1, got {fraction}")
+        if seed < 0:
+            raise ValueError(f"seed has to be non-negative, got {seed}")
+        context = clmn.SampleContext(self._id_column, fraction, seed)
+        return self._table_with_context(context)
+
+    @trace_user_frame
+    @desugar
+    @check_arg_types
+    @contextualized_operator
+    def reservoir_sample(
+        self,
+        size: int,
+        *,
+        instance: expr.ColumnExpression | None = None,
+        seed: int = 0,
+    ) -> Table[TSchema]:
+        """Keeps a uniform random sample of at most ``size`` rows for each value of
+        ``instance``, or of the whole table if ``instance`` is not given.
+
+        The rows with the lowest hashes of their ids salted with the ``seed`` are kept,
+        so the sample is deterministic and stays uniform as the table changes: when
+        a sampled row is deleted, the row with the next lowest hash takes its place,
+        and a new row replaces a sampled one only if its hash is lower. Unlike
+        :py:meth:`sample`, the result is not append-only even if ``self`` is.
+
+        Args:
+            size: the maximal number of rows kept for each instance.
+            instance: the expression splitting the table into groups sampled
+                separately.
+            seed: the seed of the hash. The samples with different seeds are
+                independent.
+
+        Returns:
+            Table: Result has the same schema as ``self`` and its ids are a subset of
+            ``self.id``.
+
+        Example:
+
+        >>> import pathway as pw
+        >>> t = pw.debug.table_from_markdown('''
+        ... sensor | value
+        ... a      | 1
+        ... a      | 2
+        ... a      | 3
+        ... b      | 4
+        ... ''')
+        >>> sampled = t.reservoir_sample(2, instance=t.sensor)
+        >>> pw.debug.compute_and_print(
+        ...     sampled.groupby(pw.this.sensor).reduce(
+        ...         pw.this.sensor, count=pw.reducers.count()
+        ...     ),
+        ...     include_id=False,
+        ... )
+        sensor | count
+        a      | 2
+        b      | 1
+        """
+        if size < 1:
+            raise ValueError(f"size has to be positive, got {size}")
+        if seed < 0:
+            raise ValueError(f"seed has to be non-negative, got {seed}")
+        if instance is None:
+            instance = expr.ColumnConstExpression(None)
+        context = clmn.ReservoirSampleContext(
+            self._id_column, self._eval(instance), size, seed
+        )
+        return self._table_with_context(context)
+
     @trace_user_frame
     @desugar
     @check_arg_types
@@ -1337,6 +1448,90 @@ id_type=<class 'pathway.engine.Pointer'>>
 
         return self._table_with_context(context)
 
+    @trace_user_frame
+    @desugar
+    @check_arg_types
+    @contextualized_operator
+    def drop_duplicates(
+        self,
+        *columns: expr.ColumnExpression,
+        time: expr.ColumnExpression | None = None,
+        horizon: int | float | datetime.timedelta | None = None,
+        name: str | None = None,
+    ) -> Table:
+        """Keeps only the first row with each value of ``columns``, or the first of the
+        repeated rows with the same id if no ``columns`` are given. It is meant for
+        cleaning up the data from the sources that deliver the same entries more than
+        once, and runs entirely in the engine.
+
+        If ``time`` is given, the first row is the one with the lowest value of ``time``
+        among the rows arriving together. If also ``horizon`` is given, a duplicate is
+        dropped only if its ``time`` is less than ``horizon`` after the ``time`` of the
+        kept row. Otherwise, it replaces the kept row and starts a new horizon.
+
+        The deletions from ``self`` are ignored, as the rows are dropped once they are
+        seen. The ids of the resulting rows are derived from the values of ``columns``.
+
+        Args:
+            columns: The columns whose values identify the duplicates.
+            time: The column with the times of the rows. It can't be optional.
+            horizon: How long after a row its duplicates are dropped, measured in the
+                units of ``time``. If not given, they are dropped forever.
+            name: An identifier, under which the rows seen so far will be persisted, or
+                ``None`` if there is no need to persist them. When a program restarts, it
+                restores them according to what was saved for their ``name``, so that
+                the duplicates of the rows seen before the restart are dropped too.
+
+        Returns:
+            Table: the table with the duplicates dropped.
+
+        Example:
+
+        >>> import pathway as pw
+        >>> table = pw.debug.table_from_markdown(
+        ...     '''
+        ...     event_id | value | __time__
+        ...         a    |   1   |     2
+        ...         b    |   2   |     4
+        ...         a    |   1   |     6
+        ...         c    |   3   |     8
+        ...         b    |   5   |    10
+        ... '''
+        ... )
+        >>> result = table.drop_duplicates(pw.this.event_id)
+        >>> pw.debug.compute_and_print_update_stream(result, include_id=False)
+        event_id | value | __time__ | __diff__
+        a        | 1     | 2        | 1
+        b        | 2     | 4        | 1
+        c        | 3     | 8        | 1
+        """
+        if horizon is not None and time is None:
+            raise ValueError("horizon can only be used together with time")
+        instance = columns if columns else (self.id,)
+        for column in instance:
+            self._validate_expression(column)
+        self._check_for_disallowed_types(*instance)
+
+        value: expr.ColumnExpression
+        if time is None:
+            value = expr.ColumnConstExpression(None)
+        else:
+            self._validate_expression(time)
+            if isinstance(self.eval_type(time), dt.Optional):
+                raise ValueError("The time column of drop_duplicates can't be optional")
+            threshold = time + horizon if horizon is not None else None
+            value = expr.MakeTupleExpression(time, threshold)
+
+        context = clmn.DeduplicateContext(
+            self._eval(value),
+            tuple(self._eval(column) for column in instance),
+            None,
+            self._id_column,
+            name,
+        )
+
+        return self._table_with_context(context)
+
     @trace_user_frame
     def ix(
         self,
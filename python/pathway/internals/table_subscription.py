@@ -2,7 +2,11 @@
 
 from __future__ import annotations
 
-from typing import Any, Iterable, Protocol
+import asyncio
+import concurrent.futures
+import inspect
+import threading
+from typing import Any, Callable, Coroutine, Iterable, Protocol
 
 from pathway.internals import datasink
 from pathway.internals.api import Pointer
@@ -29,7 +33,8 @@ class OnChangeCallback(Protocol):
     The callback to be called on every change in the table. It is required to be
     callable and to accept four parameters: the key, the row changed, the time of the
     change in milliseconds and the flag stating if the change had been an addition
-    of the row.
+    of the row. It can also be a coroutine function, its calls are then run
+    concurrently on a dedicated event loop.
     """
 
     def __call__(
@@ -76,6 +81,141 @@ class OnTimeEndCallback(Protocol):
         ...
 
 
+def _is_async(callback: Callable) -> bool:
+    return inspect.iscoroutinefunction(callback) or inspect.iscoroutinefunction(
+        getattr(callback, "__call__", None)
+    )
+
+
+class _AsyncCallbackRunner:
+    """Runs coroutines returned by the callbacks on a dedicated event loop, with at most
+    ``max_concurrency`` of them in progress at once. When the limit is reached, the
+    engine thread submitting a new coroutine waits, so the output is slowed down instead
+    of piling up. The first exception raised by a coroutine is re-raised in the engine
+    on the next callback."""
+
+    def __init__(self, max_concurrency: int) -> None:
+        if max_concurrency < 1:
+            raise ValueError("max_async_concurrency has to be positive")
+        self._semaphore = threading.BoundedSemaphore(max_concurrency)
+        self._lock = threading.Lock()
+        self._event_loop: asyncio.AbstractEventLoop | None = None
+        self._thread: threading.Thread | None = None
+        self._pending: set[concurrent.futures.Future] = set()
+        self._exception: BaseException | None = None
+
+    def _get_event_loop(self) -> asyncio.AbstractEventLoop:
+        with self._lock:
+            if self._event_loop is None:
+                self._event_loop = asyncio.new_event_loop()
+                # a daemon thread, so that a failed computation doesn't keep the process
+                self._thread = threading.Thread(
+                    target=self._event_loop.run_forever,
+                    name="pathway:subscribe-callbacks",
+                    daemon=True,
+                )
+                self._thread.start()
+            return self._event_loop
+
+    def _raise_if_failed(self) -> None:
+        if self._exception is not None:
+            raise self._exception
+
+    def _on_done(self, future: concurrent.futures.Future) -> None:
+        with self._lock:
+            self._pending.discard(future)
+            if not future.cancelled() and self._exception is None:
+                self._exception = future.exception()
+        self._semaphore.release()
+
+    def submit(self, coroutine: Coroutine[Any, Any, Any]) -> None:
+        self._raise_if_failed()
+        self._semaphore.acquire()
+        future = asyncio.run_coroutine_threadsafe(coroutine, self._get_event_loop())
+        with self._lock:
+            self._pending.add(future)
+        future.add_done_callback(self._on_done)
+
+    def submit_after_pending(self, coroutine: Coroutine[Any, Any, Any]) -> None:
+        """Submits a coroutine that starts once all the coroutines submitted so far
+        have finished."""
+        with self._lock:
+            pending = list(self._pending)
+
+        async def run_after_pending() -> None:
+            await asyncio.gather(
+                *(asyncio.wrap_future(future) for future in pending),
+                return_exceptions=True,
+            )
+            await coroutine
+
+        self.submit(run_after_pending())
+
+    def wait(self) -> None:
+        """Waits for all the submitted coroutines to finish."""
+        while True:
+            with self._lock:
+                pending = list(self._pending)
+            if not pending:
+                break
+            concurrent.futures.wait(pending)
+        self._raise_if_failed()
+
+    def run(self, coroutine: Coroutine[Any, Any, Any]) -> None:
+        """Runs a coroutine after all the submitted ones and waits for its result."""
+        self.wait()
+        asyncio.run_coroutine_threadsafe(coroutine, self._get_event_loop()).result()
+
+    def close(self) -> None:
+        try:
+            self.wait()
+        finally:
+            with self._lock:
+                event_loop, thread = self._event_loop, self._thread
+                self._event_loop, self._thread = None, None
+            if event_loop is not None and thread is not None:
+                event_loop.call_soon_threadsafe(event_loop.stop)
+                thread.join()
+                event_loop.close()
+
+
+def _wrap_async_callbacks(
+    on_change: Callable[..., Any],
+    on_time_end: Callable[[int], Any],
+    on_end: Callable[[], Any],
+    max_concurrency: int,
+) -> tuple[Callable[..., None], Callable[[int], None], Callable[[], None]]:
+    """Makes the callbacks run coroutines returned by async callbacks on a dedicated
+    event loop. Calls of ``on_change`` may run concurrently. ``on_time_end`` starts
+    after all the calls of ``on_change`` that started before it have finished, and
+    ``on_end`` after all other callbacks have finished."""
+    runner = _AsyncCallbackRunner(max_concurrency)
+
+    def on_change_wrapper(*args) -> None:
+        result = on_change(*args)
+        if inspect.isawaitable(result):
+            runner.submit(result)
+
+    def on_time_end_wrapper(time: int) -> None:
+        if _is_async(on_time_end):
+            runner.submit_after_pending(on_time_end(time))
+        else:
+            runner.wait()
+            on_time_end(time)
+
+    def on_end_wrapper() -> None:
+        try:
+            if _is_async(on_end):
+                runner.run(on_end())
+            else:
+                runner.wait()
+                on_end()
+        finally:
+            runner.close()
+
+    return on_change_wrapper, on_time_end_wrapper, on_end_wrapper
+
+
 def subscribe(
     table,
     *,
@@ -86,6 +226,8 @@ def subscribe(
     skip_errors: bool = True,
     name: str | None = None,
     sort_by: Iterable[ColumnReference] | None = None,
+    on_subscribe: Callable[[], None] | None = None,
+    max_async_concurrency: int = 16,
 ) -> None:
     """
     Calls a callback function on_change on every change happening in table. This method
@@ -111,6 +253,10 @@ def subscribe(
         sort_by: If specified, the output will be sorted in ascending order based on the
             values of the given columns within each minibatch. When multiple columns are provided,
             the corresponding value tuples will be compared lexicographically.
+        on_subscribe: the callback function to be called by each worker when it subscribes
+            to the table.
+        max_async_concurrency: the maximal number of coroutines returned by async
+            callbacks that are in progress at once.
     Returns:
         None
     """
@@ -142,6 +288,11 @@ def subscribe(
         assert diff in [-1, 1]
         return on_change(key=key, row=row, time=time, is_addition=(diff >= 1))
 
+    if any(_is_async(callback) for callback in (on_change, on_time_end, on_end)):
+        (on_change_wrapper, on_time_end, on_end) = _wrap_async_callbacks(
+            on_change_wrapper, on_time_end, on_end, max_async_concurrency
+        )
+
     table_to_datasink(
         table,
         datasink.CallbackDataSink(
@@ -152,5 +303,6 @@ def subscribe(
             skip_errors=skip_errors,
             unique_name=name,
             sort_by=sort_by,
+            on_subscribe=on_subscribe,
         ),
     )
@@ -8,7 +8,7 @@ from collections.abc import Callable
 from typing import Any, overload
 from warnings import warn
 
-from pathway.internals import dtype as dt, expression as expr, udfs
+from pathway.internals import api, dtype as dt, expression as expr, udfs
 from pathway.internals.helpers import with_optional_kwargs
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.shadows import inspect
@@ -19,9 +19,11 @@ from pathway.internals.udfs.caches import (
     InMemoryCache,
     with_cache_strategy,
 )
+from pathway.internals.udfs.error_policies import ErrorPolicy
 from pathway.internals.udfs.executors import (
     AutoExecutor,
     Executor,
+    FullyAsyncExecutor,
     SyncExecutor,
     async_executor,
     async_options,
@@ -51,6 +53,7 @@ __all__ = [
     "DefaultCache",
     "DiskCache",
     "InMemoryCache",
+    "ErrorPolicy",
     "AsyncRetryStrategy",
     "ExponentialBackoffRetryStrategy",
     "FixedDelayRetryStrategy",
@@ -110,6 +113,9 @@ class UDF(abc.ABC):
     executor: Executor
     cache_strategy: CacheStrategy | None
     max_batch_size: int | None
+    vectorized: bool
+    error_policy: ErrorPolicy | None
+    _engine_cache: api.UdfCache | None
 
     def __init__(
         self,
@@ -120,6 +126,8 @@ class UDF(abc.ABC):
         executor: Executor = AutoExecutor(),
         cache_strategy: CacheStrategy | None = None,
         max_batch_size: int | None = None,
+        vectorized: bool = False,
+        error_policy: ErrorPolicy | None = None,
     ) -> None:
         """
         Args:
@@ -147,17 +155,33 @@ class UDF(abc.ABC):
                 to a UDF at once. Then each argument is a list of values and a UDF has to
                 return a list with results with the same length as input lists. The result
                 at position `i` has to be the result for input at position `i`.
+            vectorized: If True, the UDF is called on whole batches of rows, like
+                with ``max_batch_size``, but the int, float and bool arguments are
+                passed as one-dimensional numpy arrays instead of lists. The UDF can
+                return a numpy array or a list. If ``max_batch_size`` is not set, the
+                batches are not limited in size.
+            error_policy: Defines what happens when the UDF raises an exception.
+                Defaults to None, meaning that the exception is handled like any other
+                error of the computation.
         """
         self.return_type = return_type
         self.deterministic = deterministic
         self.propagate_none = propagate_none
         self.executor = self._prepare_executor(executor)
         self.cache_strategy = cache_strategy
-        if not isinstance(self.executor, SyncExecutor) and max_batch_size is not None:
+        if not isinstance(self.executor, SyncExecutor) and (
+            max_batch_size is not None or vectorized
+        ):
             raise ValueError(
                 "Batching is currently supported only for synchronous UDFs."
             )
+        if error_policy is not None and isinstance(self.executor, FullyAsyncExecutor):
+            raise ValueError(
+                "Error policies are not supported for fully asynchronous UDFs."
+            )
         self.max_batch_size = max_batch_size
+        self.vectorized = vectorized
+        self.error_policy = error_policy
         self.func = self._wrap_function()
 
     def _get_config(self) -> dict[str, Any]:
@@ -199,20 +223,35 @@ class UDF(abc.ABC):
                 stacklevel=3,
             )
         if return_type is ...:  # return type only specified in signature
-            if self.max_batch_size is None:
+            if self.max_batch_size is None and not self.vectorized:
                 return sig_return_type
-            else:
-                if not isinstance(wrapped_sig_return_type, dt.List):
-                    raise ValueError(
-                        f"A batch UDF has to return a list but is annotated as returning {sig_return_type}"
-                    )
+            elif isinstance(wrapped_sig_return_type, dt.List):
                 return wrapped_sig_return_type.wrapped
+            elif (
+                self.vectorized
+                and isinstance(wrapped_sig_return_type, dt.Array)
+                and wrapped_sig_return_type.wrapped != dt.ANY
+            ):
+                return wrapped_sig_return_type.wrapped
+            elif self.vectorized:
+                raise ValueError(
+                    "A vectorized UDF has to return a list or a typed numpy array"
+                    + f" but is annotated as returning {sig_return_type}"
+                )
+            else:
+                raise ValueError(
+                    f"A batch UDF has to return a list but is annotated as returning {sig_return_type}"
+                )
 
         return return_type
 
     def _wrap_function(self) -> Callable:
         func = self.executor._wrap(self.__wrapped__)
-        if self.cache_strategy is not None:
+        self._engine_cache = None
+        if self.cache_strategy is not None and self.executor._supports_engine_cache:
+            # the engine caches the results per row, also in batch UDFs
+            self._engine_cache = self.cache_strategy._engine_cache(self.__wrapped__)
+        if self.cache_strategy is not None and self._engine_cache is None:
             func = with_cache_strategy(func, self.cache_strategy)
         return func
 
@@ -231,6 +270,9 @@ class UDF(abc.ABC):
             propagate_none=self.propagate_none,
             deterministic=self.deterministic,
             max_batch_size=self.max_batch_size,
+            vectorized=self.vectorized,
+            cache=self._engine_cache,
+            error_policy=self.error_policy,
             **self.executor.additional_expression_args(),
             args=args,
             kwargs=kwargs,
@@ -304,6 +346,8 @@ def udf(
     executor: Executor = AutoExecutor(),
     cache_strategy: CacheStrategy | None = None,
     max_batch_size: int | None = None,
+    vectorized: bool = False,
+    error_policy: ErrorPolicy | None = None,
 ) -> Callable[[Callable], UDF]: ...
 
 
@@ -318,6 +362,8 @@ def udf(
     executor: Executor = AutoExecutor(),
     cache_strategy: CacheStrategy | None = None,
     max_batch_size: int | None = None,
+    vectorized: bool = False,
+    error_policy: ErrorPolicy | None = None,
 ) -> UDF: ...
 
 
@@ -333,6 +379,8 @@ def udf(
     executor: Executor = AutoExecutor(),
     cache_strategy: CacheStrategy | None = None,
     max_batch_size: int | None = None,
+    vectorized: bool = False,
+    error_policy: ErrorPolicy | None = None,
 ):
     """Create a Python UDF (user-defined function) out of a callable.
 
@@ -361,6 +409,13 @@ def udf(
             to a UDF at once. Then each argument is a list of values and a UDF has to
             return a list with results with the same length as input lists. The result
             at position `i` has to be the result for input at position `i`.
+        vectorized: If True, the UDF is called on whole batches of rows, like with
+            ``max_batch_size``, but the int, float and bool arguments are passed as
+            one-dimensional numpy arrays instead of lists. The UDF can return a numpy
+            array or a list. If ``max_batch_size`` is not set, the batches are not
+            limited in size.
+        error_policy: Defines what happens when the UDF raises an exception, see
+            ``pw.udfs.ErrorPolicy``. Defaults to None.
     Example:
 
     >>> import pathway as pw
@@ -416,4 +471,6 @@ def udf(
         executor=executor,
         cache_strategy=cache_strategy,
         max_batch_size=max_batch_size,
+        vectorized=vectorized,
+        error_policy=error_policy,
     )
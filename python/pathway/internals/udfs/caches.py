@@ -3,12 +3,15 @@
 from __future__ import annotations
 
 import abc
+import datetime
 import functools
 import inspect
 import os
+import time
+from collections import OrderedDict
 from collections.abc import Awaitable, Callable
 from pathlib import Path
-from typing import Any, ClassVar, ParamSpec, TypeVar, overload
+from typing import TYPE_CHECKING, Any, ClassVar, ParamSpec, TypeVar, overload
 
 import async_lru
 import diskcache
@@ -16,6 +19,9 @@ import diskcache
 from pathway.internals import api, trace
 from pathway.internals.runtime_type_check import check_arg_types
 
+if TYPE_CHECKING:
+    from pathway.persistence import Backend
+
 T = TypeVar("T")
 P = ParamSpec("P")
 
@@ -31,6 +37,13 @@ class CacheStrategy(abc.ABC):
     @abc.abstractmethod
     def wrap_sync(self, func: Callable[P, T]) -> Callable[P, T]: ...
 
+    def _engine_cache(self, func: Callable) -> api.UdfCache | None:
+        """
+        Returns the cache used by the engine when evaluating a UDF calling ``func``.
+        If it is None, the function is wrapped with ``wrap_sync`` or ``wrap_async``.
+        """
+        return None
+
 
 class DiskCache(CacheStrategy):
     """On disk cache."""
@@ -38,15 +51,24 @@ class DiskCache(CacheStrategy):
     _cache: diskcache.Cache
     _name: str | None
     _size_limit: int
+    _ttl: datetime.timedelta | None
 
     _custom_names: ClassVar[set[str]] = set()
 
     @trace.trace_user_frame
-    def __init__(self, name: str | None = None, size_limit=2**30) -> None:
+    def __init__(
+        self,
+        name: str | None = None,
+        size_limit=2**30,
+        *,
+        ttl: datetime.timedelta | None = None,
+    ) -> None:
         """
         Args:
             name: name of the cache. When multiple caches have the same name, they share a storage.
             size_limit: a memory limit of the cache in bytes.
+            ttl: time after which a cached result expires. If set to None, the results
+                don't expire.
         """
         super().__init__()
         if name is not None:
@@ -56,6 +78,7 @@ class DiskCache(CacheStrategy):
         self._name = name
         self._cache = None
         self._size_limit = size_limit
+        self._ttl = ttl
 
     def make_key(self, args: tuple[Any, ...], kwargs: dict[str, Any]) -> str:
         return str(api.ref_scalar(args, tuple(kwargs.items())))
@@ -69,7 +92,8 @@ class DiskCache(CacheStrategy):
                 return await func(*args, **kwargs)
             if key not in cache:
                 result = await func(*args, **kwargs)
-                cache[key] = result
+                self._store(cache, key, result)
+                return result
             return cache[key]
 
         return wrapper
@@ -83,11 +107,16 @@ class DiskCache(CacheStrategy):
                 return func(*args, **kwargs)
             if key not in cache:
                 result = func(*args, **kwargs)
-                cache[key] = result
+                self._store(cache, key, result)
+                return result
             return cache[key]
 
         return wrapper
 
+    def _store(self, cache: diskcache.Cache, key: str, result: Any) -> None:
+        expire = None if self._ttl is None else self._ttl.total_seconds()
+        cache.set(key, result, expire=expire)
+
     def _get_cache(self, func: Callable) -> diskcache.Cache | None:
         if self._cache is None:
             if self._name is None:
@@ -118,23 +147,89 @@ class DefaultCache(DiskCache):
 
 
 class InMemoryCache(CacheStrategy):
-    """In-memory LRU cache. It is not persisted between runs."""
+    """
+    In-memory LRU cache. It is not persisted between runs, unless a persistence backend
+    is given.
+    """
 
     max_size: int | None
-
-    def __init__(self, max_size: int | None = None) -> None:
+    ttl: datetime.timedelta | None
+    persistence_backend: Backend | None
+
+    def __init__(
+        self,
+        max_size: int | None = None,
+        *,
+        ttl: datetime.timedelta | None = None,
+        persistence_backend: Backend | None = None,
+    ) -> None:
         """
         Args:
             max_size: Maximum size of the cache (the number of entries).
             If set to None, it is unlimited.
+            ttl: Time after which a cached result expires. If set to None, the results
+            don't expire.
+            persistence_backend: Backend to which the results are also written, so that
+            they survive restarts. The stored results are bounded by ``max_size`` as
+            well. It requires the results to be cached by the engine, so it can't be
+            used with the fully asynchronous executor.
         """
         self.max_size = max_size
+        self.ttl = ttl
+        self.persistence_backend = persistence_backend
+
+    def _engine_cache(self, func: Callable) -> api.UdfCache | None:
+        func = inspect.unwrap(func)
+        storage = (
+            None
+            if self.persistence_backend is None
+            else self.persistence_backend.engine_data_storage
+        )
+        return api.UdfCache(
+            f"{func.__module__}_{func.__qualname__}",
+            max_entries=self.max_size,
+            ttl=self.ttl,
+            storage=storage,
+        )
+
+    def _check_not_persisted(self) -> None:
+        if self.persistence_backend is not None:
+            raise ValueError(
+                "InMemoryCache with a persistence backend can only be used by UDFs"
+                + " whose results are cached by the engine"
+            )
 
     def wrap_async(self, func: Callable[P, Awaitable[T]]) -> Callable[P, Awaitable[T]]:
-        return async_lru.alru_cache(self.max_size)(func)  # type: ignore[arg-type]
+        self._check_not_persisted()
+        ttl = None if self.ttl is None else self.ttl.total_seconds()
+        cache = async_lru.alru_cache(self.max_size, ttl=ttl)
+        return cache(func)  # type: ignore[arg-type]
 
     def wrap_sync(self, func: Callable[P, T]) -> Callable[P, T]:
-        return functools.lru_cache(self.max_size)(func)  # type: ignore[return-value]
+        self._check_not_persisted()
+        if self.ttl is None:
+            cached = functools.lru_cache(self.max_size)(func)
+            return cached  # type: ignore[return-value]
+        ttl = self.ttl.total_seconds()
+        max_size = self.max_size
+        entries: OrderedDict[Any, tuple[float, T]] = OrderedDict()
+
+        @functools.wraps(func)
+        def wrapper(*args: P.args, **kwargs: P.kwargs) -> T:
+            key = (args, tuple(kwargs.items()))
+            now = time.monotonic()
+            entry = entries.get(key)
+            if entry is not None and now - entry[0] < ttl:
+                entries.move_to_end(key)
+                return entry[1]
+            result = func(*args, **kwargs)
+            entries[key] = (now, result)
+            entries.move_to_end(key)
+            if max_size is not None and len(entries) > max_size:
+                entries.popitem(last=False)
+            return result
+
+        return wrapper
 
 
 @overload
@@ -0,0 +1,95 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import datetime
+from dataclasses import dataclass
+from typing import Any, Literal
+
+from pathway.internals import api
+
+_ENGINE_ACTIONS = {
+    "propagate": api.UdfErrorAction.PROPAGATE,
+    "return_default": api.UdfErrorAction.RETURN_DEFAULT,
+    "send_to_error_log": api.UdfErrorAction.SEND_TO_ERROR_LOG,
+    "fail": api.UdfErrorAction.FAIL,
+}
+
+
+# compared by identity, the default value doesn't have to be hashable
+@dataclass(frozen=True, kw_only=True, eq=False)
+class ErrorPolicy:
+    """
+    Defines what happens when a UDF raises an exception.
+
+    Args:
+        on_error: The action taken once the retries are exhausted. With
+            ``"propagate"``, the exception is handled like any other error of the
+            computation. With ``"return_default"``, the result is ``default_value``.
+            With ``"send_to_error_log"``, the exception is written to the error log
+            and the result is an error value, even if the computation is configured to
+            terminate on errors. With ``"fail"``, the computation is stopped.
+            Defaults to ``"propagate"``.
+        default_value: The result used with ``on_error="return_default"``.
+        max_retries: The number of retries of a failed call. In synchronous UDFs,
+            the delay between the retries blocks the processing. The errors in the
+            arguments of the UDF are neither retried nor handled by the policy.
+        retry_delay: The delay before the first retry. It grows exponentially with
+            the following retries.
+        backoff_factor: The factor by which the delay grows after each retry.
+        jitter: The maximum random duration added to the delay.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> t = pw.debug.table_from_markdown(
+    ...     '''
+    ...     a | b
+    ...     6 | 2
+    ...     1 | 0
+    ... '''
+    ... )
+    >>> policy = pw.udfs.ErrorPolicy(on_error="return_default", default_value=0)
+    >>> @pw.udf(error_policy=policy)
+    ... def divide(a: int, b: int) -> int:
+    ...     return a // b
+    ...
+    >>> result = t.select(res=divide(pw.this.a, pw.this.b))
+    >>> pw.debug.compute_and_print(result, include_id=False)
+    res
+    0
+    3
+    """
+
+    on_error: Literal["propagate", "return_default", "send_to_error_log", "fail"] = (
+        "propagate"
+    )
+    default_value: Any = None
+    max_retries: int = 0
+    retry_delay: datetime.timedelta | None = None
+    backoff_factor: float | None = None
+    jitter: datetime.timedelta | None = None
+
+    def __post_init__(self):
+        if self.on_error not in _ENGINE_ACTIONS:
+            raise ValueError(
+                "on_error has to be one of "
+                + ", ".join(repr(action) for action in _ENGINE_ACTIONS)
+                + f", not {self.on_error!r}"
+            )
+        if self.max_retries < 0:
+            raise ValueError("max_retries can't be negative")
+
+    @property
+    def _returns_none(self) -> bool:
+        return self.on_error == "return_default" and self.default_value is None
+
+    def _to_engine(self) -> api.UdfErrorPolicy:
+        return api.UdfErrorPolicy(
+            on_error=_ENGINE_ACTIONS[self.on_error],
+            default_value=self.default_value,
+            max_retries=self.max_retries,
+            retry_delay=self.retry_delay,
+            backoff_factor=self.backoff_factor,
+            jitter=self.jitter,
+        )
@@ -8,7 +8,7 @@ import functools
 import sys
 from collections.abc import Awaitable, Callable
 from dataclasses import dataclass
-from typing import Any, ParamSpec, TypeVar
+from typing import Any, Literal, ParamSpec, TypeVar
 
 import pathway.internals.expression as expr
 from pathway.internals.runtime_type_check import check_arg_types
@@ -34,6 +34,10 @@ class Executor(abc.ABC):
     def additional_expression_args(self) -> dict[str, Any]:
         return {}
 
+    @property
+    def _supports_engine_cache(self) -> bool:
+        return True
+
 
 @dataclass
 class AutoExecutor(Executor):
@@ -136,11 +140,34 @@ class AsyncExecutor(Executor):
     capacity: int | None = None
     timeout: float | None = None
     retry_strategy: AsyncRetryStrategy | None = None
+    timeout_policy: Literal["fail", "return_none"] = "fail"
+    deduplicate: bool = False
+
+    def __post_init__(self):
+        if self.timeout_policy not in ("fail", "return_none"):
+            raise ValueError(
+                "timeout_policy has to be 'fail' or 'return_none'"
+                + f", not {self.timeout_policy!r}"
+            )
+        if self.timeout_policy == "return_none" and self.retry_strategy is not None:
+            raise ValueError(
+                "timeout_policy='return_none' can't be used together with"
+                + " retry_strategy"
+            )
+
+    @property
+    def _engine_timeout(self) -> float | None:
+        # with retries, the timeout limits each attempt, so it is applied in Python
+        return self.timeout if self.retry_strategy is None else None
+
+    @property
+    def _engine_capacity(self) -> int | None:
+        return self.capacity
 
     def _wrap(self, fun: Callable) -> Callable:
         return async_options(
-            capacity=self.capacity,
-            timeout=self.timeout,
+            capacity=self.capacity if self._engine_capacity is None else None,
+            timeout=self.timeout if self._engine_timeout is None else None,
             retry_strategy=self.retry_strategy,
         )(fun)
 
@@ -148,12 +175,22 @@ class AsyncExecutor(Executor):
     def _apply_expression_type(self) -> type[expr.ApplyExpression]:
         return expr.AsyncApplyExpression
 
+    def additional_expression_args(self) -> dict[str, Any]:
+        return dict(
+            max_in_flight=self._engine_capacity,
+            timeout=self._engine_timeout,
+            timeout_policy=self.timeout_policy,
+            deduplicate=self.deduplicate,
+        )
+
 
 def async_executor(
     *,
     capacity: int | None = None,
     timeout: float | None = None,
     retry_strategy: AsyncRetryStrategy | None = None,
+    timeout_policy: Literal["fail", "return_none"] = "fail",
+    deduplicate: bool = False,
 ) -> Executor:
     """
     Returns the asynchronous executor for Pathway UDFs.
@@ -171,9 +208,17 @@ def async_executor(
             Defaults to None, indicating no specific limit.
         timeout: Maximum time (in seconds) to wait for the function result. When both
             ``timeout`` and ``retry_strategy`` are used, timeout applies to a single retry.
+            A call exceeding the timeout is cancelled.
             Defaults to None, indicating no time limit.
         retry_strategy: Strategy for handling retries in case of failures.
             Defaults to None, meaning no retries.
+        timeout_policy: What a call exceeding the timeout produces: an error with
+            ``"fail"`` or None with ``"return_none"``. The latter makes the result
+            column optional and can't be used with ``retry_strategy``.
+            Defaults to ``"fail"``.
+        deduplicate: If True, the function is called once for all rows of a batch
+            with identical arguments and the result is shared between them.
+            Defaults to False.
 
     Example:
 
@@ -218,7 +263,11 @@ def async_executor(
     30
     """
     return AsyncExecutor(
-        capacity=capacity, timeout=timeout, retry_strategy=retry_strategy
+        capacity=capacity,
+        timeout=timeout,
+        retry_strategy=retry_strategy,
+        timeout_policy=timeout_policy,
+        deduplicate=deduplicate,
     )
 
 
@@ -226,6 +275,27 @@ def async_executor(
 class FullyAsyncExecutor(AsyncExecutor):
     autocommit_duration_ms: int | None
 
+    def __post_init__(self):
+        super().__post_init__()
+        if self.timeout_policy != "fail":
+            raise ValueError(
+                "timeout_policy is not supported by the fully asynchronous executor"
+            )
+        if self.deduplicate:
+            raise ValueError(
+                "deduplicate is not supported by the fully asynchronous executor"
+            )
+
+    @property
+    def _engine_timeout(self) -> float | None:
+        # the calls are made from Python, so is the timeout
+        return None
+
+    @property
+    def _engine_capacity(self) -> int | None:
+        # likewise, the number of calls in flight is limited in Python
+        return None
+
     @property
     def _apply_expression_type(self) -> type[expr.ApplyExpression]:
         return expr.FullyAsyncApplyExpression
@@ -233,6 +303,11 @@ class FullyAsyncExecutor(AsyncExecutor):
     def additional_expression_args(self) -> dict[str, Any]:
         return dict(autocommit_duration_ms=self.autocommit_duration_ms)
 
+    @property
+    def _supports_engine_cache(self) -> bool:
+        # the results are computed outside of the engine expressions
+        return False
+
 
 def fully_async_executor(
     *,
@@ -4,42 +4,70 @@ from pathway.io import (
     airbyte,
     bigquery,
     csv,
+    custom,
     debezium,
     deltalake,
     dynamodb,
     elasticsearch,
     fs,
     gdrive,
+    gsheets,
     http,
     iceberg,
+    imap,
     jsonlines,
     kafka,
     logstash,
     minio,
     mongodb,
     mqtt,
+    mssql,
+    mysql,
     nats,
     null,
+    oracle,
     plaintext,
     postgres,
+    prometheus,
     pubsub,
     pyfilesystem,
     python,
     questdb,
     redpanda,
     s3,
+    schema_drift,
     slack,
+    snapshot,
     sqlite,
+    subprocess,
+)
+from pathway.io._subscribe import (
+    BatchIterator,
+    OnChangeCallback,
+    OnFinishCallback,
+    RowChange,
+    SubscriptionBatch,
+    subscribe,
+    subscribe_arrow,
+    subscribe_batches,
 )
-from pathway.io._subscribe import OnChangeCallback, OnFinishCallback, subscribe
 from pathway.io._synchronization import register_input_synchronization_group
-from pathway.io._utils import CsvParserSettings
+from pathway.io._utils import (
+    ColumnMasking,
+    ConnectorRetryPolicy,
+    CsvParserSettings,
+    register_schema,
+    registered_schema,
+)
 
 __all__ = [
     "airbyte",
     "bigquery",
+    "ColumnMasking",
+    "ConnectorRetryPolicy",
     "csv",
     "CsvParserSettings",
+    "custom",
     "debezium",
     "elasticsearch",
     "fs",
@@ -51,6 +79,7 @@ __all__ = [
     "null",
     "plaintext",
     "postgres",
+    "prometheus",
     "pyfilesystem",
     "python",
     "OnChangeCallback",
@@ -58,16 +87,31 @@ __all__ = [
     "redpanda",
     "slack",
     "subscribe",
+    "subscribe_arrow",
+    "subscribe_batches",
+    "BatchIterator",
+    "RowChange",
+    "SubscriptionBatch",
     "s3",
     "gdrive",
+    "gsheets",
     "sqlite",
     "pubsub",
     "deltalake",
     "iceberg",
+    "imap",
     "mongodb",
     "nats",
     "register_input_synchronization_group",
+    "register_schema",
+    "registered_schema",
     "mqtt",
     "questdb",
     "dynamodb",
+    "subprocess",
+    "snapshot",
+    "mssql",
+    "mysql",
+    "oracle",
+    "schema_drift",
 ]
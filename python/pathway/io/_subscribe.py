@@ -2,9 +2,16 @@
 
 from __future__ import annotations
 
-from typing import Iterable
+import queue
+import threading
+from typing import Any, Callable, Iterable, NamedTuple
 
+import pyarrow as pa
+
+from pathway.internals import datasink
+from pathway.internals.api import Pointer
 from pathway.internals.expression import ColumnReference
+from pathway.internals.table_io import table_to_datasink
 from pathway.internals.table_subscription import (
     OnChangeCallback,
     OnFinishCallback,
@@ -21,6 +28,7 @@ def subscribe(
     *,
     name: str | None = None,
     sort_by: Iterable[ColumnReference] | None = None,
+    max_async_concurrency: int = 16,
 ):
     """
     Calls a callback function on_change on every change happening in table.
@@ -39,9 +47,17 @@ def subscribe(
         sort_by: If specified, the output will be sorted in ascending order based on the
             values of the given columns within each minibatch. When multiple columns are provided,
             the corresponding value tuples will be compared lexicographically.
+        max_async_concurrency: The maximal number of calls of async callbacks that are
+            in progress at once. When it is reached, the output waits for one of them
+            to finish.
     Returns:
         None
 
+    The callbacks can also be coroutine functions (``async def``). They are then run
+    on a dedicated event loop, so calls of ``on_change`` can wait for I/O concurrently.
+    ``on_time_end`` is called once all the calls of ``on_change`` for the given time
+    have finished and ``on_end`` once all the other callbacks have finished.
+
     Example:
 
     >>> from pathway.tests import utils  # NODOCS
@@ -79,4 +95,219 @@ def subscribe(
         on_end=on_end,
         name=name,
         sort_by=sort_by,
+        max_async_concurrency=max_async_concurrency,
+    )
+
+
+def subscribe_arrow(
+    table,
+    on_batch: Callable[[pa.RecordBatch, int], None],
+    on_end: OnFinishCallback = lambda: None,
+    *,
+    name: str | None = None,
+):
+    """
+    Calls a callback function on_batch with all changes happening in table at a given
+    time, gathered in a single ``pyarrow.RecordBatch``. The batch is passed from the
+    engine through the Arrow C data interface, without creating a Python object for
+    each cell, so it can be cheaply converted to ``pandas`` or ``polars``.
+
+    Args:
+        table: the table to subscribe.
+        on_batch: the callback to be called with the batch of changes and their time.
+          The batch contains the columns of the table followed by the columns ``id``
+          with row ids, ``__time__`` and ``__diff__``, which is ``1`` for insertions and
+          ``-1`` for deletions.
+        on_end: the callback to be called when the stream of changes ends.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+    Returns:
+        None
+
+    Example:
+
+    >>> from pathway.tests import utils  # NODOCS
+    >>> utils.skip_on_multiple_workers()  # NODOCS
+    >>> import pathway as pw
+    ...
+    >>> table = pw.debug.table_from_markdown('''
+    ...      | pet  | owner   | age | __time__
+    ...    1 | dog  | Alice   | 10  | 0
+    ...    2 | cat  | Alice   | 8   | 2
+    ... ''')
+    ...
+    >>> def on_batch(batch, time):
+    ...     print(time, batch.num_rows, batch.column("age").to_pylist())
+    ...
+    >>> pw.io.subscribe_arrow(table, on_batch)
+    >>> pw.run(monitoring_level=pw.MonitoringLevel.NONE)
+    0 1 [10]
+    2 1 [8]
+    """
+
+    def on_batch_wrapper(batch, time: int) -> None:
+        on_batch(pa.record_batch(batch), time)
+
+    table_to_datasink(
+        table,
+        datasink.ArrowCallbackDataSink(
+            on_batch=on_batch_wrapper,
+            on_end=on_end,
+            skip_persisted_batch=True,
+            skip_errors=True,
+            unique_name=name,
+        ),
+    )
+
+
+class RowChange(NamedTuple):
+    """A single change of a table, delivered by :py:func:`subscribe_batches`."""
+
+    key: Pointer
+    row: dict[str, Any]
+    is_addition: bool
+
+
+class SubscriptionBatch(NamedTuple):
+    """All changes of a table at a given time, delivered by
+    :py:func:`subscribe_batches`."""
+
+    time: int
+    changes: list[RowChange]
+
+
+class BatchIterator:
+    """Iterator over the batches of changes of a table, returned by
+    :py:func:`subscribe_batches`. Batches are passed from the engine through a bounded
+    queue: when it is full, the engine waits until the consumer takes a batch.
+    """
+
+    _END = object()
+
+    def __init__(self, max_pending_batches: int):
+        self._queue: queue.Queue = queue.Queue(maxsize=max_pending_batches)
+        self._local = threading.local()
+        self._lock = threading.Lock()
+        self._subscribed_workers = 0
+        self._finished_workers = 0
+        self._closed = threading.Event()
+        self._exhausted = False
+
+    def _put(self, item: Any) -> None:
+        # waiting with a timeout, so that a closed iterator doesn't block the engine
+        while not self._closed.is_set():
+            try:
+                self._queue.put(item, timeout=0.1)
+                return
+            except queue.Full:
+                continue
+
+    def _changes(self) -> list[RowChange]:
+        if not hasattr(self._local, "changes"):
+            self._local.changes = []
+        return self._local.changes
+
+    def _on_subscribe(self) -> None:
+        with self._lock:
+            self._subscribed_workers += 1
+
+    def _on_change(self, key: Pointer, row: dict, time: int, is_addition: bool) -> None:
+        self._changes().append(RowChange(key, row, is_addition))
+
+    def _on_time_end(self, time: int) -> None:
+        changes = self._changes()
+        if changes:
+            self._local.changes = []
+            self._put(SubscriptionBatch(time, changes))
+
+    def _on_end(self) -> None:
+        with self._lock:
+            self._finished_workers += 1
+            finished = self._finished_workers == self._subscribed_workers
+        if finished:
+            self._put(self._END)
+
+    def __iter__(self) -> BatchIterator:
+        return self
+
+    def __next__(self) -> SubscriptionBatch:
+        if self._exhausted or self._closed.is_set():
+            raise StopIteration
+        item = self._queue.get()
+        if item is self._END:
+            self._exhausted = True
+            raise StopIteration
+        return item
+
+    def close(self) -> None:
+        """Stops the iteration. Batches produced later are dropped, so the engine is no
+        longer blocked by this iterator."""
+        self._closed.set()
+        while True:
+            try:
+                self._queue.get_nowait()
+            except queue.Empty:
+                break
+
+
+def subscribe_batches(
+    table,
+    *,
+    max_pending_batches: int = 16,
+    name: str | None = None,
+) -> BatchIterator:
+    """
+    Returns an iterator over the changes of the table, grouped into batches with all
+    changes at a given time. The batches are passed through a queue holding at most
+    ``max_pending_batches`` of them: if the consumer is slower than the computation, the
+    engine waits for it instead of buffering the output without limit. The iterator has
+    to be consumed in a different thread than the one running the computation. It ends
+    when the stream of changes ends or when ``close`` is called.
+
+    With several workers, each of them produces its own batches.
+
+    Args:
+        table: the table to subscribe.
+        max_pending_batches: maximum number of batches waiting for the consumer.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+    Returns:
+        BatchIterator: iterator over ``SubscriptionBatch`` objects with the ``time`` and
+        the list of ``changes``, each with the ``key``, the ``row`` and ``is_addition``.
+
+    Example:
+
+    >>> from pathway.tests import utils  # NODOCS
+    >>> utils.skip_on_multiple_workers()  # NODOCS
+    >>> import threading
+    >>> import pathway as pw
+    ...
+    >>> table = pw.debug.table_from_markdown('''
+    ...      | pet  | owner   | age | __time__
+    ...    1 | dog  | Alice   | 10  | 0
+    ...    2 | cat  | Alice   | 8   | 2
+    ... ''')
+    >>> batches = pw.io.subscribe_batches(table, max_pending_batches=1)
+    >>> runner = threading.Thread(
+    ...     target=pw.run, kwargs=dict(monitoring_level=pw.MonitoringLevel.NONE)
+    ... )
+    >>> runner.start()
+    >>> for batch in batches:
+    ...     print(batch.time, [change.row for change in batch.changes])
+    0 [{'pet': 'dog', 'owner': 'Alice', 'age': 10}]
+    2 [{'pet': 'cat', 'owner': 'Alice', 'age': 8}]
+    >>> runner.join()
+    """
+    if max_pending_batches < 1:
+        raise ValueError("max_pending_batches has to be positive")
+    iterator = BatchIterator(max_pending_batches)
+    internal_subscribe(
+        table,
+        skip_persisted_batch=True,
+        on_change=iterator._on_change,
+        on_time_end=iterator._on_time_end,
+        on_end=iterator._on_end,
+        name=name,
+        on_subscribe=iterator._on_subscribe,
     )
+    return iterator
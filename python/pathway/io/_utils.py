@@ -2,10 +2,14 @@
 
 from __future__ import annotations
 
+import datetime
 import functools
+import operator
+import os
 import warnings
 from dataclasses import KW_ONLY, dataclass
-from typing import TYPE_CHECKING, Any, Iterable
+from pathlib import Path
+from typing import TYPE_CHECKING, Any, Iterable, Literal
 
 import pathway.internals as pw
 import pathway.internals.dtype as dt
@@ -17,7 +21,15 @@ from pathway.internals._io_helpers import (
     _format_output_value_fields,
 )
 from pathway.internals.api import ConnectorMode, PathwayType, ReadMethod
-from pathway.internals.expression import ColumnReference
+from pathway.internals.expression import (
+    ColumnBinaryOpExpression,
+    ColumnConstExpression,
+    ColumnExpression,
+    ColumnReference,
+    ColumnUnaryOpExpression,
+    IsNoneExpression,
+    IsNotNoneExpression,
+)
 from pathway.internals.schema import Schema
 from pathway.internals.table import Table
 
@@ -50,6 +62,10 @@ _DATA_FORMAT_MAPPING = {
     "plaintext_by_file": "identity",
     "plaintext_by_object": "identity",
     "only_metadata": "identity",
+    "parquet": "parquet",
+    "avro": "avro",
+    "protobuf": "protobuf",
+    "msgpack": "msgpack",
 }
 
 _PATHWAY_TYPE_MAPPING: dict[PathwayType, dt.DType] = {
@@ -67,6 +83,10 @@ _PATHWAY_TYPE_MAPPING: dict[PathwayType, dt.DType] = {
     PathwayType.PY_OBJECT_WRAPPER: dt.ANY_PY_OBJECT_WRAPPER,
 }
 
+# The encodings of the lengths preceding the records of binary streams: unsigned LEB128
+# varints or 4-byte big-endian or little-endian integers
+LengthPrefix = Literal["varint", "fixed32_be", "fixed32_le"]
+
 SUPPORTED_INPUT_FORMATS: set[str] = {
     "csv",
     "json",
@@ -76,6 +96,10 @@ SUPPORTED_INPUT_FORMATS: set[str] = {
     "plaintext_by_file",
     "plaintext_by_object",
     "only_metadata",
+    "parquet",
+    "avro",
+    "protobuf",
+    "msgpack",
 }
 
 
@@ -138,6 +162,7 @@ def internal_read_method(format: str) -> ReadMethod:
         "plaintext_by_file",
         "plaintext_by_object",
         "only_metadata",
+        "parquet",
     ):
         return ReadMethod.FULL
     return ReadMethod.BY_LINE
@@ -156,6 +181,10 @@ class CsvParserSettings:
         comment_character: If specified, the lines starting with the comment
             character will be treated as comments and therefore, will be ignored by
             parser
+        strip_bom: If set, the UTF-8 byte order mark at the beginning of a file is
+            skipped. Otherwise, it becomes a part of the name of the first column.
+        normalize_newlines: If set, the CRLF and CR line breaks inside the quoted
+            values are replaced with LF.
     """
 
     def __init__(
@@ -166,7 +195,11 @@ class CsvParserSettings:
         enable_double_quote_escapes=True,
         enable_quoting=True,
         comment_character=None,
+        strip_bom=True,
+        normalize_newlines=False,
     ):
+        self.strip_bom = strip_bom
+        self.normalize_newlines = normalize_newlines
         self.api_settings = api.CsvParserSettings(
             delimiter,
             quote,
@@ -174,9 +207,272 @@ class CsvParserSettings:
             enable_double_quote_escapes,
             enable_quoting,
             comment_character,
+            strip_bom,
+        )
+
+
+def _timedelta_from_ms(duration_ms: int | None) -> datetime.timedelta | None:
+    if duration_ms is None:
+        return None
+    return datetime.timedelta(milliseconds=duration_ms)
+
+
+class ConnectorRetryPolicy:
+    """
+    Retry settings of a connector, applied to the failed requests it sends to the
+    external storage. The settings that are not specified keep the defaults of the
+    connector, so it's enough to pass only the ones to be changed.
+
+    Args:
+        max_retries: Maximum number of retries of a failed request.
+        first_delay_ms: Delay before the first retry, in milliseconds.
+        backoff_factor: Factor by which the delay grows after each retry. Must be at
+            least 1.
+        jitter_ms: Maximum random jitter (in milliseconds) added to the delay after
+            each retry.
+        max_elapsed_ms: Maximum total time (in milliseconds) spent on a request,
+            including the retries. No retry is made if it would start later than that.
+            Not limited by default.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> retry_policy = pw.io.ConnectorRetryPolicy(max_retries=10, first_delay_ms=500)
+    """
+
+    def __init__(
+        self,
+        *,
+        max_retries: int | None = None,
+        first_delay_ms: int | None = None,
+        backoff_factor: float | None = None,
+        jitter_ms: int | None = None,
+        max_elapsed_ms: int | None = None,
+    ):
+        self.api_policy = api.ConnectorRetryPolicy(
+            max_retries=max_retries,
+            initial_delay=_timedelta_from_ms(first_delay_ms),
+            backoff_factor=backoff_factor,
+            jitter=_timedelta_from_ms(jitter_ms),
+            max_elapsed_time=_timedelta_from_ms(max_elapsed_ms),
         )
 
 
+class ColumnMasking:
+    """
+    Masking of a column applied by an output connector before the values are written,
+    so that the cleartext of the column doesn't leave the engine. The masked column is
+    written as a string column, and the missing values remain missing. The instances
+    are created with one of the methods below and passed to a connector in the
+    ``column_masking`` dictionary, keyed by the column or by its name.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> t = pw.debug.table_from_markdown('''
+    ... name  | email
+    ... Alice | alice@example.com
+    ... ''')
+    >>> pw.io.csv.write(
+    ...     t,
+    ...     "output.csv",
+    ...     column_masking={t.email: pw.io.ColumnMasking.tokenize(b"secret")},
+    ... )
+    """
+
+    def __init__(self, method: str, key: bytes | None = None):
+        self.method = method
+        self.key = key
+
+    @classmethod
+    def hash(cls) -> ColumnMasking:
+        """
+        Replaces the values with their hex-encoded SHA-256 digests. The same value is
+        always masked in the same way, so the masked column can still be used for
+        joining or counting. As there is no key, the values from a small domain can be
+        recovered by hashing all of its elements, so tokenization should be preferred
+        for such columns.
+        """
+        return cls("hash")
+
+    @classmethod
+    def tokenize(cls, key: str | bytes) -> ColumnMasking:
+        """
+        Replaces the values with their hex-encoded HMAC-SHA256 with the given secret
+        key. The same value is always masked in the same way, and the values can't be
+        recovered without the key.
+        """
+        if isinstance(key, str):
+            key = key.encode()
+        if not key:
+            raise ValueError("tokenization key must not be empty")
+        return cls("tokenize", key)
+
+    @classmethod
+    def encrypt(cls, key: bytes) -> ColumnMasking:
+        """
+        Encrypts the values with AES-256-GCM using the given 32-byte key. A value is
+        written as the base64 encoding of the random 12-byte nonce followed by the
+        ciphertext and the authentication tag. The same value is encrypted differently
+        every time, so an encrypted column can't be a part of the primary key.
+        """
+        if len(key) != 32:
+            raise ValueError(
+                f"encryption key must be 32 bytes long, got {len(key)} bytes"
+            )
+        return cls("encrypt", key)
+
+
+_SINK_COMPARISON_OPERATORS = {
+    operator.eq: "==",
+    operator.ne: "!=",
+    operator.lt: "<",
+    operator.le: "<=",
+    operator.gt: ">",
+    operator.ge: ">=",
+}
+
+# The operator to be used when the sides of a comparison are swapped
+_SWAPPED_COMPARISON_OPERATORS = {
+    "==": "==",
+    "!=": "!=",
+    "<": ">",
+    "<=": ">=",
+    ">": "<",
+    ">=": "<=",
+}
+
+
+def _sink_predicate(table: Table, expression: ColumnExpression) -> api.SinkPredicate:
+    """
+    Converts ``expression`` into a predicate evaluated by the sink on the rows of
+    ``table``. Only the comparisons of the columns with the constants, the checks for
+    ``None`` and the boolean columns, combined with ``&``, ``|`` and ``~``, are
+    supported.
+    """
+    column_names = list(table._columns)
+
+    def column_index(expression: ColumnExpression) -> int | None:
+        if isinstance(expression, ColumnReference) and expression._table == table:
+            return column_names.index(expression.name)
+        return None
+
+    if isinstance(expression, ColumnReference):
+        index = column_index(expression)
+        if index is not None and expression._column.dtype == dt.BOOL:
+            return api.SinkPredicate.compare(index, "==", True)
+    elif isinstance(expression, ColumnBinaryOpExpression):
+        if expression._operator == operator.and_:
+            return api.SinkPredicate.all_of(
+                [
+                    _sink_predicate(table, expression._left),
+                    _sink_predicate(table, expression._right),
+                ]
+            )
+        if expression._operator == operator.or_:
+            return api.SinkPredicate.any_of(
+                [
+                    _sink_predicate(table, expression._left),
+                    _sink_predicate(table, expression._right),
+                ]
+            )
+        op = _SINK_COMPARISON_OPERATORS.get(expression._operator)
+        left, right = expression._left, expression._right
+        if op is not None:
+            left_index = column_index(left)
+            right_index = column_index(right)
+            if left_index is not None and isinstance(right, ColumnConstExpression):
+                return api.SinkPredicate.compare(left_index, op, right._val)
+            if right_index is not None and isinstance(left, ColumnConstExpression):
+                return api.SinkPredicate.compare(
+                    right_index, _SWAPPED_COMPARISON_OPERATORS[op], left._val
+                )
+    elif isinstance(expression, ColumnUnaryOpExpression):
+        if expression._operator == operator.inv:
+            return api.SinkPredicate.negation(_sink_predicate(table, expression._expr))
+    elif isinstance(expression, (IsNoneExpression, IsNotNoneExpression)):
+        index = column_index(expression._expr)
+        if index is not None:
+            predicate = api.SinkPredicate.is_none(index)
+            if isinstance(expression, IsNotNoneExpression):
+                predicate = api.SinkPredicate.negation(predicate)
+            return predicate
+    raise ValueError(
+        f"The expression {expression} can't be evaluated by the sink. Only the "
+        "comparisons of the columns of the written table with the constants, the "
+        "checks for None and the boolean columns, combined with &, | and ~, are "
+        "supported in the row_filter"
+    )
+
+
+@dataclass(frozen=True)
+class _SinkOutput:
+    """
+    The value fields of the output of a table, along with the transformations the sink
+    applies to the rows of the table before they are written.
+    """
+
+    value_fields: list[api.ValueField]
+    column_masking: list[api.ColumnMasking]
+    sink_filter: api.SinkPredicate | None
+    sink_projection: list[int] | None
+
+
+def _prepare_sink_output(
+    table: Table,
+    *,
+    column_masking: dict[ColumnReference | str, ColumnMasking] | None = None,
+    row_filter: ColumnExpression | None = None,
+    columns: Iterable[ColumnReference] | None = None,
+) -> _SinkOutput:
+    """
+    Prepares the output of ``table`` with only the ``columns`` and the rows matching
+    ``row_filter`` written, and with the masked columns turned into string columns.
+    """
+    column_names = list(table._columns)
+    sink_projection = None
+    output_columns = column_names
+    if columns is not None:
+        output_columns = []
+        for column in columns:
+            if column._table != table:
+                raise ValueError(
+                    f"The column {column} doesn't belong to the target table {table}"
+                )
+            if column.name in output_columns:
+                raise ValueError(f"The column {column} is selected more than once")
+            output_columns.append(column.name)
+        if not output_columns:
+            raise ValueError("At least one column must be selected for the output")
+        sink_projection = [column_names.index(name) for name in output_columns]
+
+    masking_by_name: dict[str, ColumnMasking] = {}
+    for column, masking in (column_masking or {}).items():
+        if isinstance(column, ColumnReference):
+            if column._table != table:
+                raise ValueError(
+                    f"The column {column} doesn't belong to the target table {table}"
+                )
+            column = column.name
+        if column not in output_columns:
+            raise ValueError(f"Unknown column {column!r} in column_masking")
+        masking_by_name[column] = masking
+
+    return _SinkOutput(
+        value_fields=_format_output_value_fields(
+            table, masked_columns=masking_by_name.keys(), columns=output_columns
+        ),
+        column_masking=[
+            api.ColumnMasking(name, masking.method, key=masking.key)
+            for name, masking in masking_by_name.items()
+        ],
+        sink_filter=(
+            _sink_predicate(table, row_filter) if row_filter is not None else None
+        ),
+        sink_projection=sink_projection,
+    )
+
+
 def read_schema(
     schema: type[Schema],
 ) -> tuple[type[Schema], dict[str, Any]]:
@@ -190,6 +486,62 @@ def read_schema(
     )
 
 
+_REGISTERED_SCHEMAS: dict[str, type[Schema]] = {}
+
+
+def register_schema(name: str, schema: type[Schema]) -> type[Schema]:
+    """Registers ``schema`` under ``name``, so that it can be referenced across the
+    connectors and modules with :py:func:`registered_schema`.
+
+    The connectors reading data with a registered schema are checked in the engine:
+    the values they parse have to match the types of the columns, otherwise they are
+    reported as errors, like the other parsing errors. Registering a different schema
+    under the same name fails.
+
+    Args:
+        name: The name to register the schema under.
+        schema: The schema to be registered.
+
+    Returns:
+        The registered schema, so that the function can be used as a class decorator.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> class OrderSchema(pw.Schema):
+    ...     order_id: int = pw.column_definition(primary_key=True)
+    ...     amount: float
+    >>> _ = pw.io.register_schema("orders", OrderSchema)
+
+    The schema can then be used by any connector, also in other modules:
+
+    >>> orders = pw.io.jsonlines.read(
+    ...     "orders/", schema=pw.io.registered_schema("orders"), mode="static"
+    ... )
+    """
+    _, api_schema = read_schema(schema)
+    api.register_schema(
+        name, api_schema["value_fields"], api_schema["key_field_names"]
+    )
+    _REGISTERED_SCHEMAS[name] = schema
+    return schema
+
+
+def registered_schema(name: str) -> type[Schema]:
+    """Returns the schema registered under ``name`` with :py:func:`register_schema`."""
+    try:
+        return _REGISTERED_SCHEMAS[name]
+    except KeyError:
+        raise ValueError(f"Schema {name!r} is not registered") from None
+
+
+def _registered_schema_name(schema: type[Schema]) -> str | None:
+    for name, registered in _REGISTERED_SCHEMAS.items():
+        if registered is schema:
+            return name
+    return None
+
+
 def assert_schema_not_none(
     schema: type[Schema] | None,
     data_format_type: str | None = None,
@@ -215,6 +567,10 @@ def construct_schema_and_data_format(
     csv_settings: CsvParserSettings | None = None,
     json_field_paths: dict[str, str] | None = None,
     schema_registry_settings: SchemaRegistrySettings | None = None,
+    length_prefix: LengthPrefix | None = None,
+    avro_schema: str | None = None,
+    protobuf_descriptor_set: str | os.PathLike | None = None,
+    protobuf_message: str | None = None,
     _stacklevel: int = 1,
 ) -> tuple[type[Schema], api.DataFormat]:
     data_format_type = get_data_format_type(format, SUPPORTED_INPUT_FORMATS)
@@ -252,9 +608,11 @@ def construct_schema_and_data_format(
             schema_registry_settings=maybe_schema_registry_settings(
                 schema_registry_settings
             ),
+            length_prefix=length_prefix,
         )
 
     schema = assert_schema_not_none(schema, data_format_type)
+    schema_name = _registered_schema_name(schema)
     if with_metadata:
         schema |= MetadataSchema
 
@@ -262,6 +620,8 @@ def construct_schema_and_data_format(
     if data_format_type == "dsv":
         if json_field_paths is not None:
             raise ValueError("Unexpected argument for csv format: json_field_paths")
+        if length_prefix is not None:
+            raise ValueError("Unexpected argument for csv format: length_prefix")
         return schema, api.DataFormat(
             **api_schema,
             format_type=data_format_type,
@@ -269,6 +629,22 @@ def construct_schema_and_data_format(
             schema_registry_settings=maybe_schema_registry_settings(
                 schema_registry_settings
             ),
+            schema_name=schema_name,
+            dsv_strip_bom=csv_settings is None or csv_settings.strip_bom,
+            dsv_normalize_newlines=(
+                csv_settings is not None and csv_settings.normalize_newlines
+            ),
+        )
+    elif data_format_type == "parquet":
+        if csv_settings is not None:
+            raise ValueError("Unexpected argument for parquet format: csv_settings")
+        if json_field_paths is not None:
+            raise ValueError("Unexpected argument for parquet format: json_field_paths")
+        if length_prefix is not None:
+            raise ValueError("Unexpected argument for parquet format: length_prefix")
+        return schema, api.DataFormat(
+            **api_schema,
+            format_type=data_format_type,
         )
     elif data_format_type == "jsonlines":
         if csv_settings is not None:
@@ -280,6 +656,58 @@ def construct_schema_and_data_format(
             schema_registry_settings=maybe_schema_registry_settings(
                 schema_registry_settings
             ),
+            length_prefix=length_prefix,
+            schema_name=schema_name,
+        )
+    elif data_format_type == "avro":
+        if csv_settings is not None:
+            raise ValueError("Unexpected argument for avro format: csv_settings")
+        if json_field_paths is not None:
+            raise ValueError("Unexpected argument for avro format: json_field_paths")
+        if length_prefix is not None:
+            raise ValueError("Unexpected argument for avro format: length_prefix")
+        if avro_schema is None and schema_registry_settings is None:
+            raise ValueError(
+                "Avro format requires either avro_schema or schema_registry_settings"
+            )
+        return schema, api.DataFormat(
+            **api_schema,
+            format_type=data_format_type,
+            schema_registry_settings=maybe_schema_registry_settings(
+                schema_registry_settings
+            ),
+            avro_schema=avro_schema,
+            schema_name=schema_name,
+        )
+    elif data_format_type == "protobuf":
+        if csv_settings is not None:
+            raise ValueError("Unexpected argument for protobuf format: csv_settings")
+        if json_field_paths is not None:
+            raise ValueError(
+                "Unexpected argument for protobuf format: json_field_paths"
+            )
+        if protobuf_descriptor_set is None or protobuf_message is None:
+            raise ValueError(
+                "Protobuf format requires protobuf_descriptor_set and protobuf_message"
+            )
+        return schema, api.DataFormat(
+            **api_schema,
+            format_type=data_format_type,
+            length_prefix=length_prefix,
+            protobuf_descriptor_set=Path(protobuf_descriptor_set).read_bytes(),
+            protobuf_message=protobuf_message,
+            schema_name=schema_name,
+        )
+    elif data_format_type == "msgpack":
+        if csv_settings is not None:
+            raise ValueError("Unexpected argument for msgpack format: csv_settings")
+        if json_field_paths is not None:
+            raise ValueError("Unexpected argument for msgpack format: json_field_paths")
+        return schema, api.DataFormat(
+            **api_schema,
+            format_type=data_format_type,
+            length_prefix=length_prefix,
+            schema_name=schema_name,
         )
     else:
         raise ValueError(f"data format `{format}` not supported")
@@ -308,6 +736,7 @@ class MessageQueueOutputFormat:
     header_fields: dict[str, int]
     data_format: api.DataFormat
     topic_name_index: int | None
+    partition_field_index: int | None = None
 
     @classmethod
     def construct(
@@ -320,8 +749,10 @@ class MessageQueueOutputFormat:
         value: ColumnReference | None = None,
         headers: Iterable[ColumnReference] | None = None,
         topic_name: ColumnReference | None = None,
+        partition: ColumnReference | None = None,
         schema_registry_settings: SchemaRegistrySettings | None = None,
         subject: str | None = None,
+        avro_schema: str | None = None,
     ) -> MessageQueueOutputFormat:
         key_field_index = None
         header_fields: dict[str, int] = {}
@@ -341,6 +772,18 @@ class MessageQueueOutputFormat:
         else:
             topic_name_index = None
 
+        if partition is not None:
+            partition_field_index = cls.add_column_reference_to_extract(
+                partition, columns_to_extract, extracted_field_indices
+            )
+            if partition._column.dtype not in (dt.INT, dt.Optional(dt.INT), dt.ANY):
+                raise ValueError(
+                    "The partition column must have an integer type, however "
+                    f"{partition._column.dtype.typehint} is used"
+                )
+        else:
+            partition_field_index = None
+
         # Common part for all formats: obtain key field index and prepare header fields
         if key is not None:
             if table[key._name]._column.dtype not in allowed_column_types:
@@ -357,7 +800,22 @@ class MessageQueueOutputFormat:
                 )
 
         # Format-dependent parts: handle json and dsv separately
-        if format == "json" or format == "dsv":
+        if format == "debezium":
+            if schema_registry_settings is not None:
+                raise ValueError(
+                    "Schema registry is not supported for the 'debezium' format"
+                )
+            for column_name in table._columns:
+                cls.add_column_reference_to_extract(
+                    table[column_name], columns_to_extract, extracted_field_indices
+                )
+            table = table.select(*columns_to_extract)
+            data_format = api.DataFormat(
+                format_type="debezium",
+                key_field_names=[],
+                value_fields=_format_output_value_fields(table),
+            )
+        elif format == "json" or format == "dsv":
             for column_name in table._columns:
                 cls.add_column_reference_to_extract(
                     table[column_name], columns_to_extract, extracted_field_indices
@@ -373,6 +831,41 @@ class MessageQueueOutputFormat:
                 ),
                 subject=subject,
             )
+        elif format == "avro":
+            if avro_schema is None:
+                raise ValueError(
+                    "'avro_schema' must be specified for the 'avro' format"
+                )
+            for column_name in table._columns:
+                cls.add_column_reference_to_extract(
+                    table[column_name], columns_to_extract, extracted_field_indices
+                )
+            table = table.select(*columns_to_extract)
+            data_format = api.DataFormat(
+                format_type="avro",
+                key_field_names=[],
+                value_fields=_format_output_value_fields(table),
+                schema_registry_settings=maybe_schema_registry_settings(
+                    schema_registry_settings
+                ),
+                subject=subject,
+                avro_schema=avro_schema,
+            )
+        elif format == "msgpack":
+            if schema_registry_settings is not None:
+                raise ValueError(
+                    "Schema registry is not supported for the 'msgpack' format"
+                )
+            for column_name in table._columns:
+                cls.add_column_reference_to_extract(
+                    table[column_name], columns_to_extract, extracted_field_indices
+                )
+            table = table.select(*columns_to_extract)
+            data_format = api.DataFormat(
+                format_type="msgpack",
+                key_field_names=[],
+                value_fields=_format_output_value_fields(table),
+            )
         elif format == "raw" or format == "plaintext":
             value_field_index = None
             if key is not None and value is None:
@@ -418,6 +911,7 @@ class MessageQueueOutputFormat:
             header_fields=header_fields,
             data_format=data_format,
             topic_name_index=topic_name_index,
+            partition_field_index=partition_field_index,
         )
 
     @staticmethod
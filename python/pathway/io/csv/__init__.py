@@ -6,11 +6,15 @@ from os import PathLike
 from typing import Iterable, Literal
 
 import pathway as pw
-from pathway.internals.expression import ColumnReference
+from pathway.internals.expression import ColumnExpression, ColumnReference
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.table import Table
 from pathway.internals.trace import trace_user_frame
-from pathway.io._utils import CsvParserSettings, check_deprecated_kwargs
+from pathway.io._utils import (
+    ColumnMasking,
+    CsvParserSettings,
+    check_deprecated_kwargs,
+)
 
 
 @check_arg_types
@@ -175,6 +179,11 @@ def write(
     *,
     name: str | None = None,
     sort_by: Iterable[ColumnReference] | None = None,
+    write_bom: bool = False,
+    normalize_newlines: bool = False,
+    column_masking: dict[ColumnReference | str, ColumnMasking] | None = None,
+    row_filter: ColumnExpression | None = None,
+    columns: Iterable[ColumnReference] | None = None,
 ) -> None:
     """Writes `table`'s stream of updates to a file in delimiter-separated values format.
 
@@ -186,6 +195,21 @@ def write(
         sort_by: If specified, the output will be sorted in ascending order based on the
             values of the given columns within each minibatch. When multiple columns are provided,
             the corresponding value tuples will be compared lexicographically.
+        write_bom: If set, the file starts with the UTF-8 byte order mark, which some
+            spreadsheet programs need to detect the encoding.
+        normalize_newlines: If set, the CRLF and CR line breaks inside the values are
+            replaced with LF. The values containing line breaks are quoted either way,
+            so they can be read back.
+        column_masking: The columns to be masked before writing, mapped to the
+            :py:class:`~pathway.io.ColumnMasking` to be applied to them.
+        row_filter: If specified, only the rows for which this condition holds are
+            written. The condition is evaluated by the connector, without creating a
+            filtered table. It can consist of the comparisons of the columns of
+            ``table`` with the constants, the checks for ``None`` and the boolean
+            columns, combined with ``&``, ``|`` and ``~``.
+        columns: If specified, only these columns of ``table`` are written, in the
+            given order. The columns are selected by the connector, without creating
+            a projected table.
 
     Returns:
         None
@@ -231,4 +255,9 @@ def write(
         format="csv",
         name=name,
         sort_by=sort_by,
+        write_bom=write_bom,
+        normalize_newlines=normalize_newlines,
+        column_masking=column_masking,
+        row_filter=row_filter,
+        columns=columns,
     )
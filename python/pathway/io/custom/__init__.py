@@ -0,0 +1,285 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import json
+from typing import Any, Callable, Iterable, Iterator, Literal
+
+from pathway.internals import api, datasink, datasource
+from pathway.internals.expression import ColumnReference
+from pathway.internals.runtime_type_check import check_arg_types
+from pathway.internals.schema import Schema
+from pathway.internals.table import Table
+from pathway.internals.table_io import table_from_datasource
+from pathway.internals.trace import trace_user_frame
+from pathway.io._utils import (
+    MessageQueueOutputFormat,
+    _get_unique_name,
+    check_raw_and_plaintext_only_kwargs_for_message_queues,
+    construct_schema_and_data_format,
+)
+
+Format = Literal["json", "plaintext", "raw"]
+
+_SPECIAL_FIELDS = ("time", "diff")
+
+
+def _encode_row(row: Any, format: Format) -> bytes:
+    if format == "json":
+        return json.dumps(row).encode()
+    if format == "plaintext":
+        return row.encode()
+    return bytes(row)
+
+
+def _decode_row(payload: bytes, format: Format) -> Any:
+    if format == "json":
+        row = json.loads(payload)
+        for field in _SPECIAL_FIELDS:
+            row.pop(field, None)
+        return row
+    if format == "plaintext":
+        return payload.decode()
+    return payload
+
+
+def _encoded_records(
+    source: Callable[[bytes | None], Iterable[Any]], format: Format
+) -> Callable[[bytes | None], Iterator[tuple[bytes, bytes | None]]]:
+    def read(cursor: bytes | None) -> Iterator[tuple[bytes, bytes | None]]:
+        for item in source(cursor):
+            if isinstance(item, tuple):
+                row, row_cursor = item
+                if not isinstance(row_cursor, bytes):
+                    raise TypeError(
+                        f"the cursor must be bytes, not {type(row_cursor).__name__}"
+                    )
+            else:
+                row, row_cursor = item, None
+            yield _encode_row(row, format), row_cursor
+
+    return read
+
+
+@check_arg_types
+@trace_user_frame
+def read(
+    source: Callable[[bytes | None], Iterable[Any]],
+    *,
+    schema: type[Schema] | None = None,
+    format: Format = "json",
+    max_queue_size: int = 1024,
+    autocommit_duration_ms: int | None = 1500,
+    json_field_paths: dict[str, str] | None = None,
+    name: str | None = None,
+    max_backlog_size: int | None = None,
+    debug_data=None,
+    **kwargs,
+) -> Table:
+    """Reads a table from a source implemented in Python as a generator.
+
+    ``source`` is called once, when the computation starts, and returns an iterable
+    of rows. It's usually a generator function. The rows are consumed by a single
+    Pathway worker, and the connector finishes when the iterable is exhausted.
+
+    The rows are produced in a separate thread and passed to Pathway through a queue
+    holding at most ``max_queue_size`` rows. When the queue is full, the generator is
+    not resumed until Pathway takes some of the rows, so a source producing the rows
+    faster than they are processed doesn't fill up the memory.
+
+    It supports three formats: ``"json"``, ``"plaintext"``, and ``"raw"``. In the
+    ``"json"`` format, each row is a dictionary that can be serialized to JSON, and
+    the columns are created according to ``schema``. In the ``"plaintext"`` and
+    ``"raw"`` formats, each row is a ``str`` or ``bytes`` respectively, and the table
+    has an autogenerated primary key and a single ``"data"`` column.
+
+    The source can make its reading resumable by yielding ``(row, cursor)`` pairs
+    instead of rows. The cursor is ``bytes`` describing the position of the source
+    right after the row, such as an offset in a file or the last id read from a
+    database. If persistence is enabled and the program is restarted, ``source`` is
+    called with the cursor of the last row processed, and it should yield the rows
+    that follow it. On the first run, or if no cursor was yielded before the
+    restart, ``source`` is called with ``None``.
+
+    Args:
+        source: A function taking the cursor to resume from, or ``None``, and
+            returning the rows or ``(row, cursor)`` pairs.
+        schema: The table schema, used only when the format is set to ``"json"``.
+        format: The format of the rows, which can be ``"json"``, ``"plaintext"``, or
+            ``"raw"``.
+        max_queue_size: The maximum number of rows produced by ``source`` and not yet
+            taken by Pathway.
+        autocommit_duration_ms: The time interval (in milliseconds) between commits.
+            After this time, the updates received by the connector are committed and
+            added to Pathway's computation graph.
+        json_field_paths: For the ``"json"`` format, this allows mapping field names to
+            paths within the JSON structure. Use the format ``<field_name>: <path>``
+            where the path follows the
+            `JSON Pointer (RFC 6901) <https://www.rfc-editor.org/rfc/rfc6901>`_.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards. Additionally, if persistence is enabled, it
+            will be used as the name for the snapshot that stores the connector's
+            progress.
+        max_backlog_size: Limit on the number of entries read from the input source and
+            kept in processing at any moment. Reading pauses when the limit is reached
+            and resumes as processing of some entries completes.
+        debug_data: Static data replacing original one when debug mode is active.
+
+    Returns:
+        Table: The table read.
+
+    Example:
+
+    Suppose the events are fetched from an API page by page, with the page number
+    being enough to continue the fetching. The source can be implemented as follows,
+    where ``fetch_page`` returns the events of a page:
+
+    >>> import pathway as pw
+    >>> class InputSchema(pw.Schema):
+    ...     event_id: int = pw.column_definition(primary_key=True)
+    ...     kind: str
+    ...
+    >>> def fetch_events(cursor):
+    ...     page = int(cursor) if cursor is not None else 0
+    ...     while events := fetch_page(page):
+    ...         page += 1
+    ...         for event in events:
+    ...             yield event, str(page).encode()
+    ...
+    >>> table = pw.io.custom.read(fetch_events, schema=InputSchema, name="events")
+
+    The cursor of the last event of a page points to the next page, so if the program
+    is restarted, the fetching continues from the first page not processed entirely.
+    """
+
+    data_storage = api.DataStorage(
+        storage_type="custom",
+        mode=api.ConnectorMode.STREAMING,
+        custom_connector_settings=api.CustomConnectorSettings(
+            read=_encoded_records(source, format),
+            max_queue_size=max_queue_size,
+        ),
+    )
+    schema, data_format = construct_schema_and_data_format(
+        "binary" if format == "raw" else format,
+        schema=schema,
+        csv_settings=None,
+        json_field_paths=json_field_paths,
+    )
+    data_source_options = datasource.DataSourceOptions(
+        commit_duration_ms=autocommit_duration_ms,
+        unique_name=_get_unique_name(name, kwargs),
+        max_backlog_size=max_backlog_size,
+    )
+    return table_from_datasource(
+        datasource.GenericDataSource(
+            datastorage=data_storage,
+            dataformat=data_format,
+            data_source_options=data_source_options,
+            schema=schema,
+            datasource_name="custom",
+        ),
+        debug_datasource=datasource.debug_datasource(debug_data),
+    )
+
+
+@check_raw_and_plaintext_only_kwargs_for_message_queues
+@check_arg_types
+@trace_user_frame
+def write(
+    table: Table,
+    callback: Callable[[Any, int, int], None],
+    *,
+    on_flush: Callable[[], None] | None = None,
+    on_end: Callable[[], None] | None = None,
+    format: Format = "json",
+    value: ColumnReference | None = None,
+    max_queue_size: int = 1024,
+    name: str | None = None,
+    sort_by: Iterable[ColumnReference] | None = None,
+) -> None:
+    """Writes the updates of the table into a sink implemented in Python.
+
+    ``callback`` is called with ``(row, time, diff)`` for each update of the table,
+    where ``diff`` is ``1`` for the rows added and ``-1`` for the rows removed. At the
+    end of each minibatch, ``on_flush`` is called, and ``on_end`` is called once all
+    the updates have been written.
+
+    The callbacks are called from a separate thread, which takes the updates from a
+    queue holding at most ``max_queue_size`` updates. When the queue is full, Pathway
+    waits until ``callback`` catches up, so a slow sink slows down the computation
+    instead of the updates piling up in memory. If ``callback`` raises an exception,
+    the remaining updates of the minibatch are skipped and the computation fails
+    before ``on_flush`` would be called.
+
+    In the ``"json"`` format, the rows are dictionaries with the values of the columns
+    in their JSON representation. In the ``"plaintext"`` and ``"raw"`` formats, the
+    rows are the values of a single column as ``str`` or ``bytes`` respectively. The
+    column is specified with ``value`` or deduced if the table has only one column.
+
+    Args:
+        table: The table for output.
+        callback: The function called with ``(row, time, diff)`` for each update.
+        on_flush: The function called at the end of each minibatch, after
+            ``callback`` has been called for all its updates.
+        on_end: The function called once all the updates have been written.
+        format: The format of the rows, which can be ``"json"``, ``"plaintext"``, or
+            ``"raw"``.
+        value: Reference to the column that should be used as the row in the
+            ``"plaintext"`` or ``"raw"`` format.
+        max_queue_size: The maximum number of updates waiting for ``callback``.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+        sort_by: If specified, the output will be sorted in ascending order based on the
+            values of the given columns within each minibatch. When multiple columns are
+            provided, the corresponding value tuples will be compared lexicographically.
+
+    Example:
+
+    Suppose the rows of a table are to be sent to a service with a client that
+    buffers the requests, where ``client.send`` adds a request to the buffer and
+    ``client.commit`` sends the buffered requests. It can be done like this:
+
+    >>> import pathway as pw
+    >>> table = pw.debug.table_from_markdown('''
+    ... owner | pet
+    ... Alice | dog
+    ... Bob   | cat
+    ... ''')
+    >>> def send(row, time, diff):
+    ...     client.send({**row, "deleted": diff < 0})
+    ...
+    >>> def commit():
+    ...     client.commit()
+    ...
+    >>> pw.io.custom.write(table, send, on_flush=commit)
+    """
+    output_format = MessageQueueOutputFormat.construct(
+        table,
+        format=format,
+        value=value,
+    )
+    table = output_format.table
+
+    def write_payload(payload: bytes, time: int, diff: int) -> None:
+        callback(_decode_row(payload, format), time, diff)
+
+    data_storage = api.DataStorage(
+        storage_type="custom",
+        custom_connector_settings=api.CustomConnectorSettings(
+            write=write_payload,
+            flush=on_flush,
+            finish=on_end,
+            max_queue_size=max_queue_size,
+        ),
+    )
+
+    table.to(
+        datasink.GenericDataSink(
+            data_storage,
+            output_format.data_format,
+            datasink_name="custom",
+            unique_name=name,
+            sort_by=sort_by,
+        )
+    )
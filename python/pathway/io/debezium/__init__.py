@@ -2,6 +2,7 @@
 
 from __future__ import annotations
 
+from pathway import secrets
 from pathway.engine import DebeziumDBType
 from pathway.internals import api, datasource
 from pathway.internals.runtime_type_check import check_arg_types
@@ -34,6 +35,7 @@ def read(
     Args:
         rdkafka_settings: Connection settings in the format of
             `librdkafka <https://github.com/edenhill/librdkafka/blob/master/CONFIGURATION.md>`_.
+            Values can be :py:class:`~pathway.secrets.Secret` objects.
         topic_name: Name of topic in Kafka to which the updates are streamed.
         db_type: Type of the database from which events are streamed;
         schema: Schema of the resulting table.
@@ -111,7 +113,7 @@ def read(
 
     data_storage = api.DataStorage(
         storage_type="kafka",
-        rdkafka_settings=rdkafka_settings,
+        rdkafka_settings=secrets.resolve(rdkafka_settings),
         topic=topic_name,
     )
     schema, data_format_definition = read_schema(schema)
@@ -14,19 +14,17 @@ from typing import Any, Iterable, Literal
 from deltalake import DeltaTable
 
 from pathway.internals import api, datasink, datasource
-from pathway.internals._io_helpers import (
-    AwsS3Settings,
-    _format_output_value_fields,
-    is_s3_path,
-)
+from pathway.internals._io_helpers import AwsS3Settings, is_s3_path
 from pathway.internals.config import _check_entitlements
-from pathway.internals.expression import ColumnReference
+from pathway.internals.expression import ColumnExpression, ColumnReference
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.schema import Schema, schema_from_dict
 from pathway.internals.table import Table
 from pathway.internals.table_io import table_from_datasource
 from pathway.internals.trace import trace_user_frame
 from pathway.io._utils import (
+    ColumnMasking,
+    _prepare_sink_output,
     _get_unique_name,
     _prepare_s3_connection_settings,
     internal_connector_mode,
@@ -296,6 +294,7 @@ def read(
         AwsS3Settings | MinIOSettings | WasabiS3Settings | DigitalOceanS3Settings | None
     ) = None,
     start_from_timestamp_ms: int | None = None,
+    read_change_data_feed: bool = False,
     autocommit_duration_ms: int | None = 1500,
     name: str | None = None,
     max_backlog_size: int | None = None,
@@ -335,6 +334,13 @@ def read(
         start_from_timestamp_ms: If defined, only changes that occurred after the specified
             timestamp will be read. This parameter can only be used for tables with
             append-only behavior.
+        read_change_data_feed: If set to ``True``, the changes made after the initial
+            snapshot are read from the change data feed of the table, so that only the
+            inserted, updated, and deleted rows are processed, even when the data
+            files are rewritten. The rows are upserted by the primary key, which
+            therefore must be specified in the schema. The change data feed must be
+            enabled for the table with the ``delta.enableChangeDataFeed`` property; the
+            versions that don't have it are read from the data files.
         autocommit_duration_ms: The maximum time between two commits. Every
             ``autocommit_duration_ms`` milliseconds, the updates received by the connector are
             committed and pushed into Pathway's computation graph.
@@ -410,6 +416,7 @@ def read(
         ),
         start_from_timestamp_ms=start_from_timestamp_ms,
         backfilling_thresholds=_backfilling_thresholds,
+        read_change_data_feed=read_change_data_feed,
     )
     if schema is None:
         try:
@@ -421,6 +428,9 @@ def read(
 
     data_format = api.DataFormat(
         format_type="transparent",
+        session_type=(
+            api.SessionType.UPSERT if read_change_data_feed else api.SessionType.NATIVE
+        ),
         **api_schema,
     )
 
@@ -436,7 +446,7 @@ def read(
             schema=schema,
             data_source_options=data_source_options,
             datasource_name="deltalake",
-            append_only=True,
+            append_only=not read_change_data_feed,
         ),
         debug_datasource=datasource.debug_datasource(debug_data),
     )
@@ -476,6 +486,9 @@ def write(
     sort_by: Iterable[ColumnReference] | None = None,
     output_table_type: Literal["stream_of_changes", "snapshot"] = "stream_of_changes",
     table_optimizer: TableOptimizer | None = None,
+    column_masking: dict[ColumnReference | str, ColumnMasking] | None = None,
+    row_filter: ColumnExpression | None = None,
+    columns: Iterable[ColumnReference] | None = None,
 ) -> None:
     """
     Writes the stream of changes from ``table`` into `Delta Lake <https://delta.io/>_` data
@@ -529,6 +542,16 @@ def write(
             the output. Please also note that this method is not suitable for the tables that don't
             fit in memory.**
         table_optimizer: The optimization parameters for the output table.
+        column_masking: The columns to be masked before writing, mapped to the
+            :py:class:`~pathway.io.ColumnMasking` to be applied to them.
+        row_filter: If specified, only the rows for which this condition holds are
+            written. The condition is evaluated by the connector, without creating a
+            filtered table. It can consist of the comparisons of the columns of
+            ``table`` with the constants, the checks for ``None`` and the boolean
+            columns, combined with ``&``, ``|`` and ``~``.
+        columns: If specified, only these columns of ``table`` are written, in the
+            given order. The columns are selected by the connector, without creating
+            a projected table.
 
     Returns:
         None
@@ -591,10 +614,16 @@ def write(
         snapshot_maintenance_on_output=output_table_type == _SNAPSHOT_OUTPUT_TABLE_TYPE,
         delta_optimizer_rule=(table_optimizer.engine_rule if table_optimizer else None),
     )
+    sink_output = _prepare_sink_output(
+        table, column_masking=column_masking, row_filter=row_filter, columns=columns
+    )
     data_format = api.DataFormat(
         format_type="identity",
         key_field_names=None,
-        value_fields=_format_output_value_fields(table),
+        value_fields=sink_output.value_fields,
+        column_masking=sink_output.column_masking,
+        sink_filter=sink_output.sink_filter,
+        sink_projection=sink_output.sink_projection,
     )
 
     if table_optimizer is not None:
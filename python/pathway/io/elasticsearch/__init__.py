@@ -4,6 +4,7 @@ from __future__ import annotations
 
 from typing import Iterable
 
+from pathway import secrets
 from pathway.internals import api, datasink
 from pathway.internals._io_helpers import _format_output_value_fields
 from pathway.internals.config import _check_entitlements
@@ -11,6 +12,7 @@ from pathway.internals.expression import ColumnReference
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.table import Table
 from pathway.internals.trace import trace_user_frame
+from pathway.io._utils import ConnectorRetryPolicy, _timedelta_from_ms
 
 
 class ElasticSearchAuth:
@@ -36,8 +38,8 @@ class ElasticSearchAuth:
         return cls(
             api.ElasticSearchAuth(
                 "apikey",
-                apikey_id=apikey_id,
-                apikey=apikey,
+                apikey_id=secrets.resolve(apikey_id),
+                apikey=secrets.resolve(apikey),
             )
         )
 
@@ -56,8 +58,8 @@ class ElasticSearchAuth:
         return cls(
             api.ElasticSearchAuth(
                 "basic",
-                username=username,
-                password=password,
+                username=secrets.resolve(username),
+                password=secrets.resolve(password),
             )
         )
 
@@ -75,7 +77,7 @@ class ElasticSearchAuth:
         return cls(
             api.ElasticSearchAuth(
                 "bearer",
-                bearer=bearer,
+                bearer=secrets.resolve(bearer),
             )
         )
 
@@ -94,6 +96,8 @@ def write(
     *,
     name: str | None = None,
     sort_by: Iterable[ColumnReference] | None = None,
+    retry_policy: ConnectorRetryPolicy | None = None,
+    request_timeout_ms: int | None = None,
 ) -> None:
     """Write a table to a given index in ElasticSearch.
 
@@ -104,6 +108,10 @@ def write(
     indicates the time of the Pathway minibatch, and ``diff``, which can be either
     ``1`` (row addition) or ``-1`` (row deletion).
 
+    The document ids are derived from the updates, so that when the computation is
+    restarted from a persisted state, the updates written after the last checkpoint
+    overwrite their documents instead of being indexed twice.
+
     Args:
         table: the table to output.
         host: the host and port, on which Elasticsearch server works.
@@ -114,6 +122,10 @@ def write(
         sort_by: If specified, the output will be sorted in ascending order based on the
             values of the given columns within each minibatch. When multiple columns are provided,
             the corresponding value tuples will be compared lexicographically.
+        retry_policy: Retry settings for the failed writes. By default, a write is
+            retried five times.
+        request_timeout_ms: Timeout of a single request to Elasticsearch, in
+            milliseconds. If not specified, the default timeout of the client is used.
 
     Returns:
         None
@@ -158,6 +170,8 @@ def write(
             index_name=index_name,
             auth=auth.engine_es_auth,
         ),
+        retry_policy=retry_policy.api_policy if retry_policy else None,
+        request_timeout=_timedelta_from_ms(request_timeout_ms),
     )
 
     data_format = api.DataFormat(
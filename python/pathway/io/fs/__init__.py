@@ -2,19 +2,22 @@
 
 from __future__ import annotations
 
+import datetime
+import os
 import warnings
 from os import PathLike, fspath
 from typing import Any, Iterable, Literal
 
 from pathway.internals import Schema, api, datasink, datasource
-from pathway.internals._io_helpers import _format_output_value_fields
-from pathway.internals.expression import ColumnReference
+from pathway.internals.expression import ColumnExpression, ColumnReference
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.table import Table
 from pathway.internals.table_io import table_from_datasource
 from pathway.internals.trace import trace_user_frame
 from pathway.io._utils import (
+    ColumnMasking,
     CsvParserSettings,
+    _prepare_sink_output,
     _get_unique_name,
     construct_schema_and_data_format,
     internal_connector_mode,
@@ -24,6 +27,13 @@ from pathway.io._utils import (
 SUPPORTED_OUTPUT_FORMATS: set[str] = {
     "csv",
     "json",
+    "parquet",
+}
+
+_OUTPUT_FILE_EXTENSIONS: dict[str, str] = {
+    "csv": "csv",
+    "json": "jsonl",
+    "parquet": "parquet",
 }
 
 
@@ -32,7 +42,13 @@ SUPPORTED_OUTPUT_FORMATS: set[str] = {
 def read(
     path: str | PathLike,
     format: Literal[
-        "csv", "json", "plaintext", "plaintext_by_file", "binary", "only_metadata"
+        "csv",
+        "json",
+        "plaintext",
+        "plaintext_by_file",
+        "binary",
+        "only_metadata",
+        "parquet",
     ],
     *,
     schema: type[Schema] | None = None,
@@ -60,20 +76,26 @@ def read(
     opening and without reading the contents of the files. The metadata is then available
     in the ``_metadata`` column.
 
+    In case the format is ``"parquet"``, each file is read as a Parquet file. Its
+    columns are matched with the columns of the schema by name, the columns absent from
+    the schema are skipped, and each row of the file becomes a row of the table.
+
     Args:
         path: Path to the file or to the folder with files or
             `glob <https://en.wikipedia.org/wiki/Glob_(programming)>`_ pattern for the
             objects to be read. The connector will read the contents of all matching files as well
             as recursively read the contents of all matching folders.
         format: Format of data to be read. Currently ``"csv"``, ``"json"``, ``"plaintext"``,
-            ``"plaintext_by_file"``, ``"binary"``, and ``"only_metadata"`` formats are
-            supported. The difference between ``"plaintext"`` and ``"plaintext_by_file"`` is
-            how the input is tokenized: if the ``"plaintext"`` option is chosen, it's split
-            by the newlines. Otherwise, the files are split in full and one row will
-            correspond to one file. In case the ``"binary"`` format is specified,
-            the data is read as raw bytes without UTF-8 parsing. Finally, if ``"only_metadata"``
-            is chosen, the connector only scans the filesystem for file additions,
-            changes, modifications, and provides them in the metadata column.
+            ``"plaintext_by_file"``, ``"binary"``, ``"only_metadata"``, and
+            ``"parquet"`` formats are supported. The difference between ``"plaintext"``
+            and ``"plaintext_by_file"`` is how the input is tokenized: if the
+            ``"plaintext"`` option is chosen, it's split by the newlines. Otherwise, the
+            files are split in full and one row will correspond to one file. In case the
+            ``"binary"`` format is specified, the data is read as raw bytes without
+            UTF-8 parsing. If ``"only_metadata"`` is chosen, the connector only scans
+            the filesystem for file additions, changes, modifications, and provides them
+            in the metadata column. Finally, ``"parquet"`` reads each file as a Parquet
+            file, with one row of the table per row of the file.
         schema: Schema of the resulting table.
         mode: Denotes how the engine polls the new data from the source. Currently
             ``"streaming"`` and ``"static"`` are supported. If set to ``"streaming"`` the engine will wait for
@@ -274,23 +296,86 @@ def read(
 def write(
     table: Table,
     filename: str | PathLike,
-    format: Literal["json", "csv"],
+    format: Literal["json", "csv", "parquet"],
     *,
     name: str | None = None,
     sort_by: Iterable[ColumnReference] | None = None,
+    max_file_size: int | None = None,
+    rotation_interval: datetime.timedelta | None = None,
+    name_template: str = "{table}-{date}-{part}.{ext}",
+    write_success_markers: bool = False,
+    row_group_size: int | None = None,
+    write_bom: bool = False,
+    normalize_newlines: bool = False,
+    column_masking: dict[ColumnReference | str, ColumnMasking] | None = None,
+    row_filter: ColumnExpression | None = None,
+    columns: Iterable[ColumnReference] | None = None,
 ) -> None:
     """Writes ``table``'s stream of updates to a file in the given format.
 
+    If ``max_file_size`` or ``rotation_interval`` is set, the updates are written to
+    a sequence of files in the ``filename`` directory instead. A new file is started
+    once the current one reaches ``max_file_size`` bytes, and once the rotation period
+    of ``rotation_interval``, aligned to the Unix epoch, is over. A file is written under
+    a temporary hidden name, and gets its final name only when it is complete, so the
+    readers of the directory never see partially written files. In the ``"csv"``
+    format, each of the files starts with the header.
+
+    The ``"parquet"`` output is always a directory of files, even if neither
+    ``max_file_size`` nor ``rotation_interval`` is set, in which case a single file is
+    written and completed when the computation ends. Each file has the columns of the
+    table followed by the ``time`` and ``diff`` columns, and can be read once it's
+    complete, as its footer is written last. The ``max_file_size`` of the Parquet files
+    is checked after each minibatch, so the files may exceed it by the size of one.
+
     Args:
         table: Table to be written.
-        filename: Path to the target output file.
-        format: Format to use for data output. Currently, there are two supported
-            formats: ``"json"`` and ``"csv"``.
+        filename: Path to the target output file, or to the output directory if the
+            files are rotated.
+        format: Format to use for data output. Currently, there are three supported
+            formats: ``"json"``, ``"csv"`` and ``"parquet"``.
         name: A unique name for the connector. If provided, this name will be used in
             logs and monitoring dashboards.
         sort_by: If specified, the output will be sorted in ascending order based on the
             values of the given columns within each minibatch. When multiple columns are provided,
             the corresponding value tuples will be compared lexicographically.
+        max_file_size: The size in bytes after which a new file is started.
+        rotation_interval: The length of the rotation periods. Each file contains
+            the updates written within a single period.
+        name_template: The template of the names of the rotated files, relative to the
+            output directory. It can contain the placeholders ``{table}``, replaced with
+            ``name`` or with the name of the directory if ``name`` is not given,
+            ``{date}`` and ``{time}``, replaced with the start of the rotation period, or
+            with the time the file is started if there is no ``rotation_interval``,
+            ``{part}``, replaced with the number of the file within the period, and
+            ``{ext}``, replaced with ``csv``, ``jsonl`` or ``parquet``. The ``{part}``
+            placeholder is mandatory. The template may contain slashes, e.g.
+            ``{date}/{part}.{ext}`` places the files of each day in a separate
+            directory. The existing files are never overwritten, the next part number
+            is used instead.
+        write_success_markers: If set, once all the files of a rotation period are
+            complete, an empty ``_SUCCESS`` file is created in each of the directories
+            they are in. Without ``rotation_interval``, this happens when the computation
+            ends. To tell the periods apart, the template should put the files of each
+            period in a separate directory.
+        row_group_size: The maximum number of rows in a row group of the ``"parquet"``
+            output. Defaults to 100000.
+        write_bom: If set, the ``"csv"`` output starts with the UTF-8 byte order mark,
+            which some spreadsheet programs need to detect the encoding. If the files
+            are rotated, each of them starts with it.
+        normalize_newlines: If set, the CRLF and CR line breaks inside the values are
+            replaced with LF in the ``"csv"`` output. The values containing line breaks
+            are quoted either way, so they can be read back.
+        column_masking: The columns to be masked before writing, mapped to the
+            :py:class:`~pathway.io.ColumnMasking` to be applied to them.
+        row_filter: If specified, only the rows for which this condition holds are
+            written. The condition is evaluated by the connector, without creating a
+            filtered table. It can consist of the comparisons of the columns of
+            ``table`` with the constants, the checks for ``None`` and the boolean
+            columns, combined with ``&``, ``|`` and ``~``.
+        columns: If specified, only these columns of ``table`` are written, in the
+            given order. The columns are selected by the connector, without creating
+            a projected table.
 
     Returns:
         None
@@ -356,19 +441,78 @@ def write(
             )
         )
 
-    data_storage = api.DataStorage(storage_type="fs", path=fspath(filename))
+    filename = fspath(filename)
+    if row_group_size is not None:
+        if format != "parquet":
+            raise ValueError("row_group_size is supported only for the parquet format")
+        if row_group_size <= 0:
+            raise ValueError("row_group_size must be positive")
+    file_rotation_settings = None
+    if (
+        max_file_size is not None
+        or rotation_interval is not None
+        or format == "parquet"
+    ):
+        if max_file_size is not None and max_file_size <= 0:
+            raise ValueError("max_file_size must be positive")
+        if rotation_interval is not None and rotation_interval.total_seconds() < 1:
+            raise ValueError("rotation_interval must be at least one second")
+        file_rotation_settings = api.FileRotationSettings(
+            name or os.path.basename(os.path.normpath(filename)),
+            _OUTPUT_FILE_EXTENSIONS[format],
+            name_template=name_template,
+            max_file_size=max_file_size,
+            rotation_interval=rotation_interval,
+            write_success_markers=write_success_markers,
+            repeat_header=format == "csv",
+        )
+    elif write_success_markers:
+        raise ValueError(
+            "write_success_markers requires max_file_size or rotation_interval to be set"
+        )
+
+    data_storage = api.DataStorage(
+        storage_type="parquet" if format == "parquet" else "fs",
+        path=filename,
+        file_rotation_settings=file_rotation_settings,
+        parquet_row_group_size=row_group_size,
+    )
+    if format != "csv" and (write_bom or normalize_newlines):
+        raise ValueError(
+            "write_bom and normalize_newlines are supported only for the csv format"
+        )
+    sink_output = _prepare_sink_output(
+        table, column_masking=column_masking, row_filter=row_filter, columns=columns
+    )
     if format == "csv":
         data_format = api.DataFormat(
             format_type="dsv",
             key_field_names=[],
-            value_fields=_format_output_value_fields(table),
+            value_fields=sink_output.value_fields,
             delimiter=",",
+            dsv_write_bom=write_bom,
+            dsv_normalize_newlines=normalize_newlines,
+            column_masking=sink_output.column_masking,
+            sink_filter=sink_output.sink_filter,
+            sink_projection=sink_output.sink_projection,
         )
     elif format == "json":
         data_format = api.DataFormat(
             format_type="jsonlines",
             key_field_names=[],
-            value_fields=_format_output_value_fields(table),
+            value_fields=sink_output.value_fields,
+            column_masking=sink_output.column_masking,
+            sink_filter=sink_output.sink_filter,
+            sink_projection=sink_output.sink_projection,
+        )
+    elif format == "parquet":
+        data_format = api.DataFormat(
+            format_type="identity",
+            key_field_names=[],
+            value_fields=sink_output.value_fields,
+            column_masking=sink_output.column_masking,
+            sink_filter=sink_output.sink_filter,
+            sink_projection=sink_output.sink_projection,
         )
 
     table.to(
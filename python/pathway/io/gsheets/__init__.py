@@ -0,0 +1,295 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import hashlib
+import json
+import logging
+import time
+from collections.abc import Callable
+from typing import Any, Literal
+
+from google.oauth2.service_account import Credentials as ServiceCredentials
+from googleapiclient.discovery import build
+from googleapiclient.errors import HttpError
+
+import pathway as pw
+import pathway.internals.dtype as dt
+from pathway.internals import api
+from pathway.internals.api import SessionType
+from pathway.internals.runtime_type_check import check_arg_types
+from pathway.internals.schema import Schema
+from pathway.io.python import ConnectorSubject
+
+SCOPES = ["https://www.googleapis.com/auth/spreadsheets.readonly"]
+
+# The identity of a row is either the value of its primary key or its position
+RowIdentity = tuple
+
+
+class _GSheetsClient:
+    def __init__(
+        self, credentials_file: str, spreadsheet_id: str, range: str
+    ) -> None:
+        credentials = ServiceCredentials.from_service_account_file(
+            credentials_file, scopes=SCOPES
+        )
+        self.sheets = build("sheets", "v4", credentials=credentials, num_retries=3)
+        self.spreadsheet_id = spreadsheet_id
+        self.range = range
+
+    def get_values(self) -> list[list[Any]]:
+        response = (
+            self.sheets.spreadsheets()
+            .values()
+            .get(
+                spreadsheetId=self.spreadsheet_id,
+                range=self.range,
+                valueRenderOption="UNFORMATTED_VALUE",
+                dateTimeRenderOption="FORMATTED_STRING",
+            )
+            .execute()
+        )
+        return response.get("values", [])
+
+
+def _cell_hash(cells: list[Any]) -> str:
+    serialized = json.dumps(cells, sort_keys=True, default=str)
+    return hashlib.sha256(serialized.encode(encoding="utf-8")).hexdigest()
+
+
+def _convert_cell(value: Any, dtype: dt.DType) -> Any:
+    dtype = dt.unoptionalize(dtype)
+    if dtype == dt.STR:
+        return str(value)
+    if dtype == dt.INT and isinstance(value, (int, float)):
+        return int(value)
+    if dtype == dt.FLOAT and isinstance(value, (int, float)):
+        return float(value)
+    if dtype == dt.BOOL and isinstance(value, str):
+        return value.strip().lower() == "true"
+    return value
+
+
+class _SheetRows:
+    """The rows of the sheet as of the last poll, with the hashes of their cells."""
+
+    def __init__(self, schema: type[Schema], header: bool) -> None:
+        self._schema = schema
+        self._header = header
+        self._primary_key = schema.primary_key_columns()
+        self._rows: dict[RowIdentity, tuple[str, dict[str, Any]]] = {}
+
+    def _column_names(self, values: list[list[Any]]) -> list[str]:
+        if self._header:
+            return [str(name).strip() for name in values[0]] if values else []
+        return self._schema.column_names()
+
+    def _parse_row(self, names: list[str], cells: list[Any]) -> dict[str, Any]:
+        columns = self._schema.columns()
+        row: dict[str, Any] = {}
+        for name, column in columns.items():
+            try:
+                value = cells[names.index(name)]
+            except (ValueError, IndexError):
+                value = ""
+            if value == "":
+                # Empty cells are not returned by the API at the ends of the rows
+                if column.has_default_value():
+                    continue
+                row[name] = None
+            else:
+                row[name] = _convert_cell(value, column.dtype)
+        return row
+
+    def update(
+        self, values: list[list[Any]]
+    ) -> tuple[
+        list[tuple[RowIdentity, dict[str, Any]]],
+        list[tuple[RowIdentity, dict[str, Any]]],
+    ]:
+        """Replaces the rows with the new contents of the sheet and returns the rows
+        that have been removed and the ones that are new or have changed."""
+        names = self._column_names(values)
+        data_rows = values[1:] if self._header else values
+        rows: dict[RowIdentity, tuple[str, dict[str, Any]]] = {}
+        for position, cells in enumerate(data_rows):
+            if not any(cell != "" for cell in cells):
+                continue
+            row = self._parse_row(names, cells)
+            if self._primary_key is None:
+                identity: RowIdentity = (position,)
+            else:
+                identity = tuple(row.get(name) for name in self._primary_key)
+            rows[identity] = (_cell_hash(cells), row)
+
+        removed = [
+            (identity, row)
+            for identity, (_, row) in self._rows.items()
+            if identity not in rows
+        ]
+        changed = [
+            (identity, row)
+            for identity, (cell_hash, row) in rows.items()
+            if self._rows.get(identity, ("", None))[0] != cell_hash
+        ]
+        self._rows = rows
+        return removed, changed
+
+
+class _GSheetsSubject(ConnectorSubject):
+    _client_factory: Callable[[], _GSheetsClient]
+    _rows: _SheetRows
+    _refresh_interval: int
+    _mode: str
+
+    def __init__(
+        self,
+        *,
+        client_factory: Callable[[], _GSheetsClient],
+        schema: type[Schema],
+        header: bool,
+        refresh_interval: int,
+        mode: str,
+    ) -> None:
+        super().__init__(datasource_name="gsheets")
+        self._client_factory = client_factory
+        self._rows = _SheetRows(schema, header)
+        self._has_primary_key = schema.primary_key_columns() is not None
+        self._refresh_interval = refresh_interval
+        self._mode = mode
+        assert mode in ["streaming", "static"]
+
+    @property
+    def _session_type(self) -> SessionType:
+        return SessionType.UPSERT if self._mode == "streaming" else SessionType.NATIVE
+
+    @property
+    def _deletions_enabled(self) -> bool:
+        return self._mode == "streaming"
+
+    def _key(self, identity: RowIdentity) -> api.Pointer | None:
+        # With a primary key, the key is computed from the values by the engine
+        return None if self._has_primary_key else api.ref_scalar(*identity)
+
+    def run(self) -> None:
+        client = self._client_factory()
+        while True:
+            try:
+                values = client.get_values()
+            except HttpError as e:
+                logging.error(
+                    f"Failed to read the Google Sheet: {e}. "
+                    f"Retrying in {self._refresh_interval} seconds...",
+                )
+            else:
+                removed, changed = self._rows.update(values)
+                for identity, row in removed:
+                    self._remove_inner(self._key(identity), row)
+                for identity, row in changed:
+                    self._add_inner(self._key(identity), row)
+                if self._mode == "static":
+                    break
+                self.commit()
+            time.sleep(self._refresh_interval)
+
+
+@check_arg_types
+def read(
+    spreadsheet_id: str,
+    range: str,
+    *,
+    schema: type[Schema],
+    service_user_credentials_file: str,
+    mode: Literal["streaming", "static"] = "streaming",
+    header: bool = True,
+    refresh_interval: int = 60,
+    name: str | None = None,
+    max_backlog_size: int | None = None,
+    **kwargs,
+) -> pw.Table:
+    """Reads a table from a range of a Google Sheet.
+
+    The sheet is polled every ``refresh_interval`` seconds and only the rows whose cells
+    have changed since the previous poll, detected by comparing the hashes of their cells,
+    are sent further. It suits well the small reference or dimension tables maintained
+    by hand in spreadsheets.
+
+    The rows are identified by the values of the primary key of the ``schema``, so if
+    a row with the same primary key is modified or moved, it is updated in the table.
+    If the schema doesn't define a primary key, the rows are identified by their
+    positions in the range, so inserting a row in the middle of the range updates all
+    the rows below it. Entirely empty rows are skipped.
+
+    The cells are read as unformatted values, so the numbers are read as numbers and
+    the dates are read as the strings they are displayed as. Empty cells are read as
+    ``None``, unless the column has a default value in the ``schema``.
+
+    Args:
+        spreadsheet_id: the ID of the spreadsheet, as in its URL
+            ``https://docs.google.com/spreadsheets/d/<spreadsheet_id>/edit``.
+        range: the range to be read in the A1 notation, e.g. ``"Prices!A1:C"`` or
+            ``"Prices"`` for the whole sheet.
+        schema: the schema of the resulting table.
+        service_user_credentials_file: Google API service user json file. The
+            spreadsheet must be shared with the service account. Please follow the
+            instructions provided in the `developer's user guide
+            <https://pathway.com/developers/user-guide/connect/connectors/gdrive-connector/#setting-up-google-drive>`_
+            to obtain them, enabling the Google Sheets API instead of the Drive API.
+        mode: denotes how the engine polls the new data from the source. Currently
+            "streaming" and "static" are supported. If set to "streaming", the sheet is
+            checked for changes every ``refresh_interval`` seconds. "static" mode reads
+            the sheet once and ingests all of it in one commit.
+        header: whether the first row of the range contains the names of the columns.
+            If it doesn't, the columns of the range correspond to the columns of the
+            ``schema`` in their order.
+        refresh_interval: time in seconds between polls. Applicable if mode is set to
+            'streaming'. Note that the Sheets API has limits on the number of read
+            requests per minute.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+        max_backlog_size: Limit on the number of entries read from the input source and
+            kept in processing at any moment.
+
+    Returns:
+        The table read.
+
+    Example:
+
+    Consider a sheet ``Prices`` with the columns ``item`` and ``price``, where the
+    prices are maintained by hand. It can be read as follows:
+
+    >>> import pathway as pw
+    >>> class PriceSchema(pw.Schema):
+    ...     item: str = pw.column_definition(primary_key=True)
+    ...     price: float
+    >>> prices = pw.io.gsheets.read(
+    ...     "1BxiMVs0XRA5nFMdKvBdBZjgmUUqptlbs74OgvE2upms",
+    ...     "Prices",
+    ...     schema=PriceSchema,
+    ...     service_user_credentials_file="credentials.json",
+    ... )
+    """
+
+    def client_factory() -> _GSheetsClient:
+        return _GSheetsClient(service_user_credentials_file, spreadsheet_id, range)
+
+    subject = _GSheetsSubject(
+        client_factory=client_factory,
+        schema=schema,
+        header=header,
+        refresh_interval=refresh_interval,
+        mode=mode,
+    )
+
+    return pw.io.python.read(
+        subject,
+        schema=schema,
+        name=name,
+        max_backlog_size=max_backlog_size,
+        _stacklevel=4,
+        **kwargs,
+    )
+
+
+__all__ = ["read"]
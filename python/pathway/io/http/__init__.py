@@ -5,6 +5,8 @@ from __future__ import annotations
 from collections.abc import Callable
 from typing import Any, Literal
 
+from pathway.internals import api, datasink
+from pathway.internals._io_helpers import _format_output_value_fields
 from pathway.internals.api import Pointer
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.schema import Schema
@@ -287,9 +289,75 @@ def write(
     subscribe(table, callback, name=name)
 
 
+@check_arg_types
+@trace_user_frame
+def serve_table(
+    table: Table,
+    table_name: str,
+    *,
+    host: str = "127.0.0.1",
+    port: int = 8081,
+    name: str | None = None,
+) -> None:
+    """Keeps the current contents of the table in memory and serves them over HTTP,
+    so that they can be queried by other services without writing them to a database.
+
+    The rows are available at ``GET http://<host>:<port>/tables/<table_name>`` as a
+    JSON array of objects, with the row key in the ``id`` field. The table is updated
+    atomically when Pathway commits a time, so a response never contains a partially
+    applied update. The following query parameters select the returned rows:
+
+    - ``id=<key>`` returns only the row with the given key;
+    - ``<column>=<value>`` returns only the rows where the column equals the value.
+      Strings are compared as they are, other values are compared with their JSON
+      representation;
+    - ``limit=<n>`` returns at most ``n`` rows, in the order of their keys.
+
+    All tables served on the same host and port share one server.
+
+    Args:
+        table: table to be served.
+        table_name: the name of the table in the URL. It must be unique among the
+            tables served on the same host and port.
+        host: the IP address of the interface the server listens on.
+        port: the port the server listens on.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> pets = pw.debug.table_from_markdown("owner pet \\n Alice dog \\n Bob cat")
+    >>> pw.io.http.serve_table(pets, "pets", port=8081)
+
+    Once the program is running, the dogs can be fetched with
+    ``curl "http://127.0.0.1:8081/tables/pets?pet=dog"``.
+    """
+    data_storage = api.DataStorage(
+        storage_type="query_endpoint",
+        path=f"{host}:{port}",
+        table_name=table_name,
+    )
+    data_format = api.DataFormat(
+        format_type="identity",
+        key_field_names=[],
+        value_fields=_format_output_value_fields(table),
+    )
+
+    table.to(
+        datasink.GenericDataSink(
+            data_storage,
+            data_format,
+            datasink_name="http.serve_table",
+            unique_name=name,
+        )
+    )
+
+
 __all__ = [
     "read",
     "write",
+    "serve_table",
     "RetryPolicy",
     "rest_connector",
     "PathwayWebserver",
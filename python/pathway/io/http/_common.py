@@ -18,15 +18,33 @@ class RetryPolicy:
         first_delay_ms: Duration of the initial retry delay, in milliseconds.
         backoff_factor: Factor by which the retry delay increases after each attempt.
         jitter_ms: Maximum random jitter (in milliseconds) to add to the scaled delay.
+        full_jitter: If set, each delay is a random duration between zero and the
+            scaled delay, instead of the scaled delay with the jitter added. This way
+            the clients that failed at the same time don't retry at the same time.
+        max_elapsed_ms: Maximum total time (in milliseconds) spent on a request,
+            including the retries. No retry is made if it would start later than that.
+            Not limited by default.
 
     Returns:
         A retry policy object.
     """
 
-    def __init__(self, first_delay_ms: int, backoff_factor: float, jitter_ms: int):
+    def __init__(
+        self,
+        first_delay_ms: int,
+        backoff_factor: float,
+        jitter_ms: int,
+        *,
+        full_jitter: bool = False,
+        max_elapsed_ms: int | None = None,
+    ):
         self._next_retry_duration = first_delay_ms * 1e-3
         self._backoff_factor = backoff_factor
         self._jitter = jitter_ms * 1e-3
+        self._full_jitter = full_jitter
+        self._max_elapsed = (
+            max_elapsed_ms * 1e-3 if max_elapsed_ms is not None else None
+        )
 
     @classmethod
     def default(cls):
@@ -46,12 +64,22 @@ class RetryPolicy:
 
     def wait_duration_before_retry(self):
         result = self._next_retry_duration
+        if self._full_jitter:
+            result *= random.random()
 
         self._next_retry_duration *= self._backoff_factor
-        self._next_retry_duration += random.random() * self._jitter
+        if not self._full_jitter:
+            self._next_retry_duration += random.random() * self._jitter
 
         return result
 
+    def allows_retry_at(self, elapsed: float) -> bool:
+        """
+        Checks whether a retry starting ``elapsed`` seconds after the first attempt
+        fits in the maximum total time.
+        """
+        return self._max_elapsed is None or elapsed <= self._max_elapsed
+
 
 class Sender:
     def __init__(
@@ -84,7 +112,9 @@ class Sender:
         if "User-Agent" not in headers:
             headers["User-Agent"] = f"pathway/{pw.__version__}"
         retry_policy = self._retry_policy
+        started_at = time.monotonic()
         for n_attempt in range(0, self._n_retries + 1):
+            connect_timeout = None
             try:
                 response = requests.request(
                     self._request_method,
@@ -97,11 +127,17 @@ class Sender:
                 )
                 if response.ok or response.status_code not in self._retry_codes:
                     break
-            except requests.exceptions.ConnectTimeout:
+            except requests.exceptions.ConnectTimeout as e:
                 if n_attempt == self._n_retries:
                     raise
+                connect_timeout = e
 
             sleep_duration = retry_policy.wait_duration_before_retry()
+            elapsed = time.monotonic() - started_at
+            if not retry_policy.allows_retry_at(elapsed + sleep_duration):
+                if connect_timeout is not None:
+                    raise connect_timeout
+                break
             time.sleep(sleep_duration)
 
         return response
@@ -21,10 +21,211 @@ from pathway.io.minio import MinIOSettings
 from pathway.io.s3 import DigitalOceanS3Settings, WasabiS3Settings
 
 
+class RestCatalog:
+    """Stores the settings of an Iceberg REST catalog.
+
+    Args:
+        uri: URI of the catalog.
+        credential: OAuth2 client credential in the ``client_id:client_secret`` form.
+            It is exchanged for an access token when the connector starts.
+        token: Bearer token used to authenticate the requests to the catalog.
+        oauth2_server_uri: URI of the OAuth2 token endpoint. If not specified, the
+            token endpoint of the catalog is used.
+        scope: OAuth2 scope requested with the ``credential``. Defaults to
+            ``"catalog"``.
+        properties: Other catalog properties, passed to the catalog as they are.
+    """
+
+    def __init__(
+        self,
+        uri: str,
+        *,
+        credential: str | None = None,
+        token: str | None = None,
+        oauth2_server_uri: str | None = None,
+        scope: str | None = None,
+        properties: dict[str, str] | None = None,
+    ):
+        self.uri = uri
+        self.credential = credential
+        self.token = token
+        self.oauth2_server_uri = oauth2_server_uri
+        self.scope = scope
+        self.properties = properties
+
+    def _engine_settings(self) -> api.IcebergCatalogSettings:
+        properties = dict(self.properties or {})
+        for key, value in [
+            ("credential", self.credential),
+            ("token", self.token),
+            ("oauth2-server-uri", self.oauth2_server_uri),
+            ("scope", self.scope),
+        ]:
+            if value is not None:
+                properties[key] = value
+        return api.IcebergCatalogSettings("rest", uri=self.uri, properties=properties)
+
+
+class GlueCatalog:
+    """Stores the settings of an AWS Glue catalog.
+
+    The credentials that are not specified are taken from the environment, in the
+    same way as the AWS CLI does.
+
+    Args:
+        region: AWS region of the catalog.
+        access_key: AWS access key ID.
+        secret_access_key: AWS secret access key.
+        session_token: AWS session token, if temporary credentials are used.
+        profile_name: Name of the AWS profile to take the credentials from.
+        catalog_id: ID of the Glue catalog. Defaults to the catalog of the account.
+        endpoint: Custom endpoint of the Glue service.
+        properties: Other catalog properties, passed to the catalog as they are.
+    """
+
+    def __init__(
+        self,
+        *,
+        region: str | None = None,
+        access_key: str | None = None,
+        secret_access_key: str | None = None,
+        session_token: str | None = None,
+        profile_name: str | None = None,
+        catalog_id: str | None = None,
+        endpoint: str | None = None,
+        properties: dict[str, str] | None = None,
+    ):
+        self.region = region
+        self.access_key = access_key
+        self.secret_access_key = secret_access_key
+        self.session_token = session_token
+        self.profile_name = profile_name
+        self.catalog_id = catalog_id
+        self.endpoint = endpoint
+        self.properties = properties
+
+    def _engine_settings(self) -> api.IcebergCatalogSettings:
+        properties = dict(self.properties or {})
+        for key, value in [
+            ("region_name", self.region),
+            ("aws_access_key_id", self.access_key),
+            ("aws_secret_access_key", self.secret_access_key),
+            ("aws_session_token", self.session_token),
+            ("profile_name", self.profile_name),
+        ]:
+            if value is not None:
+                properties[key] = value
+        return api.IcebergCatalogSettings(
+            "glue",
+            uri=self.endpoint,
+            catalog_id=self.catalog_id,
+            properties=properties,
+        )
+
+
+def _catalog_engine_settings(
+    catalog: str | RestCatalog | GlueCatalog,
+) -> tuple[str | None, api.IcebergCatalogSettings | None]:
+    if isinstance(catalog, str):
+        return catalog, None
+    return None, catalog._engine_settings()
+
+
+@check_arg_types
+@trace_user_frame
+def list_namespaces(
+    catalog: str | RestCatalog | GlueCatalog,
+    *,
+    parent: list[str] | None = None,
+    s3_connection_settings: (
+        AwsS3Settings | MinIOSettings | WasabiS3Settings | DigitalOceanS3Settings | None
+    ) = None,
+    warehouse: str | None = None,
+) -> list[list[str]]:
+    """
+    Lists the namespaces of an Iceberg catalog.
+
+    Args:
+        catalog: URI of the Iceberg REST catalog, or the settings of the catalog.
+        parent: If specified, only the namespaces nested in this one are listed.
+            Otherwise, the top-level namespaces are listed.
+        s3_connection_settings: S3 credentials when using S3 as a backend.
+        warehouse: Optional, path to the Iceberg storage warehouse. Required for
+            the Glue catalog.
+
+    Returns:
+        The list of namespaces, each given as the list of its levels.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> namespaces = pw.io.iceberg.list_namespaces(  # doctest: +SKIP
+    ...     pw.io.iceberg.RestCatalog("http://localhost:8181/", token="secret-token")
+    ... )
+    """
+    _check_entitlements("iceberg")
+    catalog_uri, catalog_settings = _catalog_engine_settings(catalog)
+    data_storage = api.DataStorage(
+        storage_type="iceberg",
+        path=catalog_uri,
+        database=warehouse,
+        namespace=parent,
+        aws_s3_settings=_prepare_s3_connection_engine_settings(s3_connection_settings),
+        iceberg_catalog_settings=catalog_settings,
+    )
+    return data_storage.iceberg_list_namespaces()
+
+
+@check_arg_types
+@trace_user_frame
+def list_tables(
+    catalog: str | RestCatalog | GlueCatalog,
+    namespace: list[str],
+    *,
+    s3_connection_settings: (
+        AwsS3Settings | MinIOSettings | WasabiS3Settings | DigitalOceanS3Settings | None
+    ) = None,
+    warehouse: str | None = None,
+) -> list[str]:
+    """
+    Lists the names of the tables in a namespace of an Iceberg catalog.
+
+    Args:
+        catalog: URI of the Iceberg REST catalog, or the settings of the catalog.
+        namespace: The namespace to list the tables of.
+        s3_connection_settings: S3 credentials when using S3 as a backend.
+        warehouse: Optional, path to the Iceberg storage warehouse. Required for
+            the Glue catalog.
+
+    Returns:
+        The names of the tables in the namespace.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> tables = pw.io.iceberg.list_tables(  # doctest: +SKIP
+    ...     pw.io.iceberg.GlueCatalog(region="eu-central-1"),
+    ...     ["app"],
+    ...     warehouse="s3://warehouse/",
+    ... )
+    """
+    _check_entitlements("iceberg")
+    catalog_uri, catalog_settings = _catalog_engine_settings(catalog)
+    data_storage = api.DataStorage(
+        storage_type="iceberg",
+        path=catalog_uri,
+        database=warehouse,
+        namespace=namespace,
+        aws_s3_settings=_prepare_s3_connection_engine_settings(s3_connection_settings),
+        iceberg_catalog_settings=catalog_settings,
+    )
+    return data_storage.iceberg_list_tables()
+
+
 @check_arg_types
 @trace_user_frame
 def read(
-    catalog_uri: str,
+    catalog_uri: str | RestCatalog | GlueCatalog,
     namespace: list[str],
     table_name: str,
     schema: type[Schema],
@@ -49,7 +250,9 @@ def read(
     function.
 
     Args:
-        catalog_uri: URI of the Iceberg REST catalog.
+        catalog_uri: URI of the Iceberg REST catalog. To use a catalog requiring
+            authentication or an AWS Glue catalog, pass ``RestCatalog`` or
+            ``GlueCatalog`` settings instead.
         namespace: The name of the namespace containing the table read.
         table_name: The name of the table to be read.
         schema: Schema of the resulting table.
@@ -60,7 +263,8 @@ def read(
             the ``"static"`` mode will only consider the available data and ingest all
             of it in one commit. The default value is ``"streaming"``.
         s3_connection_settings: S3 credentials when using S3 as a backend.
-        warehouse: Optional, path to the Iceberg storage warehouse.
+        warehouse: Optional, path to the Iceberg storage warehouse. Required for
+            the Glue catalog.
         autocommit_duration_ms: The maximum time between two commits. Every
             ``autocommit_duration_ms`` milliseconds, the updates received by the connector are
             committed and pushed into Pathway's computation graph.
@@ -114,6 +318,7 @@ def read(
     _check_entitlements("iceberg")
     schema, api_schema = read_schema(schema)
     engine_s3_settings = _prepare_s3_connection_engine_settings(s3_connection_settings)
+    catalog_uri, catalog_settings = _catalog_engine_settings(catalog_uri)
 
     data_storage = api.DataStorage(
         storage_type="iceberg",
@@ -123,6 +328,7 @@ def read(
         namespace=namespace,
         mode=internal_connector_mode(mode),
         aws_s3_settings=engine_s3_settings,
+        iceberg_catalog_settings=catalog_settings,
     )
     data_format = api.DataFormat(
         format_type="transparent",
@@ -151,7 +357,7 @@ def read(
 @trace_user_frame
 def write(
     table: Table,
-    catalog_uri: str,
+    catalog_uri: str | RestCatalog | GlueCatalog,
     namespace: list[str],
     table_name: str,
     *,
@@ -176,13 +382,16 @@ def write(
 
     Args:
         table: Table to be written.
-        catalog_uri: URI of the Iceberg REST catalog.
+        catalog_uri: URI of the Iceberg REST catalog. To use a catalog requiring
+            authentication or an AWS Glue catalog, pass ``RestCatalog`` or
+            ``GlueCatalog`` settings instead.
         namespace: The name of the namespace containing the target table. If the namespace
             doesn't exist, it will be created by the connector.
         table_name: The name of the table to be written. If a table with such a name
             doesn't exist, it will be created by the connector.
         s3_connection_settings: S3 credentials when using S3 as a backend.
-        warehouse: Optional, path to the Iceberg storage warehouse.
+        warehouse: Optional, path to the Iceberg storage warehouse. Required for
+            the Glue catalog.
         min_commit_frequency: Specifies the minimum time interval between two data
             commits in storage, measured in milliseconds. If set to ``None``, finalized
             minibatches will be committed as soon as possible. Keep in mind that each
@@ -237,6 +446,7 @@ def write(
     """
     _check_entitlements("iceberg")
     engine_s3_settings = _prepare_s3_connection_engine_settings(s3_connection_settings)
+    catalog_uri, catalog_settings = _catalog_engine_settings(catalog_uri)
     data_storage = api.DataStorage(
         storage_type="iceberg",
         path=catalog_uri,
@@ -245,6 +455,7 @@ def write(
         table_name=table_name,
         namespace=namespace,
         aws_s3_settings=engine_s3_settings,
+        iceberg_catalog_settings=catalog_settings,
     )
 
     data_format = api.DataFormat(
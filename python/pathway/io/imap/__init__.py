@@ -0,0 +1,251 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import datetime
+import email
+import email.policy
+import email.utils
+from email.message import EmailMessage
+from typing import Any, Literal
+
+import pathway as pw
+import pathway.internals.dtype as dt
+from pathway.internals import api, datasource
+from pathway.internals.runtime_type_check import check_arg_types
+from pathway.internals.table import Table
+from pathway.internals.table_io import table_from_datasource
+from pathway.internals.trace import trace_user_frame
+from pathway.io._utils import (
+    _get_unique_name,
+    construct_schema_and_data_format,
+    internal_connector_mode,
+)
+
+
+class EmailSchema(pw.Schema):
+    uid: int
+    message_id: str | None
+    subject: str
+    sender: str
+    recipients: list[str]
+    date: pw.DateTimeUtc | None
+    body: str
+    attachments: pw.Json
+
+
+class EmailWithAttachmentsSchema(EmailSchema):
+    attachment_contents: list[bytes]
+
+
+def _message_uid(path: str) -> int:
+    # The messages are stored as `imap://<username>@<host>/<mailbox>/<uid>`
+    return int(path.rsplit("/", 1)[1])
+
+
+def _message_body(message: EmailMessage) -> str:
+    part = message.get_body(preferencelist=("plain", "html"))
+    if part is None:
+        return ""
+    try:
+        return part.get_content()
+    except (LookupError, UnicodeDecodeError):
+        # Unknown or wrong charset declared in the message
+        payload = part.get_payload(decode=True) or b""
+        return payload.decode(errors="replace")
+
+
+def _parse_message(data: bytes, path: str, with_attachments: bool) -> dict[str, Any]:
+    message = email.message_from_bytes(data, policy=email.policy.default)
+    assert isinstance(message, EmailMessage)
+
+    recipients = [
+        address
+        for _, address in email.utils.getaddresses(
+            message.get_all("to", []) + message.get_all("cc", [])
+        )
+        if address
+    ]
+    date = None
+    if message["date"] is not None:
+        try:
+            date = email.utils.parsedate_to_datetime(str(message["date"]))
+        except (TypeError, ValueError):
+            pass
+        else:
+            if date.tzinfo is None:
+                date = date.replace(tzinfo=datetime.timezone.utc)
+
+    attachments = []
+    attachment_contents = []
+    for part in message.iter_attachments():
+        contents = part.get_payload(decode=True) or b""
+        attachments.append(
+            {
+                "filename": part.get_filename(),
+                "content_type": part.get_content_type(),
+                "size": len(contents),
+            }
+        )
+        attachment_contents.append(contents)
+
+    row = {
+        "uid": _message_uid(path),
+        "message_id": (
+            str(message["message-id"]) if message["message-id"] is not None else None
+        ),
+        "subject": str(message["subject"] or ""),
+        "sender": str(message["from"] or ""),
+        "recipients": recipients,
+        "date": date,
+        "body": _message_body(message),
+        "attachments": attachments,
+    }
+    if with_attachments:
+        row["attachment_contents"] = attachment_contents
+    return row
+
+
+@check_arg_types
+@trace_user_frame
+def read(
+    host: str,
+    username: str,
+    password: str,
+    *,
+    mailbox: str = "INBOX",
+    port: int = 993,
+    mode: Literal["streaming", "static"] = "streaming",
+    refresh_interval: int = 30,
+    with_attachments: bool = False,
+    autocommit_duration_ms: int | None = 1500,
+    name: str | None = None,
+    max_backlog_size: int | None = None,
+    debug_data: Any = None,
+    **kwargs,
+) -> Table:
+    """Reads the messages of an IMAP mailbox, one row per message.
+
+    The connector connects to the server over TLS, reads all the messages already present
+    in the mailbox and then, in the ``"streaming"`` mode, checks for the new ones every
+    ``refresh_interval`` seconds. The messages are read without being marked as seen.
+    If a message is expunged or moved to another mailbox, its row is removed from the
+    table.
+
+    The raw messages are kept in the connector's cached object storage, so when the
+    persistence is enabled, the messages read before a restart, including their
+    attachments, don't need to be downloaded again.
+
+    The resulting table has the following columns:
+
+    - ``uid``: the UID of the message within the mailbox;
+    - ``message_id``: the value of the ``Message-ID`` header, if present;
+    - ``subject``: the subject of the message;
+    - ``sender``: the value of the ``From`` header;
+    - ``recipients``: the addresses from the ``To`` and ``Cc`` headers;
+    - ``date``: the value of the ``Date`` header, if present and valid;
+    - ``body``: the plain text body of the message, or its HTML body if there is
+      no plain text one;
+    - ``attachments``: a JSON list with the ``filename``, ``content_type`` and
+      ``size`` of each of the attachments;
+    - ``attachment_contents``: the contents of the attachments, in the same order as
+      in the ``attachments`` column. It is present only if ``with_attachments`` is set.
+
+    Args:
+        host: The host name of the IMAP server.
+        username: The name of the user to log in as.
+        password: The password of the user. For the mail providers supporting them, it
+            is advised to use an app password.
+        mailbox: The mailbox to read the messages from.
+        port: The port of the IMAP server. Only the connections over TLS are supported.
+        mode: Denotes how the engine polls the new data from the source. Currently
+            ``"streaming"`` and ``"static"`` are supported. If set to ``"streaming"``,
+            the mailbox is checked for the new messages every ``refresh_interval``
+            seconds. ``"static"`` mode reads the messages that are present in the
+            mailbox when the computation starts, and finishes.
+        refresh_interval: Time in seconds between the checks for the new messages.
+        with_attachments: Whether to add the ``attachment_contents`` column with the
+            contents of the attachments to the table.
+        autocommit_duration_ms: The maximum time between two commits. Every
+            ``autocommit_duration_ms`` milliseconds, the updates received by the connector
+            are committed and pushed into Pathway's computation graph.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards. Additionally, if persistence is enabled, it
+            will be used as the name for the snapshot that stores the connector's
+            progress.
+        max_backlog_size: Limit on the number of entries read from the input source and
+            kept in processing at any moment. Reading pauses when the limit is reached
+            and resumes as processing of some entries completes.
+        debug_data: Static data replacing original one when debug mode is active.
+
+    Returns:
+        Table: The table read.
+
+    Example:
+
+    Suppose the invoices are sent to a dedicated mailbox and their PDF attachments need
+    to be indexed. The messages can be read along with the attachments as follows:
+
+    >>> import pathway as pw
+    >>> messages = pw.io.imap.read(
+    ...     "imap.gmail.com",
+    ...     "invoices@example.com",
+    ...     "app-password",
+    ...     with_attachments=True,
+    ... )
+    """
+
+    data_storage = api.DataStorage(
+        storage_type="imap",
+        mode=internal_connector_mode(mode),
+        read_method=api.ReadMethod.FULL,
+        imap_settings=api.ImapSettings(
+            host,
+            username,
+            password,
+            port=port,
+            mailbox=mailbox,
+            refresh_interval=datetime.timedelta(seconds=refresh_interval),
+        ),
+    )
+    schema, data_format = construct_schema_and_data_format(
+        "binary",
+        with_metadata=True,
+    )
+    data_source_options = datasource.DataSourceOptions(
+        commit_duration_ms=autocommit_duration_ms,
+        unique_name=_get_unique_name(name, kwargs),
+        max_backlog_size=max_backlog_size,
+    )
+    raw_messages = table_from_datasource(
+        datasource.GenericDataSource(
+            datastorage=data_storage,
+            dataformat=data_format,
+            data_source_options=data_source_options,
+            schema=schema,
+            datasource_name="imap",
+        ),
+        debug_datasource=datasource.debug_datasource(debug_data),
+    )
+
+    output_schema = EmailWithAttachmentsSchema if with_attachments else EmailSchema
+    columns = output_schema.columns()
+
+    @pw.udf(
+        return_type=dt.Tuple(*(column.dtype for column in columns.values())),
+        deterministic=True,
+    )
+    def parse_message(data: bytes, metadata: pw.Json) -> tuple:
+        row = _parse_message(data, metadata["path"].as_str(), with_attachments)
+        row["attachments"] = pw.Json(row["attachments"])
+        return tuple(row[name] for name in columns)
+
+    parsed = raw_messages.select(
+        message=parse_message(pw.this.data, pw.this._metadata)
+    )
+    return parsed.select(
+        **{name: pw.this.message[index] for index, name in enumerate(columns)}
+    )
+
+
+__all__ = ["read"]
@@ -6,11 +6,12 @@ from os import PathLike
 from typing import Iterable, Literal
 
 import pathway as pw
-from pathway.internals.expression import ColumnReference
+from pathway.internals.expression import ColumnExpression, ColumnReference
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.schema import Schema
 from pathway.internals.table import Table
 from pathway.internals.trace import trace_user_frame
+from pathway.io._utils import ColumnMasking
 
 
 @check_arg_types
@@ -180,6 +181,9 @@ def write(
     *,
     name: str | None = None,
     sort_by: Iterable[ColumnReference] | None = None,
+    column_masking: dict[ColumnReference | str, ColumnMasking] | None = None,
+    row_filter: ColumnExpression | None = None,
+    columns: Iterable[ColumnReference] | None = None,
 ) -> None:
     """Writes ``table``'s stream of updates to a file in jsonlines format.
 
@@ -191,6 +195,16 @@ def write(
         sort_by: If specified, the output will be sorted in ascending order based on the
             values of the given columns within each minibatch. When multiple columns are provided,
             the corresponding value tuples will be compared lexicographically.
+        column_masking: The columns to be masked before writing, mapped to the
+            :py:class:`~pathway.io.ColumnMasking` to be applied to them.
+        row_filter: If specified, only the rows for which this condition holds are
+            written. The condition is evaluated by the connector, without creating a
+            filtered table. It can consist of the comparisons of the columns of
+            ``table`` with the constants, the checks for ``None`` and the boolean
+            columns, combined with ``&``, ``|`` and ``~``.
+        columns: If specified, only these columns of ``table`` are written, in the
+            given order. The columns are selected by the connector, without creating
+            a projected table.
 
     Returns:
         None
@@ -235,4 +249,7 @@ def write(
         format="json",
         name=name,
         sort_by=sort_by,
+        column_masking=column_masking,
+        row_filter=row_filter,
+        columns=columns,
     )
@@ -2,10 +2,12 @@
 
 from __future__ import annotations
 
+import os
 import uuid
 import warnings
 from typing import Iterable, Literal
 
+from pathway import secrets
 from pathway.internals import api, datasink, datasource
 from pathway.internals._io_helpers import SchemaRegistryHeader, SchemaRegistrySettings
 from pathway.internals.expression import ColumnReference
@@ -15,6 +17,8 @@ from pathway.internals.table import Table
 from pathway.internals.table_io import table_from_datasource
 from pathway.internals.trace import trace_user_frame
 from pathway.io._utils import (
+    ConnectorRetryPolicy,
+    LengthPrefix,
     MessageQueueOutputFormat,
     _get_unique_name,
     check_deprecated_kwargs,
@@ -32,8 +36,11 @@ def read(
     *,
     schema: type[Schema] | None = None,
     mode: Literal["streaming", "static"] = "streaming",
-    format: Literal["plaintext", "raw", "json"] = "raw",
+    format: Literal["plaintext", "raw", "json", "avro", "protobuf", "msgpack"] = "raw",
     schema_registry_settings: SchemaRegistrySettings | None = None,
+    avro_schema: str | None = None,
+    protobuf_descriptor_set: str | os.PathLike | None = None,
+    protobuf_message: str | None = None,
     debug_data=None,
     autocommit_duration_ms: int | None = 1500,
     json_field_paths: dict[str, str] | None = None,
@@ -43,12 +50,15 @@ def read(
     parallel_readers: int | None = None,
     name: str | None = None,
     max_backlog_size: int | None = None,
+    length_prefix: LengthPrefix | None = None,
+    lag_throttling: bool = False,
     _stacklevel: int = 1,
     **kwargs,
 ) -> Table:
     """Generalized method to read the data from the given topic in Kafka.
 
-    There are three formats currently supported: ``"plaintext"``, ``"raw"``, and ``"json"``.
+    There are six formats currently supported: ``"plaintext"``, ``"raw"``, ``"json"``,
+    ``"avro"``, ``"protobuf"``, and ``"msgpack"``.
     If the ``"raw"`` format is chosen, the key and the payload are read from the topic as raw
     bytes and used in the table "as is". If you choose the ``"plaintext"`` option, however,
     they are parsed from the UTF-8 into the plaintext entries. In both cases, the
@@ -59,9 +69,37 @@ def read(
     schema defined by the ``schema`` parameter. The values of these columns are
     taken from the respective parsed JSON fields.
 
+    The ``"msgpack"`` format works in the same way as ``"json"``, except that the
+    payload is a MessagePack map. The values are read into the columns directly, so
+    there is no intermediate JSON encoding. The binary values can be read into the
+    ``bytes`` columns and the values of the timestamp extension type into the
+    ``DateTimeUtc`` columns.
+
+    The ``"avro"`` format works in the same way, except that the payload is an Avro
+    record, either encoded with the schema given in ``avro_schema`` or, if
+    ``schema_registry_settings`` are given, in the wire format of the Confluent Schema
+    Registry, in which each message starts with the id of the schema it was written
+    with. The schemas are fetched from the registry once and cached. If both are given,
+    the records are read as if they had the ``avro_schema`` schema, following the Avro
+    schema resolution rules, so that the fields added to the schema over time get their
+    default values in the older records.
+
+    If ``"protobuf"`` is chosen, the payload is a Protobuf message of the type named in
+    ``protobuf_message``, which is described by the compiled descriptor set in the
+    ``protobuf_descriptor_set`` file, as produced by ``protoc --include_imports
+    --descriptor_set_out=<file>``. The fields of the message are read into the columns
+    of the same names. The nested messages, the maps and the repeated fields can be read
+    into the ``pw.Json`` columns, while the ``google.protobuf.Timestamp`` and
+    ``google.protobuf.Duration`` fields can be read into the ``DateTimeUtc`` and
+    ``Duration`` columns. The values of the enums are read as their names. The fields
+    with explicit presence, such as the nested messages or the ``optional`` fields, that
+    are missing in a message get the default values of their columns, or ``None`` if the
+    defaults aren't set.
+
     Args:
         rdkafka_settings: Connection settings in the format of `librdkafka
             <https://github.com/edenhill/librdkafka/blob/master/CONFIGURATION.md>`_.
+            Values can be :py:class:`~pathway.secrets.Secret` objects.
         topic: Name of topic in Kafka from which the data should be read.
         schema: Schema of the resulting table.
         mode: Specifies how the engine retrieves data from the topic. The default value is
@@ -69,9 +107,16 @@ def read(
             process them as they arrive, and send them into the engine. Alternatively,
             if set to ``"static"``, the engine will only read and process the data that
             is already available at the time of execution.
-        format: format of the input data, ``"raw"``, ``"plaintext"``, or ``"json"``.
+        format: format of the input data, ``"raw"``, ``"plaintext"``, ``"json"``,
+            ``"avro"``, ``"protobuf"``, or ``"msgpack"``.
         schema_registry_settings: settings for connecting to the Confluent Schema Registry,
             if this type of registry is used.
+        avro_schema: the Avro schema of the records, in its JSON form, if the format is
+            ``"avro"``. It can be omitted if ``schema_registry_settings`` are given.
+        protobuf_descriptor_set: the path to the compiled ``FileDescriptorSet`` with the
+            definition of the messages, required if the format is ``"protobuf"``.
+        protobuf_message: the fully qualified name of the type of the messages, such as
+            ``"package.Message"``, required if the format is ``"protobuf"``.
         debug_data: Static data replacing original one when debug mode is active.
         autocommit_duration_ms:the maximum time between two commits. Every
             autocommit_duration_ms milliseconds, the updates received by the connector are
@@ -104,6 +149,20 @@ def read(
             in processing at any moment. Reading pauses when the limit is reached and resumes
             as processing of some entries completes. Useful with large sources that
             emit an initial burst of data to avoid memory spikes.
+        length_prefix: If set, each message is treated as a sequence of binary records,
+            each preceded by its length, and every record becomes a separate row. The
+            length is encoded as a varint, as in the length-delimited protobuf streams,
+            if it's ``"varint"``, or as a 4-byte big-endian or little-endian integer if
+            it's ``"fixed32_be"`` or ``"fixed32_le"`` respectively. The records are then
+            parsed according to the ``format``, so with the ``"raw"`` format they can be
+            decoded with a UDF, e.g. from protobuf or CBOR. The records inherit the key
+            of their message, so if the messages have keys and consist of several records,
+            ``autogenerate_key`` should be set.
+        lag_throttling: If ``True``, the lags of the partitions are checked every few
+            seconds in the ``"streaming"`` mode, and once some partition is at least a
+            thousand messages behind, the partitions lagging less than half as much are
+            paused until the lags even out. This way the most lagging partitions are read
+            first and the recovery after a downtime progresses uniformly over the topic.
 
     Returns:
         Table: The table read.
@@ -223,11 +282,14 @@ def read(
 
     data_storage = api.DataStorage(
         storage_type="kafka",
-        rdkafka_settings=rdkafka_settings,
+        rdkafka_settings=secrets.resolve(rdkafka_settings),
         topic=topic,
         parallel_readers=parallel_readers,
         start_from_timestamp_ms=start_from_timestamp_ms,
         mode=internal_connector_mode(mode),
+        kafka_lag_throttling_settings=(
+            api.KafkaLagThrottlingSettings() if lag_throttling else None
+        ),
     )
     schema, data_format = construct_schema_and_data_format(
         "binary" if format == "raw" else format,
@@ -236,6 +298,10 @@ def read(
         schema=schema,
         json_field_paths=json_field_paths,
         schema_registry_settings=schema_registry_settings,
+        length_prefix=length_prefix,
+        avro_schema=avro_schema,
+        protobuf_descriptor_set=protobuf_descriptor_set,
+        protobuf_message=protobuf_message,
         _stacklevel=5,
     )
     data_source_options = datasource.DataSourceOptions(
@@ -362,15 +428,32 @@ def write(
     rdkafka_settings: dict,
     topic_name: str | ColumnReference,
     *,
-    format: Literal["raw", "plaintext", "json", "dsv"] = "json",
+    format: Literal[
+        "raw", "plaintext", "json", "dsv", "debezium", "avro", "msgpack"
+    ] = "json",
     schema_registry_settings: SchemaRegistrySettings | None = None,
     subject: str | None = None,
+    avro_schema: str | None = None,
     delimiter: str = ",",
     key: ColumnReference | None = None,
     value: ColumnReference | None = None,
     headers: Iterable[ColumnReference] | None = None,
+    partition: ColumnReference | None = None,
+    partitioner: (
+        Literal[
+            "random",
+            "consistent",
+            "consistent_random",
+            "murmur2",
+            "murmur2_random",
+            "fnv1a",
+            "fnv1a_random",
+        ]
+        | None
+    ) = None,
     name: str | None = None,
     sort_by: Iterable[ColumnReference] | None = None,
+    retry_policy: ConnectorRetryPolicy | None = None,
 ) -> None:
     """Write a table to a given topic on a Kafka instance.
 
@@ -380,9 +463,35 @@ def write(
     and ``pathway_diff`` that is either 1 or -1. Both header values are provided as UTF-8
     encoded strings.
 
-    There are several serialization formats supported: 'json', 'dsv', 'plaintext' and 'raw'.
-    The format defines how the message is formed. In case of JSON and DSV (delimiter
-    separated values), the message is formed in accordance with the respective data format.
+    Each message also has a ``pathway_idempotency_key`` header. When the computation is
+    restarted from a persisted state, the messages produced after the last checkpoint are
+    produced again with the same keys, so the consumers can drop the duplicates.
+
+    There are several serialization formats supported: 'json', 'dsv', 'debezium',
+    'avro', 'msgpack', 'plaintext' and 'raw'. The format defines how the message is
+    formed. In case of JSON and DSV (delimiter separated values), the message is formed
+    in accordance with the respective data format.
+
+    The 'msgpack' format produces MessagePack maps with the same fields as the JSON
+    objects of the 'json' format, including ``time`` and ``diff``. The binary values
+    are written as MessagePack binaries and the ``DateTimeUtc`` values as timestamps
+    of the MessagePack timestamp extension type.
+
+    The 'debezium' format produces Debezium change events, so that Pathway can be
+    consumed like any other Debezium source. A row insertion is sent as a create event
+    (``"op": "c"``) with the row in the ``after`` field and a deletion as a delete event
+    (``"op": "d"``) with the row in the ``before`` field, so an update of a row results
+    in two events. The event is wrapped in the Kafka Connect JSON envelope, i.e.
+    ``{"schema": null, "payload": {...}}``. The ``source`` field of the event contains
+    the logical ``time`` of the change.
+
+    The 'avro' format produces Avro records of the record schema given in
+    ``avro_schema``. The columns are written into the fields of the same names, and
+    the ``time`` and ``diff`` fields, if present in the schema, get the logical time
+    and the diff of the change. The other fields get their default values. If
+    ``schema_registry_settings`` are given, the schema is registered under the
+    ``subject`` and the records are written in the wire format of the Confluent Schema
+    Registry.
 
     If the selected format is either 'plaintext' or 'raw', you also need to specify, which
     columns of the table correspond to the key and the value of the produced Kafka
@@ -392,16 +501,24 @@ def write(
     serializing the extracted fields into UTF-8 strings and passing them as additional
     Kafka headers.
 
+    The partition of each message is chosen by the partitioner of the producer based on
+    the message key. The partitioning strategy can be changed with the ``partitioner``
+    parameter, and the partition can also be taken directly from a column of the table
+    with the ``partition`` parameter. Together with the ``key`` parameter, it allows
+    producing topics that can be compacted by the key and co-partitioned with other
+    topics.
+
     Args:
         table: the table to output.
         rdkafka_settings: Connection settings in the format of
             `librdkafka <https://github.com/edenhill/librdkafka/blob/master/CONFIGURATION.md>`_.
+            Values can be :py:class:`~pathway.secrets.Secret` objects.
         topic_name: The Kafka topic where data will be written. This can be a specific topic name
             or a reference to a column whose values will be used as the topic for each message.
             If using a column reference, the column must contain string values.
         format: format in which the data is put into Kafka. Currently "json",
-            "plaintext", "raw" and "dsv" are supported. If the "raw" format is selected,
-            ``table`` must either contain exactly one binary column that will be dumped as it is into the
+            "plaintext", "raw", "dsv", "debezium", "avro" and "msgpack" are supported. If
+            the "raw" format is selected, ``table`` must either contain exactly one binary column that will be dumped as it is into the
             Kafka message, or the reference to the target binary column must be specified explicitly
             in the ``value`` parameter. Similarly, if "plaintext" is chosen, the table should consist
             of a single column of the string type, or the reference to the target string column
@@ -410,6 +527,8 @@ def write(
             if this type of registry is used.
         subject: the subject name for the schema in the Confluent Schema Registry, if the
             registry is used.
+        avro_schema: the Avro schema of the produced records, in its JSON form. It's
+            required for the "avro" format.
         delimiter: field delimiter to be used in case of delimiter-separated values
             format 'dsv'.
         key: reference to the column that should be used as a key in the produced message.
@@ -423,11 +542,26 @@ def write(
             headers. These headers are named in the same way as fields that are forwarded and correspond
             to the string representations of the respective values encoded in UTF-8. If a binary
             column is requested, it will be produced "as is" in the respective header.
+        partition: reference to the integer column containing the number of the
+            partition the message is sent to. If the value is ``None``, the partition
+            is chosen by the partitioner.
+        partitioner: the strategy of choosing the partition based on the message key,
+            as defined by the ``partitioner`` setting of
+            `librdkafka <https://github.com/edenhill/librdkafka/blob/master/CONFIGURATION.md>`_.
+            The messages with the same key are always sent to the same partition,
+            except for the ``"random"`` strategy. If not specified, the partitioner
+            from ``rdkafka_settings`` is used, or ``"consistent_random"`` if it's not
+            set there either. Note that ``"murmur2_random"`` corresponds to the default
+            partitioner of the Java client, so it must be used if the topic is to be
+            co-partitioned with the topics produced by the Java client.
         name: A unique name for the connector. If provided, this name will be used in
             logs and monitoring dashboards.
         sort_by: If specified, the output will be sorted in ascending order based on the
             values of the given columns within each minibatch. When multiple columns are provided,
             the corresponding value tuples will be compared lexicographically.
+        retry_policy: Retry settings for the messages that failed to be produced. By
+            default, a message is retried five times. The timeouts of the producer can
+            be set in ``rdkafka_settings``, e.g. with ``message.timeout.ms``.
 
     Returns:
         None
@@ -467,6 +601,17 @@ def write(
 
     All the updates of table ``t`` will be sent to the Kafka instance.
 
+    If the topic is consumed by the tools that expect Debezium change events, for
+    example a sink connector replicating the table into a database, the same table can
+    be sent as follows:
+
+    >>> pw.io.kafka.write(
+    ...    t,
+    ...    rdkafka_settings,
+    ...    "animals-cdc",
+    ...    format="debezium",
+    ... )
+
     Another thing to be demonstated is the usage of 'raw' format in the output. Please
     note that the same rules will be applicable for the 'plaintext' with the only difference
     being the requirement for the columns to have the ``string`` type.
@@ -525,7 +670,37 @@ def write(
     ...    value=t2.foo,
     ...    headers=[t2.baz],
     ... )
+
+    Finally, if the messages with the same key need to land in the same partitions as
+    the messages produced by a Java application, you can use the same partitioner:
+
+    >>> pw.io.kafka.write(
+    ...    t2,
+    ...    rdkafka_settings,
+    ...    "test",
+    ...    format="raw",
+    ...    key=t2.bar,
+    ...    value=t2.foo,
+    ...    partitioner="murmur2_random",
+    ... )
+
+    Otherwise, if the partition is computed in the pipeline, you can pass it in a
+    column:
+
+    >>> t3 = t2.select(t2.foo, t2.bar, partition=t2.baz % 4)
+    >>> pw.io.kafka.write(
+    ...    t3,
+    ...    rdkafka_settings,
+    ...    "test",
+    ...    format="raw",
+    ...    key=t3.bar,
+    ...    value=t3.foo,
+    ...    partition=t3.partition,
+    ... )
     """
+    if partitioner is not None:
+        rdkafka_settings = {**rdkafka_settings, "partitioner": partitioner}
+
     output_format = MessageQueueOutputFormat.construct(
         table,
         format=format,
@@ -534,18 +709,22 @@ def write(
         value=value,
         headers=headers,
         topic_name=topic_name if isinstance(topic_name, ColumnReference) else None,
+        partition=partition,
         schema_registry_settings=schema_registry_settings,
         subject=subject,
+        avro_schema=avro_schema,
     )
     table = output_format.table
 
     data_storage = api.DataStorage(
         storage_type="kafka",
-        rdkafka_settings=rdkafka_settings,
+        rdkafka_settings=secrets.resolve(rdkafka_settings),
         topic=topic_name if isinstance(topic_name, str) else None,
         topic_name_index=output_format.topic_name_index,
         key_field_index=output_format.key_field_index,
         header_fields=[item for item in output_format.header_fields.items()],
+        retry_policy=retry_policy.api_policy if retry_policy else None,
+        partition_field_index=output_format.partition_field_index,
     )
 
     table.to(
@@ -59,7 +59,9 @@ class MinIOSettings:
 def read(
     path: str,
     minio_settings: MinIOSettings,
-    format: Literal["csv", "json", "plaintext", "plaintext_by_object", "binary"],
+    format: Literal[
+        "csv", "json", "plaintext", "plaintext_by_object", "binary", "parquet"
+    ],
     *,
     schema: type[Schema] | None = None,
     mode: Literal["streaming", "static"] = "streaming",
@@ -84,12 +86,14 @@ def read(
         path: Path to an object or to a folder of objects in MinIO S3 bucket.
         minio_settings: Connection parameters for the MinIO account and the bucket.
         format: Format of data to be read. Currently ``csv``, ``json``, ``plaintext``,
-            ``plaintext_by_object`` and ``binary`` formats are supported. The difference
-            between ``plaintext`` and ``plaintext_by_object`` is how the input is
-            tokenized: if the ``plaintext`` option is chosen, it's split by the newlines.
-            Otherwise, the files are split in full and one row will correspond to one
-            file. In case the ``binary`` format is specified, the data is read as raw
-            bytes without UTF-8 parsing.
+            ``plaintext_by_object``, ``binary`` and ``parquet`` formats are supported.
+            The difference between ``plaintext`` and ``plaintext_by_object`` is how the
+            input is tokenized: if the ``plaintext`` option is chosen, it's split by the
+            newlines. Otherwise, the files are split in full and one row will correspond
+            to one file. In case the ``binary`` format is specified, the data is read as
+            raw bytes without UTF-8 parsing. In case of ``parquet``, each object is a
+            Parquet file whose columns are matched with the columns of the schema by
+            name, and each row of the file becomes a row of the table.
         schema: Schema of the resulting table. Not required for ``plaintext_by_object``
             and ``binary`` formats: if they are chosen, the contents of the read objects
             are stored in the column ``data``.
@@ -4,6 +4,7 @@ from __future__ import annotations
 
 from typing import Iterable
 
+from pathway import secrets
 from pathway.internals import api, datasink
 from pathway.internals._io_helpers import _format_output_value_fields
 from pathway.internals.expression import ColumnReference
@@ -17,7 +18,7 @@ from pathway.internals.trace import trace_user_frame
 def write(
     table: Table,
     *,
-    connection_string: str,
+    connection_string: str | secrets.Secret,
     database: str,
     collection: str,
     max_batch_size: int | None = None,
@@ -43,7 +44,8 @@ def write(
         table: The table to output.
         connection_string: The connection string for the MongoDB database. See the \
 `MongoDB documentation <https://www.mongodb.com/docs/manual/reference/connection-string/>`_ \
-for the details.
+for the details. Can be a :py:class:`~pathway.secrets.Secret`, fetched when the \
+connector is created.
         database: The name of the database to update.
         collection: The name of the collection to write to.
         max_batch_size: The maximum number of entries to insert in one batch.
@@ -183,7 +185,7 @@ for the details.
     """
     data_storage = api.DataStorage(
         storage_type="mongodb",
-        connection_string=connection_string,
+        connection_string=secrets.resolve(connection_string),
         database=database,
         table_name=collection,
         max_batch_size=max_batch_size,
@@ -0,0 +1,254 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import datetime
+from typing import Any, Iterable
+
+from pathway.internals import api, datasink, datasource
+from pathway.internals._io_helpers import _format_output_value_fields
+from pathway.internals.expression import ColumnReference
+from pathway.internals.runtime_type_check import check_arg_types
+from pathway.internals.schema import Schema
+from pathway.internals.table import Table
+from pathway.internals.table_io import table_from_datasource
+from pathway.internals.trace import trace_user_frame
+from pathway.io._utils import read_schema
+
+
+@check_arg_types
+@trace_user_frame
+def read_incremental(
+    connection_string: str,
+    query: str,
+    schema: type[Schema],
+    *,
+    cursor_column: str,
+    start_from: Any,
+    poll_interval: datetime.timedelta = datetime.timedelta(seconds=1),
+    autocommit_duration_ms: int | None = 1500,
+    name: str | None = None,
+    max_backlog_size: int | None = None,
+    debug_data: Any = None,
+) -> Table:
+    """Reads the rows appended to a Microsoft SQL Server table by polling it with an
+    incremental query.
+
+    The query is run with the largest value of the ``cursor_column`` read so far, the
+    high-water mark, bound to its ``@P1`` parameter, and has to return only the rows
+    past it, ordered by the cursor column. Before the first row is read, the value of
+    ``start_from`` is bound instead. When the query returns no rows, it's run again
+    after ``poll_interval``. If persistence is enabled, the high-water mark is
+    persisted, so that a restarted program continues the polling from where it has
+    stopped.
+
+    Only the new rows are read, so the cursor column has to grow with each inserted
+    row, as an ``IDENTITY`` column or an insertion timestamp does. Updates and
+    deletions of the rows already read are not noticed. The cursor column has to be a
+    bool, an int, a float, a string or a datetime.
+
+    Args:
+        connection_string: The ADO.NET connection string, e.g.
+            ``"server=tcp:localhost,1433;user=sa;password=pass;database=db"``.
+        query: The incremental query, taking the high-water mark as its ``@P1``
+            parameter.
+        schema: Schema of the resulting table. It has to contain the cursor column. If
+            it defines no primary key, the keys are generated from the positions of the
+            rows in the polled stream.
+        cursor_column: The column with the values that grow with each inserted row.
+        start_from: The high-water mark used until the first row is read. The rows
+            with cursor values not greater than it are not read.
+        poll_interval: How long to wait before running the query again after it has
+            returned no rows.
+        autocommit_duration_ms: The maximum time between two commits. Every
+            autocommit_duration_ms milliseconds, the updates received by the connector are
+            committed and pushed into Pathway's computation graph.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards. Additionally, if persistence is enabled, it
+            will be used as the name for the snapshot that stores the connector's progress.
+        max_backlog_size: Limit on the number of entries read from the input source and
+            kept in processing at any moment. Reading pauses when the limit is reached and
+            resumes as processing of some entries completes.
+
+    Returns:
+        Table: The table read.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> class EventSchema(pw.Schema):
+    ...     id: int = pw.column_definition(primary_key=True)
+    ...     kind: str
+    >>> events = pw.io.mssql.read_incremental(  # doctest: +SKIP
+    ...     "server=tcp:localhost,1433;user=sa;password=pass;database=db",
+    ...     "SELECT id, kind FROM events WHERE id > @P1 ORDER BY id",
+    ...     schema=EventSchema,
+    ...     cursor_column="id",
+    ...     start_from=0,
+    ... )
+    """
+    schema, api_schema = read_schema(schema)
+    if cursor_column not in schema.column_names():
+        raise ValueError(
+            f"The cursor column {cursor_column!r} is not a part of the schema"
+        )
+
+    data_storage = api.DataStorage(
+        storage_type="mssql_polling",
+        connection_string=connection_string,
+        sql_polling_settings=api.SqlPollingSettings(
+            query,
+            cursor_column,
+            start_from,
+            poll_interval=poll_interval,
+        ),
+        mode=api.ConnectorMode.STREAMING,
+    )
+    data_format = api.DataFormat(
+        format_type="transparent",
+        **api_schema,
+    )
+
+    data_source_options = datasource.DataSourceOptions(
+        commit_duration_ms=autocommit_duration_ms,
+        unique_name=name,
+        max_backlog_size=max_backlog_size,
+    )
+    return table_from_datasource(
+        datasource.GenericDataSource(
+            datastorage=data_storage,
+            dataformat=data_format,
+            schema=schema,
+            data_source_options=data_source_options,
+            datasource_name="mssql.incremental",
+        ),
+        debug_datasource=datasource.debug_datasource(debug_data),
+    )
+
+
+def _write(
+    table: Table,
+    connection_string: str,
+    table_name: str,
+    primary_key: list[str] | None,
+    *,
+    max_batch_size: int | None,
+    name: str | None,
+    sort_by: Iterable[ColumnReference] | None,
+    datasink_name: str,
+) -> None:
+    data_storage = api.DataStorage(
+        storage_type="mssql",
+        connection_string=connection_string,
+        max_batch_size=max_batch_size,
+        table_name=table_name,
+    )
+    data_format = api.DataFormat(
+        format_type="identity",
+        key_field_names=primary_key,
+        value_fields=_format_output_value_fields(table),
+    )
+    table.to(
+        datasink.GenericDataSink(
+            data_storage,
+            data_format,
+            datasink_name=datasink_name,
+            unique_name=name,
+            sort_by=sort_by,
+        )
+    )
+
+
+@check_arg_types
+@trace_user_frame
+def write(
+    table: Table,
+    connection_string: str,
+    table_name: str,
+    *,
+    max_batch_size: int | None = None,
+    name: str | None = None,
+    sort_by: Iterable[ColumnReference] | None = None,
+) -> None:
+    """Writes ``table``'s stream of updates to a Microsoft SQL Server table.
+
+    The target table must have all the columns of ``table`` and the integer ``time``
+    and ``diff`` columns. Each batch of updates is loaded into a temporary table with
+    the bulk copy and then inserted into the target table with a single statement.
+
+    Args:
+        table: Table to be written.
+        connection_string: The ADO.NET connection string, e.g.
+            ``"server=tcp:localhost,1433;user=sa;password=pass;database=db"``.
+        table_name: Name of the target table.
+        max_batch_size: Maximum number of entries allowed to be committed within a
+            single batch.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+        sort_by: If specified, the output will be sorted in ascending order based on the
+            values of the given columns within each minibatch. When multiple columns are provided,
+            the corresponding value tuples will be compared lexicographically.
+
+    Returns:
+        None
+    """
+    _write(
+        table,
+        connection_string,
+        table_name,
+        None,
+        max_batch_size=max_batch_size,
+        name=name,
+        sort_by=sort_by,
+        datasink_name="mssql.sink",
+    )
+
+
+@check_arg_types
+@trace_user_frame
+def write_snapshot(
+    table: Table,
+    connection_string: str,
+    table_name: str,
+    primary_key: list[str],
+    *,
+    max_batch_size: int | None = None,
+    name: str | None = None,
+    sort_by: Iterable[ColumnReference] | None = None,
+) -> None:
+    """Maintains a snapshot of a table within a Microsoft SQL Server table.
+
+    The target table must have all the columns of ``table`` and the integer ``time``
+    and ``diff`` columns. Each batch of updates is loaded into a temporary table with
+    the bulk copy and then applied to the target table with a single ``MERGE``
+    statement: the rows are inserted, updated or deleted by their primary key.
+
+    Args:
+        table: Table to be written.
+        connection_string: The ADO.NET connection string.
+        table_name: Name of the target table.
+        primary_key: Names of the columns which serve as a primary key in the target
+            table.
+        max_batch_size: Maximum number of entries allowed to be committed within a
+            single batch.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+        sort_by: If specified, the output will be sorted in ascending order based on the
+            values of the given columns within each minibatch. When multiple columns are provided,
+            the corresponding value tuples will be compared lexicographically.
+
+    Returns:
+        None
+    """
+    if not primary_key:
+        raise ValueError("primary_key must not be empty")
+    _write(
+        table,
+        connection_string,
+        table_name,
+        primary_key,
+        max_batch_size=max_batch_size,
+        name=name,
+        sort_by=sort_by,
+        datasink_name="mssql.snapshot",
+    )
@@ -26,7 +26,7 @@ def read(
     topic: str,
     *,
     schema: type[Schema] | None = None,
-    format: Literal["plaintext", "raw", "json"] = "raw",
+    format: Literal["plaintext", "raw", "json", "msgpack"] = "raw",
     autocommit_duration_ms: int | None = 1500,
     json_field_paths: dict[str, str] | None = None,
     parallel_readers: int | None = None,
@@ -37,7 +37,8 @@ def read(
 ) -> Table:
     """Reads data from a specified NATS topic.
 
-    It supports three formats: ``"plaintext"``, ``"raw"``, and ``"json"``.
+    It supports four formats: ``"plaintext"``, ``"raw"``, ``"json"``, and
+    ``"msgpack"``.
 
     For the ``"raw"`` format, the payload is read as raw bytes and added directly to the
     table. In the ``"plaintext"`` format, the payload decoded from UTF-8 and stored as plain text.
@@ -48,12 +49,19 @@ def read(
     and creates table columns based on the schema provided in the ``schema`` parameter. The
     column values come from the corresponding JSON fields.
 
+    The ``"msgpack"`` format works in the same way as ``"json"``, except that the
+    payload is a MessagePack map. The values are read into the columns directly, so
+    there is no intermediate JSON encoding. The binary values can be read into the
+    ``bytes`` columns and the values of the timestamp extension type into the
+    ``DateTimeUtc`` columns.
+
     Args:
         uri: The URI of the NATS server.
         topic: The name of the NATS topic to read data from.
-        schema: The table schema, used only when the format is set to ``"json"``.
-        format: The input data format, which can be ``"raw"``, ``"plaintext"``, or
-            ``"json"``.
+        schema: The table schema, used only when the format is set to ``"json"`` or
+            ``"msgpack"``.
+        format: The input data format, which can be ``"raw"``, ``"plaintext"``,
+            ``"json"``, or ``"msgpack"``.
         autocommit_duration_ms: The time interval (in milliseconds) between commits.
             After this time, the updates received by the connector are committed and
             added to Pathway's computation graph.
@@ -160,7 +168,7 @@ def write(
     uri: str,
     topic: str | ColumnReference,
     *,
-    format: Literal["json", "dsv", "plaintext", "raw"] = "json",
+    format: Literal["json", "dsv", "msgpack", "plaintext", "raw"] = "json",
     delimiter: str = ",",
     value: ColumnReference | None = None,
     headers: Iterable[ColumnReference] | None = None,
@@ -175,9 +183,20 @@ def write(
     or ``-1``. Both header values are provided as UTF-8 encoded strings. If ``headers``
     parameter is used, additional headers can be added to the message.
 
-    There are several serialization formats supported: ``"json"``, ``"dsv"``, ``"plaintext"``
-    and ``"raw"``. The format defines how the message is formed. In case of JSON and DSV
-    (delimiter separated values), the message is formed in accordance with the respective data format.
+    Each message also has a ``pathway_idempotency_key`` header, also set as the
+    ``Nats-Msg-Id`` header. When the computation is restarted from a persisted state, the
+    messages produced after the last checkpoint are published again with the same ids,
+    so JetStream drops the duplicates within its deduplication window.
+
+    There are several serialization formats supported: ``"json"``, ``"dsv"``,
+    ``"msgpack"``, ``"plaintext"`` and ``"raw"``. The format defines how the message is
+    formed. In case of JSON and DSV (delimiter separated values), the message is formed
+    in accordance with the respective data format.
+
+    The ``"msgpack"`` format produces MessagePack maps with the same fields as the JSON
+    objects of the ``"json"`` format, including ``time`` and ``diff``. The binary values
+    are written as MessagePack binaries and the ``DateTimeUtc`` values as timestamps
+    of the MessagePack timestamp extension type.
 
     If the selected format is either ``"plaintext"`` or ``"raw"``, you also need to specify,
     which column of the table correspond to the payload of the produced NATS message. It can be
@@ -193,8 +212,8 @@ def write(
             or a reference to a column whose values will be used as the topic for each message.
             If using a column reference, the column must contain string values.
         format: format in which the data is put into NATS. Currently ``"json"``,
-            ``"plaintext"``, ``"raw"`` and ``"dsv"`` are supported. If the ``"raw"`` format is selected,
-            ``table`` must either contain exactly one binary column that will be dumped as it is
+            ``"msgpack"``, ``"plaintext"``, ``"raw"`` and ``"dsv"`` are supported. If the
+            ``"raw"`` format is selected, ``table`` must either contain exactly one binary column that will be dumped as it is
             into the message, or the reference to the target binary column must be specified explicitly
             in the ``value`` parameter. Similarly, if ``"plaintext"`` is chosen, the table should consist
             of a single column of the string type.
@@ -0,0 +1,156 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+from typing import Iterable
+
+from pathway import secrets
+from pathway.internals import api, datasink
+from pathway.internals._io_helpers import _format_output_value_fields
+from pathway.internals.expression import ColumnReference
+from pathway.internals.runtime_type_check import check_arg_types
+from pathway.internals.table import Table
+from pathway.internals.trace import trace_user_frame
+
+
+def _write(
+    table: Table,
+    oracle_settings: dict,
+    table_name: str,
+    primary_key: list[str] | None,
+    *,
+    max_batch_size: int | None,
+    name: str | None,
+    sort_by: Iterable[ColumnReference] | None,
+    datasink_name: str,
+) -> None:
+    settings = secrets.resolve(oracle_settings)
+    data_storage = api.DataStorage(
+        storage_type="oracle",
+        oracle_settings=api.OracleSettings(
+            str(settings["user"]),
+            str(settings["password"]),
+            str(settings["connect_string"]),
+        ),
+        max_batch_size=max_batch_size,
+        table_name=table_name,
+    )
+    data_format = api.DataFormat(
+        format_type="identity",
+        key_field_names=primary_key,
+        value_fields=_format_output_value_fields(table),
+    )
+    table.to(
+        datasink.GenericDataSink(
+            data_storage,
+            data_format,
+            datasink_name=datasink_name,
+            unique_name=name,
+            sort_by=sort_by,
+        )
+    )
+
+
+@check_arg_types
+@trace_user_frame
+def write(
+    table: Table,
+    oracle_settings: dict,
+    table_name: str,
+    *,
+    max_batch_size: int | None = None,
+    name: str | None = None,
+    sort_by: Iterable[ColumnReference] | None = None,
+) -> None:
+    """Writes ``table``'s stream of updates to an Oracle Database table.
+
+    The target table must have all the columns of ``table`` and the integer ``time``
+    and ``diff`` columns. Each batch of updates is inserted with a single statement
+    executed for all its rows. Boolean values are written as ``0`` and ``1``.
+
+    The connector requires the Oracle Client libraries, e.g. the
+    `Instant Client <https://www.oracle.com/database/technologies/instant-client.html>`_.
+
+    Args:
+        table: Table to be written.
+        oracle_settings: The connection settings with the ``user``, ``password`` and
+            ``connect_string`` keys, e.g. ``{"user": "scott", "password": "tiger",
+            "connect_string": "//localhost/FREEPDB1"}``. Values can be
+            :py:class:`~pathway.secrets.Secret` objects.
+        table_name: Name of the target table.
+        max_batch_size: Maximum number of entries allowed to be committed within a
+            single batch.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+        sort_by: If specified, the output will be sorted in ascending order based on the
+            values of the given columns within each minibatch. When multiple columns are provided,
+            the corresponding value tuples will be compared lexicographically.
+
+    Returns:
+        None
+    """
+    _write(
+        table,
+        oracle_settings,
+        table_name,
+        None,
+        max_batch_size=max_batch_size,
+        name=name,
+        sort_by=sort_by,
+        datasink_name="oracle.sink",
+    )
+
+
+@check_arg_types
+@trace_user_frame
+def write_snapshot(
+    table: Table,
+    oracle_settings: dict,
+    table_name: str,
+    primary_key: list[str],
+    *,
+    max_batch_size: int | None = None,
+    name: str | None = None,
+    sort_by: Iterable[ColumnReference] | None = None,
+) -> None:
+    """Maintains a snapshot of a table within an Oracle Database table.
+
+    The target table must have all the columns of ``table`` and the integer ``time``
+    and ``diff`` columns. Each batch of updates is applied with a single ``MERGE``
+    statement executed for all its rows: the rows are inserted, updated or deleted by
+    their primary key.
+
+    The connector requires the Oracle Client libraries, e.g. the
+    `Instant Client <https://www.oracle.com/database/technologies/instant-client.html>`_.
+
+    Args:
+        table: Table to be written.
+        oracle_settings: The connection settings with the ``user``, ``password`` and
+            ``connect_string`` keys. Values can be :py:class:`~pathway.secrets.Secret`
+            objects.
+        table_name: Name of the target table.
+        primary_key: Names of the columns which serve as a primary key in the target
+            table.
+        max_batch_size: Maximum number of entries allowed to be committed within a
+            single batch.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+        sort_by: If specified, the output will be sorted in ascending order based on the
+            values of the given columns within each minibatch. When multiple columns are provided,
+            the corresponding value tuples will be compared lexicographically.
+
+    Returns:
+        None
+    """
+    if not primary_key:
+        raise ValueError("primary_key must not be empty")
+    _write(
+        table,
+        oracle_settings,
+        table_name,
+        primary_key,
+        max_batch_size=max_batch_size,
+        name=name,
+        sort_by=sort_by,
+        datasink_name="oracle.snapshot",
+    )
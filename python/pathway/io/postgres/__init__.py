@@ -2,21 +2,149 @@
 
 from __future__ import annotations
 
-from typing import Iterable, Literal
+import datetime
+from typing import Any, Iterable, Literal
 
-from pathway.internals import api, datasink, dtype
-from pathway.internals._io_helpers import _format_output_value_fields
-from pathway.internals.expression import ColumnReference
+from pathway import secrets
+from pathway.internals import api, datasink, datasource, dtype
+from pathway.internals.expression import ColumnExpression, ColumnReference
 from pathway.internals.runtime_type_check import check_arg_types
+from pathway.internals.schema import Schema
 from pathway.internals.table import Table
+from pathway.internals.table_io import table_from_datasource
 from pathway.internals.trace import trace_user_frame
-from pathway.io._utils import get_column_index, init_mode_from_str
+from pathway.io._utils import (
+    ColumnMasking,
+    _prepare_sink_output,
+    get_column_index,
+    init_mode_from_str,
+    read_schema,
+)
 
 
 def _connection_string_from_settings(settings: dict):
+    settings = secrets.resolve(settings)
     return " ".join(k + "=" + str(v) for (k, v) in settings.items())
 
 
+@check_arg_types
+@trace_user_frame
+def read_incremental(
+    postgres_settings: dict,
+    query: str,
+    schema: type[Schema],
+    *,
+    cursor_column: str,
+    start_from: Any,
+    poll_interval: datetime.timedelta = datetime.timedelta(seconds=1),
+    autocommit_duration_ms: int | None = 1500,
+    name: str | None = None,
+    max_backlog_size: int | None = None,
+    debug_data: Any = None,
+) -> Table:
+    """Reads the rows appended to a Postgres table by polling it with an incremental
+    query. Useful for the tables that can't be read with a change data capture
+    connector, such as :py:func:`pathway.io.debezium.read`.
+
+    The query is run with the largest value of the ``cursor_column`` read so far, the
+    high-water mark, bound to its ``$1`` parameter, and has to return only the rows
+    past it, ordered by the cursor column. Before the first row is read, the value of
+    ``start_from`` is bound instead. When the query returns no rows, it's run again
+    after ``poll_interval``. If persistence is enabled, the high-water mark is
+    persisted, so that a restarted program continues the polling from where it has
+    stopped.
+
+    Only the new rows are read, so the cursor column has to grow with each inserted
+    row, as an auto-incremented identifier or an insertion timestamp does. Updates and
+    deletions of the rows already read are not noticed. The rows having the same
+    cursor value have to be inserted in the same transaction, otherwise those
+    committed after the others have been read are skipped.
+
+    Args:
+        postgres_settings: Components of the connection string for Postgres. The string
+            is formed by joining key-value pairs from the given dictionary with spaces,
+            with each pair formatted as `key=value`. Values can be
+            :py:class:`~pathway.secrets.Secret` objects.
+        query: The incremental query, taking the high-water mark as its ``$1``
+            parameter.
+        schema: Schema of the resulting table. It has to contain the cursor column. If
+            it defines no primary key, the keys are generated from the positions of the
+            rows in the polled stream.
+        cursor_column: The column with the values that grow with each inserted row.
+        start_from: The high-water mark used until the first row is read. The rows
+            with cursor values not greater than it are not read.
+        poll_interval: How long to wait before running the query again after it has
+            returned no rows.
+        autocommit_duration_ms: The maximum time between two commits. Every
+            autocommit_duration_ms milliseconds, the updates received by the connector are
+            committed and pushed into Pathway's computation graph.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards. Additionally, if persistence is enabled, it
+            will be used as the name for the snapshot that stores the connector's progress.
+        max_backlog_size: Limit on the number of entries read from the input source and
+            kept in processing at any moment. Reading pauses when the limit is reached and
+            resumes as processing of some entries completes.
+
+    Returns:
+        Table: The table read.
+
+    Example:
+
+    Suppose that the events are appended to the table ``events`` with an
+    auto-incremented identifier ``id``. They can be read as they are inserted with:
+
+    >>> import pathway as pw
+    >>> class EventSchema(pw.Schema):
+    ...     id: int = pw.column_definition(primary_key=True)
+    ...     kind: str
+    ...     payload: pw.Json
+    >>> events = pw.io.postgres.read_incremental(  # doctest: +SKIP
+    ...     {"host": "localhost", "dbname": "database", "user": "user"},
+    ...     "SELECT id, kind, payload FROM events WHERE id > $1 ORDER BY id",
+    ...     schema=EventSchema,
+    ...     cursor_column="id",
+    ...     start_from=0,
+    ... )
+    """
+    schema, api_schema = read_schema(schema)
+    if cursor_column not in schema.column_names():
+        raise ValueError(
+            f"The cursor column {cursor_column!r} is not a part of the schema"
+        )
+
+    data_storage = api.DataStorage(
+        storage_type="sql_polling",
+        connection_string=_connection_string_from_settings(postgres_settings),
+        sql_polling_settings=api.SqlPollingSettings(
+            query,
+            cursor_column,
+            start_from,
+            poll_interval=poll_interval,
+        ),
+        mode=api.ConnectorMode.STREAMING,
+    )
+    data_format = api.DataFormat(
+        format_type="transparent",
+        **api_schema,
+    )
+
+    data_source_options = datasource.DataSourceOptions(
+        commit_duration_ms=autocommit_duration_ms,
+        unique_name=name,
+        max_backlog_size=max_backlog_size,
+    )
+    return table_from_datasource(
+        datasource.GenericDataSource(
+            datastorage=data_storage,
+            dataformat=data_format,
+            schema=schema,
+            data_source_options=data_source_options,
+            datasource_name="postgres.incremental",
+        ),
+        debug_datasource=datasource.debug_datasource(debug_data),
+    )
+
+
 @check_arg_types
 @trace_user_frame
 def write(
@@ -28,6 +156,9 @@ def write(
     init_mode: Literal["default", "create_if_not_exists", "replace"] = "default",
     name: str | None = None,
     sort_by: Iterable[ColumnReference] | None = None,
+    column_masking: dict[ColumnReference | str, ColumnMasking] | None = None,
+    row_filter: ColumnExpression | None = None,
+    columns: Iterable[ColumnReference] | None = None,
 ) -> None:
     """Writes ``table``'s stream of updates to a postgres table.
 
@@ -40,7 +171,7 @@ def write(
             formed by joining key-value pairs from the given dictionary with spaces,
             with each pair formatted as `key=value`. Keys must be strings. Values can be
             of any type; if a value is not a string, it will be converted using Python's
-            `str()` function.
+            `str()` function. Values can be :py:class:`~pathway.secrets.Secret` objects.
         table_name: Name of the target table.
         max_batch_size: Maximum number of entries allowed to be committed within a
             single transaction.
@@ -53,6 +184,16 @@ def write(
         sort_by: If specified, the output will be sorted in ascending order based on the
             values of the given columns within each minibatch. When multiple columns are provided,
             the corresponding value tuples will be compared lexicographically.
+        column_masking: The columns to be masked before writing, mapped to the
+            :py:class:`~pathway.io.ColumnMasking` to be applied to them.
+        row_filter: If specified, only the rows for which this condition holds are
+            written. The condition is evaluated by the connector, without creating a
+            filtered table. It can consist of the comparisons of the columns of
+            ``table`` with the constants, the checks for ``None`` and the boolean
+            columns, combined with ``&``, ``|`` and ``~``.
+        columns: If specified, only these columns of ``table`` are written, in the
+            given order. The columns are selected by the connector, without creating
+            a projected table.
 
     Returns:
         None
@@ -119,11 +260,17 @@ def write(
         table_name=table_name,
         table_writer_init_mode=init_mode_from_str(init_mode),
     )
+    sink_output = _prepare_sink_output(
+        table, column_masking=column_masking, row_filter=row_filter, columns=columns
+    )
     data_format = api.DataFormat(
         format_type="sql",
         key_field_names=[],
-        value_fields=_format_output_value_fields(table),
+        value_fields=sink_output.value_fields,
         table_name=table_name,
+        column_masking=sink_output.column_masking,
+        sink_filter=sink_output.sink_filter,
+        sink_projection=sink_output.sink_projection,
     )
 
     table.to(
@@ -148,6 +295,9 @@ def write_snapshot(
     init_mode: Literal["default", "create_if_not_exists", "replace"] = "default",
     name: str | None = None,
     sort_by: Iterable[ColumnReference] | None = None,
+    column_masking: dict[ColumnReference | str, ColumnMasking] | None = None,
+    row_filter: ColumnExpression | None = None,
+    columns: Iterable[ColumnReference] | None = None,
     _external_diff_column: ColumnReference | None = None,
 ) -> None:
     """Maintains a snapshot of a table within a Postgres table.
@@ -157,6 +307,7 @@ def write_snapshot(
 
     Args:
         postgres_settings: Components of the connection string for Postgres.
+            Values can be :py:class:`~pathway.secrets.Secret` objects.
         table_name: Name of the target table.
         primary_key: Names of the fields which serve as a primary key in the Postgres table.
         max_batch_size: Maximum number of entries allowed to be committed within a
@@ -170,6 +321,16 @@ def write_snapshot(
         sort_by: If specified, the output will be sorted in ascending order based on the
             values of the given columns within each minibatch. When multiple columns are provided,
             the corresponding value tuples will be compared lexicographically.
+        column_masking: The columns to be masked before writing, mapped to the
+            :py:class:`~pathway.io.ColumnMasking` to be applied to them.
+        row_filter: If specified, only the rows for which this condition holds are
+            written. The condition is evaluated by the connector, without creating a
+            filtered table. It can consist of the comparisons of the columns of
+            ``table`` with the constants, the checks for ``None`` and the boolean
+            columns, combined with ``&``, ``|`` and ``~``.
+        columns: If specified, only these columns of ``table`` are written, in the
+            given order. The columns are selected by the connector, without creating
+            a projected table.
 
     Returns:
         None
@@ -229,12 +390,18 @@ def write_snapshot(
     ):
         raise ValueError("_external_diff_column can only have an integer type")
     external_diff_column_index = get_column_index(table, _external_diff_column)
+    sink_output = _prepare_sink_output(
+        table, column_masking=column_masking, row_filter=row_filter, columns=columns
+    )
     data_format = api.DataFormat(
         format_type="sql_snapshot",
         key_field_names=primary_key,
-        value_fields=_format_output_value_fields(table),
+        value_fields=sink_output.value_fields,
         table_name=table_name,
         external_diff_column_index=external_diff_column_index,
+        column_masking=sink_output.column_masking,
+        sink_filter=sink_output.sink_filter,
+        sink_projection=sink_output.sink_projection,
     )
 
     table.to(
@@ -0,0 +1,190 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import re
+from typing import Iterable
+
+import pathway.internals.dtype as dt
+from pathway.internals import api, datasink
+from pathway.internals._io_helpers import _format_output_value_fields
+from pathway.internals.expression import ColumnReference
+from pathway.internals.runtime_type_check import check_arg_types
+from pathway.internals.table import Table
+from pathway.internals.trace import trace_user_frame
+from pathway.io._utils import get_column_index
+
+_METRIC_NAME_PATTERN = re.compile(r"[a-zA-Z_:][a-zA-Z0-9_:]*")
+_LABEL_NAME_PATTERN = re.compile(r"[a-zA-Z_][a-zA-Z0-9_]*")
+
+_NUMERIC_DTYPES = (dt.INT, dt.FLOAT, dt.BOOL)
+_TIMESTAMP_DTYPES = (dt.DATE_TIME_UTC, dt.DATE_TIME_NAIVE, dt.INT)
+
+
+def _is_numeric(column: ColumnReference) -> bool:
+    return dt.unoptionalize(column._column.dtype) in _NUMERIC_DTYPES
+
+
+@check_arg_types
+@trace_user_frame
+def write(
+    table: Table,
+    url: str,
+    *,
+    metric_columns: Iterable[ColumnReference] | None = None,
+    label_columns: Iterable[ColumnReference] = (),
+    timestamp_column: ColumnReference | None = None,
+    metric_prefix: str = "",
+    headers: dict[str, str] | None = None,
+    name: str | None = None,
+    sort_by: Iterable[ColumnReference] | None = None,
+) -> None:
+    """Pushes the numeric columns of ``table`` as the samples of Prometheus time series,
+    with the `remote-write protocol <https://prometheus.io/docs/specs/remote_write_spec/>`_.
+    Apart from Prometheus, it is accepted by most of the compatible storages, such as
+    Grafana Mimir, Cortex, Thanos or VictoriaMetrics.
+
+    Each of the ``metric_columns`` becomes a metric named as the column, prefixed with
+    ``metric_prefix``. Each inserted row adds a sample to the series of each of these
+    metrics, labeled with the values of the ``label_columns`` in the row. The labels with
+    ``None`` values are omitted, as are the samples with ``None`` values. Boolean values
+    are sent as ``0`` and ``1``.
+
+    The samples are timestamped with the values of ``timestamp_column`` if it is given,
+    or with the time they are sent at otherwise. A time series can't be changed
+    retroactively, so the deletions from the table aren't sent: when a row of the
+    table is updated, the new sample is added to the series and the old one is kept.
+    Note that Prometheus rejects the samples older than the latest sample of the series,
+    so the timestamps of the rows of the same series should increase.
+
+    The samples are sent in a single request after each minibatch of the computation.
+
+    Args:
+        table: The table to be written.
+        url: The remote-write endpoint, e.g. ``http://localhost:9090/api/v1/write`` for
+            Prometheus started with ``--web.enable-remote-write-receiver``.
+        metric_columns: The columns with the values of the metrics. They must have
+            ``int``, ``float`` or ``bool`` types, possibly optional. If not given, all the
+            columns of such types that are neither label nor timestamp columns are used.
+        label_columns: The columns with the values of the labels of the series. The
+            values are converted to strings.
+        timestamp_column: The column with the timestamps of the samples. It must have
+            the ``DateTimeUtc`` or ``DateTimeNaive`` type, or the ``int`` type, in which
+            case the values are interpreted as milliseconds since the Unix epoch.
+        metric_prefix: The prefix added to the names of the metrics.
+        headers: Additional HTTP headers sent with the requests, e.g. for the
+            authentication or a tenant ID.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+        sort_by: If specified, the output will be sorted in ascending order based on the
+            values of the given columns within each minibatch. When multiple columns are
+            provided, the corresponding value tuples will be compared lexicographically.
+
+    Returns:
+        None
+
+    Example:
+
+    Suppose the number of the orders and the total revenue are computed per region
+    every minute:
+
+    >>> import pathway as pw
+    >>> orders = pw.debug.table_from_markdown('''
+    ...     region | amount | time
+    ...     eu     | 10.5   | 1700000000000
+    ...     us     | 20.0   | 1700000030000
+    ...     eu     | 5.0    | 1700000040000
+    ... ''')
+    >>> stats = orders.windowby(
+    ...     pw.this.time,
+    ...     window=pw.temporal.tumbling(duration=60_000),
+    ...     instance=pw.this.region,
+    ... ).reduce(
+    ...     region=pw.this._pw_instance,
+    ...     window_end=pw.this._pw_window_end,
+    ...     orders=pw.reducers.count(),
+    ...     revenue=pw.reducers.sum(pw.this.amount),
+    ... )
+
+    The statistics can be pushed to Prometheus as the series ``shop_orders`` and
+    ``shop_revenue``, labeled with the region:
+
+    >>> pw.io.prometheus.write(
+    ...     stats,
+    ...     "http://localhost:9090/api/v1/write",
+    ...     metric_columns=[stats.orders, stats.revenue],
+    ...     label_columns=[stats.region],
+    ...     timestamp_column=stats.window_end,
+    ...     metric_prefix="shop_",
+    ... )
+    """
+    label_columns = list(label_columns)
+    for column in label_columns:
+        if not _LABEL_NAME_PATTERN.fullmatch(column.name) or column.name.startswith(
+            "__"
+        ):
+            raise ValueError(f"{column.name!r} is not a valid Prometheus label name")
+
+    if timestamp_column is not None:
+        dtype = dt.unoptionalize(timestamp_column._column.dtype)
+        if dtype not in _TIMESTAMP_DTYPES:
+            raise ValueError(
+                "The timestamp column must have a DateTimeUtc, DateTimeNaive or int "
+                f"type, however {timestamp_column._column.dtype.typehint} is used"
+            )
+
+    if metric_columns is None:
+        excluded = {column.name for column in label_columns}
+        if timestamp_column is not None:
+            excluded.add(timestamp_column.name)
+        metric_columns = [
+            column
+            for column in table
+            if column.name not in excluded and _is_numeric(column)
+        ]
+    else:
+        metric_columns = list(metric_columns)
+        for column in metric_columns:
+            if not _is_numeric(column):
+                raise ValueError(
+                    f"The metric column {column.name!r} must have an int, float or "
+                    f"bool type, however {column._column.dtype.typehint} is used"
+                )
+    if not metric_columns:
+        raise ValueError("There are no columns to be written as the metrics")
+    for column in metric_columns:
+        metric_name = metric_prefix + column.name
+        if not _METRIC_NAME_PATTERN.fullmatch(metric_name):
+            raise ValueError(f"{metric_name!r} is not a valid Prometheus metric name")
+
+    data_storage = api.DataStorage(
+        storage_type="prometheus",
+        path=url,
+        prometheus_settings=api.PrometheusSettings(
+            [
+                (metric_prefix + column.name, get_column_index(table, column))
+                for column in metric_columns
+            ],
+            [(column.name, get_column_index(table, column)) for column in label_columns],
+            timestamp_field_index=get_column_index(table, timestamp_column),
+            headers=headers,
+        ),
+    )
+    data_format = api.DataFormat(
+        format_type="identity",
+        key_field_names=[],
+        value_fields=_format_output_value_fields(table),
+    )
+
+    table.to(
+        datasink.GenericDataSink(
+            data_storage,
+            data_format,
+            datasink_name="prometheus.sink",
+            unique_name=name,
+            sort_by=sort_by,
+        )
+    )
+
+
+__all__ = ["write"]
@@ -103,6 +103,15 @@ class ConnectorSubject(ABC):
         """Called after the end of the :py:meth:`run` function."""
         pass
 
+    @property
+    def stop_requested(self) -> bool:
+        """Whether termination of the computation has been requested, e.g. with
+        :py:func:`~pathway.request_shutdown` or by cancelling a run started with
+        :py:func:`~pathway.run_in_background`. A :py:meth:`run` method reading
+        an infinite stream should check it periodically and return once it is set.
+        """
+        return api.is_shutdown_requested()
+
     def _is_finite(self) -> bool:
         """
         Denotes if the connector teminates after the code inside run() routine
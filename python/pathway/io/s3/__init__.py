@@ -12,8 +12,10 @@ from pathway.internals.table import Table
 from pathway.internals.table_io import table_from_datasource
 from pathway.internals.trace import trace_user_frame
 from pathway.io._utils import (
+    ConnectorRetryPolicy,
     CsvParserSettings,
     _get_unique_name,
+    _timedelta_from_ms,
     construct_schema_and_data_format,
     internal_connector_mode,
     internal_read_method,
@@ -94,7 +96,9 @@ class WasabiS3Settings:
 @trace_user_frame
 def read(
     path: str,
-    format: Literal["csv", "json", "plaintext", "plaintext_by_object", "binary"],
+    format: Literal[
+        "csv", "json", "plaintext", "plaintext_by_object", "binary", "parquet"
+    ],
     *,
     aws_s3_settings: AwsS3Settings | None = None,
     schema: type[Schema] | None = None,
@@ -104,6 +108,8 @@ def read(
     json_field_paths: dict[str, str] | None = None,
     path_filter: str | None = None,
     downloader_threads_count: int | None = None,
+    retry_policy: ConnectorRetryPolicy | None = None,
+    request_timeout_ms: int | None = None,
     autocommit_duration_ms: int | None = 1500,
     name: str | None = None,
     max_backlog_size: int | None = None,
@@ -123,12 +129,14 @@ def read(
         path: Path to an object or to a folder of objects in Amazon S3 bucket.
         aws_s3_settings: Connection parameters for the S3 account and the bucket.
         format: Format of data to be read. Currently ``csv``, ``json``, ``plaintext``,
-            ``plaintext_by_object`` and ``binary`` formats are supported. The difference
-            between ``plaintext`` and ``plaintext_by_object`` is how the input is
-            tokenized: if the ``plaintext`` option is chosen, it's split by the newlines.
-            Otherwise, the files are split in full and one row will correspond to one
-            file. In case the ``binary`` format is specified, the data is read as raw
-            bytes without UTF-8 parsing.
+            ``plaintext_by_object``, ``binary`` and ``parquet`` formats are supported.
+            The difference between ``plaintext`` and ``plaintext_by_object`` is how the
+            input is tokenized: if the ``plaintext`` option is chosen, it's split by the
+            newlines. Otherwise, the files are split in full and one row will correspond
+            to one file. In case the ``binary`` format is specified, the data is read as
+            raw bytes without UTF-8 parsing. In case of ``parquet``, each object is a
+            Parquet file whose columns are matched with the columns of the schema by
+            name, and each row of the file becomes a row of the table.
         schema: Schema of the resulting table. Not required for ``plaintext_by_object``
             and ``binary`` formats: if they are chosen, the contents of the read objects
             are stored in the column ``data``.
@@ -156,6 +164,10 @@ def read(
             of the bucket under the given path. It defaults to the number of cores
             available on the machine. It is recommended to increase the number of
             threads if your bucket contains many small files.
+        retry_policy: Retry settings for the failed requests to S3. By default, a
+            request is retried twice.
+        request_timeout_ms: Timeout of a single request to S3, in milliseconds. If not
+            specified, the default timeout of the S3 client is used.
         autocommit_duration_ms: The maximum time between two commits. Every
             autocommit_duration_ms milliseconds, the updates received by the connector are
             committed and pushed into Pathway's computation graph.
@@ -288,6 +300,8 @@ def read(
         mode=internal_connector_mode(mode),
         read_method=internal_read_method(format),
         downloader_threads_count=downloader_threads_count,
+        retry_policy=retry_policy.api_policy if retry_policy else None,
+        request_timeout=_timedelta_from_ms(request_timeout_ms),
     )
 
     schema, data_format = construct_schema_and_data_format(
@@ -320,7 +334,9 @@ def read(
 def read_from_digital_ocean(
     path: str,
     do_s3_settings: DigitalOceanS3Settings,
-    format: Literal["csv", "json", "plaintext", "plaintext_by_object", "binary"],
+    format: Literal[
+        "csv", "json", "plaintext", "plaintext_by_object", "binary", "parquet"
+    ],
     *,
     schema: type[Schema] | None = None,
     mode: Literal["streaming", "static"] = "streaming",
@@ -345,12 +361,14 @@ def read_from_digital_ocean(
         path: Path to an object or to a folder of objects in S3 bucket.
         do_s3_settings: Connection parameters for the account and the bucket.
         format: Format of data to be read. Currently ``csv``, ``json``, ``plaintext``,
-            ``plaintext_by_object`` and ``binary`` formats are supported. The difference
-            between ``plaintext`` and ``plaintext_by_object`` is how the input is
-            tokenized: if the ``plaintext`` option is chosen, it's split by the newlines.
-            Otherwise, the files are split in full and one row will correspond to one
-            file. In case the ``binary`` format is specified, the data is read as raw
-            bytes without UTF-8 parsing.
+            ``plaintext_by_object``, ``binary`` and ``parquet`` formats are supported.
+            The difference between ``plaintext`` and ``plaintext_by_object`` is how the
+            input is tokenized: if the ``plaintext`` option is chosen, it's split by the
+            newlines. Otherwise, the files are split in full and one row will correspond
+            to one file. In case the ``binary`` format is specified, the data is read as
+            raw bytes without UTF-8 parsing. In case of ``parquet``, each object is a
+            Parquet file whose columns are matched with the columns of the schema by
+            name, and each row of the file becomes a row of the table.
         schema: Schema of the resulting table. Not required for ``plaintext_by_object``
             and ``binary`` formats: if they are chosen, the contents of the read objects
             are stored in the column ``data``.
@@ -459,7 +477,9 @@ def read_from_digital_ocean(
 def read_from_wasabi(
     path: str,
     wasabi_s3_settings: WasabiS3Settings,
-    format: Literal["csv", "json", "plaintext", "plaintext_by_object", "binary"],
+    format: Literal[
+        "csv", "json", "plaintext", "plaintext_by_object", "binary", "parquet"
+    ],
     *,
     schema: type[Schema] | None = None,
     mode: Literal["streaming", "static"] = "streaming",
@@ -483,12 +503,14 @@ def read_from_wasabi(
         path: Path to an object or to a folder of objects in S3 bucket.
         wasabi_s3_settings: Connection parameters for the account and the bucket.
         format: Format of data to be read. Currently ``csv``, ``json``, ``plaintext``,
-            ``plaintext_by_object`` and ``binary`` formats are supported. The difference
-            between ``plaintext`` and ``plaintext_by_object`` is how the input is
-            tokenized: if the ``plaintext`` option is chosen, it's split by the newlines.
-            Otherwise, the files are split in full and one row will correspond to one
-            file. In case the ``binary`` format is specified, the data is read as raw
-            bytes without UTF-8 parsing.
+            ``plaintext_by_object``, ``binary`` and ``parquet`` formats are supported.
+            The difference between ``plaintext`` and ``plaintext_by_object`` is how the
+            input is tokenized: if the ``plaintext`` option is chosen, it's split by the
+            newlines. Otherwise, the files are split in full and one row will correspond
+            to one file. In case the ``binary`` format is specified, the data is read as
+            raw bytes without UTF-8 parsing. In case of ``parquet``, each object is a
+            Parquet file whose columns are matched with the columns of the schema by
+            name, and each row of the file becomes a row of the table.
         schema: Schema of the resulting table. Not required for ``plaintext_by_object``
             and ``binary`` formats: if they are chosen, the contents of the read objects
             are stored in the column ``data``.
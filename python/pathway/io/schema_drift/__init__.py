@@ -0,0 +1,102 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import time
+
+import pathway as pw
+from pathway.internals import api
+from pathway.internals.runtime_type_check import check_arg_types
+from pathway.io.python import ConnectorSubject
+
+
+class SchemaDriftSchema(pw.Schema):
+    source: str
+    field: str
+    kind: str
+    details: str
+    observed_at: int
+
+
+class _SchemaDriftSubject(ConnectorSubject):
+    _poll_interval_ms: int
+
+    def __init__(self, *, poll_interval_ms: int) -> None:
+        super().__init__(datasource_name="schema_drift")
+        self._poll_interval_ms = poll_interval_ms
+
+    def run(self) -> None:
+        while not self.stop_requested:
+            events = api.take_schema_drift_events()
+            observed_at = int(time.time() * 1000)
+            for source, field, kind, details in events:
+                self.next(
+                    source=source,
+                    field=field,
+                    kind=kind,
+                    details=details,
+                    observed_at=observed_at,
+                )
+            if events:
+                self.commit()
+            time.sleep(self._poll_interval_ms / 1000)
+
+
+@check_arg_types
+def read(
+    *,
+    poll_interval_ms: int = 1000,
+    name: str | None = None,
+) -> pw.Table:
+    """Reads the stream of the schema drift events of the input connectors.
+
+    The connectors parsing JSON, i.e. the ones with ``format="json"``, compare the data
+    they read with their schemas and report:
+
+    - ``"new_field"``, when a field not present in the schema is observed for the first
+      time;
+    - ``"type_conflict"``, when a value can't be parsed to the type of its column. It is
+      reported once per column and kind of the JSON value, e.g. a string in a numeric
+      column;
+    - ``"null_rate_change"``, when the share of the entries in which a column is missing
+      or null changes by at least 10 percentage points between two consecutive windows
+      of 1000 entries.
+
+    Only the connectors created after this function is called are observed, so it is
+    best called before the other connectors are defined. The events are not persisted.
+
+    Args:
+        poll_interval_ms: how often, in milliseconds, the reported events are sent to
+            the resulting table.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+
+    Returns:
+        The table with the columns ``source``, the name of the connector, ``field``,
+        ``kind``, ``details``, a human-readable description of the drift, and
+        ``observed_at``, the time in milliseconds since the UNIX epoch when the event
+        was taken from the connector.
+
+    Example:
+
+    The drift of a stream of events can be written to a separate file, to be alerted on:
+
+    >>> import pathway as pw
+    >>> drift = pw.io.schema_drift.read()
+    >>> class InputSchema(pw.Schema):
+    ...     user_id: int
+    ...     amount: float
+    >>> events = pw.io.jsonlines.read("events/", schema=InputSchema)
+    >>> pw.io.jsonlines.write(drift, "schema_drift.jsonl")
+    """
+    api.enable_schema_drift_reporting()
+    subject = _SchemaDriftSubject(poll_interval_ms=poll_interval_ms)
+    return pw.io.python.read(
+        subject,
+        schema=SchemaDriftSchema,
+        name=name,
+        _stacklevel=4,
+    )
+
+
+__all__ = ["read", "SchemaDriftSchema"]
@@ -0,0 +1,108 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import datetime
+import os
+from typing import Literal
+
+from pathway.internals import api, datasink
+from pathway.internals._io_helpers import _format_output_value_fields
+from pathway.internals.runtime_type_check import check_arg_types
+from pathway.internals.table import Table
+from pathway.internals.trace import trace_user_frame
+
+
+@check_arg_types
+@trace_user_frame
+def register(table: Table, table_name: str, *, name: str | None = None) -> None:
+    """Keeps the current contents of the table in memory, so that they can be exported
+    to a file with :py:func:`export` while the computation is running.
+
+    The changes of a time are applied only once the time is committed, so an export
+    always contains a consistent state of the table. The snapshot can also be exported
+    with a ``POST`` request to ``http://localhost:<port>/tables/<table_name>/export``
+    of the monitoring HTTP server, with a JSON body containing the ``path`` and,
+    optionally, the ``format``, ``time`` and ``timeout_seconds``. The response contains
+    the number of written ``rows`` and the ``time`` of the snapshot, as returned by
+    :py:func:`export`.
+
+    The table is kept by the process running the first worker, so the exports have to be
+    requested from that process.
+
+    Args:
+        table: table to be registered.
+        table_name: the name of the table used to request the exports. It must be unique
+            within the program.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> orders = pw.debug.table_from_markdown("item amount \\n apple 3 \\n pear 5")
+    >>> pw.io.snapshot.register(orders, "orders")
+
+    Then, from another thread of the running program:
+
+    >>> pw.io.snapshot.export("orders", "./orders.parquet")  # doctest: +SKIP
+    """
+    data_storage = api.DataStorage(
+        storage_type="snapshot_export",
+        table_name=table_name,
+    )
+    data_format = api.DataFormat(
+        format_type="identity",
+        key_field_names=[],
+        value_fields=_format_output_value_fields(table),
+    )
+
+    table.to(
+        datasink.GenericDataSink(
+            data_storage,
+            data_format,
+            datasink_name="snapshot",
+            unique_name=name,
+        )
+    )
+
+
+@check_arg_types
+def export(
+    table_name: str,
+    path: str | os.PathLike,
+    *,
+    format: Literal["parquet", "csv"] = "parquet",
+    time: int | None = None,
+    timeout: datetime.timedelta | None = None,
+) -> int | None:
+    """Writes the current contents of a table registered with :py:func:`register` to a
+    file, without stopping the computation. The rows are written with their keys in the
+    ``id`` column. The file is replaced only once it is fully written.
+
+    Args:
+        table_name: the name the table was registered with.
+        path: the path of the written file.
+        format: the format of the file, either ``"parquet"`` or ``"csv"``. In CSV files,
+            the values other than strings are written as JSON.
+        time: if set, the snapshot is taken at the first commit that includes all
+            changes with times not later than ``time``, waiting for it if needed.
+            Otherwise, the snapshot is taken at the latest commit.
+        timeout: the longest time to wait for the commit. If it passes, ``ValueError``
+            is raised. By default, there is no limit.
+
+    Returns:
+        The time of the commit at which the snapshot was taken, meaning that it contains
+        exactly the changes with earlier times, or ``None`` if the computation has
+        already finished and the snapshot contains all changes.
+    """
+    _, commit_time = api.export_table_snapshot(
+        table_name, os.fspath(path), format, time=time, timeout=timeout
+    )
+    return commit_time
+
+
+__all__ = [
+    "register",
+    "export",
+]
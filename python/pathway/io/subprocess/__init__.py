@@ -0,0 +1,282 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import datetime
+import os
+from typing import Iterable, Literal
+
+from pathway.internals import api, datasink, datasource
+from pathway.internals.expression import ColumnReference
+from pathway.internals.runtime_type_check import check_arg_types
+from pathway.internals.schema import Schema
+from pathway.internals.table import Table
+from pathway.internals.table_io import table_from_datasource
+from pathway.internals.trace import trace_user_frame
+from pathway.io._utils import (
+    MessageQueueOutputFormat,
+    _get_unique_name,
+    check_raw_and_plaintext_only_kwargs_for_message_queues,
+    construct_schema_and_data_format,
+)
+
+RestartPolicy = Literal["never", "on_failure", "always"]
+
+
+def _subprocess_settings(
+    command: list[str],
+    env: dict[str, str] | None,
+    working_directory: str | os.PathLike[str] | None,
+    restart_policy: RestartPolicy,
+    max_restarts: int | None,
+    restart_delay: datetime.timedelta,
+) -> api.SubprocessSettings:
+    if not command:
+        raise ValueError("the command must not be empty")
+    return api.SubprocessSettings(
+        command,
+        env=env,
+        working_directory=(
+            os.fspath(working_directory) if working_directory is not None else None
+        ),
+        restart_policy=restart_policy,
+        max_restarts=max_restarts,
+        restart_delay=restart_delay,
+    )
+
+
+@check_arg_types
+@trace_user_frame
+def read(
+    command: list[str],
+    *,
+    schema: type[Schema] | None = None,
+    format: Literal["plaintext", "raw", "json"] = "plaintext",
+    env: dict[str, str] | None = None,
+    working_directory: str | os.PathLike[str] | None = None,
+    restart_policy: RestartPolicy = "never",
+    max_restarts: int | None = None,
+    restart_delay: datetime.timedelta = datetime.timedelta(seconds=1),
+    autocommit_duration_ms: int | None = 1500,
+    json_field_paths: dict[str, str] | None = None,
+    name: str | None = None,
+    max_backlog_size: int | None = None,
+    debug_data=None,
+    **kwargs,
+) -> Table:
+    """Runs an external command and reads the lines it prints to its standard output.
+
+    Each line becomes a row of the table. It is convenient for wrapping existing
+    extractors and scripts that print their results instead of writing them to a
+    storage supported by Pathway. The command is started by a single Pathway worker
+    when the computation starts, its standard error is passed through to the standard
+    error of the program.
+
+    It supports three formats: ``"plaintext"``, ``"raw"``, and ``"json"``. In the
+    ``"plaintext"`` format, the line is decoded from UTF-8, and in the ``"raw"``
+    format it's read as bytes. In both cases, the table has an autogenerated primary
+    key and a single ``"data"`` column. In the ``"json"`` format, each line is parsed
+    as a JSON object and the columns are created according to ``schema``.
+
+    When the command exits, it is started again according to ``restart_policy``:
+
+    - ``"never"``: the connector finishes. If the command failed, the computation
+      fails too.
+    - ``"on_failure"``: the command is started again if it exited with a non-zero
+      status, otherwise the connector finishes.
+    - ``"always"``: the command is always started again.
+
+    The output of a command can't be replayed, so if persistence is enabled, the lines
+    read before a restart of the program are not read again. Only the lines printed by
+    the command started after the restart are added.
+
+    Args:
+        command: The program to run, followed by its arguments. The program is looked
+            up in ``PATH`` if it isn't a path.
+        schema: The table schema, used only when the format is set to ``"json"``.
+        format: The format of the lines, which can be ``"plaintext"``, ``"raw"``, or
+            ``"json"``.
+        env: Additional environment variables of the command. The command also gets
+            the environment variables of the Pathway program.
+        working_directory: The working directory of the command. If None, the
+            working directory of the Pathway program is used.
+        restart_policy: When the command is started again after it exits.
+        max_restarts: The maximum number of times the command is started again. If
+            None, there is no limit.
+        restart_delay: The time to wait before starting the command again.
+        autocommit_duration_ms: The time interval (in milliseconds) between commits.
+            After this time, the updates received by the connector are committed and
+            added to Pathway's computation graph.
+        json_field_paths: For the ``"json"`` format, this allows mapping field names to
+            paths within the JSON structure. Use the format ``<field_name>: <path>``
+            where the path follows the
+            `JSON Pointer (RFC 6901) <https://www.rfc-editor.org/rfc/rfc6901>`_.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards. Additionally, if persistence is enabled, it
+            will be used as the name for the snapshot that stores the connector's
+            progress.
+        max_backlog_size: Limit on the number of entries read from the input source and
+            kept in processing at any moment. Reading pauses when the limit is reached
+            and resumes as processing of some entries completes.
+        debug_data: Static data replacing original one when debug mode is active.
+
+    Returns:
+        Table: The table read.
+
+    Example:
+
+    Suppose there is a legacy script ``extract.py`` that prints the records it extracts
+    as JSON objects, one per line, and it is sometimes killed when the source it
+    extracts from is unavailable. It can be used as a Pathway source like this:
+
+    >>> import pathway as pw
+    >>> class InputSchema(pw.Schema):
+    ...     user_id: int = pw.column_definition(primary_key=True)
+    ...     username: str
+    ...
+    >>> table = pw.io.subprocess.read(
+    ...     ["python", "extract.py", "--follow"],
+    ...     format="json",
+    ...     schema=InputSchema,
+    ...     restart_policy="on_failure",
+    ...     max_restarts=10,
+    ... )
+    """
+
+    data_storage = api.DataStorage(
+        storage_type="subprocess",
+        mode=api.ConnectorMode.STREAMING,
+        subprocess_settings=_subprocess_settings(
+            command,
+            env,
+            working_directory,
+            restart_policy,
+            max_restarts,
+            restart_delay,
+        ),
+    )
+    schema, data_format = construct_schema_and_data_format(
+        "binary" if format == "raw" else format,
+        schema=schema,
+        csv_settings=None,
+        json_field_paths=json_field_paths,
+    )
+    data_source_options = datasource.DataSourceOptions(
+        commit_duration_ms=autocommit_duration_ms,
+        unique_name=_get_unique_name(name, kwargs),
+        max_backlog_size=max_backlog_size,
+    )
+    return table_from_datasource(
+        datasource.GenericDataSource(
+            datastorage=data_storage,
+            dataformat=data_format,
+            data_source_options=data_source_options,
+            schema=schema,
+            datasource_name="subprocess",
+        ),
+        debug_datasource=datasource.debug_datasource(debug_data),
+    )
+
+
+@check_raw_and_plaintext_only_kwargs_for_message_queues
+@check_arg_types
+@trace_user_frame
+def write(
+    table: Table,
+    command: list[str],
+    *,
+    format: Literal["json", "dsv", "plaintext", "raw"] = "json",
+    delimiter: str = ",",
+    value: ColumnReference | None = None,
+    env: dict[str, str] | None = None,
+    working_directory: str | os.PathLike[str] | None = None,
+    restart_policy: RestartPolicy = "never",
+    max_restarts: int | None = None,
+    restart_delay: datetime.timedelta = datetime.timedelta(seconds=1),
+    name: str | None = None,
+    sort_by: Iterable[ColumnReference] | None = None,
+) -> None:
+    """Runs an external command and writes the updates of the table to its standard
+    input, one per line.
+
+    The command is started when the first updates are written. Its standard output and
+    standard error are passed through to the ones of the Pathway program. When the
+    computation finishes, the standard input of the command is closed and the command
+    is waited for.
+
+    In the ``"json"`` and ``"dsv"`` formats, the lines contain the values of the
+    columns together with the ``time`` and ``diff`` fields. In the ``"plaintext"`` and
+    ``"raw"`` formats, the lines are the values of a single column, specified with
+    ``value`` or deduced if the table has only one column.
+
+    When the command exits, it is started again according to ``restart_policy``, in the
+    same way as in :py:func:`read`. The updates that were sent to the command but not
+    yet processed when it exited are lost.
+
+    Args:
+        table: The table for output.
+        command: The program to run, followed by its arguments. The program is looked
+            up in ``PATH`` if it isn't a path.
+        format: The format of the lines, which can be ``"json"``, ``"dsv"``,
+            ``"plaintext"``, or ``"raw"``.
+        delimiter: Field delimiter to be used in case of delimiter-separated values
+            format.
+        value: Reference to the column that should be used as the line in the
+            ``"plaintext"`` or ``"raw"`` format.
+        env: Additional environment variables of the command. The command also gets
+            the environment variables of the Pathway program.
+        working_directory: The working directory of the command. If None, the
+            working directory of the Pathway program is used.
+        restart_policy: When the command is started again after it exits.
+        max_restarts: The maximum number of times the command is started again. If
+            None, there is no limit.
+        restart_delay: The time to wait before starting the command again.
+        name: A unique name for the connector. If provided, this name will be used in
+            logs and monitoring dashboards.
+        sort_by: If specified, the output will be sorted in ascending order based on the
+            values of the given columns within each minibatch. When multiple columns are
+            provided, the corresponding value tuples will be compared lexicographically.
+
+    Example:
+
+    Suppose there is a legacy loader ``load.sh`` that reads the records to load, one
+    per line, from its standard input. The contents of a table can be passed to it like
+    this:
+
+    >>> import pathway as pw
+    >>> table = pw.debug.table_from_markdown('''
+    ... owner | pet
+    ... Alice | dog
+    ... Bob   | cat
+    ... ''')
+    >>> pw.io.subprocess.write(table, ["./load.sh"], restart_policy="on_failure")
+    """
+    output_format = MessageQueueOutputFormat.construct(
+        table,
+        format=format,
+        delimiter=delimiter,
+        value=value,
+    )
+    table = output_format.table
+
+    data_storage = api.DataStorage(
+        storage_type="subprocess",
+        subprocess_settings=_subprocess_settings(
+            command,
+            env,
+            working_directory,
+            restart_policy,
+            max_restarts,
+            restart_delay,
+        ),
+    )
+
+    table.to(
+        datasink.GenericDataSink(
+            data_storage,
+            output_format.data_format,
+            datasink_name="subprocess",
+            unique_name=name,
+            sort_by=sort_by,
+        )
+    )
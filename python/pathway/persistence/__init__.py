@@ -1,6 +1,7 @@
 # Copyright © 2024 Pathway
 
 import contextlib
+import datetime
 import os
 import warnings
 from collections.abc import Generator
@@ -112,6 +113,17 @@ as they are used by S3 connectors.
             del os.environ["PATHWAY_PERSISTENT_STORAGE"]
 
 
+def _rewind_to_ms(rewind: dict[str, datetime.datetime]) -> dict[str, int]:
+    rewind_to_ms = {}
+    for name, time in rewind.items():
+        if time.tzinfo is None:
+            raise ValueError(
+                f"the time to rewind the source {name!r} to must be timezone-aware"
+            )
+        rewind_to_ms[name] = int(time.timestamp() * 1000)
+    return rewind_to_ms
+
+
 @dataclass(frozen=True)
 class Config:
     """
@@ -122,6 +134,21 @@ class Config:
         backend: persistence backend configuration;
         snapshot_interval_ms: the desired duration between snapshot updates in \
 milliseconds;
+        rewind: the sources to read again from a past processing time, given as \
+a mapping from the ``name`` of a source to the time. The persisted data such a \
+source read after this time is discarded and the source continues from the \
+position it had then, for example, from the Kafka offsets it had at this time. The \
+state of the computation is recomputed from the persisted data that remains, so it \
+is consistent with the rewound sources. The updates of the output connectors are \
+produced again for the data read again. The rewind is done on every run with this \
+configuration, so it should be removed after the run that rewinds. It is not \
+supported with the operator persistence.
+        handoff: whether the persistence location can be handed over between the \
+versions of the program, for example, during a blue/green deployment. A program \
+started while another one runs with the same location waits in standby. The running \
+program then stops reading, persists its final state and terminates, and the new one \
+restores this state and continues from where the old one has stopped. Both versions \
+must run with this option enabled and with the same number of processes.
     """
 
     backend: Backend
@@ -130,6 +157,8 @@ milliseconds;
     snapshot_access: api.SnapshotAccess = api.SnapshotAccess.FULL
     persistence_mode: api.PersistenceMode = api.PersistenceMode.PERSISTING
     continue_after_replay: bool = True
+    rewind: dict[str, datetime.datetime] | None = None
+    handoff: bool = False
 
     @classmethod
     def simple_config(
@@ -180,6 +209,8 @@ milliseconds;
             snapshot_access=self.snapshot_access,
             persistence_mode=self.persistence_mode,
             continue_after_replay=self.continue_after_replay,
+            rewind_to_ms=_rewind_to_ms(self.rewind or {}),
+            handoff=self.handoff,
         )
 
     def on_before_run(self):
@@ -0,0 +1,295 @@
+# Copyright © 2024 Pathway
+
+"""Resolving the credentials used by the connectors from secret stores.
+
+Instead of passing a password or an access key as plain text, pass a
+:py:class:`Secret` in its place. The connector fetches its value from the provider
+when it is created, so the credentials don't have to be present in the code or in
+the connection strings.
+
+The connectors get the values of the secrets once, when they are created, and keep
+using them until the program stops. They don't fetch the secrets again when they
+reconnect or when the credentials are rejected, so after a rotation of the
+credentials the program has to be restarted to pick up the new values.
+
+>>> import pathway as pw
+>>> rdkafka_settings = {
+...     "bootstrap.servers": "localhost:9092",
+...     "security.protocol": "sasl_ssl",
+...     "sasl.mechanism": "SCRAM-SHA-256",
+...     "sasl.username": "pathway",
+...     "sasl.password": pw.secrets.Secret("KAFKA_PASSWORD"),
+... }
+"""
+
+from __future__ import annotations
+
+import datetime
+import json
+import os
+import threading
+import time
+from abc import ABC, abstractmethod
+from typing import Any
+
+import boto3
+import requests
+
+
+class SecretNotFoundError(KeyError):
+    """Raised when the provider doesn't have the requested secret."""
+
+
+class SecretsProvider(ABC):
+    """Fetches the values of the secrets from a secret store."""
+
+    @abstractmethod
+    def fetch(self, name: str) -> str:
+        """Returns the current value of the secret.
+
+        Raises:
+            SecretNotFoundError: if there is no secret with this name.
+        """
+        ...
+
+
+def _split_field(name: str) -> tuple[str, str | None]:
+    path, separator, field = name.partition("#")
+    return path, (field if separator else None)
+
+
+def _select_field(name: str, value: str | dict, field: str | None) -> str:
+    if field is None:
+        if isinstance(value, dict):
+            raise ValueError(
+                f"secret {name!r} has several fields, select one with '<name>#<field>'"
+            )
+        return value
+    if isinstance(value, str):
+        try:
+            value = json.loads(value)
+        except json.JSONDecodeError:
+            raise ValueError(f"secret {name!r} is not a JSON object") from None
+    if not isinstance(value, dict) or field not in value:
+        raise SecretNotFoundError(f"secret {name!r} has no field {field!r}")
+    return str(value[field])
+
+
+class EnvSecretsProvider(SecretsProvider):
+    """Reads the secrets from the environment variables.
+
+    Args:
+        prefix: The prefix prepended to the name of the secret to get the name of the
+            environment variable.
+    """
+
+    def __init__(self, prefix: str = ""):
+        self.prefix = prefix
+
+    def fetch(self, name: str) -> str:
+        variable = self.prefix + name
+        value = os.environ.get(variable)
+        if value is None:
+            raise SecretNotFoundError(f"environment variable {variable!r} is not set")
+        return value
+
+
+class FileSecretsProvider(SecretsProvider):
+    """Reads the secrets from the files in a directory, one secret per file, like the
+    secrets mounted by Kubernetes or Docker. The trailing newline is stripped.
+
+    Args:
+        directory: The directory with the secret files.
+    """
+
+    def __init__(self, directory: str | os.PathLike[str] = "/run/secrets"):
+        self.directory = directory
+
+    def fetch(self, name: str) -> str:
+        path = os.path.join(self.directory, name)
+        try:
+            with open(path) as f:
+                return f.read().rstrip("\n")
+        except FileNotFoundError:
+            raise SecretNotFoundError(f"secret file {path!r} doesn't exist") from None
+
+
+class AwsSecretsManagerProvider(SecretsProvider):
+    """Fetches the secrets from AWS Secrets Manager. The credentials are looked up by
+    ``boto3`` in the usual places. A single field of a secret stored as a JSON object
+    is selected with ``<secret id>#<field>``.
+
+    Args:
+        region: The region of the secrets. If None, the default region of the
+            AWS configuration is used.
+        client: The ``boto3`` Secrets Manager client to use instead of creating one.
+    """
+
+    def __init__(self, region: str | None = None, *, client: Any = None):
+        self.region = region
+        self._client = client
+
+    def _get_client(self) -> Any:
+        if self._client is None:
+            self._client = boto3.client("secretsmanager", region_name=self.region)
+        return self._client
+
+    def fetch(self, name: str) -> str:
+        secret_id, field = _split_field(name)
+        client = self._get_client()
+        try:
+            response = client.get_secret_value(SecretId=secret_id)
+        except client.exceptions.ResourceNotFoundException:
+            raise SecretNotFoundError(
+                f"secret {secret_id!r} doesn't exist in AWS Secrets Manager"
+            ) from None
+        return _select_field(name, response["SecretString"], field)
+
+
+class VaultSecretsProvider(SecretsProvider):
+    """Fetches the secrets from the KV version 2 secrets engine of HashiCorp Vault.
+    The secrets are referred to as ``<path>#<field>``, the field can be omitted if the
+    secret has only one.
+
+    Args:
+        url: The address of the Vault server, e.g. ``https://vault.example.com:8200``.
+            If None, the ``VAULT_ADDR`` environment variable is used.
+        token: The token used to authenticate. If None, the ``VAULT_TOKEN`` environment
+            variable is used.
+        mount: The mount path of the secrets engine.
+        namespace: The Vault Enterprise namespace of the secrets.
+        timeout: The timeout of the requests to the server, in seconds.
+    """
+
+    def __init__(
+        self,
+        url: str | None = None,
+        *,
+        token: str | None = None,
+        mount: str = "secret",
+        namespace: str | None = None,
+        timeout: float = 10.0,
+    ):
+        url = url or os.environ.get("VAULT_ADDR")
+        if url is None:
+            raise ValueError("the Vault address is not set")
+        self.url = url.rstrip("/")
+        self.token = token or os.environ.get("VAULT_TOKEN")
+        self.mount = mount.strip("/")
+        self.namespace = namespace
+        self.timeout = timeout
+
+    def fetch(self, name: str) -> str:
+        path, field = _split_field(name)
+        headers = {}
+        if self.token is not None:
+            headers["X-Vault-Token"] = self.token
+        if self.namespace is not None:
+            headers["X-Vault-Namespace"] = self.namespace
+        response = requests.get(
+            f"{self.url}/v1/{self.mount}/data/{path.strip('/')}",
+            headers=headers,
+            timeout=self.timeout,
+        )
+        if response.status_code == 404:
+            raise SecretNotFoundError(f"secret {path!r} doesn't exist in Vault")
+        response.raise_for_status()
+        data = response.json()["data"]["data"]
+        if field is None and len(data) == 1:
+            return str(next(iter(data.values())))
+        return _select_field(name, data, field)
+
+
+_default_provider: SecretsProvider = EnvSecretsProvider()
+
+
+def set_default_provider(provider: SecretsProvider) -> None:
+    """Sets the provider used by the secrets created without one.
+    By default, the secrets are read from the environment variables.
+    """
+    global _default_provider
+    _default_provider = provider
+
+
+class Secret:
+    """A credential fetched from a secret store when a connector needs it, which can be
+    passed instead of a plain-text value, e.g. of a password in the connection
+    settings of a connector.
+
+    The value is fetched only when it is used for the first time, and is fetched
+    again when it is used after ``refresh_interval``, so that rotated credentials are
+    picked up by the connectors created later. A connector that is already running
+    keeps the value it got when it was created, also when it reconnects, so a
+    rotation of its credentials requires restarting the program. The value is never
+    included in the representation of the object, so it doesn't end up in the logs.
+
+    Args:
+        name: The name of the secret in the provider.
+        provider: The provider of the secret. If None, the default provider, set
+            with :py:func:`set_default_provider`, is used.
+        refresh_interval: How long the fetched value is used by the new connectors
+            before it is fetched again. If None, it is fetched only once.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        *,
+        provider: SecretsProvider | None = None,
+        refresh_interval: datetime.timedelta | None = None,
+    ):
+        self.name = name
+        self.provider = provider
+        self.refresh_interval = refresh_interval
+        self._value: str | None = None
+        self._fetched_at: float | None = None
+        self._lock = threading.Lock()
+
+    def _is_stale(self) -> bool:
+        if self._fetched_at is None:
+            return True
+        if self.refresh_interval is None:
+            return False
+        elapsed = time.monotonic() - self._fetched_at
+        return elapsed >= self.refresh_interval.total_seconds()
+
+    def get(self) -> str:
+        """Returns the value of the secret, fetching it if needed."""
+        with self._lock:
+            if self._is_stale():
+                provider = self.provider or _default_provider
+                self._value = provider.fetch(self.name)
+                self._fetched_at = time.monotonic()
+            assert self._value is not None
+            return self._value
+
+    def __repr__(self) -> str:
+        return f"Secret({self.name!r})"
+
+    __str__ = __repr__
+
+
+def resolve(value: Any) -> Any:
+    """Replaces the secrets with their values, also inside dicts, lists and tuples."""
+    if isinstance(value, Secret):
+        return value.get()
+    if isinstance(value, dict):
+        return {key: resolve(item) for key, item in value.items()}
+    if isinstance(value, list):
+        return [resolve(item) for item in value]
+    if isinstance(value, tuple):
+        return tuple(resolve(item) for item in value)
+    return value
+
+
+__all__ = [
+    "AwsSecretsManagerProvider",
+    "EnvSecretsProvider",
+    "FileSecretsProvider",
+    "Secret",
+    "SecretNotFoundError",
+    "SecretsProvider",
+    "VaultSecretsProvider",
+    "resolve",
+    "set_default_provider",
+]
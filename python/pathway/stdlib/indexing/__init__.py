@@ -8,6 +8,7 @@ from pathway.engine import BruteForceKnnMetricKind, USearchMetricKind
 
 from .bm25 import TantivyBM25, TantivyBM25Factory
 from .data_index import DataIndex
+from .expiration import expire_entries
 from .full_text_document_index import default_full_text_document_index
 from .hybrid_index import HybridIndex, HybridIndexFactory
 from .nearest_neighbors import (
@@ -19,6 +20,7 @@ from .nearest_neighbors import (
     USearchKnn,
     UsearchKnnFactory,
 )
+from .reranking import RerankingHook
 from .retrievers import AbstractRetrieverFactory
 from .vector_document_index import (
     default_brute_force_knn_document_index,
@@ -49,4 +51,6 @@ __all__ = [
     "default_usearch_knn_document_index",
     "default_brute_force_knn_document_index",
     "default_full_text_document_index",
+    "expire_entries",
+    "RerankingHook",
 ]
@@ -17,6 +17,7 @@ from pathway.stdlib.indexing.colnames import (
     _SCORE,
     _TOPK,
 )
+from pathway.stdlib.indexing.reranking import RerankingHook
 from pathway.stdlib.temporal._asof_now_join import AsofNowJoinResult
 from pathway.stdlib.utils.col import unpack_col
 
@@ -94,6 +95,8 @@ def _extract_data_collapsed_rows(
     query_table: pw.Table,
     join_general: GeneralJoin[T],
     as_of_now: bool = False,
+    query_column: pw.ColumnReference | None = None,
+    reranking: RerankingHook | None = None,
 ) -> T:
     """
     This function takes `query_table` and left-joins it with `data_table`, using
@@ -112,6 +115,8 @@ def _extract_data_collapsed_rows(
         it is computed using a table with the same universe as `data_table`,
         we need to use asof_join to extract the results, to avoid joining outdated
         IDs with current state of the data_table)
+    query_column: column of `query_table` with the queries, passed to `reranking`
+    reranking: optional hook re-scoring the matched data entries of each query
 
     Returns:
     JoinResult, with one row corresponding to one query, all matched data entries are
@@ -169,6 +174,30 @@ def _extract_data_collapsed_rows(
         )
     )
 
+    if reranking is not None:
+        assert query_column is not None
+        if reranking.column not in data_table.keys():
+            raise ValueError(
+                f"reranking column {reranking.column!r} is not present in the data table"
+            )
+        rerank = reranking._make_udf(list(data_table.keys()).index(reranking.column))
+        selected_data = (
+            selected_data.join(
+                query_table, selected_data.id == query_table.id, id=selected_data.id
+            )
+            .select(
+                _pw_reranked=rerank(
+                    pw.right[query_column.name], pw.left[_TOPK], pw.left[_SCORE]
+                )
+            )
+            .select(
+                **{
+                    _TOPK: pw.this._pw_reranked[0],
+                    _SCORE: pw.this._pw_reranked[1],
+                }
+            )
+        )
+
     @pw.udf(deterministic=True)
     def transpose(x: tuple) -> tuple:
         if x:
@@ -286,15 +315,18 @@ class DataIndex:
         inner_index (InnerIndex): a data structure that accepts data from some ``data_column``
             and for each query answers with a list of IDs, one ID per matched row from ``data_column``.
             The IDs are taken from the table that contains the ``data_column`` column
+        reranking (RerankingHook | None): optional hook re-scoring the matched rows of each
+            query before they are attached to it. Only supported with ``collapse_rows=True``.
     """
 
     data_table: pw.Table
     inner_index: InnerIndex
+    reranking: RerankingHook | None = None
 
     def _repack_results(
         self,
         raw_result: pw.Table,
-        query_table: pw.Table,
+        query_column: pw.ColumnReference,
         collapse_rows: bool,
         join_general: GeneralJoin[T],
         as_of_now: bool,
@@ -313,9 +345,8 @@ class DataIndex:
             raw_results (pw.Table[pw.Tuple[pw.Pointer, float]]):
                 matching between identifiers of queries and identifiers of items stored
                 in the index.
-            query_table (pw.Table):
-                table with queries - contains all columns from the query table,
-                passes them to the output
+            query_column (pw.ColumnReference):
+                column with queries - all columns from its table are passed to the output
             collapse_rows (bool):
                 defines output format; if set to true, each query ID has one
                 corresponding output row, containing lists with relevant responses
@@ -334,16 +365,25 @@ class DataIndex:
         unpacked_results += unpack_col(
             flattened_ret[_INDEX_REPLY], schema=IdScoreSchema
         )
-        extract_data = (
-            _extract_data_collapsed_rows if collapse_rows else _extract_data_flat
-        )
-
-        return extract_data(
+        if not collapse_rows:
+            if self.reranking is not None:
+                raise ValueError("reranking is supported only with collapse_rows=True")
+            return _extract_data_flat(
+                self.data_table,
+                unpacked_results.without(_INDEX_REPLY),
+                query_column.table,
+                join_general,
+                as_of_now=as_of_now,
+            )
+
+        return _extract_data_collapsed_rows(
             self.data_table,
             unpacked_results.without(_INDEX_REPLY),
-            query_table,
+            query_column.table,
             join_general,
             as_of_now=as_of_now,
+            query_column=query_column,
+            reranking=self.reranking,
         )
 
     def query(
@@ -403,7 +443,7 @@ class DataIndex:
         )
         return self._repack_results(
             raw_results,
-            query_column.table,
+            query_column,
             collapse_rows,
             JoinResult._table_join,
             as_of_now=False,
@@ -466,7 +506,7 @@ class DataIndex:
         )
         return self._repack_results(
             raw_results,
-            query_column.table,
+            query_column,
             collapse_rows,
             AsofNowJoinResult._asof_now_join,
             as_of_now=True,
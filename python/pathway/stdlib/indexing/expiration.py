@@ -0,0 +1,61 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import pathway.internals as pw
+from pathway.internals import expression as expr
+from pathway.internals.trace import trace_user_frame
+from pathway.stdlib.temporal.utils import IntervalType
+
+
+@trace_user_frame
+def expire_entries(
+    table: pw.Table,
+    time_column: expr.ColumnExpression,
+    *,
+    ttl: IntervalType | None = None,
+    expiration_column: expr.ColumnExpression | None = None,
+) -> pw.Table:
+    """Removes entries of a table that is going to be indexed once they expire, so that the
+    upstream table doesn't have to produce explicit deletions.
+
+    The current time is the maximum over all ``time_column`` values seen so far. An entry
+    expires when the current time reaches its time plus ``ttl`` or, if ``expiration_column``
+    is given instead, the value in this column. Expired entries are removed from any index
+    built on the returned table, and the answers computed with ``DataIndex.query`` are
+    updated accordingly. Answers computed with ``query_as_of_now`` are not changed.
+
+    Args:
+        table: table with the entries to be indexed.
+        time_column: ``ColumnExpression`` that specifies the event time of the entries.
+        ttl: time for which the entries are kept. Should match the type of the
+            ``time_column`` (``int -> int``, ``float -> float``,
+            ``datetime -> timedelta``).
+        expiration_column: ``ColumnExpression`` with the time at which each entry expires.
+            Exactly one of ``ttl`` and ``expiration_column`` has to be given.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> docs = pw.debug.table_from_markdown(
+    ...     '''
+    ...     doc   | t  | __time__
+    ...     apple | 1  |    2
+    ...     pear  | 4  |    4
+    ...     plum  | 12 |    6
+    ... '''
+    ... )
+    >>> fresh_docs = pw.indexing.expire_entries(docs, docs.t, ttl=10)
+    >>> pw.debug.compute_and_print(fresh_docs, include_id=False)
+    doc  | t
+    pear | 4
+    plum | 12
+
+    The entry ``apple`` expires at the processing time 6, when the current time becomes 12.
+    """
+    if (ttl is None) == (expiration_column is None):
+        raise ValueError("exactly one of ttl and expiration_column has to be given")
+    if ttl is not None:
+        return table.forget(time_column, ttl)
+    assert expiration_column is not None
+    return table._forget(expiration_column, time_column, mark_forgetting_records=False)
@@ -2,7 +2,7 @@
 
 import dataclasses
 from dataclasses import dataclass, field
-from typing import Callable, Tuple
+from typing import Callable, Literal, Tuple
 
 import pathway.internals as pw
 from pathway.engine import (
@@ -178,14 +178,25 @@ class BruteForceKnn(InnerIndex):
         dimensions (int): number of dimensions of vectors that are used by the index and
             queries
         reserved_space (int): initial capacity (in the number of entries) of the index
-        auxiliary_space (int): auxiliary space (in the number of entries), the maximum
-            number of distances that are stored in memory, while evaluating queries, in
-            case ``auxiliary_space`` is set to a value smaller than the current number
-            of entries in the index, it is still proportional to
-            the size of the index (the value given in this parameter is ignored)
+        auxiliary_space (int): auxiliary space (in the number of entries), the number
+            of distances computed for a block of entries before moving to the next one
+            while evaluating queries; blocks that fit in the CPU cache are scanned
+            fastest
         metric (BruteForceKnnMetricKind): metric kind that is used to determine distance
         embedder: :py:class:`~pathway.UDF` used for calculating embeddings of string. It is needed, if index
             is used for indexing texts.
+        parallel_chunk_size (int | None): if set, indices with more entries are scanned
+            in parallel, in chunks of this size. Queries with metadata filters are
+            always answered with a sequential scan.
+        quantization (str | None): if set to ``"int8"`` or ``"pq"``, vectors are stored
+            compressed with scalar or product quantization, using several times less
+            memory at the cost of approximate distances.
+        rerank (bool): whether to keep the original vectors, alongside the compressed
+            ones, to compute exact distances for the final results. Only used with
+            ``quantization``.
+        gpu_device (int | None): if set, vectors are kept on the CUDA device with this
+            ordinal, which computes the distances. Requires Pathway built with the
+            ``cuda`` feature and can't be combined with ``quantization``.
 
     """
 
@@ -194,6 +205,10 @@ class BruteForceKnn(InnerIndex):
     auxiliary_space: int = 1024 * 128
     metric: BruteForceKnnMetricKind
     embedder: pw.UDF | None = None
+    parallel_chunk_size: int | None = None
+    quantization: Literal["int8", "pq"] | None = None
+    rerank: bool = True
+    gpu_device: int | None = None
 
     # data column after applying embeddings. It is calculated during initialization and
     # cannot be set in the constructor.
@@ -239,6 +254,10 @@ class BruteForceKnn(InnerIndex):
             reserved_space=self.reserved_space,
             auxiliary_space=self.auxiliary_space,
             metric=self.metric,
+            parallel_chunk_size=self.parallel_chunk_size,
+            quantization=self.quantization,
+            rerank=self.rerank,
+            gpu_device=self.gpu_device,
         )
 
         number_of_matches_ref = number_of_matches
@@ -491,11 +510,10 @@ class BruteForceKnnFactory(KnnIndexFactory):
         dimensions (int): number of dimensions of vectors that are used by the index and
             queries. This is only needed if the `embedder` is not provided.
         reserved_space (int): initial capacity (in the number of entries) of the index
-        auxiliary_space (int): auxiliary space (in the number of entries), the maximum
-            number of distances that are stored in memory, while evaluating queries, in
-            case ``auxiliary_space`` is set to a value smaller than the current number
-            of entries in the index, it is still proportional to
-            the size of the index (the value given in this parameter is ignored)
+        auxiliary_space (int): auxiliary space (in the number of entries), the number
+            of distances computed for a block of entries before moving to the next one
+            while evaluating queries; blocks that fit in the CPU cache are scanned
+            fastest
         metric (BruteForceKnnMetricKind): metric kind that is used to determine distance.
             Defaults to cosine similarity.
         embedder: :py:class:`~pathway.UDF` used for calculating embeddings of string. It is needed, if index
@@ -0,0 +1,63 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+from collections.abc import Awaitable, Callable
+from dataclasses import dataclass
+from typing import Any
+
+import pathway.internals as pw
+from pathway.internals import udfs
+
+
+@dataclass(frozen=True)
+class RerankingHook:
+    """
+    Re-scores the entries retrieved by an index before they are attached to the queries,
+    e.g. with a cross-encoder service. Can be passed as ``reranking`` argument of
+    :py:class:`~pathway.stdlib.indexing.DataIndex`.
+
+    Args:
+        scorer: asynchronous function that gets the query and the list of values of
+            ``column`` in the retrieved rows and returns a list of scores, one per row.
+            Rows with higher scores are returned first.
+        column: name of the column of the indexed data table passed to ``scorer``.
+        max_concurrency: maximum number of ``scorer`` calls running at the same time.
+            Defaults to ``None``, indicating no limit.
+        top_n: number of best rows kept after reranking. Defaults to ``None``, which keeps
+            all retrieved rows, so it's useful to retrieve more rows than needed and keep
+            only the best ones after reranking.
+        timeout: maximum time (in seconds) to wait for ``scorer``.
+    """
+
+    scorer: Callable[[Any, list[Any]], Awaitable[list[float]]]
+    column: str
+    max_concurrency: int | None = None
+    top_n: int | None = None
+    timeout: float | None = None
+
+    def _make_udf(self, column_position: int) -> pw.UDF:
+        scorer = self.scorer
+        top_n = self.top_n
+
+        @pw.udf(
+            executor=udfs.async_executor(
+                capacity=self.max_concurrency, timeout=self.timeout
+            )
+        )
+        async def rerank(query: Any, rows: tuple, scores: tuple) -> tuple:
+            if not rows:
+                return (rows, scores)
+            new_scores = await scorer(query, [row[column_position] for row in rows])
+            if len(new_scores) != len(rows):
+                raise ValueError(
+                    f"reranking scorer returned {len(new_scores)} scores"
+                    f" for {len(rows)} rows"
+                )
+            order = sorted(range(len(rows)), key=lambda i: -new_scores[i])[:top_n]
+            return (
+                tuple(rows[i] for i in order),
+                tuple(float(new_scores[i]) for i in order),
+            )
+
+        return rerank
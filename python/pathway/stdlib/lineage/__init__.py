@@ -0,0 +1,13 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+from .tracking import LINEAGE_COLUMN, collect, merge, records, track
+
+__all__ = [
+    "LINEAGE_COLUMN",
+    "collect",
+    "merge",
+    "records",
+    "track",
+]
@@ -0,0 +1,153 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import json
+from typing import Any
+
+import pathway as pw
+
+LINEAGE_COLUMN = "_lineage"
+
+# The fields of the ``_metadata`` column identifying the position of a row in its source
+_POSITION_FIELDS = ("path", "topic", "partition", "offset")
+
+
+def _record(connector: str, row_id: pw.Pointer, metadata: pw.Json | None) -> pw.Json:
+    record: dict[str, Any] = {"connector": connector, "id": str(row_id)}
+    metadata_value = metadata.value if metadata is not None else None
+    if isinstance(metadata_value, dict):
+        for field in _POSITION_FIELDS:
+            if metadata_value.get(field) is not None:
+                record[field] = metadata_value[field]
+        if metadata_value.get("seen_at") is not None:
+            record["ingested_at"] = metadata_value["seen_at"]
+        elif metadata_value.get("timestamp_millis") is not None:
+            record["ingested_at"] = metadata_value["timestamp_millis"] / 1000
+    return pw.Json([record])
+
+
+def _merge_records(*lineages: pw.Json | None) -> pw.Json:
+    # The records are deduplicated, so that a row joined with itself or combined from
+    # the rows of the same origin refers to each input record once
+    seen: set[str] = set()
+    records = []
+    for lineage in lineages:
+        if lineage is None:
+            continue
+        for record in lineage.as_list():
+            serialized = json.dumps(record, sort_keys=True)
+            if serialized not in seen:
+                seen.add(serialized)
+                records.append(record)
+    return pw.Json(records)
+
+
+def track(table: pw.Table, *, connector: str) -> pw.Table:
+    """Starts tracking the lineage of the rows of ``table``, which is usually the
+    output of an input connector.
+
+    The returned table has an additional ``_lineage`` column, holding a JSON list with
+    a single record describing the origin of the row: the ``connector`` name, the
+    ``id`` of the input row and, if ``table`` has the ``_metadata`` column, i.e. it was
+    read with ``with_metadata=True``, its position in the source, such as the ``path``
+    of the file or the ``topic``, ``partition`` and ``offset`` of the Kafka message,
+    along with the time it was ingested at, ``ingested_at``, in seconds since the Unix
+    epoch.
+
+    The ``_lineage`` column is carried over by the operators keeping the columns of the
+    table, such as ``filter`` or ``select(*pw.this, ...)``. When the rows are combined,
+    the lineages are combined with :py:func:`merge` in joins and with
+    :py:func:`collect` in reductions, so that the lineage of each output row lists all
+    the input records it was computed from.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> orders = pw.debug.table_from_markdown('''
+    ...   | user | amount
+    ... 1 | 10   | 3
+    ... 2 | 10   | 5
+    ... ''')
+    >>> orders = pw.lineage.track(orders, connector="orders")
+    >>> totals = orders.groupby(pw.this.user).reduce(
+    ...     pw.this.user,
+    ...     total=pw.reducers.sum(pw.this.amount),
+    ...     _lineage=pw.lineage.collect(pw.this._lineage),
+    ... )
+    >>> records = pw.lineage.records(totals).select(pw.this.connector)
+    >>> pw.debug.compute_and_print(records, include_id=False)
+    connector
+    orders
+    orders
+    """
+    if LINEAGE_COLUMN in table.column_names():
+        raise ValueError(f"The lineage of table {table} is already tracked")
+    metadata = table["_metadata"] if "_metadata" in table.column_names() else None
+    return table.with_columns(
+        _lineage=pw.apply_with_type(_record, pw.Json, connector, table.id, metadata)
+    )
+
+
+def merge(*lineages: pw.ColumnExpression) -> pw.ColumnExpression:
+    """Combines the lineages of the rows joined into a single row. The missing
+    lineages, as in the unmatched rows of the outer joins, are skipped.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> orders = pw.lineage.track(pw.debug.table_from_markdown('''
+    ... user | amount
+    ... 10   | 3
+    ... '''), connector="orders")
+    >>> users = pw.lineage.track(pw.debug.table_from_markdown('''
+    ... user | name
+    ... 10   | Alice
+    ... '''), connector="users")
+    >>> joined = orders.join(users, orders.user == users.user).select(
+    ...     users.name,
+    ...     orders.amount,
+    ...     _lineage=pw.lineage.merge(orders._lineage, users._lineage),
+    ... )
+    >>> result = joined.select(
+    ...     pw.this.name, sources=pw.apply_with_type(len, int, pw.this._lineage)
+    ... )
+    >>> pw.debug.compute_and_print(result, include_id=False)
+    name  | sources
+    Alice | 2
+    """
+    return pw.apply_with_type(_merge_records, pw.Json, *lineages)
+
+
+def collect(lineage: pw.ColumnExpression) -> pw.ColumnExpression:
+    """A reducer combining the lineages of all the rows of a group."""
+    return pw.apply_with_type(
+        lambda lineages: _merge_records(*lineages),
+        pw.Json,
+        pw.reducers.tuple(lineage),
+    )
+
+
+def records(table: pw.Table) -> pw.Table:
+    """Lists the input records each row of ``table`` was computed from.
+
+    The result has one row per pair of a row of ``table`` and an input record in its
+    lineage, with the columns ``output_id``, the id of the row of ``table``,
+    ``connector``, the name of the connector the record comes from, and ``record``,
+    the whole record as JSON.
+    """
+    if LINEAGE_COLUMN not in table.column_names():
+        raise ValueError(f"The lineage of table {table} is not tracked")
+    exploded = table.select(
+        output_id=table.id,
+        record=pw.apply_with_type(
+            lambda lineage: tuple(lineage), tuple[pw.Json, ...], table[LINEAGE_COLUMN]
+        ),
+    ).flatten(pw.this.record)
+    return exploded.select(
+        pw.this.output_id,
+        connector=pw.apply_with_type(
+            lambda record: record["connector"].as_str(), str, pw.this.record
+        ),
+        record=pw.this.record,
+    )
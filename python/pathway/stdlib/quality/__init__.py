@@ -0,0 +1,25 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+from .constraints import (
+    CheckResult,
+    Constraint,
+    check,
+    in_range,
+    matches,
+    not_null,
+    references,
+    unique,
+)
+
+__all__ = [
+    "CheckResult",
+    "Constraint",
+    "check",
+    "in_range",
+    "matches",
+    "not_null",
+    "references",
+    "unique",
+]
@@ -0,0 +1,361 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import functools
+import operator
+import re
+from abc import ABC, abstractmethod
+from dataclasses import dataclass
+from typing import Any, Literal
+
+import pathway as pw
+from pathway.internals import api
+
+ColumnName = str | pw.ColumnReference
+
+
+def _column_name(column: ColumnName) -> str:
+    return column.name if isinstance(column, pw.ColumnReference) else column
+
+
+class Constraint(ABC):
+    """A condition that every row of a table should satisfy. The constraints are
+    created with :py:func:`not_null`, :py:func:`unique`, :py:func:`in_range`,
+    :py:func:`matches` and :py:func:`references`, and checked with :py:func:`check`.
+    """
+
+    name: str
+
+    def __init__(self, name: str) -> None:
+        self.name = name
+
+    @abstractmethod
+    def _violations(self, table: pw.Table) -> pw.Table:
+        """Returns the rows of ``table`` violating the constraint, with the same ids
+        and a single column ``details`` describing the violation."""
+
+
+class _NotNull(Constraint):
+    def __init__(self, column: str, name: str) -> None:
+        super().__init__(name)
+        self._column = column
+
+    def _violations(self, table: pw.Table) -> pw.Table:
+        violating = table.filter(table[self._column].is_none())
+        return violating.select(details="value is missing")
+
+
+class _Unique(Constraint):
+    def __init__(self, columns: list[str], name: str) -> None:
+        super().__init__(name)
+        self._columns = columns
+
+    def _violations(self, table: pw.Table) -> pw.Table:
+        # As in SQL, the rows with a missing value in the key are not compared
+        is_complete = functools.reduce(
+            operator.and_, (table[column].is_not_none() for column in self._columns)
+        )
+        complete = table.filter(is_complete)
+        counts = complete.groupby(
+            *(complete[column] for column in self._columns)
+        ).reduce(
+            *(pw.this[column] for column in self._columns),
+            _quality_count=pw.reducers.count(),
+        )
+        duplicated = counts.filter(counts._quality_count > 1)
+        violating = complete.join(
+            duplicated,
+            *(complete[column] == duplicated[column] for column in self._columns),
+            id=complete.id,
+        ).select(*(complete[column] for column in self._columns))
+
+        def describe(*values: Any) -> str:
+            if len(values) == 1:
+                return f"value {values[0]!r} is not unique"
+            return f"values {values!r} are not unique"
+
+        return violating.select(
+            details=pw.apply_with_type(
+                describe, str, *(violating[column] for column in self._columns)
+            )
+        )
+
+
+class _InRange(Constraint):
+    def __init__(
+        self, column: str, min: Any | None, max: Any | None, name: str
+    ) -> None:
+        super().__init__(name)
+        self._column = column
+        self._min = min
+        self._max = max
+
+    def _violations(self, table: pw.Table) -> pw.Table:
+        lower, upper = self._min, self._max
+
+        def is_out_of_range(value: Any) -> bool:
+            if value is None:
+                return False
+            return (lower is not None and value < lower) or (
+                upper is not None and value > upper
+            )
+
+        bounds = []
+        if lower is not None:
+            bounds.append(f">= {lower!r}")
+        if upper is not None:
+            bounds.append(f"<= {upper!r}")
+        expected = " and ".join(bounds)
+
+        violating = table.filter(
+            pw.apply_with_type(is_out_of_range, bool, table[self._column])
+        )
+        return violating.select(
+            details=pw.apply_with_type(
+                lambda value: f"value {value!r} is not {expected}",
+                str,
+                violating[self._column],
+            )
+        )
+
+
+class _Matches(Constraint):
+    def __init__(self, column: str, pattern: str, name: str) -> None:
+        super().__init__(name)
+        self._column = column
+        self._pattern = re.compile(pattern)
+
+    def _violations(self, table: pw.Table) -> pw.Table:
+        pattern = self._pattern
+
+        def is_mismatched(value: Any) -> bool:
+            return value is not None and pattern.fullmatch(str(value)) is None
+
+        violating = table.filter(
+            pw.apply_with_type(is_mismatched, bool, table[self._column])
+        )
+        return violating.select(
+            details=pw.apply_with_type(
+                lambda value: f"value {value!r} doesn't match {pattern.pattern!r}",
+                str,
+                violating[self._column],
+            )
+        )
+
+
+class _References(Constraint):
+    def __init__(self, column: str, target: pw.ColumnReference, name: str) -> None:
+        super().__init__(name)
+        self._column = column
+        self._target = target
+
+    def _violations(self, table: pw.Table) -> pw.Table:
+        target_table = self._target.table
+        target_values = (
+            target_table.filter(self._target.is_not_none())
+            .select(value=pw.unwrap(pw.this[self._target.name]))
+            .groupby(pw.this.value)
+            .reduce(pw.this.value)
+        )
+        values = table.filter(table[self._column].is_not_none()).select(
+            value=pw.unwrap(pw.this[self._column])
+        )
+        lookups = values.join_left(
+            target_values, values.value == target_values.value, id=values.id
+        ).select(
+            value=pw.left.value,
+            is_found=pw.right.value.is_not_none(),
+        )
+        violating = lookups.filter(~lookups.is_found)
+        target_name = self._target.name
+        return violating.select(
+            details=pw.apply_with_type(
+                lambda value: f"value {value!r} not found in column {target_name!r}",
+                str,
+                violating.value,
+            )
+        )
+
+
+def not_null(column: ColumnName, *, name: str | None = None) -> Constraint:
+    """Requires the values of the ``column`` to be present, i.e. not ``None``.
+
+    Args:
+        column: the checked column, or its name.
+        name: the name of the constraint, used in the violations and in the metrics.
+            Defaults to ``not_null(<column>)``.
+    """
+    column = _column_name(column)
+    return _NotNull(column, name or f"not_null({column})")
+
+
+def unique(*columns: ColumnName, name: str | None = None) -> Constraint:
+    """Requires the combinations of the values of the ``columns`` to be unique. All
+    the rows sharing a combination violate the constraint. The rows in which any of
+    the ``columns`` is ``None`` are not checked.
+
+    Args:
+        columns: the columns forming the key, or their names.
+        name: the name of the constraint, used in the violations and in the metrics.
+            Defaults to ``unique(<columns>)``.
+    """
+    if not columns:
+        raise ValueError("unique constraint requires at least one column")
+    names = [_column_name(column) for column in columns]
+    return _Unique(names, name or f"unique({', '.join(names)})")
+
+
+def in_range(
+    column: ColumnName,
+    *,
+    min: Any | None = None,
+    max: Any | None = None,
+    name: str | None = None,
+) -> Constraint:
+    """Requires the values of the ``column`` to lie between ``min`` and ``max``,
+    inclusive. The missing values satisfy the constraint.
+
+    Args:
+        column: the checked column, or its name.
+        min: the lowest allowed value. No lower bound if ``None``.
+        max: the highest allowed value. No upper bound if ``None``.
+        name: the name of the constraint, used in the violations and in the metrics.
+            Defaults to ``in_range(<column>)``.
+    """
+    if min is None and max is None:
+        raise ValueError("range constraint requires at least one bound")
+    column = _column_name(column)
+    return _InRange(column, min, max, name or f"in_range({column})")
+
+
+def matches(column: ColumnName, pattern: str, *, name: str | None = None) -> Constraint:
+    """Requires the values of the ``column`` to match the regular expression
+    ``pattern`` in full. The values other than strings are matched in their textual
+    representation. The missing values satisfy the constraint.
+
+    Args:
+        column: the checked column, or its name.
+        pattern: the regular expression, in the syntax of the Python ``re`` module.
+        name: the name of the constraint, used in the violations and in the metrics.
+            Defaults to ``matches(<column>)``.
+    """
+    column = _column_name(column)
+    return _Matches(column, pattern, name or f"matches({column})")
+
+
+def references(
+    column: ColumnName, target: pw.ColumnReference, *, name: str | None = None
+) -> Constraint:
+    """Requires each value of the ``column`` to be present in the ``target`` column
+    of another table, like a foreign key. The check is incremental, so a row starts
+    violating the constraint when the referenced value is removed from the target
+    table, and stops violating it when the value appears there. The missing values
+    satisfy the constraint.
+
+    Args:
+        column: the checked column, or its name.
+        target: the referenced column of the other table.
+        name: the name of the constraint, used in the violations and in the metrics.
+            Defaults to ``references(<column>)``.
+    """
+    column = _column_name(column)
+    return _References(column, target, name or f"references({column})")
+
+
+@dataclass(frozen=True)
+class CheckResult:
+    """The result of :py:func:`check`."""
+
+    #: The checked table, without the violating rows if ``on_violation="drop"``.
+    table: pw.Table
+    #: The violations, with the columns ``constraint``, the name of the violated
+    #: constraint, ``row``, the id of the violating row, and ``details``.
+    violations: pw.Table
+
+
+def check(
+    table: pw.Table,
+    *constraints: Constraint,
+    name: str = "default",
+    on_violation: Literal["keep", "drop"] = "keep",
+) -> CheckResult:
+    """Checks the rows of the ``table`` against the ``constraints``.
+
+    The constraints are checked incrementally, as a part of the computation, so a row
+    appears in the violations as soon as it starts violating a constraint, and is
+    removed from them once it is updated, deleted, or, for :py:func:`unique` and
+    :py:func:`references`, other rows make it valid.
+
+    The number of rows violating each constraint at the moment, and the number of all
+    the violations observed, are exposed with the other metrics of the monitoring
+    HTTP server as ``constraint_<name>_<constraint>_violations`` and
+    ``constraint_<name>_<constraint>_violations_observed``.
+
+    Args:
+        table: the checked table.
+        constraints: the constraints the rows should satisfy. Their names must be
+            distinct.
+        name: the name of the check, distinguishing its metrics from the ones of the
+            other checks.
+        on_violation: what to do with the violating rows in the resulting table.
+            ``"keep"`` leaves the table as it is, ``"drop"`` removes the rows violating
+            any of the constraints.
+
+    Returns:
+        The checked table and the table of the violations.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> users = pw.debug.table_from_markdown('''
+    ...   | email             | age
+    ... 1 | alice@pathway.com | 34
+    ... 2 | bob               | 27
+    ... 3 | carol@pathway.com | 210
+    ... ''')
+    >>> result = pw.quality.check(
+    ...     users,
+    ...     pw.quality.unique(users.email),
+    ...     pw.quality.matches(users.email, r"[^@]+@[^@]+"),
+    ...     pw.quality.in_range(users.age, min=0, max=150),
+    ...     on_violation="drop",
+    ... )
+    >>> pw.debug.compute_and_print(result.table, include_id=False)
+    email             | age
+    alice@pathway.com | 34
+    >>> pw.debug.compute_and_print(
+    ...     result.violations.select(pw.this.constraint, pw.this.details),
+    ...     include_id=False,
+    ... )
+    constraint     | details
+    in_range(age)  | value 210 is not >= 0 and <= 150
+    matches(email) | value 'bob' doesn't match '[^@]+@[^@]+'
+    """
+    if not constraints:
+        raise ValueError("at least one constraint has to be checked")
+    constraint_names = [constraint.name for constraint in constraints]
+    if len(set(constraint_names)) != len(constraint_names):
+        raise ValueError(f"constraint names must be distinct, got {constraint_names}")
+    if on_violation not in ("keep", "drop"):
+        raise ValueError(f"unknown on_violation value: {on_violation!r}")
+
+    violation_tables = [
+        constraint._violations(table).select(
+            constraint=constraint.name, row=pw.this.id, details=pw.this.details
+        )
+        for constraint in constraints
+    ]
+    violations = violation_tables[0].concat_reindex(*violation_tables[1:])
+
+    def on_change(key: pw.Pointer, row: dict, time: int, is_addition: bool) -> None:
+        api.record_constraint_violations(
+            name, row["constraint"], 1 if is_addition else -1
+        )
+
+    pw.io.subscribe(violations, on_change=on_change)
+
+    if on_violation == "drop":
+        violating_rows = violations.groupby(violations.row, id=violations.row).reduce()
+        table = table.difference(violating_rows)
+    return CheckResult(table=table, violations=violations)
@@ -5,7 +5,12 @@ import pytest
 
 import pathway as pw
 from pathway.debug import table_from_pandas
-from pathway.tests.utils import T, assert_table_equality, run_all
+from pathway.tests.utils import (
+    T,
+    assert_table_equality,
+    assert_table_equality_wo_index,
+    run_all,
+)
 
 
 def test_strip():
@@ -499,3 +504,76 @@ def test_to_string_for_datetime_utc():
         t.select(t=pw.this.t.dt.strptime("%Y-%m-%dT%H:%M:%S.%f%z").to_string()),
         expected,
     )
+
+
+def test_encode_decode_round_trip():
+    t = T(
+        """
+          | text
+        1 | Alice
+        2 | żółw
+        """
+    )
+    from_base64 = pw.this.text.str.encode_base64().str.decode_base64()
+    from_hex = pw.this.text.str.encode_hex().str.decode_hex()
+    result = t.select(
+        base64=from_base64.str.from_utf8_lossy(),
+        hex=from_hex.str.from_utf8_lossy(),
+        utf8=pw.this.text.str.to_utf8().str.from_utf8_lossy(),
+    )
+    expected = t.select(base64=pw.this.text, hex=pw.this.text, utf8=pw.this.text)
+    assert_table_equality(result, expected)
+
+
+def test_encode_bytes():
+    t = pw.debug.table_from_rows(
+        pw.schema_from_types(data=bytes), [(b"\xff\xfe",), (b"hi",)]
+    )
+    result = t.select(
+        base64=pw.this.data.str.encode_base64(),
+        hex=pw.this.data.str.encode_hex(),
+        text=pw.this.data.str.from_utf8_lossy(),
+    )
+    expected = pw.debug.table_from_rows(
+        pw.schema_from_types(base64=str, hex=str, text=str),
+        [("//4=", "fffe", "\ufffd\ufffd"), ("aGk=", "6869", "hi")],
+    )
+    assert_table_equality_wo_index(result, expected)
+
+
+def test_decode_to_bytes():
+    t = T(
+        """
+          | base64   | hex
+        1 | ams=     | 6a6b
+        2 | //4=     | FFFE
+        """
+    )
+    result = t.select(
+        base64=pw.this.base64.str.decode_base64(),
+        hex=pw.this.hex.str.decode_hex(),
+    )
+    expected = pw.debug.table_from_rows(
+        pw.schema_from_types(base64=bytes, hex=bytes),
+        [(b"jk", b"jk"), (b"\xff\xfe", b"\xff\xfe")],
+    )
+    assert_table_equality_wo_index(result, expected)
+
+
+@pytest.mark.parametrize("method", ["decode_base64", "decode_hex"])
+def test_decode_invalid(method):
+    t = T(
+        """
+          | a
+        1 | abc$
+        """
+    )
+    t = t.select(a=getattr(pw.this.a.str, method)())
+    with pytest.raises(ValueError):
+        run_all()
+
+
+def test_decode_on_bytes_fails():
+    t = pw.debug.table_from_rows(pw.schema_from_types(data=bytes), [(b"hi",)])
+    with pytest.raises(AttributeError):
+        t.select(a=pw.this.data.str.decode_base64())
@@ -249,7 +249,17 @@ def test_cosine_distance():
     assert_table_equality(ret, expected)
 
 
-def test_euclidean_sq_distance():
+@pytest.mark.parametrize(
+    "options",
+    [
+        {},
+        {"parallel_chunk_size": 2},
+        {"quantization": "int8"},
+        {"quantization": "pq", "pq_subquantizers": 3, "pq_training_sample_size": 4},
+    ],
+    ids=["sequential scan", "parallel scan", "int8 quantization", "pq quantization"],
+)
+def test_euclidean_sq_distance(options):
 
     index = pw.debug.table_from_markdown(
         """
@@ -283,6 +293,7 @@ def test_euclidean_sq_distance():
         reserved_space=10,
         auxiliary_space=1000,
         metric=BruteForceKnnMetricKind.L2SQ,
+        **options,
     )
 
     ret = get_ret(queries, index, index_factory)
@@ -305,3 +316,87 @@ def test_euclidean_sq_distance():
     )
 
     assert_table_equality(ret, expected)
+
+
+def test_filter_during_search():
+    class FilteredInputSchema(pw.Schema):
+        pk_source: int = pw.column_definition(primary_key=True)
+        data: str
+        price: int
+
+    class FilteredQuerySchema(pw.Schema):
+        pk_source: int = pw.column_definition(primary_key=True)
+        data: str
+        limit: int
+        filter_col: str
+
+    index = pw.debug.table_from_markdown(
+        """
+    pk_source |data         | price
+    1         | 1,0.1,0.1   | 10
+    2         | 2,0.1,0.1   | 20
+    3         | 3,0.1,0.1   | 30
+    4         | 4,0.1,0.1   | 40
+    5         | 5,0.1,0.1   | 50
+    6         | 6,0.1,0.1   | 60
+    """,
+        schema=FilteredInputSchema,
+    ).with_columns(
+        data=pw.apply(make_list, pw.this.data),
+        filter_data=pw.apply_with_type(
+            lambda price: pw.Json({"price": price}), pw.Json, pw.this.price
+        ),
+    )
+
+    # the matching entries are the furthest ones, so they'd be cut off by the limit
+    # if the results were filtered only after the search
+    queries = pw.debug.table_from_markdown(
+        """
+    pk_source|data        |limit |filter_col
+    1        |0,0.1,0.1   |2     |price>=`50`
+    2        |0,0.1,0.1   |3     |price==`30`
+    """,
+        schema=FilteredQuerySchema,
+    ).with_columns(data=pw.apply_with_type(make_list, list[float], pw.this.data))
+
+    index_factory = ExternalIndexFactory.brute_force_knn_factory(
+        dimensions=3,
+        reserved_space=10,
+        auxiliary_space=100,
+        metric=BruteForceKnnMetricKind.L2SQ,
+    )
+
+    raw_ret = index._external_index_as_of_now(
+        queries,
+        index_column=index.data,
+        query_column=queries.data,
+        index_factory=index_factory,
+        query_responses_limit_column=queries.limit,
+        index_filter_data_column=index.filter_data,
+        query_filter_column=queries.filter_col,
+    ).with_columns(q_pk_source=queries.pk_source)
+    flattened_ret = raw_ret.flatten(pw.this._pw_index_reply)
+    unpacked_ret = flattened_ret + unpack_col(
+        flattened_ret._pw_index_reply, schema=InnerSchema
+    )
+    ret = (
+        unpacked_ret.join(index, pw.left.matched_item_id == index.id)
+        .select(pw.left.q_pk_source, i_pk_source=pw.right.pk_source)
+        .with_id_from(pw.this.q_pk_source, pw.this.i_pk_source)
+    )
+
+    class FilteredExpectedSchema(pw.Schema):
+        q_pk_source: int = pw.column_definition(primary_key=True)
+        i_pk_source: int = pw.column_definition(primary_key=True)
+
+    expected = pw.debug.table_from_markdown(
+        """
+        q_pk_source | i_pk_source
+        1           | 5
+        1           | 6
+        2           | 3
+    """,
+        schema=FilteredExpectedSchema,
+    )
+
+    assert_table_equality(ret, expected)
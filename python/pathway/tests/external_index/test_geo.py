@@ -0,0 +1,83 @@
+# Copyright © 2024 Pathway
+
+from typing import Any
+
+import pathway as pw
+from pathway.engine import ExternalIndexFactory
+from pathway.tests.utils import assert_table_equality
+
+
+class ShapeSchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    shape: Any
+
+
+class QuerySchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    query: Any
+    limit: int
+
+
+class ExpectedSchema(pw.Schema):
+    q_pk_source: int = pw.column_definition(primary_key=True)
+    i_pk_source: int = pw.column_definition(primary_key=True)
+
+
+def get_matches(index: pw.Table, queries: pw.Table) -> pw.Table:
+    raw_ret = index._external_index_as_of_now(
+        queries,
+        index_column=index.shape,
+        query_column=queries.query,
+        index_factory=ExternalIndexFactory.geo_factory(),
+        query_responses_limit_column=queries.limit,
+    ).with_columns(q_pk_source=queries.pk_source)
+    flattened = raw_ret.flatten(pw.this._pw_index_reply).with_columns(
+        matched_id=pw.apply_with_type(
+            lambda reply: reply[0], pw.Pointer, pw.this._pw_index_reply
+        )
+    )
+    return (
+        flattened.join(index, flattened.matched_id == index.id)
+        .select(pw.left.q_pk_source, i_pk_source=pw.right.pk_source)
+        .with_id_from(pw.this.q_pk_source, pw.this.i_pk_source)
+    )
+
+
+def test_geo_queries():
+    index = pw.debug.table_from_rows(
+        ShapeSchema,
+        [
+            (1, (0.0, 0.0)),
+            (2, (1.0, 0.0)),
+            (3, (5.0, 5.0)),
+            # unit square with the lower left corner at (10, 10)
+            (4, ((10.0, 10.0), (11.0, 10.0), (11.0, 11.0), (10.0, 11.0))),
+        ],
+    )
+    queries = pw.debug.table_from_rows(
+        QuerySchema,
+        [
+            # two nearest points
+            (1, (0.1, 0.0), 2),
+            # within radius
+            (2, ((5.0, 4.0), 1.5), 10),
+            # bounding box intersecting only the polygon
+            (3, ((10.5, 10.5), (12.0, 12.0)), 10),
+            # point inside the polygon
+            (4, (10.5, 10.5), 1),
+        ],
+    )
+
+    expected = pw.debug.table_from_markdown(
+        """
+        q_pk_source | i_pk_source
+        1           | 1
+        1           | 2
+        2           | 3
+        3           | 4
+        4           | 4
+    """,
+        schema=ExpectedSchema,
+    )
+
+    assert_table_equality(get_matches(index, queries), expected)
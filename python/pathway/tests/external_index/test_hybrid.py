@@ -0,0 +1,77 @@
+# Copyright © 2024 Pathway
+
+import pytest
+
+import pathway as pw
+from pathway.engine import BruteForceKnnMetricKind, ExternalIndexFactory
+from pathway.tests.utils import assert_table_equality_wo_index
+
+
+class DocumentSchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    text: str
+    vector: list[float]
+
+
+class QuerySchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    text: str
+    vector: list[float]
+    limit: int
+
+
+def make_factory(fusion: str) -> ExternalIndexFactory:
+    return ExternalIndexFactory.hybrid_factory(
+        ExternalIndexFactory.tantivy_factory(ram_budget=50000000, in_memory_index=True),
+        ExternalIndexFactory.brute_force_knn_factory(
+            dimensions=2,
+            reserved_space=10,
+            auxiliary_space=10,
+            metric=BruteForceKnnMetricKind.L2SQ,
+        ),
+        fusion=fusion,
+    )
+
+
+@pytest.mark.parametrize("fusion", ["rrf", "weighted"])
+def test_hybrid_fusion(fusion):
+    documents = pw.debug.table_from_rows(
+        DocumentSchema,
+        [
+            # matches the text of the query, but not the vector
+            (1, "badger", [10.0, 10.0]),
+            # matches the vector of the query, but not the text
+            (2, "fox", [0.1, 0.0]),
+            # matches both
+            (3, "badger", [0.0, 0.0]),
+            # matches none
+            (4, "fox", [-10.0, -10.0]),
+        ],
+    ).with_columns(data=pw.make_tuple(pw.this.text, pw.this.vector))
+    queries = pw.debug.table_from_rows(
+        QuerySchema, [(1, "badger", [0.0, 0.0], 1)]
+    ).with_columns(data=pw.make_tuple(pw.this.text, pw.this.vector))
+
+    answers = documents._external_index_as_of_now(
+        queries,
+        index_column=documents.data,
+        query_column=queries.data,
+        index_factory=make_factory(fusion),
+        query_responses_limit_column=queries.limit,
+    ).select(
+        best=pw.apply_with_type(
+            lambda reply: reply[0][0], pw.Pointer, pw.this._pw_index_reply
+        )
+    )
+    result = answers.join(documents, answers.best == documents.id).select(
+        documents.pk_source
+    )
+    assert_table_equality_wo_index(
+        result,
+        pw.debug.table_from_markdown(
+            """
+            pk_source
+            3
+            """
+        ),
+    )
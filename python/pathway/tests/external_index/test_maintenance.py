@@ -0,0 +1,98 @@
+# Copyright © 2024 Pathway
+
+import pathway as pw
+from pathway.engine import BruteForceKnnMetricKind, ExternalIndexFactory
+from pathway.tests.utils import assert_table_equality
+
+
+def make_list(vector_as_str: str) -> list[float]:
+    return [float(x) for x in vector_as_str.split(",")]
+
+
+class InputSchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    data: str
+
+
+class QuerySchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    data: str
+    limit: int
+
+
+class ExpectedSchema(pw.Schema):
+    q_pk_source: int = pw.column_definition(primary_key=True)
+    i_pk_source: int = pw.column_definition(primary_key=True)
+
+
+def test_results_during_and_after_rebuild():
+    # half of the entries are removed, so the index is rebuilt one entry at a time
+    index = pw.debug.table_from_markdown(
+        """
+    pk_source |data     | __time__ | __diff__
+    1         | 1,0     | 1        | 1
+    2         | 2,0     | 1        | 1
+    3         | 3,0     | 1        | 1
+    4         | 4,0     | 1        | 1
+    5         | 5,0     | 1        | 1
+    6         | 6,0     | 1        | 1
+    1         | 1,0     | 2        | -1
+    2         | 2,0     | 2        | -1
+    3         | 3,0     | 2        | -1
+    7         | 0.5,0   | 4        | 1
+    4         | 4,0     | 6        | -1
+    """,
+        schema=InputSchema,
+    ).with_columns(data=pw.apply(make_list, pw.this.data))
+
+    queries = pw.debug.table_from_markdown(
+        """
+    pk_source|data   |limit | __time__
+    1        |0,0    |2     | 3
+    2        |0,0    |2     | 5
+    3        |0,0    |2     | 7
+    """,
+        schema=QuerySchema,
+    ).with_columns(data=pw.apply_with_type(make_list, list[float], pw.this.data))
+
+    index_factory = ExternalIndexFactory.brute_force_knn_factory(
+        dimensions=2,
+        reserved_space=10,
+        auxiliary_space=10,
+        metric=BruteForceKnnMetricKind.L2SQ,
+    ).with_maintenance(
+        max_removed_fraction=0.1, min_removals=1, rebuild_batch_size=1
+    )
+
+    raw_ret = index._external_index_as_of_now(
+        queries,
+        index_column=index.data,
+        query_column=queries.data,
+        index_factory=index_factory,
+        query_responses_limit_column=queries.limit,
+    ).with_columns(q_pk_source=queries.pk_source)
+    flattened = raw_ret.flatten(pw.this._pw_index_reply).with_columns(
+        matched_id=pw.apply_with_type(
+            lambda reply: reply[0], pw.Pointer, pw.this._pw_index_reply
+        )
+    )
+    ret = (
+        flattened.join(index, flattened.matched_id == index.id)
+        .select(pw.left.q_pk_source, i_pk_source=pw.right.pk_source)
+        .with_id_from(pw.this.q_pk_source, pw.this.i_pk_source)
+    )
+
+    expected = pw.debug.table_from_markdown(
+        """
+        q_pk_source | i_pk_source
+        1           | 4
+        1           | 5
+        2           | 7
+        2           | 4
+        3           | 7
+        3           | 5
+    """,
+        schema=ExpectedSchema,
+    )
+
+    assert_table_equality(ret, expected)
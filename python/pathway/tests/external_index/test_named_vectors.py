@@ -0,0 +1,88 @@
+# Copyright © 2024 Pathway
+
+from typing import Any
+
+import pathway as pw
+from pathway.engine import BruteForceKnnMetricKind, ExternalIndexFactory
+from pathway.tests.utils import assert_table_equality
+
+
+class DocumentSchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    vectors: pw.Json
+
+
+class QuerySchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    query: Any
+    limit: int
+
+
+class ExpectedSchema(pw.Schema):
+    q_pk_source: int = pw.column_definition(primary_key=True)
+    i_pk_source: int = pw.column_definition(primary_key=True)
+
+
+def make_space(dimensions: int) -> ExternalIndexFactory:
+    return ExternalIndexFactory.brute_force_knn_factory(
+        dimensions=dimensions,
+        reserved_space=10,
+        auxiliary_space=10,
+        metric=BruteForceKnnMetricKind.L2SQ,
+    )
+
+
+def test_named_vector_spaces():
+    documents = pw.debug.table_from_rows(
+        DocumentSchema,
+        [
+            (1, pw.Json({"text": [0.0, 0.0], "image": [5.0, 5.0, 5.0]})),
+            (2, pw.Json({"text": [5.0, 5.0], "image": [0.0, 0.0, 0.0]})),
+            (3, pw.Json({"text": [1.0, 1.0], "image": [1.0, 1.0, 1.0]})),
+            # no image
+            (4, pw.Json({"text": [0.1, 0.1]})),
+        ],
+    )
+    queries = pw.debug.table_from_rows(
+        QuerySchema,
+        [
+            # a single space
+            (1, ("text", (0.0, 0.0)), 2),
+            (2, ("image", (0.0, 0.0, 0.0)), 1),
+            # both spaces, the entry close in both wins
+            (3, (("text", (0.0, 0.0), 1.0), ("image", (0.0, 0.0, 0.0), 1.0)), 1),
+        ],
+    )
+
+    raw_ret = documents._external_index_as_of_now(
+        queries,
+        index_column=documents.vectors,
+        query_column=queries.query,
+        index_factory=ExternalIndexFactory.named_vectors_factory(
+            {"text": make_space(2), "image": make_space(3)}, candidates_multiplier=4
+        ),
+        query_responses_limit_column=queries.limit,
+    ).with_columns(q_pk_source=queries.pk_source)
+    flattened = raw_ret.flatten(pw.this._pw_index_reply).with_columns(
+        matched_id=pw.apply_with_type(
+            lambda reply: reply[0], pw.Pointer, pw.this._pw_index_reply
+        )
+    )
+    ret = (
+        flattened.join(documents, flattened.matched_id == documents.id)
+        .select(pw.left.q_pk_source, i_pk_source=pw.right.pk_source)
+        .with_id_from(pw.this.q_pk_source, pw.this.i_pk_source)
+    )
+
+    expected = pw.debug.table_from_markdown(
+        """
+        q_pk_source | i_pk_source
+        1           | 1
+        1           | 4
+        2           | 2
+        3           | 3
+    """,
+        schema=ExpectedSchema,
+    )
+
+    assert_table_equality(ret, expected)
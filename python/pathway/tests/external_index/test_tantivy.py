@@ -5,7 +5,7 @@ import json
 import pathway as pw
 from pathway.engine import ExternalIndexFactory
 from pathway.stdlib.utils.col import unpack_col
-from pathway.tests.utils import assert_table_equality
+from pathway.tests.utils import assert_table_equality, assert_table_equality_wo_index
 
 
 def test_filter():
@@ -326,3 +326,127 @@ def test_score_with_deletion():
         split_on_whitespace=False,
     )
     assert_table_equality(ret, expected)
+
+
+
+class _TextSchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    data: str
+
+
+class _QuerySchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    data: str
+    limit: int
+
+
+class _MatchCountSchema(pw.Schema):
+    pk_source: int = pw.column_definition(primary_key=True)
+    match_len: int
+
+
+def _match_counts(index_factory, documents: list[tuple], queries: list[tuple]):
+    index = pw.debug.table_from_rows(_TextSchema, documents)
+    query_table = pw.debug.table_from_rows(
+        _QuerySchema, [(pk_source, data, 4) for pk_source, data in queries]
+    )
+    return index._external_index_as_of_now(
+        query_table,
+        index_column=index.data,
+        query_column=query_table.data,
+        index_factory=index_factory,
+        query_responses_limit_column=query_table.limit,
+    ).select(
+        query_table.pk_source,
+        match_len=pw.apply_with_type(len, int, pw.this._pw_index_reply),
+    )
+
+
+def test_phrase_fuzzy_and_stemming():
+    documents = [
+        (1, "the quick brown fox"),
+        (2, "brown quick foxes"),
+        (3, "running badgers"),
+    ]
+    queries = [
+        (1, "quikc"),
+        (2, "fox"),
+        (3, "run"),
+    ]
+
+    phrase = _match_counts(
+        ExternalIndexFactory.tantivy_factory(ram_budget=50000000, in_memory_index=True),
+        documents,
+        [(1, '"quick brown"'), (2, "quick brown")],
+    )
+    assert_table_equality(
+        phrase,
+        pw.debug.table_from_rows(_MatchCountSchema, [(1, 1), (2, 2)]),
+    )
+
+    exact = _match_counts(
+        ExternalIndexFactory.tantivy_factory(ram_budget=50000000, in_memory_index=True),
+        documents,
+        queries,
+    )
+    assert_table_equality(
+        exact,
+        pw.debug.table_from_rows(_MatchCountSchema, [(1, 0), (2, 1), (3, 0)]),
+    )
+
+    fuzzy_stemmed = _match_counts(
+        ExternalIndexFactory.tantivy_factory(
+            ram_budget=50000000,
+            in_memory_index=True,
+            stemming_language="english",
+            fuzzy_distance=1,
+        ),
+        documents,
+        queries,
+    )
+    assert_table_equality(
+        fuzzy_stemmed,
+        pw.debug.table_from_rows(_MatchCountSchema, [(1, 2), (2, 2), (3, 1)]),
+    )
+
+
+def test_field_boosts():
+    class DocumentSchema(pw.Schema):
+        pk_source: int = pw.column_definition(primary_key=True)
+        data: pw.Json
+
+    index = pw.debug.table_from_rows(
+        DocumentSchema,
+        [
+            (1, pw.Json({"title": "badger", "body": "a story about a fox"})),
+            (2, pw.Json({"title": "fox", "body": "a story about a badger"})),
+        ],
+    )
+    queries = pw.debug.table_from_rows(_TextSchema, [(1, "badger")])
+    index_factory = ExternalIndexFactory.tantivy_factory(
+        ram_budget=50000000,
+        in_memory_index=True,
+        fields=[("title", 10.0), ("body", 1.0)],
+    )
+    answers = index._external_index_as_of_now(
+        queries,
+        index_column=index.data,
+        query_column=queries.data,
+        index_factory=index_factory,
+        query_responses_limit_column=None,
+    ).select(
+        # the document with the match in the boosted title field comes first
+        best=pw.apply_with_type(
+            lambda reply: reply[0][0], pw.Pointer, pw.this._pw_index_reply
+        )
+    )
+    result = answers.join(index, answers.best == index.id).select(index.pk_source)
+    assert_table_equality_wo_index(
+        result,
+        pw.debug.table_from_markdown(
+            """
+            pk_source
+            1
+            """
+        ),
+    )
@@ -20,6 +20,7 @@ from pathway.stdlib.indexing.nearest_neighbors import (
     USearchKnn,
     UsearchKnnFactory,
 )
+from pathway.stdlib.indexing.reranking import RerankingHook
 from pathway.stdlib.indexing.vector_document_index import default_lsh_knn_document_index
 from pathway.stdlib.ml.index import KNNIndex
 from pathway.tests.utils import (
@@ -1188,3 +1189,84 @@ def test_brute_force_knn_multi_removal():
     """
     ).update_types(d=int | None)
     assert_table_equality_wo_index(result, expected)
+
+
+def test_expired_entries_are_removed_from_index():
+    class DataSchema(pw.Schema):
+        a: int = pw.column_definition(primary_key=True)
+        v: np.ndarray
+        t: int
+
+    class QuerySchema(pw.Schema):
+        a: int = pw.column_definition(primary_key=True)
+        v: np.ndarray
+
+    data = pw.debug.table_from_rows(
+        DataSchema,
+        [
+            (1, np.array([1, 1, 1]), 1, 2, 1),
+            (2, np.array([5, 5, 5]), 2, 2, 1),
+            # moves the current time to 10, so the entries above expire
+            (3, np.array([9, 9, 9]), 10, 4, 1),
+        ],
+        is_stream=True,
+    )
+    queries = pw.debug.table_from_rows(
+        QuerySchema,
+        [(1, np.array([1, 1, 1]), 3, 1), (2, np.array([1, 1, 1]), 6, 1)],
+        is_stream=True,
+    )
+
+    fresh_data = pw.indexing.expire_entries(data, data.t, ttl=5)
+    index = BruteForceKnn(
+        fresh_data.v,
+        metadata_column=None,
+        dimensions=3,
+        reserved_space=0,
+        metric=BruteForceKnnMetricKind.L2SQ,
+    )
+    data_index = DataIndex(fresh_data, index)
+    result = data_index.query_as_of_now(
+        queries.v, number_of_matches=1, collapse_rows=False
+    ).select(q=pw.left.a, d=pw.right.a)
+    expected = pw.debug.table_from_markdown(
+        """
+        q | d
+        1 | 1
+        2 | 3
+    """
+    ).update_types(d=int | None)
+    assert_table_equality_wo_index(result, expected)
+
+
+def test_reranking_hook():
+    async def score_by_length(query: str, docs: list[str]) -> list[float]:
+        return [float(len(doc)) for doc in docs]
+
+    query = pw.debug.table_from_rows(pw.schema_from_types(query=str), [("a",)])
+    docs = pw.debug.table_from_rows(
+        pw.schema_from_types(doc=str), [("aa",), ("b",), ("cccc",)]
+    )
+
+    index = BruteForceKnn(
+        docs.doc,
+        metadata_column=None,
+        dimensions=3,
+        reserved_space=3,
+        metric=BruteForceKnnMetricKind.COS,
+        embedder=fake_embedder,
+    )
+    data_index = DataIndex(
+        docs,
+        index,
+        reranking=RerankingHook(
+            score_by_length, column="doc", max_concurrency=2, top_n=2
+        ),
+    )
+    res = query + data_index.query_as_of_now(
+        query.query, collapse_rows=True, number_of_matches=3
+    ).select(pw.right.doc)
+    expected = pw.debug.table_from_pandas(
+        pd.DataFrame({"query": ["a"], "doc": [("cccc", "aa")]})
+    )
+    assert_table_equality_wo_index(res.update_types(doc=list[str]), expected)
@@ -1187,6 +1187,60 @@ def test_concat_fail(event_loop):
         api.run_with_new_graph(build, event_loop)
 
 
+def test_sql_query(event_loop):
+    def build(s):
+        orders = static_table_from_md(
+            s,
+            """
+                | customer | amount
+                1 | a        | 10
+                2 | b        | 20
+                3 | a        | 30
+                """,
+            legacy=False,
+        )
+        schema = [("customer", dt.STR.to_engine()), ("amount", dt.INT.to_engine())]
+        result, column_names = s.sql_query(
+            "SELECT customer, SUM(amount) AS total FROM orders GROUP BY customer",
+            {"orders": (orders, schema)},
+        )
+        assert column_names == ["customer", "total"]
+        expected = static_table_from_md(
+            s,
+            """
+                | customer | total
+                1 | a        | 40
+                2 | b        | 20
+                """,
+            legacy=False,
+        )
+        paths = [column_path.ColumnPath((0,)), column_path.ColumnPath((1,))]
+        return (result, paths), (expected, paths)
+
+    result, expected = api.run_with_new_graph(build, event_loop)
+
+    assert_equal_tables_wo_index(result, expected)
+
+
+def test_sql_query_unknown_column(event_loop):
+    def build(s):
+        orders = static_table_from_md(
+            s,
+            """
+                | amount
+                1 | 10
+                """,
+            legacy=False,
+        )
+        schema = [("amount", dt.INT.to_engine())]
+        with pytest.raises(ValueError, match='unknown column "price"'):
+            s.sql_query("SELECT price FROM orders", {"orders": (orders, schema)})
+        return []
+
+    api.run_with_new_graph(build, event_loop)
+
+
+
 @pytest.mark.parametrize(
     "value",
     [
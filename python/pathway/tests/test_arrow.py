@@ -0,0 +1,153 @@
+# Copyright © 2024 Pathway
+
+import pandas as pd
+import pyarrow as pa
+import pytest
+
+import pathway as pw
+from pathway.tests.utils import T, assert_table_equality, assert_table_equality_wo_index
+
+
+def test_table_from_arrow():
+    batch = pa.record_batch(
+        {"a": [1, 2, 3], "b": [1.5, None, 3.5], "c": ["x", "y", "z"]}
+    )
+
+    class Schema(pw.Schema):
+        a: int
+        b: float | None
+        c: str
+
+    table = pw.debug.table_from_arrow(batch, schema=Schema)
+
+    expected = T(
+        """
+    a | b   | c
+    1 | 1.5 | x
+    2 |     | y
+    3 | 3.5 | z
+    """
+    )
+    assert_table_equality_wo_index(table, expected)
+
+
+def test_table_from_arrow_ids_match_pandas():
+    batch = pa.record_batch({"key": [10, 20], "value": ["x", "y"]})
+
+    from_arrow = pw.debug.table_from_arrow(batch, id_from=["key"])
+    from_pandas = pw.debug.table_from_pandas(batch.to_pandas(), id_from=["key"])
+    assert_table_equality(from_arrow, from_pandas)
+
+    from_arrow = pw.debug.table_from_arrow(batch)
+    from_pandas = pw.debug.table_from_pandas(batch.to_pandas())
+    assert_table_equality(from_arrow, from_pandas)
+
+
+def test_table_from_arrow_with_time_and_diff():
+    batch = pa.record_batch(
+        {"a": [1, 2, 1], "__time__": [2, 2, 4], "__diff__": [1, 1, -1]}
+    )
+
+    table = pw.debug.table_from_arrow(batch, id_from=["a"])
+
+    expected = T(
+        """
+    a
+    2
+    """
+    )
+    assert_table_equality_wo_index(table, expected)
+
+
+def test_table_from_arrow_schema_mismatch():
+    batch = pa.record_batch({"a": [1, 2]})
+
+    class Schema(pw.Schema):
+        b: int
+
+    with pytest.raises(ValueError, match="schema does not match"):
+        pw.debug.table_from_arrow(batch, schema=Schema)
+
+
+def test_subscribe_arrow():
+    table = T(
+        """
+      | a | b   | __time__ | __diff__
+    1 | 1 | foo | 2        | 1
+    2 | 2 | bar | 2        | 1
+    1 | 1 | foo | 4        | -1
+    """
+    )
+    batches: list[tuple[int, pd.DataFrame]] = []
+
+    def on_batch(batch: pa.RecordBatch, time: int) -> None:
+        assert batch.schema.names == ["a", "b", "id", "__time__", "__diff__"]
+        batches.append((time, batch.to_pandas()))
+
+    pw.io.subscribe_arrow(table, on_batch)
+    pw.run(monitoring_level=pw.MonitoringLevel.NONE)
+
+    assert [time for time, _ in batches] == [2, 4]
+    first = batches[0][1].sort_values("a")
+    assert first["a"].tolist() == [1, 2]
+    assert first["b"].tolist() == ["foo", "bar"]
+    assert first["__diff__"].tolist() == [1, 1]
+    second = batches[1][1]
+    assert second["a"].tolist() == [1]
+    assert second["__diff__"].tolist() == [-1]
+    assert second["id"].tolist() == first["id"].tolist()[:1]
+
+
+def test_arrow_roundtrip():
+    batch = pa.record_batch({"a": [3, 1, 2], "b": ["c", "a", "b"]})
+    table = pw.debug.table_from_arrow(batch)
+    collected: list[pa.RecordBatch] = []
+
+    pw.io.subscribe_arrow(table, lambda batch, time: collected.append(batch))
+    pw.run(monitoring_level=pw.MonitoringLevel.NONE)
+
+    result = pa.concat_tables([pa.Table.from_batches([b]) for b in collected])
+    assert sorted(zip(result["a"].to_pylist(), result["b"].to_pylist())) == [
+        (1, "a"),
+        (2, "b"),
+        (3, "c"),
+    ]
+
+
+def test_table_to_arrow():
+    table = T(
+        """
+      | a | b   | c
+    1 | 1 | foo | 1.5
+    2 | 2 | bar |
+    3 | 3 | baz | 3.5
+    """
+    )
+    table = table.filter(pw.this.a > 1)
+
+    batch = pw.debug.table_to_arrow(table)
+    expected = pw.debug.table_to_pandas(table)
+
+    assert batch.schema.names == ["a", "b", "c", "id"]
+    df = batch.to_pandas().set_index("id").sort_values("a")
+    assert df["a"].tolist() == [2, 3]
+    assert df["b"].tolist() == ["bar", "baz"]
+    assert df["c"].isna().tolist() == [True, False]
+    assert df.index.tolist() == [str(key) for key in expected.sort_values("a").index]
+
+
+def test_table_to_arrow_squashes_updates():
+    table = T(
+        """
+      | a | __time__ | __diff__
+    1 | 1 | 2        | 1
+    2 | 2 | 2        | 1
+    1 | 1 | 4        | -1
+    3 | 3 | 6        | 1
+    """
+    )
+
+    batch = pw.debug.table_to_arrow(table, include_id=False)
+
+    assert batch.schema.names == ["a"]
+    assert sorted(batch.column("a").to_pylist()) == [2, 3]
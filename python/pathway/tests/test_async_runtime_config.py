@@ -0,0 +1,50 @@
+# Copyright © 2024 Pathway
+
+import pytest
+
+import pathway as pw
+from pathway.internals.config import PathwayConfig, local_pathway_config
+
+
+def test_async_runtime_config_from_env(monkeypatch):
+    monkeypatch.setenv("PATHWAY_ASYNC_WORKER_THREADS_PER_RUNTIME", "4")
+    monkeypatch.setenv("PATHWAY_ASYNC_MAX_BLOCKING_THREADS", "")
+    monkeypatch.setenv("PATHWAY_ASYNC_THREAD_NAME_PREFIX", "pathway-async")
+
+    config = PathwayConfig()
+
+    assert config.async_worker_threads_per_runtime == 4
+    assert config.async_max_blocking_threads is None
+    assert config.async_thread_name_prefix == "pathway-async"
+
+
+def test_set_async_runtime_config(monkeypatch):
+    monkeypatch.setenv("PATHWAY_ASYNC_WORKER_THREADS_PER_RUNTIME", "4")
+
+    with local_pathway_config() as config:
+        pw.set_async_runtime_config(max_blocking_threads=16, thread_name_prefix="io")
+
+        assert config.async_max_blocking_threads == 16
+        assert config.async_thread_name_prefix == "io"
+        # not passed, so the value from the environment is kept
+        assert config.async_worker_threads_per_runtime == 4
+
+
+def test_set_async_runtime_config_invalid():
+    with local_pathway_config():
+        with pytest.raises(
+            ValueError, match="worker_threads_per_runtime should be positive"
+        ):
+            pw.set_async_runtime_config(worker_threads_per_runtime=0)
+
+
+def test_set_async_runtime_config_runtime_groups():
+    with local_pathway_config() as config:
+        pw.set_async_runtime_config(runtime_groups={"enrichment": ["http", "search"]})
+
+        assert config.async_runtime_groups == {"enrichment": ["http", "search"]}
+
+        with pytest.raises(ValueError, match="assigned to two runtime groups"):
+            pw.set_async_runtime_config(
+                runtime_groups={"enrichment": ["http"], "other": ["http"]}
+            )
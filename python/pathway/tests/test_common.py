@@ -5238,6 +5238,52 @@ def test_make_tuple():
     assert_table_equality_wo_index(result, expected)
 
 
+def test_format():
+    t = T(
+        """
+        name  | count | ratio
+        Alice | 3     | 0.5
+        Bob   | 255   | 0.25
+        """
+    )
+    result = t.select(
+        line=pw.format_str(
+            "{0:*^7}|{1:>4d}|{1:X}|{2:.2f}|{{x}}",
+            pw.this.name,
+            pw.this.count,
+            pw.this.ratio,
+        )
+    )
+    expected = t.select(
+        line=pw.apply_with_type(
+            lambda name, count, ratio: f"{name:*^7}|{count:>4d}|{count:X}|"
+            f"{ratio:.2f}|{{x}}",
+            str,
+            pw.this.name,
+            pw.this.count,
+            pw.this.ratio,
+        )
+    )
+    assert_table_equality_wo_index(result, expected)
+
+
+def test_format_wrong_type():
+    t = T(
+        """
+        name
+        Alice
+        """
+    )
+    t.select(line=pw.format_str("{:d}", pw.this.name))
+    with pytest.raises(ValueError):
+        run_all()
+
+
+def test_format_without_arguments():
+    with pytest.raises(ValueError, match="at least one expression"):
+        pw.format_str("constant")
+
+
 def test_sequence_get_unchecked_fixed_length():
     t1 = T(
         """
@@ -0,0 +1,65 @@
+# Copyright © 2024 Pathway
+
+import os
+import subprocess
+import sys
+
+PRINT_CONFIG = """
+from pathway.internals.config import get_pathway_config
+
+config = get_pathway_config()
+print(config.license_key, config.metric_allowlist, config.terminate_on_error)
+"""
+
+
+def _run_with_config_file(tmp_path, contents: str, **env_vars: str):
+    config_path = tmp_path / "pathway.toml"
+    config_path.write_text(contents)
+    env = os.environ.copy()
+    for name in (
+        "PATHWAY_LICENSE_KEY",
+        "PATHWAY_METRIC_ALLOWLIST",
+        "PATHWAY_TERMINATE_ON_ERROR",
+    ):
+        env.pop(name, None)
+    env["PATHWAY_CONFIG_FILE"] = os.fspath(config_path)
+    env.update(env_vars)
+    return subprocess.run(
+        [sys.executable, "-c", PRINT_CONFIG], env=env, capture_output=True, text=True
+    )
+
+
+def test_settings_from_config_file(tmp_path):
+    result = _run_with_config_file(
+        tmp_path,
+        """
+[runtime]
+terminate_on_error = false
+
+[telemetry]
+metric_allowlist = ["process.*", "input.latency"]
+
+[license]
+key = "file-license-key"
+""",
+    )
+    assert result.returncode == 0, result.stderr
+    assert result.stdout.strip() == (
+        "file-license-key ['process.*', 'input.latency'] False"
+    )
+
+
+def test_env_vars_override_config_file(tmp_path):
+    result = _run_with_config_file(
+        tmp_path,
+        '[license]\nkey = "file-license-key"\n',
+        PATHWAY_LICENSE_KEY="env-license-key",
+    )
+    assert result.returncode == 0, result.stderr
+    assert result.stdout.strip() == "env-license-key [] True"
+
+
+def test_unknown_key_is_reported(tmp_path):
+    result = _run_with_config_file(tmp_path, '[license]\nkee = "key"\n')
+    assert result.returncode != 0
+    assert "license.kee" in result.stderr
@@ -0,0 +1,28 @@
+# Copyright © 2024 Pathway
+
+import pytest
+
+import pathway as pw
+
+
+def test_connector_retry_policy():
+    retry_policy = pw.io.ConnectorRetryPolicy(
+        max_retries=10, first_delay_ms=500, max_elapsed_ms=60_000
+    )
+    table = pw.debug.table_from_markdown(
+        """
+        data
+        1
+        """
+    )
+    pw.io.kafka.write(
+        table,
+        {"bootstrap.servers": "localhost:9092"},
+        "topic",
+        retry_policy=retry_policy,
+    )
+
+
+def test_connector_retry_policy_rejects_decreasing_delays():
+    with pytest.raises(ValueError, match="backoff_factor"):
+        pw.io.ConnectorRetryPolicy(backoff_factor=0.5)
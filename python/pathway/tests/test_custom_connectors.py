@@ -0,0 +1,162 @@
+# Copyright © 2024 Pathway
+
+import pathlib
+
+import pytest
+
+import pathway as pw
+from pathway.internals import api
+from pathway.internals.parse_graph import G
+from pathway.tests.utils import T, assert_table_equality_wo_index, run
+
+
+class InputSchema(pw.Schema):
+    key: int = pw.column_definition(primary_key=True)
+    value: str
+
+
+def test_read_json():
+    def source(cursor):
+        assert cursor is None
+        for key, value in enumerate(["foo", "bar", "baz"]):
+            yield {"key": key, "value": value}
+
+    table = pw.io.custom.read(source, schema=InputSchema)
+
+    assert_table_equality_wo_index(
+        table,
+        T(
+            """
+            key | value
+            0   | foo
+            1   | bar
+            2   | baz
+            """
+        ),
+    )
+
+
+def test_read_plaintext_with_small_queue():
+    def source(cursor):
+        for index in range(100):
+            yield f"line {index}"
+
+    table = pw.io.custom.read(source, format="plaintext", max_queue_size=1)
+    result = table.reduce(count=pw.reducers.count())
+
+    assert_table_equality_wo_index(
+        result,
+        T(
+            """
+            count
+            100
+            """
+        ),
+    )
+
+
+def test_read_resumes_from_cursor(tmp_path: pathlib.Path):
+    persistent_storage_path = tmp_path / "PStorage"
+    cursors = []
+
+    def run_computation(values: list[str]) -> list[int]:
+        G.clear()
+
+        def source(cursor):
+            cursors.append(cursor)
+            start = int(cursor) if cursor is not None else 0
+            for key in range(start, len(values)):
+                yield {"key": key, "value": values[key]}, str(key + 1).encode()
+
+        table = pw.io.custom.read(source, schema=InputSchema, name="values")
+        keys = []
+        pw.io.subscribe(
+            table,
+            on_change=lambda key, row, time, is_addition: keys.append(row["key"]),
+        )
+        run(
+            persistence_config=pw.persistence.Config(
+                pw.persistence.Backend.filesystem(persistent_storage_path),
+            )
+        )
+        return sorted(keys)
+
+    assert run_computation(["foo", "bar"]) == [0, 1]
+    assert run_computation(["foo", "bar", "baz"]) == [0, 1, 2]
+    assert cursors == [None, b"2"]
+
+
+def test_read_invalid_cursor():
+    def source(cursor):
+        yield {"key": 0, "value": "foo"}, 1
+
+    table = pw.io.custom.read(source, schema=InputSchema)
+    pw.io.null.write(table)
+
+    with pytest.raises(api.EngineError, match="the cursor must be bytes"):
+        run()
+
+
+def test_write_json():
+    table = T(
+        """
+        key | value
+        0   | foo
+        1   | bar
+        """
+    )
+    rows = []
+    events = []
+
+    def callback(row, time, diff):
+        rows.append((row, diff))
+
+    pw.io.custom.write(
+        table,
+        callback,
+        on_flush=lambda: events.append("flush"),
+        on_end=lambda: events.append("end"),
+        max_queue_size=1,
+    )
+    run()
+
+    assert sorted(rows, key=lambda entry: entry[0]["key"]) == [
+        ({"key": 0, "value": "foo"}, 1),
+        ({"key": 1, "value": "bar"}, 1),
+    ]
+    assert events[-1] == "end"
+    assert "flush" in events
+
+
+def test_write_plaintext():
+    table = T(
+        """
+        data
+        foo
+        bar
+        """
+    )
+    rows = []
+    pw.io.custom.write(
+        table, lambda row, time, diff: rows.append(row), format="plaintext"
+    )
+    run()
+
+    assert sorted(rows) == ["bar", "foo"]
+
+
+def test_write_callback_fails():
+    table = T(
+        """
+        data
+        foo
+        """
+    )
+
+    def callback(row, time, diff):
+        raise ValueError("the sink is unavailable")
+
+    pw.io.custom.write(table, callback, format="plaintext")
+
+    with pytest.raises(api.EngineError, match="the sink is unavailable"):
+        run()
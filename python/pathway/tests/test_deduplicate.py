@@ -573,3 +573,134 @@ def test_deduplicate_python_tuple():
     """
     )
     assert_stream_equality_wo_index(res, expected)
+
+
+def test_drop_duplicates_by_columns():
+    t = pw.debug.table_from_markdown(
+        """
+        event_id | value | __time__
+            a    |   1   |     2
+            b    |   2   |     2
+            a    |   3   |     4
+            c    |   4   |     6
+            b    |   5   |     6
+    """
+    )
+    res = t.drop_duplicates(pw.this.event_id)
+    expected = pw.debug.table_from_markdown(
+        """
+        event_id | value | __time__ | __diff__
+            a    |   1   |     2    |     1
+            b    |   2   |     2    |     1
+            c    |   4   |     6    |     1
+    """
+    )
+    assert_stream_equality_wo_index(res, expected)
+
+
+def test_drop_duplicates_by_id():
+    t = pw.debug.table_from_markdown(
+        """
+        id | value | __time__ | __diff__
+         1 |   1   |     2    |     1
+         2 |   2   |     2    |     1
+         1 |   1   |     4    |    -1
+         1 |   3   |     6    |     1
+         3 |   4   |     6    |     1
+    """
+    )
+    res = t.drop_duplicates()
+    expected = pw.debug.table_from_markdown(
+        """
+        value | __time__ | __diff__
+          1   |     2    |     1
+          2   |     2    |     1
+          4   |     6    |     1
+    """
+    )
+    assert_stream_equality_wo_index(res, expected)
+
+
+def test_drop_duplicates_within_horizon():
+    t = pw.debug.table_from_markdown(
+        """
+        event_id | t  | __time__
+            a    |  1 |     2
+            a    |  5 |     4
+            a    | 12 |     6
+            b    |  3 |     6
+            b    |  2 |     6
+            a    | 20 |     8
+    """
+    )
+    res = t.drop_duplicates(pw.this.event_id, time=pw.this.t, horizon=10)
+    expected = pw.debug.table_from_markdown(
+        """
+        event_id | t  | __time__ | __diff__
+            a    |  1 |     2    |     1
+            a    |  1 |     6    |    -1
+            a    | 12 |     6    |     1
+            b    |  2 |     6    |     1
+    """
+    )
+    assert_stream_equality_wo_index(res, expected)
+
+
+def test_drop_duplicates_horizon_requires_time():
+    t = pw.debug.table_from_markdown(
+        """
+        event_id | t
+            a    | 1
+    """
+    )
+    with pytest.raises(ValueError, match="horizon can only be used together with time"):
+        t.drop_duplicates(pw.this.event_id, horizon=10)
+
+
+def test_drop_duplicates_keeps_seen_rows(tmp_path: pathlib.Path):
+    persistence_config = pw.persistence.Config(
+        pw.persistence.Backend.filesystem(tmp_path / "persistence")
+    )
+    data_1 = """
+    event_id | value | __time__
+        a    |   1   |     2
+        b    |   2   |     4
+        a    |   3   |     6
+    """
+    # the rows with __time__ == 0 simulate the persistence behavior of a regular connector
+    data_2 = """
+    event_id | value | __time__
+        a    |   1   |     0
+        b    |   2   |     0
+        a    |   3   |     0
+        b    |   4   |     8
+        c    |   5   |    10
+    """
+
+    table = pw.debug.table_from_markdown(data_1)
+    result = table.drop_duplicates(pw.this.event_id, name="events")
+    expected_1 = pw.debug.table_from_markdown(
+        """
+    event_id | value | __time__ | __diff__
+        a    |   1   |     2    |     1
+        b    |   2   |     4    |     1
+    """
+    )
+    assert_stream_equality_wo_index(
+        result, expected_1, persistence_config=persistence_config
+    )
+    G.clear()
+
+    table = pw.debug.table_from_markdown(data_2)
+    result = table.drop_duplicates(pw.this.event_id, name="events")
+    expected_2 = pw.debug.table_from_markdown(
+        """
+    event_id | value | __time__ | __diff__
+        a    |   1   |     0    |     1
+        b    |   2   |     0    |     1
+        c    |   5   |    10    |     1
+    """
+    )
+    assert_stream_equality_wo_index(
+        result, expected_2, persistence_config=persistence_config
+    )
@@ -1753,3 +1753,34 @@ def test_repeated_keys_for_nondeterministic_udf():
         ),
     ):
         pw.run_all(monitoring_level=pw.MonitoringLevel.NONE)
+
+
+def test_parse_error_type():
+    t = T(
+        """
+        c
+        1
+        x
+        """
+    )
+    t.select(y=pw.this.c.str.parse_int())
+
+    with pytest.raises(pw.errors.ParseError, match="cannot parse") as exc_info:
+        pw.run_all(monitoring_level=pw.MonitoringLevel.NONE, terminate_on_error=True)
+    assert isinstance(exc_info.value, pw.errors.EngineError)
+    assert isinstance(exc_info.value, ValueError)
+
+
+def test_license_error_entitlements():
+    from pathway.internals import api
+
+    with pytest.raises(pw.errors.LicenseError) as exc_info:
+        api.check_entitlements(license_key=None, entitlements=["xpack-sharepoint"])
+    assert exc_info.value.entitlements == ("XPACK-SHAREPOINT",)
+    assert isinstance(exc_info.value, RuntimeError)
+
+
+def test_connector_error_hierarchy():
+    assert issubclass(pw.errors.ConnectorError, pw.errors.EngineError)
+    assert issubclass(pw.errors.PersistenceError, pw.errors.EngineError)
+    assert pw.errors.ConnectorError("failed").connector_name is None
@@ -0,0 +1,44 @@
+# Copyright © 2024 Pathway
+
+from unittest import mock
+
+import requests
+
+from pathway.io.http import RetryPolicy
+from pathway.io.http._common import Sender
+
+
+def test_full_jitter_delays_are_within_backoff():
+    policy = RetryPolicy(
+        first_delay_ms=1000, backoff_factor=2.0, jitter_ms=300, full_jitter=True
+    )
+
+    for max_delay in [1.0, 2.0, 4.0, 8.0]:
+        assert 0.0 <= policy.wait_duration_before_retry() <= max_delay
+
+
+def test_max_elapsed_time_stops_retries():
+    policy = RetryPolicy(
+        first_delay_ms=100, backoff_factor=1.0, jitter_ms=0, max_elapsed_ms=250
+    )
+    sender = Sender(
+        request_method="GET",
+        n_retries=10,
+        retry_policy=policy,
+        connect_timeout_ms=None,
+        request_timeout_ms=None,
+        allow_redirects=True,
+        retry_codes=(503,),
+    )
+    response = requests.Response()
+    response.status_code = 503
+
+    with (
+        mock.patch("requests.request", return_value=response) as request,
+        mock.patch("time.sleep"),
+        mock.patch("time.monotonic", side_effect=[0.0, 0.0, 0.1, 0.2]),
+    ):
+        assert sender.send("http://localhost").status_code == 503
+
+    # the third retry would start 0.3s after the first attempt
+    assert request.call_count == 3
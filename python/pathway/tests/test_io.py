@@ -1,5 +1,6 @@
 # Copyright © 2024 Pathway
 
+import asyncio
 import base64
 import copy
 import datetime
@@ -29,6 +30,7 @@ import pathway as pw
 from pathway.engine import DebeziumDBType
 from pathway.internals import api
 from pathway.internals.api import SessionType
+from pathway.internals.config import local_pathway_config
 from pathway.internals.parse_graph import G
 from pathway.io.airbyte.logic import _PathwayAirbyteDestination
 from pathway.io.deltalake import _PATHWAY_COLUMN_META_FIELD
@@ -637,6 +639,92 @@ def test_subscribe():
     )
 
 
+def test_subscribe_batches():
+    table = T(
+        """
+          | a | __time__
+        1 | 1 | 2
+        2 | 2 | 2
+        3 | 3 | 4
+        4 | 4 | 6
+        """
+    )
+
+    batches = pw.io.subscribe_batches(table, max_pending_batches=1)
+    runner = threading.Thread(target=run)
+    runner.start()
+
+    received = []
+    for batch in batches:
+        # a slow consumer, the engine has to wait for it
+        time.sleep(0.1)
+        received.append(
+            (batch.time, sorted(change.row["a"] for change in batch.changes))
+        )
+    runner.join()
+
+    assert received == [(2, [1, 2]), (4, [3]), (6, [4])]
+
+
+def test_subscribe_batches_close():
+    table = T(
+        """
+          | a | __time__
+        1 | 1 | 2
+        2 | 2 | 4
+        3 | 3 | 6
+        """
+    )
+
+    batches = pw.io.subscribe_batches(table, max_pending_batches=1)
+    runner = threading.Thread(target=run)
+    runner.start()
+
+    first = next(batches)
+    batches.close()
+    runner.join(timeout=10)
+
+    assert not runner.is_alive()
+    assert [change.row for change in first.changes] == [{"a": 1}]
+    assert list(batches) == []
+
+
+def test_subscribe_async_callbacks():
+    table = T(
+        """
+          | a | __time__
+        1 | 1 | 2
+        2 | 2 | 2
+        3 | 3 | 4
+        """
+    )
+    changes: list[tuple[int, int]] = []
+    times_ended: list[tuple[int, int]] = []
+    in_progress = 0
+    max_in_progress = 0
+
+    async def on_change(key, row, time, is_addition):
+        nonlocal in_progress, max_in_progress
+        in_progress += 1
+        max_in_progress = max(max_in_progress, in_progress)
+        await asyncio.sleep(0.1)
+        in_progress -= 1
+        changes.append((time, row["a"]))
+
+    async def on_time_end(time):
+        times_ended.append((time, len(changes)))
+
+    def on_end():
+        assert in_progress == 0
+
+    pw.io.subscribe(table, on_change, on_end, on_time_end, max_async_concurrency=2)
+    run()
+
+    assert sorted(changes) == [(2, 1), (2, 2), (4, 3)]
+    assert times_ended == [(2, 2), (4, 3)]
+    assert max_in_progress == 2
+
+
 def test_python_write():
     class TestSubject(pw.io.python.ConnectorSubject):
         def run(self):
@@ -713,6 +801,67 @@ def test_fs_plaintext(tmp_path: pathlib.Path):
 
 
 @needs_multiprocessing_fork
+def test_csv_windows_produced_file(tmp_path: pathlib.Path):
+    input_path = tmp_path / "input.csv"
+    input_path.write_bytes(b'\xef\xbb\xbfk,v\r\n1,"foo\r\nbar"\r\n2,baz\r\n')
+    output_path = tmp_path / "output.csv"
+
+    class InputSchema(pw.Schema):
+        k: int = pw.column_definition(primary_key=True)
+        v: str
+
+    table = pw.io.csv.read(
+        input_path,
+        schema=InputSchema,
+        csv_settings=pw.io.CsvParserSettings(normalize_newlines=True),
+        mode="static",
+    )
+    pw.io.csv.write(table, output_path, write_bom=True)
+    run_all()
+
+    output = output_path.read_bytes()
+    assert output.startswith(b"\xef\xbb\xbf")
+    result = pd.read_csv(output_path, encoding="utf-8-sig", usecols=["k", "v"])
+    assert result.sort_values("k")["v"].tolist() == ["foo\nbar", "baz"]
+
+
+def test_csv_write_with_row_filter_and_columns(tmp_path: pathlib.Path):
+    output_path = tmp_path / "output.csv"
+    table = pw.debug.table_from_markdown(
+        """
+        name  | age | email             | active
+        Alice | 30  | alice@example.com | True
+        Bob   | 17  | bob@example.com   | True
+        Carol | 45  | None              | False
+        Dave  | 52  | dave@example.com  | True
+        """
+    )
+    pw.io.csv.write(
+        table,
+        output_path,
+        row_filter=(table.age >= 18) & table.active & table.email.is_not_none(),
+        columns=[table.email, table.name],
+        column_masking={"email": pw.io.ColumnMasking.hash()},
+    )
+    run_all()
+
+    result = pd.read_csv(output_path)
+    assert list(result.columns) == ["email", "name", "time", "diff"]
+    assert sorted(result["name"]) == ["Alice", "Dave"]
+    assert all(len(email) == 64 for email in result["email"])
+
+
+def test_sink_row_filter_unsupported_expression(tmp_path: pathlib.Path):
+    table = pw.debug.table_from_markdown(
+        """
+        a | b
+        1 | 2
+        """
+    )
+    with pytest.raises(ValueError, match="can't be evaluated by the sink"):
+        pw.io.csv.write(table, tmp_path / "output.csv", row_filter=table.a < table.b)
+
+
 def test_csv_directory(tmp_path: pathlib.Path):
     inputs_path = tmp_path / "inputs/"
     os.mkdir(inputs_path)
@@ -3125,6 +3274,22 @@ def test_raw_kafka_raises_no_value_specified_for_key():
         )
 
 
+def test_kafka_raises_wrong_partition_type():
+    table = pw.Table.empty(data=bytes, partition=str)
+    with pytest.raises(
+        ValueError,
+        match="The partition column must have an integer type, however <class 'str'> is used",
+    ):
+        pw.io.kafka.write(
+            table,
+            topic_name="test",
+            rdkafka_settings={},
+            format="raw",
+            value=table.data,
+            partition=table.partition,
+        )
+
+
 @pytest.mark.parametrize("message_queue", ["kafka", "nats", "mqtt"])
 def test_raw_mq_write_raises_no_column_selected(message_queue):
     table = pw.Table.empty(data=bytes, _metadata=dict)
@@ -3373,6 +3538,42 @@ def test_deltalake_roundtrip(
     assert final.equals(original)
 
 
+def test_deltalake_runtime_group(tmp_path: pathlib.Path):
+    data = """
+        k | v
+        1 | foo
+        2 | bar
+        3 | baz
+    """
+    input_path = tmp_path / "input.csv"
+    lake_path = tmp_path / "lake"
+    output_path = tmp_path / "output.csv"
+    write_csv(input_path, data)
+
+    class InputSchema(pw.Schema):
+        k: int = pw.column_definition(primary_key=True)
+        v: str
+
+    with local_pathway_config():
+        pw.set_async_runtime_config(
+            runtime_groups={"lake": ["lake-output", "lake-input"]}
+        )
+        table = pw.io.csv.read(str(input_path), schema=InputSchema, mode="static")
+        pw.io.deltalake.write(table, str(lake_path), name="lake-output")
+        run_all()
+
+        G.clear()
+        table = pw.io.deltalake.read(
+            lake_path, schema=InputSchema, mode="static", name="lake-input"
+        )
+        pw.io.csv.write(table, output_path)
+        run_all()
+
+    final = pd.read_csv(output_path, usecols=["k", "v"], index_col=["k"]).sort_index()
+    original = pd.read_csv(input_path, usecols=["k", "v"], index_col=["k"]).sort_index()
+    assert final.equals(original)
+
+
 @pytest.mark.parametrize(
     "snapshot_access", [api.SnapshotAccess.FULL, api.SnapshotAccess.OFFSETS_ONLY]
 )
@@ -3664,6 +3865,46 @@ def test_iceberg_no_primary_key():
         )
 
 
+@only_with_license_key
+def test_deltalake_change_data_feed_no_primary_key(tmp_path: pathlib.Path):
+    data = [{"k": 1, "v": "one"}, {"k": 2, "v": "two"}, {"k": 3, "v": "three"}]
+    df = pd.DataFrame(data).set_index("k")
+    lake_path = str(tmp_path / "lake")
+    output_path = str(tmp_path / "output.csv")
+    write_deltalake(
+        lake_path, df, configuration={"delta.enableChangeDataFeed": "true"}
+    )
+
+    class InputSchema(pw.Schema):
+        k: int
+        v: str
+
+    table = pw.io.deltalake.read(
+        lake_path, schema=InputSchema, read_change_data_feed=True
+    )
+    pw.io.jsonlines.write(table, output_path)
+    with pytest.raises(
+        ValueError,
+        match=(
+            "Reading the change data feed requires explicit primary key fields "
+            "specification"
+        ),
+    ):
+        pw.run()
+
+
+@only_with_license_key
+def test_iceberg_glue_catalog_requires_warehouse():
+    with pytest.raises(
+        OSError,
+        match="warehouse must be specified for the Glue catalog",
+    ):
+        pw.io.iceberg.list_tables(
+            pw.io.iceberg.GlueCatalog(region="eu-central-1"),
+            ["app"],
+        )
+
+
 @pytest.mark.parametrize("data_format", ["delta", "json", "csv"])
 @only_with_license_key("data_format", ["delta"])
 def test_py_object_wrapper_serialization(tmp_path: pathlib.Path, data_format):
@@ -5116,3 +5357,415 @@ def test_backpressure_management_with_rewind(tmp_path):
         ]
         write_lines(input_path, input_contents)
         run_test(10, 1)
+
+
+def test_runtime_metrics():
+    table = T(
+        """
+        a
+        1
+        2
+        3
+        """
+    )
+    result = table.filter(pw.this.a > 1)
+    pw.io.subscribe(result, lambda **kwargs: None)
+
+    pw.run(monitoring_level=pw.MonitoringLevel.ALL)
+
+    metrics = pw.runtime_metrics()
+    assert metrics is not None
+    assert metrics.input.done
+    assert metrics.output.done
+    assert len(metrics.operators) > 0
+    assert all(operator.done for operator in metrics.operators.values())
+    assert any(operator.total_rows for operator in metrics.operators.values())
+
+
+def test_runtime_metrics_backfill_progress(tmp_path: pathlib.Path):
+    input_path = tmp_path / "input"
+    input_path.mkdir()
+    (input_path / "a.txt").write_text("foo\nbar\n")
+    (input_path / "b.txt").write_text("baz\n")
+    table = pw.io.plaintext.read(input_path, mode="static")
+    pw.io.subscribe(table, lambda **kwargs: None)
+
+    pw.run(monitoring_level=pw.MonitoringLevel.IN_OUT)
+
+    metrics = pw.runtime_metrics()
+    assert metrics is not None
+    backfills = [
+        connector.backfill
+        for connector in metrics.connectors.values()
+        if connector.backfill is not None
+    ]
+    assert len(backfills) == 1
+    [backfill] = backfills
+    assert backfill.finished
+    assert backfill.fraction_done == 1.0
+    assert backfill.objects_processed == backfill.objects_discovered == 2
+    assert backfill.bytes_processed == backfill.bytes_discovered == 12
+    assert backfill.eta_ms is None
+
+
+def test_request_shutdown():
+    stop = threading.Event()
+
+    class InfiniteSubject(pw.io.python.ConnectorSubject):
+        def run(self):
+            index = 0
+            while not stop.is_set():
+                self.next(a=index)
+                index += 1
+                time.sleep(0.01)
+
+    class InputSchema(pw.Schema):
+        a: int
+
+    table = pw.io.python.read(
+        InfiniteSubject(), schema=InputSchema, autocommit_duration_ms=10
+    )
+    rows: list[int] = []
+    finished = threading.Event()
+
+    def on_change(key, row, time, is_addition):
+        rows.append(row["a"])
+        if len(rows) >= 5:
+            pw.request_shutdown()
+
+    pw.io.subscribe(table, on_change=on_change, on_end=finished.set)
+    try:
+        pw.run(monitoring_level=pw.MonitoringLevel.NONE)
+    finally:
+        stop.set()
+
+    assert finished.is_set()
+    assert len(rows) >= 5
+    assert sorted(rows) == list(range(len(rows)))
+
+
+def test_pause_unknown_connector():
+    with pytest.raises(ValueError, match='connector "missing" is not running'):
+        pw.pause_connector("missing")
+    with pytest.raises(ValueError, match='connector "missing" is not running'):
+        pw.resume_connector("missing")
+
+
+def test_run_in_background_cancel():
+    class InfiniteSubject(pw.io.python.ConnectorSubject):
+        def run(self):
+            index = 0
+            while not self.stop_requested:
+                self.next(a=index)
+                index += 1
+                time.sleep(0.01)
+
+    class InputSchema(pw.Schema):
+        a: int
+
+    table = pw.io.python.read(
+        InfiniteSubject(), schema=InputSchema, autocommit_duration_ms=10
+    )
+    rows: list[int] = []
+    enough_rows = threading.Event()
+
+    def on_change(key, row, time, is_addition):
+        rows.append(row["a"])
+        if len(rows) >= 5:
+            enough_rows.set()
+
+    pw.io.subscribe(table, on_change=on_change)
+    with pw.run_in_background() as handle:
+        assert enough_rows.wait(timeout=30)
+        assert not handle.done()
+        handle.cancel()
+        assert handle.wait(timeout=30)
+
+    assert handle.cancelled
+    assert sorted(rows) == list(range(len(rows)))
+
+
+def _infinite_table() -> pw.Table:
+    class InfiniteSubject(pw.io.python.ConnectorSubject):
+        def run(self):
+            index = 0
+            while not self.stop_requested:
+                self.next(a=index)
+                index += 1
+                time.sleep(0.01)
+
+    class InputSchema(pw.Schema):
+        a: int
+
+    return pw.io.python.read(
+        InfiniteSubject(), schema=InputSchema, autocommit_duration_ms=10
+    )
+
+
+def test_run_in_background_cancel_before_start():
+    pw.io.null.write(_infinite_table())
+    with pw.run_in_background() as handle:
+        # the computation is most likely still being built
+        handle.cancel()
+        assert handle.wait(timeout=30)
+
+
+def test_run_in_background_cancel_does_not_affect_next_run():
+    pw.io.null.write(
+        T(
+            """
+            a
+            1
+            """
+        )
+    )
+    first = pw.run_in_background()
+    assert first.wait(timeout=30)
+
+    G.clear()
+    rows: list[int] = []
+    enough_rows = threading.Event()
+
+    def on_change(key, row, time, is_addition):
+        rows.append(row["a"])
+        if len(rows) >= 5:
+            enough_rows.set()
+
+    pw.io.subscribe(_infinite_table(), on_change=on_change)
+    with pw.run_in_background() as second:
+        assert enough_rows.wait(timeout=30)
+        first.cancel()
+        assert not second.wait(timeout=0.5)
+        second.cancel()
+        assert second.wait(timeout=30)
+
+
+def test_run_in_background_error():
+    table = T(
+        """
+        a
+        1
+        """
+    )
+
+    def on_change(key, row, time, is_addition):
+        raise ValueError("failed in callback")
+
+    pw.io.subscribe(table, on_change=on_change)
+    handle = pw.run_in_background(terminate_on_error=True)
+    with pytest.raises(ValueError, match="failed in callback"):
+        handle.wait()
+    assert handle.done()
+
+
+def test_gsheets_static_read(monkeypatch):
+    class FakeClient:
+        def __init__(self, credentials_file, spreadsheet_id, range):
+            assert (spreadsheet_id, range) == ("sheet-id", "Prices")
+
+        def get_values(self):
+            return [
+                ["item", "price", "stock"],
+                ["apple", 3, 10],
+                ["pear", 2.5],
+                [],
+                ["plum", "", 4.0],
+            ]
+
+    monkeypatch.setattr(pw.io.gsheets, "_GSheetsClient", FakeClient)
+
+    class InputSchema(pw.Schema):
+        item: str = pw.column_definition(primary_key=True)
+        price: float | None
+        stock: int = pw.column_definition(default_value=0)
+
+    table = pw.io.gsheets.read(
+        "sheet-id",
+        "Prices",
+        schema=InputSchema,
+        service_user_credentials_file="credentials.json",
+        mode="static",
+    )
+    keys, columns = pw.debug.table_to_dicts(table)
+    rows = sorted(
+        (columns["item"][key], columns["price"][key], columns["stock"][key])
+        for key in keys
+    )
+    assert rows == [("apple", 3.0, 10), ("pear", 2.5, 0), ("plum", None, 4)]
+
+
+def test_gsheets_change_detection():
+    from pathway.io.gsheets import _SheetRows
+
+    class InputSchema(pw.Schema):
+        item: str = pw.column_definition(primary_key=True)
+        price: float
+
+    rows = _SheetRows(InputSchema, header=True)
+    removed, changed = rows.update(
+        [["item", "price"], ["apple", 3], ["pear", 2], ["plum", 4]]
+    )
+    assert removed == []
+    assert [identity for identity, _ in changed] == [("apple",), ("pear",), ("plum",)]
+
+    # The rows are identified by the primary key, so reordering them is not a change
+    removed, changed = rows.update(
+        [["item", "price"], ["plum", 4], ["apple", 3.5], ["kiwi", 1]]
+    )
+    assert removed == [(("pear",), {"item": "pear", "price": 2.0})]
+    assert changed == [
+        (("apple",), {"item": "apple", "price": 3.5}),
+        (("kiwi",), {"item": "kiwi", "price": 1.0}),
+    ]
+
+    removed, changed = rows.update(
+        [["item", "price"], ["plum", 4], ["apple", 3.5], ["kiwi", 1]]
+    )
+    assert (removed, changed) == ([], [])
+
+
+def test_imap_message_parsing():
+    from email.message import EmailMessage
+
+    from pathway.io.imap import _parse_message
+
+    message = EmailMessage()
+    message["From"] = "Alice <alice@example.com>"
+    message["To"] = "bob@example.com, Carol <carol@example.com>"
+    message["Cc"] = "dan@example.com"
+    message["Subject"] = "Invoice"
+    message["Date"] = "Mon, 05 Feb 2024 10:00:00 +0100"
+    message["Message-ID"] = "<1@example.com>"
+    message.set_content("Please find the invoice attached.")
+    message.add_attachment(
+        b"%PDF-1.4", maintype="application", subtype="pdf", filename="invoice.pdf"
+    )
+
+    row = _parse_message(message.as_bytes(), "imap://user@host/INBOX/42", True)
+    assert row == {
+        "uid": 42,
+        "message_id": "<1@example.com>",
+        "subject": "Invoice",
+        "sender": "Alice <alice@example.com>",
+        "recipients": ["bob@example.com", "carol@example.com", "dan@example.com"],
+        "date": datetime.datetime(2024, 2, 5, 9, 0, tzinfo=datetime.timezone.utc),
+        "body": "Please find the invoice attached.\n",
+        "attachments": [
+            {"filename": "invoice.pdf", "content_type": "application/pdf", "size": 8}
+        ],
+        "attachment_contents": [b"%PDF-1.4"],
+    }
+
+    row = _parse_message(message.as_bytes(), "imap://user@host/INBOX/42", False)
+    assert "attachment_contents" not in row
+
+
+def test_prometheus_write_validates_columns():
+    table = pw.debug.table_from_markdown(
+        """
+        region | orders | revenue
+        eu     | 2      | 15.5
+        """
+    )
+    with pytest.raises(
+        ValueError,
+        match="The metric column 'region' must have an int, float or bool type",
+    ):
+        pw.io.prometheus.write(
+            table, "http://localhost:9090/api/v1/write", metric_columns=[table.region]
+        )
+    with pytest.raises(ValueError, match="'1shop_orders' is not a valid"):
+        pw.io.prometheus.write(
+            table,
+            "http://localhost:9090/api/v1/write",
+            label_columns=[table.region],
+            metric_prefix="1shop_",
+        )
+    with pytest.raises(ValueError, match="The timestamp column must have"):
+        pw.io.prometheus.write(
+            table,
+            "http://localhost:9090/api/v1/write",
+            timestamp_column=table.region,
+        )
+
+
+def test_fs_write_rotated_files(tmp_path: pathlib.Path):
+    table = pw.debug.table_from_markdown(
+        """
+        k | v
+        1 | foo
+        2 | bar
+        3 | baz
+        """
+    )
+    output_path = tmp_path / "output"
+    pw.io.fs.write(
+        table,
+        output_path,
+        format="csv",
+        max_file_size=1,
+        name_template="{table}/{part}.{ext}",
+        write_success_markers=True,
+    )
+    run_all()
+
+    files = sorted((output_path / "output").glob("*.csv"))
+    assert [file.name for file in files] == ["00000.csv", "00001.csv", "00002.csv"]
+    result = pd.concat([pd.read_csv(file) for file in files])
+    assert sorted(result["v"]) == ["bar", "baz", "foo"]
+    assert (output_path / "output" / "_SUCCESS").exists()
+
+
+def test_fs_write_rotation_template_validation(tmp_path: pathlib.Path):
+    table = pw.debug.table_from_markdown(
+        """
+        k | v
+        1 | foo
+        """
+    )
+    with pytest.raises(ValueError, match="must contain the {part} placeholder"):
+        pw.io.fs.write(
+            table,
+            tmp_path / "output",
+            format="json",
+            max_file_size=1024,
+            name_template="{table}-{date}.{ext}",
+        )
+    with pytest.raises(ValueError, match="requires max_file_size or rotation_interval"):
+        pw.io.fs.write(
+            table, tmp_path / "output.jsonl", format="json", write_success_markers=True
+        )
+
+
+def test_registered_schema(tmp_path: pathlib.Path):
+    class OrderSchema(pw.Schema):
+        order_id: int = pw.column_definition(primary_key=True)
+        amount: float
+
+    class OtherOrderSchema(pw.Schema):
+        order_id: int = pw.column_definition(primary_key=True)
+        amount: str
+
+    assert pw.io.register_schema("test_orders", OrderSchema) is OrderSchema
+    # Registering the same schema again is allowed
+    pw.io.register_schema("test_orders", OrderSchema)
+    with pytest.raises(ValueError, match="a different schema is already registered"):
+        pw.io.register_schema("test_orders", OtherOrderSchema)
+    with pytest.raises(ValueError, match="Schema 'test_unknown' is not registered"):
+        pw.io.registered_schema("test_unknown")
+
+    input_path = tmp_path / "input.jsonl"
+    input_path.write_text(
+        '{"order_id": 1, "amount": 1.5}\n{"order_id": 2, "amount": 2.5}\n'
+    )
+    table = pw.io.jsonlines.read(
+        input_path, schema=pw.io.registered_schema("test_orders"), mode="static"
+    )
+    expected = pw.debug.table_from_markdown(
+        """
+        order_id | amount
+        1        | 1.5
+        2        | 2.5
+        """
+    ).with_id_from(pw.this.order_id)
+    assert_table_equality(table, expected)
@@ -0,0 +1,37 @@
+# Copyright © 2024 Pathway
+
+import pytest
+
+import pathway as pw
+from pathway.internals.config import local_pathway_config
+
+
+def test_license_info_without_license():
+    with local_pathway_config():
+        pw.set_license_key(None)
+        info = pw.license_info()
+
+    assert info.is_offline
+    assert info.policy is None
+    assert info.entitlements == []
+    assert info.expiration_date is None
+
+
+def test_license_info_of_license_key_does_not_list_entitlements():
+    with local_pathway_config():
+        pw.set_license_key("demo-license-key-with-telemetry")
+        info = pw.license_info()
+
+    assert not info.is_offline
+    assert info.entitlements is None
+
+
+def test_license_info_of_malformed_license_file():
+    with local_pathway_config():
+        pw.set_license_key(
+            "-----BEGIN LICENSE FILE-----\n"
+            "bm90IGEgbGljZW5zZQ==\n"
+            "-----END LICENSE FILE-----"
+        )
+        with pytest.raises(pw.errors.LicenseError, match="unable to validate license"):
+            pw.license_info()
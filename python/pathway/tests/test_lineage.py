@@ -0,0 +1,175 @@
+# Copyright © 2024 Pathway
+
+import pytest
+
+import pathway as pw
+from pathway.tests.utils import T, assert_table_equality_wo_index
+
+
+def _connectors(table: pw.Table) -> pw.Table:
+    return pw.lineage.records(table).select(pw.this.connector)
+
+
+def test_lineage_through_join_and_groupby():
+    orders = pw.lineage.track(
+        T(
+            """
+            user | amount
+            10   | 3
+            10   | 5
+            11   | 7
+            """
+        ),
+        connector="orders",
+    )
+    users = pw.lineage.track(
+        T(
+            """
+            user | region
+            10   | north
+            11   | south
+            """
+        ),
+        connector="users",
+    )
+    large = orders.filter(orders.amount > 4)
+    joined = large.join(users, large.user == users.user).select(
+        users.region,
+        large.amount,
+        _lineage=pw.lineage.merge(large._lineage, users._lineage),
+    )
+    totals = joined.groupby(pw.this.region).reduce(
+        pw.this.region,
+        total=pw.reducers.sum(pw.this.amount),
+        _lineage=pw.lineage.collect(pw.this._lineage),
+    )
+    result = pw.lineage.records(totals)
+    result = result.join(totals, result.output_id == totals.id).select(
+        totals.region, result.connector
+    )
+    assert_table_equality_wo_index(
+        result,
+        T(
+            """
+            region | connector
+            north  | orders
+            north  | users
+            south  | orders
+            south  | users
+            """
+        ),
+    )
+
+
+def test_lineage_deduplicates_records():
+    orders = pw.lineage.track(
+        T(
+            """
+            user | amount
+            10   | 3
+            """
+        ),
+        connector="orders",
+    )
+    same_orders = orders.copy()
+    joined = orders.join(same_orders, orders.user == same_orders.user).select(
+        _lineage=pw.lineage.merge(orders._lineage, same_orders._lineage)
+    )
+    assert_table_equality_wo_index(
+        _connectors(joined),
+        T(
+            """
+            connector
+            orders
+            """
+        ),
+    )
+
+
+def test_lineage_outer_join_skips_missing_side():
+    left = pw.lineage.track(
+        T(
+            """
+            a
+            1
+            2
+            """
+        ),
+        connector="left",
+    )
+    right = pw.lineage.track(
+        T(
+            """
+            a
+            1
+            """
+        ),
+        connector="right",
+    )
+    joined = left.join_left(right, left.a == right.a).select(
+        left.a, _lineage=pw.lineage.merge(left._lineage, right._lineage)
+    )
+    result = pw.lineage.records(joined)
+    result = result.join(joined, result.output_id == joined.id).select(
+        joined.a, result.connector
+    )
+    assert_table_equality_wo_index(
+        result,
+        T(
+            """
+            a | connector
+            1 | left
+            1 | right
+            2 | left
+            """
+        ),
+    )
+
+
+def test_lineage_from_file_metadata(tmp_path):
+    input_path = tmp_path / "input.csv"
+    input_path.write_text("user,amount\n10,3\n")
+
+    class InputSchema(pw.Schema):
+        user: int
+        amount: int
+
+    orders = pw.io.fs.read(
+        input_path,
+        format="csv",
+        schema=InputSchema,
+        mode="static",
+        with_metadata=True,
+    )
+    tracked = pw.lineage.track(orders, connector="orders")
+    result = pw.lineage.records(tracked).select(
+        path=pw.apply_with_type(
+            lambda record: record["path"].as_str(), str, pw.this.record
+        ),
+        has_ingest_time=pw.apply_with_type(
+            lambda record: "ingested_at" in record.as_dict(), bool, pw.this.record
+        ),
+    )
+    assert_table_equality_wo_index(
+        result,
+        T(
+            f"""
+            path         | has_ingest_time
+            {input_path} | True
+            """
+        ),
+    )
+
+
+def test_lineage_tracked_twice():
+    table = T(
+        """
+        a
+        1
+        """
+    )
+    tracked = pw.lineage.track(table, connector="first")
+    with pytest.raises(ValueError, match="already tracked"):
+        pw.lineage.track(tracked, connector="second")
+    with pytest.raises(ValueError, match="not tracked"):
+        pw.lineage.records(table)
@@ -0,0 +1,106 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import os
+import pathlib
+import shutil
+import subprocess
+import sys
+
+import pytest
+
+import pathway as pw
+from pathway.tests.utils import T, assert_table_equality, run_all
+
+PLUGIN_SOURCE = (
+    pathlib.Path(__file__).parents[3] / "tests" / "data" / "native_udf_plugin.rs"
+)
+
+
+@pytest.fixture(scope="module")
+def plugin(tmp_path_factory) -> pathlib.Path:
+    rustc = os.environ.get("RUSTC", "rustc")
+    if not PLUGIN_SOURCE.exists() or shutil.which(rustc) is None:
+        pytest.skip("building the native UDF fixture requires the sources and rustc")
+    if sys.platform == "darwin":
+        name = "libnative_udf_plugin.dylib"
+    elif sys.platform == "win32":
+        name = "native_udf_plugin.dll"
+    else:
+        name = "libnative_udf_plugin.so"
+    path = tmp_path_factory.mktemp("native_udf") / name
+    command = [rustc, "--edition", "2021", "--crate-type", "cdylib", "-o", path]
+    subprocess.run([*command, PLUGIN_SOURCE], check=True)
+    return path
+
+
+def test_native_apply(plugin):
+    t = T(
+        """
+        a | b | s     | f
+        1 | 2 | abc   | 1.5
+        3 | 4 | Hello | -4.0
+        """
+    )
+    result = t.select(
+        sum=pw.native_apply(plugin, "add", pw.this.a, pw.this.b, return_type=int),
+        upper=pw.native_apply(plugin, "upper", pw.this.s, return_type=str),
+        half=pw.native_apply(plugin, "half", pw.this.f, return_type=float),
+    )
+    expected = T(
+        """
+        sum | upper | half
+        3   | ABC   | 0.75
+        7   | HELLO | -2.0
+        """
+    )
+    assert_table_equality(result, expected)
+
+
+def test_native_apply_propagate_none(plugin):
+    t = T(
+        """
+          | a | b
+        1 | 1 | 2
+        2 |   | 4
+        """
+    )
+    result = t.select(
+        sum=pw.native_apply(
+            plugin, "add", pw.this.a, pw.this.b, return_type=int, propagate_none=True
+        ),
+        is_none=pw.native_apply(plugin, "is_none", pw.this.a, return_type=bool),
+    )
+    expected = T(
+        """
+          | sum | is_none
+        1 | 3   | False
+        2 |     | True
+        """
+    ).update_types(sum=int | None)
+    assert_table_equality(result, expected)
+
+
+def test_native_apply_error(plugin):
+    t = T(
+        """
+        a | s
+        1 | x
+        """
+    )
+    t.select(sum=pw.native_apply(plugin, "add", pw.this.a, pw.this.s, return_type=int))
+    with pytest.raises(Exception, match="add expects two ints"):
+        run_all()
+
+
+def test_native_apply_missing_function(plugin):
+    t = T(
+        """
+        a
+        1
+        """
+    )
+    t.select(r=pw.native_apply(plugin, "subtract", pw.this.a, return_type=int))
+    with pytest.raises(ValueError, match='function "subtract" not found'):
+        run_all()
@@ -3,7 +3,7 @@
 import pandas as pd
 
 import pathway as pw
-from pathway.tests.utils import T, assert_table_equality_wo_index
+from pathway.tests.utils import T, assert_table_equality_wo_index, run_all
 
 
 def test_write_parquet(tmp_path):
@@ -49,3 +49,54 @@ def test_read_parquet(tmp_path):
     )
 
     assert_table_equality_wo_index(T_parquet, tab)
+
+
+def test_fs_read_parquet(tmp_path):
+    pd.DataFrame(
+        {"name": ["Rex", "Tom"], "age": [3, 5], "owner": ["Alice", None]}
+    ).to_parquet(tmp_path / "pets_1.parquet")
+    pd.DataFrame({"name": ["Max"], "age": [1], "owner": ["Bob"]}).to_parquet(
+        tmp_path / "pets_2.parquet"
+    )
+
+    class InputSchema(pw.Schema):
+        name: str
+        age: int
+        owner: str | None
+
+    table = pw.io.fs.read(tmp_path, format="parquet", schema=InputSchema, mode="static")
+
+    assert_table_equality_wo_index(
+        table,
+        T(
+            """
+            name | age | owner
+            Rex  | 3   | Alice
+            Tom  | 5   |
+            Max  | 1   | Bob
+            """
+        ),
+    )
+
+
+def test_fs_write_parquet(tmp_path):
+    table = T(
+        """
+        name | age
+        Rex  | 3
+        Tom  | 5
+        Max  |
+        """
+    )
+    output_path = tmp_path / "output"
+    pw.io.fs.write(table, output_path, format="parquet", row_group_size=2)
+    run_all()
+
+    [path] = output_path.iterdir()
+    assert path.name.startswith("output-") and path.suffix == ".parquet"
+    result = pd.read_parquet(path).sort_values("name").reset_index(drop=True)
+    assert list(result.columns) == ["name", "age", "time", "diff"]
+    assert result["name"].tolist() == ["Max", "Rex", "Tom"]
+    assert result["age"].tolist()[1:] == [3, 5]
+    assert pd.isna(result["age"][0])
+    assert result["diff"].tolist() == [1, 1, 1]
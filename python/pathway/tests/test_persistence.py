@@ -1,6 +1,7 @@
 # Copyright © 2024 Pathway
 
 import asyncio
+import datetime
 import json
 import multiprocessing
 import os
@@ -1090,3 +1091,22 @@ def test_from_streams(tmp_path, mode):
     run(["a,b", "4,7"], ["a,b"], {"4,7,1"})
     run(["a,b", "3,6"], ["a,b", "4,7"], {"3,5,-1", "3,6,1", "4,7,-1"})
     run(["a,b"], ["a,b", "3,6"], {"3,6,-1"})
+
+
+def test_rewind_requires_timezone_aware_time(tmp_path):
+    persistence_config = pw.persistence.Config(
+        pw.persistence.Backend.filesystem(tmp_path),
+        rewind={"input": datetime.datetime(2024, 1, 1)},
+    )
+    with pytest.raises(ValueError, match="must be timezone-aware"):
+        _ = persistence_config.engine_config
+
+
+def test_rewind_not_supported_with_operator_persistence(tmp_path):
+    persistence_config = pw.persistence.Config(
+        pw.persistence.Backend.filesystem(tmp_path),
+        persistence_mode=api.PersistenceMode.OPERATOR_PERSISTING,
+        rewind={"input": datetime.datetime(2024, 1, 1, tzinfo=datetime.timezone.utc)},
+    )
+    with pytest.raises(ValueError, match="not supported with the operator persistence"):
+        _ = persistence_config.engine_config
@@ -0,0 +1,102 @@
+# Copyright © 2024 Pathway
+
+import pytest
+
+import pathway as pw
+from pathway.internals import api
+from pathway.tests.utils import T, assert_table_equality_wo_index, run
+
+
+def test_check_reports_violations():
+    orders = T(
+        """
+          | order_id | user_id | amount | email
+        1 | 1        | 10      | 5.0    | a@pathway.com
+        2 | 2        | 11      | -1.0   | b@pathway.com
+        3 | 2        | 10      | 3.0    |
+        4 | 3        | 12      | 7.0    | nobody
+        """
+    )
+    users = T(
+        """
+          | user_id
+        1 | 10
+        2 | 11
+        """
+    )
+    result = pw.quality.check(
+        orders,
+        pw.quality.not_null(orders.email),
+        pw.quality.unique(orders.order_id),
+        pw.quality.in_range(orders.amount, min=0.0),
+        pw.quality.matches(orders.email, r".+@.+"),
+        pw.quality.references(orders.user_id, users.user_id),
+    )
+    assert_table_equality_wo_index(
+        result.violations.select(pw.this.constraint, pw.this.details),
+        T(
+            """
+            constraint          | details
+            not_null(email)     | value is missing
+            unique(order_id)    | value 2 is not unique
+            unique(order_id)    | value 2 is not unique
+            in_range(amount)    | value -1.0 is not >= 0.0
+            matches(email)      | value 'nobody' doesn't match '.+@.+'
+            references(user_id) | value 12 not found in column 'user_id'
+            """,
+            split_on_whitespace=False,
+        ),
+    )
+
+
+def test_check_drops_violating_rows():
+    table = T(
+        """
+          | a  | b
+        1 | 1  | x
+        2 | 2  | y
+        3 | 20 | x
+        """
+    )
+    result = pw.quality.check(
+        table,
+        pw.quality.in_range(table.a, max=10),
+        pw.quality.unique("b"),
+        on_violation="drop",
+    )
+    assert_table_equality_wo_index(
+        result.table,
+        T(
+            """
+            a | b
+            2 | y
+            """
+        ),
+    )
+
+
+def test_check_records_metrics():
+    table = T(
+        """
+          | a
+        1 | 1
+        2 |
+        3 |
+        """
+    )
+    pw.quality.check(
+        table, pw.quality.not_null(table.a, name="a_present"), name="test_metrics"
+    )
+    run()
+    assert ("test_metrics", "a_present", 2, 2) in api.constraint_violations()
+
+
+def test_check_requires_distinct_names():
+    table = T(
+        """
+        a
+        1
+        """
+    )
+    with pytest.raises(ValueError, match="constraint names must be distinct"):
+        pw.quality.check(table, pw.quality.not_null(table.a), pw.quality.not_null("a"))
@@ -0,0 +1,102 @@
+# Copyright © 2024 Pathway
+
+import pandas as pd
+import pytest
+
+import pathway as pw
+from pathway.tests.utils import T, assert_table_equality, assert_table_equality_wo_index
+
+
+def _numbers(n: int) -> pw.Table:
+    return pw.debug.table_from_pandas(pd.DataFrame({"value": range(n)}))
+
+
+def test_sample_is_deterministic():
+    table = _numbers(1000)
+    sampled = table.sample(0.2, seed=7)
+    assert_table_equality(sampled, table.sample(0.2, seed=7))
+
+
+def test_sample_keeps_fraction_of_rows():
+    table = _numbers(10000)
+    counts = table.sample(0.2, seed=1).reduce(count=pw.reducers.count())
+    [count] = pw.debug.table_to_pandas(counts)["count"]
+    assert 1700 <= count <= 2300
+
+
+def test_sample_keeps_deletions_of_sampled_rows():
+    table = T(
+        """
+          | value | __time__ | __diff__
+        1 | 1     | 2        | 1
+        2 | 2     | 2        | 1
+        3 | 3     | 2        | 1
+        1 | 1     | 4        | -1
+        2 | 2     | 4        | -1
+        3 | 3     | 4        | -1
+        """
+    )
+    assert_table_equality_wo_index(
+        table.sample(0.5, seed=3), pw.Table.empty(value=int)
+    )
+
+
+def test_reservoir_sample_per_instance():
+    table = T(
+        """
+        sensor | value
+        a      | 1
+        a      | 2
+        a      | 3
+        a      | 4
+        b      | 5
+        b      | 6
+        c      | 7
+        """
+    )
+    sampled = table.reservoir_sample(2, instance=table.sensor, seed=5)
+    counts = sampled.groupby(pw.this.sensor).reduce(
+        pw.this.sensor, count=pw.reducers.count()
+    )
+    assert_table_equality_wo_index(
+        counts,
+        T(
+            """
+            sensor | count
+            a      | 2
+            b      | 2
+            c      | 1
+            """
+        ),
+    )
+
+
+def test_reservoir_sample_replaces_deleted_rows():
+    table = T(
+        """
+          | value | __time__ | __diff__
+        1 | 1     | 2        | 1
+        2 | 2     | 2        | 1
+        3 | 3     | 2        | 1
+        4 | 4     | 2        | 1
+        1 | 1     | 4        | -1
+        2 | 2     | 4        | -1
+        3 | 3     | 4        | -1
+        """
+    )
+    assert_table_equality_wo_index(
+        table.reservoir_sample(1),
+        T(
+            """
+            value
+            4
+            """
+        ),
+    )
+
+
+@pytest.mark.parametrize("fraction", [-0.1, 1.5])
+def test_sample_rejects_invalid_fraction(fraction):
+    table = _numbers(3)
+    with pytest.raises(ValueError, match="fraction has to be between 0 and 1"):
+        table.sample(fraction)
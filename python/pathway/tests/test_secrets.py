@@ -0,0 +1,147 @@
+# Copyright © 2024 Pathway
+
+import datetime
+import json
+
+import pytest
+
+import pathway as pw
+from pathway.io.postgres import _connection_string_from_settings
+
+
+class CountingProvider(pw.secrets.SecretsProvider):
+    def __init__(self):
+        self.fetches = 0
+
+    def fetch(self, name: str) -> str:
+        self.fetches += 1
+        return f"{name}-{self.fetches}"
+
+
+def test_env_provider(monkeypatch):
+    monkeypatch.setenv("APP_DB_PASSWORD", "hunter2")
+    provider = pw.secrets.EnvSecretsProvider(prefix="APP_")
+    assert pw.secrets.Secret("DB_PASSWORD", provider=provider).get() == "hunter2"
+    with pytest.raises(pw.secrets.SecretNotFoundError):
+        pw.secrets.Secret("MISSING", provider=provider).get()
+
+
+def test_default_provider_is_env(monkeypatch):
+    monkeypatch.setenv("KAFKA_PASSWORD", "hunter2")
+    assert pw.secrets.Secret("KAFKA_PASSWORD").get() == "hunter2"
+
+
+def test_file_provider(tmp_path):
+    (tmp_path / "db_password").write_text("hunter2\n")
+    provider = pw.secrets.FileSecretsProvider(tmp_path)
+    assert pw.secrets.Secret("db_password", provider=provider).get() == "hunter2"
+    with pytest.raises(pw.secrets.SecretNotFoundError):
+        provider.fetch("missing")
+
+
+def test_fetched_lazily_and_refreshed():
+    provider = CountingProvider()
+    secret = pw.secrets.Secret("token", provider=provider)
+    assert provider.fetches == 0
+    assert secret.get() == "token-1"
+    assert secret.get() == "token-1"
+
+    refreshed = pw.secrets.Secret(
+        "token", provider=provider, refresh_interval=datetime.timedelta(0)
+    )
+    assert refreshed.get() == "token-2"
+    assert refreshed.get() == "token-3"
+
+
+def test_value_not_in_repr(monkeypatch):
+    monkeypatch.setenv("DB_PASSWORD", "hunter2")
+    secret = pw.secrets.Secret("DB_PASSWORD")
+    secret.get()
+    assert "hunter2" not in repr(secret)
+    assert "hunter2" not in str(secret)
+
+
+def test_resolve_nested():
+    provider = CountingProvider()
+    settings = {
+        "user": "pathway",
+        "password": pw.secrets.Secret("password", provider=provider),
+        "hosts": [pw.secrets.Secret("host", provider=provider)],
+    }
+    assert pw.secrets.resolve(settings) == {
+        "user": "pathway",
+        "password": "password-1",
+        "hosts": ["host-2"],
+    }
+
+
+def test_postgres_connection_string_resolves_secrets(monkeypatch):
+    monkeypatch.setenv("PG_PASSWORD", "hunter2")
+    settings = {"user": "pathway", "password": pw.secrets.Secret("PG_PASSWORD")}
+    assert (
+        _connection_string_from_settings(settings) == "user=pathway password=hunter2"
+    )
+
+
+def test_s3_settings_resolve_secrets(monkeypatch):
+    monkeypatch.setenv("S3_SECRET_KEY", "hunter2")
+    settings = pw.io.s3.AwsS3Settings(
+        bucket_name="bucket",
+        access_key="access-key",
+        secret_access_key=pw.secrets.Secret("S3_SECRET_KEY"),
+        region="eu-central-1",
+    )
+    assert settings.settings is not None
+
+
+def test_aws_secrets_manager_provider():
+    class FakeClient:
+        class exceptions:
+            class ResourceNotFoundException(Exception):
+                pass
+
+        def get_secret_value(self, SecretId):
+            if SecretId != "prod/db":
+                raise self.exceptions.ResourceNotFoundException()
+            return {"SecretString": json.dumps({"password": "hunter2"})}
+
+    provider = pw.secrets.AwsSecretsManagerProvider(client=FakeClient())
+    assert provider.fetch("prod/db#password") == "hunter2"
+    with pytest.raises(pw.secrets.SecretNotFoundError):
+        provider.fetch("prod/db#user")
+    with pytest.raises(pw.secrets.SecretNotFoundError):
+        provider.fetch("prod/other#password")
+
+
+def test_vault_provider(monkeypatch):
+    requests_made = []
+
+    class FakeResponse:
+        def __init__(self, status_code, body):
+            self.status_code = status_code
+            self._body = body
+
+        def raise_for_status(self):
+            pass
+
+        def json(self):
+            return self._body
+
+    def fake_get(url, headers, timeout):
+        requests_made.append((url, headers))
+        if url.endswith("/db"):
+            return FakeResponse(200, {"data": {"data": {"password": "hunter2"}}})
+        return FakeResponse(404, {})
+
+    monkeypatch.setattr(pw.secrets.requests, "get", fake_get)
+    provider = pw.secrets.VaultSecretsProvider(
+        "https://vault.example.com:8200/", token="vault-token"
+    )
+    assert provider.fetch("db#password") == "hunter2"
+    assert provider.fetch("db") == "hunter2"
+    assert requests_made[0] == (
+        "https://vault.example.com:8200/v1/secret/data/db",
+        {"X-Vault-Token": "vault-token"},
+    )
+    with pytest.raises(pw.secrets.SecretNotFoundError):
+        provider.fetch("other#password")
@@ -0,0 +1,70 @@
+# Copyright © 2024 Pathway
+
+import json
+import logging
+import sys
+
+import pytest
+
+from pathway.internals import api
+from pathway.internals.monitoring import monitor_stats
+from pathway.internals.structured_logging import JsonFormatter, LogContextFilter
+
+
+def _format(record: logging.LogRecord) -> dict:
+    LogContextFilter(run_id="run-1", process_id="0").filter(record)
+    return json.loads(JsonFormatter().format(record))
+
+
+def test_json_formatter_adds_context():
+    record = logging.LogRecord(
+        "pathway_engine.connectors", logging.INFO, __file__, 1, "read %d", (3,), None
+    )
+
+    entry = _format(record)
+
+    assert entry["level"] == "INFO"
+    assert entry["logger"] == "pathway_engine.connectors"
+    assert entry["message"] == "read 3"
+    assert entry["run_id"] == "run-1"
+    assert entry["process_id"] == "0"
+    # the record was not emitted by an engine thread
+    assert entry["worker_id"] is None
+    assert entry["connector_name"] is None
+    assert "exception" not in entry
+
+
+def test_json_formatter_keeps_explicit_fields():
+    record = logging.LogRecord("test", logging.ERROR, __file__, 1, "failed", (), None)
+    record.connector_name = "kafka-input"
+    record.worker_id = 2
+
+    entry = _format(record)
+
+    assert entry["connector_name"] == "kafka-input"
+    assert entry["worker_id"] == 2
+
+
+def test_json_formatter_includes_exception():
+    try:
+        raise RuntimeError("boom")
+    except RuntimeError:
+        record = logging.LogRecord(
+            "test", logging.ERROR, __file__, 1, "failed", (), sys.exc_info()
+        )
+
+    entry = _format(record)
+
+    assert "RuntimeError: boom" in entry["exception"]
+
+
+def test_unknown_log_format():
+    with pytest.raises(ValueError, match="unsupported log format"):
+        with monitor_stats(
+            api.MonitoringLevel.NONE,
+            node_names=[],
+            default_logging=True,
+            process_id="0",
+            log_format="xml",
+        ):
+            pass
@@ -0,0 +1,136 @@
+# Copyright © 2024 Pathway
+
+import datetime
+import pathlib
+import sys
+
+import pytest
+
+import pathway as pw
+from pathway.internals import api
+from pathway.tests.utils import T, assert_table_equality_wo_index, run
+
+
+def _python(code: str) -> list[str]:
+    return [sys.executable, "-c", code]
+
+
+def test_read_plaintext():
+    table = pw.io.subprocess.read(
+        _python("print('foo'); print('bar'); print('baz')"),
+        format="plaintext",
+    )
+
+    assert_table_equality_wo_index(
+        table,
+        T(
+            """
+            data
+            foo
+            bar
+            baz
+            """
+        ),
+    )
+
+
+def test_read_json():
+    class InputSchema(pw.Schema):
+        key: int = pw.column_definition(primary_key=True)
+        value: str
+
+    code = """
+import json
+for key, value in enumerate(["foo", "bar", "baz"]):
+    print(json.dumps({"key": key, "value": value}))
+"""
+    table = pw.io.subprocess.read(_python(code), format="json", schema=InputSchema)
+
+    assert_table_equality_wo_index(
+        table,
+        T(
+            """
+            key | value
+            0   | foo
+            1   | bar
+            2   | baz
+            """
+        ),
+    )
+
+
+def test_read_restarts_on_failure(tmp_path: pathlib.Path):
+    marker_path = tmp_path / "marker"
+    code = f"""
+import os, sys
+if os.path.exists({str(marker_path)!r}):
+    print("second")
+else:
+    open({str(marker_path)!r}, "w").close()
+    print("first")
+    sys.exit(1)
+"""
+    table = pw.io.subprocess.read(
+        _python(code),
+        format="plaintext",
+        restart_policy="on_failure",
+        restart_delay=datetime.timedelta(milliseconds=10),
+    )
+
+    assert_table_equality_wo_index(
+        table,
+        T(
+            """
+            data
+            first
+            second
+            """
+        ),
+    )
+
+
+def test_read_fails_after_max_restarts():
+    table = pw.io.subprocess.read(
+        _python("print('attempt'); raise SystemExit(3)"),
+        format="plaintext",
+        restart_policy="always",
+        max_restarts=2,
+        restart_delay=datetime.timedelta(milliseconds=10),
+    )
+    pw.io.null.write(table)
+
+    with pytest.raises(api.EngineError, match="failed after 2 restarts"):
+        run()
+
+
+def test_read_fails_without_restart():
+    table = pw.io.subprocess.read(_python("raise SystemExit(3)"), format="plaintext")
+    pw.io.null.write(table)
+
+    with pytest.raises(api.EngineError, match="failed"):
+        run()
+
+
+def test_empty_command():
+    with pytest.raises(ValueError, match="the command must not be empty"):
+        pw.io.subprocess.read([])
+
+
+def test_write_plaintext(tmp_path: pathlib.Path):
+    output_path = tmp_path / "output.txt"
+    code = f"""
+import sys
+with open({str(output_path)!r}, "w") as f:
+    f.write(sys.stdin.read())
+"""
+    table = T(
+        """
+        data
+        foo
+        bar
+        """
+    )
+    pw.io.subprocess.write(table, _python(code), format="plaintext")
+    run()
+
+    assert sorted(output_path.read_text().splitlines()) == ["bar", "foo"]
@@ -0,0 +1,119 @@
+# Copyright © 2024 Pathway
+
+import pytest
+
+import pathway as pw
+from pathway.internals.config import PathwayConfig, local_pathway_config
+
+
+def test_attributes_from_env(monkeypatch):
+    monkeypatch.setenv(
+        "PATHWAY_RESOURCE_ATTRIBUTES", "team=search, environment=staging,"
+    )
+    monkeypatch.setenv("PATHWAY_METRIC_ATTRIBUTES", "pipeline=indexer")
+
+    config = PathwayConfig()
+
+    assert config.resource_attributes == {"team": "search", "environment": "staging"}
+    assert config.metric_attributes == {"pipeline": "indexer"}
+
+
+def test_attributes_default_to_empty(monkeypatch):
+    monkeypatch.delenv("PATHWAY_RESOURCE_ATTRIBUTES", raising=False)
+    monkeypatch.delenv("PATHWAY_METRIC_ATTRIBUTES", raising=False)
+
+    config = PathwayConfig()
+
+    assert config.resource_attributes == {}
+    assert config.metric_attributes == {}
+
+
+def test_attributes_from_env_invalid(monkeypatch):
+    monkeypatch.setenv("PATHWAY_RESOURCE_ATTRIBUTES", "team")
+
+    with pytest.raises(ValueError, match="is not a key=value pair"):
+        PathwayConfig()
+
+
+def test_set_monitoring_config_attributes(monkeypatch):
+    monkeypatch.setenv("PATHWAY_METRIC_ATTRIBUTES", "pipeline=indexer")
+
+    with local_pathway_config() as config:
+        pw.set_monitoring_config(
+            server_endpoint="https://example.com:4317",
+            resource_attributes={"team": "search"},
+        )
+
+        assert config.monitoring_server == "https://example.com:4317"
+        assert config.resource_attributes == {"team": "search"}
+        # not passed, so the attributes from the environment are kept
+        assert config.metric_attributes == {"pipeline": "indexer"}
+
+
+def test_metric_filters_from_env(monkeypatch):
+    monkeypatch.setenv("PATHWAY_METRIC_ALLOWLIST", "latency.*, process.memory.usage")
+    monkeypatch.setenv("PATHWAY_METRIC_DENYLIST", "latency.output")
+    monkeypatch.setenv("PATHWAY_TRACE_SAMPLE_RATIO", "0.25")
+
+    config = PathwayConfig()
+
+    assert config.metric_allowlist == ["latency.*", "process.memory.usage"]
+    assert config.metric_denylist == ["latency.output"]
+    assert config.trace_sample_ratio == 0.25
+
+
+def test_trace_sample_ratio_invalid(monkeypatch):
+    monkeypatch.setenv("PATHWAY_TRACE_SAMPLE_RATIO", "1.5")
+
+    with pytest.raises(ValueError, match="is not between 0 and 1"):
+        PathwayConfig()
+
+    monkeypatch.delenv("PATHWAY_TRACE_SAMPLE_RATIO")
+    with local_pathway_config():
+        with pytest.raises(ValueError, match="should be between 0 and 1"):
+            pw.set_monitoring_config(server_endpoint=None, trace_sample_ratio=-0.5)
+
+
+def test_telemetry_export_dir(monkeypatch, tmp_path):
+    monkeypatch.setenv("PATHWAY_TELEMETRY_EXPORT_DIR", str(tmp_path / "from-env"))
+    assert PathwayConfig().telemetry_export_dir == str(tmp_path / "from-env")
+
+    with local_pathway_config() as config:
+        pw.set_monitoring_config(server_endpoint=None, export_dir=str(tmp_path))
+        assert config.telemetry_export_dir == str(tmp_path)
+
+
+def test_signals_enabled_by_default(monkeypatch):
+    for name in [
+        "PATHWAY_USAGE_TELEMETRY_ENABLED",
+        "PATHWAY_METRICS_ENABLED",
+        "PATHWAY_TRACES_ENABLED",
+    ]:
+        monkeypatch.delenv(name, raising=False)
+
+    config = PathwayConfig()
+
+    assert config.usage_telemetry_enabled
+    assert config.metrics_enabled
+    assert config.traces_enabled
+
+
+def test_signals_disabled_independently(monkeypatch):
+    monkeypatch.setenv("PATHWAY_USAGE_TELEMETRY_ENABLED", "false")
+    monkeypatch.delenv("PATHWAY_METRICS_ENABLED", raising=False)
+    monkeypatch.setenv("PATHWAY_TRACES_ENABLED", "0")
+
+    with local_pathway_config() as config:
+        assert not config.usage_telemetry_enabled
+        assert config.metrics_enabled
+        assert not config.traces_enabled
+
+        pw.set_monitoring_config(
+            server_endpoint="https://example.com:4317", metrics_enabled=False
+        )
+
+        assert config.monitoring_server == "https://example.com:4317"
+        assert not config.metrics_enabled
+        # not passed, so the values from the environment are kept
+        assert not config.usage_telemetry_enabled
+        assert not config.traces_enabled
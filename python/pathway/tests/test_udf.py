@@ -3,6 +3,7 @@
 from __future__ import annotations
 
 import asyncio
+import datetime
 import os
 import pathlib
 import re
@@ -14,11 +15,13 @@ from typing import Optional
 from unittest import mock
 
 import numpy as np
+import numpy.typing as npt
 import pytest
 
 import pathway as pw
 from pathway.internals import api
-from pathway.internals.udfs.executors import Executor
+from pathway.internals.parse_graph import G
+from pathway.internals.udfs.executors import Executor, FullyAsyncExecutor
 from pathway.tests.utils import (
     T,
     assert_stream_equality,
@@ -663,14 +666,153 @@ def test_udf_timeout(fully_async):
     expected: type[Exception]
     if fully_async:
         expected = api.EngineError
-    elif sys.version_info < (3, 11):
-        expected = asyncio.exceptions.TimeoutError
     else:
         expected = TimeoutError
     with pytest.raises(expected):
         run_all()
 
 
+def test_udf_timeout_cancels_call():
+    started = threading.Event()
+    cancelled = threading.Event()
+    finished = threading.Event()
+
+    @pw.udf(
+        executor=pw.udfs.async_executor(timeout=0.1, timeout_policy="return_none")
+    )
+    async def inc(a: int) -> int:
+        started.set()
+        try:
+            await asyncio.sleep(1)
+        except asyncio.CancelledError:
+            cancelled.set()
+            raise
+        finished.set()
+        return a + 1
+
+    input = pw.debug.table_from_markdown(
+        """
+        a
+        1
+        """
+    )
+
+    result = input.select(ret=inc(pw.this.a))
+    assert_table_equality(
+        result,
+        T(
+            """
+            ret
+            None
+            """,
+        ).update_types(ret=int | None),
+    )
+    assert started.is_set()
+    assert cancelled.wait(timeout=1)
+    time.sleep(1)
+    assert not finished.is_set()
+
+
+def test_udf_timeout_return_none():
+    @pw.udf(
+        executor=pw.udfs.async_executor(timeout=0.5, timeout_policy="return_none")
+    )
+    async def inc(a: int) -> int:
+        await asyncio.sleep(a)
+        return a + 1
+
+    input = pw.debug.table_from_markdown(
+        """
+        a
+        0
+        5
+        """
+    )
+
+    result = input.select(ret=inc(pw.this.a))
+    assert_table_equality(
+        result,
+        T(
+            """
+            ret
+            1
+            None
+            """,
+        ).update_types(ret=int | None),
+    )
+
+
+def test_udf_timeout_return_none_with_retries():
+    with pytest.raises(ValueError, match="can't be used together with retry_strategy"):
+        pw.udfs.async_executor(
+            timeout=1.0,
+            timeout_policy="return_none",
+            retry_strategy=pw.udfs.FixedDelayRetryStrategy(),
+        )
+
+
+@xfail_on_multiple_threads  # identical rows can be processed by different workers
+def test_async_udf_deduplicate():
+    calls = []
+
+    @pw.udf(executor=pw.udfs.async_executor(deduplicate=True))
+    async def inc(a: int) -> int:
+        calls.append(a)
+        return a + 1
+
+    input = pw.debug.table_from_markdown(
+        """
+        a
+        1
+        2
+        1
+        1
+        2
+        """
+    )
+
+    result = input.select(ret=inc(pw.this.a))
+    assert_table_equality(
+        result,
+        input.select(ret=pw.this.a + 1),
+    )
+    assert sorted(calls) == [1, 2]
+
+
+@xfail_on_multiple_threads  # the limit applies to a single worker
+def test_async_udf_capacity():
+    in_flight = 0
+    max_in_flight = 0
+
+    @pw.udf(executor=pw.udfs.async_executor(capacity=2))
+    async def inc(a: int) -> int:
+        nonlocal in_flight, max_in_flight
+        in_flight += 1
+        max_in_flight = max(max_in_flight, in_flight)
+        await asyncio.sleep(0.05)
+        in_flight -= 1
+        return a + 1
+
+    input = pw.debug.table_from_markdown(
+        """
+        a
+        1
+        2
+        3
+        4
+        5
+        6
+        """
+    )
+
+    result = input.select(ret=inc(pw.this.a))
+    assert_table_equality(
+        result,
+        input.select(ret=pw.this.a + 1),
+    )
+    assert max_in_flight == 2
+
+
 @pytest.mark.parametrize("fully_async", [True, False])
 def test_udf_too_fast_for_timeout(fully_async):
     if fully_async:
@@ -872,6 +1014,289 @@ def test_udf_in_memory_cache_multiple_places(sync: str) -> None:
     assert internal_inc.call_count == 3
 
 
+def test_udf_in_memory_cache_batch() -> None:
+    internal_inc = mock.Mock()
+
+    @pw.udf(cache_strategy=pw.udfs.InMemoryCache(), max_batch_size=2)
+    def inc(a: list[int]) -> list[int]:
+        internal_inc(a)
+        return [x + 1 for x in a]
+
+    input = pw.debug.table_from_markdown(
+        """
+        a | __time__
+        1 |     2
+        2 |     2
+        1 |     2
+        1 |     4
+        3 |     4
+    """
+    )
+    result = input.select(ret=inc(pw.this.a))
+    expected = T(
+        """
+        ret
+        2
+        3
+        2
+        2
+        4
+        """
+    )
+    assert_table_equality(result, expected)
+    calls = [x for call in internal_inc.call_args_list for x in call.args[0]]
+    assert sorted(calls) == [1, 2, 3]
+
+
+def test_udf_in_memory_cache_standalone() -> None:
+    internal_inc = mock.Mock()
+
+    def inc(a: int) -> int:
+        internal_inc(a)
+        return a + 1
+
+    cached_inc = pw.udfs.with_cache_strategy(inc, pw.udfs.InMemoryCache())
+    assert [cached_inc(1), cached_inc(2), cached_inc(1)] == [2, 3, 2]
+    assert internal_inc.call_count == 2
+
+
+def test_udf_in_memory_cache_standalone_ttl() -> None:
+    internal_inc = mock.Mock()
+
+    def inc(a: int) -> int:
+        internal_inc(a)
+        return a + 1
+
+    expiring_inc = pw.udfs.with_cache_strategy(
+        inc, pw.udfs.InMemoryCache(ttl=datetime.timedelta(0))
+    )
+    assert [expiring_inc(1), expiring_inc(1)] == [2, 2]
+    assert internal_inc.call_count == 2
+
+    cached_inc = pw.udfs.with_cache_strategy(
+        inc, pw.udfs.InMemoryCache(ttl=datetime.timedelta(hours=1))
+    )
+    assert [cached_inc(1), cached_inc(1)] == [2, 2]
+    assert internal_inc.call_count == 3
+
+
+def test_udf_in_memory_cache_persisted(tmp_path: pathlib.Path) -> None:
+    internal_inc = mock.Mock()
+    cache_strategy = pw.udfs.InMemoryCache(
+        persistence_backend=pw.persistence.Backend.filesystem(tmp_path / "cache")
+    )
+
+    def run_pipeline() -> None:
+        G.clear()
+
+        @pw.udf(cache_strategy=cache_strategy)
+        def inc(a: int) -> int:
+            internal_inc(a)
+            return a + 1
+
+        input = T(
+            """
+            a
+            1
+            2
+            1
+            """
+        )
+        expected = T(
+            """
+            ret
+            2
+            3
+            2
+            """
+        )
+        assert_table_equality(input.select(ret=inc(pw.this.a)), expected)
+
+    run_pipeline()
+    assert internal_inc.call_count == 2
+    # the results are read back from the backend by a new engine cache
+    run_pipeline()
+    assert internal_inc.call_count == 2
+
+
+def test_udf_in_memory_cache_persisted_requires_engine(tmp_path: pathlib.Path):
+    def inc(a: int) -> int:
+        return a + 1
+
+    cache_strategy = pw.udfs.InMemoryCache(
+        persistence_backend=pw.persistence.Backend.filesystem(tmp_path / "cache")
+    )
+    with pytest.raises(ValueError, match="cached by the engine"):
+        pw.udfs.with_cache_strategy(inc, cache_strategy)
+
+
+@pytest.mark.parametrize("sync", [True, False])
+def test_udf_error_policy_return_default(sync: bool) -> None:
+    policy = pw.udfs.ErrorPolicy(on_error="return_default")
+
+    if sync:
+
+        @pw.udf(error_policy=policy)
+        def inv(a: int) -> int:
+            return 12 // a
+
+    else:
+
+        @pw.udf(error_policy=policy)
+        async def inv(a: int) -> int:
+            return 12 // a
+
+    input = pw.debug.table_from_markdown(
+        """
+        a
+        3
+        0
+        """
+    )
+    result = input.select(ret=inv(pw.this.a))
+    expected = T(
+        """
+        ret
+        4
+        None
+        """
+    ).update_types(ret=int | None)
+    assert_table_equality(result, expected)
+
+
+def test_udf_error_policy_skips_errors_in_arguments() -> None:
+    @pw.udf(
+        error_policy=pw.udfs.ErrorPolicy(on_error="return_default", default_value=0)
+    )
+    def inc(a: int) -> int:
+        return a + 1
+
+    input = pw.debug.table_from_markdown(
+        """
+        a | b
+        1 | 1
+        2 | 0
+        """
+    )
+    result = input.select(ret=inc(pw.this.a // pw.this.b))
+    result = result.select(ret=pw.fill_error(pw.this.ret, -1))
+    expected = T(
+        """
+        ret
+         2
+        -1
+        """
+    )
+    assert_table_equality(result, expected, terminate_on_error=False)
+
+
+@pytest.mark.parametrize("sync", [True, False])
+def test_udf_error_policy_retries(sync: bool) -> None:
+    attempts: dict[int, int] = {}
+    policy = pw.udfs.ErrorPolicy(
+        max_retries=2, retry_delay=datetime.timedelta(milliseconds=10)
+    )
+
+    def flaky_body(a: int) -> int:
+        attempts[a] = attempts.get(a, 0) + 1
+        if attempts[a] < 3:
+            raise ValueError("not yet")
+        return a
+
+    if sync:
+
+        @pw.udf(error_policy=policy)
+        def flaky(a: int) -> int:
+            return flaky_body(a)
+
+    else:
+
+        @pw.udf(error_policy=policy)
+        async def flaky(a: int) -> int:
+            return flaky_body(a)
+
+    input = pw.debug.table_from_markdown(
+        """
+        a
+        1
+        2
+        """
+    )
+    result = input.select(ret=flaky(pw.this.a))
+    expected = T(
+        """
+        ret
+        1
+        2
+        """
+    )
+    assert_table_equality(result, expected)
+    assert attempts == {1: 3, 2: 3}
+
+
+@pytest.mark.parametrize("sync", [True, False])
+def test_udf_error_policy_retries_exhausted(sync: bool) -> None:
+    policy = pw.udfs.ErrorPolicy(
+        max_retries=1, retry_delay=datetime.timedelta(milliseconds=10)
+    )
+
+    if sync:
+
+        @pw.udf(error_policy=policy)
+        def failing(a: int) -> int:
+            raise ValueError("always failing")
+
+    else:
+
+        @pw.udf(error_policy=policy)
+        async def failing(a: int) -> int:
+            raise ValueError("always failing")
+
+    input = pw.debug.table_from_markdown(
+        """
+        a
+        1
+        """
+    )
+    input.select(ret=failing(pw.this.a))
+    with pytest.raises(ValueError, match="always failing"):
+        run_all()
+
+
+def test_udf_error_policy_batch_retries() -> None:
+    batches: list[list[int]] = []
+    failed: set[int] = set()
+    policy = pw.udfs.ErrorPolicy(
+        max_retries=1, retry_delay=datetime.timedelta(milliseconds=10)
+    )
+
+    @pw.udf(error_policy=policy, max_batch_size=4)
+    def flaky(a: list[int]) -> list[int]:
+        batches.append(sorted(a))
+        if 2 in a and 2 not in failed:
+            failed.add(2)
+            raise ValueError("not yet")
+        return a
+
+    input = pw.debug.table_from_markdown(
+        """
+        a | __time__
+        1 |     2
+        2 |     2
+        """
+    )
+    result = input.select(ret=flaky(pw.this.a))
+    expected = T(
+        """
+        ret
+        1
+        2
+        """
+    )
+    assert_table_equality(result, expected)
+    assert batches == [[1, 2], [1, 2]]
+
+
 def test_udf_warn_on_too_specific_return_type() -> None:
     @pw.udf(return_type=int)
     def f(a: int) -> Optional[int]:
@@ -1088,6 +1513,14 @@ def test_fully_async_udf():
     )
 
 
+@pytest.mark.parametrize(
+    "kwargs", [dict(timeout_policy="return_none"), dict(deduplicate=True)]
+)
+def test_fully_async_executor_rejects_engine_options(kwargs):
+    with pytest.raises(ValueError, match="not supported by the fully asynchronous"):
+        FullyAsyncExecutor(autocommit_duration_ms=None, **kwargs)
+
+
 def test_fully_async_udf_propagation_allowed():
     @pw.udf(executor=pw.udfs.fully_async_executor())
     async def inc(a: int) -> int:
@@ -1574,6 +2007,38 @@ def test_batch_udf_numpy():
     assert_stream_equality(result, expected)
 
 
+def test_batch_udf_propagate_none():
+    seen = []
+
+    @pw.udf(max_batch_size=16, propagate_none=True)
+    def foo(a: list[int], b: list[int]) -> list[int]:
+        seen.extend(a)
+        return [a_i + b_i for a_i, b_i in zip(a, b, strict=True)]
+
+    input = pw.debug.table_from_markdown(
+        """
+          | a | b
+        1 | 1 | 1
+        2 |   | 0
+        3 | 3 |
+        4 | 4 | 2
+        """
+    )
+
+    result = input.select(c=foo(pw.this.a, pw.this.b))
+    expected = pw.debug.table_from_markdown(
+        """
+          | c
+        1 | 2
+        2 |
+        3 |
+        4 | 6
+    """
+    ).update_types(c=int | None)
+    assert_table_equality(result, expected)
+    assert sorted(seen) == [1, 4]
+
+
 @xfail_on_multiple_threads
 def test_batch_udf_incorrect_rows_returned():
 
@@ -1653,3 +2118,168 @@ def test_batch_async_udf_not_supported():
         @pw.udf(max_batch_size=16)
         async def foo(a: list[int], b: list[int]) -> list[int]:
             return [a_i + b_i for a_i, b_i in zip(a, b)]
+
+
+@pytest.mark.parametrize("max_batch_size", [None, 1, 2])
+def test_vectorized_udf_matches_row_wise(max_batch_size):
+    @pw.udf
+    def row_wise(a: int, b: float, c: bool, d: str) -> float:
+        return (a * b if c else a - b) + len(d)
+
+    @pw.udf(vectorized=True, max_batch_size=max_batch_size)
+    def vectorized(
+        a: npt.NDArray[np.int64],
+        b: npt.NDArray[np.float64],
+        c: npt.NDArray[np.bool_],
+        d: list[str],
+    ) -> npt.NDArray[np.float64]:
+        assert isinstance(a, np.ndarray) and a.dtype == np.int64
+        assert isinstance(b, np.ndarray) and b.dtype == np.float64
+        assert isinstance(c, np.ndarray) and c.dtype == np.bool_
+        assert isinstance(d, list)
+        return np.where(c, a * b, a - b) + np.array([len(d_i) for d_i in d])
+
+    input = pw.debug.table_from_markdown(
+        """
+        a | b    | c     | d
+        1 | 1.5  | True  | x
+        2 | 0.25 | False | yy
+        3 | -2.0 | True  | zzz
+        4 | 8.0  | False | w
+        """
+    )
+
+    assert_table_equality(
+        input.select(r=vectorized(pw.this.a, pw.this.b, pw.this.c, pw.this.d)),
+        input.select(r=row_wise(pw.this.a, pw.this.b, pw.this.c, pw.this.d)),
+    )
+
+
+def test_vectorized_udf_list_result_matches_row_wise():
+    @pw.udf
+    def row_wise(a: int) -> str:
+        return f"<{a}>"
+
+    @pw.udf(vectorized=True)
+    def vectorized(a: npt.NDArray[np.int64]) -> list[str]:
+        return [f"<{a_i}>" for a_i in a.tolist()]
+
+    input = pw.debug.table_from_markdown(
+        """
+        a
+        1
+        20
+        300
+        """
+    )
+
+    assert_table_equality(
+        input.select(r=vectorized(pw.this.a)),
+        input.select(r=row_wise(pw.this.a)),
+    )
+
+
+def test_vectorized_udf_optional_column_passed_as_masked_array():
+    @pw.udf(vectorized=True)
+    def foo(a: np.ma.MaskedArray) -> list[str]:
+        assert isinstance(a, np.ma.MaskedArray) and a.dtype == np.int64
+        return ["masked" if a_i is np.ma.masked else f"v{a_i}" for a_i in a]
+
+    input = pw.debug.table_from_markdown(
+        """
+          | a
+        1 | 1
+        2 |
+        """
+    )
+
+    result = input.select(t=foo(pw.this.a))
+    expected = pw.debug.table_from_markdown(
+        """
+          | t
+        1 | v1
+        2 | masked
+        """
+    )
+    assert_table_equality(result, expected)
+
+
+@pytest.mark.parametrize("propagate_none", [True, False])
+def test_vectorized_udf_argument_type_same_in_every_batch(propagate_none):
+    types = []
+
+    @pw.udf(vectorized=True, max_batch_size=1, propagate_none=propagate_none)
+    def foo(a: npt.NDArray[np.float64]) -> list[int]:
+        types.append(type(a))
+        return [1] * len(a)
+
+    input = pw.debug.table_from_markdown(
+        """
+          | a
+        1 | 1.5
+        2 |
+        """
+    )
+
+    input.select(r=foo(pw.this.a))
+    run_all()
+    expected = np.ndarray if propagate_none else np.ma.MaskedArray
+    assert types and all(t is expected for t in types)
+
+
+def test_vectorized_udf_other_columns_passed_as_list():
+    @pw.udf(vectorized=True)
+    def foo(a: list[str]) -> list[int]:
+        assert isinstance(a, list)
+        return [len(a_i) for a_i in a]
+
+    input = pw.debug.table_from_markdown(
+        """
+        a
+        x
+        yy
+        """
+    )
+
+    result = input.select(n=foo(pw.this.a))
+    expected = pw.debug.table_from_markdown(
+        """
+        n
+        1
+        2
+        """
+    )
+    assert_table_equality_wo_index(result, expected)
+
+
+def test_vectorized_udf_annotation_has_to_be_batch():
+    @pw.udf(vectorized=True)
+    def foo(a: npt.NDArray[np.int64]) -> int:
+        return int(a.sum())
+
+    input = pw.debug.table_from_markdown(
+        """
+        a
+        1
+        """
+    )
+
+    with pytest.raises(
+        ValueError,
+        match=re.escape(
+            "A vectorized UDF has to return a list or a typed numpy array"
+            + " but is annotated as returning <class 'int'>"
+        ),
+    ):
+        input.select(c=foo(pw.this.a))
+
+
+def test_vectorized_async_udf_not_supported():
+    with pytest.raises(
+        ValueError,
+        match=re.escape("Batching is currently supported only for synchronous UDFs."),
+    ):
+
+        @pw.udf(vectorized=True)
+        async def foo(a: npt.NDArray[np.int64]) -> list[int]:
+            return a.tolist()
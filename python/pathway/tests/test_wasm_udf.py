@@ -0,0 +1,107 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+import datetime
+import pathlib
+
+import pytest
+
+import pathway as pw
+from pathway.tests.utils import T, assert_table_equality, run_all
+
+# `first` returns its only argument by stripping the brackets of the JSON array
+MODULE_SOURCE = """
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 1024))
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "first") (param $ptr i32) (param $len i32) (result i64)
+    (i64.or
+      (i64.shl
+        (i64.extend_i32_u (i32.add (local.get $ptr) (i32.const 1)))
+        (i64.const 32))
+      (i64.extend_i32_u (i32.sub (local.get $len) (i32.const 2)))))
+  (func (export "spin") (param i32 i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0)))
+"""
+
+
+@pytest.fixture
+def module(tmp_path) -> pathlib.Path:
+    path = tmp_path / "udf.wat"
+    path.write_text(MODULE_SOURCE)
+    return path
+
+
+def test_wasm_apply(module):
+    t = T(
+        """
+        a | s
+        1 | abc
+        3 | Hello
+        """
+    )
+    result = t.select(
+        a=pw.wasm_apply(module, "first", pw.this.a, return_type=int),
+        s=pw.wasm_apply(module, "first", pw.this.s, return_type=str),
+    )
+    assert_table_equality(result, t)
+
+
+def test_wasm_apply_propagate_none(module):
+    t = T(
+        """
+          | a
+        1 | 1
+        2 |
+        """
+    )
+    result = t.select(
+        a=pw.wasm_apply(
+            module, "first", pw.this.a, return_type=int, propagate_none=True
+        ),
+    )
+    assert_table_equality(result, t)
+
+
+def test_wasm_apply_timeout(module):
+    t = T(
+        """
+        a
+        1
+        """
+    )
+    t.select(
+        r=pw.wasm_apply(
+            module,
+            "spin",
+            pw.this.a,
+            return_type=int,
+            timeout=datetime.timedelta(milliseconds=50),
+        )
+    )
+    with pytest.raises(Exception, match="time limit"):
+        run_all()
+
+
+def test_wasm_apply_missing_function(module):
+    t = T(
+        """
+        a
+        1
+        """
+    )
+    t.select(r=pw.wasm_apply(module, "missing", pw.this.a, return_type=int))
+    with pytest.raises(ValueError, match='function "missing" not exported'):
+        run_all()
+
+
+def test_wasm_apply_requires_arguments(module):
+    with pytest.raises(ValueError, match="requires at least one expression"):
+        pw.wasm_apply(module, "first", return_type=int)
@@ -49,6 +49,7 @@ from pathway.internals.udfs import (
     CacheStrategy,
     DefaultCache,
     DiskCache,
+    ErrorPolicy,
     ExponentialBackoffRetryStrategy,
     FixedDelayRetryStrategy,
     InMemoryCache,
@@ -75,6 +76,7 @@ __all__ = [
     "DefaultCache",
     "DiskCache",
     "InMemoryCache",
+    "ErrorPolicy",
     "AsyncRetryStrategy",
     "ExponentialBackoffRetryStrategy",
     "FixedDelayRetryStrategy",
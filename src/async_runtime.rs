@@ -1,9 +1,338 @@
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
 use std::io;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
 
+use futures::stream::{self, StreamExt};
+use once_cell::sync::Lazy;
+use scopeguard::guard;
 use tokio::runtime::Runtime as TokioRuntime;
 
-pub fn create_async_tokio_runtime() -> Result<TokioRuntime, io::Error> {
-    tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()
+use crate::engine::error::{DynError, DynResult};
+
+/// Parameters of the runtimes created with [`create_async_tokio_runtime`]. They apply to each
+/// runtime separately: every connector creates its own one, so the total number of threads
+/// grows with the number of connectors. The connectors of a runtime group share one runtime.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    /// Number of worker threads of each runtime. If not set, the tasks are run on the thread
+    /// blocking on the runtime. The runtime of a group has one worker thread in that case.
+    pub worker_threads_per_runtime: Option<usize>,
+    /// Maximum number of threads spawned by each runtime for blocking operations. If not set,
+    /// the tokio default is used.
+    pub max_blocking_threads: Option<usize>,
+    /// Prefix of the names of the runtime threads, followed by a sequence number.
+    pub thread_name_prefix: Option<String>,
+    /// Unique names of the connectors sharing a dedicated runtime, by the name of the group.
+    /// The threads of the runtime of a group are named after it.
+    pub runtime_groups: HashMap<String, Vec<String>>,
+}
+
+impl RuntimeConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.worker_threads_per_runtime == Some(0) {
+            return Err("the number of async worker threads must be positive".to_string());
+        }
+        if self.max_blocking_threads == Some(0) {
+            return Err(
+                "the maximum number of async blocking threads must be positive".to_string(),
+            );
+        }
+        let mut connector_groups: HashMap<&str, &str> = HashMap::new();
+        for (group, connectors) in &self.runtime_groups {
+            for connector in connectors {
+                let other_group = *connector_groups.entry(connector).or_insert(group);
+                if other_group != group {
+                    return Err(format!(
+                        "connector {connector:?} is assigned to two runtime groups: {other_group:?} and {group:?}"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn runtime_group(&self, unique_name: &str) -> Option<&str> {
+        self.runtime_groups
+            .iter()
+            .find(|(_, connectors)| connectors.iter().any(|connector| connector == unique_name))
+            .map(|(group, _)| group.as_str())
+    }
+}
+
+static RUNTIME_CONFIG: Lazy<RwLock<RuntimeConfig>> =
+    Lazy::new(|| RwLock::new(RuntimeConfig::default()));
+
+/// The runtimes of the groups, kept as long as a connector of the group uses them.
+static GROUP_RUNTIMES: Lazy<Mutex<HashMap<String, Weak<TokioRuntime>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+thread_local! {
+    /// The unique name of the connector constructed on this thread, if any.
+    static CURRENT_CONNECTOR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Sets the parameters of the runtimes created from now on.
+pub fn set_runtime_config(config: RuntimeConfig) {
+    *RUNTIME_CONFIG.write().unwrap() = config;
+    // the connectors created from now on get the runtimes of the new groups
+    GROUP_RUNTIMES.lock().unwrap().clear();
+}
+
+pub fn runtime_config() -> RuntimeConfig {
+    RUNTIME_CONFIG.read().unwrap().clone()
+}
+
+/// A handle to an asynchronous runtime. The connectors of a runtime group share the runtime of
+/// the group, the other ones have their own runtimes.
+#[derive(Debug, Clone)]
+pub struct AsyncRuntime(Arc<TokioRuntime>);
+
+impl AsyncRuntime {
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for AsyncRuntime {
+    type Target = TokioRuntime;
+
+    fn deref(&self) -> &TokioRuntime {
+        &self.0
+    }
+}
+
+/// Calls `f`, which constructs the connector with the given unique name. The runtimes created
+/// by `f` with [`create_async_tokio_runtime`] are the runtime of the group of the connector,
+/// if it is assigned to one.
+pub fn with_connector_runtime<T>(unique_name: Option<&str>, f: impl FnOnce() -> T) -> T {
+    let previous = CURRENT_CONNECTOR.replace(unique_name.map(ToString::to_string));
+    let _restore = guard(previous, |previous| CURRENT_CONNECTOR.set(previous));
+    f()
+}
+
+/// Returns the runtime of the group, if any connector of the group uses it.
+pub fn group_runtime(group: &str) -> Option<AsyncRuntime> {
+    GROUP_RUNTIMES
+        .lock()
+        .unwrap()
+        .get(group)
+        .and_then(Weak::upgrade)
+        .map(AsyncRuntime)
+}
+
+fn build_runtime(
+    config: &RuntimeConfig,
+    worker_threads: Option<usize>,
+    thread_name_prefix: Option<String>,
+) -> Result<TokioRuntime, io::Error> {
+    let mut builder = match worker_threads {
+        Some(worker_threads) => {
+            let mut builder = tokio::runtime::Builder::new_multi_thread();
+            builder.worker_threads(worker_threads);
+            builder
+        }
+        None => tokio::runtime::Builder::new_current_thread(),
+    };
+    if let Some(max_blocking_threads) = config.max_blocking_threads {
+        builder.max_blocking_threads(max_blocking_threads);
+    }
+    if let Some(prefix) = thread_name_prefix {
+        let next_id = Arc::new(AtomicUsize::new(0));
+        builder.thread_name_fn(move || {
+            format!("{prefix}-{}", next_id.fetch_add(1, Ordering::Relaxed))
+        });
+    }
+    builder.enable_all().build()
+}
+
+/// Creates a runtime with the current [`RuntimeConfig`]. Every connector creates its own
+/// runtime, so they are isolated from each other: a connector stalled on an external service
+/// can't starve the others, nor the persistence uploads.
+///
+/// Connectors assigned to a runtime group in the config share the runtime of the group
+/// instead. The assignment applies to the runtimes created while the connector is constructed,
+/// in [`with_connector_runtime`].
+pub fn create_async_tokio_runtime() -> Result<AsyncRuntime, io::Error> {
+    let config = runtime_config();
+    config
+        .validate()
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?;
+    let group = CURRENT_CONNECTOR.with_borrow(|unique_name| {
+        unique_name
+            .as_deref()
+            .and_then(|unique_name| config.runtime_group(unique_name))
+            .map(ToString::to_string)
+    });
+    let Some(group) = group else {
+        let runtime = build_runtime(
+            &config,
+            config.worker_threads_per_runtime,
+            config.thread_name_prefix.clone(),
+        )?;
+        return Ok(AsyncRuntime(Arc::new(runtime)));
+    };
+
+    let mut group_runtimes = GROUP_RUNTIMES.lock().unwrap();
+    if let Some(runtime) = group_runtimes.get(&group).and_then(Weak::upgrade) {
+        return Ok(AsyncRuntime(runtime));
+    }
+    // the runtime is shared by the threads of several connectors, so it needs worker threads
+    let thread_name_prefix = match &config.thread_name_prefix {
+        Some(prefix) => format!("{prefix}-{group}"),
+        None => group.clone(),
+    };
+    let runtime = Arc::new(build_runtime(
+        &config,
+        Some(config.worker_threads_per_runtime.unwrap_or(1)),
+        Some(thread_name_prefix),
+    )?);
+    group_runtimes.insert(group, Arc::downgrade(&runtime));
+    Ok(AsyncRuntime(runtime))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeoutPolicy {
+    /// The call that exceeded the timeout produces an error.
+    #[default]
+    Fail,
+    /// The call that exceeded the timeout produces a fallback value.
+    ReturnFallback,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AsyncCallLimits {
+    /// Maximum number of calls awaited at the same time. `None` means no limit.
+    pub max_in_flight: Option<usize>,
+    /// Maximum duration of a single call.
+    pub timeout: Option<Duration>,
+    pub timeout_policy: TimeoutPolicy,
+    /// If set, concurrent calls with identical inputs are executed only once.
+    pub deduplicate: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("asynchronous call timed out after {0:?}")]
+pub struct CallTimedOut(pub Duration);
+
+fn clone_result<T: Clone>(result: &DynResult<T>) -> DynResult<T> {
+    match result {
+        Ok(value) => Ok(value.clone()),
+        Err(error) => Err(DynError::from(error.to_string())),
+    }
+}
+
+thread_local! {
+    /// The runtime driving the calls made by [`execute_with_limits`] on this thread. It is
+    /// created on the first call and reused by the following batches.
+    static CALL_RUNTIME: OnceCell<io::Result<TokioRuntime>> = const { OnceCell::new() };
+}
+
+/// Executes `call` for all `inputs` on the current-thread runtime of the calling thread and
+/// returns the results in the order of inputs, respecting the provided `limits`.
+///
+/// Calls exceeding the timeout are dropped, so `call` should return futures that cancel the
+/// underlying work when dropped. They are resolved with an error or, depending on the timeout
+/// policy, with the result of `fallback`. When deduplication is enabled, the results of
+/// calls with repeated inputs are shared.
+pub fn execute_with_limits<I, T, F, Fut>(
+    inputs: &[I],
+    limits: &AsyncCallLimits,
+    call: F,
+    fallback: impl Fn() -> T,
+) -> Vec<DynResult<T>>
+where
+    I: Eq + Hash,
+    T: Clone,
+    F: Fn(&I) -> Fut,
+    Fut: Future<Output = DynResult<T>>,
+{
+    let (unique_inputs, positions): (Vec<&I>, Vec<usize>) = if limits.deduplicate {
+        let mut first_occurrence: HashMap<&I, usize> = HashMap::new();
+        let mut unique_inputs = Vec::new();
+        let positions = inputs
+            .iter()
+            .map(|input| {
+                *first_occurrence.entry(input).or_insert_with(|| {
+                    unique_inputs.push(input);
+                    unique_inputs.len() - 1
+                })
+            })
+            .collect();
+        (unique_inputs, positions)
+    } else {
+        (inputs.iter().collect(), (0..inputs.len()).collect())
+    };
+
+    let max_in_flight = limits.max_in_flight.unwrap_or(usize::MAX).max(1);
+    let calls = stream::iter(unique_inputs)
+        .map(|input| {
+            let future = call(input);
+            let fallback = &fallback;
+            async move {
+                let Some(timeout) = limits.timeout else {
+                    return future.await;
+                };
+                match tokio::time::timeout(timeout, future).await {
+                    Ok(result) => result,
+                    Err(_) => match limits.timeout_policy {
+                        TimeoutPolicy::Fail => Err(CallTimedOut(timeout).into()),
+                        TimeoutPolicy::ReturnFallback => Ok(fallback()),
+                    },
+                }
+            }
+        })
+        .buffered(max_in_flight)
+        .collect::<Vec<DynResult<T>>>();
+    let unique_results = CALL_RUNTIME.with(|runtime| {
+        match runtime.get_or_init(|| {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+        }) {
+            Ok(runtime) => Ok(runtime.block_on(calls)),
+            Err(error) => Err(format!("failed to create async runtime: {error}")),
+        }
+    });
+    let unique_results = match unique_results {
+        Ok(unique_results) => unique_results,
+        Err(message) => {
+            return inputs
+                .iter()
+                .map(|_| Err(DynError::from(message.clone())))
+                .collect();
+        }
+    };
+
+    if !limits.deduplicate {
+        return unique_results;
+    }
+    let mut unique_results: Vec<Option<DynResult<T>>> =
+        unique_results.into_iter().map(Some).collect();
+    let mut remaining_uses = vec![0_usize; unique_results.len()];
+    for position in &positions {
+        remaining_uses[*position] += 1;
+    }
+    positions
+        .into_iter()
+        .map(|position| {
+            remaining_uses[position] -= 1;
+            if remaining_uses[position] == 0 {
+                unique_results[position]
+                    .take()
+                    .expect("each result is taken once")
+            } else {
+                clone_result(
+                    unique_results[position]
+                        .as_ref()
+                        .expect("result is present until its last use"),
+                )
+            }
+        })
+        .collect()
 }
@@ -0,0 +1,177 @@
+// Copyright © 2024 Pathway
+
+//! Synthetic sources and sinks of the benchmarks.
+//!
+//! Each row is derived from the seed and its index only, so the generated data doesn't depend on
+//! the number of workers or on how the rows are split between them.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::engine::{Key, Value};
+
+const TAGS: [&str; 8] = [
+    "books",
+    "electronics",
+    "garden",
+    "grocery",
+    "health",
+    "music",
+    "sports",
+    "toys",
+];
+
+const REGIONS: [&str; 4] = ["north", "south", "east", "west"];
+
+/// The interval between the event times of the consecutive orders, in milliseconds.
+const ORDER_INTERVAL_MS: i64 = 10;
+
+/// The maximum delay of an order with respect to its position in the stream, in milliseconds.
+const MAX_ORDER_DELAY_MS: u64 = 1000;
+
+/// Mixes the bits of the value, as the finalizer of SplitMix64 does.
+fn mix(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+/// A single synthetic order, the input row of all the pipeline shapes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntheticOrder {
+    pub id: i64,
+    pub user: i64,
+    /// The amount in cents.
+    pub amount: i64,
+    /// The event time in milliseconds. The orders come roughly in the order of time.
+    pub time: i64,
+    pub tag: &'static str,
+}
+
+impl SyntheticOrder {
+    /// The names of the fields, in the order of [`values`](Self::values).
+    pub const FIELD_NAMES: [&'static str; 5] = ["id", "user", "amount", "time", "tag"];
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    pub fn generate(seed: u64, index: u64, n_users: u64) -> Self {
+        let hash = mix(seed ^ mix(index));
+        let id = index as i64;
+        Self {
+            id,
+            user: (hash % n_users.max(1)) as i64,
+            amount: ((hash >> 16) % 100_000) as i64,
+            time: id * ORDER_INTERVAL_MS + ((hash >> 40) % MAX_ORDER_DELAY_MS) as i64,
+            tag: TAGS[(hash >> 8) as usize % TAGS.len()],
+        }
+    }
+
+    pub fn key(&self) -> Key {
+        Key::for_value(&Value::Int(self.id))
+    }
+
+    pub fn values(&self) -> Value {
+        Value::Tuple(
+            [
+                Value::Int(self.id),
+                Value::Int(self.user),
+                Value::Int(self.amount),
+                Value::Int(self.time),
+                Value::from(self.tag),
+            ]
+            .into(),
+        )
+    }
+
+    pub fn to_json_line(&self) -> Vec<u8> {
+        json!({
+            "id": self.id,
+            "user": self.user,
+            "amount": self.amount,
+            "time": self.time,
+            "tag": self.tag,
+        })
+        .to_string()
+        .into_bytes()
+    }
+}
+
+/// The key of the user with the given id, by which the orders are joined with the users.
+pub fn user_key(user: i64) -> Key {
+    Key::for_value(&Value::Int(user))
+}
+
+/// A row of the users table: the id of the user and its region.
+#[allow(clippy::cast_possible_truncation)]
+pub fn user_row(seed: u64, user: i64) -> (Key, Value) {
+    let region = REGIONS[mix(seed ^ user.unsigned_abs()) as usize % REGIONS.len()];
+    (
+        user_key(user),
+        Value::Tuple([Value::Int(user), Value::from(region)].into()),
+    )
+}
+
+/// Generates the orders with the indices from `0` to `rows`, or the part of them assigned to a
+/// single worker.
+pub struct SyntheticSource {
+    seed: u64,
+    n_users: u64,
+    next_index: u64,
+    end_index: u64,
+    step: u64,
+}
+
+impl SyntheticSource {
+    pub fn new(seed: u64, n_users: u64, rows: u64) -> Self {
+        Self {
+            seed,
+            n_users,
+            next_index: 0,
+            end_index: rows,
+            step: 1,
+        }
+    }
+
+    /// Restricts the source to the rows of the worker with the given index.
+    #[must_use]
+    pub fn for_worker(mut self, worker_index: usize, n_workers: usize) -> Self {
+        self.next_index = worker_index as u64;
+        self.step = n_workers.max(1) as u64;
+        self
+    }
+}
+
+impl Iterator for SyntheticSource {
+    type Item = SyntheticOrder;
+
+    fn next(&mut self) -> Option<SyntheticOrder> {
+        if self.next_index >= self.end_index {
+            return None;
+        }
+        let order = SyntheticOrder::generate(self.seed, self.next_index, self.n_users);
+        self.next_index += self.step;
+        Some(order)
+    }
+}
+
+/// Counts the updates of the output of a pipeline. The clones share the counter.
+#[derive(Clone, Debug, Default)]
+pub struct CountingSink {
+    updates: Arc<AtomicU64>,
+}
+
+impl CountingSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, n_updates: u64) {
+        self.updates.fetch_add(n_updates, Ordering::Relaxed);
+    }
+
+    pub fn updates(&self) -> u64 {
+        self.updates.load(Ordering::Relaxed)
+    }
+}
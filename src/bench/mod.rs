@@ -0,0 +1,209 @@
+// Copyright © 2024 Pathway
+
+//! Throughput benchmarks of the standard pipeline shapes.
+//!
+//! A benchmark feeds synthetic orders to one of the [`PipelineShape`]s in batches, waits for each
+//! batch to be fully processed and measures how fast the rows go through the pipeline. The data
+//! depends only on the seed and the number of rows, so the reports of the same configuration are
+//! comparable between releases and machines, and a drop in the throughput points at a
+//! performance regression.
+//!
+//! The module is compiled only with the `bench` feature.
+
+pub mod generators;
+pub mod pipelines;
+
+use std::fmt::{self, Display};
+use std::time::{Duration, Instant};
+
+use differential_dataflow::input::InputSession;
+use timely::dataflow::operators::probe::Handle as ProbeHandle;
+use timely::Config as TimelyConfig;
+
+use crate::engine::{Key, Timestamp, Value};
+
+use self::generators::{user_row, CountingSink, SyntheticSource};
+pub use self::pipelines::PipelineShape;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum BenchError {
+    #[error("benchmark needs at least one row, one user, one worker and a positive batch size")]
+    InvalidConfig,
+
+    #[error("benchmark workers failed: {0}")]
+    Workers(String),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BenchConfig {
+    pub shape: PipelineShape,
+    pub rows: u64,
+    pub batch_size: usize,
+    pub workers: usize,
+    pub users: u64,
+    pub seed: u64,
+}
+
+impl BenchConfig {
+    pub const DEFAULT_BATCH_SIZE: usize = 10_000;
+    pub const DEFAULT_USERS: u64 = 10_000;
+
+    pub fn new(shape: PipelineShape, rows: u64) -> Self {
+        Self {
+            shape,
+            rows,
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            workers: 1,
+            users: Self::DEFAULT_USERS,
+            seed: 0,
+        }
+    }
+
+    /// Sets the number of rows each worker inserts before waiting for them to be processed.
+    #[must_use]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    #[must_use]
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    /// Sets the number of distinct users the orders are assigned to. It's the size of the
+    /// users table in the join-heavy shape and drives the number of groups in the window-heavy
+    /// one.
+    #[must_use]
+    pub fn with_users(mut self, users: u64) -> Self {
+        self.users = users;
+        self
+    }
+
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn validate(&self) -> Result<(), BenchError> {
+        if self.rows == 0 || self.users == 0 || self.workers == 0 || self.batch_size == 0 {
+            return Err(BenchError::InvalidConfig);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BenchReport {
+    pub config: BenchConfig,
+    /// The number of the updates of the output of the pipeline.
+    pub output_updates: u64,
+    /// The time from the first inserted row until the last batch was processed, in the slowest
+    /// worker.
+    pub elapsed: Duration,
+}
+
+impl BenchReport {
+    #[allow(clippy::cast_precision_loss)]
+    pub fn rows_per_second(&self) -> f64 {
+        self.config.rows as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} rows in {:.3}s on {} worker(s), {:.0} rows/s, {} output updates",
+            self.config.shape,
+            self.config.rows,
+            self.elapsed.as_secs_f64(),
+            self.config.workers,
+            self.rows_per_second(),
+            self.output_updates,
+        )
+    }
+}
+
+/// Runs the benchmark in a fresh set of worker threads of this process.
+#[allow(clippy::cast_possible_wrap)]
+pub fn run_benchmark(config: BenchConfig) -> Result<BenchReport, BenchError> {
+    config.validate()?;
+    let timely_config = if config.workers > 1 {
+        TimelyConfig::process(config.workers)
+    } else {
+        TimelyConfig::thread()
+    };
+    let sink = CountingSink::new();
+    let worker_sink = sink.clone();
+    let guards = timely::execute(timely_config, move |worker| {
+        let index = worker.index();
+        let peers = worker.peers();
+        let mut lines_input: InputSession<Timestamp, Vec<u8>, isize> = InputSession::new();
+        let mut orders_input: InputSession<Timestamp, (Key, Value), isize> = InputSession::new();
+        let mut users_input: InputSession<Timestamp, (Key, Value), isize> = InputSession::new();
+        let mut probe = ProbeHandle::new();
+        worker.dataflow(|scope| {
+            let lines = lines_input.to_collection(scope);
+            let orders = orders_input.to_collection(scope);
+            let users = users_input.to_collection(scope);
+            let output = match config.shape {
+                PipelineShape::ParseHeavy => pipelines::parse_heavy(&lines),
+                PipelineShape::JoinHeavy => pipelines::join_heavy(&orders, &users),
+                PipelineShape::WindowHeavy => pipelines::window_heavy(&orders),
+            };
+            let sink = worker_sink.clone();
+            output
+                .inspect(move |_update| sink.record(1))
+                .probe_with(&mut probe);
+        });
+
+        if config.shape == PipelineShape::JoinHeavy {
+            for user in (index as u64..config.users).step_by(peers) {
+                users_input.insert(user_row(config.seed, user as i64));
+            }
+        }
+
+        // All the workers go through the same number of batches, as each of them has to advance
+        // its inputs for the others to see the batches completed.
+        let batches = config
+            .rows
+            .div_ceil(peers as u64)
+            .div_ceil(config.batch_size as u64);
+        let start = Instant::now();
+        let mut source =
+            SyntheticSource::new(config.seed, config.users, config.rows).for_worker(index, peers);
+        for batch in 1..=batches {
+            for order in source.by_ref().take(config.batch_size) {
+                if config.shape == PipelineShape::ParseHeavy {
+                    lines_input.insert(order.to_json_line());
+                } else {
+                    orders_input.insert((order.key(), order.values()));
+                }
+            }
+            let time = Timestamp(batch);
+            lines_input.advance_to(time);
+            lines_input.flush();
+            orders_input.advance_to(time);
+            orders_input.flush();
+            users_input.advance_to(time);
+            users_input.flush();
+            worker.step_while(|| probe.less_than(&time));
+        }
+        start.elapsed()
+    })
+    .map_err(BenchError::Workers)?;
+
+    let mut elapsed = Duration::ZERO;
+    for result in guards.join() {
+        elapsed = elapsed.max(result.map_err(BenchError::Workers)?);
+    }
+    Ok(BenchReport {
+        config,
+        output_updates: sink.updates(),
+        elapsed,
+    })
+}
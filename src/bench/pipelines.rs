@@ -0,0 +1,193 @@
+// Copyright © 2024 Pathway
+
+//! The standard pipeline shapes of the benchmarks.
+//!
+//! All the shapes end with an aggregation of the count and the total amount of the orders per
+//! group, so their outputs are small and the cost is dominated by the stage that gives the
+//! shape its name.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use differential_dataflow::operators::{Join, Reduce};
+use differential_dataflow::Collection;
+
+use crate::bench::generators::{user_key, SyntheticOrder};
+use crate::connectors::data_format::{
+    InnerSchemaField, JsonLinesParser, ParsedEventWithErrors, Parser,
+};
+use crate::connectors::{DataEventType, ReaderContext, SessionType};
+use crate::engine::dataflow::maybe_total::MaybeTotalScope;
+use crate::engine::{Key, Type, Value};
+
+/// The length of the windows of the window-heavy pipeline, in milliseconds.
+pub const WINDOW_LENGTH_MS: i64 = 1000;
+
+/// The distance between the starts of the consecutive windows, in milliseconds. Each order
+/// belongs to `WINDOW_LENGTH_MS / WINDOW_HOP_MS` windows.
+pub const WINDOW_HOP_MS: i64 = 250;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PipelineShape {
+    /// Parses the orders from JSON lines and aggregates them per tag.
+    ParseHeavy,
+    /// Joins the orders with the users and aggregates them per region of the user.
+    JoinHeavy,
+    /// Aggregates the orders per user in sliding windows of event time.
+    WindowHeavy,
+}
+
+impl PipelineShape {
+    pub const ALL: [Self; 3] = [Self::ParseHeavy, Self::JoinHeavy, Self::WindowHeavy];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ParseHeavy => "parse-heavy",
+            Self::JoinHeavy => "join-heavy",
+            Self::WindowHeavy => "window-heavy",
+        }
+    }
+}
+
+impl Display for PipelineShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PipelineShape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|shape| shape.as_str() == s)
+            .ok_or_else(|| format!("unknown pipeline shape: {s:?}"))
+    }
+}
+
+fn orders_parser() -> JsonLinesParser {
+    let field_names: Vec<String> = SyntheticOrder::FIELD_NAMES
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let schema = field_names
+        .iter()
+        .map(|name| {
+            let type_ = if name == "tag" {
+                Type::String
+            } else {
+                Type::Int
+            };
+            (name.clone(), InnerSchemaField::new(type_, None))
+        })
+        .collect();
+    JsonLinesParser::new(
+        None,
+        field_names,
+        HashMap::new(),
+        true,
+        schema,
+        SessionType::Native,
+        None,
+    )
+    .expect("schema of the synthetic orders should be valid")
+}
+
+fn order_field(values: &Value, index: usize) -> &Value {
+    &values
+        .as_tuple()
+        .expect("synthetic order should be a tuple")[index]
+}
+
+fn order_int_field(values: &Value, index: usize) -> i64 {
+    order_field(values, index)
+        .as_int()
+        .expect("synthetic order field should be an int")
+}
+
+/// Computes the number of rows and the total amount in each group. The input rows are keyed by
+/// the group and hold its label and the amount.
+fn aggregate<S: MaybeTotalScope>(
+    rows: &Collection<S, (Key, (Value, i64))>,
+) -> Collection<S, (Key, Value)> {
+    rows.reduce(|_group, input, output| {
+        let label = input[0].0 .0.clone();
+        let mut count = 0;
+        let mut total = 0;
+        for ((_label, amount), diff) in input {
+            let diff = *diff as i64;
+            count += diff;
+            total += amount * diff;
+        }
+        output.push((
+            Value::Tuple([label, Value::Int(count), Value::Int(total)].into()),
+            1,
+        ));
+    })
+}
+
+/// Parses the JSON lines into orders and aggregates them per tag.
+pub fn parse_heavy<S: MaybeTotalScope>(
+    lines: &Collection<S, Vec<u8>>,
+) -> Collection<S, (Key, Value)> {
+    let mut parser = orders_parser();
+    let orders = lines.flat_map(move |line| {
+        parser
+            .parse(&ReaderContext::from_raw_bytes(DataEventType::Insert, line))
+            .expect("synthetic order should be valid JSON")
+            .into_iter()
+            .filter_map(|event| match event {
+                ParsedEventWithErrors::Insert((_key, values)) => Some(
+                    values
+                        .into_iter()
+                        .collect::<Result<Vec<_>, _>>()
+                        .expect("synthetic order should match the schema"),
+                ),
+                _ => None,
+            })
+    });
+    let per_tag = orders.map(|values| {
+        let tag = values[4].clone();
+        let amount = values[2]
+            .as_int()
+            .expect("amount of the synthetic order should be an int");
+        (Key::for_value(&tag), (tag, amount))
+    });
+    aggregate(&per_tag)
+}
+
+/// Joins the orders with the users and aggregates them per region of the user.
+pub fn join_heavy<S: MaybeTotalScope>(
+    orders: &Collection<S, (Key, Value)>,
+    users: &Collection<S, (Key, Value)>,
+) -> Collection<S, (Key, Value)> {
+    let orders_by_user = orders.map(|(_key, values)| {
+        let user = order_int_field(&values, 1);
+        (user_key(user), values)
+    });
+    let per_region = orders_by_user.join_map(users, |_user, order, user| {
+        let region = order_field(user, 1).clone();
+        (Key::for_value(&region), (region, order_int_field(order, 2)))
+    });
+    aggregate(&per_region)
+}
+
+/// Aggregates the orders per user in the sliding windows of event time.
+pub fn window_heavy<S: MaybeTotalScope>(
+    orders: &Collection<S, (Key, Value)>,
+) -> Collection<S, (Key, Value)> {
+    let per_window = orders.flat_map(|(_key, values)| {
+        let user = order_int_field(&values, 1);
+        let amount = order_int_field(&values, 2);
+        let time = order_int_field(&values, 3);
+        let last_start = time.div_euclid(WINDOW_HOP_MS) * WINDOW_HOP_MS;
+        (0..WINDOW_LENGTH_MS / WINDOW_HOP_MS).map(move |n| {
+            let label =
+                Value::Tuple([Value::Int(user), Value::Int(last_start - n * WINDOW_HOP_MS)].into());
+            (Key::for_value(&label), (label, amount))
+        })
+    });
+    aggregate(&per_window)
+}
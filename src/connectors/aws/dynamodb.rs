@@ -15,8 +15,8 @@ use aws_sdk_dynamodb::types::{
 use aws_sdk_dynamodb::Client;
 use aws_smithy_runtime_api::http::Response as AwsHttpResponse;
 use ndarray::ArrayD;
-use tokio::runtime::Runtime as TokioRuntime;
 
+use crate::async_runtime::AsyncRuntime;
 use crate::connectors::data_format::{
     FormatterContext, FormatterError, NDARRAY_ELEMENTS_FIELD_NAME, NDARRAY_SHAPE_FIELD_NAME,
 };
@@ -48,7 +48,7 @@ pub enum AwsRequestError {
 }
 
 pub struct DynamoDBWriter {
-    runtime: TokioRuntime,
+    runtime: AsyncRuntime,
     client: Client,
     table_name: String,
     value_fields: Vec<ValueField>,
@@ -59,7 +59,7 @@ pub struct DynamoDBWriter {
 
 impl DynamoDBWriter {
     pub fn new(
-        runtime: TokioRuntime,
+        runtime: AsyncRuntime,
         client: Client,
         table_name: String,
         value_fields: Vec<ValueField>,
@@ -241,6 +241,7 @@ impl DynamoDBWriter {
             }
             Value::IntArray(arr) => Ok(Self::array_to_attribute_value(arr)),
             Value::FloatArray(arr) => Ok(Self::array_to_attribute_value(arr)),
+            Value::Float32Array(arr) => Ok(Self::array_to_attribute_value(arr)),
             Value::DateTimeNaive(dt) => Ok(AttributeValue::S(dt.to_string())),
             Value::DateTimeUtc(dt) => Ok(AttributeValue::S(dt.to_string())),
             Value::Duration(d) => Ok(AttributeValue::N(d.nanoseconds().to_string())),
@@ -354,7 +355,9 @@ impl Writer for DynamoDBWriter {
                     }
                 }
 
-                retry.sleep_after_error();
+                if !retry.sleep_after_error() {
+                    break;
+                }
             }
             let unprocessed_items = request_items.remove(&self.table_name);
             if let Some(unprocessed_items) = unprocessed_items {
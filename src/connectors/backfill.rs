@@ -0,0 +1,288 @@
+// Copyright © 2024 Pathway
+
+//! A reader composing a bounded source with a streaming one. The backfill source, for example
+//! Parquet or CSV files on S3, is read to the end first, and only then the reader switches to
+//! the stream, for example a Kafka topic.
+//!
+//! The two sources overlap around the cutover, so both of them are split at a boundary value of
+//! one of the columns, usually the event time. Only the rows below the boundary are taken from
+//! the backfill source and only the rows at or above it are taken from the stream, which makes
+//! every row appear exactly once no matter how far back the stream is started. Rows that have no
+//! comparable boundary value, such as the deletions in upsert sessions, are passed from both
+//! sources as they are.
+//!
+//! Both sources are parsed with their own parsers and the rows are passed further as
+//! [`ReaderContext::Diff`] values, so the connector has to use a transparent parser. The offsets
+//! of the inner readers are reported as they are. The backfill readers use
+//! [`OffsetKey::Empty`] while the streaming ones use their own keys, so once the persisted
+//! frontier contains a key of the stream, the backfill is known to be complete and is not read
+//! again after a restart.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+
+use log::{error, info};
+
+use crate::connectors::data_format::{ParsedEventWithErrors, Parser};
+use crate::connectors::data_storage::{
+    ConversionError, DataEventType, ReadError, ReadResult, Reader, ReaderBuilder, ReaderContext,
+    StorageType, ValuesMap,
+};
+use crate::connectors::monitoring::BackfillProgressTracker;
+use crate::connectors::{Offset, OffsetKey};
+use crate::engine::{Type, Value};
+use crate::persistence::frontier::OffsetAntichain;
+use crate::persistence::UniqueName;
+
+/// The column and the value at which the backfill source is cut over to the stream.
+#[derive(Clone, Debug)]
+pub struct SeamBoundary {
+    column: String,
+    value: Value,
+}
+
+impl SeamBoundary {
+    /// The `value` has to be of the same type as the values of the `column`.
+    pub fn new(column: String, value: Value) -> Self {
+        Self { column, value }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Backfill,
+    Stream,
+}
+
+pub struct BackfillThenStreamReaderBuilder {
+    backfill: (Box<dyn ReaderBuilder>, Box<dyn Parser>),
+    stream: (Box<dyn ReaderBuilder>, Box<dyn Parser>),
+    value_field_names: Vec<String>,
+    boundary: SeamBoundary,
+}
+
+impl BackfillThenStreamReaderBuilder {
+    pub fn new(
+        backfill: (Box<dyn ReaderBuilder>, Box<dyn Parser>),
+        stream: (Box<dyn ReaderBuilder>, Box<dyn Parser>),
+        value_field_names: Vec<String>,
+        boundary: SeamBoundary,
+    ) -> Self {
+        Self {
+            backfill,
+            stream,
+            value_field_names,
+            boundary,
+        }
+    }
+}
+
+impl ReaderBuilder for BackfillThenStreamReaderBuilder {
+    fn build(self: Box<Self>) -> Result<Box<dyn Reader>, ReadError> {
+        let Self {
+            backfill: (backfill_reader, backfill_parser),
+            stream: (stream_reader, stream_parser),
+            value_field_names,
+            boundary,
+        } = *self;
+        Ok(Box::new(BackfillThenStreamReader::new(
+            (backfill_reader.build()?, backfill_parser),
+            (stream_reader.build()?, stream_parser),
+            value_field_names,
+            boundary,
+        )))
+    }
+
+    fn short_description(&self) -> Cow<'static, str> {
+        format!(
+            "BackfillThenStream({}, {})",
+            self.backfill.0.short_description(),
+            self.stream.0.short_description()
+        )
+        .into()
+    }
+
+    fn name(&self, unique_name: Option<&UniqueName>) -> String {
+        if let Some(unique_name) = unique_name {
+            unique_name.to_string()
+        } else {
+            "BackfillThenStream".to_string()
+        }
+    }
+
+    fn storage_type(&self) -> StorageType {
+        StorageType::BackfillThenStream
+    }
+}
+
+pub struct BackfillThenStreamReader {
+    backfill: (Box<dyn Reader>, Box<dyn Parser>),
+    stream: (Box<dyn Reader>, Box<dyn Parser>),
+    value_field_names: Vec<String>,
+    boundary_value: Value,
+    boundary_index: Option<usize>,
+    side: Side,
+    pending: VecDeque<ReadResult>,
+    backfill_progress: BackfillProgressTracker,
+}
+
+impl BackfillThenStreamReader {
+    pub fn new(
+        backfill: (Box<dyn Reader>, Box<dyn Parser>),
+        stream: (Box<dyn Reader>, Box<dyn Parser>),
+        value_field_names: Vec<String>,
+        boundary: SeamBoundary,
+    ) -> Self {
+        let boundary_index = value_field_names
+            .iter()
+            .position(|name| *name == boundary.column);
+        if boundary_index.is_none() {
+            error!(
+                "Seam column {:?} is not among the value fields, the sources won't be split",
+                boundary.column
+            );
+        }
+        Self {
+            backfill,
+            stream,
+            value_field_names,
+            boundary_value: boundary.value,
+            boundary_index,
+            side: Side::Backfill,
+            pending: VecDeque::new(),
+            backfill_progress: BackfillProgressTracker::default(),
+        }
+    }
+
+    fn current(&mut self) -> &mut (Box<dyn Reader>, Box<dyn Parser>) {
+        match self.side {
+            Side::Backfill => &mut self.backfill,
+            Side::Stream => &mut self.stream,
+        }
+    }
+
+    fn is_on_side(&self, values: &[Result<Value, Box<ConversionError>>]) -> bool {
+        let Some(Ok(value)) = self.boundary_index.and_then(|index| values.get(index)) else {
+            return true;
+        };
+        if matches!(value, Value::None) {
+            return true;
+        }
+        match self.side {
+            Side::Backfill => *value < self.boundary_value,
+            Side::Stream => *value >= self.boundary_value,
+        }
+    }
+
+    fn enqueue_parsed(&mut self, context: &ReaderContext, offset: &Offset) {
+        let events = match self.current().1.parse(context) {
+            Ok(events) => events,
+            Err(e) => {
+                error!("Failed to parse an entry of {:?} source: {e}", self.side);
+                return;
+            }
+        };
+        for event in events {
+            let (event_type, key, values) = match event {
+                ParsedEventWithErrors::AdvanceTime => continue,
+                ParsedEventWithErrors::Insert((key, values)) => {
+                    (DataEventType::Insert, key, values)
+                }
+                ParsedEventWithErrors::Delete((key, values)) => {
+                    (DataEventType::Delete, key, values)
+                }
+            };
+            let key = match key.transpose() {
+                Ok(key) => key,
+                Err(e) => {
+                    error!("Failed to parse the key of {:?} source: {e}", self.side);
+                    continue;
+                }
+            };
+            let values: Vec<_> = values
+                .into_iter()
+                .zip(&self.value_field_names)
+                .map(|(value, name)| {
+                    value.map_err(|e| {
+                        Box::new(ConversionError::new(
+                            String::new(),
+                            name.clone(),
+                            Type::Any,
+                            Some(e.to_string()),
+                        ))
+                    })
+                })
+                .collect();
+            if !self.is_on_side(&values) {
+                continue;
+            }
+            let values: HashMap<_, _> =
+                self.value_field_names.iter().cloned().zip(values).collect();
+            self.pending.push_back(ReadResult::Data(
+                ReaderContext::from_diff(event_type, key, ValuesMap::from(values)),
+                offset.clone(),
+            ));
+        }
+    }
+}
+
+impl Reader for BackfillThenStreamReader {
+    fn read(&mut self) -> Result<ReadResult, ReadError> {
+        loop {
+            if let Some(result) = self.pending.pop_front() {
+                return Ok(result);
+            }
+            match self.current().0.read()? {
+                ReadResult::Data(context, offset) => self.enqueue_parsed(&context, &offset),
+                ReadResult::NewSource(metadata) => {
+                    self.current().1.on_new_source_started(&metadata);
+                    return Ok(ReadResult::NewSource(metadata));
+                }
+                ReadResult::Finished if self.side == Side::Backfill => {
+                    info!("Backfill source is read, switching to the stream");
+                    self.backfill_progress.finish();
+                    self.side = Side::Stream;
+                }
+                result => return Ok(result),
+            }
+        }
+    }
+
+    fn seek(&mut self, frontier: &OffsetAntichain) -> Result<(), ReadError> {
+        let stream_started = frontier
+            .iter()
+            .any(|(offset_key, _)| *offset_key != OffsetKey::Empty);
+        if stream_started {
+            self.side = Side::Stream;
+            self.stream.0.seek(frontier)
+        } else {
+            self.backfill.0.seek(frontier)
+        }
+    }
+
+    fn track_backfill_progress(&mut self, tracker: BackfillProgressTracker) {
+        // The progress is reported by the backfill reader, if it's able to
+        self.backfill.0.track_backfill_progress(tracker.clone());
+        self.backfill_progress = tracker;
+    }
+
+    fn short_description(&self) -> Cow<'static, str> {
+        format!(
+            "BackfillThenStream({}, {})",
+            self.backfill.0.short_description(),
+            self.stream.0.short_description()
+        )
+        .into()
+    }
+
+    fn storage_type(&self) -> StorageType {
+        StorageType::BackfillThenStream
+    }
+
+    fn max_allowed_consecutive_errors(&self) -> usize {
+        match self.side {
+            Side::Backfill => self.backfill.0.max_allowed_consecutive_errors(),
+            Side::Stream => self.stream.0.max_allowed_consecutive_errors(),
+        }
+    }
+}
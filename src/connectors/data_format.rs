@@ -3,13 +3,19 @@
 use std::any::type_name;
 use std::borrow::Cow;
 use std::clone::Clone;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::iter::zip;
 use std::mem::take;
 use std::str::{from_utf8, Utf8Error};
 
+use crate::connectors::data_lake::parquet_row_into_values_map;
+use crate::connectors::idempotency::IdempotencyKey;
+use crate::connectors::masking::MaskingError;
 use crate::connectors::metadata::SourceMetadata;
+use crate::connectors::schema_drift::{
+    self, json_value_kind, FieldObservation, SchemaDriftTracker,
+};
 use crate::connectors::ReaderContext::{Diff, Empty, KeyValue, RawBytes, TokenizedEntries};
 use crate::connectors::{DataEventType, Offset, ReaderContext, SessionType, SnapshotEvent};
 use crate::connectors::{SPECIAL_FIELD_DIFF, SPECIAL_FIELD_TIME};
@@ -19,11 +25,19 @@ use crate::engine::{
     value::parse_pathway_pointer, DateTimeNaive, DateTimeUtc, Duration as EngineDuration, Error,
     Key, Result, Timestamp, Type, Value,
 };
+use crate::timestamp::current_unix_timestamp_ms;
 
+use apache_avro::types::Value as AvroValue;
+use apache_avro::{from_avro_datum, to_avro_datum, Error as AvroError, Schema as AvroSchema};
 use async_nats::header::HeaderMap as NatsHeaders;
 use base64::engine::general_purpose::STANDARD as base64encoder;
 use base64::Engine;
 use bincode::ErrorKind as BincodeError;
+use bytes::Bytes;
+use chrono::{DateTime as ChronoDateTime, Utc};
+use deltalake::parquet::errors::ParquetError;
+use deltalake::parquet::file::reader::SerializedFileReader;
+use deltalake::parquet::record::reader::RowIter;
 use itertools::Itertools;
 use log::error;
 use mongodb::bson::{
@@ -31,16 +45,30 @@ use mongodb::bson::{
     Bson as BsonValue, DateTime as BsonDateTime, Document as BsonDocument,
 };
 use ndarray::ArrayD;
+use prost::{DecodeError as ProstDecodeError, Message as ProstMessage};
+use prost_types::field_descriptor_proto::{Label as FieldLabel, Type as FieldType};
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorSet, MessageOptions,
+};
 use rdkafka::message::{Header as KafkaHeader, OwnedHeaders as KafkaHeaders};
+use rmpv::decode::{read_value as read_msgpack_value, Error as MsgPackDecodeError};
+use rmpv::encode::write_value as write_msgpack_value;
+use rmpv::Value as MsgPackValue;
 use schema_registry_converter::blocking::json::JsonDecoder as RegistryJsonDecoder;
 use schema_registry_converter::blocking::json::JsonEncoder as RegistryJsonEncoder;
+use schema_registry_converter::blocking::schema_registry::{
+    get_schema_by_id, post_schema, SrSettings as SchemaRegistrySettings,
+};
 use schema_registry_converter::error::SRCError as SchemaRepositoryError;
 use schema_registry_converter::schema_registry_common::SubjectNameStrategy as RegistrySubjectNameStrategy;
+use schema_registry_converter::schema_registry_common::{
+    SchemaType as RegistrySchemaType, SuppliedSchema,
+};
 use serde::ser::{SerializeMap, Serializer};
 use serde_json::json;
 use serde_json::{Map as JsonMap, Value as JsonValue};
 
-use super::data_storage::{ConversionError, SpecialEvent};
+use super::data_storage::{ConversionError, SpecialEvent, ValuesMap};
 
 pub const COMMIT_LITERAL: &str = "*COMMIT*";
 pub const NDARRAY_ELEMENTS_FIELD_NAME: &str = "elements";
@@ -204,8 +232,71 @@ pub enum ParseError {
     #[error("malformed complex field JSON representation")]
     MalformedComplexField,
 
+    #[error("failed to read parquet file: {0}")]
+    Parquet(#[from] ParquetError),
+
     #[error(transparent)]
     SchemaRepository(#[from] SchemaRepositoryError),
+
+    #[error("failed to decode avro message: {0}")]
+    Avro(Box<AvroError>),
+
+    #[error("message doesn't start with the header of the schema registry wire format")]
+    NotSchemaRegistryMessage,
+
+    #[error("schema {0} in the schema registry is not an avro schema")]
+    NotAvroSchema(u32),
+
+    #[error("avro message is not a record")]
+    NotAvroRecord,
+
+    #[error("avro format requires either a schema or a schema registry")]
+    NoAvroSchema,
+
+    #[error("failed to decode protobuf descriptor set: {0}")]
+    MalformedProtobufDescriptorSet(#[source] ProstDecodeError),
+
+    #[error("message type {0} is not defined in the protobuf descriptor set")]
+    UnknownProtobufMessageType(String),
+
+    #[error("field {field_name} is not defined in protobuf message type {message_name}")]
+    FieldNotInProtobufMessage {
+        field_name: String,
+        message_name: String,
+    },
+
+    #[error("malformed protobuf message: {0}")]
+    MalformedProtobufMessage(String),
+
+    #[error("failed to decode MessagePack payload: {0}")]
+    MalformedMsgPack(#[source] MsgPackDecodeError),
+
+    #[error("MessagePack payload is not a map")]
+    NotMsgPackMap,
+
+    #[error("failed to create a field {field_name:?} with type {type_} from MessagePack value: {}", limit_length(value.clone(), STANDARD_OBJECT_LENGTH_LIMIT))]
+    FailedToParseFromMsgPack {
+        field_name: String,
+        value: String,
+        type_: Type,
+    },
+
+    #[error("malformed varint length prefix")]
+    MalformedLengthPrefix,
+
+    #[error("frame of {length} bytes exceeds the limit of {limit} bytes")]
+    FrameTooLarge { length: u64, limit: usize },
+
+    #[error("data ends in the middle of a frame, {0} bytes are left unparsed")]
+    TruncatedFrame(usize),
+
+    #[error("value {value} of field {field_name:?} doesn't match its type {type_} in schema {schema_name:?}")]
+    SchemaMismatch {
+        schema_name: String,
+        field_name: String,
+        type_: Type,
+        value: Value,
+    },
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -275,6 +366,11 @@ pub trait Parser: Send {
     fn session_type(&self) -> SessionType {
         SessionType::Native
     }
+
+    /// Starts reporting the drift of the parsed data from the schema, see
+    /// [`schema_drift`](crate::connectors::schema_drift). The parsers that can't
+    /// detect it ignore the call.
+    fn report_schema_drift(&mut self, _source_name: &str) {}
 }
 
 #[derive(Debug, Clone)]
@@ -335,6 +431,8 @@ pub struct FormatterContext {
     pub values: Vec<Value>,
     pub time: Timestamp,
     pub diff: isize,
+    /// Set by the engine if the sink [uses idempotency keys](crate::connectors::data_storage::Writer::uses_idempotency_keys).
+    pub idempotency_key: Option<IdempotencyKey>,
 }
 
 impl FormatterContext {
@@ -355,6 +453,7 @@ impl FormatterContext {
             values,
             time,
             diff,
+            idempotency_key: None,
         }
     }
 
@@ -371,6 +470,7 @@ impl FormatterContext {
             values,
             time,
             diff,
+            idempotency_key: None,
         }
     }
 
@@ -379,7 +479,7 @@ impl FormatterContext {
         header_fields: &[(String, usize)],
         encode_bytes: bool,
     ) -> Vec<PreparedMessageHeader> {
-        let mut headers = Vec::with_capacity(header_fields.len() + 2);
+        let mut headers = Vec::with_capacity(header_fields.len() + 3);
         headers.push(PreparedMessageHeader::new(
             "pathway_time",
             self.time.to_string().as_bytes().to_vec(),
@@ -388,6 +488,12 @@ impl FormatterContext {
             "pathway_diff",
             self.diff.to_string().as_bytes().to_vec(),
         ));
+        if let Some(idempotency_key) = self.idempotency_key {
+            headers.push(PreparedMessageHeader::new(
+                "pathway_idempotency_key",
+                idempotency_key.to_string().into_bytes(),
+            ));
+        }
         for (name, position) in header_fields {
             let value: Vec<u8> = match (&self.values[*position], encode_bytes) {
                 (Value::Bytes(b), false) => (*b).to_vec(),
@@ -421,6 +527,10 @@ impl FormatterContext {
                     .expect("all prepared headers must be UTF-8 serializable"),
             );
         }
+        if let Some(idempotency_key) = self.idempotency_key {
+            // JetStream drops the messages with an id it has already received
+            nats_headers.insert("Nats-Msg-Id", idempotency_key.to_string());
+        }
         nats_headers
     }
 }
@@ -449,6 +559,12 @@ pub enum FormatterError {
     #[error("Pending value is not bson-serializable")]
     PendingValueNonBsonSerializable,
 
+    #[error("Error value is not msgpack-serializable")]
+    ErrorValueNonMsgPackSerializable,
+
+    #[error("Pending value is not msgpack-serializable")]
+    PendingValueNonMsgPackSerializable,
+
     #[error("this connector doesn't support this value type")]
     UnsupportedValueType,
 
@@ -467,8 +583,20 @@ pub enum FormatterError {
     #[error(transparent)]
     SchemaRepository(#[from] SchemaRepositoryError),
 
+    #[error("failed to encode avro record: {0}")]
+    Avro(Box<AvroError>),
+
+    #[error("avro schema of the output must be a record schema")]
+    NotAvroRecordSchema,
+
+    #[error("value {0} doesn't match the avro schema")]
+    ValueNotMatchingAvroSchema(Value),
+
     #[error("incorrect external diff value: {0}")]
     IncorrectDiffColumnValue(Value),
+
+    #[error(transparent)]
+    Masking(#[from] MaskingError),
 }
 
 pub trait Formatter: Send {
@@ -480,6 +608,12 @@ pub trait Formatter: Send {
         diff: isize,
     ) -> Result<FormatterContext, FormatterError>;
 
+    /// Whether the entry with the given values is skipped by the sink instead of being
+    /// formatted and written.
+    fn is_filtered_out(&self, _values: &[Value]) -> Result<bool, FormatterError> {
+        Ok(false)
+    }
+
     fn short_description(&self) -> Cow<'static, str> {
         type_name::<Self>().into()
     }
@@ -489,6 +623,9 @@ pub struct DsvSettings {
     key_column_names: Option<Vec<String>>,
     value_column_names: Vec<String>,
     separator: char,
+    strip_bom: bool,
+    write_bom: bool,
+    normalize_newlines: bool,
 }
 
 impl DsvSettings {
@@ -501,9 +638,36 @@ impl DsvSettings {
             key_column_names,
             value_column_names,
             separator,
+            strip_bom: true,
+            write_bom: false,
+            normalize_newlines: false,
         }
     }
 
+    /// Whether the parser removes the UTF-8 byte order mark from the beginning of the header,
+    /// which is where it ends up in the files produced by many Windows programs.
+    #[must_use]
+    pub fn with_bom_stripped(mut self, strip_bom: bool) -> Self {
+        self.strip_bom = strip_bom;
+        self
+    }
+
+    /// Whether the formatter starts the output with the UTF-8 byte order mark, which some
+    /// spreadsheet programs need to detect the encoding.
+    #[must_use]
+    pub fn with_bom_written(mut self, write_bom: bool) -> Self {
+        self.write_bom = write_bom;
+        self
+    }
+
+    /// Whether the CRLF and CR line breaks inside the values are replaced with LF, both when
+    /// parsing and when formatting.
+    #[must_use]
+    pub fn with_normalized_newlines(mut self, normalize_newlines: bool) -> Self {
+        self.normalize_newlines = normalize_newlines;
+        self
+    }
+
     pub fn formatter(self) -> Box<dyn Formatter> {
         Box::new(DsvFormatter::new(self))
     }
@@ -669,6 +833,17 @@ fn ensure_all_fields_in_schema(
 /// "magic field" containing the metadata
 const METADATA_FIELD_NAME: &str = "_metadata";
 
+const UTF8_BOM: char = '\u{feff}';
+
+/// Replaces the CRLF and CR line breaks with LF.
+fn normalize_newlines(value: &str) -> Cow<'_, str> {
+    if value.contains('\r') {
+        value.replace("\r\n", "\n").replace('\r', "\n").into()
+    } else {
+        value.into()
+    }
+}
+
 impl DsvParser {
     pub fn new(
         settings: DsvSettings,
@@ -734,21 +909,25 @@ impl DsvParser {
     }
 
     fn parse_dsv_header(&mut self, tokenized_entries: &[String]) -> Result<(), ParseError> {
+        let mut header = tokenized_entries.to_vec();
+        if self.settings.strip_bom {
+            if let Some(first_column_name) = header.first_mut() {
+                if let Some(stripped) = first_column_name.strip_prefix(UTF8_BOM) {
+                    *first_column_name = stripped.to_string();
+                }
+            }
+        }
         self.key_column_indices = match &self.settings.key_column_names {
-            Some(names) => Some(Self::column_indices_by_names(
-                tokenized_entries,
-                names,
-                &self.schema,
-            )?),
+            Some(names) => Some(Self::column_indices_by_names(&header, names, &self.schema)?),
             None => None,
         };
         self.value_column_indices = Self::column_indices_by_names(
-            tokenized_entries,
+            &header,
             &self.settings.value_column_names,
             &self.schema,
         )?;
 
-        self.header = tokenized_entries.to_vec();
+        self.header = header;
         self.dsv_header_read = true;
         Ok(())
     }
@@ -781,7 +960,12 @@ impl DsvParser {
         for index in indices {
             let token = match index {
                 DsvColumnIndex::IndexWithSchema(index, schema_item) => {
-                    parse_with_type(&tokens[*index], schema_item, &header[*index])
+                    let token = if self.settings.normalize_newlines {
+                        normalize_newlines(&tokens[*index])
+                    } else {
+                        Cow::from(&tokens[*index])
+                    };
+                    parse_with_type(&token, schema_item, &header[*index])
                 }
                 DsvColumnIndex::Metadata => Ok(self.metadata_column_value.clone()),
             };
@@ -1042,18 +1226,28 @@ impl Formatter for DsvFormatter {
                     SPECIAL_FIELD_DIFF.to_string(),
                 ])
                 .collect();
-            payloads.push(Self::format_csv_row(header, separator)?);
+            let mut header = Self::format_csv_row(header, separator)?;
+            if self.settings.write_bom {
+                header = [UTF8_BOM.to_string().into_bytes(), header].concat();
+            }
+            payloads.push(header);
             self.dsv_header_written = true;
         }
 
         let mut prepared_values = Vec::with_capacity(values.len());
         for v in values {
             let prepared = match v {
+                Value::String(v) if self.settings.normalize_newlines => {
+                    normalize_newlines(v).into_owned()
+                }
                 Value::String(v) => v.to_string(),
                 Value::PyObjectWrapper(_) => create_bincoded_value(v)?,
                 Value::Bytes(b) => base64::engine::general_purpose::STANDARD.encode(b),
                 Value::Duration(d) => format!("{}", d.nanoseconds()),
-                Value::IntArray(_) | Value::FloatArray(_) | Value::Tuple(_) => {
+                Value::IntArray(_)
+                | Value::FloatArray(_)
+                | Value::Float32Array(_)
+                | Value::Tuple(_) => {
                     let json_value = serialize_value_to_json(v)?;
                     json_value.to_string()
                 }
@@ -1329,6 +1523,17 @@ pub fn serialize_value_to_json(value: &Value) -> Result<JsonValue, FormatterErro
             });
             Ok(serialized_values)
         }
+        Value::Float32Array(a) => {
+            let mut flat_elements = Vec::with_capacity(a.len());
+            for item in a.iter() {
+                flat_elements.push(json!(item));
+            }
+            let serialized_values = json!({
+                NDARRAY_SHAPE_FIELD_NAME: a.shape(),
+                NDARRAY_ELEMENTS_FIELD_NAME: flat_elements,
+            });
+            Ok(serialized_values)
+        }
         Value::DateTimeNaive(dt) => Ok(json!(dt.to_string())),
         Value::DateTimeUtc(dt) => Ok(json!(dt.to_string())),
         Value::Duration(d) => Ok(json!(d.nanoseconds())),
@@ -1636,6 +1841,7 @@ pub struct JsonLinesParser {
     metadata_column_value: Value,
     session_type: SessionType,
     schema_registry_decoder: Option<RegistryJsonDecoder>,
+    schema_drift_tracker: Option<SchemaDriftTracker>,
 }
 
 impl JsonLinesParser {
@@ -1658,6 +1864,7 @@ impl JsonLinesParser {
             metadata_column_value: Value::None,
             session_type,
             schema_registry_decoder,
+            schema_drift_tracker: None,
         })
     }
 
@@ -1676,8 +1883,39 @@ impl JsonLinesParser {
         )
     }
 
+    fn observe_schema_drift(&mut self, payload: &JsonValue, values: &ValueFieldsWithErrors) {
+        let Some(tracker) = self.schema_drift_tracker.as_mut() else {
+            return;
+        };
+        let mut events = Vec::new();
+        if let JsonValue::Object(object) = payload {
+            events.extend(tracker.observe_field_names(object.keys().map(String::as_str)));
+        }
+        let observations = zip(&self.value_field_names, values)
+            .filter(|(name, _)| *name != METADATA_FIELD_NAME)
+            .map(|(name, value)| {
+                let raw_value = match self.column_paths.get(name) {
+                    Some(path) => payload.pointer(path),
+                    None => payload.get(name),
+                };
+                match (raw_value, value) {
+                    (None | Some(JsonValue::Null), _) => FieldObservation::Missing,
+                    (Some(raw_value), Err(_)) => FieldObservation::TypeConflict {
+                        expected: self
+                            .schema
+                            .get(name)
+                            .map_or(Type::Any, |field| field.type_.clone()),
+                        observed: json_value_kind(raw_value).to_string(),
+                    },
+                    (Some(_), Ok(_)) => FieldObservation::Present,
+                }
+            });
+        events.extend(tracker.observe_entry(observations));
+        schema_drift::report(events);
+    }
+
     fn create_events_from_parsed_object(
-        &self,
+        &mut self,
         data_event: DataEventType,
         payload: &JsonValue,
     ) -> Vec<ParsedEventWithErrors> {
@@ -1687,6 +1925,7 @@ impl JsonLinesParser {
                 .collect()
         });
         let values = self.values_from_parsed_object(payload, &self.value_field_names);
+        self.observe_schema_drift(payload, &values);
         let event = ParsedEventWithErrors::new(self.session_type, data_event, key, values);
         vec![event]
     }
@@ -1740,6 +1979,27 @@ impl Parser for JsonLinesParser {
     fn session_type(&self) -> SessionType {
         self.session_type
     }
+
+    fn report_schema_drift(&mut self, source_name: &str) {
+        let field_names = self
+            .value_field_names
+            .iter()
+            .filter(|name| *name != METADATA_FIELD_NAME)
+            .cloned()
+            .collect();
+        // A field read by a path is known by the first segment of the path
+        let known_fields = self.schema.keys().cloned().chain(
+            self.column_paths
+                .values()
+                .filter_map(|path| path.split('/').nth(1))
+                .map(|segment| segment.replace("~1", "/").replace("~0", "~")),
+        );
+        self.schema_drift_tracker = Some(SchemaDriftTracker::new(
+            source_name.to_string(),
+            field_names,
+            known_fields,
+        ));
+    }
 }
 
 /// Receives values directly from a Reader and passes them
@@ -1817,127 +2077,1423 @@ impl Parser for TransparentParser {
     }
 }
 
-#[derive(Debug)]
-pub struct PsqlUpdatesFormatter {
-    table_name: String,
+/// Parses complete Parquet files into rows, converting the columns to the types in the schema.
+///
+/// Each payload has to be a whole file, so the files are expected to be read in full. The
+/// columns of the file that are not in the schema are ignored.
+pub struct ParquetParser {
+    key_field_names: Option<Vec<String>>,
     value_field_names: Vec<String>,
+    schema: HashMap<String, InnerSchemaField>,
+    column_types: HashMap<String, Type>,
+    metadata_column_value: Value,
+    session_type: SessionType,
 }
 
-impl PsqlUpdatesFormatter {
-    pub fn new(table_name: String, value_field_names: Vec<String>) -> PsqlUpdatesFormatter {
-        PsqlUpdatesFormatter {
-            table_name,
+impl ParquetParser {
+    pub fn new(
+        key_field_names: Option<Vec<String>>,
+        value_field_names: Vec<String>,
+        schema: HashMap<String, InnerSchemaField>,
+        session_type: SessionType,
+    ) -> Result<ParquetParser> {
+        ensure_all_fields_in_schema(key_field_names.as_ref(), &value_field_names, &schema)?;
+        let column_types = schema
+            .iter()
+            .filter(|(name, _)| *name != METADATA_FIELD_NAME)
+            .map(|(name, field)| (name.clone(), field.type_.clone()))
+            .collect();
+        Ok(ParquetParser {
+            key_field_names,
             value_field_names,
+            schema,
+            column_types,
+            metadata_column_value: Value::None,
+            session_type,
+        })
+    }
+
+    fn field_value(&self, name: &str, values: &ValuesMap) -> DynResult<Value> {
+        if name == METADATA_FIELD_NAME {
+            return Ok(self.metadata_column_value.clone());
         }
+        // ensure_all_fields_in_schema in new() makes sure that all fields are in the schema
+        self.schema[name].maybe_use_default(name, values.get(name).cloned())
     }
-}
 
-impl Formatter for PsqlUpdatesFormatter {
-    fn format(
-        &mut self,
-        key: &Key,
-        values: &[Value],
-        time: Timestamp,
-        diff: isize,
-    ) -> Result<FormatterContext, FormatterError> {
-        if values.len() != self.value_field_names.len() {
-            return Err(FormatterError::ColumnsValuesCountMismatch);
+    fn parse_file(&self, event: DataEventType, contents: &[u8]) -> ParseResult {
+        if contents.is_empty() {
+            return Ok(vec![]);
+        }
+        let reader = SerializedFileReader::new(Bytes::copy_from_slice(contents))
+            .map_err(ParseError::Parquet)?;
+        let mut events = Vec::new();
+        for row in RowIter::from_file_into(Box::new(reader)) {
+            let values =
+                parquet_row_into_values_map(&row.map_err(ParseError::Parquet)?, &self.column_types);
+            let key = self.key_field_names.as_ref().map(|key_field_names| {
+                key_field_names
+                    .iter()
+                    .map(|name| self.field_value(name, &values))
+                    .collect()
+            });
+            let values = self
+                .value_field_names
+                .iter()
+                .map(|name| self.field_value(name, &values))
+                .collect();
+            events.push(ParsedEventWithErrors::new(
+                self.session_type,
+                event,
+                key,
+                values,
+            ));
         }
+        Ok(events)
+    }
+}
 
-        let mut result = Vec::new();
-        writeln!(
-            result,
-            "INSERT INTO {} ({},time,diff) VALUES ({},{},{})",
-            self.table_name,
-            self.value_field_names.iter().join(","),
-            (1..=values.len()).format_with(",", |x, f| f(&format_args!("${x}"))),
-            time,
-            diff
-        )
-        .unwrap();
+impl Parser for ParquetParser {
+    fn parse(&mut self, data: &ReaderContext) -> ParseResult {
+        match data {
+            RawBytes(event, contents) => self.parse_file(*event, contents),
+            KeyValue((_key, value)) => match value {
+                Some(contents) => self.parse_file(DataEventType::Insert, contents),
+                None => Err(ParseError::EmptyKafkaPayload.into()),
+            },
+            Diff(_) | TokenizedEntries(_, _) => Err(ParseError::UnsupportedReaderContext.into()),
+            Empty => Ok(vec![]),
+        }
+    }
 
-        Ok(FormatterContext::new_single_payload(
-            result,
-            *key,
-            values.to_vec(),
-            time,
-            diff,
-        ))
+    fn on_new_source_started(&mut self, metadata: &SourceMetadata) {
+        let metadata_serialized: JsonValue = metadata.serialize();
+        self.metadata_column_value = metadata_serialized.into();
     }
-}
 
-#[derive(Debug, thiserror::Error)]
-#[non_exhaustive]
-pub enum PsqlSnapshotFormatterError {
-    #[error("repeated value field {0:?}")]
-    RepeatedValueField(String),
+    fn column_count(&self) -> usize {
+        self.value_field_names.len()
+    }
 
-    #[error("unknown key field {0:?}")]
-    UnknownKey(String),
+    fn session_type(&self) -> SessionType {
+        self.session_type
+    }
 }
 
-#[derive(Debug)]
-pub struct PsqlSnapshotFormatter {
-    table_name: String,
-    key_field_names: Vec<String>,
-    value_field_names: Vec<String>,
-
-    key_field_positions: Vec<usize>,
-    value_field_positions: Vec<usize>,
-    external_diff_column_index: Option<usize>,
+/// The encoding of the length that precedes each record in a length-prefixed binary stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthPrefix {
+    /// Unsigned LEB128 varint, as in the length-delimited protobuf streams.
+    Varint,
+    Fixed32BigEndian,
+    Fixed32LittleEndian,
 }
 
-impl PsqlSnapshotFormatter {
-    pub fn new(
-        table_name: String,
-        mut key_field_names: Vec<String>,
-        mut value_field_names: Vec<String>,
-        external_diff_column_index: Option<usize>,
-    ) -> Result<PsqlSnapshotFormatter, PsqlSnapshotFormatterError> {
-        let mut field_positions = HashMap::<String, usize>::with_capacity(value_field_names.len());
-        for (index, field_name) in value_field_names.iter_mut().enumerate() {
-            if field_positions.contains_key(field_name) {
-                return Err(PsqlSnapshotFormatterError::RepeatedValueField(take(
-                    field_name,
-                )));
-            }
-            field_positions.insert(field_name.clone(), index);
-        }
+impl LengthPrefix {
+    const MAX_VARINT_BYTES: usize = 10;
 
-        let mut key_field_positions = Vec::with_capacity(key_field_names.len());
-        for key_field_name in &mut key_field_names {
-            let position = field_positions
-                .remove(key_field_name)
-                .ok_or_else(|| PsqlSnapshotFormatterError::UnknownKey(take(key_field_name)))?;
-            key_field_positions.push(position);
+    /// Decodes the prefix at the beginning of `data` into the length of the frame and the size
+    /// of the prefix itself. Returns `None` if `data` doesn't contain the whole prefix yet.
+    fn decode(self, data: &[u8]) -> Result<Option<(u64, usize)>, ParseError> {
+        match self {
+            Self::Varint => {
+                let mut length = 0_u64;
+                for (index, byte) in data.iter().take(Self::MAX_VARINT_BYTES).enumerate() {
+                    let bits = u64::from(byte & 0x7f);
+                    let shift = 7 * index;
+                    if shift == 63 && bits > 1 {
+                        return Err(ParseError::MalformedLengthPrefix);
+                    }
+                    length |= bits << shift;
+                    if byte & 0x80 == 0 {
+                        return Ok(Some((length, index + 1)));
+                    }
+                }
+                if data.len() >= Self::MAX_VARINT_BYTES {
+                    Err(ParseError::MalformedLengthPrefix)
+                } else {
+                    Ok(None)
+                }
+            }
+            Self::Fixed32BigEndian | Self::Fixed32LittleEndian => {
+                let Some(prefix) = data.first_chunk::<4>() else {
+                    return Ok(None);
+                };
+                let length = if self == Self::Fixed32BigEndian {
+                    u32::from_be_bytes(*prefix)
+                } else {
+                    u32::from_le_bytes(*prefix)
+                };
+                Ok(Some((length.into(), prefix.len())))
+            }
         }
+    }
+}
 
-        let mut value_field_positions: Vec<_> = field_positions.into_values().collect();
+/// Splits binary data into the records preceded by their lengths and passes each record to
+/// the inner parser, which decodes its payload.
+///
+/// The messages of message queues, given as [`KeyValue`] contexts, have to consist of whole
+/// records, each of them inheriting the key of the message. The raw byte streams, given as
+/// [`RawBytes`] contexts, may be split at any point, so the incomplete record at the end of
+/// a chunk is kept until the rest of it arrives, or until a new source is started.
+pub struct LengthPrefixedParser {
+    inner: Box<dyn Parser>,
+    prefix: LengthPrefix,
+    max_frame_length: usize,
+    pending: Vec<u8>,
+    pending_event: DataEventType,
+}
 
-        key_field_positions.sort_unstable();
-        value_field_positions.sort_unstable();
-        Ok(PsqlSnapshotFormatter {
-            table_name,
-            key_field_names,
-            value_field_names,
+impl LengthPrefixedParser {
+    pub const DEFAULT_MAX_FRAME_LENGTH: usize = 64 * 1024 * 1024;
 
-            key_field_positions,
-            value_field_positions,
-            external_diff_column_index,
-        })
+    pub fn new(inner: Box<dyn Parser>, prefix: LengthPrefix, max_frame_length: usize) -> Self {
+        Self {
+            inner,
+            prefix,
+            max_frame_length,
+            pending: Vec::new(),
+            pending_event: DataEventType::Insert,
+        }
     }
-}
 
-impl Formatter for PsqlSnapshotFormatter {
-    fn format(
-        &mut self,
-        key: &Key,
-        values: &[Value],
-        time: Timestamp,
-        diff: isize,
-    ) -> Result<FormatterContext, FormatterError> {
-        if values.len() != self.value_field_names.len() {
-            return Err(FormatterError::ColumnsValuesCountMismatch);
+    /// Returns the complete frames found in `data` and the number of bytes they take.
+    fn split_frames<'a>(&self, data: &'a [u8]) -> Result<(Vec<&'a [u8]>, usize), ParseError> {
+        let mut frames = Vec::new();
+        let mut position = 0;
+        while let Some((length, prefix_size)) = self.prefix.decode(&data[position..])? {
+            let length = usize::try_from(length)
+                .ok()
+                .filter(|length| *length <= self.max_frame_length)
+                .ok_or(ParseError::FrameTooLarge {
+                    length,
+                    limit: self.max_frame_length,
+                })?;
+            let start = position + prefix_size;
+            let Some(frame) = data.get(start..start + length) else {
+                break;
+            };
+            frames.push(frame);
+            position = start + length;
+        }
+        Ok((frames, position))
+    }
+
+    fn parse_raw_bytes(&mut self, event: DataEventType, data: &[u8]) -> ParseResult {
+        if !self.pending.is_empty() && self.pending_event != event {
+            let unparsed = take(&mut self.pending).len();
+            return Err(ParseError::TruncatedFrame(unparsed).into());
+        }
+        self.pending.extend_from_slice(data);
+        self.pending_event = event;
+        // If the prefix is malformed, the stream can't be resynchronized, so the buffered
+        // data is dropped
+        let buffer = take(&mut self.pending);
+        let (frames, consumed) = self.split_frames(&buffer)?;
+        self.pending = buffer[consumed..].to_vec();
+        let mut result = Vec::new();
+        for frame in frames {
+            result.extend(self.inner.parse(&RawBytes(event, frame.to_vec()))?);
+        }
+        Ok(result)
+    }
+
+    fn parse_message(&mut self, key: Option<&Vec<u8>>, value: &[u8]) -> ParseResult {
+        let (frames, consumed) = self.split_frames(value)?;
+        if consumed < value.len() {
+            return Err(ParseError::TruncatedFrame(value.len() - consumed).into());
+        }
+        let mut result = Vec::new();
+        for frame in frames {
+            result.extend(
+                self.inner
+                    .parse(&KeyValue((key.cloned(), Some(frame.to_vec()))))?,
+            );
+        }
+        Ok(result)
+    }
+}
+
+impl Parser for LengthPrefixedParser {
+    fn parse(&mut self, data: &ReaderContext) -> ParseResult {
+        match data {
+            RawBytes(event, raw_bytes) => self.parse_raw_bytes(*event, raw_bytes),
+            KeyValue((key, Some(value))) => self.parse_message(key.as_ref(), value),
+            _ => self.inner.parse(data),
+        }
+    }
+
+    fn on_new_source_started(&mut self, metadata: &SourceMetadata) {
+        if !self.pending.is_empty() {
+            error!(
+                "Source ended in the middle of a frame, {} bytes are left unparsed",
+                self.pending.len()
+            );
+            self.pending.clear();
+        }
+        self.inner.on_new_source_started(metadata);
+    }
+
+    fn column_count(&self) -> usize {
+        self.inner.column_count()
+    }
+
+    fn short_description(&self) -> Cow<'static, str> {
+        format!("LengthPrefixed({})", self.inner.short_description()).into()
+    }
+
+    fn session_type(&self) -> SessionType {
+        self.inner.session_type()
+    }
+
+    fn report_schema_drift(&mut self, source_name: &str) {
+        self.inner.report_schema_drift(source_name);
+    }
+}
+
+/// The first byte of a message in the wire format of Confluent Schema Registry. It's followed by
+/// the id of the schema, as a four-byte big-endian integer, and by the encoded value.
+const SCHEMA_REGISTRY_MAGIC_BYTE: u8 = 0;
+const SCHEMA_REGISTRY_HEADER_LENGTH: usize = 5;
+
+/// Splits a message in the wire format of Confluent Schema Registry into the id of the schema and
+/// the encoded value.
+pub fn split_schema_registry_header(message: &[u8]) -> Result<(u32, &[u8]), ParseError> {
+    match message.split_first_chunk::<SCHEMA_REGISTRY_HEADER_LENGTH>() {
+        Some(([SCHEMA_REGISTRY_MAGIC_BYTE, schema_id @ ..], payload)) => {
+            Ok((u32::from_be_bytes(*schema_id), payload))
+        }
+        _ => Err(ParseError::NotSchemaRegistryMessage),
+    }
+}
+
+/// Prepends the header of the wire format of Confluent Schema Registry to an encoded value.
+pub fn with_schema_registry_header(schema_id: u32, payload: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(SCHEMA_REGISTRY_HEADER_LENGTH + payload.len());
+    message.push(SCHEMA_REGISTRY_MAGIC_BYTE);
+    message.extend_from_slice(&schema_id.to_be_bytes());
+    message.extend_from_slice(payload);
+    message
+}
+
+/// The Avro schemas stored in a Confluent Schema Registry. A schema is requested once, when it's
+/// first needed, as the schema behind an id never changes.
+pub struct AvroSchemaRegistry {
+    settings: SchemaRegistrySettings,
+    schemas: HashMap<u32, AvroSchema>,
+}
+
+impl AvroSchemaRegistry {
+    pub fn new(settings: SchemaRegistrySettings) -> Self {
+        Self {
+            settings,
+            schemas: HashMap::new(),
+        }
+    }
+
+    /// Adds the schema with the given id to the cache, so that it's not requested from the
+    /// registry.
+    #[must_use]
+    pub fn with_schema(mut self, schema_id: u32, schema: AvroSchema) -> Self {
+        self.schemas.insert(schema_id, schema);
+        self
+    }
+
+    pub fn schema(&mut self, schema_id: u32) -> Result<&AvroSchema, ParseError> {
+        if !self.schemas.contains_key(&schema_id) {
+            let registered = get_schema_by_id(schema_id, &self.settings)?;
+            if registered.schema_type != RegistrySchemaType::Avro {
+                return Err(ParseError::NotAvroSchema(schema_id));
+            }
+            let schema = AvroSchema::parse_str(&registered.schema)
+                .map_err(|e| ParseError::Avro(Box::new(e)))?;
+            self.schemas.insert(schema_id, schema);
+        }
+        Ok(&self.schemas[&schema_id])
+    }
+
+    /// Registers the schema under the subject, unless it's already there, and returns its id.
+    pub fn register(&mut self, subject: &str, schema: &str) -> Result<u32, FormatterError> {
+        let registered = post_schema(
+            &self.settings,
+            subject.to_string(),
+            SuppliedSchema {
+                name: None,
+                schema_type: RegistrySchemaType::Avro,
+                schema: schema.to_string(),
+                references: Vec::new(),
+            },
+        )?;
+        Ok(registered.id)
+    }
+}
+
+fn timestamp_to_json(nanoseconds: Option<i64>, utc: bool) -> JsonValue {
+    let Some(nanoseconds) = nanoseconds else {
+        return JsonValue::Null;
+    };
+    let datetime = ChronoDateTime::<Utc>::from_timestamp_nanos(nanoseconds);
+    let format = if utc {
+        "%Y-%m-%dT%H:%M:%S%.f%z"
+    } else {
+        "%Y-%m-%dT%H:%M:%S%.f"
+    };
+    JsonValue::String(datetime.format(format).to_string())
+}
+
+/// Converts a decoded Avro value into JSON, with the timestamps, dates and binary values
+/// represented in the same way as in the JSON messages, so that they are parsed into the columns
+/// of the same types.
+fn avro_value_to_json(value: AvroValue) -> JsonValue {
+    match value {
+        AvroValue::Null => JsonValue::Null,
+        AvroValue::Boolean(b) => JsonValue::Bool(b),
+        AvroValue::Int(i) => json!(i),
+        AvroValue::Long(i) => json!(i),
+        AvroValue::Float(f) => JsonValue::from(f64::from(f)),
+        AvroValue::Double(f) => JsonValue::from(f),
+        AvroValue::Bytes(bytes) | AvroValue::Fixed(_, bytes) => {
+            JsonValue::String(base64encoder.encode(bytes))
+        }
+        AvroValue::String(s) | AvroValue::Enum(_, s) => JsonValue::String(s),
+        AvroValue::Uuid(uuid) => JsonValue::String(uuid.to_string()),
+        AvroValue::Union(_, value) => avro_value_to_json(*value),
+        AvroValue::Array(items) => {
+            JsonValue::Array(items.into_iter().map(avro_value_to_json).collect())
+        }
+        AvroValue::Map(entries) => JsonValue::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key, avro_value_to_json(value)))
+                .collect(),
+        ),
+        AvroValue::Record(fields) => JsonValue::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name, avro_value_to_json(value)))
+                .collect(),
+        ),
+        AvroValue::Date(days) => {
+            timestamp_to_json(i64::from(days).checked_mul(86_400_000_000_000), false)
+        }
+        AvroValue::TimestampMillis(ms) => timestamp_to_json(ms.checked_mul(1_000_000), true),
+        AvroValue::TimestampMicros(us) => timestamp_to_json(us.checked_mul(1_000), true),
+        AvroValue::TimestampNanos(ns) => timestamp_to_json(Some(ns), true),
+        AvroValue::LocalTimestampMillis(ms) => timestamp_to_json(ms.checked_mul(1_000_000), false),
+        AvroValue::LocalTimestampMicros(us) => timestamp_to_json(us.checked_mul(1_000), false),
+        AvroValue::LocalTimestampNanos(ns) => timestamp_to_json(Some(ns), false),
+        // The times of day become durations in nanoseconds, as the durations in JSON are
+        AvroValue::TimeMillis(ms) => json!(i64::from(ms) * 1_000_000),
+        AvroValue::TimeMicros(us) => json!(us.saturating_mul(1_000)),
+        AvroValue::Duration(duration) => json!({
+            "months": u32::from(duration.months()),
+            "days": u32::from(duration.days()),
+            "millis": u32::from(duration.millis()),
+        }),
+        AvroValue::Decimal(decimal) => Vec::<u8>::try_from(decimal)
+            .map_or(JsonValue::Null, |bytes| {
+                JsonValue::String(base64encoder.encode(bytes))
+            }),
+        AvroValue::BigDecimal(decimal) => JsonValue::String(decimal.to_string()),
+    }
+}
+
+/// Parses the Avro records, either encoded with a fixed schema or in the wire format of
+/// Confluent Schema Registry, in which each message refers to the schema it was written with.
+///
+/// If both a schema and a registry are given, the records written with the schemas from the
+/// registry are read as if they had the given schema, following the Avro schema resolution
+/// rules. This way the older records get the default values of the fields added since then and
+/// the values of the removed fields are skipped.
+pub struct AvroParser {
+    key_field_names: Option<Vec<String>>,
+    value_field_names: Vec<String>,
+    schema: HashMap<String, InnerSchemaField>,
+    metadata_column_value: Value,
+    session_type: SessionType,
+    reader_schema: Option<AvroSchema>,
+    registry: Option<AvroSchemaRegistry>,
+}
+
+impl AvroParser {
+    pub fn new(
+        key_field_names: Option<Vec<String>>,
+        value_field_names: Vec<String>,
+        schema: HashMap<String, InnerSchemaField>,
+        session_type: SessionType,
+        reader_schema: Option<AvroSchema>,
+        registry: Option<AvroSchemaRegistry>,
+    ) -> Result<AvroParser> {
+        ensure_all_fields_in_schema(key_field_names.as_ref(), &value_field_names, &schema)?;
+        if reader_schema.is_none() && registry.is_none() {
+            return Err(ParseError::NoAvroSchema.into());
+        }
+        Ok(AvroParser {
+            key_field_names,
+            value_field_names,
+            schema,
+            metadata_column_value: Value::None,
+            session_type,
+            reader_schema,
+            registry,
+        })
+    }
+
+    fn decode(&mut self, message: &[u8]) -> Result<AvroValue, ParseError> {
+        let (writer_schema, mut payload) = match self.registry.as_mut() {
+            Some(registry) => {
+                let (schema_id, payload) = split_schema_registry_header(message)?;
+                (registry.schema(schema_id)?, payload)
+            }
+            None => (
+                self.reader_schema
+                    .as_ref()
+                    .expect("reader schema must be present without a registry"),
+                message,
+            ),
+        };
+        from_avro_datum(writer_schema, &mut payload, self.reader_schema.as_ref())
+            .map_err(|e| ParseError::Avro(Box::new(e)))
+    }
+
+    fn values_from_record(
+        &self,
+        record: &JsonValue,
+        field_names: &[String],
+    ) -> ValueFieldsWithErrors {
+        values_by_names_from_json(
+            record,
+            field_names,
+            &HashMap::new(),
+            true,
+            &self.schema,
+            &self.metadata_column_value,
+        )
+    }
+}
+
+impl Parser for AvroParser {
+    fn parse(&mut self, data: &ReaderContext) -> ParseResult {
+        let (data_event, message) = match data {
+            RawBytes(event, raw_bytes) => (*event, raw_bytes),
+            KeyValue((_key, value)) => {
+                if let Some(raw_bytes) = value {
+                    (DataEventType::Insert, raw_bytes)
+                } else {
+                    return Err(ParseError::EmptyKafkaPayload.into());
+                }
+            }
+            Diff(_) | TokenizedEntries(..) => {
+                return Err(ParseError::UnsupportedReaderContext.into());
+            }
+            Empty => return Ok(vec![]),
+        };
+        if message.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let record = avro_value_to_json(self.decode(message)?);
+        if !record.is_object() {
+            return Err(ParseError::NotAvroRecord.into());
+        }
+        let key = self.key_field_names.as_ref().map(|key_field_names| {
+            self.values_from_record(&record, key_field_names)
+                .into_iter()
+                .collect()
+        });
+        let values = self.values_from_record(&record, &self.value_field_names);
+        Ok(vec![ParsedEventWithErrors::new(
+            self.session_type,
+            data_event,
+            key,
+            values,
+        )])
+    }
+
+    fn on_new_source_started(&mut self, metadata: &SourceMetadata) {
+        let metadata_serialized: JsonValue = metadata.serialize();
+        self.metadata_column_value = metadata_serialized.into();
+    }
+
+    fn column_count(&self) -> usize {
+        self.value_field_names.len()
+    }
+
+    fn session_type(&self) -> SessionType {
+        self.session_type
+    }
+}
+
+const MAX_PROTOBUF_NESTING_DEPTH: usize = 100;
+
+/// A message type of a descriptor set, with its fields indexed by their numbers.
+struct ProtobufMessageDescriptor {
+    fields: HashMap<u32, FieldDescriptorProto>,
+    is_proto3: bool,
+    is_map_entry: bool,
+}
+
+/// The message types and the enums defined in a compiled `FileDescriptorSet`, such as the one
+/// produced by `protoc --include_imports --descriptor_set_out`, by their fully qualified names.
+pub struct ProtobufDescriptors {
+    messages: HashMap<String, ProtobufMessageDescriptor>,
+    enums: HashMap<String, HashMap<i32, String>>,
+}
+
+impl ProtobufDescriptors {
+    pub fn decode(descriptor_set: &[u8]) -> Result<ProtobufDescriptors, ParseError> {
+        let descriptor_set = FileDescriptorSet::decode(descriptor_set)
+            .map_err(ParseError::MalformedProtobufDescriptorSet)?;
+        let mut descriptors = ProtobufDescriptors {
+            messages: HashMap::new(),
+            enums: HashMap::new(),
+        };
+        for file in descriptor_set.file {
+            let scope = if file.package().is_empty() {
+                String::new()
+            } else {
+                format!(".{}", file.package())
+            };
+            let is_proto3 = file.syntax() == "proto3";
+            descriptors.add_enums(&scope, &file.enum_type);
+            for message in &file.message_type {
+                descriptors.add_message(&scope, message, is_proto3);
+            }
+        }
+        Ok(descriptors)
+    }
+
+    fn add_enums(&mut self, scope: &str, enums: &[EnumDescriptorProto]) {
+        for enum_type in enums {
+            let values = enum_type
+                .value
+                .iter()
+                .map(|value| (value.number(), value.name().to_string()))
+                .collect();
+            self.enums
+                .insert(format!("{scope}.{}", enum_type.name()), values);
+        }
+    }
+
+    fn add_message(&mut self, scope: &str, message: &DescriptorProto, is_proto3: bool) {
+        let name = format!("{scope}.{}", message.name());
+        self.add_enums(&name, &message.enum_type);
+        for nested in &message.nested_type {
+            self.add_message(&name, nested, is_proto3);
+        }
+        let fields = message
+            .field
+            .iter()
+            .filter_map(|field| Some((u32::try_from(field.number()).ok()?, field.clone())))
+            .collect();
+        let is_map_entry = message
+            .options
+            .as_ref()
+            .is_some_and(MessageOptions::map_entry);
+        self.messages.insert(
+            name,
+            ProtobufMessageDescriptor {
+                fields,
+                is_proto3,
+                is_map_entry,
+            },
+        );
+    }
+
+    fn message(&self, name: &str) -> Result<&ProtobufMessageDescriptor, ParseError> {
+        self.messages
+            .get(name)
+            .ok_or_else(|| ParseError::UnknownProtobufMessageType(name.to_string()))
+    }
+
+    /// Decodes a message into a JSON object. The fields without explicit presence that are
+    /// missing from the message get their default values, while the missing fields with explicit
+    /// presence, such as the nested messages, are left out.
+    pub fn decode_message(&self, message_name: &str, data: &[u8]) -> Result<JsonValue, ParseError> {
+        self.decode_message_at_depth(message_name, data, 0)
+    }
+
+    fn decode_message_at_depth(
+        &self,
+        message_name: &str,
+        mut data: &[u8],
+        depth: usize,
+    ) -> Result<JsonValue, ParseError> {
+        if depth > MAX_PROTOBUF_NESTING_DEPTH {
+            return Err(malformed_protobuf("messages are nested too deeply"));
+        }
+        let descriptor = self.message(message_name)?;
+        let mut decoded = JsonMap::new();
+        while !data.is_empty() {
+            let key = read_protobuf_varint(&mut data)?;
+            let wire_type = key & 0x7;
+            let Some(field) = u32::try_from(key >> 3)
+                .ok()
+                .and_then(|number| descriptor.fields.get(&number))
+            else {
+                skip_protobuf_field(wire_type, &mut data)?;
+                continue;
+            };
+            let is_repeated = field.label() == FieldLabel::Repeated;
+            if is_repeated && wire_type == 2 && is_packable(field.r#type()) {
+                let mut packed = read_protobuf_length_delimited(&mut data)?;
+                while !packed.is_empty() {
+                    let value = self.decode_field_value(
+                        field,
+                        packed_wire_type(field.r#type()),
+                        &mut packed,
+                        depth,
+                    )?;
+                    push_repeated_value(&mut decoded, field.name(), value);
+                }
+                continue;
+            }
+            let value = self.decode_field_value(field, wire_type, &mut data, depth)?;
+            if !is_repeated {
+                decoded.insert(field.name().to_string(), value);
+            } else if self.is_map_field(field) {
+                let JsonValue::Object(mut entry) = value else {
+                    unreachable!("map entries are messages");
+                };
+                let key = match entry.remove("key") {
+                    Some(JsonValue::String(key)) => key,
+                    Some(key) => key.to_string(),
+                    None => String::new(),
+                };
+                let value = entry.remove("value").unwrap_or(JsonValue::Null);
+                if let JsonValue::Object(map) = decoded
+                    .entry(field.name())
+                    .or_insert_with(|| JsonValue::Object(JsonMap::new()))
+                {
+                    map.insert(key, value);
+                }
+            } else {
+                push_repeated_value(&mut decoded, field.name(), value);
+            }
+        }
+        for field in descriptor.fields.values() {
+            if !decoded.contains_key(field.name()) {
+                if let Some(default) = self.default_field_value(field, descriptor.is_proto3) {
+                    decoded.insert(field.name().to_string(), default);
+                }
+            }
+        }
+        Ok(convert_well_known_protobuf_type(message_name, decoded))
+    }
+
+    fn is_map_field(&self, field: &FieldDescriptorProto) -> bool {
+        field.r#type() == FieldType::Message
+            && self
+                .messages
+                .get(field.type_name())
+                .is_some_and(|message| message.is_map_entry)
+    }
+
+    fn enum_value_name(&self, field: &FieldDescriptorProto, number: i32) -> JsonValue {
+        // The numbers not known to the descriptor set, e.g. added in a newer version of the
+        // schema, are kept as they are
+        self.enums
+            .get(field.type_name())
+            .and_then(|values| values.get(&number))
+            .map_or_else(|| json!(number), |name| JsonValue::String(name.clone()))
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    fn decode_field_value(
+        &self,
+        field: &FieldDescriptorProto,
+        wire_type: u64,
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<JsonValue, ParseError> {
+        let field_type = field.r#type();
+        let expected_wire_type = match field_type {
+            FieldType::Double | FieldType::Fixed64 | FieldType::Sfixed64 => 1,
+            FieldType::Float | FieldType::Fixed32 | FieldType::Sfixed32 => 5,
+            FieldType::String | FieldType::Bytes | FieldType::Message => 2,
+            FieldType::Group => {
+                return Err(malformed_protobuf(format!(
+                    "field {} is a group, which is not supported",
+                    field.name()
+                )))
+            }
+            _ => 0,
+        };
+        if wire_type != expected_wire_type {
+            return Err(malformed_protobuf(format!(
+                "field {} has wire type {wire_type} instead of {expected_wire_type}",
+                field.name()
+            )));
+        }
+        let value = match field_type {
+            FieldType::Double => json!(f64::from_le_bytes(read_protobuf_fixed(data)?)),
+            FieldType::Float => json!(f32::from_le_bytes(read_protobuf_fixed(data)?)),
+            FieldType::Fixed64 => json!(u64::from_le_bytes(read_protobuf_fixed(data)?)),
+            FieldType::Sfixed64 => json!(i64::from_le_bytes(read_protobuf_fixed(data)?)),
+            FieldType::Fixed32 => json!(u32::from_le_bytes(read_protobuf_fixed(data)?)),
+            FieldType::Sfixed32 => json!(i32::from_le_bytes(read_protobuf_fixed(data)?)),
+            FieldType::Int64 => json!(read_protobuf_varint(data)? as i64),
+            FieldType::Uint64 => json!(read_protobuf_varint(data)?),
+            FieldType::Int32 => json!(read_protobuf_varint(data)? as i32),
+            FieldType::Uint32 => json!(read_protobuf_varint(data)? as u32),
+            FieldType::Sint64 => json!(decode_zigzag(read_protobuf_varint(data)?)),
+            FieldType::Sint32 => json!(decode_zigzag(read_protobuf_varint(data)?) as i32),
+            FieldType::Bool => json!(read_protobuf_varint(data)? != 0),
+            FieldType::Enum => self.enum_value_name(field, read_protobuf_varint(data)? as i32),
+            FieldType::String => {
+                let bytes = read_protobuf_length_delimited(data)?;
+                let string = std::str::from_utf8(bytes).map_err(|_| {
+                    malformed_protobuf(format!("field {} is not valid UTF-8", field.name()))
+                })?;
+                JsonValue::String(string.to_string())
+            }
+            FieldType::Bytes => {
+                JsonValue::String(base64encoder.encode(read_protobuf_length_delimited(data)?))
+            }
+            FieldType::Message => {
+                let bytes = read_protobuf_length_delimited(data)?;
+                self.decode_message_at_depth(field.type_name(), bytes, depth + 1)?
+            }
+            FieldType::Group => unreachable!("groups are rejected above"),
+        };
+        Ok(value)
+    }
+
+    fn default_field_value(
+        &self,
+        field: &FieldDescriptorProto,
+        is_proto3: bool,
+    ) -> Option<JsonValue> {
+        if field.label() == FieldLabel::Repeated {
+            return Some(if self.is_map_field(field) {
+                JsonValue::Object(JsonMap::new())
+            } else {
+                JsonValue::Array(Vec::new())
+            });
+        }
+        let field_type = field.r#type();
+        if matches!(field_type, FieldType::Message | FieldType::Group) {
+            return None;
+        }
+        if !is_proto3 {
+            let default = field.default_value.as_deref()?;
+            return Some(match field_type {
+                FieldType::String | FieldType::Enum => JsonValue::String(default.to_string()),
+                FieldType::Bytes => JsonValue::String(base64encoder.encode(default)),
+                FieldType::Bool => JsonValue::Bool(default == "true"),
+                _ => serde_json::from_str(default).ok()?,
+            });
+        }
+        // The members of oneofs, including the proto3 optional fields, have explicit presence
+        if field.oneof_index.is_some() {
+            return None;
+        }
+        Some(match field_type {
+            FieldType::String | FieldType::Bytes => JsonValue::String(String::new()),
+            FieldType::Bool => JsonValue::Bool(false),
+            FieldType::Enum => self.enum_value_name(field, 0),
+            FieldType::Double | FieldType::Float => json!(0.0),
+            _ => json!(0),
+        })
+    }
+}
+
+/// Converts the decoded well-known types that have a natural representation, such as the
+/// timestamps or the wrappers of the scalar values, into the values they represent, as the JSON
+/// mapping of Protobuf does. The other messages become JSON objects.
+fn convert_well_known_protobuf_type(
+    message_name: &str,
+    mut decoded: JsonMap<String, JsonValue>,
+) -> JsonValue {
+    let nanoseconds = |decoded: &JsonMap<String, JsonValue>| {
+        let seconds = decoded.get("seconds").and_then(JsonValue::as_i64)?;
+        let nanos = decoded.get("nanos").and_then(JsonValue::as_i64)?;
+        seconds.checked_mul(1_000_000_000)?.checked_add(nanos)
+    };
+    match message_name {
+        ".google.protobuf.Timestamp" => timestamp_to_json(nanoseconds(&decoded), true),
+        ".google.protobuf.Duration" => {
+            nanoseconds(&decoded).map_or(JsonValue::Null, |nanoseconds| json!(nanoseconds))
+        }
+        ".google.protobuf.DoubleValue"
+        | ".google.protobuf.FloatValue"
+        | ".google.protobuf.Int64Value"
+        | ".google.protobuf.UInt64Value"
+        | ".google.protobuf.Int32Value"
+        | ".google.protobuf.UInt32Value"
+        | ".google.protobuf.BoolValue"
+        | ".google.protobuf.StringValue"
+        | ".google.protobuf.BytesValue" => decoded.remove("value").unwrap_or(JsonValue::Null),
+        _ => JsonValue::Object(decoded),
+    }
+}
+
+fn malformed_protobuf(reason: impl Into<String>) -> ParseError {
+    ParseError::MalformedProtobufMessage(reason.into())
+}
+
+fn read_protobuf_varint(data: &mut &[u8]) -> Result<u64, ParseError> {
+    match LengthPrefix::Varint.decode(data) {
+        Ok(Some((value, size))) => {
+            *data = &data[size..];
+            Ok(value)
+        }
+        Ok(None) | Err(_) => Err(malformed_protobuf("malformed varint")),
+    }
+}
+
+fn read_protobuf_fixed<const N: usize>(data: &mut &[u8]) -> Result<[u8; N], ParseError> {
+    let (value, rest) = data
+        .split_first_chunk::<N>()
+        .ok_or_else(|| malformed_protobuf("message is truncated"))?;
+    *data = rest;
+    Ok(*value)
+}
+
+fn read_protobuf_length_delimited<'a>(data: &mut &'a [u8]) -> Result<&'a [u8], ParseError> {
+    let length = read_protobuf_varint(data)?;
+    let length = usize::try_from(length)
+        .ok()
+        .filter(|length| *length <= data.len())
+        .ok_or_else(|| malformed_protobuf("message is truncated"))?;
+    let (value, rest) = data.split_at(length);
+    *data = rest;
+    Ok(value)
+}
+
+fn skip_protobuf_field(wire_type: u64, data: &mut &[u8]) -> Result<(), ParseError> {
+    match wire_type {
+        0 => {
+            read_protobuf_varint(data)?;
+        }
+        1 => {
+            read_protobuf_fixed::<8>(data)?;
+        }
+        2 => {
+            read_protobuf_length_delimited(data)?;
+        }
+        5 => {
+            read_protobuf_fixed::<4>(data)?;
+        }
+        _ => {
+            return Err(malformed_protobuf(format!(
+                "unsupported wire type {wire_type}"
+            )))
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn decode_zigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn is_packable(field_type: FieldType) -> bool {
+    !matches!(
+        field_type,
+        FieldType::String | FieldType::Bytes | FieldType::Message | FieldType::Group
+    )
+}
+
+fn packed_wire_type(field_type: FieldType) -> u64 {
+    match field_type {
+        FieldType::Double | FieldType::Fixed64 | FieldType::Sfixed64 => 1,
+        FieldType::Float | FieldType::Fixed32 | FieldType::Sfixed32 => 5,
+        _ => 0,
+    }
+}
+
+fn push_repeated_value(decoded: &mut JsonMap<String, JsonValue>, name: &str, value: JsonValue) {
+    if let JsonValue::Array(values) = decoded
+        .entry(name)
+        .or_insert_with(|| JsonValue::Array(Vec::new()))
+    {
+        values.push(value);
+    }
+}
+
+/// Parses the Protobuf messages of the given type, described by a compiled descriptor set.
+///
+/// The fields of the message are mapped to the columns of the same names according to their
+/// declared types, in the same way as the fields of the JSON messages are. The nested messages
+/// and the maps become JSON objects and the repeated fields become arrays, so they can be read
+/// into the `Json` columns, while the timestamps, durations and wrappers of the scalar values
+/// are read as the values they represent. The enum values are read as their names.
+pub struct ProtobufParser {
+    key_field_names: Option<Vec<String>>,
+    value_field_names: Vec<String>,
+    schema: HashMap<String, InnerSchemaField>,
+    metadata_column_value: Value,
+    session_type: SessionType,
+    descriptors: ProtobufDescriptors,
+    message_name: String,
+}
+
+impl ProtobufParser {
+    pub fn new(
+        key_field_names: Option<Vec<String>>,
+        value_field_names: Vec<String>,
+        schema: HashMap<String, InnerSchemaField>,
+        session_type: SessionType,
+        descriptor_set: &[u8],
+        message_name: &str,
+    ) -> Result<ProtobufParser> {
+        ensure_all_fields_in_schema(key_field_names.as_ref(), &value_field_names, &schema)?;
+        let descriptors = ProtobufDescriptors::decode(descriptor_set)?;
+        // The type can be given with or without the leading dot of a fully qualified name
+        let message_name = format!(".{}", message_name.trim_start_matches('.'));
+        let message = descriptors.message(&message_name)?;
+        let known_fields: HashSet<&str> = message
+            .fields
+            .values()
+            .map(FieldDescriptorProto::name)
+            .collect();
+        for name in key_field_names.iter().flatten().chain(&value_field_names) {
+            if name != METADATA_FIELD_NAME && !known_fields.contains(name.as_str()) {
+                return Err(ParseError::FieldNotInProtobufMessage {
+                    field_name: name.clone(),
+                    message_name,
+                }
+                .into());
+            }
+        }
+        Ok(ProtobufParser {
+            key_field_names,
+            value_field_names,
+            schema,
+            metadata_column_value: Value::None,
+            session_type,
+            descriptors,
+            message_name,
+        })
+    }
+
+    fn values_from_message(
+        &self,
+        message: &JsonValue,
+        field_names: &[String],
+    ) -> ValueFieldsWithErrors {
+        // The missing fields are the ones with explicit presence, so they are absent values
+        values_by_names_from_json(
+            message,
+            field_names,
+            &HashMap::new(),
+            false,
+            &self.schema,
+            &self.metadata_column_value,
+        )
+    }
+}
+
+impl Parser for ProtobufParser {
+    fn parse(&mut self, data: &ReaderContext) -> ParseResult {
+        let (data_event, payload) = match data {
+            RawBytes(event, raw_bytes) => (*event, raw_bytes),
+            KeyValue((_key, value)) => {
+                if let Some(raw_bytes) = value {
+                    (DataEventType::Insert, raw_bytes)
+                } else {
+                    return Err(ParseError::EmptyKafkaPayload.into());
+                }
+            }
+            Diff(_) | TokenizedEntries(..) => {
+                return Err(ParseError::UnsupportedReaderContext.into());
+            }
+            Empty => return Ok(vec![]),
+        };
+
+        let message = self
+            .descriptors
+            .decode_message(&self.message_name, payload)?;
+        let key = self.key_field_names.as_ref().map(|key_field_names| {
+            self.values_from_message(&message, key_field_names)
+                .into_iter()
+                .collect()
+        });
+        let values = self.values_from_message(&message, &self.value_field_names);
+        Ok(vec![ParsedEventWithErrors::new(
+            self.session_type,
+            data_event,
+            key,
+            values,
+        )])
+    }
+
+    fn on_new_source_started(&mut self, metadata: &SourceMetadata) {
+        let metadata_serialized: JsonValue = metadata.serialize();
+        self.metadata_column_value = metadata_serialized.into();
+    }
+
+    fn column_count(&self) -> usize {
+        self.value_field_names.len()
+    }
+
+    fn session_type(&self) -> SessionType {
+        self.session_type
+    }
+}
+
+/// The MessagePack extension type of the timestamps.
+const MSGPACK_TIMESTAMP_EXT_TYPE: i8 = -1;
+
+/// Decodes the payload of the MessagePack timestamp extension, in any of its 32, 64 and 96-bit
+/// forms, into the number of nanoseconds since the epoch.
+fn msgpack_timestamp_to_nanoseconds(data: &[u8]) -> Option<i64> {
+    let (seconds, nanoseconds) = match data.len() {
+        4 => (i64::from(u32::from_be_bytes(data.try_into().ok()?)), 0),
+        8 => {
+            let packed = u64::from_be_bytes(data.try_into().ok()?);
+            (
+                i64::try_from(packed & 0x3_ffff_ffff).ok()?,
+                i64::try_from(packed >> 34).ok()?,
+            )
+        }
+        12 => {
+            let (nanoseconds, seconds) = data.split_at(4);
+            (
+                i64::from_be_bytes(seconds.try_into().ok()?),
+                i64::from(u32::from_be_bytes(nanoseconds.try_into().ok()?)),
+            )
+        }
+        _ => return None,
+    };
+    seconds.checked_mul(1_000_000_000)?.checked_add(nanoseconds)
+}
+
+/// Converts a MessagePack value into JSON, for the columns of the JSON type and the types that
+/// MessagePack has no representation for. The binary values become base64 strings and the
+/// timestamps become strings, as in the JSON messages.
+fn msgpack_value_to_json(value: &MsgPackValue) -> Option<JsonValue> {
+    let converted = match value {
+        MsgPackValue::Nil => JsonValue::Null,
+        MsgPackValue::Boolean(b) => JsonValue::Bool(*b),
+        MsgPackValue::Integer(i) => match i.as_i64() {
+            Some(i) => json!(i),
+            None => json!(i.as_u64()?),
+        },
+        MsgPackValue::F32(f) => JsonValue::from(f64::from(*f)),
+        MsgPackValue::F64(f) => JsonValue::from(*f),
+        MsgPackValue::String(s) => JsonValue::String(s.as_str()?.to_string()),
+        MsgPackValue::Binary(bytes) => JsonValue::String(base64encoder.encode(bytes)),
+        MsgPackValue::Array(items) => JsonValue::Array(
+            items
+                .iter()
+                .map(msgpack_value_to_json)
+                .collect::<Option<_>>()?,
+        ),
+        MsgPackValue::Map(entries) => JsonValue::Object(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    Some((key.as_str()?.to_string(), msgpack_value_to_json(value)?))
+                })
+                .collect::<Option<_>>()?,
+        ),
+        MsgPackValue::Ext(MSGPACK_TIMESTAMP_EXT_TYPE, data) => {
+            timestamp_to_json(Some(msgpack_timestamp_to_nanoseconds(data)?), true)
+        }
+        MsgPackValue::Ext(..) => return None,
+    };
+    Some(converted)
+}
+
+/// Converts a MessagePack value into a value of the given type. The values having a counterpart
+/// in MessagePack are converted directly, and the others, such as the pointers or the naive
+/// datetimes, are expected in the same representation as in the JSON messages.
+fn parse_value_from_msgpack(value: &MsgPackValue, dtype: &Type) -> Option<Value> {
+    if value.is_nil() {
+        if dtype.is_optional() {
+            return Some(Value::None);
+        }
+        return None;
+    }
+    match (dtype.unoptionalize(), value) {
+        (Type::Bool | Type::Any, MsgPackValue::Boolean(b)) => Some(Value::Bool(*b)),
+        (Type::Int | Type::Any, MsgPackValue::Integer(i)) => i.as_i64().map(Value::from),
+        (Type::Float, MsgPackValue::Integer(i)) => i.as_f64().map(Value::from),
+        (Type::Float | Type::Any, MsgPackValue::F32(f)) => Some(Value::from(f64::from(*f))),
+        (Type::Float | Type::Any, MsgPackValue::F64(f)) => Some(Value::from(*f)),
+        (Type::Duration, MsgPackValue::Integer(i)) => {
+            let duration_ns = i.as_i64()?;
+            let engine_duration = EngineDuration::new_with_unit(duration_ns, "ns")
+                .expect("new_with_unit can't fail when 'ns' is used as a unit");
+            Some(Value::Duration(engine_duration))
+        }
+        (Type::String | Type::Any, MsgPackValue::String(s)) => s.as_str().map(Value::from),
+        (Type::Bytes | Type::Any, MsgPackValue::Binary(bytes)) => {
+            Some(Value::Bytes(bytes.as_slice().into()))
+        }
+        (Type::DateTimeUtc | Type::Any, MsgPackValue::Ext(MSGPACK_TIMESTAMP_EXT_TYPE, data)) => {
+            msgpack_timestamp_to_nanoseconds(data)
+                .map(|timestamp| Value::DateTimeUtc(DateTimeUtc::new(timestamp)))
+        }
+        (Type::Json, value) => msgpack_value_to_json(value).map(Value::from),
+        (Type::Tuple(dtypes), MsgPackValue::Array(items)) => {
+            if items.len() != dtypes.len() {
+                return None;
+            }
+            let tuple: Option<Vec<_>> = zip(items, dtypes.iter())
+                .map(|(item, dtype)| parse_value_from_msgpack(item, dtype))
+                .collect();
+            tuple.map(Value::from)
+        }
+        (Type::List(dtype), MsgPackValue::Array(items)) => {
+            let list: Option<Vec<_>> = items
+                .iter()
+                .map(|item| parse_value_from_msgpack(item, dtype))
+                .collect();
+            list.map(Value::from)
+        }
+        (_, value) => parse_value_from_json(&msgpack_value_to_json(value)?, dtype),
+    }
+}
+
+/// Parses the MessagePack payloads, each of which must be a map from the column names to their
+/// values. The values are converted into the columns without going through JSON, except for
+/// the columns of the JSON type and the types MessagePack has no representation for.
+pub struct MsgPackParser {
+    key_field_names: Option<Vec<String>>,
+    value_field_names: Vec<String>,
+    schema: HashMap<String, InnerSchemaField>,
+    metadata_column_value: Value,
+    session_type: SessionType,
+}
+
+impl MsgPackParser {
+    pub fn new(
+        key_field_names: Option<Vec<String>>,
+        value_field_names: Vec<String>,
+        schema: HashMap<String, InnerSchemaField>,
+        session_type: SessionType,
+    ) -> Result<MsgPackParser> {
+        ensure_all_fields_in_schema(key_field_names.as_ref(), &value_field_names, &schema)?;
+        Ok(MsgPackParser {
+            key_field_names,
+            value_field_names,
+            schema,
+            metadata_column_value: Value::None,
+            session_type,
+        })
+    }
+
+    fn values_from_map(
+        &self,
+        entries: &HashMap<&str, &MsgPackValue>,
+        field_names: &[String],
+    ) -> ValueFieldsWithErrors {
+        field_names
+            .iter()
+            .map(|field_name| {
+                if field_name == METADATA_FIELD_NAME {
+                    return Ok(self.metadata_column_value.clone());
+                }
+                let (default_value, dtype) = match self.schema.get(field_name) {
+                    Some(schema_item) => (schema_item.default.as_ref(), &schema_item.type_),
+                    None => (None, &Type::Any),
+                };
+                if let Some(value) = entries.get(field_name.as_str()) {
+                    parse_value_from_msgpack(value, dtype).ok_or_else(|| {
+                        ParseError::FailedToParseFromMsgPack {
+                            field_name: field_name.clone(),
+                            value: value.to_string(),
+                            type_: dtype.clone(),
+                        }
+                        .into()
+                    })
+                } else if let Some(default) = default_value {
+                    Ok(default.clone())
+                } else {
+                    Err(ParseError::NoDefault {
+                        field_name: field_name.clone(),
+                    }
+                    .into())
+                }
+            })
+            .collect()
+    }
+}
+
+impl Parser for MsgPackParser {
+    fn parse(&mut self, data: &ReaderContext) -> ParseResult {
+        let (data_event, mut payload) = match data {
+            RawBytes(event, raw_bytes) => (*event, raw_bytes.as_slice()),
+            KeyValue((_key, value)) => {
+                if let Some(raw_bytes) = value {
+                    (DataEventType::Insert, raw_bytes.as_slice())
+                } else {
+                    return Err(ParseError::EmptyKafkaPayload.into());
+                }
+            }
+            Diff(_) | TokenizedEntries(..) => {
+                return Err(ParseError::UnsupportedReaderContext.into());
+            }
+            Empty => return Ok(vec![]),
+        };
+        if payload.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let message = read_msgpack_value(&mut payload).map_err(ParseError::MalformedMsgPack)?;
+        let MsgPackValue::Map(entries) = &message else {
+            return Err(ParseError::NotMsgPackMap.into());
+        };
+        // The entries with non-string keys can't correspond to any column
+        let entries: HashMap<&str, &MsgPackValue> = entries
+            .iter()
+            .filter_map(|(key, value)| Some((key.as_str()?, value)))
+            .collect();
+        let key = self.key_field_names.as_ref().map(|key_field_names| {
+            self.values_from_map(&entries, key_field_names)
+                .into_iter()
+                .collect()
+        });
+        let values = self.values_from_map(&entries, &self.value_field_names);
+        Ok(vec![ParsedEventWithErrors::new(
+            self.session_type,
+            data_event,
+            key,
+            values,
+        )])
+    }
+
+    fn on_new_source_started(&mut self, metadata: &SourceMetadata) {
+        let metadata_serialized: JsonValue = metadata.serialize();
+        self.metadata_column_value = metadata_serialized.into();
+    }
+
+    fn column_count(&self) -> usize {
+        self.value_field_names.len()
+    }
+
+    fn session_type(&self) -> SessionType {
+        self.session_type
+    }
+}
+
+#[derive(Debug)]
+pub struct PsqlUpdatesFormatter {
+    table_name: String,
+    value_field_names: Vec<String>,
+}
+
+impl PsqlUpdatesFormatter {
+    pub fn new(table_name: String, value_field_names: Vec<String>) -> PsqlUpdatesFormatter {
+        PsqlUpdatesFormatter {
+            table_name,
+            value_field_names,
+        }
+    }
+}
+
+impl Formatter for PsqlUpdatesFormatter {
+    fn format(
+        &mut self,
+        key: &Key,
+        values: &[Value],
+        time: Timestamp,
+        diff: isize,
+    ) -> Result<FormatterContext, FormatterError> {
+        if values.len() != self.value_field_names.len() {
+            return Err(FormatterError::ColumnsValuesCountMismatch);
+        }
+
+        let mut result = Vec::new();
+        writeln!(
+            result,
+            "INSERT INTO {} ({},time,diff) VALUES ({},{},{})",
+            self.table_name,
+            self.value_field_names.iter().join(","),
+            (1..=values.len()).format_with(",", |x, f| f(&format_args!("${x}"))),
+            time,
+            diff
+        )
+        .unwrap();
+
+        Ok(FormatterContext::new_single_payload(
+            result,
+            *key,
+            values.to_vec(),
+            time,
+            diff,
+        ))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PsqlSnapshotFormatterError {
+    #[error("repeated value field {0:?}")]
+    RepeatedValueField(String),
+
+    #[error("unknown key field {0:?}")]
+    UnknownKey(String),
+}
+
+#[derive(Debug)]
+pub struct PsqlSnapshotFormatter {
+    table_name: String,
+    key_field_names: Vec<String>,
+    value_field_names: Vec<String>,
+
+    key_field_positions: Vec<usize>,
+    value_field_positions: Vec<usize>,
+    external_diff_column_index: Option<usize>,
+}
+
+impl PsqlSnapshotFormatter {
+    pub fn new(
+        table_name: String,
+        mut key_field_names: Vec<String>,
+        mut value_field_names: Vec<String>,
+        external_diff_column_index: Option<usize>,
+    ) -> Result<PsqlSnapshotFormatter, PsqlSnapshotFormatterError> {
+        let mut field_positions = HashMap::<String, usize>::with_capacity(value_field_names.len());
+        for (index, field_name) in value_field_names.iter_mut().enumerate() {
+            if field_positions.contains_key(field_name) {
+                return Err(PsqlSnapshotFormatterError::RepeatedValueField(take(
+                    field_name,
+                )));
+            }
+            field_positions.insert(field_name.clone(), index);
+        }
+
+        let mut key_field_positions = Vec::with_capacity(key_field_names.len());
+        for key_field_name in &mut key_field_names {
+            let position = field_positions
+                .remove(key_field_name)
+                .ok_or_else(|| PsqlSnapshotFormatterError::UnknownKey(take(key_field_name)))?;
+            key_field_positions.push(position);
+        }
+
+        let mut value_field_positions: Vec<_> = field_positions.into_values().collect();
+
+        key_field_positions.sort_unstable();
+        value_field_positions.sort_unstable();
+        Ok(PsqlSnapshotFormatter {
+            table_name,
+            key_field_names,
+            value_field_names,
+
+            key_field_positions,
+            value_field_positions,
+            external_diff_column_index,
+        })
+    }
+}
+
+impl Formatter for PsqlSnapshotFormatter {
+    fn format(
+        &mut self,
+        key: &Key,
+        values: &[Value],
+        time: Timestamp,
+        diff: isize,
+    ) -> Result<FormatterContext, FormatterError> {
+        if values.len() != self.value_field_names.len() {
+            return Err(FormatterError::ColumnsValuesCountMismatch);
         }
 
         let mut result = Vec::new();
@@ -2140,6 +3696,353 @@ impl Formatter for JsonLinesFormatter {
     }
 }
 
+/// Converts a value into an Avro value matching the schema. A missing value matches the null
+/// variant of a union and any other value matches the first of the other variants it can be
+/// converted to.
+fn value_to_avro(value: &Value, schema: &AvroSchema) -> Result<AvroValue, FormatterError> {
+    if let AvroSchema::Union(union) = schema {
+        for (index, variant) in union.variants().iter().enumerate() {
+            if matches!(value, Value::None) != matches!(variant, AvroSchema::Null) {
+                continue;
+            }
+            if let Ok(converted) = value_to_avro(value, variant) {
+                let index = u32::try_from(index).expect("unions can't have that many variants");
+                return Ok(AvroValue::Union(index, Box::new(converted)));
+            }
+        }
+        return Err(FormatterError::ValueNotMatchingAvroSchema(value.clone()));
+    }
+    let converted = match (value, schema) {
+        (Value::None, _) => AvroValue::Null,
+        (Value::Bool(b), _) => AvroValue::Boolean(*b),
+        (Value::Int(i), _) => AvroValue::Long(*i),
+        (Value::Float(f), _) => AvroValue::Double(f.into_inner()),
+        (Value::String(s), _) => AvroValue::String(s.to_string()),
+        (Value::Pointer(p), _) => AvroValue::String(p.to_string()),
+        (Value::Json(j), _) => AvroValue::String(j.to_string()),
+        (Value::Bytes(b), _) => AvroValue::Bytes(b.to_vec()),
+        (Value::Duration(d), _) => AvroValue::Long(d.nanoseconds()),
+        (Value::DateTimeUtc(dt), AvroSchema::TimestampMillis) => {
+            AvroValue::TimestampMillis(dt.timestamp().div_euclid(1_000_000))
+        }
+        (Value::DateTimeUtc(dt), AvroSchema::TimestampNanos) => {
+            AvroValue::TimestampNanos(dt.timestamp())
+        }
+        (Value::DateTimeUtc(dt), _) => AvroValue::TimestampMicros(dt.timestamp().div_euclid(1_000)),
+        (Value::DateTimeNaive(dt), AvroSchema::Date) => {
+            let days = dt.timestamp().div_euclid(86_400_000_000_000);
+            AvroValue::Date(
+                i32::try_from(days)
+                    .map_err(|_| FormatterError::ValueNotMatchingAvroSchema(value.clone()))?,
+            )
+        }
+        (Value::DateTimeNaive(dt), AvroSchema::LocalTimestampMillis) => {
+            AvroValue::LocalTimestampMillis(dt.timestamp().div_euclid(1_000_000))
+        }
+        (Value::DateTimeNaive(dt), AvroSchema::LocalTimestampNanos) => {
+            AvroValue::LocalTimestampNanos(dt.timestamp())
+        }
+        (Value::DateTimeNaive(dt), _) => {
+            AvroValue::LocalTimestampMicros(dt.timestamp().div_euclid(1_000))
+        }
+        (Value::Tuple(items), AvroSchema::Array(array)) => AvroValue::Array(
+            items
+                .iter()
+                .map(|item| value_to_avro(item, &array.items))
+                .collect::<Result<_, _>>()?,
+        ),
+        _ => return Err(FormatterError::ValueNotMatchingAvroSchema(value.clone())),
+    };
+    converted
+        .resolve(schema)
+        .map_err(|_| FormatterError::ValueNotMatchingAvroSchema(value.clone()))
+}
+
+/// Formats the rows as Avro records of the given schema, which must be a record schema. The
+/// columns and the `time` and `diff` fields are matched with the fields of the schema by name,
+/// and the fields without a matching column get their default values.
+///
+/// With a registry, the schema is registered under the subject before the first record is
+/// written and the records are written in the wire format of Confluent Schema Registry.
+pub struct AvroFormatter {
+    value_field_names: Vec<String>,
+    schema: AvroSchema,
+    schema_json: String,
+    registry: Option<(AvroSchemaRegistry, String)>,
+    schema_id: Option<u32>,
+}
+
+impl AvroFormatter {
+    pub fn new(
+        value_field_names: Vec<String>,
+        schema_json: String,
+        registry: Option<(AvroSchemaRegistry, String)>,
+    ) -> Result<AvroFormatter, FormatterError> {
+        let schema =
+            AvroSchema::parse_str(&schema_json).map_err(|e| FormatterError::Avro(Box::new(e)))?;
+        if !matches!(schema, AvroSchema::Record(_)) {
+            return Err(FormatterError::NotAvroRecordSchema);
+        }
+        Ok(AvroFormatter {
+            value_field_names,
+            schema,
+            schema_json,
+            registry,
+            schema_id: None,
+        })
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn record(
+        &self,
+        values: &[Value],
+        time: Timestamp,
+        diff: isize,
+    ) -> Result<AvroValue, FormatterError> {
+        let AvroSchema::Record(record_schema) = &self.schema else {
+            unreachable!("the schema is checked to be a record schema");
+        };
+        let mut fields = Vec::with_capacity(record_schema.fields.len());
+        for field in &record_schema.fields {
+            let value = if let Some(index) = self
+                .value_field_names
+                .iter()
+                .position(|name| *name == field.name)
+            {
+                Cow::Borrowed(&values[index])
+            } else if field.name == SPECIAL_FIELD_TIME {
+                Cow::Owned(Value::Int(time.0 as i64))
+            } else if field.name == SPECIAL_FIELD_DIFF {
+                Cow::Owned(Value::Int(diff as i64))
+            } else {
+                continue;
+            };
+            fields.push((field.name.clone(), value_to_avro(&value, &field.schema)?));
+        }
+        AvroValue::Record(fields)
+            .resolve(&self.schema)
+            .map_err(|e| FormatterError::Avro(Box::new(e)))
+    }
+}
+
+impl Formatter for AvroFormatter {
+    fn format(
+        &mut self,
+        key: &Key,
+        values: &[Value],
+        time: Timestamp,
+        diff: isize,
+    ) -> Result<FormatterContext, FormatterError> {
+        let record = self.record(values, time, diff)?;
+        let payload =
+            to_avro_datum(&self.schema, record).map_err(|e| FormatterError::Avro(Box::new(e)))?;
+        let raw_bytes = match self.registry.as_mut() {
+            Some((registry, subject)) => {
+                let schema_id = match self.schema_id {
+                    Some(schema_id) => schema_id,
+                    None => {
+                        let schema_id = registry.register(subject, &self.schema_json)?;
+                        *self.schema_id.insert(schema_id)
+                    }
+                };
+                with_schema_registry_header(schema_id, &payload)
+            }
+            None => payload,
+        };
+
+        Ok(FormatterContext::new_single_payload(
+            raw_bytes,
+            *key,
+            values.to_vec(),
+            time,
+            diff,
+        ))
+    }
+}
+
+fn json_to_msgpack_value(value: &JsonValue) -> MsgPackValue {
+    match value {
+        JsonValue::Null => MsgPackValue::Nil,
+        JsonValue::Bool(b) => MsgPackValue::Boolean(*b),
+        JsonValue::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                MsgPackValue::from(i)
+            } else if let Some(u) = n.as_u64() {
+                MsgPackValue::from(u)
+            } else {
+                MsgPackValue::F64(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        JsonValue::String(s) => MsgPackValue::from(s.as_str()),
+        JsonValue::Array(items) => {
+            MsgPackValue::Array(items.iter().map(json_to_msgpack_value).collect())
+        }
+        JsonValue::Object(entries) => MsgPackValue::Map(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        MsgPackValue::from(key.as_str()),
+                        json_to_msgpack_value(value),
+                    )
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Converts a value into MessagePack. The values having a counterpart in MessagePack are
+/// converted directly, with the UTC datetimes becoming timestamp extensions, and the others are
+/// represented in the same way as in the JSON output.
+fn value_to_msgpack(value: &Value) -> Result<MsgPackValue, FormatterError> {
+    let converted = match value {
+        Value::None => MsgPackValue::Nil,
+        Value::Bool(b) => MsgPackValue::Boolean(*b),
+        Value::Int(i) => MsgPackValue::from(*i),
+        Value::Float(f) => MsgPackValue::F64(f.into_inner()),
+        Value::String(s) => MsgPackValue::from(s.as_str()),
+        Value::Bytes(b) => MsgPackValue::Binary(b.to_vec()),
+        Value::Duration(d) => MsgPackValue::from(d.nanoseconds()),
+        Value::DateTimeUtc(dt) => {
+            let timestamp = dt.timestamp();
+            let seconds = timestamp.div_euclid(1_000_000_000);
+            let nanoseconds = u32::try_from(timestamp.rem_euclid(1_000_000_000))
+                .expect("the remainder must fit into u32");
+            // The 96-bit form of the timestamp extension, which can hold any timestamp
+            let mut data = Vec::with_capacity(12);
+            data.extend_from_slice(&nanoseconds.to_be_bytes());
+            data.extend_from_slice(&seconds.to_be_bytes());
+            MsgPackValue::Ext(MSGPACK_TIMESTAMP_EXT_TYPE, data)
+        }
+        Value::Tuple(items) => MsgPackValue::Array(
+            items
+                .iter()
+                .map(value_to_msgpack)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Json(j) => json_to_msgpack_value(j),
+        Value::Error => return Err(FormatterError::ErrorValueNonMsgPackSerializable),
+        Value::Pending => return Err(FormatterError::PendingValueNonMsgPackSerializable),
+        Value::Pointer(_)
+        | Value::DateTimeNaive(_)
+        | Value::IntArray(_)
+        | Value::FloatArray(_)
+        | Value::Float32Array(_)
+        | Value::PyObjectWrapper(_) => json_to_msgpack_value(&serialize_value_to_json(value)?),
+    };
+    Ok(converted)
+}
+
+/// Formats the rows as MessagePack maps from the column names to their values, with the `time`
+/// and `diff` fields added, in the same way as the JSON formatter does.
+pub struct MsgPackFormatter {
+    value_field_names: Vec<String>,
+}
+
+impl MsgPackFormatter {
+    pub fn new(value_field_names: Vec<String>) -> MsgPackFormatter {
+        MsgPackFormatter { value_field_names }
+    }
+}
+
+impl Formatter for MsgPackFormatter {
+    #[allow(clippy::cast_possible_wrap)]
+    fn format(
+        &mut self,
+        key: &Key,
+        values: &[Value],
+        time: Timestamp,
+        diff: isize,
+    ) -> Result<FormatterContext, FormatterError> {
+        let mut entries = Vec::with_capacity(self.value_field_names.len() + 2);
+        for (name, value) in zip(self.value_field_names.iter(), values) {
+            entries.push((MsgPackValue::from(name.as_str()), value_to_msgpack(value)?));
+        }
+        entries.push((
+            MsgPackValue::from(SPECIAL_FIELD_DIFF),
+            MsgPackValue::from(diff as i64),
+        ));
+        entries.push((
+            MsgPackValue::from(SPECIAL_FIELD_TIME),
+            MsgPackValue::from(time.0),
+        ));
+        let mut payload = Vec::new();
+        write_msgpack_value(&mut payload, &MsgPackValue::Map(entries))
+            .expect("writing to a vector should not fail");
+
+        Ok(FormatterContext::new_single_payload(
+            payload,
+            *key,
+            values.to_vec(),
+            time,
+            diff,
+        ))
+    }
+}
+
+/// Formats the changes as Debezium change events, so that the consumers of Debezium topics
+/// can read them as if Pathway was a database. An insertion becomes a create event (`"op": "c"`)
+/// with the row in `after` and a deletion becomes a delete event (`"op": "d"`) with the row in
+/// `before`, so an update is a delete event followed by a create event.
+///
+/// The event is wrapped in the envelope of the Kafka Connect JSON converter with an empty
+/// schema, which the converter accepts with and without `schemas.enable`.
+pub struct DebeziumFormatter {
+    value_field_names: Vec<String>,
+}
+
+impl DebeziumFormatter {
+    pub fn new(value_field_names: Vec<String>) -> DebeziumFormatter {
+        DebeziumFormatter { value_field_names }
+    }
+
+    fn row_to_json(&self, values: &[Value]) -> Result<JsonValue, FormatterError> {
+        let mut row = JsonMap::with_capacity(self.value_field_names.len());
+        for (name, value) in zip(self.value_field_names.iter(), values) {
+            row.insert(name.clone(), serialize_value_to_json(value)?);
+        }
+        Ok(JsonValue::Object(row))
+    }
+}
+
+impl Formatter for DebeziumFormatter {
+    fn format(
+        &mut self,
+        key: &Key,
+        values: &[Value],
+        time: Timestamp,
+        diff: isize,
+    ) -> Result<FormatterContext, FormatterError> {
+        let row = self.row_to_json(values)?;
+        let (before, after, op) = if diff > 0 {
+            (JsonValue::Null, row, "c")
+        } else {
+            (row, JsonValue::Null, "d")
+        };
+        let event = json!({
+            "schema": null,
+            "payload": {
+                "before": before,
+                "after": after,
+                "op": op,
+                "ts_ms": current_unix_timestamp_ms(),
+                "source": {
+                    "connector": "pathway",
+                    SPECIAL_FIELD_TIME: time,
+                },
+            },
+        });
+
+        Ok(FormatterContext::new_single_payload(
+            event.to_string().into_bytes(),
+            *key,
+            values.to_vec(),
+            time,
+            diff,
+        ))
+    }
+}
+
 pub struct NullFormatter {}
 
 impl NullFormatter {
@@ -2228,6 +4131,13 @@ fn serialize_value_to_bson(value: &Value) -> Result<BsonValue, FormatterError> {
             }
             Ok(BsonValue::Array(items))
         }
+        Value::Float32Array(a) => {
+            let mut items = Vec::with_capacity(a.len());
+            for item in a.iter() {
+                items.push(bson!(f64::from(*item)));
+            }
+            Ok(BsonValue::Array(items))
+        }
         Value::Bytes(b) => Ok(BsonValue::Binary(BsonBinaryContents {
             subtype: BsonBinarySubtype::Generic,
             bytes: b.to_vec(),
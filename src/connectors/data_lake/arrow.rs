@@ -143,6 +143,10 @@ fn array_of_structs(
                 struct_columns[0].push(convert_shape_to_pathway_tuple(a.shape()));
                 struct_columns[1].push(convert_contents_to_pathway_tuple(a));
             }
+            Value::Float32Array(a) => {
+                struct_columns[0].push(convert_shape_to_pathway_tuple(a.shape()));
+                struct_columns[1].push(convert_contents_to_pathway_tuple(a));
+            }
             Value::Tuple(tuple_elements) => {
                 for (index, field) in tuple_elements.iter().enumerate() {
                     struct_columns[index].push(field.clone());
@@ -235,7 +239,7 @@ fn array_of_lists(
     Ok(list_array)
 }
 
-fn arrow_data_type(
+pub fn arrow_data_type(
     type_: &Type,
     settings: &LakeWriterSettings,
 ) -> Result<ArrowDataType, WriteError> {
@@ -29,7 +29,7 @@ use deltalake::operations::optimize::OptimizeBuilder;
 use deltalake::operations::vacuum::VacuumBuilder;
 use deltalake::operations::vacuum::VacuumMetrics;
 use deltalake::parquet::record::reader::RowIter as ParquetRowIterator;
-use deltalake::parquet::record::Row as ParquetRow;
+use deltalake::parquet::record::{Field as ParquetValue, Row as ParquetRow};
 use deltalake::protocol::SaveMode as DeltaTableSaveMode;
 use deltalake::table::PeekCommit as DeltaLakePeekCommit;
 use deltalake::writer::{DeltaWriter, RecordBatchWriter as DTRecordBatchWriter};
@@ -45,7 +45,7 @@ use super::{
     columns_into_pathway_values, parquet_row_into_values_map, LakeBatchWriter, LakeWriterSettings,
     MaintenanceMode, MetadataPerColumn, PATHWAY_COLUMN_META_FIELD, SPECIAL_OUTPUT_FIELDS,
 };
-use crate::async_runtime::create_async_tokio_runtime;
+use crate::async_runtime::{create_async_tokio_runtime, AsyncRuntime};
 use crate::connectors::data_format::{
     parse_bool_advanced, NDARRAY_ELEMENTS_FIELD_NAME, NDARRAY_SHAPE_FIELD_NAME,
 };
@@ -62,6 +62,7 @@ use crate::engine::time::{DateTime, DateTimeNaive};
 use crate::engine::{Type, Value};
 use crate::persistence::frontier::OffsetAntichain;
 use crate::python_api::{BackfillingThreshold, ValueField};
+use crate::retry::RetryPolicy;
 use crate::timestamp::current_unix_timestamp_ms;
 
 #[derive(Debug)]
@@ -202,6 +203,7 @@ impl fmt::Display for SchemaMismatchDetails {
 
 #[allow(clippy::module_name_repetitions)]
 pub struct DeltaBatchWriter {
+    runtime: AsyncRuntime,
     table: DeltaTable,
     writer: DTRecordBatchWriter,
     metadata_per_column: MetadataPerColumn,
@@ -217,7 +219,9 @@ impl DeltaBatchWriter {
         table_type: MaintenanceMode,
         optimizer_rule: Option<DeltaOptimizerRule>,
     ) -> Result<Self, WriteError> {
+        let runtime = create_async_tokio_runtime()?;
         let (table, metadata_per_column) = Self::open_table(
+            &runtime,
             path,
             value_fields,
             storage_options,
@@ -226,6 +230,7 @@ impl DeltaBatchWriter {
         )?;
         let writer = DTRecordBatchWriter::for_table(&table)?;
         Ok(Self {
+            runtime,
             table,
             writer,
             metadata_per_column,
@@ -234,6 +239,7 @@ impl DeltaBatchWriter {
     }
 
     pub fn open_table(
+        runtime: &TokioRuntime,
         path: &str,
         schema_fields: &Vec<ValueField>,
         storage_options: HashMap<String, String>,
@@ -263,7 +269,6 @@ impl DeltaBatchWriter {
             ));
         }
 
-        let runtime = create_async_tokio_runtime()?;
         let table: DeltaTable = runtime
             .block_on(async {
                 let mut builder = DeltaTableCreateBuilder::new()
@@ -454,7 +459,8 @@ impl LakeBatchWriter for DeltaBatchWriter {
         batch: ArrowRecordBatch,
         payload_type: PayloadType,
     ) -> Result<(), WriteError> {
-        create_async_tokio_runtime()?.block_on(async {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
             self.table.update().await?;
             match payload_type {
                 PayloadType::FullSnapshot => {
@@ -534,7 +540,11 @@ impl ObjectDownloader {
         let obj = match self {
             Self::Local => File::open(path)?,
             Self::S3(bucket) => {
-                let contents = S3Scanner::download_object_from_path_and_bucket(path, bucket)?;
+                let contents = S3Scanner::download_object_from_path_and_bucket(
+                    path,
+                    bucket,
+                    &RetryPolicy::default(),
+                )?;
                 let mut tempfile = tempfile()?;
                 tempfile.write_all(contents.bytes())?;
                 tempfile.flush()?;
@@ -552,6 +562,7 @@ pub struct DeltaReaderAction {
     action_type: DataEventType,
     path: String,
     is_last_in_version: bool,
+    is_change_data: bool,
     partition_values: ValuesMap,
 }
 
@@ -562,6 +573,16 @@ impl DeltaReaderAction {
             path,
             partition_values,
             is_last_in_version: false,
+            is_change_data: false,
+        }
+    }
+
+    /// An action reading a file of the change data feed. The type of the event is given
+    /// for each row separately, in the change type column.
+    pub fn new_change_data(path: String, partition_values: ValuesMap) -> Self {
+        Self {
+            is_change_data: true,
+            ..Self::new(DataEventType::Insert, path, partition_values)
         }
     }
 
@@ -649,6 +670,7 @@ pub fn read_delta_table<S: std::hash::BuildHasher>(
 
 #[allow(clippy::module_name_repetitions)]
 pub struct DeltaTableReader {
+    runtime: AsyncRuntime,
     table: DeltaTable,
     streaming_mode: ConnectorMode,
     column_types: HashMap<String, Type>,
@@ -661,9 +683,12 @@ pub struct DeltaTableReader {
     parquet_files_queue: VecDeque<DeltaReaderAction>,
     current_action: Option<DeltaReaderAction>,
     backfilling_entries_queue: VecDeque<BackfillingEntry>,
+    read_change_data_feed: bool,
 }
 
 const APPEND_ONLY_PROPERTY_NAME: &str = "delta.appendOnly";
+const CHANGE_DATA_FEED_PROPERTY_NAME: &str = "delta.enableChangeDataFeed";
+const CHANGE_TYPE_COLUMN_NAME: &str = "_change_type";
 const DELTA_LAKE_INITIAL_POLL_DURATION: Duration = Duration::from_millis(5);
 const DELTA_LAKE_MAX_POLL_DURATION: Duration = Duration::from_millis(100);
 const DELTA_LAKE_POLL_BACKOFF: u32 = 2;
@@ -679,6 +704,7 @@ impl DeltaTableReader {
         start_from_timestamp_ms: Option<i64>,
         has_primary_key: bool,
         backfilling_thresholds: Vec<BackfillingThreshold>,
+        read_change_data_feed: bool,
     ) -> Result<Self, ReadError> {
         let runtime = create_async_tokio_runtime()?;
         let mut table =
@@ -695,6 +721,16 @@ impl DeltaTableReader {
         if !has_primary_key && !is_append_only {
             return Err(ReadError::PrimaryKeyRequired);
         }
+        if read_change_data_feed {
+            let change_data_feed_property = table_props.get(CHANGE_DATA_FEED_PROPERTY_NAME);
+            let is_change_data_feed_enabled = matches!(
+                change_data_feed_property,
+                Some(Some(property)) if parse_bool_advanced(property).unwrap_or(false)
+            );
+            if !is_change_data_feed_enabled {
+                warn!("The change data feed is not enabled for the Delta table at {path}. The changes will be read from the rewritten data files.");
+            }
+        }
         let mut current_version = table.version();
 
         let mut parquet_files_queue = {
@@ -751,6 +787,7 @@ impl DeltaTableReader {
         }
 
         Ok(Self {
+            runtime,
             table,
             column_types,
             streaming_mode,
@@ -763,6 +800,7 @@ impl DeltaTableReader {
             parquet_files_queue,
             rows_read_within_version: 0,
             current_action: None,
+            read_change_data_feed,
         })
     }
 
@@ -1126,7 +1164,7 @@ impl DeltaTableReader {
     }
 
     fn upgrade_table_version(&mut self, is_polling_enabled: bool) -> Result<(), ReadError> {
-        let runtime = create_async_tokio_runtime()?;
+        let runtime = self.runtime.clone();
         runtime.block_on(async {
             self.parquet_files_queue.clear();
             let mut sleep_duration = DELTA_LAKE_INITIAL_POLL_DURATION;
@@ -1150,6 +1188,7 @@ impl DeltaTableReader {
                 };
 
                 let mut added_blocks = VecDeque::new();
+                let mut change_data_blocks = VecDeque::new();
                 let mut data_changed = false;
                 for action in txn_actions {
                     // Protocol description for Delta Lake actions:
@@ -1184,10 +1223,28 @@ impl DeltaTableReader {
                                 partition_values,
                             )
                         }
+                        DeltaLakeAction::Cdc(action) if self.read_change_data_feed => {
+                            let action_path = self.ensure_absolute_path(&action.path);
+                            let partition_values = Self::parse_partition_values(
+                                &action.partition_values,
+                                &self.column_types,
+                            );
+                            change_data_blocks.push_back(DeltaReaderAction::new_change_data(
+                                action_path,
+                                partition_values,
+                            ));
+                            continue;
+                        }
                         _ => continue,
                     };
                     added_blocks.push_back(action);
                 }
+                // When a commit comes with the change data files, they contain exactly the
+                // changed rows, while the data files may have been rewritten in full
+                if !change_data_blocks.is_empty() {
+                    data_changed = true;
+                    added_blocks = change_data_blocks;
+                }
 
                 self.current_version = next_version;
                 self.rows_read_within_version = 0;
@@ -1203,6 +1260,36 @@ impl DeltaTableReader {
         })
     }
 
+    /// Returns the type of the event a row is read as, or `None` if the row is to be skipped.
+    fn row_event_type(&self, parquet_row: &ParquetRow) -> Option<DataEventType> {
+        let current_action = self
+            .current_action
+            .as_ref()
+            .expect("current action must be set if there's a reader");
+        if !current_action.is_change_data {
+            return Some(current_action.action_type);
+        }
+        let change_type = parquet_row
+            .get_column_iter()
+            .find_map(|(name, value)| (name == CHANGE_TYPE_COLUMN_NAME).then_some(value));
+        match change_type {
+            Some(ParquetValue::Str(change_type)) => match change_type.as_str() {
+                "insert" | "update_postimage" => Some(DataEventType::Insert),
+                "delete" => Some(DataEventType::Delete),
+                // In the upsert session, the post-image alone replaces the previous row
+                "update_preimage" => None,
+                other => {
+                    warn!("Unknown change type in the Delta table change data feed: {other:?}");
+                    None
+                }
+            },
+            other => {
+                warn!("Unexpected change type in the Delta table change data feed: {other:?}");
+                None
+            }
+        }
+    }
+
     fn read_next_row_native(
         &mut self,
         is_polling_enabled: bool,
@@ -1248,12 +1335,14 @@ impl DeltaTableReader {
 
 impl Reader for DeltaTableReader {
     fn read(&mut self) -> Result<ReadResult, ReadError> {
-        let mut row_map = if let Some(maybe_row_map) = self.backfilling_entries_queue.pop_front() {
-            match maybe_row_map {
-                BackfillingEntry::SourceEvent(event) => return Ok(event),
-                BackfillingEntry::Entry(entry) => entry,
+        let (mut row_map, event_type) = loop {
+            if let Some(maybe_row_map) = self.backfilling_entries_queue.pop_front() {
+                match maybe_row_map {
+                    BackfillingEntry::SourceEvent(event) => return Ok(event),
+                    // Backfilling thresholds only imply insertions
+                    BackfillingEntry::Entry(entry) => break (entry, DataEventType::Insert),
+                }
             }
-        } else {
             let parquet_row =
                 match self.read_next_row_native(self.streaming_mode.is_polling_enabled()) {
                     Ok(ParquetReaderOutcome::Row(row)) => row,
@@ -1261,7 +1350,12 @@ impl Reader for DeltaTableReader {
                     Err(ReadError::NoObjectsToRead) => return Ok(ReadResult::Finished),
                     Err(other) => return Err(other),
                 };
-            parquet_row_into_values_map(&parquet_row, &self.column_types)
+            if let Some(event_type) = self.row_event_type(&parquet_row) {
+                break (
+                    parquet_row_into_values_map(&parquet_row, &self.column_types),
+                    event_type,
+                );
+            }
         };
         if let Some(current_action) = self.current_action.as_ref() {
             row_map.merge(&current_action.partition_values);
@@ -1269,14 +1363,7 @@ impl Reader for DeltaTableReader {
 
         self.rows_read_within_version += 1;
         Ok(ReadResult::Data(
-            ReaderContext::from_diff(
-                self.current_action
-                    .as_ref()
-                    // If there's no current action, backfilling thresholds were used. They only imply insertions
-                    .map_or(DataEventType::Insert, |action| action.action_type),
-                None,
-                row_map,
-            ),
+            ReaderContext::from_diff(event_type, None, row_map),
             (
                 OffsetKey::Empty,
                 OffsetValue::DeltaTablePosition {
@@ -1300,7 +1387,7 @@ impl Reader for DeltaTableReader {
         };
 
         self.reader = None;
-        let runtime = create_async_tokio_runtime()?;
+        let runtime = self.runtime.clone();
 
         // The last saved offset corresponds to the last version that has been read in full
         self.current_version = *version;
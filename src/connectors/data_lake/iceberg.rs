@@ -21,15 +21,16 @@ use iceberg::writer::file_writer::location_generator::{
 };
 use iceberg::writer::file_writer::ParquetWriterBuilder;
 use iceberg::writer::{IcebergWriter, IcebergWriterBuilder};
-use iceberg::Error as IcebergError;
 use iceberg::{Catalog, Namespace, NamespaceIdent, TableCreation, TableIdent};
+use iceberg::{Error as IcebergError, ErrorKind as IcebergErrorKind};
+use iceberg_catalog_glue::{GlueCatalog, GlueCatalogConfig};
 use iceberg_catalog_rest::{RestCatalog, RestCatalogConfig};
 use tokio::runtime::Runtime as TokioRuntime;
 
 use super::{
     columns_into_pathway_values, LakeBatchWriter, LakeWriterSettings, SPECIAL_OUTPUT_FIELDS,
 };
-use crate::async_runtime::create_async_tokio_runtime;
+use crate::async_runtime::{create_async_tokio_runtime, AsyncRuntime};
 use crate::connectors::data_format::NDARRAY_SINGLE_ELEMENT_FIELD_NAME;
 use crate::connectors::data_lake::buffering::PayloadType;
 use crate::connectors::data_storage::ConnectorMode;
@@ -43,10 +44,85 @@ use crate::persistence::frontier::OffsetAntichain;
 use crate::python_api::ValueField;
 use crate::timestamp::current_unix_timestamp_ms;
 
+/// The kind of the catalog the Iceberg tables are registered in.
+#[derive(Clone, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum IcebergCatalogKind {
+    /// A REST catalog. The authentication, such as an OAuth2 `credential` or a bearer `token`,
+    /// is configured with the catalog properties.
+    Rest { uri: String },
+    /// An AWS Glue catalog. The region and the credentials are taken from the catalog
+    /// properties, or from the environment if not specified there.
+    Glue {
+        catalog_id: Option<String>,
+        endpoint: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum IcebergCatalog {
+    Rest(RestCatalog),
+    Glue(GlueCatalog),
+}
+
+macro_rules! with_catalog {
+    ($self:ident, $catalog:ident => $body:expr) => {
+        match $self {
+            IcebergCatalog::Rest($catalog) => $body,
+            IcebergCatalog::Glue($catalog) => $body,
+        }
+    };
+}
+
+impl IcebergCatalog {
+    async fn get_namespace(&self, ident: &NamespaceIdent) -> Result<Namespace, IcebergError> {
+        with_catalog!(self, catalog => catalog.get_namespace(ident).await)
+    }
+
+    async fn create_namespace(
+        &self,
+        ident: &NamespaceIdent,
+        properties: HashMap<String, String>,
+    ) -> Result<Namespace, IcebergError> {
+        with_catalog!(self, catalog => catalog.create_namespace(ident, properties).await)
+    }
+
+    async fn list_namespaces(
+        &self,
+        parent: Option<&NamespaceIdent>,
+    ) -> Result<Vec<NamespaceIdent>, IcebergError> {
+        with_catalog!(self, catalog => catalog.list_namespaces(parent).await)
+    }
+
+    async fn list_tables(
+        &self,
+        namespace: &NamespaceIdent,
+    ) -> Result<Vec<TableIdent>, IcebergError> {
+        with_catalog!(self, catalog => catalog.list_tables(namespace).await)
+    }
+
+    async fn load_table(&self, table: &TableIdent) -> Result<IcebergTable, IcebergError> {
+        with_catalog!(self, catalog => catalog.load_table(table).await)
+    }
+
+    async fn create_table(
+        &self,
+        namespace: &NamespaceIdent,
+        creation: TableCreation,
+    ) -> Result<IcebergTable, IcebergError> {
+        with_catalog!(self, catalog => catalog.create_table(namespace, creation).await)
+    }
+
+    async fn commit(&self, transaction: Transaction<'_>) -> Result<IcebergTable, IcebergError> {
+        with_catalog!(self, catalog => transaction.commit(catalog).await)
+    }
+}
+
 #[derive(Clone)]
 #[allow(clippy::module_name_repetitions)]
 pub struct IcebergDBParams {
-    uri: String,
+    catalog: IcebergCatalogKind,
     warehouse: Option<String>,
     namespace: Vec<String>,
     props: HashMap<String, String>,
@@ -54,36 +130,65 @@ pub struct IcebergDBParams {
 
 impl IcebergDBParams {
     pub fn new(
-        uri: String,
+        catalog: IcebergCatalogKind,
         warehouse: Option<String>,
         namespace: Vec<String>,
         props: HashMap<String, String>,
     ) -> Self {
         Self {
-            uri,
+            catalog,
             warehouse,
             namespace,
             props,
         }
     }
 
-    pub fn create_catalog(&self) -> RestCatalog {
-        let config_builder = RestCatalogConfig::builder().uri(self.uri.clone());
-        let config = if let Some(warehouse) = &self.warehouse {
-            config_builder
-                .warehouse(warehouse.clone())
-                .props(self.props.clone())
-                .build()
-        } else {
-            config_builder.props(self.props.clone()).build()
-        };
-        RestCatalog::new(config)
+    pub fn create_catalog(&self, runtime: &TokioRuntime) -> Result<IcebergCatalog, IcebergError> {
+        match &self.catalog {
+            IcebergCatalogKind::Rest { uri } => {
+                let config_builder = RestCatalogConfig::builder().uri(uri.clone());
+                let config = if let Some(warehouse) = &self.warehouse {
+                    config_builder
+                        .warehouse(warehouse.clone())
+                        .props(self.props.clone())
+                        .build()
+                } else {
+                    config_builder.props(self.props.clone()).build()
+                };
+                Ok(IcebergCatalog::Rest(RestCatalog::new(config)))
+            }
+            IcebergCatalogKind::Glue {
+                catalog_id,
+                endpoint,
+            } => {
+                let Some(warehouse) = &self.warehouse else {
+                    return Err(IcebergError::new(
+                        IcebergErrorKind::DataInvalid,
+                        "warehouse must be specified for the Glue catalog",
+                    ));
+                };
+                let config = GlueCatalogConfig::builder()
+                    .warehouse(warehouse.clone())
+                    .props(self.props.clone());
+                let config = match (catalog_id, endpoint) {
+                    (Some(catalog_id), Some(endpoint)) => config
+                        .catalog_id(catalog_id.clone())
+                        .uri(endpoint.clone())
+                        .build(),
+                    (Some(catalog_id), None) => config.catalog_id(catalog_id.clone()).build(),
+                    (None, Some(endpoint)) => config.uri(endpoint.clone()).build(),
+                    (None, None) => config.build(),
+                };
+                let catalog = runtime.block_on(GlueCatalog::new(config))?;
+                Ok(IcebergCatalog::Glue(catalog))
+            }
+        }
     }
 
     pub fn ensure_namespace(
         &self,
         runtime: &TokioRuntime,
-        catalog: &RestCatalog,
+        catalog: &IcebergCatalog,
     ) -> Result<Namespace, IcebergError> {
         let ident = NamespaceIdent::from_strs(self.namespace.clone())?;
         runtime.block_on(async {
@@ -98,6 +203,31 @@ impl IcebergDBParams {
                 .await
         })
     }
+
+    /// Lists the namespaces nested in the namespace of the parameters, or the top-level ones
+    /// if the namespace is empty.
+    pub fn list_namespaces(&self) -> Result<Vec<Vec<String>>, IcebergError> {
+        let runtime = create_async_tokio_runtime()
+            .map_err(|e| IcebergError::new(IcebergErrorKind::Unexpected, e.to_string()))?;
+        let catalog = self.create_catalog(&runtime)?;
+        let parent = if self.namespace.is_empty() {
+            None
+        } else {
+            Some(NamespaceIdent::from_strs(self.namespace.clone())?)
+        };
+        let namespaces = runtime.block_on(catalog.list_namespaces(parent.as_ref()))?;
+        Ok(namespaces.into_iter().map(NamespaceIdent::inner).collect())
+    }
+
+    /// Lists the names of the tables in the namespace of the parameters.
+    pub fn list_tables(&self) -> Result<Vec<String>, IcebergError> {
+        let runtime = create_async_tokio_runtime()
+            .map_err(|e| IcebergError::new(IcebergErrorKind::Unexpected, e.to_string()))?;
+        let catalog = self.create_catalog(&runtime)?;
+        let namespace = NamespaceIdent::from_strs(self.namespace.clone())?;
+        let tables = runtime.block_on(catalog.list_tables(&namespace))?;
+        Ok(tables.into_iter().map(|table| table.name).collect())
+    }
 }
 
 #[derive(Clone)]
@@ -116,7 +246,7 @@ impl IcebergTableParams {
     pub fn ensure_table(
         &self,
         runtime: &TokioRuntime,
-        catalog: &RestCatalog,
+        catalog: &IcebergCatalog,
         namespace: &Namespace,
         warehouse: Option<&String>,
     ) -> Result<IcebergTable, WriteError> {
@@ -208,8 +338,8 @@ impl IcebergTableParams {
 
 #[allow(clippy::module_name_repetitions)]
 pub struct IcebergBatchWriter {
-    runtime: TokioRuntime,
-    catalog: RestCatalog,
+    runtime: AsyncRuntime,
+    catalog: IcebergCatalog,
     table: IcebergTable,
     table_ident: TableIdent,
 }
@@ -220,7 +350,7 @@ impl IcebergBatchWriter {
         table_params: &IcebergTableParams,
     ) -> Result<Self, WriteError> {
         let runtime = create_async_tokio_runtime()?;
-        let catalog = db_params.create_catalog();
+        let catalog = db_params.create_catalog(&runtime)?;
         let namespace = db_params.ensure_namespace(&runtime, &catalog)?;
         let table = table_params.ensure_table(
             &runtime,
@@ -281,7 +411,7 @@ impl LakeBatchWriter for IcebergBatchWriter {
             let mut append_action = tx.fast_append(None, vec![])?;
             append_action.add_data_files(data_file.clone())?;
             let tx = append_action.apply().await?;
-            let _ = tx.commit(&self.catalog).await?;
+            let _ = self.catalog.commit(tx).await?;
 
             self.table = self.catalog.load_table(&self.table_ident).await?;
 
@@ -328,12 +458,12 @@ pub type IcebergSnapshotId = i64;
 
 #[allow(clippy::module_name_repetitions)]
 pub struct IcebergReader {
-    catalog: RestCatalog,
+    catalog: IcebergCatalog,
     table_ident: TableIdent,
     column_types: HashMap<String, Type>,
     streaming_mode: ConnectorMode,
 
-    runtime: TokioRuntime,
+    runtime: AsyncRuntime,
     current_table_plan: HashMap<FileScanTaskDescriptor, FileScanTask>,
     current_snapshot_id: Option<IcebergSnapshotId>,
     diff_queue: VecDeque<ReadResult>,
@@ -350,7 +480,7 @@ impl IcebergReader {
         streaming_mode: ConnectorMode,
     ) -> Result<Self, ReadError> {
         let runtime = create_async_tokio_runtime()?;
-        let catalog = db_params.create_catalog();
+        let catalog = db_params.create_catalog(&runtime)?;
         let namespace = db_params.ensure_namespace(&runtime, &catalog)?;
         let table_ident = TableIdent::new(namespace.name().clone(), table_params.name.clone());
 
@@ -134,6 +134,15 @@ pub fn parquet_value_into_pathway_value(
         (ParquetValue::Long(i), Type::Duration) => Some(Value::from(
             EngineDuration::new_with_unit(*i, "us").unwrap(),
         )),
+        (ParquetValue::Byte(i), Type::Int | Type::Any) => Some(Value::from(i64::from(*i))),
+        (ParquetValue::Short(i), Type::Int | Type::Any) => Some(Value::from(i64::from(*i))),
+        (ParquetValue::Int(i), Type::Int | Type::Any) => Some(Value::from(i64::from(*i))),
+        (ParquetValue::UByte(i), Type::Int | Type::Any) => Some(Value::from(i64::from(*i))),
+        (ParquetValue::UShort(i), Type::Int | Type::Any) => Some(Value::from(i64::from(*i))),
+        (ParquetValue::UInt(i), Type::Int | Type::Any) => Some(Value::from(i64::from(*i))),
+        (ParquetValue::Float(f), Type::Float | Type::Any) => {
+            Some(Value::Float(f64::from(*f).into()))
+        }
         (ParquetValue::Double(f), Type::Float | Type::Any) => Some(Value::Float((*f).into())),
         (ParquetValue::Str(s), Type::String | Type::Any) => Some(Value::String(s.into())),
         (ParquetValue::Str(s), Type::Pointer) => parse_pathway_pointer(s).ok(),
@@ -146,6 +155,12 @@ pub fn parquet_value_into_pathway_value(
         (ParquetValue::TimestampMicros(us), Type::DateTimeUtc) => {
             Some(Value::from(DateTimeUtc::from_timestamp(*us, "us").unwrap()))
         }
+        (ParquetValue::TimestampMillis(ms), Type::DateTimeNaive | Type::Any) => Some(Value::from(
+            DateTimeNaive::from_timestamp(*ms, "ms").unwrap(),
+        )),
+        (ParquetValue::TimestampMillis(ms), Type::DateTimeUtc) => {
+            Some(Value::from(DateTimeUtc::from_timestamp(*ms, "ms").unwrap()))
+        }
         (ParquetValue::Bytes(b), Type::Bytes | Type::Any) => Some(Value::Bytes(b.data().into())),
         (ParquetValue::Bytes(b), Type::PyObjectWrapper) => {
             bincode::deserialize::<Value>(b.data()).ok()
@@ -21,7 +21,7 @@ use std::mem::take;
 use std::str::{from_utf8, Utf8Error};
 use std::sync::Arc;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use arcstr::ArcStr;
 use aws_sdk_dynamodb::error::BuildError as DynamoDBBuildError;
@@ -33,6 +33,7 @@ use deltalake::parquet::errors::ParquetError;
 use deltalake::DeltaTableError;
 use futures::StreamExt;
 use iceberg::Error as IcebergError;
+use imap::error::Error as ImapError;
 use itertools::Itertools;
 use log::{error, info, warn};
 use postgres::types::ToSql;
@@ -46,26 +47,34 @@ use rumqttc::{
     Connection as MqttConnection, ConnectionError as MqttConnectionError, Event as MqttEvent,
     Incoming as MqttIncoming, Outgoing as MqttOutgoing, Packet as MqttPacket,
 };
-use tokio::runtime::Runtime as TokioRuntime;
 
-use crate::async_runtime::create_async_tokio_runtime;
+use crate::async_runtime::AsyncRuntime;
 use crate::connectors::aws::dynamodb::AwsRequestError;
+use crate::connectors::backfill::BackfillThenStreamReader;
 use crate::connectors::data_format::{
     create_bincoded_value, serialize_value_to_json, FormatterContext, FormatterError,
     COMMIT_LITERAL,
 };
 use crate::connectors::data_lake::buffering::IncorrectSnapshotError;
+use crate::connectors::lag_throttling::{LagThrottler, LagThrottlingConfig};
 use crate::connectors::metadata::{KafkaMetadata, SQLiteMetadata, SourceMetadata};
+use crate::connectors::monitoring::BackfillProgressTracker;
 use crate::connectors::offset::EMPTY_OFFSET;
 use crate::connectors::posix_like::PosixLikeReader;
-use crate::connectors::scanner::s3::S3CommandName;
+use crate::connectors::prometheus::PrometheusError;
+use crate::connectors::scanner::s3::{is_retryable_s3_error, S3CommandName};
+use crate::connectors::sdk::ConnectorError;
+use crate::connectors::sql_merge::SqlSinkError;
+use crate::connectors::sql_polling::{SqlPollingError, SqlPollingReader};
+use crate::connectors::subprocess::{SubprocessError, SubprocessReader};
 use crate::connectors::{Offset, OffsetKey, OffsetValue, SPECIAL_FIELD_DIFF, SPECIAL_FIELD_TIME};
 use crate::engine::error::limit_length;
 use crate::engine::error::DynResult;
 use crate::engine::error::STANDARD_OBJECT_LENGTH_LIMIT;
+use crate::engine::reload;
 use crate::engine::time::DateTime;
 use crate::engine::Type;
-use crate::engine::{Key, Value};
+use crate::engine::{Key, Timestamp, Value};
 use crate::persistence::backends::Error as PersistenceBackendError;
 use crate::persistence::frontier::OffsetAntichain;
 use crate::persistence::tracker::WorkerPersistentStorage;
@@ -306,6 +315,18 @@ pub enum ReadError {
     #[error(transparent)]
     Persistence(#[from] PersistenceBackendError),
 
+    #[error(transparent)]
+    Subprocess(#[from] SubprocessError),
+
+    #[error(transparent)]
+    SqlPolling(#[from] SqlPollingError),
+
+    #[error(transparent)]
+    Custom(#[from] ConnectorError),
+
+    #[error("failed to perform IMAP request: {0}")]
+    Imap(#[from] ImapError),
+
     #[error("malformed data")]
     MalformedData,
 
@@ -370,6 +391,10 @@ pub enum StorageType {
     PosixLike,
     Iceberg,
     Mqtt,
+    Subprocess,
+    BackfillThenStream,
+    SqlPolling,
+    Custom,
 }
 
 impl StorageType {
@@ -391,6 +416,14 @@ impl StorageType {
             StorageType::Nats => NatsReader::merge_two_frontiers(lhs, rhs),
             StorageType::Iceberg => IcebergReader::merge_two_frontiers(lhs, rhs),
             StorageType::Mqtt => MqttReader::merge_two_frontiers(lhs, rhs),
+            StorageType::Subprocess => SubprocessReader::merge_two_frontiers(lhs, rhs),
+            StorageType::BackfillThenStream => {
+                BackfillThenStreamReader::merge_two_frontiers(lhs, rhs)
+            }
+            StorageType::SqlPolling => {
+                SqlPollingReader::<PsqlClient>::merge_two_frontiers(lhs, rhs)
+            }
+            StorageType::Custom => PythonReader::merge_two_frontiers(lhs, rhs),
         }
     }
 }
@@ -413,6 +446,9 @@ pub trait Reader {
         Ok(())
     }
 
+    /// Passes the tracker of the backfill progress, for the readers that are able to report it.
+    fn track_backfill_progress(&mut self, _: BackfillProgressTracker) {}
+
     fn merge_two_frontiers(lhs: &OffsetAntichain, rhs: &OffsetAntichain) -> OffsetAntichain
     where
         Self: Sized,
@@ -472,6 +508,16 @@ pub trait Reader {
                             total_entries_read: other_line_idx,
                             ..
                         },
+                    )
+                    | (
+                        OffsetValue::SqlPollingCursor {
+                            total_entries_read: offset_line_idx,
+                            ..
+                        },
+                        OffsetValue::SqlPollingCursor {
+                            total_entries_read: other_line_idx,
+                            ..
+                        },
                     ) => {
                         if other_line_idx > offset_line_idx {
                             result.advance_offset(offset_key.clone(), other_value.clone());
@@ -575,6 +621,9 @@ pub enum WriteError {
     #[error(transparent)]
     Arrow(#[from] ArrowError),
 
+    #[error(transparent)]
+    Parquet(#[from] ParquetError),
+
     #[error(transparent)]
     NatsPublish(#[from] NatsPublishError),
 
@@ -605,6 +654,9 @@ pub enum WriteError {
     #[error("value {0} can't be used as a key because it's neither 'bytes' nor 'string'")]
     IncorrectKeyFieldType(Value),
 
+    #[error("value {0} can't be used as a partition because it's not a non-negative 'int'")]
+    IncorrectPartitionFieldType(Value),
+
     #[error("unsupported type: {0:?}")]
     UnsupportedType(Type),
 
@@ -650,6 +702,24 @@ pub enum WriteError {
     #[error(transparent)]
     AwsRequest(#[from] AwsRequestError),
 
+    #[error(transparent)]
+    Subprocess(#[from] SubprocessError),
+
+    #[error(transparent)]
+    Prometheus(#[from] PrometheusError),
+
+    #[error(transparent)]
+    SqlSink(#[from] SqlSinkError),
+
+    #[error(transparent)]
+    Custom(#[from] ConnectorError),
+
+    #[error("failed to perform write in SQL Server: {0}")]
+    SqlServer(#[from] tiberius::error::Error),
+
+    #[error("failed to perform write in Oracle: {0}")]
+    Oracle(#[from] ::oracle::Error),
+
     #[error("after several retried attempts, {0} items haven't been saved")]
     SomeItemsNotDelivered(usize),
 
@@ -657,6 +727,39 @@ pub enum WriteError {
     NotIndexType(Type),
 }
 
+impl WriteError {
+    /// Whether writing the same entry again may succeed. Errors caused by the entry itself or
+    /// by missing permissions are not retried.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Kafka(e) => !matches!(
+                e.rdkafka_error_code(),
+                Some(
+                    RDKafkaErrorCode::InvalidMessage
+                        | RDKafkaErrorCode::InvalidMessageSize
+                        | RDKafkaErrorCode::MessageSizeTooLarge
+                        | RDKafkaErrorCode::TopicAuthorizationFailed
+                        | RDKafkaErrorCode::ClusterAuthorizationFailed
+                )
+            ),
+            Self::S3(_, e) => is_retryable_s3_error(e),
+            Self::Prometheus(e) => e.is_retryable(),
+            Self::Custom(e) => e.is_retryable(),
+            Self::Formatter(_)
+            | Self::QuestDBAtColumnNotTime(_)
+            | Self::TypeMismatchWithSchema(..)
+            | Self::IntOutOfRange(_)
+            | Self::IncorrectKeyFieldType(_)
+            | Self::IncorrectPartitionFieldType(_)
+            | Self::UnsupportedType(_)
+            | Self::SqlSink(_)
+            | Self::DynamicTopicIsNotAString(_)
+            | Self::NotIndexType(_) => false,
+            _ => true,
+        }
+    }
+}
+
 pub trait Writer: Send {
     fn write(&mut self, data: FormatterContext) -> Result<(), WriteError>;
 
@@ -664,6 +767,12 @@ pub trait Writer: Send {
         Ok(())
     }
 
+    /// Called before the flush once all entries with times earlier than `time` have been
+    /// written, or with `None` once all entries have been written.
+    fn on_time_committed(&mut self, _time: Option<Timestamp>) -> Result<(), WriteError> {
+        Ok(())
+    }
+
     fn retriable(&self) -> bool {
         false
     }
@@ -672,6 +781,12 @@ pub trait Writer: Send {
         true
     }
 
+    /// Whether the sink makes the repeated writes of an entry idempotent with the
+    /// [`FormatterContext::idempotency_key`] that the engine then sets.
+    fn uses_idempotency_keys(&self) -> bool {
+        false
+    }
+
     fn name(&self) -> String {
         let short_description: Cow<'static, str> = type_name::<Self>().into();
         short_description.split("::").last().unwrap().to_string()
@@ -771,6 +886,7 @@ pub struct KafkaReader {
     watermarks: Vec<RdkafkaWatermark>,
     deferred_read_result: Option<ReadResult>,
     mode: ConnectorMode,
+    lag_throttler: Option<LagThrottler>,
 }
 
 impl Reader for KafkaReader {
@@ -781,6 +897,14 @@ impl Reader for KafkaReader {
 
         loop {
             let kafka_message = match self.mode {
+                ConnectorMode::Streaming if self.lag_throttler.is_some() => {
+                    self.throttle_lagging_partitions()?;
+                    let poll_timeout = self.lag_throttler.as_ref().map(LagThrottler::poll_timeout);
+                    let Some(kafka_message) = self.consumer.poll(poll_timeout) else {
+                        continue;
+                    };
+                    kafka_message?
+                }
                 ConnectorMode::Streaming => self
                     .consumer
                     .poll(None)
@@ -890,6 +1014,7 @@ impl KafkaReader {
         positions_for_seek: HashMap<i32, KafkaOffset>,
         watermarks: Vec<RdkafkaWatermark>,
         mode: ConnectorMode,
+        lag_throttling: Option<LagThrottlingConfig>,
     ) -> KafkaReader {
         KafkaReader {
             consumer,
@@ -898,9 +1023,75 @@ impl KafkaReader {
             watermarks,
             mode,
             deferred_read_result: None,
+            lag_throttler: lag_throttling.map(LagThrottler::new),
         }
     }
 
+    /// The lags of the assigned partitions, in messages. The partitions still waiting for a
+    /// seek are skipped, as their positions aren't known yet.
+    fn partition_lags(&self) -> Result<HashMap<i32, u64>, ReadError> {
+        let positions = self.consumer.position()?;
+        let mut lags = HashMap::new();
+        for element in positions.elements_for_topic(self.topic.as_str()) {
+            let partition = element.partition();
+            if self.positions_for_seek.contains_key(&partition) {
+                continue;
+            }
+            let (low, high) = self.consumer.fetch_watermarks(
+                self.topic.as_str(),
+                partition,
+                Self::default_timeout(),
+            )?;
+            let position = match element.offset() {
+                KafkaOffset::Offset(offset) => offset,
+                _ => low,
+            };
+            lags.insert(partition, u64::try_from(high - position).unwrap_or(0));
+        }
+        Ok(lags)
+    }
+
+    fn partition_list(&self, partitions: &[i32]) -> TopicPartitionList {
+        let mut tpl = TopicPartitionList::with_capacity(partitions.len());
+        for partition in partitions {
+            tpl.add_partition(self.topic.as_str(), *partition);
+        }
+        tpl
+    }
+
+    fn throttle_lagging_partitions(&mut self) -> Result<(), ReadError> {
+        let now = Instant::now();
+        if !self
+            .lag_throttler
+            .as_ref()
+            .is_some_and(|throttler| throttler.is_check_due(now))
+        {
+            return Ok(());
+        }
+        let lags = self.partition_lags()?;
+        let decision = self
+            .lag_throttler
+            .as_mut()
+            .expect("lag throttler must be present")
+            .on_lags_checked(now, &lags);
+        // The assignment may have changed since the lags were checked, so the failures are
+        // only logged
+        if !decision.pause.is_empty() {
+            if let Err(e) = self.consumer.pause(&self.partition_list(&decision.pause)) {
+                warn!("Failed to pause Kafka partitions {:?}: {e}", decision.pause);
+            }
+        }
+        if !decision.resume.is_empty() {
+            if let Err(e) = self.consumer.resume(&self.partition_list(&decision.resume)) {
+                warn!(
+                    "Failed to resume Kafka partitions {:?}: {e}",
+                    decision.resume
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn poll_duration_for_static_mode() -> Duration {
         Duration::from_millis(500)
     }
@@ -1493,6 +1684,15 @@ mod to_sql {
                     }
                     "float array"
                 }
+                Self::Float32Array(a) => {
+                    if a.ndim() == 1 {
+                        let v: Vec<f32> = a.iter().copied().collect();
+                        let v_16: Vec<f16> = v.iter().map(|e| f16::from_f32(*e)).collect();
+                        try_forward!(Vector, Vector::from(v));
+                        try_forward!(HalfVector, HalfVector::from(v_16));
+                    }
+                    "float32 array"
+                }
                 Self::DateTimeNaive(dt) => {
                     try_forward!(NaiveDateTime, dt.as_chrono_datetime());
                     "naive date/time"
@@ -1533,8 +1733,8 @@ mod to_sql {
 impl Writer for PsqlWriter {
     fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
         self.buffer.push(data);
-        if let Some(max_batch_size) = self.max_batch_size {
-            if self.buffer.len() == max_batch_size {
+        if let Some(max_batch_size) = reload::max_batch_size(self.max_batch_size) {
+            if self.buffer.len() >= max_batch_size {
                 self.flush(true)?;
             }
         }
@@ -1586,6 +1786,7 @@ pub struct KafkaWriter {
     topic: MessageQueueTopic,
     header_fields: Vec<(String, usize)>,
     key_field_index: Option<usize>,
+    partition_field_index: Option<usize>,
 }
 
 impl KafkaWriter {
@@ -1594,12 +1795,32 @@ impl KafkaWriter {
         topic: MessageQueueTopic,
         header_fields: Vec<(String, usize)>,
         key_field_index: Option<usize>,
+        partition_field_index: Option<usize>,
     ) -> KafkaWriter {
         KafkaWriter {
             producer,
             topic,
             header_fields,
             key_field_index,
+            partition_field_index,
+        }
+    }
+
+    /// Returns the partition the message must be sent to. If there is none, the
+    /// partitioner configured for the producer chooses it, based on the message key.
+    fn target_partition(&self, values: &[Value]) -> Result<Option<i32>, WriteError> {
+        let Some(index) = self.partition_field_index else {
+            return Ok(None);
+        };
+        match &values[index] {
+            Value::None => Ok(None),
+            Value::Int(partition) => match i32::try_from(*partition) {
+                Ok(partition) if partition >= 0 => Ok(Some(partition)),
+                _ => Err(WriteError::IncorrectPartitionFieldType(
+                    values[index].clone(),
+                )),
+            },
+            other => Err(WriteError::IncorrectPartitionFieldType(other.clone())),
         }
     }
 }
@@ -1626,6 +1847,7 @@ impl Writer for KafkaWriter {
         };
 
         let headers = data.construct_kafka_headers(&self.header_fields);
+        let partition = self.target_partition(&data.values)?;
         for payload in data.payloads {
             let payload = payload.into_raw_bytes()?;
             let effective_topic = self.topic.get_for_posting(&data.values)?;
@@ -1633,6 +1855,9 @@ impl Writer for KafkaWriter {
                 .payload(&payload)
                 .headers(headers.clone())
                 .key(&key_as_bytes);
+            if let Some(partition) = partition {
+                entry = entry.partition(partition);
+            }
             loop {
                 match self.producer.send(entry) {
                     Ok(()) => break,
@@ -1661,9 +1886,14 @@ impl Writer for KafkaWriter {
     fn single_threaded(&self) -> bool {
         false
     }
+
+    fn uses_idempotency_keys(&self) -> bool {
+        true
+    }
 }
 
 pub struct ElasticSearchWriter {
+    runtime: AsyncRuntime,
     client: Elasticsearch,
     index_name: String,
     max_batch_size: Option<usize>,
@@ -1672,8 +1902,14 @@ pub struct ElasticSearchWriter {
 }
 
 impl ElasticSearchWriter {
-    pub fn new(client: Elasticsearch, index_name: String, max_batch_size: Option<usize>) -> Self {
+    pub fn new(
+        runtime: AsyncRuntime,
+        client: Elasticsearch,
+        index_name: String,
+        max_batch_size: Option<usize>,
+    ) -> Self {
         ElasticSearchWriter {
+            runtime,
             client,
             index_name,
             max_batch_size,
@@ -1684,12 +1920,22 @@ impl ElasticSearchWriter {
 
 impl Writer for ElasticSearchWriter {
     fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
-        for payload in data.payloads {
-            self.docs_buffer.push(b"{\"index\": {}}".to_vec());
+        for (index, payload) in data.payloads.into_iter().enumerate() {
+            // Writing a document with the same id again overwrites it instead of duplicating it
+            let action = match data.idempotency_key {
+                Some(idempotency_key) if index == 0 => {
+                    format!("{{\"index\": {{\"_id\": \"{idempotency_key}\"}}}}")
+                }
+                Some(idempotency_key) => {
+                    format!("{{\"index\": {{\"_id\": \"{idempotency_key}-{index}\"}}}}")
+                }
+                None => "{\"index\": {}}".to_string(),
+            };
+            self.docs_buffer.push(action.into_bytes());
             self.docs_buffer.push(payload.into_raw_bytes()?);
         }
 
-        if let Some(max_batch_size) = self.max_batch_size {
+        if let Some(max_batch_size) = reload::max_batch_size(self.max_batch_size) {
             if self.docs_buffer.len() / 2 >= max_batch_size {
                 self.flush(true)?;
             }
@@ -1702,7 +1948,7 @@ impl Writer for ElasticSearchWriter {
         if self.docs_buffer.is_empty() {
             return Ok(());
         }
-        create_async_tokio_runtime()?.block_on(async {
+        self.runtime.block_on(async {
             self.client
                 .bulk(BulkParts::Index(&self.index_name))
                 .body(take(&mut self.docs_buffer))
@@ -1723,6 +1969,10 @@ impl Writer for ElasticSearchWriter {
     fn single_threaded(&self) -> bool {
         false
     }
+
+    fn uses_idempotency_keys(&self) -> bool {
+        true
+    }
 }
 
 #[derive(Default, Debug)]
@@ -1966,7 +2216,7 @@ impl Writer for MongoWriter {
         for payload in data.payloads {
             self.buffer.push(payload.into_bson_document()?);
         }
-        if let Some(max_batch_size) = self.max_batch_size {
+        if let Some(max_batch_size) = reload::max_batch_size(self.max_batch_size) {
             if self.buffer.len() >= max_batch_size {
                 self.flush(true)?;
             }
@@ -1989,7 +2239,7 @@ impl Writer for MongoWriter {
 }
 
 pub struct NatsReader {
-    runtime: TokioRuntime,
+    runtime: AsyncRuntime,
     subscriber: NatsSubscriber,
     worker_index: usize,
     total_entries_read: usize,
@@ -2043,7 +2293,7 @@ impl Reader for NatsReader {
 
 impl NatsReader {
     pub fn new(
-        runtime: TokioRuntime,
+        runtime: AsyncRuntime,
         subscriber: NatsSubscriber,
         worker_index: usize,
         stream_name: String,
@@ -2059,7 +2309,7 @@ impl NatsReader {
 }
 
 pub struct NatsWriter {
-    runtime: TokioRuntime,
+    runtime: AsyncRuntime,
     client: NatsClient,
     topic: MessageQueueTopic,
     header_fields: Vec<(String, usize)>,
@@ -2107,6 +2357,10 @@ impl Writer for NatsWriter {
     fn single_threaded(&self) -> bool {
         false
     }
+
+    fn uses_idempotency_keys(&self) -> bool {
+        true
+    }
 }
 
 impl Drop for NatsWriter {
@@ -2117,7 +2371,7 @@ impl Drop for NatsWriter {
 
 impl NatsWriter {
     pub fn new(
-        runtime: TokioRuntime,
+        runtime: AsyncRuntime,
         client: NatsClient,
         topic: MessageQueueTopic,
         header_fields: Vec<(String, usize)>,
@@ -2427,7 +2681,10 @@ impl Writer for QuestDBWriter {
                     let encoded = base64::engine::general_purpose::STANDARD.encode(b);
                     self.buffer.column_str(column_name.as_str(), encoded)?
                 }
-                Value::IntArray(_) | Value::FloatArray(_) | Value::Tuple(_) => {
+                Value::IntArray(_)
+                | Value::FloatArray(_)
+                | Value::Float32Array(_)
+                | Value::Tuple(_) => {
                     let json_value = serialize_value_to_json(&value)?;
                     self.buffer
                         .column_str(column_name.as_str(), json_value.to_string())?
@@ -1,7 +1,7 @@
 // Copyright © 2024 Pathway
 
 use std::io::BufReader;
-use std::io::Read;
+use std::io::{BufRead, Read};
 use std::mem::take;
 
 use csv::Reader as CsvReader;
@@ -12,6 +12,8 @@ use crate::connectors::{DataEventType, ReadError, ReaderContext};
 
 type TokenizedEntry = (ReaderContext, u64); // The second value is a position of the record within the object read
 
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
 pub trait Tokenize: Send + 'static {
     fn set_new_reader(
         &mut self,
@@ -23,8 +25,10 @@ pub trait Tokenize: Send + 'static {
 
 pub struct CsvTokenizer {
     parser_builder: CsvReaderBuilder,
+    strip_bom: bool,
     current_event_type: DataEventType,
     csv_reader: Option<CsvReader<Box<dyn Read + Send + 'static>>>,
+    bom_length: u64,
     deferred_next_entry: Option<TokenizedEntry>,
 }
 
@@ -32,11 +36,21 @@ impl CsvTokenizer {
     pub fn new(parser_builder: CsvReaderBuilder) -> Self {
         Self {
             parser_builder,
+            strip_bom: true,
             current_event_type: DataEventType::Insert,
             csv_reader: None,
+            bom_length: 0,
             deferred_next_entry: None,
         }
     }
+
+    /// Whether the UTF-8 byte order mark at the beginning of an object is skipped. Otherwise,
+    /// it becomes a part of the first field, usually the name of the first column.
+    #[must_use]
+    pub fn with_bom_stripped(mut self, strip_bom: bool) -> Self {
+        self.strip_bom = strip_bom;
+        self
+    }
 }
 
 impl Tokenize for CsvTokenizer {
@@ -45,6 +59,13 @@ impl Tokenize for CsvTokenizer {
         source: Box<dyn Read + Send + 'static>,
         data_event_type: DataEventType,
     ) -> Result<(), ReadError> {
+        let mut source = BufReader::new(source);
+        self.bom_length = 0;
+        if self.strip_bom && source.fill_buf()?.starts_with(UTF8_BOM) {
+            source.consume(UTF8_BOM.len());
+            self.bom_length = UTF8_BOM.len() as u64;
+        }
+        let source: Box<dyn Read + Send + 'static> = Box::new(source);
         self.csv_reader = Some(self.parser_builder.flexible(true).from_reader(source));
         self.current_event_type = data_event_type;
         Ok(())
@@ -66,7 +87,7 @@ impl Tokenize for CsvTokenizer {
                             .map(std::string::ToString::to_string)
                             .collect(),
                     ),
-                    csv_reader.position().byte(),
+                    csv_reader.position().byte() + self.bom_length,
                 )))
             } else {
                 Ok(None)
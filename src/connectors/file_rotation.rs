@@ -0,0 +1,383 @@
+// Copyright © 2024 Pathway
+
+//! Writing the output of the plain file sink into a sequence of files instead of a single one.
+//!
+//! A new file is started once the current one reaches the size limit or once the rotation
+//! period, aligned to the Unix epoch, is over. The files are named after a template and are
+//! written under temporary names, so that the readers of the directory never see a partially
+//! written file: a file gets its final name only when it is complete. Optionally, once all the
+//! files of a rotation period are complete, a `_SUCCESS` marker is placed next to them.
+//!
+//! If the template puts the files into subdirectories of the output directory, the
+//! subdirectories are written as [`StagedDirectory`]s, published when the rotation period is
+//! over, so that their readers never see a period partially written either.
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, rename, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use log::info;
+
+use crate::connectors::data_format::FormatterContext;
+use crate::connectors::data_storage::{WriteError, Writer};
+use crate::engine::Timestamp;
+use crate::fs_helpers::StagedDirectory;
+
+pub const DEFAULT_NAME_TEMPLATE: &str = "{table}-{date}-{part}.{ext}";
+pub const SUCCESS_MARKER_NAME: &str = "_SUCCESS";
+
+const IN_PROGRESS_SUFFIX: &str = ".inprogress";
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum NameTemplateError {
+    #[error("unknown placeholder {{{0}}} in the file name template, expected one of {{table}}, {{date}}, {{time}}, {{part}} or {{ext}}")]
+    UnknownPlaceholder(String),
+
+    #[error("unclosed placeholder in the file name template {0:?}")]
+    UnclosedPlaceholder(String),
+
+    #[error("the file name template {0:?} must contain the {{part}} placeholder")]
+    MissingPart(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TemplateSegment {
+    Literal(String),
+    Table,
+    Date,
+    Time,
+    Part,
+    Extension,
+}
+
+/// The template of the names of the files, relative to the output directory. It may contain
+/// the `{table}`, `{date}`, `{time}`, `{part}` and `{ext}` placeholders, the last but one being
+/// mandatory, as it makes the names unique. A template may contain slashes, so that e.g. each
+/// day has its own subdirectory.
+#[derive(Clone, Debug)]
+pub struct NameTemplate {
+    segments: Vec<TemplateSegment>,
+}
+
+impl NameTemplate {
+    pub fn parse(template: &str) -> Result<Self, NameTemplateError> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(TemplateSegment::Literal(rest[..start].to_string()));
+            }
+            let Some(length) = rest[start..].find('}') else {
+                return Err(NameTemplateError::UnclosedPlaceholder(template.to_string()));
+            };
+            let placeholder = &rest[start + 1..start + length];
+            segments.push(match placeholder {
+                "table" => TemplateSegment::Table,
+                "date" => TemplateSegment::Date,
+                "time" => TemplateSegment::Time,
+                "part" => TemplateSegment::Part,
+                "ext" => TemplateSegment::Extension,
+                other => return Err(NameTemplateError::UnknownPlaceholder(other.to_string())),
+            });
+            rest = &rest[start + length + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(TemplateSegment::Literal(rest.to_string()));
+        }
+        if !segments.contains(&TemplateSegment::Part) {
+            return Err(NameTemplateError::MissingPart(template.to_string()));
+        }
+        Ok(Self { segments })
+    }
+
+    fn render(&self, table: &str, extension: &str, time: DateTime<Utc>, part: usize) -> String {
+        let mut name = String::new();
+        for segment in &self.segments {
+            match segment {
+                TemplateSegment::Literal(literal) => name.push_str(literal),
+                TemplateSegment::Table => name.push_str(table),
+                TemplateSegment::Date => name.push_str(&time.format("%Y-%m-%d").to_string()),
+                TemplateSegment::Time => name.push_str(&time.format("%H-%M-%S").to_string()),
+                TemplateSegment::Part => name.push_str(&format!("{part:05}")),
+                TemplateSegment::Extension => name.push_str(extension),
+            }
+        }
+        name
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FileRotationConfig {
+    pub name_template: NameTemplate,
+    /// The value of the `{table}` placeholder.
+    pub table_name: String,
+    /// The value of the `{ext}` placeholder.
+    pub extension: String,
+    /// A file is completed once it has at least this many bytes.
+    pub max_file_size: Option<u64>,
+    /// The length of the rotation periods. Without it, the whole run is a single period.
+    pub rotation_interval: Option<Duration>,
+    pub write_success_markers: bool,
+    /// Whether the first line ever written is a header, which has to be repeated at the
+    /// beginning of every file, as with CSV.
+    pub repeat_header: bool,
+}
+
+/// The naming of the rotated files and the bookkeeping of the rotation periods, shared by the
+/// writers of the rotated files of different formats.
+pub(crate) struct RotationState {
+    directory: PathBuf,
+    config: FileRotationConfig,
+    current_period: Option<DateTime<Utc>>,
+    next_part: usize,
+    /// The subdirectories written in the current period, by their final paths.
+    staged_directories: BTreeMap<PathBuf, StagedDirectory>,
+    /// Whether a file of the current period was completed directly in the output directory.
+    has_unstaged_files: bool,
+}
+
+impl RotationState {
+    pub(crate) fn new(directory: PathBuf, config: FileRotationConfig) -> Result<Self, WriteError> {
+        create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            config,
+            current_period: None,
+            next_part: 0,
+            staged_directories: BTreeMap::new(),
+            has_unstaged_files: false,
+        })
+    }
+
+    pub(crate) fn config(&self) -> &FileRotationConfig {
+        &self.config
+    }
+
+    pub(crate) fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn period_start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let interval = i64::try_from(self.config.rotation_interval?.as_secs())
+            .unwrap_or(i64::MAX)
+            .max(1);
+        let timestamp = now.timestamp();
+        DateTime::from_timestamp(timestamp - timestamp.rem_euclid(interval), 0)
+    }
+
+    fn in_progress_path(path: &Path) -> PathBuf {
+        let mut file_name = path
+            .file_name()
+            .expect("rendered file names are never empty")
+            .to_os_string();
+        file_name.push(IN_PROGRESS_SUFFIX);
+        path.with_file_name(format!(".{}", file_name.to_string_lossy()))
+    }
+
+    /// The path the file is written to before its directory is published. The files placed
+    /// directly in the output directory are written there.
+    fn staged_path(&mut self, path: &Path) -> Result<PathBuf, WriteError> {
+        let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else {
+            return Ok(path.to_path_buf());
+        };
+        if parent == self.directory {
+            return Ok(path.to_path_buf());
+        }
+        let staged_directory = match self.staged_directories.entry(parent.to_path_buf()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(StagedDirectory::extending(parent)?),
+        };
+        Ok(staged_directory.path().join(file_name))
+    }
+
+    /// Picks the path to write the next file to, once it's complete, and the temporary path to
+    /// write it under.
+    pub(crate) fn next_file_paths(
+        &mut self,
+        now: DateTime<Utc>,
+    ) -> Result<(PathBuf, PathBuf), WriteError> {
+        let period = self.period_start(now);
+        self.current_period = period;
+        // The parts that already exist, e.g. from a previous run, are not overwritten
+        let path = loop {
+            let name = self.config.name_template.render(
+                &self.config.table_name,
+                &self.config.extension,
+                period.unwrap_or(now),
+                self.next_part,
+            );
+            self.next_part += 1;
+            let path = self.directory.join(name);
+            if path.exists() {
+                continue;
+            }
+            let path = self.staged_path(&path)?;
+            if !path.exists() {
+                break path;
+            }
+        };
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let in_progress_path = Self::in_progress_path(&path);
+        Ok((path, in_progress_path))
+    }
+
+    /// Records that the file written under the temporary name is complete and moves it to the
+    /// path returned with it by [`Self::next_file_paths`].
+    pub(crate) fn complete_file(
+        &mut self,
+        in_progress_path: &Path,
+        path: &Path,
+    ) -> Result<(), WriteError> {
+        rename(in_progress_path, path)?;
+        if path.parent() == Some(self.directory.as_path()) {
+            info!("Output file {} is complete", path.display());
+            self.has_unstaged_files = true;
+        }
+        Ok(())
+    }
+
+    /// Places the success markers of the current period, if configured, and publishes the
+    /// subdirectories written in it. All the files of the period must be complete at this
+    /// point.
+    pub(crate) fn finish_period(&mut self) -> Result<(), WriteError> {
+        let staged_directories = std::mem::take(&mut self.staged_directories);
+        for (directory, staged_directory) in staged_directories {
+            if self.config.write_success_markers {
+                File::create(staged_directory.path().join(SUCCESS_MARKER_NAME))?;
+            }
+            staged_directory.publish()?;
+            info!("Output directory {} is complete", directory.display());
+        }
+        if std::mem::take(&mut self.has_unstaged_files) && self.config.write_success_markers {
+            File::create(self.directory.join(SUCCESS_MARKER_NAME))?;
+        }
+        self.current_period = None;
+        self.next_part = 0;
+        Ok(())
+    }
+
+    pub(crate) fn is_period_over(&self, now: DateTime<Utc>) -> bool {
+        self.current_period.is_some() && self.current_period != self.period_start(now)
+    }
+}
+
+struct OpenFile {
+    writer: BufWriter<File>,
+    in_progress_path: PathBuf,
+    path: PathBuf,
+    size: u64,
+}
+
+/// Writes the lines into the files within `directory`, rotating them as configured in the
+/// [`FileRotationConfig`]. If the writer is dropped before the computation ends, e.g. due to
+/// a failure, the file being written directly in `directory` is left under its temporary name,
+/// and the subdirectories written in the current period are discarded.
+pub struct RotatingFileWriter {
+    rotation: RotationState,
+    current_file: Option<OpenFile>,
+    header: Option<Vec<u8>>,
+}
+
+impl RotatingFileWriter {
+    pub fn new(directory: PathBuf, config: FileRotationConfig) -> Result<Self, WriteError> {
+        Ok(Self {
+            rotation: RotationState::new(directory, config)?,
+            current_file: None,
+            header: None,
+        })
+    }
+
+    fn open_file(&mut self, now: DateTime<Utc>) -> Result<&mut OpenFile, WriteError> {
+        if self.current_file.is_none() {
+            let (path, in_progress_path) = self.rotation.next_file_paths(now)?;
+            let mut file = OpenFile {
+                writer: BufWriter::new(File::create(&in_progress_path)?),
+                in_progress_path,
+                path,
+                size: 0,
+            };
+            if let Some(header) = &self.header {
+                file.writer.write_all(header)?;
+                file.writer.write_all(b"\n")?;
+                file.size += header.len() as u64 + 1;
+            }
+            self.current_file = Some(file);
+        }
+        Ok(self
+            .current_file
+            .as_mut()
+            .expect("the file must be open at this point"))
+    }
+
+    fn finalize_current_file(&mut self) -> Result<(), WriteError> {
+        if let Some(mut file) = self.current_file.take() {
+            file.writer.flush()?;
+            file.writer.get_ref().sync_all()?;
+            self.rotation
+                .complete_file(&file.in_progress_path, &file.path)?;
+        }
+        Ok(())
+    }
+
+    fn finish_period(&mut self) -> Result<(), WriteError> {
+        self.finalize_current_file()?;
+        self.rotation.finish_period()
+    }
+
+    fn finish_period_if_over(&mut self, now: DateTime<Utc>) -> Result<(), WriteError> {
+        if self.rotation.is_period_over(now) {
+            self.finish_period()?;
+        }
+        Ok(())
+    }
+}
+
+impl Writer for RotatingFileWriter {
+    fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
+        let now = Utc::now();
+        self.finish_period_if_over(now)?;
+        let max_file_size = self.rotation.config().max_file_size;
+        for payload in data.payloads {
+            let payload = payload.into_raw_bytes()?;
+            if self.rotation.config().repeat_header && self.header.is_none() {
+                self.header = Some(payload);
+                continue;
+            }
+            let file = self.open_file(now)?;
+            file.writer.write_all(&payload)?;
+            file.writer.write_all(b"\n")?;
+            file.size += payload.len() as u64 + 1;
+            if max_file_size.is_some_and(|max_file_size| file.size >= max_file_size) {
+                self.finalize_current_file()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, _forced: bool) -> Result<(), WriteError> {
+        // The period may be over even if nothing has been written since then
+        self.finish_period_if_over(Utc::now())?;
+        if let Some(file) = &mut self.current_file {
+            file.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn on_time_committed(&mut self, time: Option<Timestamp>) -> Result<(), WriteError> {
+        if time.is_none() {
+            self.finish_period()?;
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> String {
+        format!("FileSystem({})", self.rotation.directory().display())
+    }
+}
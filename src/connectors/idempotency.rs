@@ -0,0 +1,120 @@
+// Copyright © 2024 Pathway
+
+//! Idempotency keys of the entries sent to the output connectors, for the sinks that can't write
+//! transactionally but can ignore or overwrite an entry that they have already received.
+//!
+//! After a recovery, the computation is restarted from the last checkpoint, so the entries
+//! written after it are produced and written again. Their times differ from the times in the
+//! failed run, so instead of the time, the key of an entry is derived from the epoch the entry
+//! belongs to, that is the checkpoint that the computation would be restarted from, together
+//! with the row key, the values, the diff and the number of identical entries before it in the
+//! epoch. For a deterministic computation, the entries written again get the same keys as the
+//! first time, which makes the output effectively exactly-once.
+//!
+//! The entries written before the first checkpoint of a run belong to the checkpoint the run
+//! started from. If the computation fails again right after its first checkpoint, these entries
+//! may be written again with different keys.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use xxhash_rust::xxh3::Xxh3 as Hasher;
+
+use crate::engine::value::HashInto;
+use crate::engine::{Key, Timestamp, TotalFrontier, Value};
+use crate::persistence::PersistenceTime;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey(pub u128);
+
+impl fmt::Display for IdempotencyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// Generates the idempotency keys of the entries written by a single output connector on a
+/// single worker.
+#[derive(Debug)]
+pub struct IdempotencyKeyGenerator {
+    snapshot_interval: Duration,
+    recovery_epoch: TotalFrontier<Timestamp>,
+    is_checkpoint_taken: bool,
+    current_epoch: Option<Timestamp>,
+    occurrences: HashMap<u128, u64>,
+}
+
+impl IdempotencyKeyGenerator {
+    /// Creates the generator for a computation restarted from `last_checkpoint`, with the
+    /// checkpoints taken every `snapshot_interval`.
+    pub fn new(last_checkpoint: TotalFrontier<Timestamp>, snapshot_interval: Duration) -> Self {
+        Self {
+            snapshot_interval,
+            recovery_epoch: last_checkpoint,
+            is_checkpoint_taken: false,
+            current_epoch: None,
+            occurrences: HashMap::new(),
+        }
+    }
+
+    /// Creates the generator for a computation that can't be restarted. The entries still get
+    /// the same keys if their writes are retried.
+    pub fn without_persistence() -> Self {
+        Self {
+            snapshot_interval: Duration::ZERO,
+            recovery_epoch: TotalFrontier::At(Timestamp(0)),
+            is_checkpoint_taken: true,
+            current_epoch: None,
+            occurrences: HashMap::new(),
+        }
+    }
+
+    /// Has to be called when the output connector commits a time, with the last checkpoint at
+    /// that moment.
+    pub fn on_commit(&mut self, last_checkpoint: TotalFrontier<Timestamp>) {
+        if last_checkpoint != self.recovery_epoch {
+            self.is_checkpoint_taken = true;
+        }
+    }
+
+    fn epoch(&self, time: Timestamp) -> Timestamp {
+        if self.is_checkpoint_taken {
+            time.most_recent_possible_snapshot_time(self.snapshot_interval)
+        } else {
+            match self.recovery_epoch {
+                TotalFrontier::At(epoch) => epoch,
+                TotalFrontier::Done => Timestamp(u64::MAX),
+            }
+        }
+    }
+
+    /// The key of the next entry written. It has to be called once per entry, in the order the
+    /// entries are written, and not again when the write of the entry is retried.
+    pub fn next_key(
+        &mut self,
+        key: Key,
+        values: &[Value],
+        time: Timestamp,
+        diff: isize,
+    ) -> IdempotencyKey {
+        let epoch = self.epoch(time);
+        if self.current_epoch != Some(epoch) {
+            self.current_epoch = Some(epoch);
+            self.occurrences.clear();
+        }
+
+        let mut hasher = Hasher::default();
+        epoch.0.hash_into(&mut hasher);
+        key.hash_into(&mut hasher);
+        values.hash_into(&mut hasher);
+        hasher.update(&diff.to_le_bytes());
+        let entry_hash = hasher.digest128();
+
+        let occurrence = self.occurrences.entry(entry_hash).or_default();
+        entry_hash.hash_into(&mut hasher);
+        occurrence.hash_into(&mut hasher);
+        *occurrence += 1;
+        IdempotencyKey(hasher.digest128())
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright © 2024 Pathway
+
+//! Prioritizing the most-lagging partitions of a Kafka topic when the reader is behind, e.g.
+//! after a downtime.
+//!
+//! The lags of the assigned partitions are checked periodically. Once the largest of them
+//! reaches the configured minimum, the partitions lagging less than half as much are paused, so
+//! that the consumer spends its polls and the batches it sends to the engine on the partitions
+//! that are the furthest behind. The paused partitions keep accumulating messages while the
+//! others catch up, so the lags even out and all the partitions are resumed, which makes the
+//! recovery progress uniformly over the whole topic instead of finishing some partitions hours
+//! before the others.
+
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+
+use log::info;
+
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+pub const DEFAULT_MIN_LAG: u64 = 1000;
+
+#[derive(Clone, Copy, Debug)]
+pub struct LagThrottlingConfig {
+    /// How often the lags of the partitions are checked.
+    pub check_interval: Duration,
+    /// No partition is paused while all the lags are below this number of messages.
+    pub min_lag: u64,
+}
+
+impl Default for LagThrottlingConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: DEFAULT_CHECK_INTERVAL,
+            min_lag: DEFAULT_MIN_LAG,
+        }
+    }
+}
+
+/// The partitions to pause and to resume after a check of the lags. The partitions that have
+/// to stay paused are included in `pause` as well, since the consumer resumes the partitions
+/// reassigned to it during a rebalance.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ThrottlingDecision {
+    pub pause: Vec<i32>,
+    pub resume: Vec<i32>,
+}
+
+pub struct LagThrottler {
+    config: LagThrottlingConfig,
+    last_check_at: Option<Instant>,
+    paused: BTreeSet<i32>,
+}
+
+impl LagThrottler {
+    pub fn new(config: LagThrottlingConfig) -> Self {
+        Self {
+            config,
+            last_check_at: None,
+            paused: BTreeSet::new(),
+        }
+    }
+
+    pub fn is_check_due(&self, now: Instant) -> bool {
+        self.last_check_at
+            .is_none_or(|last_check_at| now >= last_check_at + self.config.check_interval)
+    }
+
+    /// The reader mustn't block for longer than this while polling, so that the paused
+    /// partitions are resumed in time, even if the others have no new messages.
+    pub fn poll_timeout(&self) -> Duration {
+        self.config.check_interval
+    }
+
+    pub fn paused_partitions(&self) -> &BTreeSet<i32> {
+        &self.paused
+    }
+
+    /// Accepts the lags of the partitions assigned to the reader, in messages. The partitions
+    /// that are no longer assigned are forgotten.
+    pub fn on_lags_checked(
+        &mut self,
+        now: Instant,
+        lags: &HashMap<i32, u64>,
+    ) -> ThrottlingDecision {
+        self.last_check_at = Some(now);
+        let max_lag = lags.values().copied().max().unwrap_or(0);
+        let pause: BTreeSet<i32> = if max_lag < self.config.min_lag {
+            BTreeSet::new()
+        } else {
+            lags.iter()
+                .filter(|(_, lag)| lag.saturating_mul(2) < max_lag)
+                .map(|(partition, _)| *partition)
+                .collect()
+        };
+        let resume: Vec<i32> = self
+            .paused
+            .iter()
+            .filter(|partition| lags.contains_key(partition) && !pause.contains(partition))
+            .copied()
+            .collect();
+        if self.paused.is_empty() && !pause.is_empty() {
+            info!("The largest partition lag is {max_lag} messages, prioritizing the most lagging partitions");
+        } else if !self.paused.is_empty() && pause.is_empty() {
+            info!("Partition lags have evened out, reading from all partitions");
+        }
+        self.paused.clone_from(&pause);
+        ThrottlingDecision {
+            pause: pause.into_iter().collect(),
+            resume,
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright © 2024 Pathway
+
+//! Masking of the selected columns before they are passed to the output connector.
+//!
+//! A masked column is replaced with a string, so that its cleartext never leaves the engine.
+//! Three methods are available:
+//! - hashing, which is a hex-encoded SHA-256 of the value. It is deterministic, so the hashed
+//!   values can still be joined and counted, but the values from a small domain can be
+//!   recovered by enumerating it;
+//! - tokenization, which is a hex-encoded HMAC-SHA256 of the value with a secret key. It is
+//!   deterministic as well, but can't be reversed without the key;
+//! - encryption with AES-256-GCM, which produces a base64-encoded random nonce followed by the
+//!   ciphertext. It can be reversed by the holder of the key, but the same value is encrypted
+//!   differently every time.
+//!
+//! The strings and the bytes are masked as they are, the other values are masked in their
+//! textual representation. The nulls are kept, as well as the error values.
+
+use std::borrow::Cow;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::Aes256Gcm;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::connectors::data_format::{Formatter, FormatterContext, FormatterError};
+use crate::engine::{Key, Timestamp, Value};
+
+const AES_256_KEY_LENGTH: usize = 32;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum MaskingError {
+    #[error("unknown column {0:?}")]
+    UnknownColumn(String),
+
+    #[error("column {0:?} is masked more than once")]
+    ColumnMaskedTwice(String),
+
+    #[error("column {0:?} is a part of the primary key, so it can't be encrypted")]
+    EncryptedKeyColumn(String),
+
+    #[error("tokenization key must not be empty")]
+    EmptyTokenizationKey,
+
+    #[error("encryption key must be {AES_256_KEY_LENGTH} bytes long, got {0}")]
+    InvalidEncryptionKeyLength(usize),
+
+    #[error("failed to encrypt a value")]
+    Encryption,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MaskingMethod {
+    Hash,
+    Tokenize { key: Vec<u8> },
+    Encrypt { key: Vec<u8> },
+}
+
+impl MaskingMethod {
+    /// Whether the same value is always masked in the same way.
+    pub fn is_deterministic(&self) -> bool {
+        !matches!(self, Self::Encrypt { .. })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnMasking {
+    pub column_name: String,
+    pub method: MaskingMethod,
+}
+
+enum Masker {
+    Hash,
+    Tokenize(Hmac<Sha256>),
+    Encrypt(Box<Aes256Gcm>),
+}
+
+impl Masker {
+    fn new(method: &MaskingMethod) -> Result<Self, MaskingError> {
+        match method {
+            MaskingMethod::Hash => Ok(Self::Hash),
+            MaskingMethod::Tokenize { key } => {
+                if key.is_empty() {
+                    return Err(MaskingError::EmptyTokenizationKey);
+                }
+                let mac =
+                    Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+                Ok(Self::Tokenize(mac))
+            }
+            MaskingMethod::Encrypt { key } => {
+                let cipher = Aes256Gcm::new_from_slice(key)
+                    .map_err(|_| MaskingError::InvalidEncryptionKeyLength(key.len()))?;
+                Ok(Self::Encrypt(Box::new(cipher)))
+            }
+        }
+    }
+
+    fn plaintext(value: &Value) -> Cow<'_, [u8]> {
+        match value {
+            Value::String(s) => Cow::Borrowed(s.as_bytes()),
+            Value::Bytes(b) => Cow::Borrowed(&b[..]),
+            other => Cow::Owned(other.to_string().into_bytes()),
+        }
+    }
+
+    fn mask(&self, value: &Value) -> Result<Value, MaskingError> {
+        if matches!(value, Value::None | Value::Error | Value::Pending) {
+            return Ok(value.clone());
+        }
+        let plaintext = Self::plaintext(value);
+        let masked = match self {
+            Self::Hash => hex::encode(Sha256::digest(&plaintext)),
+            Self::Tokenize(mac) => {
+                let mut mac = mac.clone();
+                mac.update(&plaintext);
+                hex::encode(mac.finalize().into_bytes())
+            }
+            Self::Encrypt(cipher) => {
+                let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+                let ciphertext = cipher
+                    .encrypt(&nonce, plaintext.as_ref())
+                    .map_err(|_| MaskingError::Encryption)?;
+                let mut payload = nonce.to_vec();
+                payload.extend(ciphertext);
+                BASE64_STANDARD.encode(payload)
+            }
+        };
+        Ok(Value::from(masked))
+    }
+}
+
+/// Masks the configured columns and passes the result to the wrapped formatter.
+pub struct MaskingFormatter {
+    inner: Box<dyn Formatter>,
+    maskers: Vec<(usize, Masker)>,
+}
+
+impl MaskingFormatter {
+    pub fn new(
+        inner: Box<dyn Formatter>,
+        value_field_names: &[String],
+        key_field_names: &[String],
+        column_maskings: &[ColumnMasking],
+    ) -> Result<Self, MaskingError> {
+        let mut maskers: Vec<(usize, Masker)> = Vec::with_capacity(column_maskings.len());
+        for masking in column_maskings {
+            let name = &masking.column_name;
+            let index = value_field_names
+                .iter()
+                .position(|field_name| field_name == name)
+                .ok_or_else(|| MaskingError::UnknownColumn(name.clone()))?;
+            if maskers
+                .iter()
+                .any(|(masked_index, _)| *masked_index == index)
+            {
+                return Err(MaskingError::ColumnMaskedTwice(name.clone()));
+            }
+            // A non-deterministic masking would make the same row look like a different one
+            if !masking.method.is_deterministic() && key_field_names.contains(name) {
+                return Err(MaskingError::EncryptedKeyColumn(name.clone()));
+            }
+            maskers.push((index, Masker::new(&masking.method)?));
+        }
+        Ok(Self { inner, maskers })
+    }
+}
+
+impl Formatter for MaskingFormatter {
+    fn format(
+        &mut self,
+        key: &Key,
+        values: &[Value],
+        time: Timestamp,
+        diff: isize,
+    ) -> Result<FormatterContext, FormatterError> {
+        let mut values = values.to_vec();
+        for (index, masker) in &self.maskers {
+            let value = values
+                .get_mut(*index)
+                .ok_or(FormatterError::IncorrectColumnIndex)?;
+            *value = masker.mask(value)?;
+        }
+        self.inner.format(key, &values, time, diff)
+    }
+
+    fn is_filtered_out(&self, values: &[Value]) -> Result<bool, FormatterError> {
+        self.inner.is_filtered_out(values)
+    }
+
+    fn short_description(&self) -> Cow<'static, str> {
+        self.inner.short_description()
+    }
+}
@@ -80,6 +80,20 @@ impl FileLikeMetadata {
         }
     }
 
+    /// The metadata of an e-mail message, identified by its mailbox and UID in `path`. The
+    /// messages are immutable in IMAP, so the time the message was received at by the server is
+    /// reported as the modification time.
+    pub fn from_email_message(path: String, size: u64, received_at: Option<i64>) -> Self {
+        Self {
+            created_at: None,
+            modified_at: received_at.and_then(|received_at| received_at.try_into().ok()),
+            owner: None,
+            path,
+            size,
+            seen_at: current_unix_timestamp_secs(),
+        }
+    }
+
     /// Checks if file contents could have been changed.
     pub fn is_changed(&self, other: &FileLikeMetadata) -> bool {
         self.modified_at != other.modified_at
@@ -139,7 +153,9 @@ mod file_owner {
 
         let user = execute_with_retries(
             || User::from_uid(uid.into()),
-            RetryConfig::new(Duration::from_millis(50), 1.5, Duration::from_millis(10)),
+            // a local lookup, so it doesn't take retries from the budget of network calls
+            RetryConfig::new(Duration::from_millis(50), 1.5, Duration::from_millis(10))
+                .with_budget(None),
             5,
         );
         if let Ok(Some(user)) = user {
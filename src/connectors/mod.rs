@@ -4,6 +4,7 @@ use adaptors::InputAdaptor;
 use crossbeam_channel::{self as channel, Sender, TryRecvError};
 use itertools::Itertools;
 use log::{error, info, warn};
+use opentelemetry::KeyValue;
 use scopeguard::guard;
 use std::cell::RefCell;
 use std::env;
@@ -18,23 +19,48 @@ use timely::dataflow::operators::probe::Handle;
 
 pub mod adaptors;
 pub mod aws;
+pub mod backfill;
 pub mod backlog;
 pub mod data_format;
 pub mod data_lake;
 pub mod data_storage;
 pub mod data_tokenize;
+pub mod file_rotation;
+pub mod idempotency;
+pub mod lag_throttling;
+pub mod masking;
 pub mod metadata;
 pub mod monitoring;
+pub mod mssql;
+pub mod named_schema;
 pub mod offset;
+pub mod oracle;
+pub mod parquet_output;
+pub mod pausing;
 pub mod posix_like;
+pub mod prometheus;
+pub mod pushdown;
+pub mod query_endpoint;
 pub mod scanner;
+pub mod schema_drift;
+pub mod sdk;
+pub mod snapshot_export;
+pub mod sql_merge;
+pub mod sql_polling;
+pub mod subprocess;
 pub mod synchronization;
 
-use crate::connectors::monitoring::ConnectorMonitor;
+use crate::connectors::monitoring::{BackfillProgressTracker, ConnectorMonitor};
+use crate::connectors::pausing::{shared_pause_switch, ConnectorKind};
+use crate::engine::dataflow::shutdown::is_shutdown_requested;
 use crate::engine::error::{DynError, Trace};
+use crate::engine::health;
+use crate::engine::interning::StringInterner;
+use crate::engine::log_context::{self, LogContext};
 use crate::engine::report_error::{
     LogError, ReportError, SpawnWithReporter, UnwrapWithErrorLogger,
 };
+use crate::engine::spans;
 use crate::engine::{DataError, Key, Value};
 
 use crate::connectors::synchronization::ConnectorGroupAccessor;
@@ -109,6 +135,8 @@ pub struct Connector {
     n_parse_attempts: usize,
     n_parse_errors_in_log: usize,
     backlog_tracker: BacklogTracker,
+    stop_on_shutdown: bool,
+    string_interner: StringInterner,
 }
 
 #[derive(Debug)]
@@ -220,9 +248,18 @@ impl Connector {
             n_parse_attempts: 0,
             n_parse_errors_in_log: 0,
             backlog_tracker: BacklogTracker::new(),
+            stop_on_shutdown: true,
+            string_interner: StringInterner::from_env(),
         }
     }
 
+    /// Makes the connector keep reading after a shutdown is requested. Used by internal
+    /// connectors, which have to deliver everything that the dataflow sends to them.
+    pub fn ignore_shutdown_requests(mut self) -> Self {
+        self.stop_on_shutdown = false;
+        self
+    }
+
     /// The optimization method. Used when streaming objects that are
     /// tied into atomic batches. Each batch must end up in a single
     /// Pathway minibatch, but the reverse is not necessarily true:
@@ -324,6 +361,7 @@ impl Connector {
         Ok(frontier)
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[allow(clippy::too_many_lines)]
     pub fn read_realtime_updates(
         reader: &mut dyn Reader,
@@ -332,17 +370,51 @@ impl Connector {
         main_thread: &Thread,
         error_reporter: &(impl ReportError + 'static),
         mut group: Option<&mut ConnectorGroupAccessor>,
+        stop_on_shutdown: bool,
+        connector_name: &str,
     ) {
         let use_rare_wakeup = env::var("PATHWAY_YOLO_RARE_WAKEUPS") == Ok("1".to_string());
         let mut amt_send = 0;
         let mut consecutive_errors = 0;
+        let pause_switch = shared_pause_switch(connector_name, ConnectorKind::Source);
         loop {
-            let row_read_result = reader.read();
+            // The entries that are already sent are processed and committed while paused
+            pause_switch.wait_while_paused(|| stop_on_shutdown && is_shutdown_requested());
+            if stop_on_shutdown && is_shutdown_requested() {
+                break;
+            }
+            let row_read_result = spans::trace_if_slow(
+                spans::CONNECTOR_POLL,
+                || reader.read(),
+                |_| {
+                    vec![KeyValue::new(
+                        spans::CONNECTOR_NAME,
+                        connector_name.to_string(),
+                    )]
+                },
+            );
             let finished = matches!(row_read_result, Ok(ReadResult::Finished));
 
             match row_read_result {
                 Ok(ReadResult::Data(reader_context, offset)) => {
-                    match parser.parse(&reader_context) {
+                    let parse_result = spans::trace_if_slow(
+                        spans::PARSER_BATCH,
+                        || parser.parse(&reader_context),
+                        |result| {
+                            let mut attributes = vec![KeyValue::new(
+                                spans::CONNECTOR_NAME,
+                                connector_name.to_string(),
+                            )];
+                            if let Ok(entries) = result {
+                                attributes.push(KeyValue::new(
+                                    spans::BATCH_ENTRIES,
+                                    i64::try_from(entries.len()).unwrap(),
+                                ));
+                            }
+                            attributes
+                        },
+                    );
+                    match parse_result {
                         Ok(entries) => {
                             if let Some(group) = group.as_mut() {
                                 let mut entries_for_sending = Vec::new();
@@ -413,7 +485,10 @@ impl Connector {
                     error!("There had been an error processing the row read result: {error}");
                     consecutive_errors += 1;
                     if consecutive_errors > reader.max_allowed_consecutive_errors() {
-                        error_reporter.report(EngineError::ReaderFailed(error));
+                        error_reporter.report(EngineError::connector_failed(
+                            connector_name,
+                            EngineError::ReaderFailed(error),
+                        ));
                     }
                 }
             }
@@ -553,6 +628,8 @@ impl Connector {
         let reader_name = reader.name(unique_name);
         let session_type = parser.session_type();
         let in_connector_group = group.is_some();
+        let stop_on_shutdown = self.stop_on_shutdown;
+        let connector_name = reader_name.clone();
 
         let mut snapshot_writer = Self::snapshot_writer(
             reader.as_ref(),
@@ -562,9 +639,18 @@ impl Connector {
         )
         .map_err(|e| EngineError::SnapshotWriterError(Box::new(e)))?;
 
+        let backfill_progress = BackfillProgressTracker::default();
+        let reader_backfill_progress = backfill_progress.clone();
+
+        health::connector_registered();
+        let worker_log_context = log_context::current();
         let input_thread_handle = thread::Builder::new()
             .name(thread_name)
             .spawn_with_reporter(error_reporter, move |reporter| {
+                log_context::set(LogContext {
+                    connector_name: Some(connector_name.clone()),
+                    ..worker_log_context
+                });
                 let sender = guard(sender, |sender| {
                     // ensure that we always unpark the main thread after dropping the sender, so it
                     // notices we are done sending
@@ -572,7 +658,11 @@ impl Connector {
                     main_thread.unpark();
                 });
 
-                let mut reader = reader.build()?;
+                let mut reader = reader
+                    .build()
+                    .map_err(|e| EngineError::connector_failed(&connector_name, e))?;
+                reader.track_backfill_progress(reader_backfill_progress);
+                health::connector_connected();
                 Self::read_snapshot(
                     &mut *reader,
                     persistent_storage.as_ref(),
@@ -582,7 +672,9 @@ impl Connector {
                     snapshot_access,
                     realtime_reader_needed,
                 )
-                .map_err(EngineError::ReaderFailed)?;
+                .map_err(|e| {
+                    EngineError::connector_failed(&connector_name, EngineError::ReaderFailed(e))
+                })?;
                 if realtime_reader_needed {
                     Self::read_realtime_updates(
                         &mut *reader,
@@ -591,6 +683,8 @@ impl Connector {
                         &main_thread,
                         reporter,
                         group.as_mut(),
+                        stop_on_shutdown,
+                        &connector_name,
                     );
                 }
 
@@ -601,12 +695,43 @@ impl Connector {
         let mut next_commit_at = self.commit_duration.map(|x| SystemTime::now() + x);
         let mut backfilling_finished = false;
 
-        let connector_monitor = Rc::new(RefCell::new(ConnectorMonitor::new(reader_name)));
+        let connector_monitor = Rc::new(RefCell::new(ConnectorMonitor::new(
+            reader_name,
+            backfill_progress,
+        )));
         let cloned_connector_monitor = connector_monitor.clone();
         let mut commit_allowed = true;
         let mut deferred_events = Vec::new();
         let poller = Box::new(move || {
             let iteration_start = SystemTime::now();
+            if self.stop_on_shutdown && backfilling_finished && is_shutdown_requested() {
+                // Stop reading, commit the entries that are already in the input session and
+                // finish as if the source has ended. The entries that are still in the
+                // channel are not in the frontier, so they are read again after a restart.
+                if commit_allowed {
+                    self.on_parsed_data(
+                        vec![ParsedEventWithErrors::AdvanceTime],
+                        None, // no key generation for time advancement
+                        input_session.as_mut(),
+                        &mut values_to_key,
+                        &mut snapshot_writer,
+                        &mut Some(&mut *connector_monitor.borrow_mut()),
+                        session_type,
+                    );
+                }
+                if let Some(snapshot_writer) = &snapshot_writer {
+                    let snapshot_event = SnapshotEvent::AdvanceTime(
+                        Timestamp(self.current_timestamp.0 + 2),
+                        self.current_frontier.clone(),
+                    );
+                    info!(
+                        "Shutdown requested. Terminating with snapshot event: {snapshot_event:?}"
+                    );
+                    snapshot_writer.lock().unwrap().write(&snapshot_event);
+                }
+                (*connector_monitor).borrow_mut().finish();
+                return ControlFlow::Break(());
+            }
             if matches!(persistence_mode, PersistenceMode::SpeedrunReplay)
                 && !backfilling_finished
                 && output_probe.less_than(input_session.time())
@@ -823,6 +948,7 @@ impl Connector {
             Entry::RewindFinishSentinel(restored_frontier) => {
                 assert!(!*backfilling_finished);
                 *backfilling_finished = true;
+                health::connector_recovered();
                 self.current_frontier = restored_frontier;
                 let parsed_entries = vec![ParsedEventWithErrors::AdvanceTime];
                 self.on_parsed_data(
@@ -838,10 +964,12 @@ impl Connector {
             Entry::Snapshot(snapshot) => {
                 assert!(!*backfilling_finished);
                 match snapshot {
-                    SnapshotEvent::Insert(key, value) => {
+                    SnapshotEvent::Insert(key, mut value) => {
+                        self.string_interner.intern(&mut value);
                         Self::on_insert(key, value, input_session);
                     }
-                    SnapshotEvent::Delete(key, value) => {
+                    SnapshotEvent::Delete(key, mut value) => {
+                        self.string_interner.intern(&mut value);
                         Self::on_remove(key, value, input_session);
                     }
                     SnapshotEvent::AdvanceTime(_, _) | SnapshotEvent::Finished => {
@@ -918,28 +1046,30 @@ impl Connector {
             }
 
             match entry {
-                ParsedEvent::Insert((_, values)) => {
+                ParsedEvent::Insert((_, mut values)) => {
                     if values.len() != self.num_columns {
                         error!("There are {} tokens in the entry, but the expected number of tokens was {}", values.len(), self.num_columns);
                         continue;
                     }
+                    self.string_interner.intern(&mut values);
                     Self::on_insert(key.expect("No key"), values, input_session);
                     self.backlog_tracker.on_event(&self.current_timestamp);
                 }
-                ParsedEvent::Delete((_, values)) => {
+                ParsedEvent::Delete((_, mut values)) => {
                     if matches!(session_type, SessionType::Native)
                         && values.len() != self.num_columns
                     {
                         error!("There are {} tokens in the entry, but the expected number of tokens was {}", values.len(), self.num_columns);
                         continue;
                     }
+                    self.string_interner.intern(&mut values);
                     Self::on_remove(key.expect("No key"), values, input_session);
                     self.backlog_tracker.on_event(&self.current_timestamp);
                 }
                 ParsedEvent::AdvanceTime => {
                     let time_advanced = self.advance_time(input_session);
                     if let Some(ref mut connector_monitor) = connector_monitor {
-                        connector_monitor.commit();
+                        connector_monitor.commit(time_advanced);
                     }
                     if let Some(snapshot_writer) = snapshot_writer {
                         snapshot_writer
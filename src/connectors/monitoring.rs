@@ -1,11 +1,14 @@
 // Copyright © 2024 Pathway
 
 use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use log::{info, warn};
 use pyo3::pyclass;
 
+use crate::engine::Timestamp;
+
 #[derive(Debug, Clone, Copy)]
 #[pyclass]
 pub struct ConnectorStats {
@@ -17,6 +20,116 @@ pub struct ConnectorStats {
     pub num_messages_recently_committed: usize,
     #[pyo3(get, set)]
     pub finished: bool,
+    /// The time of the last minibatch committed by the connector.
+    #[pyo3(get, set)]
+    pub time: Option<Timestamp>,
+    /// Wall-clock time between the last two commits, in milliseconds.
+    #[pyo3(get, set)]
+    pub epoch_duration: Option<u64>,
+    /// The progress of reading the data present in the source at the start, if the reader
+    /// reports it.
+    #[pyo3(get, set)]
+    pub backfill: Option<BackfillProgress>,
+}
+
+/// The progress of reading the objects that were present in the source when the connector
+/// started, e.g. the files in an S3 bucket. The objects are discovered gradually, so the totals
+/// only cover the objects listed so far.
+#[derive(Debug, Clone, Copy)]
+#[pyclass]
+pub struct BackfillProgress {
+    #[pyo3(get, set)]
+    pub objects_processed: usize,
+    #[pyo3(get, set)]
+    pub objects_discovered: usize,
+    #[pyo3(get, set)]
+    pub bytes_processed: u64,
+    #[pyo3(get, set)]
+    pub bytes_discovered: u64,
+    #[pyo3(get, set)]
+    pub finished: bool,
+    /// The estimated time until the backfill is finished, in milliseconds, based on the rate at
+    /// which the bytes have been processed so far.
+    #[pyo3(get, set)]
+    pub eta: Option<u64>,
+}
+
+struct BackfillProgressState {
+    started_at: Instant,
+    objects_processed: usize,
+    objects_discovered: usize,
+    bytes_processed: u64,
+    bytes_discovered: u64,
+    finished: bool,
+}
+
+impl BackfillProgressState {
+    fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            objects_processed: 0,
+            objects_discovered: 0,
+            bytes_processed: 0,
+            bytes_discovered: 0,
+            finished: false,
+        }
+    }
+}
+
+/// Shared between the reader, which reports the backfill progress from the connector thread,
+/// and the [`ConnectorMonitor`], which includes it in the stats. Nothing is reported for the
+/// readers that don't use it.
+#[derive(Clone, Default)]
+pub struct BackfillProgressTracker {
+    state: Arc<Mutex<Option<BackfillProgressState>>>,
+}
+
+impl BackfillProgressTracker {
+    fn update(&self, f: impl FnOnce(&mut BackfillProgressState)) {
+        let mut state = self.state.lock().unwrap();
+        let state = state.get_or_insert_with(BackfillProgressState::new);
+        if !state.finished {
+            f(state);
+        }
+    }
+
+    pub fn on_object_discovered(&self, size: u64) {
+        self.update(|state| {
+            state.objects_discovered += 1;
+            state.bytes_discovered += size;
+        });
+    }
+
+    pub fn on_object_processed(&self, size: u64) {
+        self.update(|state| {
+            state.objects_processed += 1;
+            state.bytes_processed += size;
+        });
+    }
+
+    /// Marks the backfill as finished. The objects reported afterwards are not a part of it.
+    pub fn finish(&self) {
+        self.update(|state| state.finished = true);
+    }
+
+    pub fn progress(&self) -> Option<BackfillProgress> {
+        let state = self.state.lock().unwrap();
+        let state = state.as_ref()?;
+        let eta = (!state.finished && state.bytes_processed > 0).then(|| {
+            let remaining = state.bytes_discovered.saturating_sub(state.bytes_processed);
+            #[allow(clippy::cast_precision_loss)]
+            let remaining_share = remaining as f64 / state.bytes_processed as f64;
+            state.started_at.elapsed().mul_f64(remaining_share)
+        });
+        Some(BackfillProgress {
+            objects_processed: state.objects_processed,
+            objects_discovered: state.objects_discovered,
+            bytes_processed: state.bytes_processed,
+            bytes_discovered: state.bytes_discovered,
+            finished: state.finished,
+            eta: eta.map(|eta| u64::try_from(eta.as_millis()).unwrap_or(u64::MAX)),
+        })
+    }
 }
 
 struct ConnectorLogger {
@@ -88,11 +201,13 @@ pub struct ConnectorMonitor {
     stats: ConnectorStats,
     last_minute_queue: VecDeque<(usize, Instant)>,
     current_num_messages: usize,
+    last_commit_at: Option<Instant>,
     logger: ConnectorLogger,
+    backfill_progress: BackfillProgressTracker,
 }
 
 impl ConnectorMonitor {
-    pub fn new(name: String) -> Self {
+    pub fn new(name: String, backfill_progress: BackfillProgressTracker) -> Self {
         ConnectorMonitor {
             name: name.clone(),
             stats: ConnectorStats {
@@ -100,10 +215,15 @@ impl ConnectorMonitor {
                 num_messages_in_last_minute: 0,
                 num_messages_recently_committed: 0,
                 finished: false,
+                time: None,
+                epoch_duration: None,
+                backfill: None,
             },
             last_minute_queue: VecDeque::new(),
             current_num_messages: 0,
+            last_commit_at: None,
             logger: ConnectorLogger::new(name),
+            backfill_progress,
         }
     }
 
@@ -118,9 +238,14 @@ impl ConnectorMonitor {
         self.logger.on_finished();
     }
 
-    pub fn commit(&mut self) {
+    pub fn commit(&mut self, time: Timestamp) {
         self.stats.num_messages_recently_committed = self.current_num_messages;
         let now = Instant::now();
+        self.stats.time = Some(time);
+        self.stats.epoch_duration = self.last_commit_at.map(|last_commit_at| {
+            u64::try_from(now.duration_since(last_commit_at).as_millis()).unwrap()
+        });
+        self.last_commit_at = Some(now);
         while let Some(elem) = self.last_minute_queue.front() {
             if now.duration_since(elem.1) < Duration::from_secs(60) {
                 break;
@@ -141,7 +266,10 @@ impl ConnectorMonitor {
     }
 
     pub fn get_stats(&self) -> ConnectorStats {
-        self.stats
+        ConnectorStats {
+            backfill: self.backfill_progress.progress(),
+            ..self.stats
+        }
     }
 }
 
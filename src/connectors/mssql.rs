@@ -0,0 +1,249 @@
+// Copyright © 2024 Pathway
+
+//! Writing to a Microsoft SQL Server table.
+//!
+//! The batch is loaded with the bulk copy into a temporary staging table, whose columns have
+//! the types corresponding to the types of the output columns, and then moved into the target
+//! table with a single statement: an `INSERT ... SELECT` for the stream of changes or a `MERGE`
+//! for the snapshot. The server converts the staged values to the types of the target table.
+
+use std::borrow::Cow;
+
+use itertools::Itertools;
+use tiberius::{Client, ColumnData, Config, IntoSql, TokenRow};
+use tokio::net::TcpStream;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use crate::async_runtime::AsyncRuntime;
+use crate::connectors::data_format::FormatterContext;
+use crate::connectors::data_storage::{WriteError, Writer};
+use crate::connectors::sql_merge::{SqlRow, SqlSinkBuffer, SqlSinkError, SqlSinkMode};
+use crate::engine::time::DateTime as _;
+use crate::engine::{Type, Value};
+use crate::python_api::ValueField;
+use crate::reload;
+
+pub const STAGING_TABLE_NAME: &str = "#pathway_staging";
+
+const DATABASE_NAME: &str = "SQL Server";
+
+/// The type of the column of the staging table for the values of the given type.
+pub fn staging_column_type(type_: &Type) -> Result<&'static str, WriteError> {
+    match type_ {
+        Type::Bool => Ok("BIT"),
+        Type::Int | Type::Duration => Ok("BIGINT"),
+        Type::Float => Ok("FLOAT"),
+        Type::String | Type::Pointer | Type::Json => Ok("NVARCHAR(MAX)"),
+        Type::Bytes => Ok("VARBINARY(MAX)"),
+        Type::DateTimeNaive => Ok("DATETIME2"),
+        Type::DateTimeUtc => Ok("DATETIMEOFFSET"),
+        Type::Optional(wrapped) => staging_column_type(wrapped),
+        _ => Err(WriteError::UnsupportedType(type_.clone())),
+    }
+}
+
+pub fn create_staging_table_statement(value_fields: &[ValueField]) -> Result<String, WriteError> {
+    let columns: Vec<_> = value_fields
+        .iter()
+        .map(|field| {
+            Ok(format!(
+                "{} {} NULL",
+                field.name,
+                staging_column_type(&field.type_)?
+            ))
+        })
+        .try_collect()?;
+    Ok(format!(
+        "CREATE TABLE {STAGING_TABLE_NAME} ({},time BIGINT NOT NULL,diff BIGINT NOT NULL)",
+        columns.join(",")
+    ))
+}
+
+pub fn insert_statement(table_name: &str, value_field_names: &[String]) -> String {
+    let columns = value_field_names.iter().join(",");
+    format!(
+        "INSERT INTO {table_name} ({columns},time,diff) SELECT {columns},time,diff FROM {STAGING_TABLE_NAME}"
+    )
+}
+
+pub fn merge_statement(
+    table_name: &str,
+    value_field_names: &[String],
+    key_field_names: &[&str],
+) -> String {
+    let on_condition = key_field_names
+        .iter()
+        .map(|name| format!("target.{name}=source.{name}"))
+        .join(" AND ");
+    let update_pairs = value_field_names
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !key_field_names.contains(name))
+        .chain(["time", "diff"])
+        .map(|name| format!("target.{name}=source.{name}"))
+        .join(",");
+    let columns = value_field_names.iter().join(",");
+    let source_columns = value_field_names
+        .iter()
+        .map(|name| format!("source.{name}"))
+        .join(",");
+    format!(
+        "MERGE INTO {table_name} AS target USING {STAGING_TABLE_NAME} AS source ON {on_condition} \
+         WHEN MATCHED AND source.diff<0 THEN DELETE \
+         WHEN MATCHED THEN UPDATE SET {update_pairs} \
+         WHEN NOT MATCHED BY TARGET AND source.diff>0 THEN \
+         INSERT ({columns},time,diff) VALUES ({source_columns},source.time,source.diff);"
+    )
+}
+
+fn null_of_type(type_: &Type) -> ColumnData<'static> {
+    match type_.unoptionalize() {
+        Type::Bool => ColumnData::Bit(None),
+        Type::Int | Type::Duration => ColumnData::I64(None),
+        Type::Float => ColumnData::F64(None),
+        Type::Bytes => ColumnData::Binary(None),
+        Type::DateTimeNaive => ColumnData::DateTime2(None),
+        Type::DateTimeUtc => ColumnData::DateTimeOffset(None),
+        _ => ColumnData::String(None),
+    }
+}
+
+fn column_data(value: &Value, type_: &Type) -> Result<ColumnData<'static>, SqlSinkError> {
+    let data = match value {
+        Value::None => null_of_type(type_),
+        Value::Bool(b) => ColumnData::Bit(Some(*b)),
+        Value::Int(i) => ColumnData::I64(Some(*i)),
+        Value::Float(f) => ColumnData::F64(Some(f.into_inner())),
+        Value::String(s) => ColumnData::String(Some(Cow::Owned(s.to_string()))),
+        Value::Pointer(p) => ColumnData::String(Some(Cow::Owned(p.to_string()))),
+        Value::Json(j) => ColumnData::String(Some(Cow::Owned(j.to_string()))),
+        Value::Bytes(b) => ColumnData::Binary(Some(Cow::Owned(b.to_vec()))),
+        Value::DateTimeNaive(dt) => dt.as_chrono_datetime().into_sql(),
+        Value::DateTimeUtc(dt) => dt.as_chrono_datetime().and_utc().into_sql(),
+        Value::Duration(d) => ColumnData::I64(Some(d.microseconds())),
+        other => return Err(SqlSinkError::UnsupportedValue(other.kind(), DATABASE_NAME)),
+    };
+    Ok(data)
+}
+
+pub struct SqlServerWriter {
+    runtime: AsyncRuntime,
+    client: Client<Compat<TcpStream>>,
+    buffer: SqlSinkBuffer,
+    value_types: Vec<Type>,
+    max_batch_size: Option<usize>,
+}
+
+impl SqlServerWriter {
+    pub fn new(
+        runtime: AsyncRuntime,
+        connection_string: &str,
+        table_name: String,
+        value_fields: &[ValueField],
+        mode: &SqlSinkMode,
+        max_batch_size: Option<usize>,
+    ) -> Result<Self, WriteError> {
+        let buffer = SqlSinkBuffer::new(
+            table_name,
+            value_fields
+                .iter()
+                .map(|field| field.name.clone())
+                .collect(),
+            mode,
+        )?;
+        let create_staging_table = create_staging_table_statement(value_fields)?;
+        let config = Config::from_ado_string(connection_string)?;
+        let client = runtime.block_on(async {
+            let tcp = TcpStream::connect(config.get_addr()).await?;
+            tcp.set_nodelay(true)?;
+            let mut client = Client::connect(config, tcp.compat_write()).await?;
+            // Not a parametrized query, otherwise the temporary table would be dropped as soon
+            // as the statement completes
+            client
+                .simple_query(create_staging_table)
+                .await?
+                .into_results()
+                .await?;
+            Ok::<_, WriteError>(client)
+        })?;
+        Ok(Self {
+            runtime,
+            client,
+            buffer,
+            value_types: value_fields
+                .iter()
+                .map(|field| field.type_.clone())
+                .collect(),
+            max_batch_size,
+        })
+    }
+
+    fn token_row(&self, row: &SqlRow) -> Result<TokenRow<'static>, WriteError> {
+        let mut token_row = TokenRow::new();
+        for (value, type_) in row.values.iter().zip(self.value_types.iter()) {
+            token_row.push(column_data(value, type_)?);
+        }
+        token_row.push(ColumnData::I64(Some(row.time_as_i64()?)));
+        token_row.push(ColumnData::I64(Some(
+            i64::try_from(row.diff).expect("diff should fit into i64"),
+        )));
+        Ok(token_row)
+    }
+
+    fn apply_statement(&self) -> String {
+        match self.buffer.key_field_names() {
+            Some(key_field_names) => merge_statement(
+                &self.buffer.table_name,
+                &self.buffer.value_field_names,
+                &key_field_names,
+            ),
+            None => insert_statement(&self.buffer.table_name, &self.buffer.value_field_names),
+        }
+    }
+}
+
+impl Writer for SqlServerWriter {
+    fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
+        self.buffer.push(data);
+        if let Some(max_batch_size) = reload::max_batch_size(self.max_batch_size) {
+            if self.buffer.len() >= max_batch_size {
+                self.flush(true)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, _forced: bool) -> Result<(), WriteError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let token_rows: Vec<_> = self
+            .buffer
+            .take_rows()
+            .iter()
+            .map(|row| self.token_row(row))
+            .try_collect()?;
+        let apply_statement = self.apply_statement();
+        let client = &mut self.client;
+        self.runtime.block_on(async {
+            client
+                .execute(format!("TRUNCATE TABLE {STAGING_TABLE_NAME}"), &[])
+                .await?;
+            let mut request = client.bulk_insert(STAGING_TABLE_NAME).await?;
+            for token_row in token_rows {
+                request.send(token_row).await?;
+            }
+            request.finalize().await?;
+            client.execute(apply_statement, &[]).await?;
+            Ok(())
+        })
+    }
+
+    fn name(&self) -> String {
+        format!("SqlServer({})", self.buffer.table_name)
+    }
+
+    fn single_threaded(&self) -> bool {
+        self.buffer.key_field_positions.is_some()
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright © 2024 Pathway
+
+//! Schemas registered once under a name, so that the connectors reading the same data can
+//! reference them instead of declaring the columns each time.
+//!
+//! A connector referencing a schema has to declare all of its columns with the same types and
+//! defaults, possibly along with other columns, such as the metadata. The values parsed by such
+//! a connector are checked against the types of the schema by a [`SchemaValidatingParser`].
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::connectors::data_format::{ParseError, ParseResult, ParsedEventWithErrors, Parser};
+use crate::connectors::metadata::SourceMetadata;
+use crate::connectors::{ReaderContext, SessionType};
+use crate::engine::{Type, Value};
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum NamedSchemaError {
+    #[error("schema {0:?} is not registered")]
+    NotRegistered(String),
+
+    #[error("a different schema is already registered under the name {0:?}")]
+    AlreadyRegistered(String),
+
+    #[error("column {column:?} of schema {schema:?} is not declared")]
+    MissingColumn { schema: String, column: String },
+
+    #[error(
+        "column {column:?} of schema {schema:?} has type {expected}, but {declared} is declared"
+    )]
+    TypeMismatch {
+        schema: String,
+        column: String,
+        expected: Type,
+        declared: Type,
+    },
+
+    #[error("column {column:?} of schema {schema:?} is declared with a different default value")]
+    DefaultMismatch { schema: String, column: String },
+
+    #[error("schema {schema:?} has the primary key {expected:?}, but {declared:?} is declared")]
+    KeyMismatch {
+        schema: String,
+        expected: Option<Vec<String>>,
+        declared: Option<Vec<String>>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaColumn {
+    pub name: String,
+    pub type_: Type,
+    pub default: Option<Value>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamedSchema {
+    pub columns: Vec<SchemaColumn>,
+    /// The columns the primary key is computed from. `None` if the key is generated.
+    pub key_column_names: Option<Vec<String>>,
+}
+
+/// The schemas registered in this process, by their names.
+static SCHEMAS: Lazy<Mutex<HashMap<String, Arc<NamedSchema>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Registers the schema under the name. Registering the same schema again is allowed, so that
+/// the modules defining the schemas can be imported more than once.
+pub fn register_schema(name: &str, schema: NamedSchema) -> Result<(), NamedSchemaError> {
+    let mut schemas = SCHEMAS.lock().unwrap();
+    if let Some(registered) = schemas.get(name) {
+        if **registered != schema {
+            return Err(NamedSchemaError::AlreadyRegistered(name.to_string()));
+        }
+        return Ok(());
+    }
+    schemas.insert(name.to_string(), Arc::new(schema));
+    Ok(())
+}
+
+pub fn registered_schema(name: &str) -> Result<Arc<NamedSchema>, NamedSchemaError> {
+    SCHEMAS
+        .lock()
+        .unwrap()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| NamedSchemaError::NotRegistered(name.to_string()))
+}
+
+impl NamedSchema {
+    /// Checks that the columns declared by a connector include the columns of this schema, and
+    /// that the primary keys are the same. Returns the positions of the columns of this schema
+    /// among the declared ones.
+    pub fn check_declared(
+        &self,
+        schema_name: &str,
+        declared: &[SchemaColumn],
+        declared_key_column_names: Option<&[String]>,
+    ) -> Result<Vec<usize>, NamedSchemaError> {
+        if self.key_column_names.as_deref() != declared_key_column_names {
+            return Err(NamedSchemaError::KeyMismatch {
+                schema: schema_name.to_string(),
+                expected: self.key_column_names.clone(),
+                declared: declared_key_column_names.map(<[String]>::to_vec),
+            });
+        }
+        self.columns
+            .iter()
+            .map(|column| {
+                let Some(position) = declared.iter().position(|d| d.name == column.name) else {
+                    return Err(NamedSchemaError::MissingColumn {
+                        schema: schema_name.to_string(),
+                        column: column.name.clone(),
+                    });
+                };
+                let declared_column = &declared[position];
+                if declared_column.type_ != column.type_ {
+                    return Err(NamedSchemaError::TypeMismatch {
+                        schema: schema_name.to_string(),
+                        column: column.name.clone(),
+                        expected: column.type_.clone(),
+                        declared: declared_column.type_.clone(),
+                    });
+                }
+                if declared_column.default != column.default {
+                    return Err(NamedSchemaError::DefaultMismatch {
+                        schema: schema_name.to_string(),
+                        column: column.name.clone(),
+                    });
+                }
+                Ok(position)
+            })
+            .collect()
+    }
+}
+
+pub fn value_matches_type(value: &Value, type_: &Type) -> bool {
+    match (value, type_) {
+        // The errors are reported where they occur
+        (Value::Error, _) | (_, Type::Any) | (Value::Pending, Type::Future(_)) => true,
+        (Value::None, type_) => type_.can_be_none(),
+        (value, Type::Optional(arg) | Type::Future(arg)) => value_matches_type(value, arg),
+        (Value::Tuple(values), Type::Tuple(types)) => {
+            values.len() == types.len()
+                && values
+                    .iter()
+                    .zip(types.iter())
+                    .all(|(value, type_)| value_matches_type(value, type_))
+        }
+        (Value::Tuple(values), Type::List(arg)) => {
+            values.iter().all(|value| value_matches_type(value, arg))
+        }
+        (Value::IntArray(_) | Value::FloatArray(_) | Value::Float32Array(_), Type::Array(..))
+        | (Value::Bool(_), Type::Bool)
+        | (Value::Int(_), Type::Int)
+        | (Value::Float(_), Type::Float)
+        | (Value::Pointer(_), Type::Pointer)
+        | (Value::String(_), Type::String)
+        | (Value::Bytes(_), Type::Bytes)
+        | (Value::DateTimeNaive(_), Type::DateTimeNaive)
+        | (Value::DateTimeUtc(_), Type::DateTimeUtc)
+        | (Value::Duration(_), Type::Duration)
+        | (Value::Json(_), Type::Json)
+        | (Value::PyObjectWrapper(_), Type::PyObjectWrapper) => true,
+        _ => false,
+    }
+}
+
+/// Checks that the values parsed by the inner parser match the types of a registered schema.
+/// A mismatching value is reported as an error in its field, like the other parsing errors.
+pub struct SchemaValidatingParser {
+    inner: Box<dyn Parser>,
+    schema_name: String,
+    schema: Arc<NamedSchema>,
+    positions: Vec<usize>,
+}
+
+impl SchemaValidatingParser {
+    /// The `positions` are the positions of the columns of the schema among the values parsed
+    /// by the inner parser, as returned by [`NamedSchema::check_declared`].
+    pub fn new(
+        inner: Box<dyn Parser>,
+        schema_name: String,
+        schema: Arc<NamedSchema>,
+        positions: Vec<usize>,
+    ) -> Self {
+        Self {
+            inner,
+            schema_name,
+            schema,
+            positions,
+        }
+    }
+}
+
+impl Parser for SchemaValidatingParser {
+    fn parse(&mut self, data: &ReaderContext) -> ParseResult {
+        let mut events = self.inner.parse(data)?;
+        for event in &mut events {
+            let (ParsedEventWithErrors::Insert((_, values))
+            | ParsedEventWithErrors::Delete((_, values))) = event
+            else {
+                continue;
+            };
+            for (column, position) in self.schema.columns.iter().zip(&self.positions) {
+                let Some(Ok(value)) = values.get(*position) else {
+                    continue;
+                };
+                if !value_matches_type(value, &column.type_) {
+                    let error = ParseError::SchemaMismatch {
+                        schema_name: self.schema_name.clone(),
+                        field_name: column.name.clone(),
+                        type_: column.type_.clone(),
+                        value: value.clone(),
+                    };
+                    values[*position] = Err(error.into());
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    fn on_new_source_started(&mut self, metadata: &SourceMetadata) {
+        self.inner.on_new_source_started(metadata);
+    }
+
+    fn column_count(&self) -> usize {
+        self.inner.column_count()
+    }
+
+    fn short_description(&self) -> Cow<'static, str> {
+        self.inner.short_description()
+    }
+
+    fn session_type(&self) -> SessionType {
+        self.inner.session_type()
+    }
+
+    fn report_schema_drift(&mut self, source_name: &str) {
+        self.inner.report_schema_drift(source_name);
+    }
+}
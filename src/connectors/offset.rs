@@ -11,6 +11,7 @@ use xxhash_rust::xxh3::Xxh3 as Hasher;
 
 use crate::connectors::data_lake::iceberg::IcebergSnapshotId;
 use crate::engine::value::HashInto;
+use crate::engine::Value;
 use crate::persistence::cached_object_storage::CachedObjectVersion;
 
 fn os_str_to_bytes(os_str: &std::ffi::OsStr) -> Vec<u8> {
@@ -80,6 +81,11 @@ pub enum OffsetValue {
     NatsReadEntriesCount(usize),
     MqttReadEntriesCount(usize),
     Empty,
+    SubprocessReadEntriesCount(usize),
+    SqlPollingCursor {
+        high_water_mark: Value,
+        total_entries_read: u64,
+    },
 }
 
 impl OffsetValue {
@@ -148,12 +154,21 @@ impl HashInto for OffsetValue {
                 version.hash_into(hasher);
                 rows_read_within_version.hash_into(hasher);
             }
-            OffsetValue::NatsReadEntriesCount(count) | OffsetValue::MqttReadEntriesCount(count) => {
+            OffsetValue::NatsReadEntriesCount(count)
+            | OffsetValue::MqttReadEntriesCount(count)
+            | OffsetValue::SubprocessReadEntriesCount(count) => {
                 count.hash_into(hasher);
             }
             OffsetValue::IcebergSnapshot { snapshot_id } => {
                 snapshot_id.hash_into(hasher);
             }
+            OffsetValue::SqlPollingCursor {
+                high_water_mark,
+                total_entries_read,
+            } => {
+                high_water_mark.hash_into(hasher);
+                total_entries_read.hash_into(hasher);
+            }
             OffsetValue::Empty => {}
         }
     }
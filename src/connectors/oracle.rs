@@ -0,0 +1,230 @@
+// Copyright © 2024 Pathway
+
+//! Writing to an Oracle Database table.
+//!
+//! A batch is sent with the array DML, i.e. a single statement is executed for all the rows of
+//! the batch in one round trip: an `INSERT` for the stream of changes or a `MERGE` for the
+//! snapshot. The `MERGE` deletes the matched rows for which the change is a deletion.
+
+use ::oracle::sql_type::ToSql;
+use ::oracle::Connection;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use itertools::Itertools;
+
+use crate::connectors::data_format::FormatterContext;
+use crate::connectors::data_storage::{WriteError, Writer};
+use crate::connectors::sql_merge::{SqlRow, SqlSinkBuffer, SqlSinkError, SqlSinkMode};
+use crate::engine::time::DateTime as _;
+use crate::engine::{Type, Value};
+use crate::python_api::ValueField;
+use crate::reload;
+
+const DATABASE_NAME: &str = "Oracle";
+
+fn placeholders(count: usize) -> impl Iterator<Item = String> {
+    (1..=count).map(|index| format!(":{index}"))
+}
+
+pub fn insert_statement(table_name: &str, value_field_names: &[String]) -> String {
+    format!(
+        "INSERT INTO {table_name} ({},time,diff) VALUES ({})",
+        value_field_names.iter().join(","),
+        placeholders(value_field_names.len() + 2).join(",")
+    )
+}
+
+pub fn merge_statement(
+    table_name: &str,
+    value_field_names: &[String],
+    key_field_names: &[&str],
+) -> String {
+    let all_columns: Vec<&str> = value_field_names
+        .iter()
+        .map(String::as_str)
+        .chain(["time", "diff"])
+        .collect();
+    let source_columns = all_columns
+        .iter()
+        .zip(placeholders(all_columns.len()))
+        .map(|(name, placeholder)| format!("{placeholder} AS {name}"))
+        .join(",");
+    let on_condition = key_field_names
+        .iter()
+        .map(|name| format!("target.{name}=source.{name}"))
+        .join(" AND ");
+    let update_pairs = all_columns
+        .iter()
+        .filter(|name| !key_field_names.contains(name))
+        .map(|name| format!("target.{name}=source.{name}"))
+        .join(",");
+    format!(
+        "MERGE INTO {table_name} target USING (SELECT {source_columns} FROM dual) source \
+         ON ({on_condition}) \
+         WHEN MATCHED THEN UPDATE SET {update_pairs} DELETE WHERE source.diff<0 \
+         WHEN NOT MATCHED THEN INSERT ({}) VALUES ({}) WHERE source.diff>0",
+        all_columns.iter().join(","),
+        all_columns
+            .iter()
+            .map(|name| format!("source.{name}"))
+            .join(",")
+    )
+}
+
+/// A value bound to a statement. The nulls are bound with the type of their column, since the
+/// type of a position is fixed for all the rows of a batch.
+enum OracleParam {
+    Int(Option<i64>),
+    Float(Option<f64>),
+    Text(Option<String>),
+    Raw(Option<Vec<u8>>),
+    Timestamp(Option<NaiveDateTime>),
+    TimestampUtc(Option<DateTime<Utc>>),
+}
+
+impl OracleParam {
+    fn null_of_type(type_: &Type) -> Self {
+        match type_.unoptionalize() {
+            Type::Bool | Type::Int | Type::Duration => Self::Int(None),
+            Type::Float => Self::Float(None),
+            Type::Bytes => Self::Raw(None),
+            Type::DateTimeNaive => Self::Timestamp(None),
+            Type::DateTimeUtc => Self::TimestampUtc(None),
+            _ => Self::Text(None),
+        }
+    }
+
+    fn new(value: &Value, type_: &Type) -> Result<Self, SqlSinkError> {
+        let param = match value {
+            Value::None => Self::null_of_type(type_),
+            // Oracle has no boolean type in SQL before 23ai
+            Value::Bool(b) => Self::Int(Some(i64::from(*b))),
+            Value::Int(i) => Self::Int(Some(*i)),
+            Value::Float(f) => Self::Float(Some(f.into_inner())),
+            Value::String(s) => Self::Text(Some(s.to_string())),
+            Value::Pointer(p) => Self::Text(Some(p.to_string())),
+            Value::Json(j) => Self::Text(Some(j.to_string())),
+            Value::Bytes(b) => Self::Raw(Some(b.to_vec())),
+            Value::DateTimeNaive(dt) => Self::Timestamp(Some(dt.as_chrono_datetime())),
+            Value::DateTimeUtc(dt) => Self::TimestampUtc(Some(dt.as_chrono_datetime().and_utc())),
+            Value::Duration(d) => Self::Int(Some(d.microseconds())),
+            other => return Err(SqlSinkError::UnsupportedValue(other.kind(), DATABASE_NAME)),
+        };
+        Ok(param)
+    }
+
+    fn as_to_sql(&self) -> &dyn ToSql {
+        match self {
+            Self::Int(value) => value,
+            Self::Float(value) => value,
+            Self::Text(value) => value,
+            Self::Raw(value) => value,
+            Self::Timestamp(value) => value,
+            Self::TimestampUtc(value) => value,
+        }
+    }
+}
+
+pub struct OracleWriter {
+    connection: Connection,
+    buffer: SqlSinkBuffer,
+    value_types: Vec<Type>,
+    max_batch_size: Option<usize>,
+}
+
+impl OracleWriter {
+    pub fn new(
+        connection: Connection,
+        table_name: String,
+        value_fields: &[ValueField],
+        mode: &SqlSinkMode,
+        max_batch_size: Option<usize>,
+    ) -> Result<Self, WriteError> {
+        let buffer = SqlSinkBuffer::new(
+            table_name,
+            value_fields
+                .iter()
+                .map(|field| field.name.clone())
+                .collect(),
+            mode,
+        )?;
+        Ok(Self {
+            connection,
+            buffer,
+            value_types: value_fields
+                .iter()
+                .map(|field| field.type_.clone())
+                .collect(),
+            max_batch_size,
+        })
+    }
+
+    fn params(&self, row: &SqlRow) -> Result<Vec<OracleParam>, WriteError> {
+        let mut params: Vec<_> = row
+            .values
+            .iter()
+            .zip(self.value_types.iter())
+            .map(|(value, type_)| OracleParam::new(value, type_))
+            .try_collect()?;
+        params.push(OracleParam::Int(Some(row.time_as_i64()?)));
+        params.push(OracleParam::Int(Some(
+            i64::try_from(row.diff).expect("diff should fit into i64"),
+        )));
+        Ok(params)
+    }
+
+    fn statement(&self) -> String {
+        match self.buffer.key_field_names() {
+            Some(key_field_names) => merge_statement(
+                &self.buffer.table_name,
+                &self.buffer.value_field_names,
+                &key_field_names,
+            ),
+            None => insert_statement(&self.buffer.table_name, &self.buffer.value_field_names),
+        }
+    }
+
+    fn execute_batch(&self, rows: &[SqlRow]) -> Result<(), WriteError> {
+        let statement = self.statement();
+        let mut batch = self.connection.batch(&statement, rows.len()).build()?;
+        for row in rows {
+            let params = self.params(row)?;
+            let params: Vec<_> = params.iter().map(OracleParam::as_to_sql).collect();
+            batch.append_row(&params)?;
+        }
+        batch.execute()?;
+        Ok(())
+    }
+}
+
+impl Writer for OracleWriter {
+    fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
+        self.buffer.push(data);
+        if let Some(max_batch_size) = reload::max_batch_size(self.max_batch_size) {
+            if self.buffer.len() >= max_batch_size {
+                self.flush(true)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, _forced: bool) -> Result<(), WriteError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let rows = self.buffer.take_rows();
+        if let Err(e) = self.execute_batch(&rows) {
+            self.connection.rollback()?;
+            return Err(e);
+        }
+        self.connection.commit()?;
+        Ok(())
+    }
+
+    fn name(&self) -> String {
+        format!("Oracle({})", self.buffer.table_name)
+    }
+
+    fn single_threaded(&self) -> bool {
+        self.buffer.key_field_positions.is_some()
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright © 2024 Pathway
+
+//! Writing the output of a table into a sequence of Parquet files.
+//!
+//! The files are rotated and named in the same way as the rotated files of the plain file sink,
+//! see [`crate::connectors::file_rotation`]. Each file has the columns of the table followed by
+//! the `time` and `diff` columns of the updates. As a Parquet file can be read only once its
+//! footer is written, a file gets its final name when it's closed, either because it has
+//! reached the size limit, because its rotation period is over, or because the computation has
+//! ended.
+
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arcstr::ArcStr;
+use chrono::{DateTime, Utc};
+use deltalake::arrow::array::{ArrayRef, RecordBatch};
+use deltalake::arrow::datatypes::{
+    DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema,
+};
+use deltalake::parquet::arrow::ArrowWriter;
+use deltalake::parquet::file::properties::WriterProperties;
+
+use crate::connectors::data_format::FormatterContext;
+use crate::connectors::data_lake::arrow::{array_for_type, arrow_data_type};
+use crate::connectors::data_lake::LakeWriterSettings;
+use crate::connectors::data_storage::{WriteError, Writer};
+use crate::connectors::file_rotation::{FileRotationConfig, RotationState};
+use crate::connectors::{SPECIAL_FIELD_DIFF, SPECIAL_FIELD_TIME};
+use crate::engine::{Timestamp, Type, Value};
+
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 100_000;
+
+struct OpenParquetFile {
+    writer: ArrowWriter<File>,
+    in_progress_path: PathBuf,
+    path: PathBuf,
+}
+
+/// Writes the updates of a table into the Parquet files within `directory`, rotating them as
+/// configured in the [`FileRotationConfig`]. The updates are buffered until the flush, and the
+/// rows of a file are grouped into the row groups of at most `row_group_size` rows.
+pub struct ParquetFileWriter {
+    rotation: RotationState,
+    schema: Arc<ArrowSchema>,
+    arrow_types: Vec<ArrowDataType>,
+    row_group_size: usize,
+    buffered_rows: Vec<(Vec<Value>, Timestamp, isize)>,
+    current_file: Option<OpenParquetFile>,
+}
+
+impl ParquetFileWriter {
+    pub fn new(
+        directory: PathBuf,
+        config: FileRotationConfig,
+        column_names: &[String],
+        dtypes: &[Type],
+        row_group_size: usize,
+    ) -> Result<Self, WriteError> {
+        let settings = LakeWriterSettings {
+            use_64bit_size_type: false,
+            utc_timezone_name: ArcStr::from("UTC"),
+        };
+        let mut fields = Vec::with_capacity(column_names.len() + 2);
+        let mut arrow_types = Vec::with_capacity(column_names.len());
+        for (name, dtype) in column_names.iter().zip(dtypes) {
+            let arrow_type = arrow_data_type(dtype, &settings)?;
+            fields.push(ArrowField::new(
+                name,
+                arrow_type.clone(),
+                dtype.can_be_none(),
+            ));
+            arrow_types.push(arrow_type);
+        }
+        fields.push(ArrowField::new(
+            SPECIAL_FIELD_TIME,
+            ArrowDataType::Int64,
+            false,
+        ));
+        fields.push(ArrowField::new(
+            SPECIAL_FIELD_DIFF,
+            ArrowDataType::Int64,
+            false,
+        ));
+        Ok(Self {
+            rotation: RotationState::new(directory, config)?,
+            schema: Arc::new(ArrowSchema::new(fields)),
+            arrow_types,
+            row_group_size: row_group_size.max(1),
+            buffered_rows: Vec::new(),
+            current_file: None,
+        })
+    }
+
+    fn open_file(&mut self, now: DateTime<Utc>) -> Result<&mut OpenParquetFile, WriteError> {
+        if self.current_file.is_none() {
+            let (path, in_progress_path) = self.rotation.next_file_paths(now)?;
+            let properties = WriterProperties::builder()
+                .set_max_row_group_size(self.row_group_size)
+                .build();
+            let writer = ArrowWriter::try_new(
+                File::create(&in_progress_path)?,
+                self.schema.clone(),
+                Some(properties),
+            )?;
+            self.current_file = Some(OpenParquetFile {
+                writer,
+                in_progress_path,
+                path,
+            });
+        }
+        Ok(self
+            .current_file
+            .as_mut()
+            .expect("the file must be open at this point"))
+    }
+
+    fn finalize_current_file(&mut self) -> Result<(), WriteError> {
+        if let Some(mut file) = self.current_file.take() {
+            file.writer.finish()?;
+            file.writer.inner().sync_all()?;
+            self.rotation
+                .complete_file(&file.in_progress_path, &file.path)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered rows, which belong to the current period, and completes it.
+    fn finish_period(&mut self, now: DateTime<Utc>) -> Result<(), WriteError> {
+        self.write_buffered_rows(now)?;
+        self.finalize_current_file()?;
+        self.rotation.finish_period()
+    }
+
+    fn finish_period_if_over(&mut self, now: DateTime<Utc>) -> Result<(), WriteError> {
+        if self.rotation.is_period_over(now) {
+            self.finish_period(now)?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn record_batch(
+        &self,
+        rows: &[(Vec<Value>, Timestamp, isize)],
+    ) -> Result<RecordBatch, WriteError> {
+        let mut arrays: Vec<ArrayRef> = Vec::with_capacity(self.arrow_types.len() + 2);
+        for (index, arrow_type) in self.arrow_types.iter().enumerate() {
+            let values: Vec<Value> = rows
+                .iter()
+                .map(|(values, _, _)| values[index].clone())
+                .collect();
+            arrays.push(array_for_type(arrow_type, &values)?);
+        }
+        let times: Vec<Value> = rows
+            .iter()
+            .map(|(_, time, _)| Value::Int(time.0 as i64))
+            .collect();
+        arrays.push(array_for_type(&ArrowDataType::Int64, &times)?);
+        let diffs: Vec<Value> = rows
+            .iter()
+            .map(|(_, _, diff)| Value::Int(*diff as i64))
+            .collect();
+        arrays.push(array_for_type(&ArrowDataType::Int64, &diffs)?);
+        Ok(RecordBatch::try_new(self.schema.clone(), arrays)?)
+    }
+
+    fn write_buffered_rows(&mut self, now: DateTime<Utc>) -> Result<(), WriteError> {
+        if self.buffered_rows.is_empty() {
+            return Ok(());
+        }
+        let rows = std::mem::take(&mut self.buffered_rows);
+        let batch = self.record_batch(&rows)?;
+        let max_file_size = self.rotation.config().max_file_size;
+        let file = self.open_file(now)?;
+        file.writer.write(&batch)?;
+        // The size includes the encoded rows that are not yet flushed to the file
+        let size = (file.writer.bytes_written() + file.writer.in_progress_size()) as u64;
+        if max_file_size.is_some_and(|max_file_size| size >= max_file_size) {
+            self.finalize_current_file()?;
+        }
+        Ok(())
+    }
+}
+
+impl Writer for ParquetFileWriter {
+    fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
+        self.finish_period_if_over(Utc::now())?;
+        self.buffered_rows.push((data.values, data.time, data.diff));
+        Ok(())
+    }
+
+    fn flush(&mut self, _forced: bool) -> Result<(), WriteError> {
+        let now = Utc::now();
+        self.finish_period_if_over(now)?;
+        self.write_buffered_rows(now)
+    }
+
+    fn on_time_committed(&mut self, time: Option<Timestamp>) -> Result<(), WriteError> {
+        if time.is_none() {
+            self.finish_period(Utc::now())?;
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> String {
+        format!("Parquet({})", self.rotation.directory().display())
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright © 2024 Pathway
+
+//! Pausing the connectors of the running computation, e.g. to stop the ingestion while the
+//! downstream systems are under maintenance, without stopping the computation and losing its
+//! state.
+//!
+//! A paused source stops reading new entries, while the ones that are already read are
+//! processed and committed as usual. A paused sink finishes writing the batch of the current
+//! time and then holds the further batches in memory until it is resumed. The connectors are
+//! identified by their names, so all the workers of the process running the same connector
+//! share a single [`PauseSwitch`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::time::Duration;
+
+use log::info;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+/// How often the paused connectors check if they should stop waiting.
+const PAUSE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PauseError {
+    #[error("connector {0:?} is not running")]
+    UnknownConnector(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectorKind {
+    Source,
+    Sink,
+}
+
+/// The state of a running connector, as reported by [`connector_states`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ConnectorState {
+    pub name: String,
+    pub kind: ConnectorKind,
+    pub paused: bool,
+}
+
+pub struct PauseSwitch {
+    name: String,
+    kind: ConnectorKind,
+    paused: Mutex<bool>,
+    resumed: Condvar,
+}
+
+impl PauseSwitch {
+    fn new(name: &str, kind: ConnectorKind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            paused: Mutex::new(false),
+            resumed: Condvar::new(),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.lock().unwrap()
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        let mut current = self.paused.lock().unwrap();
+        if *current != paused {
+            info!(
+                "{:?} {:?} is {}",
+                self.kind,
+                self.name,
+                if paused { "paused" } else { "resumed" }
+            );
+        }
+        *current = paused;
+        self.resumed.notify_all();
+    }
+
+    /// Blocks the calling thread while the connector is paused. The `should_stop` condition is
+    /// checked periodically, and once it holds, the thread is released even if the connector is
+    /// still paused.
+    pub fn wait_while_paused(&self, should_stop: impl Fn() -> bool) {
+        let mut paused = self.paused.lock().unwrap();
+        while *paused && !should_stop() {
+            paused = self
+                .resumed
+                .wait_timeout(paused, PAUSE_CHECK_INTERVAL)
+                .unwrap()
+                .0;
+        }
+    }
+
+    fn state(&self) -> ConnectorState {
+        ConnectorState {
+            name: self.name.clone(),
+            kind: self.kind,
+            paused: self.is_paused(),
+        }
+    }
+}
+
+static PAUSE_SWITCHES: Lazy<Mutex<HashMap<String, Weak<PauseSwitch>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the switch shared by all the callers using the same name, e.g. all the workers
+/// reading from the same source. It is created unpaused if there is none yet, and dropped once
+/// the last caller drops it.
+pub fn shared_pause_switch(name: &str, kind: ConnectorKind) -> Arc<PauseSwitch> {
+    let mut pause_switches = PAUSE_SWITCHES.lock().unwrap();
+    if let Some(pause_switch) = pause_switches.get(name).and_then(Weak::upgrade) {
+        return pause_switch;
+    }
+    let pause_switch = Arc::new(PauseSwitch::new(name, kind));
+    pause_switches.insert(name.to_string(), Arc::downgrade(&pause_switch));
+    pause_switch
+}
+
+/// Pauses or resumes the running connector with the given name.
+pub fn set_connector_paused(name: &str, paused: bool) -> Result<ConnectorState, PauseError> {
+    let pause_switch = PAUSE_SWITCHES
+        .lock()
+        .unwrap()
+        .get(name)
+        .and_then(Weak::upgrade)
+        .ok_or_else(|| PauseError::UnknownConnector(name.to_string()))?;
+    pause_switch.set_paused(paused);
+    Ok(pause_switch.state())
+}
+
+/// The states of the running connectors that can be paused, ordered by their names.
+pub fn connector_states() -> Vec<ConnectorState> {
+    let mut pause_switches = PAUSE_SWITCHES.lock().unwrap();
+    pause_switches.retain(|_, pause_switch| pause_switch.strong_count() > 0);
+    let mut states: Vec<_> = pause_switches
+        .values()
+        .filter_map(|pause_switch| Some(pause_switch.upgrade()?.state()))
+        .collect();
+    states.sort_by(|a, b| a.name.cmp(&b.name));
+    states
+}
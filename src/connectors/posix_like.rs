@@ -11,6 +11,7 @@ use std::time::Duration;
 
 use crate::connectors::data_storage::ConnectorMode;
 use crate::connectors::data_tokenize::Tokenize;
+use crate::connectors::monitoring::BackfillProgressTracker;
 use crate::connectors::scanner::{PosixLikeScanner, QueuedAction};
 use crate::connectors::{
     DataEventType, OffsetKey, OffsetValue, ReadError, ReadResult, Reader, StorageType,
@@ -48,6 +49,7 @@ pub struct PosixLikeReader {
     cached_object_storage: CachedObjectStorage,
     current_action: Option<CurrentAction>,
     scanner_actions_queue: VecDeque<QueuedAction>,
+    backfill_progress: BackfillProgressTracker,
 }
 
 impl PosixLikeReader {
@@ -70,6 +72,7 @@ impl PosixLikeReader {
             current_action: None,
             scanner_actions_queue: VecDeque::new(),
             cached_object_storage: CachedObjectStorage::new(Box::new(MockKVStorage {}))?,
+            backfill_progress: BackfillProgressTracker::default(),
         })
     }
 }
@@ -137,6 +140,10 @@ impl Reader for PosixLikeReader {
         Ok(())
     }
 
+    fn track_backfill_progress(&mut self, tracker: BackfillProgressTracker) {
+        self.backfill_progress = tracker;
+    }
+
     fn read(&mut self) -> Result<ReadResult, ReadError> {
         // Try to continue to read the current object.
         let maybe_entry = self.tokenizer.next_entry()?;
@@ -185,7 +192,8 @@ impl PosixLikeReader {
                         .push_front(QueuedAction::Read(path, metadata));
                     false
                 }
-                QueuedAction::Read(path, _) => {
+                QueuedAction::Read(path, metadata) => {
+                    self.backfill_progress.on_object_processed(metadata.size);
                     let are_deletions_enabled = self.are_deletions_enabled();
                     if !self.is_persisted && !are_deletions_enabled {
                         // Don't store a copy in memory if it won't be
@@ -257,6 +265,11 @@ impl PosixLikeReader {
                     return Ok(Some(result));
                 }
                 None => {
+                    // The objects found by the first complete listing of the source are
+                    // the backfill, the ones found later are the regular updates
+                    if self.had_queue_refresh && !self.scanner.has_pending_actions() {
+                        self.backfill_progress.finish();
+                    }
                     if self.streaming_mode.is_polling_enabled()
                         || !self.had_queue_refresh
                         || self.scanner.has_pending_actions()
@@ -267,6 +280,11 @@ impl PosixLikeReader {
                             &self.cached_object_storage,
                         )?;
                         for action in new_actions {
+                            if let QueuedAction::Read(_, metadata)
+                            | QueuedAction::Update(_, metadata) = &action
+                            {
+                                self.backfill_progress.on_object_discovered(metadata.size);
+                            }
                             self.scanner_actions_queue.push_back(action);
                         }
                         if self.scanner_actions_queue.is_empty() {
@@ -0,0 +1,276 @@
+// Copyright © 2024 Pathway
+
+//! Writing the numeric columns of a table as the samples of Prometheus time series, with the
+//! [remote-write protocol](https://prometheus.io/docs/specs/remote_write_spec/). It is accepted
+//! by Prometheus itself as well as by most of the compatible storages, such as Mimir, Cortex,
+//! Thanos or VictoriaMetrics.
+//!
+//! Every numeric column configured as a metric becomes a series whose labels are taken from
+//! the label columns of the row, and each inserted row adds a sample to each of these series.
+//! A time series can't be updated retroactively, so the deletions are not sent. The samples
+//! are buffered and pushed in a single request on each flush.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use prost::Message;
+use reqwest::blocking::Client as HttpClient;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_ENCODING, CONTENT_TYPE};
+use reqwest::StatusCode;
+
+use crate::connectors::data_format::FormatterContext;
+use crate::connectors::data_storage::{WriteError, Writer};
+use crate::engine::Value;
+
+const METRIC_NAME_LABEL: &str = "__name__";
+const REMOTE_WRITE_VERSION: &str = "0.1.0";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PrometheusError {
+    #[error("failed to send the samples: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("the samples were rejected with status {status}: {body}")]
+    Rejected { status: StatusCode, body: String },
+
+    #[error("value {0} of the metric {1:?} is not a number")]
+    NotANumber(Value, String),
+
+    #[error("value {0} can't be used as the timestamp of a sample")]
+    IncorrectTimestamp(Value),
+
+    #[error("invalid HTTP header {0:?}")]
+    InvalidHeader(String),
+}
+
+impl PrometheusError {
+    /// The samples that are rejected because they are malformed or out of order would be
+    /// rejected again, so only the server errors and the throttled requests are retried.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Request(_) => true,
+            Self::Rejected { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::NotANumber(..) | Self::IncorrectTimestamp(_) | Self::InvalidHeader(_) => false,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct WriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    timeseries: Vec<TimeSeries>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TimeSeries {
+    #[prost(message, repeated, tag = "1")]
+    labels: Vec<Label>,
+    #[prost(message, repeated, tag = "2")]
+    samples: Vec<Sample>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Label {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    value: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Sample {
+    #[prost(double, tag = "1")]
+    value: f64,
+    #[prost(int64, tag = "2")]
+    timestamp: i64,
+}
+
+/// The labels of a series, including the metric name, ordered by the label names as the
+/// protocol requires.
+type SeriesLabels = Vec<(String, String)>;
+
+pub struct PrometheusWriter {
+    client: HttpClient,
+    url: String,
+    headers: HeaderMap,
+    metric_fields: Vec<(String, usize)>,
+    label_fields: Vec<(String, usize)>,
+    timestamp_field_index: Option<usize>,
+    pending_series: BTreeMap<SeriesLabels, Vec<Sample>>,
+}
+
+impl PrometheusWriter {
+    /// The `metric_fields` and `label_fields` are the pairs of the names of the metrics and
+    /// labels and the indices of the columns their values are taken from. If there is no
+    /// `timestamp_field_index`, the samples are timestamped with the time they are written at.
+    pub fn new(
+        url: String,
+        headers: &[(String, String)],
+        metric_fields: Vec<(String, usize)>,
+        label_fields: Vec<(String, usize)>,
+        timestamp_field_index: Option<usize>,
+    ) -> Result<Self, PrometheusError> {
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            let name = HeaderName::try_from(name)
+                .map_err(|_| PrometheusError::InvalidHeader(name.clone()))?;
+            let value = HeaderValue::try_from(value)
+                .map_err(|_| PrometheusError::InvalidHeader(name.to_string()))?;
+            header_map.insert(name, value);
+        }
+        header_map.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-protobuf"),
+        );
+        header_map.insert(CONTENT_ENCODING, HeaderValue::from_static("snappy"));
+        header_map.insert(
+            "x-prometheus-remote-write-version",
+            HeaderValue::from_static(REMOTE_WRITE_VERSION),
+        );
+        let client = HttpClient::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self {
+            client,
+            url,
+            headers: header_map,
+            metric_fields,
+            label_fields,
+            timestamp_field_index,
+            pending_series: BTreeMap::new(),
+        })
+    }
+
+    fn sample_timestamp(&self, values: &[Value]) -> Result<i64, PrometheusError> {
+        let Some(index) = self.timestamp_field_index else {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Failed to get the current timestamp");
+            return Ok(now.as_millis().try_into().unwrap_or(i64::MAX));
+        };
+        // The time columns are stored in nanoseconds, while the samples use milliseconds
+        match &values[index] {
+            Value::DateTimeUtc(dt) => Ok(dt.timestamp() / 1_000_000),
+            Value::DateTimeNaive(dt) => Ok(dt.timestamp() / 1_000_000),
+            Value::Int(timestamp_ms) => Ok(*timestamp_ms),
+            value => Err(PrometheusError::IncorrectTimestamp(value.clone())),
+        }
+    }
+
+    fn label_value(value: &Value) -> Option<String> {
+        match value {
+            Value::None => None,
+            Value::String(s) => Some(s.to_string()),
+            value => Some(value.to_string()),
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn sample_value(value: &Value, metric: &str) -> Result<Option<f64>, PrometheusError> {
+        match value {
+            Value::None => Ok(None),
+            Value::Int(i) => Ok(Some(*i as f64)),
+            Value::Float(f) => Ok(Some(f.into_inner())),
+            Value::Bool(b) => Ok(Some(if *b { 1.0 } else { 0.0 })),
+            value => Err(PrometheusError::NotANumber(
+                value.clone(),
+                metric.to_string(),
+            )),
+        }
+    }
+
+    fn write_request(&self) -> WriteRequest {
+        let timeseries = self
+            .pending_series
+            .iter()
+            .map(|(labels, samples)| {
+                let mut samples = samples.clone();
+                samples.sort_by_key(|sample| sample.timestamp);
+                TimeSeries {
+                    labels: labels
+                        .iter()
+                        .map(|(name, value)| Label {
+                            name: name.clone(),
+                            value: value.clone(),
+                        })
+                        .collect(),
+                    samples,
+                }
+            })
+            .collect();
+        WriteRequest { timeseries }
+    }
+
+    fn push(&self, request: &WriteRequest) -> Result<(), PrometheusError> {
+        let body = snap::raw::Encoder::new()
+            .compress_vec(&request.encode_to_vec())
+            .expect("snappy compression of a buffer in memory can't fail");
+        let response = self
+            .client
+            .post(&self.url)
+            .headers(self.headers.clone())
+            .body(body)
+            .send()?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            let body = response.text().unwrap_or_default();
+            Err(PrometheusError::Rejected { status, body })
+        }
+    }
+}
+
+impl Writer for PrometheusWriter {
+    fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
+        if data.diff < 0 {
+            // The samples already sent can't be retracted
+            return Ok(());
+        }
+        let timestamp = self.sample_timestamp(&data.values)?;
+        let labels: SeriesLabels = self
+            .label_fields
+            .iter()
+            .filter_map(|(name, index)| {
+                Some((name.clone(), Self::label_value(&data.values[*index])?))
+            })
+            .collect();
+        for (metric, index) in &self.metric_fields {
+            let Some(value) = Self::sample_value(&data.values[*index], metric)? else {
+                continue;
+            };
+            let mut series_labels = labels.clone();
+            series_labels.push((METRIC_NAME_LABEL.to_string(), metric.clone()));
+            series_labels.sort();
+            self.pending_series
+                .entry(series_labels)
+                .or_default()
+                .push(Sample { value, timestamp });
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, _forced: bool) -> Result<(), WriteError> {
+        if self.pending_series.is_empty() {
+            return Ok(());
+        }
+        // The samples are kept until they are accepted, so that they are sent again on retry
+        self.push(&self.write_request())?;
+        self.pending_series.clear();
+        Ok(())
+    }
+
+    fn name(&self) -> String {
+        format!("Prometheus({})", self.url)
+    }
+
+    fn retriable(&self) -> bool {
+        true
+    }
+
+    fn single_threaded(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright © 2024 Pathway
+
+//! Filtering and projection of the output entries done by the sink itself.
+//!
+//! The sink receives the full rows of the table. The rows not matching the predicate are
+//! skipped before they are formatted, and the remaining ones are narrowed down to the selected
+//! columns, so that neither a filtered nor a projected copy of the table has to be maintained
+//! only to be written.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+use crate::connectors::data_format::{Formatter, FormatterContext, FormatterError};
+use crate::engine::{Key, Timestamp, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComparisonOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl ComparisonOp {
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        match self {
+            Self::Eq => ordering == Some(Ordering::Equal),
+            Self::Ne => ordering != Some(Ordering::Equal),
+            Self::Lt => ordering == Some(Ordering::Less),
+            Self::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Self::Gt => ordering == Some(Ordering::Greater),
+            Self::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+/// The integers and the floats are compared by their numeric values. The values of the other
+/// different kinds aren't comparable, so only the inequality holds for them. In particular,
+/// a null is only equal to a null.
+#[allow(clippy::cast_precision_loss)]
+fn compare_values(lhs: &Value, rhs: &Value) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Value::None, Value::None) => Some(Ordering::Equal),
+        (Value::Int(lhs), Value::Float(rhs)) => (*lhs as f64).partial_cmp(&rhs.into_inner()),
+        (Value::Float(lhs), Value::Int(rhs)) => lhs.into_inner().partial_cmp(&(*rhs as f64)),
+        (lhs, rhs) if lhs.kind() == rhs.kind() => Some(lhs.cmp(rhs)),
+        _ => None,
+    }
+}
+
+/// A condition on the values of a row, referring to the columns by their positions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SinkPredicate {
+    Compare {
+        column_index: usize,
+        op: ComparisonOp,
+        value: Value,
+    },
+    IsNone(usize),
+    And(Vec<SinkPredicate>),
+    Or(Vec<SinkPredicate>),
+    Not(Box<SinkPredicate>),
+}
+
+impl SinkPredicate {
+    pub fn matches(&self, values: &[Value]) -> Result<bool, FormatterError> {
+        let column = |index: usize| {
+            values
+                .get(index)
+                .ok_or(FormatterError::IncorrectColumnIndex)
+        };
+        match self {
+            Self::Compare {
+                column_index,
+                op,
+                value,
+            } => Ok(op.holds(compare_values(column(*column_index)?, value))),
+            Self::IsNone(column_index) => Ok(matches!(column(*column_index)?, Value::None)),
+            Self::And(predicates) => {
+                for predicate in predicates {
+                    if !predicate.matches(values)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Self::Or(predicates) => {
+                for predicate in predicates {
+                    if predicate.matches(values)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Self::Not(predicate) => Ok(!predicate.matches(values)?),
+        }
+    }
+}
+
+/// Skips the rows not matching the predicate and passes the selected columns of the other ones
+/// to the wrapped formatter.
+pub struct PushdownFormatter {
+    inner: Box<dyn Formatter>,
+    predicate: Option<SinkPredicate>,
+    projection: Option<Vec<usize>>,
+}
+
+impl PushdownFormatter {
+    pub fn new(
+        inner: Box<dyn Formatter>,
+        predicate: Option<SinkPredicate>,
+        projection: Option<Vec<usize>>,
+    ) -> Self {
+        Self {
+            inner,
+            predicate,
+            projection,
+        }
+    }
+}
+
+impl Formatter for PushdownFormatter {
+    fn format(
+        &mut self,
+        key: &Key,
+        values: &[Value],
+        time: Timestamp,
+        diff: isize,
+    ) -> Result<FormatterContext, FormatterError> {
+        let Some(projection) = &self.projection else {
+            return self.inner.format(key, values, time, diff);
+        };
+        let projected_values: Vec<_> = projection
+            .iter()
+            .map(|index| {
+                values
+                    .get(*index)
+                    .cloned()
+                    .ok_or(FormatterError::IncorrectColumnIndex)
+            })
+            .collect::<Result<_, _>>()?;
+        self.inner.format(key, &projected_values, time, diff)
+    }
+
+    fn is_filtered_out(&self, values: &[Value]) -> Result<bool, FormatterError> {
+        match &self.predicate {
+            Some(predicate) => Ok(!predicate.matches(values)?),
+            None => Ok(false),
+        }
+    }
+
+    fn short_description(&self) -> Cow<'static, str> {
+        self.inner.short_description()
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright © 2024 Pathway
+
+//! Serving the current contents of output tables over HTTP, so that they can be queried without
+//! copying them to a database first.
+//!
+//! A table is kept in memory by its [`QueryEndpointWriter`], which applies all updates of a time
+//! at once when the time is committed, so the queries never see a partially updated table. The
+//! tables are available at `GET /tables/{name}`, which returns the rows as a JSON array of
+//! objects with the row key in the `id` field. The rows can be selected with the `id=<key>` and
+//! `<column>=<value>` parameters, where the value is compared with the JSON representation of
+//! the column value, with strings unquoted, and their number limited with `limit=<n>`.
+//!
+//! All tables served at the same address share one server thread, started with the first one.
+
+use std::collections::HashMap;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::Builder;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use log::{error, info};
+use once_cell::sync::Lazy;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::connectors::data_format::{serialize_value_to_json, FormatterContext};
+use crate::connectors::data_storage::{WriteError, Writer};
+use crate::engine::value::parse_pathway_pointer;
+use crate::engine::{Key, Value};
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum QueryEndpointError {
+    #[error("failed to serve tables at {0}: {1}")]
+    Bind(SocketAddr, #[source] std::io::Error),
+
+    #[error("table {0:?} is already served at {1}")]
+    AlreadyServed(String, SocketAddr),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum QueryError {
+    #[error("invalid id {0:?}")]
+    InvalidId(String),
+
+    #[error("invalid limit {0:?}")]
+    InvalidLimit(String),
+
+    #[error("unknown column {0:?}")]
+    UnknownColumn(String),
+}
+
+/// The current rows of a served table.
+#[derive(Debug)]
+pub struct MaterializedTable {
+    column_names: Vec<String>,
+    rows: HashMap<Key, Vec<Value>>,
+}
+
+impl MaterializedTable {
+    pub fn new(column_names: Vec<String>) -> Self {
+        Self {
+            column_names,
+            rows: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn column_names(&self) -> &[String] {
+        &self.column_names
+    }
+
+    /// The current rows, in no particular order.
+    pub fn rows(&self) -> impl Iterator<Item = (&Key, &Vec<Value>)> {
+        self.rows.iter()
+    }
+
+    /// Applies the updates of a single time.
+    pub fn apply(&mut self, mut updates: Vec<(Key, Vec<Value>, isize)>) {
+        // deletions first, so that an updated row doesn't remove its new version
+        updates.sort_by_key(|(_, _, diff)| *diff);
+        for (key, values, diff) in updates {
+            if diff > 0 {
+                self.rows.insert(key, values);
+            } else {
+                self.rows.remove(&key);
+            }
+        }
+    }
+
+    /// The rows selected by the query parameters, ordered by their keys.
+    pub fn query(&self, parameters: &[(String, String)]) -> Result<JsonValue, QueryError> {
+        let mut key = None;
+        let mut limit = usize::MAX;
+        let mut filters = Vec::new();
+        for (name, value) in parameters {
+            match name.as_str() {
+                "id" => {
+                    // the parser expects the leading `^` of the formatted keys without checking it
+                    let pointer = value.starts_with('^').then(|| parse_pathway_pointer(value));
+                    match pointer {
+                        Some(Ok(Value::Pointer(pointer))) => key = Some(pointer),
+                        _ => return Err(QueryError::InvalidId(value.clone())),
+                    }
+                }
+                "limit" => {
+                    limit = value
+                        .parse()
+                        .map_err(|_| QueryError::InvalidLimit(value.clone()))?;
+                }
+                column => {
+                    let index = self
+                        .column_names
+                        .iter()
+                        .position(|name| name == column)
+                        .ok_or_else(|| QueryError::UnknownColumn(column.to_string()))?;
+                    filters.push((index, value.as_str()));
+                }
+            }
+        }
+
+        let mut rows: Vec<_> = match key {
+            Some(key) => self.rows.get_key_value(&key).into_iter().collect(),
+            None => self.rows.iter().collect(),
+        };
+        rows.retain(|(_, values)| {
+            filters
+                .iter()
+                .all(|(index, expected)| value_matches(&values[*index], expected))
+        });
+        rows.sort_unstable_by_key(|(key, _)| **key);
+        Ok(rows
+            .into_iter()
+            .take(limit)
+            .map(|(key, values)| self.row_to_json(*key, values))
+            .collect())
+    }
+
+    fn row_to_json(&self, key: Key, values: &[Value]) -> JsonValue {
+        let mut row = JsonMap::new();
+        row.insert("id".to_string(), JsonValue::String(key.to_string()));
+        for (name, value) in self.column_names.iter().zip(values) {
+            let value = serialize_value_to_json(value)
+                .unwrap_or_else(|_| JsonValue::String(value.to_string()));
+            row.insert(name.clone(), value);
+        }
+        JsonValue::Object(row)
+    }
+}
+
+fn value_matches(value: &Value, expected: &str) -> bool {
+    match value {
+        Value::String(string) => string.as_str() == expected,
+        value => serialize_value_to_json(value).is_ok_and(|value| value.to_string() == expected),
+    }
+}
+
+type SharedTables = Arc<RwLock<HashMap<String, Arc<RwLock<MaterializedTable>>>>>;
+
+/// The tables of the servers started in this process, by their addresses.
+static SERVERS: Lazy<Mutex<HashMap<SocketAddr, SharedTables>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn json_response(status: StatusCode, body: &JsonValue) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, &serde_json::json!({ "error": message }))
+}
+
+fn handle_request(tables: &SharedTables, request: &Request<Body>) -> Response<Body> {
+    if request.method() != Method::GET {
+        return error_response(StatusCode::METHOD_NOT_ALLOWED, "only GET is supported");
+    }
+    let Some(name) = request.uri().path().strip_prefix("/tables/") else {
+        return error_response(StatusCode::NOT_FOUND, "not found");
+    };
+    let Some(table) = tables.read().unwrap().get(name).cloned() else {
+        return error_response(StatusCode::NOT_FOUND, &format!("table {name:?} not found"));
+    };
+    let parameters: Vec<(String, String)> =
+        form_urlencoded::parse(request.uri().query().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+    let result = table.read().unwrap().query(&parameters);
+    match result {
+        Ok(rows) => json_response(StatusCode::OK, &rows),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+    }
+}
+
+fn start_server(address: SocketAddr) -> Result<SharedTables, QueryEndpointError> {
+    let listener = TcpListener::bind(address).map_err(|e| QueryEndpointError::Bind(address, e))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| QueryEndpointError::Bind(address, e))?;
+    let tables = SharedTables::default();
+    let server_tables = tables.clone();
+    Builder::new()
+        .name("pathway:query_endpoint".to_string())
+        .spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_io()
+                .build()
+                .unwrap();
+            runtime.block_on(async move {
+                let make_service = make_service_fn(move |_| {
+                    let tables = server_tables.clone();
+                    async move {
+                        Ok::<_, hyper::Error>(service_fn(move |request| {
+                            let response = handle_request(&tables, &request);
+                            async move { Ok::<_, hyper::Error>(response) }
+                        }))
+                    }
+                });
+                let server = match Server::from_tcp(listener) {
+                    Ok(server) => server.serve(make_service),
+                    Err(e) => {
+                        error!("Failed to serve tables at {address}: {e}");
+                        return;
+                    }
+                };
+                info!("Tables served at http://{address}/tables/");
+                if let Err(e) = server.await {
+                    error!("Query endpoint server error: {e}");
+                }
+            });
+        })
+        .map_err(|e| QueryEndpointError::Bind(address, e))?;
+    Ok(tables)
+}
+
+/// Keeps the table served while it's alive.
+struct Registration {
+    name: String,
+    tables: SharedTables,
+    table: Arc<RwLock<MaterializedTable>>,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.tables.write().unwrap().remove(&self.name);
+    }
+}
+
+pub struct QueryEndpointWriter {
+    name: String,
+    registration: Option<Registration>,
+    pending: Vec<(Key, Vec<Value>, isize)>,
+}
+
+impl QueryEndpointWriter {
+    /// Creates the writer serving the table at `address`. Only the writer on the worker that
+    /// receives the rows has to serve the table, the others are created with `is_serving` set
+    /// to false.
+    pub fn new(
+        address: SocketAddr,
+        name: String,
+        column_names: Vec<String>,
+        is_serving: bool,
+    ) -> Result<Self, QueryEndpointError> {
+        let registration = if is_serving {
+            let tables = {
+                let mut servers = SERVERS.lock().unwrap();
+                if let Some(tables) = servers.get(&address) {
+                    tables.clone()
+                } else {
+                    let tables = start_server(address)?;
+                    servers.insert(address, tables.clone());
+                    tables
+                }
+            };
+            let table = Arc::new(RwLock::new(MaterializedTable::new(column_names)));
+            {
+                let mut tables = tables.write().unwrap();
+                if tables.contains_key(&name) {
+                    return Err(QueryEndpointError::AlreadyServed(name, address));
+                }
+                tables.insert(name.clone(), table.clone());
+            }
+            Some(Registration {
+                name: name.clone(),
+                tables,
+                table,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            name,
+            registration,
+            pending: Vec::new(),
+        })
+    }
+}
+
+impl Writer for QueryEndpointWriter {
+    fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
+        self.pending.push((data.key, data.values, data.diff));
+        Ok(())
+    }
+
+    fn flush(&mut self, _forced: bool) -> Result<(), WriteError> {
+        let updates = std::mem::take(&mut self.pending);
+        if let Some(registration) = &self.registration {
+            if !updates.is_empty() {
+                registration.table.write().unwrap().apply(updates);
+            }
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> String {
+        format!("QueryEndpoint({})", self.name)
+    }
+}
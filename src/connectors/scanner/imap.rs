@@ -0,0 +1,242 @@
+// Copyright © 2024 Pathway
+
+use std::collections::{HashMap, HashSet};
+use std::net::TcpStream;
+use std::str::from_utf8;
+use std::time::{Duration, Instant};
+
+use imap::types::{Fetch, Uid};
+use imap::Session;
+use itertools::Itertools;
+use log::{info, warn};
+use native_tls::{TlsConnector, TlsStream};
+
+use crate::connectors::metadata::FileLikeMetadata;
+use crate::connectors::scanner::{PosixLikeScanner, QueuedAction};
+use crate::connectors::ReadError;
+use crate::persistence::cached_object_storage::CachedObjectStorage;
+
+const MAX_MESSAGES_IN_BULK_DOWNLOAD: usize = 64;
+const MESSAGE_METADATA_QUERY: &str = "(UID RFC822.SIZE INTERNALDATE)";
+// `BODY.PEEK[]` is used instead of `RFC822` so that reading doesn't mark the messages as seen
+const MESSAGE_QUERY: &str = "(UID RFC822.SIZE INTERNALDATE BODY.PEEK[])";
+
+type ImapSession = Session<TlsStream<TcpStream>>;
+
+#[derive(Clone, Debug)]
+pub struct ImapConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub mailbox: String,
+    pub refresh_interval: Duration,
+}
+
+/// Reads the messages of an IMAP mailbox as objects, each being the raw RFC 822 message,
+/// with its headers, body and attachments. A message is identified by its UID within the
+/// mailbox, so new messages are read as they arrive, and the messages that are expunged or
+/// moved to another mailbox are deleted if deletions are enabled. The messages in IMAP never
+/// change, so there are no updates.
+#[allow(clippy::module_name_repetitions)]
+pub struct ImapScanner {
+    settings: ImapConfig,
+    session: Option<ImapSession>,
+    last_search_at: Option<Instant>,
+    pending_message_uids: Vec<Uid>,
+    pending_messages: HashMap<String, Vec<u8>>,
+}
+
+impl PosixLikeScanner for ImapScanner {
+    fn object_metadata(
+        &mut self,
+        object_path: &[u8],
+    ) -> Result<Option<FileLikeMetadata>, ReadError> {
+        let Some(uid) = self.message_uid(object_path) else {
+            return Ok(None);
+        };
+        let fetches = self
+            .with_session(|session| session.uid_fetch(uid.to_string(), MESSAGE_METADATA_QUERY))?;
+        Ok(fetches
+            .iter()
+            .find(|fetch| fetch.uid == Some(uid))
+            .map(|fetch| self.message_metadata(uid, fetch)))
+    }
+
+    fn read_object(&mut self, object_path: &[u8]) -> Result<Vec<u8>, ReadError> {
+        let path = from_utf8(object_path).expect("IMAP paths are expected to be UTF-8 strings");
+        if let Some(prepared_message) = self.pending_messages.remove(path) {
+            return Ok(prepared_message);
+        }
+        let uid = self
+            .message_uid(object_path)
+            .ok_or(ReadError::MalformedData)?;
+        let fetches =
+            self.with_session(|session| session.uid_fetch(uid.to_string(), MESSAGE_QUERY))?;
+        fetches
+            .iter()
+            .find(|fetch| fetch.uid == Some(uid))
+            .and_then(|fetch| fetch.body().map(<[u8]>::to_vec))
+            .ok_or(ReadError::NoObjectsToRead)
+    }
+
+    fn next_scanner_actions(
+        &mut self,
+        are_deletions_enabled: bool,
+        cached_object_storage: &CachedObjectStorage,
+    ) -> Result<Vec<QueuedAction>, ReadError> {
+        let mut result = Vec::new();
+        if self.pending_message_uids.is_empty() {
+            let is_search_due = self.last_search_at.is_none_or(|last_search_at| {
+                last_search_at.elapsed() >= self.settings.refresh_interval
+            });
+            if !is_search_due {
+                return Ok(result);
+            }
+            self.last_search_at = Some(Instant::now());
+            let mailbox_uids = self.with_session(|session| session.uid_search("ALL"))?;
+            for uid in &mailbox_uids {
+                if !cached_object_storage.contains_object(self.message_path(*uid).as_bytes()) {
+                    self.pending_message_uids.push(*uid);
+                }
+            }
+            // The oldest messages are at the end, as the bulks are taken from there
+            self.pending_message_uids.sort_unstable_by(|a, b| b.cmp(a));
+            info!(
+                "New messages found in the mailbox {:?}: {}",
+                self.settings.mailbox,
+                self.pending_message_uids.len()
+            );
+            if are_deletions_enabled {
+                result.extend(self.removed_messages(&mailbox_uids, cached_object_storage));
+            }
+        }
+
+        let split_at = self
+            .pending_message_uids
+            .len()
+            .saturating_sub(MAX_MESSAGES_IN_BULK_DOWNLOAD);
+        let bulk_for_download = self.pending_message_uids.split_off(split_at);
+        if bulk_for_download.is_empty() {
+            return Ok(result);
+        }
+        let uid_set = bulk_for_download.iter().rev().join(",");
+        let fetches = match self.with_session(|session| session.uid_fetch(&uid_set, MESSAGE_QUERY))
+        {
+            Ok(fetches) => fetches,
+            Err(e) => {
+                warn!("Failed to fetch the new messages: {e}. They will be retried with the next bulk.");
+                self.pending_message_uids.extend(bulk_for_download);
+                return Ok(result);
+            }
+        };
+        for fetch in fetches.iter() {
+            let (Some(uid), Some(body)) = (fetch.uid, fetch.body()) else {
+                continue;
+            };
+            let metadata = self.message_metadata(uid, fetch);
+            let message_path = metadata.path.clone();
+            result.push(QueuedAction::Read(message_path.as_bytes().into(), metadata));
+            self.pending_messages.insert(message_path, body.to_vec());
+        }
+        Ok(result)
+    }
+
+    fn has_pending_actions(&self) -> bool {
+        !self.pending_message_uids.is_empty()
+    }
+
+    fn short_description(&self) -> String {
+        format!("IMAP({}/{})", self.settings.host, self.settings.mailbox)
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+impl ImapScanner {
+    pub fn new(settings: ImapConfig) -> Result<Self, ReadError> {
+        let mut scanner = Self {
+            settings,
+            session: None,
+            last_search_at: None,
+            pending_message_uids: Vec::new(),
+            pending_messages: HashMap::new(),
+        };
+        // Fail early if the server can't be reached or the credentials are wrong
+        scanner.with_session(|session| session.noop())?;
+        Ok(scanner)
+    }
+
+    fn connect(settings: &ImapConfig) -> Result<ImapSession, imap::error::Error> {
+        let tls_connector = TlsConnector::builder().build()?;
+        let client = imap::connect(
+            (settings.host.as_str(), settings.port),
+            &settings.host,
+            &tls_connector,
+        )?;
+        let mut session = client
+            .login(&settings.username, &settings.password)
+            .map_err(|(e, _)| e)?;
+        session.select(&settings.mailbox)?;
+        Ok(session)
+    }
+
+    /// Runs the command in the session, connecting to the server first if there is no session
+    /// yet. If the command fails, the session is dropped, so that the next command is run in a
+    /// new one.
+    fn with_session<T>(
+        &mut self,
+        command: impl FnOnce(&mut ImapSession) -> imap::error::Result<T>,
+    ) -> Result<T, ReadError> {
+        if self.session.is_none() {
+            self.session = Some(Self::connect(&self.settings)?);
+        }
+        let session = self
+            .session
+            .as_mut()
+            .expect("IMAP session must be established at this point");
+        command(session).map_err(|e| {
+            self.session = None;
+            ReadError::Imap(e)
+        })
+    }
+
+    fn mailbox_path(&self) -> String {
+        format!(
+            "imap://{}@{}/{}/",
+            self.settings.username, self.settings.host, self.settings.mailbox
+        )
+    }
+
+    fn message_path(&self, uid: Uid) -> String {
+        format!("{}{uid}", self.mailbox_path())
+    }
+
+    fn message_uid(&self, object_path: &[u8]) -> Option<Uid> {
+        let path = from_utf8(object_path).ok()?;
+        path.strip_prefix(&self.mailbox_path())?.parse().ok()
+    }
+
+    fn message_metadata(&self, uid: Uid, fetch: &Fetch) -> FileLikeMetadata {
+        FileLikeMetadata::from_email_message(
+            self.message_path(uid),
+            fetch.size.unwrap_or_default().into(),
+            fetch
+                .internal_date()
+                .map(|received_at| received_at.timestamp()),
+        )
+    }
+
+    fn removed_messages(
+        &self,
+        mailbox_uids: &HashSet<Uid>,
+        cached_object_storage: &CachedObjectStorage,
+    ) -> Vec<QueuedAction> {
+        cached_object_storage
+            .get_iter()
+            .filter_map(|(object_path, _)| {
+                let uid = self.message_uid(object_path)?;
+                (!mailbox_uids.contains(&uid)).then(|| QueuedAction::Delete(object_path.clone()))
+            })
+            .collect()
+    }
+}
@@ -3,11 +3,15 @@ use crate::connectors::ReadError;
 use crate::persistence::cached_object_storage::CachedObjectStorage;
 
 pub mod filesystem;
+pub mod imap;
 pub mod s3;
 
 #[allow(clippy::module_name_repetitions)]
 pub use filesystem::FilesystemScanner;
 
+#[allow(clippy::module_name_repetitions)]
+pub use imap::{ImapConfig, ImapScanner};
+
 #[allow(clippy::module_name_repetitions)]
 pub use s3::S3Scanner;
 
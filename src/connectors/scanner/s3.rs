@@ -14,15 +14,25 @@ use crate::connectors::metadata::FileLikeMetadata;
 use crate::connectors::scanner::{PosixLikeScanner, QueuedAction};
 use crate::connectors::ReadError;
 use crate::persistence::cached_object_storage::CachedObjectStorage;
-use crate::retry::{execute_with_retries, RetryConfig};
+use crate::retry::{execute_with_retries_if, RetryPolicy};
 
 use s3::bucket::Bucket as S3Bucket;
+use s3::error::S3Error;
 use s3::request::request_trait::ResponseData as S3ResponseData;
 use s3::serde_types::ListBucketResult as S3ListBucketResult;
 
 const MAX_S3_RETRIES: usize = 2;
 const S3_PATH_PREFIXES: [&str; 2] = ["s3://", "s3a://"];
 
+/// Errors returned for requests that can't succeed, such as a missing object or denied access,
+/// are not retried. Timeouts, throttling and server errors are.
+pub fn is_retryable_s3_error(error: &S3Error) -> bool {
+    match error {
+        S3Error::HttpFailWithBody(status_code, _) => matches!(*status_code, 408 | 429 | 500..),
+        _ => true,
+    }
+}
+
 struct S3DownloadedObject {
     path: ArcStr,
     contents: Vec<u8>,
@@ -73,6 +83,7 @@ pub struct S3Scanner {
     pending_modification_download_tasks: Vec<FileLikeMetadata>,
     pending_modifications: HashMap<String, Vec<u8>>,
     downloader_pool: ThreadPool,
+    retry_policy: RetryPolicy,
 }
 
 impl PosixLikeScanner for S3Scanner {
@@ -81,10 +92,11 @@ impl PosixLikeScanner for S3Scanner {
         object_path: &[u8],
     ) -> Result<Option<FileLikeMetadata>, ReadError> {
         let path = from_utf8(object_path).expect("S3 path are expected to be UTF-8 strings");
-        let object_lists = execute_with_retries(
+        let object_lists = execute_with_retries_if(
             || self.bucket.list(path.to_string(), None),
-            RetryConfig::default(),
-            MAX_S3_RETRIES,
+            is_retryable_s3_error,
+            self.retry_policy.retry_config().with_full_jitter(),
+            self.retry_policy.max_retries_or(MAX_S3_RETRIES),
         )
         .map_err(|e| ReadError::S3(S3CommandName::ListObjectsV2, e))?;
         for list in object_lists {
@@ -195,14 +207,16 @@ impl S3Scanner {
         object_pattern: impl Into<String>,
         downloader_threads_count: usize,
         is_polling_enabled: bool,
+        retry_policy: RetryPolicy,
     ) -> Result<Self, ReadError> {
         let objects_prefix = objects_prefix.into();
         let object_pattern = object_pattern.into();
 
-        let (object_list, _) = execute_with_retries(
+        let (object_list, _) = execute_with_retries_if(
             || bucket.list_page(objects_prefix.clone(), None, None, None, Some(1)),
-            RetryConfig::default(),
-            MAX_S3_RETRIES,
+            is_retryable_s3_error,
+            retry_policy.retry_config().with_full_jitter(),
+            retry_policy.max_retries_or(MAX_S3_RETRIES),
         )
         .map_err(|e| ReadError::S3(S3CommandName::ListPage, e))?;
         if object_list.contents.is_empty() {
@@ -222,6 +236,7 @@ impl S3Scanner {
                 .expect("Failed to create downloader pool"),
             pending_modifications: HashMap::new(),
             pending_modification_download_tasks: Vec::new(),
+            retry_policy,
         })
     }
 
@@ -242,12 +257,14 @@ impl S3Scanner {
     pub fn download_object_from_path_and_bucket(
         object_path_ref: &str,
         bucket: &S3Bucket,
+        retry_policy: &RetryPolicy,
     ) -> Result<S3ResponseData, ReadError> {
         let (_, deduced_path) = Self::deduce_bucket_and_path(object_path_ref);
-        execute_with_retries(
+        execute_with_retries_if(
             || bucket.get_object(&deduced_path), // returns Err on incorrect status code because fail-on-err feature is enabled
-            RetryConfig::default(),
-            MAX_S3_RETRIES,
+            is_retryable_s3_error,
+            retry_policy.retry_config().with_full_jitter(),
+            retry_policy.max_retries_or(MAX_S3_RETRIES),
         )
         .map_err(|e| ReadError::S3(S3CommandName::GetObject, e))
     }
@@ -255,9 +272,11 @@ impl S3Scanner {
     fn stream_object_from_path_and_bucket(
         object_path_ref: &str,
         bucket: &S3Bucket,
+        retry_policy: &RetryPolicy,
     ) -> S3DownloadResult {
         let object_path = object_path_ref.to_string();
-        let response = Self::download_object_from_path_and_bucket(&object_path, bucket)?;
+        let response =
+            Self::download_object_from_path_and_bucket(&object_path, bucket, retry_policy)?;
 
         Ok(S3DownloadedObject::new(
             object_path_ref.to_string().into(),
@@ -280,8 +299,12 @@ impl S3Scanner {
             new_objects
                 .par_iter()
                 .map(|task| {
-                    Self::stream_object_from_path_and_bucket(&task.path, &self.bucket)
-                        .map(|result| result.set_metadata(task.clone()))
+                    Self::stream_object_from_path_and_bucket(
+                        &task.path,
+                        &self.bucket,
+                        &self.retry_policy,
+                    )
+                    .map(|result| result.set_metadata(task.clone()))
                 })
                 .collect()
         });
@@ -295,10 +318,11 @@ impl S3Scanner {
         cached_object_storage: &CachedObjectStorage,
         seen_object_keys: &mut HashSet<String>,
     ) -> Result<(), ReadError> {
-        let object_lists: Vec<S3ListBucketResult> = execute_with_retries(
+        let object_lists: Vec<S3ListBucketResult> = execute_with_retries_if(
             || self.bucket.list(self.objects_prefix.to_string(), None),
-            RetryConfig::default(),
-            MAX_S3_RETRIES,
+            is_retryable_s3_error,
+            self.retry_policy.retry_config().with_full_jitter(),
+            self.retry_policy.max_retries_or(MAX_S3_RETRIES),
         )
         .map_err(|e| ReadError::S3(S3CommandName::ListObjectsV2, e))?;
         for list in object_lists {
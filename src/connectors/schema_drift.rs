@@ -0,0 +1,249 @@
+// Copyright © 2024 Pathway
+
+//! Detection of the drift of the input data from the schema of a connector.
+//!
+//! The parsers that support it observe each parsed entry and report:
+//! - the fields present in the data but not in the schema, once per field;
+//! - the values that can't be parsed to the type of their column, once per column and kind of
+//!   the observed value;
+//! - the changes of the share of the missing values of a column between two consecutive
+//!   windows of entries, if the change is large enough.
+//!
+//! The events are collected in a process-wide queue, from which they're taken by the built-in
+//! schema drift input connector. The reporting is off until that connector is created, so the
+//! parsers don't track anything if nobody listens.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use log::warn;
+use serde_json::Value as JsonValue;
+
+use crate::engine::Type;
+
+pub const DEFAULT_NULL_RATE_WINDOW: usize = 1000;
+pub const DEFAULT_NULL_RATE_THRESHOLD: f64 = 0.1;
+
+/// The maximum number of events waiting to be taken. The oldest ones are dropped above it.
+const MAX_PENDING_EVENTS: usize = 10_000;
+
+static REPORTING_ENABLED: AtomicBool = AtomicBool::new(false);
+static PENDING_EVENTS: Mutex<VecDeque<SchemaDriftEvent>> = Mutex::new(VecDeque::new());
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchemaDriftKind {
+    NewField,
+    TypeConflict,
+    NullRateChange,
+}
+
+impl SchemaDriftKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NewField => "new_field",
+            Self::TypeConflict => "type_conflict",
+            Self::NullRateChange => "null_rate_change",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaDriftEvent {
+    /// The name of the connector that read the data.
+    pub source: String,
+    pub field: String,
+    pub kind: SchemaDriftKind,
+    pub details: String,
+}
+
+/// What was observed in a field of the schema in a single entry.
+#[derive(Clone, Debug)]
+pub enum FieldObservation {
+    Present,
+    Missing,
+    TypeConflict { expected: Type, observed: String },
+}
+
+/// Describes the kind of a JSON value for the details of a type conflict.
+pub fn json_value_kind(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "a boolean",
+        JsonValue::Number(_) => "a number",
+        JsonValue::String(_) => "a string",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+    }
+}
+
+pub fn enable_reporting() {
+    REPORTING_ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_reporting_enabled() -> bool {
+    REPORTING_ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn report(events: Vec<SchemaDriftEvent>) {
+    if events.is_empty() {
+        return;
+    }
+    let mut pending_events = PENDING_EVENTS.lock().unwrap();
+    pending_events.extend(events);
+    if pending_events.len() > MAX_PENDING_EVENTS {
+        let n_dropped = pending_events.len() - MAX_PENDING_EVENTS;
+        pending_events.drain(..n_dropped);
+        warn!("Dropped {n_dropped} schema drift events that were not taken in time");
+    }
+}
+
+/// Takes the events reported since the previous call.
+pub fn take_events() -> Vec<SchemaDriftEvent> {
+    PENDING_EVENTS.lock().unwrap().drain(..).collect()
+}
+
+/// Tracks the drift of the entries read by a single connector.
+pub struct SchemaDriftTracker {
+    source: String,
+    field_names: Vec<String>,
+    known_fields: HashSet<String>,
+    reported_new_fields: HashSet<String>,
+    reported_type_conflicts: HashSet<(usize, String)>,
+    null_rate_window: usize,
+    null_rate_threshold: f64,
+    window_entries: usize,
+    window_missing: Vec<usize>,
+    previous_null_rates: Option<Vec<f64>>,
+}
+
+impl SchemaDriftTracker {
+    /// Creates a tracker of the given fields of the schema. The `known_fields` are the names
+    /// that may appear in the data without being reported as new.
+    pub fn new(
+        source: String,
+        field_names: Vec<String>,
+        known_fields: impl IntoIterator<Item = String>,
+    ) -> Self {
+        let window_missing = vec![0; field_names.len()];
+        Self {
+            source,
+            field_names,
+            known_fields: known_fields.into_iter().collect(),
+            reported_new_fields: HashSet::new(),
+            reported_type_conflicts: HashSet::new(),
+            null_rate_window: DEFAULT_NULL_RATE_WINDOW,
+            null_rate_threshold: DEFAULT_NULL_RATE_THRESHOLD,
+            window_entries: 0,
+            window_missing,
+            previous_null_rates: None,
+        }
+    }
+
+    /// Sets the number of entries over which the null rates are computed, and the change of
+    /// the rate between two windows that is reported.
+    #[must_use]
+    pub fn with_null_rate_window(mut self, window: usize, threshold: f64) -> Self {
+        self.null_rate_window = window.max(1);
+        self.null_rate_threshold = threshold;
+        self
+    }
+
+    pub fn field_names(&self) -> &[String] {
+        &self.field_names
+    }
+
+    fn event(&self, field: &str, kind: SchemaDriftKind, details: String) -> SchemaDriftEvent {
+        SchemaDriftEvent {
+            source: self.source.clone(),
+            field: field.to_string(),
+            kind,
+            details,
+        }
+    }
+
+    /// Observes the names of the fields present in an entry.
+    pub fn observe_field_names<'a>(
+        &mut self,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<SchemaDriftEvent> {
+        let mut events = Vec::new();
+        for name in names {
+            if self.known_fields.contains(name) || self.reported_new_fields.contains(name) {
+                continue;
+            }
+            self.reported_new_fields.insert(name.to_string());
+            events.push(self.event(
+                name,
+                SchemaDriftKind::NewField,
+                "field not present in the schema".to_string(),
+            ));
+        }
+        events
+    }
+
+    /// Observes the fields of the schema in an entry, in the order of
+    /// [`field_names`](Self::field_names).
+    pub fn observe_entry(
+        &mut self,
+        observations: impl IntoIterator<Item = FieldObservation>,
+    ) -> Vec<SchemaDriftEvent> {
+        let mut events = Vec::new();
+        for (index, observation) in observations.into_iter().enumerate() {
+            match observation {
+                FieldObservation::Present => {}
+                FieldObservation::Missing => self.window_missing[index] += 1,
+                FieldObservation::TypeConflict { expected, observed } => {
+                    if self
+                        .reported_type_conflicts
+                        .insert((index, observed.clone()))
+                    {
+                        events.push(self.event(
+                            &self.field_names[index],
+                            SchemaDriftKind::TypeConflict,
+                            format!("expected a value of type {expected}, got {observed}"),
+                        ));
+                    }
+                }
+            }
+        }
+        self.window_entries += 1;
+        if self.window_entries == self.null_rate_window {
+            events.extend(self.finish_window());
+        }
+        events
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn finish_window(&mut self) -> Vec<SchemaDriftEvent> {
+        let null_rates: Vec<f64> = self
+            .window_missing
+            .iter()
+            .map(|missing| *missing as f64 / self.window_entries as f64)
+            .collect();
+        let mut events = Vec::new();
+        if let Some(previous_null_rates) = &self.previous_null_rates {
+            for (index, (previous, current)) in previous_null_rates
+                .iter()
+                .zip(null_rates.iter())
+                .enumerate()
+            {
+                if (current - previous).abs() >= self.null_rate_threshold {
+                    events.push(self.event(
+                        &self.field_names[index],
+                        SchemaDriftKind::NullRateChange,
+                        format!(
+                            "share of missing values changed from {:.1}% to {:.1}%",
+                            previous * 100.0,
+                            current * 100.0
+                        ),
+                    ));
+                }
+            }
+        }
+        self.previous_null_rates = Some(null_rates);
+        self.window_entries = 0;
+        self.window_missing.fill(0);
+        events
+    }
+}
@@ -0,0 +1,276 @@
+// Copyright © 2024 Pathway
+
+//! The interface for the connectors implemented outside of this crate.
+//!
+//! The connectors of the engine implement [`Reader`] and [`Writer`], which follow the internals
+//! of the engine and change along with them. The custom connectors implement [`Source`] and
+//! [`Sink`] instead. These traits only use the types defined in this module and the values of
+//! the engine, and they are kept compatible between the releases: the methods added later get
+//! default implementations, and the changes that would break the existing connectors are made
+//! only together with an increase of [`SDK_VERSION`].
+//!
+//! A source is turned into a reader with [`SourceReader`] and a sink into a writer with
+//! [`SinkWriter`], after which they are used by the engine in the same way as its own
+//! connectors. The records read from a source are parsed by the parser of the chosen data
+//! format, and the records written into a sink are formatted by the chosen formatter.
+//!
+//! The [`template`] module has a small source and sink that can be used as a starting point,
+//! and the [`testing`] module has the checks that the connectors are expected to pass.
+
+pub mod template;
+pub mod testing;
+
+use std::any::type_name;
+use std::borrow::Cow;
+use std::error::Error;
+use std::io;
+
+use crate::connectors::data_format::FormatterContext;
+use crate::connectors::data_storage::{
+    DataEventType, ReadError, ReadResult, Reader, ReaderContext, StorageType, WriteError, Writer,
+};
+use crate::connectors::{OffsetKey, OffsetValue};
+use crate::engine::{Key, Timestamp, Value};
+use crate::persistence::frontier::OffsetAntichain;
+
+/// The version of the interface. It's increased with every change that can break the existing
+/// connectors.
+pub const SDK_VERSION: u32 = 1;
+
+pub type BoxedError = Box<dyn Error + Send + Sync>;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ConnectorError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("the position {0:?} can't be resumed from")]
+    InvalidPosition(Vec<u8>),
+
+    /// An error after which the operation may succeed if repeated, such as a network timeout.
+    #[error("transient error: {0}")]
+    Transient(#[source] BoxedError),
+
+    #[error(transparent)]
+    Other(BoxedError),
+}
+
+impl ConnectorError {
+    pub fn transient(error: impl Into<BoxedError>) -> Self {
+        Self::Transient(error.into())
+    }
+
+    pub fn other(error: impl Into<BoxedError>) -> Self {
+        Self::Other(error.into())
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Io(_) | Self::Transient(_))
+    }
+}
+
+fn short_type_name<T: ?Sized>() -> String {
+    type_name::<T>().split("::").last().unwrap().to_string()
+}
+
+/// An entry read from a [`Source`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourceRecord {
+    pub key: Option<Vec<u8>>,
+    pub payload: Vec<u8>,
+    /// The position of the source right after this record. If the computation is restarted
+    /// from a persisted state, the reading continues from the position of the last record
+    /// processed, see [`Source::resume`].
+    pub position: Vec<u8>,
+}
+
+impl SourceRecord {
+    pub fn new(payload: Vec<u8>, position: Vec<u8>) -> Self {
+        Self {
+            key: None,
+            payload,
+            position,
+        }
+    }
+
+    /// Sets the key of the record, which is then used as the key of the row if the data format
+    /// doesn't define the primary key.
+    #[must_use]
+    pub fn with_key(mut self, key: Vec<u8>) -> Self {
+        self.key = Some(key);
+        self
+    }
+}
+
+/// A source of the records for a custom input connector.
+pub trait Source: Send + 'static {
+    /// Returns the next record, waiting for it if necessary, or `None` once there will be no
+    /// more records.
+    fn next_record(&mut self) -> Result<Option<SourceRecord>, ConnectorError>;
+
+    /// Continues the reading right after the record with the given position. It's called before
+    /// the first record is read, if the computation is restarted from a persisted state. The
+    /// sources that can't be replayed can ignore it, in which case the records are read again
+    /// from the beginning.
+    fn resume(&mut self, _position: &[u8]) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
+    /// The name of the source used in the logs.
+    fn name(&self) -> String {
+        short_type_name::<Self>()
+    }
+}
+
+/// An entry written into a [`Sink`]. The payloads are the values of the row formatted by the
+/// formatter of the connector.
+#[derive(Clone, Debug)]
+pub struct SinkRecord {
+    pub key: Key,
+    pub values: Vec<Value>,
+    pub payloads: Vec<Vec<u8>>,
+    pub time: Timestamp,
+    pub diff: isize,
+}
+
+/// The destination of the records of a custom output connector.
+pub trait Sink: Send + 'static {
+    fn write(&mut self, record: SinkRecord) -> Result<(), ConnectorError>;
+
+    /// Makes the records written so far durable. It's called at the end of each minibatch.
+    fn flush(&mut self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
+    /// Called once all the records have been written and flushed.
+    fn finish(&mut self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
+    /// Whether the writes that fail with a retryable error can be repeated.
+    fn retriable(&self) -> bool {
+        false
+    }
+
+    /// The name of the sink used in the logs.
+    fn name(&self) -> String {
+        short_type_name::<Self>()
+    }
+}
+
+/// Reads the records of a [`Source`] as a [`Reader`] of the engine.
+pub struct SourceReader<S> {
+    source: S,
+    total_entries_read: u64,
+}
+
+impl<S: Source> SourceReader<S> {
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            total_entries_read: 0,
+        }
+    }
+}
+
+impl<S: Source> Reader for SourceReader<S> {
+    fn read(&mut self) -> Result<ReadResult, ReadError> {
+        let Some(record) = self.source.next_record()? else {
+            return Ok(ReadResult::Finished);
+        };
+        self.total_entries_read += 1;
+        // The positions of the custom sources are stored in the same way as the ones of the
+        // Python connectors, which are opaque to the engine as well
+        let offset = (
+            OffsetKey::Empty,
+            OffsetValue::PythonCursor {
+                raw_external_offset: record.position.into(),
+                total_entries_read: self.total_entries_read,
+            },
+        );
+        let context = match record.key {
+            Some(key) => ReaderContext::from_key_value(Some(key), Some(record.payload)),
+            None => ReaderContext::from_raw_bytes(DataEventType::Insert, record.payload),
+        };
+        Ok(ReadResult::Data(context, offset))
+    }
+
+    fn seek(&mut self, frontier: &OffsetAntichain) -> Result<(), ReadError> {
+        if let Some(OffsetValue::PythonCursor {
+            raw_external_offset,
+            total_entries_read,
+        }) = frontier.get_offset(&OffsetKey::Empty)
+        {
+            self.source.resume(raw_external_offset)?;
+            self.total_entries_read = *total_entries_read;
+        }
+        Ok(())
+    }
+
+    fn short_description(&self) -> Cow<'static, str> {
+        format!("Custom({})", self.source.name()).into()
+    }
+
+    fn storage_type(&self) -> StorageType {
+        StorageType::Custom
+    }
+}
+
+/// Writes the formatted rows into a [`Sink`] as a [`Writer`] of the engine.
+pub struct SinkWriter<S> {
+    sink: S,
+    finish_pending: bool,
+}
+
+impl<S: Sink> SinkWriter<S> {
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            finish_pending: false,
+        }
+    }
+}
+
+impl<S: Sink> Writer for SinkWriter<S> {
+    fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
+        let payloads = data
+            .payloads
+            .into_iter()
+            .map(|payload| payload.into_raw_bytes())
+            .collect::<Result<_, _>>()?;
+        self.sink.write(SinkRecord {
+            key: data.key,
+            values: data.values,
+            payloads,
+            time: data.time,
+            diff: data.diff,
+        })?;
+        Ok(())
+    }
+
+    fn flush(&mut self, _forced: bool) -> Result<(), WriteError> {
+        self.sink.flush()?;
+        if self.finish_pending {
+            self.finish_pending = false;
+            self.sink.finish()?;
+        }
+        Ok(())
+    }
+
+    fn on_time_committed(&mut self, time: Option<Timestamp>) -> Result<(), WriteError> {
+        // The last flush follows, and the sink is finished after it
+        if time.is_none() {
+            self.finish_pending = true;
+        }
+        Ok(())
+    }
+
+    fn retriable(&self) -> bool {
+        self.sink.retriable()
+    }
+
+    fn name(&self) -> String {
+        format!("Custom({})", self.sink.name())
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright © 2024 Pathway
+
+//! A source reading the lines of a file and a sink appending the payloads to a file, to be used
+//! as a starting point for new connectors.
+//!
+//! The source remembers the byte offset after each line as its position, so the reading can be
+//! resumed after a restart. The sink writes each payload of a record on a separate line and
+//! makes the lines durable on each flush.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::connectors::sdk::{ConnectorError, Sink, SinkRecord, Source, SourceRecord};
+
+pub struct FileLinesSource {
+    path: PathBuf,
+    reader: Option<BufReader<File>>,
+    bytes_offset: u64,
+}
+
+impl FileLinesSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            reader: None,
+            bytes_offset: 0,
+        }
+    }
+
+    fn reader(&mut self) -> Result<&mut BufReader<File>, ConnectorError> {
+        if self.reader.is_none() {
+            let mut file = File::open(&self.path)?;
+            file.seek(SeekFrom::Start(self.bytes_offset))?;
+            self.reader = Some(BufReader::new(file));
+        }
+        Ok(self
+            .reader
+            .as_mut()
+            .expect("the reader must be open at this point"))
+    }
+}
+
+impl Source for FileLinesSource {
+    fn next_record(&mut self) -> Result<Option<SourceRecord>, ConnectorError> {
+        let mut line = Vec::new();
+        let bytes_read = self.reader()?.read_until(b'\n', &mut line)?;
+        if bytes_read == 0 {
+            return Ok(None);
+        }
+        self.bytes_offset += bytes_read as u64;
+        if line.ends_with(b"\n") {
+            line.pop();
+        }
+        Ok(Some(SourceRecord::new(
+            line,
+            self.bytes_offset.to_be_bytes().to_vec(),
+        )))
+    }
+
+    fn resume(&mut self, position: &[u8]) -> Result<(), ConnectorError> {
+        let bytes_offset = position
+            .try_into()
+            .map_err(|_| ConnectorError::InvalidPosition(position.to_vec()))?;
+        self.bytes_offset = u64::from_be_bytes(bytes_offset);
+        self.reader = None;
+        Ok(())
+    }
+
+    fn name(&self) -> String {
+        format!("FileLines({})", self.path.display())
+    }
+}
+
+pub struct FileLinesSink {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl FileLinesSink {
+    pub fn new(path: &Path) -> Result<Self, ConnectorError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+        })
+    }
+}
+
+impl Sink for FileLinesSink {
+    fn write(&mut self, record: SinkRecord) -> Result<(), ConnectorError> {
+        for payload in record.payloads {
+            self.writer.write_all(&payload)?;
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ConnectorError> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()?;
+        Ok(())
+    }
+
+    fn name(&self) -> String {
+        format!("FileLines({})", self.path.display())
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright © 2024 Pathway
+
+//! Checks for the tests of the custom connectors. The connectors are driven through
+//! [`SourceReader`] and [`SinkWriter`], in the same way as the engine uses them.
+
+use crate::connectors::data_format::FormatterContext;
+use crate::connectors::data_storage::{
+    ReadError, ReadResult, Reader, ReaderContext, WriteError, Writer,
+};
+use crate::connectors::sdk::{Sink, SinkWriter, Source, SourceReader};
+use crate::connectors::Offset;
+use crate::persistence::frontier::OffsetAntichain;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum HarnessError {
+    #[error(transparent)]
+    Read(#[from] ReadError),
+
+    #[error(transparent)]
+    Write(#[from] WriteError),
+
+    #[error("unexpected read result: {0}")]
+    UnexpectedReadResult(String),
+
+    #[error("after resuming from the record {index}, the source read {actual:?} instead of {expected:?}")]
+    ResumeMismatch {
+        index: usize,
+        expected: Vec<Vec<u8>>,
+        actual: Vec<Vec<u8>>,
+    },
+}
+
+fn read_with_offsets<S: Source>(
+    reader: &mut SourceReader<S>,
+) -> Result<Vec<(Vec<u8>, Offset)>, HarnessError> {
+    let mut entries = Vec::new();
+    loop {
+        match reader.read()? {
+            ReadResult::Data(
+                ReaderContext::RawBytes(_, payload) | ReaderContext::KeyValue((_, Some(payload))),
+                offset,
+            ) => entries.push((payload, offset)),
+            ReadResult::Finished => return Ok(entries),
+            other => return Err(HarnessError::UnexpectedReadResult(format!("{other:?}"))),
+        }
+    }
+}
+
+/// Reads all the payloads of a finite source.
+pub fn read_all<S: Source>(source: S) -> Result<Vec<Vec<u8>>, HarnessError> {
+    let mut reader = SourceReader::new(source);
+    Ok(read_with_offsets(&mut reader)?
+        .into_iter()
+        .map(|(payload, _)| payload)
+        .collect())
+}
+
+/// Checks that a finite source, after being resumed from the position of any of its records,
+/// reads exactly the records that follow it. The sources are created with `new_source`, which
+/// must return the sources reading the same data each time.
+pub fn check_resume<S, F>(mut new_source: F) -> Result<(), HarnessError>
+where
+    S: Source,
+    F: FnMut() -> S,
+{
+    let mut reader = SourceReader::new(new_source());
+    let entries = read_with_offsets(&mut reader)?;
+    for (index, (_, (offset_key, offset_value))) in entries.iter().enumerate() {
+        let mut frontier = OffsetAntichain::new();
+        frontier.advance_offset(offset_key.clone(), offset_value.clone());
+        let mut resumed_reader = SourceReader::new(new_source());
+        resumed_reader.seek(&frontier)?;
+        let actual: Vec<_> = read_with_offsets(&mut resumed_reader)?
+            .into_iter()
+            .map(|(payload, _)| payload)
+            .collect();
+        let expected: Vec<_> = entries[index + 1..]
+            .iter()
+            .map(|(payload, _)| payload.clone())
+            .collect();
+        if actual != expected {
+            return Err(HarnessError::ResumeMismatch {
+                index,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Writes the entries into a sink. As in the engine, each time is committed and the sink is
+/// flushed once all the entries with this time are written, and the sink is finished at the end.
+pub fn write_all<S: Sink>(sink: S, entries: Vec<FormatterContext>) -> Result<(), HarnessError> {
+    let mut writer = SinkWriter::new(sink);
+    let mut current_time = None;
+    for entry in entries {
+        if current_time.is_some_and(|time| time != entry.time) {
+            writer.on_time_committed(current_time)?;
+            writer.flush(true)?;
+        }
+        current_time = Some(entry.time);
+        writer.write(entry)?;
+    }
+    if current_time.is_some() {
+        writer.on_time_committed(current_time)?;
+        writer.flush(true)?;
+    }
+    writer.on_time_committed(None)?;
+    writer.flush(true)?;
+    Ok(())
+}
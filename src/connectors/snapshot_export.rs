@@ -0,0 +1,335 @@
+// Copyright © 2024 Pathway
+
+//! Exporting the contents of a table at a commit time to a file while the computation keeps
+//! running, e.g. for audits or for analyzing them with other tools.
+//!
+//! The tables that can be exported are kept in memory by their [`SnapshotExportWriter`]s,
+//! which apply the updates of a time only once the time is committed, so an export always
+//! contains exactly the changes with times earlier than some output frontier. The exports are
+//! requested with [`export_snapshot`] from any thread of the process running the first worker.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use arrow::array::{ArrayRef, RecordBatch};
+use arrow::datatypes::{DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema};
+use arrow::error::ArrowError;
+use deltalake::parquet::arrow::ArrowWriter;
+use deltalake::parquet::errors::ParquetError;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tempfile::NamedTempFile;
+
+use crate::connectors::data_format::{serialize_value_to_json, FormatterContext};
+use crate::connectors::data_lake::arrow::{array_for_type, arrow_data_type};
+use crate::connectors::data_lake::LakeWriterSettings;
+use crate::connectors::data_storage::{WriteError, Writer};
+use crate::connectors::query_endpoint::MaterializedTable;
+use crate::engine::{Key, Timestamp, Type, Value};
+
+const ID_COLUMN: &str = "id";
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ExportError {
+    #[error("table {0:?} can't be exported, it is not registered")]
+    UnknownTable(String),
+
+    #[error("table {0:?} is already registered for exports")]
+    AlreadyRegistered(String),
+
+    #[error("unknown export format {0:?}, expected \"csv\" or \"parquet\"")]
+    UnknownFormat(String),
+
+    #[error("table {name:?} didn't reach time {time} within {timeout:?}")]
+    Timeout {
+        name: String,
+        time: Timestamp,
+        timeout: Duration,
+    },
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error(transparent)]
+    Arrow(#[from] ArrowError),
+
+    #[error(transparent)]
+    Parquet(#[from] ParquetError),
+
+    #[error(transparent)]
+    Write(#[from] WriteError),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl FromStr for ExportFormat {
+    type Err = ExportError;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            other => Err(ExportError::UnknownFormat(other.to_string())),
+        }
+    }
+}
+
+/// The summary of a finished export.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExportedSnapshot {
+    pub rows: usize,
+    /// The export contains the changes with times earlier than this one. `None` if it contains
+    /// all changes, because the computation has finished.
+    pub time: Option<Timestamp>,
+}
+
+struct TableState {
+    table: MaterializedTable,
+    frontier: Option<Timestamp>,
+}
+
+struct ExportableTable {
+    dtypes: Vec<Type>,
+    state: Mutex<TableState>,
+    committed: Condvar,
+}
+
+/// The tables that can be exported in this process, by their names.
+static TABLES: Lazy<Mutex<HashMap<String, Arc<ExportableTable>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Writes the rows of the table as of the first commit that includes all changes up to `time`,
+/// or as of the latest commit if `time` is not set. Waits for the commit at most `timeout`.
+pub fn export_snapshot(
+    name: &str,
+    path: &Path,
+    format: ExportFormat,
+    time: Option<Timestamp>,
+    timeout: Option<Duration>,
+) -> Result<ExportedSnapshot, ExportError> {
+    let table = TABLES
+        .lock()
+        .unwrap()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| ExportError::UnknownTable(name.to_string()))?;
+
+    let (column_names, rows, frontier) = {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut state = table.state.lock().unwrap();
+        while let (Some(time), Some(frontier)) = (time, state.frontier) {
+            if frontier > time {
+                break;
+            }
+            state = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Err(ExportError::Timeout {
+                            name: name.to_string(),
+                            time,
+                            timeout: timeout.unwrap(),
+                        });
+                    }
+                    table.committed.wait_timeout(state, remaining).unwrap().0
+                }
+                None => table.committed.wait(state).unwrap(),
+            };
+        }
+        // the values are reference counted, so copying them is cheaper than writing the file
+        // while the writer waits
+        let mut rows: Vec<(Key, Vec<Value>)> = state
+            .table
+            .rows()
+            .map(|(key, values)| (*key, values.clone()))
+            .collect();
+        rows.sort_unstable_by_key(|(key, _)| *key);
+        (state.table.column_names().to_vec(), rows, state.frontier)
+    };
+
+    // written next to the target first, so that a partial file is never visible
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut file = NamedTempFile::new_in(directory)?;
+    match format {
+        ExportFormat::Csv => write_csv(file.as_file_mut(), &column_names, &rows)?,
+        ExportFormat::Parquet => {
+            write_parquet(file.as_file_mut(), &column_names, &table.dtypes, &rows)?;
+        }
+    }
+    file.persist(path).map_err(|e| e.error)?;
+
+    Ok(ExportedSnapshot {
+        rows: rows.len(),
+        time: frontier,
+    })
+}
+
+fn csv_field(value: &Value) -> String {
+    match value {
+        Value::None => String::new(),
+        Value::String(string) => string.to_string(),
+        value => serialize_value_to_json(value)
+            .map_or_else(|_| value.to_string(), |value| value.to_string()),
+    }
+}
+
+fn write_csv(
+    file: &mut File,
+    column_names: &[String],
+    rows: &[(Key, Vec<Value>)],
+) -> Result<(), ExportError> {
+    let mut writer = csv::Writer::from_writer(file);
+    writer.write_record(column_names.iter().map(String::as_str).chain([ID_COLUMN]))?;
+    for (key, values) in rows {
+        writer.write_record(values.iter().map(csv_field).chain([key.to_string()]))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_parquet(
+    file: &mut File,
+    column_names: &[String],
+    dtypes: &[Type],
+    rows: &[(Key, Vec<Value>)],
+) -> Result<(), ExportError> {
+    let settings = LakeWriterSettings {
+        use_64bit_size_type: false,
+        utc_timezone_name: "UTC".into(),
+    };
+    let mut fields = Vec::with_capacity(column_names.len() + 1);
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(column_names.len() + 1);
+    for (index, (name, dtype)) in column_names.iter().zip(dtypes).enumerate() {
+        let arrow_type = arrow_data_type(dtype, &settings)?;
+        let values: Vec<Value> = rows
+            .iter()
+            .map(|(_, values)| values[index].clone())
+            .collect();
+        arrays.push(array_for_type(&arrow_type, &values)?);
+        fields.push(ArrowField::new(name, arrow_type, dtype.can_be_none()));
+    }
+    let keys: Vec<Value> = rows.iter().map(|(key, _)| Value::Pointer(*key)).collect();
+    arrays.push(array_for_type(&ArrowDataType::Utf8, &keys)?);
+    fields.push(ArrowField::new(ID_COLUMN, ArrowDataType::Utf8, false));
+
+    let schema = Arc::new(ArrowSchema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), arrays)?;
+    let mut writer = ArrowWriter::try_new(file, schema, None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Keeps the table registered while it's alive.
+struct Registration {
+    name: String,
+    table: Arc<ExportableTable>,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        TABLES.lock().unwrap().remove(&self.name);
+    }
+}
+
+pub struct SnapshotExportWriter {
+    name: String,
+    registration: Option<Registration>,
+    pending: Vec<(Timestamp, Key, Vec<Value>, isize)>,
+}
+
+impl SnapshotExportWriter {
+    /// Creates the writer keeping the table for exports. Only the writer on the worker that
+    /// receives the rows registers the table, the others are created with `is_registered` set
+    /// to false.
+    pub fn new(
+        name: String,
+        column_names: Vec<String>,
+        dtypes: Vec<Type>,
+        is_registered: bool,
+    ) -> Result<Self, ExportError> {
+        let registration = if is_registered {
+            let table = Arc::new(ExportableTable {
+                dtypes,
+                state: Mutex::new(TableState {
+                    table: MaterializedTable::new(column_names),
+                    frontier: Some(Timestamp(0)),
+                }),
+                committed: Condvar::new(),
+            });
+            let mut tables = TABLES.lock().unwrap();
+            if tables.contains_key(&name) {
+                return Err(ExportError::AlreadyRegistered(name));
+            }
+            tables.insert(name.clone(), table.clone());
+            Some(Registration {
+                name: name.clone(),
+                table,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            name,
+            registration,
+            pending: Vec::new(),
+        })
+    }
+}
+
+impl Writer for SnapshotExportWriter {
+    fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
+        self.pending
+            .push((data.time, data.key, data.values, data.diff));
+        Ok(())
+    }
+
+    fn on_time_committed(&mut self, time: Option<Timestamp>) -> Result<(), WriteError> {
+        let Some(registration) = &self.registration else {
+            self.pending.clear();
+            return Ok(());
+        };
+        // the entries of later times may already be written, they wait for their commit
+        let (mut committed, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|(entry_time, _, _, _)| time.is_none_or(|time| *entry_time < time));
+        self.pending = pending;
+        committed.sort_by_key(|(entry_time, _, _, _)| *entry_time);
+
+        let table = &registration.table;
+        let mut state = table.state.lock().unwrap();
+        let mut updates = Vec::new();
+        let mut updates_time = None;
+        for (entry_time, key, values, diff) in committed {
+            if updates_time != Some(entry_time) && !updates.is_empty() {
+                state.table.apply(std::mem::take(&mut updates));
+            }
+            updates_time = Some(entry_time);
+            updates.push((key, values, diff));
+        }
+        state.table.apply(updates);
+        state.frontier = time;
+        table.committed.notify_all();
+        Ok(())
+    }
+
+    fn name(&self) -> String {
+        format!("SnapshotExport({})", self.name)
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright © 2024 Pathway
+
+//! The parts shared by the sinks writing to the SQL databases that are updated in batches, such
+//! as SQL Server and Oracle.
+//!
+//! Like the Postgres sink, these sinks either append the stream of changes to the table, or
+//! maintain a snapshot of the output in it, and the table has the `time` and `diff` columns in
+//! both cases. The entries of a batch are buffered until the flush. In the snapshot mode, the
+//! changes to each primary key in the batch are collapsed into the final one, so that a batch
+//! can be applied with a single `MERGE`, which doesn't allow matching a row twice.
+
+use std::mem::take;
+
+use indexmap::IndexMap;
+
+use crate::connectors::data_format::FormatterContext;
+use crate::engine::value::Kind;
+use crate::engine::{Timestamp, Value};
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SqlSinkError {
+    #[error("unknown key field {0:?}")]
+    UnknownKey(String),
+
+    #[error("primary key must be specified")]
+    NoPrimaryKey,
+
+    #[error("value of kind {0:?} can't be written to {1}")]
+    UnsupportedValue(Kind, &'static str),
+
+    #[error("time {0} doesn't fit into the 'time' column")]
+    TimeOutOfRange(Timestamp),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SqlSinkMode {
+    /// Each change is inserted as a new row.
+    StreamOfChanges,
+    /// The table contains the current state of the output, identified by the given columns.
+    Snapshot { key_field_names: Vec<String> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SqlRow {
+    pub values: Vec<Value>,
+    pub time: Timestamp,
+    pub diff: isize,
+}
+
+impl SqlRow {
+    pub fn time_as_i64(&self) -> Result<i64, SqlSinkError> {
+        i64::try_from(self.time.0).map_err(|_| SqlSinkError::TimeOutOfRange(self.time))
+    }
+
+    pub fn is_deletion(&self) -> bool {
+        self.diff < 0
+    }
+}
+
+/// The table written by a batched SQL sink and the entries waiting for the flush.
+#[derive(Debug)]
+pub struct SqlSinkBuffer {
+    pub table_name: String,
+    pub value_field_names: Vec<String>,
+    /// The positions of the primary key columns in the snapshot mode.
+    pub key_field_positions: Option<Vec<usize>>,
+    rows: Vec<SqlRow>,
+}
+
+impl SqlSinkBuffer {
+    pub fn new(
+        table_name: String,
+        value_field_names: Vec<String>,
+        mode: &SqlSinkMode,
+    ) -> Result<Self, SqlSinkError> {
+        let key_field_positions = match mode {
+            SqlSinkMode::StreamOfChanges => None,
+            SqlSinkMode::Snapshot { key_field_names } => {
+                if key_field_names.is_empty() {
+                    return Err(SqlSinkError::NoPrimaryKey);
+                }
+                let positions = key_field_names
+                    .iter()
+                    .map(|key_field_name| {
+                        value_field_names
+                            .iter()
+                            .position(|name| name == key_field_name)
+                            .ok_or_else(|| SqlSinkError::UnknownKey(key_field_name.clone()))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Some(positions)
+            }
+        };
+        Ok(Self {
+            table_name,
+            value_field_names,
+            key_field_positions,
+            rows: Vec::new(),
+        })
+    }
+
+    pub fn key_field_names(&self) -> Option<Vec<&str>> {
+        self.key_field_positions.as_ref().map(|positions| {
+            positions
+                .iter()
+                .map(|position| self.value_field_names[*position].as_str())
+                .collect()
+        })
+    }
+
+    pub fn push(&mut self, data: FormatterContext) {
+        self.rows.push(SqlRow {
+            values: data.values,
+            time: data.time,
+            diff: data.diff,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Takes the buffered rows. In the snapshot mode, only the final change of each primary key
+    /// is returned.
+    pub fn take_rows(&mut self) -> Vec<SqlRow> {
+        let rows = take(&mut self.rows);
+        let Some(key_field_positions) = &self.key_field_positions else {
+            return rows;
+        };
+        let mut final_changes: IndexMap<Vec<Value>, SqlRow> = IndexMap::new();
+        for row in rows {
+            let key: Vec<_> = key_field_positions
+                .iter()
+                .map(|position| row.values[*position].clone())
+                .collect();
+            match final_changes.get_mut(&key) {
+                // A change of the values is a deletion of the old row and an insertion of the
+                // new one at the same time, in any order
+                Some(current) if row.is_deletion() && current.time == row.time => {}
+                Some(current) => *current = row,
+                None => {
+                    final_changes.insert(key, row);
+                }
+            }
+        }
+        final_changes.into_values().collect()
+    }
+}
@@ -0,0 +1,576 @@
+// Copyright © 2024 Pathway
+
+//! Reading a table of a SQL database by polling it with an incremental query, for the databases
+//! without a change data capture connector.
+//!
+//! The query is given the largest value of the cursor column read so far, the high-water mark,
+//! as its only parameter, and has to return the rows past it, ordered by the cursor column, e.g.
+//! `SELECT * FROM events WHERE id > $1 ORDER BY id` in `PostgreSQL`,
+//! `SELECT * FROM events WHERE id > ? ORDER BY id` in `MySQL` or
+//! `SELECT * FROM events WHERE id > @P1 ORDER BY id` in SQL Server. The high-water mark is a
+//! part of the offsets of the connector, so with persistence enabled the polling continues from
+//! where it has stopped. Only the new rows are read, so the cursor column has to grow with each
+//! inserted row, as an auto-incremented identifier or an insertion time does, and the updates
+//! and deletions of the rows already read are not noticed.
+//!
+//! The queries are run by a [`SqlPollingClient`], which is implemented for the `PostgreSQL`
+//! client, for [`MySqlPollingClient`] and for [`SqlServerPollingClient`].
+
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::thread::sleep;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use log::{error, info};
+use mysql_async::prelude::{FromValue as MySqlFromValue, Queryable};
+use mysql_async::{
+    Conn as MySqlConn, Opts as MySqlOpts, Params as MySqlParams, Row as MySqlRow,
+    Value as MySqlValue,
+};
+use postgres::types::{FromSql, ToSql};
+use postgres::Client as PsqlClient;
+use postgres::Row as PsqlRow;
+use tiberius::{
+    Client as SqlServerClient, Config as SqlServerConfig, FromSql as SqlServerFromSql,
+    Query as SqlServerQuery, Row as SqlServerRow,
+};
+use tokio::net::TcpStream;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use crate::async_runtime::AsyncRuntime;
+use crate::connectors::data_storage::{
+    ConversionError, DataEventType, ReadError, ReadResult, Reader, ReaderContext, StorageType,
+    ValuesMap,
+};
+use crate::connectors::{OffsetKey, OffsetValue};
+use crate::engine::time::DateTime as _;
+use crate::engine::value::Kind;
+use crate::engine::{DateTimeNaive, DateTimeUtc, Type, Value};
+use crate::persistence::frontier::OffsetAntichain;
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SqlPollingError {
+    #[error("failed to run the polling query: {0}")]
+    Postgres(#[from] postgres::Error),
+
+    #[error("failed to run the polling query: {0}")]
+    MySql(#[from] mysql_async::Error),
+
+    #[error("failed to run the polling query: {0}")]
+    SqlServer(#[from] tiberius::error::Error),
+
+    #[error("value of kind {0:?} can't be bound as the high-water mark in {1}")]
+    UnsupportedHighWaterMark(Kind, &'static str),
+
+    #[error("the cursor column {0:?} is not a part of the schema")]
+    UnknownCursorColumn(String),
+
+    #[error("row with an invalid value in the cursor column {0:?}: {1}")]
+    InvalidCursor(String, String),
+}
+
+#[derive(Clone, Debug)]
+pub struct SqlPollingConfig {
+    /// The incremental query, taking the high-water mark as its only parameter.
+    pub query: String,
+    pub cursor_column: String,
+    /// The high-water mark used until the first row is read.
+    pub start_from: Value,
+    /// How long to wait before polling again after a query has returned no rows.
+    pub poll_interval: Duration,
+}
+
+/// A connection to a database able to run the incremental query.
+pub trait SqlPollingClient: Send {
+    /// Runs `query` with `cursor` bound as its only parameter and returns the values of the
+    /// columns of `schema` in the returned rows, converted to the types of the schema.
+    fn fetch_rows(
+        &mut self,
+        query: &str,
+        cursor: &Value,
+        schema: &[(String, Type)],
+    ) -> Result<Vec<ValuesMap>, SqlPollingError>;
+
+    fn database_name(&self) -> &'static str;
+}
+
+impl SqlPollingClient for PsqlClient {
+    fn fetch_rows(
+        &mut self,
+        query: &str,
+        cursor: &Value,
+        schema: &[(String, Type)],
+    ) -> Result<Vec<ValuesMap>, SqlPollingError> {
+        let rows = self.query(query, &[cursor as &(dyn ToSql + Sync)])?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let values: HashMap<_, _> = schema
+                    .iter()
+                    .map(|(name, dtype)| (name.clone(), postgres_value(row, name, dtype)))
+                    .collect();
+                values.into()
+            })
+            .collect())
+    }
+
+    fn database_name(&self) -> &'static str {
+        "Postgres"
+    }
+}
+
+fn postgres_field<'a, T: FromSql<'a>>(row: &'a PsqlRow, name: &str) -> Option<Option<T>> {
+    row.try_get::<_, Option<T>>(name).ok()
+}
+
+fn postgres_field_as_value<'a, T: FromSql<'a>>(
+    row: &'a PsqlRow,
+    name: &str,
+    convert: impl Fn(T) -> Option<Value>,
+) -> Option<Option<Value>> {
+    postgres_field(row, name).map(|value| value.and_then(convert))
+}
+
+/// Reads the field of a `PostgreSQL` row as a value of the given type. The outer `None` means
+/// that the field can't be read as this type.
+fn postgres_typed_value(row: &PsqlRow, name: &str, dtype: &Type) -> Option<Option<Value>> {
+    match dtype {
+        Type::Bool => postgres_field_as_value(row, name, |v: bool| Some(v.into())),
+        Type::Int => postgres_field_as_value(row, name, |v: i64| Some(v.into()))
+            .or_else(|| postgres_field_as_value(row, name, |v: i32| Some(i64::from(v).into())))
+            .or_else(|| postgres_field_as_value(row, name, |v: i16| Some(i64::from(v).into()))),
+        Type::Float => postgres_field_as_value(row, name, |v: f64| Some(v.into()))
+            .or_else(|| postgres_field_as_value(row, name, |v: f32| Some(v.into()))),
+        Type::String => postgres_field_as_value(row, name, |v: &str| Some(v.into())),
+        Type::Bytes => postgres_field_as_value(row, name, |v: &[u8]| Some(v.into())),
+        Type::Json => postgres_field_as_value(row, name, |v: serde_json::Value| Some(v.into())),
+        Type::DateTimeNaive => postgres_field_as_value(row, name, |v: NaiveDateTime| {
+            let timestamp = v.and_utc().timestamp_nanos_opt()?;
+            Some(DateTimeNaive::new(timestamp).into())
+        }),
+        Type::DateTimeUtc => postgres_field_as_value(row, name, |v: DateTime<Utc>| {
+            let timestamp = v.timestamp_nanos_opt()?;
+            Some(DateTimeUtc::new(timestamp).into())
+        }),
+        Type::Any => [
+            Type::Int,
+            Type::Float,
+            Type::Bool,
+            Type::String,
+            Type::Bytes,
+            Type::Json,
+            Type::DateTimeUtc,
+            Type::DateTimeNaive,
+        ]
+        .iter()
+        .find_map(|dtype| postgres_typed_value(row, name, dtype)),
+        _ => None,
+    }
+}
+
+/// Turns the result of reading a field as a value of `dtype` into the value or a conversion
+/// error. `column_type` gives the database type of the column, used in the error.
+fn field_value(
+    typed_value: Option<Option<Value>>,
+    name: &str,
+    dtype: &Type,
+    column_type: impl FnOnce() -> Option<String>,
+) -> Result<Value, Box<ConversionError>> {
+    match typed_value {
+        Some(Some(value)) => Ok(value),
+        Some(None) if dtype.can_be_none() => Ok(Value::None),
+        Some(None) => Err(Box::new(ConversionError::new(
+            "NULL".to_string(),
+            name.to_owned(),
+            dtype.clone(),
+            None,
+        ))),
+        None => Err(Box::new(ConversionError::new(
+            column_type().unwrap_or_else(|| "<missing column>".to_string()),
+            name.to_owned(),
+            dtype.clone(),
+            None,
+        ))),
+    }
+}
+
+fn postgres_value(row: &PsqlRow, name: &str, dtype: &Type) -> Result<Value, Box<ConversionError>> {
+    let typed_value = postgres_typed_value(row, name, dtype.unoptionalize());
+    field_value(typed_value, name, dtype, || {
+        row.columns()
+            .iter()
+            .find(|column| column.name() == name)
+            .map(|column| format!("value of Postgres type {}", column.type_()))
+    })
+}
+
+const MYSQL_NAME: &str = "MySQL";
+
+/// A connection to a `MySQL` database, binding the high-water mark as the `?` parameter of the
+/// query. The session time zone is set to UTC, so that the `TIMESTAMP` columns are read as UTC.
+pub struct MySqlPollingClient {
+    runtime: AsyncRuntime,
+    conn: MySqlConn,
+}
+
+impl MySqlPollingClient {
+    pub fn connect(runtime: AsyncRuntime, url: &str) -> Result<Self, SqlPollingError> {
+        let opts = MySqlOpts::from_url(url).map_err(mysql_async::Error::from)?;
+        let conn = runtime.block_on(async {
+            let mut conn = MySqlConn::new(opts).await?;
+            conn.query_drop("SET time_zone = '+00:00'").await?;
+            Ok::<_, mysql_async::Error>(conn)
+        })?;
+        Ok(Self { runtime, conn })
+    }
+}
+
+impl SqlPollingClient for MySqlPollingClient {
+    fn fetch_rows(
+        &mut self,
+        query: &str,
+        cursor: &Value,
+        schema: &[(String, Type)],
+    ) -> Result<Vec<ValuesMap>, SqlPollingError> {
+        let parameter = match cursor {
+            Value::Bool(b) => MySqlValue::from(*b),
+            Value::Int(i) => MySqlValue::from(*i),
+            Value::Float(f) => MySqlValue::from(f.into_inner()),
+            Value::String(s) => MySqlValue::from(s.to_string()),
+            Value::DateTimeNaive(dt) => MySqlValue::from(dt.as_chrono_datetime()),
+            Value::DateTimeUtc(dt) => MySqlValue::from(dt.as_chrono_datetime()),
+            other => {
+                return Err(SqlPollingError::UnsupportedHighWaterMark(
+                    other.kind(),
+                    MYSQL_NAME,
+                ))
+            }
+        };
+        let conn = &mut self.conn;
+        let rows: Vec<MySqlRow> = self.runtime.block_on(async {
+            conn.exec(query, MySqlParams::Positional(vec![parameter]))
+                .await
+        })?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let values: HashMap<_, _> = schema
+                    .iter()
+                    .map(|(name, dtype)| (name.clone(), mysql_value(row, name, dtype)))
+                    .collect();
+                values.into()
+            })
+            .collect())
+    }
+
+    fn database_name(&self) -> &'static str {
+        MYSQL_NAME
+    }
+}
+
+fn mysql_field_as_value<T: MySqlFromValue>(
+    row: &MySqlRow,
+    name: &str,
+    convert: impl Fn(T) -> Option<Value>,
+) -> Option<Option<Value>> {
+    row.get_opt::<Option<T>, _>(name)?
+        .ok()
+        .map(|value| value.and_then(convert))
+}
+
+/// Reads the field of a `MySQL` row as a value of the given type. The outer `None` means that
+/// the field can't be read as this type. The values of `MySQL` are converted leniently, e.g.
+/// a string holding a number can be read as a number, so for [`Type::Any`] the type is chosen
+/// from the kind of the value returned by the database.
+fn mysql_typed_value(row: &MySqlRow, name: &str, dtype: &Type) -> Option<Option<Value>> {
+    match dtype {
+        Type::Bool => mysql_field_as_value(row, name, |v: bool| Some(v.into())),
+        Type::Int => mysql_field_as_value(row, name, |v: i64| Some(v.into())),
+        Type::Float => mysql_field_as_value(row, name, |v: f64| Some(v.into())),
+        Type::String => mysql_field_as_value(row, name, |v: String| Some(v.into())),
+        Type::Bytes => mysql_field_as_value(row, name, |v: Vec<u8>| Some(v.into())),
+        Type::Json => mysql_field_as_value(row, name, |v: String| {
+            let value: serde_json::Value = serde_json::from_str(&v).ok()?;
+            Some(value.into())
+        }),
+        Type::DateTimeNaive => mysql_field_as_value(row, name, |v: NaiveDateTime| {
+            let timestamp = v.and_utc().timestamp_nanos_opt()?;
+            Some(DateTimeNaive::new(timestamp).into())
+        }),
+        Type::DateTimeUtc => mysql_field_as_value(row, name, |v: NaiveDateTime| {
+            let timestamp = v.and_utc().timestamp_nanos_opt()?;
+            Some(DateTimeUtc::new(timestamp).into())
+        }),
+        Type::Any => {
+            let dtype = match row.as_ref(
+                row.columns_ref()
+                    .iter()
+                    .position(|column| column.name_str() == name)?,
+            )? {
+                MySqlValue::NULL => return Some(None),
+                MySqlValue::Int(_) | MySqlValue::UInt(_) => Type::Int,
+                MySqlValue::Float(_) | MySqlValue::Double(_) => Type::Float,
+                MySqlValue::Bytes(bytes) if std::str::from_utf8(bytes).is_ok() => Type::String,
+                MySqlValue::Bytes(_) => Type::Bytes,
+                MySqlValue::Date(..) => Type::DateTimeNaive,
+                MySqlValue::Time(..) => return None,
+            };
+            mysql_typed_value(row, name, &dtype)
+        }
+        _ => None,
+    }
+}
+
+fn mysql_value(row: &MySqlRow, name: &str, dtype: &Type) -> Result<Value, Box<ConversionError>> {
+    let typed_value = mysql_typed_value(row, name, dtype.unoptionalize());
+    field_value(typed_value, name, dtype, || {
+        row.columns_ref()
+            .iter()
+            .find(|column| column.name_str() == name)
+            .map(|column| format!("value of MySQL type {:?}", column.column_type()))
+    })
+}
+
+const SQL_SERVER_NAME: &str = "SQL Server";
+
+/// A connection to a Microsoft SQL Server database, binding the high-water mark as the `@P1`
+/// parameter of the query.
+pub struct SqlServerPollingClient {
+    runtime: AsyncRuntime,
+    client: SqlServerClient<Compat<TcpStream>>,
+}
+
+impl SqlServerPollingClient {
+    pub fn connect(
+        runtime: AsyncRuntime,
+        connection_string: &str,
+    ) -> Result<Self, SqlPollingError> {
+        let config = SqlServerConfig::from_ado_string(connection_string)?;
+        let client = runtime.block_on(async {
+            let tcp = TcpStream::connect(config.get_addr()).await?;
+            tcp.set_nodelay(true)?;
+            Ok::<_, tiberius::error::Error>(
+                SqlServerClient::connect(config, tcp.compat_write()).await?,
+            )
+        })?;
+        Ok(Self { runtime, client })
+    }
+}
+
+impl SqlPollingClient for SqlServerPollingClient {
+    fn fetch_rows(
+        &mut self,
+        query: &str,
+        cursor: &Value,
+        schema: &[(String, Type)],
+    ) -> Result<Vec<ValuesMap>, SqlPollingError> {
+        let mut query = SqlServerQuery::new(query);
+        match cursor {
+            Value::Bool(b) => query.bind(*b),
+            Value::Int(i) => query.bind(*i),
+            Value::Float(f) => query.bind(f.into_inner()),
+            Value::String(s) => query.bind(s.to_string()),
+            Value::DateTimeNaive(dt) => query.bind(dt.as_chrono_datetime()),
+            Value::DateTimeUtc(dt) => query.bind(dt.as_chrono_datetime().and_utc()),
+            other => {
+                return Err(SqlPollingError::UnsupportedHighWaterMark(
+                    other.kind(),
+                    SQL_SERVER_NAME,
+                ))
+            }
+        }
+        let client = &mut self.client;
+        let rows = self
+            .runtime
+            .block_on(async { query.query(client).await?.into_first_result().await })?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let values: HashMap<_, _> = schema
+                    .iter()
+                    .map(|(name, dtype)| (name.clone(), sql_server_value(row, name, dtype)))
+                    .collect();
+                values.into()
+            })
+            .collect())
+    }
+
+    fn database_name(&self) -> &'static str {
+        SQL_SERVER_NAME
+    }
+}
+
+fn sql_server_field_as_value<'a, T: SqlServerFromSql<'a>>(
+    row: &'a SqlServerRow,
+    name: &str,
+    convert: impl Fn(T) -> Option<Value>,
+) -> Option<Option<Value>> {
+    row.try_get::<T, _>(name)
+        .ok()
+        .map(|value| value.and_then(convert))
+}
+
+/// Reads the field of a SQL Server row as a value of the given type. The outer `None` means
+/// that the field can't be read as this type.
+fn sql_server_typed_value(row: &SqlServerRow, name: &str, dtype: &Type) -> Option<Option<Value>> {
+    match dtype {
+        Type::Bool => sql_server_field_as_value(row, name, |v: bool| Some(v.into())),
+        Type::Int => sql_server_field_as_value(row, name, |v: i64| Some(v.into()))
+            .or_else(|| sql_server_field_as_value(row, name, |v: i32| Some(i64::from(v).into())))
+            .or_else(|| sql_server_field_as_value(row, name, |v: i16| Some(i64::from(v).into())))
+            .or_else(|| sql_server_field_as_value(row, name, |v: u8| Some(i64::from(v).into()))),
+        Type::Float => sql_server_field_as_value(row, name, |v: f64| Some(v.into()))
+            .or_else(|| sql_server_field_as_value(row, name, |v: f32| Some(v.into()))),
+        Type::String => sql_server_field_as_value(row, name, |v: &str| Some(v.into())),
+        Type::Bytes => sql_server_field_as_value(row, name, |v: &[u8]| Some(v.into())),
+        Type::DateTimeNaive => sql_server_field_as_value(row, name, |v: NaiveDateTime| {
+            let timestamp = v.and_utc().timestamp_nanos_opt()?;
+            Some(DateTimeNaive::new(timestamp).into())
+        }),
+        Type::DateTimeUtc => sql_server_field_as_value(row, name, |v: DateTime<Utc>| {
+            let timestamp = v.timestamp_nanos_opt()?;
+            Some(DateTimeUtc::new(timestamp).into())
+        }),
+        Type::Any => [
+            Type::Int,
+            Type::Float,
+            Type::Bool,
+            Type::String,
+            Type::Bytes,
+            Type::DateTimeUtc,
+            Type::DateTimeNaive,
+        ]
+        .iter()
+        .find_map(|dtype| sql_server_typed_value(row, name, dtype)),
+        _ => None,
+    }
+}
+
+fn sql_server_value(
+    row: &SqlServerRow,
+    name: &str,
+    dtype: &Type,
+) -> Result<Value, Box<ConversionError>> {
+    let typed_value = sql_server_typed_value(row, name, dtype.unoptionalize());
+    field_value(typed_value, name, dtype, || {
+        row.columns()
+            .iter()
+            .find(|column| column.name() == name)
+            .map(|column| format!("value of SQL Server type {:?}", column.column_type()))
+    })
+}
+
+pub struct SqlPollingReader<C: SqlPollingClient> {
+    client: C,
+    config: SqlPollingConfig,
+    schema: Vec<(String, Type)>,
+
+    high_water_mark: Value,
+    total_entries_read: u64,
+    queued_updates: VecDeque<ReadResult>,
+}
+
+impl<C: SqlPollingClient> SqlPollingReader<C> {
+    pub fn new(
+        client: C,
+        config: SqlPollingConfig,
+        schema: Vec<(String, Type)>,
+    ) -> Result<Self, SqlPollingError> {
+        if !schema.iter().any(|(name, _)| *name == config.cursor_column) {
+            return Err(SqlPollingError::UnknownCursorColumn(config.cursor_column));
+        }
+        Ok(Self {
+            client,
+            high_water_mark: config.start_from.clone(),
+            config,
+            schema,
+            total_entries_read: 0,
+            queued_updates: VecDeque::new(),
+        })
+    }
+
+    fn poll(&mut self) -> Result<(), SqlPollingError> {
+        let rows =
+            self.client
+                .fetch_rows(&self.config.query, &self.high_water_mark, &self.schema)?;
+        for values in rows {
+            let cursor = match values.get(&self.config.cursor_column) {
+                Some(Ok(Value::None)) => Err("NULL".to_string()),
+                Some(Ok(cursor)) => Ok(cursor.clone()),
+                Some(Err(e)) => Err(e.to_string()),
+                None => unreachable!("the cursor column is a part of the schema"),
+            }
+            .map_err(|e| SqlPollingError::InvalidCursor(self.config.cursor_column.clone(), e))?;
+            if cursor > self.high_water_mark {
+                self.high_water_mark = cursor;
+            }
+            self.total_entries_read += 1;
+            let offset = (
+                OffsetKey::Empty,
+                OffsetValue::SqlPollingCursor {
+                    high_water_mark: self.high_water_mark.clone(),
+                    total_entries_read: self.total_entries_read,
+                },
+            );
+            self.queued_updates.push_back(ReadResult::Data(
+                ReaderContext::from_diff(DataEventType::Insert, None, values),
+                offset,
+            ));
+        }
+        if !self.queued_updates.is_empty() {
+            self.queued_updates.push_back(ReadResult::FinishedSource {
+                commit_allowed: true,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<C: SqlPollingClient> Reader for SqlPollingReader<C> {
+    fn read(&mut self) -> Result<ReadResult, ReadError> {
+        loop {
+            if let Some(queued_update) = self.queued_updates.pop_front() {
+                return Ok(queued_update);
+            }
+            self.poll()?;
+            if self.queued_updates.is_empty() {
+                sleep(self.config.poll_interval);
+            }
+        }
+    }
+
+    fn seek(&mut self, frontier: &OffsetAntichain) -> Result<(), ReadError> {
+        let offset_value = frontier.get_offset(&OffsetKey::Empty);
+        if let Some(offset) = offset_value {
+            if let OffsetValue::SqlPollingCursor {
+                high_water_mark,
+                total_entries_read,
+            } = offset
+            {
+                info!("Continuing the polling from the cursor value {high_water_mark}");
+                self.high_water_mark = high_water_mark.clone();
+                self.total_entries_read = *total_entries_read;
+            } else {
+                error!("Unexpected offset type for SQL polling reader: {offset:?}");
+            }
+        }
+        Ok(())
+    }
+
+    fn short_description(&self) -> Cow<'static, str> {
+        format!(
+            "SqlPolling({}, {})",
+            self.client.database_name(),
+            self.config.cursor_column
+        )
+        .into()
+    }
+
+    fn storage_type(&self) -> StorageType {
+        StorageType::SqlPolling
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright © 2024 Pathway
+
+//! Connectors wrapping an external command. The reader streams the lines the command prints to
+//! its standard output, and the writer feeds the serialized entries to its standard input, one
+//! per line. The command is started when it is first read from or written to, by the worker
+//! that runs the connector. When it exits, it is started again according to its restart policy.
+//!
+//! The standard streams of the command are connected with the pipes from [`crate::pipe`], so
+//! that the ends kept by the engine are not inherited by the other spawned processes.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::sleep;
+use std::time::Duration;
+
+use log::{error, info, warn};
+
+use crate::connectors::data_format::FormatterContext;
+use crate::connectors::data_storage::{
+    DataEventType, ReadError, ReadResult, Reader, ReaderContext, StorageType, WriteError, Writer,
+};
+use crate::connectors::{OffsetKey, OffsetValue};
+use crate::persistence::frontier::OffsetAntichain;
+use crate::pipe::{pipe, ReaderType, WriterType};
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SubprocessError {
+    #[error("failed to start command {0:?}: {1}")]
+    SpawnFailed(String, #[source] io::Error),
+
+    #[error("command {0:?} failed: {1}")]
+    Failed(String, ExitStatus),
+
+    #[error("command {0:?} failed after {1} restarts: {2}")]
+    TooManyRestarts(String, usize, ExitStatus),
+}
+
+/// When the command is started again after it exits.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RestartPolicy {
+    /// The connector finishes when the command exits, with an error if the command failed.
+    #[default]
+    Never,
+    /// The command is started again only if it failed.
+    OnFailure,
+    /// The command is always started again.
+    Always,
+}
+
+#[derive(Clone, Debug)]
+pub struct SubprocessCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub working_directory: Option<PathBuf>,
+    pub restart_policy: RestartPolicy,
+    /// The maximum number of restarts. If `None`, the command is restarted indefinitely.
+    pub max_restarts: Option<usize>,
+    pub restart_delay: Duration,
+}
+
+impl SubprocessCommand {
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).envs(&self.env);
+        if let Some(working_directory) = &self.working_directory {
+            command.current_dir(working_directory);
+        }
+        command
+    }
+
+    fn spawn(&self, stdin: Stdio, stdout: Stdio) -> Result<Child, SubprocessError> {
+        // `Command` owns the ends of the pipes given to the child and closes them when dropped,
+        // so that only the child keeps them open
+        self.command()
+            .stdin(stdin)
+            .stdout(stdout)
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| SubprocessError::SpawnFailed(self.program.clone(), e))
+    }
+
+    fn spawn_with_stdout(&self) -> Result<(Child, File), SubprocessError> {
+        let pipe = pipe(ReaderType::Blocking, WriterType::Blocking)
+            .map_err(|e| SubprocessError::SpawnFailed(self.program.clone(), e))?;
+        let child = self.spawn(Stdio::null(), Stdio::from(pipe.writer))?;
+        Ok((child, File::from(pipe.reader)))
+    }
+
+    fn spawn_with_stdin(&self) -> Result<(Child, File), SubprocessError> {
+        let pipe = pipe(ReaderType::Blocking, WriterType::Blocking)
+            .map_err(|e| SubprocessError::SpawnFailed(self.program.clone(), e))?;
+        let child = self.spawn(Stdio::from(pipe.reader), Stdio::inherit())?;
+        Ok((child, File::from(pipe.writer)))
+    }
+}
+
+/// Keeps track of the restarts of the command.
+struct Supervisor {
+    command: SubprocessCommand,
+    restarts: usize,
+}
+
+impl Supervisor {
+    fn new(command: SubprocessCommand) -> Self {
+        Self {
+            command,
+            restarts: 0,
+        }
+    }
+
+    /// Decides whether the command that exited with `status` has to be started again. Waits for
+    /// the restart delay if it does. Fails if the command failed and won't be started again.
+    fn should_restart(&mut self, status: ExitStatus) -> Result<bool, SubprocessError> {
+        let program = &self.command.program;
+        let wanted = match self.command.restart_policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => !status.success(),
+            RestartPolicy::Always => true,
+        };
+        let allowed = self
+            .command
+            .max_restarts
+            .is_none_or(|max_restarts| self.restarts < max_restarts);
+        if wanted && allowed {
+            self.restarts += 1;
+            warn!(
+                "Command {program:?} exited with {status}, restarting it in {:?} (restart {})",
+                self.command.restart_delay, self.restarts
+            );
+            sleep(self.command.restart_delay);
+            Ok(true)
+        } else if status.success() {
+            info!("Command {program:?} has finished");
+            Ok(false)
+        } else if wanted {
+            Err(SubprocessError::TooManyRestarts(
+                program.clone(),
+                self.restarts,
+                status,
+            ))
+        } else {
+            Err(SubprocessError::Failed(program.clone(), status))
+        }
+    }
+}
+
+struct RunningChild<S> {
+    child: Child,
+    stream: S,
+}
+
+pub struct SubprocessReader {
+    supervisor: Supervisor,
+    running: Option<RunningChild<BufReader<File>>>,
+    is_finished: bool,
+    total_entries_read: usize,
+}
+
+impl SubprocessReader {
+    pub fn new(command: SubprocessCommand) -> Self {
+        Self {
+            supervisor: Supervisor::new(command),
+            running: None,
+            is_finished: false,
+            total_entries_read: 0,
+        }
+    }
+
+    fn start(&mut self) -> Result<(), SubprocessError> {
+        let (child, stdout) = self.supervisor.command.spawn_with_stdout()?;
+        self.running = Some(RunningChild {
+            child,
+            stream: BufReader::new(stdout),
+        });
+        Ok(())
+    }
+}
+
+impl Reader for SubprocessReader {
+    fn read(&mut self) -> Result<ReadResult, ReadError> {
+        loop {
+            if self.is_finished {
+                return Ok(ReadResult::Finished);
+            }
+            if self.running.is_none() {
+                self.start()?;
+            }
+            let running = self.running.as_mut().unwrap();
+            let mut line = Vec::new();
+            if running.stream.read_until(b'\n', &mut line)? > 0 {
+                if line.ends_with(b"\n") {
+                    line.pop();
+                    if line.ends_with(b"\r") {
+                        line.pop();
+                    }
+                }
+                self.total_entries_read += 1;
+                let offset = (
+                    OffsetKey::Empty,
+                    OffsetValue::SubprocessReadEntriesCount(self.total_entries_read),
+                );
+                return Ok(ReadResult::Data(
+                    ReaderContext::from_raw_bytes(DataEventType::Insert, line),
+                    offset,
+                ));
+            }
+
+            // The output is closed, so the command has exited or is about to
+            let status = running.child.wait()?;
+            self.running = None;
+            let restart = self.supervisor.should_restart(status);
+            self.is_finished = !matches!(restart, Ok(true));
+            restart?;
+        }
+    }
+
+    fn seek(&mut self, frontier: &OffsetAntichain) -> Result<(), ReadError> {
+        // The output of the command can't be replayed, only the numbering of the entries is
+        // continued so that the new entries don't get the keys of the ones read before
+        let offset_value = frontier.get_offset(&OffsetKey::Empty);
+        if let Some(offset) = offset_value {
+            if let OffsetValue::SubprocessReadEntriesCount(last_run_entries_read) = offset {
+                self.total_entries_read = *last_run_entries_read;
+            } else {
+                error!("Unexpected offset type for subprocess reader: {offset:?}");
+            }
+        }
+        Ok(())
+    }
+
+    fn short_description(&self) -> Cow<'static, str> {
+        format!("Subprocess({})", self.supervisor.command.program).into()
+    }
+
+    fn storage_type(&self) -> StorageType {
+        StorageType::Subprocess
+    }
+}
+
+impl Drop for SubprocessReader {
+    fn drop(&mut self) {
+        if let Some(mut running) = self.running.take() {
+            // The computation is over, the command won't be read from anymore
+            if let Err(e) = running.child.kill() {
+                warn!("Failed to stop command: {e}");
+            }
+            let _ = running.child.wait();
+        }
+    }
+}
+
+pub struct SubprocessWriter {
+    supervisor: Supervisor,
+    running: Option<RunningChild<BufWriter<File>>>,
+    is_finished: bool,
+}
+
+impl SubprocessWriter {
+    pub fn new(command: SubprocessCommand) -> Self {
+        Self {
+            supervisor: Supervisor::new(command),
+            running: None,
+            is_finished: false,
+        }
+    }
+
+    fn start(&mut self) -> Result<(), SubprocessError> {
+        let (child, stdin) = self.supervisor.command.spawn_with_stdin()?;
+        self.running = Some(RunningChild {
+            child,
+            stream: BufWriter::new(stdin),
+        });
+        Ok(())
+    }
+
+    /// Called when the input of the command is closed. Returns whether the command can be
+    /// started again according to its restart policy.
+    fn on_exit(&mut self) -> Result<bool, WriteError> {
+        let Some(mut running) = self.running.take() else {
+            return Ok(false);
+        };
+        let status = running.child.wait()?;
+        let restart = self.supervisor.should_restart(status);
+        self.is_finished = !matches!(restart, Ok(true));
+        Ok(restart?)
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> Result<(), WriteError> {
+        loop {
+            if self.is_finished {
+                return Err(io::Error::from(ErrorKind::BrokenPipe).into());
+            }
+            if self.running.is_none() {
+                self.start()?;
+            }
+            let running = self.running.as_mut().unwrap();
+            let result = running
+                .stream
+                .write_all(line)
+                .and_then(|()| running.stream.write_all(b"\n"));
+            match result {
+                Ok(()) => return Ok(()),
+                // The part of the line already written is lost, the whole line is written to
+                // the restarted command
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => {
+                    if !self.on_exit()? {
+                        return Err(e.into());
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+impl Writer for SubprocessWriter {
+    fn write(&mut self, data: FormatterContext) -> Result<(), WriteError> {
+        for payload in data.payloads {
+            self.write_line(&payload.into_raw_bytes()?)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, _forced: bool) -> Result<(), WriteError> {
+        let Some(running) = &mut self.running else {
+            return Ok(());
+        };
+        match running.stream.flush() {
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => {
+                // The buffered entries are lost together with the command that exited
+                self.on_exit()?;
+                Ok(())
+            }
+            result => Ok(result?),
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("Subprocess({})", self.supervisor.command.program)
+    }
+}
+
+impl Drop for SubprocessWriter {
+    fn drop(&mut self) {
+        if let Some(running) = self.running.take() {
+            let RunningChild { mut child, stream } = running;
+            // Closing the input lets the command finish processing the entries and exit
+            if let Err(e) = stream.into_inner().map_err(io::IntoInnerError::into_error) {
+                warn!("Failed to send the final entries to the command: {e}");
+            }
+            match child.wait() {
+                Ok(status) if !status.success() => {
+                    error!(
+                        "Command {:?} failed: {status}",
+                        self.supervisor.command.program
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to wait for the command to finish: {e}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright © 2024 Pathway
+
+//! Counters of the violations of the data quality constraints.
+//!
+//! The constraints are checked by the dataflow built by `pathway.quality.check`, which reports
+//! every row that starts or stops violating a constraint. The counters are kept per check and
+//! constraint and are exposed with the other metrics of the monitoring HTTP server.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ViolationCounts {
+    /// The number of rows violating the constraint at the moment.
+    pub current: u64,
+    /// The number of times a row started violating the constraint.
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ConstraintId {
+    pub check: String,
+    pub constraint: String,
+}
+
+static VIOLATIONS: Mutex<BTreeMap<ConstraintId, ViolationCounts>> = Mutex::new(BTreeMap::new());
+
+/// Records that `diff` rows started violating the constraint, or stopped if `diff` is
+/// negative.
+pub fn record_violations(id: ConstraintId, diff: isize) {
+    let mut violations = VIOLATIONS.lock().unwrap();
+    let counts = violations.entry(id).or_default();
+    if diff > 0 {
+        counts.current += diff.unsigned_abs() as u64;
+        counts.total += diff.unsigned_abs() as u64;
+    } else {
+        counts.current = counts.current.saturating_sub(diff.unsigned_abs() as u64);
+    }
+}
+
+pub fn violation_counts() -> Vec<(ConstraintId, ViolationCounts)> {
+    VIOLATIONS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(id, counts)| (id.clone(), *counts))
+        .collect()
+}
@@ -4,21 +4,28 @@
 #![allow(clippy::non_canonical_partial_ord_impl)] // False positive with Derivative
 
 mod async_transformer;
+pub mod cluster_tls;
 mod complex_columns;
 pub mod config;
+pub mod discovery;
 mod export;
+mod keyed_mat_mul;
 pub mod maybe_total;
+pub mod memory;
 pub mod monitoring;
 pub mod operators;
 pub mod persist;
 pub mod shard;
+pub mod shutdown;
 pub mod time;
 mod variable;
 
 use crate::connectors::adaptors::{InputAdaptor, UpsertSession};
 use crate::connectors::data_format::{Formatter, Parser};
-use crate::connectors::data_storage::{ReaderBuilder, Writer};
+use crate::connectors::data_storage::{ReaderBuilder, WriteError, Writer};
+use crate::connectors::idempotency::IdempotencyKeyGenerator;
 use crate::connectors::monitoring::{ConnectorMonitor, OutputConnectorStats};
+use crate::connectors::pausing::{shared_pause_switch, ConnectorKind};
 use crate::connectors::synchronization::{
     ConnectorGroupDescriptor, ConnectorSynchronizer, SharedConnectorSynchronizer,
 };
@@ -26,6 +33,8 @@ use crate::connectors::{Connector, PersistenceMode, SessionType, SnapshotAccess}
 use crate::engine::dataflow::monitoring::{OperatorProbe, Prober, ProberStats};
 use crate::engine::dataflow::operators::external_index::UseExternalIndexAsOfNow;
 use crate::engine::dataflow::operators::gradual_broadcast::GradualBroadcast;
+use crate::engine::dataflow::operators::group_operation::ApplyGroupOperation;
+use crate::engine::dataflow::operators::sampling::Sample;
 use crate::engine::dataflow::operators::time_column::{TimeColumnForget, TimeColumnFreeze};
 use crate::engine::dataflow::operators::ExtendedProbeWith;
 use crate::engine::graph::JoinExactlyOnce;
@@ -37,9 +46,12 @@ use crate::engine::reduce::{
 use crate::engine::telemetry::Config as TelemetryConfig;
 use crate::engine::value::HashInto;
 use crate::persistence::config::PersistenceManagerOuterConfig;
+use crate::persistence::handoff::HandoffCoordinator;
 use crate::persistence::tracker::{RequiredPersistenceMode, SharedWorkerPersistentStorage};
 use crate::persistence::{IntoPersistentId, PersistenceTime, UniqueName};
-use crate::retry::{execute_with_retries, RetryConfig};
+use crate::retry::{
+    execute_with_circuit_breaker, shared_circuit_breaker, CircuitBreaker, RetryPolicy,
+};
 
 use std::borrow::{Borrow, Cow};
 use std::cell::RefCell;
@@ -79,7 +91,7 @@ use differential_dataflow::trace::implementations::spine_fueled::Spine;
 use differential_dataflow::{AsCollection as _, Data};
 use differential_dataflow::{Collection, ExchangeData};
 use hyperloglogplus::{HyperLogLog, HyperLogLogPlus};
-use id_arena::Arena;
+use id_arena::{Arena, ArenaBehavior};
 use itertools::{chain, process_results, Itertools};
 use log::{error, info};
 use ndarray::ArrayD;
@@ -94,7 +106,6 @@ use timely::dataflow::operators::probe::Handle as ProbeHandle;
 use timely::dataflow::operators::{Filter, Inspect, Probe};
 use timely::dataflow::operators::{Map, ToStream as _};
 use timely::dataflow::scopes::Child;
-use timely::execute;
 use timely::order::{Product, TotalOrder};
 use timely::progress::timestamp::Refines;
 use timely::progress::Timestamp as TimestampTrait;
@@ -104,6 +115,7 @@ use self::async_transformer::async_transformer;
 use self::complex_columns::complex_columns;
 use self::export::{export_table, import_table};
 use self::maybe_total::MaybeTotalScope;
+use self::memory::MemoryAccountant;
 use self::operators::output::{ConsolidateForOutput, OutputBatch};
 use self::operators::prev_next::add_prev_next_pointers;
 use self::operators::stateful_reduce::StatefulReduce;
@@ -111,6 +123,7 @@ use self::operators::time_column::TimeColumnBuffer;
 use self::operators::{ArrangeWithTypes, FlatMapBatchedWithDeletionsFirst, MapWrapped};
 use self::operators::{MaybeTotal, Reshard};
 use self::shard::Shard;
+use self::shutdown::CancellationToken;
 use self::time::{Epsilon, MaybeEpsilon, OriginalOrRetraction};
 use self::variable::SafeVariable;
 use super::error::{register_custom_panic_hook, DataError, DataResult, DynError, DynResult, Trace};
@@ -119,18 +132,24 @@ use super::external_index_wrappers::{ExternalIndexData, ExternalIndexQuery};
 use super::graph::{
     DataRow, ExportedTable, OperatorProperties, SubscribeCallbacks, SubscribeConfig,
 };
+use super::health::{self, maybe_run_health_server};
 use super::http_server::maybe_run_http_server_thread;
+use super::interning::StringInterner;
 use super::license::License;
+use super::log_context;
 use super::progress_reporter::{maybe_run_reporter, MonitoringLevel};
 use super::reduce::{
     AnyReducer, ArgMaxReducer, ArgMinReducer, ArraySumReducer, CountReducer, EarliestReducer,
     FloatSumReducer, LatestReducer, MaxReducer, MinReducer, ReducerImpl, SortedTupleReducer,
     StatefulCombineFn, StatefulReducer, TupleReducer, UniqueReducer,
 };
+use super::reload;
 use super::report_error::{
     LogError, ReportError, ReportErrorExt, SpawnWithReporter, UnwrapWithErrorLogger,
     UnwrapWithReporter,
 };
+use super::spans;
+use super::stateless_pool;
 use super::telemetry::maybe_run_telemetry_thread;
 use super::{
     BatchWrapper, ColumnHandle, ColumnPath, ColumnProperties, ComplexColumn, Error, ErrorLogHandle,
@@ -241,6 +260,34 @@ impl<S: MaybeTotalScope> Values<S> {
     }
 }
 
+/// Shares one allocation between the equal strings of the values of a collection about to be
+/// arranged, so that the arrangement keeps every distinct string of a column once.
+fn intern_strings<S: MaybeTotalScope, D: Data>(
+    collection: &Collection<S, D>,
+    value: fn(&mut D) -> &mut Value,
+) -> Collection<S, D> {
+    let mut interner = StringInterner::from_env();
+    if !interner.is_enabled() {
+        return collection.clone();
+    }
+    collection.map_named("intern_strings", move |mut data| {
+        interner.intern_value(value(&mut data));
+        data
+    })
+}
+
+impl<S: MaybeTotalScope> Values<S> {
+    /// The generic collection with interned strings. Integers and pointers have none.
+    fn interned(&self) -> Collection<S, (Key, Value)> {
+        match self {
+            Self::Generic { generic_collection } => {
+                intern_strings(generic_collection, |(_key, value)| value)
+            }
+            Self::Int { .. } | Self::Pointer { .. } => self.as_generic().clone(),
+        }
+    }
+}
+
 impl<S: MaybeTotalScope> From<Collection<S, (Key, i64)>> for Values<S> {
     fn from(int_collection: Collection<S, (Key, i64)>) -> Self {
         Values::Int {
@@ -414,7 +461,7 @@ impl<S: MaybeTotalScope> ColumnData<S> {
         match self {
             Self::Arranged { arranged, .. } => arranged,
             Self::Collection { arranged, .. } => {
-                arranged.get_or_init(|| self.collection().arrange())
+                arranged.get_or_init(|| self.collection().interned().arrange())
             }
         }
     }
@@ -434,6 +481,7 @@ impl<S: MaybeTotalScope> ColumnData<S> {
             } => persisted_arranged.get_or_try_init(|| {
                 Ok(self
                     .collection()
+                    .interned()
                     .maybe_persist_internal(
                         persistence_wrapper,
                         pollers,
@@ -658,9 +706,19 @@ impl ErrorLog {
 struct ErrorLogger {
     operator_id: i64,
     error_log: Option<ErrorLog>,
+    error_reporter: ErrorReporter,
+    terminate_on_error: bool,
 }
 
 impl ErrorLogger {
+    fn should_terminate(&self, error: &DataError) -> bool {
+        match error {
+            DataError::Fatal(_) => true,
+            DataError::ToErrorLog(_) | DataError::ErrorInValue => false,
+            _ => self.terminate_on_error,
+        }
+    }
+
     fn inner_log(&self, error: &DataError, trace: Option<String>) {
         if matches!(error, DataError::ErrorInValue) {
             return;
@@ -683,11 +741,19 @@ impl ErrorLogger {
 
 impl LogError for ErrorLogger {
     fn log_error(&self, error: DataError) {
+        if self.should_terminate(&error) {
+            self.error_reporter.report_and_panic(error);
+        }
         self.inner_log(&error, None);
     }
 
     fn log_error_with_trace(&self, error: DynError, trace: &Trace) {
-        self.inner_log(&error.into(), Some(format!("{trace}")));
+        let error = error.into();
+        if self.should_terminate(&error) {
+            self.error_reporter
+                .report_and_panic_with_trace(DynError::from(error), trace);
+        }
+        self.inner_log(&error, Some(format!("{trace}")));
     }
 }
 
@@ -721,10 +787,12 @@ struct DataflowGraphInner<S: MaybeTotalScope> {
     flushers: Vec<Box<dyn FnMut() -> SystemTime>>,
     pollers: Vec<Poller>,
     connector_threads: Vec<JoinHandle<()>>,
+    reader_threads: Vec<JoinHandle<()>>,
     connector_monitors: Vec<Rc<RefCell<ConnectorMonitor>>>,
     error_reporter: ErrorReporter,
     input_probe: ProbeHandle<S::Timestamp>,
     output_probe: ProbeHandle<S::Timestamp>,
+    output_connector_probes: Vec<(String, ProbeHandle<S::Timestamp>)>,
     probers: Vec<Prober>,
     probes: HashMap<usize, OperatorProbe<S::Timestamp>>,
     ignore_asserts: bool,
@@ -1130,13 +1198,19 @@ where
                 worker_persistent_storage.create_operator_snapshot_reader(persistent_id)?;
             let writer =
                 worker_persistent_storage.create_operator_snapshot_writer(persistent_id)?;
-            let (persisted_collection, poller, thread_handle) =
-                self.persisted_stateful_reduce_named(name, logic, reader, writer);
+            let (persisted_collection, poller, thread_handle) = self
+                .persisted_stateful_reduce_named(
+                    name,
+                    graph.error_reporter.clone(),
+                    logic,
+                    reader,
+                    writer,
+                );
             graph.pollers.push(poller);
             graph.connector_threads.push(thread_handle);
             Ok(persisted_collection)
         } else {
-            Ok(self.stateful_reduce_named(name, logic))
+            Ok(self.stateful_reduce_named(name, graph.error_reporter.clone(), logic))
         }
     }
 }
@@ -1226,10 +1300,12 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
             flushers: Vec::new(),
             pollers: Vec::new(),
             connector_threads: Vec::new(),
+            reader_threads: Vec::new(),
             connector_monitors: Vec::new(),
             error_reporter,
             input_probe: ProbeHandle::new(),
             output_probe: ProbeHandle::new(),
+            output_connector_probes: Vec::new(),
             probers: Vec::new(),
             probes: HashMap::new(),
             ignore_asserts,
@@ -1592,10 +1668,12 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
                 // split this closure here into two - first part (extraction from paths) before consolidation
                 // and second part (evals) after consolidation
                 for (i, expression_data) in expressions.iter().enumerate() {
-                    let result_for_expression: Vec<_> = args
-                        .chunks(max_expression_batch_size)
-                        .flat_map(|args| expression_data.expression.eval(args))
-                        .collect();
+                    let result_for_expression = stateless_pool::map_chunks(
+                        &args,
+                        max_expression_batch_size,
+                        expression_data.gil,
+                        |args| expression_data.expression.eval(args),
+                    );
                     for (j, result_i) in result_for_expression.into_iter().enumerate() {
                         let result_i = result_i.unwrap_or_log_with_trace(
                             error_logger.as_ref(),
@@ -1714,10 +1792,12 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
                                 }
                             }
 
-                            let result_for_expression: Vec<_> = args_for_expression
-                                .chunks(max_expression_batch_size)
-                                .flat_map(|args| expression_data.expression.eval(args))
-                                .collect();
+                            let result_for_expression = stateless_pool::map_chunks(
+                                &args_for_expression,
+                                max_expression_batch_size,
+                                expression_data.gil,
+                                |args| expression_data.expression.eval(args),
+                            );
 
                             for (result_i, (position, key)) in result_for_expression
                                 .into_iter()
@@ -1994,6 +2074,54 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
             .alloc(Table::from_collection(new_table).with_properties(table_properties)))
     }
 
+    fn sample_table(
+        &mut self,
+        table_handle: TableHandle,
+        fraction: f64,
+        seed: u64,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        let table = self
+            .tables
+            .get(table_handle)
+            .ok_or(Error::InvalidTableHandle)?;
+        let new_table = table.values().sample(fraction, seed);
+        Ok(self
+            .tables
+            .alloc(Table::from_collection(new_table).with_properties(table_properties)))
+    }
+
+    fn reservoir_sample_table(
+        &mut self,
+        table_handle: TableHandle,
+        instance_column_path: ColumnPath,
+        size: usize,
+        seed: u64,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        let table = self
+            .tables
+            .get(table_handle)
+            .ok_or(Error::InvalidTableHandle)?;
+
+        let error_reporter = self.error_reporter.clone();
+        let trace = table_properties.trace().clone();
+
+        let new_table = table.values().reservoir_sample(
+            move |key, values| {
+                let instance = instance_column_path
+                    .extract(key, values)
+                    .unwrap_with_reporter_and_trace(&error_reporter, &trace);
+                Key::for_value(&instance)
+            },
+            size,
+            seed,
+        );
+        Ok(self
+            .tables
+            .alloc(Table::from_collection(new_table).with_properties(table_properties)))
+    }
+
     fn freeze(
         &mut self,
         table_handle: TableHandle,
@@ -2247,6 +2375,7 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
             let wrapped = match value {
                 Value::IntArray(array) => Ok(flatten_ndarray(&array)),
                 Value::FloatArray(array) => Ok(flatten_ndarray(&array)),
+                Value::Float32Array(array) => Ok(flatten_ndarray(&array)),
                 Value::Tuple(array) => Ok((*array).to_vec()),
                 Value::String(s) => Ok((*s)
                     .chars()
@@ -2541,6 +2670,65 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
             .alloc(Table::from_collection(new_values).with_properties(table_properties)))
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn keyed_mat_mul(
+        &mut self,
+        table_handle: TableHandle,
+        group_path: ColumnPath,
+        operand_path: ColumnPath,
+        matrix_table_handle: TableHandle,
+        matrix_group_path: ColumnPath,
+        matrix_path: ColumnPath,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        let table = self
+            .tables
+            .get(table_handle)
+            .ok_or(Error::InvalidTableHandle)?;
+        let matrix_table = self
+            .tables
+            .get(matrix_table_handle)
+            .ok_or(Error::InvalidTableHandle)?;
+
+        let error_reporter = self.error_reporter.clone();
+        let matrices = matrix_table.values().map_named(
+            "keyed_mat_mul extracting matrices",
+            move |(key, values)| {
+                let group = matrix_group_path
+                    .extract(&key, &values)
+                    .unwrap_with_reporter(&error_reporter);
+                let matrix = matrix_path
+                    .extract(&key, &values)
+                    .unwrap_with_reporter(&error_reporter);
+                (Key::for_values(&[group]), matrix)
+            },
+        );
+        let error_reporter = self.error_reporter.clone();
+        let rows =
+            table
+                .values()
+                .map_named("keyed_mat_mul extracting operands", move |(key, values)| {
+                    let group = group_path
+                        .extract(&key, &values)
+                        .unwrap_with_reporter(&error_reporter);
+                    let operand = operand_path
+                        .extract(&key, &values)
+                        .unwrap_with_reporter(&error_reporter);
+                    (key, (Key::for_values(&[group]), (values, operand)))
+                });
+
+        let operation = KeyedMatMul::new(self.create_error_logger()?, table_properties.trace());
+        let new_values = matrices.apply_group_operation(&rows, operation).map_named(
+            "wrap keyed_mat_mul result into value",
+            |(key, ((values, _operand), product))| {
+                (key, Value::Tuple(Arc::from([values, product])))
+            },
+        );
+        Ok(self
+            .tables
+            .alloc(Table::from_collection(new_values).with_properties(table_properties)))
+    }
+
     fn ix_table(
         &mut self,
         to_ix_handle: TableHandle,
@@ -2668,8 +2856,14 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
         let filter_acc =
             make_option_accessor(query_stream.filter_column, self.error_reporter.clone());
 
+        // shared by the workers, so that they stop calling the index together if it's down
+        let circuit_breaker = shared_circuit_breaker(&format!(
+            "external_index_{}",
+            TableHandle::index(index_stream.table)
+        ));
         let extended_external_index = Box::new(IndexDerivedImpl::new(
             external_index,
+            circuit_breaker,
             self.create_error_logger()?,
             data_acc,
             filter_data_acc,
@@ -2774,8 +2968,10 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
             } else {
                 (join_side, None)
             };
+            let join_side_interned =
+                intern_strings(&join_side_updated, |(_join_key, (_key, values))| values);
             let join_side_arranged: ArrangedByKey<S, Key, (Key, Value)> =
-                join_side_updated.maybe_persist(graph, "join")?.arrange();
+                join_side_interned.maybe_persist(graph, "join")?.arrange();
             Ok((side_with_join_key, retractions, join_side_arranged))
         }
 
@@ -3034,30 +3230,30 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
     }
 
     fn create_error_logger(&self) -> Result<Box<dyn LogError>> {
-        if self.terminate_on_error {
-            Ok(Box::new(self.error_reporter.clone()))
+        let operator_properties = match self.current_operator_properties.as_ref() {
+            Some(operator_properties) => operator_properties,
+            None if self.terminate_on_error => return Ok(Box::new(self.error_reporter.clone())),
+            None => return Err(Error::OperatorIdNotSet),
+        };
+        let error_log = if operator_properties.depends_on_error_log {
+            None
+            // if the current operator depends on error log table, we can't insert errors from it
+            // to the log as it'll prevent dropping InputSession and timely will never finish
         } else {
-            let operator_properties = self
-                .current_operator_properties
-                .as_ref()
-                .ok_or_else(|| Error::OperatorIdNotSet)?;
-            let error_log = if operator_properties.depends_on_error_log {
-                None
-                // if the current operator depends on error log table, we can't insert errors from it
-                // to the log as it'll prevent dropping InputSession and timely will never finish
-            } else {
-                self.current_error_log
-                    .clone()
-                    .or(self.default_error_log.clone())
-            };
-            Ok(Box::new(ErrorLogger {
-                operator_id: operator_properties.id.try_into().map_err(DynError::from)?,
-                error_log,
-            }))
-        }
+            self.current_error_log
+                .clone()
+                .or(self.default_error_log.clone())
+        };
+        Ok(Box::new(ErrorLogger {
+            operator_id: operator_properties.id.try_into().map_err(DynError::from)?,
+            error_log,
+            error_reporter: self.error_reporter.clone(),
+            terminate_on_error: self.terminate_on_error,
+        }))
     }
 
     fn set_operator_properties(&mut self, operator_properties: OperatorProperties) -> Result<()> {
+        memory::set_current_operator(Some(operator_properties.id));
         self.current_operator_properties = Some(operator_properties);
         Ok(())
     }
@@ -3988,7 +4184,7 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
             )?;
 
             self.pollers.push(state.poller);
-            self.connector_threads.push(state.input_thread_handle);
+            self.reader_threads.push(state.input_thread_handle);
             if let Some(persistent_id) = persistent_id {
                 // If there is a persistent id, there's also a persistent storage
                 // It is checked in the beginning of the method
@@ -4160,17 +4356,22 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
         });
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn output_batch(
         stats: &mut OutputConnectorStats,
         mut batch: OutputBatch<Timestamp, (Key, Tuple), isize>,
         data_sink: &mut Box<dyn Writer>,
         data_formatter: &mut Box<dyn Formatter>,
+        circuit_breaker: &CircuitBreaker,
+        retry_policy: &RetryPolicy,
         worker_persistent_storage: Option<&SharedWorkerPersistentStorage>,
         sort_by_indices: Option<&Vec<usize>>,
+        mut idempotency_keys: Option<&mut IdempotencyKeyGenerator>,
     ) -> Result<(), DynError> {
         stats.on_batch_started();
         let time = batch.time;
         let batch_size = batch.data.len();
+        let mut entries_filtered_out = 0;
         if let Some(sort_by_indices) = sort_by_indices {
             Self::prepare_batch_for_output(&mut batch.data, sort_by_indices);
         }
@@ -4179,26 +4380,43 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
                 // Ignore entries, which had been written before
                 continue;
             }
+            if data_formatter
+                .is_filtered_out(&values)
+                .map_err(DynError::from)?
+            {
+                entries_filtered_out += 1;
+                continue;
+            }
 
-            // TODO: provide a way to configure it individually per connector maybe?
-            let retries = if data_sink.retriable() {
+            let retries = retry_policy.max_retries_or(if data_sink.retriable() {
                 OUTPUT_RETRIES
             } else {
                 1
-            };
-
-            execute_with_retries(
+            });
+            // generated once, so that the retried writes of the entry are idempotent too
+            let idempotency_key = idempotency_keys
+                .as_deref_mut()
+                .map(|generator| generator.next_key(key, &values, time, diff));
+
+            // formatting is deterministic, so only the errors of the sink are retried
+            execute_with_circuit_breaker(
+                circuit_breaker,
                 || {
-                    let formatted = data_formatter
+                    let mut formatted = data_formatter
                         .format(&key, &values, time, diff)
                         .map_err(DynError::from)?;
+                    formatted.idempotency_key = idempotency_key;
                     data_sink.write(formatted).map_err(DynError::from)
                 },
-                RetryConfig::default(),
+                |e: &DynError| {
+                    e.downcast_ref::<WriteError>()
+                        .is_some_and(WriteError::is_retryable)
+                },
+                retry_policy.retry_config().with_full_jitter(),
                 retries,
             )?;
         }
-        stats.on_batch_entries_written(batch_size);
+        stats.on_batch_entries_written(batch_size - entries_filtered_out);
         stats.on_batch_finished();
 
         // This line can be removed. In this case, flush will happen on the next time advancement.
@@ -4212,20 +4430,23 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
         t: Option<Timestamp>,
         sink_id: Option<usize>,
         worker_persistent_storage: Option<&SharedWorkerPersistentStorage>,
+        idempotency_keys: Option<&mut IdempotencyKeyGenerator>,
     ) -> Result<()> {
         if let Some(worker_persistent_storage) = worker_persistent_storage {
-            worker_persistent_storage
-                .lock()
-                .unwrap()
-                .update_sink_finalized_time(
-                    sink_id.expect("undefined sink_id while using persistent storage"),
-                    t,
-                )?;
+            let mut worker_persistent_storage = worker_persistent_storage.lock().unwrap();
+            worker_persistent_storage.update_sink_finalized_time(
+                sink_id.expect("undefined sink_id while using persistent storage"),
+                t,
+            )?;
+            if let Some(idempotency_keys) = idempotency_keys {
+                idempotency_keys.on_commit(worker_persistent_storage.last_finalized_timestamp());
+            }
         }
         stats.on_time_committed(t.map(|t| t.0));
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn output_table(
         &mut self,
         mut data_sink: Box<dyn Writer>,
@@ -4234,6 +4455,7 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
         column_paths: Vec<ColumnPath>,
         unique_name: Option<UniqueName>,
         sort_by_indices: Option<Vec<usize>>,
+        retry_policy: RetryPolicy,
     ) -> Result<()> {
         let worker_index = self.scope.index();
         let error_logger = self.create_error_logger()?;
@@ -4251,6 +4473,11 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
             .get_worker_persistent_storage()
             .map(|storage| storage.lock().unwrap().register_sink());
 
+        let stats_name = unique_name.unwrap_or(data_sink.name());
+        let output_connector_probe = ProbeHandle::new();
+        self.output_connector_probes
+            .push((stats_name.clone(), output_connector_probe.clone()));
+
         let sender = {
             let (sender, receiver) = mpsc::channel();
 
@@ -4265,24 +4492,52 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
                 .get_worker_persistent_storage()
                 .cloned();
 
-            let stats_name = unique_name.unwrap_or(data_sink.name());
+            let mut idempotency_keys = data_sink.uses_idempotency_keys().then(|| {
+                worker_persistent_storage.as_ref().map_or_else(
+                    IdempotencyKeyGenerator::without_persistence,
+                    |storage| {
+                        let storage = storage.lock().unwrap();
+                        IdempotencyKeyGenerator::new(
+                            storage.last_finalized_timestamp(),
+                            storage.snapshot_interval(),
+                        )
+                    },
+                )
+            });
+
+            let connector_name = stats_name.clone();
+            // shared by the workers, so that they stop writing together if the sink is down
+            let circuit_breaker = shared_circuit_breaker(&stats_name);
+            let pause_switch = shared_pause_switch(&stats_name, ConnectorKind::Sink);
             let mut stats = OutputConnectorStats::new(stats_name);
+            let mut at_commit_boundary = true;
             let output_joiner_handle = Builder::new()
                 .name(thread_name)
                 .spawn_with_reporter(
                     self.error_reporter.clone().with_extra(receiver),
                     move |error_reporter_with_receiver| loop {
+                        // A paused sink only stops between the times, so that the batches
+                        // of a time are written together
+                        if at_commit_boundary {
+                            pause_switch.wait_while_paused(shutdown::is_shutdown_requested);
+                        }
                         let receiver = error_reporter_with_receiver.get();
-                        match receiver.recv() {
+                        let event = receiver.recv();
+                        at_commit_boundary = matches!(event, Ok(OutputEvent::Commit(_)));
+                        match event {
                             Ok(OutputEvent::Batch(batch)) => {
                                 Self::output_batch(
                                     &mut stats,
                                     batch,
                                     &mut data_sink,
                                     &mut data_formatter,
+                                    &circuit_breaker,
+                                    &retry_policy,
                                     worker_persistent_storage.as_ref(),
                                     sort_by_indices.as_ref(),
-                                )?;
+                                    idempotency_keys.as_mut(),
+                                )
+                                .map_err(|e| Error::connector_failed(&connector_name, e))?;
                             }
                             Ok(OutputEvent::Commit(t)) => {
                                 Self::commit_output_time(
@@ -4290,8 +4545,14 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
                                     t,
                                     sink_id,
                                     worker_persistent_storage.as_ref(),
+                                    idempotency_keys.as_mut(),
                                 )?;
-                                data_sink.flush(t.is_none()).map_err(DynError::from)?;
+                                data_sink
+                                    .on_time_committed(t)
+                                    .map_err(|e| Error::connector_failed(&connector_name, e))?;
+                                data_sink
+                                    .flush(t.is_none())
+                                    .map_err(|e| Error::connector_failed(&connector_name, e))?;
                                 if t.is_none() {
                                     break Ok(());
                                 }
@@ -4324,7 +4585,8 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
                     }
                 }
             })
-            .probe_with(&self.output_probe);
+            .probe_with(&self.output_probe)
+            .probe_with(&output_connector_probe);
 
         Ok(())
     }
@@ -4371,8 +4633,11 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
         } = callbacks;
         let wrapper_2 = wrapper.clone();
 
-        let output_connector_id = self.connector_threads.len() - self.connector_monitors.len();
+        let output_connector_id = self.connector_threads.len();
         let stats_name = unique_name.unwrap_or(format!("subscribe-{output_connector_id}"));
+        let output_connector_probe = ProbeHandle::new();
+        self.output_connector_probes
+            .push((stats_name.clone(), output_connector_probe.clone()));
         let mut stats = OutputConnectorStats::new(stats_name);
 
         let output_columns = self
@@ -4460,7 +4725,8 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
                     }
                 }
             })
-            .probe_with(&self.output_probe);
+            .probe_with(&self.output_probe)
+            .probe_with(&output_connector_probe);
 
         Ok(())
     }
@@ -5298,6 +5564,35 @@ impl<S: MaybeTotalScope> Graph for InnerDataflowGraph<S> {
             .remove_retractions_from_table(table_handle, table_properties)
     }
 
+    fn sample_table(
+        &self,
+        table_handle: TableHandle,
+        fraction: f64,
+        seed: u64,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.0
+            .borrow_mut()
+            .sample_table(table_handle, fraction, seed, table_properties)
+    }
+
+    fn reservoir_sample_table(
+        &self,
+        table_handle: TableHandle,
+        instance_column_path: ColumnPath,
+        size: usize,
+        seed: u64,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.0.borrow_mut().reservoir_sample_table(
+            table_handle,
+            instance_column_path,
+            size,
+            seed,
+            table_properties,
+        )
+    }
+
     fn forget(
         &self,
         _table_handle: TableHandle,
@@ -5515,6 +5810,27 @@ impl<S: MaybeTotalScope> Graph for InnerDataflowGraph<S> {
         )
     }
 
+    fn keyed_mat_mul(
+        &self,
+        table_handle: TableHandle,
+        group_path: ColumnPath,
+        operand_path: ColumnPath,
+        matrix_table_handle: TableHandle,
+        matrix_group_path: ColumnPath,
+        matrix_path: ColumnPath,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.0.borrow_mut().keyed_mat_mul(
+            table_handle,
+            group_path,
+            operand_path,
+            matrix_table_handle,
+            matrix_group_path,
+            matrix_path,
+            table_properties,
+        )
+    }
+
     fn use_external_index_as_of_now(
         &self,
         index_stream: ExternalIndexData,
@@ -5604,6 +5920,7 @@ impl<S: MaybeTotalScope> Graph for InnerDataflowGraph<S> {
         Err(Error::IoNotPossible)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn output_table(
         &self,
         mut _data_sink: Box<dyn Writer>,
@@ -5612,6 +5929,7 @@ impl<S: MaybeTotalScope> Graph for InnerDataflowGraph<S> {
         _column_paths: Vec<ColumnPath>,
         _unique_name: Option<UniqueName>,
         _sort_by_indices: Option<Vec<usize>>,
+        _retry_policy: RetryPolicy,
     ) -> Result<()> {
         Err(Error::IoNotPossible)
     }
@@ -5941,6 +6259,35 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
             .remove_retractions_from_table(table_handle, table_properties)
     }
 
+    fn sample_table(
+        &self,
+        table_handle: TableHandle,
+        fraction: f64,
+        seed: u64,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.0
+            .borrow_mut()
+            .sample_table(table_handle, fraction, seed, table_properties)
+    }
+
+    fn reservoir_sample_table(
+        &self,
+        table_handle: TableHandle,
+        instance_column_path: ColumnPath,
+        size: usize,
+        seed: u64,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.0.borrow_mut().reservoir_sample_table(
+            table_handle,
+            instance_column_path,
+            size,
+            seed,
+            table_properties,
+        )
+    }
+
     fn forget(
         &self,
         table_handle: TableHandle,
@@ -6193,6 +6540,27 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         )
     }
 
+    fn keyed_mat_mul(
+        &self,
+        table_handle: TableHandle,
+        group_path: ColumnPath,
+        operand_path: ColumnPath,
+        matrix_table_handle: TableHandle,
+        matrix_group_path: ColumnPath,
+        matrix_path: ColumnPath,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.0.borrow_mut().keyed_mat_mul(
+            table_handle,
+            group_path,
+            operand_path,
+            matrix_table_handle,
+            matrix_group_path,
+            matrix_path,
+            table_properties,
+        )
+    }
+
     fn use_external_index_as_of_now(
         &self,
         index_stream: ExternalIndexData,
@@ -6293,6 +6661,7 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn output_table(
         &self,
         data_sink: Box<dyn Writer>,
@@ -6301,6 +6670,7 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         column_paths: Vec<ColumnPath>,
         unique_name: Option<UniqueName>,
         sort_by_indices: Option<Vec<usize>>,
+        retry_policy: RetryPolicy,
     ) -> Result<()> {
         self.0.borrow_mut().output_table(
             data_sink,
@@ -6309,6 +6679,7 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
             column_paths,
             unique_name,
             sort_by_indices,
+            retry_policy,
         )
     }
 
@@ -6459,6 +6830,7 @@ pub fn run_with_new_dataflow_graph<R, R2>(
     telemetry_config: TelemetryConfig,
     terminate_on_error: bool,
     max_expression_batch_size: usize,
+    cancellation_token: Option<CancellationToken>,
 ) -> Result<Vec<R2>>
 where
     R: 'static,
@@ -6483,9 +6855,34 @@ where
     let connector_synchronizer =
         Arc::new(Mutex::new(ConnectorSynchronizer::new(is_multiprocessed)));
     let stats_monitor = Arc::new(Mutex::new(stats_monitor));
+    shutdown::reset_shutdown(cancellation_token);
+    health::reset_health();
+    spans::configure(&telemetry_config, config.min_traced_span());
+    reload::apply_from_env().map_err(|e| Error::Other(e.into()))?;
+    let _sighup_listener = reload::maybe_start_sighup_listener();
+
+    // The handoff has to happen before the workers start, so that the state persisted by the
+    // previous process is complete once it is restored
+    let handoff_coordinator = match &persistence_config {
+        Some(persistence_config) => persistence_config
+            .create_handoff_coordinator(config.process_id())?
+            .map(Arc::new),
+        None => None,
+    };
+    if let Some(handoff_coordinator) = &handoff_coordinator {
+        handoff_coordinator.take_over()?;
+    }
+    let lease_guard = handoff_coordinator
+        .as_ref()
+        .map(HandoffCoordinator::keep_lease);
 
-    let guards = execute(config.to_timely_config(), move |worker| {
+    let tls = config.cluster_tls().cloned();
+    let guards = cluster_tls::execute(config.to_timely_config(), tls.as_ref(), move |worker| {
         catch_unwind(AssertUnwindSafe(|| {
+            log_context::set_worker_id(worker.index());
+            spans::register_operator_step_logger(&mut worker.log_register());
+            let mut memory_accountant =
+                MemoryAccountant::new(worker.index(), config.arrangement_memory_soft_limit());
             if let Ok(addr) = env::var("DIFFERENTIAL_LOG_ADDR") {
                 if let Ok(stream) = std::net::TcpStream::connect(&addr) {
                     differential_dataflow::logging::enable(worker, stream);
@@ -6500,13 +6897,16 @@ where
                 mut flushers,
                 mut pollers,
                 connector_threads,
+                reader_threads,
                 connector_monitors,
                 input_probe,
                 output_probe,
+                output_connector_probes,
                 intermediate_probes,
                 mut probers,
                 progress_reporter_runner,
                 http_server_runner,
+                health_server_runner,
                 telemetry_runner,
             ) = worker.dataflow::<Timestamp, _, _>(|scope| {
                 let graph = OuterDataflowGraph::new(
@@ -6532,34 +6932,46 @@ where
                     maybe_run_reporter(&monitoring_level, &graph, stats_monitor_local);
                 let http_server_runner =
                     maybe_run_http_server_thread(with_http_server, &graph, config.process_id());
+                let health_server_runner = maybe_run_health_server(
+                    config.health_port(),
+                    config.readiness_max_stall(),
+                    &graph,
+                );
                 let graph = graph.0.into_inner();
                 (
                     res,
                     graph.flushers,
                     graph.pollers,
                     graph.connector_threads,
+                    graph.reader_threads,
                     graph.connector_monitors,
                     graph.input_probe,
                     graph.output_probe,
+                    graph.output_connector_probes,
                     graph.probes,
                     graph.probers,
                     progress_reporter_runner,
                     http_server_runner,
+                    health_server_runner,
                     telemetry_runner,
                 )
             });
 
+            shutdown::register_worker();
             loop {
                 if failed.load(Ordering::SeqCst) {
                     resume_unwind(Box::new("other worker panicked"));
                 }
 
+                memory_accountant.maybe_measure();
+
                 for prober in &mut probers {
                     prober.update(
                         &input_probe,
                         &output_probe,
                         &intermediate_probes,
                         &connector_monitors,
+                        &output_connector_probes,
                     );
                 }
 
@@ -6604,6 +7016,16 @@ where
                 }
             }
 
+            for reader_thread in reader_threads {
+                // after a shutdown request, a reader blocked on reading is left behind, it
+                // stops as soon as it tries to send the next entry
+                if shutdown::is_shutdown_requested() && !reader_thread.is_finished() {
+                    continue;
+                }
+                reader_thread
+                    .join()
+                    .expect("connector thread should not panic");
+            }
             for connector_thread in connector_threads {
                 connector_thread
                     .join()
@@ -6616,12 +7038,15 @@ where
                     &output_probe,
                     &intermediate_probes,
                     &connector_monitors,
+                    &output_connector_probes,
                 );
             }
 
             drop(http_server_runner);
+            drop(health_server_runner);
             drop(progress_reporter_runner);
             drop(telemetry_runner);
+            drop(memory_accountant);
 
             finish(res)
         }))
@@ -6670,5 +7095,9 @@ where
         .into_iter()
         .map(|res| res.map_err(Error::WorkerPanic))
         .collect::<Result<Vec<_>>>()?;
+    drop(lease_guard);
+    if let Some(handoff_coordinator) = handoff_coordinator {
+        handoff_coordinator.release()?;
+    }
     Ok(res)
 }
@@ -150,7 +150,8 @@ where
         parser.column_count(),
         graph.terminate_on_error,
         graph.create_error_logger()?.into(),
-    );
+    )
+    .ignore_shutdown_requests();
     let state = connector.run(
         reader,
         parser,
@@ -0,0 +1,286 @@
+// Copyright © 2024 Pathway
+
+//! TLS on the channels between the processes of a cluster.
+//!
+//! Timely splits each connection into a reader and a writer owned by separate threads, which a
+//! TLS session can't be split into. So the connections are made by timely as usual, and then
+//! each of them is wrapped in a TLS session run by a small runtime, which relays the traffic
+//! between the session and a loopback connection whose other end is given to timely.
+//!
+//! Both sides of a session present the certificate of their process and check the one of the
+//! peer against the cluster CA, so only the processes having a certificate issued by it can join
+//! the cluster. The certificates have to be valid for the hosts of the cluster addresses, or for
+//! a single name shared by all the processes if it is set.
+
+use std::error::Error as StdError;
+use std::io;
+use std::mem::take;
+use std::net::{Ipv4Addr, TcpListener, TcpStream as StdTcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use log::error;
+use timely::communication::allocator::zero_copy::initialize::{
+    initialize_networking_from_sockets, CommsGuard,
+};
+use timely::communication::allocator::GenericBuilder;
+use timely::communication::networking::create_sockets;
+use timely::communication::{Allocator, WorkerGuards};
+use timely::execute::execute_from;
+use timely::worker::Worker;
+use timely::{CommunicationConfig, Config as TimelyConfig};
+use tokio::io::{copy_bidirectional, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls::crypto::ring::default_provider;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+use tokio_rustls::{TlsAcceptor, TlsConnector, TlsStream};
+
+const HANDSHAKE_CONFIRMATION: u8 = 1;
+
+#[derive(Clone, Debug)]
+pub struct ClusterTlsConfig {
+    /// The PEM certificate chain of this process.
+    pub certificate_path: PathBuf,
+    /// The PEM private key of the certificate.
+    pub key_path: PathBuf,
+    /// The PEM certificates of the cluster CA, used to verify the peers.
+    pub ca_path: PathBuf,
+    /// The name the certificates of the peers are checked against. If not set, the host of the
+    /// address of the peer is used.
+    pub server_name: Option<String>,
+}
+
+fn invalid_data(error: impl Into<Box<dyn StdError + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn load_certificates(path: &PathBuf) -> io::Result<Vec<CertificateDer<'static>>> {
+    CertificateDer::pem_file_iter(path)
+        .map_err(invalid_data)?
+        .collect::<Result<_, _>>()
+        .map_err(invalid_data)
+}
+
+/// The host part of a `host:port` address, without the brackets of an IPv6 address.
+fn address_host(address: &str) -> &str {
+    let host = address
+        .rsplit_once(':')
+        .map_or(address, |(host, _port)| host);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+struct TlsContext {
+    acceptor: TlsAcceptor,
+    connector: TlsConnector,
+}
+
+impl ClusterTlsConfig {
+    fn context(&self) -> io::Result<TlsContext> {
+        let provider = Arc::new(default_provider());
+        let certificates = load_certificates(&self.certificate_path)?;
+        let key = PrivateKeyDer::from_pem_file(&self.key_path).map_err(invalid_data)?;
+        let mut roots = RootCertStore::empty();
+        for certificate in load_certificates(&self.ca_path)? {
+            roots.add(certificate).map_err(invalid_data)?;
+        }
+        let roots = Arc::new(roots);
+
+        let client_verifier =
+            WebPkiClientVerifier::builder_with_provider(roots.clone(), provider.clone())
+                .build()
+                .map_err(invalid_data)?;
+        let server_config = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(invalid_data)?
+            .with_client_cert_verifier(client_verifier)
+            .with_single_cert(certificates.clone(), key.clone_key())
+            .map_err(invalid_data)?;
+        let client_config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(invalid_data)?
+            .with_root_certificates(roots)
+            .with_client_auth_cert(certificates, key)
+            .map_err(invalid_data)?;
+
+        Ok(TlsContext {
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+            connector: TlsConnector::from(Arc::new(client_config)),
+        })
+    }
+
+    fn server_name(&self, address: &str) -> io::Result<ServerName<'static>> {
+        let name = self
+            .server_name
+            .clone()
+            .unwrap_or_else(|| address_host(address).to_string());
+        ServerName::try_from(name).map_err(invalid_data)
+    }
+}
+
+/// Makes a connected pair of loopback sockets. The connection accepted by the listener is
+/// checked to come from the other socket, as any local process could connect to it.
+fn loopback_pair() -> io::Result<(StdTcpStream, StdTcpStream)> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let connected = StdTcpStream::connect(listener.local_addr()?)?;
+    loop {
+        let (accepted, peer_address) = listener.accept()?;
+        if peer_address == connected.local_addr()? {
+            connected.set_nodelay(true)?;
+            accepted.set_nodelay(true)?;
+            return Ok((connected, accepted));
+        }
+    }
+}
+
+async fn relay(mut session: TlsStream<TcpStream>, mut local: TcpStream, peer: usize) {
+    if let Err(e) = copy_bidirectional(&mut session, &mut local).await {
+        error!("TLS channel to process {peer} failed: {e}");
+    }
+}
+
+/// Joins the send and receive threads of timely first, as they need the relays to deliver
+/// their last messages, and then waits for the relays to finish.
+struct TlsCommsGuard {
+    comms: Option<CommsGuard>,
+    relays: Vec<JoinHandle<()>>,
+    runtime: Runtime,
+}
+
+impl Drop for TlsCommsGuard {
+    fn drop(&mut self) {
+        drop(self.comms.take());
+        let relays = take(&mut self.relays);
+        self.runtime.block_on(async {
+            for relay in relays {
+                relay.await.ok();
+            }
+        });
+    }
+}
+
+fn initialize_tls_networking(
+    tls: &ClusterTlsConfig,
+    addresses: Vec<String>,
+    my_index: usize,
+    threads: usize,
+) -> io::Result<(Vec<GenericBuilder>, TlsCommsGuard)> {
+    let context = tls.context()?;
+    let server_names = addresses
+        .iter()
+        .map(|address| tls.server_name(address))
+        .collect::<io::Result<Vec<_>>>()?;
+    let sockets = create_sockets(addresses, my_index, false)?;
+    let runtime = RuntimeBuilder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("pathway:cluster_tls")
+        .enable_io()
+        .build()?;
+
+    // the processes with lower indices are connected to by this process, so it is the client
+    let handshakes: Vec<_> = sockets
+        .into_iter()
+        .enumerate()
+        .map(|(index, socket)| {
+            let socket = socket?;
+            let acceptor = context.acceptor.clone();
+            let connector = context.connector.clone();
+            let server_name = server_names[index].clone();
+            Some(runtime.spawn(async move {
+                socket.set_nonblocking(true)?;
+                let socket = TcpStream::from_std(socket)?;
+                let mut session: TlsStream<TcpStream> = if index < my_index {
+                    connector.connect(server_name, socket).await?.into()
+                } else {
+                    acceptor.accept(socket).await?.into()
+                };
+                // in TLS 1.3 the client finishes the handshake before its certificate is
+                // checked, so both sides confirm that they have accepted the peer
+                session.write_all(&[HANDSHAKE_CONFIRMATION]).await?;
+                session.flush().await?;
+                let mut confirmation = [0];
+                session.read_exact(&mut confirmation).await?;
+                if confirmation != [HANDSHAKE_CONFIRMATION] {
+                    return Err(invalid_data("invalid TLS handshake confirmation"));
+                }
+                Ok(session)
+            }))
+        })
+        .collect();
+
+    let mut local_sockets = Vec::with_capacity(handshakes.len());
+    let mut relays = Vec::new();
+    for (index, handshake) in handshakes.into_iter().enumerate() {
+        let Some(handshake) = handshake else {
+            local_sockets.push(None);
+            continue;
+        };
+        let session = runtime
+            .block_on(handshake)
+            .map_err(io::Error::other)?
+            .map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("TLS handshake with process {index} failed: {e}"),
+                )
+            })?;
+        let (local, relayed) = loopback_pair()?;
+        relayed.set_nonblocking(true)?;
+        let relayed = {
+            let _guard = runtime.enter();
+            TcpStream::from_std(relayed)?
+        };
+        relays.push(runtime.spawn(relay(session, relayed, index)));
+        local_sockets.push(Some(local));
+    }
+
+    let (builders, comms) =
+        initialize_networking_from_sockets(local_sockets, my_index, threads, Box::new(|_| None))?;
+    Ok((
+        builders.into_iter().map(GenericBuilder::ZeroCopy).collect(),
+        TlsCommsGuard {
+            comms: Some(comms),
+            relays,
+            runtime,
+        },
+    ))
+}
+
+/// Executes the computation like [`timely::execute`], but with the channels between the
+/// processes of the cluster wrapped in TLS if `tls` is given.
+pub fn execute<T, F>(
+    config: TimelyConfig,
+    tls: Option<&ClusterTlsConfig>,
+    func: F,
+) -> Result<WorkerGuards<T>, String>
+where
+    T: Send + 'static,
+    F: Fn(&mut Worker<Allocator>) -> T + Send + Sync + 'static,
+{
+    match (tls, config.communication) {
+        (
+            Some(tls),
+            CommunicationConfig::Cluster {
+                threads,
+                process,
+                addresses,
+                ..
+            },
+        ) => {
+            let (builders, guard) = initialize_tls_networking(tls, addresses, process, threads)
+                .map_err(|e| format!("failed to initialize networking: {e}"))?;
+            execute_from(builders, Box::new(guard), config.worker, func)
+        }
+        (_, communication) => timely::execute(
+            TimelyConfig {
+                communication,
+                worker: config.worker,
+            },
+            func,
+        ),
+    }
+}
@@ -1,9 +1,17 @@
 // Copyright © 2024 Pathway
 
+use std::time::Duration;
+
+use super::cluster_tls::ClusterTlsConfig;
+use super::discovery::{local_process_id, Discovery, DiscoveryError};
 use crate::env::{parse_env_var, parse_env_var_required, Error as EnvError};
 use log::warn;
 use timely::{CommunicationConfig, Config as TimelyConfig, WorkerConfig};
 
+const DEFAULT_READINESS_MAX_STALL: Duration = Duration::from_secs(60);
+const DEFAULT_MIN_TRACED_SPAN: Duration = Duration::from_millis(50);
+const DEFAULT_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(300);
+
 const MAX_WORKERS: usize = if cfg!(feature = "unlimited-workers") {
     usize::MAX
 } else {
@@ -22,6 +30,21 @@ pub enum Error {
     #[error("invalid process ID {0}")]
     InvalidId(usize),
 
+    #[error("only one of PATHWAY_CLUSTER_ADDRESSES and PATHWAY_CLUSTER_DNS can be set")]
+    ConflictingDiscovery,
+
+    #[error("{0} processes were requested, but {1} cluster addresses are given")]
+    ProcessesMismatch(usize, usize),
+
+    #[error("PATHWAY_CLUSTER_TLS_CERT, _KEY and _CA have to be set together")]
+    IncompleteTls,
+
+    #[error("{0} processes is more than the maximum allowed number of workers ({MAX_WORKERS})")]
+    TooManyProcesses(usize),
+
+    #[error(transparent)]
+    Discovery(#[from] DiscoveryError),
+
     #[error(transparent)]
     EnvError(#[from] EnvError),
 }
@@ -38,6 +61,11 @@ pub struct Config {
     threads: usize,
     processes: Processes,
     process_id: usize,
+    health_port: Option<u16>,
+    readiness_max_stall: Duration,
+    min_traced_span: Duration,
+    arrangement_memory_soft_limit: Option<u64>,
+    cluster_tls: Option<ClusterTlsConfig>,
 }
 
 impl Config {
@@ -60,6 +88,34 @@ impl Config {
         self.process_id
     }
 
+    /// The port of the health probes server of the first process. Each process serves the
+    /// probes on the port shifted by its ID.
+    pub fn health_port(&self) -> Option<u16> {
+        self.health_port
+    }
+
+    /// How long the input frontier may stay in place before the process is reported as not
+    /// ready.
+    pub fn readiness_max_stall(&self) -> Duration {
+        self.readiness_max_stall
+    }
+
+    /// The minimum duration of the connector polls, parser batches and operator steps for which
+    /// tracing spans are exported.
+    pub fn min_traced_span(&self) -> Duration {
+        self.min_traced_span
+    }
+
+    /// The size in bytes above which an arrangement is reported in the logs.
+    pub fn arrangement_memory_soft_limit(&self) -> Option<u64> {
+        self.arrangement_memory_soft_limit
+    }
+
+    /// The TLS settings of the channels between the processes, if they are encrypted.
+    pub fn cluster_tls(&self) -> Option<&ClusterTlsConfig> {
+        self.cluster_tls.as_ref()
+    }
+
     pub fn to_timely_config(&self) -> TimelyConfig {
         match &self.processes {
             Processes::Single => {
@@ -90,12 +146,20 @@ impl Config {
         if threads == 0 {
             return Err(Error::NeedsThreads);
         }
-        let mut processes: usize = parse_env_var("PATHWAY_PROCESSES")?.unwrap_or(1);
+        let discovery = discovery_from_env()?;
+        let mut processes: usize = match &discovery {
+            Some(discovery) => discovery.processes(),
+            None => parse_env_var("PATHWAY_PROCESSES")?.unwrap_or(1),
+        };
         if processes == 0 {
             return Err(Error::NeedsProcesses);
         }
         let workers = threads * processes;
         if workers > MAX_WORKERS {
+            if discovery.is_some() && processes > MAX_WORKERS {
+                // the cluster addresses are given, so the processes can't be dropped
+                return Err(Error::TooManyProcesses(processes));
+            }
             warn!("{workers} is greater than the the maximum allowed number of workers ({MAX_WORKERS}), reducing");
             threads = MAX_WORKERS / processes;
             if threads == 0 {
@@ -105,7 +169,20 @@ impl Config {
         }
         let workers = threads * processes;
         assert!(workers <= MAX_WORKERS);
-        let (process_id, processes) = if processes > 1 {
+        let (process_id, processes) = if processes == 1 {
+            (0, Processes::Single)
+        } else if let Some(discovery) = discovery {
+            let addresses = discovery.addresses()?;
+            let process_id = match parse_env_var("PATHWAY_PROCESS_ID")? {
+                Some(process_id) => process_id,
+                None => local_process_id(&addresses)?,
+            };
+            if process_id >= processes {
+                return Err(Error::InvalidId(process_id));
+            }
+            let addresses = addresses.iter().map(ToString::to_string).collect();
+            (process_id, Processes::Multi(addresses))
+        } else {
             let process_id: usize = parse_env_var_required("PATHWAY_PROCESS_ID")?;
             if process_id >= processes {
                 return Err(Error::InvalidId(process_id));
@@ -115,14 +192,76 @@ impl Config {
                 .map(|id| format!("127.0.0.1:{}", first_port + id))
                 .collect();
             (process_id, Processes::Multi(addresses))
-        } else {
-            (0, Processes::Single)
         };
+        let health_port = parse_env_var("PATHWAY_HEALTH_PORT")?;
+        let readiness_max_stall = parse_env_var("PATHWAY_READINESS_MAX_STALL_SECONDS")?
+            .map_or(DEFAULT_READINESS_MAX_STALL, Duration::from_secs);
+        let min_traced_span = parse_env_var("PATHWAY_TRACING_MIN_SPAN_MS")?
+            .map_or(DEFAULT_MIN_TRACED_SPAN, Duration::from_millis);
+        let arrangement_memory_soft_limit =
+            parse_env_var::<u64>("PATHWAY_ARRANGEMENT_MEMORY_SOFT_LIMIT_MB")?
+                .map(|limit_mb| limit_mb * 1024 * 1024);
+        let cluster_tls = cluster_tls_from_env()?;
         Ok(Self {
             workers,
             threads,
             processes,
             process_id,
+            health_port,
+            readiness_max_stall,
+            min_traced_span,
+            arrangement_memory_soft_limit,
+            cluster_tls,
         })
     }
 }
+
+/// The TLS settings of the cluster channels, used if the certificate, its key and the CA are
+/// all given.
+fn cluster_tls_from_env() -> Result<Option<ClusterTlsConfig>, Error> {
+    let certificate_path = parse_env_var("PATHWAY_CLUSTER_TLS_CERT")?;
+    let key_path = parse_env_var("PATHWAY_CLUSTER_TLS_KEY")?;
+    let ca_path = parse_env_var("PATHWAY_CLUSTER_TLS_CA")?;
+    match (certificate_path, key_path, ca_path) {
+        (None, None, None) => Ok(None),
+        (Some(certificate_path), Some(key_path), Some(ca_path)) => Ok(Some(ClusterTlsConfig {
+            certificate_path,
+            key_path,
+            ca_path,
+            server_name: parse_env_var("PATHWAY_CLUSTER_TLS_SERVER_NAME")?,
+        })),
+        _ => Err(Error::IncompleteTls),
+    }
+}
+
+/// The discovery of the cluster processes, if their addresses are not derived from
+/// `PATHWAY_FIRST_PORT`.
+fn discovery_from_env() -> Result<Option<Discovery>, Error> {
+    let addresses: Option<String> = parse_env_var("PATHWAY_CLUSTER_ADDRESSES")?;
+    let dns_name: Option<String> = parse_env_var("PATHWAY_CLUSTER_DNS")?;
+    let timeout = parse_env_var("PATHWAY_CLUSTER_DISCOVERY_TIMEOUT_SECONDS")?
+        .map_or(DEFAULT_DISCOVERY_TIMEOUT, Duration::from_secs);
+    match (addresses, dns_name) {
+        (Some(_), Some(_)) => Err(Error::ConflictingDiscovery),
+        (Some(addresses), None) => {
+            let addresses: Vec<String> = addresses
+                .split(',')
+                .map(str::trim)
+                .filter(|address| !address.is_empty())
+                .map(ToOwned::to_owned)
+                .collect();
+            if let Some(processes) = parse_env_var::<usize>("PATHWAY_PROCESSES")? {
+                if processes != addresses.len() {
+                    return Err(Error::ProcessesMismatch(processes, addresses.len()));
+                }
+            }
+            Ok(Some(Discovery::Static { addresses, timeout }))
+        }
+        (None, Some(name)) => Ok(Some(Discovery::Dns {
+            name,
+            processes: parse_env_var_required("PATHWAY_PROCESSES")?,
+            timeout,
+        })),
+        (None, None) => Ok(None),
+    }
+}
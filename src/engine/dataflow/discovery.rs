@@ -0,0 +1,166 @@
+// Copyright © 2024 Pathway
+
+//! Finding the addresses of the processes of a cluster, so that processes running on different
+//! machines can form a cluster without configuring each of them separately.
+//!
+//! The addresses are either listed explicitly or resolved from a DNS name that has one record
+//! per process, like a headless service. All processes order the addresses the same way, and a
+//! process whose ID is not set explicitly takes the ID of the only address that belongs to the
+//! machine it runs on.
+
+use std::io;
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use log::info;
+
+const RESOLVE_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum DiscoveryError {
+    #[error("failed to resolve cluster address {0:?}: {1}")]
+    ResolutionFailed(String, #[source] io::Error),
+
+    #[error("cluster address {0:?} doesn't resolve to any address")]
+    NoAddress(String),
+
+    #[error("cluster address {0} is listed more than once")]
+    DuplicateAddress(SocketAddr),
+
+    #[error("{name:?} resolved to {found} addresses instead of {expected} within {timeout:?}")]
+    Timeout {
+        name: String,
+        expected: usize,
+        found: usize,
+        timeout: Duration,
+    },
+
+    #[error("none of the cluster addresses belongs to this machine, set PATHWAY_PROCESS_ID")]
+    NoLocalAddress,
+
+    #[error(
+        "more than one of the cluster addresses belongs to this machine, set PATHWAY_PROCESS_ID"
+    )]
+    AmbiguousLocalAddress,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Discovery {
+    /// The `host:port` addresses of the processes, in the order of their IDs.
+    Static {
+        addresses: Vec<String>,
+        timeout: Duration,
+    },
+    /// A `name:port` that resolves to one address per process. The IDs of the processes follow
+    /// the sorted addresses.
+    Dns {
+        name: String,
+        processes: usize,
+        timeout: Duration,
+    },
+}
+
+impl Discovery {
+    pub fn processes(&self) -> usize {
+        match self {
+            Self::Static { addresses, .. } => addresses.len(),
+            Self::Dns { processes, .. } => *processes,
+        }
+    }
+
+    /// Resolves the addresses of all processes. The names that don't resolve yet, e.g. because
+    /// the other processes are still starting, are retried until the timeout.
+    pub fn addresses(&self) -> Result<Vec<SocketAddr>, DiscoveryError> {
+        let addresses = match self {
+            Self::Static { addresses, timeout } => {
+                let deadline = Instant::now() + *timeout;
+                addresses
+                    .iter()
+                    .map(|address| resolve_first(address, deadline))
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            Self::Dns {
+                name,
+                processes,
+                timeout,
+            } => resolve_all(name, *processes, *timeout)?,
+        };
+        for (index, address) in addresses.iter().enumerate() {
+            if addresses[..index].contains(address) {
+                return Err(DiscoveryError::DuplicateAddress(*address));
+            }
+        }
+        Ok(addresses)
+    }
+}
+
+fn resolve(name: &str) -> io::Result<Vec<SocketAddr>> {
+    Ok(name.to_socket_addrs()?.collect())
+}
+
+fn resolve_first(name: &str, deadline: Instant) -> Result<SocketAddr, DiscoveryError> {
+    loop {
+        match resolve(name) {
+            Ok(addresses) if !addresses.is_empty() => return Ok(addresses[0]),
+            _ if Instant::now() < deadline => sleep(RESOLVE_RETRY_DELAY),
+            Ok(_) => return Err(DiscoveryError::NoAddress(name.to_string())),
+            Err(e) => return Err(DiscoveryError::ResolutionFailed(name.to_string(), e)),
+        }
+    }
+}
+
+fn resolve_all(
+    name: &str,
+    expected: usize,
+    timeout: Duration,
+) -> Result<Vec<SocketAddr>, DiscoveryError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let found = match resolve(name) {
+            Ok(mut addresses) => {
+                addresses.sort_unstable();
+                addresses.dedup();
+                if addresses.len() == expected {
+                    return Ok(addresses);
+                }
+                addresses.len()
+            }
+            Err(e) if Instant::now() >= deadline => {
+                return Err(DiscoveryError::ResolutionFailed(name.to_string(), e));
+            }
+            Err(_) => 0,
+        };
+        if Instant::now() >= deadline {
+            return Err(DiscoveryError::Timeout {
+                name: name.to_string(),
+                expected,
+                found,
+                timeout,
+            });
+        }
+        info!("{name:?} resolved to {found} of {expected} cluster addresses, waiting");
+        sleep(RESOLVE_RETRY_DELAY);
+    }
+}
+
+fn is_local(address: &SocketAddr) -> bool {
+    // Binding succeeds only for the addresses assigned to the interfaces of this machine
+    !address.ip().is_unspecified() && TcpListener::bind((address.ip(), 0)).is_ok()
+}
+
+/// The ID of the current process, that is the position of the only address among `addresses`
+/// that belongs to this machine.
+pub fn local_process_id(addresses: &[SocketAddr]) -> Result<usize, DiscoveryError> {
+    let mut local_ids = addresses
+        .iter()
+        .enumerate()
+        .filter(|(_, address)| is_local(address))
+        .map(|(id, _)| id);
+    match (local_ids.next(), local_ids.next()) {
+        (Some(id), None) => Ok(id),
+        (None, _) => Err(DiscoveryError::NoLocalAddress),
+        (Some(_), Some(_)) => Err(DiscoveryError::AmbiguousLocalAddress),
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright © 2024 Pathway
+
+use std::sync::Arc;
+
+use ndarray::{ArrayD, CowArray, IxDyn};
+
+use super::operators::group_operation::GroupOperation;
+use crate::engine::error::{DataError, DynError, Trace};
+use crate::engine::report_error::LogError;
+use crate::engine::Value;
+use crate::mat_mul::{MatMulScalar, PackedMatrix};
+
+pub(super) enum PreparedMatrix {
+    Int(PackedMatrix<i64>),
+    Float(PackedMatrix<f64>),
+    Float32(PackedMatrix<f32>),
+    Invalid,
+}
+
+impl PreparedMatrix {
+    fn kind_name(&self) -> &'static str {
+        match self {
+            Self::Int(_) => "IntArray",
+            Self::Float(_) => "FloatArray",
+            Self::Float32(_) => "Float32Array",
+            Self::Invalid => "invalid matrix",
+        }
+    }
+}
+
+/// Multiplies the operands of rows by the matrix of their group. Rows are `(values, operand)`
+/// pairs. The matrix is packed once per its update and the operands of all rows computed
+/// together are multiplied as a single product.
+pub(super) struct KeyedMatMul {
+    error_logger: Box<dyn LogError>,
+    trace: Arc<Trace>,
+}
+
+impl KeyedMatMul {
+    pub(super) fn new(error_logger: Box<dyn LogError>, trace: Arc<Trace>) -> Self {
+        Self {
+            error_logger,
+            trace,
+        }
+    }
+
+    fn log_error(&self, message: String) -> Value {
+        self.error_logger
+            .log_error_with_trace(DynError::from(DataError::ValueError(message)), &self.trace);
+        Value::Error
+    }
+
+    fn mul_batch<T>(
+        &self,
+        matrix: &PackedMatrix<T>,
+        operands: &[(usize, CowArray<T, IxDyn>)],
+        results: &mut [Value],
+    ) where
+        T: MatMulScalar,
+        Value: From<ArrayD<T>>,
+    {
+        let views: Vec<_> = operands.iter().map(|(_, operand)| operand.view()).collect();
+        let products = matrix.mul_batch(&views);
+        for ((position, operand), product) in operands.iter().zip(products) {
+            results[*position] = if let Some(product) = product {
+                Value::from(product)
+            } else {
+                self.log_error(format!(
+                    "can't multiply arrays of shapes {:?} and {:?}",
+                    operand.shape(),
+                    matrix.view().shape()
+                ))
+            };
+        }
+    }
+}
+
+impl GroupOperation<Value, (Value, Value), Value> for KeyedMatMul {
+    type Prepared = PreparedMatrix;
+
+    fn prepare(&mut self, matrix: &Value) -> PreparedMatrix {
+        let (prepared, shape) = match matrix {
+            Value::IntArray(array) => (
+                PackedMatrix::new(&array.view()).map(PreparedMatrix::Int),
+                array.shape(),
+            ),
+            Value::FloatArray(array) => (
+                PackedMatrix::new(&array.view()).map(PreparedMatrix::Float),
+                array.shape(),
+            ),
+            Value::Float32Array(array) => (
+                PackedMatrix::new(&array.view()).map(PreparedMatrix::Float32),
+                array.shape(),
+            ),
+            _ => {
+                let kind = matrix.kind();
+                self.log_error(format!("can't perform matrix multiplication by {kind:?}"));
+                return PreparedMatrix::Invalid;
+            }
+        };
+        prepared.unwrap_or_else(|| {
+            self.log_error(format!("can't use array of shape {shape:?} as a matrix"));
+            PreparedMatrix::Invalid
+        })
+    }
+
+    fn compute(&mut self, prepared: &PreparedMatrix, rows: &[&(Value, Value)]) -> Vec<Value> {
+        let mut results = vec![Value::Error; rows.len()];
+        let mut int_operands = Vec::new();
+        let mut float_operands = Vec::new();
+        let mut float32_operands = Vec::new();
+        for (position, (_values, operand)) in rows.iter().enumerate() {
+            // mixed precision is computed in double precision, like in the expression
+            match (prepared, operand) {
+                (PreparedMatrix::Invalid, _) => {}
+                (_, Value::None) => results[position] = Value::None,
+                (PreparedMatrix::Int(_), Value::IntArray(a)) => {
+                    int_operands.push((position, CowArray::from(a.view())));
+                }
+                (PreparedMatrix::Float(_), Value::FloatArray(a))
+                | (PreparedMatrix::Float32(_), Value::FloatArray(a)) => {
+                    float_operands.push((position, CowArray::from(a.view())));
+                }
+                (PreparedMatrix::Float(_), Value::Float32Array(a)) => {
+                    float_operands.push((position, CowArray::from(a.mapv(f64::from))));
+                }
+                (PreparedMatrix::Float32(_), Value::Float32Array(a)) => {
+                    float32_operands.push((position, CowArray::from(a.view())));
+                }
+                (_, operand) => {
+                    let kind = operand.kind();
+                    results[position] = self.log_error(format!(
+                        "can't perform matrix multiplication on {kind:?} and {}",
+                        prepared.kind_name()
+                    ));
+                }
+            }
+        }
+        match prepared {
+            PreparedMatrix::Int(matrix) => self.mul_batch(matrix, &int_operands, &mut results),
+            PreparedMatrix::Float(matrix) => self.mul_batch(matrix, &float_operands, &mut results),
+            PreparedMatrix::Float32(matrix) => {
+                self.mul_batch(matrix, &float32_operands, &mut results);
+                if !float_operands.is_empty() {
+                    let matrix =
+                        PackedMatrix::new(&matrix.view().mapv(f64::from).into_dyn().view())
+                            .expect("matrix should be two-dimensional");
+                    self.mul_batch(&matrix, &float_operands, &mut results);
+                }
+            }
+            PreparedMatrix::Invalid => {}
+        }
+        results
+    }
+}
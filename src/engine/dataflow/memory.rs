@@ -0,0 +1,175 @@
+// Copyright © 2024 Pathway
+
+//! Approximate accounting of the memory held by the arrangements, i.e. the indexed collections
+//! kept by the joins, reductions and other stateful operators.
+//!
+//! An arrangement is attributed to the operator of the program that was being built when the
+//! arrangement was created. The arrangements of a table are created when an operator needs them
+//! first, so they are attributed to the operator reading them, e.g. to the join, rather than to
+//! the operator that produced the table. Each worker periodically counts the updates in the
+//! batches of its arrangements and multiplies them by the in-memory size of an update. The data
+//! an update only points to, such as the contents of strings, isn't counted, so the sizes are a
+//! lower bound of the actual usage, but they show which operators keep the most data.
+//!
+//! An arrangement exceeding the soft limit is reported in the logs once, until it shrinks below
+//! the limit again.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::mem::size_of;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use differential_dataflow::lattice::Lattice;
+use differential_dataflow::operators::arrange::TraceAgent;
+use differential_dataflow::trace::{BatchReader, TraceReader};
+use log::warn;
+use once_cell::sync::Lazy;
+use timely::progress::{Antichain, Timestamp as TimelyTimestamp};
+
+pub const MEASUREMENT_INTERVAL: Duration = Duration::from_secs(10);
+
+struct TrackedArrangement {
+    operator_id: Option<usize>,
+    name: String,
+    update_size: usize,
+    count_updates: Box<dyn Fn() -> usize>,
+}
+
+thread_local! {
+    static CURRENT_OPERATOR: Cell<Option<usize>> = const { Cell::new(None) };
+    static ARRANGEMENTS: RefCell<Vec<TrackedArrangement>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArrangementMemory {
+    pub worker_id: usize,
+    /// The operator of the program the arrangement is attributed to, if it was created while
+    /// building one.
+    pub operator_id: Option<usize>,
+    pub name: String,
+    pub updates: usize,
+    pub bytes: u64,
+}
+
+/// The last measurements of the workers of this process, by worker.
+static MEASUREMENTS: Lazy<Mutex<HashMap<usize, Vec<ArrangementMemory>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Sets the operator the arrangements created by this worker thread are attributed to.
+pub fn set_current_operator(operator_id: Option<usize>) {
+    CURRENT_OPERATOR.with(|current| current.set(operator_id));
+}
+
+/// Starts measuring the arrangement with the given trace in this worker thread. The measuring
+/// handle doesn't hold back the compaction of the trace.
+pub fn track_arrangement<Tr>(trace: &TraceAgent<Tr>, name: &str)
+where
+    Tr: TraceReader + 'static,
+    Tr::Time: TimelyTimestamp + Lattice,
+{
+    let mut trace = trace.clone();
+    trace.set_logical_compaction(Antichain::new().borrow());
+    trace.set_physical_compaction(Antichain::new().borrow());
+    let arrangement = TrackedArrangement {
+        operator_id: CURRENT_OPERATOR.with(Cell::get),
+        name: name.to_string(),
+        update_size: size_of::<((Tr::Key, Tr::Val), Tr::Time, Tr::R)>(),
+        count_updates: Box::new(move || {
+            let mut updates = 0;
+            trace.map_batches(|batch| updates += batch.len());
+            updates
+        }),
+    };
+    ARRANGEMENTS.with(|arrangements| arrangements.borrow_mut().push(arrangement));
+}
+
+/// The last measurements of all the arrangements of this process.
+pub fn arrangements_memory() -> Vec<ArrangementMemory> {
+    MEASUREMENTS
+        .lock()
+        .unwrap()
+        .values()
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+/// Measures the arrangements of a worker. Has to be created on the worker thread before the
+/// dataflow is built.
+pub struct MemoryAccountant {
+    worker_id: usize,
+    soft_limit: Option<u64>,
+    next_measurement_at: Instant,
+    /// The positions of the arrangements exceeding the soft limit in the last measurement.
+    over_limit: HashSet<usize>,
+}
+
+impl MemoryAccountant {
+    pub fn new(worker_id: usize, soft_limit: Option<u64>) -> Self {
+        set_current_operator(None);
+        ARRANGEMENTS.with(|arrangements| arrangements.borrow_mut().clear());
+        Self {
+            worker_id,
+            soft_limit,
+            next_measurement_at: Instant::now() + MEASUREMENT_INTERVAL,
+            over_limit: HashSet::new(),
+        }
+    }
+
+    pub fn maybe_measure(&mut self) {
+        let now = Instant::now();
+        if now < self.next_measurement_at {
+            return;
+        }
+        self.next_measurement_at = now + MEASUREMENT_INTERVAL;
+        self.measure();
+    }
+
+    pub fn measure(&mut self) {
+        let measurements: Vec<_> = ARRANGEMENTS.with(|arrangements| {
+            arrangements
+                .borrow()
+                .iter()
+                .map(|arrangement| {
+                    let updates = (arrangement.count_updates)();
+                    ArrangementMemory {
+                        worker_id: self.worker_id,
+                        operator_id: arrangement.operator_id,
+                        name: arrangement.name.clone(),
+                        updates,
+                        bytes: (updates * arrangement.update_size) as u64,
+                    }
+                })
+                .collect()
+        });
+        if let Some(soft_limit) = self.soft_limit {
+            for (position, measurement) in measurements.iter().enumerate() {
+                if measurement.bytes <= soft_limit {
+                    self.over_limit.remove(&position);
+                } else if self.over_limit.insert(position) {
+                    warn!(
+                        "Arrangement {:?} of operator {} holds about {} bytes in {} updates, which exceeds the soft limit of {soft_limit} bytes",
+                        measurement.name,
+                        measurement
+                            .operator_id
+                            .map_or_else(|| "<unknown>".to_string(), |id| id.to_string()),
+                        measurement.bytes,
+                        measurement.updates,
+                    );
+                }
+            }
+        }
+        MEASUREMENTS
+            .lock()
+            .unwrap()
+            .insert(self.worker_id, measurements);
+    }
+}
+
+impl Drop for MemoryAccountant {
+    fn drop(&mut self) {
+        ARRANGEMENTS.with(|arrangements| arrangements.borrow_mut().clear());
+        MEASUREMENTS.lock().unwrap().remove(&self.worker_id);
+    }
+}
@@ -21,23 +21,20 @@ pub struct OperatorStats {
     pub done: bool,
 }
 
+/// Milliseconds between `time` and `now`, zero if `time` is in the future.
+pub fn processing_lag(time: Timestamp, now: SystemTime) -> u64 {
+    let duration = u64::try_from(
+        now.duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis(),
+    )
+    .unwrap();
+    duration.saturating_sub(time.0)
+}
+
 impl OperatorStats {
     pub fn latency(&self, now: SystemTime) -> Option<u64> {
-        if let Some(time) = self.time {
-            let duration = u64::try_from(
-                now.duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis(),
-            )
-            .unwrap();
-            if duration < time.0 {
-                Some(0)
-            } else {
-                Some(duration - time.0)
-            }
-        } else {
-            None
-        }
+        self.time.map(|time| processing_lag(time, now))
     }
 }
 
@@ -52,6 +49,8 @@ pub struct ProberStats {
     pub operators_stats: HashMap<usize, OperatorStats>,
     #[pyo3(get, set)]
     pub connector_stats: Vec<(String, ConnectorStats)>,
+    #[pyo3(get, set)]
+    pub output_connector_stats: Vec<(String, OperatorStats)>,
     #[pyo3(get)]
     pub row_counts: HashMap<usize, CountStats>,
 }
@@ -100,6 +99,7 @@ pub struct Prober {
     input_time_changed: Option<SystemTime>,
     output_time: Option<Timestamp>,
     output_time_changed: Option<SystemTime>,
+    output_connector_times: Vec<Option<Timestamp>>,
     intermediate_probes_required: bool,
     run_callback_every_time: bool,
     stats: HashMap<usize, OperatorStats>,
@@ -117,6 +117,7 @@ impl Prober {
             input_time_changed: None,
             output_time: None,
             output_time_changed: None,
+            output_connector_times: Vec::new(),
             intermediate_probes_required,
             run_callback_every_time,
             stats: HashMap::new(),
@@ -162,6 +163,7 @@ impl Prober {
         output_probe: &ProbeHandle<Timestamp>,
         intermediate_probes: &HashMap<usize, OperatorProbe<Timestamp>>,
         connector_monitors: &[Rc<RefCell<ConnectorMonitor>>],
+        output_connector_probes: &[(String, ProbeHandle<Timestamp>)],
     ) {
         let now = Lazy::new(SystemTime::now);
 
@@ -181,6 +183,15 @@ impl Prober {
             changed = true;
         }
 
+        let new_output_connector_times: Vec<Option<Timestamp>> = output_connector_probes
+            .iter()
+            .map(|(_, probe)| probe.with_frontier(|frontier| frontier.as_option().copied()))
+            .collect();
+        if new_output_connector_times != self.output_connector_times {
+            self.output_connector_times = new_output_connector_times;
+            changed = true;
+        }
+
         if self.intermediate_probes_required {
             for (id, probe) in intermediate_probes {
                 let new_time = probe
@@ -220,6 +231,10 @@ impl Prober {
                 output_stats: Self::create_stats(output_probe, self.input_time),
                 operators_stats: self.stats.clone(),
                 connector_stats,
+                output_connector_stats: output_connector_probes
+                    .iter()
+                    .map(|(name, probe)| (name.clone(), Self::create_stats(probe, self.input_time)))
+                    .collect(),
                 row_counts,
             };
 
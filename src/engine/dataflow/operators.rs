@@ -2,8 +2,11 @@
 
 pub mod external_index;
 pub mod gradual_broadcast;
+pub mod group_operation;
 pub mod output;
 pub mod prev_next;
+pub mod sampling;
+pub mod spill;
 pub mod stateful_reduce;
 pub mod time_column;
 mod utils;
@@ -26,6 +29,7 @@ use crate::engine::BatchWrapper;
 use self::output::ConsolidateForOutput;
 
 use super::maybe_total::{MaybeTotalScope, MaybeTotalSwitch};
+use super::memory::track_arrangement;
 use super::monitoring::OperatorProbe;
 use super::shard::Shard;
 use super::ArrangedBySelf;
@@ -126,9 +130,12 @@ where
         let exchange =
             Exchange::new(move |((key, _value), _time, _diff): &((K, V), _, _)| sharding(key));
         #[allow(clippy::disallowed_methods)]
-        differential_dataflow::operators::arrange::arrangement::Arrange::arrange_core(
-            self, exchange, &name,
-        )
+        let arranged =
+            differential_dataflow::operators::arrange::arrangement::Arrange::arrange_core(
+                self, exchange, &name,
+            );
+        track_arrangement(&arranged.trace, &name);
+        arranged
     }
 }
 
@@ -0,0 +1,285 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::panic::Location;
+
+use differential_dataflow::operators::arrange::{Arranged, TraceAgent};
+use differential_dataflow::trace::implementations::ord::OrdValSpine;
+use differential_dataflow::{AsCollection, Collection, ExchangeData};
+use itertools::Either;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::Operator;
+
+use crate::engine::dataflow::maybe_total::MaybeTotalScope;
+use crate::engine::dataflow::shard::Shard;
+
+use super::utils::batch_by_time;
+use super::{ArrangeWithTypes, MapWrapped};
+
+type KeyValArr<G, K, V> =
+    Arranged<G, TraceAgent<OrdValSpine<K, V, <G as MaybeTotalScope>::MaybeTotalTimestamp, isize>>>;
+
+/// Computation applied to the rows of a group together with an operand shared by the whole
+/// group, e.g. multiplication of the rows by a matrix assigned to the group.
+pub trait GroupOperation<W, V, Ret> {
+    /// Form of the operand that is computed once per its update and then reused for all rows
+    /// of the group, e.g. a matrix in a memory layout suitable for multiplication.
+    type Prepared;
+
+    fn prepare(&mut self, operand: &W) -> Self::Prepared;
+
+    /// Computes the results for a batch of rows of a single group, one result per row.
+    fn compute(&mut self, prepared: &Self::Prepared, rows: &[&V]) -> Vec<Ret>;
+}
+
+/// Trait denoting that a collection of per-group operands `(group, operand)` can be applied to
+/// a collection of rows `(key, (group, value))`. Each row with an operand present in its group
+/// produces `(key, (value, result))`.
+///
+/// If a group has more than one distinct operand, it's treated as having none.
+pub trait ApplyGroupOperation<G: MaybeTotalScope, KG, W> {
+    fn apply_group_operation<K, V, Ret, Op>(
+        &self,
+        rows: &Collection<G, (K, (KG, V))>,
+        operation: Op,
+    ) -> Collection<G, (K, (V, Ret))>
+    where
+        K: ExchangeData + Hash,
+        V: ExchangeData + Hash,
+        Ret: ExchangeData,
+        Op: GroupOperation<W, V, Ret> + 'static;
+}
+
+impl<G, KG, W> ApplyGroupOperation<G, KG, W> for Collection<G, (KG, W)>
+where
+    G: MaybeTotalScope,
+    KG: ExchangeData + Shard + Hash,
+    W: ExchangeData + Hash,
+{
+    #[track_caller]
+    fn apply_group_operation<K, V, Ret, Op>(
+        &self,
+        rows: &Collection<G, (K, (KG, V))>,
+        operation: Op,
+    ) -> Collection<G, (K, (V, Ret))>
+    where
+        K: ExchangeData + Hash,
+        V: ExchangeData + Hash,
+        Ret: ExchangeData,
+        Op: GroupOperation<W, V, Ret> + 'static,
+    {
+        apply_group_operation_core(self, rows, operation)
+    }
+}
+
+struct GroupState<K, V, W, P, Ret> {
+    operands: HashMap<W, isize>,
+    current_operand: Option<(W, P)>,
+    rows: HashMap<(K, V), isize>,
+    results: HashMap<(K, V), Ret>,
+}
+
+impl<K, V, W, P, Ret> Default for GroupState<K, V, W, P, Ret> {
+    fn default() -> Self {
+        Self {
+            operands: HashMap::new(),
+            current_operand: None,
+            rows: HashMap::new(),
+            results: HashMap::new(),
+        }
+    }
+}
+
+impl<K, V, W, P, Ret> GroupState<K, V, W, P, Ret>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+    W: Clone + Eq + Hash,
+    Ret: Clone,
+{
+    fn unique_operand(&self) -> Option<&W> {
+        let mut present = self.operands.iter().filter(|(_, count)| **count > 0);
+        match (present.next(), present.next()) {
+            (Some((operand, _)), None) => Some(operand),
+            _ => None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.operands.is_empty() && self.rows.is_empty()
+    }
+
+    /// Applies the updates from a single time and appends the resulting changes of the output to
+    /// `output`. Only the rows that changed are computed, unless the operand changed, in which
+    /// case the whole group is recomputed.
+    fn update<Op: GroupOperation<W, V, Ret, Prepared = P>>(
+        &mut self,
+        operation: &mut Op,
+        operand_updates: Vec<(W, isize)>,
+        row_updates: Vec<((K, V), isize)>,
+        output: &mut Vec<((K, (V, Ret)), isize)>,
+    ) {
+        for (operand, diff) in operand_updates {
+            *self.operands.entry(operand).or_default() += diff;
+        }
+        self.operands.retain(|_, count| *count != 0);
+        let new_operand = self.unique_operand().cloned();
+        let operand_changed =
+            new_operand.as_ref() != self.current_operand.as_ref().map(|(operand, _)| operand);
+        if operand_changed {
+            for (row, result) in self.results.drain() {
+                let count = self.rows[&row];
+                let (key, value) = row;
+                output.push(((key, (value, result)), -count));
+            }
+            self.current_operand = new_operand.map(|operand| {
+                let prepared = operation.prepare(&operand);
+                (operand, prepared)
+            });
+        }
+
+        let mut previous_counts = HashMap::new();
+        for (row, diff) in row_updates {
+            let count = self.rows.entry(row.clone()).or_default();
+            previous_counts.entry(row).or_insert(*count);
+            *count += diff;
+        }
+        self.rows.retain(|_, count| *count != 0);
+
+        let Some((_, prepared)) = &self.current_operand else {
+            return;
+        };
+        let to_compute: Vec<(K, V)> = if operand_changed {
+            self.rows
+                .iter()
+                .filter(|(_, count)| **count > 0)
+                .map(|(row, _)| row.clone())
+                .collect()
+        } else {
+            let mut to_compute = Vec::new();
+            for (row, previous_count) in previous_counts {
+                let count = self.rows.get(&row).copied().unwrap_or(0);
+                if let Some(result) = self.results.get(&row) {
+                    if count > 0 {
+                        if count != previous_count {
+                            let (key, value) = row;
+                            output.push(((key, (value, result.clone())), count - previous_count));
+                        }
+                    } else {
+                        let result = self.results.remove(&row).expect("result should be present");
+                        output.push(((row.0, (row.1, result)), -previous_count));
+                    }
+                } else if count > 0 {
+                    to_compute.push(row);
+                }
+            }
+            to_compute
+        };
+        if to_compute.is_empty() {
+            return;
+        }
+        let values: Vec<&V> = to_compute.iter().map(|(_, value)| value).collect();
+        let results = operation.compute(prepared, &values);
+        assert_eq!(results.len(), to_compute.len());
+        for (row, result) in to_compute.into_iter().zip(results) {
+            let count = self.rows[&row];
+            output.push(((row.0.clone(), (row.1.clone(), result.clone())), count));
+            self.results.insert(row, result);
+        }
+    }
+}
+
+/// Implementation of `apply_group_operation`.
+///  - both streams are sharded by the group, so that the state of a group is kept by one worker
+///  - the streams are merged and arranged, so that we work on complete data for each time
+///
+/// The state of each group holds the prepared operand and the results of its rows, so an update
+/// of a row only computes the result for this row, and an update of the operand recomputes the
+/// group in a single batch.
+#[track_caller]
+fn apply_group_operation_core<G, KG, K, W, V, Ret, Op>(
+    operand_stream: &Collection<G, (KG, W)>,
+    row_stream: &Collection<G, (K, (KG, V))>,
+    operation: Op,
+) -> Collection<G, (K, (V, Ret))>
+where
+    G: MaybeTotalScope,
+    KG: ExchangeData + Shard + Hash,
+    K: ExchangeData + Hash,
+    W: ExchangeData + Hash,
+    V: ExchangeData + Hash,
+    Ret: ExchangeData,
+    Op: GroupOperation<W, V, Ret> + 'static,
+{
+    let merged_stream = operand_stream
+        .map_named("wrap group operand stream in Either", |(group, operand)| {
+            (group, Either::Left(operand))
+        })
+        .concat(&row_stream.map_named(
+            "wrap group row stream in Either",
+            |(key, (group, value))| (group, Either::Right((key, value))),
+        ));
+    let merged_stream_arranged: KeyValArr<G, KG, Either<W, (K, V)>> =
+        merged_stream.arrange_named("apply group operation::arrange");
+
+    let caller = Location::caller();
+    merged_stream_arranged
+        .stream
+        .unary(
+            Pipeline,
+            &format!("apply group operation at {caller}"),
+            move |_capability, _info| {
+                // Swappable buffer for input extraction.
+                let mut input_buffer = Vec::new();
+
+                let mut operation = operation;
+                let mut groups: HashMap<KG, GroupState<K, V, W, Op::Prepared, Ret>> =
+                    HashMap::new();
+                move |input, output| {
+                    input.for_each(|capability, batch| {
+                        batch.swap(&mut input_buffer);
+                        let grouped = batch_by_time(&input_buffer, |group, val, _time, diff| {
+                            (group.clone(), val.clone(), *diff)
+                        });
+
+                        for (time, data) in grouped {
+                            let mut updates_by_group: HashMap<KG, (Vec<_>, Vec<_>)> =
+                                HashMap::new();
+                            for (group, val, diff) in data {
+                                let (operand_updates, row_updates) =
+                                    updates_by_group.entry(group).or_default();
+                                match val {
+                                    Either::Left(operand) => operand_updates.push((operand, diff)),
+                                    Either::Right(row) => row_updates.push((row, diff)),
+                                }
+                            }
+
+                            let mut changes = Vec::new();
+                            for (group, (operand_updates, row_updates)) in updates_by_group {
+                                let state = groups.entry(group.clone()).or_default();
+                                state.update(
+                                    &mut operation,
+                                    operand_updates,
+                                    row_updates,
+                                    &mut changes,
+                                );
+                                if state.is_empty() {
+                                    groups.remove(&group);
+                                }
+                            }
+
+                            let delayed = &capability.delayed(&time);
+                            let mut session = output.session(delayed);
+                            let mut ret: Vec<_> = changes
+                                .into_iter()
+                                .map(|(data, diff)| (data, time.clone(), diff))
+                                .collect();
+                            session.give_vec(&mut ret);
+                        }
+                    });
+                }
+            },
+        )
+        .as_collection()
+}
@@ -0,0 +1,72 @@
+// Copyright © 2024 Pathway
+
+//! Deterministic sampling of the rows of a table.
+//!
+//! Each row gets a priority, which is a hash of its key salted with the seed of the sample.
+//! A sample of a fraction keeps the rows with the priority below that fraction of the range of
+//! the hash, and a reservoir of a given size keeps the rows with the lowest priorities within
+//! each instance. As the priority depends only on the key and the seed, a row is either always
+//! in the sample or never, regardless of the order of the updates or the number of workers. In
+//! particular, a deletion of a sampled row removes it from the sample, and a reservoir then
+//! takes in the row with the next lowest priority.
+
+use differential_dataflow::operators::reduce::Reduce;
+use differential_dataflow::Collection;
+
+use crate::engine::dataflow::maybe_total::MaybeTotalScope;
+use crate::engine::dataflow::DIFF_INSERTION;
+use crate::engine::{Key, Value};
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+pub fn sampling_priority(key: Key, seed: u64) -> u64 {
+    let salted_key = Key::for_values(&[Value::Pointer(key), Value::Int(seed as i64)]);
+    (salted_key.0 >> 64) as u64
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub fn is_in_sample(priority: u64, fraction: f64) -> bool {
+    fraction >= 1.0 || (priority as f64) < fraction * u64::MAX as f64
+}
+
+pub trait Sample {
+    /// Keeps the given fraction of the rows.
+    #[must_use]
+    fn sample(&self, fraction: f64, seed: u64) -> Self;
+
+    /// Keeps at most `size` rows for each instance, computed by `instance_fn`.
+    #[must_use]
+    fn reservoir_sample(
+        &self,
+        instance_fn: impl FnMut(&Key, &Value) -> Key + 'static,
+        size: usize,
+        seed: u64,
+    ) -> Self;
+}
+
+impl<S: MaybeTotalScope> Sample for Collection<S, (Key, Value)> {
+    fn sample(&self, fraction: f64, seed: u64) -> Self {
+        self.filter(move |(key, _values)| is_in_sample(sampling_priority(*key, seed), fraction))
+    }
+
+    fn reservoir_sample(
+        &self,
+        mut instance_fn: impl FnMut(&Key, &Value) -> Key + 'static,
+        size: usize,
+        seed: u64,
+    ) -> Self {
+        self.map(move |(key, values)| {
+            let instance = instance_fn(&key, &values);
+            (instance, (sampling_priority(key, seed), key, values))
+        })
+        .reduce(move |_instance, input, output| {
+            // the input is sorted, so the rows with the lowest priorities come first
+            output.extend(
+                input
+                    .iter()
+                    .take(size)
+                    .map(|(entry, _count)| ((*entry).clone(), DIFF_INSERTION)),
+            );
+        })
+        .map(|(_instance, (_priority, key, values))| (key, values))
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright © 2024 Pathway
+
+//! Per-key operator state that can be larger than the memory. When there are too many values,
+//! the least recently used ones are moved to a temporary file in the directory set by
+//! `PATHWAY_SPILL_DIRECTORY`, and only their keys and positions stay in memory. A spilled value
+//! is read back when its key is updated again. The file is rewritten without the values read
+//! back once they take most of it.
+//!
+//! If the directory is not set, all values are kept in memory.
+//!
+//! Only the states of stateful reducers and deduplication are spilled. Arrangements, such as
+//! the state of joins, are always kept in memory.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::env::{parse_env_var, Error as EnvError};
+use crate::fs_helpers::ensure_directory;
+
+const DEFAULT_HOT_ENTRIES: usize = 100_000;
+const MIN_COMPACTED_GARBAGE: u64 = 64 * 1024 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpillConfig {
+    pub directory: PathBuf,
+    /// The number of values of a single operator kept in memory.
+    pub hot_entries: usize,
+}
+
+fn spill_config_from_env() -> Option<SpillConfig> {
+    let config = || -> Result<Option<SpillConfig>, EnvError> {
+        let Some(directory) = parse_env_var("PATHWAY_SPILL_DIRECTORY")? else {
+            return Ok(None);
+        };
+        let hot_entries =
+            parse_env_var("PATHWAY_SPILL_HOT_ENTRIES")?.unwrap_or(DEFAULT_HOT_ENTRIES);
+        Ok(Some(SpillConfig {
+            directory,
+            hot_entries,
+        }))
+    };
+    config().unwrap_or_else(|e| {
+        warn!("Operator state is not spilled to disk: {e}");
+        None
+    })
+}
+
+static SPILL_CONFIG: Lazy<Option<SpillConfig>> = Lazy::new(spill_config_from_env);
+
+/// The spilling configured for the run. `None` if the operator state is kept in memory.
+pub fn spill_config() -> Option<SpillConfig> {
+    SPILL_CONFIG.clone()
+}
+
+/// The spilled values, appended to an unnamed temporary file that is removed with it.
+struct ColdStorage<K, V> {
+    directory: PathBuf,
+    file: File,
+    end: u64,
+    positions: HashMap<K, (u64, u64)>,
+    garbage: u64,
+    value_type: PhantomData<V>,
+}
+
+impl<K, V> ColdStorage<K, V>
+where
+    K: Eq + Hash,
+    V: Serialize + DeserializeOwned,
+{
+    fn new(directory: &Path) -> io::Result<Self> {
+        ensure_directory(directory)?;
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            file: tempfile::tempfile_in(directory)?,
+            end: 0,
+            positions: HashMap::new(),
+            garbage: 0,
+            value_type: PhantomData,
+        })
+    }
+
+    fn append(&mut self, entries: Vec<(K, V)>) -> io::Result<()> {
+        let mut buffer = Vec::new();
+        for (key, value) in entries {
+            let start = buffer.len();
+            bincode::serialize_into(&mut buffer, &value).map_err(io::Error::other)?;
+            let position = (self.end + start as u64, (buffer.len() - start) as u64);
+            if let Some((_, length)) = self.positions.insert(key, position) {
+                self.garbage += length;
+            }
+        }
+        self.file.seek(SeekFrom::Start(self.end))?;
+        self.file.write_all(&buffer)?;
+        self.end += buffer.len() as u64;
+        Ok(())
+    }
+
+    fn read_at(&mut self, offset: u64, length: u64, buffer: &mut Vec<u8>) -> io::Result<()> {
+        buffer.resize(usize::try_from(length).map_err(io::Error::other)?, 0);
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(buffer)
+    }
+
+    fn take(&mut self, key: &K) -> io::Result<Option<V>> {
+        let Some((offset, length)) = self.positions.remove(key) else {
+            return Ok(None);
+        };
+        let mut buffer = Vec::new();
+        self.read_at(offset, length, &mut buffer)?;
+        let value = bincode::deserialize(&buffer).map_err(io::Error::other)?;
+        self.garbage += length;
+        self.maybe_compact()?;
+        Ok(Some(value))
+    }
+
+    fn discard(&mut self, key: &K) -> io::Result<()> {
+        if let Some((_, length)) = self.positions.remove(key) {
+            self.garbage += length;
+            self.maybe_compact()?;
+        }
+        Ok(())
+    }
+
+    fn maybe_compact(&mut self) -> io::Result<()> {
+        if self.garbage < MIN_COMPACTED_GARBAGE || self.garbage * 2 < self.end {
+            return Ok(());
+        }
+        let mut writer = BufWriter::new(tempfile::tempfile_in(&self.directory)?);
+        let mut positions: Vec<_> = self.positions.values_mut().collect();
+        positions.sort_unstable_by_key(|(offset, _)| *offset);
+        let mut end = 0;
+        let mut buffer = Vec::new();
+        for position in positions {
+            let (offset, length) = *position;
+            buffer.resize(usize::try_from(length).map_err(io::Error::other)?, 0);
+            self.file.seek(SeekFrom::Start(offset))?;
+            self.file.read_exact(&mut buffer)?;
+            writer.write_all(&buffer)?;
+            *position = (end, length);
+            end += length;
+        }
+        self.file = writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+        self.end = end;
+        self.garbage = 0;
+        Ok(())
+    }
+}
+
+/// A map from the keys to the operator state, keeping at most
+/// [`SpillConfig::hot_entries`] values in memory.
+pub struct SpillableMap<K, V> {
+    config: Option<SpillConfig>,
+    hot: HashMap<K, (V, u64)>,
+    clock: u64,
+    cold: Option<ColdStorage<K, V>>,
+}
+
+impl<K, V> SpillableMap<K, V>
+where
+    K: Eq + Hash,
+    V: Serialize + DeserializeOwned,
+{
+    pub fn new(config: Option<SpillConfig>) -> Self {
+        Self {
+            config,
+            hot: HashMap::new(),
+            clock: 0,
+            cold: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.hot.len() + self.spilled_len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of values kept on disk.
+    pub fn spilled_len(&self) -> usize {
+        self.cold.as_ref().map_or(0, |cold| cold.positions.len())
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> io::Result<()> {
+        if let Some(cold) = &mut self.cold {
+            cold.discard(&key)?;
+        }
+        self.clock += 1;
+        self.hot.insert(key, (value, self.clock));
+        self.maybe_spill()
+    }
+
+    pub fn remove(&mut self, key: &K) -> io::Result<Option<V>> {
+        if let Some((value, _)) = self.hot.remove(key) {
+            return Ok(Some(value));
+        }
+        match &mut self.cold {
+            Some(cold) => cold.take(key),
+            None => Ok(None),
+        }
+    }
+
+    fn maybe_spill(&mut self) -> io::Result<()> {
+        let Some(config) = &self.config else {
+            return Ok(());
+        };
+        if self.hot.len() <= config.hot_entries {
+            return Ok(());
+        }
+        // Spilling half of the values at once makes finding the least recently used ones cheap
+        let spilled_count = self.hot.len() - config.hot_entries / 2;
+        let mut last_uses: Vec<u64> = self.hot.values().map(|(_, last_use)| *last_use).collect();
+        let (_, threshold, _) = last_uses.select_nth_unstable(spilled_count - 1);
+        let threshold = *threshold;
+
+        let mut spilled = Vec::with_capacity(spilled_count);
+        for (key, (value, last_use)) in std::mem::take(&mut self.hot) {
+            if last_use <= threshold {
+                spilled.push((key, value));
+            } else {
+                self.hot.insert(key, (value, last_use));
+            }
+        }
+        if self.cold.is_none() {
+            self.cold = Some(ColdStorage::new(&config.directory)?);
+        }
+        self.cold.as_mut().unwrap().append(spilled)
+    }
+}
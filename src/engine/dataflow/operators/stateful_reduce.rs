@@ -1,6 +1,6 @@
 // Copyright © 2024 Pathway
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::hash::Hash;
 use std::panic::Location;
 
@@ -12,10 +12,13 @@ use timely::dataflow::channels::pact::Pipeline;
 use timely::dataflow::operators::Operator;
 use timely::order::TotalOrder;
 
+use super::spill::{spill_config, SpillableMap};
 use super::ArrangeWithTypes;
 use crate::engine::dataflow::maybe_total::MaybeTotalScope;
 use crate::engine::dataflow::shard::Shard;
 use crate::engine::dataflow::ArrangedByKey;
+use crate::engine::report_error::{ReportError, ReportErrorExt};
+use crate::engine::Error;
 
 pub trait StatefulReduce<S, K, V, R>
 where
@@ -24,16 +27,19 @@ where
     R: Semigroup,
 {
     #[track_caller]
-    fn stateful_reduce<V2: Data>(
+    fn stateful_reduce<V2: ExchangeData>(
         &self,
+        error_reporter: impl ReportError + 'static,
         logic: impl FnMut(Option<&V2>, Vec<(V, R)>) -> Option<V2> + 'static,
     ) -> Collection<S, (K, V2), R> {
-        self.stateful_reduce_named("StatefulReduce", logic)
+        self.stateful_reduce_named("StatefulReduce", error_reporter, logic)
     }
 
-    fn stateful_reduce_named<V2: Data>(
+    /// Failures of spilling the state to disk are reported with `error_reporter`.
+    fn stateful_reduce_named<V2: ExchangeData>(
         &self,
         name: &str,
+        error_reporter: impl ReportError + 'static,
         logic: impl FnMut(Option<&V2>, Vec<(V, R)>) -> Option<V2> + 'static,
     ) -> Collection<S, (K, V2), R>;
 }
@@ -47,13 +53,14 @@ where
     R: ExchangeData + Semigroup + From<i8>,
 {
     #[track_caller]
-    fn stateful_reduce_named<V2: Data>(
+    fn stateful_reduce_named<V2: ExchangeData>(
         &self,
         name: &str,
+        error_reporter: impl ReportError + 'static,
         logic: impl FnMut(Option<&V2>, Vec<(V, R)>) -> Option<V2> + 'static,
     ) -> Collection<S, (K, V2), R> {
         let arranged: ArrangedByKey<S, K, V, R> = self.arrange_named(&format!("Arrange: {name}"));
-        arranged.stateful_reduce_named(name, logic)
+        arranged.stateful_reduce_named(name, error_reporter, logic)
     }
 }
 
@@ -62,20 +69,21 @@ where
     S: MaybeTotalScope,
     S::Timestamp: TotalOrder,
     Tr: TraceReader<Time = S::Timestamp> + Clone,
-    Tr::Key: Data + Hash,
+    Tr::Key: ExchangeData + Hash,
     Tr::Val: Data,
     Tr::R: Semigroup + From<i8>,
 {
     #[track_caller]
-    fn stateful_reduce_named<V2: Data>(
+    fn stateful_reduce_named<V2: ExchangeData>(
         &self,
         name: &str,
+        error_reporter: impl ReportError + 'static,
         mut logic: impl FnMut(Option<&V2>, Vec<(Tr::Val, Tr::R)>) -> Option<V2> + 'static,
     ) -> Collection<S, (Tr::Key, V2), Tr::R> {
         let caller = Location::caller();
         let name = format!("{name} at {caller}");
 
-        let mut state_by_key: HashMap<Tr::Key, V2> = HashMap::new();
+        let mut state_by_key: SpillableMap<Tr::Key, V2> = SpillableMap::new(spill_config());
         self.stream
             .unary(Pipeline, &name, move |_, _| {
                 move |input, output| {
@@ -97,7 +105,9 @@ where
                                     });
                                     cursor.step_val(batch);
                                 }
-                                let mut state = state_by_key.remove(key);
+                                let mut state = state_by_key.remove(key).unwrap_or_else(|e| {
+                                    error_reporter.report_and_panic(Error::SpillFailed(e))
+                                });
                                 for (time, data) in data_by_time {
                                     let new_state = logic(state.as_ref(), data);
                                     if new_state == state {
@@ -120,7 +130,9 @@ where
                                     state = new_state;
                                 }
                                 if let Some(state) = state {
-                                    state_by_key.insert(key.clone(), state);
+                                    state_by_key.insert(key.clone(), state).unwrap_or_else(|e| {
+                                        error_reporter.report_and_panic(Error::SpillFailed(e));
+                                    });
                                 }
                                 cursor.step_key(batch);
                             }
@@ -28,6 +28,7 @@ use crate::engine::reduce::{
     AppendOnlyAnyState, AppendOnlyArgMaxState, AppendOnlyArgMinState, AppendOnlyMaxState,
     AppendOnlyMinState, ArraySumState, ErrorStateWrapper, FloatSumState, IntSumState,
 };
+use crate::engine::report_error::ReportError;
 use crate::engine::{Key, Result, Timestamp, Value};
 use crate::persistence::config::PersistenceManagerConfig;
 use crate::persistence::operator_snapshot::{OperatorSnapshotReader, OperatorSnapshotWriter};
@@ -857,6 +858,7 @@ where
     fn persisted_stateful_reduce_named<V2>(
         &self,
         name: &str,
+        error_reporter: impl ReportError + 'static,
         logic: impl FnMut(Option<&V2>, Vec<(V, R)>) -> Option<V2> + 'static,
         reader: Box<dyn OperatorSnapshotReader<(K, V2), R> + Send>,
         writer: Arc<Mutex<dyn OperatorSnapshotWriter<S::Timestamp, (K, V2), R>>>,
@@ -883,6 +885,7 @@ where
     fn persisted_stateful_reduce_named<V2>(
         &self,
         name: &str,
+        error_reporter: impl ReportError + 'static,
         mut logic: impl FnMut(Option<&V2>, Vec<(V, R)>) -> Option<V2> + 'static,
         reader: Box<dyn OperatorSnapshotReader<(K, V2), R> + Send>,
         writer: Arc<Mutex<dyn OperatorSnapshotWriter<S::Timestamp, (K, V2), R>>>,
@@ -894,9 +897,10 @@ where
         let (state, poller, thread_handle) = read_persisted_state(name, self.scope(), reader);
         let new_data = self.map_named("Persist:New", |(key, value)| (key, OldOrNew::New(value)));
         let state = state.map_named("Persist:Old", |(key, value)| (key, OldOrNew::Old(value)));
-        let reduced = new_data
-            .concat(&state)
-            .stateful_reduce_named(name, move |state, data| {
+        let reduced = new_data.concat(&state).stateful_reduce_named(
+            name,
+            error_reporter,
+            move |state, data| {
                 let mut old = None;
                 let mut new = Vec::with_capacity(data.len());
                 for entry in data {
@@ -923,7 +927,8 @@ where
                 } else {
                     logic(state, new)
                 }
-            });
+            },
+        );
         let collection_after_saving =
             persist_state(&reduced, &format!("Persist: {name}"), writer, |key_state| {
                 key_state
@@ -0,0 +1,94 @@
+// Copyright © 2024 Pathway
+
+//! Graceful termination of the computation running in this process. Once a shutdown is
+//! requested, input connectors stop reading and commit what they have already read, the data
+//! that is already in the dataflow is processed to the end, outputs are flushed and the run
+//! returns as if all the sources were bounded.
+//!
+//! A shutdown can also be requested through the [`CancellationToken`] passed to a single run.
+//! Cancelling the token stops only the run it was passed to, even if it is cancelled before
+//! that run has started.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Thread};
+
+use once_cell::sync::Lazy;
+
+struct ShutdownState {
+    requested: AtomicBool,
+    // worker threads are woken up on request, as they may be parked waiting for new data
+    workers: Mutex<Vec<Thread>>,
+    // the token of the running computation
+    token: Mutex<Option<CancellationToken>>,
+}
+
+static SHUTDOWN_STATE: Lazy<ShutdownState> = Lazy::new(|| ShutdownState {
+    requested: AtomicBool::new(false),
+    workers: Mutex::new(Vec::new()),
+    token: Mutex::new(None),
+});
+
+/// Requests graceful termination of the single run it is passed to.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests termination of the run that got this token. If the run hasn't started yet, it
+    /// is terminated as soon as it starts. Can be called from any thread and returns without
+    /// waiting for the run to finish.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+        // the flag is set before taking the lock, so either the run sees it when the token is
+        // installed, or the token is already installed here
+        let token = SHUTDOWN_STATE.token.lock().unwrap();
+        if token
+            .as_ref()
+            .is_some_and(|token| Arc::ptr_eq(&token.0, &self.0))
+        {
+            request_shutdown();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Requests graceful termination of the running computation. Can be called from any thread,
+/// returns without waiting for the computation to finish.
+pub fn request_shutdown() {
+    SHUTDOWN_STATE.requested.store(true, Ordering::SeqCst);
+    for worker in SHUTDOWN_STATE.workers.lock().unwrap().iter() {
+        worker.unpark();
+    }
+}
+
+pub fn is_shutdown_requested() -> bool {
+    SHUTDOWN_STATE.requested.load(Ordering::SeqCst)
+}
+
+/// Clears the state left by the previous computation and installs the cancellation token of
+/// the new one. Called once before the workers start.
+pub fn reset_shutdown(cancellation_token: Option<CancellationToken>) {
+    SHUTDOWN_STATE.workers.lock().unwrap().clear();
+    let mut token = SHUTDOWN_STATE.token.lock().unwrap();
+    let cancelled = cancellation_token
+        .as_ref()
+        .is_some_and(CancellationToken::is_cancelled);
+    *token = cancellation_token;
+    SHUTDOWN_STATE.requested.store(cancelled, Ordering::SeqCst);
+}
+
+/// Registers the current thread as a worker that has to be woken up on shutdown request.
+pub fn register_worker() {
+    SHUTDOWN_STATE
+        .workers
+        .lock()
+        .unwrap()
+        .push(thread::current());
+}
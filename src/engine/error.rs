@@ -3,6 +3,7 @@
 use std::any::Any;
 use std::error;
 use std::fmt;
+use std::io;
 use std::result;
 
 use super::ColumnPath;
@@ -125,6 +126,13 @@ pub enum Error {
     #[error("reader failed: {0:?}")]
     ReaderFailed(#[source] ReadError),
 
+    #[error("connector {connector_name} failed: {inner}")]
+    ConnectorFailed {
+        connector_name: String,
+        #[source]
+        inner: DynError,
+    },
+
     #[error("computation of imported table failed")]
     ImportedTableFailed,
 
@@ -148,6 +156,9 @@ pub enum Error {
 
     #[error("exactly once join is not supported in iteration")]
     ExactlyOnceJoinNotSupportedInIteration,
+
+    #[error("spilling the operator state to disk failed: {0}")]
+    SpillFailed(#[source] io::Error),
 }
 
 const OTHER_WORKER_ERROR_MESSAGES: [&str; 3] = [
@@ -182,6 +193,13 @@ impl Error {
         }
     }
 
+    pub fn connector_failed(connector_name: impl Into<String>, error: impl Into<DynError>) -> Self {
+        Self::ConnectorFailed {
+            connector_name: connector_name.into(),
+            inner: error.into(),
+        }
+    }
+
     pub fn with_trace(error: impl Into<DynError>, trace: Trace) -> Self {
         Self::WithTrace {
             inner: error.into(),
@@ -352,6 +370,15 @@ pub enum DataError {
     #[error("Repeated entry in a batch.")]
     RepeatedEntryInBatch,
 
+    /// An error that stops the computation even if it is not configured to terminate on errors.
+    #[error(transparent)]
+    Fatal(DynError),
+
+    /// An error that goes to the error log even if the computation is configured to terminate
+    /// on errors.
+    #[error(transparent)]
+    ToErrorLog(DynError),
+
     #[error(transparent)]
     Other(DynError),
 }
@@ -3,11 +3,14 @@
 #![allow(clippy::module_name_repetitions)]
 
 use arcstr::ArcStr;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
 use log::warn;
-use ndarray::{ArrayD, Axis, LinalgScalar};
+use ndarray::{ArrayD, Axis};
 use num_integer::Integer;
 use ordered_float::OrderedFloat;
 use std::cmp::Ordering;
+use std::mem::take;
 use std::ops::{Deref, Range};
 use std::sync::Arc;
 use std::vec::IntoIter;
@@ -21,7 +24,7 @@ use super::time::{DateTime, DateTimeNaive, DateTimeUtc, Duration};
 use super::value::Kind;
 use super::{Key, Type, Value};
 use crate::engine::ShardPolicy;
-use crate::mat_mul::mat_mul;
+use crate::mat_mul::{mat_mul, MatMulScalar};
 
 #[derive(Debug)]
 pub enum Expressions {
@@ -287,6 +290,17 @@ pub enum StringExpression {
     DateTimeNaiveStrftime(Arc<Expression>, Arc<Expression>),
     DateTimeUtcStrftime(Arc<Expression>, Arc<Expression>),
     ToString(Arc<Expression>),
+    EncodeBase64(Arc<Expression>),
+    EncodeHex(Arc<Expression>),
+    FromUtf8Lossy(Arc<Expression>),
+    Format(Arc<FormatTemplate>, Expressions),
+}
+
+#[derive(Debug)]
+pub enum BytesExpression {
+    DecodeBase64(Arc<Expression>),
+    DecodeHex(Arc<Expression>),
+    ToUtf8(Arc<Expression>),
 }
 
 #[derive(Debug)]
@@ -341,6 +355,7 @@ pub enum Expression {
     Float(FloatExpression),
     Pointer(PointerExpression),
     String(StringExpression),
+    Bytes(BytesExpression),
     DateTimeNaive(DateTimeNaiveExpression),
     DateTimeUtc(DateTimeUtcExpression),
     Duration(DurationExpression),
@@ -381,6 +396,7 @@ fn get_array_element(value: Value, index: i64) -> DynResult<Option<Value>> {
     match value {
         Value::IntArray(array) => get_ndarray_element(&array, index),
         Value::FloatArray(array) => get_ndarray_element(&array, index),
+        Value::Float32Array(array) => get_ndarray_element(&array, index),
         Value::Tuple(tuple) => get_tuple_element(&tuple, index),
         _ => Err(DynError::from(DataError::ValueError(format!(
             "Can't get element at index {index} out of {value:?}"
@@ -405,9 +421,275 @@ fn get_json_item(value: &Value, index: Value) -> DynResult<Option<Value>> {
     Ok(json.map(|json| Value::from(json.clone())))
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FormatAlign {
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FormatKind {
+    Default,
+    String,
+    Int,
+    Float,
+    Hex,
+    UpperHex,
+}
+
+#[derive(Debug, Clone)]
+struct FormatSpec {
+    raw: String,
+    argument: usize,
+    fill: char,
+    align: Option<FormatAlign>,
+    width: usize,
+    precision: Option<usize>,
+    kind: FormatKind,
+}
+
+#[derive(Debug, Clone)]
+enum FormatSegment {
+    Literal(String),
+    Placeholder(FormatSpec),
+}
+
+/// A parsed `format`-style template, e.g. `"{}: {:.2}"`.
+///
+/// Placeholders are `{[index][:[[fill]align][width][.precision][type]]}` where `align` is
+/// one of `<`, `>`, `^` and `type` is one of `s`, `d`, `f`, `x`, `X`. Indices can either be
+/// given for all placeholders or omitted for all of them. Literal braces are written as
+/// `{{` and `}}`.
+#[derive(Debug, Clone)]
+pub struct FormatTemplate {
+    segments: Vec<FormatSegment>,
+    n_arguments: usize,
+}
+
+impl FormatTemplate {
+    pub fn parse(template: &str) -> Result<Self, DataError> {
+        let invalid =
+            |msg: &str| DataError::ValueError(format!("invalid format string {template:?}: {msg}"));
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut next_auto_index = 0;
+        let mut explicit_indices = None;
+        let mut n_arguments = 0;
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(invalid("single '}' encountered")),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => return Err(invalid("unmatched '{'")),
+                            Some(c) => placeholder.push(c),
+                        }
+                    }
+                    let (index, spec) = placeholder
+                        .split_once(':')
+                        .unwrap_or((placeholder.as_str(), ""));
+                    let is_explicit = !index.is_empty();
+                    if *explicit_indices.get_or_insert(is_explicit) != is_explicit {
+                        return Err(invalid(
+                            "cannot mix automatic and explicit argument indices",
+                        ));
+                    }
+                    let argument = if is_explicit {
+                        index
+                            .parse()
+                            .map_err(|_| invalid(&format!("bad argument index {index:?}")))?
+                    } else {
+                        next_auto_index += 1;
+                        next_auto_index - 1
+                    };
+                    n_arguments = n_arguments.max(argument + 1);
+                    let spec = FormatSpec::parse(spec, argument).map_err(|msg| invalid(&msg))?;
+                    if !literal.is_empty() {
+                        segments.push(FormatSegment::Literal(take(&mut literal)));
+                    }
+                    segments.push(FormatSegment::Placeholder(spec));
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(FormatSegment::Literal(literal));
+        }
+        Ok(Self {
+            segments,
+            n_arguments,
+        })
+    }
+
+    pub fn n_arguments(&self) -> usize {
+        self.n_arguments
+    }
+
+    pub fn render(&self, arguments: &[Value]) -> DynResult<ArcStr> {
+        let mut result = String::new();
+        for segment in &self.segments {
+            match segment {
+                FormatSegment::Literal(literal) => result.push_str(literal),
+                FormatSegment::Placeholder(spec) => {
+                    let value = arguments
+                        .get(spec.argument)
+                        .ok_or(DataError::IndexOutOfBounds)?;
+                    spec.render_into(value, &mut result)?;
+                }
+            }
+        }
+        Ok(result.into())
+    }
+}
+
+impl FormatSpec {
+    fn parse(raw: &str, argument: usize) -> Result<Self, String> {
+        let chars: Vec<char> = raw.chars().collect();
+        let as_align = |c: char| match c {
+            '<' => Some(FormatAlign::Left),
+            '>' => Some(FormatAlign::Right),
+            '^' => Some(FormatAlign::Center),
+            _ => None,
+        };
+        let mut pos = 0;
+        let mut fill = ' ';
+        let mut align = None;
+        if let Some(a) = chars.get(1).copied().and_then(as_align) {
+            fill = chars[0];
+            align = Some(a);
+            pos = 2;
+        } else if let Some(a) = chars.first().copied().and_then(as_align) {
+            align = Some(a);
+            pos = 1;
+        }
+        let take_number = |pos: &mut usize| -> Option<usize> {
+            let start = *pos;
+            while chars.get(*pos).is_some_and(char::is_ascii_digit) {
+                *pos += 1;
+            }
+            if start == *pos {
+                None
+            } else {
+                chars[start..*pos].iter().collect::<String>().parse().ok()
+            }
+        };
+        let width = take_number(&mut pos).unwrap_or(0);
+        let mut precision = None;
+        if chars.get(pos) == Some(&'.') {
+            pos += 1;
+            precision = Some(take_number(&mut pos).ok_or("missing precision after '.'")?);
+        }
+        let kind = match chars.get(pos) {
+            None => FormatKind::Default,
+            Some('s') => FormatKind::String,
+            Some('d') => FormatKind::Int,
+            Some('f') => FormatKind::Float,
+            Some('x') => FormatKind::Hex,
+            Some('X') => FormatKind::UpperHex,
+            Some(c) => return Err(format!("unknown format type {c:?}")),
+        };
+        if pos + usize::from(kind != FormatKind::Default) != chars.len() {
+            return Err(format!("unexpected trailing characters in {{:{raw}}}"));
+        }
+        if precision.is_some()
+            && matches!(
+                kind,
+                FormatKind::Int | FormatKind::Hex | FormatKind::UpperHex
+            )
+        {
+            return Err(format!(
+                "precision not allowed for integer format {{:{raw}}}"
+            ));
+        }
+        Ok(Self {
+            raw: raw.to_string(),
+            argument,
+            fill,
+            align,
+            width,
+            precision,
+            kind,
+        })
+    }
+
+    fn render_into(&self, value: &Value, output: &mut String) -> DynResult<()> {
+        let mismatch = || {
+            DynError::from(DataError::ValueError(format!(
+                "cannot format {value} with {{:{}}}",
+                self.raw
+            )))
+        };
+        let truncate = |s: &str| match self.precision {
+            Some(precision) => s.chars().take(precision).collect(),
+            None => s.to_string(),
+        };
+        let (text, is_numeric) = match (self.kind, value) {
+            (FormatKind::Default | FormatKind::String, Value::String(s)) => (truncate(s), false),
+            (FormatKind::Default | FormatKind::Int, Value::Int(i)) if self.precision.is_none() => {
+                (i.to_string(), true)
+            }
+            (FormatKind::Hex, Value::Int(i)) => (format_signed_hex(*i, false), true),
+            (FormatKind::UpperHex, Value::Int(i)) => (format_signed_hex(*i, true), true),
+            #[allow(clippy::cast_precision_loss)]
+            (FormatKind::Float, Value::Int(i)) => {
+                let precision = self.precision.unwrap_or(6);
+                (format!("{:.precision$}", *i as f64), true)
+            }
+            (FormatKind::Float, Value::Float(OrderedFloat(f))) => {
+                let precision = self.precision.unwrap_or(6);
+                (format!("{f:.precision$}"), true)
+            }
+            (FormatKind::Default, Value::Float(OrderedFloat(f))) => match self.precision {
+                Some(precision) => (format!("{f:.precision$}"), true),
+                None => (value.to_string(), true),
+            },
+            (FormatKind::Default, value) if !matches!(value, Value::Int(_)) => {
+                (truncate(&value.to_string()), false)
+            }
+            _ => return Err(mismatch()),
+        };
+        let padding = self.width.saturating_sub(text.chars().count());
+        let align = self.align.unwrap_or(if is_numeric {
+            FormatAlign::Right
+        } else {
+            FormatAlign::Left
+        });
+        let (left, right) = match align {
+            FormatAlign::Left => (0, padding),
+            FormatAlign::Right => (padding, 0),
+            FormatAlign::Center => (padding / 2, padding - padding / 2),
+        };
+        output.extend(std::iter::repeat_n(self.fill, left));
+        output.push_str(&text);
+        output.extend(std::iter::repeat_n(self.fill, right));
+        Ok(())
+    }
+}
+
+fn format_signed_hex(value: i64, uppercase: bool) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    if uppercase {
+        format!("{sign}{:X}", value.unsigned_abs())
+    } else {
+        format!("{sign}{:x}", value.unsigned_abs())
+    }
+}
+
 fn mat_mul_wrapper<T>(lhs: &ArrayD<T>, rhs: &ArrayD<T>) -> DynResult<Value>
 where
-    T: LinalgScalar,
+    T: MatMulScalar,
     Value: From<ArrayD<T>>,
 {
     if let Some(result) = mat_mul(&lhs.view(), &rhs.view()) {
@@ -462,8 +744,13 @@ fn compare_tuples(lhs: &Arc<[Value]>, rhs: &Arc<[Value]>) -> DynResult<Ordering>
             (val_l, val_r) => {
                 let type_l = val_l.kind();
                 let type_r = val_r.kind();
-                let is_incomparable_type =
-                    [Kind::Json, Kind::IntArray, Kind::FloatArray].contains(&type_l);
+                let is_incomparable_type = [
+                    Kind::Json,
+                    Kind::IntArray,
+                    Kind::FloatArray,
+                    Kind::Float32Array,
+                ]
+                .contains(&type_l);
                 if type_l != type_r || is_incomparable_type {
                     let msg = format!(
                         "comparison not supported between instances of '{type_l:?}' and '{type_r:?}'",
@@ -819,6 +1106,16 @@ impl AnyExpression {
                 binary_expr_err(lhs, rhs, values, |lhs, rhs| match (lhs, rhs) {
                     (Value::FloatArray(lhs), Value::FloatArray(rhs)) => mat_mul_wrapper(&lhs, &rhs),
                     (Value::IntArray(lhs), Value::IntArray(rhs)) => mat_mul_wrapper(&lhs, &rhs),
+                    (Value::Float32Array(lhs), Value::Float32Array(rhs)) => {
+                        mat_mul_wrapper(&lhs, &rhs)
+                    }
+                    // mixed precision is computed in double precision
+                    (Value::Float32Array(lhs), Value::FloatArray(rhs)) => {
+                        mat_mul_wrapper(&lhs.mapv(f64::from), &rhs)
+                    }
+                    (Value::FloatArray(lhs), Value::Float32Array(rhs)) => {
+                        mat_mul_wrapper(&lhs, &rhs.mapv(f64::from))
+                    }
                     (lhs_val, rhs_val) => {
                         let lhs_type = lhs_val.kind();
                         let rhs_type = rhs_val.kind();
@@ -1302,6 +1599,45 @@ impl StringExpression {
                 Value::String(s) => s,
                 v => v.to_string().into(),
             }),
+            Self::EncodeBase64(e) => unary_expr(e, values, |v: Arc<[u8]>| {
+                ArcStr::from(BASE64_STANDARD.encode(v))
+            }),
+            Self::EncodeHex(e) => {
+                unary_expr(e, values, |v: Arc<[u8]>| ArcStr::from(hex::encode(v)))
+            }
+            Self::FromUtf8Lossy(e) => unary_expr(e, values, |v: Arc<[u8]>| {
+                ArcStr::from(String::from_utf8_lossy(&v))
+            }),
+            Self::Format(template, args) => args
+                .eval(values)
+                .into_iter()
+                .map(|args| template.render(&args?))
+                .collect(),
+        }
+    }
+}
+
+impl BytesExpression {
+    pub fn eval(&self, values: &[&[Value]]) -> Vec<DynResult<Arc<[u8]>>> {
+        match self {
+            Self::DecodeBase64(e) => unary_expr_err(e, values, &|v: ArcStr| {
+                BASE64_STANDARD
+                    .decode(v.trim())
+                    .map(Arc::from)
+                    .map_err(|e| {
+                        DynError::from(DataError::ParseError(format!(
+                            "cannot decode {v:?} as base64: {e}"
+                        )))
+                    })
+            }),
+            Self::DecodeHex(e) => unary_expr_err(e, values, &|v: ArcStr| {
+                hex::decode(v.trim()).map(Arc::from).map_err(|e| {
+                    DynError::from(DataError::ParseError(format!(
+                        "cannot decode {v:?} as hex: {e}"
+                    )))
+                })
+            }),
+            Self::ToUtf8(e) => unary_expr(e, values, |v: ArcStr| Arc::from(v.as_bytes())),
         }
     }
 }
@@ -1457,6 +1793,7 @@ impl Expression {
             Self::Float(_) => "float",
             Self::Pointer(_) => "pointer",
             Self::String(_) => "string",
+            Self::Bytes(_) => "bytes",
             Self::DateTimeNaive(_) => "DateTimeNaive",
             Self::DateTimeUtc(_) => "DateTimeUtc",
             Self::Duration(_) => "Duration",
@@ -1487,6 +1824,7 @@ impl EvalAs<Value> for Expression {
             Self::Float(_) => unary_expr(self, values, |v: f64| Value::from(v)),
             Self::Pointer(_) => unary_expr(self, values, |v: Key| Value::from(v)),
             Self::String(_) => unary_expr(self, values, |v: ArcStr| Value::from(v)),
+            Self::Bytes(_) => unary_expr(self, values, |v: Arc<[u8]>| Value::Bytes(v)),
             Self::DateTimeNaive(_) => unary_expr(self, values, |v: DateTimeNaive| Value::from(v)),
             Self::DateTimeUtc(_) => unary_expr(self, values, |v: DateTimeUtc| Value::from(v)),
             Self::Duration(_) => unary_expr(self, values, |v: Duration| Value::from(v)),
@@ -1544,6 +1882,19 @@ impl EvalAs<ArcStr> for Expression {
     }
 }
 
+impl EvalAs<Arc<[u8]>> for Expression {
+    fn eval_as(&self, values: &[&[Value]]) -> Vec<DynResult<Arc<[u8]>>> {
+        match self {
+            Self::Bytes(expr) => expr.eval(values),
+            Self::Any(_) => unary_expr_err(self, values, &|v: Value| v.as_bytes().cloned()),
+            _ => values
+                .iter()
+                .map(|_| Err(self.type_error("bytes")))
+                .collect(),
+        }
+    }
+}
+
 impl EvalAs<Key> for Expression {
     fn eval_as(&self, values: &[&[Value]]) -> Vec<DynResult<Key>> {
         match self {
@@ -1638,6 +1989,12 @@ impl From<StringExpression> for Expression {
     }
 }
 
+impl From<BytesExpression> for Expression {
+    fn from(expr: BytesExpression) -> Self {
+        Self::Bytes(expr)
+    }
+}
+
 impl From<DateTimeNaiveExpression> for Expression {
     fn from(expr: DateTimeNaiveExpression) -> Self {
         Self::DateTimeNaive(expr)
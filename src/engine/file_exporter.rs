@@ -0,0 +1,364 @@
+// Copyright © 2024 Pathway
+
+//! Exporting metrics and spans to local JSON Lines files, for deployments that can't reach an
+//! OTLP endpoint. The files can be collected and uploaded later. Each worker writes to its own
+//! files, which are rotated when they grow too large, and only the most recent ones are kept.
+
+use std::ffi::OsStr;
+use std::fmt::{self, Debug};
+use std::fs::{self, File, OpenOptions};
+use std::future::{ready, Future};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use opentelemetry::trace::Status;
+use opentelemetry::{KeyValue, Value as OtelValue};
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData, ResourceMetrics};
+use opentelemetry_sdk::metrics::exporter::PushMetricExporter;
+use opentelemetry_sdk::metrics::Temporality;
+use opentelemetry_sdk::trace::{SpanData, SpanExporter as SdkSpanExporter};
+use opentelemetry_sdk::Resource;
+use serde::Serialize;
+use serde_json::{json, Map, Value as JsonValue};
+
+const MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+const MAX_FILES: usize = 16;
+
+/// Appends lines to `{prefix}-{creation time in ms}.jsonl` files in a directory, starting a new
+/// file when the current one exceeds the size limit.
+struct RotatingWriter {
+    directory: PathBuf,
+    prefix: String,
+    max_file_size: u64,
+    max_files: usize,
+    current: Option<(BufWriter<File>, u64)>,
+}
+
+impl RotatingWriter {
+    fn new(directory: PathBuf, prefix: String, max_file_size: u64, max_files: usize) -> Self {
+        Self {
+            directory,
+            prefix,
+            max_file_size,
+            max_files,
+            current: None,
+        }
+    }
+
+    fn write_lines(&mut self, lines: &[String]) -> io::Result<()> {
+        for line in lines {
+            if self
+                .current
+                .as_ref()
+                .is_none_or(|(_, size)| *size >= self.max_file_size)
+            {
+                self.rotate()?;
+            }
+            let (writer, size) = self.current.as_mut().unwrap();
+            writeln!(writer, "{line}")?;
+            *size += line.len() as u64 + 1;
+        }
+        self.flush()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.current {
+            Some((writer, _)) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.flush()?;
+        fs::create_dir_all(&self.directory)?;
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = self
+            .directory
+            .join(format!("{}-{created_at}.jsonl", self.prefix));
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        self.current = Some((BufWriter::new(file), size));
+        self.remove_old_files()
+    }
+
+    fn remove_old_files(&self) -> io::Result<()> {
+        let file_prefix = format!("{}-", self.prefix);
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.directory)?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(OsStr::to_str)
+                    .is_some_and(|name| name.starts_with(&file_prefix) && name.ends_with(".jsonl"))
+            })
+            .collect();
+        // the names differ only by the creation time, so they are sorted from the oldest
+        paths.sort();
+        let excess = paths.len().saturating_sub(self.max_files);
+        for path in &paths[..excess] {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_lines(writer: &Mutex<RotatingWriter>, lines: &[String]) -> OTelSdkResult {
+    writer
+        .lock()
+        .unwrap()
+        .write_lines(lines)
+        .map_err(|e| OTelSdkError::InternalFailure(e.to_string()))
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    let nanos = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    u64::try_from(nanos).unwrap_or(u64::MAX)
+}
+
+fn value_to_json(value: &OtelValue) -> JsonValue {
+    match value {
+        OtelValue::Bool(value) => json!(value),
+        OtelValue::I64(value) => json!(value),
+        OtelValue::F64(value) => json!(value),
+        OtelValue::String(value) => json!(value.as_str()),
+        value => json!(value.to_string()),
+    }
+}
+
+fn attributes_to_json<'a>(attributes: impl Iterator<Item = &'a KeyValue>) -> JsonValue {
+    let attributes: Map<String, JsonValue> = attributes
+        .map(|attribute| (attribute.key.to_string(), value_to_json(&attribute.value)))
+        .collect();
+    JsonValue::Object(attributes)
+}
+
+fn resource_to_json(resource: &Resource) -> JsonValue {
+    let attributes: Map<String, JsonValue> = resource
+        .iter()
+        .map(|(key, value)| (key.to_string(), value_to_json(value)))
+        .collect();
+    JsonValue::Object(attributes)
+}
+
+/// Creates a line for every data point. The fields common to all the points of the metric are
+/// passed in `metric`.
+fn metric_data_to_json<T: Copy + Serialize>(
+    metric: &Map<String, JsonValue>,
+    data: &MetricData<T>,
+) -> Vec<JsonValue> {
+    let point = |time: SystemTime, kind: &str, fields: JsonValue| {
+        let mut line = metric.clone();
+        line.insert("time_unix_nano".to_string(), json!(unix_nanos(time)));
+        line.insert("kind".to_string(), json!(kind));
+        if let JsonValue::Object(fields) = fields {
+            line.extend(fields);
+        }
+        JsonValue::Object(line)
+    };
+    match data {
+        MetricData::Gauge(gauge) => gauge
+            .data_points()
+            .map(|data_point| {
+                point(
+                    gauge.time(),
+                    "gauge",
+                    json!({
+                        "attributes": attributes_to_json(data_point.attributes()),
+                        "value": data_point.value(),
+                    }),
+                )
+            })
+            .collect(),
+        MetricData::Sum(sum) => sum
+            .data_points()
+            .map(|data_point| {
+                point(
+                    sum.time(),
+                    "sum",
+                    json!({
+                        "attributes": attributes_to_json(data_point.attributes()),
+                        "value": data_point.value(),
+                        "monotonic": sum.is_monotonic(),
+                    }),
+                )
+            })
+            .collect(),
+        MetricData::Histogram(histogram) => histogram
+            .data_points()
+            .map(|data_point| {
+                point(
+                    histogram.time(),
+                    "histogram",
+                    json!({
+                        "attributes": attributes_to_json(data_point.attributes()),
+                        "count": data_point.count(),
+                        "sum": data_point.sum(),
+                        "min": data_point.min(),
+                        "max": data_point.max(),
+                        "bounds": data_point.bounds().collect::<Vec<_>>(),
+                        "bucket_counts": data_point.bucket_counts().collect::<Vec<_>>(),
+                    }),
+                )
+            })
+            .collect(),
+        MetricData::ExponentialHistogram(histogram) => histogram
+            .data_points()
+            .map(|data_point| {
+                point(
+                    histogram.time(),
+                    "exponential_histogram",
+                    json!({
+                        "attributes": attributes_to_json(data_point.attributes()),
+                        "count": data_point.count(),
+                        "sum": data_point.sum(),
+                    }),
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Encodes the collected metrics as JSON objects, one per data point.
+pub fn metrics_to_json(metrics: &ResourceMetrics) -> Vec<JsonValue> {
+    let resource = resource_to_json(metrics.resource());
+    let mut lines = Vec::new();
+    for scope_metrics in metrics.scope_metrics() {
+        for metric in scope_metrics.metrics() {
+            let mut fields = Map::new();
+            fields.insert("resource".to_string(), resource.clone());
+            fields.insert("scope".to_string(), json!(scope_metrics.scope().name()));
+            fields.insert("name".to_string(), json!(metric.name()));
+            fields.insert("unit".to_string(), json!(metric.unit()));
+            let data_points = match metric.data() {
+                AggregatedMetrics::F64(data) => metric_data_to_json(&fields, data),
+                AggregatedMetrics::U64(data) => metric_data_to_json(&fields, data),
+                AggregatedMetrics::I64(data) => metric_data_to_json(&fields, data),
+            };
+            lines.extend(data_points);
+        }
+    }
+    lines
+}
+
+/// Encodes a span as a JSON object.
+pub fn span_to_json(span: &SpanData, resource: &JsonValue) -> JsonValue {
+    let status = match &span.status {
+        Status::Unset => json!({"code": "unset"}),
+        Status::Ok => json!({"code": "ok"}),
+        Status::Error { description } => {
+            json!({"code": "error", "description": description.as_ref()})
+        }
+    };
+    json!({
+        "resource": resource,
+        "scope": span.instrumentation_scope.name(),
+        "name": span.name.as_ref(),
+        "trace_id": span.span_context.trace_id().to_string(),
+        "span_id": span.span_context.span_id().to_string(),
+        "parent_span_id": span.parent_span_id.to_string(),
+        "start_time_unix_nano": unix_nanos(span.start_time),
+        "end_time_unix_nano": unix_nanos(span.end_time),
+        "attributes": attributes_to_json(span.attributes.iter()),
+        "status": status,
+    })
+}
+
+/// Writes the metrics to `metrics-{name}-*.jsonl` files.
+pub struct MetricExporter {
+    writer: Mutex<RotatingWriter>,
+}
+
+impl MetricExporter {
+    pub fn new(directory: PathBuf, name: &str) -> Self {
+        Self {
+            writer: Mutex::new(RotatingWriter::new(
+                directory,
+                format!("metrics-{name}"),
+                MAX_FILE_SIZE,
+                MAX_FILES,
+            )),
+        }
+    }
+}
+
+impl PushMetricExporter for MetricExporter {
+    fn export(&self, metrics: &ResourceMetrics) -> impl Future<Output = OTelSdkResult> + Send {
+        let lines: Vec<String> = metrics_to_json(metrics)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        ready(write_lines(&self.writer, &lines))
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.writer
+            .lock()
+            .unwrap()
+            .flush()
+            .map_err(|e| OTelSdkError::InternalFailure(e.to_string()))
+    }
+
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+        self.force_flush()
+    }
+
+    fn temporality(&self) -> Temporality {
+        Temporality::Cumulative
+    }
+}
+
+/// Writes the spans to `spans-{name}-*.jsonl` files.
+pub struct SpanExporter {
+    writer: Mutex<RotatingWriter>,
+    resource: JsonValue,
+}
+
+impl SpanExporter {
+    pub fn new(directory: PathBuf, name: &str) -> Self {
+        Self {
+            writer: Mutex::new(RotatingWriter::new(
+                directory,
+                format!("spans-{name}"),
+                MAX_FILE_SIZE,
+                MAX_FILES,
+            )),
+            resource: JsonValue::Null,
+        }
+    }
+}
+
+impl Debug for SpanExporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpanExporter").finish_non_exhaustive()
+    }
+}
+
+impl SdkSpanExporter for SpanExporter {
+    fn export(&self, batch: Vec<SpanData>) -> impl Future<Output = OTelSdkResult> + Send {
+        let lines: Vec<String> = batch
+            .iter()
+            .map(|span| span_to_json(span, &self.resource).to_string())
+            .collect();
+        ready(write_lines(&self.writer, &lines))
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.writer
+            .get_mut()
+            .unwrap()
+            .flush()
+            .map_err(|e| OTelSdkError::InternalFailure(e.to_string()))
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.resource = resource_to_json(resource);
+    }
+}
@@ -21,6 +21,7 @@ use crate::engine::dataflow::monitoring::ProberStats;
 use crate::external_integration::ExternalIndex;
 use crate::persistence::UniqueName;
 use crate::python_api::extract_value;
+use crate::retry::RetryPolicy;
 
 use super::error::{DynResult, Trace};
 use super::external_index_wrappers::{ExternalIndexData, ExternalIndexQuery};
@@ -723,6 +724,23 @@ pub trait Graph {
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle>;
 
+    fn sample_table(
+        &self,
+        table_handle: TableHandle,
+        fraction: f64,
+        seed: u64,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle>;
+
+    fn reservoir_sample_table(
+        &self,
+        table_handle: TableHandle,
+        instance_column_path: ColumnPath,
+        size: usize,
+        seed: u64,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle>;
+
     fn forget(
         &self,
         table_handle: TableHandle,
@@ -865,6 +883,20 @@ pub trait Graph {
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle>;
 
+    /// Multiplies the operand of each row by the matrix of the row's group. Groups are matched
+    /// by the values of `group_path` and `matrix_group_path`.
+    #[allow(clippy::too_many_arguments)]
+    fn keyed_mat_mul(
+        &self,
+        table_handle: TableHandle,
+        group_path: ColumnPath,
+        operand_path: ColumnPath,
+        matrix_table_handle: TableHandle,
+        matrix_group_path: ColumnPath,
+        matrix_path: ColumnPath,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle>;
+
     fn use_external_index_as_of_now(
         &self,
         index_stream: ExternalIndexData,
@@ -923,6 +955,7 @@ pub trait Graph {
         max_backlog_size: Option<usize>,
     ) -> Result<TableHandle>;
 
+    #[allow(clippy::too_many_arguments)]
     fn output_table(
         &self,
         data_sink: Box<dyn Writer>,
@@ -931,6 +964,7 @@ pub trait Graph {
         column_paths: Vec<ColumnPath>,
         unique_name: Option<UniqueName>,
         sort_by_indices: Option<Vec<usize>>,
+        retry_policy: RetryPolicy,
     ) -> Result<()>;
 
     fn set_operator_properties(&self, operator_properties: OperatorProperties) -> Result<()>;
@@ -1216,6 +1250,35 @@ impl Graph for ScopedGraph {
         self.try_with(|g| g.remove_retractions_from_table(table_handle, table_properties))
     }
 
+    fn sample_table(
+        &self,
+        table_handle: TableHandle,
+        fraction: f64,
+        seed: u64,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.try_with(|g| g.sample_table(table_handle, fraction, seed, table_properties))
+    }
+
+    fn reservoir_sample_table(
+        &self,
+        table_handle: TableHandle,
+        instance_column_path: ColumnPath,
+        size: usize,
+        seed: u64,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.try_with(|g| {
+            g.reservoir_sample_table(
+                table_handle,
+                instance_column_path,
+                size,
+                seed,
+                table_properties,
+            )
+        })
+    }
+
     fn forget(
         &self,
         table_handle: TableHandle,
@@ -1484,6 +1547,29 @@ impl Graph for ScopedGraph {
         })
     }
 
+    fn keyed_mat_mul(
+        &self,
+        table_handle: TableHandle,
+        group_path: ColumnPath,
+        operand_path: ColumnPath,
+        matrix_table_handle: TableHandle,
+        matrix_group_path: ColumnPath,
+        matrix_path: ColumnPath,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.try_with(|g| {
+            g.keyed_mat_mul(
+                table_handle,
+                group_path,
+                operand_path,
+                matrix_table_handle,
+                matrix_group_path,
+                matrix_path,
+                table_properties,
+            )
+        })
+    }
+
     fn ix_table(
         &self,
         to_ix_handle: TableHandle,
@@ -1573,6 +1659,7 @@ impl Graph for ScopedGraph {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn output_table(
         &self,
         data_sink: Box<dyn Writer>,
@@ -1581,6 +1668,7 @@ impl Graph for ScopedGraph {
         column_paths: Vec<ColumnPath>,
         unique_name: Option<UniqueName>,
         sort_by_indices: Option<Vec<usize>>,
+        retry_policy: RetryPolicy,
     ) -> Result<()> {
         self.try_with(|g| {
             g.output_table(
@@ -1590,6 +1678,7 @@ impl Graph for ScopedGraph {
                 column_paths,
                 unique_name,
                 sort_by_indices,
+                retry_policy,
             )
         })
     }
@@ -0,0 +1,272 @@
+// Copyright © 2024 Pathway
+
+//! Liveness and readiness probes, served over HTTP at `/healthz` and `/readyz` when
+//! `PATHWAY_HEALTH_PORT` is set, so that orchestrators like Kubernetes can check the state of
+//! the computation without parsing the logs.
+//!
+//! The process is live as long as its helper threads haven't died. It is ready once all input
+//! connectors have connected to their sources and recovered their persisted state, and as long
+//! as the input frontier keeps advancing.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, Builder, JoinHandle};
+use std::time::{Duration, Instant};
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Response, Server, StatusCode};
+use log::{error, info};
+use once_cell::sync::Lazy;
+use tokio::sync::oneshot;
+
+use super::dataflow::monitoring::ProberStats;
+use super::{Error, Graph, Timestamp};
+
+struct Watermark {
+    time: Option<Timestamp>,
+    done: bool,
+    last_advanced_at: Instant,
+}
+
+struct HealthState {
+    connectors_registered: AtomicUsize,
+    connectors_connected: AtomicUsize,
+    connectors_recovered: AtomicUsize,
+    telemetry_failed: AtomicBool,
+    watermark: Mutex<Watermark>,
+}
+
+static HEALTH_STATE: Lazy<HealthState> = Lazy::new(|| HealthState {
+    connectors_registered: AtomicUsize::new(0),
+    connectors_connected: AtomicUsize::new(0),
+    connectors_recovered: AtomicUsize::new(0),
+    telemetry_failed: AtomicBool::new(false),
+    watermark: Mutex::new(Watermark {
+        time: None,
+        done: false,
+        last_advanced_at: Instant::now(),
+    }),
+});
+
+/// Clears the state left by the previous computation. Called once before the workers start.
+pub fn reset_health() {
+    HEALTH_STATE
+        .connectors_registered
+        .store(0, Ordering::SeqCst);
+    HEALTH_STATE.connectors_connected.store(0, Ordering::SeqCst);
+    HEALTH_STATE.connectors_recovered.store(0, Ordering::SeqCst);
+    HEALTH_STATE.telemetry_failed.store(false, Ordering::SeqCst);
+    *HEALTH_STATE.watermark.lock().unwrap() = Watermark {
+        time: None,
+        done: false,
+        last_advanced_at: Instant::now(),
+    };
+}
+
+pub fn connector_registered() {
+    HEALTH_STATE
+        .connectors_registered
+        .fetch_add(1, Ordering::SeqCst);
+}
+
+/// Marks that a connector has established the connection to its source.
+pub fn connector_connected() {
+    HEALTH_STATE
+        .connectors_connected
+        .fetch_add(1, Ordering::SeqCst);
+}
+
+/// Marks that a connector has finished rewinding its persisted state.
+pub fn connector_recovered() {
+    HEALTH_STATE
+        .connectors_recovered
+        .fetch_add(1, Ordering::SeqCst);
+}
+
+/// Held by the telemetry thread for its whole lifetime. If the thread dies by panicking, the
+/// process is reported as not live.
+pub struct TelemetryThreadGuard;
+
+impl Drop for TelemetryThreadGuard {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            HEALTH_STATE.telemetry_failed.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+fn update_watermark(stats: &ProberStats) {
+    let mut watermark = HEALTH_STATE.watermark.lock().unwrap();
+    if stats.input_stats.time != watermark.time {
+        watermark.time = stats.input_stats.time;
+        watermark.last_advanced_at = Instant::now();
+    }
+    watermark.done = stats.input_stats.done;
+}
+
+/// The state of the computation, as seen by the probes.
+#[derive(Clone, Debug)]
+pub struct HealthSnapshot {
+    pub connectors_registered: usize,
+    pub connectors_connected: usize,
+    pub connectors_recovered: usize,
+    pub telemetry_failed: bool,
+    pub inputs_done: bool,
+    pub since_watermark_advanced: Duration,
+}
+
+impl HealthSnapshot {
+    pub fn current() -> Self {
+        let watermark = HEALTH_STATE.watermark.lock().unwrap();
+        Self {
+            connectors_registered: HEALTH_STATE.connectors_registered.load(Ordering::SeqCst),
+            connectors_connected: HEALTH_STATE.connectors_connected.load(Ordering::SeqCst),
+            connectors_recovered: HEALTH_STATE.connectors_recovered.load(Ordering::SeqCst),
+            telemetry_failed: HEALTH_STATE.telemetry_failed.load(Ordering::SeqCst),
+            inputs_done: watermark.done,
+            since_watermark_advanced: watermark.last_advanced_at.elapsed(),
+        }
+    }
+
+    /// Returns the reasons why the process is not live, empty if it is.
+    pub fn liveness_problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.telemetry_failed {
+            problems.push("telemetry thread has died".to_string());
+        }
+        problems
+    }
+
+    /// Returns the reasons why the process is not ready, empty if it is. The input frontier
+    /// is considered stalled if it hasn't advanced for longer than `max_stall`.
+    pub fn readiness_problems(&self, max_stall: Duration) -> Vec<String> {
+        let mut problems = self.liveness_problems();
+        if self.connectors_connected < self.connectors_registered {
+            problems.push(format!(
+                "{} of {} connectors connected",
+                self.connectors_connected, self.connectors_registered
+            ));
+        }
+        if self.connectors_recovered < self.connectors_registered {
+            problems.push(format!(
+                "{} of {} connectors recovered",
+                self.connectors_recovered, self.connectors_registered
+            ));
+        }
+        if !self.inputs_done && self.since_watermark_advanced > max_stall {
+            problems.push(format!(
+                "input frontier hasn't advanced for {}s",
+                self.since_watermark_advanced.as_secs()
+            ));
+        }
+        problems
+    }
+}
+
+fn probe_response(problems: &[String]) -> Response<Body> {
+    if problems.is_empty() {
+        return Response::new(Body::from("ok\n"));
+    }
+    let mut response = Response::new(Body::from(format!("{}\n", problems.join("\n"))));
+    *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    response
+}
+
+/// Runs the HTTP server with the probes. Stops the server when dropped.
+pub struct Runner {
+    thread_handle: Option<JoinHandle<()>>,
+    terminate_sender: Option<oneshot::Sender<()>>,
+}
+
+impl Runner {
+    /// Starts the server on all interfaces, as probes are sent to the address of the pod.
+    fn run(port: u16, readiness_max_stall: Duration) -> Self {
+        let (terminate_sender, terminate_receiver) = oneshot::channel::<()>();
+        let thread_handle = Builder::new()
+            .name("pathway:health".to_string())
+            .spawn(move || {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_io()
+                    .build()
+                    .unwrap()
+                    .block_on(async {
+                        let addr = ([0, 0, 0, 0], port).into();
+                        let make_service = make_service_fn(move |_| async move {
+                            Ok::<_, Error>(service_fn(move |req| async move {
+                                let response = match (req.method(), req.uri().path()) {
+                                    (&Method::GET, "/healthz") => probe_response(
+                                        &HealthSnapshot::current().liveness_problems(),
+                                    ),
+                                    (&Method::GET, "/readyz") => probe_response(
+                                        &HealthSnapshot::current()
+                                            .readiness_problems(readiness_max_stall),
+                                    ),
+                                    _ => {
+                                        let mut response = Response::new(Body::empty());
+                                        *response.status_mut() = StatusCode::NOT_FOUND;
+                                        response
+                                    }
+                                };
+                                Ok::<_, Error>(response)
+                            }))
+                        });
+                        let server = match Server::try_bind(&addr) {
+                            Ok(server) => server.serve(make_service),
+                            Err(e) => {
+                                error!("failed to start the health probes on port {port}: {e}");
+                                return;
+                            }
+                        };
+                        let shutdown_signal = async move {
+                            terminate_receiver.await.ok();
+                        };
+                        info!("Health probes available at http://{addr}/healthz and /readyz");
+                        if let Err(e) = server.with_graceful_shutdown(shutdown_signal).await {
+                            error!("health probes server error: {e}");
+                        }
+                    });
+            })
+            .expect("health probes thread creation failed");
+        Self {
+            thread_handle: Some(thread_handle),
+            terminate_sender: Some(terminate_sender),
+        }
+    }
+}
+
+impl Drop for Runner {
+    fn drop(&mut self) {
+        // the server may have already stopped if it failed to bind
+        let _ = self.terminate_sender.take().unwrap().send(());
+        self.thread_handle
+            .take()
+            .unwrap()
+            .join()
+            .expect("health probes thread failed");
+    }
+}
+
+/// Starts the probes in the first worker of each process, if a port is configured.
+pub fn maybe_run_health_server(
+    health_port: Option<u16>,
+    readiness_max_stall: Duration,
+    graph: &dyn Graph,
+) -> Option<Runner> {
+    let port = health_port?;
+    if graph.worker_index() % graph.thread_count() != 0 {
+        return None;
+    }
+    let process_id = graph.worker_index() / graph.thread_count();
+    let runner = Runner::run(
+        port + u16::try_from(process_id).unwrap(),
+        readiness_max_stall,
+    );
+    graph
+        .attach_prober(
+            Box::new(|prober_stats| update_watermark(&prober_stats)),
+            false,
+            false,
+        )
+        .expect("failed to start health probes");
+    Some(runner)
+}
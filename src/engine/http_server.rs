@@ -1,9 +1,11 @@
 // Copyright © 2024 Pathway
 
+use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::thread::{Builder, JoinHandle};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use arc_swap::ArcSwapOption;
 use hyper::service::{make_service_fn, service_fn};
@@ -12,15 +14,31 @@ use log::{error, info};
 use prometheus_client::encoding::text::encode;
 use prometheus_client::metrics::gauge::Gauge;
 use prometheus_client::registry::Registry;
+use serde::Deserialize;
 use tokio::sync::oneshot::Sender;
 
+use crate::connectors::pausing::{connector_states, set_connector_paused};
+use crate::connectors::snapshot_export::{export_snapshot, ExportError, ExportedSnapshot};
+use crate::engine::constraints::violation_counts;
+use crate::engine::dataflow::memory::arrangements_memory;
 use crate::engine::dataflow::monitoring::ProberStats;
+use crate::engine::reload::{self, ReloadableSettings};
 
 use super::Error;
 use super::Graph;
+use super::Timestamp;
 
 const DEFAULT_MONITORING_HTTP_PORT: u16 = 20000;
 
+/// Builds the prefix of the metrics of a constraint, replacing the characters not allowed in
+/// the metric names.
+fn constraint_metric_name(check: &str, constraint: &str) -> String {
+    format!("constraint_{check}_{constraint}")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
 /// Retrieves metrics from prober stats in the `OpenMetrics` format
 /// See <https://github.com/OpenObservability/OpenMetrics>
 fn metrics_from_stats(stats: &Arc<ArcSwapOption<ProberStats>>) -> String {
@@ -106,13 +124,171 @@ fn metrics_from_stats(stats: &Arc<ArcSwapOption<ProberStats>>) -> String {
             );
         }
 
+        let mut arrangement_bytes: HashMap<usize, u64> = HashMap::new();
+        for arrangement in arrangements_memory() {
+            if let Some(operator_id) = arrangement.operator_id {
+                *arrangement_bytes.entry(operator_id).or_default() += arrangement.bytes;
+            }
+        }
+        for (operator_id, bytes) in arrangement_bytes {
+            let gauge: Gauge = Gauge::default();
+            gauge.set(i64::try_from(bytes).unwrap_or(i64::MAX));
+            registry.register(
+                format!("{operator_id}_arrangement_bytes").as_str(),
+                format!("Approximate size of the arrangements of operator {operator_id} in bytes")
+                    .as_str(),
+                gauge,
+            );
+        }
+
+        for (id, counts) in violation_counts() {
+            let name = constraint_metric_name(&id.check, &id.constraint);
+            let gauge: Gauge = Gauge::default();
+            gauge.set(i64::try_from(counts.current).unwrap_or(i64::MAX));
+            registry.register(
+                format!("{name}_violations").as_str(),
+                format!(
+                    "Number of rows violating constraint {} of check {}",
+                    id.constraint, id.check
+                )
+                .as_str(),
+                gauge,
+            );
+            let gauge: Gauge = Gauge::default();
+            gauge.set(i64::try_from(counts.total).unwrap_or(i64::MAX));
+            registry.register(
+                format!("{name}_violations_observed").as_str(),
+                format!(
+                    "Number of times a row started violating constraint {} of check {}",
+                    id.constraint, id.check
+                )
+                .as_str(),
+                gauge,
+            );
+        }
+
         encode(&mut metrics_text, &registry).unwrap();
     }
     metrics_text
 }
 
+/// Returns the settings in effect as JSON, or the reason why they couldn't be changed.
+fn settings_response(settings: Result<Arc<ReloadableSettings>, reload::Error>) -> Response<Body> {
+    match settings {
+        Ok(settings) => {
+            let mut response = Response::new(Body::from(
+                serde_json::to_string(&*settings).expect("settings should serialize"),
+            ));
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/json"),
+            );
+            response
+        }
+        Err(e) => {
+            let mut response = Response::new(Body::from(format!("{e}\n")));
+            *response.status_mut() = StatusCode::BAD_REQUEST;
+            response
+        }
+    }
+}
+
+/// Applies the settings sent as a JSON object.
+async fn update_settings(body: Body) -> Response<Body> {
+    match hyper::body::to_bytes(body).await {
+        Ok(body) => settings_response(reload::update_from_json(&body)),
+        Err(e) => {
+            let mut response = Response::new(Body::from(format!("{e}\n")));
+            *response.status_mut() = StatusCode::BAD_REQUEST;
+            response
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ExportRequest {
+    path: PathBuf,
+    #[serde(default = "default_export_format")]
+    format: String,
+    time: Option<u64>,
+    timeout_seconds: Option<u64>,
+}
+
+fn default_export_format() -> String {
+    "parquet".to_string()
+}
+
+impl ExportRequest {
+    fn export(self, name: &str) -> Result<ExportedSnapshot, ExportError> {
+        export_snapshot(
+            name,
+            &self.path,
+            self.format.parse()?,
+            self.time.map(Timestamp),
+            self.timeout_seconds.map(Duration::from_secs),
+        )
+    }
+}
+
+/// Exports a snapshot of the table as requested in the JSON body. The export may wait for a
+/// commit, so it runs outside of the server thread.
+async fn export_table(name: String, body: Body) -> Response<Body> {
+    let request = match hyper::body::to_bytes(body).await {
+        Ok(body) => serde_json::from_slice::<ExportRequest>(&body).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let (status, body) = match request {
+        Ok(request) => {
+            let result = tokio::task::spawn_blocking(move || request.export(&name))
+                .await
+                .expect("snapshot export should not panic");
+            match result {
+                Ok(snapshot) => (
+                    StatusCode::OK,
+                    serde_json::to_string(&snapshot).expect("snapshot summary should serialize"),
+                ),
+                Err(e @ ExportError::UnknownTable(_)) => (StatusCode::NOT_FOUND, format!("{e}\n")),
+                Err(e) => (StatusCode::BAD_REQUEST, format!("{e}\n")),
+            }
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, format!("{e}\n")),
+    };
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response
+}
+
+fn json_response(body: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+/// Pauses or resumes the connector, returning its state as JSON.
+fn set_paused(name: &str, paused: bool) -> Response<Body> {
+    match set_connector_paused(name, paused) {
+        Ok(state) => {
+            json_response(serde_json::to_string(&state).expect("connector state should serialize"))
+        }
+        Err(e) => {
+            let mut response = Response::new(Body::from(format!("{e}\n")));
+            *response.status_mut() = StatusCode::NOT_FOUND;
+            response
+        }
+    }
+}
+
 /// Starts a lightweight http server allowing monitoring.
 /// Available at: http://localhost:PORT/status
+/// The reloadable settings can be read and updated at http://localhost:PORT/settings
+/// The snapshots of the tables registered for exports are written with a POST request to
+/// http://localhost:PORT/tables/NAME/export
+/// The running connectors are listed at http://localhost:PORT/connectors and are paused and
+/// resumed with POST requests to http://localhost:PORT/connectors/NAME/pause and
+/// http://localhost:PORT/connectors/NAME/resume
 /// where PORT is `PATHWAY_MONITORING_HTTP_PORT + process_id`
 /// It uses tokio and hyper. The status is passed using arcswap to avoid mutexes.
 pub fn start_http_server_thread(
@@ -148,7 +324,9 @@ pub fn start_http_server_thread(
                                     let stats = stats.clone();
 
                                     let metrics_text = metrics_from_stats(&stats);
-                                    match (req.method(), req.uri().path()) {
+                                    let method = req.method().clone();
+                                    let path = req.uri().path().to_string();
+                                    match (&method, path.as_str()) {
                                         (&Method::GET, "/status") => {
                                             *response.body_mut() = Body::from(metrics_text);
                                             response.headers_mut().insert(
@@ -167,7 +345,48 @@ pub fn start_http_server_thread(
                                                 ),
                                             );
                                         }
-
+                                        (&Method::GET, "/settings") => {
+                                            response = settings_response(Ok(reload::current()));
+                                        }
+                                        (&Method::POST, "/settings") => {
+                                            response = update_settings(req.into_body()).await;
+                                        }
+                                        (&Method::POST, "/settings/reload") => {
+                                            response = settings_response(
+                                                reload::reload_from_config_file()
+                                                    .map(|()| reload::current()),
+                                            );
+                                        }
+                                        (&Method::POST, path)
+                                            if path.starts_with("/tables/")
+                                                && path.ends_with("/export") =>
+                                        {
+                                            let name = path["/tables/".len()..]
+                                                .strip_suffix("/export")
+                                                .unwrap_or_default()
+                                                .to_string();
+                                            response = export_table(name, req.into_body()).await;
+                                        }
+                                        (&Method::GET, "/connectors") => {
+                                            response = json_response(
+                                                serde_json::to_string(&connector_states())
+                                                    .expect("connector states should serialize"),
+                                            );
+                                        }
+                                        (&Method::POST, path)
+                                            if path.starts_with("/connectors/")
+                                                && (path.ends_with("/pause")
+                                                    || path.ends_with("/resume")) =>
+                                        {
+                                            let path = &path["/connectors/".len()..];
+                                            response = match path.strip_suffix("/pause") {
+                                                Some(name) => set_paused(name, true),
+                                                None => set_paused(
+                                                    path.strip_suffix("/resume").unwrap_or_default(),
+                                                    false,
+                                                ),
+                                            };
+                                        }
                                         _ => {
                                             *response.status_mut() = StatusCode::NOT_FOUND;
                                         }
@@ -0,0 +1,129 @@
+// Copyright © 2024 Pathway
+
+//! Sharing one allocation between the equal strings of a column. The strings of
+//! [`Value::String`] are reference counted, so once the equal values read by a connector point
+//! to the same string, the copies kept in arrangements and passed between operators don't
+//! allocate either. It pays off for low-cardinality columns like categories or country codes,
+//! so a column stops being interned once it has more distinct strings than the limit set by
+//! `PATHWAY_STRING_INTERNING_MAX_DISTINCT`.
+//!
+//! The rows read by connectors are interned, and so are the values kept by the arrangements of
+//! tables and join sides, which also covers the strings computed by operators.
+
+use std::collections::HashSet;
+use std::slice;
+
+use arcstr::ArcStr;
+use log::warn;
+use once_cell::sync::Lazy;
+
+use super::Value;
+use crate::env::parse_env_var;
+
+const DEFAULT_MAX_DISTINCT: usize = 4096;
+
+static MAX_DISTINCT: Lazy<usize> = Lazy::new(|| {
+    parse_env_var("PATHWAY_STRING_INTERNING_MAX_DISTINCT")
+        .unwrap_or_else(|e| {
+            warn!("Using the default string interning limit: {e}");
+            None
+        })
+        .unwrap_or(DEFAULT_MAX_DISTINCT)
+});
+
+#[derive(Debug, Default)]
+struct ColumnPool {
+    strings: HashSet<ArcStr>,
+    is_disabled: bool,
+}
+
+impl ColumnPool {
+    fn intern(&mut self, string: &mut ArcStr, max_distinct: usize) {
+        if self.is_disabled {
+            return;
+        }
+        if let Some(interned) = self.strings.get(string.as_str()) {
+            *string = interned.clone();
+        } else if self.strings.len() < max_distinct {
+            self.strings.insert(string.clone());
+        } else {
+            self.is_disabled = true;
+            self.strings = HashSet::new();
+        }
+    }
+}
+
+/// Interns the strings of the rows of a single table, separately in each column.
+#[derive(Debug)]
+pub struct StringInterner {
+    max_distinct: usize,
+    columns: Vec<ColumnPool>,
+}
+
+impl StringInterner {
+    /// Creates an interner keeping at most `max_distinct` strings per column. Zero disables
+    /// interning.
+    pub fn new(max_distinct: usize) -> Self {
+        Self {
+            max_distinct,
+            columns: Vec::new(),
+        }
+    }
+
+    /// Creates an interner with the limit configured for the run.
+    pub fn from_env() -> Self {
+        Self::new(*MAX_DISTINCT)
+    }
+
+    /// Replaces the strings in `values` with the equal strings seen before in the same columns.
+    pub fn intern(&mut self, values: &mut [Value]) {
+        if self.max_distinct == 0 {
+            return;
+        }
+        if self.columns.len() < values.len() {
+            self.columns.resize_with(values.len(), ColumnPool::default);
+        }
+        for (pool, value) in self.columns.iter_mut().zip(values) {
+            if let Value::String(string) = value {
+                pool.intern(string, self.max_distinct);
+            }
+        }
+    }
+
+    /// Like [`StringInterner::intern`], but for a whole row. The columns of a tuple are interned
+    /// separately and any other value is treated as a single column.
+    pub fn intern_value(&mut self, value: &mut Value) {
+        match value {
+            Value::Tuple(values) if self.is_enabled() => {
+                if !values.iter().any(|value| matches!(value, Value::String(_))) {
+                    return;
+                }
+                let mut interned = values.to_vec();
+                self.intern(&mut interned);
+                let is_changed = values.iter().zip(&interned).any(|pair| match pair {
+                    (Value::String(old), Value::String(new)) => !ArcStr::ptr_eq(old, new),
+                    _ => false,
+                });
+                if is_changed {
+                    *values = interned.into();
+                }
+            }
+            Value::String(_) => self.intern(slice::from_mut(value)),
+            _ => {}
+        }
+    }
+
+    /// Whether any strings are interned at all.
+    pub fn is_enabled(&self) -> bool {
+        self.max_distinct > 0
+    }
+
+    /// Whether the strings of the column are still interned.
+    pub fn is_interning(&self, column: usize) -> bool {
+        self.max_distinct > 0
+            && self
+                .columns
+                .get(column)
+                .is_none_or(|pool| !pool.is_disabled)
+    }
+}
@@ -123,6 +123,34 @@ impl License {
         }
     }
 
+    /// Describes the license without contacting the license server. The entitlements of a
+    /// license key are known only to the license server, so they are not listed.
+    pub fn info(&self) -> LicenseInfo {
+        match self {
+            License::NoLicenseKey => LicenseInfo {
+                is_offline: true,
+                policy: None,
+                entitlements: Some(Vec::new()),
+                expiration_date: None,
+                file_expiration_date: None,
+            },
+            License::OfflineLicense(license) => LicenseInfo {
+                is_offline: true,
+                policy: Some(license.policy.clone()),
+                entitlements: Some(license.entitlements.clone()),
+                expiration_date: license.expiration_date,
+                file_expiration_date: license.file_expiration_date,
+            },
+            License::LicenseKey(_) => LicenseInfo {
+                is_offline: false,
+                policy: None,
+                entitlements: None,
+                expiration_date: None,
+                file_expiration_date: None,
+            },
+        }
+    }
+
     pub fn telemetry_required(&self) -> bool {
         match self {
             License::NoLicenseKey => false,
@@ -275,6 +303,21 @@ pub struct LicenseDetails {
     entitlements: Vec<String>,
     policy: String,
     expiration_date: Option<DateTime<Utc>>,
+    file_expiration_date: Option<DateTime<Utc>>,
+}
+
+/// What a license grants, as returned by [`License::info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct LicenseInfo {
+    /// Whether the license is validated without the license server.
+    pub is_offline: bool,
+    pub policy: Option<String>,
+    /// `None` if only the license server knows the entitlements.
+    pub entitlements: Option<Vec<String>>,
+    pub expiration_date: Option<DateTime<Utc>>,
+    /// When the license file has to be replaced with a newer one.
+    pub file_expiration_date: Option<DateTime<Utc>>,
 }
 
 fn deserialize_signed_license(public_key: &str, license: &str) -> DynResult<serde_json::Value> {
@@ -360,13 +403,13 @@ fn read_license_content(license: String) -> Result<LicenseDetails, Error> {
     };
 
     let expiration_date = parse_datetime(&lic["data"]["attributes"]["expiry"])?;
-    let license_file_ttl = parse_datetime(&lic["meta"]["expiry"])?;
+    let file_expiration_date = parse_datetime(&lic["meta"]["expiry"])?;
 
     let is_expired = |date: Option<DateTime<Utc>>| date.is_some_and(|d| d < Utc::now());
 
     if is_expired(expiration_date) {
         warn!("License has expired. Please renew to continue using the service.");
-    } else if is_expired(license_file_ttl) {
+    } else if is_expired(file_expiration_date) {
         warn!("License file's time-to-live has been exceeded. Please update the license file.");
     }
 
@@ -375,6 +418,7 @@ fn read_license_content(license: String) -> Result<LicenseDetails, Error> {
         entitlements,
         policy,
         expiration_date,
+        file_expiration_date,
     };
 
     debug!(
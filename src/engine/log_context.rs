@@ -0,0 +1,29 @@
+// Copyright © 2024 Pathway
+
+//! Context attached to log records, so that structured logs can tell which worker and which
+//! connector a message comes from. The context is kept per thread. Connector threads inherit
+//! the context of the worker that started them.
+
+use std::cell::RefCell;
+
+#[derive(Clone, Debug, Default)]
+pub struct LogContext {
+    pub worker_id: Option<usize>,
+    pub connector_name: Option<String>,
+}
+
+thread_local! {
+    static LOG_CONTEXT: RefCell<LogContext> = RefCell::new(LogContext::default());
+}
+
+pub fn current() -> LogContext {
+    LOG_CONTEXT.with(|context| context.borrow().clone())
+}
+
+pub fn set(context: LogContext) {
+    LOG_CONTEXT.with(|current| *current.borrow_mut() = context);
+}
+
+pub fn set_worker_id(worker_id: usize) {
+    LOG_CONTEXT.with(|context| context.borrow_mut().worker_id = Some(worker_id));
+}
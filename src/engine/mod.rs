@@ -12,6 +12,8 @@ pub mod report_error;
 pub mod value;
 pub use self::value::{Key, KeyImpl, ShardPolicy, Type, Value};
 
+pub mod interning;
+
 pub mod reduce;
 pub use reduce::Reducer;
 
@@ -26,16 +28,23 @@ pub use graph::{
 pub mod http_server;
 pub use http_server::maybe_run_http_server_thread;
 
+pub mod health;
+pub use health::maybe_run_health_server;
+
 pub mod dataflow;
 pub use dataflow::{run_with_new_dataflow_graph, WakeupReceiver};
 
 pub mod expression;
 pub use expression::{
-    AnyExpression, BoolExpression, DateTimeNaiveExpression, DateTimeUtcExpression,
-    DurationExpression, Expression, Expressions, FloatExpression, IntExpression, PointerExpression,
-    StringExpression,
+    AnyExpression, BoolExpression, BytesExpression, DateTimeNaiveExpression, DateTimeUtcExpression,
+    DurationExpression, Expression, Expressions, FloatExpression, FormatTemplate, IntExpression,
+    PointerExpression, StringExpression,
 };
 
+pub mod constraints;
+
+pub mod sql;
+
 pub mod progress_reporter;
 pub mod time;
 pub use time::{DateTimeNaive, DateTimeUtc, Duration};
@@ -46,8 +55,32 @@ pub use frontier::TotalFrontier;
 pub mod telemetry;
 pub use telemetry::Config;
 
+pub mod prometheus;
+
+pub mod file_exporter;
+
+pub mod log_context;
+
+pub mod spans;
+
+pub mod reload;
+
+pub mod stateless_pool;
+
 pub mod external_index_wrappers;
 
+pub mod native_udf;
+pub use native_udf::{NativeUdf, NativeUdfPlugin};
+
+pub mod udf_cache;
+pub use udf_cache::{UdfCache, UdfCacheConfig};
+
+pub mod udf_error_policy;
+pub use udf_error_policy::{UdfErrorAction, UdfErrorPolicy};
+
+pub mod wasm_udf;
+pub use wasm_udf::{WasmUdf, WasmUdfLimits, WasmUdfModule};
+
 pub mod timestamp;
 pub use timestamp::Timestamp;
 
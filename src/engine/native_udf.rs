@@ -0,0 +1,322 @@
+// Copyright © 2024 Pathway
+
+//! Loading of user-defined functions compiled into native dynamic libraries.
+//!
+//! A plugin is a `cdylib` exporting a function named [`NATIVE_UDF_ENTRY_POINT`] of type
+//! [`FfiPluginEntryPoint`]. The function returns a static [`FfiPluginDescriptor`] that lists
+//! the functions provided by the plugin. Values cross the library boundary as [`FfiValue`]s:
+//! arguments are borrowed from the engine for the duration of the call, while results are
+//! owned by the plugin and released with [`FfiPluginDescriptor::free_value`] once copied.
+
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::slice;
+use std::sync::{Arc, Mutex};
+
+use arcstr::ArcStr;
+use libloading::{Library, Symbol};
+use once_cell::sync::Lazy;
+
+use super::error::{DynError, DynResult};
+use super::Value;
+
+/// Version of the plugin ABI implemented by the engine.
+pub const NATIVE_UDF_ABI_VERSION: u32 = 1;
+
+/// Name of the symbol each plugin has to export.
+pub const NATIVE_UDF_ENTRY_POINT: &str = "pathway_udf_plugin";
+
+/// Kinds of [`FfiValue`]. Plain integers are used so that an invalid tag coming from a plugin
+/// is reported as an error rather than being undefined behavior.
+pub mod ffi_value_tag {
+    pub const NONE: u8 = 0;
+    pub const BOOL: u8 = 1;
+    pub const INT: u8 = 2;
+    pub const FLOAT: u8 = 3;
+    pub const STRING: u8 = 4;
+    pub const BYTES: u8 = 5;
+}
+
+/// A value passed between the engine and a plugin.
+///
+/// `int` holds integers and booleans (as `0` or `1`), `float` holds floats, and `data` with
+/// `len` point to UTF-8 text or raw bytes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FfiValue {
+    pub tag: u8,
+    pub int: i64,
+    pub float: f64,
+    pub data: *const u8,
+    pub len: usize,
+}
+
+impl FfiValue {
+    pub const NONE: Self = Self {
+        tag: ffi_value_tag::NONE,
+        int: 0,
+        float: 0.0,
+        data: ptr::null(),
+        len: 0,
+    };
+
+    fn borrowed(value: &Value) -> DynResult<Self> {
+        let result = match value {
+            Value::None => Self::NONE,
+            Value::Bool(b) => Self {
+                tag: ffi_value_tag::BOOL,
+                int: i64::from(*b),
+                ..Self::NONE
+            },
+            Value::Int(i) => Self {
+                tag: ffi_value_tag::INT,
+                int: *i,
+                ..Self::NONE
+            },
+            Value::Float(f) => Self {
+                tag: ffi_value_tag::FLOAT,
+                float: **f,
+                ..Self::NONE
+            },
+            Value::String(s) => Self {
+                tag: ffi_value_tag::STRING,
+                data: s.as_ptr(),
+                len: s.len(),
+                ..Self::NONE
+            },
+            Value::Bytes(b) => Self {
+                tag: ffi_value_tag::BYTES,
+                data: b.as_ptr(),
+                len: b.len(),
+                ..Self::NONE
+            },
+            other => return Err(NativeUdfError::UnsupportedArgument(other.clone()).into()),
+        };
+        Ok(result)
+    }
+
+    /// # Safety
+    ///
+    /// For strings and bytes, `data` has to point to `len` readable bytes.
+    unsafe fn data(&self) -> &[u8] {
+        if self.len == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(self.data, self.len)
+        }
+    }
+
+    /// # Safety
+    ///
+    /// See [`FfiValue::data`].
+    unsafe fn to_value(self) -> DynResult<Value> {
+        let result = match self.tag {
+            ffi_value_tag::NONE => Value::None,
+            ffi_value_tag::BOOL => Value::Bool(self.int != 0),
+            ffi_value_tag::INT => Value::Int(self.int),
+            ffi_value_tag::FLOAT => Value::from(self.float),
+            ffi_value_tag::STRING => {
+                let text = std::str::from_utf8(self.data())?;
+                Value::String(ArcStr::from(text))
+            }
+            ffi_value_tag::BYTES => Value::Bytes(self.data().into()),
+            tag => return Err(NativeUdfError::InvalidTag(tag).into()),
+        };
+        Ok(result)
+    }
+}
+
+/// Status returned by a plugin function on success. Any other status means that the call
+/// failed and the result holds a string with the error message.
+pub const FFI_STATUS_OK: i32 = 0;
+
+pub type FfiUdfFn =
+    unsafe extern "C" fn(args: *const FfiValue, n_args: usize, result: *mut FfiValue) -> i32;
+
+#[repr(C)]
+pub struct FfiUdfDescriptor {
+    /// Null-terminated name under which the function is registered.
+    pub name: *const c_char,
+    pub call: FfiUdfFn,
+}
+
+#[repr(C)]
+pub struct FfiPluginDescriptor {
+    pub abi_version: u32,
+    pub functions: *const FfiUdfDescriptor,
+    pub n_functions: usize,
+    /// Releases the memory owned by a result produced by one of the functions.
+    pub free_value: unsafe extern "C" fn(value: *mut FfiValue),
+}
+
+pub type FfiPluginEntryPoint = unsafe extern "C" fn() -> *const FfiPluginDescriptor;
+
+#[derive(Debug, thiserror::Error)]
+pub enum NativeUdfError {
+    #[error("failed to load native UDF plugin {path:?}: {source}")]
+    Load {
+        path: PathBuf,
+        #[source]
+        source: libloading::Error,
+    },
+
+    #[error("native UDF plugin {0:?} returned no descriptor")]
+    MissingDescriptor(PathBuf),
+
+    #[error("native UDF plugin {path:?} uses ABI version {actual}, expected {expected}")]
+    AbiVersionMismatch {
+        path: PathBuf,
+        actual: u32,
+        expected: u32,
+    },
+
+    #[error("function {name:?} not found in native UDF plugin {path:?}")]
+    FunctionNotFound { path: PathBuf, name: String },
+
+    #[error("value {0:?} can't be passed to a native UDF")]
+    UnsupportedArgument(Value),
+
+    #[error("native UDF returned a value with invalid tag {0}")]
+    InvalidTag(u8),
+
+    #[error("native UDF failed: {0}")]
+    CallFailed(String),
+}
+
+/// A loaded plugin library.
+#[derive(Debug)]
+pub struct NativeUdfPlugin {
+    path: PathBuf,
+    descriptor: *const FfiPluginDescriptor,
+    _library: Library,
+}
+
+// The descriptor is immutable static data of the library, kept alive by `_library`.
+unsafe impl Send for NativeUdfPlugin {}
+unsafe impl Sync for NativeUdfPlugin {}
+
+/// Plugins loaded so far, by path. They are never unloaded: the code of a library can still be
+/// referenced after the last [`NativeUdf`] is dropped, e.g. by the thread-local destructors it
+/// registered, so unloading it is not safe. Loading the same path again reuses the library.
+static LOADED_PLUGINS: Lazy<Mutex<HashMap<PathBuf, Arc<NativeUdfPlugin>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+impl NativeUdfPlugin {
+    /// Loads the plugin at `path`, reusing an already loaded instance if there is one.
+    pub fn load(path: impl AsRef<Path>) -> Result<Arc<Self>, NativeUdfError> {
+        let path = path.as_ref().to_path_buf();
+        let mut loaded_plugins = LOADED_PLUGINS.lock().unwrap();
+        if let Some(plugin) = loaded_plugins.get(&path) {
+            return Ok(plugin.clone());
+        }
+        let plugin = Arc::new(Self::open(path.clone())?);
+        loaded_plugins.insert(path, plugin.clone());
+        Ok(plugin)
+    }
+
+    fn open(path: PathBuf) -> Result<Self, NativeUdfError> {
+        let load_error = |source| NativeUdfError::Load {
+            path: path.clone(),
+            source,
+        };
+        // SAFETY: loading a library runs its initialization routines; plugins are trusted code
+        // provided explicitly by the user.
+        let library = unsafe { Library::new(&path) }.map_err(load_error)?;
+        let descriptor = unsafe {
+            let entry_point: Symbol<FfiPluginEntryPoint> = library
+                .get(NATIVE_UDF_ENTRY_POINT.as_bytes())
+                .map_err(load_error)?;
+            entry_point()
+        };
+        if descriptor.is_null() {
+            return Err(NativeUdfError::MissingDescriptor(path));
+        }
+        let abi_version = unsafe { (*descriptor).abi_version };
+        if abi_version != NATIVE_UDF_ABI_VERSION {
+            return Err(NativeUdfError::AbiVersionMismatch {
+                path,
+                actual: abi_version,
+                expected: NATIVE_UDF_ABI_VERSION,
+            });
+        }
+        Ok(Self {
+            path,
+            descriptor,
+            _library: library,
+        })
+    }
+
+    fn descriptor(&self) -> &FfiPluginDescriptor {
+        unsafe { &*self.descriptor }
+    }
+
+    fn functions(&self) -> &[FfiUdfDescriptor] {
+        let descriptor = self.descriptor();
+        if descriptor.n_functions == 0 {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(descriptor.functions, descriptor.n_functions) }
+        }
+    }
+
+    pub fn function_names(&self) -> Vec<String> {
+        self.functions()
+            .iter()
+            .map(|function| {
+                unsafe { CStr::from_ptr(function.name) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    pub fn function(self: &Arc<Self>, name: &str) -> Result<NativeUdf, NativeUdfError> {
+        let call = self
+            .functions()
+            .iter()
+            .find(|function| unsafe { CStr::from_ptr(function.name) }.to_bytes() == name.as_bytes())
+            .map(|function| function.call)
+            .ok_or_else(|| NativeUdfError::FunctionNotFound {
+                path: self.path.clone(),
+                name: name.to_string(),
+            })?;
+        Ok(NativeUdf {
+            plugin: self.clone(),
+            call,
+        })
+    }
+}
+
+/// A single function of a [`NativeUdfPlugin`].
+#[derive(Debug, Clone)]
+pub struct NativeUdf {
+    plugin: Arc<NativeUdfPlugin>,
+    call: FfiUdfFn,
+}
+
+impl NativeUdf {
+    pub fn call(&self, args: &[Value]) -> DynResult<Value> {
+        let args: Vec<FfiValue> = args
+            .iter()
+            .map(FfiValue::borrowed)
+            .collect::<DynResult<_>>()?;
+        let mut result = FfiValue::NONE;
+        // SAFETY: arguments borrow from `args` which outlive the call, the result is released
+        // by the plugin after being copied.
+        unsafe {
+            let status = (self.call)(args.as_ptr(), args.len(), &raw mut result);
+            let value = result.to_value();
+            (self.plugin.descriptor().free_value)(&raw mut result);
+            if status == FFI_STATUS_OK {
+                return value;
+            }
+            let message = match value {
+                Ok(Value::String(message)) => message.to_string(),
+                _ => format!("status {status}"),
+            };
+            Err(DynError::from(NativeUdfError::CallFailed(message)))
+        }
+    }
+}
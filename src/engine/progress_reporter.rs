@@ -6,6 +6,7 @@ use std::thread::{self, JoinHandle};
 use std::time::{Duration, SystemTime};
 
 use arc_swap::ArcSwapOption;
+use once_cell::sync::Lazy;
 use pyo3::{PyObject, Python};
 
 use crate::engine::dataflow::monitoring::ProberStats;
@@ -15,6 +16,14 @@ use super::Graph;
 
 const PROGRESS_REPORTING_PERIOD: Duration = Duration::from_millis(200);
 
+// the most recent stats of the running computation, available to Python on demand
+static LATEST_STATS: Lazy<ArcSwapOption<ProberStats>> = Lazy::new(|| ArcSwapOption::from(None));
+
+/// Returns the most recent stats of the computation in this process, if there are any.
+pub fn latest_stats() -> Option<Arc<ProberStats>> {
+    LATEST_STATS.load_full()
+}
+
 pub struct Runner {
     should_finish: Arc<AtomicBool>,
     reporting_thread_handle: Option<JoinHandle<()>>,
@@ -85,26 +94,34 @@ pub fn maybe_run_reporter(
     graph: &dyn Graph,
     stats_monitor: Option<PyObject>,
 ) -> Option<Runner> {
-    if *monitoring_level != MonitoringLevel::None && stats_monitor.is_some() {
-        let stats_shared = Arc::new(ArcSwapOption::from(None));
-        let progress_reporter_runner = Runner::run(
+    // stats of the first worker are kept for `latest_stats`, even without the dashboard
+    if graph.worker_index() != 0 {
+        return None;
+    }
+    LATEST_STATS.store(None);
+    let stats_shared = Arc::new(ArcSwapOption::from(None));
+    let progress_reporter_runner = match stats_monitor {
+        Some(stats_monitor) if *monitoring_level != MonitoringLevel::None => Some(Runner::run(
             PROGRESS_REPORTING_PERIOD,
             &stats_shared,
-            stats_monitor.unwrap(),
-        );
-
-        graph
-            .attach_prober(
-                Box::new(move |prober_stats| stats_shared.store(Some(Arc::new(prober_stats)))),
-                *monitoring_level == MonitoringLevel::All,
-                true,
-            )
-            .expect("Failed to start progress reporter");
-
-        Some(progress_reporter_runner)
-    } else {
-        None
-    }
+            stats_monitor,
+        )),
+        _ => None,
+    };
+
+    graph
+        .attach_prober(
+            Box::new(move |prober_stats| {
+                let prober_stats = Arc::new(prober_stats);
+                LATEST_STATS.store(Some(prober_stats.clone()));
+                stats_shared.store(Some(prober_stats));
+            }),
+            *monitoring_level == MonitoringLevel::All,
+            true,
+        )
+        .expect("Failed to start progress reporter");
+
+    progress_reporter_runner
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
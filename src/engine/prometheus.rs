@@ -0,0 +1,293 @@
+// Copyright © 2024 Pathway
+
+//! Exposing the metrics registered with OpenTelemetry in the Prometheus text format, for setups
+//! where metrics are scraped by Prometheus and there is no OTLP collector. The instruments are
+//! read on every scrape, so the endpoint always returns their current values.
+
+use std::fmt::{Display, Write};
+use std::iter::once;
+use std::sync::{Arc, Weak};
+use std::thread::{Builder, JoinHandle};
+use std::time::Duration;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Response, Server, StatusCode};
+use log::{error, info};
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData, ResourceMetrics};
+use opentelemetry_sdk::metrics::reader::MetricReader;
+use opentelemetry_sdk::metrics::{InstrumentKind, ManualReader, Pipeline, Temporality};
+use tokio::sync::oneshot;
+
+use super::Error;
+
+const TEXT_FORMAT_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// A pull-based metric reader, shared between the meter provider and the HTTP server.
+#[derive(Debug, Clone, Default)]
+pub struct Reader(Arc<ManualReader>);
+
+impl Reader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the current values of all the instruments and encodes them in the text format.
+    pub fn encode(&self) -> Result<String, OTelSdkError> {
+        let mut metrics = ResourceMetrics::default();
+        self.0.collect(&mut metrics)?;
+        Ok(encode_metrics(&metrics))
+    }
+}
+
+impl MetricReader for Reader {
+    fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+        self.0.register_pipeline(pipeline);
+    }
+
+    fn collect(&self, rm: &mut ResourceMetrics) -> OTelSdkResult {
+        self.0.collect(rm)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.0.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.0.shutdown_with_timeout(timeout)
+    }
+
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.0.temporality(kind)
+    }
+}
+
+/// Replaces the characters not allowed in Prometheus metric and label names with underscores.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .enumerate()
+        .map(|(index, c)| {
+            if c.is_ascii_alphabetic() || c == '_' || c == ':' || (index > 0 && c.is_ascii_digit())
+            {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn unit_suffix(unit: &str) -> Option<&'static str> {
+    match unit {
+        "ms" => Some("milliseconds"),
+        "s" => Some("seconds"),
+        "byte" | "By" => Some("bytes"),
+        _ => None,
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_labels<'a>(
+    attributes: impl Iterator<Item = &'a KeyValue>,
+    extra: Option<(&str, &str)>,
+) -> String {
+    let labels: Vec<String> = attributes
+        .map(|attribute| {
+            format!(
+                "{}=\"{}\"",
+                sanitize_name(attribute.key.as_str()),
+                escape_label_value(&attribute.value.to_string())
+            )
+        })
+        .chain(extra.map(|(name, value)| format!("{name}=\"{value}\"")))
+        .collect();
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels.join(","))
+    }
+}
+
+fn write_header(out: &mut String, name: &str, description: &str, kind: &str) {
+    if !description.is_empty() {
+        let description = description.replace('\\', "\\\\").replace('\n', "\\n");
+        writeln!(out, "# HELP {name} {description}").unwrap();
+    }
+    writeln!(out, "# TYPE {name} {kind}").unwrap();
+}
+
+fn write_metric_data<T: Copy + Display>(
+    out: &mut String,
+    name: &str,
+    description: &str,
+    data: &MetricData<T>,
+) {
+    match data {
+        MetricData::Gauge(gauge) => {
+            write_header(out, name, description, "gauge");
+            for point in gauge.data_points() {
+                let labels = format_labels(point.attributes(), None);
+                writeln!(out, "{name}{labels} {}", point.value()).unwrap();
+            }
+        }
+        MetricData::Sum(sum) => {
+            let (name, kind) = if sum.is_monotonic() {
+                (format!("{name}_total"), "counter")
+            } else {
+                (name.to_string(), "gauge")
+            };
+            write_header(out, &name, description, kind);
+            for point in sum.data_points() {
+                let labels = format_labels(point.attributes(), None);
+                writeln!(out, "{name}{labels} {}", point.value()).unwrap();
+            }
+        }
+        MetricData::Histogram(histogram) => {
+            write_header(out, name, description, "histogram");
+            for point in histogram.data_points() {
+                let bounds = point
+                    .bounds()
+                    .map(|bound| bound.to_string())
+                    .chain(once("+Inf".to_string()));
+                let mut cumulative_count = 0;
+                for (bound, count) in bounds.zip(point.bucket_counts()) {
+                    cumulative_count += count;
+                    let labels = format_labels(point.attributes(), Some(("le", &bound)));
+                    writeln!(out, "{name}_bucket{labels} {cumulative_count}").unwrap();
+                }
+                let labels = format_labels(point.attributes(), None);
+                writeln!(out, "{name}_sum{labels} {}", point.sum()).unwrap();
+                writeln!(out, "{name}_count{labels} {}", point.count()).unwrap();
+            }
+        }
+        // there is no equivalent in the text format
+        MetricData::ExponentialHistogram(_) => {}
+    }
+}
+
+/// Encodes the collected metrics in the Prometheus text exposition format. Metric names are
+/// sanitized and get a suffix with the unit, monotonic sums are exposed as counters.
+pub fn encode_metrics(metrics: &ResourceMetrics) -> String {
+    let mut out = String::new();
+    for scope_metrics in metrics.scope_metrics() {
+        for metric in scope_metrics.metrics() {
+            let mut name = sanitize_name(metric.name());
+            if let Some(suffix) = unit_suffix(metric.unit()) {
+                if !name.ends_with(suffix) {
+                    name = format!("{name}_{suffix}");
+                }
+            }
+            let description = metric.description();
+            match metric.data() {
+                AggregatedMetrics::F64(data) => {
+                    write_metric_data(&mut out, &name, description, data);
+                }
+                AggregatedMetrics::U64(data) => {
+                    write_metric_data(&mut out, &name, description, data);
+                }
+                AggregatedMetrics::I64(data) => {
+                    write_metric_data(&mut out, &name, description, data);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Runs an HTTP server exposing the metrics at `/metrics`. Stops the server when dropped.
+pub struct Runner {
+    thread_handle: Option<JoinHandle<()>>,
+    terminate_sender: Option<oneshot::Sender<()>>,
+}
+
+impl Runner {
+    /// Starts the server on all interfaces, so that it can be reached by a Prometheus
+    /// instance running on another host.
+    pub fn run(port: u16, reader: Reader) -> Self {
+        let (terminate_sender, terminate_receiver) = oneshot::channel::<()>();
+        let thread_handle = Builder::new()
+            .name("pathway:prometheus".to_string())
+            .spawn(move || {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_io()
+                    .build()
+                    .unwrap()
+                    .block_on(async {
+                        let addr = ([0, 0, 0, 0], port).into();
+                        let make_service = make_service_fn(move |_| {
+                            let reader = reader.clone();
+                            async move {
+                                Ok::<_, Error>(service_fn(move |req| {
+                                    let reader = reader.clone();
+                                    async move {
+                                        let mut response = Response::new(Body::empty());
+                                        match (req.method(), req.uri().path()) {
+                                            (&Method::GET, "/metrics") => match reader.encode() {
+                                                Ok(metrics_text) => {
+                                                    *response.body_mut() = Body::from(metrics_text);
+                                                    response.headers_mut().insert(
+                                                        header::CONTENT_TYPE,
+                                                        header::HeaderValue::from_static(
+                                                            TEXT_FORMAT_CONTENT_TYPE,
+                                                        ),
+                                                    );
+                                                }
+                                                Err(e) => {
+                                                    error!("failed to collect metrics: {e}");
+                                                    *response.status_mut() =
+                                                        StatusCode::INTERNAL_SERVER_ERROR;
+                                                }
+                                            },
+                                            _ => {
+                                                *response.status_mut() = StatusCode::NOT_FOUND;
+                                            }
+                                        }
+                                        Ok::<_, Error>(response)
+                                    }
+                                }))
+                            }
+                        });
+                        let server = match Server::try_bind(&addr) {
+                            Ok(server) => server.serve(make_service),
+                            Err(e) => {
+                                error!(
+                                    "failed to start the Prometheus endpoint on port {port}: {e}"
+                                );
+                                return;
+                            }
+                        };
+                        let shutdown_signal = async move {
+                            terminate_receiver.await.ok();
+                        };
+                        info!("Prometheus metrics available at http://{addr}/metrics");
+                        if let Err(e) = server.with_graceful_shutdown(shutdown_signal).await {
+                            error!("Prometheus endpoint error: {e}");
+                        }
+                    });
+            })
+            .expect("prometheus thread creation failed");
+        Self {
+            thread_handle: Some(thread_handle),
+            terminate_sender: Some(terminate_sender),
+        }
+    }
+}
+
+impl Drop for Runner {
+    fn drop(&mut self) {
+        // the server may have already stopped if it failed to bind
+        let _ = self.terminate_sender.take().unwrap().send(());
+        self.thread_handle
+            .take()
+            .unwrap()
+            .join()
+            .expect("prometheus thread failed");
+    }
+}
@@ -17,6 +17,40 @@ use super::{error::DynResult, DataError, Key, Value};
 pub type StatefulCombineFn =
     Arc<dyn Fn(Option<&Value>, Vec<(Vec<Value>, isize)>) -> DynResult<Option<Value>> + Send + Sync>;
 
+/// Keeps the first row of each group, in the order of the deduplication values, which are the
+/// first values of the rows. A deduplication value is either `None`, in which case the later
+/// rows of the group are dropped forever, or a pair of the time of the row and the time until
+/// which the duplicates of the row are dropped, after which the next row replaces it. The
+/// deletions are ignored, as the rows are dropped once they are seen.
+pub fn keep_first_combine_fn() -> StatefulCombineFn {
+    Arc::new(|state, values| {
+        let mut rows: Vec<_> = values
+            .into_iter()
+            .filter(|(row, diff)| *diff > 0 && row[0] != Value::Error)
+            .map(|(row, _diff)| row)
+            .collect();
+        rows.sort_by(|a, b| a[0].cmp(&b[0]));
+        let mut state = state.cloned();
+        for row in rows {
+            let is_duplicate = state.as_ref().is_some_and(|state| {
+                let Value::Tuple(kept_row) = state else {
+                    return true;
+                };
+                match (&kept_row[0], &row[0]) {
+                    (Value::Tuple(kept), Value::Tuple(current)) => {
+                        kept[1] == Value::None || current[0] < kept[1]
+                    }
+                    _ => true,
+                }
+            });
+            if !is_duplicate {
+                state = Some(Value::from(row));
+            }
+        }
+        Ok(state)
+    })
+}
+
 fn take_first_value<T>(v: Vec<T>) -> T {
     v.into_iter()
         .next()
@@ -316,7 +350,7 @@ impl Multiply<isize> for ArraySumState {
         }
         let count = self.count * rhs;
         let sum = CowNdArray::new(&self.sum, *rhs)
-            .expect("value contains only IntArray and FloatArray")
+            .expect("value contains only IntArray, FloatArray and Float32Array")
             .into();
         Self { count, sum }
     }
@@ -325,7 +359,10 @@ impl Multiply<isize> for ArraySumState {
 impl SemigroupState for ArraySumState {
     fn init(key: Key, values: Vec<Value>) -> DynResult<Self> {
         let value = take_first_value(values);
-        if matches!(value, Value::IntArray(_) | Value::FloatArray(_)) {
+        if matches!(
+            value,
+            Value::IntArray(_) | Value::FloatArray(_) | Value::Float32Array(_)
+        ) {
             Ok(Self {
                 count: 1,
                 sum: value,
@@ -605,6 +642,7 @@ pub struct ArraySumReducer;
 enum CowNdArray<'a> {
     IntArray(CowArray<'a, i64, IxDyn>),
     FloatArray(CowArray<'a, f64, IxDyn>),
+    Float32Array(CowArray<'a, f32, IxDyn>),
 }
 
 impl<'a> CowNdArray<'a> {
@@ -628,6 +666,14 @@ impl<'a> CowNdArray<'a> {
                     Ok(Self::FloatArray(CowArray::from(&**array * cnt as f64)))
                 }
             }
+            #[allow(clippy::cast_precision_loss)]
+            Value::Float32Array(array) => {
+                if cnt == 1 {
+                    Ok(Self::Float32Array(CowArray::from(&**array)))
+                } else {
+                    Ok(Self::Float32Array(CowArray::from(&**array * cnt as f32)))
+                }
+            }
             value => Err(DataError::TypeMismatch {
                 expected: "Array",
                 value: value.clone(),
@@ -648,6 +694,9 @@ fn add_ndarrays<'a>(
         (CowNdArray::FloatArray(lhs), CowNdArray::FloatArray(rhs)) => Ok(CowNdArray::FloatArray(
             CowArray::from(lhs.into_owned() + &rhs),
         )),
+        (CowNdArray::Float32Array(lhs), CowNdArray::Float32Array(rhs)) => Ok(
+            CowNdArray::Float32Array(CowArray::from(lhs.into_owned() + &rhs)),
+        ),
         _ => Err(DataError::MixingTypesInNpSum.into()),
     }
 }
@@ -657,6 +706,7 @@ impl<'a> From<CowNdArray<'a>> for Value {
         match state {
             CowNdArray::IntArray(a) => Self::from(a.into_owned()),
             CowNdArray::FloatArray(a) => Self::from(a.into_owned()),
+            CowNdArray::Float32Array(a) => Self::from(a.into_owned()),
         }
     }
 }
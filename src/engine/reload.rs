@@ -0,0 +1,251 @@
+// Copyright © 2024 Pathway
+
+//! Settings that can be changed while the computation is running, without restarting it and
+//! losing its state: the log level, the global retry budget, the maximum batch size of the
+//! output connectors, the interval of the metrics export and the number of threads evaluating
+//! the stateless operators.
+//!
+//! They are read from the environment variables and the configuration file when the computation
+//! starts. They are read again from the configuration file on `SIGHUP`, with the environment
+//! variables still taking precedence, and can be updated with a `POST` request to `/settings`
+//! of the monitoring HTTP server. The other settings are not affected by the reload.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use log::LevelFilter;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::engine::stateless_pool;
+use crate::env::{self, parse_env_var_with_config, ConfigFile};
+use crate::retry::set_global_retry_budget;
+
+/// The level used when `log_level` is not set, as set up by the logger.
+const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Debug;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Env(#[from] env::Error),
+
+    #[error("unexpected value of {0:?} setting: {1}")]
+    InvalidValue(&'static str, String),
+
+    #[error("malformed settings: {0}")]
+    MalformedJson(#[from] serde_json::Error),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReloadableSettings {
+    /// The maximum level of the logs of the engine, like `info` or `debug`.
+    pub log_level: Option<String>,
+    /// The number of retries per minute allowed by the global retry budget.
+    pub retry_budget_per_minute: Option<usize>,
+    /// Replaces the maximum batch size of the output connectors that write in batches.
+    pub max_batch_size: Option<usize>,
+    /// The minimum interval between the exports of the metrics. It can only make the exports
+    /// less frequent than the interval set at the start.
+    pub metrics_export_interval_seconds: Option<u64>,
+    /// The number of threads evaluating the stateless operators, shared by all the workers.
+    pub stateless_threads: Option<usize>,
+}
+
+impl ReloadableSettings {
+    /// Reads the settings from the environment variables, falling back to the given
+    /// configuration file.
+    pub fn from_config_file(config_file: Option<&ConfigFile>) -> Result<Self, Error> {
+        Ok(Self {
+            log_level: parse_env_var_with_config("PATHWAY_LOG_LEVEL", config_file)?,
+            retry_budget_per_minute: parse_env_var_with_config(
+                "PATHWAY_RETRY_BUDGET_PER_MINUTE",
+                config_file,
+            )?,
+            max_batch_size: parse_env_var_with_config("PATHWAY_MAX_BATCH_SIZE", config_file)?,
+            metrics_export_interval_seconds: parse_env_var_with_config(
+                "PATHWAY_METRICS_READER_INTERVAL_SECONDS",
+                config_file,
+            )?,
+            stateless_threads: parse_env_var_with_config("PATHWAY_STATELESS_THREADS", config_file)?,
+        })
+    }
+
+    /// Returns the settings with the fields set in `update` replaced.
+    #[must_use]
+    pub fn merge(&self, update: &Self) -> Self {
+        Self {
+            log_level: update.log_level.clone().or_else(|| self.log_level.clone()),
+            retry_budget_per_minute: update
+                .retry_budget_per_minute
+                .or(self.retry_budget_per_minute),
+            max_batch_size: update.max_batch_size.or(self.max_batch_size),
+            metrics_export_interval_seconds: update
+                .metrics_export_interval_seconds
+                .or(self.metrics_export_interval_seconds),
+            stateless_threads: update.stateless_threads.or(self.stateless_threads),
+        }
+    }
+
+    fn log_level_filter(&self) -> Result<LevelFilter, Error> {
+        self.log_level
+            .as_deref()
+            .map_or(Ok(DEFAULT_LOG_LEVEL), LevelFilter::from_str)
+            .map_err(|e| Error::InvalidValue("log_level", e.to_string()))
+    }
+
+    fn validate(&self) -> Result<LevelFilter, Error> {
+        if self.max_batch_size == Some(0) {
+            return Err(Error::InvalidValue(
+                "max_batch_size",
+                "has to be positive".to_string(),
+            ));
+        }
+        if self.stateless_threads == Some(0) {
+            return Err(Error::InvalidValue(
+                "stateless_threads",
+                "has to be positive".to_string(),
+            ));
+        }
+        self.log_level_filter()
+    }
+}
+
+static SETTINGS: Lazy<ArcSwap<ReloadableSettings>> =
+    Lazy::new(|| ArcSwap::from_pointee(ReloadableSettings::default()));
+
+pub fn current() -> Arc<ReloadableSettings> {
+    SETTINGS.load_full()
+}
+
+/// Validates the settings and makes them effective. Nothing is changed if they are invalid.
+pub fn apply(settings: ReloadableSettings) -> Result<(), Error> {
+    let log_level = settings.validate()?;
+    log::set_max_level(log_level);
+    if settings.retry_budget_per_minute != current().retry_budget_per_minute {
+        set_global_retry_budget(settings.retry_budget_per_minute);
+    }
+    if settings.stateless_threads != current().stateless_threads {
+        stateless_pool::resize(settings.stateless_threads);
+    }
+    SETTINGS.store(Arc::new(settings));
+    Ok(())
+}
+
+/// Applies the settings from the environment variables and the configuration file loaded at
+/// the start. Called once before the workers start.
+pub fn apply_from_env() -> Result<(), Error> {
+    apply(ReloadableSettings::from_config_file(env::config_file()?)?)
+}
+
+/// Reads the configuration file from the disk again and applies the settings from it.
+pub fn reload_from_config_file() -> Result<(), Error> {
+    let config_file = env::load_config_file()?;
+    apply(ReloadableSettings::from_config_file(config_file.as_ref())?)
+}
+
+/// Applies the fields set in a JSON object, keeping the current values of the other ones.
+/// Returns the settings in effect.
+pub fn update_from_json(body: &[u8]) -> Result<Arc<ReloadableSettings>, Error> {
+    let update: ReloadableSettings = serde_json::from_slice(body)?;
+    apply(current().merge(&update))?;
+    Ok(current())
+}
+
+/// The maximum batch size of an output connector configured with `configured`.
+pub fn max_batch_size(configured: Option<usize>) -> Option<usize> {
+    current().max_batch_size.or(configured)
+}
+
+/// The minimum interval between the exports of the metrics, if it was changed.
+pub fn metrics_export_interval() -> Option<Duration> {
+    current()
+        .metrics_export_interval_seconds
+        .map(Duration::from_secs)
+}
+
+/// Reloads the settings from the configuration file on every `SIGHUP`. Stops listening when
+/// dropped.
+pub struct SighupListener {
+    thread_handle: Option<JoinHandle<()>>,
+    terminate_sender: Option<oneshot::Sender<()>>,
+}
+
+impl SighupListener {
+    #[cfg(unix)]
+    fn start() -> Self {
+        use std::thread::Builder;
+
+        use log::{error, info};
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let (terminate_sender, mut terminate_receiver) = oneshot::channel::<()>();
+        let thread_handle = Builder::new()
+            .name("pathway:sighup".to_string())
+            .spawn(move || {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_io()
+                    .build()
+                    .unwrap()
+                    .block_on(async {
+                        let mut hangup = match signal(SignalKind::hangup()) {
+                            Ok(hangup) => hangup,
+                            Err(e) => {
+                                error!("failed to listen for SIGHUP: {e}");
+                                return;
+                            }
+                        };
+                        loop {
+                            tokio::select! {
+                                _ = &mut terminate_receiver => break,
+                                received = hangup.recv() => {
+                                    if received.is_none() {
+                                        break;
+                                    }
+                                    match reload_from_config_file() {
+                                        Ok(()) => info!("Reloaded the settings on SIGHUP"),
+                                        Err(e) => error!("failed to reload the settings: {e}"),
+                                    }
+                                }
+                            }
+                        }
+                    });
+            })
+            .expect("sighup listener thread creation failed");
+        Self {
+            thread_handle: Some(thread_handle),
+            terminate_sender: Some(terminate_sender),
+        }
+    }
+}
+
+impl Drop for SighupListener {
+    fn drop(&mut self) {
+        // the thread may have already stopped if it failed to listen for the signal
+        let _ = self.terminate_sender.take().unwrap().send(());
+        self.thread_handle
+            .take()
+            .unwrap()
+            .join()
+            .expect("sighup listener thread failed");
+    }
+}
+
+/// Starts listening for `SIGHUP` if there is a configuration file to reload the settings from.
+/// `SIGHUP` keeps its default behavior otherwise.
+#[cfg(unix)]
+pub fn maybe_start_sighup_listener() -> Option<SighupListener> {
+    let has_config_file =
+        std::env::var_os(env::CONFIG_FILE_ENV).is_some_and(|path| !path.is_empty());
+    has_config_file.then(SighupListener::start)
+}
+
+#[cfg(not(unix))]
+pub fn maybe_start_sighup_listener() -> Option<SighupListener> {
+    None
+}
@@ -0,0 +1,187 @@
+// Copyright © 2024 Pathway
+
+//! Tracing spans around the parts of the computation that may take long: connector polls,
+//! parsing of the read batches, persistence checkpoints and operator steps. The spans are
+//! exported to the tracing servers or the export directory of the telemetry config, as children
+//! of the trace parent of the run, so they end up in the same trace as the spans of the Python
+//! part of the run.
+//!
+//! Polls, parser batches and operator steps happen very often, so their spans are exported only
+//! if they take at least the configured minimum duration. Nothing is measured if tracing is not
+//! configured.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime};
+
+use once_cell::sync::Lazy;
+use opentelemetry::global;
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::{Span, Tracer};
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use timely::logging::{StartStop, TimelyEvent, WorkerIdentifier};
+use timely::logging_core::Registry;
+
+use super::telemetry::Config as TelemetryConfig;
+
+const TRACER_NAME: &str = "pathway-engine";
+
+pub const CONNECTOR_POLL: &str = "connector.poll";
+pub const PARSER_BATCH: &str = "parser.batch";
+pub const CHECKPOINT: &str = "persistence.checkpoint";
+pub const OPERATOR_STEP: &str = "operator.step";
+
+pub const CONNECTOR_NAME: &str = "connector.name";
+pub const BATCH_ENTRIES: &str = "batch.entries";
+pub const CHECKPOINT_TIMESTAMP: &str = "checkpoint.timestamp";
+pub const OPERATOR_ID: &str = "operator.id";
+pub const OPERATOR_NAME: &str = "operator.name";
+pub const WORKER_ID: &str = "worker.id";
+
+struct TracingState {
+    enabled: AtomicBool,
+    min_duration: RwLock<Duration>,
+    parent: RwLock<Context>,
+}
+
+static TRACING_STATE: Lazy<TracingState> = Lazy::new(|| TracingState {
+    enabled: AtomicBool::new(false),
+    min_duration: RwLock::new(Duration::ZERO),
+    parent: RwLock::new(Context::new()),
+});
+
+/// Enables the spans if the telemetry config has tracing servers or an export directory. Called
+/// once before the workers start.
+pub fn configure(telemetry_config: &TelemetryConfig, min_duration: Duration) {
+    let (enabled, parent) = match telemetry_config {
+        TelemetryConfig::Enabled(config)
+            if !config.tracing_servers.is_empty() || config.trace_export_dir().is_some() =>
+        {
+            let parent = config
+                .trace_parent
+                .clone()
+                .map_or_else(Context::new, |trace_parent| {
+                    let carrier = HashMap::from([("traceparent".to_string(), trace_parent)]);
+                    TraceContextPropagator::new().extract(&carrier)
+                });
+            (true, parent)
+        }
+        _ => (false, Context::new()),
+    };
+    *TRACING_STATE.min_duration.write().unwrap() = min_duration;
+    *TRACING_STATE.parent.write().unwrap() = parent;
+    TRACING_STATE.enabled.store(enabled, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    TRACING_STATE.enabled.load(Ordering::Relaxed)
+}
+
+fn min_duration() -> Duration {
+    *TRACING_STATE.min_duration.read().unwrap()
+}
+
+/// Exports a span of work that has already finished.
+pub fn record(name: &'static str, start: SystemTime, end: SystemTime, attributes: Vec<KeyValue>) {
+    let tracer = global::tracer(TRACER_NAME);
+    let mut span = tracer
+        .span_builder(name)
+        .with_start_time(start)
+        .with_attributes(attributes)
+        .start_with_context(&tracer, &TRACING_STATE.parent.read().unwrap());
+    span.end_with_timestamp(end);
+}
+
+fn traced<R>(
+    name: &'static str,
+    min_duration: impl FnOnce() -> Duration,
+    work: impl FnOnce() -> R,
+    attributes: impl FnOnce(&R) -> Vec<KeyValue>,
+) -> R {
+    if !is_enabled() {
+        return work();
+    }
+    let start = SystemTime::now();
+    let timer = Instant::now();
+    let result = work();
+    let elapsed = timer.elapsed();
+    if elapsed >= min_duration() {
+        record(name, start, start + elapsed, attributes(&result));
+    }
+    result
+}
+
+/// Runs `work` and exports a span around it. The attributes are computed from its result.
+pub fn trace<R>(
+    name: &'static str,
+    work: impl FnOnce() -> R,
+    attributes: impl FnOnce(&R) -> Vec<KeyValue>,
+) -> R {
+    traced(name, || Duration::ZERO, work, attributes)
+}
+
+/// Runs `work` and exports a span around it if it took at least the configured minimum
+/// duration.
+pub fn trace_if_slow<R>(
+    name: &'static str,
+    work: impl FnOnce() -> R,
+    attributes: impl FnOnce(&R) -> Vec<KeyValue>,
+) -> R {
+    traced(name, min_duration, work, attributes)
+}
+
+/// Exports spans for the operator steps of the worker that took at least the configured minimum
+/// duration, based on the scheduling events logged by timely. Has to be called before the
+/// dataflow is built, so that the operators get the logger.
+pub fn register_operator_step_logger(register: &mut Registry<WorkerIdentifier>) {
+    // the logger set up with `TIMELY_WORKER_LOG_ADDR` is not replaced
+    if !is_enabled() || register.get::<TimelyEvent>("timely").is_some() {
+        return;
+    }
+    let min_duration = min_duration();
+    let mut operator_names: HashMap<usize, String> = HashMap::new();
+    let mut step_starts: HashMap<usize, Duration> = HashMap::new();
+    register.insert::<TimelyEvent, _>("timely", move |time, events| {
+        // event times are relative to the creation of the worker, `time` is the current one
+        let Some(epoch) = SystemTime::now().checked_sub(*time) else {
+            return;
+        };
+        for (event_time, worker_id, event) in events.drain(..) {
+            match event {
+                TimelyEvent::Operates(operates) => {
+                    operator_names.insert(operates.id, operates.name);
+                }
+                TimelyEvent::Schedule(schedule) => match schedule.start_stop {
+                    StartStop::Start => {
+                        step_starts.insert(schedule.id, event_time);
+                    }
+                    StartStop::Stop => {
+                        let Some(step_start) = step_starts.remove(&schedule.id) else {
+                            continue;
+                        };
+                        if event_time.saturating_sub(step_start) < min_duration {
+                            continue;
+                        }
+                        let operator_name = operator_names
+                            .get(&schedule.id)
+                            .cloned()
+                            .unwrap_or_default();
+                        record(
+                            OPERATOR_STEP,
+                            epoch + step_start,
+                            epoch + event_time,
+                            vec![
+                                KeyValue::new(OPERATOR_ID, i64::try_from(schedule.id).unwrap()),
+                                KeyValue::new(OPERATOR_NAME, operator_name),
+                                KeyValue::new(WORKER_ID, i64::try_from(worker_id).unwrap()),
+                            ],
+                        );
+                    }
+                },
+                _ => {}
+            }
+        }
+    });
+}
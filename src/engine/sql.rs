@@ -0,0 +1,1621 @@
+// Copyright © 2024 Pathway
+
+//! Compiling streaming SQL queries onto the graph, so that pipelines can be defined
+//! declaratively with a query instead of building the operators one by one.
+//!
+//! A query is first planned with [`plan_query`] against the schemas of the tables it reads,
+//! which resolves the columns and checks their types, and the plan is then built with
+//! [`QueryPlan::build`] on the graph of each worker. Both steps are done by `Scope.sql_query`
+//! of the Python API. The supported subset is a single `SELECT` with:
+//! - `FROM` a table, followed by any number of `[INNER | LEFT | RIGHT | FULL] JOIN`s on
+//!   equalities of columns,
+//! - `WHERE`, `GROUP BY` and `HAVING`,
+//! - the `COUNT(*)`, `COUNT(DISTINCT ...)`, `SUM`, `AVG`, `MIN` and `MAX` aggregates,
+//! - the `TUMBLE(time, size)` and `TUMBLE_END(time, size)` functions, giving the start and the
+//!   end of the tumbling window of a row, where the size is an integer for integer times and
+//!   an interval like `INTERVAL '5' MINUTE` for date times.
+//!
+//! Tumbling windows are the only windows supported. Queries using window functions (`OVER`,
+//! `WINDOW` or `QUALIFY`) or sliding and session windows (`HOP` and `SESSION`) are rejected
+//! when they are planned, with [`SqlError::UnsupportedWindow`].
+//!
+//! `NULL` propagates through all operators, a row is kept by `WHERE` only if the condition is
+//! true, and dividing integers gives a float.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use sqlparser::ast::{
+    BinaryOperator, DateTimeField, DuplicateTreatment, Expr as SqlExpr, Function, FunctionArg,
+    FunctionArgExpr, FunctionArguments, GroupByExpr, Interval, JoinConstraint, JoinOperator, Query,
+    SelectItem, SetExpr, Statement, TableFactor, TableWithJoins, UnaryOperator, Value as SqlValue,
+    WildcardAdditionalOptions,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::{Parser, ParserError};
+
+use super::error::Trace;
+use super::graph::JoinExactlyOnce;
+use super::{
+    AnyExpression, BoolExpression, ColumnPath, ColumnProperties, DateTimeNaiveExpression,
+    DateTimeUtcExpression, Duration, DurationExpression, Error, Expression, ExpressionData,
+    FloatExpression, Graph, IntExpression, JoinData, JoinType, Reducer, ReducerData, ShardPolicy,
+    StringExpression, TableHandle, TableProperties, Type, Value,
+};
+
+const AGGREGATES: [&str; 5] = ["count", "sum", "avg", "min", "max"];
+
+const UNSUPPORTED_WINDOWS: [(&str, &str); 4] = [
+    ("hop", "sliding windows"),
+    ("hop_end", "sliding windows"),
+    ("session", "session windows"),
+    ("session_end", "session windows"),
+];
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SqlError {
+    #[error(transparent)]
+    Parse(#[from] ParserError),
+
+    #[error("expected a single SELECT query")]
+    NotAQuery,
+
+    #[error("{0} is not supported")]
+    Unsupported(String),
+
+    #[error("{0} are not supported, only tumbling windows with TUMBLE(time, size) are")]
+    UnsupportedWindow(String),
+
+    #[error("unknown table {0:?}")]
+    UnknownTable(String),
+
+    #[error("unknown column {0:?}")]
+    UnknownColumn(String),
+
+    #[error("column {0:?} is ambiguous")]
+    AmbiguousColumn(String),
+
+    #[error("column {0:?} has to appear in GROUP BY or be used in an aggregate")]
+    NotGrouped(String),
+
+    #[error("aggregate {0} is not allowed here")]
+    MisplacedAggregate(String),
+
+    #[error("{operation} can't be applied to {types:?}")]
+    InvalidTypes { operation: String, types: Vec<Type> },
+
+    #[error("invalid literal {0}")]
+    InvalidLiteral(String),
+
+    #[error("invalid interval {0}")]
+    InvalidInterval(String),
+
+    #[error("duplicate output column {0:?}")]
+    DuplicateColumn(String),
+
+    #[error(transparent)]
+    Engine(#[from] Error),
+}
+
+/// The columns of a table that can be read by the queries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableSchema {
+    pub columns: Vec<(String, Type)>,
+}
+
+impl TableSchema {
+    pub fn new(columns: Vec<(String, Type)>) -> Self {
+        Self { columns }
+    }
+}
+
+fn column_properties(dtype: Type) -> TableProperties {
+    TableProperties::Column(Arc::new(ColumnProperties {
+        dtype,
+        append_only: false,
+        trace: Arc::new(Trace::Empty),
+    }))
+}
+
+fn flat_properties(dtypes: impl IntoIterator<Item = Type>) -> Arc<TableProperties> {
+    let properties: Vec<_> = dtypes.into_iter().map(column_properties).collect();
+    Arc::new(TableProperties::Table(
+        properties.into(),
+        Arc::new(Trace::Empty),
+    ))
+}
+
+fn optionalize(dtype: &Type) -> Type {
+    if dtype.can_be_none() {
+        dtype.clone()
+    } else {
+        Type::Optional(Arc::new(dtype.clone()))
+    }
+}
+
+fn optionalize_properties(properties: &TableProperties) -> TableProperties {
+    match properties {
+        TableProperties::Table(inner, trace) => TableProperties::Table(
+            inner.iter().map(optionalize_properties).collect(),
+            trace.clone(),
+        ),
+        TableProperties::Column(column) => column_properties(optionalize(&column.dtype)),
+        TableProperties::Empty => TableProperties::Empty,
+    }
+}
+
+/// An expression over the columns of the current table, with the type of its values.
+#[derive(Clone, Debug)]
+struct Typed {
+    expression: Arc<Expression>,
+    dtype: Type,
+}
+
+impl Typed {
+    fn new(expression: Expression, dtype: Type) -> Self {
+        Self {
+            expression: Arc::new(expression),
+            dtype,
+        }
+    }
+
+    fn argument(index: usize, dtype: Type) -> Self {
+        Self::new(Expression::Any(AnyExpression::Argument(index)), dtype)
+    }
+
+    fn unoptionalized(&self) -> Self {
+        Self {
+            expression: self.expression.clone(),
+            dtype: self.dtype.unoptionalize().clone(),
+        }
+    }
+
+    fn as_float(self) -> Self {
+        match self.dtype {
+            Type::Int => Self::new(
+                Expression::Float(FloatExpression::CastFromInt(self.expression)),
+                Type::Float,
+            ),
+            _ => self,
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(self.dtype, Type::Int | Type::Float)
+    }
+}
+
+fn invalid_types(operation: &str, operands: &[&Typed]) -> SqlError {
+    SqlError::InvalidTypes {
+        operation: operation.to_string(),
+        types: operands
+            .iter()
+            .map(|operand| operand.dtype.clone())
+            .collect(),
+    }
+}
+
+fn none_if(condition: Arc<Expression>, result: Typed) -> Typed {
+    Typed::new(
+        Expression::Any(AnyExpression::IfElse(
+            condition,
+            Arc::new(Expression::Any(AnyExpression::Const(Value::None))),
+            result.expression,
+        )),
+        optionalize(&result.dtype),
+    )
+}
+
+fn is_none(operand: &Typed) -> Arc<Expression> {
+    Arc::new(Expression::Bool(BoolExpression::IsNone(
+        operand.expression.clone(),
+    )))
+}
+
+/// Applies `compile` to the values of the operand, and gives `NULL` if the operand is `NULL`.
+fn propagate_null(
+    operand: Typed,
+    compile: impl FnOnce(Typed) -> Result<Typed, SqlError>,
+) -> Result<Typed, SqlError> {
+    if operand.dtype.is_optional() {
+        let result = compile(operand.unoptionalized())?;
+        Ok(none_if(is_none(&operand), result))
+    } else {
+        compile(operand)
+    }
+}
+
+/// Applies `compile` to the values of the operands, and gives `NULL` if any of them is `NULL`.
+fn propagate_nulls(
+    left: Typed,
+    right: Typed,
+    compile: impl FnOnce(Typed, Typed) -> Result<Typed, SqlError>,
+) -> Result<Typed, SqlError> {
+    match (left.dtype.is_optional(), right.dtype.is_optional()) {
+        (false, false) => compile(left, right),
+        (true, false) => {
+            let result = compile(left.unoptionalized(), right)?;
+            Ok(none_if(is_none(&left), result))
+        }
+        (false, true) => {
+            let result = compile(left, right.unoptionalized())?;
+            Ok(none_if(is_none(&right), result))
+        }
+        (true, true) => {
+            let result = compile(left.unoptionalized(), right.unoptionalized())?;
+            let condition = Arc::new(Expression::Bool(BoolExpression::Or(
+                is_none(&left),
+                is_none(&right),
+            )));
+            Ok(none_if(condition, result))
+        }
+    }
+}
+
+type BinaryConstructor<E> = fn(Arc<Expression>, Arc<Expression>) -> E;
+
+/// The `=`, `<>`, `<`, `<=`, `>` and `>=` of the types with an order.
+fn ordered_comparisons(
+    left: &Type,
+    right: &Type,
+) -> Option<[BinaryConstructor<BoolExpression>; 6]> {
+    let constructors: [BinaryConstructor<BoolExpression>; 6] = match (left, right) {
+        (Type::Int, Type::Int) => [
+            BoolExpression::IntEq,
+            BoolExpression::IntNe,
+            BoolExpression::IntLt,
+            BoolExpression::IntLe,
+            BoolExpression::IntGt,
+            BoolExpression::IntGe,
+        ],
+        (Type::Float, Type::Float) => [
+            BoolExpression::FloatEq,
+            BoolExpression::FloatNe,
+            BoolExpression::FloatLt,
+            BoolExpression::FloatLe,
+            BoolExpression::FloatGt,
+            BoolExpression::FloatGe,
+        ],
+        (Type::String, Type::String) => [
+            BoolExpression::StringEq,
+            BoolExpression::StringNe,
+            BoolExpression::StringLt,
+            BoolExpression::StringLe,
+            BoolExpression::StringGt,
+            BoolExpression::StringGe,
+        ],
+        (Type::Bool, Type::Bool) => [
+            BoolExpression::BoolEq,
+            BoolExpression::BoolNe,
+            BoolExpression::BoolLt,
+            BoolExpression::BoolLe,
+            BoolExpression::BoolGt,
+            BoolExpression::BoolGe,
+        ],
+        (Type::Pointer, Type::Pointer) => [
+            BoolExpression::PtrEq,
+            BoolExpression::PtrNe,
+            BoolExpression::PtrLt,
+            BoolExpression::PtrLe,
+            BoolExpression::PtrGt,
+            BoolExpression::PtrGe,
+        ],
+        (Type::DateTimeNaive, Type::DateTimeNaive) => [
+            BoolExpression::DateTimeNaiveEq,
+            BoolExpression::DateTimeNaiveNe,
+            BoolExpression::DateTimeNaiveLt,
+            BoolExpression::DateTimeNaiveLe,
+            BoolExpression::DateTimeNaiveGt,
+            BoolExpression::DateTimeNaiveGe,
+        ],
+        (Type::DateTimeUtc, Type::DateTimeUtc) => [
+            BoolExpression::DateTimeUtcEq,
+            BoolExpression::DateTimeUtcNe,
+            BoolExpression::DateTimeUtcLt,
+            BoolExpression::DateTimeUtcLe,
+            BoolExpression::DateTimeUtcGt,
+            BoolExpression::DateTimeUtcGe,
+        ],
+        (Type::Duration, Type::Duration) => [
+            BoolExpression::DurationEq,
+            BoolExpression::DurationNe,
+            BoolExpression::DurationLt,
+            BoolExpression::DurationLe,
+            BoolExpression::DurationGt,
+            BoolExpression::DurationGe,
+        ],
+        _ => return None,
+    };
+    Some(constructors)
+}
+
+fn compile_comparison(op: &BinaryOperator, left: Typed, right: Typed) -> Result<Typed, SqlError> {
+    let index = match op {
+        BinaryOperator::Eq => 0,
+        BinaryOperator::NotEq => 1,
+        BinaryOperator::Lt => 2,
+        BinaryOperator::LtEq => 3,
+        BinaryOperator::Gt => 4,
+        _ => 5,
+    };
+    let (left, right) = if left.is_numeric() && right.is_numeric() && left.dtype != right.dtype {
+        (left.as_float(), right.as_float())
+    } else {
+        (left, right)
+    };
+    if let Some(constructors) = ordered_comparisons(&left.dtype, &right.dtype) {
+        return Ok(Typed::new(
+            Expression::Bool(constructors[index](left.expression, right.expression)),
+            Type::Bool,
+        ));
+    }
+    let is_comparable = left.dtype == right.dtype
+        || matches!(left.dtype, Type::Any)
+        || matches!(right.dtype, Type::Any);
+    let constructor: BinaryConstructor<BoolExpression> = match index {
+        0 if is_comparable => BoolExpression::Eq,
+        1 if is_comparable => BoolExpression::Ne,
+        _ => return Err(invalid_types(&op.to_string(), &[&left, &right])),
+    };
+    Ok(Typed::new(
+        Expression::Bool(constructor(left.expression, right.expression)),
+        Type::Bool,
+    ))
+}
+
+fn compile_arithmetic(op: &BinaryOperator, left: Typed, right: Typed) -> Result<Typed, SqlError> {
+    if left.is_numeric() && right.is_numeric() {
+        let (int, float): (
+            Option<BinaryConstructor<IntExpression>>,
+            BinaryConstructor<_>,
+        ) = match op {
+            BinaryOperator::Plus => (Some(IntExpression::Add), FloatExpression::Add),
+            BinaryOperator::Minus => (Some(IntExpression::Sub), FloatExpression::Sub),
+            BinaryOperator::Multiply => (Some(IntExpression::Mul), FloatExpression::Mul),
+            BinaryOperator::Modulo => (Some(IntExpression::Mod), FloatExpression::Mod),
+            BinaryOperator::Divide => (None, FloatExpression::TrueDiv),
+            _ => return Err(invalid_types(&op.to_string(), &[&left, &right])),
+        };
+        return Ok(match (int, &left.dtype, &right.dtype) {
+            (Some(int), Type::Int, Type::Int) => Typed::new(
+                Expression::Int(int(left.expression, right.expression)),
+                Type::Int,
+            ),
+            (None, Type::Int, Type::Int) => Typed::new(
+                Expression::Float(FloatExpression::IntTrueDiv(
+                    left.expression,
+                    right.expression,
+                )),
+                Type::Float,
+            ),
+            _ => Typed::new(
+                Expression::Float(float(
+                    left.as_float().expression,
+                    right.as_float().expression,
+                )),
+                Type::Float,
+            ),
+        });
+    }
+    let is_plus = matches!(op, BinaryOperator::Plus);
+    let is_minus = matches!(op, BinaryOperator::Minus);
+    let (expression, dtype) = match (&left.dtype, &right.dtype) {
+        (Type::String, Type::String) if is_plus || matches!(op, BinaryOperator::StringConcat) => (
+            Expression::String(StringExpression::Add(left.expression, right.expression)),
+            Type::String,
+        ),
+        (Type::DateTimeNaive, Type::Duration) if is_plus || is_minus => {
+            let constructor = if is_plus {
+                DateTimeNaiveExpression::AddDuration
+            } else {
+                DateTimeNaiveExpression::SubDuration
+            };
+            (
+                Expression::DateTimeNaive(constructor(left.expression, right.expression)),
+                Type::DateTimeNaive,
+            )
+        }
+        (Type::DateTimeUtc, Type::Duration) if is_plus || is_minus => {
+            let constructor = if is_plus {
+                DateTimeUtcExpression::AddDuration
+            } else {
+                DateTimeUtcExpression::SubDuration
+            };
+            (
+                Expression::DateTimeUtc(constructor(left.expression, right.expression)),
+                Type::DateTimeUtc,
+            )
+        }
+        (Type::Duration, Type::Duration) if is_plus || is_minus => {
+            let constructor = if is_plus {
+                DurationExpression::Add
+            } else {
+                DurationExpression::Sub
+            };
+            (
+                Expression::Duration(constructor(left.expression, right.expression)),
+                Type::Duration,
+            )
+        }
+        (Type::DateTimeNaive, Type::DateTimeNaive) if is_minus => (
+            Expression::Duration(DurationExpression::DateTimeNaiveSub(
+                left.expression,
+                right.expression,
+            )),
+            Type::Duration,
+        ),
+        (Type::DateTimeUtc, Type::DateTimeUtc) if is_minus => (
+            Expression::Duration(DurationExpression::DateTimeUtcSub(
+                left.expression,
+                right.expression,
+            )),
+            Type::Duration,
+        ),
+        _ => return Err(invalid_types(&op.to_string(), &[&left, &right])),
+    };
+    Ok(Typed::new(expression, dtype))
+}
+
+fn compile_binary(op: &BinaryOperator, left: Typed, right: Typed) -> Result<Typed, SqlError> {
+    propagate_nulls(left, right, |left, right| match op {
+        BinaryOperator::And | BinaryOperator::Or => {
+            if left.dtype != Type::Bool || right.dtype != Type::Bool {
+                return Err(invalid_types(&op.to_string(), &[&left, &right]));
+            }
+            let constructor = if matches!(op, BinaryOperator::And) {
+                BoolExpression::And
+            } else {
+                BoolExpression::Or
+            };
+            Ok(Typed::new(
+                Expression::Bool(constructor(left.expression, right.expression)),
+                Type::Bool,
+            ))
+        }
+        BinaryOperator::Eq
+        | BinaryOperator::NotEq
+        | BinaryOperator::Lt
+        | BinaryOperator::LtEq
+        | BinaryOperator::Gt
+        | BinaryOperator::GtEq => compile_comparison(op, left, right),
+        BinaryOperator::Plus
+        | BinaryOperator::Minus
+        | BinaryOperator::Multiply
+        | BinaryOperator::Divide
+        | BinaryOperator::Modulo
+        | BinaryOperator::StringConcat => compile_arithmetic(op, left, right),
+        _ => Err(SqlError::Unsupported(format!("operator {op}"))),
+    })
+}
+
+fn compile_unary(op: UnaryOperator, operand: Typed) -> Result<Typed, SqlError> {
+    propagate_null(operand, |operand| {
+        let expression = match (op, &operand.dtype) {
+            (UnaryOperator::Plus, Type::Int | Type::Float | Type::Duration) => return Ok(operand),
+            (UnaryOperator::Minus, Type::Int) => {
+                Expression::Int(IntExpression::Neg(operand.expression))
+            }
+            (UnaryOperator::Minus, Type::Float) => {
+                Expression::Float(FloatExpression::Neg(operand.expression))
+            }
+            (UnaryOperator::Minus, Type::Duration) => {
+                Expression::Duration(DurationExpression::Neg(operand.expression))
+            }
+            (UnaryOperator::Not, Type::Bool) => {
+                Expression::Bool(BoolExpression::Not(operand.expression))
+            }
+            _ => return Err(invalid_types(&op.to_string(), &[&operand])),
+        };
+        Ok(Typed::new(expression, operand.dtype))
+    })
+}
+
+fn compile_literal(value: &SqlValue) -> Result<Typed, SqlError> {
+    Ok(match value {
+        SqlValue::Number(number, _) => {
+            let number = number.to_string();
+            if let Ok(number) = number.parse() {
+                Typed::new(Expression::Int(IntExpression::Const(number)), Type::Int)
+            } else {
+                let number = number
+                    .parse()
+                    .map_err(|_| SqlError::InvalidLiteral(number.clone()))?;
+                Typed::new(
+                    Expression::Float(FloatExpression::Const(number)),
+                    Type::Float,
+                )
+            }
+        }
+        SqlValue::SingleQuotedString(string) => Typed::new(
+            Expression::Any(AnyExpression::Const(Value::from(string.as_str()))),
+            Type::String,
+        ),
+        SqlValue::Boolean(value) => {
+            Typed::new(Expression::Bool(BoolExpression::Const(*value)), Type::Bool)
+        }
+        SqlValue::Null => Typed::new(
+            Expression::Any(AnyExpression::Const(Value::None)),
+            Type::Optional(Arc::new(Type::Any)),
+        ),
+        value => return Err(SqlError::Unsupported(format!("literal {value}"))),
+    })
+}
+
+fn interval_unit(field: &DateTimeField) -> Option<&'static str> {
+    match field {
+        DateTimeField::Week(_) => Some("W"),
+        DateTimeField::Day => Some("D"),
+        DateTimeField::Hour => Some("h"),
+        DateTimeField::Minute => Some("m"),
+        DateTimeField::Second => Some("s"),
+        DateTimeField::Millisecond | DateTimeField::Milliseconds => Some("ms"),
+        DateTimeField::Microsecond | DateTimeField::Microseconds => Some("us"),
+        DateTimeField::Nanosecond | DateTimeField::Nanoseconds => Some("ns"),
+        _ => None,
+    }
+}
+
+/// Parses `INTERVAL '<n>' <unit>` and `INTERVAL '<n> <unit>'`.
+fn compile_interval(interval: &Interval) -> Result<Typed, SqlError> {
+    let invalid = || SqlError::InvalidInterval(interval.to_string());
+    let text = match interval.value.as_ref() {
+        SqlExpr::Value(SqlValue::SingleQuotedString(text)) => text.clone(),
+        SqlExpr::Value(SqlValue::Number(number, _)) => number.to_string(),
+        _ => return Err(invalid()),
+    };
+    if interval.last_field.is_some() {
+        return Err(invalid());
+    }
+    let (amount, unit) = if let Some(field) = &interval.leading_field {
+        (text.trim(), interval_unit(field).ok_or_else(invalid)?)
+    } else {
+        let mut parts = text.split_whitespace();
+        let (Some(amount), Some(unit), None) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        let unit = match unit {
+            "week" | "weeks" => "W",
+            unit => unit,
+        };
+        (amount, unit)
+    };
+    let amount = amount.parse().map_err(|_| invalid())?;
+    let duration = Duration::new_with_unit(amount, unit).map_err(|_| invalid())?;
+    Ok(Typed::new(
+        Expression::Any(AnyExpression::Const(Value::Duration(duration))),
+        Type::Duration,
+    ))
+}
+
+fn compile_tumble(is_end: bool, time: Typed, size: Typed) -> Result<Typed, SqlError> {
+    propagate_nulls(time, size, |time, size| {
+        let (start, dtype) = match (&time.dtype, &size.dtype) {
+            (Type::Int, Type::Int) => {
+                let offset = Arc::new(Expression::Int(IntExpression::Mod(
+                    time.expression.clone(),
+                    size.expression.clone(),
+                )));
+                (
+                    Expression::Int(IntExpression::Sub(time.expression, offset)),
+                    Type::Int,
+                )
+            }
+            (Type::DateTimeNaive, Type::Duration) => (
+                Expression::DateTimeNaive(DateTimeNaiveExpression::Floor(
+                    time.expression,
+                    size.expression.clone(),
+                )),
+                Type::DateTimeNaive,
+            ),
+            (Type::DateTimeUtc, Type::Duration) => (
+                Expression::DateTimeUtc(DateTimeUtcExpression::Floor(
+                    time.expression,
+                    size.expression.clone(),
+                )),
+                Type::DateTimeUtc,
+            ),
+            _ => return Err(invalid_types("TUMBLE", &[&time, &size])),
+        };
+        let start = Typed::new(start, dtype);
+        if is_end {
+            compile_arithmetic(&BinaryOperator::Plus, start, size)
+        } else {
+            Ok(start)
+        }
+    })
+}
+
+fn function_arguments(function: &Function) -> Result<(bool, Vec<&FunctionArgExpr>), SqlError> {
+    let name = function.name.to_string();
+    if function.over.is_some() {
+        return Err(SqlError::UnsupportedWindow(format!(
+            "window functions like {name} with OVER"
+        )));
+    }
+    if function.filter.is_some() || !function.within_group.is_empty() {
+        return Err(SqlError::Unsupported(format!(
+            "{name} with FILTER or WITHIN GROUP"
+        )));
+    }
+    let FunctionArguments::List(list) = &function.args else {
+        return Err(SqlError::Unsupported(format!("{name} without arguments")));
+    };
+    if !list.clauses.is_empty() {
+        return Err(SqlError::Unsupported(format!(
+            "clauses in arguments of {name}"
+        )));
+    }
+    let arguments = list
+        .args
+        .iter()
+        .map(|argument| match argument {
+            FunctionArg::Unnamed(argument) => Ok(argument),
+            _ => Err(SqlError::Unsupported(format!("named arguments of {name}"))),
+        })
+        .collect::<Result<_, _>>()?;
+    let is_distinct = matches!(list.duplicate_treatment, Some(DuplicateTreatment::Distinct));
+    Ok((is_distinct, arguments))
+}
+
+fn is_aggregate(function: &Function) -> bool {
+    AGGREGATES.contains(&function.name.to_string().to_lowercase().as_str())
+}
+
+fn contains_aggregate(expr: &SqlExpr) -> bool {
+    match expr {
+        SqlExpr::Function(function) => {
+            if is_aggregate(function) {
+                return true;
+            }
+            let FunctionArguments::List(list) = &function.args else {
+                return false;
+            };
+            list.args.iter().any(|argument| match argument {
+                FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => contains_aggregate(expr),
+                _ => false,
+            })
+        }
+        SqlExpr::BinaryOp { left, right, .. } => {
+            contains_aggregate(left) || contains_aggregate(right)
+        }
+        SqlExpr::UnaryOp { expr, .. }
+        | SqlExpr::Nested(expr)
+        | SqlExpr::IsNull(expr)
+        | SqlExpr::IsNotNull(expr) => contains_aggregate(expr),
+        _ => false,
+    }
+}
+
+/// Rejects the window functions and the windows other than tumbling ones, so that they fail
+/// with a clear error before the rest of the query is checked.
+fn check_windows(expr: &SqlExpr) -> Result<(), SqlError> {
+    match expr {
+        SqlExpr::Function(function) => {
+            let name = function.name.to_string();
+            if function.over.is_some() {
+                return Err(SqlError::UnsupportedWindow(format!(
+                    "window functions like {name} with OVER"
+                )));
+            }
+            let lowercase_name = name.to_lowercase();
+            if let Some((_, kind)) = UNSUPPORTED_WINDOWS
+                .iter()
+                .find(|(window_name, _)| *window_name == lowercase_name)
+            {
+                return Err(SqlError::UnsupportedWindow(format!("{kind} like {name}")));
+            }
+            let FunctionArguments::List(list) = &function.args else {
+                return Ok(());
+            };
+            list.args.iter().try_for_each(|argument| match argument {
+                FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => check_windows(expr),
+                _ => Ok(()),
+            })
+        }
+        SqlExpr::BinaryOp { left, right, .. } => {
+            check_windows(left)?;
+            check_windows(right)
+        }
+        SqlExpr::UnaryOp { expr, .. }
+        | SqlExpr::Nested(expr)
+        | SqlExpr::IsNull(expr)
+        | SqlExpr::IsNotNull(expr) => check_windows(expr),
+        _ => Ok(()),
+    }
+}
+
+/// The name and the optional table name of a column reference.
+fn column_reference(expr: &SqlExpr) -> Option<(Option<&str>, &str)> {
+    match expr {
+        SqlExpr::Identifier(name) => Some((None, &name.value)),
+        SqlExpr::CompoundIdentifier(parts) => match parts.as_slice() {
+            [table, name] => Some((Some(&table.value), &name.value)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ScopeColumn {
+    table: Option<String>,
+    name: String,
+    dtype: Type,
+    path: Vec<usize>,
+}
+
+/// The columns visible at some point of the query, with their positions in the rows of the
+/// current table.
+#[derive(Clone, Debug)]
+struct Scope {
+    columns: Vec<ScopeColumn>,
+    properties: Arc<TableProperties>,
+}
+
+impl Scope {
+    fn table(name: String, schema: &TableSchema) -> Self {
+        let columns = schema
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, (column, dtype))| ScopeColumn {
+                table: Some(name.clone()),
+                name: column.clone(),
+                dtype: dtype.clone(),
+                path: vec![index],
+            })
+            .collect();
+        let properties = flat_properties(schema.columns.iter().map(|(_, dtype)| dtype.clone()));
+        Self {
+            columns,
+            properties,
+        }
+    }
+
+    fn paths(&self) -> Vec<ColumnPath> {
+        self.columns
+            .iter()
+            .map(|column| ColumnPath::ValuePath(column.path.clone()))
+            .collect()
+    }
+
+    fn matches(&self, table: Option<&str>, name: &str) -> Vec<usize> {
+        self.columns
+            .iter()
+            .enumerate()
+            .filter(|(_, column)| {
+                column.name == name
+                    && table.is_none_or(|table| column.table.as_deref() == Some(table))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn resolve(&self, expr: &SqlExpr) -> Result<usize, SqlError> {
+        let (table, name) = column_reference(expr)
+            .ok_or_else(|| SqlError::Unsupported(format!("column reference {expr}")))?;
+        match self.matches(table, name).as_slice() {
+            [index] => Ok(*index),
+            [] => Err(SqlError::UnknownColumn(expr.to_string())),
+            _ => Err(SqlError::AmbiguousColumn(expr.to_string())),
+        }
+    }
+
+    /// The scope of the rows of an expression table computing `columns`, followed by columns
+    /// not visible in the query.
+    fn computed(columns: Vec<(Option<String>, String, Type)>, hidden: &[Type]) -> Self {
+        let properties = flat_properties(
+            columns
+                .iter()
+                .map(|(_, _, dtype)| dtype.clone())
+                .chain(hidden.iter().cloned()),
+        );
+        let columns = columns
+            .into_iter()
+            .enumerate()
+            .map(|(index, (table, name, dtype))| ScopeColumn {
+                table,
+                name,
+                dtype,
+                path: vec![index],
+            })
+            .collect();
+        Self {
+            columns,
+            properties,
+        }
+    }
+}
+
+struct Aggregate {
+    reducer: Reducer,
+    input: Option<usize>,
+    dtype: Type,
+}
+
+/// The state of compiling a grouped query: the grouping expressions and the aggregates used
+/// so far, all computed from the rows before grouping.
+struct Grouping {
+    keys: Vec<SqlExpr>,
+    key_columns: Vec<Option<usize>>,
+    inputs: Vec<Typed>,
+    aggregates: Vec<Aggregate>,
+}
+
+impl Grouping {
+    fn new(scope: &Scope, keys: &[SqlExpr]) -> Result<Self, SqlError> {
+        let mut compiler = ExpressionCompiler::rows(scope);
+        let inputs = keys
+            .iter()
+            .map(|key| compiler.compile(key))
+            .collect::<Result<_, _>>()?;
+        let key_columns = keys
+            .iter()
+            .map(|key| {
+                column_reference(key)
+                    .is_some()
+                    .then(|| scope.resolve(key))
+                    .transpose()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            keys: keys.to_vec(),
+            key_columns,
+            inputs,
+            aggregates: Vec::new(),
+        })
+    }
+
+    fn key(&self, index: usize) -> Typed {
+        Typed::argument(index, self.inputs[index].dtype.clone())
+    }
+
+    fn add_aggregate(&mut self, reducer: Reducer, input: Option<Typed>, dtype: Type) -> Typed {
+        let input = input.map(|input| {
+            self.inputs.push(input);
+            self.inputs.len() - 1
+        });
+        self.aggregates.push(Aggregate {
+            reducer,
+            input,
+            dtype: dtype.clone(),
+        });
+        Typed::argument(self.keys.len() + self.aggregates.len() - 1, dtype)
+    }
+
+    fn output_types(&self) -> Vec<Type> {
+        self.inputs[..self.keys.len()]
+            .iter()
+            .map(|key| key.dtype.clone())
+            .chain(
+                self.aggregates
+                    .iter()
+                    .map(|aggregate| aggregate.dtype.clone()),
+            )
+            .collect()
+    }
+}
+
+/// Compiles the expressions over the columns of a scope, or, in grouped queries, over the
+/// grouping expressions and the aggregates.
+struct ExpressionCompiler<'a> {
+    scope: &'a Scope,
+    grouping: Option<&'a mut Grouping>,
+}
+
+impl<'a> ExpressionCompiler<'a> {
+    fn rows(scope: &'a Scope) -> Self {
+        Self {
+            scope,
+            grouping: None,
+        }
+    }
+
+    fn compile(&mut self, expr: &SqlExpr) -> Result<Typed, SqlError> {
+        if let Some(grouping) = &self.grouping {
+            if let Some(index) = grouping.keys.iter().position(|key| key == expr) {
+                return Ok(grouping.key(index));
+            }
+        }
+        match expr {
+            SqlExpr::Identifier(_) | SqlExpr::CompoundIdentifier(_) => {
+                let index = self.scope.resolve(expr)?;
+                match &self.grouping {
+                    None => Ok(Typed::argument(
+                        index,
+                        self.scope.columns[index].dtype.clone(),
+                    )),
+                    Some(grouping) => {
+                        let key = grouping
+                            .key_columns
+                            .iter()
+                            .position(|column| *column == Some(index))
+                            .ok_or_else(|| SqlError::NotGrouped(expr.to_string()))?;
+                        Ok(grouping.key(key))
+                    }
+                }
+            }
+            SqlExpr::Value(value) => compile_literal(value),
+            SqlExpr::Interval(interval) => compile_interval(interval),
+            SqlExpr::Nested(expr) => self.compile(expr),
+            SqlExpr::IsNull(expr) => {
+                let operand = self.compile(expr)?;
+                Ok(Typed::new(
+                    Expression::Bool(BoolExpression::IsNone(operand.expression)),
+                    Type::Bool,
+                ))
+            }
+            SqlExpr::IsNotNull(expr) => {
+                let operand = self.compile(expr)?;
+                Ok(Typed::new(
+                    Expression::Bool(BoolExpression::Not(is_none(&operand))),
+                    Type::Bool,
+                ))
+            }
+            SqlExpr::UnaryOp { op, expr } => {
+                let operand = self.compile(expr)?;
+                compile_unary(*op, operand)
+            }
+            SqlExpr::BinaryOp { left, op, right } => {
+                let left = self.compile(left)?;
+                let right = self.compile(right)?;
+                compile_binary(op, left, right)
+            }
+            SqlExpr::Function(function) => self.compile_function(function),
+            expr => Err(SqlError::Unsupported(format!("expression {expr}"))),
+        }
+    }
+
+    fn compile_function(&mut self, function: &Function) -> Result<Typed, SqlError> {
+        let name = function.name.to_string().to_lowercase();
+        let (is_distinct, arguments) = function_arguments(function)?;
+        if is_aggregate(function) {
+            return self.compile_aggregate(&name, is_distinct, &arguments);
+        }
+        if is_distinct {
+            return Err(SqlError::Unsupported(format!("DISTINCT in {name}")));
+        }
+        let arguments = arguments
+            .into_iter()
+            .map(|argument| match argument {
+                FunctionArgExpr::Expr(expr) => self.compile(expr),
+                _ => Err(SqlError::Unsupported(format!("* in arguments of {name}"))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        match (name.as_str(), <[Typed; 2]>::try_from(arguments)) {
+            ("tumble", Ok([time, size])) => compile_tumble(false, time, size),
+            ("tumble_end", Ok([time, size])) => compile_tumble(true, time, size),
+            _ => Err(SqlError::Unsupported(format!("function {function}"))),
+        }
+    }
+
+    fn compile_aggregate(
+        &mut self,
+        name: &str,
+        is_distinct: bool,
+        arguments: &[&FunctionArgExpr],
+    ) -> Result<Typed, SqlError> {
+        let unsupported = || SqlError::Unsupported(format!("this form of {name}"));
+        let input = match arguments {
+            [FunctionArgExpr::Wildcard] => None,
+            [FunctionArgExpr::Expr(expr)] => {
+                Some(ExpressionCompiler::rows(self.scope).compile(expr)?)
+            }
+            _ => return Err(unsupported()),
+        };
+        let Some(grouping) = self.grouping.as_deref_mut() else {
+            return Err(SqlError::MisplacedAggregate(name.to_uppercase()));
+        };
+        let sum_reducer = |input: &Typed| match input.dtype {
+            Type::Int => Ok((Reducer::IntSum, Type::Int)),
+            Type::Float => Ok((Reducer::FloatSum { strict: false }, Type::Float)),
+            _ => Err(invalid_types(&name.to_uppercase(), &[input])),
+        };
+        match (name, is_distinct, input) {
+            ("count", false, None) => Ok(grouping.add_aggregate(Reducer::Count, None, Type::Int)),
+            ("count", true, Some(input)) => {
+                Ok(grouping.add_aggregate(Reducer::CountDistinct, Some(input), Type::Int))
+            }
+            ("sum", false, Some(input)) => {
+                let (reducer, dtype) = sum_reducer(&input)?;
+                Ok(grouping.add_aggregate(reducer, Some(input), dtype))
+            }
+            ("avg", false, Some(input)) => {
+                let (reducer, dtype) = sum_reducer(&input)?;
+                let sum = grouping.add_aggregate(reducer, Some(input), dtype);
+                let count = grouping.add_aggregate(Reducer::Count, None, Type::Int);
+                Ok(Typed::new(
+                    Expression::Float(FloatExpression::TrueDiv(
+                        sum.as_float().expression,
+                        count.as_float().expression,
+                    )),
+                    Type::Float,
+                ))
+            }
+            ("min", false, Some(input)) => {
+                let dtype = input.dtype.clone();
+                Ok(grouping.add_aggregate(Reducer::Min, Some(input), dtype))
+            }
+            ("max", false, Some(input)) => {
+                let dtype = input.dtype.clone();
+                Ok(grouping.add_aggregate(Reducer::Max, Some(input), dtype))
+            }
+            _ => Err(unsupported()),
+        }
+    }
+
+    fn wildcard(&self, table: Option<&str>) -> Result<Vec<(String, Typed)>, SqlError> {
+        if self.grouping.is_some() {
+            return Err(SqlError::Unsupported("* in grouped queries".to_string()));
+        }
+        let columns: Vec<_> = self
+            .scope
+            .columns
+            .iter()
+            .enumerate()
+            .filter(|(_, column)| table.is_none_or(|table| column.table.as_deref() == Some(table)))
+            .map(|(index, column)| {
+                (
+                    column.name.clone(),
+                    Typed::argument(index, column.dtype.clone()),
+                )
+            })
+            .collect();
+        if let (Some(table), true) = (table, columns.is_empty()) {
+            return Err(SqlError::UnknownTable(table.to_string()));
+        }
+        Ok(columns)
+    }
+
+    fn compile_projection(
+        &mut self,
+        projection: &[SelectItem],
+    ) -> Result<Vec<(String, Typed)>, SqlError> {
+        let mut items = Vec::new();
+        for (position, item) in projection.iter().enumerate() {
+            match item {
+                SelectItem::UnnamedExpr(expr) => {
+                    let name = match column_reference(expr) {
+                        Some((_, name)) => name.to_string(),
+                        None => format!("_col_{position}"),
+                    };
+                    items.push((name, self.compile(expr)?));
+                }
+                SelectItem::ExprWithAlias { expr, alias } => {
+                    items.push((alias.value.clone(), self.compile(expr)?));
+                }
+                SelectItem::Wildcard(options) => {
+                    check_wildcard_options(options)?;
+                    items.extend(self.wildcard(None)?);
+                }
+                SelectItem::QualifiedWildcard(table, options) => {
+                    check_wildcard_options(options)?;
+                    items.extend(self.wildcard(Some(&table.to_string()))?);
+                }
+            }
+        }
+        Ok(items)
+    }
+}
+
+fn check_wildcard_options(options: &WildcardAdditionalOptions) -> Result<(), SqlError> {
+    if *options == WildcardAdditionalOptions::default() {
+        Ok(())
+    } else {
+        Err(SqlError::Unsupported(format!("wildcard options {options}")))
+    }
+}
+
+/// The columns of a side of a join, at `position` in the joined rows.
+fn join_side_columns(
+    scope: &Scope,
+    position: usize,
+    is_optional: bool,
+) -> impl Iterator<Item = ScopeColumn> + '_ {
+    scope.columns.iter().map(move |column| ScopeColumn {
+        table: column.table.clone(),
+        name: column.name.clone(),
+        dtype: if is_optional {
+            optionalize(&column.dtype)
+        } else {
+            column.dtype.clone()
+        },
+        path: [position].into_iter().chain(column.path.clone()).collect(),
+    })
+}
+
+fn conjuncts(expr: &SqlExpr) -> Vec<&SqlExpr> {
+    match expr {
+        SqlExpr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            let mut conjuncts = conjuncts(left);
+            conjuncts.extend(conjuncts(right));
+            conjuncts
+        }
+        SqlExpr::Nested(expr) => conjuncts(expr),
+        expr => vec![expr],
+    }
+}
+
+enum Step {
+    Join {
+        table: String,
+        left_paths: Vec<ColumnPath>,
+        right_paths: Vec<ColumnPath>,
+        join_type: JoinType,
+        properties: Arc<TableProperties>,
+    },
+    Expressions {
+        column_paths: Vec<ColumnPath>,
+        expressions: Vec<ExpressionData>,
+    },
+    Filter {
+        column_path: ColumnPath,
+        properties: Arc<TableProperties>,
+    },
+    GroupBy {
+        grouping_paths: Vec<ColumnPath>,
+        reducers: Vec<ReducerData>,
+        properties: Arc<TableProperties>,
+    },
+}
+
+/// A planned query, which can be built on the graph of each worker.
+pub struct QueryPlan {
+    source: String,
+    steps: Vec<Step>,
+    column_names: Vec<String>,
+    dtypes: Vec<Type>,
+}
+
+impl QueryPlan {
+    /// The names of the output columns.
+    pub fn column_names(&self) -> &[String] {
+        &self.column_names
+    }
+
+    /// The types of the output columns.
+    pub fn dtypes(&self) -> &[Type] {
+        &self.dtypes
+    }
+
+    /// The names of the tables read by the query.
+    pub fn tables(&self) -> Vec<&str> {
+        let mut tables = vec![self.source.as_str()];
+        for step in &self.steps {
+            if let Step::Join { table, .. } = step {
+                tables.push(table);
+            }
+        }
+        tables
+    }
+
+    /// Adds the query to the graph, reading the tables with the given handles. The rows of the
+    /// returned table are the tuples of the output columns.
+    pub fn build(
+        &self,
+        graph: &dyn Graph,
+        tables: &HashMap<String, TableHandle>,
+    ) -> Result<TableHandle, SqlError> {
+        let handle = |name: &str| {
+            tables
+                .get(name)
+                .copied()
+                .ok_or_else(|| SqlError::UnknownTable(name.to_string()))
+        };
+        let mut table = handle(&self.source)?;
+        for step in &self.steps {
+            table = match step {
+                Step::Join {
+                    table: right_table,
+                    left_paths,
+                    right_paths,
+                    join_type,
+                    properties,
+                } => graph.join_tables(
+                    JoinData::new(table, left_paths.clone()),
+                    JoinData::new(handle(right_table)?, right_paths.clone()),
+                    ShardPolicy::WholeKey,
+                    *join_type,
+                    JoinExactlyOnce::new(false, false),
+                    properties.clone(),
+                )?,
+                Step::Expressions {
+                    column_paths,
+                    expressions,
+                } => graph.expression_table(
+                    table,
+                    column_paths.clone(),
+                    expressions.clone(),
+                    true,
+                )?,
+                Step::Filter {
+                    column_path,
+                    properties,
+                } => graph.filter_table(table, column_path.clone(), properties.clone())?,
+                Step::GroupBy {
+                    grouping_paths,
+                    reducers,
+                    properties,
+                } => graph.group_by_table(
+                    table,
+                    grouping_paths.clone(),
+                    ShardPolicy::WholeKey,
+                    reducers.clone(),
+                    false,
+                    properties.clone(),
+                )?,
+            };
+        }
+        Ok(table)
+    }
+}
+
+struct Planner<'a> {
+    schemas: &'a HashMap<String, TableSchema>,
+    steps: Vec<Step>,
+}
+
+impl Planner<'_> {
+    fn table(&self, relation: &TableFactor) -> Result<(String, Scope), SqlError> {
+        let TableFactor::Table {
+            name, alias, args, ..
+        } = relation
+        else {
+            return Err(SqlError::Unsupported(format!("reading from {relation}")));
+        };
+        let [table] = name.0.as_slice() else {
+            return Err(SqlError::Unsupported(format!("table name {name}")));
+        };
+        if args.is_some() {
+            return Err(SqlError::Unsupported(format!("table function {name}")));
+        }
+        let schema = self
+            .schemas
+            .get(&table.value)
+            .ok_or_else(|| SqlError::UnknownTable(table.value.clone()))?;
+        let scope_name = alias
+            .as_ref()
+            .map_or_else(|| table.value.clone(), |alias| alias.name.value.clone());
+        Ok((table.value.clone(), Scope::table(scope_name, schema)))
+    }
+
+    fn push_expressions(&mut self, scope: &Scope, expressions: Vec<Typed>) {
+        let expressions = expressions
+            .into_iter()
+            .map(|expression| ExpressionData {
+                expression: expression.expression,
+                properties: Arc::new(column_properties(expression.dtype)),
+                append_only: false,
+                deterministic: true,
+                gil: false,
+            })
+            .collect();
+        self.steps.push(Step::Expressions {
+            column_paths: scope.paths(),
+            expressions,
+        });
+    }
+
+    fn plan_join(
+        &mut self,
+        left: &Scope,
+        right_table: String,
+        right: &Scope,
+        join_operator: &JoinOperator,
+    ) -> Result<Scope, SqlError> {
+        let (join_type, constraint) = match join_operator {
+            JoinOperator::Inner(constraint) => (JoinType::Inner, constraint),
+            JoinOperator::LeftOuter(constraint) => (JoinType::LeftOuter, constraint),
+            JoinOperator::RightOuter(constraint) => (JoinType::RightOuter, constraint),
+            JoinOperator::FullOuter(constraint) => (JoinType::FullOuter, constraint),
+            _ => return Err(SqlError::Unsupported("this kind of JOIN".to_string())),
+        };
+        let JoinConstraint::On(condition) = constraint else {
+            return Err(SqlError::Unsupported("JOIN without ON".to_string()));
+        };
+
+        let mut left_paths = Vec::new();
+        let mut right_paths = Vec::new();
+        for condition in conjuncts(condition) {
+            let unsupported = || {
+                SqlError::Unsupported(format!(
+                    "join condition {condition}, only equalities of columns"
+                ))
+            };
+            let SqlExpr::BinaryOp {
+                left: first,
+                op: BinaryOperator::Eq,
+                right: second,
+            } = condition
+            else {
+                return Err(unsupported());
+            };
+            // the side of a column, true for the right one
+            let side = |expr: &SqlExpr| {
+                let (table, name) = column_reference(expr).ok_or_else(unsupported)?;
+                match (
+                    left.matches(table, name).as_slice(),
+                    right.matches(table, name).as_slice(),
+                ) {
+                    ([index], []) => Ok((false, *index)),
+                    ([], [index]) => Ok((true, *index)),
+                    ([], []) => Err(SqlError::UnknownColumn(expr.to_string())),
+                    _ => Err(SqlError::AmbiguousColumn(expr.to_string())),
+                }
+            };
+            let (left_index, right_index) = match (side(first)?, side(second)?) {
+                ((false, left_index), (true, right_index))
+                | ((true, right_index), (false, left_index)) => (left_index, right_index),
+                _ => return Err(unsupported()),
+            };
+            left_paths.push(ColumnPath::ValuePath(left.columns[left_index].path.clone()));
+            right_paths.push(ColumnPath::ValuePath(
+                right.columns[right_index].path.clone(),
+            ));
+        }
+
+        // the joined rows are (left key, left row, right key, right row)
+        let is_left_optional = matches!(join_type, JoinType::RightOuter | JoinType::FullOuter);
+        let is_right_optional = matches!(join_type, JoinType::LeftOuter | JoinType::FullOuter);
+        let side_properties = |scope: &Scope, is_optional: bool| {
+            if is_optional {
+                optionalize_properties(&scope.properties)
+            } else {
+                scope.properties.as_ref().clone()
+            }
+        };
+        let pointer_properties = column_properties(Type::Pointer);
+        let properties = Arc::new(TableProperties::Table(
+            [
+                pointer_properties.clone(),
+                side_properties(left, is_left_optional),
+                pointer_properties,
+                side_properties(right, is_right_optional),
+            ]
+            .into(),
+            Arc::new(Trace::Empty),
+        ));
+        let columns = join_side_columns(left, 1, is_left_optional)
+            .chain(join_side_columns(right, 3, is_right_optional))
+            .collect();
+
+        self.steps.push(Step::Join {
+            table: right_table,
+            left_paths,
+            right_paths,
+            join_type,
+            properties: properties.clone(),
+        });
+        Ok(Scope {
+            columns,
+            properties,
+        })
+    }
+
+    fn plan_from(&mut self, from: &TableWithJoins) -> Result<(String, Scope), SqlError> {
+        let (source, mut scope) = self.table(&from.relation)?;
+        for join in &from.joins {
+            let (table, right) = self.table(&join.relation)?;
+            scope = self.plan_join(&scope, table, &right, &join.join_operator)?;
+        }
+        Ok((source, scope))
+    }
+
+    fn plan_filter(&mut self, scope: &Scope, predicate: Typed) -> Result<Scope, SqlError> {
+        if predicate.dtype.unoptionalize() != &Type::Bool {
+            return Err(invalid_types("WHERE", &[&predicate]));
+        }
+        let predicate = if predicate.dtype.is_optional() {
+            Typed::new(
+                Expression::Bool(BoolExpression::Eq(
+                    predicate.expression,
+                    Arc::new(Expression::Bool(BoolExpression::Const(true))),
+                )),
+                Type::Bool,
+            )
+        } else {
+            predicate
+        };
+
+        let mut expressions: Vec<_> = scope
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| Typed::argument(index, column.dtype.clone()))
+            .collect();
+        expressions.push(predicate);
+        self.push_expressions(scope, expressions);
+
+        let filtered = Scope::computed(
+            scope
+                .columns
+                .iter()
+                .map(|column| {
+                    (
+                        column.table.clone(),
+                        column.name.clone(),
+                        column.dtype.clone(),
+                    )
+                })
+                .collect(),
+            &[Type::Bool],
+        );
+        self.steps.push(Step::Filter {
+            column_path: ColumnPath::ValuePath(vec![scope.columns.len()]),
+            properties: filtered.properties.clone(),
+        });
+        Ok(filtered)
+    }
+
+    /// Plans the grouping and the aggregates, returning the scope of the grouped rows and the
+    /// output columns computed from them.
+    fn plan_grouped(
+        &mut self,
+        scope: &Scope,
+        keys: &[SqlExpr],
+        projection: &[SelectItem],
+        having: Option<&SqlExpr>,
+    ) -> Result<(Scope, Vec<(String, Typed)>), SqlError> {
+        let mut grouping = Grouping::new(scope, keys)?;
+        let (items, having) = {
+            let mut compiler = ExpressionCompiler {
+                scope,
+                grouping: Some(&mut grouping),
+            };
+            let items = compiler.compile_projection(projection)?;
+            let having = having.map(|having| compiler.compile(having)).transpose()?;
+            (items, having)
+        };
+
+        let key_count = grouping.keys.len();
+        let reducer_data = |reducer: Reducer, inputs: Vec<usize>| ReducerData {
+            reducer,
+            skip_errors: false,
+            append_only: false,
+            column_paths: inputs
+                .into_iter()
+                .map(|input| ColumnPath::ValuePath(vec![input]))
+                .collect(),
+            trace: Trace::Empty,
+        };
+        // the values of the keys are taken from any row of the group
+        let reducers: Vec<_> = (0..key_count)
+            .map(|index| reducer_data(Reducer::Any, vec![index]))
+            .chain(grouping.aggregates.iter().map(|aggregate| {
+                reducer_data(
+                    aggregate.reducer.clone(),
+                    aggregate.input.into_iter().collect(),
+                )
+            }))
+            .collect();
+        let output_types = grouping.output_types();
+        self.push_expressions(scope, grouping.inputs);
+
+        let grouped = Scope::computed(
+            output_types
+                .into_iter()
+                .enumerate()
+                .map(|(index, dtype)| (None, format!("_group_{index}"), dtype))
+                .collect(),
+            &[],
+        );
+        self.steps.push(Step::GroupBy {
+            grouping_paths: (0..key_count)
+                .map(|index| ColumnPath::ValuePath(vec![index]))
+                .collect(),
+            reducers,
+            properties: grouped.properties.clone(),
+        });
+
+        let grouped = match having {
+            Some(having) => self.plan_filter(&grouped, having)?,
+            None => grouped,
+        };
+        Ok((grouped, items))
+    }
+
+    fn plan(&mut self, query: &Query) -> Result<QueryPlan, SqlError> {
+        if query.with.is_some() {
+            return Err(SqlError::Unsupported("WITH".to_string()));
+        }
+        if query.order_by.is_some() {
+            return Err(SqlError::Unsupported("ORDER BY".to_string()));
+        }
+        if query.limit.is_some() || query.offset.is_some() || query.fetch.is_some() {
+            return Err(SqlError::Unsupported("LIMIT".to_string()));
+        }
+        let SetExpr::Select(select) = query.body.as_ref() else {
+            return Err(SqlError::Unsupported(format!("query {}", query.body)));
+        };
+        if select.distinct.is_some() {
+            return Err(SqlError::Unsupported("DISTINCT".to_string()));
+        }
+        if select.top.is_some() || select.into.is_some() {
+            return Err(SqlError::Unsupported("TOP and INTO".to_string()));
+        }
+        let [from] = select.from.as_slice() else {
+            return Err(SqlError::Unsupported(
+                "reading other than from a single table or a JOIN".to_string(),
+            ));
+        };
+        let GroupByExpr::Expressions(keys, modifiers) = &select.group_by else {
+            return Err(SqlError::Unsupported("GROUP BY ALL".to_string()));
+        };
+        if !modifiers.is_empty() {
+            return Err(SqlError::Unsupported("GROUP BY modifiers".to_string()));
+        }
+        if !select.named_window.is_empty() || select.qualify.is_some() {
+            return Err(SqlError::UnsupportedWindow(
+                "window functions with WINDOW or QUALIFY".to_string(),
+            ));
+        }
+        let projection = select.projection.iter().filter_map(|item| match item {
+            SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => Some(expr),
+            _ => None,
+        });
+        projection
+            .chain(keys)
+            .chain(&select.selection)
+            .chain(&select.having)
+            .try_for_each(check_windows)?;
+
+        let (source, mut scope) = self.plan_from(from)?;
+        if let Some(selection) = &select.selection {
+            let predicate = ExpressionCompiler::rows(&scope).compile(selection)?;
+            scope = self.plan_filter(&scope, predicate)?;
+        }
+
+        let is_grouped = !keys.is_empty()
+            || select.having.is_some()
+            || select.projection.iter().any(|item| match item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                    contains_aggregate(expr)
+                }
+                _ => false,
+            });
+        let (scope, items) = if is_grouped {
+            self.plan_grouped(&scope, keys, &select.projection, select.having.as_ref())?
+        } else {
+            let items = ExpressionCompiler::rows(&scope).compile_projection(&select.projection)?;
+            (scope, items)
+        };
+
+        let mut column_names: Vec<String> = Vec::with_capacity(items.len());
+        for (name, _) in &items {
+            if column_names.contains(name) {
+                return Err(SqlError::DuplicateColumn(name.clone()));
+            }
+            column_names.push(name.clone());
+        }
+        let dtypes = items.iter().map(|(_, item)| item.dtype.clone()).collect();
+        self.push_expressions(&scope, items.into_iter().map(|(_, item)| item).collect());
+
+        Ok(QueryPlan {
+            source,
+            steps: std::mem::take(&mut self.steps),
+            column_names,
+            dtypes,
+        })
+    }
+}
+
+/// Plans the query reading the tables with the given schemas.
+pub fn plan_query(
+    query: &str,
+    schemas: &HashMap<String, TableSchema>,
+) -> Result<QueryPlan, SqlError> {
+    let statements = Parser::parse_sql(&GenericDialect {}, query)?;
+    let [Statement::Query(query)] = statements.as_slice() else {
+        return Err(SqlError::NotAQuery);
+    };
+    Planner {
+        schemas,
+        steps: Vec::new(),
+    }
+    .plan(query)
+}
@@ -0,0 +1,80 @@
+// Copyright © 2024 Pathway
+
+//! A thread pool shared by the workers for evaluating the stateless parts of the dataflow, such
+//! as the expressions computing new columns. They don't keep any state between the batches, so
+//! the number of threads evaluating them can change while the computation is running, unlike
+//! the number of workers, which owns the state of the stateful operators.
+//!
+//! By default, the expressions are evaluated by the worker itself. If `stateless_threads` is set
+//! to more than one, the chunks of a batch are evaluated by a pool of that many threads, and the
+//! worker waits for the results. The setting is reloadable, see [`super::reload`].
+//!
+//! The expressions calling Python functions, including the asynchronous ones, are always
+//! evaluated by the worker. They rely on the state of the worker thread, like the runtime
+//! driving the asynchronous calls, and on the order in which the worker acquires the GIL.
+
+use std::sync::Arc;
+
+use arc_swap::ArcSwapOption;
+use log::{error, info};
+use once_cell::sync::Lazy;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+static POOL: Lazy<ArcSwapOption<ThreadPool>> = Lazy::new(ArcSwapOption::empty);
+
+/// Sets the number of threads evaluating the stateless parts of the dataflow. With `None` or one
+/// thread, the evaluation happens on the worker threads. The batches being evaluated keep the
+/// previous pool until they are done.
+pub fn resize(threads: Option<usize>) {
+    let pool = match threads {
+        Some(threads) if threads > 1 => {
+            match ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(|index| format!("pathway:stateless-{index}"))
+                .build()
+            {
+                Ok(pool) => Some(Arc::new(pool)),
+                Err(e) => {
+                    error!("Failed to create the pool of {threads} stateless threads: {e}");
+                    return;
+                }
+            }
+        }
+        _ => None,
+    };
+    match &pool {
+        Some(pool) => info!(
+            "Evaluating the stateless operators on {} threads",
+            pool.current_num_threads()
+        ),
+        None => info!("Evaluating the stateless operators on the worker threads"),
+    }
+    POOL.store(pool);
+}
+
+/// The number of threads of the pool, or `None` if the workers evaluate the expressions
+/// themselves.
+pub fn current_threads() -> Option<usize> {
+    POOL.load().as_ref().map(|pool| pool.current_num_threads())
+}
+
+/// Applies `f` to the chunks of `items` of at most `chunk_size` elements and concatenates the
+/// results, keeping their order. The chunks are processed in parallel if the pool is enabled,
+/// unless `on_worker` is set.
+pub fn map_chunks<T, U, F>(items: &[T], chunk_size: usize, on_worker: bool, f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&[T]) -> Vec<U> + Sync,
+{
+    let pool = if on_worker { None } else { POOL.load_full() };
+    match pool {
+        Some(pool) if items.len() > chunk_size => pool.install(|| {
+            let chunks: Vec<&[T]> = items.chunks(chunk_size).collect();
+            let results: Vec<Vec<U>> = chunks.par_iter().map(|chunk| f(chunk)).collect();
+            results.into_iter().flatten().collect()
+        }),
+        _ => items.chunks(chunk_size).flat_map(f).collect(),
+    }
+}
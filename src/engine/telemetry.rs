@@ -1,12 +1,26 @@
 use opentelemetry::InstrumentationScope;
 use std::{
-    sync::Arc,
+    collections::BTreeMap,
+    future::Future,
+    path::PathBuf,
+    sync::{
+        mpsc::{self as std_mpsc, RecvTimeoutError},
+        Arc, Mutex,
+    },
     thread::{Builder, JoinHandle},
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
-use super::{error::DynError, license::License, Graph, Result};
-use crate::{engine::dataflow::monitoring::ProberStats, env::parse_env_var};
+use super::{
+    error::DynError, file_exporter, health, license::License, prometheus, reload, spans, Graph,
+    Result,
+};
+use crate::{
+    engine::dataflow::memory::arrangements_memory,
+    engine::dataflow::monitoring::{processing_lag, ProberStats},
+    env::parse_env_var,
+    retry::{shared_circuit_breakers_stats, CircuitState},
+};
 use arc_swap::ArcSwapOption;
 use itertools::Itertools;
 use log::{debug, info};
@@ -29,9 +43,13 @@ use opentelemetry::{
 };
 use opentelemetry_otlp::{Protocol, WithExportConfig, WithTonicConfig};
 use opentelemetry_sdk::{
-    metrics::{PeriodicReader, SdkMeterProvider},
+    error::OTelSdkResult,
+    metrics::{
+        data::ResourceMetrics, exporter::PushMetricExporter, PeriodicReader, SdkMeterProvider,
+        Temporality,
+    },
     propagation::TraceContextPropagator,
-    trace::SdkTracerProvider,
+    trace::{Sampler, SdkTracerProvider},
     Resource,
 };
 use opentelemetry_semantic_conventions::resource::{
@@ -45,12 +63,24 @@ use uuid::Uuid;
 const PATHWAY_TELEMETRY_SERVER: &str = "https://usage.pathway.com";
 const PERIODIC_READER_INTERVAL: Duration = Duration::from_secs(60);
 const OPENTELEMETRY_EXPORT_TIMEOUT: Duration = Duration::from_secs(3);
+const SYS_SAMPLING_INTERVAL: Duration = Duration::from_secs(1);
 
 const PROCESS_MEMORY_USAGE: &str = "process.memory.usage";
 const PROCESS_CPU_USER_TIME: &str = "process.cpu.utime";
 const PROCESS_CPU_SYSTEM_TIME: &str = "process.cpu.stime";
 const INPUT_LATENCY: &str = "latency.input";
 const OUTPUT_LATENCY: &str = "latency.output";
+const INPUT_WATERMARK: &str = "watermark.input";
+const OUTPUT_WATERMARK: &str = "watermark.output";
+const INPUT_LAG: &str = "lag.input";
+const OUTPUT_LAG: &str = "lag.output";
+const COMMIT_EPOCH_DURATION: &str = "commit.epoch.duration";
+const CIRCUIT_BREAKER_STATE: &str = "circuit_breaker.state";
+const CIRCUIT_BREAKER_OPENED: &str = "circuit_breaker.opened";
+const CIRCUIT_BREAKER_REJECTED: &str = "circuit_breaker.rejected";
+const ARRANGEMENT_MEMORY: &str = "memory.arrangement";
+
+const CIRCUIT_BREAKER_NAME: &str = "circuit_breaker.name";
 
 const ROOT_TRACE_ID: &str = "root.trace.id";
 const RUN_ID: &str = "run.id";
@@ -92,19 +122,90 @@ fn get_process_cpu_times() -> Result<(i64, i64), &'static str> {
     }
 }
 
+/// Skips the exports until the interval set with [`reload`] has passed since the previous one,
+/// so that the metrics can be exported less often without restarting the computation. The
+/// metrics are cumulative, so nothing is lost when an export is skipped.
+struct ThrottledMetricExporter<E> {
+    inner: E,
+    base_interval: Duration,
+    last_export: Mutex<Option<Instant>>,
+}
+
+impl<E> ThrottledMetricExporter<E> {
+    fn new(inner: E, base_interval: Duration) -> Self {
+        Self {
+            inner,
+            base_interval,
+            last_export: Mutex::new(None),
+        }
+    }
+
+    fn should_export(&self) -> bool {
+        let Some(interval) = reload::metrics_export_interval() else {
+            return true;
+        };
+        let mut last_export = self.last_export.lock().unwrap();
+        // the reader ticks are not exact, so half of its interval is tolerated
+        if last_export.is_some_and(|last| last.elapsed() + self.base_interval / 2 < interval) {
+            return false;
+        }
+        *last_export = Some(Instant::now());
+        true
+    }
+}
+
+impl<E: PushMetricExporter> PushMetricExporter for ThrottledMetricExporter<E> {
+    fn export(&self, metrics: &ResourceMetrics) -> impl Future<Output = OTelSdkResult> + Send {
+        let should_export = self.should_export();
+        async move {
+            if should_export {
+                self.inner.export(metrics).await
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.inner.temporality()
+    }
+}
+
 struct Telemetry {
     pub config: Box<TelemetryEnabled>,
+    prometheus_reader: Option<prometheus::Reader>,
+    worker_index: usize,
 }
 
 impl Telemetry {
-    fn new(config: Box<TelemetryEnabled>) -> Self {
-        Telemetry { config }
+    fn new(config: Box<TelemetryEnabled>, worker_index: usize) -> Self {
+        let prometheus_reader = config.prometheus_port.map(|_| prometheus::Reader::new());
+        Telemetry {
+            config,
+            prometheus_reader,
+            worker_index,
+        }
+    }
+
+    /// Distinguishes the files written by the workers sharing the export directory.
+    fn export_file_name(&self) -> String {
+        format!("worker-{}", self.worker_index)
     }
 
     fn resource(&self) -> Resource {
         let root_trace_id = root_trace_id(self.config.trace_parent.as_deref()).unwrap_or_default();
 
+        // the custom attributes go first, so that they can't override the built-in ones
         Resource::builder()
+            .with_attributes(key_values(&self.config.resource_attributes))
             .with_attributes([
                 KeyValue::new(SERVICE_NAME, self.config.service_name.clone()),
                 KeyValue::new(SERVICE_VERSION, self.config.service_version.clone()),
@@ -118,12 +219,19 @@ impl Telemetry {
     }
 
     fn init_tracer_provider(&self) -> Option<SdkTracerProvider> {
-        if self.config.tracing_servers.is_empty() {
+        if self.config.tracing_servers.is_empty() && self.config.trace_export_dir().is_none() {
             return None;
         }
         global::set_text_map_propagator(TraceContextPropagator::new());
 
-        let mut provider_builder = SdkTracerProvider::builder().with_resource(self.resource());
+        // spans with a sampled parent, like the spans of the Python part of the run, are
+        // always sampled, so that traces are not broken
+        let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            self.config.sampling.trace_ratio,
+        )));
+        let mut provider_builder = SdkTracerProvider::builder()
+            .with_resource(self.resource())
+            .with_sampler(sampler);
 
         for endpoint in &self.config.tracing_servers {
             let exporter = opentelemetry_otlp::SpanExporter::builder()
@@ -138,13 +246,22 @@ impl Telemetry {
             provider_builder = provider_builder.with_batch_exporter(exporter);
         }
 
+        if let Some(export_dir) = self.config.trace_export_dir() {
+            let exporter =
+                file_exporter::SpanExporter::new(export_dir.clone(), &self.export_file_name());
+            provider_builder = provider_builder.with_batch_exporter(exporter);
+        }
+
         let tracer_provider = provider_builder.build();
         global::set_tracer_provider(tracer_provider.clone());
         Some(tracer_provider)
     }
 
     fn init_meter_provider(&self) -> Option<SdkMeterProvider> {
-        if self.config.metrics_servers.is_empty() {
+        if self.config.metrics_servers.is_empty()
+            && self.prometheus_reader.is_none()
+            && self.config.metric_export_dir().is_none()
+        {
             return None;
         }
 
@@ -160,6 +277,8 @@ impl Telemetry {
                 .build()
                 .expect("exporter initialization should not fail");
 
+            let exporter =
+                ThrottledMetricExporter::new(exporter, self.config.periodic_reader_interval);
             let reader = PeriodicReader::builder(exporter)
                 .with_interval(self.config.periodic_reader_interval)
                 .build();
@@ -167,6 +286,21 @@ impl Telemetry {
             provider_builder = provider_builder.with_reader(reader);
         }
 
+        if let Some(export_dir) = self.config.metric_export_dir() {
+            let exporter =
+                file_exporter::MetricExporter::new(export_dir.clone(), &self.export_file_name());
+            let exporter =
+                ThrottledMetricExporter::new(exporter, self.config.periodic_reader_interval);
+            let reader = PeriodicReader::builder(exporter)
+                .with_interval(self.config.periodic_reader_interval)
+                .build();
+            provider_builder = provider_builder.with_reader(reader);
+        }
+
+        if let Some(reader) = &self.prometheus_reader {
+            provider_builder = provider_builder.with_reader(reader.clone());
+        }
+
         let meter_provider = provider_builder.build();
         global::set_meter_provider(meter_provider.clone());
         Some(meter_provider)
@@ -178,10 +312,16 @@ impl Telemetry {
 
         let meter_provider = self.init_meter_provider();
         let tracer_provider = self.init_tracer_provider();
+        let prometheus_runner = self
+            .config
+            .prometheus_port
+            .zip(self.prometheus_reader.clone())
+            .map(|(port, reader)| prometheus::Runner::run(port, reader));
 
         TelemetryGuard {
             meter_provider,
             tracer_provider,
+            prometheus_runner,
             noop_meter_provider,
             noop_tracer_provider,
         }
@@ -202,12 +342,26 @@ impl MeterProvider for MeterProviderWrapper {
 struct TelemetryGuard {
     meter_provider: Option<SdkMeterProvider>,
     tracer_provider: Option<SdkTracerProvider>,
+    prometheus_runner: Option<prometheus::Runner>,
     noop_meter_provider: MeterProviderWrapper,
     noop_tracer_provider: SdkTracerProvider,
 }
 
+impl TelemetryGuard {
+    /// Creates a meter of this run's provider. The global provider is not used, as other
+    /// workers may replace it concurrently.
+    fn meter(&self, name: &'static str) -> Meter {
+        match &self.meter_provider {
+            Some(provider) => provider.meter(name),
+            None => global::meter(name),
+        }
+    }
+}
+
 impl Drop for TelemetryGuard {
     fn drop(&mut self) {
+        // the endpoint is stopped first, so that it doesn't read from a provider being shut down
+        self.prometheus_runner.take();
         if let Some(provider) = self.meter_provider.take() {
             provider.force_flush().unwrap_or(());
             provider.shutdown().unwrap_or(());
@@ -222,6 +376,13 @@ impl Drop for TelemetryGuard {
     }
 }
 
+fn key_values(attributes: &BTreeMap<String, String>) -> Vec<KeyValue> {
+    attributes
+        .iter()
+        .map(|(key, value)| KeyValue::new(key.clone(), value.clone()))
+        .collect()
+}
+
 fn root_trace_id(trace_parent: Option<&str>) -> Option<&str> {
     if let Some(trace_parent) = trace_parent {
         Some(
@@ -239,6 +400,74 @@ fn deduplicate(input: Vec<Option<String>>) -> Vec<String> {
     input.into_iter().flatten().sorted().dedup().collect()
 }
 
+/// Limits the amount of the exported telemetry.
+#[derive(Clone, Debug)]
+pub struct Sampling {
+    /// The fraction of the traces that are exported.
+    pub trace_ratio: f64,
+    /// If not empty, only the metrics matching one of the patterns are exported.
+    pub metric_allowlist: Vec<String>,
+    /// The metrics matching one of the patterns are not exported, even if allowlisted.
+    pub metric_denylist: Vec<String>,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Self {
+            trace_ratio: 1.0,
+            metric_allowlist: Vec::new(),
+            metric_denylist: Vec::new(),
+        }
+    }
+}
+
+/// Checks if the metric name matches the pattern. A pattern ending with `*` matches all the
+/// names starting with the rest of the pattern, other patterns have to be equal to the name.
+fn metric_name_matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    }
+}
+
+impl Sampling {
+    pub fn is_metric_enabled(&self, name: &str) -> bool {
+        let allowed = self.metric_allowlist.is_empty()
+            || self
+                .metric_allowlist
+                .iter()
+                .any(|pattern| metric_name_matches(pattern, name));
+        let denied = self
+            .metric_denylist
+            .iter()
+            .any(|pattern| metric_name_matches(pattern, name));
+        allowed && !denied
+    }
+}
+
+/// The signals that are exported. Each of them can be turned off independently of the others,
+/// without affecting the destinations of the rest.
+#[derive(Clone, Copy, Debug)]
+pub struct Signals {
+    /// Whether the usage telemetry is sent to the Pathway telemetry server.
+    pub usage_telemetry: bool,
+    /// Whether the metrics are pushed to the OTLP servers and the export directory. The
+    /// Prometheus endpoint is not affected, as it is only read on request.
+    pub metrics: bool,
+    /// Whether the spans are exported to the OTLP servers and the export directory.
+    pub traces: bool,
+}
+
+impl Default for Signals {
+    fn default() -> Self {
+        Self {
+            usage_telemetry: true,
+            metrics: true,
+            traces: true,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 #[allow(clippy::module_name_repetitions)]
 pub struct TelemetryEnabled {
@@ -255,6 +484,25 @@ pub struct TelemetryEnabled {
     pub trace_parent: Option<String>,
     pub license_key: String,
     pub periodic_reader_interval: Duration,
+    pub prometheus_port: Option<u16>,
+    pub resource_attributes: BTreeMap<String, String>,
+    pub metric_attributes: BTreeMap<String, String>,
+    pub sampling: Sampling,
+    /// The directory to which metrics and spans are written as JSON Lines files.
+    pub export_dir: Option<PathBuf>,
+    pub signals: Signals,
+}
+
+impl TelemetryEnabled {
+    /// The directory to which spans are written, if traces are enabled.
+    pub fn trace_export_dir(&self) -> Option<&PathBuf> {
+        self.export_dir.as_ref().filter(|_| self.signals.traces)
+    }
+
+    /// The directory to which metrics are written, if metrics are enabled.
+    pub fn metric_export_dir(&self) -> Option<&PathBuf> {
+        self.export_dir.as_ref().filter(|_| self.signals.metrics)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -264,12 +512,19 @@ pub enum Config {
 }
 
 impl Config {
+    #[allow(clippy::too_many_arguments)]
     pub fn create(
         license: &License,
         run_id: Option<String>,
         monitoring_server: Option<String>,
         trace_parent: Option<String>,
         periodic_reader_interval: Option<u64>,
+        prometheus_port: Option<u16>,
+        resource_attributes: BTreeMap<String, String>,
+        metric_attributes: BTreeMap<String, String>,
+        sampling: Sampling,
+        export_dir: Option<PathBuf>,
+        signals: Signals,
     ) -> Result<Self> {
         let run_id = run_id.unwrap_or_else(|| Uuid::new_v4().to_string());
 
@@ -279,13 +534,16 @@ impl Config {
                 .map_err(DynError::from)?;
         }
 
-        let telemetry_server = if license.telemetry_required() {
+        let telemetry_server = if signals.usage_telemetry && license.telemetry_required() {
             Some(PATHWAY_TELEMETRY_SERVER.to_string())
         } else {
             None
         };
 
-        if monitoring_server.is_none() && telemetry_server.is_none() {
+        // the local endpoints don't need any license
+        let local_export = prometheus_port.is_some() || export_dir.is_some();
+
+        if monitoring_server.is_none() && telemetry_server.is_none() && !local_export {
             return Ok(Config::Disabled);
         }
 
@@ -299,7 +557,7 @@ impl Config {
         };
 
         match license {
-            License::NoLicenseKey => Ok(Config::Disabled),
+            License::NoLicenseKey if !local_export => Ok(Config::Disabled),
             _ => Config::create_enabled(
                 run_id,
                 telemetry_server,
@@ -307,10 +565,17 @@ impl Config {
                 trace_parent,
                 license,
                 periodic_reader_interval,
+                prometheus_port,
+                resource_attributes,
+                metric_attributes,
+                sampling,
+                export_dir,
+                signals,
             ),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn create_enabled(
         run_id: String,
         telemetry_server: Option<String>,
@@ -318,6 +583,12 @@ impl Config {
         trace_parent: Option<String>,
         license: &License,
         periodic_reader_interval: Duration,
+        prometheus_port: Option<u16>,
+        resource_attributes: BTreeMap<String, String>,
+        metric_attributes: BTreeMap<String, String>,
+        sampling: Sampling,
+        export_dir: Option<PathBuf>,
+        signals: Signals,
     ) -> Result<Self> {
         let service_instance_id: String = parse_env_var("PATHWAY_SERVICE_INSTANCE_ID")
             .map_err(DynError::from)?
@@ -335,8 +606,16 @@ impl Config {
             telemetry_server: telemetry_server.clone(),
             monitoring_server: monitoring_server.clone(),
             logging_servers: deduplicate(vec![monitoring_server.clone()]),
-            tracing_servers: deduplicate(vec![telemetry_server.clone(), monitoring_server.clone()]),
-            metrics_servers: deduplicate(vec![telemetry_server, monitoring_server]),
+            tracing_servers: if signals.traces {
+                deduplicate(vec![telemetry_server.clone(), monitoring_server.clone()])
+            } else {
+                Vec::new()
+            },
+            metrics_servers: if signals.metrics {
+                deduplicate(vec![telemetry_server, monitoring_server])
+            } else {
+                Vec::new()
+            },
             service_name: env!("CARGO_PKG_NAME").to_string(),
             service_version: env!("CARGO_PKG_VERSION").to_string(),
             service_namespace,
@@ -345,6 +624,12 @@ impl Config {
             trace_parent,
             license_key: license.shortcut(),
             periodic_reader_interval,
+            prometheus_port,
+            resource_attributes,
+            metric_attributes,
+            sampling,
+            export_dir,
+            signals,
         })))
     }
 }
@@ -374,6 +659,7 @@ fn start_telemetry_thread(
     let handle: JoinHandle<()> = Builder::new()
         .name("pathway:telemetry_thread".to_string())
         .spawn(move || {
+            let _health_guard = health::TelemetryThreadGuard;
             tokio::runtime::Builder::new_multi_thread()
                 .enable_time()
                 .enable_io()
@@ -381,9 +667,30 @@ fn start_telemetry_thread(
                 .unwrap()
                 .block_on(async {
                     let (tx, mut rx) = mpsc::channel::<()>(1);
-                    let _telemetry_guard = telemetry.init();
-                    register_stats_metrics(&stats);
-                    register_sys_metrics();
+                    let telemetry_guard = telemetry.init();
+                    let attributes = key_values(&telemetry.config.metric_attributes);
+                    let sampling = &telemetry.config.sampling;
+                    register_stats_metrics(
+                        &telemetry_guard.meter("pathway-stats"),
+                        &stats,
+                        &attributes,
+                        sampling,
+                    );
+                    register_circuit_breaker_metrics(
+                        &telemetry_guard.meter("pathway-stats"),
+                        &attributes,
+                        sampling,
+                    );
+                    register_memory_metrics(
+                        &telemetry_guard.meter("pathway-stats"),
+                        &attributes,
+                        sampling,
+                    );
+                    let _sys_sampler = register_sys_metrics(
+                        &telemetry_guard.meter("pathway-sys"),
+                        &attributes,
+                        sampling,
+                    );
                     start_sender.send(tx).await.expect("should not fail");
                     rx.recv().await;
                 });
@@ -392,118 +699,392 @@ fn start_telemetry_thread(
     handle
 }
 
-fn register_stats_metrics(stats: &Arc<ArcSwapOption<ProberStats>>) {
-    let meter = global::meter("pathway-stats");
+/// Registers the latency gauges. The `attributes` are attached to every observation.
+fn register_stats_metrics(
+    meter: &Meter,
+    stats: &Arc<ArcSwapOption<ProberStats>>,
+    attributes: &[KeyValue],
+    sampling: &Sampling,
+) {
+    if sampling.is_metric_enabled(INPUT_LATENCY) {
+        let input_stats = stats.clone();
+        let input_attributes = attributes.to_vec();
+        meter
+            .u64_observable_gauge(INPUT_LATENCY)
+            .with_unit("ms")
+            .with_callback(move |observer| {
+                let now = SystemTime::now();
+                if let Some(ref stats) = *input_stats.load() {
+                    if let Some(latency) = stats.input_stats.latency(now) {
+                        observer.observe(latency, &input_attributes);
+                    }
+                }
+            })
+            .build();
+    }
 
-    let input_stats = stats.clone();
-    meter
-        .u64_observable_gauge(INPUT_LATENCY)
-        .with_unit("ms")
-        .with_callback(move |observer| {
-            let now = SystemTime::now();
-            if let Some(ref stats) = *input_stats.load() {
-                if let Some(latency) = stats.input_stats.latency(now) {
-                    observer.observe(latency, &[]);
+    if sampling.is_metric_enabled(OUTPUT_LATENCY) {
+        let output_stats = stats.clone();
+        let output_attributes = attributes.to_vec();
+        meter
+            .u64_observable_gauge(OUTPUT_LATENCY)
+            .with_unit("ms")
+            .with_callback(move |observer| {
+                let now = SystemTime::now();
+                if let Some(ref stats) = *output_stats.load() {
+                    if let Some(latency) = stats.output_stats.latency(now) {
+                        observer.observe(latency, &output_attributes);
+                    }
                 }
-            }
-        })
-        .build();
-
-    let output_stats = stats.clone();
-    meter
-        .u64_observable_gauge(OUTPUT_LATENCY)
-        .with_unit("ms")
-        .with_callback(move |observer| {
-            let now = SystemTime::now();
-            if let Some(ref stats) = *output_stats.load() {
-                if let Some(latency) = stats.output_stats.latency(now) {
-                    observer.observe(latency, &[]);
+            })
+            .build();
+    }
+
+    if sampling.is_metric_enabled(INPUT_WATERMARK) {
+        let watermark_stats = stats.clone();
+        let watermark_attributes = attributes.to_vec();
+        meter
+            .u64_observable_gauge(INPUT_WATERMARK)
+            .with_unit("ms")
+            .with_callback(move |observer| {
+                if let Some(ref stats) = *watermark_stats.load() {
+                    for (name, connector_stats) in &stats.connector_stats {
+                        if let Some(time) = connector_stats.time {
+                            observer
+                                .observe(time.0, &with_connector_name(&watermark_attributes, name));
+                        }
+                    }
                 }
-            }
-        })
-        .build();
-}
-
-fn cpu_refresh(pid: Pid, sys: &mut System) {
-    sys.refresh_processes_specifics(
-        ProcessesToUpdate::Some(&[pid]),
-        true,
-        ProcessRefreshKind::nothing().with_cpu(),
-    );
-    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
-    sys.refresh_processes_specifics(
-        ProcessesToUpdate::Some(&[pid]),
-        true,
-        ProcessRefreshKind::nothing().with_cpu(),
-    );
-}
-
-fn register_sys_metrics() {
-    let meter = global::meter("pathway-sys");
-
-    let pid = get_current_pid().expect("Failed to get current PID");
-
-    meter
-        .u64_observable_gauge(PROCESS_MEMORY_USAGE)
-        .with_unit("byte")
-        .with_callback(move |observer| {
-            let mut sys: System = System::new();
-            sys.refresh_processes_specifics(
-                ProcessesToUpdate::Some(&[pid]),
-                true,
-                ProcessRefreshKind::nothing().with_memory(),
-            );
-            if let Some(process) = sys.process(pid) {
-                observer.observe(process.memory(), &[]);
-            }
-        })
-        .build();
-
-    meter
-        .i64_observable_gauge(PROCESS_CPU_USER_TIME)
-        .with_unit("s")
-        .with_callback(move |observer| {
-            let mut sys: System = System::new();
-            cpu_refresh(pid, &mut sys);
-            
-            #[cfg(unix)]
-            {
-                let usage = getrusage(UsageWho::RUSAGE_SELF).expect("Failed to call getrusage");
-                observer.observe(usage.user_time().num_seconds(), &[]);
-            }
-            
-            #[cfg(windows)]
-            {
-                match get_process_cpu_times() {
-                    Ok((user_time, _)) => observer.observe(user_time, &[]),
-                    Err(_) => observer.observe(0, &[]),
+            })
+            .build();
+    }
+
+    if sampling.is_metric_enabled(OUTPUT_WATERMARK) {
+        let watermark_stats = stats.clone();
+        let watermark_attributes = attributes.to_vec();
+        meter
+            .u64_observable_gauge(OUTPUT_WATERMARK)
+            .with_unit("ms")
+            .with_callback(move |observer| {
+                if let Some(ref stats) = *watermark_stats.load() {
+                    for (name, output_stats) in &stats.output_connector_stats {
+                        if let Some(time) = output_stats.time {
+                            observer
+                                .observe(time.0, &with_connector_name(&watermark_attributes, name));
+                        }
+                    }
                 }
-            }
-        })
-        .build();
-
-    meter
-        .i64_observable_gauge(PROCESS_CPU_SYSTEM_TIME)
-        .with_unit("s")
-        .with_callback(move |observer| {
-            let mut sys: System = System::new();
-            cpu_refresh(pid, &mut sys);
-            
-            #[cfg(unix)]
-            {
-                let usage = getrusage(UsageWho::RUSAGE_SELF).expect("Failed to call getrusage");
-                observer.observe(usage.system_time().num_seconds(), &[]);
-            }
-            
-            #[cfg(windows)]
-            {
-                match get_process_cpu_times() {
-                    Ok((_, system_time)) => observer.observe(system_time, &[]),
-                    Err(_) => observer.observe(0, &[]),
+            })
+            .build();
+    }
+
+    if sampling.is_metric_enabled(INPUT_LAG) {
+        let lag_stats = stats.clone();
+        let lag_attributes = attributes.to_vec();
+        meter
+            .u64_observable_gauge(INPUT_LAG)
+            .with_unit("ms")
+            .with_callback(move |observer| {
+                let now = SystemTime::now();
+                if let Some(ref stats) = *lag_stats.load() {
+                    for (name, connector_stats) in &stats.connector_stats {
+                        if connector_stats.finished {
+                            continue;
+                        }
+                        if let Some(time) = connector_stats.time {
+                            observer.observe(
+                                processing_lag(time, now),
+                                &with_connector_name(&lag_attributes, name),
+                            );
+                        }
+                    }
                 }
-            }
-        })
-        .build();
+            })
+            .build();
+    }
+
+    if sampling.is_metric_enabled(OUTPUT_LAG) {
+        let lag_stats = stats.clone();
+        let lag_attributes = attributes.to_vec();
+        meter
+            .u64_observable_gauge(OUTPUT_LAG)
+            .with_unit("ms")
+            .with_callback(move |observer| {
+                let now = SystemTime::now();
+                if let Some(ref stats) = *lag_stats.load() {
+                    for (name, output_stats) in &stats.output_connector_stats {
+                        if let Some(lag) = output_stats.latency(now) {
+                            observer.observe(lag, &with_connector_name(&lag_attributes, name));
+                        }
+                    }
+                }
+            })
+            .build();
+    }
+
+    if sampling.is_metric_enabled(COMMIT_EPOCH_DURATION) {
+        let epoch_stats = stats.clone();
+        let epoch_attributes = attributes.to_vec();
+        meter
+            .u64_observable_gauge(COMMIT_EPOCH_DURATION)
+            .with_unit("ms")
+            .with_callback(move |observer| {
+                if let Some(ref stats) = *epoch_stats.load() {
+                    for (name, connector_stats) in &stats.connector_stats {
+                        if let Some(epoch_duration) = connector_stats.epoch_duration {
+                            observer.observe(
+                                epoch_duration,
+                                &with_connector_name(&epoch_attributes, name),
+                            );
+                        }
+                    }
+                }
+            })
+            .build();
+    }
+}
+
+/// The attributes of a per-connector data point.
+fn with_connector_name(attributes: &[KeyValue], name: &str) -> Vec<KeyValue> {
+    let mut attributes = attributes.to_vec();
+    attributes.push(KeyValue::new(spans::CONNECTOR_NAME, name.to_string()));
+    attributes
+}
+
+/// Registers the metrics of the circuit breakers shared by the sinks. The state is reported as
+/// 0 when closed, 1 when half-open and 2 when open.
+fn register_circuit_breaker_metrics(meter: &Meter, attributes: &[KeyValue], sampling: &Sampling) {
+    if sampling.is_metric_enabled(CIRCUIT_BREAKER_STATE) {
+        let state_attributes = attributes.to_vec();
+        meter
+            .u64_observable_gauge(CIRCUIT_BREAKER_STATE)
+            .with_callback(move |observer| {
+                for (name, stats) in shared_circuit_breakers_stats() {
+                    let state = match stats.state {
+                        CircuitState::Closed => 0,
+                        CircuitState::HalfOpen => 1,
+                        CircuitState::Open => 2,
+                    };
+                    observer.observe(state, &with_circuit_breaker_name(&state_attributes, name));
+                }
+            })
+            .build();
+    }
+
+    if sampling.is_metric_enabled(CIRCUIT_BREAKER_OPENED) {
+        let opened_attributes = attributes.to_vec();
+        meter
+            .u64_observable_counter(CIRCUIT_BREAKER_OPENED)
+            .with_callback(move |observer| {
+                for (name, stats) in shared_circuit_breakers_stats() {
+                    observer.observe(
+                        stats.times_opened,
+                        &with_circuit_breaker_name(&opened_attributes, name),
+                    );
+                }
+            })
+            .build();
+    }
+
+    if sampling.is_metric_enabled(CIRCUIT_BREAKER_REJECTED) {
+        let rejected_attributes = attributes.to_vec();
+        meter
+            .u64_observable_counter(CIRCUIT_BREAKER_REJECTED)
+            .with_callback(move |observer| {
+                for (name, stats) in shared_circuit_breakers_stats() {
+                    observer.observe(
+                        stats.rejected_calls,
+                        &with_circuit_breaker_name(&rejected_attributes, name),
+                    );
+                }
+            })
+            .build();
+    }
+}
+
+fn with_circuit_breaker_name(attributes: &[KeyValue], name: String) -> Vec<KeyValue> {
+    let mut attributes = attributes.to_vec();
+    attributes.push(KeyValue::new(CIRCUIT_BREAKER_NAME, name));
+    attributes
+}
+
+/// Registers the approximate sizes of the arrangements of this process, as last measured by
+/// the workers.
+fn register_memory_metrics(meter: &Meter, attributes: &[KeyValue], sampling: &Sampling) {
+    if sampling.is_metric_enabled(ARRANGEMENT_MEMORY) {
+        let memory_attributes = attributes.to_vec();
+        meter
+            .u64_observable_gauge(ARRANGEMENT_MEMORY)
+            .with_unit("By")
+            .with_callback(move |observer| {
+                for arrangement in arrangements_memory() {
+                    let mut attributes = memory_attributes.clone();
+                    if let Some(operator_id) = arrangement.operator_id {
+                        attributes.push(KeyValue::new(
+                            spans::OPERATOR_ID,
+                            i64::try_from(operator_id).unwrap(),
+                        ));
+                    }
+                    attributes.push(KeyValue::new(spans::OPERATOR_NAME, arrangement.name));
+                    attributes.push(KeyValue::new(
+                        spans::WORKER_ID,
+                        i64::try_from(arrangement.worker_id).unwrap(),
+                    ));
+                    observer.observe(arrangement.bytes, &attributes);
+                }
+            })
+            .build();
+    }
+}
+
+/// The resource usage of the process, as last seen by the [`SysSampler`].
+#[derive(Clone, Copy, Debug)]
+struct SysSnapshot {
+    memory: Option<u64>,
+    cpu_user_time: i64,
+    cpu_system_time: i64,
+}
+
+impl SysSnapshot {
+    fn take(pid: Pid, sys: &mut System) -> Self {
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[pid]),
+            true,
+            ProcessRefreshKind::nothing().with_memory(),
+        );
+        let memory = sys.process(pid).map(sysinfo::Process::memory);
+
+        #[cfg(unix)]
+        let (cpu_user_time, cpu_system_time) = {
+            let usage = getrusage(UsageWho::RUSAGE_SELF).expect("Failed to call getrusage");
+            (
+                usage.user_time().num_seconds(),
+                usage.system_time().num_seconds(),
+            )
+        };
+
+        #[cfg(windows)]
+        let (cpu_user_time, cpu_system_time) = get_process_cpu_times().unwrap_or((0, 0));
+
+        Self {
+            memory,
+            cpu_user_time,
+            cpu_system_time,
+        }
+    }
+}
+
+/// Samples the resource usage of the process in a background thread, so that the metric
+/// callbacks only read the latest snapshot and never block the exporter. Stops the thread when
+/// dropped.
+struct SysSampler {
+    snapshot: Arc<ArcSwapOption<SysSnapshot>>,
+    stop_sender: std_mpsc::Sender<()>,
+    thread_handle: Option<JoinHandle<()>>,
+}
+
+impl SysSampler {
+    fn start(interval: Duration) -> Self {
+        let pid = get_current_pid().expect("Failed to get current PID");
+        let mut sys = System::new();
+        // the first snapshot is taken right away, so that the metrics are available immediately
+        let snapshot = Arc::new(ArcSwapOption::from_pointee(SysSnapshot::take(
+            pid, &mut sys,
+        )));
+        let (stop_sender, stop_receiver) = std_mpsc::channel::<()>();
+        let thread_snapshot = snapshot.clone();
+        let thread_handle = Builder::new()
+            .name("pathway:sys_sampler".to_string())
+            .spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(interval) {
+                    thread_snapshot.store(Some(Arc::new(SysSnapshot::take(pid, &mut sys))));
+                }
+            })
+            .expect("sys sampler thread creation failed");
+        Self {
+            snapshot,
+            stop_sender,
+            thread_handle: Some(thread_handle),
+        }
+    }
+}
+
+impl Drop for SysSampler {
+    fn drop(&mut self) {
+        // the thread may have already stopped if the receiver was dropped
+        let _ = self.stop_sender.send(());
+        self.thread_handle
+            .take()
+            .unwrap()
+            .join()
+            .expect("sys sampler thread failed");
+    }
+}
+
+/// Registers the process metrics. Returns the sampler feeding them, which has to be kept alive
+/// as long as the metrics are exported, or `None` if all of them are disabled.
+fn register_sys_metrics(
+    meter: &Meter,
+    attributes: &[KeyValue],
+    sampling: &Sampling,
+) -> Option<SysSampler> {
+    let enabled = [
+        PROCESS_MEMORY_USAGE,
+        PROCESS_CPU_USER_TIME,
+        PROCESS_CPU_SYSTEM_TIME,
+    ]
+    .iter()
+    .any(|name| sampling.is_metric_enabled(name));
+    if !enabled {
+        return None;
+    }
+    let sampler = SysSampler::start(SYS_SAMPLING_INTERVAL);
+
+    if sampling.is_metric_enabled(PROCESS_MEMORY_USAGE) {
+        let memory_snapshot = sampler.snapshot.clone();
+        let memory_attributes = attributes.to_vec();
+        meter
+            .u64_observable_gauge(PROCESS_MEMORY_USAGE)
+            .with_unit("byte")
+            .with_callback(move |observer| {
+                if let Some(ref snapshot) = *memory_snapshot.load() {
+                    if let Some(memory) = snapshot.memory {
+                        observer.observe(memory, &memory_attributes);
+                    }
+                }
+            })
+            .build();
+    }
+
+    if sampling.is_metric_enabled(PROCESS_CPU_USER_TIME) {
+        let user_time_snapshot = sampler.snapshot.clone();
+        let user_time_attributes = attributes.to_vec();
+        meter
+            .i64_observable_gauge(PROCESS_CPU_USER_TIME)
+            .with_unit("s")
+            .with_callback(move |observer| {
+                if let Some(ref snapshot) = *user_time_snapshot.load() {
+                    observer.observe(snapshot.cpu_user_time, &user_time_attributes);
+                }
+            })
+            .build();
+    }
+
+    if sampling.is_metric_enabled(PROCESS_CPU_SYSTEM_TIME) {
+        let system_time_snapshot = sampler.snapshot.clone();
+        let system_time_attributes = attributes.to_vec();
+        meter
+            .i64_observable_gauge(PROCESS_CPU_SYSTEM_TIME)
+            .with_unit("s")
+            .with_callback(move |observer| {
+                if let Some(ref snapshot) = *system_time_snapshot.load() {
+                    observer.observe(snapshot.cpu_system_time, &system_time_attributes);
+                }
+            })
+            .build();
+    }
+
+    Some(sampler)
 }
 
 impl Drop for Runner {
@@ -519,7 +1100,20 @@ impl Drop for Runner {
 
 pub fn maybe_run_telemetry_thread(graph: &dyn Graph, config: Config) -> Option<Runner> {
     match config {
-        Config::Enabled(config) => {
+        Config::Enabled(mut config) => {
+            // the endpoint is served by the first worker of each process, on consecutive ports
+            let process_id = graph.worker_index() / graph.thread_count();
+            config.prometheus_port = config
+                .prometheus_port
+                .filter(|_| graph.worker_index() % graph.thread_count() == 0)
+                .map(|port| port + u16::try_from(process_id).unwrap());
+            if config.telemetry_server.is_none()
+                && config.monitoring_server.is_none()
+                && config.prometheus_port.is_none()
+                && config.export_dir.is_none()
+            {
+                return None;
+            }
             if config.telemetry_server.is_some() {
                 info!("Telemetry enabled");
             }
@@ -527,7 +1121,7 @@ pub fn maybe_run_telemetry_thread(graph: &dyn Graph, config: Config) -> Option<R
                 info!("Monitoring server: {monitoring_server}");
             }
 
-            let telemetry = Telemetry::new(config.clone());
+            let telemetry = Telemetry::new(config.clone(), graph.worker_index());
             let stats_shared = Arc::new(ArcSwapOption::from(None));
             let runner = Runner::run(telemetry, stats_shared.clone());
 
@@ -0,0 +1,378 @@
+// Copyright © 2024 Pathway
+
+//! Cache for the results of user-defined functions, keyed by the hash of the arguments.
+//!
+//! Entries are evicted when they're older than the configured TTL or, if the number of entries
+//! exceeds the configured bound, in the least-recently-used order. Optionally, the entries are
+//! also written to a persistence backend, so that the results survive restarts of the program.
+//! The persisted entries are bounded by the same limit and the oldest ones are removed first.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::error::DynResult;
+use super::{Key, KeyImpl, Value};
+use crate::persistence::backends::PersistenceBackend;
+
+#[derive(Debug, Clone, Default)]
+pub struct UdfCacheConfig {
+    /// Maximum number of entries kept in memory and in the persistence backend. `None` means
+    /// no limit.
+    pub max_entries: Option<usize>,
+    /// Time after which an entry is no longer used. `None` means entries don't expire.
+    pub ttl: Option<Duration>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedEntry {
+    value: Value,
+    created_at_ms: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    value: Value,
+    created_at_ms: u64,
+    last_use: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<Key, CacheEntry>,
+    by_last_use: BTreeMap<u64, Key>,
+    next_use: u64,
+}
+
+impl CacheState {
+    fn touch(&mut self, key: Key) -> Option<Value> {
+        let use_id = self.next_use;
+        let entry = self.entries.get_mut(&key)?;
+        let previous_use = std::mem::replace(&mut entry.last_use, use_id);
+        let value = entry.value.clone();
+        self.next_use += 1;
+        self.by_last_use.remove(&previous_use);
+        self.by_last_use.insert(use_id, key);
+        Some(value)
+    }
+
+    fn remove(&mut self, key: Key) {
+        if let Some(entry) = self.entries.remove(&key) {
+            self.by_last_use.remove(&entry.last_use);
+        }
+    }
+
+    fn insert(&mut self, key: Key, value: Value, created_at_ms: u64, max_entries: Option<usize>) {
+        self.remove(key);
+        let last_use = self.next_use;
+        self.next_use += 1;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                value,
+                created_at_ms,
+                last_use,
+            },
+        );
+        self.by_last_use.insert(last_use, key);
+        if let Some(max_entries) = max_entries {
+            while self.entries.len() > max_entries {
+                let (_, evicted) = self
+                    .by_last_use
+                    .pop_first()
+                    .expect("entries and use order must be in sync");
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+}
+
+/// The keys stored in the persistence backend, ordered by the creation time of the entries.
+#[derive(Debug, Default)]
+struct PersistedKeys {
+    by_age: BTreeSet<(u64, Key)>,
+    created_at_ms: HashMap<Key, u64>,
+}
+
+impl PersistedKeys {
+    fn remove(&mut self, key: Key) {
+        if let Some(created_at_ms) = self.created_at_ms.remove(&key) {
+            self.by_age.remove(&(created_at_ms, key));
+        }
+    }
+
+    /// Registers a persisted entry and returns the keys that have to be removed from the
+    /// backend to keep at most `max_entries` of them.
+    fn insert(&mut self, key: Key, created_at_ms: u64, max_entries: Option<usize>) -> Vec<Key> {
+        self.remove(key);
+        self.created_at_ms.insert(key, created_at_ms);
+        self.by_age.insert((created_at_ms, key));
+        let mut evicted = Vec::new();
+        if let Some(max_entries) = max_entries {
+            while self.by_age.len() > max_entries {
+                let (_, key) = self
+                    .by_age
+                    .pop_first()
+                    .expect("keys and their age must be in sync");
+                self.created_at_ms.remove(&key);
+                evicted.push(key);
+            }
+        }
+        evicted
+    }
+}
+
+#[derive(Debug)]
+pub struct UdfCache {
+    name: String,
+    config: UdfCacheConfig,
+    state: Mutex<CacheState>,
+    persisted: Mutex<PersistedKeys>,
+    backend: Option<Box<dyn PersistenceBackend>>,
+}
+
+fn now_ms() -> u64 {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+}
+
+impl UdfCache {
+    /// Creates a cache. The `name` distinguishes the entries of different functions stored in
+    /// the same persistence `backend`. The entries already stored in the backend are indexed, so
+    /// that they count towards `max_entries`, and the expired ones are removed.
+    pub fn new(
+        name: impl Into<String>,
+        config: UdfCacheConfig,
+        backend: Option<Box<dyn PersistenceBackend>>,
+    ) -> Self {
+        let cache = Self {
+            name: name.into(),
+            config,
+            state: Mutex::new(CacheState::default()),
+            persisted: Mutex::new(PersistedKeys::default()),
+            backend,
+        };
+        cache.restore_persisted_keys();
+        cache
+    }
+
+    fn restore_persisted_keys(&self) {
+        let Some(backend) = &self.backend else {
+            return;
+        };
+        let keys = match backend.list_keys() {
+            Ok(keys) => keys,
+            Err(e) => {
+                warn!(
+                    "Failed to list the cached results of UDF {}: {e}",
+                    self.name
+                );
+                return;
+            }
+        };
+        let prefix = self.persistent_key_prefix();
+        let now_ms = now_ms();
+        let mut evicted = Vec::new();
+        for persistent_key in keys {
+            let Some(key) = persistent_key
+                .strip_prefix(&prefix)
+                .and_then(|key| KeyImpl::from_str_radix(key, 16).ok())
+                .map(Key)
+            else {
+                continue;
+            };
+            match self.get_persisted(key) {
+                Some(entry) if !self.is_expired(entry.created_at_ms, now_ms) => {
+                    evicted.extend(self.persisted.lock().unwrap().insert(
+                        key,
+                        entry.created_at_ms,
+                        self.config.max_entries,
+                    ));
+                }
+                _ => evicted.push(key),
+            }
+        }
+        self.remove_persisted(&evicted);
+    }
+
+    fn is_expired(&self, created_at_ms: u64, now_ms: u64) -> bool {
+        self.config
+            .ttl
+            .is_some_and(|ttl| u128::from(now_ms.saturating_sub(created_at_ms)) >= ttl.as_millis())
+    }
+
+    fn persistent_key_prefix(&self) -> String {
+        format!("udf-cache/{}/", self.name)
+    }
+
+    fn persistent_key(&self, key: Key) -> String {
+        format!("{}{:x}", self.persistent_key_prefix(), key.0)
+    }
+
+    fn remove_persisted(&self, keys: &[Key]) {
+        let Some(backend) = &self.backend else {
+            return;
+        };
+        for key in keys {
+            let persistent_key = self.persistent_key(*key);
+            if let Err(e) = backend.remove_key(&persistent_key) {
+                warn!("Failed to remove cached UDF result {persistent_key}: {e}");
+            }
+        }
+    }
+
+    fn get_persisted(&self, key: Key) -> Option<PersistedEntry> {
+        let backend = self.backend.as_ref()?;
+        let persistent_key = self.persistent_key(key);
+        // a missing key is reported as an error by the backends, so read errors mean a miss
+        let serialized = backend.get_value(&persistent_key).ok()?;
+        match bincode::deserialize(&serialized) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("Failed to deserialize cached UDF result {persistent_key}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Returns the cached result for `args`, if present and not expired.
+    pub fn get(&self, args: &[Value]) -> Option<Value> {
+        let key = Key::for_values(args);
+        let now_ms = now_ms();
+        {
+            let mut state = self.state.lock().unwrap();
+            if let Some(entry) = state.entries.get(&key) {
+                if self.is_expired(entry.created_at_ms, now_ms) {
+                    state.remove(key);
+                } else {
+                    return state.touch(key);
+                }
+            }
+        }
+        // the backend is read without holding the lock, so that the other calls aren't blocked
+        let entry = self.get_persisted(key)?;
+        if self.is_expired(entry.created_at_ms, now_ms) {
+            self.persisted.lock().unwrap().remove(key);
+            self.remove_persisted(&[key]);
+            return None;
+        }
+        self.state.lock().unwrap().insert(
+            key,
+            entry.value.clone(),
+            entry.created_at_ms,
+            self.config.max_entries,
+        );
+        Some(entry.value)
+    }
+
+    fn persist(&self, backend: &dyn PersistenceBackend, key: Key, entry: &PersistedEntry) {
+        let persistent_key = self.persistent_key(key);
+        let serialized = match bincode::serialize(entry) {
+            Ok(serialized) => serialized,
+            Err(e) => {
+                warn!("Failed to serialize UDF result for caching: {e}");
+                return;
+            }
+        };
+        // the write is awaited, so that an evicted entry can't be written after its removal
+        match futures::executor::block_on(backend.put_value(&persistent_key, serialized)) {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Failed to persist cached UDF result {persistent_key}: {e}"),
+            Err(_) => warn!("Persisting cached UDF result {persistent_key} was cancelled"),
+        }
+        let evicted = self.persisted.lock().unwrap().insert(
+            key,
+            entry.created_at_ms,
+            self.config.max_entries,
+        );
+        self.remove_persisted(&evicted);
+    }
+
+    /// Stores the result of the call with `args`. If the cache is persisted, the entry is
+    /// written to the backend before returning.
+    pub fn put(&self, args: &[Value], value: Value) {
+        let key = Key::for_values(args);
+        let created_at_ms = now_ms();
+        if let Some(backend) = &self.backend {
+            let entry = PersistedEntry {
+                value: value.clone(),
+                created_at_ms,
+            };
+            self.persist(backend.as_ref(), key, &entry);
+        }
+        self.state
+            .lock()
+            .unwrap()
+            .insert(key, value, created_at_ms, self.config.max_entries);
+    }
+
+    /// Computes the results for a batch of calls, calling `compute` only for the calls
+    /// without a cached result. The calls repeated within the batch are computed once, unless
+    /// the first of them fails. Errors aren't cached.
+    pub fn apply(
+        &self,
+        input: &[&[Value]],
+        mut compute: impl FnMut(&[&[Value]]) -> Vec<DynResult<Value>>,
+    ) -> Vec<DynResult<Value>> {
+        let mut results: Vec<Option<DynResult<Value>>> =
+            input.iter().map(|args| self.get(args).map(Ok)).collect();
+        let mut first_positions: HashMap<Key, usize> = HashMap::new();
+        let mut missing_positions = Vec::new();
+        let mut repeated_positions = Vec::new();
+        for (position, args) in input.iter().enumerate() {
+            if results[position].is_some() {
+                continue;
+            }
+            match first_positions.entry(Key::for_values(args)) {
+                Entry::Occupied(first) => repeated_positions.push((position, *first.get())),
+                Entry::Vacant(first) => {
+                    first.insert(position);
+                    missing_positions.push(position);
+                }
+            }
+        }
+        self.compute_missing(input, &missing_positions, &mut results, &mut compute);
+
+        let mut failed_positions = Vec::new();
+        for (position, first_position) in repeated_positions {
+            match &results[first_position] {
+                Some(Ok(value)) => results[position] = Some(Ok(value.clone())),
+                _ => failed_positions.push(position),
+            }
+        }
+        self.compute_missing(input, &failed_positions, &mut results, &mut compute);
+
+        results
+            .into_iter()
+            .map(|result| result.expect("each call should have a result"))
+            .collect()
+    }
+
+    fn compute_missing(
+        &self,
+        input: &[&[Value]],
+        positions: &[usize],
+        results: &mut [Option<DynResult<Value>>],
+        compute: &mut impl FnMut(&[&[Value]]) -> Vec<DynResult<Value>>,
+    ) {
+        if positions.is_empty() {
+            return;
+        }
+        let missing_input: Vec<&[Value]> = positions.iter().map(|i| input[*i]).collect();
+        let computed = compute(&missing_input);
+        for ((position, args), result) in positions.iter().zip(missing_input).zip(computed) {
+            if let Ok(value) = &result {
+                if !matches!(value, Value::Error | Value::Pending) {
+                    self.put(args, value.clone());
+                }
+            }
+            results[*position] = Some(result);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright © 2024 Pathway
+
+//! Handling of errors raised by user-defined functions: retries with backoff and the action
+//! taken once the retries are exhausted.
+//!
+//! The delay between the attempts of an asynchronous function is a timer of the runtime
+//! executing the calls, so it doesn't block the worker thread or the other calls. Synchronous
+//! functions are called again on the rows whose calls failed, after a delay blocking the worker.
+//! The errors in the arguments of a call aren't raised by the function, they are neither retried
+//! nor handled by the policy.
+
+use std::future::Future;
+use std::thread;
+use std::time::Duration;
+
+use super::error::{DataError, DynError, DynResult};
+use super::Value;
+use crate::retry::{
+    RetryConfig, DEFAULT_JITTER, DEFAULT_SLEEP_BACKOFF_FACTOR, DEFAULT_SLEEP_INITIAL_DURATION,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UdfErrorAction {
+    /// The error is handled like any other error of the computation.
+    #[default]
+    Propagate,
+    /// The result of the call is replaced with the default value of the policy.
+    ReturnDefault,
+    /// The error is written to the error log and the result is an error value, even if the
+    /// computation is configured to terminate on errors.
+    SendToErrorLog,
+    /// The computation is stopped, even if it is not configured to terminate on errors.
+    Fail,
+}
+
+#[derive(Debug, Clone)]
+pub struct UdfErrorPolicy {
+    pub max_retries: usize,
+    pub retry_delay: Duration,
+    pub backoff_factor: f64,
+    pub jitter: Duration,
+    pub on_error: UdfErrorAction,
+    pub default_value: Value,
+}
+
+impl Default for UdfErrorPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            retry_delay: DEFAULT_SLEEP_INITIAL_DURATION,
+            backoff_factor: DEFAULT_SLEEP_BACKOFF_FACTOR,
+            jitter: DEFAULT_JITTER,
+            on_error: UdfErrorAction::Propagate,
+            default_value: Value::None,
+        }
+    }
+}
+
+/// Whether the error was raised by the function, rather than coming from its arguments.
+fn is_raised_by_udf(error: &DynError) -> bool {
+    !matches!(
+        error.downcast_ref::<DataError>(),
+        Some(DataError::ErrorInValue)
+    )
+}
+
+impl UdfErrorPolicy {
+    /// Handles the error of a call, after the retries, according to the policy.
+    pub fn handle(&self, result: DynResult<Value>) -> DynResult<Value> {
+        match result {
+            Err(error) if is_raised_by_udf(&error) => match self.on_error {
+                UdfErrorAction::Propagate => Err(error),
+                UdfErrorAction::ReturnDefault => Ok(self.default_value.clone()),
+                UdfErrorAction::SendToErrorLog => Err(DataError::ToErrorLog(error).into()),
+                UdfErrorAction::Fail => Err(DataError::Fatal(error).into()),
+            },
+            result => result,
+        }
+    }
+
+    /// Awaits the futures created by `call` until one of them succeeds or the retries are
+    /// exhausted. The delay between the attempts grows exponentially.
+    pub async fn retry<F, Fut>(&self, call: F) -> DynResult<Value>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = DynResult<Value>>,
+    {
+        let mut retry_config = RetryConfig::new(self.retry_delay, self.backoff_factor, self.jitter);
+        let mut result = call().await;
+        for _ in 0..self.max_retries {
+            match &result {
+                Err(error) if is_raised_by_udf(error) => {}
+                _ => break,
+            }
+            let Some(delay) = retry_config.next_delay() else {
+                break;
+            };
+            tokio::time::sleep(delay).await;
+            result = call().await;
+        }
+        result
+    }
+
+    /// Calls `call` on a batch of rows and then again on the rows whose calls failed, until
+    /// they succeed or the retries are exhausted. The delay between the attempts grows like
+    /// in [`Self::retry`], but it blocks the calling thread.
+    pub fn retry_batch<F>(&self, input: &[&[Value]], call: F) -> Vec<DynResult<Value>>
+    where
+        F: Fn(&[&[Value]]) -> Vec<DynResult<Value>>,
+    {
+        let mut retry_config = RetryConfig::new(self.retry_delay, self.backoff_factor, self.jitter);
+        let mut results = call(input);
+        for _ in 0..self.max_retries {
+            let failed: Vec<usize> = results
+                .iter()
+                .enumerate()
+                .filter(|(_, result)| matches!(result, Err(error) if is_raised_by_udf(error)))
+                .map(|(position, _)| position)
+                .collect();
+            if failed.is_empty() {
+                break;
+            }
+            let Some(delay) = retry_config.next_delay() else {
+                break;
+            };
+            thread::sleep(delay);
+            let rows: Vec<&[Value]> = failed.iter().map(|position| input[*position]).collect();
+            for (position, result) in failed.into_iter().zip(call(&rows)) {
+                results[position] = result;
+            }
+        }
+        results
+    }
+}
@@ -226,6 +226,8 @@ pub enum Value {
     Error,
     PyObjectWrapper(Handle<PyObjectWrapper>),
     Pending,
+    // placed last so that the serialized representation of other variants doesn't change
+    Float32Array(Handle<ArrayD<f32>>),
 }
 
 const _: () = assert!(align_of::<Value>() <= 16);
@@ -296,6 +298,14 @@ impl Value {
         }
     }
 
+    pub fn as_bytes(&self) -> DynResult<&Arc<[u8]>> {
+        if let Self::Bytes(b) = self {
+            Ok(b)
+        } else {
+            Err(self.type_mismatch("bytes"))
+        }
+    }
+
     pub fn as_tuple(&self) -> DynResult<&Arc<[Self]>> {
         if let Self::Tuple(t) = self {
             Ok(t)
@@ -357,6 +367,7 @@ impl Display for Value {
             Self::Tuple(vals) => write!(fmt, "({})", vals.iter().format(", ")),
             Self::IntArray(array) => write!(fmt, "{array}"),
             Self::FloatArray(array) => write!(fmt, "{array}"),
+            Self::Float32Array(array) => write!(fmt, "{array}"),
             Self::DateTimeNaive(date_time) => write!(fmt, "{date_time}"),
             Self::DateTimeUtc(date_time) => write!(fmt, "{date_time}"),
             Self::Duration(duration) => write!(fmt, "{duration}"),
@@ -386,6 +397,12 @@ impl From<f64> for Value {
     }
 }
 
+impl From<f32> for Value {
+    fn from(f: f32) -> Self {
+        Self::Float(f64::from(f).into())
+    }
+}
+
 impl From<OrderedFloat<f64>> for Value {
     fn from(f: OrderedFloat<f64>) -> Self {
         Self::Float(f)
@@ -440,6 +457,12 @@ impl From<ArrayD<f64>> for Value {
     }
 }
 
+impl From<ArrayD<f32>> for Value {
+    fn from(a: ArrayD<f32>) -> Self {
+        Self::Float32Array(Handle::new(a))
+    }
+}
+
 impl<T> From<Option<T>> for Value
 where
     T: Into<Value>,
@@ -504,6 +527,7 @@ pub enum Kind {
     Error,
     PyObjectWrapper,
     Pending,
+    Float32Array,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -588,6 +612,7 @@ impl Value {
             Self::Tuple(_) => Kind::Tuple,
             Self::IntArray(_) => Kind::IntArray,
             Self::FloatArray(_) => Kind::FloatArray,
+            Self::Float32Array(_) => Kind::Float32Array,
             Self::DateTimeNaive(_) => Kind::DateTimeNaive,
             Self::DateTimeUtc(_) => Kind::DateTimeUtc,
             Self::Duration(_) => Kind::Duration,
@@ -623,6 +648,20 @@ impl HashInto for f64 {
     }
 }
 
+impl HashInto for f32 {
+    fn hash_into(&self, hasher: &mut Hasher) {
+        #[allow(clippy::float_cmp)]
+        let raw = if self.is_nan() {
+            !0
+        } else if self == &0.0 {
+            0 // -0.0 and 0.0 should hash to the same value
+        } else {
+            self.to_bits()
+        };
+        raw.hash_into(hasher);
+    }
+}
+
 impl HashInto for OrderedFloat<f64> {
     fn hash_into(&self, hasher: &mut Hasher) {
         self.0.hash_into(hasher);
@@ -733,6 +772,7 @@ impl HashInto for Value {
             Self::Tuple(vals) => vals.hash_into(hasher),
             Self::IntArray(handle) => handle.hash_into(hasher),
             Self::FloatArray(handle) => handle.hash_into(hasher),
+            Self::Float32Array(handle) => handle.hash_into(hasher),
             Self::DateTimeNaive(date_time) => date_time.hash_into(hasher),
             Self::DateTimeUtc(date_time) => date_time.hash_into(hasher),
             Self::Duration(duration) => duration.hash_into(hasher),
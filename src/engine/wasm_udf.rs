@@ -0,0 +1,271 @@
+// Copyright © 2024 Pathway
+
+//! Execution of user-defined functions compiled to WebAssembly.
+//!
+//! Each call runs in a fresh instance of the module, so calls can't affect each other, and is
+//! bounded by a memory limit and a time limit. A module has to export its linear memory as
+//! `memory`, an allocator `alloc(len: i32) -> i32` and the functions themselves with the
+//! signature `(ptr: i32, len: i32) -> i64`. The arguments are passed as a JSON array written
+//! to a buffer obtained from `alloc`. The result is a JSON value whose location is returned
+//! packed as `(ptr << 32) | len`.
+//!
+//! All modules are compiled by a single engine whose epoch is advanced by one ticker thread,
+//! and a module is compiled only once per process, no matter how many expressions use it.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use arcstr::ArcStr;
+use once_cell::sync::{Lazy, OnceCell};
+use serde_json::{Number as JsonNumber, Value as JsonValue};
+use wasmtime::{
+    Config, Engine, InstancePre, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap,
+};
+
+use super::error::{DynError, DynResult};
+use super::Value;
+
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+const ALLOC_EXPORT: &str = "alloc";
+const MEMORY_EXPORT: &str = "memory";
+
+#[derive(Debug, Clone)]
+pub struct WasmUdfLimits {
+    /// Maximum size of the linear memory of a single instance, in bytes.
+    pub max_memory_bytes: usize,
+    /// Maximum wall-clock duration of a single call.
+    pub timeout: Duration,
+}
+
+impl Default for WasmUdfLimits {
+    fn default() -> Self {
+        Self {
+            max_memory_bytes: 64 * 1024 * 1024,
+            timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WasmUdfError {
+    #[error("failed to load WASM module {path:?}: {message}")]
+    Load { path: PathBuf, message: String },
+
+    #[error("function {0:?} not exported by the WASM module")]
+    FunctionNotFound(String),
+
+    #[error("value {0:?} can't be passed to a WASM UDF")]
+    UnsupportedArgument(Value),
+
+    #[error("WASM UDF returned an unsupported value: {0}")]
+    UnsupportedResult(JsonValue),
+
+    #[error("WASM UDF returned a result outside of its memory")]
+    ResultOutOfBounds,
+
+    #[error("WASM UDF exceeded the time limit of {0:?}")]
+    TimedOut(Duration),
+
+    #[error("WASM UDF failed: {0}")]
+    CallFailed(String),
+}
+
+struct StoreState {
+    limits: StoreLimits,
+}
+
+static ENGINE: OnceCell<Engine> = OnceCell::new();
+
+static COMPILED_MODULES: Lazy<Mutex<HashMap<PathBuf, InstancePre<StoreState>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the engine shared by all the modules, starting its epoch ticker on first use.
+fn shared_engine() -> Result<&'static Engine, wasmtime::Error> {
+    ENGINE.get_or_try_init(|| {
+        let mut config = Config::new();
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config)?;
+        let ticker_engine = engine.clone();
+        thread::Builder::new()
+            .name("pathway:wasm_udf_epoch".to_string())
+            .spawn(move || loop {
+                ticker_engine.increment_epoch();
+                thread::sleep(EPOCH_TICK);
+            })
+            .expect("epoch ticker thread creation should not fail");
+        Ok(engine)
+    })
+}
+
+/// A compiled WASM module together with the limits applied to its calls.
+pub struct WasmUdfModule {
+    engine: &'static Engine,
+    instance_pre: InstancePre<StoreState>,
+    limits: WasmUdfLimits,
+}
+
+impl WasmUdfModule {
+    /// Loads the module at `path`, reusing an already compiled instance if there is one.
+    pub fn load(path: impl AsRef<Path>, limits: WasmUdfLimits) -> Result<Arc<Self>, WasmUdfError> {
+        let path = path.as_ref();
+        let load_error = |error: wasmtime::Error| WasmUdfError::Load {
+            path: path.to_path_buf(),
+            message: format!("{error:#}"),
+        };
+        let engine = shared_engine().map_err(load_error)?;
+        let mut compiled_modules = COMPILED_MODULES.lock().unwrap();
+        let instance_pre = if let Some(instance_pre) = compiled_modules.get(path) {
+            instance_pre.clone()
+        } else {
+            let module = Module::from_file(engine, path).map_err(load_error)?;
+            // Modules get no imports, so they can't interact with the host in any other way.
+            let instance_pre = Linker::new(engine)
+                .instantiate_pre(&module)
+                .map_err(load_error)?;
+            compiled_modules.insert(path.to_path_buf(), instance_pre.clone());
+            instance_pre
+        };
+        Ok(Arc::new(Self {
+            engine,
+            instance_pre,
+            limits,
+        }))
+    }
+
+    pub fn function(self: &Arc<Self>, name: &str) -> Result<WasmUdf, WasmUdfError> {
+        let has_export = self
+            .instance_pre
+            .module()
+            .exports()
+            .any(|export| export.name() == name);
+        if !has_export {
+            return Err(WasmUdfError::FunctionNotFound(name.to_string()));
+        }
+        Ok(WasmUdf {
+            module: self.clone(),
+            name: name.to_string(),
+        })
+    }
+
+    fn call(&self, name: &str, input: &[u8]) -> Result<Vec<u8>, WasmUdfError> {
+        let call_error = |error: wasmtime::Error| {
+            if matches!(error.downcast_ref::<Trap>(), Some(Trap::Interrupt)) {
+                WasmUdfError::TimedOut(self.limits.timeout)
+            } else {
+                WasmUdfError::CallFailed(format!("{error:#}"))
+            }
+        };
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.limits.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store = Store::new(self.engine, StoreState { limits });
+        store.limiter(|state| &mut state.limits);
+        let ticks = self.limits.timeout.as_millis() / EPOCH_TICK.as_millis();
+        store.set_epoch_deadline(u64::try_from(ticks).unwrap_or(u64::MAX).max(1));
+
+        let instance = self
+            .instance_pre
+            .instantiate(&mut store)
+            .map_err(call_error)?;
+        let memory = instance
+            .get_memory(&mut store, MEMORY_EXPORT)
+            .ok_or_else(|| WasmUdfError::FunctionNotFound(MEMORY_EXPORT.to_string()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, ALLOC_EXPORT)
+            .map_err(|_| WasmUdfError::FunctionNotFound(ALLOC_EXPORT.to_string()))?;
+        let function = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, name)
+            .map_err(|_| WasmUdfError::FunctionNotFound(name.to_string()))?;
+
+        let input_len = i32::try_from(input.len())
+            .map_err(|_| WasmUdfError::CallFailed("arguments too large".to_string()))?;
+        let input_ptr = alloc.call(&mut store, input_len).map_err(call_error)?;
+        memory
+            .write(&mut store, to_offset(input_ptr.cast_unsigned())?, input)
+            .map_err(|_| WasmUdfError::ResultOutOfBounds)?;
+        let packed = function
+            .call(&mut store, (input_ptr, input_len))
+            .map_err(call_error)?
+            .cast_unsigned();
+        let output_ptr = to_offset(packed >> 32)?;
+        let output_len = to_offset(packed & u64::from(u32::MAX))?;
+        let mut output = vec![0; output_len];
+        memory
+            .read(&store, output_ptr, &mut output)
+            .map_err(|_| WasmUdfError::ResultOutOfBounds)?;
+        Ok(output)
+    }
+}
+
+fn to_offset(offset: impl TryInto<usize>) -> Result<usize, WasmUdfError> {
+    offset
+        .try_into()
+        .map_err(|_| WasmUdfError::ResultOutOfBounds)
+}
+
+/// A single function exported by a [`WasmUdfModule`].
+#[derive(Clone)]
+pub struct WasmUdf {
+    module: Arc<WasmUdfModule>,
+    name: String,
+}
+
+impl WasmUdf {
+    pub fn call(&self, args: &[Value]) -> DynResult<Value> {
+        let args = args
+            .iter()
+            .map(value_to_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        let input = serde_json::to_vec(&args)?;
+        let output = self.module.call(&self.name, &input)?;
+        let result: JsonValue = serde_json::from_slice(&output)?;
+        json_to_value(result).map_err(DynError::from)
+    }
+}
+
+fn value_to_json(value: &Value) -> Result<JsonValue, WasmUdfError> {
+    let result = match value {
+        Value::None => JsonValue::Null,
+        Value::Bool(b) => JsonValue::Bool(*b),
+        Value::Int(i) => JsonValue::from(*i),
+        Value::Float(f) => JsonNumber::from_f64(**f)
+            .map(JsonValue::Number)
+            .ok_or_else(|| WasmUdfError::UnsupportedArgument(value.clone()))?,
+        Value::String(s) => JsonValue::String(s.to_string()),
+        Value::Json(json) => (**json).clone(),
+        Value::Tuple(values) => {
+            JsonValue::Array(values.iter().map(value_to_json).collect::<Result<_, _>>()?)
+        }
+        other => return Err(WasmUdfError::UnsupportedArgument(other.clone())),
+    };
+    Ok(result)
+}
+
+fn json_to_value(json: JsonValue) -> Result<Value, WasmUdfError> {
+    let result = match json {
+        JsonValue::Null => Value::None,
+        JsonValue::Bool(b) => Value::Bool(b),
+        JsonValue::Number(ref n) => {
+            if let Some(i) = n.as_i64() {
+                Value::Int(i)
+            } else if let Some(f) = n.as_f64() {
+                Value::from(f)
+            } else {
+                return Err(WasmUdfError::UnsupportedResult(json));
+            }
+        }
+        JsonValue::String(s) => Value::String(ArcStr::from(s)),
+        JsonValue::Array(values) => Value::Tuple(
+            values
+                .into_iter()
+                .map(json_to_value)
+                .collect::<Result<_, _>>()?,
+        ),
+        JsonValue::Object(_) => Value::from(json),
+    };
+    Ok(result)
+}
@@ -1,7 +1,130 @@
+//! Reading the settings of the run from the `PATHWAY_*` environment variables and from the
+//! configuration file.
+//!
+//! The configuration file is a TOML file pointed to by `PATHWAY_CONFIG_FILE`. Its keys are
+//! grouped in sections, and each key corresponds to one environment variable, e.g.
+//! `runtime.threads` to `PATHWAY_THREADS`. The environment variables take precedence over the
+//! file, so that a single setting can be overridden without editing it.
+
+use std::collections::HashMap;
 use std::env;
 use std::error;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use once_cell::sync::OnceCell;
+use toml::Value;
+
+pub const CONFIG_FILE_ENV: &str = "PATHWAY_CONFIG_FILE";
+
+/// The keys allowed in the configuration file, with the environment variables they correspond
+/// to.
+pub const CONFIG_KEYS: &[(&str, &str)] = &[
+    ("runtime.threads", "PATHWAY_THREADS"),
+    ("runtime.processes", "PATHWAY_PROCESSES"),
+    ("runtime.first_port", "PATHWAY_FIRST_PORT"),
+    ("runtime.cluster_addresses", "PATHWAY_CLUSTER_ADDRESSES"),
+    ("runtime.cluster_dns", "PATHWAY_CLUSTER_DNS"),
+    (
+        "runtime.cluster_discovery_timeout_seconds",
+        "PATHWAY_CLUSTER_DISCOVERY_TIMEOUT_SECONDS",
+    ),
+    ("runtime.cluster_tls_cert", "PATHWAY_CLUSTER_TLS_CERT"),
+    ("runtime.cluster_tls_key", "PATHWAY_CLUSTER_TLS_KEY"),
+    ("runtime.cluster_tls_ca", "PATHWAY_CLUSTER_TLS_CA"),
+    (
+        "runtime.cluster_tls_server_name",
+        "PATHWAY_CLUSTER_TLS_SERVER_NAME",
+    ),
+    ("runtime.health_port", "PATHWAY_HEALTH_PORT"),
+    (
+        "runtime.readiness_max_stall_seconds",
+        "PATHWAY_READINESS_MAX_STALL_SECONDS",
+    ),
+    ("runtime.terminate_on_error", "PATHWAY_TERMINATE_ON_ERROR"),
+    (
+        "runtime.suppress_other_worker_errors",
+        "PATHWAY_SUPPRESS_OTHER_WORKER_ERRORS",
+    ),
+    ("runtime.ignore_asserts", "PATHWAY_IGNORE_ASSERTS"),
+    (
+        "runtime.runtime_typechecking",
+        "PATHWAY_RUNTIME_TYPECHECKING",
+    ),
+    (
+        "runtime.async_worker_threads_per_runtime",
+        "PATHWAY_ASYNC_WORKER_THREADS_PER_RUNTIME",
+    ),
+    (
+        "runtime.async_max_blocking_threads",
+        "PATHWAY_ASYNC_MAX_BLOCKING_THREADS",
+    ),
+    (
+        "runtime.async_thread_name_prefix",
+        "PATHWAY_ASYNC_THREAD_NAME_PREFIX",
+    ),
+    (
+        "runtime.retry_budget_per_minute",
+        "PATHWAY_RETRY_BUDGET_PER_MINUTE",
+    ),
+    ("runtime.mat_mul_backend", "PATHWAY_MAT_MUL_BACKEND"),
+    ("runtime.log_format", "PATHWAY_LOG_FORMAT"),
+    ("runtime.log_level", "PATHWAY_LOG_LEVEL"),
+    ("runtime.max_batch_size", "PATHWAY_MAX_BATCH_SIZE"),
+    ("runtime.spill_directory", "PATHWAY_SPILL_DIRECTORY"),
+    ("runtime.spill_hot_entries", "PATHWAY_SPILL_HOT_ENTRIES"),
+    ("runtime.stateless_threads", "PATHWAY_STATELESS_THREADS"),
+    (
+        "runtime.arrangement_memory_soft_limit_mb",
+        "PATHWAY_ARRANGEMENT_MEMORY_SOFT_LIMIT_MB",
+    ),
+    (
+        "runtime.string_interning_max_distinct",
+        "PATHWAY_STRING_INTERNING_MAX_DISTINCT",
+    ),
+    ("persistence.mode", "PATHWAY_PERSISTENCE_MODE"),
+    ("persistence.snapshot_access", "PATHWAY_SNAPSHOT_ACCESS"),
+    ("persistence.replay_storage", "PATHWAY_REPLAY_STORAGE"),
+    (
+        "persistence.continue_after_replay",
+        "PATHWAY_CONTINUE_AFTER_REPLAY",
+    ),
+    ("telemetry.monitoring_server", "PATHWAY_MONITORING_SERVER"),
+    ("telemetry.service_namespace", "PATHWAY_SERVICE_NAMESPACE"),
+    (
+        "telemetry.service_instance_id",
+        "PATHWAY_SERVICE_INSTANCE_ID",
+    ),
+    (
+        "telemetry.resource_attributes",
+        "PATHWAY_RESOURCE_ATTRIBUTES",
+    ),
+    ("telemetry.metric_attributes", "PATHWAY_METRIC_ATTRIBUTES"),
+    ("telemetry.trace_sample_ratio", "PATHWAY_TRACE_SAMPLE_RATIO"),
+    ("telemetry.metric_allowlist", "PATHWAY_METRIC_ALLOWLIST"),
+    ("telemetry.metric_denylist", "PATHWAY_METRIC_DENYLIST"),
+    ("telemetry.export_dir", "PATHWAY_TELEMETRY_EXPORT_DIR"),
+    (
+        "telemetry.usage_telemetry_enabled",
+        "PATHWAY_USAGE_TELEMETRY_ENABLED",
+    ),
+    ("telemetry.metrics_enabled", "PATHWAY_METRICS_ENABLED"),
+    ("telemetry.traces_enabled", "PATHWAY_TRACES_ENABLED"),
+    (
+        "telemetry.metrics_reader_interval_seconds",
+        "PATHWAY_METRICS_READER_INTERVAL_SECONDS",
+    ),
+    (
+        "telemetry.tracing_min_span_ms",
+        "PATHWAY_TRACING_MIN_SPAN_MS",
+    ),
+    ("telemetry.prometheus_port", "PATHWAY_PROMETHEUS_PORT"),
+    ("license.key", "PATHWAY_LICENSE_KEY"),
+];
+
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
 pub enum Error {
@@ -13,9 +136,167 @@ pub enum Error {
 
     #[error("environment variable {0:?} is not set")]
     NotSet(String),
+
+    #[error("couldn't read the configuration file {0:?}: {1}")]
+    ConfigFileReadFailed(PathBuf, #[source] io::Error),
+
+    #[error("couldn't parse the configuration file {0:?}: {1}")]
+    ConfigFileParsingFailed(PathBuf, #[source] toml::de::Error),
+
+    #[error("configuration file {0:?} is not a TOML file, only TOML files are supported")]
+    UnsupportedConfigFormat(PathBuf),
+
+    #[error("unknown key {0:?} in the configuration file")]
+    UnknownConfigKey(String),
+
+    #[error("unexpected value of {0:?} key in the configuration file: {1}")]
+    InvalidConfigValue(String, String),
+
+    #[error("couldn't parse the value of {0:?} key in the configuration file: {1}")]
+    ConfigValueParsingFailed(String, #[source] Box<dyn error::Error + Send + Sync>),
+}
+
+/// A setting read from the configuration file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigValue {
+    /// The key of the setting in the file, like `runtime.threads`.
+    pub key: String,
+    /// The value, formatted like the value of the environment variable would be.
+    pub value: String,
+}
+
+/// The settings read from the configuration file, by the names of the corresponding
+/// environment variables.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigFile {
+    values: HashMap<&'static str, ConfigValue>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        if path.extension().and_then(OsStr::to_str) != Some("toml") {
+            return Err(Error::UnsupportedConfigFormat(path.to_path_buf()));
+        }
+        let contents = fs::read_to_string(path)
+            .map_err(|e| Error::ConfigFileReadFailed(path.to_path_buf(), e))?;
+        let table: toml::Table = contents
+            .parse()
+            .map_err(|e| Error::ConfigFileParsingFailed(path.to_path_buf(), e))?;
+        Self::from_table(&table)
+    }
+
+    pub fn from_toml_str(contents: &str) -> Result<Self, Error> {
+        let table: toml::Table = contents
+            .parse()
+            .map_err(|e| Error::ConfigFileParsingFailed(PathBuf::new(), e))?;
+        Self::from_table(&table)
+    }
+
+    fn from_table(table: &toml::Table) -> Result<Self, Error> {
+        let mut values = HashMap::new();
+        for (section_name, section) in table {
+            let Value::Table(section) = section else {
+                return Err(Error::InvalidConfigValue(
+                    section_name.clone(),
+                    "expected a section".to_string(),
+                ));
+            };
+            for (name, value) in section {
+                let key = format!("{section_name}.{name}");
+                let Some((_, env_var)) = CONFIG_KEYS.iter().find(|(known, _)| *known == key) else {
+                    return Err(Error::UnknownConfigKey(key));
+                };
+                let value = format_value(&key, value)?;
+                values.insert(*env_var, ConfigValue { key, value });
+            }
+        }
+        Ok(Self { values })
+    }
+
+    /// Returns the setting corresponding to the environment variable, if it is in the file.
+    pub fn get(&self, env_var: &str) -> Option<&ConfigValue> {
+        self.values.get(env_var)
+    }
+}
+
+fn format_scalar(key: &str, value: &Value) -> Result<String, Error> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Float(value) => Ok(value.to_string()),
+        Value::Boolean(value) => Ok(value.to_string()),
+        _ => Err(Error::InvalidConfigValue(
+            key.to_string(),
+            format!("expected a string, a number or a boolean, got {value}"),
+        )),
+    }
+}
+
+/// Formats the value like the environment variable: arrays as comma-separated lists and tables
+/// as comma-separated `key=value` pairs.
+fn format_value(key: &str, value: &Value) -> Result<String, Error> {
+    match value {
+        Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| format_scalar(key, item))
+                .collect::<Result<_, _>>()?;
+            Ok(items.join(","))
+        }
+        Value::Table(pairs) => {
+            let pairs: Vec<String> = pairs
+                .iter()
+                .map(|(name, value)| Ok(format!("{name}={}", format_scalar(key, value)?)))
+                .collect::<Result<_, Error>>()?;
+            Ok(pairs.join(","))
+        }
+        value => format_scalar(key, value),
+    }
+}
+
+static CONFIG_FILE: OnceCell<Option<ConfigFile>> = OnceCell::new();
+
+/// Reads the configuration file pointed to by `PATHWAY_CONFIG_FILE` from the disk.
+pub fn load_config_file() -> Result<Option<ConfigFile>, Error> {
+    match env::var_os(CONFIG_FILE_ENV) {
+        Some(path) if !path.is_empty() => ConfigFile::load(Path::new(&path)).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Returns the configuration file pointed to by `PATHWAY_CONFIG_FILE`, loaded once per process.
+pub fn config_file() -> Result<Option<&'static ConfigFile>, Error> {
+    let config_file = CONFIG_FILE.get_or_try_init(load_config_file)?;
+    Ok(config_file.as_ref())
+}
+
+/// Returns the value of the environment variable, or of the corresponding key of the
+/// configuration file if the variable is not set.
+pub fn env_var_or_config(name: &str) -> Result<Option<String>, Error> {
+    if let Some(value) = env::var_os(name) {
+        return value
+            .into_string()
+            .map(Some)
+            .map_err(|_| Error::NotUtf8(name.to_string()));
+    }
+    Ok(config_file()?
+        .and_then(|config_file| config_file.get(name))
+        .map(|config_value| config_value.value.clone()))
 }
 
 pub fn parse_env_var<T: FromStr>(name: &str) -> Result<Option<T>, Error>
+where
+    T::Err: error::Error + Send + Sync + 'static,
+{
+    parse_env_var_with_config(name, config_file()?)
+}
+
+/// Like [`parse_env_var`], but falls back to the given configuration file instead of the one
+/// loaded at the start.
+pub fn parse_env_var_with_config<T: FromStr>(
+    name: &str,
+    config_file: Option<&ConfigFile>,
+) -> Result<Option<T>, Error>
 where
     T::Err: error::Error + Send + Sync + 'static,
 {
@@ -27,6 +308,10 @@ where
                 .parse()
                 .map_err(|err| Error::ParsingFailed(name.to_string(), Box::new(err)))?,
         ))
+    } else if let Some(config_value) = config_file.and_then(|config_file| config_file.get(name)) {
+        Ok(Some(config_value.value.parse().map_err(|err| {
+            Error::ConfigValueParsingFailed(config_value.key.clone(), Box::new(err))
+        })?))
     } else {
         Ok(None)
     }
@@ -1,15 +1,22 @@
 // Copyright © 2024 Pathway
 
-use itertools::Itertools;
-use ndarray::{s, Array2, ArrayView2, Axis};
+use std::cmp::{max, min};
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+use ndarray::Array2;
+use ordered_float::OrderedFloat;
+use rayon::prelude::*;
 
 use crate::engine::error::DynResult;
 use crate::engine::{Error, Key};
-use ordered_float::{self, OrderedFloat};
-use std::cmp::max;
 
+use super::distance_kernels::DistanceKernels;
+#[cfg(feature = "cuda")]
+use super::gpu_knn_integration::GpuKNNIndex;
+use super::quantization::{QuantizationConfig, QuantizedKNNIndex};
 use super::{
-    DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyScoreMatch,
+    DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyFilter, KeyScoreMatch,
     KeyToU64IdMapper, NonFilteringExternalIndex,
 };
 
@@ -21,92 +28,160 @@ pub enum BruteForceKnnMetricKind {
 
 pub struct BruteForceKNNIndex {
     index_array: Array2<f64>,
+    // squared norms of the rows, needed for the cosine distance
+    sq_norms: Vec<f64>,
     current_size: usize,
     current_allocated: usize,
     minimum_allocated: usize,
     auxiliary_space: usize,
     dimensions: usize,
     metric: BruteForceKnnMetricKind,
+    parallel_chunk_size: Option<usize>,
+    kernels: DistanceKernels,
     key_to_id_mapper: KeyToU64IdMapper,
 }
 
+struct ScanQuery<'a> {
+    data: &'a [f64],
+    sq_norm: f64,
+    limit: usize,
+}
+
+/// The `limit` closest rows seen so far, as a max-heap with the farthest one on top.
+pub(super) struct TopK {
+    limit: usize,
+    heap: BinaryHeap<(OrderedFloat<f64>, usize)>,
+}
+
+impl TopK {
+    pub(super) fn new(limit: usize) -> TopK {
+        TopK {
+            limit,
+            heap: BinaryHeap::with_capacity(limit.saturating_add(1).min(1024)),
+        }
+    }
+
+    pub(super) fn admits(&self, distance: f64) -> bool {
+        self.heap.len() < self.limit
+            || self
+                .heap
+                .peek()
+                .is_some_and(|(worst, _)| OrderedFloat(distance) < *worst)
+    }
+
+    pub(super) fn push(&mut self, distance: f64, idx: usize) {
+        if self.limit == 0 {
+            return;
+        }
+        self.heap.push((OrderedFloat(distance), idx));
+        if self.heap.len() > self.limit {
+            self.heap.pop();
+        }
+    }
+
+    fn merge(&mut self, other: TopK) {
+        for (distance, idx) in other.heap {
+            self.push(distance.0, idx);
+        }
+    }
+
+    /// Returns the rows with their distances, closest first.
+    pub(super) fn into_sorted(self) -> Vec<(f64, usize)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|(distance, idx)| (distance.0, idx))
+            .collect()
+    }
+}
+
 impl BruteForceKNNIndex {
     pub fn new(
         dimensions: usize,
         reserved_space: usize,
         auxiliary_space: usize,
         metric: BruteForceKnnMetricKind,
+        parallel_chunk_size: Option<usize>,
     ) -> DynResult<BruteForceKNNIndex> {
         let arr = Array2::default((reserved_space, dimensions));
         Ok(BruteForceKNNIndex {
             index_array: arr,
+            sq_norms: vec![0.0; reserved_space],
             current_size: 0,
             current_allocated: reserved_space,
             minimum_allocated: reserved_space,
             auxiliary_space,
             dimensions,
             metric,
+            parallel_chunk_size,
+            kernels: DistanceKernels::detect(),
             key_to_id_mapper: KeyToU64IdMapper::new(),
         })
     }
 
-    fn fill_distances(
-        &self,
-        index_arr: &ArrayView2<f64>,
-        query_arr: &ArrayView2<f64>,
-        dot_p: &mut Array2<f64>,
-    ) {
+    fn row(&self, idx: usize) -> &[f64] {
+        let data = self
+            .index_array
+            .as_slice()
+            .expect("index array should be in the standard layout");
+        &data[idx * self.dimensions..(idx + 1) * self.dimensions]
+    }
+
+    fn resize(&mut self, new_allocated: usize) {
+        let mut new_arr: Array2<f64> = Array2::default((new_allocated, self.dimensions));
+        for (dst, src) in new_arr.iter_mut().zip(&self.index_array) {
+            *dst = *src;
+        }
+        self.index_array = new_arr;
+        self.sq_norms.resize(new_allocated, 0.0);
+        self.current_allocated = new_allocated;
+    }
+
+    fn distance(&self, idx: usize, query: &ScanQuery) -> f64 {
+        let row = self.row(idx);
         match self.metric {
-            BruteForceKnnMetricKind::L2sq => {
-                fill_l2sq_distances(index_arr, query_arr, dot_p);
-            }
+            BruteForceKnnMetricKind::L2sq => (self.kernels.l2sq)(row, query.data),
             BruteForceKnnMetricKind::Cos => {
-                fill_cos_distances(index_arr, query_arr, dot_p);
+                1.0 - (self.kernels.dot)(row, query.data)
+                    / (self.sq_norms[idx] * query.sq_norm).sqrt()
             }
         }
     }
-}
 
-fn fill_cos_distances(
-    index_arr: &ArrayView2<f64>,
-    query_arr: &ArrayView2<f64>,
-    dot_p: &mut Array2<f64>,
-) {
-    let index_sq_norms: Vec<f64> = index_arr
-        .axis_iter(Axis(0))
-        .map(|row| (row.dot(&row)))
-        .collect();
-
-    let query_sq_norms: Vec<f64> = query_arr
-        .axis_iter(Axis(1))
-        .map(|col| (col.dot(&col)))
-        .collect();
-
-    for (i, mut row) in dot_p.axis_iter_mut(Axis(0)).enumerate() {
-        for (j, entry) in row.iter_mut().enumerate() {
-            *entry = 1.0 - *entry / (index_sq_norms[i] * query_sq_norms[j]).sqrt();
-        }
+    fn key_for_idx(&self, idx: usize) -> Key {
+        self.key_to_id_mapper
+            .get_key_for_id(u64::try_from(idx).unwrap())
+            .unwrap()
     }
-}
 
-fn fill_l2sq_distances(
-    index_arr: &ArrayView2<f64>,
-    query_arr: &ArrayView2<f64>,
-    dot_p: &mut Array2<f64>,
-) {
-    let index_sq_norms: Vec<f64> = index_arr
-        .axis_iter(Axis(0))
-        .map(|row| (row.dot(&row)))
-        .collect();
-    let query_sq_norms: Vec<f64> = query_arr
-        .axis_iter(Axis(1))
-        .map(|col| (col.dot(&col)))
-        .collect();
-
-    for (i, mut row) in dot_p.axis_iter_mut(Axis(0)).enumerate() {
-        for (j, entry) in row.iter_mut().enumerate() {
-            *entry = index_sq_norms[i] + query_sq_norms[j] - 2.0 * (*entry);
+    /// Finds the closest rows within `rows` for each query. Rows are scanned in blocks, so that
+    /// a block stays in the cache while it is compared with all the queries. A filter is only
+    /// evaluated for rows that are close enough to be among the results.
+    fn scan_rows(
+        &self,
+        rows: Range<usize>,
+        queries: &[ScanQuery],
+        filters: Option<&[Option<&KeyFilter<'_>>]>,
+    ) -> Vec<TopK> {
+        let mut results: Vec<TopK> = queries.iter().map(|query| TopK::new(query.limit)).collect();
+        let block_size = max(1, self.auxiliary_space / max(1, queries.len()));
+        let mut block_start = rows.start;
+        while block_start < rows.end {
+            let block = block_start..min(block_start + block_size, rows.end);
+            for (i, (query, top_k)) in queries.iter().zip(&mut results).enumerate() {
+                let filter = filters.and_then(|filters| filters[i]);
+                for idx in block.clone() {
+                    let distance = self.distance(idx, query);
+                    if top_k.admits(distance)
+                        && filter.is_none_or(|filter| filter(self.key_for_idx(idx)))
+                    {
+                        top_k.push(distance, idx);
+                    }
+                }
+            }
+            block_start = block.end;
         }
+        results
     }
 }
 
@@ -117,14 +192,7 @@ impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for BruteForceKNNIndex {
                 2 * self.current_allocated,
                 add_data.len() + self.current_size,
             );
-
-            let mut new_arr: Array2<f64> = Array2::default((new_allocated, self.dimensions));
-            for (dst, src) in new_arr.iter_mut().zip(&self.index_array) {
-                *dst = *src;
-            }
-
-            self.index_array = new_arr;
-            self.current_allocated = new_allocated;
+            self.resize(new_allocated);
         }
 
         self.current_size += add_data.len();
@@ -137,6 +205,7 @@ impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for BruteForceKNNIndex {
                 for (value, input_value) in row.iter_mut().zip(data) {
                     *value = input_value;
                 }
+                self.sq_norms[idx] = (self.kernels.dot)(self.row(idx), self.row(idx));
 
                 (key, Ok(()))
             })
@@ -158,9 +227,9 @@ impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for BruteForceKNNIndex {
                         if last_row_key != key {
                             // if the last row had a different key, put its entry to the removed position
                             let last_row = &self.index_array.row(self.current_size).to_owned();
-                            self.index_array
-                                .row_mut(usize::try_from(removed_key_id).unwrap())
-                                .assign(last_row);
+                            let removed_idx = usize::try_from(removed_key_id).unwrap();
+                            self.index_array.row_mut(removed_idx).assign(last_row);
+                            self.sq_norms[removed_idx] = self.sq_norms[self.current_size];
                             self.key_to_id_mapper
                                 .assign_key(last_row_key, removed_key_id);
                         }
@@ -174,14 +243,7 @@ impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for BruteForceKNNIndex {
         if 4 * self.current_size < self.current_allocated
             && self.current_allocated / 2 >= self.minimum_allocated
         {
-            let new_allocated = self.current_allocated / 2;
-            let mut new_arr: Array2<f64> = Array2::default((new_allocated, self.dimensions));
-
-            for (dst, src) in new_arr.iter_mut().zip(&self.index_array) {
-                *dst = *src;
-            }
-            self.index_array = new_arr;
-            self.current_allocated = new_allocated;
+            self.resize(self.current_allocated / 2);
         }
         ret
     }
@@ -190,51 +252,80 @@ impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for BruteForceKNNIndex {
         &self,
         queries: &[(Key, Vec<f64>, usize)],
     ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
-        if self.current_size == 0 {
-            return queries
-                .iter()
-                .map(|(key, _, _)| (*key, Ok(Vec::new())))
-                .collect();
-        }
+        let queries: Vec<_> = queries
+            .iter()
+            .map(|(key, data, limit)| (*key, data.as_slice(), *limit, None))
+            .collect();
+        self.search_impl(&queries)
+    }
 
-        let index_arr = self
-            .index_array
-            .slice(s![..self.current_size, ..self.dimensions]);
-
-        let mut ret = Vec::with_capacity(queries.len());
-        let max_queries = max(1, self.auxiliary_space / self.current_size);
-        let query_batches = queries.chunks(max_queries);
-        let mut query_arr = Array2::<f64>::default((self.dimensions, max_queries));
-        for query_batch in query_batches {
-            for (mut col, (_key, data, _k)) in query_arr.axis_iter_mut(Axis(1)).zip(query_batch) {
-                for (entry, val) in col.iter_mut().zip(data) {
-                    *entry = *val;
-                }
+    fn filtered_search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize, KeyFilter<'_>)],
+    ) -> Option<Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>> {
+        let queries: Vec<_> = queries
+            .iter()
+            .map(|(key, data, limit, filter)| (*key, data.as_slice(), *limit, Some(filter)))
+            .collect();
+        Some(self.search_impl(&queries))
+    }
+}
+
+impl BruteForceKNNIndex {
+    fn search_impl(
+        &self,
+        queries: &[(Key, &[f64], usize, Option<&KeyFilter<'_>>)],
+    ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
+        let scan_queries: Vec<ScanQuery> = queries
+            .iter()
+            .map(|(_key, data, limit, _filter)| ScanQuery {
+                data: *data,
+                sq_norm: (self.kernels.dot)(data, data),
+                limit: *limit,
+            })
+            .collect();
+        let filters: Vec<Option<&KeyFilter<'_>>> =
+            queries.iter().map(|(_, _, _, filter)| *filter).collect();
+
+        // filters can't be shared between threads, so filtered queries are always answered
+        // with a sequential scan
+        let results = match self.parallel_chunk_size {
+            Some(chunk_size)
+                if self.current_size > chunk_size && filters.iter().all(Option::is_none) =>
+            {
+                let chunk_starts: Vec<usize> = (0..self.current_size).step_by(chunk_size).collect();
+                chunk_starts
+                    .into_par_iter()
+                    .map(|start| {
+                        let end = min(start + chunk_size, self.current_size);
+                        self.scan_rows(start..end, &scan_queries, None)
+                    })
+                    .reduce_with(|mut results, chunk_results| {
+                        for (top_k, chunk_top_k) in results.iter_mut().zip(chunk_results) {
+                            top_k.merge(chunk_top_k);
+                        }
+                        results
+                    })
+                    .expect("there should be at least one chunk")
             }
-            let slice_query_array = query_arr.slice(s![..self.dimensions, ..query_batch.len()]);
-            let mut dot_p = index_arr.dot(&slice_query_array);
-            self.fill_distances(&index_arr, &slice_query_array, &mut dot_p);
-
-            ret.extend(dot_p.axis_iter(Axis(1)).zip(query_batch).map(
-                |(col, (key, _data, limit))| {
-                    let result = col
-                        .iter()
-                        .enumerate()
-                        .map(|(idx, x)| (OrderedFloat::from(*x), idx)) //order by distance
-                        .k_smallest(*limit)
-                        .map(|(distance, i)| KeyScoreMatch {
-                            key: self
-                                .key_to_id_mapper
-                                .get_key_for_id(u64::try_from(i).unwrap())
-                                .unwrap(),
-                            score: -(*distance),
-                        })
-                        .collect();
-                    (*key, Ok(result))
-                },
-            ));
-        }
-        ret
+            _ => self.scan_rows(0..self.current_size, &scan_queries, Some(&filters)),
+        };
+
+        queries
+            .iter()
+            .zip(results)
+            .map(|((key, _, _, _), top_k)| {
+                let matches = top_k
+                    .into_sorted()
+                    .into_iter()
+                    .map(|(distance, idx)| KeyScoreMatch {
+                        key: self.key_for_idx(idx),
+                        score: -distance,
+                    })
+                    .collect();
+                (*key, Ok(matches))
+            })
+            .collect()
     }
 }
 
@@ -243,6 +334,10 @@ pub struct BruteForceKNNIndexFactory {
     reserved_space: usize,
     auxiliary_space: usize,
     metric: BruteForceKnnMetricKind,
+    parallel_chunk_size: Option<usize>,
+    quantization: Option<QuantizationConfig>,
+    #[cfg(feature = "cuda")]
+    gpu_device: Option<usize>,
 }
 
 impl BruteForceKNNIndexFactory {
@@ -257,17 +352,61 @@ impl BruteForceKNNIndexFactory {
             reserved_space,
             auxiliary_space,
             metric,
+            parallel_chunk_size: None,
+            quantization: None,
+            #[cfg(feature = "cuda")]
+            gpu_device: None,
         }
     }
+
+    /// Scans indices with more than `chunk_size` entries in parallel, in chunks of that size.
+    /// Queries with filters are still answered with a sequential scan.
+    #[must_use]
+    pub fn with_parallel_scan(mut self, chunk_size: usize) -> Self {
+        self.parallel_chunk_size = Some(max(1, chunk_size));
+        self
+    }
+
+    /// Stores the vectors compressed, as described by `config`. Such indices are always scanned
+    /// sequentially.
+    #[must_use]
+    pub fn with_quantization(mut self, config: QuantizationConfig) -> Self {
+        self.quantization = Some(config);
+        self
+    }
+
+    /// Keeps the vectors on the CUDA device with the given ordinal and computes the distances
+    /// there. Takes precedence over the parallel scan and is ignored for quantized indices.
+    #[cfg(feature = "cuda")]
+    #[must_use]
+    pub fn with_gpu(mut self, device_ordinal: usize) -> Self {
+        self.gpu_device = Some(device_ordinal);
+        self
+    }
 }
 
 impl ExternalIndexFactory for BruteForceKNNIndexFactory {
     fn make_instance(&self) -> Result<Box<dyn ExternalIndex>, Error> {
+        if let Some(quantization) = self.quantization {
+            let q_index = QuantizedKNNIndex::new(self.dimensions, self.metric, quantization)?;
+            return Ok(Box::new(DerivedFilteredSearchIndex::new(Box::new(q_index))));
+        }
+        #[cfg(feature = "cuda")]
+        if let Some(device_ordinal) = self.gpu_device {
+            let g_index = GpuKNNIndex::new(
+                self.dimensions,
+                self.reserved_space,
+                self.metric,
+                device_ordinal,
+            )?;
+            return Ok(Box::new(DerivedFilteredSearchIndex::new(Box::new(g_index))));
+        }
         let u_index = BruteForceKNNIndex::new(
             self.dimensions,
             self.reserved_space,
             self.auxiliary_space,
             self.metric,
+            self.parallel_chunk_size,
         )?;
         Ok(Box::new(DerivedFilteredSearchIndex::new(Box::new(u_index))) as Box<dyn ExternalIndex>)
     }
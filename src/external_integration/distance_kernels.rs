@@ -0,0 +1,163 @@
+// Copyright © 2024 Pathway
+
+//! Vectorized distance kernels for exact vector search.
+//!
+//! On `x86_64` CPUs supporting AVX2 and FMA the kernels use them explicitly, elsewhere they
+//! fall back to a portable implementation with independent accumulators, which the compiler
+//! turns into whatever SIMD instructions the target has. The implementation is selected once,
+//! with [`DistanceKernels::detect`].
+
+pub type DistanceFn = fn(&[f64], &[f64]) -> f64;
+
+#[derive(Clone, Copy)]
+pub struct DistanceKernels {
+    /// Dot product of two vectors.
+    pub dot: DistanceFn,
+    /// Squared euclidean distance between two vectors.
+    pub l2sq: DistanceFn,
+}
+
+impl DistanceKernels {
+    pub fn detect() -> DistanceKernels {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            return DistanceKernels {
+                dot: avx2::dot,
+                l2sq: avx2::l2sq,
+            };
+        }
+        DistanceKernels {
+            dot: portable::dot,
+            l2sq: portable::l2sq,
+        }
+    }
+}
+
+mod portable {
+    const LANES: usize = 8;
+
+    pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+        let len = a.len().min(b.len());
+        let (a, b) = (&a[..len], &b[..len]);
+        let mut acc = [0.0; LANES];
+        let a_chunks = a.chunks_exact(LANES);
+        let b_chunks = b.chunks_exact(LANES);
+        let tail: f64 = a_chunks
+            .remainder()
+            .iter()
+            .zip(b_chunks.remainder())
+            .map(|(x, y)| x * y)
+            .sum();
+        for (a_chunk, b_chunk) in a_chunks.zip(b_chunks) {
+            for lane in 0..LANES {
+                acc[lane] += a_chunk[lane] * b_chunk[lane];
+            }
+        }
+        acc.iter().sum::<f64>() + tail
+    }
+
+    pub fn l2sq(a: &[f64], b: &[f64]) -> f64 {
+        let len = a.len().min(b.len());
+        let (a, b) = (&a[..len], &b[..len]);
+        let mut acc = [0.0; LANES];
+        let a_chunks = a.chunks_exact(LANES);
+        let b_chunks = b.chunks_exact(LANES);
+        let tail: f64 = a_chunks
+            .remainder()
+            .iter()
+            .zip(b_chunks.remainder())
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum();
+        for (a_chunk, b_chunk) in a_chunks.zip(b_chunks) {
+            for lane in 0..LANES {
+                let diff = a_chunk[lane] - b_chunk[lane];
+                acc[lane] += diff * diff;
+            }
+        }
+        acc.iter().sum::<f64>() + tail
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    #[allow(clippy::wildcard_imports)]
+    use std::arch::x86_64::*;
+
+    // The functions below are only reachable through `DistanceKernels::detect`, which checks
+    // that the CPU supports the required instructions.
+
+    pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+        // SAFETY: AVX2 and FMA support is checked before selecting this kernel
+        unsafe { dot_impl(a, b) }
+    }
+
+    pub fn l2sq(a: &[f64], b: &[f64]) -> f64 {
+        // SAFETY: AVX2 and FMA support is checked before selecting this kernel
+        unsafe { l2sq_impl(a, b) }
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn horizontal_sum(v: __m256d) -> f64 {
+        let mut lanes = [0.0; 4];
+        _mm256_storeu_pd(lanes.as_mut_ptr(), v);
+        lanes.iter().sum()
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn dot_impl(a: &[f64], b: &[f64]) -> f64 {
+        let len = a.len().min(b.len());
+        let (a, b) = (a.as_ptr(), b.as_ptr());
+        // two accumulators hide the latency of the fused multiply-add
+        let mut acc_0 = _mm256_setzero_pd();
+        let mut acc_1 = _mm256_setzero_pd();
+        let mut i = 0;
+        while i + 8 <= len {
+            acc_0 = _mm256_fmadd_pd(_mm256_loadu_pd(a.add(i)), _mm256_loadu_pd(b.add(i)), acc_0);
+            acc_1 = _mm256_fmadd_pd(
+                _mm256_loadu_pd(a.add(i + 4)),
+                _mm256_loadu_pd(b.add(i + 4)),
+                acc_1,
+            );
+            i += 8;
+        }
+        if i + 4 <= len {
+            acc_0 = _mm256_fmadd_pd(_mm256_loadu_pd(a.add(i)), _mm256_loadu_pd(b.add(i)), acc_0);
+            i += 4;
+        }
+        let mut sum = horizontal_sum(_mm256_add_pd(acc_0, acc_1));
+        while i < len {
+            sum += *a.add(i) * *b.add(i);
+            i += 1;
+        }
+        sum
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn l2sq_impl(a: &[f64], b: &[f64]) -> f64 {
+        let len = a.len().min(b.len());
+        let (a, b) = (a.as_ptr(), b.as_ptr());
+        let mut acc_0 = _mm256_setzero_pd();
+        let mut acc_1 = _mm256_setzero_pd();
+        let mut i = 0;
+        while i + 8 <= len {
+            let diff_0 = _mm256_sub_pd(_mm256_loadu_pd(a.add(i)), _mm256_loadu_pd(b.add(i)));
+            let diff_1 =
+                _mm256_sub_pd(_mm256_loadu_pd(a.add(i + 4)), _mm256_loadu_pd(b.add(i + 4)));
+            acc_0 = _mm256_fmadd_pd(diff_0, diff_0, acc_0);
+            acc_1 = _mm256_fmadd_pd(diff_1, diff_1, acc_1);
+            i += 8;
+        }
+        if i + 4 <= len {
+            let diff = _mm256_sub_pd(_mm256_loadu_pd(a.add(i)), _mm256_loadu_pd(b.add(i)));
+            acc_0 = _mm256_fmadd_pd(diff, diff, acc_0);
+            i += 4;
+        }
+        let mut sum = horizontal_sum(_mm256_add_pd(acc_0, acc_1));
+        while i < len {
+            let diff = *a.add(i) - *b.add(i);
+            sum += diff * diff;
+            i += 1;
+        }
+        sum
+    }
+}
@@ -0,0 +1,404 @@
+// Copyright © 2024 Pathway
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CString;
+
+use faiss::index::NativeIndex;
+use faiss::selector::IdSelector;
+use faiss::{index_factory, Idx, Index, IndexImpl, MetricType};
+use itertools::Itertools;
+use log::info;
+use ordered_float::OrderedFloat;
+use rand::{rng, Rng};
+
+use crate::engine::error::{DataError, DynResult};
+use crate::engine::{Error, Key};
+
+use super::{
+    DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyScoreMatch,
+    KeyToU64IdMapper, NonFilteringExternalIndex,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum FaissIndexKind {
+    /// Inverted lists holding the vectors uncompressed.
+    IvfFlat,
+    /// Inverted lists holding the vectors compressed with product quantization into
+    /// `subquantizers` codes of `bits` bits each.
+    IvfPq { subquantizers: usize, bits: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum FaissMetricKind {
+    L2sq,
+    InnerProduct,
+}
+
+#[derive(Debug, Clone)]
+pub struct FaissIndexConfig {
+    pub dimensions: usize,
+    pub kind: FaissIndexKind,
+    pub metric: FaissMetricKind,
+    /// Number of inverted lists, i.e. clusters the vectors are assigned to.
+    pub nlist: usize,
+    /// Number of inverted lists visited by a search.
+    pub nprobe: usize,
+    /// Number of vectors the clustering (and quantization) is trained on. Until that many
+    /// vectors are indexed, they're kept as they are and searched exhaustively.
+    pub training_sample_size: usize,
+    /// If set, the index is retrained on a new sample whenever the number of vectors grows
+    /// this many times since the last training. Retraining needs all the vectors, so a copy
+    /// of them is kept in memory.
+    pub retrain_growth_factor: Option<f64>,
+}
+
+impl FaissIndexConfig {
+    fn description(&self) -> String {
+        match self.kind {
+            FaissIndexKind::IvfFlat => format!("IVF{},Flat", self.nlist),
+            FaissIndexKind::IvfPq {
+                subquantizers,
+                bits,
+            } => format!("IVF{},PQ{subquantizers}x{bits}", self.nlist),
+        }
+    }
+
+    fn metric_type(&self) -> MetricType {
+        match self.metric {
+            FaissMetricKind::L2sq => MetricType::L2,
+            FaissMetricKind::InnerProduct => MetricType::InnerProduct,
+        }
+    }
+
+    /// Score of a match, higher is better, like in the other vector indices.
+    fn score(&self, distance: f32) -> f64 {
+        match self.metric {
+            FaissMetricKind::L2sq => -f64::from(distance),
+            FaissMetricKind::InnerProduct => f64::from(distance),
+        }
+    }
+
+    fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self.metric {
+            FaissMetricKind::L2sq => a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum(),
+            FaissMetricKind::InnerProduct => a.iter().zip(b).map(|(x, y)| x * y).sum(),
+        }
+    }
+}
+
+/// Uniform sample of the indexed vectors, maintained with reservoir sampling, so that the index
+/// can be retrained on data representative of the whole collection.
+struct TrainingSample {
+    capacity: usize,
+    entries: Vec<(u64, Vec<f32>)>,
+    positions: HashMap<u64, usize>,
+    seen: u64,
+}
+
+impl TrainingSample {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Vec::with_capacity(capacity),
+            positions: HashMap::with_capacity(capacity),
+            seen: 0,
+        }
+    }
+
+    fn offer(&mut self, id: u64, vector: &[f32]) {
+        self.seen += 1;
+        if self.entries.len() < self.capacity {
+            self.positions.insert(id, self.entries.len());
+            self.entries.push((id, vector.to_vec()));
+            return;
+        }
+        let position = rng().random_range(0..self.seen);
+        if let Ok(position) = usize::try_from(position) {
+            if position < self.capacity {
+                let (replaced_id, _) =
+                    std::mem::replace(&mut self.entries[position], (id, vector.to_vec()));
+                self.positions.remove(&replaced_id);
+                self.positions.insert(id, position);
+            }
+        }
+    }
+
+    fn remove(&mut self, id: u64) {
+        let Some(position) = self.positions.remove(&id) else {
+            return;
+        };
+        self.entries.swap_remove(position);
+        if let Some((moved_id, _)) = self.entries.get(position) {
+            self.positions.insert(*moved_id, position);
+        }
+    }
+
+    fn vectors(&self) -> Vec<f32> {
+        self.entries
+            .iter()
+            .flat_map(|(_id, vector)| vector.iter().copied())
+            .collect()
+    }
+}
+
+pub struct FaissKNNIndex {
+    config: FaissIndexConfig,
+    // `None` until enough vectors are collected to train it; searching needs exclusive access
+    // only because of the signatures of the bindings
+    index: Option<RefCell<IndexImpl>>,
+    // vectors that are not in `index`, searched exhaustively
+    pending: HashMap<u64, Vec<f32>>,
+    // copy of all vectors, kept only if the index is retrained
+    vectors: Option<HashMap<u64, Vec<f32>>>,
+    sample: TrainingSample,
+    size: usize,
+    size_at_training: usize,
+    key_to_id_mapper: KeyToU64IdMapper,
+}
+
+impl FaissKNNIndex {
+    pub fn new(config: FaissIndexConfig) -> DynResult<FaissKNNIndex> {
+        if config.training_sample_size < config.nlist {
+            return Err(DataError::ValueError(format!(
+                "training sample of {} vectors is too small for {} inverted lists",
+                config.training_sample_size, config.nlist
+            ))
+            .into());
+        }
+        Ok(FaissKNNIndex {
+            vectors: config.retrain_growth_factor.map(|_| HashMap::new()),
+            sample: TrainingSample::new(config.training_sample_size),
+            config,
+            index: None,
+            pending: HashMap::new(),
+            size: 0,
+            size_at_training: 0,
+            key_to_id_mapper: KeyToU64IdMapper::new(),
+        })
+    }
+
+    fn set_nprobe(&self, index: &IndexImpl) -> DynResult<()> {
+        let name = CString::new("nprobe")?;
+        #[allow(clippy::cast_precision_loss)]
+        let nprobe = self.config.nprobe as f64;
+        // SAFETY: the parameter space is created and freed here and the index pointer is valid
+        // as long as `index` is alive.
+        let code = unsafe {
+            let mut space = std::ptr::null_mut();
+            let mut code = faiss_sys::faiss_ParameterSpace_new(&mut space);
+            if code == 0 {
+                code = faiss_sys::faiss_ParameterSpace_set_index_parameter(
+                    space,
+                    index.inner_ptr(),
+                    name.as_ptr(),
+                    nprobe,
+                );
+                faiss_sys::faiss_ParameterSpace_free(space);
+            }
+            code
+        };
+        if code != 0 {
+            return Err(DataError::ValueError(format!(
+                "failed to set nprobe of FAISS index: {code}"
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Trains a new index on the current sample and moves `vectors` into it.
+    fn train(&mut self, vectors: Vec<(u64, Vec<f32>)>) -> DynResult<()> {
+        let dimensions = u32::try_from(self.config.dimensions)?;
+        let mut index = index_factory(
+            dimensions,
+            self.config.description(),
+            self.config.metric_type(),
+        )?;
+        index.train(&self.sample.vectors())?;
+        self.set_nprobe(&index)?;
+        let ids: Vec<Idx> = vectors.iter().map(|(id, _)| Idx::new(*id)).collect();
+        let data: Vec<f32> = vectors
+            .into_iter()
+            .flat_map(|(_id, vector)| vector)
+            .collect();
+        index.add_with_ids(&data, &ids)?;
+        info!(
+            "Trained FAISS index {} on {} vectors, indexed {} vectors",
+            self.config.description(),
+            self.sample.entries.len(),
+            ids.len()
+        );
+        self.index = Some(RefCell::new(index));
+        self.size_at_training = self.size;
+        Ok(())
+    }
+
+    fn maybe_train(&mut self) -> DynResult<()> {
+        match (
+            &self.index,
+            &self.vectors,
+            self.config.retrain_growth_factor,
+        ) {
+            (None, _, _) if self.pending.len() >= self.config.training_sample_size => {
+                let pending = std::mem::take(&mut self.pending);
+                self.train(pending.into_iter().collect())
+            }
+            (Some(_), Some(vectors), Some(growth_factor)) => {
+                #[allow(clippy::cast_precision_loss)]
+                let grown = self.size as f64 >= self.size_at_training as f64 * growth_factor;
+                if !grown {
+                    return Ok(());
+                }
+                let vectors = vectors
+                    .iter()
+                    .map(|(id, vector)| (*id, vector.clone()))
+                    .collect();
+                self.train(vectors)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn search_pending(&self, data: &[f32], limit: usize) -> Vec<(f32, u64)> {
+        let ordered = |distance: f32| match self.config.metric {
+            FaissMetricKind::L2sq => OrderedFloat(distance),
+            FaissMetricKind::InnerProduct => OrderedFloat(-distance),
+        };
+        self.pending
+            .iter()
+            .map(|(id, vector)| (self.config.distance(data, vector), *id))
+            .k_smallest_by_key(limit, |(distance, id)| (ordered(*distance), *id))
+            .collect()
+    }
+
+    fn search_one(&self, data: &[f64], limit: usize) -> DynResult<Vec<KeyScoreMatch>> {
+        #[allow(clippy::cast_possible_truncation)]
+        let data: Vec<f32> = data.iter().map(|x| *x as f32).collect();
+        let mut matches = self.search_pending(&data, limit);
+        if let Some(index) = &self.index {
+            let result = index.borrow_mut().search(&data, limit)?;
+            for (label, distance) in result.labels.into_iter().zip(result.distances) {
+                if let Some(id) = label.get() {
+                    matches.push((distance, id));
+                }
+            }
+        }
+        let mut results: Vec<KeyScoreMatch> = matches
+            .into_iter()
+            .filter_map(|(distance, id)| {
+                Some(KeyScoreMatch {
+                    key: self.key_to_id_mapper.get_key_for_id(id)?,
+                    score: self.config.score(distance),
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    fn add_one(&mut self, key: Key, data: &[f64]) -> DynResult<()> {
+        if data.len() != self.config.dimensions {
+            return Err(DataError::ValueError(format!(
+                "expected a vector of {} dimensions, got {}",
+                self.config.dimensions,
+                data.len()
+            ))
+            .into());
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let vector: Vec<f32> = data.iter().map(|x| *x as f32).collect();
+        let id = self.key_to_id_mapper.get_next_free_u64_id(key);
+        self.sample.offer(id, &vector);
+        if let Some(vectors) = &mut self.vectors {
+            vectors.insert(id, vector.clone());
+        }
+        match &mut self.index {
+            Some(index) => index.get_mut().add_with_ids(&vector, &[Idx::new(id)])?,
+            None => {
+                self.pending.insert(id, vector);
+            }
+        }
+        self.size += 1;
+        Ok(())
+    }
+}
+
+impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for FaissKNNIndex {
+    fn add(&mut self, add_data: Vec<(Key, Vec<f64>)>) -> Vec<(Key, DynResult<()>)> {
+        let mut ret: Vec<_> = add_data
+            .into_iter()
+            .map(|(key, data)| (key, self.add_one(key, &data)))
+            .collect();
+        if let Err(error) = self.maybe_train() {
+            // the vectors stay searchable, training is attempted again with the next batch
+            let message = format!("failed to train FAISS index: {error}");
+            for (_key, result) in &mut ret {
+                if result.is_ok() {
+                    *result = Err(DataError::ValueError(message.clone()).into());
+                }
+            }
+        }
+        ret
+    }
+
+    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+        let mut ret = Vec::with_capacity(keys.len());
+        let mut indexed_ids = Vec::new();
+        for key in keys {
+            match self.key_to_id_mapper.remove_key(key) {
+                Ok(id) => {
+                    self.sample.remove(id);
+                    if let Some(vectors) = &mut self.vectors {
+                        vectors.remove(&id);
+                    }
+                    if self.pending.remove(&id).is_none() {
+                        indexed_ids.push(Idx::new(id));
+                    }
+                    self.size -= 1;
+                    ret.push((key, Ok(())));
+                }
+                Err(error) => ret.push((key, Err(error))),
+            }
+        }
+        if let (Some(index), false) = (&mut self.index, indexed_ids.is_empty()) {
+            let removed = IdSelector::batch(&indexed_ids)
+                .and_then(|selector| index.get_mut().remove_ids(&selector));
+            if let Err(error) = removed {
+                let message = format!("failed to remove vectors from FAISS index: {error}");
+                for (_key, result) in &mut ret {
+                    *result = Err(DataError::ValueError(message.clone()).into());
+                }
+            }
+        }
+        ret
+    }
+
+    fn search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize)],
+    ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
+        queries
+            .iter()
+            .map(|(key, data, limit)| (*key, self.search_one(data, *limit)))
+            .collect()
+    }
+}
+
+pub struct FaissKNNIndexFactory {
+    config: FaissIndexConfig,
+}
+
+impl FaissKNNIndexFactory {
+    pub fn new(config: FaissIndexConfig) -> FaissKNNIndexFactory {
+        FaissKNNIndexFactory { config }
+    }
+}
+
+impl ExternalIndexFactory for FaissKNNIndexFactory {
+    fn make_instance(&self) -> Result<Box<dyn ExternalIndex>, Error> {
+        let f_index = FaissKNNIndex::new(self.config.clone())?;
+        Ok(Box::new(DerivedFilteredSearchIndex::new(Box::new(f_index))) as Box<dyn ExternalIndex>)
+    }
+}
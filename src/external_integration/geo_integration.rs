@@ -0,0 +1,336 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use rstar::{Envelope, PointDistance, RTree, RTreeObject, AABB};
+
+use crate::engine::error::{DataError, DynResult};
+use crate::engine::{Error, Key, Value};
+
+use super::{
+    DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyFilter, KeyScoreMatch,
+    NonFilteringExternalIndex, Unpack,
+};
+
+type Point = [f64; 2];
+
+/// Indexed geometry. Coordinates are treated as planar, so geographic coordinates should be
+/// projected first if distances matter.
+#[derive(Debug, Clone)]
+pub enum GeoShape {
+    Point(Point),
+    /// Simple polygon given by its vertices, without repeating the first one at the end.
+    Polygon(Vec<Point>),
+}
+
+#[derive(Debug, Clone)]
+pub enum GeoQuery {
+    /// Shapes closest to the point.
+    Nearest(Point),
+    /// Shapes within the distance from the point, closest first.
+    WithinRadius(Point, f64),
+    /// Shapes intersecting the box given by two opposite corners, closest to its center first.
+    BoundingBox(Point, Point),
+}
+
+fn unpack_point(value: &Value) -> DynResult<Point> {
+    let coordinates: Vec<f64> = value.clone().unpack()?;
+    match coordinates.as_slice() {
+        [x, y] => Ok([*x, *y]),
+        _ => Err(DataError::ValueError(format!(
+            "expected a point with two coordinates, got {value}"
+        ))
+        .into()),
+    }
+}
+
+impl Unpack<GeoShape> for Value {
+    fn unpack(self) -> DynResult<GeoShape> {
+        if let Ok(point) = unpack_point(&self) {
+            return Ok(GeoShape::Point(point));
+        }
+        let vertices: Vec<Point> = self.as_tuple()?.iter().map(unpack_point).try_collect()?;
+        if vertices.len() < 3 {
+            return Err(DataError::ValueError(format!(
+                "expected a point or a polygon with at least three vertices, got {self}"
+            ))
+            .into());
+        }
+        Ok(GeoShape::Polygon(vertices))
+    }
+}
+
+impl Unpack<GeoQuery> for Value {
+    fn unpack(self) -> DynResult<GeoQuery> {
+        if let Ok(point) = unpack_point(&self) {
+            return Ok(GeoQuery::Nearest(point));
+        }
+        match self.as_tuple()?.as_ref() {
+            [center, Value::Int(_) | Value::Float(_)] => {
+                let center = unpack_point(center)?;
+                let radius = self.as_tuple()?[1].as_float()?;
+                Ok(GeoQuery::WithinRadius(center, radius))
+            }
+            [corner, opposite_corner] => Ok(GeoQuery::BoundingBox(
+                unpack_point(corner)?,
+                unpack_point(opposite_corner)?,
+            )),
+            _ => Err(DataError::ValueError(format!(
+                "expected a point, a (point, radius) pair or a pair of corners, got {self}"
+            ))
+            .into()),
+        }
+    }
+}
+
+fn squared_distance(a: Point, b: Point) -> f64 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)
+}
+
+fn squared_distance_to_segment(point: Point, start: Point, end: Point) -> f64 {
+    let direction = [end[0] - start[0], end[1] - start[1]];
+    let length_2 = direction[0].powi(2) + direction[1].powi(2);
+    if length_2 == 0.0 {
+        return squared_distance(point, start);
+    }
+    let t = (((point[0] - start[0]) * direction[0] + (point[1] - start[1]) * direction[1])
+        / length_2)
+        .clamp(0.0, 1.0);
+    squared_distance(
+        point,
+        [start[0] + t * direction[0], start[1] + t * direction[1]],
+    )
+}
+
+fn edges(vertices: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(start, end)| (*start, *end))
+}
+
+fn polygon_contains(vertices: &[Point], point: Point) -> bool {
+    // ray casting
+    let mut inside = false;
+    for (start, end) in edges(vertices) {
+        if (start[1] > point[1]) != (end[1] > point[1]) {
+            let crossing =
+                start[0] + (point[1] - start[1]) / (end[1] - start[1]) * (end[0] - start[0]);
+            if point[0] < crossing {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn segments_intersect(a: (Point, Point), b: (Point, Point)) -> bool {
+    let orientation = |p: Point, q: Point, r: Point| {
+        ((q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0])).signum()
+    };
+    let d1 = orientation(b.0, b.1, a.0);
+    let d2 = orientation(b.0, b.1, a.1);
+    let d3 = orientation(a.0, a.1, b.0);
+    let d4 = orientation(a.0, a.1, b.1);
+    d1 * d2 <= 0.0 && d3 * d4 <= 0.0
+}
+
+#[derive(Debug, Clone)]
+struct GeoEntry {
+    key: Key,
+    shape: GeoShape,
+}
+
+impl PartialEq for GeoEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl RTreeObject for GeoEntry {
+    type Envelope = AABB<Point>;
+
+    fn envelope(&self) -> Self::Envelope {
+        match &self.shape {
+            GeoShape::Point(point) => AABB::from_point(*point),
+            GeoShape::Polygon(vertices) => AABB::from_points(vertices),
+        }
+    }
+}
+
+impl PointDistance for GeoEntry {
+    fn distance_2(&self, point: &Point) -> f64 {
+        match &self.shape {
+            GeoShape::Point(own_point) => squared_distance(*own_point, *point),
+            GeoShape::Polygon(vertices) => {
+                if polygon_contains(vertices, *point) {
+                    0.0
+                } else {
+                    edges(vertices)
+                        .map(|(start, end)| squared_distance_to_segment(*point, start, end))
+                        .fold(f64::INFINITY, f64::min)
+                }
+            }
+        }
+    }
+}
+
+impl GeoEntry {
+    fn intersects(&self, bounding_box: &AABB<Point>) -> bool {
+        match &self.shape {
+            GeoShape::Point(point) => bounding_box.contains_point(point),
+            GeoShape::Polygon(vertices) => {
+                let [min_x, min_y] = bounding_box.lower();
+                let [max_x, max_y] = bounding_box.upper();
+                let corners = [
+                    [min_x, min_y],
+                    [max_x, min_y],
+                    [max_x, max_y],
+                    [min_x, max_y],
+                ];
+                vertices
+                    .iter()
+                    .any(|vertex| bounding_box.contains_point(vertex))
+                    || corners
+                        .iter()
+                        .any(|corner| polygon_contains(vertices, *corner))
+                    || edges(vertices).any(|edge| {
+                        edges(&corners).any(|box_edge| segments_intersect(edge, box_edge))
+                    })
+            }
+        }
+    }
+}
+
+/// R-tree over points and simple polygons, answering nearest-neighbor, radius and bounding-box
+/// queries. Scores are negated distances, like in the vector indices.
+pub struct GeoIndex {
+    tree: RTree<GeoEntry>,
+    entries: HashMap<Key, GeoEntry>,
+}
+
+impl GeoIndex {
+    pub fn new() -> GeoIndex {
+        GeoIndex {
+            tree: RTree::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    fn add_one(&mut self, key: Key, shape: GeoShape) -> DynResult<()> {
+        if self.entries.contains_key(&key) {
+            return Err(DataError::ValueError(format!("key {key} is already indexed")).into());
+        }
+        let entry = GeoEntry { key, shape };
+        self.tree.insert(entry.clone());
+        self.entries.insert(key, entry);
+        Ok(())
+    }
+
+    fn remove_one(&mut self, key: Key) -> DynResult<()> {
+        let entry = self
+            .entries
+            .remove(&key)
+            .ok_or(DataError::ValueError(format!("key {key} is not indexed")))?;
+        self.tree.remove(&entry);
+        Ok(())
+    }
+
+    fn search_one(
+        &self,
+        query: &GeoQuery,
+        limit: usize,
+        filter: &dyn Fn(Key) -> bool,
+    ) -> Vec<KeyScoreMatch> {
+        let to_match = |(entry, distance_2): (&GeoEntry, f64)| KeyScoreMatch {
+            key: entry.key,
+            score: -distance_2.sqrt(),
+        };
+        match query {
+            GeoQuery::Nearest(point) => self
+                .tree
+                .nearest_neighbor_iter_with_distance_2(point)
+                .filter(|(entry, _)| filter(entry.key))
+                .take(limit)
+                .map(to_match)
+                .collect(),
+            GeoQuery::WithinRadius(center, radius) => self
+                .tree
+                .nearest_neighbor_iter_with_distance_2(center)
+                .take_while(|(_, distance_2)| *distance_2 <= radius * radius)
+                .filter(|(entry, _)| filter(entry.key))
+                .take(limit)
+                .map(to_match)
+                .collect(),
+            GeoQuery::BoundingBox(corner, opposite_corner) => {
+                let bounding_box = AABB::from_corners(*corner, *opposite_corner);
+                let center = bounding_box.center();
+                let mut matches: Vec<(&GeoEntry, f64)> = self
+                    .tree
+                    .locate_in_envelope_intersecting(&bounding_box)
+                    .filter(|entry| entry.intersects(&bounding_box) && filter(entry.key))
+                    .map(|entry| (entry, entry.distance_2(&center)))
+                    .collect();
+                matches.sort_by(|a, b| a.1.total_cmp(&b.1));
+                matches.into_iter().take(limit).map(to_match).collect()
+            }
+        }
+    }
+}
+
+impl Default for GeoIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NonFilteringExternalIndex<GeoShape, GeoQuery> for GeoIndex {
+    fn add(&mut self, add_data: Vec<(Key, GeoShape)>) -> Vec<(Key, DynResult<()>)> {
+        add_data
+            .into_iter()
+            .map(|(key, shape)| (key, self.add_one(key, shape)))
+            .collect()
+    }
+
+    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+        keys.into_iter()
+            .map(|key| (key, self.remove_one(key)))
+            .collect()
+    }
+
+    fn search(
+        &self,
+        queries: &[(Key, GeoQuery, usize)],
+    ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
+        queries
+            .iter()
+            .map(|(key, query, limit)| (*key, Ok(self.search_one(query, *limit, &|_| true))))
+            .collect()
+    }
+
+    fn filtered_search(
+        &self,
+        queries: &[(Key, GeoQuery, usize, KeyFilter<'_>)],
+    ) -> Option<Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>> {
+        Some(
+            queries
+                .iter()
+                .map(|(key, query, limit, filter)| {
+                    (*key, Ok(self.search_one(query, *limit, filter)))
+                })
+                .collect(),
+        )
+    }
+}
+
+pub struct GeoIndexFactory;
+
+impl ExternalIndexFactory for GeoIndexFactory {
+    fn make_instance(&self) -> Result<Box<dyn ExternalIndex>, Error> {
+        Ok(
+            Box::new(DerivedFilteredSearchIndex::new(Box::new(GeoIndex::new())))
+                as Box<dyn ExternalIndex>,
+        )
+    }
+}
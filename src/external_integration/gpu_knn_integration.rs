@@ -0,0 +1,329 @@
+// Copyright © 2024 Pathway
+
+use std::cmp::max;
+use std::sync::Arc;
+
+use cudarc::cublas::sys::cublasOperation_t;
+use cudarc::cublas::{CudaBlas, Gemm, GemmConfig};
+use cudarc::driver::{CudaDevice, CudaSlice};
+
+use crate::engine::error::DynResult;
+use crate::engine::Key;
+
+use super::brute_force_knn_integration::{BruteForceKnnMetricKind, TopK};
+use super::{KeyFilter, KeyScoreMatch, KeyToU64IdMapper, NonFilteringExternalIndex};
+
+// upper bound on the number of distances computed by a single matrix multiplication,
+// queries are split into batches so that the result buffer stays below 256MiB
+const MAX_DISTANCES_PER_BATCH: usize = 1 << 26;
+
+/// Brute-force k-NN index keeping the vectors in the memory of a CUDA device. Dot products
+/// of the queries with all the rows are computed with a single cuBLAS matrix multiplication
+/// per batch of queries, the closest rows are then selected on the host.
+pub struct GpuKNNIndex {
+    device: Arc<CudaDevice>,
+    blas: CudaBlas,
+    // row-major, with `allocated` rows of which the first `current_size` are in use
+    rows: CudaSlice<f32>,
+    sq_norms: Vec<f64>,
+    current_size: usize,
+    allocated: usize,
+    minimum_allocated: usize,
+    dimensions: usize,
+    metric: BruteForceKnnMetricKind,
+    key_to_id_mapper: KeyToU64IdMapper,
+}
+
+struct GpuQuery<'a> {
+    key: Key,
+    data: &'a [f64],
+    limit: usize,
+    filter: Option<&'a KeyFilter<'a>>,
+}
+
+impl GpuKNNIndex {
+    pub fn new(
+        dimensions: usize,
+        reserved_space: usize,
+        metric: BruteForceKnnMetricKind,
+        device_ordinal: usize,
+    ) -> DynResult<GpuKNNIndex> {
+        let device = CudaDevice::new(device_ordinal)?;
+        let blas = CudaBlas::new(device.clone())?;
+        let allocated = max(1, reserved_space);
+        let rows = device.alloc_zeros::<f32>(allocated * dimensions)?;
+        Ok(GpuKNNIndex {
+            device,
+            blas,
+            rows,
+            sq_norms: vec![0.0; allocated],
+            current_size: 0,
+            allocated,
+            minimum_allocated: allocated,
+            dimensions,
+            metric,
+            key_to_id_mapper: KeyToU64IdMapper::new(),
+        })
+    }
+
+    fn resize(&mut self, new_allocated: usize) -> DynResult<()> {
+        let mut new_rows = self
+            .device
+            .alloc_zeros::<f32>(new_allocated * self.dimensions)?;
+        if self.current_size > 0 {
+            let used = self.current_size * self.dimensions;
+            self.device
+                .dtod_copy(&self.rows.slice(0..used), &mut new_rows.slice_mut(0..used))?;
+        }
+        self.rows = new_rows;
+        self.sq_norms.resize(new_allocated, 0.0);
+        self.allocated = new_allocated;
+        Ok(())
+    }
+
+    fn upload_row(&mut self, idx: usize, row: &[f32]) -> DynResult<()> {
+        let mut view = self
+            .rows
+            .slice_mut(idx * self.dimensions..(idx + 1) * self.dimensions);
+        self.device.htod_sync_copy_into(row, &mut view)?;
+        Ok(())
+    }
+
+    fn download_row(&self, idx: usize) -> DynResult<Vec<f32>> {
+        let view = self
+            .rows
+            .slice(idx * self.dimensions..(idx + 1) * self.dimensions);
+        Ok(self.device.dtoh_sync_copy(&view)?)
+    }
+
+    fn key_for_idx(&self, idx: usize) -> Key {
+        self.key_to_id_mapper
+            .get_key_for_id(u64::try_from(idx).unwrap())
+            .unwrap()
+    }
+
+    fn add_one(&mut self, key: Key, data: &[f64]) -> DynResult<()> {
+        if data.len() != self.dimensions {
+            return Err(format!(
+                "vector has {} dimensions, the index expects {}",
+                data.len(),
+                self.dimensions
+            )
+            .into());
+        }
+        if self.current_size == self.allocated {
+            self.resize(2 * self.allocated)?;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let row: Vec<f32> = data.iter().map(|value| *value as f32).collect();
+        let idx = usize::try_from(self.key_to_id_mapper.get_next_free_u64_id(key))?;
+        self.upload_row(idx, &row)?;
+        self.sq_norms[idx] = data.iter().map(|value| value * value).sum();
+        // a key that is already present keeps its row, which is overwritten
+        self.current_size = max(self.current_size, idx + 1);
+        Ok(())
+    }
+
+    fn remove_one(&mut self, key: Key) -> DynResult<()> {
+        let last_row_key = self
+            .current_size
+            .checked_sub(1)
+            .map(|last_idx| self.key_for_idx(last_idx));
+        let removed_id = self.key_to_id_mapper.remove_key(key)?;
+        let last_row_key = last_row_key.expect("index with a removed key can't be empty");
+        self.current_size -= 1;
+        self.key_to_id_mapper.decrement_next_free_id();
+        if last_row_key != key {
+            // if the last row had a different key, put its entry to the removed position
+            let removed_idx = usize::try_from(removed_id)?;
+            let last_row = self.download_row(self.current_size)?;
+            self.upload_row(removed_idx, &last_row)?;
+            self.sq_norms[removed_idx] = self.sq_norms[self.current_size];
+            self.key_to_id_mapper.assign_key(last_row_key, removed_id);
+        }
+        Ok(())
+    }
+
+    /// Computes the dot products of all rows with the queries. The result is column-major,
+    /// i.e. the dot product of row `i` with query `j` is at `i + j * current_size`.
+    fn dot_products(&self, queries: &[&GpuQuery]) -> DynResult<Vec<f32>> {
+        #[allow(clippy::cast_possible_truncation)]
+        let host_queries: Vec<f32> = queries
+            .iter()
+            .flat_map(|query| query.data.iter().map(|value| *value as f32))
+            .collect();
+        let device_queries = self.device.htod_sync_copy(&host_queries)?;
+        let mut result = self
+            .device
+            .alloc_zeros::<f32>(self.current_size * queries.len())?;
+        let n_rows = i32::try_from(self.current_size)?;
+        let dimensions = i32::try_from(self.dimensions)?;
+        let config = GemmConfig {
+            transa: cublasOperation_t::CUBLAS_OP_T,
+            transb: cublasOperation_t::CUBLAS_OP_N,
+            m: n_rows,
+            n: i32::try_from(queries.len())?,
+            k: dimensions,
+            alpha: 1.0,
+            lda: dimensions,
+            ldb: dimensions,
+            beta: 0.0,
+            ldc: n_rows,
+        };
+        // SAFETY: buffer sizes match the dimensions given in `config`
+        unsafe {
+            self.blas
+                .gemm(config, &self.rows, &device_queries, &mut result)?;
+        }
+        Ok(self.device.dtoh_sync_copy(&result)?)
+    }
+
+    fn distance(&self, idx: usize, dot: f64, query_sq_norm: f64) -> f64 {
+        match self.metric {
+            BruteForceKnnMetricKind::L2sq => self.sq_norms[idx] + query_sq_norm - 2.0 * dot,
+            BruteForceKnnMetricKind::Cos => 1.0 - dot / (self.sq_norms[idx] * query_sq_norm).sqrt(),
+        }
+    }
+
+    fn search_batch(&self, queries: &[&GpuQuery]) -> DynResult<Vec<Vec<KeyScoreMatch>>> {
+        let dot_products = self.dot_products(queries)?;
+        let results = queries
+            .iter()
+            .zip(dot_products.chunks(self.current_size))
+            .map(|(query, dots)| {
+                let query_sq_norm: f64 = query.data.iter().map(|value| value * value).sum();
+                let mut top_k = TopK::new(query.limit);
+                for (idx, dot) in dots.iter().enumerate() {
+                    let distance = self.distance(idx, f64::from(*dot), query_sq_norm);
+                    if top_k.admits(distance)
+                        && query
+                            .filter
+                            .is_none_or(|filter| filter(self.key_for_idx(idx)))
+                    {
+                        top_k.push(distance, idx);
+                    }
+                }
+                top_k
+                    .into_sorted()
+                    .into_iter()
+                    .map(|(distance, idx)| KeyScoreMatch {
+                        key: self.key_for_idx(idx),
+                        score: -distance,
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok(results)
+    }
+
+    fn search_impl(&self, queries: &[GpuQuery]) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
+        let mut results: Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> = Vec::new();
+        let mut valid_queries = Vec::new();
+        let mut valid_positions = Vec::new();
+        for query in queries {
+            if query.data.len() == self.dimensions {
+                valid_positions.push(results.len());
+                valid_queries.push(query);
+                results.push((query.key, Ok(Vec::new())));
+            } else {
+                results.push((
+                    query.key,
+                    Err(format!(
+                        "query has {} dimensions, the index expects {}",
+                        query.data.len(),
+                        self.dimensions
+                    )
+                    .into()),
+                ));
+            }
+        }
+        if self.current_size == 0 {
+            return results;
+        }
+
+        let batch_size = max(1, MAX_DISTANCES_PER_BATCH / self.current_size);
+        for (batch, positions) in valid_queries
+            .chunks(batch_size)
+            .zip(valid_positions.chunks(batch_size))
+        {
+            match self.search_batch(batch) {
+                Ok(matches) => {
+                    for (position, matches) in positions.iter().zip(matches) {
+                        results[*position].1 = Ok(matches);
+                    }
+                }
+                Err(error) => {
+                    let message = error.to_string();
+                    for position in positions {
+                        results[*position].1 = Err(message.clone().into());
+                    }
+                }
+            }
+        }
+        results
+    }
+}
+
+impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for GpuKNNIndex {
+    fn add(&mut self, add_data: Vec<(Key, Vec<f64>)>) -> Vec<(Key, DynResult<()>)> {
+        let needed = self.current_size + add_data.len();
+        if needed > self.allocated {
+            if let Err(error) = self.resize(max(2 * self.allocated, needed)) {
+                let message = error.to_string();
+                return add_data
+                    .into_iter()
+                    .map(|(key, _)| (key, Err(message.clone().into())))
+                    .collect();
+            }
+        }
+        add_data
+            .into_iter()
+            .map(|(key, data)| (key, self.add_one(key, &data)))
+            .collect()
+    }
+
+    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+        let ret = keys
+            .into_iter()
+            .map(|key| (key, self.remove_one(key)))
+            .collect();
+
+        if 4 * self.current_size < self.allocated && self.allocated / 2 >= self.minimum_allocated {
+            // shrinking is only an optimization, the index stays usable if it fails
+            let _ = self.resize(self.allocated / 2);
+        }
+        ret
+    }
+
+    fn search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize)],
+    ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
+        let queries: Vec<_> = queries
+            .iter()
+            .map(|(key, data, limit)| GpuQuery {
+                key: *key,
+                data,
+                limit: *limit,
+                filter: None,
+            })
+            .collect();
+        self.search_impl(&queries)
+    }
+
+    fn filtered_search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize, KeyFilter<'_>)],
+    ) -> Option<Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>> {
+        let queries: Vec<_> = queries
+            .iter()
+            .map(|(key, data, limit, filter)| GpuQuery {
+                key: *key,
+                data,
+                limit: *limit,
+                filter: Some(filter),
+            })
+            .collect();
+        Some(self.search_impl(&queries))
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use itertools::Itertools;
+
+use crate::engine::error::{DataError, DynResult};
+use crate::engine::{Error, Key, Value};
+
+use super::{AddDataEntry, ExternalIndex, ExternalIndexFactory, KeyScoreMatch, QueryEntry};
+
+/// Constant dampening the influence of the top ranks in reciprocal rank fusion, as proposed in
+/// the original paper.
+pub const DEFAULT_RRF_K: f64 = 60.0;
+
+/// Method of merging the rankings returned by the lexical and the vector index.
+#[derive(Debug, Clone, Copy)]
+pub enum FusionMethod {
+    /// Each match is scored with the sum of `1 / (k + rank)` over the rankings it appears in.
+    ReciprocalRank { k: f64 },
+    /// The scores of each ranking are min-max normalized to `[0, 1]` and summed with the given
+    /// weights. A match missing from a ranking gets 0 from it.
+    Weighted {
+        lexical_weight: f64,
+        vector_weight: f64,
+    },
+}
+
+/// Index running lexical and vector retrieval over the same documents and returning a single
+/// fused ranking. The indexed data and the queries are pairs `(text, vector)`.
+pub struct HybridIndex {
+    lexical: Box<dyn ExternalIndex>,
+    vector: Box<dyn ExternalIndex>,
+    fusion: FusionMethod,
+    candidates_multiplier: usize,
+}
+
+fn split_pair(value: &Value) -> DynResult<(Value, Value)> {
+    match value.as_tuple()?.as_ref() {
+        [text, vector] => Ok((text.clone(), vector.clone())),
+        _ => Err(DataError::ValueError(format!(
+            "hybrid index expects pairs (text, vector), got {value}"
+        ))
+        .into()),
+    }
+}
+
+impl HybridIndex {
+    pub fn new(
+        lexical: Box<dyn ExternalIndex>,
+        vector: Box<dyn ExternalIndex>,
+        fusion: FusionMethod,
+        candidates_multiplier: usize,
+    ) -> HybridIndex {
+        HybridIndex {
+            lexical,
+            vector,
+            fusion,
+            candidates_multiplier: candidates_multiplier.max(1),
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn fuse(&self, rankings: [Vec<KeyScoreMatch>; 2], limit: usize) -> Vec<KeyScoreMatch> {
+        let mut scores: HashMap<Key, f64> = HashMap::new();
+        // keys in the order of the first appearance, so that ties are resolved deterministically
+        let mut order = Vec::new();
+        let mut add_score = |key: Key, score: f64| {
+            *scores.entry(key).or_insert_with(|| {
+                order.push(key);
+                0.0
+            }) += score;
+        };
+        match self.fusion {
+            FusionMethod::ReciprocalRank { k } => {
+                for ranking in &rankings {
+                    for (rank, entry) in ranking.iter().enumerate() {
+                        add_score(entry.key, 1.0 / (k + (rank + 1) as f64));
+                    }
+                }
+            }
+            FusionMethod::Weighted {
+                lexical_weight,
+                vector_weight,
+            } => {
+                for (ranking, weight) in rankings.iter().zip([lexical_weight, vector_weight]) {
+                    let (min, max) = ranking
+                        .iter()
+                        .map(|entry| entry.score)
+                        .minmax()
+                        .into_option()
+                        .unwrap_or_default();
+                    for entry in ranking {
+                        let normalized = if max > min {
+                            (entry.score - min) / (max - min)
+                        } else {
+                            1.0
+                        };
+                        add_score(entry.key, weight * normalized);
+                    }
+                }
+            }
+        }
+        let mut fused: Vec<KeyScoreMatch> = order
+            .into_iter()
+            .map(|key| KeyScoreMatch {
+                key,
+                score: scores[&key],
+            })
+            .collect();
+        fused.sort_by(|a, b| b.score.total_cmp(&a.score));
+        fused.truncate(limit);
+        fused
+    }
+}
+
+impl ExternalIndex for HybridIndex {
+    fn add(&mut self, add_data: Vec<AddDataEntry>) -> Vec<(Key, DynResult<()>)> {
+        let mut lexical_batch = Vec::with_capacity(add_data.len());
+        let mut vector_batch = Vec::with_capacity(add_data.len());
+        let mut ret = Vec::new();
+        for entry in add_data {
+            match split_pair(&entry.data) {
+                Ok((text, vector)) => {
+                    lexical_batch.push(AddDataEntry {
+                        key: entry.key,
+                        data: text,
+                        filter_data: entry.filter_data.clone(),
+                    });
+                    vector_batch.push(AddDataEntry {
+                        key: entry.key,
+                        data: vector,
+                        filter_data: entry.filter_data,
+                    });
+                }
+                Err(error) => ret.push((entry.key, Err(error))),
+            }
+        }
+        // an entry is added if both indices accepted it, otherwise it's removed from the one
+        // that accepted it, so that the indices hold the same documents
+        let mut lexical_results: HashMap<Key, DynResult<()>> =
+            self.lexical.add(lexical_batch).into_iter().collect();
+        let mut to_remove_lexical = Vec::new();
+        let mut to_remove_vector = Vec::new();
+        for (key, vector_result) in self.vector.add(vector_batch) {
+            let result = match (lexical_results.remove(&key), vector_result) {
+                (Some(Ok(())), Ok(())) => Ok(()),
+                (Some(Ok(())), Err(error)) => {
+                    to_remove_lexical.push(key);
+                    Err(error)
+                }
+                (Some(Err(error)), vector_result) => {
+                    if vector_result.is_ok() {
+                        to_remove_vector.push(key);
+                    }
+                    Err(error)
+                }
+                (None, vector_result) => vector_result,
+            };
+            ret.push((key, result));
+        }
+        drop(self.lexical.remove(to_remove_lexical));
+        drop(self.vector.remove(to_remove_vector));
+        ret
+    }
+
+    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+        let mut lexical_results: HashMap<Key, DynResult<()>> =
+            self.lexical.remove(keys.clone()).into_iter().collect();
+        self.vector
+            .remove(keys)
+            .into_iter()
+            .map(|(key, vector_result)| {
+                let lexical_result = lexical_results.remove(&key).unwrap_or(Ok(()));
+                (key, lexical_result.and(vector_result))
+            })
+            .collect()
+    }
+
+    fn search(&self, query_data: &[QueryEntry]) -> Vec<(Key, DynResult<Value>)> {
+        let mut ret = Vec::with_capacity(query_data.len());
+        let mut limits = Vec::with_capacity(query_data.len());
+        let mut lexical_queries = Vec::with_capacity(query_data.len());
+        let mut vector_queries = Vec::with_capacity(query_data.len());
+        for query in query_data {
+            let limit = match &query.limit {
+                Some(limit) => limit.as_int().and_then(|limit| Ok(usize::try_from(limit)?)),
+                None => Ok(1),
+            };
+            let prepared = limit.and_then(|limit| Ok((limit, split_pair(&query.data)?)));
+            match prepared {
+                Ok((limit, (text, vector))) => {
+                    // each ranking is deeper than the result, so that documents ranked
+                    // moderately by both indices can make it to the top
+                    let candidates = Value::from(
+                        i64::try_from(limit.saturating_mul(self.candidates_multiplier))
+                            .unwrap_or(i64::MAX),
+                    );
+                    limits.push((query.key, limit));
+                    lexical_queries.push(QueryEntry {
+                        key: query.key,
+                        data: text,
+                        limit: Some(candidates.clone()),
+                        filter: query.filter.clone(),
+                    });
+                    vector_queries.push(QueryEntry {
+                        key: query.key,
+                        data: vector,
+                        limit: Some(candidates),
+                        filter: query.filter.clone(),
+                    });
+                }
+                Err(error) => ret.push((query.key, Err(error))),
+            }
+        }
+        let mut lexical_replies: HashMap<Key, DynResult<Value>> =
+            self.lexical.search(&lexical_queries).into_iter().collect();
+        let mut vector_replies: HashMap<Key, DynResult<Value>> =
+            self.vector.search(&vector_queries).into_iter().collect();
+        for (key, limit) in limits {
+            let lexical = lexical_replies
+                .remove(&key)
+                .expect("each query should have a reply");
+            let vector = vector_replies
+                .remove(&key)
+                .expect("each query should have a reply");
+            let fused = lexical.and_then(|lexical| {
+                let rankings = [
+                    KeyScoreMatch::from_reply(&lexical)?,
+                    KeyScoreMatch::from_reply(&vector?)?,
+                ];
+                Ok(Value::Tuple(
+                    self.fuse(rankings, limit)
+                        .into_iter()
+                        .map(KeyScoreMatch::into_value)
+                        .collect(),
+                ))
+            });
+            ret.push((key, fused));
+        }
+        ret
+    }
+}
+
+pub struct HybridIndexFactory {
+    lexical: Arc<dyn ExternalIndexFactory>,
+    vector: Arc<dyn ExternalIndexFactory>,
+    fusion: FusionMethod,
+    // number of candidates taken from each index, relative to the number of requested results
+    candidates_multiplier: usize,
+}
+
+impl HybridIndexFactory {
+    pub fn new(
+        lexical: Arc<dyn ExternalIndexFactory>,
+        vector: Arc<dyn ExternalIndexFactory>,
+        fusion: FusionMethod,
+        candidates_multiplier: usize,
+    ) -> HybridIndexFactory {
+        HybridIndexFactory {
+            lexical,
+            vector,
+            fusion,
+            candidates_multiplier,
+        }
+    }
+}
+
+impl ExternalIndexFactory for HybridIndexFactory {
+    fn make_instance(&self) -> Result<Box<dyn ExternalIndex>, Error> {
+        Ok(Box::new(HybridIndex::new(
+            self.lexical.make_instance()?,
+            self.vector.make_instance()?,
+            self.fusion,
+            self.candidates_multiplier,
+        )))
+    }
+}
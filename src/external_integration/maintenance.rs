@@ -0,0 +1,218 @@
+// Copyright © 2024 Pathway
+
+//! Rebuilding of external indices degraded by many removals and updates.
+//!
+//! Removed entries often aren't reclaimed by the indices right away (e.g. they're only marked
+//! as deleted in HNSW graphs), which wastes memory and lowers the recall of searches. A
+//! maintained index counts the removals and, once they make up a large enough fraction of the
+//! index, builds a fresh instance from the live entries. The new instance is filled a few
+//! entries per update, while the old one keeps answering queries, and replaces it once it holds
+//! all the entries.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use log::{error, info};
+
+use crate::engine::error::DynResult;
+use crate::engine::{Error, Key, Value};
+
+use super::{AddDataEntry, ExternalIndex, ExternalIndexFactory, QueryEntry};
+
+#[derive(Debug, Clone)]
+pub struct MaintenanceConfig {
+    /// Fraction of removed entries among all entries added since the index was built, above
+    /// which the index is rebuilt.
+    pub max_removed_fraction: f64,
+    /// Minimal number of removals before the index is rebuilt, so that small indices aren't
+    /// rebuilt all the time.
+    pub min_removals: usize,
+    /// Number of entries copied to the new index with each update.
+    pub rebuild_batch_size: usize,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            max_removed_fraction: 0.3,
+            min_removals: 1000,
+            rebuild_batch_size: 1000,
+        }
+    }
+}
+
+struct Rebuild {
+    index: Box<dyn ExternalIndex>,
+    to_copy: Vec<Key>,
+    copied: HashSet<Key>,
+}
+
+pub struct MaintainedIndex {
+    factory: Arc<dyn ExternalIndexFactory>,
+    config: MaintenanceConfig,
+    current: Box<dyn ExternalIndex>,
+    // live entries, needed to fill a new index
+    entries: HashMap<Key, (Value, Option<Value>)>,
+    added_since_build: usize,
+    removed_since_build: usize,
+    rebuild: Option<Rebuild>,
+}
+
+impl MaintainedIndex {
+    pub fn new(
+        factory: Arc<dyn ExternalIndexFactory>,
+        config: MaintenanceConfig,
+    ) -> Result<MaintainedIndex, Error> {
+        Ok(MaintainedIndex {
+            current: factory.make_instance()?,
+            factory,
+            config,
+            entries: HashMap::new(),
+            added_since_build: 0,
+            removed_since_build: 0,
+            rebuild: None,
+        })
+    }
+
+    fn entry(&self, key: Key) -> Option<AddDataEntry> {
+        self.entries
+            .get(&key)
+            .map(|(data, filter_data)| AddDataEntry {
+                key,
+                data: data.clone(),
+                filter_data: filter_data.clone(),
+            })
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn needs_rebuild(&self) -> bool {
+        self.rebuild.is_none()
+            && self.removed_since_build >= self.config.min_removals
+            && self.removed_since_build as f64
+                > self.config.max_removed_fraction * self.added_since_build as f64
+    }
+
+    fn start_rebuild(&mut self) -> DynResult<()> {
+        info!(
+            "Rebuilding external index with {} entries after {} removals",
+            self.entries.len(),
+            self.removed_since_build
+        );
+        self.rebuild = Some(Rebuild {
+            index: self.factory.make_instance()?,
+            to_copy: self.entries.keys().copied().collect(),
+            copied: HashSet::new(),
+        });
+        Ok(())
+    }
+
+    /// Copies the next batch of entries to the new index and replaces the current index with it
+    /// if it's complete.
+    fn continue_rebuild(&mut self) {
+        let Some(mut rebuild) = self.rebuild.take() else {
+            return;
+        };
+        let mut batch = Vec::with_capacity(self.config.rebuild_batch_size);
+        while batch.len() < self.config.rebuild_batch_size {
+            let Some(key) = rebuild.to_copy.pop() else {
+                break;
+            };
+            if rebuild.copied.contains(&key) {
+                continue;
+            }
+            if let Some(entry) = self.entry(key) {
+                rebuild.copied.insert(key);
+                batch.push(entry);
+            }
+        }
+        // the entries were accepted by the current index, so errors aren't expected here
+        drop(rebuild.index.add(batch));
+        if rebuild.to_copy.is_empty() {
+            self.current = rebuild.index;
+            self.added_since_build = self.entries.len();
+            self.removed_since_build = 0;
+        } else {
+            self.rebuild = Some(rebuild);
+        }
+    }
+}
+
+impl ExternalIndex for MaintainedIndex {
+    fn add(&mut self, add_data: Vec<AddDataEntry>) -> Vec<(Key, DynResult<()>)> {
+        let mut stored: HashMap<Key, (Value, Option<Value>)> = add_data
+            .iter()
+            .map(|entry| (entry.key, (entry.data.clone(), entry.filter_data.clone())))
+            .collect();
+        let results = self.current.add(add_data);
+        let mut added = Vec::new();
+        for (key, result) in &results {
+            if result.is_ok() {
+                if let Some(entry) = stored.remove(key) {
+                    self.entries.insert(*key, entry);
+                    added.push(*key);
+                }
+            }
+        }
+        self.added_since_build += added.len();
+        if let Some(mut rebuild) = self.rebuild.take() {
+            let batch: Vec<AddDataEntry> =
+                added.iter().filter_map(|key| self.entry(*key)).collect();
+            rebuild.copied.extend(added);
+            drop(rebuild.index.add(batch));
+            self.rebuild = Some(rebuild);
+        }
+        self.continue_rebuild();
+        results
+    }
+
+    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+        if let Some(rebuild) = &mut self.rebuild {
+            let copied: Vec<Key> = keys
+                .iter()
+                .filter(|key| rebuild.copied.remove(*key))
+                .copied()
+                .collect();
+            drop(rebuild.index.remove(copied));
+        }
+        let results = self.current.remove(keys);
+        for (key, result) in &results {
+            if result.is_ok() && self.entries.remove(key).is_some() {
+                self.removed_since_build += 1;
+            }
+        }
+        if self.needs_rebuild() {
+            if let Err(error) = self.start_rebuild() {
+                error!("Failed to start rebuilding external index: {error}");
+            }
+        }
+        self.continue_rebuild();
+        results
+    }
+
+    fn search(&self, query_data: &[QueryEntry]) -> Vec<(Key, DynResult<Value>)> {
+        self.current.search(query_data)
+    }
+}
+
+pub struct MaintainedIndexFactory {
+    inner: Arc<dyn ExternalIndexFactory>,
+    config: MaintenanceConfig,
+}
+
+impl MaintainedIndexFactory {
+    pub fn new(
+        inner: Arc<dyn ExternalIndexFactory>,
+        config: MaintenanceConfig,
+    ) -> MaintainedIndexFactory {
+        MaintainedIndexFactory { inner, config }
+    }
+}
+
+impl ExternalIndexFactory for MaintainedIndexFactory {
+    fn make_instance(&self) -> Result<Box<dyn ExternalIndex>, Error> {
+        Ok(Box::new(MaintainedIndex::new(
+            self.inner.clone(),
+            self.config.clone(),
+        )?))
+    }
+}
@@ -1,8 +1,19 @@
 // Copyright © 2024 Pathway
 
 pub mod brute_force_knn_integration;
+pub mod distance_kernels;
+#[cfg(feature = "faiss")]
+pub mod faiss_integration;
+pub mod geo_integration;
+#[cfg(feature = "cuda")]
+pub mod gpu_knn_integration;
+pub mod hybrid_integration;
+pub mod maintenance;
+pub mod named_vectors_integration;
+pub mod quantization;
 pub mod tantivy_integration;
 pub mod usearch_integration;
+use std::cell::RefCell;
 use std::ops::Deref;
 use std::{collections::HashMap, rc::Rc, sync::Arc};
 
@@ -16,11 +27,12 @@ use jmespath::{
 use differential_dataflow::difference::Abelian;
 
 use crate::engine::dataflow::operators::external_index::Index as IndexTrait;
-use crate::engine::error::DynResult;
+use crate::engine::error::{DynError, DynResult};
 use crate::engine::report_error::{
     LogError, ReportError, UnwrapWithErrorLogger, UnwrapWithReporter,
 };
 use crate::engine::{ColumnPath, DataError, Error, Key, Value};
+use crate::retry::{CircuitBreaker, CircuitOpenError};
 
 type PendingQueryEntry<'a, QType> = (&'a Key, (&'a QType, usize, usize, &'a Expression<'a>));
 
@@ -55,11 +67,14 @@ pub struct IndexDerivedImpl {
     query_accessor: Accessor,
     query_limit_accessor: OptionAccessor,
     query_filter_accessor: OptionAccessor,
+    circuit_breaker: Arc<CircuitBreaker>,
 }
 
 impl IndexDerivedImpl {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         inner: Box<dyn ExternalIndex>,
+        circuit_breaker: Arc<CircuitBreaker>,
         error_logger: Box<dyn LogError>,
         data_accessor: Accessor,
         filter_data_accessor: OptionAccessor,
@@ -75,10 +90,41 @@ impl IndexDerivedImpl {
             query_accessor,
             query_limit_accessor,
             query_filter_accessor,
+            circuit_breaker,
         }
     }
 }
 
+/// Makes a call to the index through the circuit breaker. A call fails if none of its entries
+/// succeeded, which is what happens when the dependency behind the index is down. If the
+/// circuit is open, the call isn't made and all the entries fail. Calls without entries aren't
+/// guarded, as they don't tell anything about the dependency.
+fn call_with_circuit_breaker<T>(
+    circuit_breaker: &CircuitBreaker,
+    keys: impl IntoIterator<Item = Key>,
+    call: impl FnOnce() -> Vec<(Key, DynResult<T>)>,
+) -> Vec<(Key, DynResult<T>)> {
+    let mut keys = keys.into_iter().peekable();
+    if keys.peek().is_none() {
+        return call();
+    }
+    if !circuit_breaker.try_acquire() {
+        return keys
+            .map(|key| {
+                let error = CircuitOpenError(circuit_breaker.name().to_string());
+                (key, Err(error.into()))
+            })
+            .collect();
+    }
+    let results = call();
+    if !results.is_empty() && results.iter().all(|(_key, result)| result.is_err()) {
+        circuit_breaker.on_failure();
+    } else {
+        circuit_breaker.on_success();
+    }
+    results
+}
+
 pub trait CanBeRetraction {
     fn is_retraction(&self) -> bool;
 }
@@ -130,11 +176,19 @@ impl<R: Abelian + CanBeRetraction> IndexTrait<Key, Value, R, Key, Value, Value>
                 }
             });
 
-        for (_key, res) in self.inner.remove(to_remove) {
+        let remove_keys = to_remove.clone();
+        let removed = call_with_circuit_breaker(&self.circuit_breaker, remove_keys, || {
+            self.inner.remove(to_remove)
+        });
+        for (_key, res) in removed {
             res.unwrap_or_log(self.error_logger.as_ref(), ());
         }
 
-        for (_key, res) in self.inner.add(to_insert) {
+        let insert_keys: Vec<Key> = to_insert.iter().map(|entry| entry.key).collect();
+        let added = call_with_circuit_breaker(&self.circuit_breaker, insert_keys, || {
+            self.inner.add(to_insert)
+        });
+        for (_key, res) in added {
             res.unwrap_or_log(self.error_logger.as_ref(), ());
         }
     }
@@ -167,7 +221,11 @@ impl<R: Abelian + CanBeRetraction> IndexTrait<Key, Value, R, Key, Value, Value>
                 }
             });
 
-        let maybe_error_responses = self.inner.search(&to_query_without_errors);
+        let maybe_error_responses = call_with_circuit_breaker(
+            &self.circuit_breaker,
+            to_query_without_errors.iter().map(|entry| entry.key),
+            || self.inner.search(&to_query_without_errors),
+        );
         let mut responses = HashMap::with_capacity(queries.len());
         for (key, maybe_result) in maybe_error_responses {
             responses.insert(
@@ -226,6 +284,19 @@ impl KeyToU64IdMapper {
         self.key_to_id_map.insert(key, id);
     }
 
+    /// Makes sure that IDs below `next_id` aren't given to new keys.
+    fn reserve_ids_below(&mut self, next_id: u64) {
+        self.next_id = self.next_id.max(next_id);
+    }
+
+    fn next_free_id(&self) -> u64 {
+        self.next_id
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (Key, u64)> + '_ {
+        self.key_to_id_map.iter().map(|(key, id)| (*key, *id))
+    }
+
     fn decrement_next_free_id(&mut self) {
         self.next_id -= 1;
     }
@@ -322,6 +393,21 @@ impl KeyScoreMatch {
     fn into_value(self) -> Value {
         Value::Tuple(Arc::new([Value::from(self.key), Value::from(self.score)]))
     }
+
+    /// Parses a reply of an index, i.e. a tuple of `(key, score)` pairs.
+    fn from_reply(reply: &Value) -> DynResult<Vec<KeyScoreMatch>> {
+        reply
+            .as_tuple()?
+            .iter()
+            .map(|entry| match entry.as_tuple()?.as_ref() {
+                [key, score] => Ok(KeyScoreMatch {
+                    key: key.as_pointer()?,
+                    score: score.as_float()?,
+                }),
+                _ => Err(DataError::ValueError(format!("malformed index reply {entry}")).into()),
+            })
+            .try_collect()
+    }
 }
 
 /*data unpacking types, so that we can define unpacking from values to types by setting generic types*/
@@ -338,6 +424,7 @@ impl Unpack<Vec<f64>> for Value {
             Value::Tuple(values) => Ok(values.iter().map(Value::as_float).try_collect()?),
             Value::IntArray(values) => Ok(values.iter().map(|i| *i as f64).collect()),
             Value::FloatArray(values) => Ok(values.iter().copied().collect()),
+            Value::Float32Array(values) => Ok(values.iter().copied().map(f64::from).collect()),
             value => Err(Box::new(DataError::TypeMismatch {
                 expected: "vector of floats",
                 value,
@@ -361,6 +448,9 @@ impl Unpack<String> for Value {
     }
 }
 
+/// Predicate deciding whether an entry of the index, identified by its key, can be returned.
+pub type KeyFilter<'a> = Box<dyn Fn(Key) -> bool + 'a>;
+
 pub trait NonFilteringExternalIndex<DataType, QueryType> {
     fn add(&mut self, batch: Vec<(Key, DataType)>) -> Vec<(Key, DynResult<()>)>;
     fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)>;
@@ -368,6 +458,19 @@ pub trait NonFilteringExternalIndex<DataType, QueryType> {
         &self,
         queries: &[(Key, QueryType, usize)],
     ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>;
+
+    /// Searches only among the entries accepted by the filter of each query. The filters are
+    /// checked while the index is traversed, so that even with selective filters the number
+    /// of results is only limited by the number of matching entries.
+    ///
+    /// Returns `None` if the index doesn't support it, the results are then filtered after
+    /// the search.
+    fn filtered_search(
+        &self,
+        _queries: &[(Key, QueryType, usize, KeyFilter<'_>)],
+    ) -> Option<Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>> {
+        None
+    }
 }
 
 pub struct DerivedFilteredSearchIndex<DataType, QueryType> {
@@ -443,6 +546,66 @@ where
             .try_collect()?)
     }
 
+    /// Filters the results with `expr` while the inner index is searched. Returns `None` if
+    /// the inner index doesn't support it.
+    fn search_with_filter_in_traversal(
+        &self,
+        queries: &[(Key, QueryType, usize, Expression)],
+    ) -> Option<Vec<(Key, DynResult<Value>)>>
+    where
+        QueryType: Clone,
+    {
+        // errors can't be returned from the filters, so the first one is kept for each query
+        let errors: Vec<RefCell<Option<DynError>>> =
+            queries.iter().map(|_| RefCell::new(None)).collect();
+        let filtered_queries: Vec<_> = queries
+            .iter()
+            .zip(&errors)
+            .map(|((key, query, limit, expr), error)| {
+                let filter: KeyFilter<'_> = Box::new(move |entry_key| {
+                    let Some(filter_data) = self.filter_data_map.get(&entry_key) else {
+                        return false;
+                    };
+                    let accepted =
+                        expr.search(filter_data)
+                            .map_err(DynError::from)
+                            .and_then(|result| {
+                                result.as_boolean().ok_or_else(|| {
+                                    DataError::ValueError(
+                                        "jmespath filter expression did not return a boolean value"
+                                            .to_string(),
+                                    )
+                                    .into()
+                                })
+                            });
+                    accepted.unwrap_or_else(|e| {
+                        error.borrow_mut().get_or_insert(e);
+                        false
+                    })
+                });
+                (*key, query.clone(), *limit, filter)
+            })
+            .collect();
+        let answers = self.inner.filtered_search(&filtered_queries)?;
+        drop(filtered_queries);
+        Some(
+            answers
+                .into_iter()
+                .zip(errors)
+                .map(|((key, results), error)| {
+                    let results = match error.into_inner() {
+                        Some(error) => Err(error),
+                        None => results,
+                    };
+                    let response = results.map(|results| {
+                        Value::Tuple(results.into_iter().map(KeyScoreMatch::into_value).collect())
+                    });
+                    (key, response)
+                })
+                .collect(),
+        )
+    }
+
     fn retain_unfinished_queries<'a>(
         &self,
         pending: &[PendingQueryEntry<'a, QueryType>],
@@ -569,6 +732,11 @@ where
             }
         }
 
+        if let Some(filtered_responses) = self.search_with_filter_in_traversal(&filtering_queries) {
+            responses.extend(filtered_responses);
+            return responses;
+        }
+
         let mut pending = Vec::with_capacity(filtering_queries.len());
         for (key, query, limit, filter) in &filtering_queries {
             pending.push((key, (query, *limit, *limit, filter)));
@@ -0,0 +1,328 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use itertools::Itertools;
+
+use crate::engine::error::{DataError, DynError, DynResult};
+use crate::engine::{Error, Key, Value};
+
+use super::{AddDataEntry, ExternalIndex, ExternalIndexFactory, KeyScoreMatch, QueryEntry};
+
+/// Index over rows with several vectors, each in its own named space with a separate inner
+/// index. The indexed data is either a JSON object mapping space names to vectors or a tuple
+/// of `(name, vector)` pairs; a row doesn't have to have a vector in every space.
+///
+/// A query is either a `(name, vector)` pair, searching a single space, or a tuple of
+/// `(name, vector, weight)` triples. In the latter case the matches are scored with the
+/// weighted sum of their scores in the spaces. A match missing from the results of a space
+/// gets the lowest score found in it, so weights should take the scales of the scores in the
+/// spaces into account.
+pub struct NamedVectorsIndex {
+    spaces: HashMap<String, Box<dyn ExternalIndex>>,
+    // spaces in which each indexed row has a vector
+    memberships: HashMap<Key, Vec<String>>,
+    candidates_multiplier: usize,
+}
+
+struct SpaceQuery {
+    space: String,
+    vector: Value,
+    weight: f64,
+}
+
+fn json_to_vector(json: &serde_json::Value) -> DynResult<Value> {
+    let coordinates: Vec<Value> = json
+        .as_array()
+        .ok_or_else(|| DataError::ValueError(format!("expected a vector, got {json}")))?
+        .iter()
+        .map(|coordinate| {
+            coordinate.as_f64().map(Value::from).ok_or_else(|| {
+                DataError::ValueError(format!("expected a number, got {coordinate}"))
+            })
+        })
+        .try_collect()?;
+    Ok(Value::Tuple(coordinates.into()))
+}
+
+fn split_vectors(value: &Value) -> DynResult<Vec<(String, Value)>> {
+    if let Value::Json(json) = value {
+        let object = json.as_object().ok_or_else(|| {
+            DataError::ValueError(format!(
+                "expected a JSON object mapping names to vectors, got {json}"
+            ))
+        })?;
+        return object
+            .iter()
+            .map(|(space, vector)| Ok((space.clone(), json_to_vector(vector)?)))
+            .try_collect();
+    }
+    value
+        .as_tuple()?
+        .iter()
+        .map(|entry| match entry.as_tuple()?.as_ref() {
+            [space, vector] => Ok((space.as_string()?.to_string(), vector.clone())),
+            _ => Err(
+                DataError::ValueError(format!("expected a pair (name, vector), got {entry}"))
+                    .into(),
+            ),
+        })
+        .try_collect()
+}
+
+fn split_query(value: &Value) -> DynResult<Vec<SpaceQuery>> {
+    let entries = value.as_tuple()?;
+    if let [Value::String(space), vector] = entries.as_ref() {
+        return Ok(vec![SpaceQuery {
+            space: space.to_string(),
+            vector: vector.clone(),
+            weight: 1.0,
+        }]);
+    }
+    entries
+        .iter()
+        .map(|entry| match entry.as_tuple()?.as_ref() {
+            [space, vector, weight] => Ok(SpaceQuery {
+                space: space.as_string()?.to_string(),
+                vector: vector.clone(),
+                weight: weight.as_float()?,
+            }),
+            _ => Err(DataError::ValueError(format!(
+                "expected a triple (name, vector, weight), got {entry}"
+            ))
+            .into()),
+        })
+        .try_collect()
+}
+
+impl NamedVectorsIndex {
+    pub fn new(
+        spaces: HashMap<String, Box<dyn ExternalIndex>>,
+        candidates_multiplier: usize,
+    ) -> NamedVectorsIndex {
+        NamedVectorsIndex {
+            spaces,
+            memberships: HashMap::new(),
+            candidates_multiplier: candidates_multiplier.max(1),
+        }
+    }
+
+    fn check_space(&self, space: &str) -> DynResult<()> {
+        if self.spaces.contains_key(space) {
+            Ok(())
+        } else {
+            Err(DataError::ValueError(format!("unknown vector space {space:?}")).into())
+        }
+    }
+
+    fn combine(rankings: &[(f64, Vec<KeyScoreMatch>)], limit: usize) -> Vec<KeyScoreMatch> {
+        // keys in the order of the first appearance, so that ties are resolved deterministically
+        let order: Vec<Key> = rankings
+            .iter()
+            .flat_map(|(_, ranking)| ranking.iter().map(KeyScoreMatch::key))
+            .unique()
+            .collect();
+        let mut combined: Vec<KeyScoreMatch> = order
+            .into_iter()
+            .map(|key| {
+                let score = rankings
+                    .iter()
+                    .map(|(weight, ranking)| {
+                        let score = ranking
+                            .iter()
+                            .find(|entry| entry.key == key)
+                            .or_else(|| ranking.last())
+                            .map_or(0.0, |entry| entry.score);
+                        weight * score
+                    })
+                    .sum();
+                KeyScoreMatch { key, score }
+            })
+            .collect();
+        combined.sort_by(|a, b| b.score.total_cmp(&a.score));
+        combined.truncate(limit);
+        combined
+    }
+}
+
+impl ExternalIndex for NamedVectorsIndex {
+    fn add(&mut self, add_data: Vec<AddDataEntry>) -> Vec<(Key, DynResult<()>)> {
+        let mut batches: HashMap<String, Vec<AddDataEntry>> = HashMap::new();
+        let mut ret = Vec::new();
+        let mut pending: HashMap<Key, Vec<String>> = HashMap::new();
+        for entry in add_data {
+            let vectors = split_vectors(&entry.data).and_then(|vectors| {
+                if vectors.is_empty() {
+                    return Err(DataError::ValueError("row has no vectors".to_string()).into());
+                }
+                for (space, _) in &vectors {
+                    self.check_space(space)?;
+                }
+                Ok(vectors)
+            });
+            match vectors {
+                Ok(vectors) => {
+                    let mut spaces = Vec::with_capacity(vectors.len());
+                    for (space, vector) in vectors {
+                        batches
+                            .entry(space.clone())
+                            .or_default()
+                            .push(AddDataEntry {
+                                key: entry.key,
+                                data: vector,
+                                filter_data: entry.filter_data.clone(),
+                            });
+                        spaces.push(space);
+                    }
+                    pending.insert(entry.key, spaces);
+                }
+                Err(error) => ret.push((entry.key, Err(error))),
+            }
+        }
+
+        // a row is added if all its spaces accepted it, otherwise it's removed from the ones
+        // that did, so that a row is either fully indexed or not at all
+        let mut errors: HashMap<Key, DynError> = HashMap::new();
+        let mut accepted: HashMap<Key, Vec<String>> = HashMap::new();
+        for (space, batch) in batches {
+            let index = self.spaces.get_mut(&space).expect("space should exist");
+            for (key, result) in index.add(batch) {
+                match result {
+                    Ok(()) => accepted.entry(key).or_default().push(space.clone()),
+                    Err(error) => {
+                        errors.entry(key).or_insert(error);
+                    }
+                }
+            }
+        }
+        for (key, spaces) in pending {
+            if let Some(error) = errors.remove(&key) {
+                for space in accepted.remove(&key).unwrap_or_default() {
+                    let index = self.spaces.get_mut(&space).expect("space should exist");
+                    drop(index.remove(vec![key]));
+                }
+                ret.push((key, Err(error)));
+            } else {
+                self.memberships.insert(key, spaces);
+                ret.push((key, Ok(())));
+            }
+        }
+        ret
+    }
+
+    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+        keys.into_iter()
+            .map(|key| {
+                let Some(spaces) = self.memberships.remove(&key) else {
+                    return (
+                        key,
+                        Err(DataError::ValueError(format!("key {key} is not indexed")).into()),
+                    );
+                };
+                let mut result = Ok(());
+                for space in spaces {
+                    let index = self.spaces.get_mut(&space).expect("space should exist");
+                    for (_, space_result) in index.remove(vec![key]) {
+                        result = result.and(space_result);
+                    }
+                }
+                (key, result)
+            })
+            .collect()
+    }
+
+    fn search(&self, query_data: &[QueryEntry]) -> Vec<(Key, DynResult<Value>)> {
+        query_data
+            .iter()
+            .map(|query| (query.key, self.search_one(query)))
+            .collect()
+    }
+}
+
+impl NamedVectorsIndex {
+    fn search_one(&self, query: &QueryEntry) -> DynResult<Value> {
+        let space_queries = split_query(&query.data)?;
+        for space_query in &space_queries {
+            self.check_space(&space_query.space)?;
+        }
+        if let [space_query] = space_queries.as_slice() {
+            if space_query.weight > 0.0 {
+                // a single space answers the query directly
+                return self.spaces[&space_query.space]
+                    .search(&[QueryEntry {
+                        key: query.key,
+                        data: space_query.vector.clone(),
+                        limit: query.limit.clone(),
+                        filter: query.filter.clone(),
+                    }])
+                    .pop()
+                    .expect("each query should have a reply")
+                    .1;
+            }
+        }
+        let limit = match &query.limit {
+            Some(limit) => usize::try_from(limit.as_int()?)?,
+            None => 1,
+        };
+        // each ranking is deeper than the result, so that rows ranked moderately in several
+        // spaces can make it to the top
+        let candidates = Value::from(
+            i64::try_from(limit.saturating_mul(self.candidates_multiplier)).unwrap_or(i64::MAX),
+        );
+        let rankings: Vec<(f64, Vec<KeyScoreMatch>)> = space_queries
+            .into_iter()
+            .map(|space_query| {
+                let reply = self.spaces[&space_query.space]
+                    .search(&[QueryEntry {
+                        key: query.key,
+                        data: space_query.vector,
+                        limit: Some(candidates.clone()),
+                        filter: query.filter.clone(),
+                    }])
+                    .pop()
+                    .expect("each query should have a reply")
+                    .1?;
+                Ok::<_, DynError>((space_query.weight, KeyScoreMatch::from_reply(&reply)?))
+            })
+            .try_collect()?;
+        Ok(Value::Tuple(
+            Self::combine(&rankings, limit)
+                .into_iter()
+                .map(KeyScoreMatch::into_value)
+                .collect(),
+        ))
+    }
+}
+
+pub struct NamedVectorsIndexFactory {
+    spaces: HashMap<String, Arc<dyn ExternalIndexFactory>>,
+    // number of candidates taken from each space, relative to the number of requested results
+    candidates_multiplier: usize,
+}
+
+impl NamedVectorsIndexFactory {
+    pub fn new(
+        spaces: HashMap<String, Arc<dyn ExternalIndexFactory>>,
+        candidates_multiplier: usize,
+    ) -> NamedVectorsIndexFactory {
+        NamedVectorsIndexFactory {
+            spaces,
+            candidates_multiplier,
+        }
+    }
+}
+
+impl ExternalIndexFactory for NamedVectorsIndexFactory {
+    fn make_instance(&self) -> Result<Box<dyn ExternalIndex>, Error> {
+        let spaces = self
+            .spaces
+            .iter()
+            .map(|(name, factory)| Ok((name.clone(), factory.make_instance()?)))
+            .collect::<Result<_, Error>>()?;
+        Ok(Box::new(NamedVectorsIndex::new(
+            spaces,
+            self.candidates_multiplier,
+        )))
+    }
+}
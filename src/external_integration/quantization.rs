@@ -0,0 +1,459 @@
+// Copyright © 2024 Pathway
+
+//! Compressed storage of vectors for the brute-force k-NN index.
+//!
+//! Vectors are scanned in their compressed form, which gives approximate distances. If the
+//! original vectors are kept for reranking, the closest candidates found with the approximate
+//! distances are ordered again with the exact ones, which restores most of the lost recall.
+
+use std::collections::HashMap;
+
+use crate::engine::error::{DataError, DynResult};
+use crate::engine::Key;
+
+use super::brute_force_knn_integration::{BruteForceKnnMetricKind, TopK};
+use super::distance_kernels::DistanceKernels;
+use super::{KeyFilter, KeyScoreMatch, NonFilteringExternalIndex};
+
+/// Number of centroids in each subspace of the product quantizer, so that a code fits a byte.
+const PQ_CENTROIDS: usize = 256;
+const KMEANS_ITERATIONS: usize = 10;
+
+#[derive(Debug, Clone, Copy)]
+pub enum QuantizationKind {
+    /// Each coordinate is stored as a signed byte, scaled by the largest absolute coordinate of
+    /// the vector. Cuts the memory used by the vectors 8 times.
+    ScalarInt8,
+    /// The vector is split into `subquantizers` parts, each stored as the index of the closest
+    /// of 256 centroids. The centroids are trained on the first `training_sample_size` vectors,
+    /// which are kept uncompressed until then.
+    Product {
+        subquantizers: usize,
+        training_sample_size: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QuantizationConfig {
+    pub kind: QuantizationKind,
+    /// Whether to keep the original vectors (as `f32`) to rerank the candidates.
+    pub rerank: bool,
+    /// How many times more candidates than requested are found before reranking.
+    pub rerank_multiplier: usize,
+}
+
+struct ProductCodebook {
+    subquantizers: usize,
+    sub_dimensions: usize,
+    // centroids of subspace `j` are stored in `centroids[j]`, one after another
+    centroids: Vec<Vec<f64>>,
+}
+
+impl ProductCodebook {
+    fn train(
+        vectors: &[f64],
+        dimensions: usize,
+        subquantizers: usize,
+        kernels: DistanceKernels,
+    ) -> ProductCodebook {
+        let sub_dimensions = dimensions / subquantizers;
+        let centroids = (0..subquantizers)
+            .map(|j| {
+                let samples: Vec<&[f64]> = vectors
+                    .chunks_exact(dimensions)
+                    .map(|vector| &vector[j * sub_dimensions..(j + 1) * sub_dimensions])
+                    .collect();
+                train_kmeans(&samples, sub_dimensions, kernels)
+            })
+            .collect();
+        ProductCodebook {
+            subquantizers,
+            sub_dimensions,
+            centroids,
+        }
+    }
+
+    fn encode(&self, vector: &[f64], kernels: DistanceKernels, codes: &mut Vec<u8>) {
+        for (j, centroids) in self.centroids.iter().enumerate() {
+            let part = &vector[j * self.sub_dimensions..(j + 1) * self.sub_dimensions];
+            let closest = closest_centroid(part, centroids, self.sub_dimensions, kernels);
+            codes.push(u8::try_from(closest).expect("there are at most 256 centroids"));
+        }
+    }
+
+    /// Distances from the parts of the query to all centroids, so that the distance to an
+    /// encoded vector is a sum of `subquantizers` lookups.
+    fn distance_table(&self, query: &[f64], kernels: DistanceKernels) -> Vec<f64> {
+        let mut table = vec![f64::INFINITY; self.subquantizers * PQ_CENTROIDS];
+        for (j, centroids) in self.centroids.iter().enumerate() {
+            let part = &query[j * self.sub_dimensions..(j + 1) * self.sub_dimensions];
+            for (c, centroid) in centroids.chunks_exact(self.sub_dimensions).enumerate() {
+                table[j * PQ_CENTROIDS + c] = (kernels.l2sq)(part, centroid);
+            }
+        }
+        table
+    }
+}
+
+fn closest_centroid(
+    vector: &[f64],
+    centroids: &[f64],
+    dimensions: usize,
+    kernels: DistanceKernels,
+) -> usize {
+    centroids
+        .chunks_exact(dimensions)
+        .map(|centroid| (kernels.l2sq)(vector, centroid))
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(0, |(c, _)| c)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn train_kmeans(samples: &[&[f64]], dimensions: usize, kernels: DistanceKernels) -> Vec<f64> {
+    let k = samples.len().clamp(1, PQ_CENTROIDS);
+    // samples spread over the whole sample set make the initial centroids
+    let mut centroids: Vec<f64> = (0..k)
+        .flat_map(|c| samples[c * samples.len() / k].iter().copied())
+        .collect();
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut sums = vec![0.0; k * dimensions];
+        let mut counts = vec![0_usize; k];
+        for sample in samples {
+            let c = closest_centroid(sample, &centroids, dimensions, kernels);
+            counts[c] += 1;
+            for (sum, value) in sums[c * dimensions..(c + 1) * dimensions]
+                .iter_mut()
+                .zip(*sample)
+            {
+                *sum += value;
+            }
+        }
+        for (c, count) in counts.into_iter().enumerate() {
+            // an empty cluster keeps its previous centroid
+            if count > 0 {
+                for (centroid, sum) in centroids[c * dimensions..(c + 1) * dimensions]
+                    .iter_mut()
+                    .zip(&sums[c * dimensions..(c + 1) * dimensions])
+                {
+                    *centroid = sum / count as f64;
+                }
+            }
+        }
+    }
+    centroids
+}
+
+enum Storage {
+    ScalarInt8 {
+        codes: Vec<i8>,
+        scales: Vec<f64>,
+    },
+    Product {
+        codes: Vec<u8>,
+        codebook: ProductCodebook,
+    },
+    /// Vectors waiting for the product quantizer to be trained.
+    Untrained {
+        vectors: Vec<f64>,
+        subquantizers: usize,
+        training_sample_size: usize,
+    },
+}
+
+/// Brute-force k-NN index scanning compressed vectors. Like [`BruteForceKNNIndex`], it returns
+/// negated squared euclidean or cosine distances as scores. Cosine distances are computed from
+/// normalized vectors, so they're handled like euclidean ones.
+///
+/// [`BruteForceKNNIndex`]: super::brute_force_knn_integration::BruteForceKNNIndex
+pub struct QuantizedKNNIndex {
+    dimensions: usize,
+    metric: BruteForceKnnMetricKind,
+    config: QuantizationConfig,
+    kernels: DistanceKernels,
+    storage: Storage,
+    keys: Vec<Key>,
+    positions: HashMap<Key, usize>,
+    originals: Vec<f32>,
+}
+
+impl QuantizedKNNIndex {
+    pub fn new(
+        dimensions: usize,
+        metric: BruteForceKnnMetricKind,
+        config: QuantizationConfig,
+    ) -> DynResult<QuantizedKNNIndex> {
+        let storage = match config.kind {
+            QuantizationKind::ScalarInt8 => Storage::ScalarInt8 {
+                codes: Vec::new(),
+                scales: Vec::new(),
+            },
+            QuantizationKind::Product {
+                subquantizers,
+                training_sample_size,
+            } => {
+                if subquantizers == 0 || dimensions % subquantizers != 0 {
+                    return Err(DataError::ValueError(format!(
+                        "the number of dimensions ({dimensions}) has to be divisible by the number of subquantizers ({subquantizers})"
+                    ))
+                    .into());
+                }
+                Storage::Untrained {
+                    vectors: Vec::new(),
+                    subquantizers,
+                    training_sample_size,
+                }
+            }
+        };
+        Ok(QuantizedKNNIndex {
+            dimensions,
+            metric,
+            config,
+            kernels: DistanceKernels::detect(),
+            storage,
+            keys: Vec::new(),
+            positions: HashMap::new(),
+            originals: Vec::new(),
+        })
+    }
+
+    /// Normalizes the vector for the cosine distance, so that it's proportional to the
+    /// euclidean one.
+    fn prepare(&self, vector: &[f64]) -> Vec<f64> {
+        match self.metric {
+            BruteForceKnnMetricKind::L2sq => vector.to_vec(),
+            BruteForceKnnMetricKind::Cos => {
+                let norm = (self.kernels.dot)(vector, vector).sqrt();
+                if norm == 0.0 {
+                    vector.to_vec()
+                } else {
+                    vector.iter().map(|x| x / norm).collect()
+                }
+            }
+        }
+    }
+
+    /// Converts the squared euclidean distance between prepared vectors to the index metric.
+    fn reported_distance(&self, l2sq: f64) -> f64 {
+        match self.metric {
+            BruteForceKnnMetricKind::L2sq => l2sq,
+            // for unit vectors |a - b|^2 = 2 - 2 * a.b
+            BruteForceKnnMetricKind::Cos => l2sq / 2.0,
+        }
+    }
+
+    fn store(&mut self, vector: &[f64]) {
+        let kernels = self.kernels;
+        match &mut self.storage {
+            Storage::ScalarInt8 { codes, scales } => {
+                let max_abs = vector.iter().fold(0.0_f64, |acc, x| acc.max(x.abs()));
+                let scale = if max_abs == 0.0 { 1.0 } else { max_abs / 127.0 };
+                #[allow(clippy::cast_possible_truncation)]
+                codes.extend(vector.iter().map(|x| (x / scale).round() as i8));
+                scales.push(scale);
+            }
+            Storage::Product { codes, codebook } => codebook.encode(vector, kernels, codes),
+            Storage::Untrained { vectors, .. } => vectors.extend_from_slice(vector),
+        }
+        if self.config.rerank {
+            #[allow(clippy::cast_possible_truncation)]
+            self.originals.extend(vector.iter().map(|x| *x as f32));
+        }
+    }
+
+    fn train_if_ready(&mut self) {
+        let Storage::Untrained {
+            vectors,
+            subquantizers,
+            training_sample_size,
+        } = &self.storage
+        else {
+            return;
+        };
+        if self.keys.len() < *training_sample_size {
+            return;
+        }
+        let codebook =
+            ProductCodebook::train(vectors, self.dimensions, *subquantizers, self.kernels);
+        let mut codes = Vec::with_capacity(self.keys.len() * *subquantizers);
+        for vector in vectors.chunks_exact(self.dimensions) {
+            codebook.encode(vector, self.kernels, &mut codes);
+        }
+        self.storage = Storage::Product { codes, codebook };
+    }
+
+    fn check_dimensions(&self, vector: &[f64]) -> DynResult<()> {
+        if vector.len() == self.dimensions {
+            Ok(())
+        } else {
+            Err(DataError::ValueError(format!(
+                "expected a vector with {} dimensions, got {}",
+                self.dimensions,
+                vector.len()
+            ))
+            .into())
+        }
+    }
+
+    fn add_one(&mut self, key: Key, data: &[f64]) -> DynResult<()> {
+        self.check_dimensions(data)?;
+        if self.positions.contains_key(&key) {
+            return Err(DataError::ValueError(format!("key {key} is already indexed")).into());
+        }
+        let vector = self.prepare(data);
+        self.store(&vector);
+        self.positions.insert(key, self.keys.len());
+        self.keys.push(key);
+        Ok(())
+    }
+
+    fn remove_one(&mut self, key: Key) -> DynResult<()> {
+        let position = self
+            .positions
+            .remove(&key)
+            .ok_or(DataError::ValueError(format!("key {key} is not indexed")))?;
+        // the last entry is moved in place of the removed one
+        let last = self.keys.len() - 1;
+        self.keys.swap_remove(position);
+        if position != last {
+            self.positions.insert(self.keys[position], position);
+        }
+        let dimensions = self.dimensions;
+        match &mut self.storage {
+            Storage::ScalarInt8 { codes, scales } => {
+                swap_remove_chunk(codes, position, dimensions);
+                scales.swap_remove(position);
+            }
+            Storage::Product { codes, codebook } => {
+                swap_remove_chunk(codes, position, codebook.subquantizers);
+            }
+            Storage::Untrained { vectors, .. } => swap_remove_chunk(vectors, position, dimensions),
+        }
+        if self.config.rerank {
+            swap_remove_chunk(&mut self.originals, position, dimensions);
+        }
+        Ok(())
+    }
+
+    fn search_one(
+        &self,
+        query: &[f64],
+        limit: usize,
+        filter: Option<&KeyFilter<'_>>,
+    ) -> DynResult<Vec<KeyScoreMatch>> {
+        self.check_dimensions(query)?;
+        let query = self.prepare(query);
+        let candidates_limit = if self.config.rerank {
+            limit.saturating_mul(self.config.rerank_multiplier)
+        } else {
+            limit
+        };
+        let mut top_k = TopK::new(candidates_limit);
+        let mut consider = |position: usize, distance: f64| {
+            if top_k.admits(distance) && filter.is_none_or(|filter| filter(self.keys[position])) {
+                top_k.push(distance, position);
+            }
+        };
+        match &self.storage {
+            Storage::ScalarInt8 { codes, scales } => {
+                for (position, (code, scale)) in
+                    codes.chunks_exact(self.dimensions).zip(scales).enumerate()
+                {
+                    let distance = query
+                        .iter()
+                        .zip(code)
+                        .map(|(x, c)| (x - scale * f64::from(*c)).powi(2))
+                        .sum();
+                    consider(position, distance);
+                }
+            }
+            Storage::Product { codes, codebook } => {
+                let table = codebook.distance_table(&query, self.kernels);
+                for (position, code) in codes.chunks_exact(codebook.subquantizers).enumerate() {
+                    let distance = code
+                        .iter()
+                        .enumerate()
+                        .map(|(j, c)| table[j * PQ_CENTROIDS + usize::from(*c)])
+                        .sum();
+                    consider(position, distance);
+                }
+            }
+            Storage::Untrained { vectors, .. } => {
+                for (position, vector) in vectors.chunks_exact(self.dimensions).enumerate() {
+                    consider(position, (self.kernels.l2sq)(&query, vector));
+                }
+            }
+        }
+
+        let mut candidates = top_k.into_sorted();
+        if self.config.rerank {
+            for (distance, position) in &mut candidates {
+                let original =
+                    &self.originals[*position * self.dimensions..(*position + 1) * self.dimensions];
+                *distance = query
+                    .iter()
+                    .zip(original)
+                    .map(|(x, y)| (x - f64::from(*y)).powi(2))
+                    .sum();
+            }
+            candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+        candidates.truncate(limit);
+        Ok(candidates
+            .into_iter()
+            .map(|(distance, position)| KeyScoreMatch {
+                key: self.keys[position],
+                score: -self.reported_distance(distance),
+            })
+            .collect())
+    }
+}
+
+fn swap_remove_chunk<T: Copy>(values: &mut Vec<T>, position: usize, chunk_size: usize) {
+    let last_start = values.len() - chunk_size;
+    if position * chunk_size != last_start {
+        values.copy_within(last_start.., position * chunk_size);
+    }
+    values.truncate(last_start);
+}
+
+impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for QuantizedKNNIndex {
+    fn add(&mut self, add_data: Vec<(Key, Vec<f64>)>) -> Vec<(Key, DynResult<()>)> {
+        let results = add_data
+            .into_iter()
+            .map(|(key, data)| (key, self.add_one(key, &data)))
+            .collect();
+        self.train_if_ready();
+        results
+    }
+
+    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+        keys.into_iter()
+            .map(|key| (key, self.remove_one(key)))
+            .collect()
+    }
+
+    fn search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize)],
+    ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
+        queries
+            .iter()
+            .map(|(key, data, limit)| (*key, self.search_one(data, *limit, None)))
+            .collect()
+    }
+
+    fn filtered_search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize, KeyFilter<'_>)],
+    ) -> Option<Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>> {
+        Some(
+            queries
+                .iter()
+                .map(|(key, data, limit, filter)| {
+                    (*key, self.search_one(data, *limit, Some(filter)))
+                })
+                .collect(),
+        )
+    }
+}
@@ -1,32 +1,202 @@
 // Copyright © 2024 Pathway
 
-use crate::engine::error::DynResult;
-use crate::engine::{Error, Key};
+use std::collections::HashMap;
+
+use crate::engine::error::{DataError, DynResult};
+use crate::engine::{Error, Key, Value as EngineValue};
 use log::warn;
 use tantivy::collector::TopDocs;
 use tantivy::query::{Query, QueryParser};
-use tantivy::schema::{Field, Schema, Term, Value, INDEXED, STORED, TEXT};
-use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, TantivyDocument};
+use tantivy::schema::{
+    Field, IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions, Value, INDEXED, STORED,
+};
+use tantivy::tokenizer::{
+    Language, LowerCaser, NgramTokenizer, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer,
+    TextAnalyzer, WhitespaceTokenizer,
+};
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, TantivyDocument};
 
 use super::{
     DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyScoreMatch,
-    KeyToU64IdMapper, NonFilteringExternalIndex,
+    KeyToU64IdMapper, NonFilteringExternalIndex, Unpack,
 };
 
+const ID_FIELD_NAME: &str = "id";
+const DEFAULT_FIELD_NAME: &str = "data";
+const TOKENIZER_NAME: &str = "pathway";
+const MAX_TOKEN_LENGTH: usize = 40;
+
+/// Splits the text into tokens before they're indexed or searched for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TantivyTokenizerKind {
+    /// Splits on whitespace and punctuation.
+    Simple,
+    /// Splits on whitespace only.
+    Whitespace,
+    /// Keeps the whole text as a single token.
+    Raw,
+    /// Emits all substrings of lengths from `min_gram` to `max_gram`, or only the prefixes of
+    /// the text if `prefix_only` is set.
+    NGram {
+        min_gram: usize,
+        max_gram: usize,
+        prefix_only: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct TantivyAnalyzerConfig {
+    pub tokenizer: TantivyTokenizerKind,
+    pub lowercase: bool,
+    /// Language used for stemming the tokens. `None` disables stemming.
+    pub stemming_language: Option<Language>,
+}
+
+impl Default for TantivyAnalyzerConfig {
+    fn default() -> Self {
+        Self {
+            tokenizer: TantivyTokenizerKind::Simple,
+            lowercase: true,
+            stemming_language: None,
+        }
+    }
+}
+
+impl TantivyAnalyzerConfig {
+    fn build(&self) -> DynResult<TextAnalyzer> {
+        let mut builder = match self.tokenizer {
+            TantivyTokenizerKind::Simple => {
+                TextAnalyzer::builder(SimpleTokenizer::default()).dynamic()
+            }
+            TantivyTokenizerKind::Whitespace => {
+                TextAnalyzer::builder(WhitespaceTokenizer::default()).dynamic()
+            }
+            TantivyTokenizerKind::Raw => TextAnalyzer::builder(RawTokenizer::default()).dynamic(),
+            TantivyTokenizerKind::NGram {
+                min_gram,
+                max_gram,
+                prefix_only,
+            } => TextAnalyzer::builder(NgramTokenizer::new(min_gram, max_gram, prefix_only)?)
+                .dynamic(),
+        };
+        // the same limit as in the default tantivy analyzer
+        builder = builder.filter_dynamic(RemoveLongFilter::limit(MAX_TOKEN_LENGTH));
+        if self.lowercase {
+            builder = builder.filter_dynamic(LowerCaser);
+        }
+        if let Some(language) = self.stemming_language {
+            builder = builder.filter_dynamic(Stemmer::new(language));
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Approximate matching of the query terms.
+#[derive(Debug, Clone, Copy)]
+pub struct TantivyFuzzyConfig {
+    /// Maximum edit distance between a query term and a matching term.
+    pub distance: u8,
+    /// Whether query terms only have to match a prefix of the indexed terms.
+    pub prefix: bool,
+    /// Whether swapping two adjacent characters counts as a single edit.
+    pub transpose_cost_one: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TantivyFieldConfig {
+    pub name: String,
+    /// Multiplier of the scores of matches in this field.
+    pub boost: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TantivySearchConfig {
+    /// Fields of the indexed documents. If empty, the documents have a single field.
+    pub fields: Vec<TantivyFieldConfig>,
+    pub analyzer: TantivyAnalyzerConfig,
+    pub fuzzy: Option<TantivyFuzzyConfig>,
+}
+
+/// Text of an indexed document, either a single string or a JSON object with the texts of
+/// the fields.
+pub struct TantivyDocumentText(HashMap<String, String>);
+
+impl Unpack<TantivyDocumentText> for EngineValue {
+    fn unpack(self) -> DynResult<TantivyDocumentText> {
+        match self {
+            EngineValue::String(text) => Ok(TantivyDocumentText(HashMap::from([(
+                String::new(),
+                text.to_string(),
+            )]))),
+            EngineValue::Json(json) => match json.as_object() {
+                Some(object) => Ok(TantivyDocumentText(
+                    object
+                        .iter()
+                        .map(|(name, value)| {
+                            let text = match value.as_str() {
+                                Some(text) => text.to_string(),
+                                None => value.to_string(),
+                            };
+                            (name.clone(), text)
+                        })
+                        .collect(),
+                )),
+                None => Err(DataError::ValueError(format!(
+                    "expected a JSON object with texts of the fields, got {json}"
+                ))
+                .into()),
+            },
+            value => Err(Box::new(DataError::TypeMismatch {
+                expected: "string or JSON object",
+                value,
+            })),
+        }
+    }
+}
+
 pub struct TantivyIndex {
     // non configurable parameters
     reader: IndexReader,
     writer: IndexWriter,
     id_field: Field,
-    data_field: Field,
+    data_fields: Vec<(String, Field)>,
     query_parser: QueryParser,
     key_to_id_mapper: KeyToU64IdMapper,
 }
 impl TantivyIndex {
-    pub fn new(ram_budget: usize, in_memory_index: bool) -> DynResult<TantivyIndex> {
+    pub fn new(
+        ram_budget: usize,
+        in_memory_index: bool,
+        config: &TantivySearchConfig,
+    ) -> DynResult<TantivyIndex> {
+        let fields = if config.fields.is_empty() {
+            vec![TantivyFieldConfig {
+                name: DEFAULT_FIELD_NAME.to_string(),
+                boost: 1.0,
+            }]
+        } else {
+            config.fields.clone()
+        };
+
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(TOKENIZER_NAME)
+                // positions are needed for phrase queries
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
         let mut schema_builder = Schema::builder();
-        schema_builder.add_u64_field("id", INDEXED | STORED);
-        schema_builder.add_text_field("data", TEXT);
+        let id_field = schema_builder.add_u64_field(ID_FIELD_NAME, INDEXED | STORED);
+        let mut data_fields = Vec::with_capacity(fields.len());
+        for field in &fields {
+            if field.name == ID_FIELD_NAME {
+                return Err(DataError::ValueError(format!(
+                    "field name {ID_FIELD_NAME:?} is reserved"
+                ))
+                .into());
+            }
+            let data_field = schema_builder.add_text_field(&field.name, text_options.clone());
+            data_fields.push((field.name.clone(), data_field));
+        }
         let schema = schema_builder.build();
 
         let index = if in_memory_index {
@@ -35,6 +205,9 @@ impl TantivyIndex {
             // TODO use some pathway storage, if defined
             Index::create_from_tempdir(schema.clone())?
         };
+        index
+            .tokenizers()
+            .register(TOKENIZER_NAME, config.analyzer.build()?);
 
         let index_writer: IndexWriter = index.writer(ram_budget)?;
         let index_reader = index
@@ -42,15 +215,28 @@ impl TantivyIndex {
             .reload_policy(ReloadPolicy::Manual)
             .try_into()?;
 
-        let data_field = schema.get_field("data").unwrap();
-        let id_field = schema.get_field("id").unwrap();
-        let query_parser = QueryParser::for_index(&index, vec![data_field]);
+        // phrase queries ("...") are supported by the parser, given positions are indexed
+        let mut query_parser = QueryParser::for_index(
+            &index,
+            data_fields.iter().map(|(_name, field)| *field).collect(),
+        );
+        for (field, (_name, data_field)) in fields.iter().zip(&data_fields) {
+            query_parser.set_field_boost(*data_field, field.boost);
+            if let Some(fuzzy) = config.fuzzy {
+                query_parser.set_field_fuzzy(
+                    *data_field,
+                    fuzzy.prefix,
+                    fuzzy.distance,
+                    fuzzy.transpose_cost_one,
+                );
+            }
+        }
 
         Ok(TantivyIndex {
             reader: index_reader,
             writer: index_writer,
             id_field,
-            data_field,
+            data_fields,
             query_parser,
             key_to_id_mapper: KeyToU64IdMapper::new(),
         })
@@ -88,12 +274,24 @@ impl TantivyIndex {
         Ok(ret_vec)
     }
 
-    fn add_one(&mut self, key: Key, data: String) -> DynResult<()> {
+    fn add_one(&mut self, key: Key, data: TantivyDocumentText) -> DynResult<()> {
+        let mut document = TantivyDocument::new();
+        let mut texts = data.0;
+        if let Some(text) = texts.remove("") {
+            // a plain string goes to the first field
+            document.add_text(self.data_fields[0].1, text);
+        }
+        for (name, field) in &self.data_fields {
+            if let Some(text) = texts.remove(name) {
+                document.add_text(*field, text);
+            }
+        }
+        if let Some(name) = texts.keys().next() {
+            return Err(DataError::ValueError(format!("unknown text field {name:?}")).into());
+        }
         let key_id = self.key_to_id_mapper.get_next_free_u64_id(key);
-        self.writer.add_document(doc!(
-            self.id_field => key_id,
-            self.data_field => data,
-        ))?;
+        document.add_u64(self.id_field, key_id);
+        self.writer.add_document(document)?;
         Ok(())
     }
 
@@ -107,8 +305,8 @@ impl TantivyIndex {
 
 // index methods
 // maybe todo -> make search generic wrt ResultType
-impl NonFilteringExternalIndex<String, String> for TantivyIndex {
-    fn add(&mut self, add_data: Vec<(Key, String)>) -> Vec<(Key, DynResult<()>)> {
+impl NonFilteringExternalIndex<TantivyDocumentText, String> for TantivyIndex {
+    fn add(&mut self, add_data: Vec<(Key, TantivyDocumentText)>) -> Vec<(Key, DynResult<()>)> {
         let ret = add_data
             .into_iter()
             .map(|(key, data)| (key, self.add_one(key, data)))
@@ -152,6 +350,7 @@ pub struct TantivyIndexFactory {
     // if set to true, the index is created in ram, otherwise it should be created in some default
     // storage place
     in_memory_index: bool,
+    search_config: TantivySearchConfig,
 }
 
 impl TantivyIndexFactory {
@@ -159,13 +358,21 @@ impl TantivyIndexFactory {
         TantivyIndexFactory {
             ram_budget,
             in_memory_index,
+            search_config: TantivySearchConfig::default(),
         }
     }
+
+    #[must_use]
+    pub fn with_search_config(mut self, search_config: TantivySearchConfig) -> Self {
+        self.search_config = search_config;
+        self
+    }
 }
 
 impl ExternalIndexFactory for TantivyIndexFactory {
     fn make_instance(&self) -> Result<Box<dyn ExternalIndex>, Error> {
-        let t_index = TantivyIndex::new(self.ram_budget, self.in_memory_index)?;
+        let t_index =
+            TantivyIndex::new(self.ram_budget, self.in_memory_index, &self.search_config)?;
         Ok(Box::new(DerivedFilteredSearchIndex::new(Box::new(t_index))) as Box<dyn ExternalIndex>)
     }
 }
@@ -1,25 +1,74 @@
 // Copyright © 2024 Pathway
 
 use std::cmp::max;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use crate::engine::error::DynResult;
 use crate::engine::{Error, Key};
-use log::warn;
+use crate::persistence::backends::PersistenceBackend;
+use crate::persistence::config::PersistentStorageConfig;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
 use usearch::ffi::{IndexOptions, MetricKind, ScalarKind};
 use usearch::{new_index, Index};
+use xxhash_rust::xxh3::Xxh3;
 
 use super::{
-    DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyScoreMatch,
+    DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyFilter, KeyScoreMatch,
     KeyToU64IdMapper, NonFilteringExternalIndex,
 };
 
 #[derive(Clone, Copy)]
 pub struct USearchMetricKind(pub MetricKind);
 
+/// Where and how often snapshots of the index are written, so that after a restart the index
+/// can be restored instead of being rebuilt from all the vectors.
+#[derive(Debug, Clone)]
+pub struct USearchSnapshotConfig {
+    pub storage: PersistentStorageConfig,
+    /// Distinguishes the snapshots of different indices kept in the same storage.
+    pub name: String,
+    /// Number of added and removed vectors after which a new snapshot is written.
+    pub interval: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct USearchSnapshot {
+    index: Vec<u8>,
+    next_id: u64,
+    // key, its ID in the index and the fingerprint of its vector
+    entries: Vec<(Key, u64, u64)>,
+}
+
+struct SnapshotWriter {
+    backend: Box<dyn PersistenceBackend>,
+    key: String,
+    interval: usize,
+    updates_since_snapshot: usize,
+}
+
+fn snapshot_key_prefix(name: &str) -> String {
+    format!("usearch-snapshot/{name}/")
+}
+
+fn fingerprint(data: &[f64]) -> u64 {
+    let mut hasher = Xxh3::default();
+    for value in data {
+        hasher.update(&value.to_le_bytes());
+    }
+    hasher.digest()
+}
+
 pub struct USearchKNNIndex {
     index: Arc<Index>,
     key_to_id_mapper: KeyToU64IdMapper,
+    fingerprints: HashMap<u64, u64>,
+    // Entries restored from a snapshot that haven't been added again since the restart.
+    // They're not returned from searches and are dropped when the next snapshot is written.
+    unconfirmed: HashMap<Key, u64>,
+    snapshot_writer: Option<SnapshotWriter>,
 }
 
 impl USearchKNNIndex {
@@ -47,9 +96,119 @@ impl USearchKNNIndex {
         Ok(USearchKNNIndex {
             index: Arc::from(index),
             key_to_id_mapper: KeyToU64IdMapper::new(),
+            fingerprints: HashMap::new(),
+            unconfirmed: HashMap::new(),
+            snapshot_writer: None,
         })
     }
 
+    /// Restores the index from the snapshot stored under `key`, if there is one, and
+    /// writes snapshots under this key from now on.
+    fn with_snapshots(mut self, config: &USearchSnapshotConfig, key: String) -> DynResult<Self> {
+        let backend = config.storage.create()?;
+        // a missing key is reported as an error by the backends
+        let serialized = backend.get_value(&key).ok().or_else(|| {
+            // the number of instances changed since the snapshot was written, but all instances
+            // hold the same vectors, so any other snapshot of this index is as good
+            let prefix = snapshot_key_prefix(&config.name);
+            let other_key = backend
+                .list_keys()
+                .ok()?
+                .into_iter()
+                .find(|other_key| other_key.starts_with(&prefix))?;
+            backend.get_value(&other_key).ok()
+        });
+        if let Some(serialized) = serialized {
+            match self.restore(&serialized) {
+                Ok(()) => info!(
+                    "Restored USearch index {:?} with {} vectors",
+                    config.name,
+                    self.unconfirmed.len()
+                ),
+                Err(e) => warn!("Failed to restore USearch index {:?}: {e}", config.name),
+            }
+        }
+        self.snapshot_writer = Some(SnapshotWriter {
+            backend,
+            key,
+            interval: config.interval.max(1),
+            updates_since_snapshot: 0,
+        });
+        Ok(self)
+    }
+
+    fn restore(&mut self, serialized: &[u8]) -> DynResult<()> {
+        let snapshot: USearchSnapshot = bincode::deserialize(serialized)?;
+        let dimensions = self.index.dimensions();
+        if let Err(e) = self.index.load_from_buffer(&snapshot.index) {
+            self.index.reset()?;
+            return Err(e.into());
+        }
+        if self.index.dimensions() != dimensions {
+            self.index.reset()?;
+            return Err(format!(
+                "snapshot has {} dimensions, expected {dimensions}",
+                self.index.dimensions()
+            )
+            .into());
+        }
+        if self.index.capacity() < self.index.size() {
+            self.index.reserve(self.index.size())?;
+        }
+        self.key_to_id_mapper.reserve_ids_below(snapshot.next_id);
+        for (key, id, fingerprint) in snapshot.entries {
+            self.unconfirmed.insert(key, id);
+            self.fingerprints.insert(id, fingerprint);
+        }
+        Ok(())
+    }
+
+    fn remove_unconfirmed(&mut self) {
+        for (_key, id) in std::mem::take(&mut self.unconfirmed) {
+            self.fingerprints.remove(&id);
+            if let Err(e) = self.index.remove(id) {
+                warn!("Failed to remove a stale entry from USearch index: {e}");
+            }
+        }
+    }
+
+    fn serialize(&self) -> DynResult<Vec<u8>> {
+        let mut index = vec![0; self.index.serialized_length()];
+        self.index.save_to_buffer(&mut index)?;
+        let entries = self
+            .key_to_id_mapper
+            .iter()
+            .map(|(key, id)| (key, id, self.fingerprints[&id]))
+            .collect();
+        let snapshot = USearchSnapshot {
+            index,
+            next_id: self.key_to_id_mapper.next_free_id(),
+            entries,
+        };
+        Ok(bincode::serialize(&snapshot)?)
+    }
+
+    fn register_updates(&mut self, count: usize) {
+        let Some(writer) = &mut self.snapshot_writer else {
+            return;
+        };
+        writer.updates_since_snapshot += count;
+        if writer.updates_since_snapshot < writer.interval {
+            return;
+        }
+        writer.updates_since_snapshot = 0;
+        // by now the entries that are still present were added again
+        self.remove_unconfirmed();
+        match self.serialize() {
+            Ok(serialized) => {
+                let writer = self.snapshot_writer.as_ref().expect("writer is present");
+                // the write completes in the background, its result isn't awaited
+                drop(writer.backend.put_value(&writer.key, serialized));
+            }
+            Err(e) => warn!("Failed to create a snapshot of USearch index: {e}"),
+        }
+    }
+
     fn search_one(&self, data: &[f64], limit: usize) -> DynResult<Vec<KeyScoreMatch>> {
         let matches = self.index.search(data, limit)?;
         Ok(matches
@@ -58,7 +217,9 @@ impl USearchKNNIndex {
             .zip(matches.distances)
             .filter_map(|(k, d)| {
                 let Some(key) = self.key_to_id_mapper.get_key_for_id(k) else {
-                    warn!("USearch index returned a nonexistent ID {k}, ignoring");
+                    if !self.fingerprints.contains_key(&k) {
+                        warn!("USearch index returned a nonexistent ID {k}, ignoring");
+                    }
                     return None;
                 };
                 Some(KeyScoreMatch {
@@ -69,14 +230,53 @@ impl USearchKNNIndex {
             .collect())
     }
 
+    fn filtered_search_one(
+        &self,
+        data: &[f64],
+        limit: usize,
+        filter: &KeyFilter<'_>,
+    ) -> DynResult<Vec<KeyScoreMatch>> {
+        let matches = self.index.filtered_search(data, limit, |id| {
+            self.key_to_id_mapper
+                .get_key_for_id(id)
+                .is_some_and(|key| filter(key))
+        })?;
+        Ok(matches
+            .keys
+            .into_iter()
+            .zip(matches.distances)
+            .filter_map(|(k, d)| {
+                Some(KeyScoreMatch {
+                    key: self.key_to_id_mapper.get_key_for_id(k)?,
+                    score: -f64::from(d),
+                })
+            })
+            .collect())
+    }
+
     fn add_one(&mut self, key: Key, data: &[f64]) -> DynResult<()> {
+        let fingerprint = fingerprint(data);
+        if let Some(key_id) = self.unconfirmed.remove(&key) {
+            if self.fingerprints.get(&key_id) == Some(&fingerprint) {
+                // the vector restored from the snapshot is up to date, no need to insert it
+                self.key_to_id_mapper.assign_key(key, key_id);
+                return Ok(());
+            }
+            self.fingerprints.remove(&key_id);
+            self.index.remove(key_id)?;
+        }
         let key_id = self.key_to_id_mapper.get_next_free_u64_id(key);
         self.index.add(key_id, data)?;
+        self.fingerprints.insert(key_id, fingerprint);
         Ok(())
     }
 
     fn remove_one(&mut self, key: Key) -> DynResult<()> {
-        let key_id = self.key_to_id_mapper.remove_key(key)?;
+        let key_id = match self.unconfirmed.remove(&key) {
+            Some(key_id) => key_id,
+            None => self.key_to_id_mapper.remove_key(key)?,
+        };
+        self.fingerprints.remove(&key_id);
         self.index.remove(key_id)?;
         Ok(())
     }
@@ -94,16 +294,21 @@ impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for USearchKNNIndex {
                 .is_ok());
         }
 
-        add_data
+        let results = add_data
             .into_iter()
             .map(|(key, data)| (key, self.add_one(key, &data)))
-            .collect()
+            .collect::<Vec<_>>();
+        self.register_updates(results.len());
+        results
     }
 
     fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
-        keys.into_iter()
+        let results = keys
+            .into_iter()
             .map(|key| (key, self.remove_one(key)))
-            .collect()
+            .collect::<Vec<_>>();
+        self.register_updates(results.len());
+        results
     }
 
     fn search(
@@ -115,6 +320,20 @@ impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for USearchKNNIndex {
             .map(|(key, data, limit)| (*key, self.search_one(data, *limit)))
             .collect()
     }
+
+    fn filtered_search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize, KeyFilter<'_>)],
+    ) -> Option<Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>> {
+        Some(
+            queries
+                .iter()
+                .map(|(key, data, limit, filter)| {
+                    (*key, self.filtered_search_one(data, *limit, filter))
+                })
+                .collect(),
+        )
+    }
 }
 
 // index factory structure
@@ -122,9 +341,14 @@ pub struct USearchKNNIndexFactory {
     dimensions: usize,
     reserved_space: usize,
     metric: MetricKind,
+    /// Number of neighbors of each node of the HNSW graph (`M`).
     connectivity: usize,
+    /// Size of the candidate list used when inserting (`ef_construction`).
     expansion_add: usize,
+    /// Size of the candidate list used when searching (`ef_search`).
     expansion_search: usize,
+    snapshot_config: Option<USearchSnapshotConfig>,
+    instances_created: AtomicUsize,
 }
 
 impl USearchKNNIndexFactory {
@@ -143,14 +367,22 @@ impl USearchKNNIndexFactory {
             connectivity,
             expansion_add,
             expansion_search,
+            snapshot_config: None,
+            instances_created: AtomicUsize::new(0),
         }
     }
+
+    #[must_use]
+    pub fn with_snapshots(mut self, snapshot_config: USearchSnapshotConfig) -> Self {
+        self.snapshot_config = Some(snapshot_config);
+        self
+    }
 }
 
 // implement make_instance method, which then is used to produce instance of the index for each worker / operator
 impl ExternalIndexFactory for USearchKNNIndexFactory {
     fn make_instance(&self) -> Result<Box<dyn ExternalIndex>, Error> {
-        let u_index = USearchKNNIndex::new(
+        let mut u_index = USearchKNNIndex::new(
             self.dimensions,
             self.reserved_space,
             self.metric,
@@ -158,6 +390,13 @@ impl ExternalIndexFactory for USearchKNNIndexFactory {
             self.expansion_add,
             self.expansion_search,
         )?;
+        if let Some(snapshot_config) = &self.snapshot_config {
+            // Each instance receives all the vectors, so separate keys are only used so that
+            // the instances don't write to the same object.
+            let instance = self.instances_created.fetch_add(1, Ordering::Relaxed);
+            let key = format!("{}{instance}", snapshot_key_prefix(&snapshot_config.name));
+            u_index = u_index.with_snapshots(snapshot_config, key)?;
+        }
         Ok(Box::new(DerivedFilteredSearchIndex::new(Box::new(u_index))) as Box<dyn ExternalIndex>)
     }
 }
@@ -1,7 +1,17 @@
 // Copyright © 2024 Pathway
 
+use std::ffi::OsString;
 use std::io::{Error, ErrorKind};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use cfg_if::cfg_if;
+use log::warn;
+
+const RENAME_ATTEMPTS: usize = 5;
+const RENAME_RETRY_DELAY: Duration = Duration::from_millis(50);
 
 pub fn ensure_directory(fs_path: &Path) -> Result<(), Error> {
     if !fs_path.exists() {
@@ -17,3 +27,183 @@ pub fn ensure_directory(fs_path: &Path) -> Result<(), Error> {
     }
     Ok(())
 }
+
+/// A hidden path in the same directory as `path`, so that renaming between them doesn't cross
+/// filesystems.
+fn sibling_path(path: &Path, kind: &str) -> Result<PathBuf, Error> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "path has no file name"))?;
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let mut sibling_name = OsString::from(".");
+    sibling_name.push(name);
+    sibling_name.push(format!(".{kind}-{}-{unique}", process::id()));
+    Ok(path.with_file_name(sibling_name))
+}
+
+/// Whether a failed rename may succeed if retried. On Windows, a rename fails while another
+/// process, like an antivirus or a search indexer, has a file in the renamed directory open.
+fn is_transient_rename_error(error: &Error) -> bool {
+    cfg_if! {
+        if #[cfg(windows)] {
+            const ERROR_ACCESS_DENIED: i32 = 5;
+            const ERROR_SHARING_VIOLATION: i32 = 32;
+            matches!(
+                error.raw_os_error(),
+                Some(ERROR_ACCESS_DENIED | ERROR_SHARING_VIOLATION)
+            )
+        } else {
+            let _ = error;
+            false
+        }
+    }
+}
+
+fn rename(from: &Path, to: &Path) -> Result<(), Error> {
+    let mut attempt = 1;
+    loop {
+        match std::fs::rename(from, to) {
+            Err(e) if attempt < RENAME_ATTEMPTS && is_transient_rename_error(&e) => {
+                warn!("Failed to rename {from:?} to {to:?}, retrying: {e}");
+                attempt += 1;
+                sleep(RENAME_RETRY_DELAY);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Swaps the two existing directories in a single step, if the platform and the filesystem
+/// allow it. Returns `false` if they don't.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn exchange_directories(source: &Path, target: &Path) -> Result<bool, Error> {
+    use nix::errno::Errno;
+    use nix::fcntl::{renameat2, RenameFlags, AT_FDCWD};
+
+    match renameat2(
+        AT_FDCWD,
+        source,
+        AT_FDCWD,
+        target,
+        RenameFlags::RENAME_EXCHANGE,
+    ) {
+        Ok(()) => Ok(true),
+        Err(Errno::EINVAL | Errno::ENOSYS) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+#[allow(clippy::unnecessary_wraps)]
+fn exchange_directories(_source: &Path, _target: &Path) -> Result<bool, Error> {
+    Ok(false)
+}
+
+/// Replaces the directory `target` with the directory `source`, which must be on the same
+/// filesystem. The readers of `target` see either its old or its new contents, never a mix.
+///
+/// Where the directories can't be exchanged in a single step, which includes Windows, where a
+/// rename can't replace an existing directory, the old directory is first moved aside. Then
+/// `target` is briefly missing, but still never partially written.
+pub fn replace_directory(source: &Path, target: &Path) -> Result<(), Error> {
+    if !target.exists() {
+        return rename(source, target);
+    }
+    if exchange_directories(source, target)? {
+        // `source` now holds the old contents
+        return std::fs::remove_dir_all(source);
+    }
+    let backup = sibling_path(target, "old")?;
+    rename(target, &backup)?;
+    if let Err(e) = rename(source, target) {
+        // bring the old contents back, so that the directory doesn't disappear
+        rename(&backup, target)?;
+        return Err(e);
+    }
+    std::fs::remove_dir_all(&backup)
+}
+
+fn link_contents(source: &Path, target: &Path) -> Result<(), Error> {
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let destination = target.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            std::fs::create_dir(&destination)?;
+            link_contents(&entry.path(), &destination)?;
+        } else if std::fs::hard_link(entry.path(), &destination).is_err() {
+            std::fs::copy(entry.path(), &destination)?;
+        }
+    }
+    Ok(())
+}
+
+/// A directory that is written in a staging directory next to it and published all at once
+/// with [`StagedDirectory::publish`], so that its readers never see it partially written. The
+/// staging directory is removed if the contents are not published.
+#[derive(Debug)]
+pub struct StagedDirectory {
+    target: PathBuf,
+    staging: PathBuf,
+    is_published: bool,
+}
+
+impl StagedDirectory {
+    pub fn new(target: &Path) -> Result<Self, Error> {
+        if let Some(parent) = target
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            ensure_directory(parent)?;
+        }
+        let staging = sibling_path(target, "staging")?;
+        std::fs::create_dir(&staging)?;
+        Ok(Self {
+            target: target.to_path_buf(),
+            staging,
+            is_published: false,
+        })
+    }
+
+    /// A staged directory starting with the contents of `target`, if it exists, so that
+    /// publishing it adds to them instead of replacing them. The files are hard linked where
+    /// the filesystem allows it and copied otherwise.
+    pub fn extending(target: &Path) -> Result<Self, Error> {
+        let staged = Self::new(target)?;
+        if target.is_dir() {
+            link_contents(target, &staged.staging)?;
+        }
+        Ok(staged)
+    }
+
+    /// The directory where the contents have to be written before they are published.
+    pub fn path(&self) -> &Path {
+        &self.staging
+    }
+
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Replaces the target directory with the contents written so far.
+    pub fn publish(mut self) -> Result<(), Error> {
+        replace_directory(&self.staging, &self.target)?;
+        self.is_published = true;
+        Ok(())
+    }
+}
+
+impl Drop for StagedDirectory {
+    fn drop(&mut self) {
+        if !self.is_published {
+            if let Err(e) = std::fs::remove_dir_all(&self.staging) {
+                warn!(
+                    "Failed to remove the staging directory {:?}: {e}",
+                    self.staging
+                );
+            }
+        }
+    }
+}
@@ -16,11 +16,13 @@ pub mod persistence;
 pub mod python_api;
 
 pub mod async_runtime;
-mod env;
-mod fs_helpers;
+pub mod env;
+pub mod fs_helpers;
+pub mod pipe;
+pub mod retry;
+#[cfg(feature = "bench")]
+pub mod bench;
 mod mat_mul;
-mod pipe;
-mod retry;
 mod timestamp;
 
 #[cfg(all(not(feature = "standard-allocator"), unix))]
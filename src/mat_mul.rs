@@ -1,10 +1,131 @@
 // Copyright © 2024 Pathway
 
-use ndarray::{arr0, ArrayD, ArrayViewD, Ix1, Ix2, LinalgScalar};
+use std::str::FromStr;
+
+use faer::Parallelism;
+use log::warn;
+use ndarray::{arr0, s, Array2, ArrayD, ArrayView2, ArrayViewD, Axis, Ix1, Ix2, LinalgScalar};
+use once_cell::sync::Lazy;
+
+use crate::env::parse_env_var;
+
+const MAT_MUL_BACKEND_ENV: &str = "PATHWAY_MAT_MUL_BACKEND";
+
+/// Below this number of multiplications, the overhead of spawning threads outweighs the gains.
+const PARALLEL_MIN_MULTIPLICATIONS: usize = 64 * 64 * 64;
+
+/// Implementation used for multiplying matrices, selected with the `PATHWAY_MAT_MUL_BACKEND`
+/// environment variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatMulBackend {
+    /// `faer` for large matrices of supported types, `ndarray` otherwise.
+    Auto,
+    /// Single-threaded `ndarray` implementation.
+    Ndarray,
+    /// Blocked, multithreaded `faer` implementation, where the element type supports it.
+    Faer,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("unknown matrix multiplication backend {0:?}, expected one of: auto, ndarray, faer")]
+pub struct UnknownMatMulBackend(String);
+
+impl FromStr for MatMulBackend {
+    type Err = UnknownMatMulBackend;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "ndarray" => Ok(Self::Ndarray),
+            "faer" => Ok(Self::Faer),
+            _ => Err(UnknownMatMulBackend(s.to_string())),
+        }
+    }
+}
+
+static MAT_MUL_BACKEND: Lazy<MatMulBackend> = Lazy::new(|| {
+    parse_env_var(MAT_MUL_BACKEND_ENV)
+        .unwrap_or_else(|e| {
+            warn!("{e}, using the default one");
+            None
+        })
+        .unwrap_or(MatMulBackend::Auto)
+});
+
+fn use_fast_mat_mul(rows: usize, inner: usize, columns: usize) -> bool {
+    match *MAT_MUL_BACKEND {
+        MatMulBackend::Ndarray => false,
+        MatMulBackend::Faer => true,
+        MatMulBackend::Auto => {
+            rows.saturating_mul(inner).saturating_mul(columns) >= PARALLEL_MIN_MULTIPLICATIONS
+        }
+    }
+}
+
+pub trait MatMulScalar: LinalgScalar {
+    /// Multiplies matrices of compatible shapes with an optimized multithreaded kernel.
+    /// Returns `None` if there's no such kernel for the element type.
+    fn fast_mat_mul(_a: &ArrayView2<Self>, _b: &ArrayView2<Self>) -> Option<Array2<Self>> {
+        None
+    }
+}
+
+// integer products are accumulated exactly, without converting to floating point
+impl MatMulScalar for i64 {}
+
+macro_rules! impl_faer_mat_mul {
+    ($($type:ty),+) => {
+        $(impl MatMulScalar for $type {
+            fn fast_mat_mul(a: &ArrayView2<Self>, b: &ArrayView2<Self>) -> Option<Array2<Self>> {
+                Some(faer_mat_mul(a, b))
+            }
+        })+
+    };
+}
+
+impl_faer_mat_mul!(f32, f64);
+
+fn faer_mat_mul<T>(a: &ArrayView2<T>, b: &ArrayView2<T>) -> Array2<T>
+where
+    T: LinalgScalar + faer::SimpleEntity + faer::ComplexField,
+{
+    let (rows, inner) = a.dim();
+    let columns = b.dim().1;
+    let mut result = Array2::zeros((rows, columns));
+    let result_strides = (result.strides()[0], result.strides()[1]);
+    // SAFETY: pointers and strides come from valid ndarray views of the given shapes and
+    // the result is not accessed in any other way while `dst` is alive.
+    let (lhs, rhs, dst) = unsafe {
+        (
+            faer::mat::from_raw_parts(a.as_ptr(), rows, inner, a.strides()[0], a.strides()[1]),
+            faer::mat::from_raw_parts(b.as_ptr(), inner, columns, b.strides()[0], b.strides()[1]),
+            faer::mat::from_raw_parts_mut(
+                result.as_mut_ptr(),
+                rows,
+                columns,
+                result_strides.0,
+                result_strides.1,
+            ),
+        )
+    };
+    faer::linalg::matmul::matmul(dst, lhs, rhs, None, T::one(), Parallelism::Rayon(0));
+    result
+}
+
+fn dot2<T: MatMulScalar>(a: &ArrayView2<T>, b: &ArrayView2<T>) -> Array2<T> {
+    let (rows, inner) = a.dim();
+    let columns = b.dim().1;
+    if use_fast_mat_mul(rows, inner, columns) {
+        if let Some(result) = T::fast_mat_mul(a, b) {
+            return result;
+        }
+    }
+    a.dot(b)
+}
 
 pub fn mat_mul<T>(a: &ArrayViewD<T>, b: &ArrayViewD<T>) -> Option<ArrayD<T>>
 where
-    T: LinalgScalar,
+    T: MatMulScalar,
 {
     if a.ndim() < 1 || 2 < a.ndim() || b.ndim() < 1 || 2 < b.ndim() {
         return None;
@@ -12,18 +133,87 @@ where
         if a.shape()[1] != b.shape()[0] {
             return None;
         } else if let Ok(b) = b.view().into_dimensionality::<Ix2>() {
-            return Some(a.dot(&b).into_dyn());
+            return Some(dot2(&a, &b).into_dyn());
         } else if let Ok(b) = b.view().into_dimensionality::<Ix1>() {
-            return Some(a.dot(&b).into_dyn());
+            let b = b.insert_axis(Axis(1));
+            return Some(dot2(&a, &b).remove_axis(Axis(1)).into_dyn());
         }
     } else if let Ok(a) = a.view().into_dimensionality::<Ix1>() {
         if a.shape()[0] != b.shape()[0] {
             return None;
         } else if let Ok(b) = b.view().into_dimensionality::<Ix2>() {
-            return Some(a.dot(&b).into_dyn());
+            let a = a.insert_axis(Axis(0));
+            return Some(dot2(&a, &b).remove_axis(Axis(0)).into_dyn());
         } else if let Ok(b) = b.view().into_dimensionality::<Ix1>() {
             return Some(arr0(a.dot(&b)).into_dyn());
         }
     }
     None
 }
+
+/// Right-hand operand of many multiplications, stored in the standard layout once, so that it
+/// isn't copied or converted for each product.
+#[derive(Debug, Clone)]
+pub struct PackedMatrix<T> {
+    matrix: Array2<T>,
+}
+
+impl<T: MatMulScalar> PackedMatrix<T> {
+    /// Returns `None` if `matrix` is not two-dimensional.
+    pub fn new(matrix: &ArrayViewD<T>) -> Option<Self> {
+        let matrix = matrix.view().into_dimensionality::<Ix2>().ok()?;
+        Some(Self {
+            matrix: matrix.as_standard_layout().into_owned(),
+        })
+    }
+
+    pub fn view(&self) -> ArrayView2<'_, T> {
+        self.matrix.view()
+    }
+
+    fn lhs_rows(&self, lhs: &ArrayViewD<T>) -> Option<usize> {
+        let inner = self.matrix.nrows();
+        match lhs.shape() {
+            [length] if *length == inner => Some(1),
+            [rows, columns] if *columns == inner => Some(*rows),
+            _ => None,
+        }
+    }
+
+    /// Multiplies each of the `lhs` operands by the matrix. The operands are stacked, so that
+    /// the whole batch is computed as a single product. Operands of shapes not compatible with
+    /// the matrix result in `None`.
+    pub fn mul_batch(&self, lhs: &[ArrayViewD<T>]) -> Vec<Option<ArrayD<T>>> {
+        let inner = self.matrix.nrows();
+        let lhs_rows: Vec<Option<usize>> = lhs.iter().map(|a| self.lhs_rows(a)).collect();
+        let total_rows = lhs_rows.iter().flatten().sum();
+        let mut stacked = Array2::zeros((total_rows, inner));
+        let mut offset = 0;
+        for (a, rows) in lhs.iter().zip(&lhs_rows) {
+            if let Some(rows) = rows {
+                let mut target = stacked.slice_mut(s![offset..offset + rows, ..]);
+                if let Ok(a) = a.view().into_dimensionality::<Ix1>() {
+                    target.row_mut(0).assign(&a);
+                } else if let Ok(a) = a.view().into_dimensionality::<Ix2>() {
+                    target.assign(&a);
+                }
+                offset += rows;
+            }
+        }
+        let product = dot2(&stacked.view(), &self.matrix.view());
+        let mut offset = 0;
+        lhs.iter()
+            .zip(lhs_rows)
+            .map(|(a, rows)| {
+                let rows = rows?;
+                let result = product.slice(s![offset..offset + rows, ..]);
+                offset += rows;
+                if a.ndim() == 1 {
+                    Some(result.row(0).to_owned().into_dyn())
+                } else {
+                    Some(result.to_owned().into_dyn())
+                }
+            })
+            .collect()
+    }
+}
@@ -4,9 +4,8 @@ use azure_core::{ExponentialRetryOptions, RetryOptions};
 use azure_storage::StorageCredentials;
 use azure_storage_blobs::prelude::{BlobClient, ClientBuilder, ContainerClient};
 use futures::stream::StreamExt;
-use tokio::runtime::Runtime as TokioRuntime;
 
-use crate::async_runtime::create_async_tokio_runtime;
+use crate::async_runtime::{create_async_tokio_runtime, AsyncRuntime};
 use crate::persistence::backends::PersistenceBackend;
 use crate::persistence::Error;
 
@@ -21,7 +20,7 @@ pub struct AzureKVStorage {
     account: String,
     container: String,
     credentials: StorageCredentials,
-    runtime: TokioRuntime,
+    runtime: AsyncRuntime,
     background_uploader: BackgroundObjectUploader,
 }
 
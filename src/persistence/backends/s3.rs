@@ -2,10 +2,11 @@
 
 use s3::bucket::Bucket as S3Bucket;
 
+use crate::connectors::scanner::s3::is_retryable_s3_error;
 use crate::deepcopy::DeepCopy;
 use crate::persistence::backends::PersistenceBackend;
 use crate::persistence::Error;
-use crate::retry::{execute_with_retries, RetryConfig};
+use crate::retry::{execute_with_retries_if, RetryConfig};
 
 use super::{BackendPutFuture, BackgroundObjectUploader};
 
@@ -28,9 +29,10 @@ impl S3KVStorage {
 
         let uploader_bucket = bucket.deep_copy();
         let upload_object = move |key: String, value: Vec<u8>| {
-            let _ = execute_with_retries(
+            let _ = execute_with_retries_if(
                 || uploader_bucket.put_object(&key, &value),
-                RetryConfig::default(),
+                is_retryable_s3_error,
+                RetryConfig::default().with_full_jitter(),
                 MAX_S3_RETRIES,
             )?;
             Ok(())
@@ -53,9 +55,10 @@ impl PersistenceBackend for S3KVStorage {
         let prefix_len = self.root_path.len();
         let mut keys = Vec::new();
 
-        let object_lists = execute_with_retries(
+        let object_lists = execute_with_retries_if(
             || self.bucket.list(self.root_path.clone(), None),
-            RetryConfig::default(),
+            is_retryable_s3_error,
+            RetryConfig::default().with_full_jitter(),
             MAX_S3_RETRIES,
         )?;
 
@@ -73,9 +76,10 @@ impl PersistenceBackend for S3KVStorage {
 
     fn get_value(&self, key: &str) -> Result<Vec<u8>, Error> {
         let full_key_path = self.full_key_path(key);
-        let response_data = execute_with_retries(
+        let response_data = execute_with_retries_if(
             || self.bucket.get_object(&full_key_path), // returns Err on incorrect status code because fail-on-err feature is enabled
-            RetryConfig::default(),
+            is_retryable_s3_error,
+            RetryConfig::default().with_full_jitter(),
             MAX_S3_RETRIES,
         )?;
         Ok(response_data.bytes().to_vec())
@@ -88,9 +92,10 @@ impl PersistenceBackend for S3KVStorage {
 
     fn remove_key(&self, key: &str) -> Result<(), Error> {
         let full_key_path = self.full_key_path(key);
-        let _ = execute_with_retries(
+        let _ = execute_with_retries_if(
             || self.bucket.delete_object(full_key_path.clone()),
-            RetryConfig::default(),
+            is_retryable_s3_error,
+            RetryConfig::default().with_full_jitter(),
             MAX_S3_RETRIES,
         )?;
         Ok(())
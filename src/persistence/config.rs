@@ -16,6 +16,7 @@ use s3::bucket::Bucket as S3Bucket;
 
 use crate::connectors::{PersistenceMode, SnapshotAccess};
 use crate::deepcopy::DeepCopy;
+use crate::engine::dataflow::shard::Shard;
 use crate::engine::error::DynError;
 use crate::engine::license::License;
 use crate::engine::{Result, Timestamp, TotalFrontier};
@@ -24,20 +25,22 @@ use crate::persistence::backends::{
     AzureKVStorage, FilesystemKVStorage, MockKVStorage, PersistenceBackend, S3KVStorage,
 };
 use crate::persistence::cached_object_storage::CachedObjectStorage;
+use crate::persistence::handoff::{HandoffCoordinator, HANDOFF_DIRECTORY_NAME};
 use crate::persistence::input_snapshot::{
     Event, InputSnapshotReader, InputSnapshotWriter, MockSnapshotReader, ReadInputSnapshot,
     SnapshotMode,
 };
 use crate::persistence::operator_snapshot::{
     ConcreteSnapshotMerger, ConcreteSnapshotReader, ConcreteSnapshotWriter,
-    MultiConcreteSnapshotReader,
+    MultiConcreteSnapshotReader, OperatorSnapshotReader, ReshardingSnapshotReader,
 };
 use crate::persistence::state::FinalizedTimeQuerier;
-use crate::persistence::state::MetadataAccessor;
+use crate::persistence::state::{MetadataAccessor, OperatorSnapshotLayout};
 use crate::persistence::Error as PersistenceBackendError;
 use crate::persistence::{PersistentId, SharedSnapshotWriter};
 
 const STREAMS_DIRECTORY_NAME: &str = "streams";
+const RESHARDED_OPERATOR_SNAPSHOTS_DIRECTORY_NAME: &str = "operator-snapshots";
 
 pub type ConnectorWorkerPair = (PersistentId, usize);
 
@@ -79,6 +82,42 @@ impl PersistentStorageConfig {
             Self::Mock(_) => Ok(Box::new(MockKVStorage {})),
         }
     }
+
+    /// Creates the backend storing its keys at the given path relative to the root.
+    pub fn create_at(
+        &self,
+        relative_path: &str,
+    ) -> Result<Box<dyn PersistenceBackend>, PersistenceBackendError> {
+        match &self {
+            Self::Filesystem(root_path) => {
+                let path = root_path.join(relative_path);
+                ensure_directory(&path)?;
+                Ok(Box::new(FilesystemKVStorage::new(&path)?))
+            }
+            Self::S3 { bucket, root_path } => Ok(Box::new(S3KVStorage::new(
+                bucket.deep_copy(),
+                &format!(
+                    "{}/{relative_path}",
+                    root_path.strip_suffix('/').unwrap_or(root_path)
+                ),
+            ))),
+            Self::Azure {
+                account,
+                credentials,
+                container,
+                root_path,
+            } => Ok(Box::new(AzureKVStorage::new(
+                &format!(
+                    "{}/{relative_path}",
+                    root_path.strip_suffix('/').unwrap_or(root_path)
+                ),
+                account.clone(),
+                container.clone(),
+                credentials.clone(),
+            )?)),
+            Self::Mock(_) => Ok(Box::new(MockKVStorage {})),
+        }
+    }
 }
 
 /// Persistence in Pathway consists of two parts: actual frontier
@@ -93,6 +132,8 @@ pub struct PersistenceManagerOuterConfig {
     snapshot_access: SnapshotAccess,
     persistence_mode: PersistenceMode,
     continue_after_replay: bool,
+    rewind_thresholds: HashMap<PersistentId, Timestamp>,
+    handoff_lease_duration: Option<Duration>,
 }
 
 impl PersistenceManagerOuterConfig {
@@ -109,9 +150,47 @@ impl PersistenceManagerOuterConfig {
             snapshot_access,
             persistence_mode,
             continue_after_replay,
+            rewind_thresholds: HashMap::new(),
+            handoff_lease_duration: None,
         }
     }
 
+    /// Makes the sources with the given persistent ids read again from the given times. Their
+    /// snapshots are replayed only up to these times and the rest is removed, then the sources
+    /// continue from the offsets they had at these times.
+    #[must_use]
+    pub fn with_rewind_thresholds(
+        mut self,
+        rewind_thresholds: HashMap<PersistentId, Timestamp>,
+    ) -> Self {
+        self.rewind_thresholds = rewind_thresholds;
+        self
+    }
+
+    /// Makes the process hold a lease with the given duration on the persistence location, so
+    /// that it can be handed over to a new version of the program, as described in
+    /// [`crate::persistence::handoff`].
+    #[must_use]
+    pub fn with_handoff(mut self, lease_duration: Duration) -> Self {
+        self.handoff_lease_duration = Some(lease_duration);
+        self
+    }
+
+    pub fn create_handoff_coordinator(
+        &self,
+        process_id: usize,
+    ) -> Result<Option<HandoffCoordinator>, PersistenceBackendError> {
+        let Some(lease_duration) = self.handoff_lease_duration else {
+            return Ok(None);
+        };
+        let backend = self.backend.create_at(HANDOFF_DIRECTORY_NAME)?;
+        Ok(Some(HandoffCoordinator::new(
+            backend,
+            process_id,
+            lease_duration,
+        )))
+    }
+
     pub fn into_inner(self, worker_id: usize, total_workers: usize) -> PersistenceManagerConfig {
         PersistenceManagerConfig::new(self, worker_id, total_workers)
     }
@@ -137,6 +216,7 @@ pub struct PersistenceManagerConfig {
     pub worker_id: usize,
     pub snapshot_interval: Duration,
     total_workers: usize,
+    rewind_thresholds: HashMap<PersistentId, Timestamp>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -191,9 +271,15 @@ impl PersistenceManagerConfig {
             snapshot_interval: outer_config.snapshot_interval,
             worker_id,
             total_workers,
+            rewind_thresholds: outer_config.rewind_thresholds,
         }
     }
 
+    /// The time the source with the given persistent id has to be read again from, if any.
+    pub fn rewind_threshold(&self, persistent_id: PersistentId) -> Option<Timestamp> {
+        self.rewind_thresholds.get(&persistent_id).copied()
+    }
+
     pub fn create_cached_object_storage(
         &self,
         persistent_id: PersistentId,
@@ -241,19 +327,21 @@ impl PersistenceManagerConfig {
         MetadataAccessor::new(backend, self.worker_id, self.total_workers)
     }
 
+    /// Returns the backends of the snapshots assigned to this worker, along with the ids of the
+    /// workers that have written them.
     fn get_readers_backends(
         &self,
         persistent_id: PersistentId,
         query_purpose: ReadersQueryPurpose,
-    ) -> Result<Vec<Box<dyn PersistenceBackend>>, PersistenceBackendError> {
-        let mut result: Vec<Box<dyn PersistenceBackend>> = Vec::new();
+    ) -> Result<Vec<(usize, Box<dyn PersistenceBackend>)>, PersistenceBackendError> {
+        let mut result: Vec<(usize, Box<dyn PersistenceBackend>)> = Vec::new();
         match &self.backend {
             PersistentStorageConfig::Filesystem(root_path) => {
                 let assigned_snapshot_paths =
                     self.assigned_local_snapshot_paths(root_path, persistent_id, query_purpose)?;
-                for (_, path) in assigned_snapshot_paths {
+                for (worker_id, path) in assigned_snapshot_paths {
                     let backend = FilesystemKVStorage::new(&path)?;
-                    result.push(Box::new(backend));
+                    result.push((worker_id, Box::new(backend)));
                 }
                 Ok(result)
             }
@@ -266,9 +354,9 @@ impl PersistenceManagerConfig {
                     persistent_id,
                     query_purpose,
                 )?;
-                for (_, path) in assigned_snapshot_paths {
+                for (worker_id, path) in assigned_snapshot_paths {
                     let backend = S3KVStorage::new(bucket.deep_copy(), &path);
-                    result.push(Box::new(backend));
+                    result.push((worker_id, Box::new(backend)));
                 }
                 Ok(result)
             }
@@ -291,14 +379,14 @@ impl PersistenceManagerConfig {
                     persistent_id,
                     query_purpose,
                 )?;
-                for (_, path) in assigned_snapshot_paths {
+                for (worker_id, path) in assigned_snapshot_paths {
                     let backend = AzureKVStorage::new(
                         &path,
                         account.to_string(),
                         container.to_string(),
                         credentials.clone(),
                     )?;
-                    result.push(Box::new(backend));
+                    result.push((worker_id, Box::new(backend)));
                 }
                 Ok(result)
             }
@@ -324,7 +412,7 @@ impl PersistenceManagerConfig {
             Ok(result)
         } else {
             let backends = self.get_readers_backends(persistent_id, query_purpose)?;
-            for backend in backends {
+            for (_, backend) in backends {
                 let reader = InputSnapshotReader::new(
                     backend,
                     threshold_time,
@@ -343,14 +431,24 @@ impl PersistenceManagerConfig {
     fn get_writer_backend(
         &mut self,
         persistent_id: PersistentId,
+    ) -> Result<Box<dyn PersistenceBackend>, PersistenceBackendError> {
+        self.get_worker_backend(self.worker_id, persistent_id)
+    }
+
+    fn get_worker_backend(
+        &self,
+        worker_id: usize,
+        persistent_id: PersistentId,
     ) -> Result<Box<dyn PersistenceBackend>, PersistenceBackendError> {
         match &self.backend {
-            PersistentStorageConfig::Filesystem(root_path) => Ok(Box::new(
-                FilesystemKVStorage::new(&self.snapshot_writer_path(root_path, persistent_id)?)?,
-            )),
+            PersistentStorageConfig::Filesystem(root_path) => {
+                Ok(Box::new(FilesystemKVStorage::new(
+                    &Self::snapshot_writer_path(root_path, worker_id, persistent_id)?,
+                )?))
+            }
             PersistentStorageConfig::S3 { bucket, root_path } => Ok(Box::new(S3KVStorage::new(
                 bucket.deep_copy(),
-                &self.cloud_snapshot_path(root_path, persistent_id),
+                &Self::cloud_snapshot_path(root_path, worker_id, persistent_id),
             ))),
             PersistentStorageConfig::Azure {
                 root_path,
@@ -358,7 +456,7 @@ impl PersistenceManagerConfig {
                 container,
                 credentials,
             } => Ok(Box::new(AzureKVStorage::new(
-                &self.cloud_snapshot_path(root_path, persistent_id),
+                &Self::cloud_snapshot_path(root_path, worker_id, persistent_id),
                 account.to_string(),
                 container.to_string(),
                 credentials.clone(),
@@ -369,6 +467,21 @@ impl PersistenceManagerConfig {
         }
     }
 
+    fn get_operator_snapshot_backend(
+        &self,
+        layout: OperatorSnapshotLayout,
+        worker_id: usize,
+        persistent_id: PersistentId,
+    ) -> Result<Box<dyn PersistenceBackend>, PersistenceBackendError> {
+        let OperatorSnapshotLayout::Resharded { total_workers } = layout else {
+            return self.get_worker_backend(worker_id, persistent_id);
+        };
+        let relative_path = format!(
+            "{RESHARDED_OPERATOR_SNAPSHOTS_DIRECTORY_NAME}/{total_workers}/{worker_id}/{persistent_id}"
+        );
+        self.backend.create_at(&relative_path)
+    }
+
     pub fn create_snapshot_writer(
         &mut self,
         persistent_id: PersistentId,
@@ -386,12 +499,12 @@ impl PersistenceManagerConfig {
     }
 
     fn snapshot_writer_path(
-        &self,
         root_path: &Path,
+        worker_id: usize,
         persistent_id: PersistentId,
     ) -> Result<PathBuf, IoError> {
         let streams_path = root_path.join(STREAMS_DIRECTORY_NAME);
-        let worker_path = streams_path.join(self.worker_id.to_string());
+        let worker_path = streams_path.join(worker_id.to_string());
         ensure_directory(&worker_path)?;
         Ok(worker_path.join(persistent_id.to_string()))
     }
@@ -403,11 +516,15 @@ impl PersistenceManagerConfig {
         )
     }
 
-    fn cloud_snapshot_path(&self, root_path: &str, persistent_id: PersistentId) -> String {
+    fn cloud_snapshot_path(
+        root_path: &str,
+        worker_id: usize,
+        persistent_id: PersistentId,
+    ) -> String {
         format!(
             "{}/{}/{}",
             Self::cloud_snapshots_root_path(root_path),
-            self.worker_id,
+            worker_id,
             persistent_id
         )
     }
@@ -492,18 +609,17 @@ impl PersistenceManagerConfig {
 
     fn create_operator_snapshot_merger<D, R>(
         &mut self,
-        persistent_id: PersistentId,
+        merger_backends: Vec<Box<dyn PersistenceBackend>>,
         receiver: mpsc::Receiver<()>,
     ) -> Result<ConcreteSnapshotMerger, PersistenceBackendError>
     where
         D: ExchangeData,
         R: ExchangeData + Semigroup,
     {
-        let merger_backend = self.get_writer_backend(persistent_id)?;
         let metadata_backend = self.backend.create()?;
         let time_querier = FinalizedTimeQuerier::new(metadata_backend, self.total_workers);
         let merger = ConcreteSnapshotMerger::new::<D, R>(
-            merger_backend,
+            merger_backends,
             self.snapshot_interval,
             time_querier,
             receiver,
@@ -511,38 +627,130 @@ impl PersistenceManagerConfig {
         Ok(merger)
     }
 
+    /// Creates the reader of the state saved in the snapshots of the `past_runs_layout`, and
+    /// the merger of the snapshots written in this run in the given `layout`. If the layouts
+    /// differ, the state is re-sharded for the current number of workers.
     pub fn create_operator_snapshot_readers<D, R>(
         &mut self,
         persistent_id: PersistentId,
         threshold_time: TotalFrontier<Timestamp>,
-    ) -> Result<(MultiConcreteSnapshotReader, ConcreteSnapshotMerger), PersistenceBackendError>
+        past_runs_layout: OperatorSnapshotLayout,
+        layout: OperatorSnapshotLayout,
+    ) -> Result<
+        (
+            Box<dyn OperatorSnapshotReader<D, R> + Send>,
+            ConcreteSnapshotMerger,
+        ),
+        PersistenceBackendError,
+    >
     where
-        D: ExchangeData,
+        D: ExchangeData + Shard,
         R: ExchangeData + Semigroup,
     {
         info!("Using threshold time: {threshold_time:?} to create operator snapshot readers");
+        if past_runs_layout != layout {
+            return self.create_resharding_snapshot_reader::<D, R>(
+                persistent_id,
+                threshold_time,
+                past_runs_layout,
+                layout,
+            );
+        }
+
         let mut readers: Vec<ConcreteSnapshotReader> = Vec::new();
-        let backends =
-            self.get_readers_backends(persistent_id, ReadersQueryPurpose::ReadSnapshot)?;
-        for backend in backends {
-            let reader = ConcreteSnapshotReader::new(backend, threshold_time);
-            readers.push(reader);
+        let mut merger_backends =
+            vec![self.get_operator_snapshot_backend(layout, self.worker_id, persistent_id)?];
+        match layout {
+            OperatorSnapshotLayout::Streams => {
+                let backends =
+                    self.get_readers_backends(persistent_id, ReadersQueryPurpose::ReadSnapshot)?;
+                for (worker_id, backend) in backends {
+                    // The snapshots of the former workers, written by the older versions
+                    // without re-sharding, are still read on every restart, so they are
+                    // compacted too, otherwise they would only grow
+                    if worker_id != self.worker_id {
+                        merger_backends.push(self.get_worker_backend(worker_id, persistent_id)?);
+                    }
+                    readers.push(ConcreteSnapshotReader::new(backend, threshold_time));
+                }
+            }
+            OperatorSnapshotLayout::Resharded { .. } => {
+                let backend =
+                    self.get_operator_snapshot_backend(layout, self.worker_id, persistent_id)?;
+                readers.push(ConcreteSnapshotReader::new(backend, threshold_time));
+            }
         }
         let (sender, receiver) = mpsc::channel(); // pair used to block merger until reader finishes
         let reader = MultiConcreteSnapshotReader::new(readers, sender);
-        let merger = self.create_operator_snapshot_merger::<D, R>(persistent_id, receiver)?;
-        Ok((reader, merger))
+        let merger = self.create_operator_snapshot_merger::<D, R>(merger_backends, receiver)?;
+        Ok((Box::new(reader), merger))
+    }
+
+    fn create_resharding_snapshot_reader<D, R>(
+        &mut self,
+        persistent_id: PersistentId,
+        threshold_time: TotalFrontier<Timestamp>,
+        past_runs_layout: OperatorSnapshotLayout,
+        layout: OperatorSnapshotLayout,
+    ) -> Result<
+        (
+            Box<dyn OperatorSnapshotReader<D, R> + Send>,
+            ConcreteSnapshotMerger,
+        ),
+        PersistenceBackendError,
+    >
+    where
+        D: ExchangeData + Shard,
+        R: ExchangeData + Semigroup,
+    {
+        info!("Re-sharding the operator snapshots from {past_runs_layout:?} to {layout:?}");
+        let source_backends = match past_runs_layout {
+            // All the snapshots are read, like when the frontier is reconstructed
+            OperatorSnapshotLayout::Streams => self
+                .get_readers_backends(persistent_id, ReadersQueryPurpose::ReconstructFrontier)?
+                .into_iter()
+                .map(|(_, backend)| backend)
+                .collect(),
+            OperatorSnapshotLayout::Resharded { total_workers } => (0..total_workers)
+                .map(|worker_id| {
+                    self.get_operator_snapshot_backend(past_runs_layout, worker_id, persistent_id)
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        // The leftovers of a failed attempt to re-shard for the same number of workers
+        // are never valid: the metadata version of that attempt hasn't become stable
+        let target_backend =
+            self.get_operator_snapshot_backend(layout, self.worker_id, persistent_id)?;
+        for key in target_backend.list_keys()? {
+            target_backend.remove_key(&key)?;
+        }
+
+        let (sender, receiver) = mpsc::channel(); // pair used to block merger until reader finishes
+        let reader = ReshardingSnapshotReader::new(
+            source_backends,
+            target_backend,
+            threshold_time,
+            self.worker_id,
+            self.total_workers,
+            sender,
+        );
+        let merger_backends =
+            vec![self.get_operator_snapshot_backend(layout, self.worker_id, persistent_id)?];
+        let merger = self.create_operator_snapshot_merger::<D, R>(merger_backends, receiver)?;
+        Ok((Box::new(reader), merger))
     }
 
     pub fn create_operator_snapshot_writer<D, R>(
         &mut self,
         persistent_id: PersistentId,
+        layout: OperatorSnapshotLayout,
     ) -> Result<ConcreteSnapshotWriter<D, R>, PersistenceBackendError>
     where
         D: ExchangeData,
         R: ExchangeData + Semigroup,
     {
-        let backend = self.get_writer_backend(persistent_id)?;
+        let backend = self.get_operator_snapshot_backend(layout, self.worker_id, persistent_id)?;
         let writer = ConcreteSnapshotWriter::new(backend, self.snapshot_interval);
         Ok(writer)
     }
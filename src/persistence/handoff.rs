@@ -0,0 +1,220 @@
+// Copyright © 2024 Pathway
+
+//! Handing the persistence location over from a running process to a new version of the
+//! program, e.g. during a blue/green deployment.
+//!
+//! A process running with the handoff enabled holds a lease on the location, which it renews
+//! periodically. A new process started with the same location waits in standby: it requests
+//! the takeover and blocks until the current holder releases the lease. The holder notices the
+//! request, shuts down gracefully, which makes it commit what it has read and persist its final
+//! state, and only then releases the lease. The new process then restores the state and
+//! continues reading from where the old one has stopped, so no two processes ever read the
+//! sources or write to the sinks at the same time.
+//!
+//! If the holder crashes, its lease expires and the waiting process takes over anyway. Each
+//! process of a multi-process computation holds a separate lease, so the processes are handed
+//! over independently, and both versions have to run with the same number of processes.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::dataflow::shutdown;
+use crate::persistence::backends::PersistenceBackend;
+use crate::persistence::Error;
+use crate::timestamp::current_unix_timestamp_ms;
+
+pub const HANDOFF_DIRECTORY_NAME: &str = "handoff";
+pub const DEFAULT_LEASE_DURATION: Duration = Duration::from_secs(30);
+
+/// How often the waiting process checks if the lease has been released.
+const TAKEOVER_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+enum LeaseState {
+    Held,
+    HandoffRequested,
+    Lost,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct LeaseRecord {
+    token: String,
+    renewed_at_ms: u128,
+}
+
+pub struct HandoffCoordinator {
+    backend: Box<dyn PersistenceBackend>,
+    process_id: usize,
+    token: String,
+    lease_duration: Duration,
+}
+
+impl HandoffCoordinator {
+    pub fn new(
+        backend: Box<dyn PersistenceBackend>,
+        process_id: usize,
+        lease_duration: Duration,
+    ) -> Self {
+        Self {
+            backend,
+            process_id,
+            token: uuid::Uuid::new_v4().to_string(),
+            lease_duration,
+        }
+    }
+
+    fn lease_key(&self) -> String {
+        format!("lease-{}", self.process_id)
+    }
+
+    fn request_key(&self) -> String {
+        format!("request-{}", self.process_id)
+    }
+
+    fn released_key(&self) -> String {
+        format!("released-{}", self.process_id)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        if !self.backend.list_keys()?.iter().any(|k| k == key) {
+            return Ok(None);
+        }
+        self.backend.get_value(key).map(Some)
+    }
+
+    fn put(&self, key: &str, value: Vec<u8>) -> Result<(), Error> {
+        futures::executor::block_on(self.backend.put_value(key, value))
+            .expect("unexpected future cancelling")
+    }
+
+    fn lease(&self) -> Result<Option<LeaseRecord>, Error> {
+        let Some(data) = self.get(&self.lease_key())? else {
+            return Ok(None);
+        };
+        let data = std::str::from_utf8(&data)?;
+        let lease = serde_json::from_str(data)
+            .map_err(|e| Error::IncorrectMetadataFormat(data.to_string(), e))?;
+        Ok(Some(lease))
+    }
+
+    fn renew_lease(&self) -> Result<(), Error> {
+        let lease = LeaseRecord {
+            token: self.token.clone(),
+            renewed_at_ms: current_unix_timestamp_ms(),
+        };
+        let data = serde_json::to_string(&lease).expect("lease serialization should not fail");
+        self.put(&self.lease_key(), data.into_bytes())
+    }
+
+    /// Waits until the process holding the lease releases it or lets it expire, then takes the
+    /// lease over. Returns immediately if no process holds the lease.
+    pub fn take_over(&self) -> Result<(), Error> {
+        self.put(&self.request_key(), self.token.clone().into_bytes())?;
+        let mut reported_waiting = false;
+        while let Some(lease) = self.lease()? {
+            if self.get(&self.released_key())? == Some(lease.token.clone().into_bytes()) {
+                info!("The persistence location has been handed over by the previous process");
+                break;
+            }
+            let lease_age = current_unix_timestamp_ms().saturating_sub(lease.renewed_at_ms);
+            if lease_age > self.lease_duration.as_millis() {
+                warn!(
+                    "The process holding the persistence location hasn't renewed its lease for {lease_age} ms, taking over"
+                );
+                break;
+            }
+            if !reported_waiting {
+                info!("Waiting in standby for the running process to hand the persistence location over");
+                reported_waiting = true;
+            }
+            thread::sleep(TAKEOVER_POLL_INTERVAL);
+        }
+        self.renew_lease()?;
+        self.backend.remove_key(&self.released_key())?;
+        if self.get(&self.request_key())? == Some(self.token.clone().into_bytes()) {
+            self.backend.remove_key(&self.request_key())?;
+        }
+        Ok(())
+    }
+
+    fn lease_state(&self) -> Result<LeaseState, Error> {
+        if let Some(lease) = self.lease()? {
+            if lease.token != self.token {
+                return Ok(LeaseState::Lost);
+            }
+        }
+        let request = self.get(&self.request_key())?;
+        if request.is_some_and(|request| request != self.token.as_bytes()) {
+            Ok(LeaseState::HandoffRequested)
+        } else {
+            Ok(LeaseState::Held)
+        }
+    }
+
+    /// Lets the waiting process take the persistence location over. Has to be called only once
+    /// the computation has finished and its final state has been persisted, and after the
+    /// [`LeaseGuard`] is dropped.
+    pub fn release(&self) -> Result<(), Error> {
+        self.put(&self.released_key(), self.token.clone().into_bytes())
+    }
+
+    /// Renews the lease in a background thread until the returned guard is dropped. Once
+    /// another process requests the handoff, a graceful shutdown of this process is requested.
+    pub fn keep_lease(self: &Arc<Self>) -> LeaseGuard {
+        let finished = Arc::new(AtomicBool::new(false));
+        let coordinator = self.clone();
+        let thread_finished = finished.clone();
+        let renewal_interval = self.lease_duration / 3;
+        let thread = thread::Builder::new()
+            .name("pathway:handoff".to_string())
+            .spawn(move || {
+                while !thread_finished.load(Ordering::SeqCst) {
+                    match coordinator.lease_state() {
+                        Ok(LeaseState::Held) => {}
+                        Ok(LeaseState::HandoffRequested) => {
+                            if !shutdown::is_shutdown_requested() {
+                                info!("Another process has requested the handoff, shutting down");
+                                shutdown::request_shutdown();
+                            }
+                        }
+                        Ok(LeaseState::Lost) => {
+                            // The lease has expired and the location is used by another process
+                            // already, so the lease mustn't be renewed anymore
+                            error!("The lease on the persistence location has been taken over by another process, shutting down");
+                            shutdown::request_shutdown();
+                            break;
+                        }
+                        Err(e) => error!("Failed to check the lease on the persistence location: {e}"),
+                    }
+                    if let Err(e) = coordinator.renew_lease() {
+                        error!("Failed to renew the lease on the persistence location: {e}");
+                    }
+                    thread::park_timeout(renewal_interval);
+                }
+            })
+            .expect("handoff thread creation failed");
+        LeaseGuard {
+            finished,
+            thread: Some(thread),
+        }
+    }
+}
+
+pub struct LeaseGuard {
+    finished: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for LeaseGuard {
+    fn drop(&mut self) {
+        self.finished.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            thread.join().expect("handoff thread should not panic");
+        }
+    }
+}
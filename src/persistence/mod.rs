@@ -14,6 +14,7 @@ pub mod backends;
 pub mod cached_object_storage;
 pub mod config;
 pub mod frontier;
+pub mod handoff;
 pub mod input_snapshot;
 pub mod operator_snapshot;
 pub mod state;
@@ -12,6 +12,7 @@ use differential_dataflow::ExchangeData;
 use differential_dataflow::{consolidation::consolidate, difference::Semigroup};
 use log::error;
 
+use crate::engine::dataflow::shard::Shard;
 use crate::engine::{Timestamp, TotalFrontier};
 use crate::persistence::backends::{BackendPutFuture, Error as BackendError, PersistenceBackend};
 use crate::persistence::state::FinalizedTimeQuerier;
@@ -228,6 +229,73 @@ where
         Ok(result)
     }
 }
+
+/// Restores the state from the snapshots written for a different number of workers. Each
+/// worker reads all of them and keeps the entries of the keys it owns now, then saves these
+/// entries as the base of its own re-sharded snapshot. The snapshots read are left intact, as
+/// they are still needed if the run fails before its metadata becomes stable.
+pub struct ReshardingSnapshotReader {
+    source_backends: Vec<Box<dyn PersistenceBackend>>,
+    target_backend: Box<dyn PersistenceBackend>,
+    threshold_time: TotalFrontier<Timestamp>,
+    worker_id: usize,
+    total_workers: usize,
+    sender: mpsc::Sender<()>,
+}
+
+impl ReshardingSnapshotReader {
+    /// The `target_backend` is expected to be empty.
+    pub fn new(
+        source_backends: Vec<Box<dyn PersistenceBackend>>,
+        target_backend: Box<dyn PersistenceBackend>,
+        threshold_time: TotalFrontier<Timestamp>,
+        worker_id: usize,
+        total_workers: usize,
+        sender: mpsc::Sender<()>,
+    ) -> Self {
+        Self {
+            source_backends,
+            target_backend,
+            threshold_time,
+            worker_id,
+            total_workers,
+            sender,
+        }
+    }
+}
+
+impl<D, R> OperatorSnapshotReader<D, R> for ReshardingSnapshotReader
+where
+    D: ExchangeData + Shard,
+    R: ExchangeData + Semigroup,
+{
+    fn load_persisted(&mut self) -> Result<Vec<(D, R)>, BackendError> {
+        let mut result = Vec::new();
+        for backend in &self.source_backends {
+            // The chunks are only read, as the other workers read the same snapshots
+            let chunks = get_chunks(backend.list_keys()?, self.threshold_time);
+            let mut v: Vec<(D, R)> = read_chunks(&chunks.current, backend.as_ref())?;
+            v.retain(|(data, _diff)| data.shard_as_usize() % self.total_workers == self.worker_id);
+            result.append(&mut v);
+        }
+        consolidate(&mut result);
+        if !result.is_empty() {
+            let chunk_name = ChunkName {
+                level: 0,
+                time: Timestamp::persistence_time(),
+                len: result.len(),
+            };
+            let serialized_data = serialize(&result).expect("entry should be serializable");
+            let future = self
+                .target_backend
+                .put_value(&chunk_name.to_string(), serialized_data);
+            futures::executor::block_on(future).expect("unexpected future cancelling")?;
+        }
+        self.sender.send(()).expect("merger should exist"); // inform merger that it can start its work
+        Ok(result)
+    }
+}
+
 pub struct ConcreteSnapshotWriter<D, R> {
     backend: Box<dyn PersistenceBackend>,
     single_time_buffer: Vec<(D, R)>,
@@ -345,8 +413,11 @@ pub struct ConcreteSnapshotMerger {
 }
 
 impl ConcreteSnapshotMerger {
+    /// Starts merging the snapshots stored in `backends`. Besides the snapshot of the worker
+    /// itself, these are the snapshots of the former workers assigned to it, if the number of
+    /// workers has decreased since they were written.
     pub fn new<D, R>(
-        backend: Box<dyn PersistenceBackend>,
+        backends: Vec<Box<dyn PersistenceBackend>>,
         snapshot_interval: core::time::Duration,
         time_querier: FinalizedTimeQuerier,
         receiver: mpsc::Receiver<()>,
@@ -356,7 +427,7 @@ impl ConcreteSnapshotMerger {
         R: ExchangeData + Semigroup,
     {
         let (finish_sender, thread_handle) =
-            Self::start::<D, R>(backend, snapshot_interval, time_querier, receiver);
+            Self::start::<D, R>(backends, snapshot_interval, time_querier, receiver);
         Self {
             finish_sender,
             thread_handle: Some(thread_handle),
@@ -438,7 +509,7 @@ impl ConcreteSnapshotMerger {
     }
 
     fn run<D, R>(
-        mut backend: Box<dyn PersistenceBackend>,
+        mut backends: Vec<Box<dyn PersistenceBackend>>,
         receiver: &mpsc::Receiver<()>,
         timeout: core::time::Duration,
         time_querier: &mut FinalizedTimeQuerier,
@@ -461,8 +532,10 @@ impl ConcreteSnapshotMerger {
                 .expect("now with added timeout should fit into Instant");
             match receiver.recv_timeout(duration) {
                 Err(mpsc::RecvTimeoutError::Timeout) => {
-                    if let Err(e) = Self::maybe_merge::<D, R>(backend.as_mut(), time_querier) {
-                        error!("Error while trying to merge persisted data: {e}");
+                    for backend in &mut backends {
+                        if let Err(e) = Self::maybe_merge::<D, R>(backend.as_mut(), time_querier) {
+                            error!("Error while trying to merge persisted data: {e}");
+                        }
                     }
                 }
                 Ok(()) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
@@ -471,7 +544,7 @@ impl ConcreteSnapshotMerger {
     }
 
     fn start<D, R>(
-        backend: Box<dyn PersistenceBackend>,
+        backends: Vec<Box<dyn PersistenceBackend>>,
         timeout: core::time::Duration,
         mut time_querier: FinalizedTimeQuerier,
         reader_finished_receiver: mpsc::Receiver<()>,
@@ -486,7 +559,7 @@ impl ConcreteSnapshotMerger {
             .name("SnapshotMerger".to_string()) // TODO maybe better name
             .spawn(move || {
                 Self::run::<D, R>(
-                    backend,
+                    backends,
                     &receiver,
                     timeout,
                     &mut time_querier,
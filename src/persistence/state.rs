@@ -29,6 +29,25 @@ pub struct StoredMetadata {
     // better than to use the current number of workers.
     #[serde(default)]
     pub total_workers: usize,
+
+    // Whether the operator snapshots are re-sharded for the number of workers of
+    // this run, see `OperatorSnapshotLayout`. Unspecified in the older versions,
+    // where they are always stored along with the input snapshots.
+    #[serde(default)]
+    pub operator_snapshots_resharded: bool,
+}
+
+/// The way the operator snapshots of a run are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatorSnapshotLayout {
+    /// Along with the input snapshots, in the directories of the workers that have written
+    /// them. The state of a key may be spread over the snapshots of several workers.
+    Streams,
+
+    /// Re-sharded for the given number of workers: the snapshot of each worker contains only
+    /// the keys the worker owns. The runs switch to this layout once the number of workers
+    /// changes, and re-shard the snapshots again on each further change.
+    Resharded { total_workers: usize },
 }
 
 #[derive(Debug)]
@@ -36,6 +55,8 @@ pub struct MetadataAccessor {
     backend: Box<dyn PersistenceBackend>,
     internal_state: StoredMetadata,
     past_runs_threshold_time: TotalFrontier<Timestamp>,
+    past_runs_total_workers: Option<usize>,
+    past_runs_operator_snapshot_layout: OperatorSnapshotLayout,
 
     current_key_to_use: String,
     next_key_to_use: String,
@@ -46,6 +67,7 @@ impl StoredMetadata {
         Self {
             last_advanced_timestamp: TotalFrontier::At(Timestamp(0)),
             total_workers,
+            operator_snapshots_resharded: false,
         }
     }
 
@@ -123,12 +145,14 @@ impl Display for MetadataKey {
 
 struct VersionInformation {
     worker_finalized_times: Vec<Option<TotalFrontier<Timestamp>>>,
+    operator_snapshots_resharded: bool,
 }
 
 impl VersionInformation {
-    pub fn new(total_workers: usize) -> Self {
+    pub fn new(total_workers: usize, operator_snapshots_resharded: bool) -> Self {
         Self {
             worker_finalized_times: vec![None; total_workers],
+            operator_snapshots_resharded,
         }
     }
 
@@ -147,6 +171,20 @@ impl VersionInformation {
         }
     }
 
+    pub fn total_workers(&self) -> usize {
+        self.worker_finalized_times.len()
+    }
+
+    pub fn operator_snapshot_layout(&self) -> OperatorSnapshotLayout {
+        if self.operator_snapshots_resharded {
+            OperatorSnapshotLayout::Resharded {
+                total_workers: self.total_workers(),
+            }
+        } else {
+            OperatorSnapshotLayout::Streams
+        }
+    }
+
     pub fn threshold_time(&self) -> Option<TotalFrontier<Timestamp>> {
         if self.worker_finalized_times.contains(&None) {
             // Not all workers reported their threshold times
@@ -157,11 +195,26 @@ impl VersionInformation {
     }
 }
 
+/// The number of workers of the run that has saved the latest stable version,
+/// and the layout of its operator snapshots.
+struct PastRunsWorkers {
+    total_workers: usize,
+    operator_snapshot_layout: OperatorSnapshotLayout,
+}
+
 fn compute_threshold_time_and_versions(
     backend: &mut dyn PersistenceBackend,
     should_remove: bool,
     total_workers: usize,
-) -> Result<(TotalFrontier<Timestamp>, u128, Option<u128>), Error> {
+) -> Result<
+    (
+        TotalFrontier<Timestamp>,
+        u128,
+        Option<u128>,
+        Option<PastRunsWorkers>,
+    ),
+    Error,
+> {
     // We want to start from the latest version that has metadata for all its workers.
     // In the code, we call it the latest stable version.
     // Only top-level keys are needed for the metadata reconstruction.
@@ -187,7 +240,10 @@ fn compute_threshold_time_and_versions(
             Ok(block) => {
                 version_information
                     .entry(metadata_key.version)
-                    .or_insert(VersionInformation::new(block.total_workers))
+                    .or_insert(VersionInformation::new(
+                        block.total_workers,
+                        block.operator_snapshots_resharded,
+                    ))
                     .update_worker_time(metadata_key.worker_id, block.last_advanced_timestamp);
             }
             Err(e) => {
@@ -203,6 +259,7 @@ fn compute_threshold_time_and_versions(
     }
 
     let mut past_runs_threshold_time = TotalFrontier::At(Timestamp(0));
+    let mut past_runs_workers = None;
     let mut latest_stable_version = None;
     for (version_number, version_data) in &version_information {
         let threshold_time = version_data.threshold_time();
@@ -212,6 +269,10 @@ fn compute_threshold_time_and_versions(
         if latest_stable_version.is_none_or(|current_version| current_version < *version_number) {
             latest_stable_version = Some(*version_number);
             past_runs_threshold_time = threshold_time;
+            past_runs_workers = Some(PastRunsWorkers {
+                total_workers: version_data.total_workers(),
+                operator_snapshot_layout: version_data.operator_snapshot_layout(),
+            });
         }
     }
 
@@ -241,6 +302,7 @@ fn compute_threshold_time_and_versions(
         past_runs_threshold_time,
         current_version,
         latest_stable_version,
+        past_runs_workers,
     ))
 }
 
@@ -250,10 +312,26 @@ impl MetadataAccessor {
         worker_id: usize,
         total_workers: usize,
     ) -> Result<Self, Error> {
-        let internal_state = StoredMetadata::new(total_workers);
-        let (past_runs_threshold_time, current_version, latest_stable_version) =
+        let mut internal_state = StoredMetadata::new(total_workers);
+        let (past_runs_threshold_time, current_version, latest_stable_version, past_runs_workers) =
             compute_threshold_time_and_versions(backend.as_mut(), worker_id == 0, total_workers)?;
         info!("Worker {worker_id} is on the version {current_version}. The latest stable metadata version is {latest_stable_version:?}");
+        let past_runs_total_workers = past_runs_workers.as_ref().map(|past| past.total_workers);
+        let past_runs_operator_snapshot_layout = past_runs_workers
+            .as_ref()
+            .map_or(OperatorSnapshotLayout::Streams, |past| {
+                past.operator_snapshot_layout
+            });
+        let changed_total_workers = past_runs_total_workers
+            .filter(|past_runs_total_workers| *past_runs_total_workers != total_workers);
+        if let Some(past_runs_total_workers) = changed_total_workers {
+            if worker_id == 0 {
+                info!("The number of workers has changed from {past_runs_total_workers} to {total_workers}. The operator snapshots will be re-sharded");
+            }
+        }
+        // Once re-sharded, the snapshots stay in the re-sharded layout
+        internal_state.operator_snapshots_resharded = changed_total_workers.is_some()
+            || past_runs_operator_snapshot_layout != OperatorSnapshotLayout::Streams;
         let current_key_to_use =
             MetadataKey::from_components(current_version, worker_id, 0).to_string();
         let next_key_to_use =
@@ -263,6 +341,8 @@ impl MetadataAccessor {
             backend,
             internal_state,
             past_runs_threshold_time,
+            past_runs_total_workers,
+            past_runs_operator_snapshot_layout,
             current_key_to_use,
             next_key_to_use,
         })
@@ -272,6 +352,29 @@ impl MetadataAccessor {
         self.past_runs_threshold_time
     }
 
+    /// The number of workers in the run that has saved the latest stable version of the
+    /// metadata. `None` if there is no such version, e.g. on the first run.
+    pub fn past_runs_total_workers(&self) -> Option<usize> {
+        self.past_runs_total_workers
+    }
+
+    /// The layout of the operator snapshots of the run that has saved the latest stable version
+    /// of the metadata. These are the snapshots to restore the state from.
+    pub fn past_runs_operator_snapshot_layout(&self) -> OperatorSnapshotLayout {
+        self.past_runs_operator_snapshot_layout
+    }
+
+    /// The layout of the operator snapshots written in this run.
+    pub fn operator_snapshot_layout(&self) -> OperatorSnapshotLayout {
+        if self.internal_state.operator_snapshots_resharded {
+            OperatorSnapshotLayout::Resharded {
+                total_workers: self.internal_state.total_workers,
+            }
+        } else {
+            OperatorSnapshotLayout::Streams
+        }
+    }
+
     pub fn accept_finalized_timestamp(&mut self, timestamp: TotalFrontier<Timestamp>) {
         self.internal_state.last_advanced_timestamp = timestamp;
     }
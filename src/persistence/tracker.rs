@@ -3,11 +3,15 @@
 use differential_dataflow::difference::Semigroup;
 use differential_dataflow::ExchangeData;
 use log::error;
+use opentelemetry::KeyValue;
 use std::collections::{HashMap, HashSet};
 use std::mem::take;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use crate::connectors::PersistenceMode;
+use crate::engine::dataflow::shard::Shard;
+use crate::engine::spans;
 use crate::engine::{Timestamp, TotalFrontier};
 use crate::persistence::backends::BackendPutFuture as PersistenceBackendFlushFuture;
 use crate::persistence::cached_object_storage::{
@@ -141,6 +145,10 @@ impl WorkerPersistentStorage {
         required_persistence_mode.matches(self.config.persistence_mode)
     }
 
+    pub fn snapshot_interval(&self) -> Duration {
+        self.config.snapshot_interval
+    }
+
     pub fn last_finalized_timestamp(&self) -> TotalFrontier<Timestamp> {
         self.metadata_storage.last_advanced_timestamp()
     }
@@ -181,10 +189,21 @@ impl WorkerPersistentStorage {
         };
         let timestamp_updated = normalized_finalized_timestamp != self.last_finalized_timestamp();
         if timestamp_updated {
-            let mut commit_data =
-                self.accept_finalized_timestamp(normalized_finalized_timestamp)?;
-            commit_data.prepare()?;
-            self.commit_finalized_timestamp(&commit_data)?;
+            spans::trace(
+                spans::CHECKPOINT,
+                || {
+                    let mut commit_data =
+                        self.accept_finalized_timestamp(normalized_finalized_timestamp)?;
+                    commit_data.prepare()?;
+                    self.commit_finalized_timestamp(&commit_data)
+                },
+                |_| {
+                    vec![KeyValue::new(
+                        spans::CHECKPOINT_TIMESTAMP,
+                        format!("{normalized_finalized_timestamp:?}"),
+                    )]
+                },
+            )?;
         }
         Ok(())
     }
@@ -253,11 +272,13 @@ impl WorkerPersistentStorage {
         persistent_id: PersistentId,
         query_purpose: ReadersQueryPurpose,
     ) -> Result<Vec<Box<dyn ReadInputSnapshot>>, PersistenceBackendError> {
-        self.config.create_snapshot_readers(
-            persistent_id,
-            self.metadata_storage.past_runs_threshold_time(),
-            query_purpose,
-        )
+        let mut threshold_time = self.metadata_storage.past_runs_threshold_time();
+        if let Some(rewind_threshold) = self.config.rewind_threshold(persistent_id) {
+            // The rest of the snapshot is truncated, so the source is read again from there
+            threshold_time = threshold_time.min(TotalFrontier::At(rewind_threshold));
+        }
+        self.config
+            .create_snapshot_readers(persistent_id, threshold_time, query_purpose)
     }
 
     pub fn create_snapshot_writer(
@@ -281,15 +302,17 @@ impl WorkerPersistentStorage {
         persistent_id: PersistentId,
     ) -> Result<Box<dyn OperatorSnapshotReader<D, R> + Send>, PersistenceBackendError>
     where
-        D: ExchangeData,
+        D: ExchangeData + Shard,
         R: ExchangeData + Semigroup,
     {
         let (reader, merger) = self.config.create_operator_snapshot_readers::<D, R>(
             persistent_id,
             self.metadata_storage.past_runs_threshold_time(),
+            self.metadata_storage.past_runs_operator_snapshot_layout(),
+            self.metadata_storage.operator_snapshot_layout(),
         )?;
         self.operator_snapshot_mergers.push(merger);
-        Ok(Box::new(reader))
+        Ok(reader)
     }
 
     pub fn create_operator_snapshot_writer<D, R>(
@@ -300,7 +323,10 @@ impl WorkerPersistentStorage {
         D: ExchangeData,
         R: ExchangeData + Semigroup,
     {
-        let writer = self.config.create_operator_snapshot_writer(persistent_id)?;
+        let writer = self.config.create_operator_snapshot_writer(
+            persistent_id,
+            self.metadata_storage.operator_snapshot_layout(),
+        )?;
         let writer = Arc::new(Mutex::new(writer));
         let writer_flushable: Arc<Mutex<dyn Flushable + Send>> = writer.clone();
         self.operator_snapshot_writers
@@ -1,6 +1,6 @@
 // Copyright © 2024 Pathway
 
-use std::io;
+use std::io::{self, Read};
 
 use cfg_if::cfg_if;
 
@@ -10,21 +10,21 @@ cfg_if! {
         use nix::unistd;
         use std::os::fd::{AsFd, OwnedFd};
     } else if #[cfg(windows)] {
-        use std::ptr::null_mut;
-        use std::os::windows::io::{OwnedHandle, FromRawHandle};
-        use windows_sys::Win32::System::Pipes::CreatePipe;
+        use std::ptr::{null, null_mut};
+        use std::os::windows::io::{AsHandle, AsRawHandle, OwnedHandle, FromRawHandle};
+        use windows_sys::Win32::System::Pipes::{
+            CreatePipe, SetNamedPipeHandleState, PIPE_NOWAIT, PIPE_READMODE_BYTE,
+        };
         use windows_sys::Win32::Foundation;
     }
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub enum ReaderType {
     Blocking,
     NonBlocking,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub enum WriterType {
     Blocking,
@@ -56,6 +56,41 @@ fn set_non_blocking(fd: impl AsFd) -> io::Result<()> {
     Ok(())
 }
 
+/// Anonymous pipes are named pipes under the hood, so they can be switched to the `PIPE_NOWAIT`
+/// mode. In this mode, a read from an empty pipe fails with `ERROR_NO_DATA` instead of waiting,
+/// and a write to a full pipe returns immediately, having written only what fit.
+#[cfg(windows)]
+fn set_non_blocking(handle: impl AsHandle) -> io::Result<()> {
+    let mode = PIPE_READMODE_BYTE | PIPE_NOWAIT;
+    let success = unsafe {
+        SetNamedPipeHandleState(handle.as_handle().as_raw_handle(), &mode, null(), null())
+    };
+    if success == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Reads from the reading end of a pipe. A read from an empty non-blocking pipe fails with
+/// [`io::ErrorKind::WouldBlock`] on all platforms: on Windows, it fails with `ERROR_NO_DATA`,
+/// which the standard library doesn't report as `WouldBlock`.
+pub fn read(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    cfg_if! {
+        if #[cfg(windows)] {
+            reader.read(buffer).map_err(|error| {
+                let code = error.raw_os_error().and_then(|code| u32::try_from(code).ok());
+                if code == Some(Foundation::ERROR_NO_DATA) {
+                    io::Error::new(io::ErrorKind::WouldBlock, error)
+                } else {
+                    error
+                }
+            })
+        } else {
+            reader.read(buffer)
+        }
+    }
+}
+
 #[cfg(unix)]
 #[cfg_attr(target_os = "linux", allow(dead_code))]
 fn set_cloexec(fd: impl AsFd) -> io::Result<()> {
@@ -91,8 +126,6 @@ pub fn pipe(reader_type: ReaderType, writer_type: WriterType) -> io::Result<Pipe
         } else if #[cfg(windows)] {
             use Foundation::{HANDLE, INVALID_HANDLE_VALUE, CloseHandle};
             
-            // For Windows, we'll use anonymous pipes
-            // with named pipes and overlapped I/O operations
             let mut read_handle: HANDLE = null_mut();
             let mut write_handle: HANDLE = null_mut();
             
@@ -127,7 +160,15 @@ pub fn pipe(reader_type: ReaderType, writer_type: WriterType) -> io::Result<Pipe
             // Wrap raw handles in OwnedHandle for automatic resource management
             let reader = unsafe { OwnedHandle::from_raw_handle(read_handle) };
             let writer = unsafe { OwnedHandle::from_raw_handle(write_handle) };
-            
+
+            if let ReaderType::NonBlocking = reader_type {
+                set_non_blocking(&reader)?;
+            }
+
+            if let WriterType::NonBlocking = writer_type {
+                set_non_blocking(&writer)?;
+            }
+
             Ok(Pipe { reader, writer })
         }
     }
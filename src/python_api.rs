@@ -3,18 +3,23 @@
 // `PyRef`s need to be passed by value
 #![allow(clippy::needless_pass_by_value)]
 
-use crate::async_runtime::create_async_tokio_runtime;
+use crate::async_runtime::{
+    create_async_tokio_runtime, execute_with_limits, set_runtime_config, with_connector_runtime,
+    AsyncCallLimits, CallTimedOut, RuntimeConfig as AsyncRuntimeConfig, TimeoutPolicy,
+};
 use crate::engine::graph::{
     ErrorLogHandle, ExportedTable, JoinExactlyOnce, OperatorProperties, SubscribeCallbacks,
     SubscribeCallbacksBuilder, SubscribeConfig,
 };
-use crate::engine::license::{Error as LicenseError, License};
+use crate::engine::license::{Error as LicenseError, License, LicenseInfo as EngineLicenseInfo};
 use crate::engine::{
     Computer as EngineComputer, Expressions, PyObjectWrapper as InternalPyObjectWrapper,
     ShardPolicy, TotalFrontier,
 };
+use crate::env;
 use crate::persistence::frontier::OffsetAntichain;
 
+use apache_avro::Schema as AvroSchema;
 use async_nats::connect as nats_connect;
 use async_nats::Client as NatsClient;
 use async_nats::Subscriber as NatsSubscriber;
@@ -30,9 +35,7 @@ use elasticsearch::{
     },
     Elasticsearch,
 };
-use futures::future;
-use futures::stream::FuturesOrdered;
-use futures::StreamExt;
+use futures::channel::oneshot;
 use itertools::Itertools;
 use log::{info, warn};
 use mongodb::sync::Client as MongoClient;
@@ -42,7 +45,7 @@ use once_cell::sync::Lazy;
 use postgres::{Client, NoTls};
 use pyo3::exceptions::{
     PyBaseException, PyException, PyIOError, PyIndexError, PyKeyError, PyNotImplementedError,
-    PyRuntimeError, PyTypeError, PyValueError, PyZeroDivisionError,
+    PyRuntimeError, PyTimeoutError, PyTypeError, PyValueError, PyZeroDivisionError,
 };
 use pyo3::pyclass::CompareOp;
 use pyo3::sync::{GILOnceCell, GILProtected};
@@ -70,27 +73,38 @@ use serde_json::Value as JsonValue;
 use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ffi::CString;
 use std::fs::File;
-use std::io::{BufWriter, Read};
+use std::future::Future;
+use std::io::BufWriter;
 use std::mem::take;
 #[cfg(unix)]
 use std::os::unix::prelude::*;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time;
 
+use self::arrow_interchange::{
+    captured_table_to_arrow, check_arrow_types, data_rows_from_record_batch,
+    record_batch_from_data_rows, record_batch_from_py, PyArrowBatch,
+};
+use self::batch_conversion::{column_into_py, extract_column, ArgumentBatch};
+use self::custom_connectors::{PythonSink, PythonSinkCallbacks, PythonSource};
 use self::external_index_wrappers::{
     PyBruteForceKnnMetricKind, PyExternalIndexData, PyExternalIndexQuery, PyUSearchMetricKind,
 };
 use self::threads::PythonThreadState;
 
 use crate::connectors::aws::DynamoDBWriter;
+use crate::connectors::backfill::{BackfillThenStreamReaderBuilder, SeamBoundary};
 use crate::connectors::data_format::{
-    BsonFormatter, DebeziumDBType, DebeziumMessageParser, DsvSettings, Formatter,
-    IdentityFormatter, IdentityParser, InnerSchemaField, JsonLinesFormatter, JsonLinesParser,
-    KeyGenerationPolicy, NullFormatter, Parser, PsqlSnapshotFormatter, PsqlUpdatesFormatter,
+    AvroFormatter, AvroParser, AvroSchemaRegistry, BsonFormatter, DebeziumDBType,
+    DebeziumFormatter, DebeziumMessageParser, DsvSettings, Formatter, IdentityFormatter,
+    IdentityParser, InnerSchemaField, JsonLinesFormatter, JsonLinesParser, KeyGenerationPolicy,
+    LengthPrefix, LengthPrefixedParser, MsgPackFormatter, MsgPackParser, NullFormatter,
+    ParquetParser, Parser, ProtobufParser, PsqlSnapshotFormatter, PsqlUpdatesFormatter,
     RegistryEncoderWrapper, SingleColumnFormatter, TransparentParser,
 };
 use crate::connectors::data_lake::arrow::construct_schema as construct_arrow_schema;
@@ -99,7 +113,7 @@ use crate::connectors::data_lake::buffering::{
 };
 use crate::connectors::data_lake::delta::DeltaOptimizerRule;
 use crate::connectors::data_lake::iceberg::{
-    IcebergBatchWriter, IcebergDBParams, IcebergTableParams,
+    IcebergBatchWriter, IcebergCatalogKind, IcebergDBParams, IcebergTableParams,
 };
 use crate::connectors::data_lake::{DeltaBatchWriter, MaintenanceMode};
 use crate::connectors::data_storage::{
@@ -111,15 +125,54 @@ use crate::connectors::data_storage::{
     MQTT_CLIENT_MAX_CHANNEL_SIZE,
 };
 use crate::connectors::data_tokenize::{BufReaderTokenizer, CsvTokenizer, Tokenize};
+use crate::connectors::file_rotation::{
+    FileRotationConfig, NameTemplate, RotatingFileWriter, DEFAULT_NAME_TEMPLATE,
+};
+use crate::connectors::lag_throttling::{
+    LagThrottlingConfig, DEFAULT_CHECK_INTERVAL, DEFAULT_MIN_LAG,
+};
+use crate::connectors::masking::{ColumnMasking, MaskingFormatter, MaskingMethod};
+use crate::connectors::monitoring::{BackfillProgress, ConnectorStats};
+use crate::connectors::mssql::SqlServerWriter;
+use crate::connectors::named_schema::{
+    register_schema as register_named_schema, registered_schema, NamedSchema, SchemaColumn,
+    SchemaValidatingParser,
+};
+use crate::connectors::oracle::OracleWriter;
+use crate::connectors::parquet_output::{ParquetFileWriter, DEFAULT_ROW_GROUP_SIZE};
+use crate::connectors::pausing::set_connector_paused;
 use crate::connectors::posix_like::PosixLikeReader;
-use crate::connectors::scanner::{FilesystemScanner, S3Scanner};
+use crate::connectors::prometheus::PrometheusWriter;
+use crate::connectors::pushdown::{ComparisonOp, PushdownFormatter, SinkPredicate};
+use crate::connectors::query_endpoint::QueryEndpointWriter;
+use crate::connectors::scanner::{FilesystemScanner, ImapConfig, ImapScanner, S3Scanner};
+use crate::connectors::schema_drift;
+use crate::connectors::sdk::{SinkWriter, SourceReader};
+use crate::connectors::snapshot_export::{
+    export_snapshot, ExportError, ExportFormat, ExportedSnapshot, SnapshotExportWriter,
+};
+use crate::connectors::sql_merge::SqlSinkMode;
+use crate::connectors::sql_polling::{
+    MySqlPollingClient, SqlPollingClient, SqlPollingConfig, SqlPollingReader,
+    SqlServerPollingClient,
+};
+use crate::connectors::subprocess::{
+    RestartPolicy, SubprocessCommand, SubprocessReader, SubprocessWriter,
+};
 use crate::connectors::synchronization::ConnectorGroupDescriptor;
 use crate::connectors::{PersistenceMode, SessionType, SnapshotAccess};
+use crate::engine::constraints::{self, ConstraintId};
+use crate::engine::dataflow::monitoring::{CountStats, OperatorStats, ProberStats};
+use crate::engine::dataflow::shutdown::{self, CancellationToken};
 use crate::engine::dataflow::Config;
 use crate::engine::error::{DataError, DynError, DynResult, Trace as EngineTrace};
 use crate::engine::graph::ScopedContext;
-use crate::engine::progress_reporter::MonitoringLevel;
-use crate::engine::reduce::StatefulCombineFn;
+use crate::engine::log_context;
+use crate::engine::progress_reporter::{latest_stats, MonitoringLevel};
+use crate::engine::reduce::{keep_first_combine_fn, StatefulCombineFn};
+use crate::engine::sql::{plan_query, SqlError, TableSchema};
+use crate::engine::telemetry::Sampling as TelemetrySampling;
+use crate::engine::telemetry::Signals as TelemetrySignals;
 use crate::engine::time::DateTime;
 use crate::engine::Config as EngineTelemetryConfig;
 use crate::engine::Timestamp;
@@ -127,28 +180,34 @@ use crate::engine::Timestamp;
 use crate::engine::{
     run_with_new_dataflow_graph, BatchWrapper, ColumnHandle, ColumnPath,
     ColumnProperties as EngineColumnProperties, DataRow, DateTimeNaive, DateTimeUtc, Duration,
-    ExpressionData, IxKeyPolicy, JoinData, JoinType, Key, KeyImpl, PointerExpression, Reducer,
-    ReducerData, ScopedGraph, TableHandle, TableProperties as EngineTableProperties, Type,
-    UniverseHandle, Value,
+    ExpressionData, IxKeyPolicy, JoinData, JoinType, Key, KeyImpl, NativeUdfPlugin,
+    PointerExpression, Reducer, ReducerData, ScopedGraph, TableHandle,
+    TableProperties as EngineTableProperties, Type, UdfCache, UdfCacheConfig, UdfErrorAction,
+    UdfErrorPolicy, UniverseHandle, Value, WasmUdfLimits, WasmUdfModule,
 };
 use crate::engine::{AnyExpression, Context as EngineContext};
-use crate::engine::{BoolExpression, Error as EngineError};
+use crate::engine::{BoolExpression, BytesExpression, Error as EngineError};
 use crate::engine::{ComplexColumn as EngineComplexColumn, WakeupReceiver};
 use crate::engine::{DateTimeNaiveExpression, DateTimeUtcExpression, DurationExpression};
 use crate::engine::{Expression, IntExpression};
-use crate::engine::{FloatExpression, Graph};
+use crate::engine::{FloatExpression, FormatTemplate, Graph};
 use crate::engine::{LegacyTable as EngineLegacyTable, StringExpression};
 use crate::persistence::config::{
     ConnectorWorkerPair, PersistenceManagerOuterConfig, PersistentStorageConfig,
 };
+use crate::persistence::handoff::DEFAULT_LEASE_DURATION;
 use crate::persistence::input_snapshot::Event as SnapshotEvent;
 use crate::persistence::{IntoPersistentId, UniqueName};
-use crate::pipe::{pipe, ReaderType, WriterType};
+use crate::pipe::{self, pipe, ReaderType, WriterType};
 use crate::python_api::external_index_wrappers::PyExternalIndexFactory;
+use crate::retry::RetryPolicy;
 use crate::timestamp::current_unix_timestamp_ms;
 
 use s3::creds::Credentials as AwsCredentials;
 
+mod arrow_interchange;
+mod batch_conversion;
+mod custom_connectors;
 mod external_index_wrappers;
 mod logging;
 pub mod threads;
@@ -334,15 +393,17 @@ fn extract_int_array(ob: &Bound<PyAny>, dim: Option<usize>) -> Option<ndarray::A
 }
 
 #[allow(clippy::cast_precision_loss)]
-fn extract_float_array(ob: &Bound<PyAny>, dim: Option<usize>) -> Option<ndarray::ArrayD<f64>> {
+fn extract_float_array(ob: &Bound<PyAny>, dim: Option<usize>) -> Option<Value> {
+    if let Ok(array) = ob.extract::<PyReadonlyArrayDyn<f32>>() {
+        // single precision is kept to save memory
+        return array_with_proper_dimensions(array.as_array().to_owned(), dim).map(Value::from);
+    }
     let array = if let Ok(array) = ob.extract::<PyReadonlyArrayDyn<f64>>() {
         array.as_array().to_owned()
-    } else if let Ok(array) = ob.extract::<PyReadonlyArrayDyn<f32>>() {
-        array.as_array().mapv(f64::from)
     } else {
         extract_int_array(ob, dim).map(|array| array.mapv(|v| v as f64))?
     };
-    array_with_proper_dimensions(array, dim)
+    array_with_proper_dimensions(array, dim).map(Value::from)
 }
 
 fn py_type_error(ob: &Bound<PyAny>, type_: &Type) -> PyErr {
@@ -392,10 +453,10 @@ pub fn extract_value(ob: &Bound<PyAny>, type_: &Type) -> PyResult<Value> {
         }
         Type::Array(dim, wrapped) => match wrapped.borrow() {
             Type::Int => Ok(extract_int_array(ob, *dim).map(Value::from)),
-            Type::Float => Ok(extract_float_array(ob, *dim).map(Value::from)),
+            Type::Float => Ok(extract_float_array(ob, *dim)),
             Type::Any => Ok(extract_int_array(ob, *dim)
                 .map(Value::from)
-                .or_else(|| extract_float_array(ob, *dim).map(Value::from))),
+                .or_else(|| extract_float_array(ob, *dim))),
             wrapped => Err(PyValueError::new_err(format!(
                 "{wrapped:?} is invalid type for Array"
             ))),
@@ -493,7 +554,7 @@ impl<'py> FromPyObject<'py> for Value {
         } else if let Ok(array) = ob.extract::<PyReadonlyArrayDyn<f64>>() {
             Ok(Value::from(array.as_array().to_owned()))
         } else if let Ok(array) = ob.extract::<PyReadonlyArrayDyn<f32>>() {
-            Ok(Value::from(array.as_array().mapv(f64::from)))
+            Ok(Value::from(array.as_array().to_owned()))
         } else if let Ok(i) = ob.extract::<i64>() {
             Ok(Value::Int(i))
         } else if let Ok(f) = ob.extract::<f64>() {
@@ -560,6 +621,7 @@ impl<'py> IntoPyObject<'py> for &Value {
             Value::Tuple(t) => Ok(PyTuple::new(py, t.iter())?.into_any()),
             Value::IntArray(a) => Ok(PyArray::from_array(py, a).into_any()),
             Value::FloatArray(a) => Ok(PyArray::from_array(py, a).into_any()),
+            Value::Float32Array(a) => Ok(PyArray::from_array(py, a).into_any()),
             Value::DateTimeNaive(dt) => dt.into_bound_py_any(py),
             Value::DateTimeUtc(dt) => dt.into_bound_py_any(py),
             Value::Duration(d) => d.into_bound_py_any(py),
@@ -612,6 +674,36 @@ impl<'py> IntoPyObject<'py> for Type {
     }
 }
 
+impl<'py> FromPyObject<'py> for TimeoutPolicy {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<PyRef<PyAsyncTimeoutPolicy>>()?.0)
+    }
+}
+
+impl<'py> IntoPyObject<'py> for TimeoutPolicy {
+    type Target = PyAny;
+    type Output = Bound<'py, Self::Target>;
+    type Error = PyErr;
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        PyAsyncTimeoutPolicy(self).into_bound_py_any(py)
+    }
+}
+
+impl<'py> FromPyObject<'py> for UdfErrorAction {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<PyRef<PyUdfErrorAction>>()?.0)
+    }
+}
+
+impl<'py> IntoPyObject<'py> for UdfErrorAction {
+    type Target = PyAny;
+    type Output = Bound<'py, Self::Target>;
+    type Error = PyErr;
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        PyUdfErrorAction(self).into_bound_py_any(py)
+    }
+}
+
 impl<'py> FromPyObject<'py> for ReadMethod {
     fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
         Ok(ob.extract::<PyRef<PyReadMethod>>()?.0)
@@ -744,6 +836,31 @@ impl From<EngineError> for PyErr {
                 let args = (inner, trace);
                 return PyErr::from_type(ENGINE_ERROR_WITH_TRACE_TYPE.bind(py).clone(), args);
             }
+            if let EngineError::DataError(DataError::Fatal(inner) | DataError::ToErrorLog(inner)) =
+                error
+            {
+                // keep the type of the original error, e.g. a Python exception raised by a UDF
+                return PyErr::from(EngineError::from(inner));
+            }
+            if let EngineError::ConnectorFailed {
+                connector_name,
+                inner,
+            } = error
+            {
+                let inner = match EngineError::from(inner).downcast::<PyErr>() {
+                    // exceptions raised by the Python code of a connector keep their type
+                    Ok(inner) | Err(EngineError::ReaderFailed(ReadError::Py(inner))) => {
+                        return inner
+                    }
+                    Err(inner) => inner,
+                };
+                let message = format!("connector {connector_name} failed: {inner}");
+                return exception_with_fields(
+                    CONNECTOR_ERROR_TYPE.bind(py),
+                    message,
+                    &[("connector_name", connector_name.into_bound_py_any(py))],
+                );
+            }
             let exception_type = match error {
                 EngineError::DataError(ref error) => match error {
                     DataError::TypeMismatch { .. } => PyTypeError::type_object(py),
@@ -752,8 +869,8 @@ impl From<EngineError> for PyErr {
                     | DataError::KeyMissingInOutputTable(_)
                     | DataError::KeyMissingInInputTable(_) => PyKeyError::type_object(py),
                     DataError::DivisionByZero => PyZeroDivisionError::type_object(py),
-                    DataError::ParseError(_)
-                    | DataError::ValueError(_)
+                    DataError::ParseError(_) => PARSE_ERROR_TYPE.bind(py).clone(),
+                    DataError::ValueError(_)
                     | DataError::AppendOnlyViolation(_, _)
                     | DataError::RepeatedEntryInBatch => PyValueError::type_object(py),
                     DataError::IndexOutOfBounds => PyIndexError::type_object(py),
@@ -763,6 +880,10 @@ impl From<EngineError> for PyErr {
                 | EngineError::InconsistentColumnProperties
                 | EngineError::IdInTableProperties => PyValueError::type_object(py),
                 EngineError::ReaderFailed(ReadError::Py(e)) => return e,
+                EngineError::ReaderFailed(_) => CONNECTOR_ERROR_TYPE.bind(py).clone(),
+                EngineError::PersistentStorageError(_) | EngineError::SnapshotWriterError(_) => {
+                    PERSISTENCE_ERROR_TYPE.bind(py).clone()
+                }
                 EngineError::OtherWorkerPanic => OTHER_WORKER_ERROR.bind(py).clone(),
                 _ => ENGINE_ERROR_TYPE.bind(py).clone(),
             };
@@ -1250,80 +1371,171 @@ macro_rules! binary_expr {
     };
 }
 
-fn batch_apply(
+type ApplyFn = Box<dyn Fn(&[&[Value]]) -> Vec<DynResult<Value>> + Send + Sync>;
+
+fn with_error_policy(func: ApplyFn, error_policy: Option<UdfErrorPolicy>) -> ApplyFn {
+    match error_policy {
+        Some(error_policy) => Box::new(move |input: &[&[Value]]| {
+            error_policy
+                .retry_batch(input, &func)
+                .into_iter()
+                .map(|result| error_policy.handle(result))
+                .collect()
+        }),
+        None => func,
+    }
+}
+
+fn with_cache(func: ApplyFn, cache: Option<Arc<UdfCache>>) -> ApplyFn {
+    match cache {
+        Some(cache) => {
+            Box::new(move |input: &[&[Value]]| cache.apply(input, |missing| func(missing)))
+        }
+        None => func,
+    }
+}
+
+/// Calls a batch UDF on the columns of a single batch of arguments.
+fn call_batch(
     py: Python<'_>,
+    function: &Py<PyAny>,
+    columns: &[Vec<Value>],
+    n_rows: usize,
+    arg_dtypes: &[Type],
+    dtype: &Type,
+    vectorized: bool,
+) -> Vec<DynResult<Value>> {
+    let args = columns
+        .iter()
+        .zip(arg_dtypes)
+        .map(|(column, arg_dtype)| column_into_py(py, column, arg_dtype, vectorized))
+        .collect::<PyResult<Vec<_>>>()
+        .and_then(|data| PyTuple::new(py, data));
+    let results = args.and_then(|args| {
+        function
+            .call1(py, args)
+            .and_then(|results| extract_column(results.bind(py), dtype))
+    });
+    match results {
+        Ok(results) => {
+            if results.len() == n_rows {
+                results
+            } else {
+                let msg = format!("The number of rows produced by a UDF ({})", results.len())
+                    + &format!(" is different than the number of rows on its input ({n_rows}).");
+                (0..n_rows)
+                    .map(|_i| Err(PyValueError::new_err(msg.clone()).into()))
+                    .collect()
+            }
+        }
+        Err(e) => {
+            let msg = "Error in batch UDF.";
+            [Err(e.into())]
+                .into_iter()
+                .chain((1..n_rows).map(|_| Err(PyValueError::new_err(msg).into())))
+                .collect()
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn batch_apply(
     input: &[&[Value]],
-    n_args: usize,
     function: &Py<PyAny>,
+    arg_dtypes: &[Type],
     dtype: &Type,
     propagate_none: bool,
     max_batch_size: usize,
+    vectorized: bool,
 ) -> Vec<DynResult<Value>> {
-    input
+    let batches: Vec<_> = input
         .chunks(max_batch_size)
-        .flat_map(|inputs| {
-            let mut mask = vec![false; inputs.len()];
-            let mut data = Vec::with_capacity(n_args);
-            data.resize_with(n_args, || Vec::with_capacity(inputs.len()));
-            for (i, input_i) in inputs.iter().enumerate() {
-                if propagate_none && input_i.iter().any(|a| matches!(a, Value::None)) {
-                    mask[i] = true;
-                } else {
-                    for (j, input_ij) in input_i.iter().enumerate() {
-                        data[j].push(input_ij.clone());
-                    }
-                }
-            }
-            let args = PyTuple::new(py, data);
-            let results = args.and_then(|args| {
-                function
-                    .call1(py, args)
-                    .and_then(|results| results.extract::<Vec<Bound<PyAny>>>(py))
-            });
-            let batch_result: Vec<DynResult<Value>> = match results {
-                Ok(results) => {
-                    if results.len() == inputs.len() {
-                        results
-                            .into_iter()
-                            .map(|result| Ok(extract_value(&result, dtype)?))
-                            .collect()
-                    } else {
-                        let msg =
-                            format!("The number of rows produced by a UDF ({})", results.len())
-                                + &format!(
-                                    " is different than the number of rows on its input ({}).",
-                                    inputs.len()
-                                );
-                        inputs
-                            .iter()
-                            .map(|_i| Err(PyValueError::new_err(msg.clone()).into()))
-                            .collect()
-                    }
-                }
-                Err(e) => {
-                    let msg = "Error in batch UDF.";
-                    [Err(e.into())]
-                        .into_iter()
-                        .chain((1..inputs.len()).map(|_| Err(PyValueError::new_err(msg).into())))
-                        .collect()
-                }
-            };
-            if propagate_none {
-                let mut batch_result_iter = batch_result.into_iter();
-                mask.into_iter()
-                    .map(|m| {
-                        if m {
-                            Ok(Value::None)
-                        } else {
-                            batch_result_iter.next().unwrap()
-                        }
-                    })
-                    .collect()
-            } else {
-                batch_result
+        .map(|rows| ArgumentBatch::new(rows, propagate_none))
+        .collect();
+    let columns: Vec<_> = batches
+        .iter()
+        .map(|batch| batch.columns(arg_dtypes.len()))
+        .collect();
+    // the GIL is taken once for all the batches and only for the Python side of the work
+    let results: Vec<_> = Python::with_gil(|py| {
+        batches
+            .iter()
+            .zip(&columns)
+            .map(|(batch, columns)| {
+                call_batch(
+                    py,
+                    function,
+                    columns,
+                    batch.n_rows(),
+                    arg_dtypes,
+                    dtype,
+                    vectorized,
+                )
+            })
+            .collect()
+    });
+    drop(columns);
+    batches
+        .iter()
+        .zip(results)
+        .flat_map(|(batch, results)| batch.merge_results(results))
+        .collect()
+}
+
+/// Passes the result of a `concurrent.futures.Future` to Rust, used as its done callback.
+#[pyclass(module = "pathway.engine", frozen)]
+struct FutureResultSender(Mutex<Option<oneshot::Sender<PyResult<PyObject>>>>);
+
+#[pymethods]
+impl FutureResultSender {
+    fn __call__(&self, future: &Bound<PyAny>) {
+        let result = future.call_method0("result").map(Bound::unbind);
+        if let Some(sender) = self.0.lock().unwrap().take() {
+            // the receiver is gone if the call was cancelled, the result is not needed then
+            let _ = sender.send(result);
+        }
+    }
+}
+
+/// Cancels the task running a coroutine when dropped. Cancelling a finished task does nothing.
+struct CancelOnDrop(PyObject);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        Python::with_gil(|py| {
+            if let Err(e) = self.0.call_method0(py, "cancel") {
+                warn!("Failed to cancel an asynchronous UDF call: {e}");
             }
+        });
+    }
+}
+
+/// Runs the coroutine on the event loop and returns a future resolving to its result.
+/// Dropping the future, e.g. when the call times out, cancels the coroutine, so that it
+/// doesn't keep running in the background.
+fn run_coroutine_cancellable(
+    event_loop: &Bound<PyAny>,
+    coroutine: &Bound<PyAny>,
+) -> PyResult<impl Future<Output = PyResult<PyObject>> + Send + 'static> {
+    let py = event_loop.py();
+    let (sender, receiver) = oneshot::channel();
+    // `run_coroutine_threadsafe` schedules the task in a copy of the current context
+    let task = py
+        .import("asyncio")?
+        .call_method1("run_coroutine_threadsafe", (coroutine, event_loop))?;
+    task.call_method1(
+        "add_done_callback",
+        (FutureResultSender(Mutex::new(Some(sender))),),
+    )?;
+    let guard = CancelOnDrop(task.unbind());
+    Ok(async move {
+        let _guard = guard;
+        receiver.await.unwrap_or_else(|_| {
+            Err(PyRuntimeError::new_err(
+                "asynchronous UDF call finished without a result",
+            ))
         })
-        .collect()
+    })
 }
 
 #[pymethods]
@@ -1343,64 +1555,184 @@ impl PyExpression {
     }
 
     #[staticmethod]
-    #[pyo3(signature = (function, *args, dtype, propagate_none=false, max_batch_size=None))]
+    #[pyo3(signature = (function, *args, dtype, arg_dtypes=None, propagate_none=false, max_batch_size=None, vectorized=false, cache=None, error_policy=None))]
+    #[allow(clippy::too_many_arguments)]
     fn apply(
         function: Py<PyAny>,
         args: Vec<PyRef<PyExpression>>,
         dtype: Type,
+        arg_dtypes: Option<Vec<Type>>,
         propagate_none: bool,
         max_batch_size: Option<usize>,
-    ) -> Self {
+        vectorized: bool,
+        cache: Option<PyRef<PyUdfCache>>,
+        error_policy: Option<PyRef<PyUdfErrorPolicy>>,
+    ) -> PyResult<Self> {
+        let cache = cache.map(|cache| cache.inner.clone());
+        let error_policy = error_policy.map(|error_policy| error_policy.inner.clone());
         let args = args
             .into_iter()
             .map(|expr| expr.inner.clone())
             .collect_vec();
-        let n_args = args.len();
+        // rows with a `None` argument don't reach the function if `None` is propagated
+        let arg_dtypes = match arg_dtypes {
+            Some(arg_dtypes) if propagate_none => arg_dtypes
+                .iter()
+                .map(|arg_dtype| arg_dtype.unoptionalize().clone())
+                .collect(),
+            Some(arg_dtypes) => arg_dtypes,
+            None => vec![Type::Any; args.len()],
+        };
+        if arg_dtypes.len() != args.len() {
+            return Err(PyValueError::new_err(
+                "the number of argument types has to match the number of arguments",
+            ));
+        }
+        // a vectorized function without an explicit limit gets the whole batch at once
+        let max_batch_size = max_batch_size.or(vectorized.then_some(usize::MAX));
         let expression = if let Some(max_batch_size) = max_batch_size {
             let func = Box::new(move |input: &[&[Value]]| {
-                Python::with_gil(|py| -> Vec<DynResult<Value>> {
-                    batch_apply(
-                        py,
-                        input,
-                        n_args,
-                        &function,
-                        &dtype,
-                        propagate_none,
-                        max_batch_size,
-                    )
-                })
+                batch_apply(
+                    input,
+                    &function,
+                    &arg_dtypes,
+                    &dtype,
+                    propagate_none,
+                    max_batch_size,
+                    vectorized,
+                )
             });
-            AnyExpression::Apply(func, args.into())
+            AnyExpression::Apply(
+                with_cache(with_error_policy(func, error_policy), cache),
+                args.into(),
+            )
         } else {
             let func = Box::new(move |input: &[&[Value]]| {
-                Python::with_gil(|py| -> Vec<DynResult<Value>> {
-                    input
+                let batch = ArgumentBatch::new(input, propagate_none);
+                let results = Python::with_gil(|py| -> Vec<DynResult<Value>> {
+                    batch
+                        .rows()
                         .iter()
-                        .map(|input_i| {
-                            if propagate_none && input_i.iter().any(|a| matches!(a, Value::None)) {
-                                Ok(Value::None)
-                            } else {
-                                let args = PyTuple::new(py, *input_i)?;
-                                let result = function.call1(py, args)?;
-                                Ok(extract_value(result.bind(py), &dtype)?)
-                            }
+                        .map(|row| {
+                            let args = PyTuple::new(py, *row)?;
+                            let result = function.call1(py, args)?;
+                            Ok(extract_value(result.bind(py), &dtype)?)
                         })
                         .collect()
-                })
+                });
+                batch.merge_results(results)
             });
-            AnyExpression::Apply(func, args.into())
+            AnyExpression::Apply(
+                with_cache(with_error_policy(func, error_policy), cache),
+                args.into(),
+            )
+        };
+        Ok(Self::new(Arc::new(Expression::Any(expression)), true))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (library_path, function_name, *args, propagate_none=false))]
+    fn native_apply(
+        library_path: String,
+        function_name: &str,
+        args: Vec<PyRef<PyExpression>>,
+        propagate_none: bool,
+    ) -> PyResult<Self> {
+        let function = NativeUdfPlugin::load(&library_path)
+            .and_then(|plugin| plugin.function(function_name))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let args = args
+            .into_iter()
+            .map(|expr| expr.inner.clone())
+            .collect_vec();
+        let func = Box::new(move |input: &[&[Value]]| {
+            input
+                .iter()
+                .map(|input_i| {
+                    if propagate_none && input_i.iter().any(|a| matches!(a, Value::None)) {
+                        Ok(Value::None)
+                    } else {
+                        function.call(input_i)
+                    }
+                })
+                .collect()
+        });
+        let expression = AnyExpression::Apply(func, args.into());
+        Ok(Self::new(Arc::new(Expression::Any(expression)), false))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (
+        module_path,
+        function_name,
+        *args,
+        propagate_none=false,
+        max_memory_bytes=None,
+        timeout=None,
+    ))]
+    fn wasm_apply(
+        module_path: String,
+        function_name: &str,
+        args: Vec<PyRef<PyExpression>>,
+        propagate_none: bool,
+        max_memory_bytes: Option<usize>,
+        timeout: Option<time::Duration>,
+    ) -> PyResult<Self> {
+        let default_limits = WasmUdfLimits::default();
+        let limits = WasmUdfLimits {
+            max_memory_bytes: max_memory_bytes.unwrap_or(default_limits.max_memory_bytes),
+            timeout: timeout.unwrap_or(default_limits.timeout),
         };
-        Self::new(Arc::new(Expression::Any(expression)), true)
+        let function = WasmUdfModule::load(&module_path, limits)
+            .and_then(|module| module.function(function_name))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let args = args
+            .into_iter()
+            .map(|expr| expr.inner.clone())
+            .collect_vec();
+        let func = Box::new(move |input: &[&[Value]]| {
+            input
+                .iter()
+                .map(|input_i| {
+                    if propagate_none && input_i.iter().any(|a| matches!(a, Value::None)) {
+                        Ok(Value::None)
+                    } else {
+                        function.call(input_i)
+                    }
+                })
+                .collect()
+        });
+        let expression = AnyExpression::Apply(func, args.into());
+        Ok(Self::new(Arc::new(Expression::Any(expression)), false))
     }
 
     #[staticmethod]
-    #[pyo3(signature = (scope, function, *args, dtype, propagate_none=false))]
+    #[pyo3(signature = (
+        scope,
+        function,
+        *args,
+        dtype,
+        propagate_none=false,
+        max_in_flight=None,
+        timeout=None,
+        timeout_policy=TimeoutPolicy::Fail,
+        deduplicate=false,
+        cache=None,
+        error_policy=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn async_apply(
         scope: &Bound<Scope>,
         function: Py<PyAny>,
         args: Vec<PyRef<PyExpression>>,
         dtype: Type,
         propagate_none: bool,
+        max_in_flight: Option<usize>,
+        timeout: Option<time::Duration>,
+        timeout_policy: TimeoutPolicy,
+        deduplicate: bool,
+        cache: Option<PyRef<PyUdfCache>>,
+        error_policy: Option<PyRef<PyUdfErrorPolicy>>,
     ) -> Self {
         let args = args
             .into_iter()
@@ -1417,13 +1749,9 @@ impl PyExpression {
                 None
             } else {
                 Some(Python::with_gil(|py| {
-                    let event_loop = event_loop.clone_ref(py);
                     let args = PyTuple::new(py, input_i)?;
-                    let awaitable = function.call1(py, args)?;
-                    let awaitable = awaitable.into_bound(py);
-                    let locals = pyo3_async_runtimes::TaskLocals::new(event_loop.into_bound(py))
-                        .copy_context(py)?;
-                    pyo3_async_runtimes::into_future_with_locals(&locals, awaitable)
+                    let coroutine = function.call1(py, args)?;
+                    run_coroutine_cancellable(event_loop.bind(py), coroutine.bind(py))
                 }))
             };
 
@@ -1437,17 +1765,50 @@ impl PyExpression {
                 }
             })
         };
+        let limits = AsyncCallLimits {
+            max_in_flight,
+            timeout,
+            timeout_policy,
+            deduplicate,
+        };
+        let error_policy = error_policy.map(|error_policy| error_policy.inner.clone());
         let func = Box::new(move |input: &[&[Value]]| {
-            let futures: FuturesOrdered<_> = input.iter().map(|input_i| logic(input_i)).collect();
-            let mut result = Vec::with_capacity(futures.len());
-            futures::executor::block_on(futures.for_each(|item| {
-                result.push(item);
-                future::ready(())
-            }));
-            result
+            let results = match &error_policy {
+                // the retries of a call are awaited together with the other calls
+                Some(error_policy) => execute_with_limits(
+                    input,
+                    &limits,
+                    |input_i| {
+                        let input_i = input_i.to_vec();
+                        let logic = &logic;
+                        async move { error_policy.retry(|| logic(&input_i)).await }
+                    },
+                    || Value::None,
+                ),
+                None => {
+                    execute_with_limits(input, &limits, |input_i| logic(input_i), || Value::None)
+                }
+            };
+            results
+                .into_iter()
+                .map(|result| {
+                    result.map_err(|error| match error.downcast::<CallTimedOut>() {
+                        Ok(timed_out) => PyTimeoutError::new_err(timed_out.to_string()).into(),
+                        Err(error) => error,
+                    })
+                })
+                .map(|result| match &error_policy {
+                    Some(error_policy) => error_policy.handle(result),
+                    None => result,
+                })
+                .collect()
         });
+        let cache = cache.map(|cache| cache.inner.clone());
         Self::new(
-            Arc::new(Expression::Any(AnyExpression::Apply(func, args.into()))),
+            Arc::new(Expression::Any(AnyExpression::Apply(
+                with_cache(func, cache),
+                args.into(),
+            ))),
             true,
         )
     }
@@ -1767,6 +2128,31 @@ impl PyExpression {
         )
     }
 
+    #[staticmethod]
+    #[pyo3(signature = (template, *args))]
+    fn format(template: &str, args: Vec<PyRef<PyExpression>>) -> PyResult<Self> {
+        let template = FormatTemplate::parse(template).map_err(EngineError::from)?;
+        if template.n_arguments() != args.len() {
+            return Err(PyValueError::new_err(format!(
+                "format string expects {} arguments, got {}",
+                template.n_arguments(),
+                args.len()
+            )));
+        }
+        let gil = args.iter().any(|a| a.gil);
+        let args = args
+            .into_iter()
+            .map(|expr| expr.inner.clone())
+            .collect_vec();
+        Ok(Self::new(
+            Arc::new(Expression::String(StringExpression::Format(
+                Arc::new(template),
+                args.into(),
+            ))),
+            gil,
+        ))
+    }
+
     #[staticmethod]
     fn sequence_get_item_checked(
         expr: &PyExpression,
@@ -1912,6 +2298,12 @@ unary_expr!(duration_days, IntExpression::DurationDays);
 unary_expr!(duration_weeks, IntExpression::DurationWeeks);
 unary_expr!(unwrap, AnyExpression::Unwrap);
 unary_expr!(to_string, StringExpression::ToString);
+unary_expr!(encode_base64, StringExpression::EncodeBase64);
+unary_expr!(decode_base64, BytesExpression::DecodeBase64);
+unary_expr!(encode_hex, StringExpression::EncodeHex);
+unary_expr!(decode_hex, BytesExpression::DecodeHex);
+unary_expr!(to_utf8, BytesExpression::ToUtf8);
+unary_expr!(from_utf8_lossy, StringExpression::FromUtf8Lossy);
 unary_expr!(parse_int, AnyExpression::ParseStringToInt, optional: bool);
 unary_expr!(parse_float, AnyExpression::ParseStringToFloat, optional: bool);
 unary_expr!(
@@ -2063,6 +2455,17 @@ impl PyMonitoringLevel {
     pub const ALL: MonitoringLevel = MonitoringLevel::All;
 }
 
+#[pyclass(module = "pathway.engine", frozen, name = "AsyncTimeoutPolicy")]
+pub struct PyAsyncTimeoutPolicy(TimeoutPolicy);
+
+#[pymethods]
+impl PyAsyncTimeoutPolicy {
+    #[classattr]
+    pub const FAIL: TimeoutPolicy = TimeoutPolicy::Fail;
+    #[classattr]
+    pub const RETURN_NONE: TimeoutPolicy = TimeoutPolicy::ReturnFallback;
+}
+
 #[pyclass(module = "pathway.engine", frozen, name = "TableWriterInitMode")]
 pub struct PyTableWriterInitMode(TableWriterInitMode);
 
@@ -2076,6 +2479,116 @@ impl PyTableWriterInitMode {
     pub const REPLACE: TableWriterInitMode = TableWriterInitMode::Replace;
 }
 
+#[pyclass(module = "pathway.engine", frozen, name = "UdfErrorAction")]
+pub struct PyUdfErrorAction(UdfErrorAction);
+
+#[pymethods]
+impl PyUdfErrorAction {
+    #[classattr]
+    pub const PROPAGATE: UdfErrorAction = UdfErrorAction::Propagate;
+    #[classattr]
+    pub const RETURN_DEFAULT: UdfErrorAction = UdfErrorAction::ReturnDefault;
+    #[classattr]
+    pub const SEND_TO_ERROR_LOG: UdfErrorAction = UdfErrorAction::SendToErrorLog;
+    #[classattr]
+    pub const FAIL: UdfErrorAction = UdfErrorAction::Fail;
+}
+
+#[pyclass(module = "pathway.engine", frozen, name = "UdfErrorPolicy")]
+pub struct PyUdfErrorPolicy {
+    inner: UdfErrorPolicy,
+}
+
+#[pymethods]
+impl PyUdfErrorPolicy {
+    #[new]
+    #[pyo3(signature = (
+        *,
+        on_error=UdfErrorAction::Propagate,
+        default_value=Value::None,
+        max_retries=0,
+        retry_delay=None,
+        backoff_factor=None,
+        jitter=None,
+    ))]
+    fn new(
+        on_error: UdfErrorAction,
+        default_value: Value,
+        max_retries: usize,
+        retry_delay: Option<time::Duration>,
+        backoff_factor: Option<f64>,
+        jitter: Option<time::Duration>,
+    ) -> Self {
+        let defaults = UdfErrorPolicy::default();
+        Self {
+            inner: UdfErrorPolicy {
+                max_retries,
+                retry_delay: retry_delay.unwrap_or(defaults.retry_delay),
+                backoff_factor: backoff_factor.unwrap_or(defaults.backoff_factor),
+                jitter: jitter.unwrap_or(defaults.jitter),
+                on_error,
+                default_value,
+            },
+        }
+    }
+}
+
+#[pyclass(module = "pathway.engine", frozen, name = "UdfCache")]
+pub struct PyUdfCache {
+    inner: Arc<UdfCache>,
+}
+
+#[pymethods]
+impl PyUdfCache {
+    #[new]
+    #[pyo3(signature = (name, *, max_entries=None, ttl=None, storage=None))]
+    fn new(
+        name: String,
+        max_entries: Option<usize>,
+        ttl: Option<time::Duration>,
+        storage: Option<PyRef<DataStorage>>,
+    ) -> PyResult<Self> {
+        let backend = match storage {
+            Some(storage) => Some(
+                storage
+                    .construct_persistent_storage_config()?
+                    .create()
+                    .map_err(EngineError::from)?,
+            ),
+            None => None,
+        };
+        let config = UdfCacheConfig { max_entries, ttl };
+        Ok(Self {
+            inner: Arc::new(UdfCache::new(name, config, backend)),
+        })
+    }
+}
+
+/// Requests termination of the single run it is passed to, see [`CancellationToken`].
+#[pyclass(module = "pathway.engine", frozen, name = "CancellationToken")]
+pub struct PyCancellationToken {
+    inner: CancellationToken,
+}
+
+#[pymethods]
+impl PyCancellationToken {
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: CancellationToken::new(),
+        }
+    }
+
+    fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+}
+
 #[pyclass(module = "pathway.engine", frozen)]
 pub struct Universe {
     scope: Py<Scope>,
@@ -2429,7 +2942,107 @@ static ENGINE_ERROR_WITH_TRACE_TYPE: Lazy<Py<PyType>> = Lazy::new(|| {
     })
 });
 
-static OTHER_WORKER_ERROR: Lazy<Py<PyType>> = Lazy::new(|| {
+/// Creates an exception type in `pathway.engine` deriving from all the `bases`. Class
+/// attributes in `fields` are the default values of the structured fields of the exception.
+fn new_engine_exception_type(
+    py: Python<'_>,
+    name: &str,
+    doc: &str,
+    bases: &[Bound<'_, PyType>],
+    fields: &[(&str, Bound<'_, PyAny>)],
+) -> PyResult<Py<PyType>> {
+    let dict = PyDict::new(py);
+    dict.set_item("__module__", "pathway.engine")?;
+    dict.set_item("__doc__", doc)?;
+    for (field, default) in fields {
+        dict.set_item(field, default)?;
+    }
+    let exception_type = PyType::type_object(py).call1((name, PyTuple::new(py, bases)?, dict))?;
+    Ok(exception_type.downcast_into::<PyType>()?.unbind())
+}
+
+/// Creates an exception of the given type with its structured fields set.
+fn exception_with_fields(
+    exception_type: &Bound<PyType>,
+    message: String,
+    fields: &[(&str, PyResult<Bound<PyAny>>)],
+) -> PyErr {
+    let exception = exception_type.call1((message,)).and_then(|exception| {
+        for (field, value) in fields {
+            match value {
+                Ok(value) => exception.setattr(*field, value)?,
+                Err(error) => return Err(error.clone_ref(exception.py())),
+            }
+        }
+        Ok(exception)
+    });
+    match exception {
+        Ok(exception) => PyErr::from_value(exception),
+        Err(error) => error,
+    }
+}
+
+static PARSE_ERROR_TYPE: Lazy<Py<PyType>> = Lazy::new(|| {
+    Python::with_gil(|py| {
+        new_engine_exception_type(
+            py,
+            "ParseError",
+            "Raised when the data can't be parsed into values of the declared types.",
+            &[
+                ENGINE_ERROR_TYPE.bind(py).clone(),
+                PyValueError::type_object(py),
+            ],
+            &[],
+        )
+        .expect("creating ParseError type should not fail")
+    })
+});
+
+static CONNECTOR_ERROR_TYPE: Lazy<Py<PyType>> = Lazy::new(|| {
+    Python::with_gil(|py| {
+        new_engine_exception_type(
+            py,
+            "ConnectorError",
+            "Raised when an input or output connector fails. The name of the connector is \
+            available as `connector_name`, if it is known.",
+            &[ENGINE_ERROR_TYPE.bind(py).clone()],
+            &[("connector_name", py.None().into_bound(py))],
+        )
+        .expect("creating ConnectorError type should not fail")
+    })
+});
+
+static PERSISTENCE_ERROR_TYPE: Lazy<Py<PyType>> = Lazy::new(|| {
+    Python::with_gil(|py| {
+        new_engine_exception_type(
+            py,
+            "PersistenceError",
+            "Raised when the persisted state can't be read or written.",
+            &[ENGINE_ERROR_TYPE.bind(py).clone()],
+            &[],
+        )
+        .expect("creating PersistenceError type should not fail")
+    })
+});
+
+static LICENSE_ERROR_TYPE: Lazy<Py<PyType>> = Lazy::new(|| {
+    Python::with_gil(|py| {
+        new_engine_exception_type(
+            py,
+            "LicenseError",
+            "Raised when the license doesn't allow using a feature. The missing entitlements \
+            are available as `entitlements`.",
+            &[
+                ENGINE_ERROR_TYPE.bind(py).clone(),
+                PyRuntimeError::type_object(py),
+            ],
+            &[("entitlements", PyTuple::empty(py).into_any())],
+        )
+        .expect("creating LicenseError type should not fail")
+    })
+});
+
+static OTHER_WORKER_ERROR: Lazy<Py<PyType>> = Lazy::new(|| {
     Python::with_gil(|py| {
         PyErr::new_type(
             py,
@@ -2751,6 +3364,33 @@ impl Scope {
         Table::new(self_, handle)
     }
 
+    #[pyo3(signature = (batch, column_names, properties, *, id_from = None))]
+    pub fn static_table_from_arrow(
+        self_: &Bound<Self>,
+        batch: &Bound<PyAny>,
+        column_names: Vec<String>,
+        properties: ConnectorProperties,
+        id_from: Option<Vec<String>>,
+    ) -> PyResult<Py<Table>> {
+        let column_properties = properties.column_properties();
+        if column_properties.len() != column_names.len() {
+            return Err(PyValueError::new_err(
+                "provided connector properties do not match the column names",
+            ));
+        }
+        let dtypes: Vec<Type> = column_properties
+            .iter()
+            .map(|properties| properties.dtype.clone())
+            .collect();
+        let batch = record_batch_from_py(batch)?;
+        let data = data_rows_from_record_batch(&batch, &column_names, &dtypes, id_from.as_deref())?;
+        let handle = self_.borrow().graph.static_table(
+            data,
+            Arc::new(EngineTableProperties::flat(column_properties)),
+        )?;
+        Table::new(self_, handle)
+    }
+
     pub fn connector_table(
         self_: &Bound<Self>,
         data_source: &Bound<DataStorage>,
@@ -2765,16 +3405,22 @@ impl Scope {
             .register_unique_name(unique_name.as_ref(), py)?;
         let connector_index = *self_.borrow().total_connectors.get(py).borrow();
         *self_.borrow().total_connectors.get(py).borrow_mut() += 1;
-        let (reader_impl, parallel_readers) = data_source.borrow().construct_reader(
-            py,
-            &data_format.borrow(),
-            connector_index,
-            self_.borrow().worker_index(),
-            self_.borrow().license.as_ref(),
-            self_.borrow().is_persisted,
-        )?;
+        let (reader_impl, parallel_readers) =
+            with_connector_runtime(unique_name.as_deref(), || {
+                data_source.borrow().construct_reader(
+                    py,
+                    &data_format.borrow(),
+                    connector_index,
+                    self_.borrow().worker_index(),
+                    self_.borrow().license.as_ref(),
+                    self_.borrow().is_persisted,
+                )
+            })?;
 
-        let parser_impl = data_format.borrow().construct_parser(py)?;
+        let mut parser_impl = data_format.borrow().construct_parser(py)?;
+        if schema_drift::is_reporting_enabled() && !reader_impl.is_internal() {
+            parser_impl.report_schema_drift(&reader_impl.name(unique_name.as_ref()));
+        }
 
         let column_properties = properties.borrow().column_properties();
         let table_handle = self_.borrow().graph.connector_table(
@@ -2987,6 +3633,30 @@ impl Scope {
         Table::new(self_, new_table_handle)
     }
 
+    /// Adds the SQL `query` reading the tables with the given names and columns to the graph.
+    /// Returns the resulting table, whose rows are the tuples of the output columns, with the
+    /// names of these columns.
+    pub fn sql_query(
+        self_: &Bound<Self>,
+        query: &str,
+        tables: HashMap<String, (Py<Table>, Vec<(String, Type)>)>,
+    ) -> PyResult<(Py<Table>, Vec<String>)> {
+        let schemas = tables
+            .iter()
+            .map(|(name, (_, columns))| (name.clone(), TableSchema::new(columns.clone())))
+            .collect();
+        let handles = tables
+            .iter()
+            .map(|(name, (table, _))| (name.clone(), table.get().handle))
+            .collect();
+        let plan = plan_query(query, &schemas)?;
+        let new_table_handle = plan.build(&*self_.borrow().graph, &handles)?;
+        Ok((
+            Table::new(self_, new_table_handle)?,
+            plan.column_names().to_vec(),
+        ))
+    }
+
     pub fn remove_retractions_from_table(
         self_: &Bound<Self>,
         table: PyRef<Table>,
@@ -2999,6 +3669,39 @@ impl Scope {
         Table::new(self_, new_table_handle)
     }
 
+    pub fn sample_table(
+        self_: &Bound<Self>,
+        table: PyRef<Table>,
+        fraction: f64,
+        seed: u64,
+        table_properties: TableProperties,
+    ) -> PyResult<Py<Table>> {
+        let new_table_handle =
+            self_
+                .borrow()
+                .graph
+                .sample_table(table.handle, fraction, seed, table_properties.0)?;
+        Table::new(self_, new_table_handle)
+    }
+
+    pub fn reservoir_sample_table(
+        self_: &Bound<Self>,
+        table: PyRef<Table>,
+        instance_column_path: ColumnPath,
+        size: usize,
+        seed: u64,
+        table_properties: TableProperties,
+    ) -> PyResult<Py<Table>> {
+        let new_table_handle = self_.borrow().graph.reservoir_sample_table(
+            table.handle,
+            instance_column_path,
+            size,
+            seed,
+            table_properties.0,
+        )?;
+        Table::new(self_, new_table_handle)
+    }
+
     pub fn forget(
         self_: &Bound<Self>,
         table: PyRef<Table>,
@@ -3081,6 +3784,29 @@ impl Scope {
         Table::new(self_, new_table_handle)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn keyed_mat_mul(
+        self_: &Bound<Self>,
+        table: PyRef<Table>,
+        group_path: ColumnPath,
+        operand_path: ColumnPath,
+        matrix_table: PyRef<Table>,
+        matrix_group_path: ColumnPath,
+        matrix_path: ColumnPath,
+        table_properties: TableProperties,
+    ) -> PyResult<Py<Table>> {
+        let new_table_handle = self_.borrow().graph.keyed_mat_mul(
+            table.handle,
+            group_path,
+            operand_path,
+            matrix_table.handle,
+            matrix_group_path,
+            matrix_path,
+            table_properties.0,
+        )?;
+        Table::new(self_, new_table_handle)
+    }
+
     pub fn use_external_index_as_of_now(
         self_: &Bound<Self>,
         index: &PyExternalIndexData,
@@ -3314,6 +4040,26 @@ impl Scope {
         Table::new(self_, table_handle)
     }
 
+    #[pyo3(signature = (table, grouping_columns_paths, reduced_column_paths, unique_name, table_properties))]
+    pub fn drop_duplicates(
+        self_: &Bound<Self>,
+        table: PyRef<Table>,
+        #[pyo3(from_py_with = from_py_iterable)] grouping_columns_paths: Vec<ColumnPath>,
+        #[pyo3(from_py_with = from_py_iterable)] reduced_column_paths: Vec<ColumnPath>,
+        unique_name: Option<UniqueName>,
+        table_properties: TableProperties,
+    ) -> PyResult<Py<Table>> {
+        let table_handle = self_.borrow().graph.deduplicate(
+            table.handle,
+            grouping_columns_paths,
+            reduced_column_paths,
+            keep_first_combine_fn(),
+            unique_name.as_ref(),
+            table_properties.0,
+        )?;
+        Table::new(self_, table_handle)
+    }
+
     pub fn ix_table(
         self_: &Bound<Self>,
         to_ix_table: PyRef<Table>,
@@ -3450,12 +4196,16 @@ impl Scope {
         self_
             .borrow()
             .register_unique_name(unique_name.as_ref(), py)?;
-        let sink_impl = data_sink.borrow().construct_writer(
-            py,
-            &data_format.borrow(),
-            self_.borrow().license.as_ref(),
-        )?;
+        let sink_impl = with_connector_runtime(unique_name.as_deref(), || {
+            data_sink.borrow().construct_writer(
+                py,
+                &data_format.borrow(),
+                self_.borrow().license.as_ref(),
+                self_.borrow().worker_index(),
+            )
+        })?;
         let format_impl = data_format.borrow().construct_formatter(py)?;
+        let retry_policy = data_sink.borrow().retry_policy;
 
         self_.borrow().graph.output_table(
             sink_impl,
@@ -3464,6 +4214,7 @@ impl Scope {
             column_paths,
             unique_name,
             sort_by_indices,
+            retry_policy,
         )?;
 
         Ok(())
@@ -3503,6 +4254,46 @@ impl Scope {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, column_paths, column_names, dtypes, on_batch, on_end, *, skip_persisted_batch = false, skip_errors = false, unique_name = None))]
+    pub fn subscribe_table_arrow(
+        self_: &Bound<Self>,
+        table: PyRef<Table>,
+        #[pyo3(from_py_with = from_py_iterable)] column_paths: Vec<ColumnPath>,
+        column_names: Vec<String>,
+        dtypes: Vec<Type>,
+        on_batch: Py<PyAny>,
+        on_end: Py<PyAny>,
+        skip_persisted_batch: bool,
+        skip_errors: bool,
+        unique_name: Option<UniqueName>,
+    ) -> PyResult<()> {
+        let py = self_.py();
+        if column_names.len() != column_paths.len() || dtypes.len() != column_paths.len() {
+            return Err(PyValueError::new_err(
+                "column_names and dtypes have to match column_paths",
+            ));
+        }
+        check_arrow_types(&dtypes)?;
+        self_
+            .borrow()
+            .register_unique_name(unique_name.as_ref(), py)?;
+        let callbacks = build_arrow_subscribe_callback(column_names, dtypes, on_batch, on_end);
+        self_.borrow().graph.subscribe_table(
+            table.handle,
+            column_paths,
+            callbacks,
+            SubscribeConfig {
+                skip_persisted_batch,
+                skip_errors,
+                skip_pending: true,
+            },
+            unique_name,
+            None,
+        )?;
+        Ok(())
+    }
+
     pub fn set_operator_properties(
         self_: &Bound<Self>,
         operator_id: usize,
@@ -3718,6 +4509,46 @@ fn build_subscribe_callback(
         .build()
 }
 
+/// Collects the rows of each time and passes them to `on_batch` as a single Arrow batch.
+fn build_arrow_subscribe_callback(
+    column_names: Vec<String>,
+    dtypes: Vec<Type>,
+    on_batch: Py<PyAny>,
+    on_end: Py<PyAny>,
+) -> SubscribeCallbacks {
+    let rows: Arc<Mutex<Vec<DataRow>>> = Arc::new(Mutex::new(Vec::new()));
+    let rows_for_data = rows.clone();
+    SubscribeCallbacksBuilder::new()
+        .wrapper(BatchWrapper::WithGil)
+        .on_data(Box::new(move |key, values, time, diff| {
+            rows_for_data.lock().unwrap().push(DataRow::from_engine(
+                key,
+                Vec::from(values),
+                time,
+                diff,
+            ));
+            Ok(())
+        }))
+        .on_time_end(Box::new(move |time| {
+            let rows = take(&mut *rows.lock().unwrap());
+            if rows.is_empty() {
+                return Ok(());
+            }
+            let batch = record_batch_from_data_rows(&rows, &column_names, &dtypes, true)?;
+            Python::with_gil(|py| {
+                on_batch.call1(py, (PyArrowBatch::new(batch), time))?;
+                Ok(())
+            })
+        }))
+        .on_end(Box::new(move || {
+            Python::with_gil(|py| {
+                on_end.call0(py)?;
+                Ok(())
+            })
+        }))
+        .build()
+}
+
 type CapturedTableData = Arc<Mutex<Vec<DataRow>>>;
 
 fn capture_table_data(
@@ -3765,6 +4596,7 @@ pub fn make_captured_table(table_data: Vec<CapturedTableData>) -> PyResult<Vec<D
 
 #[pyfunction]
 #[allow(clippy::too_many_arguments)]
+#[allow(clippy::fn_params_excessive_bools)]
 #[pyo3(signature = (
     logic,
     event_loop,
@@ -3781,6 +4613,21 @@ pub fn make_captured_table(table_data: Vec<CapturedTableData>) -> PyResult<Vec<D
     run_id = None,
     terminate_on_error = true,
     max_expression_batch_size = 1024,
+    prometheus_port = None,
+    resource_attributes = BTreeMap::new(),
+    metric_attributes = BTreeMap::new(),
+    trace_sample_ratio = 1.0,
+    metric_allowlist = Vec::new(),
+    metric_denylist = Vec::new(),
+    telemetry_export_dir = None,
+    usage_telemetry_enabled = true,
+    metrics_enabled = true,
+    traces_enabled = true,
+    async_worker_threads_per_runtime = None,
+    async_max_blocking_threads = None,
+    async_thread_name_prefix = None,
+    async_runtime_groups = HashMap::new(),
+    cancellation_token = None,
 ))]
 pub fn run_with_new_graph(
     py: Python,
@@ -3798,13 +4645,39 @@ pub fn run_with_new_graph(
     run_id: Option<String>,
     terminate_on_error: bool,
     max_expression_batch_size: usize,
+    prometheus_port: Option<u16>,
+    resource_attributes: BTreeMap<String, String>,
+    metric_attributes: BTreeMap<String, String>,
+    trace_sample_ratio: f64,
+    metric_allowlist: Vec<String>,
+    metric_denylist: Vec<String>,
+    telemetry_export_dir: Option<PathBuf>,
+    usage_telemetry_enabled: bool,
+    metrics_enabled: bool,
+    traces_enabled: bool,
+    async_worker_threads_per_runtime: Option<usize>,
+    async_max_blocking_threads: Option<usize>,
+    async_thread_name_prefix: Option<String>,
+    async_runtime_groups: HashMap<String, Vec<String>>,
+    cancellation_token: Option<PyRef<PyCancellationToken>>,
 ) -> PyResult<Vec<Vec<DataRow>>> {
+    let cancellation_token = cancellation_token.map(|token| token.inner.clone());
     LOGGING_RESET_HANDLE.reset();
     defer! {
         log::logger().flush();
     }
     let config = Config::from_env()
         .map_err(|msg| PyErr::from_type(ENGINE_ERROR_TYPE.bind(py).clone(), msg.to_string()))?;
+    let async_runtime_config = AsyncRuntimeConfig {
+        worker_threads_per_runtime: async_worker_threads_per_runtime,
+        max_blocking_threads: async_max_blocking_threads,
+        thread_name_prefix: async_thread_name_prefix,
+        runtime_groups: async_runtime_groups,
+    };
+    async_runtime_config
+        .validate()
+        .map_err(PyValueError::new_err)?;
+    set_runtime_config(async_runtime_config);
     let license = License::new(license_key)?;
     let persistence_config = {
         if let Some(persistence_config) = persistence_config {
@@ -3822,6 +4695,20 @@ pub fn run_with_new_graph(
         monitoring_server,
         trace_parent,
         metrics_reader_interval_secs,
+        prometheus_port,
+        resource_attributes,
+        metric_attributes,
+        TelemetrySampling {
+            trace_ratio: trace_sample_ratio,
+            metric_allowlist,
+            metric_denylist,
+        },
+        telemetry_export_dir,
+        TelemetrySignals {
+            usage_telemetry: usage_telemetry_enabled,
+            metrics: metrics_enabled,
+            traces: traces_enabled,
+        },
     )?;
     let results: Vec<Vec<_>> = run_with_wakeup_receiver(py, |wakeup_receiver| {
         let scope_license = license.clone();
@@ -3864,6 +4751,7 @@ pub fn run_with_new_graph(
                 telemetry_config,
                 terminate_on_error,
                 max_expression_batch_size,
+                cancellation_token,
             )
         })
     })??;
@@ -4221,22 +5109,26 @@ impl ElasticSearchParams {
 }
 
 impl ElasticSearchParams {
-    fn client(&self, py: pyo3::Python) -> PyResult<Elasticsearch> {
+    fn client(
+        &self,
+        py: pyo3::Python,
+        request_timeout: Option<time::Duration>,
+    ) -> PyResult<Elasticsearch> {
         let creds = self.auth.borrow(py).as_client_auth()?;
 
         let url = Url::parse(&self.host)
             .map_err(|e| PyValueError::new_err(format!("Failed to parse node URL: {e:?}")))?;
         let conn_pool = SingleNodeConnectionPool::new(url);
 
-        let transport = TransportBuilder::new(conn_pool)
-            .auth(creds)
-            .disable_proxy()
-            .build()
-            .map_err(|e| {
-                PyValueError::new_err(format!(
-                    "Failed to build ES transfer with the given params: {e:?}"
-                ))
-            })?;
+        let mut transport_builder = TransportBuilder::new(conn_pool).auth(creds).disable_proxy();
+        if let Some(request_timeout) = request_timeout {
+            transport_builder = transport_builder.timeout(request_timeout);
+        }
+        let transport = transport_builder.build().map_err(|e| {
+            PyValueError::new_err(format!(
+                "Failed to build ES transfer with the given params: {e:?}"
+            ))
+        })?;
 
         Ok(Elasticsearch::new(transport))
     }
@@ -4323,74 +5215,584 @@ impl MqttSettings {
 }
 
 #[derive(Clone, Debug)]
-#[pyclass(module = "pathway.engine", frozen)]
-pub struct DataStorage {
-    storage_type: String,
-    path: Option<String>,
-    rdkafka_settings: Option<HashMap<String, String>>,
-    topic: Option<String>,
-    connection_string: Option<String>,
-    csv_parser_settings: Option<CsvParserSettings>,
-    #[pyo3(get)]
-    mode: ConnectorMode,
-    read_method: ReadMethod,
-    snapshot_maintenance_on_output: bool,
-    aws_s3_settings: Option<Arc<Py<AwsS3Settings>>>,
-    elasticsearch_params: Option<Arc<Py<ElasticSearchParams>>>,
-    parallel_readers: Option<usize>,
-    python_subject: Option<Arc<Py<PythonSubject>>>,
-    unique_name: Option<UniqueName>,
-    max_batch_size: Option<usize>,
-    object_pattern: String,
-    mock_events: Option<HashMap<(UniqueName, usize), Vec<SnapshotEvent>>>,
-    table_name: Option<String>,
-    header_fields: Vec<(String, usize)>,
-    key_field_index: Option<usize>,
-    min_commit_frequency: Option<u64>,
-    downloader_threads_count: Option<usize>,
-    database: Option<String>,
-    start_from_timestamp_ms: Option<i64>,
-    namespace: Option<Vec<String>>,
-    table_writer_init_mode: TableWriterInitMode,
-    topic_name_index: Option<usize>,
-    partition_columns: Option<Vec<String>>,
-    backfilling_thresholds: Option<Vec<BackfillingThreshold>>,
-    azure_blob_storage_settings: Option<AzureBlobStorageSettings>,
-    delta_optimizer_rule: Option<PyDeltaOptimizerRule>,
-    mqtt_settings: Option<MqttSettings>,
-    only_provide_metadata: bool,
-    sort_key_index: Option<usize>,
-}
-
-#[pyclass(module = "pathway.engine", frozen, name = "PersistenceMode")]
-pub struct PyPersistenceMode(PersistenceMode);
+#[pyclass(module = "pathway.engine", frozen, name = "SubprocessSettings")]
+pub struct SubprocessSettings(SubprocessCommand);
 
 #[pymethods]
-impl PyPersistenceMode {
-    #[classattr]
-    pub const REALTIME_REPLAY: PersistenceMode = PersistenceMode::RealtimeReplay;
-    #[classattr]
-    pub const SPEEDRUN_REPLAY: PersistenceMode = PersistenceMode::SpeedrunReplay;
-    #[classattr]
-    pub const BATCH: PersistenceMode = PersistenceMode::Batch;
-    #[classattr]
-    pub const PERSISTING: PersistenceMode = PersistenceMode::Persisting;
-    #[classattr]
-    pub const SELECTIVE_PERSISTING: PersistenceMode = PersistenceMode::SelectivePersisting;
-    #[classattr]
-    pub const UDF_CACHING: PersistenceMode = PersistenceMode::UdfCaching;
-    #[classattr]
-    pub const OPERATOR_PERSISTING: PersistenceMode = PersistenceMode::OperatorPersisting;
+impl SubprocessSettings {
+    #[new]
+    #[pyo3(signature = (
+        command,
+        *,
+        env=None,
+        working_directory=None,
+        restart_policy="never",
+        max_restarts=None,
+        restart_delay=time::Duration::from_secs(1),
+    ))]
+    fn new(
+        command: Vec<String>,
+        env: Option<HashMap<String, String>>,
+        working_directory: Option<PathBuf>,
+        restart_policy: &str,
+        max_restarts: Option<usize>,
+        restart_delay: time::Duration,
+    ) -> PyResult<Self> {
+        let Some((program, args)) = command.split_first() else {
+            return Err(PyValueError::new_err("The command must not be empty"));
+        };
+        let restart_policy = match restart_policy {
+            "never" => RestartPolicy::Never,
+            "on_failure" => RestartPolicy::OnFailure,
+            "always" => RestartPolicy::Always,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Restart policy can only be 'never', 'on_failure' or 'always'. Specified value: {restart_policy}"
+                )))
+            }
+        };
+        Ok(Self(SubprocessCommand {
+            program: program.clone(),
+            args: args.to_vec(),
+            env: env.unwrap_or_default(),
+            working_directory,
+            restart_policy,
+            max_restarts,
+            restart_delay,
+        }))
+    }
 }
 
-impl<'py> FromPyObject<'py> for PersistenceMode {
-    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
-        Ok(ob.extract::<PyRef<PyPersistenceMode>>()?.0)
-    }
+/// The Python callables of a custom connector. A source needs `read`, and a sink needs `write`
+/// and optionally `flush` and `finish`.
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "CustomConnectorSettings")]
+pub struct CustomConnectorSettings {
+    read: Option<Arc<Py<PyAny>>>,
+    write: Option<Arc<Py<PyAny>>>,
+    flush: Option<Arc<Py<PyAny>>>,
+    finish: Option<Arc<Py<PyAny>>>,
+    max_queue_size: usize,
 }
 
-impl<'py> IntoPyObject<'py> for PersistenceMode {
-    type Target = PyAny;
+#[pymethods]
+impl CustomConnectorSettings {
+    #[new]
+    #[pyo3(signature = (
+        *,
+        read=None,
+        write=None,
+        flush=None,
+        finish=None,
+        max_queue_size=1024,
+    ))]
+    fn new(
+        read: Option<Py<PyAny>>,
+        write: Option<Py<PyAny>>,
+        flush: Option<Py<PyAny>>,
+        finish: Option<Py<PyAny>>,
+        max_queue_size: usize,
+    ) -> PyResult<Self> {
+        if max_queue_size == 0 {
+            return Err(PyValueError::new_err("max_queue_size must be positive"));
+        }
+        Ok(Self {
+            read: read.map(Into::into),
+            write: write.map(Into::into),
+            flush: flush.map(Into::into),
+            finish: finish.map(Into::into),
+            max_queue_size,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "SqlPollingSettings")]
+pub struct SqlPollingSettings(SqlPollingConfig);
+
+#[pymethods]
+impl SqlPollingSettings {
+    #[new]
+    #[pyo3(signature = (
+        query,
+        cursor_column,
+        start_from,
+        *,
+        poll_interval=time::Duration::from_secs(1),
+    ))]
+    fn new(
+        query: String,
+        cursor_column: String,
+        start_from: Value,
+        poll_interval: time::Duration,
+    ) -> Self {
+        Self(SqlPollingConfig {
+            query,
+            cursor_column,
+            start_from,
+            poll_interval,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "ColumnMasking")]
+pub struct PyColumnMasking(ColumnMasking);
+
+#[pymethods]
+impl PyColumnMasking {
+    #[new]
+    #[pyo3(signature = (column_name, method, *, key = None))]
+    fn new(column_name: String, method: &str, key: Option<Vec<u8>>) -> PyResult<Self> {
+        let method = match (method, key) {
+            ("hash", None) => MaskingMethod::Hash,
+            ("tokenize", Some(key)) => MaskingMethod::Tokenize { key },
+            ("encrypt", Some(key)) => MaskingMethod::Encrypt { key },
+            ("hash", Some(_)) => {
+                return Err(PyValueError::new_err("Hashing doesn't use a key"));
+            }
+            ("tokenize" | "encrypt", None) => {
+                return Err(PyValueError::new_err(format!(
+                    "Masking method {method:?} requires a key"
+                )));
+            }
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown masking method: {method:?}"
+                )));
+            }
+        };
+        Ok(Self(ColumnMasking {
+            column_name,
+            method,
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "SinkPredicate")]
+pub struct PySinkPredicate(SinkPredicate);
+
+#[pymethods]
+impl PySinkPredicate {
+    #[staticmethod]
+    fn compare(column_index: usize, op: &str, value: Value) -> PyResult<Self> {
+        let op = match op {
+            "==" => ComparisonOp::Eq,
+            "!=" => ComparisonOp::Ne,
+            "<" => ComparisonOp::Lt,
+            "<=" => ComparisonOp::Le,
+            ">" => ComparisonOp::Gt,
+            ">=" => ComparisonOp::Ge,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown comparison operator: {op:?}"
+                )))
+            }
+        };
+        Ok(Self(SinkPredicate::Compare {
+            column_index,
+            op,
+            value,
+        }))
+    }
+
+    #[staticmethod]
+    fn is_none(column_index: usize) -> Self {
+        Self(SinkPredicate::IsNone(column_index))
+    }
+
+    #[staticmethod]
+    fn all_of(predicates: Vec<Self>) -> Self {
+        Self(SinkPredicate::And(
+            predicates
+                .into_iter()
+                .map(|predicate| predicate.0)
+                .collect(),
+        ))
+    }
+
+    #[staticmethod]
+    fn any_of(predicates: Vec<Self>) -> Self {
+        Self(SinkPredicate::Or(
+            predicates
+                .into_iter()
+                .map(|predicate| predicate.0)
+                .collect(),
+        ))
+    }
+
+    #[staticmethod]
+    fn negation(predicate: Self) -> Self {
+        Self(SinkPredicate::Not(Box::new(predicate.0)))
+    }
+}
+
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "OracleSettings")]
+pub struct OracleSettings {
+    user: String,
+    password: String,
+    connect_string: String,
+}
+
+#[pymethods]
+impl OracleSettings {
+    #[new]
+    fn new(user: String, password: String, connect_string: String) -> Self {
+        Self {
+            user,
+            password,
+            connect_string,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "ImapSettings")]
+pub struct ImapSettings(ImapConfig);
+
+#[pymethods]
+impl ImapSettings {
+    #[new]
+    #[pyo3(signature = (
+        host,
+        username,
+        password,
+        *,
+        port=993,
+        mailbox="INBOX".to_string(),
+        refresh_interval=time::Duration::from_secs(30),
+    ))]
+    fn new(
+        host: String,
+        username: String,
+        password: String,
+        port: u16,
+        mailbox: String,
+        refresh_interval: time::Duration,
+    ) -> Self {
+        Self(ImapConfig {
+            host,
+            port,
+            username,
+            password,
+            mailbox,
+            refresh_interval,
+        })
+    }
+}
+
+/// The columns of a Prometheus remote-write sink: the metrics, given as the pairs of their
+/// names and the indices of the columns with their values, the labels, given the same way, and
+/// the column with the timestamps of the samples.
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "PrometheusSettings")]
+pub struct PrometheusSettings {
+    metric_fields: Vec<(String, usize)>,
+    label_fields: Vec<(String, usize)>,
+    timestamp_field_index: Option<usize>,
+    headers: Vec<(String, String)>,
+}
+
+#[pymethods]
+impl PrometheusSettings {
+    #[new]
+    #[pyo3(signature = (
+        metric_fields,
+        label_fields,
+        *,
+        timestamp_field_index=None,
+        headers=None,
+    ))]
+    fn new(
+        metric_fields: Vec<(String, usize)>,
+        label_fields: Vec<(String, usize)>,
+        timestamp_field_index: Option<usize>,
+        headers: Option<HashMap<String, String>>,
+    ) -> Self {
+        Self {
+            metric_fields,
+            label_fields,
+            timestamp_field_index,
+            headers: headers.unwrap_or_default().into_iter().collect(),
+        }
+    }
+}
+
+/// The rotation of the files of the filesystem sink, which then treats its path as the
+/// directory to write the files into.
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "FileRotationSettings")]
+pub struct FileRotationSettings(FileRotationConfig);
+
+#[pymethods]
+impl FileRotationSettings {
+    #[new]
+    #[pyo3(signature = (
+        table_name,
+        extension,
+        *,
+        name_template=DEFAULT_NAME_TEMPLATE.to_string(),
+        max_file_size=None,
+        rotation_interval=None,
+        write_success_markers=false,
+        repeat_header=false,
+    ))]
+    fn new(
+        table_name: String,
+        extension: String,
+        name_template: String,
+        max_file_size: Option<u64>,
+        rotation_interval: Option<time::Duration>,
+        write_success_markers: bool,
+        repeat_header: bool,
+    ) -> PyResult<Self> {
+        let name_template = NameTemplate::parse(&name_template)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self(FileRotationConfig {
+            name_template,
+            table_name,
+            extension,
+            max_file_size,
+            rotation_interval,
+            write_success_markers,
+            repeat_header,
+        }))
+    }
+}
+
+/// Pausing the Kafka partitions that lag less than the others, so that the reader catches up
+/// uniformly after a downtime.
+#[derive(Clone, Copy, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "KafkaLagThrottlingSettings")]
+pub struct KafkaLagThrottlingSettings(LagThrottlingConfig);
+
+#[pymethods]
+impl KafkaLagThrottlingSettings {
+    #[new]
+    #[pyo3(signature = (*, check_interval=DEFAULT_CHECK_INTERVAL, min_lag=DEFAULT_MIN_LAG))]
+    fn new(check_interval: time::Duration, min_lag: u64) -> PyResult<Self> {
+        if check_interval.is_zero() {
+            return Err(PyValueError::new_err(
+                "the interval between the lag checks must be positive",
+            ));
+        }
+        Ok(Self(LagThrottlingConfig {
+            check_interval,
+            min_lag,
+        }))
+    }
+}
+
+/// The catalog of an Iceberg connector, along with the properties passed to it, such as the
+/// authentication settings.
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "IcebergCatalogSettings")]
+pub struct IcebergCatalogSettings {
+    kind: IcebergCatalogKind,
+    properties: HashMap<String, String>,
+}
+
+#[pymethods]
+impl IcebergCatalogSettings {
+    #[new]
+    #[pyo3(signature = (catalog_type, *, uri=None, catalog_id=None, properties=None))]
+    fn new(
+        catalog_type: &str,
+        uri: Option<String>,
+        catalog_id: Option<String>,
+        properties: Option<HashMap<String, String>>,
+    ) -> PyResult<Self> {
+        let kind = match catalog_type {
+            "rest" => {
+                let uri = uri.ok_or_else(|| {
+                    PyValueError::new_err("For the REST catalog, uri must be specified")
+                })?;
+                IcebergCatalogKind::Rest { uri }
+            }
+            "glue" => IcebergCatalogKind::Glue {
+                catalog_id,
+                endpoint: uri,
+            },
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Iceberg catalog type can only be 'rest' or 'glue'. Specified value: {catalog_type}"
+                )))
+            }
+        };
+        Ok(Self {
+            kind,
+            properties: properties.unwrap_or_default(),
+        })
+    }
+}
+
+/// The sources of a connector that reads a bounded source first and then switches to a stream.
+/// Both sources are split at `seam_value` of the `seam_column`.
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "BackfillThenStreamSettings")]
+pub struct BackfillThenStreamSettings {
+    backfill_storage: Arc<Py<DataStorage>>,
+    backfill_format: Arc<Py<DataFormat>>,
+    stream_storage: Arc<Py<DataStorage>>,
+    stream_format: Arc<Py<DataFormat>>,
+    seam: SeamBoundary,
+}
+
+#[pymethods]
+impl BackfillThenStreamSettings {
+    #[new]
+    #[pyo3(signature = (
+        backfill_storage,
+        backfill_format,
+        stream_storage,
+        stream_format,
+        *,
+        seam_column,
+        seam_value,
+    ))]
+    fn new(
+        backfill_storage: Py<DataStorage>,
+        backfill_format: Py<DataFormat>,
+        stream_storage: Py<DataStorage>,
+        stream_format: Py<DataFormat>,
+        seam_column: String,
+        seam_value: Value,
+    ) -> Self {
+        Self {
+            backfill_storage: backfill_storage.into(),
+            backfill_format: backfill_format.into(),
+            stream_storage: stream_storage.into(),
+            stream_format: stream_format.into(),
+            seam: SeamBoundary::new(seam_column, seam_value),
+        }
+    }
+}
+
+/// Retry settings of a connector. The settings that are not passed take the defaults of the
+/// connector.
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, name = "ConnectorRetryPolicy")]
+pub struct PyConnectorRetryPolicy(RetryPolicy);
+
+#[pymethods]
+impl PyConnectorRetryPolicy {
+    #[new]
+    #[pyo3(signature = (
+        *,
+        max_retries=None,
+        initial_delay=None,
+        backoff_factor=None,
+        jitter=None,
+        max_elapsed_time=None,
+    ))]
+    fn new(
+        max_retries: Option<usize>,
+        initial_delay: Option<time::Duration>,
+        backoff_factor: Option<f64>,
+        jitter: Option<time::Duration>,
+        max_elapsed_time: Option<time::Duration>,
+    ) -> PyResult<Self> {
+        if backoff_factor.is_some_and(|backoff_factor| backoff_factor < 1.0) {
+            return Err(PyValueError::new_err(
+                "backoff_factor must be greater than or equal to 1",
+            ));
+        }
+        Ok(Self(RetryPolicy {
+            max_retries,
+            initial_delay,
+            backoff_factor,
+            jitter,
+            max_elapsed_time,
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen)]
+pub struct DataStorage {
+    storage_type: String,
+    path: Option<String>,
+    rdkafka_settings: Option<HashMap<String, String>>,
+    topic: Option<String>,
+    connection_string: Option<String>,
+    csv_parser_settings: Option<CsvParserSettings>,
+    #[pyo3(get)]
+    mode: ConnectorMode,
+    read_method: ReadMethod,
+    snapshot_maintenance_on_output: bool,
+    aws_s3_settings: Option<Arc<Py<AwsS3Settings>>>,
+    elasticsearch_params: Option<Arc<Py<ElasticSearchParams>>>,
+    parallel_readers: Option<usize>,
+    python_subject: Option<Arc<Py<PythonSubject>>>,
+    unique_name: Option<UniqueName>,
+    max_batch_size: Option<usize>,
+    object_pattern: String,
+    mock_events: Option<HashMap<(UniqueName, usize), Vec<SnapshotEvent>>>,
+    table_name: Option<String>,
+    header_fields: Vec<(String, usize)>,
+    key_field_index: Option<usize>,
+    min_commit_frequency: Option<u64>,
+    downloader_threads_count: Option<usize>,
+    database: Option<String>,
+    start_from_timestamp_ms: Option<i64>,
+    namespace: Option<Vec<String>>,
+    table_writer_init_mode: TableWriterInitMode,
+    topic_name_index: Option<usize>,
+    partition_columns: Option<Vec<String>>,
+    backfilling_thresholds: Option<Vec<BackfillingThreshold>>,
+    azure_blob_storage_settings: Option<AzureBlobStorageSettings>,
+    delta_optimizer_rule: Option<PyDeltaOptimizerRule>,
+    mqtt_settings: Option<MqttSettings>,
+    only_provide_metadata: bool,
+    sort_key_index: Option<usize>,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<time::Duration>,
+    subprocess_settings: Option<SubprocessSettings>,
+    backfill_then_stream_settings: Option<BackfillThenStreamSettings>,
+    iceberg_catalog_settings: Option<IcebergCatalogSettings>,
+    read_change_data_feed: bool,
+    partition_field_index: Option<usize>,
+    imap_settings: Option<ImapSettings>,
+    prometheus_settings: Option<PrometheusSettings>,
+    file_rotation_settings: Option<FileRotationSettings>,
+    kafka_lag_throttling_settings: Option<KafkaLagThrottlingSettings>,
+    sql_polling_settings: Option<SqlPollingSettings>,
+    oracle_settings: Option<OracleSettings>,
+    parquet_row_group_size: Option<usize>,
+    custom_connector_settings: Option<CustomConnectorSettings>,
+}
+
+#[pyclass(module = "pathway.engine", frozen, name = "PersistenceMode")]
+pub struct PyPersistenceMode(PersistenceMode);
+
+#[pymethods]
+impl PyPersistenceMode {
+    #[classattr]
+    pub const REALTIME_REPLAY: PersistenceMode = PersistenceMode::RealtimeReplay;
+    #[classattr]
+    pub const SPEEDRUN_REPLAY: PersistenceMode = PersistenceMode::SpeedrunReplay;
+    #[classattr]
+    pub const BATCH: PersistenceMode = PersistenceMode::Batch;
+    #[classattr]
+    pub const PERSISTING: PersistenceMode = PersistenceMode::Persisting;
+    #[classattr]
+    pub const SELECTIVE_PERSISTING: PersistenceMode = PersistenceMode::SelectivePersisting;
+    #[classattr]
+    pub const UDF_CACHING: PersistenceMode = PersistenceMode::UdfCaching;
+    #[classattr]
+    pub const OPERATOR_PERSISTING: PersistenceMode = PersistenceMode::OperatorPersisting;
+}
+
+impl<'py> FromPyObject<'py> for PersistenceMode {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<PyRef<PyPersistenceMode>>()?.0)
+    }
+}
+
+impl<'py> IntoPyObject<'py> for PersistenceMode {
+    type Target = PyAny;
     type Output = Bound<'py, Self::Target>;
     type Error = PyErr;
     fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
@@ -4435,6 +5837,8 @@ pub struct PersistenceConfig {
     snapshot_access: SnapshotAccess,
     persistence_mode: PersistenceMode,
     continue_after_replay: bool,
+    rewind_to_ms: HashMap<UniqueName, u64>,
+    handoff: bool,
 }
 
 #[pymethods]
@@ -4447,6 +5851,8 @@ impl PersistenceConfig {
         snapshot_access = SnapshotAccess::Full,
         persistence_mode = PersistenceMode::Batch,
         continue_after_replay = true,
+        rewind_to_ms = HashMap::new(),
+        handoff = false,
     ))]
     fn new(
         snapshot_interval_ms: u64,
@@ -4454,26 +5860,48 @@ impl PersistenceConfig {
         snapshot_access: SnapshotAccess,
         persistence_mode: PersistenceMode,
         continue_after_replay: bool,
-    ) -> Self {
-        Self {
+        rewind_to_ms: HashMap<UniqueName, u64>,
+        handoff: bool,
+    ) -> PyResult<Self> {
+        if !rewind_to_ms.is_empty()
+            && matches!(persistence_mode, PersistenceMode::OperatorPersisting)
+        {
+            // The state of the operators can't be rolled back for a part of the sources
+            return Err(PyValueError::new_err(
+                "Rewinding the sources is not supported with the operator persistence",
+            ));
+        }
+        Ok(Self {
             snapshot_interval: ::std::time::Duration::from_millis(snapshot_interval_ms),
             backend,
             snapshot_access,
             persistence_mode,
             continue_after_replay,
-        }
+            rewind_to_ms,
+            handoff,
+        })
     }
 }
 
 impl PersistenceConfig {
     fn prepare(self) -> PyResult<PersistenceManagerOuterConfig> {
-        Ok(PersistenceManagerOuterConfig::new(
+        let rewind_thresholds = self
+            .rewind_to_ms
+            .into_iter()
+            .map(|(unique_name, time_ms)| (unique_name.into_persistent_id(), Timestamp(time_ms)))
+            .collect();
+        let mut config = PersistenceManagerOuterConfig::new(
             self.snapshot_interval,
             self.backend.construct_persistent_storage_config()?,
             self.snapshot_access,
             self.persistence_mode,
             self.continue_after_replay,
-        ))
+        )
+        .with_rewind_thresholds(rewind_thresholds);
+        if self.handoff {
+            config = config.with_handoff(DEFAULT_LEASE_DURATION);
+        }
+        Ok(config)
     }
 }
 
@@ -4489,23 +5917,42 @@ pub struct TelemetryConfig {
     service_instance_id: Option<String>,
     run_id: String,
     license_key: Option<String>,
+    resource_attributes: BTreeMap<String, String>,
+    metric_attributes: BTreeMap<String, String>,
+    trace_sample_ratio: f64,
+    export_dir: Option<String>,
 }
 
 #[pymethods]
 impl TelemetryConfig {
     #[staticmethod]
+    #[allow(clippy::too_many_arguments)]
     #[pyo3(signature = (
         *,
         run_id = None,
         license_key = None,
         monitoring_server = None,
         metrics_reader_interval_secs = None,
+        resource_attributes = BTreeMap::new(),
+        metric_attributes = BTreeMap::new(),
+        trace_sample_ratio = 1.0,
+        export_dir = None,
+        usage_telemetry_enabled = true,
+        metrics_enabled = true,
+        traces_enabled = true,
     ))]
     fn create(
         run_id: Option<String>,
         license_key: Option<String>,
         monitoring_server: Option<String>,
         metrics_reader_interval_secs: Option<u64>,
+        resource_attributes: BTreeMap<String, String>,
+        metric_attributes: BTreeMap<String, String>,
+        trace_sample_ratio: f64,
+        export_dir: Option<PathBuf>,
+        usage_telemetry_enabled: bool,
+        metrics_enabled: bool,
+        traces_enabled: bool,
     ) -> PyResult<TelemetryConfig> {
         let license = License::new(license_key)?;
         let config = EngineTelemetryConfig::create(
@@ -4514,6 +5961,19 @@ impl TelemetryConfig {
             monitoring_server,
             None,
             metrics_reader_interval_secs,
+            None,
+            resource_attributes,
+            metric_attributes,
+            TelemetrySampling {
+                trace_ratio: trace_sample_ratio,
+                ..TelemetrySampling::default()
+            },
+            export_dir,
+            TelemetrySignals {
+                usage_telemetry: usage_telemetry_enabled,
+                metrics: metrics_enabled,
+                traces: traces_enabled,
+            },
         )?;
         Ok(config.into())
     }
@@ -4522,17 +5982,27 @@ impl TelemetryConfig {
 impl From<EngineTelemetryConfig> for TelemetryConfig {
     fn from(config: EngineTelemetryConfig) -> Self {
         match config {
-            EngineTelemetryConfig::Enabled(config) => Self {
-                logging_servers: config.logging_servers,
-                tracing_servers: config.tracing_servers,
-                metrics_servers: config.metrics_servers,
-                service_name: Some(config.service_name),
-                service_version: Some(config.service_version),
-                service_namespace: Some(config.service_namespace),
-                service_instance_id: Some(config.service_instance_id),
-                run_id: config.run_id,
-                license_key: Some(config.license_key),
-            },
+            EngineTelemetryConfig::Enabled(config) => {
+                // only spans are written by the Python part of the run
+                let export_dir = config
+                    .trace_export_dir()
+                    .map(|export_dir| export_dir.to_string_lossy().into_owned());
+                Self {
+                    logging_servers: config.logging_servers,
+                    tracing_servers: config.tracing_servers,
+                    metrics_servers: config.metrics_servers,
+                    service_name: Some(config.service_name),
+                    service_version: Some(config.service_version),
+                    service_namespace: Some(config.service_namespace),
+                    service_instance_id: Some(config.service_instance_id),
+                    run_id: config.run_id,
+                    license_key: Some(config.license_key),
+                    resource_attributes: config.resource_attributes,
+                    metric_attributes: config.metric_attributes,
+                    trace_sample_ratio: config.sampling.trace_ratio,
+                    export_dir,
+                }
+            }
             EngineTelemetryConfig::Disabled => Self::default(),
         }
     }
@@ -4627,6 +6097,14 @@ impl ValueField {
     fn as_inner_schema_field(&self) -> InnerSchemaField {
         InnerSchemaField::new(self.type_.clone(), self.default.clone())
     }
+
+    fn as_schema_column(&self) -> SchemaColumn {
+        SchemaColumn {
+            name: self.name.clone(),
+            type_: self.type_.clone(),
+            default: self.default.clone(),
+        }
+    }
 }
 
 #[pymethods]
@@ -4777,6 +6255,10 @@ impl PySchemaRegistrySettings {
     pub fn build_encoder(self) -> PyResult<RegistryJsonEncoder> {
         Ok(RegistryJsonEncoder::new(self.create_settings()?))
     }
+
+    pub fn build_avro_registry(self) -> PyResult<AvroSchemaRegistry> {
+        Ok(AvroSchemaRegistry::new(self.create_settings()?))
+    }
 }
 
 #[pyclass(module = "pathway.engine", frozen, get_all)]
@@ -4797,6 +6279,17 @@ pub struct DataFormat {
     subject: Option<String>,
     designated_timestamp_policy: Option<String>,
     external_diff_column_index: Option<usize>,
+    length_prefix: Option<String>,
+    schema_name: Option<String>,
+    avro_schema: Option<String>,
+    protobuf_descriptor_set: Option<Vec<u8>>,
+    protobuf_message: Option<String>,
+    dsv_strip_bom: bool,
+    dsv_write_bom: bool,
+    dsv_normalize_newlines: bool,
+    column_masking: Vec<ColumnMasking>,
+    sink_filter: Option<SinkPredicate>,
+    sink_projection: Option<Vec<usize>>,
 }
 
 #[pymethods]
@@ -4837,6 +6330,21 @@ impl DataStorage {
         mqtt_settings = None,
         only_provide_metadata = false,
         sort_key_index = None,
+        retry_policy = None,
+        request_timeout = None,
+        subprocess_settings = None,
+        backfill_then_stream_settings = None,
+        iceberg_catalog_settings = None,
+        read_change_data_feed = false,
+        partition_field_index = None,
+        imap_settings = None,
+        prometheus_settings = None,
+        file_rotation_settings = None,
+        kafka_lag_throttling_settings = None,
+        sql_polling_settings = None,
+        oracle_settings = None,
+        parquet_row_group_size = None,
+        custom_connector_settings = None,
     ))]
     #[allow(clippy::too_many_arguments)]
     fn new(
@@ -4874,6 +6382,21 @@ impl DataStorage {
         mqtt_settings: Option<MqttSettings>,
         only_provide_metadata: bool,
         sort_key_index: Option<usize>,
+        retry_policy: Option<PyConnectorRetryPolicy>,
+        request_timeout: Option<time::Duration>,
+        subprocess_settings: Option<SubprocessSettings>,
+        backfill_then_stream_settings: Option<BackfillThenStreamSettings>,
+        iceberg_catalog_settings: Option<IcebergCatalogSettings>,
+        read_change_data_feed: bool,
+        partition_field_index: Option<usize>,
+        imap_settings: Option<ImapSettings>,
+        prometheus_settings: Option<PrometheusSettings>,
+        file_rotation_settings: Option<FileRotationSettings>,
+        kafka_lag_throttling_settings: Option<KafkaLagThrottlingSettings>,
+        sql_polling_settings: Option<SqlPollingSettings>,
+        oracle_settings: Option<OracleSettings>,
+        parquet_row_group_size: Option<usize>,
+        custom_connector_settings: Option<CustomConnectorSettings>,
     ) -> Self {
         DataStorage {
             storage_type,
@@ -4910,6 +6433,23 @@ impl DataStorage {
             mqtt_settings,
             only_provide_metadata,
             sort_key_index,
+            retry_policy: retry_policy
+                .map(|retry_policy| retry_policy.0)
+                .unwrap_or_default(),
+            request_timeout,
+            subprocess_settings,
+            backfill_then_stream_settings,
+            iceberg_catalog_settings,
+            read_change_data_feed,
+            partition_field_index,
+            imap_settings,
+            prometheus_settings,
+            file_rotation_settings,
+            kafka_lag_throttling_settings,
+            sql_polling_settings,
+            oracle_settings,
+            parquet_row_group_size,
+            custom_connector_settings,
         }
     }
 
@@ -4981,6 +6521,22 @@ impl DataStorage {
 
         Ok(storage_options)
     }
+
+    fn iceberg_list_namespaces(&self, py: pyo3::Python) -> PyResult<Vec<Vec<String>>> {
+        let db_params = self.iceberg_db_params(self.namespace.clone().unwrap_or_default())?;
+        py.allow_threads(|| db_params.list_namespaces())
+            .map_err(|e| PyIOError::new_err(format!("Failed to list Iceberg namespaces: {e}")))
+    }
+
+    fn iceberg_list_tables(&self, py: pyo3::Python) -> PyResult<Vec<String>> {
+        let namespace = self
+            .namespace
+            .clone()
+            .ok_or_else(|| PyValueError::new_err("Namespace must be specified"))?;
+        let db_params = self.iceberg_db_params(namespace)?;
+        py.allow_threads(|| db_params.list_tables())
+            .map_err(|e| PyIOError::new_err(format!("Failed to list Iceberg tables: {e}")))
+    }
 }
 
 #[pymethods]
@@ -5004,6 +6560,17 @@ impl DataFormat {
         subject = None,
         designated_timestamp_policy = None,
         external_diff_column_index = None,
+        length_prefix = None,
+        schema_name = None,
+        avro_schema = None,
+        protobuf_descriptor_set = None,
+        protobuf_message = None,
+        dsv_strip_bom = true,
+        dsv_write_bom = false,
+        dsv_normalize_newlines = false,
+        column_masking = Vec::new(),
+        sink_filter = None,
+        sink_projection = None,
     ))]
     #[allow(clippy::too_many_arguments)]
     fn new(
@@ -5023,6 +6590,17 @@ impl DataFormat {
         subject: Option<String>,
         designated_timestamp_policy: Option<String>,
         external_diff_column_index: Option<usize>,
+        length_prefix: Option<String>,
+        schema_name: Option<String>,
+        avro_schema: Option<String>,
+        protobuf_descriptor_set: Option<Vec<u8>>,
+        protobuf_message: Option<String>,
+        dsv_strip_bom: bool,
+        dsv_write_bom: bool,
+        dsv_normalize_newlines: bool,
+        column_masking: Vec<PyColumnMasking>,
+        sink_filter: Option<PySinkPredicate>,
+        sink_projection: Option<Vec<usize>>,
     ) -> Self {
         DataFormat {
             format_type,
@@ -5041,6 +6619,20 @@ impl DataFormat {
             subject,
             designated_timestamp_policy,
             external_diff_column_index,
+            length_prefix,
+            schema_name,
+            avro_schema,
+            protobuf_descriptor_set,
+            protobuf_message,
+            dsv_strip_bom,
+            dsv_write_bom,
+            dsv_normalize_newlines,
+            column_masking: column_masking
+                .into_iter()
+                .map(|masking| masking.0)
+                .collect(),
+            sink_filter: sink_filter.map(|predicate| predicate.0),
+            sink_projection,
         }
     }
 
@@ -5058,6 +6650,7 @@ pub struct CsvParserSettings {
     pub enable_double_quote_escapes: bool,
     pub enable_quoting: bool,
     pub comment_character: Option<u8>,
+    pub strip_bom: bool,
 }
 
 #[pymethods]
@@ -5070,6 +6663,7 @@ impl CsvParserSettings {
         enable_double_quote_escapes = true,
         enable_quoting = true,
         comment_character = None,
+        strip_bom = true,
     ))]
     pub fn new(
         delimiter: char,
@@ -5078,6 +6672,7 @@ impl CsvParserSettings {
         enable_double_quote_escapes: bool,
         enable_quoting: bool,
         comment_character: Option<char>,
+        strip_bom: bool,
     ) -> PyResult<CsvParserSettings> {
         let mut comment_character_ascii: Option<u8> = None;
         if let Some(comment_character) = comment_character {
@@ -5099,6 +6694,7 @@ impl CsvParserSettings {
             enable_double_quote_escapes,
             enable_quoting,
             comment_character: comment_character_ascii,
+            strip_bom,
         })
     }
 }
@@ -5181,7 +6777,11 @@ impl DataStorage {
                 PyValueError::new_err("For AWS storage, aws_s3_settings must be specified")
             })?
             .borrow();
-        bucket_py.get().construct_bucket(bucket_name.as_deref())
+        let mut bucket = bucket_py.get().construct_bucket(bucket_name.as_deref())?;
+        if let Some(request_timeout) = self.request_timeout {
+            bucket.set_request_timeout(Some(request_timeout));
+        }
+        Ok(bucket)
     }
 
     fn mqtt_settings(&self) -> PyResult<MqttSettings> {
@@ -5191,6 +6791,30 @@ impl DataStorage {
             .cloned()
     }
 
+    fn subprocess_command(&self) -> PyResult<SubprocessCommand> {
+        self.subprocess_settings
+            .as_ref()
+            .map(|settings| settings.0.clone())
+            .ok_or_else(|| {
+                PyValueError::new_err("For subprocess, subprocess_settings must be specified")
+            })
+    }
+
+    fn custom_connector_settings(&self) -> PyResult<&CustomConnectorSettings> {
+        self.custom_connector_settings.as_ref().ok_or_else(|| {
+            PyValueError::new_err(
+                "For custom connectors, custom_connector_settings must be specified",
+            )
+        })
+    }
+
+    fn imap_config(&self) -> PyResult<ImapConfig> {
+        self.imap_settings
+            .as_ref()
+            .map(|settings| settings.0.clone())
+            .ok_or_else(|| PyValueError::new_err("For IMAP, imap_settings must be specified"))
+    }
+
     fn downloader_threads_count(&self) -> PyResult<usize> {
         if let Some(count) = self.downloader_threads_count {
             Ok(count)
@@ -5261,6 +6885,24 @@ impl DataStorage {
         props
     }
 
+    fn iceberg_db_params(&self, namespace: Vec<String>) -> PyResult<IcebergDBParams> {
+        let mut props = self.iceberg_s3_storage_options();
+        let kind = if let Some(settings) = &self.iceberg_catalog_settings {
+            props.extend(settings.properties.clone());
+            settings.kind.clone()
+        } else {
+            IcebergCatalogKind::Rest {
+                uri: self.path()?.to_string(),
+            }
+        };
+        Ok(IcebergDBParams::new(
+            kind,
+            self.database.clone(),
+            namespace,
+            props,
+        ))
+    }
+
     fn kafka_client_config(&self) -> PyResult<ClientConfig> {
         let rdkafka_settings = self.rdkafka_settings.as_ref().ok_or_else(|| {
             PyValueError::new_err("For kafka input, rdkafka_settings must be specified")
@@ -5321,7 +6963,13 @@ impl DataStorage {
 
     fn build_tokenizer_for_posix_like_read(&self, data_format: &DataFormat) -> Box<dyn Tokenize> {
         match data_format.format_type.as_ref() {
-            "dsv" => Box::new(CsvTokenizer::new(self.build_csv_parser_settings())),
+            "dsv" => Box::new(
+                CsvTokenizer::new(self.build_csv_parser_settings()).with_bom_stripped(
+                    self.csv_parser_settings
+                        .as_ref()
+                        .is_none_or(|settings| settings.strip_bom),
+                ),
+            ),
             _ => Box::new(BufReaderTokenizer::new(self.read_method)),
         }
     }
@@ -5357,6 +7005,7 @@ impl DataStorage {
             self.object_pattern.clone(),
             self.downloader_threads_count()?,
             self.mode.is_polling_enabled(),
+            self.retry_policy,
         )
         .map_err(|e| PyIOError::new_err(format!("Failed to initialize S3 scanner: {e}")))?;
         let storage = PosixLikeReader::new(
@@ -5370,6 +7019,24 @@ impl DataStorage {
         Ok((Box::new(storage), 1))
     }
 
+    fn construct_imap_reader(
+        &self,
+        is_persisted: bool,
+        data_format: &DataFormat,
+    ) -> PyResult<(Box<dyn ReaderBuilder>, usize)> {
+        let scanner = ImapScanner::new(self.imap_config()?)
+            .map_err(|e| PyIOError::new_err(format!("Failed to initialize IMAP scanner: {e}")))?;
+        let storage = PosixLikeReader::new(
+            Box::new(scanner),
+            self.build_tokenizer_for_posix_like_read(data_format),
+            self.mode,
+            self.only_provide_metadata,
+            is_persisted,
+        )
+        .map_err(|e| PyRuntimeError::new_err(format!("Creating IMAP reader failed: {e}")))?;
+        Ok((Box::new(storage), 1))
+    }
+
     /// Returns the total number of partitions for a Kafka topic
     fn total_partitions_for_topic(consumer: &BaseConsumer, topic: &str) -> PyResult<usize> {
         let metadata = consumer
@@ -5495,6 +7162,8 @@ impl DataStorage {
             seek_positions,
             watermarks,
             self.mode,
+            self.kafka_lag_throttling_settings
+                .map(|settings| settings.0),
         );
         Ok((Box::new(reader), self.parallel_readers.unwrap_or(256)))
     }
@@ -5574,6 +7243,11 @@ impl DataStorage {
         if self.start_from_timestamp_ms.is_some() && !backfilling_thresholds.is_empty() {
             return Err(PyValueError::new_err("The simultaneous use of 'start_from_timestamp_ms' and 'backfilling_thresholds' is not supported."));
         }
+        if self.read_change_data_feed && data_format.key_field_names.is_none() {
+            return Err(PyValueError::new_err(
+                "Reading the change data feed requires explicit primary key fields specification",
+            ));
+        }
         let reader = DeltaTableReader::new(
             self.path()?,
             self.object_downloader()?,
@@ -5583,6 +7257,7 @@ impl DataStorage {
             self.start_from_timestamp_ms,
             data_format.key_field_names.is_some(),
             backfilling_thresholds,
+            self.read_change_data_feed,
         )
         .map_err(|e| PyIOError::new_err(format!("Failed to connect to DeltaLake: {e}")))?;
         Ok((Box::new(reader), 1))
@@ -5629,8 +7304,6 @@ impl DataStorage {
             license.check_entitlements(["iceberg"])?;
         }
 
-        let uri = self.path()?;
-        let warehouse = self.database.as_ref();
         let table_name = self.table_name()?;
         let namespace = self
             .namespace
@@ -5641,12 +7314,7 @@ impl DataStorage {
             value_fields.push(field.borrow(py).clone());
         }
 
-        let db_params = IcebergDBParams::new(
-            uri.to_string(),
-            warehouse.cloned(),
-            namespace,
-            self.iceberg_s3_storage_options(),
-        );
+        let db_params = self.iceberg_db_params(namespace)?;
         let table_params =
             IcebergTableParams::new(table_name.to_string(), &value_fields).map_err(|e| {
                 PyIOError::new_err(format!(
@@ -5679,25 +7347,145 @@ impl DataStorage {
                 "Failed to establish connection with MQTT broker: {e}"
             ))
         })?;
-
-        // Wait for the subscription acknowledgement from the broker
-        loop {
-            let maybe_event = connection
-                .recv_timeout(std::time::Duration::from_secs(1))
-                .map_err(|e| {
-                    PyRuntimeError::new_err(format!("MQTT broker connection timeout: {e:?}"))
-                })?;
-            let event = maybe_event.map_err(|e| {
-                PyRuntimeError::new_err(format!(
-                    "Failed to receive subscription confirmation from MQTT broker: {e:?}"
-                ))
-            })?;
-            if matches!(event, MqttEvent::Incoming(MqttPacket::SubAck(_))) {
-                break;
-            }
+
+        // Wait for the subscription acknowledgement from the broker
+        loop {
+            let maybe_event = connection
+                .recv_timeout(std::time::Duration::from_secs(1))
+                .map_err(|e| {
+                    PyRuntimeError::new_err(format!("MQTT broker connection timeout: {e:?}"))
+                })?;
+            let event = maybe_event.map_err(|e| {
+                PyRuntimeError::new_err(format!(
+                    "Failed to receive subscription confirmation from MQTT broker: {e:?}"
+                ))
+            })?;
+            if matches!(event, MqttEvent::Incoming(MqttPacket::SubAck(_))) {
+                break;
+            }
+        }
+
+        Ok((Box::new(MqttReader::new(connection)), 1))
+    }
+
+    fn sql_polling_reader(
+        &self,
+        py: pyo3::Python,
+        client: impl SqlPollingClient + 'static,
+        data_format: &DataFormat,
+    ) -> PyResult<(Box<dyn ReaderBuilder>, usize)> {
+        let config = self
+            .sql_polling_settings
+            .as_ref()
+            .map(|settings| settings.0.clone())
+            .ok_or_else(|| {
+                PyValueError::new_err("For SQL polling, sql_polling_settings must be specified")
+            })?;
+        let reader = SqlPollingReader::new(
+            client,
+            config,
+            data_format.value_fields_type_map(py).into_iter().collect(),
+        )
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        // The high-water mark is shared by all the rows, so a single worker polls the table
+        Ok((Box::new(reader), 1))
+    }
+
+    fn construct_sql_polling_reader(
+        &self,
+        py: pyo3::Python,
+        data_format: &DataFormat,
+    ) -> PyResult<(Box<dyn ReaderBuilder>, usize)> {
+        let client = Client::connect(self.connection_string()?, NoTls).map_err(|e| {
+            PyIOError::new_err(format!("Failed to establish PostgreSQL connection: {e:?}"))
+        })?;
+        self.sql_polling_reader(py, client, data_format)
+    }
+
+    fn construct_mysql_polling_reader(
+        &self,
+        py: pyo3::Python,
+        data_format: &DataFormat,
+    ) -> PyResult<(Box<dyn ReaderBuilder>, usize)> {
+        let runtime = create_async_tokio_runtime()
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to create async runtime: {e}")))?;
+        let client =
+            MySqlPollingClient::connect(runtime, self.connection_string()?).map_err(|e| {
+                PyIOError::new_err(format!("Failed to establish MySQL connection: {e}"))
+            })?;
+        self.sql_polling_reader(py, client, data_format)
+    }
+
+    fn construct_mssql_polling_reader(
+        &self,
+        py: pyo3::Python,
+        data_format: &DataFormat,
+    ) -> PyResult<(Box<dyn ReaderBuilder>, usize)> {
+        let runtime = create_async_tokio_runtime()
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to create async runtime: {e}")))?;
+        let client =
+            SqlServerPollingClient::connect(runtime, self.connection_string()?).map_err(|e| {
+                PyIOError::new_err(format!("Failed to establish SQL Server connection: {e}"))
+            })?;
+        self.sql_polling_reader(py, client, data_format)
+    }
+
+    fn construct_subprocess_reader(&self) -> PyResult<(Box<dyn ReaderBuilder>, usize)> {
+        let reader = SubprocessReader::new(self.subprocess_command()?);
+        Ok((Box::new(reader), 1))
+    }
+
+    fn construct_custom_reader(&self) -> PyResult<(Box<dyn ReaderBuilder>, usize)> {
+        let settings = self.custom_connector_settings()?;
+        let read = settings.read.clone().ok_or_else(|| {
+            PyValueError::new_err("For custom source, the read callable must be specified")
+        })?;
+        let reader = SourceReader::new(PythonSource::new(read, settings.max_queue_size));
+        // The iterator can only be consumed once, so it's read by a single worker
+        Ok((Box::new(reader), 1))
+    }
+
+    fn construct_backfill_then_stream_reader(
+        &self,
+        py: pyo3::Python,
+        data_format: &DataFormat,
+        connector_index: usize,
+        worker_index: usize,
+        license: Option<&License>,
+        is_persisted: bool,
+    ) -> PyResult<(Box<dyn ReaderBuilder>, usize)> {
+        let settings = self.backfill_then_stream_settings.as_ref().ok_or_else(|| {
+            PyValueError::new_err(
+                "For backfill_then_stream, backfill_then_stream_settings must be specified",
+            )
+        })?;
+        let mut sources = Vec::with_capacity(2);
+        for (storage, format) in [
+            (&settings.backfill_storage, &settings.backfill_format),
+            (&settings.stream_storage, &settings.stream_format),
+        ] {
+            let format = format.get();
+            let (reader, _) = storage.get().construct_reader(
+                py,
+                format,
+                connector_index,
+                worker_index,
+                license,
+                is_persisted,
+            )?;
+            sources.push((reader, format.construct_parser(py)?));
         }
-
-        Ok((Box::new(MqttReader::new(connection)), 1))
+        let stream = sources.pop().unwrap();
+        let backfill = sources.pop().unwrap();
+        let reader = BackfillThenStreamReaderBuilder::new(
+            backfill,
+            stream,
+            data_format.value_field_names(py),
+            settings.seam.clone(),
+        );
+        // The backfill has to be read completely before any row of the stream, so both
+        // sources are read by a single worker
+        Ok((Box::new(reader), 1))
     }
 
     fn construct_reader(
@@ -5712,6 +7500,7 @@ impl DataStorage {
         match self.storage_type.as_ref() {
             "fs" => self.construct_fs_reader(is_persisted, data_format),
             "s3" => self.construct_s3_reader(is_persisted, data_format),
+            "imap" => self.construct_imap_reader(is_persisted, data_format),
             "kafka" => self.construct_kafka_reader(),
             "python" => self.construct_python_reader(py, data_format),
             "sqlite" => self.construct_sqlite_reader(py, data_format),
@@ -5719,6 +7508,19 @@ impl DataStorage {
             "nats" => self.construct_nats_reader(connector_index, worker_index),
             "iceberg" => self.construct_iceberg_reader(py, data_format, license),
             "mqtt" => self.construct_mqtt_reader(),
+            "subprocess" => self.construct_subprocess_reader(),
+            "custom" => self.construct_custom_reader(),
+            "sql_polling" => self.construct_sql_polling_reader(py, data_format),
+            "mysql_polling" => self.construct_mysql_polling_reader(py, data_format),
+            "mssql_polling" => self.construct_mssql_polling_reader(py, data_format),
+            "backfill_then_stream" => self.construct_backfill_then_stream_reader(
+                py,
+                data_format,
+                connector_index,
+                worker_index,
+                license,
+                is_persisted,
+            ),
             other => Err(PyValueError::new_err(format!(
                 "Unknown data source {other:?}"
             ))),
@@ -5762,6 +7564,13 @@ impl DataStorage {
 
     fn construct_fs_writer(&self) -> PyResult<Box<dyn Writer>> {
         let path = self.path()?;
+        if let Some(rotation_settings) = &self.file_rotation_settings {
+            let writer = RotatingFileWriter::new(path.into(), rotation_settings.0.clone())
+                .map_err(|e| {
+                    PyIOError::new_err(format!("Filesystem operation (create) failed: {e}"))
+                })?;
+            return Ok(Box::new(writer));
+        }
         let storage = {
             let file = File::create(path);
             match file {
@@ -5793,6 +7602,7 @@ impl DataStorage {
             topic,
             self.header_fields.clone(),
             self.key_field_index,
+            self.partition_field_index,
         );
 
         Ok(Box::new(writer))
@@ -5826,6 +7636,69 @@ impl DataStorage {
         Ok(Box::new(storage))
     }
 
+    fn sql_sink_mode(data_format: &DataFormat) -> SqlSinkMode {
+        match &data_format.key_field_names {
+            Some(key_field_names) => SqlSinkMode::Snapshot {
+                key_field_names: key_field_names.clone(),
+            },
+            None => SqlSinkMode::StreamOfChanges,
+        }
+    }
+
+    fn construct_mssql_writer(
+        &self,
+        py: pyo3::Python,
+        data_format: &DataFormat,
+    ) -> PyResult<Box<dyn Writer>> {
+        let runtime = create_async_tokio_runtime()
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to create async runtime: {e}")))?;
+        let value_fields: Vec<ValueField> = data_format
+            .value_fields
+            .iter()
+            .map(|field| field.borrow(py).clone())
+            .collect();
+        let writer = SqlServerWriter::new(
+            runtime,
+            self.connection_string()?,
+            self.table_name()?.to_string(),
+            &value_fields,
+            &Self::sql_sink_mode(data_format),
+            self.max_batch_size,
+        )
+        .map_err(|e| PyIOError::new_err(format!("Failed to create SQL Server writer: {e}")))?;
+        Ok(Box::new(writer))
+    }
+
+    fn construct_oracle_writer(
+        &self,
+        py: pyo3::Python,
+        data_format: &DataFormat,
+    ) -> PyResult<Box<dyn Writer>> {
+        let settings = self.oracle_settings.as_ref().ok_or_else(|| {
+            PyValueError::new_err("For Oracle, oracle_settings must be specified")
+        })?;
+        let connection = ::oracle::Connection::connect(
+            &settings.user,
+            &settings.password,
+            &settings.connect_string,
+        )
+        .map_err(|e| PyIOError::new_err(format!("Failed to establish Oracle connection: {e}")))?;
+        let value_fields: Vec<ValueField> = data_format
+            .value_fields
+            .iter()
+            .map(|field| field.borrow(py).clone())
+            .collect();
+        let writer = OracleWriter::new(
+            connection,
+            self.table_name()?.to_string(),
+            &value_fields,
+            &Self::sql_sink_mode(data_format),
+            self.max_batch_size,
+        )
+        .map_err(|e| PyValueError::new_err(format!("Failed to create Oracle writer: {e}")))?;
+        Ok(Box::new(writer))
+    }
+
     fn construct_elasticsearch_writer(
         &self,
         py: pyo3::Python,
@@ -5845,11 +7718,12 @@ impl DataStorage {
             })?
             .borrow();
         let elasticsearch_client_params = elasticsearch_client_params_py.get();
-        let client = elasticsearch_client_params.client(py)?;
+        let client = elasticsearch_client_params.client(py, self.request_timeout)?;
         let index_name = elasticsearch_client_params.index_name.clone();
         let max_batch_size = self.max_batch_size;
+        let runtime = create_async_tokio_runtime()?;
 
-        let writer = ElasticSearchWriter::new(client, index_name, max_batch_size);
+        let writer = ElasticSearchWriter::new(runtime, client, index_name, max_batch_size);
         Ok(Box::new(writer))
     }
 
@@ -5937,8 +7811,6 @@ impl DataStorage {
             ));
         }
 
-        let uri = self.path()?;
-        let warehouse = self.database.as_ref();
         let table_name = self.table_name()?;
         let namespace = self
             .namespace
@@ -5949,12 +7821,7 @@ impl DataStorage {
             value_fields.push(field.borrow(py).clone());
         }
 
-        let db_params = IcebergDBParams::new(
-            uri.to_string(),
-            warehouse.cloned(),
-            namespace,
-            self.iceberg_s3_storage_options(),
-        );
+        let db_params = self.iceberg_db_params(namespace)?;
         let table_params =
             IcebergTableParams::new(table_name.to_string(), &value_fields).map_err(|e| {
                 PyIOError::new_err(format!(
@@ -6019,6 +7886,108 @@ impl DataStorage {
         Ok(Box::new(writer))
     }
 
+    fn construct_prometheus_writer(&self) -> PyResult<Box<dyn Writer>> {
+        let settings = self.prometheus_settings.as_ref().ok_or_else(|| {
+            PyValueError::new_err("For Prometheus, prometheus_settings must be specified")
+        })?;
+        let writer = PrometheusWriter::new(
+            self.path()?.to_string(),
+            &settings.headers,
+            settings.metric_fields.clone(),
+            settings.label_fields.clone(),
+            settings.timestamp_field_index,
+        )
+        .map_err(|e| PyValueError::new_err(format!("Failed to create Prometheus writer: {e}")))?;
+        Ok(Box::new(writer))
+    }
+
+    fn construct_subprocess_writer(&self) -> PyResult<Box<dyn Writer>> {
+        let writer = SubprocessWriter::new(self.subprocess_command()?);
+        Ok(Box::new(writer))
+    }
+
+    fn construct_custom_writer(&self) -> PyResult<Box<dyn Writer>> {
+        let settings = self.custom_connector_settings()?;
+        let write = settings.write.clone().ok_or_else(|| {
+            PyValueError::new_err("For custom sink, the write callable must be specified")
+        })?;
+        let callbacks = PythonSinkCallbacks {
+            write,
+            flush: settings.flush.clone(),
+            finish: settings.finish.clone(),
+        };
+        let writer = SinkWriter::new(PythonSink::new(callbacks, settings.max_queue_size));
+        Ok(Box::new(writer))
+    }
+
+    fn construct_query_endpoint_writer(
+        &self,
+        py: pyo3::Python,
+        data_format: &DataFormat,
+        worker_index: usize,
+    ) -> PyResult<Box<dyn Writer>> {
+        let address = self.path()?;
+        let address = address.parse().map_err(|e| {
+            PyValueError::new_err(format!("Invalid query endpoint address {address:?}: {e}"))
+        })?;
+        // The rows of the table are sent to the first worker only
+        let writer = QueryEndpointWriter::new(
+            address,
+            self.table_name()?.to_string(),
+            data_format.value_field_names(py),
+            worker_index == 0,
+        )
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Box::new(writer))
+    }
+
+    fn construct_parquet_writer(
+        &self,
+        py: pyo3::Python,
+        data_format: &DataFormat,
+    ) -> PyResult<Box<dyn Writer>> {
+        let rotation_settings = self.file_rotation_settings.as_ref().ok_or_else(|| {
+            PyValueError::new_err("For Parquet output, file_rotation_settings must be specified")
+        })?;
+        let dtypes: Vec<_> = data_format
+            .value_fields
+            .iter()
+            .map(|field| field.borrow(py).type_.clone())
+            .collect();
+        let writer = ParquetFileWriter::new(
+            self.path()?.into(),
+            rotation_settings.0.clone(),
+            &data_format.value_field_names(py),
+            &dtypes,
+            self.parquet_row_group_size
+                .unwrap_or(DEFAULT_ROW_GROUP_SIZE),
+        )
+        .map_err(|e| PyIOError::new_err(format!("Failed to create Parquet writer: {e}")))?;
+        Ok(Box::new(writer))
+    }
+
+    fn construct_snapshot_export_writer(
+        &self,
+        py: pyo3::Python,
+        data_format: &DataFormat,
+        worker_index: usize,
+    ) -> PyResult<Box<dyn Writer>> {
+        let dtypes = data_format
+            .value_fields
+            .iter()
+            .map(|field| field.borrow(py).type_.clone())
+            .collect();
+        // The rows of the table are sent to the first worker only
+        let writer = SnapshotExportWriter::new(
+            self.table_name()?.to_string(),
+            data_format.value_field_names(py),
+            dtypes,
+            worker_index == 0,
+        )
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Box::new(writer))
+    }
+
     fn construct_questdb_writer(
         &self,
         py: pyo3::Python,
@@ -6129,11 +8098,15 @@ impl DataStorage {
         py: pyo3::Python,
         data_format: &DataFormat,
         license: Option<&License>,
+        worker_index: usize,
     ) -> PyResult<Box<dyn Writer>> {
         match self.storage_type.as_ref() {
             "fs" => self.construct_fs_writer(),
+            "parquet" => self.construct_parquet_writer(py, data_format),
             "kafka" => self.construct_kafka_writer(),
             "postgres" => self.construct_postgres_writer(py, data_format),
+            "mssql" => self.construct_mssql_writer(py, data_format),
+            "oracle" => self.construct_oracle_writer(py, data_format),
             "elasticsearch" => self.construct_elasticsearch_writer(py, license),
             "deltalake" => self.construct_deltalake_writer(py, data_format, license),
             "mongodb" => self.construct_mongodb_writer(),
@@ -6143,6 +8116,13 @@ impl DataStorage {
             "mqtt" => self.construct_mqtt_writer(),
             "questdb" => self.construct_questdb_writer(py, data_format, license),
             "dynamodb" => self.construct_dynamodb_writer(py, data_format, license),
+            "subprocess" => self.construct_subprocess_writer(),
+            "custom" => self.construct_custom_writer(),
+            "prometheus" => self.construct_prometheus_writer(),
+            "query_endpoint" => self.construct_query_endpoint_writer(py, data_format, worker_index),
+            "snapshot_export" => {
+                self.construct_snapshot_export_writer(py, data_format, worker_index)
+            }
             other => Err(PyValueError::new_err(format!(
                 "Unknown data sink {other:?}"
             ))),
@@ -6181,7 +8161,10 @@ impl DataFormat {
             self.key_field_names.clone(),
             self.value_field_names(py),
             *delimiter,
-        ))
+        )
+        .with_bom_stripped(self.dsv_strip_bom)
+        .with_bom_written(self.dsv_write_bom)
+        .with_normalized_newlines(self.dsv_normalize_newlines))
     }
 
     fn table_name(&self) -> PyResult<String> {
@@ -6213,6 +8196,50 @@ impl DataFormat {
     }
 
     fn construct_parser(&self, py: pyo3::Python) -> PyResult<Box<dyn Parser>> {
+        let mut parser = self.construct_payload_parser(py)?;
+        if let Some(schema_name) = &self.schema_name {
+            let schema =
+                registered_schema(schema_name).map_err(|e| PyValueError::new_err(e.to_string()))?;
+            let declared_columns: Vec<_> = self
+                .value_fields
+                .iter()
+                .map(|field| field.borrow(py).as_schema_column())
+                .collect();
+            let positions = schema
+                .check_declared(
+                    schema_name,
+                    &declared_columns,
+                    self.key_field_names.as_deref(),
+                )
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+            parser = Box::new(SchemaValidatingParser::new(
+                parser,
+                schema_name.clone(),
+                schema,
+                positions,
+            ));
+        }
+        let Some(length_prefix) = &self.length_prefix else {
+            return Ok(parser);
+        };
+        let length_prefix = match length_prefix.as_str() {
+            "varint" => LengthPrefix::Varint,
+            "fixed32_be" => LengthPrefix::Fixed32BigEndian,
+            "fixed32_le" => LengthPrefix::Fixed32LittleEndian,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown length prefix {other:?}, expected \"varint\", \"fixed32_be\" or \"fixed32_le\""
+                )))
+            }
+        };
+        Ok(Box::new(LengthPrefixedParser::new(
+            parser,
+            length_prefix,
+            LengthPrefixedParser::DEFAULT_MAX_FRAME_LENGTH,
+        )))
+    }
+
+    fn construct_payload_parser(&self, py: pyo3::Python) -> PyResult<Box<dyn Parser>> {
         match self.format_type.as_ref() {
             "dsv" => {
                 let settings = self.construct_dsv_settings(py)?;
@@ -6254,11 +8281,85 @@ impl DataFormat {
                 self.schema(py)?,
                 self.session_type,
             )?)),
+            "parquet" => Ok(Box::new(ParquetParser::new(
+                self.key_field_names.clone(),
+                self.value_field_names(py),
+                self.schema(py)?,
+                self.session_type,
+            )?)),
+            "avro" => {
+                let reader_schema = self
+                    .avro_schema
+                    .as_deref()
+                    .map(AvroSchema::parse_str)
+                    .transpose()
+                    .map_err(|e| PyValueError::new_err(format!("Incorrect avro schema: {e}")))?;
+                let parser = AvroParser::new(
+                    self.key_field_names.clone(),
+                    self.value_field_names(py),
+                    self.schema(py)?,
+                    self.session_type,
+                    reader_schema,
+                    self.schema_registry_settings
+                        .clone()
+                        .map(PySchemaRegistrySettings::build_avro_registry)
+                        .transpose()?,
+                )?;
+                Ok(Box::new(parser))
+            }
+            "protobuf" => {
+                let (Some(descriptor_set), Some(message_name)) =
+                    (&self.protobuf_descriptor_set, &self.protobuf_message)
+                else {
+                    return Err(PyValueError::new_err(
+                        "Protobuf data format requires 'protobuf_descriptor_set' and 'protobuf_message' to be set",
+                    ));
+                };
+                let parser = ProtobufParser::new(
+                    self.key_field_names.clone(),
+                    self.value_field_names(py),
+                    self.schema(py)?,
+                    self.session_type,
+                    descriptor_set,
+                    message_name,
+                )?;
+                Ok(Box::new(parser))
+            }
+            "msgpack" => Ok(Box::new(MsgPackParser::new(
+                self.key_field_names.clone(),
+                self.value_field_names(py),
+                self.schema(py)?,
+                self.session_type,
+            )?)),
             _ => Err(PyValueError::new_err("Unknown data format")),
         }
     }
 
     fn construct_formatter(&self, py: pyo3::Python) -> PyResult<Box<dyn Formatter>> {
+        let mut formatter = self.construct_unmasked_formatter(py)?;
+        if !self.column_masking.is_empty() {
+            formatter = Box::new(
+                MaskingFormatter::new(
+                    formatter,
+                    &self.value_field_names(py),
+                    self.key_field_names.as_deref().unwrap_or_default(),
+                    &self.column_masking,
+                )
+                .map_err(|e| PyValueError::new_err(format!("Incorrect column masking: {e}")))?,
+            );
+        }
+        // The masking refers to the projected columns, so it's applied after the projection
+        if self.sink_filter.is_some() || self.sink_projection.is_some() {
+            formatter = Box::new(PushdownFormatter::new(
+                formatter,
+                self.sink_filter.clone(),
+                self.sink_projection.clone(),
+            ));
+        }
+        Ok(formatter)
+    }
+
+    fn construct_unmasked_formatter(&self, py: pyo3::Python) -> PyResult<Box<dyn Formatter>> {
         match self.format_type.as_ref() {
             "dsv" => {
                 let settings = self.construct_dsv_settings(py)?;
@@ -6308,6 +8409,31 @@ impl DataFormat {
                     JsonLinesFormatter::new(self.value_field_names(py), schema_registry_settings);
                 Ok(Box::new(formatter))
             }
+            "avro" => {
+                let avro_schema = self.avro_schema.clone().ok_or_else(|| {
+                    PyValueError::new_err("Avro data formatter requires 'avro_schema' to be set")
+                })?;
+                let registry =
+                    if let Some(schema_registry_settings) = &self.schema_registry_settings {
+                        let subject = self.subject.clone().ok_or_else(|| {
+                            PyValueError::new_err(
+                                "If a data formatter has 'schema_registry_settings' ".to_owned()
+                                    + "specified, it must also have 'subject' set",
+                            )
+                        })?;
+                        Some((
+                            schema_registry_settings.clone().build_avro_registry()?,
+                            subject,
+                        ))
+                    } else {
+                        None
+                    };
+                let formatter =
+                    AvroFormatter::new(self.value_field_names(py), avro_schema, registry).map_err(
+                        |e| PyValueError::new_err(format!("Incorrect avro schema: {e}")),
+                    )?;
+                Ok(Box::new(formatter))
+            }
             "null" => {
                 let formatter = NullFormatter::new();
                 Ok(Box::new(formatter))
@@ -6327,6 +8453,14 @@ impl DataFormat {
                 let formatter = BsonFormatter::new(self.value_field_names(py));
                 Ok(Box::new(formatter))
             }
+            "debezium" => {
+                let formatter = DebeziumFormatter::new(self.value_field_names(py));
+                Ok(Box::new(formatter))
+            }
+            "msgpack" => {
+                let formatter = MsgPackFormatter::new(self.value_field_names(py));
+                Ok(Box::new(formatter))
+            }
             _ => Err(PyValueError::new_err("Unknown data format")),
         }
     }
@@ -6699,8 +8833,7 @@ fn run_with_wakeup_receiver<R>(
     let wakeup_thread = thread::Builder::new()
         .name("pathway:signal_wakeup".to_string())
         .spawn(move || loop {
-            let amount = wakeup_reader
-                .read(&mut [0; 1024])
+            let amount = pipe::read(&mut wakeup_reader, &mut [0; 1024])
                 .expect("reading from the wakeup pipe should not fail");
             if amount == 0 {
                 break;
@@ -6723,10 +8856,33 @@ fn run_with_wakeup_receiver<R>(
 
 static LOGGING_RESET_HANDLE: Lazy<ResetHandle> = Lazy::new(logging::init);
 
+impl From<SqlError> for PyErr {
+    fn from(error: SqlError) -> Self {
+        match error {
+            SqlError::Engine(error) => error.into(),
+            error => PyValueError::new_err(error.to_string()),
+        }
+    }
+}
+
 impl From<LicenseError> for PyErr {
     fn from(error: LicenseError) -> Self {
         let message = error.to_string();
-        PyRuntimeError::new_err(message)
+        let entitlements = match error {
+            LicenseError::InsufficientLicenseEntitlements(entitlements) => entitlements,
+            _ => Vec::new(),
+        };
+        Python::with_gil(|py| {
+            let entitlements = PyTuple::new(py, entitlements).map(Bound::into_any);
+            match entitlements {
+                Ok(entitlements) => exception_with_fields(
+                    LICENSE_ERROR_TYPE.bind(py),
+                    message,
+                    &[("entitlements", Ok(entitlements))],
+                ),
+                Err(error) => error,
+            }
+        })
     }
 }
 
@@ -6741,6 +8897,157 @@ fn check_entitlements(license_key: Option<String>, entitlements: Vec<String>) ->
     Ok(())
 }
 
+#[derive(Clone, Debug)]
+#[pyclass(module = "pathway.engine", frozen, get_all)]
+pub struct LicenseInfo {
+    is_offline: bool,
+    policy: Option<String>,
+    entitlements: Option<Vec<String>>,
+    expiration_date: Option<String>,
+    file_expiration_date: Option<String>,
+}
+
+impl From<EngineLicenseInfo> for LicenseInfo {
+    fn from(info: EngineLicenseInfo) -> Self {
+        Self {
+            is_offline: info.is_offline,
+            policy: info.policy,
+            entitlements: info.entitlements,
+            expiration_date: info.expiration_date.map(|date| date.to_rfc3339()),
+            file_expiration_date: info.file_expiration_date.map(|date| date.to_rfc3339()),
+        }
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (*, license_key))]
+fn license_info(license_key: Option<String>) -> PyResult<LicenseInfo> {
+    Ok(License::new(license_key)?.info().into())
+}
+
+/// Returns the most recent runtime stats of the computation in this process.
+#[pyfunction]
+fn runtime_stats() -> Option<ProberStats> {
+    latest_stats().map(|stats| (*stats).clone())
+}
+
+/// Requests graceful termination of the computation running in this process.
+#[pyfunction]
+fn request_shutdown() {
+    shutdown::request_shutdown();
+}
+
+/// Pauses or resumes the running connector with the given name.
+#[pyfunction]
+fn set_connector_paused_state(name: &str, paused: bool) -> PyResult<()> {
+    set_connector_paused(name, paused).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(())
+}
+
+/// Writes the current contents of a table registered for exports to a file. Returns the
+/// number of rows and the time before which the changes are included.
+#[pyfunction]
+#[pyo3(signature = (name, path, format, time = None, timeout = None))]
+fn export_table_snapshot(
+    py: Python,
+    name: &str,
+    path: PathBuf,
+    format: &str,
+    time: Option<u64>,
+    timeout: Option<time::Duration>,
+) -> PyResult<(usize, Option<u64>)> {
+    let format: ExportFormat = format
+        .parse()
+        .map_err(|e: ExportError| PyValueError::new_err(e.to_string()))?;
+    // the export may wait for the computation, which can need the GIL to advance
+    let ExportedSnapshot { rows, time } = py
+        .allow_threads(|| export_snapshot(name, &path, format, time.map(Timestamp), timeout))
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok((rows, time.map(|time| time.0)))
+}
+
+/// Registers a schema under a name, so that the data formats can reference it with their
+/// `schema_name`.
+#[pyfunction]
+#[pyo3(signature = (name, value_fields, key_field_names = None))]
+#[allow(clippy::needless_pass_by_value)]
+fn register_schema(
+    py: Python,
+    name: &str,
+    value_fields: Vec<Py<ValueField>>,
+    key_field_names: Option<Vec<String>>,
+) -> PyResult<()> {
+    let schema = NamedSchema {
+        columns: value_fields
+            .iter()
+            .map(|field| field.borrow(py).as_schema_column())
+            .collect(),
+        key_column_names: key_field_names,
+    };
+    register_named_schema(name, schema).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Returns the worker ID and the connector name of the log records emitted by the current
+/// thread.
+#[pyfunction]
+fn current_log_context() -> (Option<usize>, Option<String>) {
+    let context = log_context::current();
+    (context.worker_id, context.connector_name)
+}
+
+/// Checks if termination of the running computation has been requested.
+#[pyfunction]
+fn is_shutdown_requested() -> bool {
+    shutdown::is_shutdown_requested()
+}
+
+/// Starts reporting the schema drift of the input connectors created from now on.
+#[pyfunction]
+fn enable_schema_drift_reporting() {
+    schema_drift::enable_reporting();
+}
+
+/// Takes the schema drift events reported since the previous call, as tuples of the source,
+/// the field, the kind of the drift and its details.
+#[pyfunction]
+fn take_schema_drift_events() -> Vec<(String, String, &'static str, String)> {
+    schema_drift::take_events()
+        .into_iter()
+        .map(|event| {
+            (
+                event.source,
+                event.field,
+                event.kind.as_str(),
+                event.details,
+            )
+        })
+        .collect()
+}
+
+/// Records that `diff` rows started violating a data quality constraint, or stopped if `diff`
+/// is negative.
+#[pyfunction]
+fn record_constraint_violations(check: String, constraint: String, diff: isize) {
+    constraints::record_violations(ConstraintId { check, constraint }, diff);
+}
+
+/// Returns the check, the constraint, the number of rows violating it at the moment and the
+/// number of all the violations observed, for each of the constraints checked in this process.
+#[pyfunction]
+fn constraint_violations() -> Vec<(String, String, u64, u64)> {
+    constraints::violation_counts()
+        .into_iter()
+        .map(|(id, counts)| (id.check, id.constraint, counts.current, counts.total))
+        .collect()
+}
+
+/// Returns the value of the environment variable, or of the corresponding key of the
+/// configuration file pointed to by `PATHWAY_CONFIG_FILE` if the variable is not set.
+#[pyfunction]
+fn env_var_or_config(name: &str) -> PyResult<Option<String>> {
+    env::env_var_or_config(name).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
 #[pymodule]
 #[pyo3(name = "engine")]
 fn engine(_py: Python<'_>, m: &Bound<PyModule>) -> PyResult<()> {
@@ -6767,11 +9074,22 @@ fn engine(_py: Python<'_>, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<PyReadMethod>()?;
     m.add_class::<PyMonitoringLevel>()?;
     m.add_class::<PyTableWriterInitMode>()?;
+    m.add_class::<PyAsyncTimeoutPolicy>()?;
+    m.add_class::<PyUdfCache>()?;
+    m.add_class::<PyCancellationToken>()?;
+    m.add_class::<PyUdfErrorAction>()?;
+    m.add_class::<PyUdfErrorPolicy>()?;
     m.add_class::<Universe>()?;
     m.add_class::<Column>()?;
     m.add_class::<LegacyTable>()?;
     m.add_class::<Table>()?;
     m.add_class::<DataRow>()?;
+    m.add_class::<PyArrowBatch>()?;
+    m.add_class::<ProberStats>()?;
+    m.add_class::<OperatorStats>()?;
+    m.add_class::<CountStats>()?;
+    m.add_class::<ConnectorStats>()?;
+    m.add_class::<BackfillProgress>()?;
     m.add_class::<Computer>()?;
     m.add_class::<Scope>()?;
     m.add_class::<Context>()?;
@@ -6791,9 +9109,23 @@ fn engine(_py: Python<'_>, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<PySnapshotEvent>()?;
     m.add_class::<PyConnectorGroupDescriptor>()?;
     m.add_class::<TelemetryConfig>()?;
+    m.add_class::<LicenseInfo>()?;
     m.add_class::<BackfillingThreshold>()?;
     m.add_class::<PyDeltaOptimizerRule>()?;
+    m.add_class::<PyConnectorRetryPolicy>()?;
     m.add_class::<MqttSettings>()?;
+    m.add_class::<SubprocessSettings>()?;
+    m.add_class::<CustomConnectorSettings>()?;
+    m.add_class::<SqlPollingSettings>()?;
+    m.add_class::<OracleSettings>()?;
+    m.add_class::<PyColumnMasking>()?;
+    m.add_class::<PySinkPredicate>()?;
+    m.add_class::<ImapSettings>()?;
+    m.add_class::<PrometheusSettings>()?;
+    m.add_class::<FileRotationSettings>()?;
+    m.add_class::<KafkaLagThrottlingSettings>()?;
+    m.add_class::<BackfillThenStreamSettings>()?;
+    m.add_class::<IcebergCatalogSettings>()?;
     m.add_class::<PySchemaRegistrySettings>()?;
 
     m.add_class::<ConnectorProperties>()?;
@@ -6817,12 +9149,30 @@ fn engine(_py: Python<'_>, m: &Bound<PyModule>) -> PyResult<()> {
     #[allow(clippy::unsafe_removed_from_name)] // false positive
     m.add_function(wrap_pyfunction!(unsafe_make_pointer, m)?)?;
     m.add_function(wrap_pyfunction!(check_entitlements, m)?)?;
+    m.add_function(wrap_pyfunction!(license_info, m)?)?;
     m.add_function(wrap_pyfunction!(deserialize, m)?)?;
     m.add_function(wrap_pyfunction!(serialize, m)?)?;
+    m.add_function(wrap_pyfunction!(captured_table_to_arrow, m)?)?;
+    m.add_function(wrap_pyfunction!(runtime_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(request_shutdown, m)?)?;
+    m.add_function(wrap_pyfunction!(set_connector_paused_state, m)?)?;
+    m.add_function(wrap_pyfunction!(export_table_snapshot, m)?)?;
+    m.add_function(wrap_pyfunction!(register_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(is_shutdown_requested, m)?)?;
+    m.add_function(wrap_pyfunction!(current_log_context, m)?)?;
+    m.add_function(wrap_pyfunction!(env_var_or_config, m)?)?;
+    m.add_function(wrap_pyfunction!(enable_schema_drift_reporting, m)?)?;
+    m.add_function(wrap_pyfunction!(take_schema_drift_events, m)?)?;
+    m.add_function(wrap_pyfunction!(record_constraint_violations, m)?)?;
+    m.add_function(wrap_pyfunction!(constraint_violations, m)?)?;
 
     m.add("MissingValueError", &*MISSING_VALUE_ERROR_TYPE)?;
     m.add("EngineError", &*ENGINE_ERROR_TYPE)?;
     m.add("EngineErrorWithTrace", &*ENGINE_ERROR_WITH_TRACE_TYPE)?;
+    m.add("ParseError", &*PARSE_ERROR_TYPE)?;
+    m.add("ConnectorError", &*CONNECTOR_ERROR_TYPE)?;
+    m.add("PersistenceError", &*PERSISTENCE_ERROR_TYPE)?;
+    m.add("LicenseError", &*LICENSE_ERROR_TYPE)?;
     m.add("OtherWorkerError", &*OTHER_WORKER_ERROR)?;
 
     m.add("DONE", &*DONE)?;
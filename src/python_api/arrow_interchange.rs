@@ -0,0 +1,318 @@
+// Copyright © 2024 Pathway
+
+//! Exchanging batches of rows with Python as Arrow record batches. Batches are passed through
+//! the Arrow `PyCapsule` interface (the C data interface), so no Python object is created per
+//! cell and libraries like `pyarrow` or `polars` can use the buffers without copying them.
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, RecordBatch, StructArray};
+use arrow::datatypes::{DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema};
+use arrow::error::ArrowError;
+use arrow::ffi::{from_ffi, to_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+use pyo3::exceptions::{PyKeyError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyCapsule;
+
+use crate::connectors::data_lake::arrow::{array_for_type, arrow_data_type};
+use crate::connectors::data_lake::{columns_into_pathway_values, LakeWriterSettings};
+use crate::connectors::WriteError;
+use crate::engine::{DataRow, Key, Timestamp, Type, Value};
+
+pub const ID_COLUMN: &str = "id";
+pub const TIME_COLUMN: &str = "__time__";
+pub const DIFF_COLUMN: &str = "__diff__";
+
+fn arrow_error(error: ArrowError) -> PyErr {
+    PyValueError::new_err(format!("Arrow error: {error}"))
+}
+
+fn write_error(error: WriteError) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+fn writer_settings() -> LakeWriterSettings {
+    LakeWriterSettings {
+        use_64bit_size_type: false,
+        utc_timezone_name: "UTC".into(),
+    }
+}
+
+/// A record batch produced by the engine. Implements `__arrow_c_array__`, so it can be passed
+/// directly to e.g. `pyarrow.record_batch` or `polars.from_arrow`.
+#[pyclass(module = "pathway.engine", frozen, name = "ArrowBatch")]
+pub struct PyArrowBatch {
+    batch: RecordBatch,
+}
+
+impl PyArrowBatch {
+    pub fn new(batch: RecordBatch) -> Self {
+        Self { batch }
+    }
+}
+
+#[pymethods]
+impl PyArrowBatch {
+    #[getter]
+    fn num_rows(&self) -> usize {
+        self.batch.num_rows()
+    }
+
+    #[getter]
+    fn column_names(&self) -> Vec<String> {
+        self.batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect()
+    }
+
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_array__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<(Bound<'py, PyCapsule>, Bound<'py, PyCapsule>)> {
+        // casting to the requested schema is optional in the protocol, the consumer does it
+        let _ = requested_schema;
+        let struct_array = StructArray::from(self.batch.clone());
+        let (ffi_array, ffi_schema) = to_ffi(&struct_array.to_data()).map_err(arrow_error)?;
+        // the capsules release the exported structures when dropped, unless the consumer has
+        // moved them out and marked them as released
+        let schema_capsule =
+            PyCapsule::new(py, ffi_schema, Some(CString::new("arrow_schema").unwrap()))?;
+        let array_capsule =
+            PyCapsule::new(py, ffi_array, Some(CString::new("arrow_array").unwrap()))?;
+        Ok((schema_capsule, array_capsule))
+    }
+
+    fn __len__(&self) -> usize {
+        self.batch.num_rows()
+    }
+}
+
+/// Imports a record batch from any Python object implementing `__arrow_c_array__`, e.g.
+/// `pyarrow.RecordBatch`.
+pub fn record_batch_from_py(batch: &Bound<PyAny>) -> PyResult<RecordBatch> {
+    if !batch.hasattr("__arrow_c_array__")? {
+        return Err(PyTypeError::new_err(
+            "expected an Arrow record batch implementing __arrow_c_array__",
+        ));
+    }
+    let (schema_capsule, array_capsule): (Bound<PyCapsule>, Bound<PyCapsule>) =
+        batch.call_method0("__arrow_c_array__")?.extract()?;
+    // SAFETY: by the Arrow PyCapsule interface, the capsules hold an `ArrowSchema` and an
+    // `ArrowArray`. The array is moved out of its capsule, which marks it as released there.
+    let array_data = unsafe {
+        let schema = &*schema_capsule.pointer().cast::<FFI_ArrowSchema>();
+        let array = FFI_ArrowArray::from_raw(array_capsule.pointer().cast::<FFI_ArrowArray>());
+        from_ffi(array, schema)
+    }
+    .map_err(arrow_error)?;
+    if !matches!(array_data.data_type(), ArrowDataType::Struct(_)) {
+        return Err(PyValueError::new_err(format!(
+            "expected an Arrow record batch, got an array of type {}",
+            array_data.data_type()
+        )));
+    }
+    Ok(RecordBatch::from(StructArray::from(array_data)))
+}
+
+/// Converts a record batch into rows of a static table. Keys are computed from the columns in
+/// `id_from` or, if not given, from the row positions, as for a `pandas` data frame with the
+/// default index. Optional `__time__` and `__diff__` columns set times and diffs of the rows.
+pub fn data_rows_from_record_batch(
+    batch: &RecordBatch,
+    column_names: &[String],
+    dtypes: &[Type],
+    id_from: Option<&[String]>,
+) -> PyResult<Vec<DataRow>> {
+    let mut column_types: HashMap<String, Type> = HashMap::new();
+    for (name, dtype) in column_names.iter().zip(dtypes) {
+        if batch.column_by_name(name).is_none() {
+            return Err(PyValueError::new_err(format!(
+                "column {name:?} is missing in the Arrow batch"
+            )));
+        }
+        column_types.insert(name.clone(), dtype.clone());
+    }
+    for name in id_from.unwrap_or_default() {
+        if !column_types.contains_key(name) {
+            return Err(PyValueError::new_err(format!(
+                "id_from column {name:?} is not one of the table columns"
+            )));
+        }
+    }
+    for name in [TIME_COLUMN, DIFF_COLUMN] {
+        if batch.column_by_name(name).is_some() {
+            column_types.insert(name.to_string(), Type::Int);
+        }
+    }
+
+    columns_into_pathway_values(batch, &column_types)
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let get = |name: &str| match row.get(name) {
+                Some(Ok(value)) => Ok(value.clone()),
+                Some(Err(error)) => Err(PyValueError::new_err(error.to_string())),
+                None => Ok(Value::None),
+            };
+            let values = column_names
+                .iter()
+                .map(|name| get(name))
+                .collect::<PyResult<Vec<_>>>()?;
+            let key = match id_from {
+                Some(id_from) => Key::for_values(
+                    &id_from
+                        .iter()
+                        .map(|name| get(name))
+                        .collect::<PyResult<Vec<_>>>()?,
+                ),
+                None => Key::for_values(&[Value::Int(i64::try_from(index).unwrap())]),
+            };
+            let time = match get(TIME_COLUMN)? {
+                Value::None => Timestamp(0),
+                Value::Int(time) => Timestamp(u64::try_from(time).map_err(|_| {
+                    PyValueError::new_err(format!("Column {TIME_COLUMN} can't be negative."))
+                })?),
+                _ => unreachable!("column {TIME_COLUMN} is parsed as int"),
+            };
+            let diff = match get(DIFF_COLUMN)? {
+                Value::None | Value::Int(1) => 1,
+                Value::Int(-1) => -1,
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "Column {DIFF_COLUMN} can only contain 1 and -1."
+                    )))
+                }
+            };
+            Ok(DataRow {
+                key,
+                values,
+                time,
+                diff,
+                shard: None,
+            })
+        })
+        .collect()
+}
+
+/// Converts rows of a table into a record batch with the given columns, followed by the `id`
+/// column and, if `with_changes` is set, the `__time__` and `__diff__` columns.
+pub fn record_batch_from_data_rows(
+    rows: &[DataRow],
+    column_names: &[String],
+    dtypes: &[Type],
+    with_changes: bool,
+) -> PyResult<RecordBatch> {
+    let settings = writer_settings();
+    let mut fields = Vec::with_capacity(column_names.len() + 3);
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(column_names.len() + 3);
+    for (index, (name, dtype)) in column_names.iter().zip(dtypes).enumerate() {
+        let arrow_type = arrow_data_type(dtype, &settings).map_err(write_error)?;
+        let values: Vec<Value> = rows.iter().map(|row| row.values[index].clone()).collect();
+        arrays.push(array_for_type(&arrow_type, &values).map_err(write_error)?);
+        fields.push(ArrowField::new(name, arrow_type, dtype.can_be_none()));
+    }
+
+    let keys: Vec<Value> = rows.iter().map(|row| Value::Pointer(row.key)).collect();
+    arrays.push(array_for_type(&ArrowDataType::Utf8, &keys).map_err(write_error)?);
+    fields.push(ArrowField::new(ID_COLUMN, ArrowDataType::Utf8, false));
+
+    if !with_changes {
+        return RecordBatch::try_new(Arc::new(ArrowSchema::new(fields)), arrays)
+            .map_err(arrow_error);
+    }
+
+    let times: Vec<Value> = rows
+        .iter()
+        .map(|row| Value::Int(i64::try_from(row.time.0).unwrap()))
+        .collect();
+    arrays.push(array_for_type(&ArrowDataType::Int64, &times).map_err(write_error)?);
+    fields.push(ArrowField::new(TIME_COLUMN, ArrowDataType::Int64, false));
+
+    let diffs: Vec<Value> = rows
+        .iter()
+        .map(|row| Value::Int(i64::try_from(row.diff).unwrap()))
+        .collect();
+    arrays.push(array_for_type(&ArrowDataType::Int64, &diffs).map_err(write_error)?);
+    fields.push(ArrowField::new(DIFF_COLUMN, ArrowDataType::Int64, false));
+
+    RecordBatch::try_new(Arc::new(ArrowSchema::new(fields)), arrays).map_err(arrow_error)
+}
+
+/// Checks that the types of all columns can be represented in Arrow.
+pub fn check_arrow_types(dtypes: &[Type]) -> PyResult<()> {
+    let settings = writer_settings();
+    for dtype in dtypes {
+        arrow_data_type(dtype, &settings).map_err(write_error)?;
+    }
+    Ok(())
+}
+
+/// Applies the updates in `rows`, leaving only the rows present in the final state of the
+/// table, ordered by their keys.
+fn squash_updates(mut rows: Vec<DataRow>) -> PyResult<Vec<DataRow>> {
+    rows.sort_by_key(|row| (row.time, row.diff));
+    let mut state: HashMap<Key, DataRow> = HashMap::new();
+    for row in rows {
+        match row.diff {
+            1 => {
+                if state.contains_key(&row.key) {
+                    return Err(PyKeyError::new_err(format!(
+                        "duplicated entries for key {}",
+                        row.key
+                    )));
+                }
+                state.insert(row.key, row);
+            }
+            -1 => match state.get(&row.key) {
+                Some(present) if present.values == row.values => {
+                    state.remove(&row.key);
+                }
+                _ => {
+                    return Err(PyKeyError::new_err(format!(
+                        "deleting non-existing entry {:?}",
+                        row.values
+                    )))
+                }
+            },
+            diff => {
+                return Err(PyKeyError::new_err(format!("invalid diff value: {diff}")));
+            }
+        }
+    }
+    let mut rows: Vec<DataRow> = state.into_values().collect();
+    rows.sort_by_key(|row| row.key);
+    Ok(rows)
+}
+
+/// Converts the rows captured from a table into a single Arrow batch, without creating Python
+/// objects for the values. With `squash`, only the final state of the table is returned,
+/// otherwise all the updates with their times and diffs.
+#[pyfunction]
+#[pyo3(signature = (captured, column_names, dtypes, *, squash = true))]
+pub fn captured_table_to_arrow(
+    captured: Vec<DataRow>,
+    column_names: Vec<String>,
+    dtypes: Vec<Type>,
+    squash: bool,
+) -> PyResult<PyArrowBatch> {
+    if column_names.len() != dtypes.len() {
+        return Err(PyValueError::new_err(
+            "column_names and dtypes have to be of the same length",
+        ));
+    }
+    check_arrow_types(&dtypes)?;
+    let rows = if squash {
+        squash_updates(captured)?
+    } else {
+        captured
+    };
+    let batch = record_batch_from_data_rows(&rows, &column_names, &dtypes, !squash)?;
+    Ok(PyArrowBatch::new(batch))
+}
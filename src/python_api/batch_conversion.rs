@@ -0,0 +1,228 @@
+// Copyright © 2024 Pathway
+
+//! Converting batches of values between the engine and Python. The engine-side part of the
+//! work (splitting rows into columns, skipping rows with `None` arguments, putting the results
+//! back in place) is done without the GIL, which is then acquired once per batch and held only
+//! for creating and reading Python objects.
+
+use numpy::{PyArray, PyReadonlyArray1};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PySequence, PyString};
+
+use crate::engine::error::DynResult;
+use crate::engine::{Type, Value};
+
+use super::extract_value;
+
+/// Arguments of a batch of UDF calls. Rows with a `None` argument are not passed to the
+/// function if `None` is propagated, their result is `None`.
+pub struct ArgumentBatch<'a> {
+    rows: Vec<&'a [Value]>,
+    skipped: Vec<bool>,
+}
+
+impl<'a> ArgumentBatch<'a> {
+    pub fn new(input: &[&'a [Value]], propagate_none: bool) -> Self {
+        let mut rows = Vec::with_capacity(input.len());
+        let mut skipped = Vec::with_capacity(input.len());
+        for row in input {
+            let skip = propagate_none && row.iter().any(|value| matches!(value, Value::None));
+            if !skip {
+                rows.push(*row);
+            }
+            skipped.push(skip);
+        }
+        Self { rows, skipped }
+    }
+
+    /// Number of rows passed to the function.
+    pub fn n_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Splits the rows into `n_args` columns.
+    pub fn columns(&self, n_args: usize) -> Vec<Vec<Value>> {
+        let mut columns: Vec<Vec<Value>> = (0..n_args)
+            .map(|_| Vec::with_capacity(self.rows.len()))
+            .collect();
+        for row in &self.rows {
+            for (column, value) in columns.iter_mut().zip(row.iter()) {
+                column.push(value.clone());
+            }
+        }
+        columns
+    }
+
+    pub fn rows(&self) -> &[&'a [Value]] {
+        &self.rows
+    }
+
+    /// Puts the results for the rows passed to the function back among the skipped rows.
+    pub fn merge_results(&self, results: Vec<DynResult<Value>>) -> Vec<DynResult<Value>> {
+        if results.len() == self.skipped.len() {
+            return results;
+        }
+        let mut results = results.into_iter();
+        self.skipped
+            .iter()
+            .map(|skip| {
+                if *skip {
+                    Ok(Value::None)
+                } else {
+                    results
+                        .next()
+                        .expect("there should be a result for every row passed to the function")
+                }
+            })
+            .collect()
+    }
+}
+
+/// Types of arguments passed to vectorized UDFs as typed numpy arrays.
+#[derive(Debug, Clone, Copy)]
+enum PrimitiveType {
+    Int,
+    Float,
+    Bool,
+}
+
+impl PrimitiveType {
+    fn for_dtype(dtype: &Type) -> Option<Self> {
+        match dtype {
+            Type::Int => Some(Self::Int),
+            Type::Float => Some(Self::Float),
+            Type::Bool => Some(Self::Bool),
+            _ => None,
+        }
+    }
+}
+
+enum PrimitiveColumn {
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+    Bool(Vec<bool>),
+}
+
+impl PrimitiveColumn {
+    /// Copies a column into a typed buffer. `None` values are replaced with zeros and marked
+    /// in the returned mask.
+    fn from_values(type_: PrimitiveType, column: &[Value]) -> PyResult<(Self, Vec<bool>)> {
+        let mut result = match type_ {
+            PrimitiveType::Int => Self::Int(Vec::with_capacity(column.len())),
+            PrimitiveType::Float => Self::Float(Vec::with_capacity(column.len())),
+            PrimitiveType::Bool => Self::Bool(Vec::with_capacity(column.len())),
+        };
+        let mut mask = Vec::with_capacity(column.len());
+        for value in column {
+            mask.push(matches!(value, Value::None));
+            match (&mut result, value) {
+                (Self::Int(ints), Value::Int(i)) => ints.push(*i),
+                (Self::Int(ints), Value::None) => ints.push(0),
+                (Self::Float(floats), Value::Float(f)) => floats.push(f.0),
+                (Self::Float(floats), Value::None) => floats.push(0.0),
+                (Self::Bool(bools), Value::Bool(b)) => bools.push(*b),
+                (Self::Bool(bools), Value::None) => bools.push(false),
+                _ => {
+                    return Err(PyTypeError::new_err(format!(
+                        "value {value} doesn't match the declared {type_:?} type of the argument"
+                    )))
+                }
+            }
+        }
+        Ok((result, mask))
+    }
+
+    fn into_py(self, py: Python<'_>) -> Bound<'_, PyAny> {
+        match self {
+            Self::Int(ints) => PyArray::from_vec(py, ints).into_any(),
+            Self::Float(floats) => PyArray::from_vec(py, floats).into_any(),
+            Self::Bool(bools) => PyArray::from_vec(py, bools).into_any(),
+        }
+    }
+}
+
+/// Converts a column of a UDF batch into a Python object passed to the UDF.
+///
+/// In the vectorized mode, the way a column is passed depends only on the declared type of
+/// the argument, so that it is the same in every batch. Int, float and bool columns are
+/// passed as numpy arrays, so that the function can operate on them without per-element
+/// conversions, and their optional variants as masked arrays with `None` values masked.
+/// All other columns, as well as all columns in the non-vectorized mode, are passed
+/// as lists.
+pub fn column_into_py<'py>(
+    py: Python<'py>,
+    column: &[Value],
+    dtype: &Type,
+    vectorized: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    if vectorized {
+        let (optional, primitive_type) = match dtype {
+            Type::Optional(arg) => (true, PrimitiveType::for_dtype(arg)),
+            dtype => (false, PrimitiveType::for_dtype(dtype)),
+        };
+        if let Some(primitive_type) = primitive_type {
+            // the typed buffer is filled in a single pass, the array takes its ownership
+            let (primitive, mask) = PrimitiveColumn::from_values(primitive_type, column)?;
+            let data = primitive.into_py(py);
+            if !optional {
+                if mask.contains(&true) {
+                    return Err(PyTypeError::new_err(
+                        "None passed as an argument of a non-optional type",
+                    ));
+                }
+                return Ok(data);
+            }
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("mask", PyArray::from_vec(py, mask))?;
+            return py
+                .import("numpy.ma")?
+                .call_method("masked_array", (data,), Some(&kwargs));
+        }
+    }
+    Ok(PyList::new(py, column)?.into_any())
+}
+
+/// Converts the output of a batch UDF into values. One-dimensional numpy arrays matching
+/// the declared type are read directly, without going through Python objects.
+pub fn extract_column(results: &Bound<PyAny>, dtype: &Type) -> PyResult<Vec<DynResult<Value>>> {
+    match dtype {
+        Type::Int => {
+            if let Ok(array) = results.extract::<PyReadonlyArray1<i64>>() {
+                return Ok(array
+                    .as_array()
+                    .iter()
+                    .map(|i| Ok(Value::Int(*i)))
+                    .collect());
+            }
+        }
+        Type::Float => {
+            if let Ok(array) = results.extract::<PyReadonlyArray1<f64>>() {
+                return Ok(array
+                    .as_array()
+                    .iter()
+                    .map(|f| Ok(Value::from(*f)))
+                    .collect());
+            }
+        }
+        Type::Bool => {
+            if let Ok(array) = results.extract::<PyReadonlyArray1<bool>>() {
+                return Ok(array
+                    .as_array()
+                    .iter()
+                    .map(|b| Ok(Value::Bool(*b)))
+                    .collect());
+            }
+        }
+        _ => {}
+    }
+    if results.is_instance_of::<PyString>() {
+        return Err(PyTypeError::new_err("Can't extract `str` to `Vec`"));
+    }
+    let results = results.downcast::<PySequence>()?;
+    let mut values = Vec::with_capacity(results.len()?);
+    for result in results.try_iter()? {
+        values.push(Ok(extract_value(&result?, dtype)?));
+    }
+    Ok(values)
+}
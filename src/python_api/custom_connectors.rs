@@ -0,0 +1,240 @@
+// Copyright © 2024 Pathway
+
+//! Custom connectors implemented in Python. The records of a source are produced by a Python
+//! iterator, and the records written into a sink are passed to a Python callback.
+//!
+//! The Python code of each connector runs in its own thread, so that a worker doesn't wait for
+//! the GIL while the user code is running. The thread and the connector exchange the records
+//! through a bounded queue: the iterator isn't advanced while the queue is full, and the writes
+//! wait until there is space in it. A slow consumer therefore slows down the producer instead of
+//! the records piling up in memory.
+
+use std::sync::Arc;
+use std::thread;
+
+use crossbeam_channel as channel;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use super::threads::PythonThreadState;
+use crate::connectors::sdk::{ConnectorError, Sink, SinkRecord, Source, SourceRecord};
+
+type SourceMessage = Result<SourceRecord, ConnectorError>;
+
+/// A source reading the records from a Python iterator.
+///
+/// The iterator is created by calling `read` with the position to start from, which is `None` if
+/// the reading starts from the beginning. It yields `(payload, position)` pairs, where both are
+/// `bytes` and the position can be `None` if it didn't change since the previous record.
+pub struct PythonSource {
+    read: Arc<Py<PyAny>>,
+    queue_size: usize,
+    position: Option<Vec<u8>>,
+    receiver: Option<channel::Receiver<SourceMessage>>,
+}
+
+impl PythonSource {
+    pub fn new(read: Arc<Py<PyAny>>, queue_size: usize) -> Self {
+        Self {
+            read,
+            queue_size,
+            position: None,
+            receiver: None,
+        }
+    }
+
+    fn start(&self) -> channel::Receiver<SourceMessage> {
+        let (sender, receiver) = channel::bounded(self.queue_size);
+        let read = self.read.clone();
+        let position = self.position.clone();
+        thread::Builder::new()
+            .name("pathway:python_source".to_owned())
+            .spawn(move || {
+                let thread_state = PythonThreadState::new();
+                if let Err(error) = produce_records(&read, position, &sender) {
+                    sender.send(Err(ConnectorError::other(error))).unwrap_or(());
+                }
+                drop(thread_state);
+            })
+            .expect("python source thread creation should not fail");
+        receiver
+    }
+}
+
+/// Sends the records of the iterator until it's exhausted or the source is dropped.
+fn produce_records(
+    read: &Py<PyAny>,
+    mut position: Option<Vec<u8>>,
+    sender: &channel::Sender<SourceMessage>,
+) -> PyResult<()> {
+    Python::with_gil(|py| {
+        let start_position = position
+            .as_deref()
+            .map(|position| PyBytes::new(py, position));
+        let records = read.bind(py).call1((start_position,))?.try_iter()?;
+        for record in records {
+            let (payload, new_position): (Vec<u8>, Option<Vec<u8>>) = record?.extract()?;
+            if new_position.is_some() {
+                position = new_position;
+            }
+            let record = SourceRecord::new(payload, position.clone().unwrap_or_default());
+            // The GIL is released while waiting for space in the queue
+            if py.allow_threads(|| sender.send(Ok(record))).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    })
+}
+
+impl Source for PythonSource {
+    fn next_record(&mut self) -> Result<Option<SourceRecord>, ConnectorError> {
+        if self.receiver.is_none() {
+            self.receiver = Some(self.start());
+        }
+        let receiver = self
+            .receiver
+            .as_ref()
+            .expect("the source must be started at this point");
+        match receiver.recv() {
+            Ok(message) => message.map(Some),
+            // The thread has finished, so the iterator is exhausted
+            Err(channel::RecvError) => Ok(None),
+        }
+    }
+
+    fn resume(&mut self, position: &[u8]) -> Result<(), ConnectorError> {
+        // An empty position is saved if the iterator never provided one
+        self.position = (!position.is_empty()).then(|| position.to_vec());
+        // Dropping the queue stops the thread started from the previous position, if any
+        self.receiver = None;
+        Ok(())
+    }
+}
+
+type Acknowledgement = channel::Sender<Result<(), ConnectorError>>;
+
+enum SinkMessage {
+    Record(SinkRecord),
+    Flush(Acknowledgement),
+    Finish(Acknowledgement),
+}
+
+/// The Python callables of a [`PythonSink`].
+#[derive(Clone)]
+pub struct PythonSinkCallbacks {
+    /// Called with `(payload, time, diff)` for each payload of the records written.
+    pub write: Arc<Py<PyAny>>,
+    /// Called without arguments at the end of each minibatch.
+    pub flush: Option<Arc<Py<PyAny>>>,
+    /// Called without arguments once all the records have been written.
+    pub finish: Option<Arc<Py<PyAny>>>,
+}
+
+/// A sink passing the records to a Python callback.
+pub struct PythonSink {
+    callbacks: PythonSinkCallbacks,
+    queue_size: usize,
+    sender: Option<channel::Sender<SinkMessage>>,
+}
+
+impl PythonSink {
+    pub fn new(callbacks: PythonSinkCallbacks, queue_size: usize) -> Self {
+        Self {
+            callbacks,
+            queue_size,
+            sender: None,
+        }
+    }
+
+    fn send(&mut self, message: SinkMessage) -> Result<(), ConnectorError> {
+        let sender = self.sender.get_or_insert_with(|| {
+            let (sender, receiver) = channel::bounded(self.queue_size);
+            let callbacks = self.callbacks.clone();
+            thread::Builder::new()
+                .name("pathway:python_sink".to_owned())
+                .spawn(move || {
+                    let thread_state = PythonThreadState::new();
+                    consume_records(&callbacks, &receiver);
+                    drop(thread_state);
+                })
+                .expect("python sink thread creation should not fail");
+            sender
+        });
+        sender
+            .send(message)
+            .map_err(|_| ConnectorError::other("the thread of the Python sink has stopped"))
+    }
+
+    fn call_and_wait(
+        &mut self,
+        message: impl FnOnce(Acknowledgement) -> SinkMessage,
+    ) -> Result<(), ConnectorError> {
+        let (ack_sender, ack_receiver) = channel::bounded(1);
+        self.send(message(ack_sender))?;
+        ack_receiver
+            .recv()
+            .map_err(|_| ConnectorError::other("the thread of the Python sink has stopped"))?
+    }
+}
+
+/// Handles the messages until the sink is dropped. Once the callback fails, the following records
+/// are skipped and the error is reported at the next flush.
+fn consume_records(callbacks: &PythonSinkCallbacks, receiver: &channel::Receiver<SinkMessage>) {
+    let mut error = None;
+    for message in receiver {
+        match message {
+            SinkMessage::Record(record) => {
+                if error.is_none() {
+                    error = write_record(&callbacks.write, record).err();
+                }
+            }
+            SinkMessage::Flush(ack_sender) => {
+                let result = match error.take() {
+                    Some(error) => Err(error),
+                    None => call_optional(callbacks.flush.as_deref()),
+                };
+                ack_sender
+                    .send(result.map_err(ConnectorError::other))
+                    .unwrap_or(());
+            }
+            SinkMessage::Finish(ack_sender) => {
+                let result = call_optional(callbacks.finish.as_deref());
+                ack_sender
+                    .send(result.map_err(ConnectorError::other))
+                    .unwrap_or(());
+            }
+        }
+    }
+}
+
+fn write_record(write: &Py<PyAny>, record: SinkRecord) -> PyResult<()> {
+    Python::with_gil(|py| {
+        for payload in record.payloads {
+            write.call1(py, (PyBytes::new(py, &payload), record.time.0, record.diff))?;
+        }
+        Ok(())
+    })
+}
+
+fn call_optional(callback: Option<&Py<PyAny>>) -> PyResult<()> {
+    if let Some(callback) = callback {
+        Python::with_gil(|py| callback.call0(py))?;
+    }
+    Ok(())
+}
+
+impl Sink for PythonSink {
+    fn write(&mut self, record: SinkRecord) -> Result<(), ConnectorError> {
+        // Waits if the queue is full, until the callback catches up
+        self.send(SinkMessage::Record(record))
+    }
+
+    fn flush(&mut self) -> Result<(), ConnectorError> {
+        self.call_and_wait(SinkMessage::Flush)
+    }
+
+    fn finish(&mut self) -> Result<(), ConnectorError> {
+        self.call_and_wait(SinkMessage::Finish)
+    }
+}
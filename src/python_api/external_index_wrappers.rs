@@ -1,21 +1,43 @@
 // Copyright © 2024 Pathway
 
+use pyo3::exceptions::PyValueError;
 use pyo3::{prelude::*, IntoPyObjectExt};
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use tantivy::tokenizer::Language;
 use usearch::ffi::MetricKind;
 
 use crate::engine::external_index_wrappers::{ExternalIndexData, ExternalIndexQuery};
 use crate::external_integration::brute_force_knn_integration::{
     BruteForceKNNIndexFactory, BruteForceKnnMetricKind,
 };
-use crate::external_integration::tantivy_integration::TantivyIndexFactory;
+#[cfg(feature = "faiss")]
+use crate::external_integration::faiss_integration::{
+    FaissIndexConfig, FaissIndexKind, FaissKNNIndexFactory, FaissMetricKind,
+};
+use crate::external_integration::geo_integration::GeoIndexFactory;
+use crate::external_integration::hybrid_integration::{
+    FusionMethod, HybridIndexFactory, DEFAULT_RRF_K,
+};
+use crate::external_integration::maintenance::{MaintainedIndexFactory, MaintenanceConfig};
+use crate::external_integration::named_vectors_integration::NamedVectorsIndexFactory;
+use crate::external_integration::quantization::{QuantizationConfig, QuantizationKind};
+use crate::external_integration::tantivy_integration::{
+    TantivyAnalyzerConfig, TantivyFieldConfig, TantivyFuzzyConfig, TantivyIndexFactory,
+    TantivySearchConfig, TantivyTokenizerKind,
+};
 use crate::external_integration::usearch_integration::USearchMetricKind;
 #[cfg(not(windows))]
-use crate::external_integration::usearch_integration::USearchKNNIndexFactory;
+use crate::external_integration::usearch_integration::{
+    USearchKNNIndexFactory, USearchSnapshotConfig,
+};
 use crate::external_integration::ExternalIndexFactory;
-use crate::{engine::ColumnPath, python_api::Table};
+use crate::{
+    engine::ColumnPath,
+    python_api::{DataStorage, Table},
+};
 
 #[derive(Clone)]
 #[pyclass(module = "pathway.engine", frozen, name = "ExternalIndexFactory")]
@@ -27,6 +49,19 @@ pub struct PyExternalIndexFactory {
 #[pymethods]
 impl PyExternalIndexFactory {
     #[staticmethod]
+    #[pyo3(signature = (
+        dimensions,
+        reserved_space,
+        metric,
+        connectivity,
+        expansion_add,
+        expansion_search,
+        *,
+        snapshot_storage=None,
+        snapshot_name="usearch".to_string(),
+        snapshot_interval=100_000,
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn usearch_knn_factory(
         dimensions: usize,
         reserved_space: usize,
@@ -34,7 +69,10 @@ impl PyExternalIndexFactory {
         connectivity: usize,
         expansion_add: usize,
         expansion_search: usize,
-    ) -> PyExternalIndexFactory {
+        snapshot_storage: Option<PyRef<DataStorage>>,
+        snapshot_name: String,
+        snapshot_interval: usize,
+    ) -> PyResult<PyExternalIndexFactory> {
         #[cfg(windows)]
         {
             // Use BruteForce on Windows due to USearch access violations
@@ -46,56 +84,389 @@ impl PyExternalIndexFactory {
                 _ => BruteForceKnnMetricKind::L2sq,
             };
             
-            PyExternalIndexFactory {
+            // snapshots are only supported by the USearch index
+            let _ = (snapshot_storage, snapshot_name, snapshot_interval);
+
+            Ok(PyExternalIndexFactory {
                 inner: Arc::new(BruteForceKNNIndexFactory::new(
                     dimensions,
                     reserved_space,
                     reserved_space * 2,
                     brute_force_metric,
                 )),
-            }
+            })
         }
         
         #[cfg(not(windows))]
         {
-            PyExternalIndexFactory {
-                inner: Arc::new(USearchKNNIndexFactory::new(
-                    dimensions,
-                    reserved_space,
-                    metric.0,
-                    connectivity,
-                    expansion_add,
-                    expansion_search,
-                )),
+            let mut factory = USearchKNNIndexFactory::new(
+                dimensions,
+                reserved_space,
+                metric.0,
+                connectivity,
+                expansion_add,
+                expansion_search,
+            );
+            if let Some(storage) = snapshot_storage {
+                factory = factory.with_snapshots(USearchSnapshotConfig {
+                    storage: storage.construct_persistent_storage_config()?,
+                    name: snapshot_name,
+                    interval: snapshot_interval,
+                });
             }
+            Ok(PyExternalIndexFactory {
+                inner: Arc::new(factory),
+            })
         }
     }
 
     #[staticmethod]
-    fn tantivy_factory(ram_budget: usize, in_memory_index: bool) -> PyExternalIndexFactory {
+    #[pyo3(signature = (
+        ram_budget,
+        in_memory_index,
+        *,
+        fields=None,
+        tokenizer="simple",
+        ngram_min=2,
+        ngram_max=3,
+        ngram_prefix_only=false,
+        lowercase=true,
+        stemming_language=None,
+        fuzzy_distance=None,
+        fuzzy_prefix=false,
+        fuzzy_transpose_cost_one=true,
+    ))]
+    #[allow(clippy::too_many_arguments, clippy::fn_params_excessive_bools)]
+    fn tantivy_factory(
+        ram_budget: usize,
+        in_memory_index: bool,
+        fields: Option<Vec<(String, f32)>>,
+        tokenizer: &str,
+        ngram_min: usize,
+        ngram_max: usize,
+        ngram_prefix_only: bool,
+        lowercase: bool,
+        stemming_language: Option<&str>,
+        fuzzy_distance: Option<u8>,
+        fuzzy_prefix: bool,
+        fuzzy_transpose_cost_one: bool,
+    ) -> PyResult<PyExternalIndexFactory> {
+        let tokenizer = match tokenizer {
+            "simple" => TantivyTokenizerKind::Simple,
+            "whitespace" => TantivyTokenizerKind::Whitespace,
+            "raw" => TantivyTokenizerKind::Raw,
+            "ngram" => TantivyTokenizerKind::NGram {
+                min_gram: ngram_min,
+                max_gram: ngram_max,
+                prefix_only: ngram_prefix_only,
+            },
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown tokenizer {other:?}"
+                )))
+            }
+        };
+        let search_config = TantivySearchConfig {
+            fields: fields
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, boost)| TantivyFieldConfig { name, boost })
+                .collect(),
+            analyzer: TantivyAnalyzerConfig {
+                tokenizer,
+                lowercase,
+                stemming_language: stemming_language.map(parse_language).transpose()?,
+            },
+            fuzzy: fuzzy_distance.map(|distance| TantivyFuzzyConfig {
+                distance,
+                prefix: fuzzy_prefix,
+                transpose_cost_one: fuzzy_transpose_cost_one,
+            }),
+        };
+        Ok(PyExternalIndexFactory {
+            inner: Arc::new(
+                TantivyIndexFactory::new(ram_budget, in_memory_index)
+                    .with_search_config(search_config),
+            ),
+        })
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (
+        lexical_factory,
+        vector_factory,
+        *,
+        fusion="rrf",
+        rrf_k=DEFAULT_RRF_K,
+        lexical_weight=0.5,
+        vector_weight=0.5,
+        candidates_multiplier=2,
+    ))]
+    fn hybrid_factory(
+        lexical_factory: PyRef<PyExternalIndexFactory>,
+        vector_factory: PyRef<PyExternalIndexFactory>,
+        fusion: &str,
+        rrf_k: f64,
+        lexical_weight: f64,
+        vector_weight: f64,
+        candidates_multiplier: usize,
+    ) -> PyResult<PyExternalIndexFactory> {
+        let fusion = match fusion {
+            "rrf" => FusionMethod::ReciprocalRank { k: rrf_k },
+            "weighted" => FusionMethod::Weighted {
+                lexical_weight,
+                vector_weight,
+            },
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown fusion method {other:?}, expected \"rrf\" or \"weighted\""
+                )))
+            }
+        };
+        Ok(PyExternalIndexFactory {
+            inner: Arc::new(HybridIndexFactory::new(
+                lexical_factory.inner.clone(),
+                vector_factory.inner.clone(),
+                fusion,
+                candidates_multiplier,
+            )),
+        })
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (spaces, *, candidates_multiplier=2))]
+    fn named_vectors_factory(
+        spaces: HashMap<String, PyRef<PyExternalIndexFactory>>,
+        candidates_multiplier: usize,
+    ) -> PyResult<PyExternalIndexFactory> {
+        if spaces.is_empty() {
+            return Err(PyValueError::new_err(
+                "at least one vector space is required",
+            ));
+        }
+        let spaces = spaces
+            .into_iter()
+            .map(|(name, factory)| (name, factory.inner.clone()))
+            .collect();
+        Ok(PyExternalIndexFactory {
+            inner: Arc::new(NamedVectorsIndexFactory::new(spaces, candidates_multiplier)),
+        })
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (
+        dimensions,
+        *,
+        kind="ivf_flat",
+        metric="l2sq",
+        nlist=1024,
+        nprobe=16,
+        pq_subquantizers=8,
+        pq_bits=8,
+        training_sample_size=None,
+        retrain_growth_factor=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(not(feature = "faiss"), allow(unused_variables))]
+    fn faiss_knn_factory(
+        dimensions: usize,
+        kind: &str,
+        metric: &str,
+        nlist: usize,
+        nprobe: usize,
+        pq_subquantizers: usize,
+        pq_bits: usize,
+        training_sample_size: Option<usize>,
+        retrain_growth_factor: Option<f64>,
+    ) -> PyResult<PyExternalIndexFactory> {
+        #[cfg(feature = "faiss")]
+        {
+            let kind = match kind {
+                "ivf_flat" => FaissIndexKind::IvfFlat,
+                "ivf_pq" => FaissIndexKind::IvfPq {
+                    subquantizers: pq_subquantizers,
+                    bits: pq_bits,
+                },
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "unknown FAISS index kind {other:?}, expected \"ivf_flat\" or \"ivf_pq\""
+                    )))
+                }
+            };
+            let metric = match metric {
+                "l2sq" => FaissMetricKind::L2sq,
+                "inner_product" => FaissMetricKind::InnerProduct,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "unknown FAISS metric {other:?}, expected \"l2sq\" or \"inner_product\""
+                    )))
+                }
+            };
+            let config = FaissIndexConfig {
+                dimensions,
+                kind,
+                metric,
+                nlist,
+                nprobe,
+                // FAISS warns when there are fewer than 39 training vectors per list
+                training_sample_size: training_sample_size.unwrap_or(39 * nlist),
+                retrain_growth_factor,
+            };
+            Ok(PyExternalIndexFactory {
+                inner: Arc::new(FaissKNNIndexFactory::new(config)),
+            })
+        }
+
+        #[cfg(not(feature = "faiss"))]
+        {
+            Err(PyValueError::new_err(
+                "FAISS indices are not available in this build of Pathway",
+            ))
+        }
+    }
+
+    #[pyo3(signature = (*, max_removed_fraction=0.3, min_removals=1000, rebuild_batch_size=1000))]
+    fn with_maintenance(
+        &self,
+        max_removed_fraction: f64,
+        min_removals: usize,
+        rebuild_batch_size: usize,
+    ) -> PyExternalIndexFactory {
+        let config = MaintenanceConfig {
+            max_removed_fraction,
+            min_removals,
+            rebuild_batch_size,
+        };
         PyExternalIndexFactory {
-            inner: Arc::new(TantivyIndexFactory::new(ram_budget, in_memory_index)),
+            inner: Arc::new(MaintainedIndexFactory::new(self.inner.clone(), config)),
         }
     }
 
     #[staticmethod]
+    fn geo_factory() -> PyExternalIndexFactory {
+        PyExternalIndexFactory {
+            inner: Arc::new(GeoIndexFactory),
+        }
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (
+        dimensions,
+        reserved_space,
+        auxiliary_space,
+        metric,
+        *,
+        parallel_chunk_size=None,
+        quantization=None,
+        pq_subquantizers=8,
+        pq_training_sample_size=10_000,
+        rerank=true,
+        rerank_multiplier=4,
+        gpu_device=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn brute_force_knn_factory(
         dimensions: usize,
         reserved_space: usize,
         auxiliary_space: usize,
         metric: BruteForceKnnMetricKind,
-    ) -> PyExternalIndexFactory {
-        PyExternalIndexFactory {
-            inner: Arc::new(BruteForceKNNIndexFactory::new(
-                dimensions,
-                reserved_space,
-                auxiliary_space,
-                metric,
-            )),
+        parallel_chunk_size: Option<usize>,
+        quantization: Option<&str>,
+        pq_subquantizers: usize,
+        pq_training_sample_size: usize,
+        rerank: bool,
+        rerank_multiplier: usize,
+        gpu_device: Option<usize>,
+    ) -> PyResult<PyExternalIndexFactory> {
+        let mut factory =
+            BruteForceKNNIndexFactory::new(dimensions, reserved_space, auxiliary_space, metric);
+        if let Some(chunk_size) = parallel_chunk_size {
+            if chunk_size == 0 {
+                return Err(PyValueError::new_err(
+                    "parallel_chunk_size has to be positive",
+                ));
+            }
+            factory = factory.with_parallel_scan(chunk_size);
+        }
+        if let Some(quantization) = quantization {
+            let kind = match quantization {
+                "int8" => QuantizationKind::ScalarInt8,
+                "pq" => {
+                    if pq_subquantizers == 0 || dimensions % pq_subquantizers != 0 {
+                        return Err(PyValueError::new_err(format!(
+                            "dimensions ({dimensions}) have to be divisible by pq_subquantizers ({pq_subquantizers})"
+                        )));
+                    }
+                    QuantizationKind::Product {
+                        subquantizers: pq_subquantizers,
+                        training_sample_size: pq_training_sample_size,
+                    }
+                }
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "unknown quantization {other:?}, expected \"int8\" or \"pq\""
+                    )))
+                }
+            };
+            factory = factory.with_quantization(QuantizationConfig {
+                kind,
+                rerank,
+                rerank_multiplier: rerank_multiplier.max(1),
+            });
         }
+        if let Some(device_ordinal) = gpu_device {
+            if quantization.is_some() {
+                return Err(PyValueError::new_err(
+                    "gpu_device can't be combined with quantization",
+                ));
+            }
+            #[cfg(feature = "cuda")]
+            {
+                factory = factory.with_gpu(device_ordinal);
+            }
+            #[cfg(not(feature = "cuda"))]
+            {
+                let _ = device_ordinal;
+                return Err(PyValueError::new_err(
+                    "GPU indices are not available in this build of Pathway",
+                ));
+            }
+        }
+        Ok(PyExternalIndexFactory {
+            inner: Arc::new(factory),
+        })
     }
 }
 
+fn parse_language(name: &str) -> PyResult<Language> {
+    let language = match name.to_lowercase().as_str() {
+        "arabic" => Language::Arabic,
+        "danish" => Language::Danish,
+        "dutch" => Language::Dutch,
+        "english" => Language::English,
+        "finnish" => Language::Finnish,
+        "french" => Language::French,
+        "german" => Language::German,
+        "greek" => Language::Greek,
+        "hungarian" => Language::Hungarian,
+        "italian" => Language::Italian,
+        "norwegian" => Language::Norwegian,
+        "portuguese" => Language::Portuguese,
+        "romanian" => Language::Romanian,
+        "russian" => Language::Russian,
+        "spanish" => Language::Spanish,
+        "swedish" => Language::Swedish,
+        "tamil" => Language::Tamil,
+        "turkish" => Language::Turkish,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "stemming is not supported for language {name:?}"
+            )))
+        }
+    };
+    Ok(language)
+}
+
 #[pyclass(module = "pathway.engine", frozen, name = "ExternalIndexData")]
 pub struct PyExternalIndexData {
     pub table: Py<Table>,
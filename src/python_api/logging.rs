@@ -12,6 +12,8 @@ use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
 use pyo3::Python;
 use pyo3_log::{Logger as PyLogger, ResetHandle};
 
+use crate::engine::log_context::{self, LogContext};
+
 use super::threads::PythonThreadState;
 
 struct OwnedMetadata {
@@ -43,6 +45,9 @@ struct OwnedRecord {
     module_path: Option<Cow<'static, str>>,
     file: Option<Cow<'static, str>>,
     line: Option<u32>,
+    // records are passed to Python on the logger thread, so the context of the thread that
+    // emitted the record is captured with it
+    context: LogContext,
 }
 
 impl OwnedRecord {
@@ -77,6 +82,7 @@ impl From<&Record<'_>> for OwnedRecord {
                 .or_else(|| record.file().map(|s| Cow::Owned(s.to_owned())))
                 .or(Some(Cow::Borrowed("<none>"))),
             line: record.line(),
+            context: log_context::current(),
         }
     }
 }
@@ -104,6 +110,7 @@ impl Logger {
                         loop {
                             match receiver.recv() {
                                 Ok(Message::Record(record)) => {
+                                    log_context::set(record.context.clone());
                                     record.with(|record| inner.log(&record));
                                 }
                                 Ok(Message::Flush(ack_sender)) => {
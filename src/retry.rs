@@ -1,17 +1,114 @@
-use std::time::Duration;
+//! Retrying failed operations with an exponential backoff.
+//!
+//! Besides the number of retries, a retry can be limited by the total time spent on the
+//! operation and by a retry budget, shared by all the operations using it. The default budget
+//! is global for the process and is set with `PATHWAY_RETRY_BUDGET_PER_MINUTE`, so that a
+//! failing dependency is not flooded with retries coming from many call sites at once.
+//!
+//! A [`CircuitBreaker`] can be put in front of the retries. Once a dependency keeps failing,
+//! the breaker opens and the calls sharing it fail right away, without being retried, until a
+//! trial call succeeds.
 
-use log::error;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwapOption;
+use log::{error, warn};
+use once_cell::sync::Lazy;
 use rand::{rng, Rng};
 
-const DEFAULT_SLEEP_INITIAL_DURATION: Duration = Duration::from_secs(1);
-const DEFAULT_SLEEP_BACKOFF_FACTOR: f64 = 1.2;
-const DEFAULT_JITTER: Duration = Duration::from_millis(800);
+use crate::env::parse_env_var;
+
+pub(crate) const DEFAULT_SLEEP_INITIAL_DURATION: Duration = Duration::from_secs(1);
+pub(crate) const DEFAULT_SLEEP_BACKOFF_FACTOR: f64 = 1.2;
+pub(crate) const DEFAULT_JITTER: Duration = Duration::from_millis(800);
+
+const RETRY_BUDGET_WINDOW: Duration = Duration::from_secs(60);
+const DEFAULT_CIRCUIT_FAILURE_THRESHOLD: usize = 5;
+const DEFAULT_CIRCUIT_OPEN_DURATION: Duration = Duration::from_secs(30);
+
+struct RetryBudgetWindow {
+    started_at: Instant,
+    retries_used: usize,
+}
+
+/// Limits the number of retries made within a time window by all the operations sharing the
+/// budget.
+#[allow(clippy::module_name_repetitions)]
+pub struct RetryBudget {
+    max_retries: usize,
+    window: Duration,
+    current_window: Mutex<RetryBudgetWindow>,
+}
+
+impl RetryBudget {
+    pub fn new(max_retries: usize, window: Duration) -> Self {
+        Self {
+            max_retries,
+            window,
+            current_window: Mutex::new(RetryBudgetWindow {
+                started_at: Instant::now(),
+                retries_used: 0,
+            }),
+        }
+    }
+
+    /// Takes one retry from the budget. Returns `false` if the budget for the current window
+    /// is exhausted.
+    pub fn try_acquire(&self) -> bool {
+        let mut current_window = self.current_window.lock().unwrap();
+        if current_window.started_at.elapsed() >= self.window {
+            current_window.started_at = Instant::now();
+            current_window.retries_used = 0;
+        }
+        if current_window.retries_used >= self.max_retries {
+            return false;
+        }
+        current_window.retries_used += 1;
+        true
+    }
+}
+
+fn retry_budget_from_env() -> Option<Arc<RetryBudget>> {
+    match parse_env_var::<usize>("PATHWAY_RETRY_BUDGET_PER_MINUTE") {
+        Ok(max_retries) => max_retries
+            .map(|max_retries| Arc::new(RetryBudget::new(max_retries, RETRY_BUDGET_WINDOW))),
+        Err(e) => {
+            warn!("Retry budget is not limited: {e}");
+            None
+        }
+    }
+}
+
+static GLOBAL_RETRY_BUDGET: Lazy<ArcSwapOption<RetryBudget>> =
+    Lazy::new(|| ArcSwapOption::new(retry_budget_from_env()));
+
+/// The budget shared by all the operations retried with the default config. `None` if the
+/// number of retries is not limited.
+pub fn global_retry_budget() -> Option<Arc<RetryBudget>> {
+    GLOBAL_RETRY_BUDGET.load_full()
+}
+
+/// Replaces the global budget with a fresh one. The operations that have already started
+/// keep using the previous budget. `None` doesn't limit the number of retries.
+pub fn set_global_retry_budget(max_retries_per_minute: Option<usize>) {
+    GLOBAL_RETRY_BUDGET.store(
+        max_retries_per_minute
+            .map(|max_retries| Arc::new(RetryBudget::new(max_retries, RETRY_BUDGET_WINDOW))),
+    );
+}
 
 #[allow(clippy::module_name_repetitions)]
 pub struct RetryConfig {
     sleep_duration: Duration,
     backoff_factor: f64,
     jitter: Duration,
+    full_jitter: bool,
+    max_elapsed_time: Option<Duration>,
+    started_at: Instant,
+    budget: Option<Arc<RetryBudget>>,
 }
 
 impl RetryConfig {
@@ -20,13 +117,72 @@ impl RetryConfig {
             sleep_duration,
             backoff_factor,
             jitter,
+            full_jitter: false,
+            max_elapsed_time: None,
+            started_at: Instant::now(),
+            budget: global_retry_budget(),
         }
     }
 
-    pub fn sleep_after_error(&mut self) {
-        std::thread::sleep(self.sleep_duration);
-        self.sleep_duration = self.sleep_duration.mul_f64(self.backoff_factor)
-            + rng().random_range(Duration::ZERO..self.jitter);
+    /// Makes each delay a random duration between zero and the current backoff, instead of
+    /// the backoff with a small jitter added, so that clients failing at the same time don't
+    /// retry at the same time.
+    #[must_use]
+    pub fn with_full_jitter(mut self) -> Self {
+        self.full_jitter = true;
+        self
+    }
+
+    /// Stops retrying once the next retry would start later than `max_elapsed_time` after
+    /// the config was created.
+    #[must_use]
+    pub fn with_max_elapsed_time(mut self, max_elapsed_time: Duration) -> Self {
+        self.max_elapsed_time = Some(max_elapsed_time);
+        self
+    }
+
+    /// Replaces the global retry budget. `None` doesn't limit the number of retries.
+    #[must_use]
+    pub fn with_budget(mut self, budget: Option<Arc<RetryBudget>>) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Returns the delay before the next retry and advances the backoff, or `None` if the
+    /// operation shouldn't be retried anymore.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        let delay = if self.full_jitter {
+            rng().random_range(Duration::ZERO..=self.sleep_duration)
+        } else {
+            self.sleep_duration
+        };
+        if let Some(max_elapsed_time) = self.max_elapsed_time {
+            if self.started_at.elapsed() + delay > max_elapsed_time {
+                return None;
+            }
+        }
+        if let Some(budget) = &self.budget {
+            if !budget.try_acquire() {
+                return None;
+            }
+        }
+        self.sleep_duration = self.sleep_duration.mul_f64(self.backoff_factor);
+        if !self.full_jitter && !self.jitter.is_zero() {
+            self.sleep_duration += rng().random_range(Duration::ZERO..self.jitter);
+        }
+        Some(delay)
+    }
+
+    /// Sleeps before the next retry. Returns `false` without sleeping if the operation
+    /// shouldn't be retried anymore.
+    pub fn sleep_after_error(&mut self) -> bool {
+        match self.next_delay() {
+            Some(delay) => {
+                std::thread::sleep(delay);
+                true
+            }
+            None => false,
+        }
     }
 }
 
@@ -40,22 +196,253 @@ impl Default for RetryConfig {
     }
 }
 
-pub fn execute_with_retries<T, E: std::fmt::Debug>(
+pub fn execute_with_retries<T, E: Debug>(
+    func: impl FnMut() -> Result<T, E>,
+    retry_config: RetryConfig,
+    max_retries: usize,
+) -> Result<T, E> {
+    execute_with_retries_if(func, |_| true, retry_config, max_retries)
+}
+
+/// Same as `execute_with_retries`, but the errors for which `is_retryable` returns `false`
+/// are returned right away.
+pub fn execute_with_retries_if<T, E: Debug>(
     mut func: impl FnMut() -> Result<T, E>,
+    is_retryable: impl Fn(&E) -> bool,
     mut retry_config: RetryConfig,
     max_retries: usize,
 ) -> Result<T, E> {
     let mut exec_result = func();
-    for _ in 0..max_retries {
-        if exec_result.is_ok() {
-            return exec_result;
+    let mut n_retries = 0;
+    while n_retries < max_retries {
+        match exec_result {
+            Ok(_) => return exec_result,
+            Err(ref e) if !is_retryable(e) => return exec_result,
+            Err(_) => {}
+        }
+        if !retry_config.sleep_after_error() {
+            break;
         }
-        retry_config.sleep_after_error();
+        n_retries += 1;
         exec_result = func();
     }
     if let Err(ref e) = exec_result {
-        error!("Operation failed after {max_retries} retries: {e:?}");
+        error!("Operation failed after {n_retries} retries: {e:?}");
     }
 
     exec_result
 }
+
+/// Retry settings of a single connector. The settings that are not set take the defaults of
+/// the connector.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetryPolicy {
+    pub max_retries: Option<usize>,
+    pub initial_delay: Option<Duration>,
+    pub backoff_factor: Option<f64>,
+    pub jitter: Option<Duration>,
+    pub max_elapsed_time: Option<Duration>,
+}
+
+impl RetryPolicy {
+    pub fn max_retries_or(&self, default: usize) -> usize {
+        self.max_retries.unwrap_or(default)
+    }
+
+    /// Creates the config for retrying a single operation.
+    pub fn retry_config(&self) -> RetryConfig {
+        let retry_config = RetryConfig::new(
+            self.initial_delay.unwrap_or(DEFAULT_SLEEP_INITIAL_DURATION),
+            self.backoff_factor.unwrap_or(DEFAULT_SLEEP_BACKOFF_FACTOR),
+            self.jitter.unwrap_or(DEFAULT_JITTER),
+        );
+        match self.max_elapsed_time {
+            Some(max_elapsed_time) => retry_config.with_max_elapsed_time(max_elapsed_time),
+            None => retry_config,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls are made and their consecutive failures are counted.
+    Closed,
+    /// Calls are rejected until the open duration passes.
+    Open,
+    /// A single trial call is made. The circuit closes if it succeeds and opens again if not.
+    HalfOpen,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CircuitBreakerStats {
+    pub state: CircuitState,
+    pub times_opened: u64,
+    pub rejected_calls: u64,
+}
+
+struct CircuitBreakerInner {
+    state: CircuitState,
+    consecutive_failures: usize,
+    opened_at: Instant,
+    times_opened: u64,
+    rejected_calls: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("circuit breaker {0:?} is open, the call was not made")]
+pub struct CircuitOpenError(pub String);
+
+/// Opens after `failure_threshold` consecutive failed calls, so that the calls to a dependency
+/// that is down fail fast instead of being retried.
+pub struct CircuitBreaker {
+    name: String,
+    failure_threshold: usize,
+    open_duration: Duration,
+    inner: Mutex<CircuitBreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: impl Into<String>, failure_threshold: usize, open_duration: Duration) -> Self {
+        Self {
+            name: name.into(),
+            failure_threshold,
+            open_duration,
+            inner: Mutex::new(CircuitBreakerInner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: Instant::now(),
+                times_opened: 0,
+                rejected_calls: 0,
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Checks whether a call can be made now. Once the open duration has passed, a single
+    /// trial call is allowed.
+    pub fn try_acquire(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open if inner.opened_at.elapsed() >= self.open_duration => {
+                inner.state = CircuitState::HalfOpen;
+                true
+            }
+            CircuitState::Open | CircuitState::HalfOpen => {
+                inner.rejected_calls += 1;
+                false
+            }
+        }
+    }
+
+    pub fn on_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+    }
+
+    pub fn on_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        let should_open = match inner.state {
+            CircuitState::Closed => inner.consecutive_failures >= self.failure_threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+        if should_open {
+            warn!(
+                "Circuit breaker {:?} opened after {} consecutive failures",
+                self.name, inner.consecutive_failures
+            );
+            inner.state = CircuitState::Open;
+            inner.opened_at = Instant::now();
+            inner.times_opened += 1;
+        }
+    }
+
+    pub fn stats(&self) -> CircuitBreakerStats {
+        let inner = self.inner.lock().unwrap();
+        CircuitBreakerStats {
+            state: inner.state,
+            times_opened: inner.times_opened,
+            rejected_calls: inner.rejected_calls,
+        }
+    }
+}
+
+static CIRCUIT_BREAKERS: Lazy<Mutex<HashMap<String, Weak<CircuitBreaker>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the breaker shared by all the callers using the same name, e.g. all the workers
+/// writing to the same sink. It is created with the default settings if there is none yet, and
+/// dropped once the last caller drops it.
+pub fn shared_circuit_breaker(name: &str) -> Arc<CircuitBreaker> {
+    let mut circuit_breakers = CIRCUIT_BREAKERS.lock().unwrap();
+    if let Some(circuit_breaker) = circuit_breakers.get(name).and_then(Weak::upgrade) {
+        return circuit_breaker;
+    }
+    let circuit_breaker = Arc::new(CircuitBreaker::new(
+        name,
+        DEFAULT_CIRCUIT_FAILURE_THRESHOLD,
+        DEFAULT_CIRCUIT_OPEN_DURATION,
+    ));
+    circuit_breakers.insert(name.to_string(), Arc::downgrade(&circuit_breaker));
+    circuit_breaker
+}
+
+/// The stats of the shared breakers that are still in use.
+pub fn shared_circuit_breakers_stats() -> Vec<(String, CircuitBreakerStats)> {
+    let mut circuit_breakers = CIRCUIT_BREAKERS.lock().unwrap();
+    circuit_breakers.retain(|_, circuit_breaker| circuit_breaker.strong_count() > 0);
+    circuit_breakers
+        .iter()
+        .filter_map(|(name, circuit_breaker)| {
+            Some((name.clone(), circuit_breaker.upgrade()?.stats()))
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+enum CircuitCallError<E> {
+    Rejected,
+    Failed(E),
+}
+
+/// Same as `execute_with_retries_if`, but every attempt goes through the circuit breaker.
+/// Only the retryable errors are counted as failures, as the other ones mean that the
+/// dependency has answered. If the circuit is open, the error is returned without retrying.
+pub fn execute_with_circuit_breaker<T, E: Debug + From<CircuitOpenError>>(
+    circuit_breaker: &CircuitBreaker,
+    mut func: impl FnMut() -> Result<T, E>,
+    is_retryable: impl Fn(&E) -> bool,
+    retry_config: RetryConfig,
+    max_retries: usize,
+) -> Result<T, E> {
+    execute_with_retries_if(
+        || {
+            if !circuit_breaker.try_acquire() {
+                return Err(CircuitCallError::Rejected);
+            }
+            let result = func();
+            match &result {
+                Err(e) if is_retryable(e) => circuit_breaker.on_failure(),
+                _ => circuit_breaker.on_success(),
+            }
+            result.map_err(CircuitCallError::Failed)
+        },
+        |e| match e {
+            CircuitCallError::Rejected => false,
+            CircuitCallError::Failed(e) => is_retryable(e),
+        },
+        retry_config,
+        max_retries,
+    )
+    .map_err(|e| match e {
+        CircuitCallError::Rejected => CircuitOpenError(circuit_breaker.name().to_string()).into(),
+        CircuitCallError::Failed(e) => e,
+    })
+}
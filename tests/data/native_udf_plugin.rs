@@ -0,0 +1,251 @@
+// Copyright © 2024 Pathway
+
+//! A native UDF plugin used by the tests. It doesn't depend on any crate, so it is built
+//! directly with `rustc --crate-type cdylib`.
+
+use std::cell::Cell;
+use std::ffi::c_char;
+use std::ptr;
+use std::slice;
+
+const TAG_NONE: u8 = 0;
+const TAG_BOOL: u8 = 1;
+const TAG_INT: u8 = 2;
+const TAG_FLOAT: u8 = 3;
+const TAG_STRING: u8 = 4;
+const TAG_BYTES: u8 = 5;
+
+const STATUS_OK: i32 = 0;
+const STATUS_FAILED: i32 = 1;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FfiValue {
+    tag: u8,
+    int: i64,
+    float: f64,
+    data: *const u8,
+    len: usize,
+}
+
+type FfiUdfFn = unsafe extern "C" fn(*const FfiValue, usize, *mut FfiValue) -> i32;
+
+#[repr(C)]
+pub struct FfiUdfDescriptor {
+    name: *const c_char,
+    call: FfiUdfFn,
+}
+
+#[repr(C)]
+pub struct FfiPluginDescriptor {
+    abi_version: u32,
+    functions: *const FfiUdfDescriptor,
+    n_functions: usize,
+    free_value: unsafe extern "C" fn(*mut FfiValue),
+}
+
+// The descriptors only point to static data.
+struct Functions([FfiUdfDescriptor; 8]);
+unsafe impl Sync for Functions {}
+struct Descriptor(FfiPluginDescriptor);
+unsafe impl Sync for Descriptor {}
+
+static FUNCTIONS: Functions = Functions([
+    FfiUdfDescriptor {
+        name: c"add".as_ptr(),
+        call: add,
+    },
+    FfiUdfDescriptor {
+        name: c"negate".as_ptr(),
+        call: negate,
+    },
+    FfiUdfDescriptor {
+        name: c"half".as_ptr(),
+        call: half,
+    },
+    FfiUdfDescriptor {
+        name: c"upper".as_ptr(),
+        call: upper,
+    },
+    FfiUdfDescriptor {
+        name: c"reverse".as_ptr(),
+        call: reverse,
+    },
+    FfiUdfDescriptor {
+        name: c"is_none".as_ptr(),
+        call: is_none,
+    },
+    FfiUdfDescriptor {
+        name: c"fail".as_ptr(),
+        call: fail,
+    },
+    FfiUdfDescriptor {
+        name: c"bad_tag".as_ptr(),
+        call: bad_tag,
+    },
+]);
+
+static DESCRIPTOR: Descriptor = Descriptor(FfiPluginDescriptor {
+    abi_version: 1,
+    functions: ptr::addr_of!(FUNCTIONS.0).cast(),
+    n_functions: 8,
+    free_value,
+});
+
+thread_local! {
+    // the engine releases a result on the thread that produced it
+    static FREED_VALUES: Cell<usize> = const { Cell::new(0) };
+}
+
+#[no_mangle]
+pub extern "C" fn pathway_udf_plugin() -> *const FfiPluginDescriptor {
+    &DESCRIPTOR.0
+}
+
+/// The number of values released by `free_value` on the calling thread.
+#[no_mangle]
+pub extern "C" fn fixture_freed_values() -> usize {
+    FREED_VALUES.with(Cell::get)
+}
+
+const NONE: FfiValue = FfiValue {
+    tag: TAG_NONE,
+    int: 0,
+    float: 0.0,
+    data: ptr::null(),
+    len: 0,
+};
+
+fn owned(tag: u8, data: &[u8]) -> FfiValue {
+    let data: Box<[u8]> = data.into();
+    FfiValue {
+        tag,
+        len: data.len(),
+        data: Box::into_raw(data).cast(),
+        ..NONE
+    }
+}
+
+unsafe extern "C" fn free_value(value: *mut FfiValue) {
+    let value = &mut *value;
+    if matches!(value.tag, TAG_STRING | TAG_BYTES) {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            value.data.cast_mut(),
+            value.len,
+        )));
+    }
+    *value = NONE;
+    FREED_VALUES.with(|freed| freed.set(freed.get() + 1));
+}
+
+unsafe fn args<'a>(args: *const FfiValue, n_args: usize) -> &'a [FfiValue] {
+    if n_args == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(args, n_args)
+    }
+}
+
+unsafe fn data<'a>(value: &FfiValue) -> &'a [u8] {
+    if value.len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(value.data, value.len)
+    }
+}
+
+unsafe fn finish(result: *mut FfiValue, value: Result<FfiValue, &str>) -> i32 {
+    match value {
+        Ok(value) => {
+            *result = value;
+            STATUS_OK
+        }
+        Err(message) => {
+            *result = owned(TAG_STRING, message.as_bytes());
+            STATUS_FAILED
+        }
+    }
+}
+
+unsafe extern "C" fn add(values: *const FfiValue, n_args: usize, result: *mut FfiValue) -> i32 {
+    let value = match args(values, n_args) {
+        [a, b] if a.tag == TAG_INT && b.tag == TAG_INT => Ok(FfiValue {
+            tag: TAG_INT,
+            int: a.int + b.int,
+            ..NONE
+        }),
+        _ => Err("add expects two ints"),
+    };
+    finish(result, value)
+}
+
+unsafe extern "C" fn negate(values: *const FfiValue, n_args: usize, result: *mut FfiValue) -> i32 {
+    let value = match args(values, n_args) {
+        [a] if a.tag == TAG_BOOL => Ok(FfiValue {
+            tag: TAG_BOOL,
+            int: i64::from(a.int == 0),
+            ..NONE
+        }),
+        _ => Err("negate expects a bool"),
+    };
+    finish(result, value)
+}
+
+unsafe extern "C" fn half(values: *const FfiValue, n_args: usize, result: *mut FfiValue) -> i32 {
+    let value = match args(values, n_args) {
+        [a] if a.tag == TAG_FLOAT => Ok(FfiValue {
+            tag: TAG_FLOAT,
+            float: a.float / 2.0,
+            ..NONE
+        }),
+        _ => Err("half expects a float"),
+    };
+    finish(result, value)
+}
+
+unsafe extern "C" fn upper(values: *const FfiValue, n_args: usize, result: *mut FfiValue) -> i32 {
+    let value = match args(values, n_args) {
+        [a] if a.tag == TAG_STRING => match std::str::from_utf8(data(a)) {
+            Ok(text) => Ok(owned(TAG_STRING, text.to_uppercase().as_bytes())),
+            Err(_) => Err("upper got invalid UTF-8"),
+        },
+        _ => Err("upper expects a string"),
+    };
+    finish(result, value)
+}
+
+unsafe extern "C" fn reverse(values: *const FfiValue, n_args: usize, result: *mut FfiValue) -> i32 {
+    let value = match args(values, n_args) {
+        [a] if a.tag == TAG_BYTES => {
+            let mut reversed = data(a).to_vec();
+            reversed.reverse();
+            Ok(owned(TAG_BYTES, &reversed))
+        }
+        _ => Err("reverse expects bytes"),
+    };
+    finish(result, value)
+}
+
+unsafe extern "C" fn is_none(values: *const FfiValue, n_args: usize, result: *mut FfiValue) -> i32 {
+    let value = match args(values, n_args) {
+        [a] => Ok(FfiValue {
+            tag: TAG_BOOL,
+            int: i64::from(a.tag == TAG_NONE),
+            ..NONE
+        }),
+        _ => Err("is_none expects one argument"),
+    };
+    finish(result, value)
+}
+
+unsafe extern "C" fn fail(_values: *const FfiValue, _n_args: usize, result: *mut FfiValue) -> i32 {
+    finish(result, Err("failure requested"))
+}
+
+unsafe extern "C" fn bad_tag(
+    _values: *const FfiValue,
+    _n_args: usize,
+    result: *mut FfiValue,
+) -> i32 {
+    finish(result, Ok(FfiValue { tag: 42, ..NONE }))
+}
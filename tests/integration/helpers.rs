@@ -260,7 +260,13 @@ pub fn create_persistence_manager(
     if recreate {
         let _ = std::fs::remove_dir_all(fs_path);
     }
+    create_rewinding_persistence_manager(fs_path, HashMap::new())
+}
 
+pub fn create_rewinding_persistence_manager(
+    fs_path: &Path,
+    rewind_thresholds: HashMap<PersistentId, Timestamp>,
+) -> Arc<Mutex<WorkerPersistentStorage>> {
     Arc::new(Mutex::new(
         WorkerPersistentStorage::new(
             PersistenceManagerOuterConfig::new(
@@ -270,6 +276,7 @@ pub fn create_persistence_manager(
                 PersistenceMode::Batch,
                 true,
             )
+            .with_rewind_thresholds(rewind_thresholds)
             .into_inner(0, 1),
         )
         .expect("Failed to create persistence manager"),
@@ -6,33 +6,84 @@ mod helpers;
 mod operator_test_utils;
 
 mod test_arrow;
+mod test_arrangement_memory;
+mod test_async_limits;
+mod test_async_runtime;
+mod test_avro;
+mod test_backfill;
+#[cfg(feature = "bench")]
+mod test_bench;
 mod test_bson;
 mod test_bytes;
 mod test_cached_object_storage;
+mod test_cluster_tls;
+mod test_config_file;
 mod test_connector_field_defaults;
+mod test_connector_monitor;
 mod test_connector_sync;
 mod test_dd_distinct_total;
 mod test_debezium;
 mod test_deltalake;
+mod test_discovery;
+mod test_distance_kernels;
 mod test_dsv;
 mod test_dsv_dir;
 mod test_dsv_output;
+mod test_expression;
+mod test_file_exporter;
 mod test_file_kv;
+mod test_file_rotation;
+mod test_fs_helpers;
+mod test_group_operation;
+mod test_handoff;
+mod test_health;
+mod test_idempotency;
+mod test_interning;
 mod test_json_output;
 mod test_jsonlines;
+mod test_lag_throttling;
+mod test_length_prefixed;
+mod test_masking;
 mod test_metadata;
+mod test_msgpack;
+mod test_named_schema;
+mod test_native_udf;
 mod test_null_writer;
 mod test_offsets_storage;
 mod test_operator_persistence;
+mod test_parquet;
+mod test_parquet_output;
 mod test_parser;
 mod test_parser_errors;
+mod test_pausing;
+mod test_pipe;
 mod test_prev_next;
+mod test_prometheus;
+mod test_prometheus_writer;
+mod test_protobuf;
 mod test_psql_output;
 mod test_psql_snapshot;
+mod test_query_endpoint;
+mod test_reload;
+mod test_retry;
+mod test_schema_drift;
+mod test_sdk;
 mod test_seek;
+mod test_sink_pushdown;
+mod test_snapshot_export;
+mod test_spans;
+mod test_spill;
+mod test_sql;
+mod test_sql_merge;
+mod test_sql_polling;
 mod test_sqlite;
 mod test_stream_snapshot;
+mod test_subprocess;
+mod test_telemetry;
 mod test_time;
 mod test_time_column;
 mod test_types;
+mod test_udf_cache;
+mod test_udf_error_policy;
 mod test_value_to_sql;
+mod test_wasm_udf;
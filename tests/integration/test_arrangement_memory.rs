@@ -0,0 +1,53 @@
+// Copyright © 2024 Pathway
+
+use differential_dataflow::input::Input;
+use differential_dataflow::trace::implementations::ord::OrdValSpine;
+use eyre::{eyre, Result};
+use timely::dataflow::operators::Probe;
+
+use pathway_engine::engine::dataflow::memory::{
+    arrangements_memory, set_current_operator, ArrangementMemory, MemoryAccountant,
+};
+use pathway_engine::engine::dataflow::operators::ArrangeWithTypes;
+
+const OPERATOR_ID: usize = 1_000_017;
+
+fn operator_arrangements() -> Vec<ArrangementMemory> {
+    arrangements_memory()
+        .into_iter()
+        .filter(|arrangement| arrangement.operator_id == Some(OPERATOR_ID))
+        .collect()
+}
+
+#[test]
+fn test_arrangements_are_measured() -> Result<()> {
+    timely::execute_directly(move |worker| {
+        let mut accountant = MemoryAccountant::new(worker.index(), Some(1));
+        let (mut input_session, probe) = worker.dataflow(|scope| {
+            let (input_session, input) = scope.new_collection::<(u64, String), isize>();
+            set_current_operator(Some(OPERATOR_ID));
+            let arranged = input.arrange_named::<OrdValSpine<u64, String, _, _>>("Join");
+            set_current_operator(None);
+            (input_session, arranged.stream.probe())
+        });
+
+        input_session.update_at((1, "a".to_string()), 0, 1);
+        input_session.update_at((2, "b".to_string()), 0, 1);
+        input_session.update_at((3, "c".to_string()), 0, 1);
+        input_session.advance_to(1);
+        input_session.flush();
+        worker.step_while(|| probe.less_than(&1));
+
+        accountant.measure();
+        let arrangements = operator_arrangements();
+        assert_eq!(arrangements.len(), 1);
+        assert!(arrangements[0].name.starts_with("Join"));
+        assert_eq!(arrangements[0].updates, 3);
+        assert!(arrangements[0].bytes >= 3 * 8);
+
+        drop(accountant);
+        assert!(operator_arrangements().is_empty());
+        Ok::<_, eyre::Report>(())
+    })
+    .map_err(|e| eyre!("timely error: {e}"))?
+}
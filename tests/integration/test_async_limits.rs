@@ -0,0 +1,118 @@
+// Copyright © 2024 Pathway
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use pathway_engine::async_runtime::{execute_with_limits, AsyncCallLimits, TimeoutPolicy};
+
+#[test]
+fn test_results_keep_input_order() {
+    let inputs = vec![3_u64, 1, 2];
+    let limits = AsyncCallLimits {
+        max_in_flight: Some(2),
+        ..Default::default()
+    };
+    let results = execute_with_limits(
+        &inputs,
+        &limits,
+        |input| {
+            let input = *input;
+            async move {
+                tokio::time::sleep(Duration::from_millis(input * 10)).await;
+                Ok(input * 2)
+            }
+        },
+        || 0,
+    );
+    let results: Vec<u64> = results.into_iter().map(Result::unwrap).collect();
+    assert_eq!(results, vec![6, 2, 4]);
+}
+
+#[test]
+fn test_deduplication_calls_once_per_input() {
+    let calls = AtomicUsize::new(0);
+    let inputs = vec![1_i64, 2, 1, 1, 2];
+    let limits = AsyncCallLimits {
+        deduplicate: true,
+        ..Default::default()
+    };
+    let results = execute_with_limits(
+        &inputs,
+        &limits,
+        |input| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            let input = *input;
+            async move { Ok(input + 10) }
+        },
+        || 0,
+    );
+    let results: Vec<i64> = results.into_iter().map(Result::unwrap).collect();
+    assert_eq!(results, vec![11, 12, 11, 11, 12]);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_timeout_policies() {
+    let inputs = vec![0_u64, 500];
+    let call = |input: &u64| {
+        let input = *input;
+        async move {
+            tokio::time::sleep(Duration::from_millis(input)).await;
+            Ok(input)
+        }
+    };
+
+    let limits = AsyncCallLimits {
+        timeout: Some(Duration::from_millis(50)),
+        ..Default::default()
+    };
+    let results = execute_with_limits(&inputs, &limits, call, || 42);
+    assert_eq!(*results[0].as_ref().unwrap(), 0);
+    assert!(results[1]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("timed out"));
+
+    let limits = AsyncCallLimits {
+        timeout: Some(Duration::from_millis(50)),
+        timeout_policy: TimeoutPolicy::ReturnFallback,
+        ..Default::default()
+    };
+    let results = execute_with_limits(&inputs, &limits, call, || 42);
+    let results: Vec<u64> = results.into_iter().map(Result::unwrap).collect();
+    assert_eq!(results, vec![0, 42]);
+}
+
+struct SetOnDrop<'a>(&'a AtomicBool);
+
+impl Drop for SetOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_timed_out_call_is_dropped() {
+    let dropped = AtomicBool::new(false);
+    let finished = AtomicBool::new(false);
+    let limits = AsyncCallLimits {
+        timeout: Some(Duration::from_millis(50)),
+        ..Default::default()
+    };
+    let (dropped_ref, finished_ref) = (&dropped, &finished);
+    let results = execute_with_limits(
+        &[()],
+        &limits,
+        move |()| async move {
+            let _guard = SetOnDrop(dropped_ref);
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            finished_ref.store(true, Ordering::SeqCst);
+            Ok(())
+        },
+        || (),
+    );
+    assert!(results[0].is_err());
+    assert!(dropped.load(Ordering::SeqCst));
+    assert!(!finished.load(Ordering::SeqCst));
+}
@@ -0,0 +1,214 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+
+use tempfile::tempdir;
+
+use pathway_engine::async_runtime::{
+    create_async_tokio_runtime, group_runtime, runtime_config, set_runtime_config,
+    with_connector_runtime, RuntimeConfig,
+};
+use pathway_engine::connectors::data_format::{
+    Formatter, IdentityFormatter, InnerSchemaField, TransparentParser,
+};
+use pathway_engine::connectors::data_lake::arrow::construct_schema as construct_arrow_schema;
+use pathway_engine::connectors::data_lake::buffering::AppendOnlyColumnBuffer;
+use pathway_engine::connectors::data_lake::{DeltaBatchWriter, MaintenanceMode};
+use pathway_engine::connectors::data_storage::{
+    ConnectorMode, DeltaTableReader, LakeWriter, ObjectDownloader, Writer,
+};
+use pathway_engine::connectors::SessionType;
+use pathway_engine::engine::{Key, Timestamp, Type, Value};
+use pathway_engine::python_api::ValueField;
+
+use crate::helpers::read_data_from_reader;
+
+/// The config is global, so the tests changing it can't run concurrently.
+static CONFIG_LOCK: Mutex<()> = Mutex::new(());
+
+fn with_runtime_config<T>(config: RuntimeConfig, f: impl FnOnce() -> T) -> T {
+    let _lock = CONFIG_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let previous_config = runtime_config();
+    set_runtime_config(config);
+    let result = f();
+    set_runtime_config(previous_config);
+    result
+}
+
+fn runtime_groups(groups: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+    groups
+        .iter()
+        .map(|(group, connectors)| {
+            (
+                (*group).to_string(),
+                connectors.iter().map(ToString::to_string).collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_runtime_config_validation() {
+    assert!(RuntimeConfig::default().validate().is_ok());
+    assert!(RuntimeConfig {
+        worker_threads_per_runtime: Some(0),
+        ..RuntimeConfig::default()
+    }
+    .validate()
+    .is_err());
+    assert!(RuntimeConfig {
+        max_blocking_threads: Some(0),
+        ..RuntimeConfig::default()
+    }
+    .validate()
+    .is_err());
+    assert!(RuntimeConfig {
+        runtime_groups: runtime_groups(&[("a", &["x", "y"]), ("b", &["y"])]),
+        ..RuntimeConfig::default()
+    }
+    .validate()
+    .is_err());
+}
+
+#[test]
+fn test_runtime_uses_config() -> eyre::Result<()> {
+    let config = RuntimeConfig {
+        worker_threads_per_runtime: Some(2),
+        max_blocking_threads: Some(4),
+        thread_name_prefix: Some("test-async".to_string()),
+        ..RuntimeConfig::default()
+    };
+    let runtime = with_runtime_config(config, create_async_tokio_runtime);
+
+    let thread_name = runtime?.block_on(async {
+        tokio::task::spawn_blocking(|| thread::current().name().map(ToString::to_string))
+            .await
+            .unwrap()
+    });
+    assert!(thread_name.unwrap().starts_with("test-async-"));
+    Ok(())
+}
+
+#[test]
+fn test_stalled_runtime_does_not_block_others() {
+    let (stalled_sender, stalled_receiver) = std::sync::mpsc::channel::<()>();
+    let stalled = thread::spawn(move || {
+        let runtime = create_async_tokio_runtime().unwrap();
+        runtime.block_on(async move {
+            // stands for a call to an external service that doesn't respond
+            tokio::task::spawn_blocking(move || stalled_receiver.recv())
+                .await
+                .unwrap()
+        })
+    });
+
+    let runtime = create_async_tokio_runtime().unwrap();
+    let result = runtime.block_on(async { tokio::spawn(async { 42 }).await.unwrap() });
+    assert_eq!(result, 42);
+
+    stalled_sender.send(()).unwrap();
+    stalled.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_connectors_of_group_share_runtime() {
+    let config = RuntimeConfig {
+        thread_name_prefix: Some("test".to_string()),
+        runtime_groups: runtime_groups(&[("enrichment", &["http-a", "http-b"])]),
+        ..RuntimeConfig::default()
+    };
+    with_runtime_config(config, || {
+        let first = with_connector_runtime(Some("http-a"), create_async_tokio_runtime).unwrap();
+        let second = with_connector_runtime(Some("http-b"), create_async_tokio_runtime).unwrap();
+        let other = with_connector_runtime(Some("kafka"), create_async_tokio_runtime).unwrap();
+        let unnamed = create_async_tokio_runtime().unwrap();
+        assert!(first.ptr_eq(&second));
+        assert!(!first.ptr_eq(&other));
+        assert!(!first.ptr_eq(&unnamed));
+        assert!(first.ptr_eq(&group_runtime("enrichment").unwrap()));
+
+        let thread_name = first.block_on(async {
+            tokio::spawn(async { thread::current().name().map(ToString::to_string) })
+                .await
+                .unwrap()
+        });
+        assert!(thread_name.unwrap().starts_with("test-enrichment-"));
+
+        // the runtime of a group lives as long as its connectors
+        drop((first, second));
+        assert!(group_runtime("enrichment").is_none());
+    });
+}
+
+#[test]
+fn test_delta_connectors_use_group_runtime() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let path = test_storage.path().to_str().unwrap();
+    let value_fields = vec![ValueField {
+        name: "field".to_string(),
+        type_: Type::Int,
+        default: None,
+        metadata: None,
+    }];
+    let config = RuntimeConfig {
+        runtime_groups: runtime_groups(&[("lake", &["delta-output", "delta-input"])]),
+        ..RuntimeConfig::default()
+    };
+
+    with_runtime_config(config, || {
+        let batch_writer = with_connector_runtime(Some("delta-output"), || {
+            DeltaBatchWriter::new(
+                path,
+                &value_fields,
+                HashMap::new(),
+                Vec::new(),
+                MaintenanceMode::StreamOfChanges,
+                None,
+            )
+        })?;
+        let lake_runtime = group_runtime("lake").expect("the writer uses the runtime of its group");
+        let schema = construct_arrow_schema(
+            &value_fields,
+            &batch_writer,
+            MaintenanceMode::StreamOfChanges,
+        )?;
+        let buffer = AppendOnlyColumnBuffer::new(Arc::new(schema));
+        let mut writer = LakeWriter::new(Box::new(batch_writer), Box::new(buffer), None)?;
+        let mut formatter = IdentityFormatter::new();
+        for value in [1, 2, 3] {
+            let context =
+                formatter.format(&Key::random(), &[Value::Int(value)], Timestamp(0), 1)?;
+            writer.write(context)?;
+        }
+        writer.flush(true)?;
+
+        let reader = with_connector_runtime(Some("delta-input"), || {
+            DeltaTableReader::new(
+                path,
+                ObjectDownloader::Local,
+                HashMap::new(),
+                HashMap::from([("field".to_string(), Type::Int)]),
+                ConnectorMode::Static,
+                None,
+                true,
+                Vec::new(),
+                false,
+            )
+        })?;
+        assert!(group_runtime("lake").unwrap().ptr_eq(&lake_runtime));
+        let parser = TransparentParser::new(
+            None,
+            vec!["field".to_string()],
+            HashMap::from([("field".to_string(), InnerSchemaField::new(Type::Int, None))]),
+            SessionType::Native,
+        )?;
+        let events = read_data_from_reader(Box::new(reader), Box::new(parser))?;
+        assert_eq!(events.len(), 3);
+
+        drop((writer, lake_runtime));
+        assert!(group_runtime("lake").is_none());
+        Ok(())
+    })
+}
@@ -0,0 +1,249 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+
+use apache_avro::types::Value as AvroValue;
+use apache_avro::{to_avro_datum, Schema as AvroSchema};
+use schema_registry_converter::blocking::schema_registry::SrSettings;
+
+use pathway_engine::connectors::data_format::{
+    split_schema_registry_header, with_schema_registry_header, AvroFormatter, AvroParser,
+    AvroSchemaRegistry, Formatter, InnerSchemaField, ParseError, ParsedEvent, Parser,
+};
+use pathway_engine::connectors::data_storage::{DataEventType, ReaderContext};
+use pathway_engine::connectors::SessionType;
+use pathway_engine::engine::{DateTimeUtc, Key, Timestamp, Type, Value};
+
+use crate::helpers::ReplaceErrors;
+
+const USER_SCHEMA_V1: &str = r#"{
+    "type": "record",
+    "name": "User",
+    "fields": [
+        {"name": "id", "type": "long"},
+        {"name": "name", "type": "string"},
+        {"name": "created_at", "type": {"type": "long", "logicalType": "timestamp-micros"}}
+    ]
+}"#;
+
+const USER_SCHEMA_V2: &str = r#"{
+    "type": "record",
+    "name": "User",
+    "fields": [
+        {"name": "id", "type": "long"},
+        {"name": "name", "type": "string"},
+        {"name": "created_at", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+        {"name": "score", "type": "double", "default": 0.5}
+    ]
+}"#;
+
+// The registry is never contacted, as all the schemas used are already cached
+fn offline_registry() -> AvroSchemaRegistry {
+    AvroSchemaRegistry::new(SrSettings::new("http://localhost:1".to_string()))
+}
+
+fn user_record_v1(id: i64, name: &str, created_at_micros: i64) -> eyre::Result<Vec<u8>> {
+    let schema = AvroSchema::parse_str(USER_SCHEMA_V1)?;
+    let record = AvroValue::Record(vec![
+        ("id".to_string(), AvroValue::Long(id)),
+        ("name".to_string(), AvroValue::String(name.to_string())),
+        (
+            "created_at".to_string(),
+            AvroValue::TimestampMicros(created_at_micros),
+        ),
+    ]);
+    Ok(to_avro_datum(&schema, record)?)
+}
+
+fn user_fields(with_score: bool) -> (Vec<String>, HashMap<String, InnerSchemaField>) {
+    let mut fields = vec![
+        ("id".to_string(), InnerSchemaField::new(Type::Int, None)),
+        (
+            "name".to_string(),
+            InnerSchemaField::new(Type::String, None),
+        ),
+        (
+            "created_at".to_string(),
+            InnerSchemaField::new(Type::DateTimeUtc, None),
+        ),
+    ];
+    if with_score {
+        fields.push((
+            "score".to_string(),
+            InnerSchemaField::new(Type::Float, None),
+        ));
+    }
+    let names = fields.iter().map(|(name, _)| name.clone()).collect();
+    (names, fields.into_iter().collect())
+}
+
+fn parse_message(parser: &mut AvroParser, message: Vec<u8>) -> Result<Vec<Value>, ParseError> {
+    let context = ReaderContext::RawBytes(DataEventType::Insert, message);
+    let mut events = parser.parse(&context).map_err(ParseError::from)?;
+    assert_eq!(events.len(), 1);
+    let ParsedEvent::Insert((_, values)) = events.remove(0).replace_errors() else {
+        panic!("an insertion is expected");
+    };
+    Ok(values)
+}
+
+#[test]
+fn test_schema_registry_header() -> eyre::Result<()> {
+    let message = with_schema_registry_header(258, b"abc");
+    assert_eq!(message, vec![0, 0, 0, 1, 2, b'a', b'b', b'c']);
+    assert_eq!(split_schema_registry_header(&message)?, (258, &b"abc"[..]));
+    assert!(matches!(
+        split_schema_registry_header(&[0, 0, 1]),
+        Err(ParseError::NotSchemaRegistryMessage)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_avro_with_schema_registry() -> eyre::Result<()> {
+    let (field_names, schema) = user_fields(false);
+    let registry = offline_registry().with_schema(7, AvroSchema::parse_str(USER_SCHEMA_V1)?);
+    let mut parser = AvroParser::new(
+        None,
+        field_names,
+        schema,
+        SessionType::Native,
+        None,
+        Some(registry),
+    )?;
+    let message = with_schema_registry_header(7, &user_record_v1(1, "Alice", 1_500_000)?);
+    assert_eq!(
+        parse_message(&mut parser, message)?,
+        vec![
+            Value::Int(1),
+            Value::from("Alice"),
+            Value::DateTimeUtc(DateTimeUtc::new(1_500_000_000)),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_avro_schema_evolution() -> eyre::Result<()> {
+    let (field_names, schema) = user_fields(true);
+    let registry = offline_registry().with_schema(7, AvroSchema::parse_str(USER_SCHEMA_V1)?);
+    let mut parser = AvroParser::new(
+        None,
+        field_names,
+        schema,
+        SessionType::Native,
+        Some(AvroSchema::parse_str(USER_SCHEMA_V2)?),
+        Some(registry),
+    )?;
+
+    // The record written with the older schema gets the default value of the new field
+    let message = with_schema_registry_header(7, &user_record_v1(2, "Bob", 0)?);
+    assert_eq!(
+        parse_message(&mut parser, message)?,
+        vec![
+            Value::Int(2),
+            Value::from("Bob"),
+            Value::DateTimeUtc(DateTimeUtc::new(0)),
+            Value::Float(0.5.into()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_avro_wrong_magic_byte() -> eyre::Result<()> {
+    let (field_names, schema) = user_fields(false);
+    let registry = offline_registry().with_schema(7, AvroSchema::parse_str(USER_SCHEMA_V1)?);
+    let mut parser = AvroParser::new(
+        None,
+        field_names,
+        schema,
+        SessionType::Native,
+        None,
+        Some(registry),
+    )?;
+    let mut message = with_schema_registry_header(7, &user_record_v1(1, "Alice", 0)?);
+    message[0] = 1;
+    assert!(matches!(
+        parse_message(&mut parser, message),
+        Err(ParseError::NotSchemaRegistryMessage)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_avro_requires_schema() {
+    let (field_names, schema) = user_fields(false);
+    let parser = AvroParser::new(None, field_names, schema, SessionType::Native, None, None);
+    assert!(parser.is_err());
+}
+
+#[test]
+fn test_avro_formatter_round_trip() -> eyre::Result<()> {
+    let output_schema = r#"{
+        "type": "record",
+        "name": "Output",
+        "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "name", "type": ["null", "string"]},
+            {"name": "source", "type": "string", "default": "pathway"},
+            {"name": "time", "type": "long"},
+            {"name": "diff", "type": "int"}
+        ]
+    }"#;
+    let mut formatter = AvroFormatter::new(
+        vec!["id".to_string(), "name".to_string()],
+        output_schema.to_string(),
+        None,
+    )?;
+    let context = formatter.format(
+        &Key::random(),
+        &[Value::Int(3), Value::None],
+        Timestamp(10),
+        -1,
+    )?;
+    let payload = context
+        .payloads
+        .into_iter()
+        .next()
+        .expect("a single payload is expected")
+        .into_raw_bytes()?;
+
+    let schema = [
+        ("id".to_string(), InnerSchemaField::new(Type::Int, None)),
+        (
+            "name".to_string(),
+            InnerSchemaField::new(Type::Optional(Type::String.into()), None),
+        ),
+        (
+            "source".to_string(),
+            InnerSchemaField::new(Type::String, None),
+        ),
+        ("time".to_string(), InnerSchemaField::new(Type::Int, None)),
+        ("diff".to_string(), InnerSchemaField::new(Type::Int, None)),
+    ];
+    let mut parser = AvroParser::new(
+        None,
+        schema.iter().map(|(name, _)| name.clone()).collect(),
+        schema.into(),
+        SessionType::Native,
+        Some(AvroSchema::parse_str(output_schema)?),
+        None,
+    )?;
+    assert_eq!(
+        parse_message(&mut parser, payload)?,
+        vec![
+            Value::Int(3),
+            Value::None,
+            Value::from("pathway"),
+            Value::Int(10),
+            Value::Int(-1),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_avro_formatter_requires_record_schema() {
+    assert!(AvroFormatter::new(vec![], r#""string""#.to_string(), None).is_err());
+}
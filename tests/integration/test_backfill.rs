@@ -0,0 +1,165 @@
+// Copyright © 2024 Pathway
+
+use super::helpers::{new_filesystem_reader, read_data_from_reader};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use tempfile::tempdir;
+
+use pathway_engine::connectors::backfill::{BackfillThenStreamReader, SeamBoundary};
+use pathway_engine::connectors::data_format::{
+    InnerSchemaField, JsonLinesParser, ParsedEvent, Parser, TransparentParser,
+};
+use pathway_engine::connectors::data_storage::{
+    ConnectorMode, DataEventType, ReadError, ReadMethod, ReadResult, Reader, ReaderContext,
+    StorageType,
+};
+use pathway_engine::connectors::{OffsetKey, OffsetValue, SessionType};
+use pathway_engine::engine::{Type, Value};
+use pathway_engine::persistence::frontier::OffsetAntichain;
+
+/// Replays the given lines as if they were the messages of a single Kafka partition.
+struct PartitionReader {
+    lines: Vec<String>,
+    position: usize,
+}
+
+impl PartitionReader {
+    fn new(lines: Vec<String>) -> Self {
+        Self { lines, position: 0 }
+    }
+
+    fn offset_key() -> OffsetKey {
+        OffsetKey::Kafka("events".into(), 0)
+    }
+}
+
+impl Reader for PartitionReader {
+    fn read(&mut self) -> Result<ReadResult, ReadError> {
+        let Some(line) = self.lines.get(self.position) else {
+            return Ok(ReadResult::Finished);
+        };
+        let offset = (
+            Self::offset_key(),
+            OffsetValue::KafkaOffset(self.position.try_into().unwrap()),
+        );
+        self.position += 1;
+        Ok(ReadResult::Data(
+            ReaderContext::from_raw_bytes(DataEventType::Insert, line.as_bytes().to_vec()),
+            offset,
+        ))
+    }
+
+    fn seek(&mut self, frontier: &OffsetAntichain) -> Result<(), ReadError> {
+        if let Some(OffsetValue::KafkaOffset(offset)) = frontier.get_offset(&Self::offset_key()) {
+            self.position = usize::try_from(*offset).unwrap() + 1;
+        }
+        Ok(())
+    }
+
+    fn storage_type(&self) -> StorageType {
+        StorageType::Kafka
+    }
+}
+
+fn field_names() -> Vec<String> {
+    vec!["id".to_string(), "ts".to_string()]
+}
+
+fn schema() -> HashMap<String, InnerSchemaField> {
+    HashMap::from([
+        ("id".to_string(), InnerSchemaField::new(Type::String, None)),
+        ("ts".to_string(), InnerSchemaField::new(Type::Int, None)),
+    ])
+}
+
+fn json_parser() -> Box<dyn Parser> {
+    Box::new(
+        JsonLinesParser::new(
+            None,
+            field_names(),
+            HashMap::new(),
+            true,
+            schema(),
+            SessionType::Native,
+            None,
+        )
+        .unwrap(),
+    )
+}
+
+fn rows(range: std::ops::RangeInclusive<i64>) -> Vec<String> {
+    range
+        .map(|ts| format!(r#"{{"id": "row-{ts}", "ts": {ts}}}"#))
+        .collect()
+}
+
+fn backfill_then_stream(
+    backfill_path: &Path,
+    stream: PartitionReader,
+) -> eyre::Result<BackfillThenStreamReader> {
+    let backfill = new_filesystem_reader(
+        backfill_path.to_str().unwrap(),
+        ConnectorMode::Static,
+        ReadMethod::ByLine,
+        "*",
+        false,
+    )?;
+    Ok(BackfillThenStreamReader::new(
+        (Box::new(backfill), json_parser()),
+        (Box::new(stream), json_parser()),
+        field_names(),
+        SeamBoundary::new("ts".to_string(), Value::Int(4)),
+    ))
+}
+
+fn read_timestamps(reader: BackfillThenStreamReader) -> eyre::Result<Vec<i64>> {
+    let parser = TransparentParser::new(None, field_names(), schema(), SessionType::Native)?;
+    let events = read_data_from_reader(Box::new(reader), Box::new(parser))?;
+    Ok(events
+        .into_iter()
+        .map(|event| match event {
+            ParsedEvent::Insert((_, values)) => {
+                let ts = values[1].as_int().unwrap();
+                assert_eq!(values[0], Value::from(format!("row-{ts}").as_str()));
+                ts
+            }
+            event => panic!("unexpected event: {event:?}"),
+        })
+        .collect())
+}
+
+#[test]
+fn test_rows_are_taken_once_across_the_seam() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let backfill_path = test_storage.path().join("backfill.jsonl");
+    let mut backfill_file = File::create(&backfill_path)?;
+    for row in rows(1..=5) {
+        writeln!(backfill_file, "{row}")?;
+    }
+
+    let reader = backfill_then_stream(&backfill_path, PartitionReader::new(rows(2..=7)))?;
+    assert_eq!(read_timestamps(reader)?, [1, 2, 3, 4, 5, 6, 7]);
+    Ok(())
+}
+
+#[test]
+fn test_backfill_is_skipped_after_the_stream_started() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let backfill_path = test_storage.path().join("backfill.jsonl");
+    let mut backfill_file = File::create(&backfill_path)?;
+    for row in rows(1..=5) {
+        writeln!(backfill_file, "{row}")?;
+    }
+
+    let mut reader = backfill_then_stream(&backfill_path, PartitionReader::new(rows(2..=7)))?;
+    let mut frontier = OffsetAntichain::new();
+    // The stream has been read up to the row with the timestamp 5
+    frontier.advance_offset(PartitionReader::offset_key(), OffsetValue::KafkaOffset(3));
+    reader.seek(&frontier)?;
+    assert_eq!(read_timestamps(reader)?, [6, 7]);
+    Ok(())
+}
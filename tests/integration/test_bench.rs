@@ -0,0 +1,85 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashSet;
+
+use assert_matches::assert_matches;
+
+use pathway_engine::bench::generators::{SyntheticOrder, SyntheticSource};
+use pathway_engine::bench::{run_benchmark, BenchConfig, BenchError, PipelineShape};
+
+#[test]
+fn test_source_is_deterministic() {
+    let first: Vec<_> = SyntheticSource::new(7, 100, 50).collect();
+    let second: Vec<_> = SyntheticSource::new(7, 100, 50).collect();
+    assert_eq!(first, second);
+    assert_eq!(first.len(), 50);
+    assert!(first.iter().all(|order| (0..100).contains(&order.user)));
+
+    let other_seed: Vec<_> = SyntheticSource::new(8, 100, 50).collect();
+    assert_ne!(first, other_seed);
+}
+
+#[test]
+fn test_source_split_between_workers() {
+    let all: Vec<_> = SyntheticSource::new(3, 10, 101).collect();
+    let mut split: Vec<SyntheticOrder> = (0..4)
+        .flat_map(|worker| SyntheticSource::new(3, 10, 101).for_worker(worker, 4))
+        .collect();
+    split.sort_by_key(|order| order.id);
+    assert_eq!(all, split);
+}
+
+#[test]
+fn test_json_line_matches_values() -> eyre::Result<()> {
+    let order = SyntheticOrder::generate(1, 42, 10);
+    let parsed: serde_json::Value = serde_json::from_slice(&order.to_json_line())?;
+    assert_eq!(parsed["id"], 42);
+    assert_eq!(parsed["user"], order.user);
+    assert_eq!(parsed["tag"], order.tag);
+    Ok(())
+}
+
+#[test]
+fn test_shape_names() {
+    let names: HashSet<_> = PipelineShape::ALL
+        .iter()
+        .map(|shape| shape.as_str())
+        .collect();
+    assert_eq!(names.len(), PipelineShape::ALL.len());
+    for shape in PipelineShape::ALL {
+        assert_eq!(shape.to_string().parse::<PipelineShape>(), Ok(shape));
+    }
+    assert!("sort-heavy".parse::<PipelineShape>().is_err());
+}
+
+#[test]
+fn test_all_shapes_run() -> eyre::Result<()> {
+    for shape in PipelineShape::ALL {
+        for workers in [1, 2] {
+            let config = BenchConfig::new(shape, 2_000)
+                .with_batch_size(300)
+                .with_users(50)
+                .with_workers(workers);
+            let report = run_benchmark(config)?;
+            assert!(report.output_updates > 0, "no output in {report}");
+            assert!(report.rows_per_second() > 0.0);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_worker_without_rows() -> eyre::Result<()> {
+    let config = BenchConfig::new(PipelineShape::WindowHeavy, 1)
+        .with_users(1)
+        .with_workers(3);
+    let report = run_benchmark(config)?;
+    assert!(report.output_updates > 0);
+    Ok(())
+}
+
+#[test]
+fn test_invalid_config() {
+    let config = BenchConfig::new(PipelineShape::JoinHeavy, 10).with_batch_size(0);
+    assert_matches!(run_benchmark(config), Err(BenchError::InvalidConfig));
+}
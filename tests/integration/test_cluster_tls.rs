@@ -0,0 +1,92 @@
+// Copyright © 2024 Pathway
+
+use std::cell::RefCell;
+use std::net::TcpListener;
+use std::path::Path;
+use std::rc::Rc;
+use std::thread;
+
+use timely::dataflow::operators::{Exchange, Inspect, Probe, ToStream};
+use timely::{CommunicationConfig, Config as TimelyConfig, WorkerConfig};
+
+use pathway_engine::engine::dataflow::cluster_tls::{execute, ClusterTlsConfig};
+
+fn tls_config(name: &str) -> ClusterTlsConfig {
+    let data = Path::new("tests/data/cluster_tls");
+    ClusterTlsConfig {
+        certificate_path: data.join(format!("{name}.pem")),
+        key_path: data.join(format!("{name}.key")),
+        ca_path: data.join("ca.pem"),
+        server_name: None,
+    }
+}
+
+fn free_addresses(count: usize) -> Vec<String> {
+    let listeners: Vec<_> = (0..count)
+        .map(|_| TcpListener::bind("127.0.0.1:0").unwrap())
+        .collect();
+    listeners
+        .iter()
+        .map(|listener| listener.local_addr().unwrap().to_string())
+        .collect()
+}
+
+/// Runs a cluster of two processes, each with the certificate of the given name, in which every
+/// process sends 100 numbers to the process they belong to.
+fn run_cluster(names: [&'static str; 2]) -> Vec<Result<Vec<u64>, String>> {
+    let addresses = free_addresses(names.len());
+    let handles: Vec<_> = names
+        .into_iter()
+        .enumerate()
+        .map(|(process, name)| {
+            let addresses = addresses.clone();
+            thread::spawn(move || {
+                let config = TimelyConfig {
+                    communication: CommunicationConfig::Cluster {
+                        threads: 1,
+                        process,
+                        addresses,
+                        report: false,
+                        log_fn: Box::new(|_| None),
+                    },
+                    worker: WorkerConfig::default(),
+                };
+                let guards = execute(config, Some(&tls_config(name)), |worker| {
+                    let index = worker.index() as u64;
+                    let received = Rc::new(RefCell::new(Vec::new()));
+                    let sink = received.clone();
+                    worker.dataflow::<u64, _, _>(move |scope| {
+                        (0..100)
+                            .map(move |x| x * 2 + index)
+                            .to_stream(scope)
+                            .exchange(|x| *x)
+                            .inspect(move |x| sink.borrow_mut().push(*x))
+                            .probe();
+                    });
+                    while worker.step() {}
+                    received.take()
+                })?;
+                Ok(guards.join().pop().unwrap().unwrap())
+            })
+        })
+        .collect();
+    handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_tls_cluster_exchanges_data() {
+    for (process, result) in run_cluster(["node", "node"]).into_iter().enumerate() {
+        let received = result.unwrap();
+        assert_eq!(received.len(), 100);
+        assert!(received.iter().all(|x| x % 2 == process as u64));
+    }
+}
+
+#[test]
+fn test_tls_cluster_rejects_untrusted_certificate() {
+    let results = run_cluster(["node", "intruder"]);
+    assert!(results.iter().all(Result::is_err), "{results:?}");
+}
@@ -0,0 +1,115 @@
+// Copyright © 2024 Pathway
+
+use std::fs;
+
+use pathway_engine::env::{ConfigFile, ConfigValue, Error};
+
+#[test]
+fn test_keys_map_to_env_vars() -> eyre::Result<()> {
+    let config_file = ConfigFile::from_toml_str(
+        r#"
+        [runtime]
+        threads = 4
+
+        [persistence]
+        mode = "speedrun"
+        continue_after_replay = true
+
+        [telemetry]
+        trace_sample_ratio = 0.5
+
+        [license]
+        key = "demo-license-key-with-telemetry"
+        "#,
+    )?;
+    assert_eq!(
+        config_file.get("PATHWAY_THREADS"),
+        Some(&ConfigValue {
+            key: "runtime.threads".to_string(),
+            value: "4".to_string(),
+        })
+    );
+    assert_eq!(
+        config_file.get("PATHWAY_PERSISTENCE_MODE").unwrap().value,
+        "speedrun"
+    );
+    assert_eq!(
+        config_file
+            .get("PATHWAY_CONTINUE_AFTER_REPLAY")
+            .unwrap()
+            .value,
+        "true"
+    );
+    assert_eq!(
+        config_file.get("PATHWAY_TRACE_SAMPLE_RATIO").unwrap().value,
+        "0.5"
+    );
+    assert_eq!(
+        config_file.get("PATHWAY_LICENSE_KEY").unwrap().value,
+        "demo-license-key-with-telemetry"
+    );
+    assert_eq!(config_file.get("PATHWAY_PROCESSES"), None);
+    Ok(())
+}
+
+#[test]
+fn test_lists_and_attributes_are_formatted_like_env_vars() -> eyre::Result<()> {
+    let config_file = ConfigFile::from_toml_str(
+        r#"
+        [telemetry]
+        metric_allowlist = ["process.*", "input.latency"]
+        resource_attributes = { team = "search", environment = "staging" }
+        "#,
+    )?;
+    assert_eq!(
+        config_file.get("PATHWAY_METRIC_ALLOWLIST").unwrap().value,
+        "process.*,input.latency"
+    );
+    assert_eq!(
+        config_file
+            .get("PATHWAY_RESOURCE_ATTRIBUTES")
+            .unwrap()
+            .value,
+        "environment=staging,team=search"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_unknown_key_is_named_in_error() {
+    let error = ConfigFile::from_toml_str("[runtime]\nthreadz = 4\n").unwrap_err();
+    assert!(matches!(&error, Error::UnknownConfigKey(key) if key == "runtime.threadz"));
+    assert!(error.to_string().contains("runtime.threadz"));
+}
+
+#[test]
+fn test_invalid_value_is_named_in_error() {
+    let error = ConfigFile::from_toml_str("[runtime]\nthreads = [[1], [2]]\n").unwrap_err();
+    assert!(matches!(&error, Error::InvalidConfigValue(key, _) if key == "runtime.threads"));
+
+    let error = ConfigFile::from_toml_str("threads = 4\n").unwrap_err();
+    assert!(matches!(&error, Error::InvalidConfigValue(key, _) if key == "threads"));
+}
+
+#[test]
+fn test_load_from_file() -> eyre::Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = directory.path().join("pathway.toml");
+    fs::write(&path, "[runtime]\nprocesses = 2\n")?;
+    let config_file = ConfigFile::load(&path)?;
+    assert_eq!(config_file.get("PATHWAY_PROCESSES").unwrap().value, "2");
+
+    let yaml_path = directory.path().join("pathway.yaml");
+    fs::write(&yaml_path, "runtime:\n  processes: 2\n")?;
+    assert!(matches!(
+        ConfigFile::load(&yaml_path),
+        Err(Error::UnsupportedConfigFormat(_))
+    ));
+
+    fs::write(&path, "[runtime\n")?;
+    assert!(matches!(
+        ConfigFile::load(&path),
+        Err(Error::ConfigFileParsingFailed(_, _))
+    ));
+    Ok(())
+}
@@ -0,0 +1,75 @@
+// Copyright © 2024 Pathway
+
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+use pathway_engine::connectors::monitoring::{BackfillProgressTracker, ConnectorMonitor};
+use pathway_engine::engine::dataflow::monitoring::processing_lag;
+use pathway_engine::engine::Timestamp;
+
+#[test]
+fn test_commit_records_watermark_and_epoch_duration() {
+    let mut monitor =
+        ConnectorMonitor::new("input".to_string(), BackfillProgressTracker::default());
+    assert_eq!(monitor.get_stats().time, None);
+    assert_eq!(monitor.get_stats().epoch_duration, None);
+
+    monitor.increment();
+    monitor.commit(Timestamp(10));
+    let stats = monitor.get_stats();
+    assert_eq!(stats.time, Some(Timestamp(10)));
+    // there is no previous commit to measure the epoch from
+    assert_eq!(stats.epoch_duration, None);
+    assert_eq!(stats.num_messages_recently_committed, 1);
+
+    sleep(Duration::from_millis(20));
+    monitor.commit(Timestamp(12));
+    let stats = monitor.get_stats();
+    assert_eq!(stats.time, Some(Timestamp(12)));
+    assert!(stats.epoch_duration.unwrap() >= 20);
+}
+
+#[test]
+fn test_backfill_progress() {
+    let tracker = BackfillProgressTracker::default();
+    let monitor = ConnectorMonitor::new("input".to_string(), tracker.clone());
+    // nothing is reported by the readers that don't track the backfill
+    assert!(monitor.get_stats().backfill.is_none());
+
+    tracker.on_object_discovered(100);
+    tracker.on_object_discovered(300);
+    let progress = monitor.get_stats().backfill.unwrap();
+    assert_eq!(progress.objects_discovered, 2);
+    assert_eq!(progress.bytes_discovered, 400);
+    assert_eq!(progress.objects_processed, 0);
+    assert!(!progress.finished);
+    // there is no rate to estimate from yet
+    assert_eq!(progress.eta, None);
+
+    sleep(Duration::from_millis(20));
+    tracker.on_object_processed(100);
+    let progress = monitor.get_stats().backfill.unwrap();
+    assert_eq!(progress.objects_processed, 1);
+    assert_eq!(progress.bytes_processed, 100);
+    // the remaining 300 bytes take about three times as long as the first 100
+    assert!(progress.eta.unwrap() >= 60);
+
+    tracker.on_object_processed(300);
+    tracker.finish();
+    // the objects found after the backfill are not a part of it
+    tracker.on_object_discovered(50);
+    let progress = monitor.get_stats().backfill.unwrap();
+    assert_eq!(progress.objects_processed, 2);
+    assert_eq!(progress.objects_discovered, 2);
+    assert!(progress.finished);
+    assert_eq!(progress.eta, None);
+}
+
+#[test]
+fn test_processing_lag() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_millis(1_000);
+    assert_eq!(processing_lag(Timestamp(400), now), 600);
+    assert_eq!(processing_lag(Timestamp(1_000), now), 0);
+    // times from the future don't make the lag negative
+    assert_eq!(processing_lag(Timestamp(1_500), now), 0);
+}
@@ -10,11 +10,12 @@ use std::io::{BufRead, BufReader};
 use assert_matches::assert_matches;
 
 use pathway_engine::connectors::data_format::{
-    DebeziumDBType, DebeziumMessageParser, ParseError, ParsedEvent, Parser,
+    DebeziumDBType, DebeziumFormatter, DebeziumMessageParser, Formatter, ParseError, ParsedEvent,
+    Parser,
 };
 use pathway_engine::connectors::data_storage::{ConnectorMode, ReadMethod, ReaderContext};
 use pathway_engine::connectors::SessionType;
-use pathway_engine::engine::Value;
+use pathway_engine::engine::{Key, Timestamp, Value};
 
 #[test]
 fn test_debezium_reads_ok() -> eyre::Result<()> {
@@ -231,3 +232,72 @@ fn test_postgres_with_empty_pkey() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_debezium_output_format() -> eyre::Result<()> {
+    let mut formatter = DebeziumFormatter::new(vec!["first_name".to_string(), "age".to_string()]);
+    let values = [Value::from("Sally"), Value::Int(30)];
+
+    let inserted = formatter.format(&Key::random(), &values, Timestamp(4), 1)?;
+    assert_eq!(inserted.payloads.len(), 1);
+    let event: serde_json::Value =
+        serde_json::from_slice(&inserted.payloads[0].clone().into_raw_bytes()?)?;
+    assert_eq!(event["schema"], serde_json::Value::Null);
+    let payload = &event["payload"];
+    assert_eq!(payload["op"], "c");
+    assert_eq!(payload["before"], serde_json::Value::Null);
+    assert_eq!(
+        payload["after"],
+        serde_json::json!({"first_name": "Sally", "age": 30})
+    );
+    assert_eq!(payload["source"]["time"], 4);
+    assert!(payload["ts_ms"].is_u64());
+
+    let deleted = formatter.format(&Key::random(), &values, Timestamp(6), -1)?;
+    let event: serde_json::Value =
+        serde_json::from_slice(&deleted.payloads[0].clone().into_raw_bytes()?)?;
+    assert_eq!(event["payload"]["op"], "d");
+    assert_eq!(event["payload"]["after"], serde_json::Value::Null);
+    assert_eq!(
+        event["payload"]["before"],
+        serde_json::json!({"first_name": "Sally", "age": 30})
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_debezium_output_is_parsed_back() -> eyre::Result<()> {
+    let mut formatter = DebeziumFormatter::new(vec!["first_name".to_string(), "age".to_string()]);
+    let mut parser = DebeziumMessageParser::new(
+        None,
+        vec!["first_name".to_string(), "age".to_string()],
+        DebeziumMessageParser::standard_separator(),
+        DebeziumDBType::Postgres,
+    );
+    let values = [Value::from("Sally"), Value::Int(30)];
+
+    let mut changelog = Vec::new();
+    for diff in [1, -1] {
+        let formatted = formatter.format(&Key::random(), &values, Timestamp(0), diff)?;
+        for payload in formatted.payloads {
+            let context = ReaderContext::KeyValue((None, Some(payload.into_raw_bytes()?)));
+            changelog.extend(
+                parser
+                    .parse(&context)
+                    .map_err(ParseError::from)?
+                    .into_iter()
+                    .map(|entry| entry.replace_errors()),
+            );
+        }
+    }
+    assert_eq!(
+        changelog,
+        vec![
+            ParsedEvent::Insert((None, values.to_vec())),
+            ParsedEvent::Delete((None, values.to_vec())),
+        ]
+    );
+
+    Ok(())
+}
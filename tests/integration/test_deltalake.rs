@@ -5,7 +5,12 @@ use std::path::Path;
 use std::sync::Arc;
 
 use assert_matches::assert_matches;
+use deltalake::arrow::array::{Int64Array, RecordBatch, StringArray};
+use deltalake::arrow::datatypes::{DataType as ArrowDataType, Field as ArrowField, Schema};
 use deltalake::datafusion::parquet::file::reader::SerializedFileReader;
+use deltalake::datafusion::prelude::{col, lit};
+use deltalake::kernel::{DataType as DeltaDataType, PrimitiveType, StructField};
+use deltalake::{DeltaOps, TableProperty};
 use ndarray::ArrayD;
 use serde_json::json;
 use tempfile::tempdir;
@@ -126,6 +131,7 @@ fn read_with_connector(path: &str, type_: &Type) -> Result<Vec<Value>> {
         None,
         true,
         Vec::new(),
+        false,
     )
     .unwrap();
     let parser =
@@ -688,3 +694,99 @@ fn test_snapshot_mode() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_change_data_feed() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let path = test_storage.path().to_str().unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let table = runtime.block_on(async {
+        let table = DeltaOps::try_from_uri(path)
+            .await?
+            .create()
+            .with_columns(vec![
+                StructField::new("key", DeltaDataType::Primitive(PrimitiveType::Long), false),
+                StructField::new(
+                    "value",
+                    DeltaDataType::Primitive(PrimitiveType::String),
+                    false,
+                ),
+            ])
+            .with_configuration_property(TableProperty::EnableChangeDataFeed, Some("true"))
+            .await?;
+        let schema = Arc::new(Schema::new(vec![
+            ArrowField::new("key", ArrowDataType::Int64, false),
+            ArrowField::new("value", ArrowDataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec!["a", "b", "c"])),
+            ],
+        )?;
+        Ok::<_, eyre::Report>(DeltaOps(table).write(vec![batch]).await?)
+    })?;
+
+    let type_map = HashMap::from([
+        ("key".to_string(), Type::Int),
+        ("value".to_string(), Type::String),
+    ]);
+    let reader = DeltaTableReader::new(
+        path,
+        ObjectDownloader::Local,
+        HashMap::new(),
+        type_map,
+        ConnectorMode::Static,
+        None,
+        true,
+        Vec::new(),
+        true,
+    )?;
+
+    // The changes are made after the snapshot is taken, so they come from the change data feed
+    runtime.block_on(async {
+        let (table, _) = DeltaOps(table)
+            .delete()
+            .with_predicate(col("key").eq(lit(2_i64)))
+            .await?;
+        DeltaOps(table)
+            .update()
+            .with_predicate(col("key").eq(lit(3_i64)))
+            .with_update("value", lit("z"))
+            .await?;
+        Ok::<_, eyre::Report>(())
+    })?;
+
+    let schema = HashMap::from([
+        ("key".to_string(), InnerSchemaField::new(Type::Int, None)),
+        (
+            "value".to_string(),
+            InnerSchemaField::new(Type::String, None),
+        ),
+    ]);
+    let parser = TransparentParser::new(
+        Some(vec!["key".to_string()]),
+        vec!["key".to_string(), "value".to_string()],
+        schema,
+        SessionType::Native,
+    )?;
+    let events: Vec<_> = read_data_from_reader(Box::new(reader), Box::new(parser))?
+        .into_iter()
+        .map(|event| match event {
+            ParsedEvent::Insert((_, values)) => (1, values),
+            ParsedEvent::Delete((_, values)) => (-1, values),
+            event => panic!("Unexpected event type: {event:?}"),
+        })
+        .collect();
+    let row = |diff, key, value: &str| (diff, vec![Value::Int(key), Value::from(value)]);
+    assert_eq!(events.len(), 5);
+    let mut snapshot = events[..3].to_vec();
+    snapshot.sort();
+    assert_eq!(snapshot, [row(1, 1, "a"), row(1, 2, "b"), row(1, 3, "c")]);
+    assert_eq!(events[3..], [row(-1, 2, "b"), row(1, 3, "z")]);
+    Ok(())
+}
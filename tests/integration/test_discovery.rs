@@ -0,0 +1,94 @@
+// Copyright © 2024 Pathway
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use pathway_engine::engine::dataflow::discovery::{local_process_id, Discovery, DiscoveryError};
+
+fn static_discovery(addresses: &[&str]) -> Discovery {
+    Discovery::Static {
+        addresses: addresses.iter().map(ToString::to_string).collect(),
+        timeout: Duration::ZERO,
+    }
+}
+
+#[test]
+fn test_static_addresses_keep_order() -> eyre::Result<()> {
+    let discovery = static_discovery(&["192.0.2.2:10000", "127.0.0.1:10000", "192.0.2.1:10000"]);
+    assert_eq!(discovery.processes(), 3);
+
+    let addresses = discovery.addresses()?;
+    let expected: Vec<SocketAddr> = vec![
+        "192.0.2.2:10000".parse()?,
+        "127.0.0.1:10000".parse()?,
+        "192.0.2.1:10000".parse()?,
+    ];
+    assert_eq!(addresses, expected);
+    Ok(())
+}
+
+#[test]
+fn test_static_duplicate_address() {
+    let discovery = static_discovery(&["127.0.0.1:10000", "127.0.0.1:10000"]);
+    assert!(matches!(
+        discovery.addresses(),
+        Err(DiscoveryError::DuplicateAddress(_))
+    ));
+}
+
+#[test]
+fn test_local_process_id() -> eyre::Result<()> {
+    // 192.0.2.0/24 is reserved for documentation, so it's not assigned to this machine
+    let addresses: Vec<SocketAddr> = vec![
+        "192.0.2.1:10000".parse()?,
+        "127.0.0.1:10000".parse()?,
+        "192.0.2.2:10000".parse()?,
+    ];
+    assert_eq!(local_process_id(&addresses)?, 1);
+    Ok(())
+}
+
+#[test]
+fn test_local_process_id_ambiguous() -> eyre::Result<()> {
+    let addresses: Vec<SocketAddr> = vec!["127.0.0.1:10000".parse()?, "127.0.0.1:10001".parse()?];
+    assert!(matches!(
+        local_process_id(&addresses),
+        Err(DiscoveryError::AmbiguousLocalAddress)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_local_process_id_not_found() -> eyre::Result<()> {
+    let addresses: Vec<SocketAddr> = vec!["192.0.2.1:10000".parse()?, "192.0.2.2:10000".parse()?];
+    assert!(matches!(
+        local_process_id(&addresses),
+        Err(DiscoveryError::NoLocalAddress)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_dns_resolves_expected_number_of_addresses() -> eyre::Result<()> {
+    let discovery = Discovery::Dns {
+        name: "127.0.0.1:10000".to_string(),
+        processes: 1,
+        timeout: Duration::ZERO,
+    };
+    assert_eq!(discovery.addresses()?, vec!["127.0.0.1:10000".parse()?]);
+
+    let discovery = Discovery::Dns {
+        name: "127.0.0.1:10000".to_string(),
+        processes: 2,
+        timeout: Duration::ZERO,
+    };
+    assert!(matches!(
+        discovery.addresses(),
+        Err(DiscoveryError::Timeout {
+            expected: 2,
+            found: 1,
+            ..
+        })
+    ));
+    Ok(())
+}
@@ -0,0 +1,37 @@
+// Copyright © 2024 Pathway
+
+use pathway_engine::external_integration::distance_kernels::DistanceKernels;
+
+fn naive_dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn naive_l2sq(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn vector(len: usize, seed: f64) -> Vec<f64> {
+    (0..len).map(|i| ((i as f64 + seed) * 0.37).sin()).collect()
+}
+
+#[test]
+fn test_kernels_match_naive_implementation() {
+    let kernels = DistanceKernels::detect();
+    // lengths covering the vectorized part, the remainder and both of them
+    for len in [0, 1, 3, 4, 7, 8, 9, 16, 17, 100, 1023] {
+        let a = vector(len, 1.0);
+        let b = vector(len, 2.5);
+        assert!(((kernels.dot)(&a, &b) - naive_dot(&a, &b)).abs() < 1e-9);
+        assert!(((kernels.l2sq)(&a, &b) - naive_l2sq(&a, &b)).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_kernels_with_different_lengths() {
+    let kernels = DistanceKernels::detect();
+    let a = vector(13, 1.0);
+    let b = vector(10, 2.0);
+    assert!(((kernels.dot)(&a, &b) - naive_dot(&a[..10], &b)).abs() < 1e-9);
+    assert!(((kernels.l2sq)(&a, &b) - naive_l2sq(&a[..10], &b)).abs() < 1e-9);
+}
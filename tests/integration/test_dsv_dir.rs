@@ -1,5 +1,7 @@
 // Copyright © 2024 Pathway
 
+use tempfile::tempdir;
+
 use super::helpers::{new_csv_filesystem_reader, read_data_from_reader};
 
 use pathway_engine::connectors::data_format::{DsvParser, DsvSettings};
@@ -280,3 +282,49 @@ fn test_special_fields() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_windows_produced_file() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let path = test_storage.path().join("input.csv");
+    std::fs::write(
+        &path,
+        b"\xEF\xBB\xBFkey,value\r\n1,\"2\r\n3\"\r\n\"a\",b\r\n",
+    )?;
+
+    let mut builder = csv::ReaderBuilder::new();
+    builder.has_headers(false);
+    let schema = [
+        ("key".to_string(), InnerSchemaField::new(Type::String, None)),
+        (
+            "value".to_string(),
+            InnerSchemaField::new(Type::String, None),
+        ),
+    ];
+
+    let reader = new_csv_filesystem_reader(
+        path.to_str().unwrap(),
+        builder,
+        ConnectorMode::Static,
+        "*",
+        false,
+    )?;
+    let parser = DsvParser::new(
+        DsvSettings::new(
+            Some(vec!["key".to_string()]),
+            vec!["value".to_string()],
+            ',',
+        )
+        .with_normalized_newlines(true),
+        schema.into(),
+    )?;
+
+    let read_lines = read_data_from_reader(Box::new(reader), Box::new(parser))?;
+    let expected_values = vec![
+        ParsedEvent::Insert((Some(vec![Value::from("1")]), vec![Value::from("2\n3")])),
+        ParsedEvent::Insert((Some(vec![Value::from("a")]), vec![Value::from("b")])),
+    ];
+    assert_eq!(read_lines, expected_values);
+
+    Ok(())
+}
@@ -62,3 +62,30 @@ fn test_dsv_columns_mismatch() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_dsv_format_bom_and_newlines() -> eyre::Result<()> {
+    let mut formatter = DsvFormatter::new(
+        DsvSettings::new(None, vec!["a".to_string()], ',')
+            .with_bom_written(true)
+            .with_normalized_newlines(true),
+    );
+
+    let result = formatter.format(
+        &Key::for_value(&Value::from("1")),
+        &[Value::from("x\r\ny\rz")],
+        Timestamp(0),
+        1,
+    )?;
+
+    let target_payloads = vec![
+        b"\xEF\xBB\xBF\"a\",\"time\",\"diff\"".to_vec(),
+        b"\"x\ny\nz\",\"0\",\"1\"".to_vec(),
+    ];
+    assert_eq!(result.payloads.len(), target_payloads.len());
+    for (result_payload, target_payload) in zip(result.payloads, target_payloads) {
+        assert_document_raw_byte_contents(&result_payload, &target_payload);
+    }
+
+    Ok(())
+}
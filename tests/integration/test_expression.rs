@@ -0,0 +1,198 @@
+// Copyright © 2024 Pathway
+
+use std::sync::Arc;
+
+use ndarray::{Array1, Array2};
+
+use pathway_engine::engine::{
+    AnyExpression, BytesExpression, Expression, FormatTemplate, StringExpression, Value,
+};
+
+fn argument(index: usize) -> Arc<Expression> {
+    Arc::new(Expression::Any(AnyExpression::Argument(index)))
+}
+
+fn eval_single(expression: &Expression, row: &[Value]) -> Option<Value> {
+    expression.eval(&[row]).into_iter().next().unwrap().ok()
+}
+
+#[test]
+fn test_base64_roundtrip() {
+    let encode = Arc::new(Expression::from(StringExpression::EncodeBase64(argument(
+        0,
+    ))));
+    let decode = Expression::from(BytesExpression::DecodeBase64(encode.clone()));
+    let row = [Value::Bytes(b"pathway\x00\xff".as_slice().into())];
+    assert_eq!(
+        eval_single(&encode, &row),
+        Some(Value::from("cGF0aHdheQD/"))
+    );
+    assert_eq!(eval_single(&decode, &row), Some(row[0].clone()));
+}
+
+#[test]
+fn test_hex_roundtrip() {
+    let encode = Arc::new(Expression::from(StringExpression::EncodeHex(argument(0))));
+    let decode = Expression::from(BytesExpression::DecodeHex(encode.clone()));
+    let row = [Value::Bytes(b"\x01\xab\xff".as_slice().into())];
+    assert_eq!(eval_single(&encode, &row), Some(Value::from("01abff")));
+    assert_eq!(eval_single(&decode, &row), Some(row[0].clone()));
+}
+
+#[test]
+fn test_decode_invalid_input() {
+    let decode_base64 = Expression::from(BytesExpression::DecodeBase64(argument(0)));
+    let decode_hex = Expression::from(BytesExpression::DecodeHex(argument(0)));
+    let row = [Value::from("not valid!")];
+    assert_eq!(eval_single(&decode_base64, &row), None);
+    assert_eq!(eval_single(&decode_hex, &row), None);
+}
+
+#[test]
+fn test_utf8_conversions() {
+    let to_utf8 = Expression::from(BytesExpression::ToUtf8(argument(0)));
+    assert_eq!(
+        eval_single(&to_utf8, &[Value::from("zażółć")]),
+        Some(Value::Bytes("zażółć".as_bytes().into()))
+    );
+
+    let from_utf8 = Expression::from(StringExpression::FromUtf8Lossy(argument(0)));
+    assert_eq!(
+        eval_single(&from_utf8, &[Value::Bytes(b"ok\xffok".as_slice().into())]),
+        Some(Value::from("ok\u{FFFD}ok"))
+    );
+}
+
+fn format_expression(template: &str) -> Expression {
+    let template = FormatTemplate::parse(template).expect("template should be valid");
+    let args: Vec<_> = (0..template.n_arguments()).map(argument).collect();
+    Expression::from(StringExpression::Format(Arc::new(template), args.into()))
+}
+
+#[test]
+fn test_format_placeholders() {
+    let row = [Value::from("temp"), Value::from(21.456), Value::Int(7)];
+    assert_eq!(
+        eval_single(&format_expression("{}: {:.2} ({})"), &row),
+        Some(Value::from("temp: 21.46 (7)"))
+    );
+    assert_eq!(
+        eval_single(&format_expression("{2:>4}|{0:-^8}|{1:8.1f}"), &row),
+        Some(Value::from("   7|--temp--|    21.5"))
+    );
+    assert_eq!(
+        eval_single(
+            &format_expression("{{{0:x}}} {0:X} {0:d}"),
+            &[Value::Int(255)]
+        ),
+        Some(Value::from("{ff} FF 255"))
+    );
+    assert_eq!(
+        eval_single(
+            &format_expression("{:.3} {}"),
+            &[Value::from("abcdef"), Value::None]
+        ),
+        Some(Value::from("abc None"))
+    );
+}
+
+#[test]
+fn test_format_type_mismatch() {
+    assert_eq!(
+        eval_single(&format_expression("{:d}"), &[Value::from("x")]),
+        None
+    );
+    assert_eq!(
+        eval_single(&format_expression("{:s}"), &[Value::Int(1)]),
+        None
+    );
+}
+
+#[test]
+fn test_format_invalid_templates() {
+    for template in ["{", "}", "{0}{}", "{:.}", "{:q}", "{:.2d}", "{:5.2fx}"] {
+        assert!(
+            FormatTemplate::parse(template).is_err(),
+            "{template:?} should be rejected"
+        );
+    }
+    assert_eq!(FormatTemplate::parse("{1}{1}").unwrap().n_arguments(), 2);
+}
+
+fn mat_mul_expression() -> Expression {
+    Expression::Any(AnyExpression::MatMul(argument(0), argument(1)))
+}
+
+#[test]
+fn test_mat_mul_large_matrices() {
+    let (rows, inner, columns) = (96, 80, 72);
+    #[allow(clippy::cast_precision_loss)]
+    let lhs = Array2::from_shape_fn((rows, inner), |(i, j)| ((i * 7 + j * 3) % 11) as f64 - 5.0);
+    #[allow(clippy::cast_precision_loss)]
+    let rhs = Array2::from_shape_fn((inner, columns), |(i, j)| ((i * 5 + j) % 13) as f64 / 4.0);
+    let expected = lhs.dot(&rhs);
+
+    let result = eval_single(
+        &mat_mul_expression(),
+        &[
+            Value::from(lhs.clone().into_dyn()),
+            Value::from(rhs.clone().into_dyn()),
+        ],
+    );
+    let Some(Value::FloatArray(result)) = result else {
+        panic!("expected a float array, got {result:?}");
+    };
+    assert_eq!(result.shape(), &[rows, columns]);
+    for (actual, expected) in result.iter().zip(expected.iter()) {
+        assert!((actual - expected).abs() < 1e-9);
+    }
+
+    let vector = Array1::from_shape_fn(inner, |i| if i % 2 == 0 { 1.0 } else { -1.0 });
+    let expected = lhs.dot(&vector);
+    let result = eval_single(
+        &mat_mul_expression(),
+        &[Value::from(lhs.into_dyn()), Value::from(vector.into_dyn())],
+    );
+    let Some(Value::FloatArray(result)) = result else {
+        panic!("expected a float array, got {result:?}");
+    };
+    assert_eq!(result.shape(), &[rows]);
+    for (actual, expected) in result.iter().zip(expected.iter()) {
+        assert!((actual - expected).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_mat_mul_shape_mismatch() {
+    let lhs = Array2::<f64>::zeros((3, 4)).into_dyn();
+    let rhs = Array2::<f64>::zeros((5, 2)).into_dyn();
+    assert_eq!(
+        eval_single(&mat_mul_expression(), &[lhs.into(), rhs.into()]),
+        None
+    );
+}
+
+#[test]
+fn test_mat_mul_single_precision() {
+    let lhs = Array2::from_shape_vec((2, 3), vec![1.0_f32, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    let rhs = Array2::from_shape_vec((3, 2), vec![0.5_f32, -1.0, 2.0, 0.0, 1.0, 1.5]).unwrap();
+    let expected = lhs.dot(&rhs).into_dyn();
+
+    let result = eval_single(
+        &mat_mul_expression(),
+        &[
+            Value::from(lhs.clone().into_dyn()),
+            Value::from(rhs.clone().into_dyn()),
+        ],
+    );
+    assert_eq!(result, Some(Value::from(expected.clone())));
+
+    let result = eval_single(
+        &mat_mul_expression(),
+        &[
+            Value::from(lhs.into_dyn()),
+            Value::from(rhs.mapv(f64::from).into_dyn()),
+        ],
+    );
+    assert_eq!(result, Some(Value::from(expected.mapv(f64::from))));
+}
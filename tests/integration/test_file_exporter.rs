@@ -0,0 +1,77 @@
+// Copyright © 2024 Pathway
+
+use std::borrow::Cow;
+use std::fs;
+use std::time::{Duration, UNIX_EPOCH};
+
+use opentelemetry::trace::{
+    SpanContext, SpanId, SpanKind, Status, TraceFlags, TraceId, TraceState,
+};
+use opentelemetry::{InstrumentationScope, KeyValue};
+use opentelemetry_sdk::trace::{SpanData, SpanEvents, SpanExporter as _, SpanLinks};
+use opentelemetry_sdk::Resource;
+use serde_json::Value as JsonValue;
+use tempfile::tempdir;
+
+use pathway_engine::engine::file_exporter::SpanExporter;
+
+fn span(name: &'static str) -> SpanData {
+    SpanData {
+        span_context: SpanContext::new(
+            TraceId::from(0x0af7_6519_16cd_43dd_8448_eb21_1c80_319c),
+            SpanId::from(0xb7ad_6b71_6920_3331),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        ),
+        parent_span_id: SpanId::INVALID,
+        span_kind: SpanKind::Internal,
+        name: Cow::Borrowed(name),
+        start_time: UNIX_EPOCH + Duration::from_secs(1),
+        end_time: UNIX_EPOCH + Duration::from_secs(2),
+        attributes: vec![KeyValue::new("connector.name", "kafka-input")],
+        dropped_attributes_count: 0,
+        events: SpanEvents::default(),
+        links: SpanLinks::default(),
+        status: Status::Unset,
+        instrumentation_scope: InstrumentationScope::builder("pathway-engine").build(),
+    }
+}
+
+#[test]
+fn test_spans_written_as_json_lines() -> eyre::Result<()> {
+    let export_dir = tempdir()?;
+    let mut exporter = SpanExporter::new(export_dir.path().to_path_buf(), "worker-0");
+    exporter.set_resource(
+        &Resource::builder_empty()
+            .with_attributes([KeyValue::new("run.id", "run")])
+            .build(),
+    );
+
+    futures::executor::block_on(
+        exporter.export(vec![span("connector.poll"), span("parser.batch")]),
+    )?;
+    exporter.force_flush()?;
+
+    let files: Vec<_> = fs::read_dir(export_dir.path())?.collect::<Result<_, _>>()?;
+    assert_eq!(files.len(), 1);
+    let file_name = files[0].file_name().into_string().unwrap();
+    assert!(file_name.starts_with("spans-worker-0-"));
+    assert!(file_name.ends_with(".jsonl"));
+
+    let lines: Vec<JsonValue> = fs::read_to_string(files[0].path())?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["name"], "connector.poll");
+    assert_eq!(lines[1]["name"], "parser.batch");
+    assert_eq!(lines[0]["trace_id"], "0af7651916cd43dd8448eb211c80319c");
+    assert_eq!(lines[0]["span_id"], "b7ad6b7169203331");
+    assert_eq!(lines[0]["start_time_unix_nano"], 1_000_000_000_u64);
+    assert_eq!(lines[0]["end_time_unix_nano"], 2_000_000_000_u64);
+    assert_eq!(lines[0]["attributes"]["connector.name"], "kafka-input");
+    assert_eq!(lines[0]["resource"]["run.id"], "run");
+    assert_eq!(lines[0]["status"]["code"], "unset");
+    Ok(())
+}
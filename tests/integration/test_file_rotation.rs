@@ -0,0 +1,156 @@
+// Copyright © 2024 Pathway
+
+use std::fs;
+use std::path::Path;
+
+use pathway_engine::connectors::data_format::FormatterContext;
+use pathway_engine::connectors::data_storage::Writer;
+use pathway_engine::connectors::file_rotation::{
+    FileRotationConfig, NameTemplate, NameTemplateError, RotatingFileWriter, SUCCESS_MARKER_NAME,
+};
+use pathway_engine::engine::{Key, Timestamp};
+
+fn config(template: &str, max_file_size: Option<u64>, repeat_header: bool) -> FileRotationConfig {
+    FileRotationConfig {
+        name_template: NameTemplate::parse(template).unwrap(),
+        table_name: "orders".to_string(),
+        extension: "csv".to_string(),
+        max_file_size,
+        rotation_interval: None,
+        write_success_markers: true,
+        repeat_header,
+    }
+}
+
+fn lines(lines: &[&str]) -> FormatterContext {
+    FormatterContext::new(
+        lines.iter().map(|line| line.as_bytes().to_vec()).collect(),
+        Key::random(),
+        Vec::new(),
+        Timestamp(0),
+        1,
+    )
+}
+
+fn directory_contents(directory: &Path) -> eyre::Result<Vec<String>> {
+    let mut names: Vec<_> = fs::read_dir(directory)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().to_string()))
+        .collect::<eyre::Result<_>>()?;
+    names.sort();
+    Ok(names)
+}
+
+#[test]
+fn test_files_rotated_by_size() -> eyre::Result<()> {
+    let directory = tempfile::tempdir()?;
+    let mut writer = RotatingFileWriter::new(
+        directory.path().to_path_buf(),
+        config("{table}-{part}.{ext}", Some(10), true),
+    )?;
+    writer.write(lines(&["a,b", "1,2"]))?;
+    writer.write(lines(&["3,4"]))?;
+    writer.write(lines(&["5,6"]))?;
+    writer.flush(true)?;
+
+    // The second file isn't complete yet, so it has a temporary name
+    assert_eq!(
+        directory_contents(directory.path())?,
+        vec![".orders-00001.csv.inprogress", "orders-00000.csv"]
+    );
+
+    writer.on_time_committed(None)?;
+    assert_eq!(
+        directory_contents(directory.path())?,
+        vec![SUCCESS_MARKER_NAME, "orders-00000.csv", "orders-00001.csv"]
+    );
+    assert_eq!(
+        fs::read_to_string(directory.path().join("orders-00000.csv"))?,
+        "a,b\n1,2\n3,4\n"
+    );
+    assert_eq!(
+        fs::read_to_string(directory.path().join("orders-00001.csv"))?,
+        "a,b\n5,6\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_existing_files_not_overwritten() -> eyre::Result<()> {
+    let directory = tempfile::tempdir()?;
+    fs::create_dir(directory.path().join("out"))?;
+    fs::write(directory.path().join("out/00000.csv"), "old\n")?;
+
+    let mut writer = RotatingFileWriter::new(
+        directory.path().to_path_buf(),
+        config("out/{part}.{ext}", None, false),
+    )?;
+    writer.write(lines(&["new"]))?;
+    writer.on_time_committed(None)?;
+
+    assert_eq!(
+        directory_contents(&directory.path().join("out"))?,
+        vec!["00000.csv", "00001.csv", SUCCESS_MARKER_NAME]
+    );
+    assert_eq!(
+        fs::read_to_string(directory.path().join("out/00000.csv"))?,
+        "old\n"
+    );
+    assert_eq!(
+        fs::read_to_string(directory.path().join("out/00001.csv"))?,
+        "new\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_subdirectory_published_when_period_is_over() -> eyre::Result<()> {
+    let directory = tempfile::tempdir()?;
+    fs::create_dir(directory.path().join("orders"))?;
+    fs::write(directory.path().join("orders/00000.csv"), "old\n")?;
+
+    let mut writer = RotatingFileWriter::new(
+        directory.path().to_path_buf(),
+        config("{table}/{part}.{ext}", Some(1), false),
+    )?;
+    writer.write(lines(&["1", "2"]))?;
+    writer.flush(true)?;
+
+    // Both files are complete, but the period isn't over, so only the old file is visible
+    assert_eq!(
+        directory_contents(&directory.path().join("orders"))?,
+        vec!["00000.csv"]
+    );
+
+    writer.on_time_committed(None)?;
+    assert_eq!(
+        directory_contents(&directory.path().join("orders"))?,
+        vec!["00000.csv", "00001.csv", "00002.csv", SUCCESS_MARKER_NAME]
+    );
+    assert_eq!(
+        fs::read_to_string(directory.path().join("orders/00000.csv"))?,
+        "old\n"
+    );
+    assert_eq!(
+        fs::read_to_string(directory.path().join("orders/00002.csv"))?,
+        "2\n"
+    );
+    // No staging directory is left behind
+    assert_eq!(directory_contents(directory.path())?, vec!["orders"]);
+    Ok(())
+}
+
+#[test]
+fn test_invalid_name_templates() {
+    assert!(matches!(
+        NameTemplate::parse("{table}-{date}.{ext}"),
+        Err(NameTemplateError::MissingPart(_))
+    ));
+    assert!(matches!(
+        NameTemplate::parse("{table}-{part}.{extension}"),
+        Err(NameTemplateError::UnknownPlaceholder(placeholder)) if placeholder == "extension"
+    ));
+    assert!(matches!(
+        NameTemplate::parse("{table}-{part"),
+        Err(NameTemplateError::UnclosedPlaceholder(_))
+    ));
+}
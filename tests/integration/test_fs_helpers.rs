@@ -0,0 +1,99 @@
+// Copyright © 2024 Pathway
+
+use std::fs;
+use std::path::Path;
+
+use pathway_engine::fs_helpers::{replace_directory, StagedDirectory};
+
+fn directory_entries(path: &Path) -> eyre::Result<Vec<String>> {
+    let mut entries: Vec<String> = fs::read_dir(path)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<eyre::Result<_>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+#[test]
+fn test_staged_directory_creates_target() -> eyre::Result<()> {
+    let root = tempfile::tempdir()?;
+    let target = root.path().join("output");
+
+    let staged = StagedDirectory::new(&target)?;
+    fs::write(staged.path().join("part-0"), "a")?;
+    assert!(!target.exists());
+    staged.publish()?;
+
+    assert_eq!(directory_entries(&target)?, ["part-0"]);
+    assert_eq!(directory_entries(root.path())?, ["output"]);
+    Ok(())
+}
+
+#[test]
+fn test_staged_directory_replaces_target() -> eyre::Result<()> {
+    let root = tempfile::tempdir()?;
+    let target = root.path().join("output");
+    fs::create_dir(&target)?;
+    fs::write(target.join("part-0"), "old")?;
+    fs::write(target.join("part-1"), "old")?;
+
+    let staged = StagedDirectory::new(&target)?;
+    fs::write(staged.path().join("part-0"), "new")?;
+    // the old contents stay visible until the new ones are published
+    assert_eq!(directory_entries(&target)?, ["part-0", "part-1"]);
+    staged.publish()?;
+
+    assert_eq!(directory_entries(&target)?, ["part-0"]);
+    assert_eq!(fs::read_to_string(target.join("part-0"))?, "new");
+    // neither the staging directory nor the old contents are left behind
+    assert_eq!(directory_entries(root.path())?, ["output"]);
+    Ok(())
+}
+
+#[test]
+fn test_staged_directory_extending_target() -> eyre::Result<()> {
+    let root = tempfile::tempdir()?;
+    let target = root.path().join("output");
+    fs::create_dir_all(target.join("nested"))?;
+    fs::write(target.join("part-0"), "old")?;
+    fs::write(target.join("nested/part-0"), "old")?;
+
+    let staged = StagedDirectory::extending(&target)?;
+    fs::write(staged.path().join("part-1"), "new")?;
+    assert_eq!(directory_entries(&target)?, ["nested", "part-0"]);
+    staged.publish()?;
+
+    assert_eq!(directory_entries(&target)?, ["nested", "part-0", "part-1"]);
+    assert_eq!(fs::read_to_string(target.join("part-0"))?, "old");
+    assert_eq!(fs::read_to_string(target.join("nested/part-0"))?, "old");
+    assert_eq!(directory_entries(root.path())?, ["output"]);
+    Ok(())
+}
+
+#[test]
+fn test_staged_directory_removed_if_not_published() -> eyre::Result<()> {
+    let root = tempfile::tempdir()?;
+    let target = root.path().join("output");
+    fs::create_dir(&target)?;
+    fs::write(target.join("part-0"), "old")?;
+
+    let staged = StagedDirectory::new(&target)?;
+    fs::write(staged.path().join("part-0"), "new")?;
+    drop(staged);
+
+    assert_eq!(fs::read_to_string(target.join("part-0"))?, "old");
+    assert_eq!(directory_entries(root.path())?, ["output"]);
+    Ok(())
+}
+
+#[test]
+fn test_replace_directory_fails_without_source() -> eyre::Result<()> {
+    let root = tempfile::tempdir()?;
+    let target = root.path().join("output");
+    fs::create_dir(&target)?;
+    fs::write(target.join("part-0"), "old")?;
+
+    assert!(replace_directory(&root.path().join("missing"), &target).is_err());
+    assert_eq!(fs::read_to_string(target.join("part-0"))?, "old");
+    assert_eq!(directory_entries(root.path())?, ["output"]);
+    Ok(())
+}
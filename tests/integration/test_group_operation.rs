@@ -0,0 +1,86 @@
+// Copyright © 2024 Pathway
+
+use std::sync::{Arc, Mutex};
+
+use differential_dataflow::AsCollection;
+use timely::dataflow::operators::capture::{Capture, Extract};
+use timely::dataflow::operators::ToStream;
+
+use pathway_engine::engine::dataflow::operators::group_operation::{
+    ApplyGroupOperation, GroupOperation,
+};
+
+struct Multiply {
+    batch_sizes: Arc<Mutex<Vec<usize>>>,
+}
+
+impl GroupOperation<i64, i64, i64> for Multiply {
+    type Prepared = i64;
+
+    fn prepare(&mut self, operand: &i64) -> i64 {
+        *operand
+    }
+
+    fn compute(&mut self, prepared: &i64, rows: &[&i64]) -> Vec<i64> {
+        self.batch_sizes.lock().unwrap().push(rows.len());
+        rows.iter().map(|row| *row * prepared).collect()
+    }
+}
+
+#[test]
+fn test_group_operation_recomputes_only_changes() {
+    let batch_sizes = Arc::new(Mutex::new(Vec::new()));
+    let operation = Multiply {
+        batch_sizes: batch_sizes.clone(),
+    };
+    let result = timely::example(move |scope| {
+        let operands = vec![
+            ((1, 10), 0, 1),
+            ((1, 10), 2, -1),
+            ((1, 20), 2, 1),
+            ((2, 7), 3, 1),
+        ]
+        .into_iter()
+        .to_stream(scope)
+        .as_collection();
+        let rows = vec![
+            ((100, (1, 1)), 0, 1),
+            ((101, (1, 2)), 0, 1),
+            ((102, (2, 5)), 0, 1),
+            ((103, (1, 3)), 1, 1),
+            ((101, (1, 2)), 3, -1),
+        ]
+        .into_iter()
+        .to_stream(scope)
+        .as_collection();
+        operands
+            .apply_group_operation(&rows, operation)
+            .inner
+            .capture()
+    });
+
+    let mut result: Vec<_> = result.extract();
+    for (_time, updates) in &mut result {
+        updates.sort_unstable();
+    }
+    assert_eq!(
+        result,
+        vec![
+            (0, vec![((100, (1, 10)), 0, 1), ((101, (2, 20)), 0, 1)]),
+            (1, vec![((103, (3, 30)), 1, 1)]),
+            (
+                2,
+                vec![
+                    ((100, (1, 10)), 2, -1),
+                    ((100, (1, 20)), 2, 1),
+                    ((101, (2, 20)), 2, -1),
+                    ((101, (2, 40)), 2, 1),
+                    ((103, (3, 30)), 2, -1),
+                    ((103, (3, 60)), 2, 1),
+                ]
+            ),
+            (3, vec![((101, (2, 40)), 3, -1), ((102, (5, 35)), 3, 1)]),
+        ]
+    );
+    assert_eq!(*batch_sizes.lock().unwrap(), vec![2, 1, 3, 1]);
+}
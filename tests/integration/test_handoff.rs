@@ -0,0 +1,58 @@
+// Copyright © 2024 Pathway
+
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tempfile::tempdir;
+
+use pathway_engine::persistence::backends::FilesystemKVStorage;
+use pathway_engine::persistence::handoff::HandoffCoordinator;
+
+fn coordinator(path: &Path, lease_duration: Duration) -> eyre::Result<HandoffCoordinator> {
+    Ok(HandoffCoordinator::new(
+        Box::new(FilesystemKVStorage::new(path)?),
+        0,
+        lease_duration,
+    ))
+}
+
+#[test]
+fn test_takeover_waits_for_release() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let path = test_storage.path().to_path_buf();
+
+    let old = coordinator(&path, Duration::from_secs(60))?;
+    // Nobody holds the lease yet
+    old.take_over()?;
+
+    let (sender, receiver) = mpsc::channel();
+    let new_process = thread::spawn(move || -> eyre::Result<()> {
+        let new = coordinator(&path, Duration::from_secs(60))?;
+        new.take_over()?;
+        sender.send(()).unwrap();
+        Ok(())
+    });
+    assert!(receiver.recv_timeout(Duration::from_secs(2)).is_err());
+
+    old.release()?;
+    receiver.recv_timeout(Duration::from_secs(10))?;
+    new_process.join().unwrap()?;
+    Ok(())
+}
+
+#[test]
+fn test_takeover_after_lease_expiry() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let path = test_storage.path();
+
+    let lease_duration = Duration::from_secs(1);
+    // The lease is never renewed, as if the process had crashed
+    coordinator(path, lease_duration)?.take_over()?;
+
+    let start = Instant::now();
+    coordinator(path, lease_duration)?.take_over()?;
+    assert!(start.elapsed() >= lease_duration);
+    Ok(())
+}
@@ -0,0 +1,73 @@
+// Copyright © 2024 Pathway
+
+use std::time::Duration;
+
+use pathway_engine::engine::health::HealthSnapshot;
+
+const MAX_STALL: Duration = Duration::from_secs(60);
+
+fn ready_snapshot() -> HealthSnapshot {
+    HealthSnapshot {
+        connectors_registered: 2,
+        connectors_connected: 2,
+        connectors_recovered: 2,
+        telemetry_failed: false,
+        inputs_done: false,
+        since_watermark_advanced: Duration::from_secs(1),
+    }
+}
+
+#[test]
+fn test_ready_when_connectors_recovered_and_frontier_advances() {
+    let snapshot = ready_snapshot();
+    assert!(snapshot.liveness_problems().is_empty());
+    assert!(snapshot.readiness_problems(MAX_STALL).is_empty());
+}
+
+#[test]
+fn test_not_ready_until_connectors_connect_and_recover() {
+    let snapshot = HealthSnapshot {
+        connectors_connected: 1,
+        connectors_recovered: 0,
+        ..ready_snapshot()
+    };
+    assert!(snapshot.liveness_problems().is_empty());
+    assert_eq!(
+        snapshot.readiness_problems(MAX_STALL),
+        vec![
+            "1 of 2 connectors connected".to_string(),
+            "0 of 2 connectors recovered".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_not_ready_when_frontier_stalls() {
+    let snapshot = HealthSnapshot {
+        since_watermark_advanced: Duration::from_secs(120),
+        ..ready_snapshot()
+    };
+    assert_eq!(
+        snapshot.readiness_problems(MAX_STALL),
+        vec!["input frontier hasn't advanced for 120s".to_string()]
+    );
+
+    let finished = HealthSnapshot {
+        inputs_done: true,
+        ..snapshot
+    };
+    assert!(finished.readiness_problems(MAX_STALL).is_empty());
+}
+
+#[test]
+fn test_not_live_after_telemetry_failure() {
+    let snapshot = HealthSnapshot {
+        telemetry_failed: true,
+        ..ready_snapshot()
+    };
+    assert_eq!(
+        snapshot.liveness_problems(),
+        vec!["telemetry thread has died".to_string()]
+    );
+    assert_eq!(snapshot.readiness_problems(MAX_STALL).len(), 1);
+}
@@ -0,0 +1,71 @@
+// Copyright © 2024 Pathway
+
+use std::time::Duration;
+
+use pathway_engine::connectors::idempotency::{IdempotencyKey, IdempotencyKeyGenerator};
+use pathway_engine::engine::{Key, Timestamp, TotalFrontier, Value};
+
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(100);
+
+fn keys(
+    generator: &mut IdempotencyKeyGenerator,
+    entries: &[(u128, &str, u64, isize)],
+) -> Vec<IdempotencyKey> {
+    entries
+        .iter()
+        .map(|(key, value, time, diff)| {
+            generator.next_key(Key(*key), &[Value::from(*value)], Timestamp(*time), *diff)
+        })
+        .collect()
+}
+
+#[test]
+fn test_keys_are_deterministic() {
+    let entries = [(1, "foo", 2, 1), (2, "bar", 2, 1), (1, "foo", 4, -1)];
+    let mut first = IdempotencyKeyGenerator::without_persistence();
+    let mut second = IdempotencyKeyGenerator::without_persistence();
+    assert_eq!(keys(&mut first, &entries), keys(&mut second, &entries));
+}
+
+#[test]
+fn test_repeated_entries_get_different_keys() {
+    let mut generator =
+        IdempotencyKeyGenerator::new(TotalFrontier::At(Timestamp(0)), SNAPSHOT_INTERVAL);
+    let keys = keys(
+        &mut generator,
+        &[(1, "foo", 2, 1), (1, "foo", 4, -1), (1, "foo", 6, 1)],
+    );
+    assert_ne!(keys[0], keys[2]);
+    assert_ne!(keys[0], keys[1]);
+}
+
+#[test]
+fn test_entries_written_again_after_recovery_get_same_keys() {
+    // the first run takes a checkpoint at 200 and fails after writing the entries up to 250
+    let mut failed_run =
+        IdempotencyKeyGenerator::new(TotalFrontier::At(Timestamp(0)), SNAPSHOT_INTERVAL);
+    keys(&mut failed_run, &[(1, "foo", 120, 1), (2, "bar", 150, 1)]);
+    failed_run.on_commit(TotalFrontier::At(Timestamp(200)));
+    let written = keys(
+        &mut failed_run,
+        &[(1, "foo", 210, -1), (3, "baz", 230, 1), (1, "foo", 250, 1)],
+    );
+
+    // the recovered run produces the same entries again, at later times
+    let mut recovered_run =
+        IdempotencyKeyGenerator::new(TotalFrontier::At(Timestamp(200)), SNAPSHOT_INTERVAL);
+    let written_again = keys(
+        &mut recovered_run,
+        &[
+            (1, "foo", 1010, -1),
+            (3, "baz", 1010, 1),
+            (1, "foo", 1020, 1),
+        ],
+    );
+    assert_eq!(written, written_again);
+
+    // the new entries after the first checkpoint of the recovered run get new keys
+    recovered_run.on_commit(TotalFrontier::At(Timestamp(1000)));
+    let new_entries = keys(&mut recovered_run, &[(1, "foo", 1110, -1)]);
+    assert_ne!(new_entries[0], written[0]);
+}
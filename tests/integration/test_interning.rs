@@ -0,0 +1,87 @@
+// Copyright © 2024 Pathway
+
+use arcstr::ArcStr;
+
+use pathway_engine::engine::interning::StringInterner;
+use pathway_engine::engine::Value;
+
+fn string(value: &Value) -> &ArcStr {
+    match value {
+        Value::String(string) => string,
+        _ => panic!("not a string: {value:?}"),
+    }
+}
+
+#[test]
+fn test_equal_strings_share_allocation() {
+    let mut interner = StringInterner::new(10);
+    let mut first = vec![Value::from("books"), Value::Int(1)];
+    let mut second = vec![Value::from("books"), Value::Int(2)];
+    interner.intern(&mut first);
+    interner.intern(&mut second);
+    assert!(ArcStr::ptr_eq(string(&first[0]), string(&second[0])));
+    assert_eq!(second, vec![Value::from("books"), Value::Int(2)]);
+}
+
+#[test]
+fn test_columns_are_interned_separately() {
+    let mut interner = StringInterner::new(10);
+    let mut row = vec![Value::from("x"), Value::from("x")];
+    interner.intern(&mut row);
+    assert!(!ArcStr::ptr_eq(string(&row[0]), string(&row[1])));
+}
+
+#[test]
+fn test_high_cardinality_column_stops_interning() {
+    let mut interner = StringInterner::new(2);
+    for index in 0..3 {
+        let mut row = vec![
+            Value::from(format!("value-{index}").as_str()),
+            Value::from("a"),
+        ];
+        interner.intern(&mut row);
+    }
+    assert!(!interner.is_interning(0));
+    assert!(interner.is_interning(1));
+
+    let mut first = vec![Value::from("value-0")];
+    let mut second = vec![Value::from("value-0")];
+    interner.intern(&mut first);
+    interner.intern(&mut second);
+    assert!(!ArcStr::ptr_eq(string(&first[0]), string(&second[0])));
+}
+
+#[test]
+fn test_disabled() {
+    let mut interner = StringInterner::new(0);
+    let mut first = vec![Value::from("a")];
+    let mut second = vec![Value::from("a")];
+    interner.intern(&mut first);
+    interner.intern(&mut second);
+    assert!(!ArcStr::ptr_eq(string(&first[0]), string(&second[0])));
+    assert!(!interner.is_interning(0));
+}
+
+#[test]
+fn test_tuple_columns_share_allocation() {
+    let mut interner = StringInterner::new(10);
+    let mut first = Value::from(vec![Value::from("books"), Value::Int(1)]);
+    let mut second = Value::from(vec![Value::from("books"), Value::Int(2)]);
+    interner.intern_value(&mut first);
+    interner.intern_value(&mut second);
+    let (Value::Tuple(first), Value::Tuple(second)) = (&first, &second) else {
+        panic!("not tuples");
+    };
+    assert!(ArcStr::ptr_eq(string(&first[0]), string(&second[0])));
+    assert_eq!(second[1], Value::Int(2));
+}
+
+#[test]
+fn test_single_value_is_one_column() {
+    let mut interner = StringInterner::new(10);
+    let mut first = Value::from("books");
+    let mut second = Value::from("books");
+    interner.intern_value(&mut first);
+    interner.intern_value(&mut second);
+    assert!(ArcStr::ptr_eq(string(&first), string(&second)));
+}
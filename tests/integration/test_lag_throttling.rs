@@ -0,0 +1,74 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use pathway_engine::connectors::lag_throttling::{
+    LagThrottler, LagThrottlingConfig, ThrottlingDecision,
+};
+
+fn throttler() -> LagThrottler {
+    LagThrottler::new(LagThrottlingConfig {
+        check_interval: Duration::from_secs(5),
+        min_lag: 100,
+    })
+}
+
+#[test]
+fn test_small_lags_not_throttled() {
+    let mut throttler = throttler();
+    let lags = HashMap::from([(0, 90), (1, 0)]);
+    assert_eq!(
+        throttler.on_lags_checked(Instant::now(), &lags),
+        ThrottlingDecision::default()
+    );
+    assert!(throttler.paused_partitions().is_empty());
+}
+
+#[test]
+fn test_most_lagging_partitions_prioritized() {
+    let mut throttler = throttler();
+    let now = Instant::now();
+    let lags = HashMap::from([(0, 1000), (1, 600), (2, 400), (3, 0)]);
+    let decision = throttler.on_lags_checked(now, &lags);
+    assert_eq!(decision.pause, vec![2, 3]);
+    assert!(decision.resume.is_empty());
+
+    // The partitions that are still behind stay paused, even if they are paused already
+    let lags = HashMap::from([(0, 700), (1, 300), (2, 450), (3, 100)]);
+    let decision = throttler.on_lags_checked(now, &lags);
+    assert_eq!(decision.pause, vec![1, 3]);
+    assert_eq!(decision.resume, vec![2]);
+
+    // The lags have evened out
+    let lags = HashMap::from([(0, 400), (1, 350), (2, 300), (3, 250)]);
+    let decision = throttler.on_lags_checked(now, &lags);
+    assert!(decision.pause.is_empty());
+    assert_eq!(decision.resume, vec![1, 3]);
+}
+
+#[test]
+fn test_revoked_partitions_forgotten() {
+    let mut throttler = throttler();
+    let now = Instant::now();
+    let lags = HashMap::from([(0, 1000), (1, 0)]);
+    assert_eq!(throttler.on_lags_checked(now, &lags).pause, vec![1]);
+
+    // The partition is no longer assigned, so it can't be resumed
+    let lags = HashMap::from([(0, 1000)]);
+    assert_eq!(
+        throttler.on_lags_checked(now, &lags),
+        ThrottlingDecision::default()
+    );
+    assert!(throttler.paused_partitions().is_empty());
+}
+
+#[test]
+fn test_check_interval() {
+    let mut throttler = throttler();
+    let now = Instant::now();
+    assert!(throttler.is_check_due(now));
+    throttler.on_lags_checked(now, &HashMap::new());
+    assert!(!throttler.is_check_due(now + Duration::from_secs(1)));
+    assert!(throttler.is_check_due(now + Duration::from_secs(5)));
+}
@@ -0,0 +1,106 @@
+// Copyright © 2024 Pathway
+
+use pathway_engine::connectors::data_format::{
+    IdentityParser, KeyGenerationPolicy, LengthPrefix, LengthPrefixedParser, ParseError,
+    ParsedEvent, Parser,
+};
+use pathway_engine::connectors::data_storage::{DataEventType, ReaderContext};
+use pathway_engine::connectors::SessionType;
+use pathway_engine::engine::Value;
+
+use crate::helpers::ReplaceErrors;
+
+fn new_parser(prefix: LengthPrefix) -> LengthPrefixedParser {
+    let inner = IdentityParser::new(
+        vec!["data".to_string()],
+        false,
+        KeyGenerationPolicy::PreferMessageKey,
+        SessionType::Native,
+    );
+    LengthPrefixedParser::new(
+        Box::new(inner),
+        prefix,
+        LengthPrefixedParser::DEFAULT_MAX_FRAME_LENGTH,
+    )
+}
+
+fn parse_chunk(
+    parser: &mut LengthPrefixedParser,
+    chunk: &[u8],
+) -> Result<Vec<Vec<u8>>, ParseError> {
+    let context = ReaderContext::RawBytes(DataEventType::Insert, chunk.to_vec());
+    let mut payloads = Vec::new();
+    for event in parser.parse(&context).map_err(ParseError::from)? {
+        let ParsedEvent::Insert((_, values)) = event.replace_errors() else {
+            panic!("only insertions are expected");
+        };
+        let [Value::Bytes(payload)] = values.as_slice() else {
+            panic!("a single binary value is expected");
+        };
+        payloads.push(payload.to_vec());
+    }
+    Ok(payloads)
+}
+
+#[test]
+fn test_varint_frames() -> eyre::Result<()> {
+    let mut parser = new_parser(LengthPrefix::Varint);
+    let long_payload = vec![7; 300];
+    let mut data = vec![3, b'a', b'b', b'c', 0, 0xac, 0x02];
+    data.extend_from_slice(&long_payload);
+    assert_eq!(
+        parse_chunk(&mut parser, &data)?,
+        vec![b"abc".to_vec(), Vec::new(), long_payload]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_fixed32_frames() -> eyre::Result<()> {
+    let mut big_endian = new_parser(LengthPrefix::Fixed32BigEndian);
+    assert_eq!(
+        parse_chunk(&mut big_endian, &[0, 0, 0, 2, b'h', b'i', 0, 0, 0, 1, b'!'])?,
+        vec![b"hi".to_vec(), b"!".to_vec()]
+    );
+    let mut little_endian = new_parser(LengthPrefix::Fixed32LittleEndian);
+    assert_eq!(
+        parse_chunk(&mut little_endian, &[2, 0, 0, 0, b'h', b'i'])?,
+        vec![b"hi".to_vec()]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_frames_split_between_chunks() -> eyre::Result<()> {
+    let mut parser = new_parser(LengthPrefix::Fixed32BigEndian);
+    assert!(parse_chunk(&mut parser, &[0, 0])?.is_empty());
+    assert!(parse_chunk(&mut parser, &[0, 5, b'h', b'e'])?.is_empty());
+    assert_eq!(
+        parse_chunk(&mut parser, &[b'l', b'l', b'o', 0, 0, 0, 1])?,
+        vec![b"hello".to_vec()]
+    );
+    assert_eq!(parse_chunk(&mut parser, b"!")?, vec![b"!".to_vec()]);
+    Ok(())
+}
+
+#[test]
+fn test_frame_too_large() -> eyre::Result<()> {
+    let mut parser = new_parser(LengthPrefix::Fixed32LittleEndian);
+    let error = parse_chunk(&mut parser, &[0xff, 0xff, 0xff, 0xff]).unwrap_err();
+    assert!(matches!(error, ParseError::FrameTooLarge { .. }));
+    Ok(())
+}
+
+#[test]
+fn test_messages_must_contain_whole_frames() -> eyre::Result<()> {
+    let mut parser = new_parser(LengthPrefix::Varint);
+    let key = Some(b"key".to_vec());
+
+    let whole = ReaderContext::KeyValue((key.clone(), Some(vec![1, b'a', 1, b'b'])));
+    assert_eq!(parser.parse(&whole).map_err(ParseError::from)?.len(), 2);
+
+    let truncated = ReaderContext::KeyValue((key, Some(vec![1, b'a', 2, b'b'])));
+    let error = ParseError::from(parser.parse(&truncated).unwrap_err());
+    assert!(matches!(error, ParseError::TruncatedFrame(2)));
+    Ok(())
+}
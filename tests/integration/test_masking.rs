@@ -0,0 +1,202 @@
+// Copyright © 2024 Pathway
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+
+use pathway_engine::connectors::data_format::{Formatter, IdentityFormatter};
+use pathway_engine::connectors::masking::{
+    ColumnMasking, MaskingError, MaskingFormatter, MaskingMethod,
+};
+use pathway_engine::engine::{Key, Timestamp, Value};
+
+const ENCRYPTION_KEY: [u8; 32] = [7; 32];
+
+fn field_names() -> Vec<String> {
+    vec!["name".to_string(), "email".to_string()]
+}
+
+fn masking(column_name: &str, method: MaskingMethod) -> ColumnMasking {
+    ColumnMasking {
+        column_name: column_name.to_string(),
+        method,
+    }
+}
+
+fn masking_formatter(
+    key_field_names: &[String],
+    column_maskings: &[ColumnMasking],
+) -> Result<MaskingFormatter, MaskingError> {
+    MaskingFormatter::new(
+        Box::new(IdentityFormatter::new()),
+        &field_names(),
+        key_field_names,
+        column_maskings,
+    )
+}
+
+fn format(formatter: &mut MaskingFormatter, values: &[Value]) -> eyre::Result<Vec<Value>> {
+    let context = formatter.format(&Key::random(), values, Timestamp(0), 1)?;
+    Ok(context.values)
+}
+
+#[test]
+fn test_hash() -> eyre::Result<()> {
+    let mut formatter = masking_formatter(&[], &[masking("email", MaskingMethod::Hash)])?;
+    let values = format(
+        &mut formatter,
+        &[Value::from("Alice"), Value::from("alice@example.com")],
+    )?;
+    assert_eq!(
+        values,
+        [
+            Value::from("Alice"),
+            Value::from("ff8d9819fc0e12bf0d24892e45987e249a28dce836a85cad60e28eaaa8c6d976"),
+        ]
+    );
+
+    // The values other than strings are masked in their textual representation
+    let values = format(&mut formatter, &[Value::from("Alice"), Value::Int(42)])?;
+    assert_eq!(
+        values[1],
+        Value::from("73475cb40a568e8da8a045ced110137e159f890ac4da883b6b17dc651b3a8049")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_tokenize() -> eyre::Result<()> {
+    let mut formatter = masking_formatter(
+        &[],
+        &[masking(
+            "email",
+            MaskingMethod::Tokenize {
+                key: b"secret".to_vec(),
+            },
+        )],
+    )?;
+    let values = format(
+        &mut formatter,
+        &[Value::from("Alice"), Value::from("alice@example.com")],
+    )?;
+    assert_eq!(
+        values[1],
+        Value::from("a398d49ce1980b3642bc4dbd110121e3c953e1eadb497d50dea23e9611f83ee7")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_encrypt() -> eyre::Result<()> {
+    let mut formatter = masking_formatter(
+        &[],
+        &[masking(
+            "email",
+            MaskingMethod::Encrypt {
+                key: ENCRYPTION_KEY.to_vec(),
+            },
+        )],
+    )?;
+    let values = [Value::from("Alice"), Value::from("alice@example.com")];
+    let first = format(&mut formatter, &values)?;
+    let second = format(&mut formatter, &values)?;
+    assert_ne!(first[1], second[1]);
+
+    let cipher = Aes256Gcm::new_from_slice(&ENCRYPTION_KEY).expect("key should be valid");
+    for masked in [&first[1], &second[1]] {
+        let Value::String(masked) = masked else {
+            panic!("unexpected masked value: {masked:?}");
+        };
+        let payload = BASE64_STANDARD.decode(masked.as_bytes())?;
+        let (nonce, ciphertext) = payload.split_at(12);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .expect("decryption should succeed");
+        assert_eq!(plaintext, b"alice@example.com");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_missing_values_are_kept() -> eyre::Result<()> {
+    let mut formatter = masking_formatter(
+        &[],
+        &[
+            masking("name", MaskingMethod::Hash),
+            masking(
+                "email",
+                MaskingMethod::Encrypt {
+                    key: ENCRYPTION_KEY.to_vec(),
+                },
+            ),
+        ],
+    )?;
+    let values = format(&mut formatter, &[Value::None, Value::None])?;
+    assert_eq!(values, [Value::None, Value::None]);
+    Ok(())
+}
+
+#[test]
+fn test_invalid_configurations() {
+    assert!(matches!(
+        masking_formatter(&[], &[masking("phone", MaskingMethod::Hash)]),
+        Err(MaskingError::UnknownColumn(name)) if name == "phone"
+    ));
+    assert!(matches!(
+        masking_formatter(
+            &[],
+            &[
+                masking("email", MaskingMethod::Hash),
+                masking("email", MaskingMethod::Hash),
+            ]
+        ),
+        Err(MaskingError::ColumnMaskedTwice(name)) if name == "email"
+    ));
+    assert!(matches!(
+        masking_formatter(
+            &[],
+            &[masking(
+                "email",
+                MaskingMethod::Tokenize { key: Vec::new() }
+            )]
+        ),
+        Err(MaskingError::EmptyTokenizationKey)
+    ));
+    assert!(matches!(
+        masking_formatter(
+            &[],
+            &[masking(
+                "email",
+                MaskingMethod::Encrypt { key: vec![7; 16] }
+            )]
+        ),
+        Err(MaskingError::InvalidEncryptionKeyLength(16))
+    ));
+    assert!(matches!(
+        masking_formatter(
+            &["email".to_string()],
+            &[masking(
+                "email",
+                MaskingMethod::Encrypt {
+                    key: ENCRYPTION_KEY.to_vec()
+                }
+            )]
+        ),
+        Err(MaskingError::EncryptedKeyColumn(name)) if name == "email"
+    ));
+}
+
+#[test]
+fn test_deterministic_masking_of_key_columns() -> eyre::Result<()> {
+    let mut formatter = masking_formatter(
+        &["email".to_string()],
+        &[masking("email", MaskingMethod::Hash)],
+    )?;
+    let values = [Value::from("Alice"), Value::from("alice@example.com")];
+    assert_eq!(
+        format(&mut formatter, &values)?,
+        format(&mut formatter, &values)?
+    );
+    Ok(())
+}
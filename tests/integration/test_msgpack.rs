@@ -0,0 +1,238 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+
+use rmpv::decode::read_value;
+use rmpv::encode::write_value;
+use rmpv::Value as MsgPackValue;
+use serde_json::json;
+
+use pathway_engine::connectors::data_format::{
+    Formatter, InnerSchemaField, MsgPackFormatter, MsgPackParser, ParseError, ParsedEvent, Parser,
+};
+use pathway_engine::connectors::data_storage::{DataEventType, ReaderContext};
+use pathway_engine::connectors::SessionType;
+use pathway_engine::engine::{DateTimeUtc, Key, Timestamp, Type, Value};
+
+use crate::helpers::ReplaceErrors;
+
+fn encode(entries: Vec<(&str, MsgPackValue)>) -> eyre::Result<Vec<u8>> {
+    let map = MsgPackValue::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (MsgPackValue::from(key), value))
+            .collect(),
+    );
+    let mut payload = Vec::new();
+    write_value(&mut payload, &map)?;
+    Ok(payload)
+}
+
+fn new_parser(fields: Vec<(&str, InnerSchemaField)>) -> eyre::Result<MsgPackParser> {
+    let names = fields.iter().map(|(name, _)| (*name).to_string()).collect();
+    let schema: HashMap<String, InnerSchemaField> = fields
+        .into_iter()
+        .map(|(name, field)| (name.to_string(), field))
+        .collect();
+    Ok(MsgPackParser::new(
+        None,
+        names,
+        schema,
+        SessionType::Native,
+    )?)
+}
+
+fn parse_message(parser: &mut MsgPackParser, message: Vec<u8>) -> Result<Vec<Value>, ParseError> {
+    let context = ReaderContext::RawBytes(DataEventType::Insert, message);
+    let mut events = parser.parse(&context).map_err(ParseError::from)?;
+    assert_eq!(events.len(), 1);
+    let ParsedEvent::Insert((_, values)) = events.remove(0).replace_errors() else {
+        panic!("an insertion is expected");
+    };
+    Ok(values)
+}
+
+#[test]
+fn test_msgpack_scalars() -> eyre::Result<()> {
+    let mut parser = new_parser(vec![
+        ("id", InnerSchemaField::new(Type::Int, None)),
+        ("name", InnerSchemaField::new(Type::String, None)),
+        ("score", InnerSchemaField::new(Type::Float, None)),
+        ("active", InnerSchemaField::new(Type::Bool, None)),
+        ("data", InnerSchemaField::new(Type::Bytes, None)),
+    ])?;
+    let message = encode(vec![
+        ("id", MsgPackValue::from(42)),
+        ("name", MsgPackValue::from("Alice")),
+        ("score", MsgPackValue::from(3)),
+        ("active", MsgPackValue::from(true)),
+        ("data", MsgPackValue::Binary(vec![0, 159, 255])),
+        ("unused", MsgPackValue::Nil),
+    ])?;
+    assert_eq!(
+        parse_message(&mut parser, message)?,
+        vec![
+            Value::Int(42),
+            Value::from("Alice"),
+            Value::from(3.0),
+            Value::Bool(true),
+            Value::Bytes(vec![0, 159, 255].into()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_msgpack_timestamp_extension() -> eyre::Result<()> {
+    let mut parser = new_parser(vec![(
+        "created_at",
+        InnerSchemaField::new(Type::DateTimeUtc, None),
+    )])?;
+
+    // The 32-bit form holds only the seconds
+    let message = encode(vec![(
+        "created_at",
+        MsgPackValue::Ext(-1, 1_700_000_000_u32.to_be_bytes().to_vec()),
+    )])?;
+    assert_eq!(
+        parse_message(&mut parser, message)?,
+        vec![Value::DateTimeUtc(DateTimeUtc::new(
+            1_700_000_000_000_000_000
+        ))]
+    );
+
+    // The 96-bit form holds the nanoseconds and the signed seconds
+    let mut data = 500_u32.to_be_bytes().to_vec();
+    data.extend_from_slice(&(-2_i64).to_be_bytes());
+    let message = encode(vec![("created_at", MsgPackValue::Ext(-1, data))])?;
+    assert_eq!(
+        parse_message(&mut parser, message)?,
+        vec![Value::DateTimeUtc(DateTimeUtc::new(-1_999_999_500))]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_msgpack_json_column() -> eyre::Result<()> {
+    let mut parser = new_parser(vec![("payload", InnerSchemaField::new(Type::Json, None))])?;
+    let message = encode(vec![(
+        "payload",
+        MsgPackValue::Map(vec![
+            (MsgPackValue::from("name"), MsgPackValue::from("Bob")),
+            (
+                MsgPackValue::from("tags"),
+                MsgPackValue::Array(vec![MsgPackValue::from(1), MsgPackValue::Nil]),
+            ),
+        ]),
+    )])?;
+    assert_eq!(
+        parse_message(&mut parser, message)?,
+        vec![Value::from(json!({"name": "Bob", "tags": [1, null]}))]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_msgpack_missing_fields() -> eyre::Result<()> {
+    let mut parser = new_parser(vec![
+        ("id", InnerSchemaField::new(Type::Int, None)),
+        (
+            "source",
+            InnerSchemaField::new(Type::String, Some(Value::from("pathway"))),
+        ),
+    ])?;
+    let message = encode(vec![("id", MsgPackValue::from(1))])?;
+    assert_eq!(
+        parse_message(&mut parser, message)?,
+        vec![Value::Int(1), Value::from("pathway")]
+    );
+
+    let message = encode(vec![("source", MsgPackValue::from("kafka"))])?;
+    assert!(matches!(
+        parse_message(&mut parser, message),
+        Err(ParseError::NoDefault { field_name }) if field_name == "id"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_msgpack_invalid_messages() -> eyre::Result<()> {
+    let mut parser = new_parser(vec![("id", InnerSchemaField::new(Type::Int, None))])?;
+
+    let mut message = Vec::new();
+    write_value(&mut message, &MsgPackValue::from(1))?;
+    assert!(matches!(
+        parse_message(&mut parser, message),
+        Err(ParseError::NotMsgPackMap)
+    ));
+
+    let message = encode(vec![("id", MsgPackValue::from("one"))])?;
+    assert!(matches!(
+        parse_message(&mut parser, message),
+        Err(ParseError::FailedToParseFromMsgPack { field_name, .. }) if field_name == "id"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_msgpack_formatter() -> eyre::Result<()> {
+    let mut formatter = MsgPackFormatter::new(vec![
+        "id".to_string(),
+        "data".to_string(),
+        "created_at".to_string(),
+    ]);
+    let context = formatter.format(
+        &Key::random(),
+        &[
+            Value::Int(3),
+            Value::Bytes(vec![1, 2].into()),
+            Value::DateTimeUtc(DateTimeUtc::new(1_500_000_000)),
+        ],
+        Timestamp(10),
+        -1,
+    )?;
+    let payload = context
+        .payloads
+        .into_iter()
+        .next()
+        .expect("a single payload is expected")
+        .into_raw_bytes()?;
+
+    let message = read_value(&mut payload.as_slice())?;
+    let MsgPackValue::Map(entries) = &message else {
+        panic!("a map is expected");
+    };
+    let keys: Vec<_> = entries.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(
+        keys,
+        vec![
+            Some("id"),
+            Some("data"),
+            Some("created_at"),
+            Some("diff"),
+            Some("time")
+        ]
+    );
+    assert_eq!(entries[0].1, MsgPackValue::from(3));
+    assert_eq!(entries[1].1, MsgPackValue::Binary(vec![1, 2]));
+    assert_eq!(entries[3].1, MsgPackValue::from(-1));
+    assert_eq!(entries[4].1, MsgPackValue::from(10));
+
+    // The payload produced by the formatter can be read by the parser
+    let mut parser = new_parser(vec![
+        ("id", InnerSchemaField::new(Type::Int, None)),
+        ("data", InnerSchemaField::new(Type::Bytes, None)),
+        ("created_at", InnerSchemaField::new(Type::DateTimeUtc, None)),
+        ("diff", InnerSchemaField::new(Type::Int, None)),
+    ])?;
+    assert_eq!(
+        parse_message(&mut parser, payload)?,
+        vec![
+            Value::Int(3),
+            Value::Bytes(vec![1, 2].into()),
+            Value::DateTimeUtc(DateTimeUtc::new(1_500_000_000)),
+            Value::Int(-1),
+        ]
+    );
+    Ok(())
+}
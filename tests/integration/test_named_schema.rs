@@ -0,0 +1,144 @@
+// Copyright © 2024 Pathway
+
+use std::sync::Arc;
+
+use pathway_engine::connectors::data_format::{
+    IdentityParser, KeyGenerationPolicy, ParseError, ParsedEventWithErrors, Parser,
+};
+use pathway_engine::connectors::data_storage::{DataEventType, ReaderContext};
+use pathway_engine::connectors::named_schema::{
+    register_schema, registered_schema, value_matches_type, NamedSchema, NamedSchemaError,
+    SchemaColumn, SchemaValidatingParser,
+};
+use pathway_engine::connectors::SessionType;
+use pathway_engine::engine::{Type, Value};
+
+fn column(name: &str, type_: Type) -> SchemaColumn {
+    SchemaColumn {
+        name: name.to_string(),
+        type_,
+        default: None,
+    }
+}
+
+fn orders_schema() -> NamedSchema {
+    NamedSchema {
+        columns: vec![
+            column("order_id", Type::Int),
+            column("note", Type::Optional(Type::String.into())),
+        ],
+        key_column_names: Some(vec!["order_id".to_string()]),
+    }
+}
+
+#[test]
+fn test_registration() -> eyre::Result<()> {
+    register_schema("test_registration_orders", orders_schema())?;
+    // Registering the same schema again is a no-op
+    register_schema("test_registration_orders", orders_schema())?;
+    assert_eq!(
+        *registered_schema("test_registration_orders")?,
+        orders_schema()
+    );
+
+    let mut other_schema = orders_schema();
+    other_schema.columns.pop();
+    assert!(matches!(
+        register_schema("test_registration_orders", other_schema),
+        Err(NamedSchemaError::AlreadyRegistered(_))
+    ));
+    assert!(matches!(
+        registered_schema("test_registration_unknown"),
+        Err(NamedSchemaError::NotRegistered(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_declared_columns() -> eyre::Result<()> {
+    let schema = orders_schema();
+    let key = ["order_id".to_string()];
+    let declared = vec![
+        column("_metadata", Type::Json),
+        column("note", Type::Optional(Type::String.into())),
+        column("order_id", Type::Int),
+    ];
+    assert_eq!(
+        schema.check_declared("orders", &declared, Some(&key))?,
+        vec![2, 1]
+    );
+
+    assert!(matches!(
+        schema.check_declared("orders", &declared, None),
+        Err(NamedSchemaError::KeyMismatch { .. })
+    ));
+    assert!(matches!(
+        schema.check_declared("orders", &declared[..2], Some(&key)),
+        Err(NamedSchemaError::MissingColumn { column, .. }) if column == "order_id"
+    ));
+    let mistyped = vec![column("order_id", Type::Float), declared[1].clone()];
+    assert!(matches!(
+        schema.check_declared("orders", &mistyped, Some(&key)),
+        Err(NamedSchemaError::TypeMismatch { column, .. }) if column == "order_id"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_value_matches_type() {
+    let optional_int = Type::Optional(Type::Int.into());
+    assert!(value_matches_type(&Value::Int(1), &optional_int));
+    assert!(value_matches_type(&Value::None, &optional_int));
+    assert!(!value_matches_type(&Value::None, &Type::Int));
+    assert!(!value_matches_type(&Value::Float(1.0.into()), &Type::Int));
+    assert!(value_matches_type(&Value::Error, &Type::Int));
+
+    let pair = Type::Tuple([Type::Int, Type::String].into());
+    assert!(value_matches_type(
+        &Value::from(vec![Value::Int(1), "a".into()]),
+        &pair
+    ));
+    assert!(!value_matches_type(
+        &Value::from(vec![Value::Int(1), Value::Int(2)]),
+        &pair
+    ));
+    let list = Type::List(Type::Int.into());
+    assert!(value_matches_type(
+        &Value::from(vec![Value::Int(1), Value::Int(2)]),
+        &list
+    ));
+}
+
+#[test]
+fn test_mismatching_values_are_errors() -> eyre::Result<()> {
+    let schema = NamedSchema {
+        columns: vec![column("data", Type::Int)],
+        key_column_names: None,
+    };
+    let inner = IdentityParser::new(
+        vec!["data".to_string()],
+        true,
+        KeyGenerationPolicy::AlwaysAutogenerate,
+        SessionType::Native,
+    );
+    let mut parser = SchemaValidatingParser::new(
+        Box::new(inner),
+        "raw".to_string(),
+        Arc::new(schema),
+        vec![0],
+    );
+
+    let context = ReaderContext::RawBytes(DataEventType::Insert, b"text".to_vec());
+    let events = parser.parse(&context).map_err(ParseError::from)?;
+    let [ParsedEventWithErrors::Insert((_, values))] = events.as_slice() else {
+        panic!("a single insertion is expected");
+    };
+    let [Err(error)] = values.as_slice() else {
+        panic!("the value is expected to be an error");
+    };
+    assert!(matches!(
+        error.downcast_ref::<ParseError>(),
+        Some(ParseError::SchemaMismatch { field_name, .. }) if field_name == "data"
+    ));
+    Ok(())
+}
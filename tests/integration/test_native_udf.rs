@@ -0,0 +1,164 @@
+// Copyright © 2024 Pathway
+
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, OnceLock};
+
+use assert_matches::assert_matches;
+use tempfile::TempDir;
+
+use pathway_engine::engine::error::DynError;
+use pathway_engine::engine::native_udf::NativeUdfError;
+use pathway_engine::engine::{NativeUdfPlugin, Value};
+
+/// Builds the plugin from `tests/data/native_udf_plugin.rs` once per test run.
+fn fixture_plugin() -> &'static Path {
+    static PLUGIN: OnceLock<(TempDir, PathBuf)> = OnceLock::new();
+    let (_dir, path) = PLUGIN.get_or_init(|| {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir
+            .path()
+            .join(format!("{DLL_PREFIX}native_udf_plugin{DLL_SUFFIX}"));
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/native_udf_plugin.rs");
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let status = Command::new(rustc)
+            .args(["--edition", "2021", "--crate-type", "cdylib", "-o"])
+            .arg(&path)
+            .arg(source)
+            .status()
+            .unwrap();
+        assert!(status.success(), "failed to build the native UDF fixture");
+        (dir, path)
+    });
+    path
+}
+
+fn freed_values() -> usize {
+    unsafe {
+        let library = libloading::Library::new(fixture_plugin()).unwrap();
+        let freed_values: libloading::Symbol<extern "C" fn() -> usize> =
+            library.get(b"fixture_freed_values").unwrap();
+        freed_values()
+    }
+}
+
+fn native_udf_error(error: &DynError) -> &NativeUdfError {
+    error.downcast_ref().unwrap()
+}
+
+#[test]
+fn test_missing_library() {
+    let result = NativeUdfPlugin::load("/nonexistent/libpathway_udf_plugin.so");
+    assert_matches!(result, Err(NativeUdfError::Load { .. }));
+}
+
+#[test]
+fn test_library_without_entry_point() {
+    let path = if cfg!(target_os = "macos") {
+        "libSystem.dylib"
+    } else if cfg!(windows) {
+        "kernel32.dll"
+    } else {
+        "libc.so.6"
+    };
+    let result = NativeUdfPlugin::load(path);
+    assert_matches!(result, Err(NativeUdfError::Load { .. }));
+}
+
+#[test]
+fn test_plugin_loaded_once() {
+    let plugin = NativeUdfPlugin::load(fixture_plugin()).unwrap();
+    let reloaded = NativeUdfPlugin::load(fixture_plugin()).unwrap();
+    assert!(Arc::ptr_eq(&plugin, &reloaded));
+    assert_eq!(
+        plugin.function_names(),
+        vec!["add", "negate", "half", "upper", "reverse", "is_none", "fail", "bad_tag"]
+    );
+}
+
+#[test]
+fn test_function_not_found() {
+    let plugin = NativeUdfPlugin::load(fixture_plugin()).unwrap();
+    assert_matches!(
+        plugin.function("subtract"),
+        Err(NativeUdfError::FunctionNotFound { name, .. }) if name == "subtract"
+    );
+}
+
+#[test]
+fn test_value_types() {
+    let plugin = NativeUdfPlugin::load(fixture_plugin()).unwrap();
+    let call = |name: &str, args: &[Value]| plugin.function(name).unwrap().call(args).unwrap();
+
+    assert_eq!(
+        call("add", &[Value::Int(40), Value::Int(2)]),
+        Value::Int(42)
+    );
+    assert_eq!(call("negate", &[Value::Bool(true)]), Value::Bool(false));
+    assert_eq!(call("negate", &[Value::Bool(false)]), Value::Bool(true));
+    assert_eq!(call("half", &[Value::from(5.0)]), Value::from(2.5));
+    assert_eq!(call("upper", &[Value::from("żółw")]), Value::from("ŻÓŁW"));
+    assert_eq!(call("upper", &[Value::from("")]), Value::from(""));
+    assert_eq!(
+        call("reverse", &[Value::Bytes(b"abc".as_slice().into())]),
+        Value::Bytes(b"cba".as_slice().into())
+    );
+    assert_eq!(call("is_none", &[Value::None]), Value::Bool(true));
+    assert_eq!(call("is_none", &[Value::Int(0)]), Value::Bool(false));
+}
+
+#[test]
+fn test_each_result_is_freed() {
+    let plugin = NativeUdfPlugin::load(fixture_plugin()).unwrap();
+    let upper = plugin.function("upper").unwrap();
+    let fail = plugin.function("fail").unwrap();
+    let add = plugin.function("add").unwrap();
+
+    let freed_before = freed_values();
+    upper.call(&[Value::from("a")]).unwrap();
+    fail.call(&[]).unwrap_err();
+    add.call(&[Value::Int(1), Value::Int(2)]).unwrap();
+    assert_eq!(freed_values() - freed_before, 3);
+}
+
+#[test]
+fn test_call_failed() {
+    let plugin = NativeUdfPlugin::load(fixture_plugin()).unwrap();
+    let error = plugin.function("fail").unwrap().call(&[]).unwrap_err();
+    assert_matches!(
+        native_udf_error(&error),
+        NativeUdfError::CallFailed(message) if message == "failure requested"
+    );
+
+    let error = plugin
+        .function("add")
+        .unwrap()
+        .call(&[Value::Int(1), Value::from("2")])
+        .unwrap_err();
+    assert_matches!(
+        native_udf_error(&error),
+        NativeUdfError::CallFailed(message) if message == "add expects two ints"
+    );
+}
+
+#[test]
+fn test_invalid_result_tag() {
+    let plugin = NativeUdfPlugin::load(fixture_plugin()).unwrap();
+    let error = plugin.function("bad_tag").unwrap().call(&[]).unwrap_err();
+    assert_matches!(native_udf_error(&error), NativeUdfError::InvalidTag(42));
+}
+
+#[test]
+fn test_unsupported_argument() {
+    let plugin = NativeUdfPlugin::load(fixture_plugin()).unwrap();
+    let error = plugin
+        .function("is_none")
+        .unwrap()
+        .call(&[Value::Tuple([Value::Int(1)].into())])
+        .unwrap_err();
+    assert_matches!(
+        native_udf_error(&error),
+        NativeUdfError::UnsupportedArgument(_)
+    );
+}
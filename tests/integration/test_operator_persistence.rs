@@ -8,26 +8,33 @@ use differential_dataflow::{ExchangeData, Hashable};
 use futures::channel::oneshot;
 use mockall::mock;
 use mockall::predicate::eq;
+use pathway_engine::connectors::{PersistenceMode, SnapshotAccess};
 use pathway_engine::engine::dataflow::persist::Persist;
 use pathway_engine::engine::dataflow::shard::Shard;
 use pathway_engine::engine::{Timestamp, TotalFrontier};
 use pathway_engine::persistence::backends::{
     BackendPutFuture, Error as BackendError, FilesystemKVStorage, PersistenceBackend,
 };
+use pathway_engine::persistence::config::{
+    PersistenceManagerConfig, PersistenceManagerOuterConfig, PersistentStorageConfig,
+};
 use pathway_engine::persistence::operator_snapshot::{
     ConcreteSnapshotMerger, ConcreteSnapshotReader, ConcreteSnapshotWriter,
     MultiConcreteSnapshotReader, OperatorSnapshotReader, OperatorSnapshotWriter,
 };
-use pathway_engine::persistence::state::{FinalizedTimeQuerier, StoredMetadata};
+use pathway_engine::persistence::state::{
+    FinalizedTimeQuerier, OperatorSnapshotLayout, StoredMetadata,
+};
 use pathway_engine::persistence::PersistenceTime;
 use serde::Deserialize;
 
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::ops::ControlFlow;
+use std::path::Path;
 use std::sync::{mpsc, Arc, Mutex, MutexGuard};
 use std::thread;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use tempfile::tempdir;
 use timely::communication::allocator::Generic;
 use timely::order::TotalOrder;
@@ -557,6 +564,7 @@ fn metadata_from_timestamp(timestamp: Timestamp) -> Vec<u8> {
     StoredMetadata {
         last_advanced_timestamp: TotalFrontier::At(timestamp),
         total_workers: 1,
+        operator_snapshots_resharded: false,
     }
     .serialize()
     .into()
@@ -840,3 +848,93 @@ fn test_snapshot_writer_with_merger() {
     keys.sort();
     assert_eq!(keys, vec!["2-3200-3", "3-2900-4"]);
 }
+
+fn persistence_config(
+    root_path: &Path,
+    worker_id: usize,
+    total_workers: usize,
+) -> PersistenceManagerConfig {
+    PersistenceManagerOuterConfig::new(
+        Duration::ZERO,
+        PersistentStorageConfig::Filesystem(root_path.to_path_buf()),
+        SnapshotAccess::Full,
+        PersistenceMode::OperatorPersisting,
+        true,
+    )
+    .into_inner(worker_id, total_workers)
+}
+
+#[test]
+fn test_state_resharded_with_fewer_workers() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let root_path = test_storage.path();
+    let persistent_id = 42;
+
+    // The first run has four workers, each of them has its own part of the state
+    let mut metadata_accessors = (0..4)
+        .map(|worker_id| persistence_config(root_path, worker_id, 4).create_metadata_storage())
+        .collect::<Result<Vec<_>, _>>()?;
+    for worker_id in 0..4 {
+        let mut writer = persistence_config(root_path, worker_id, 4)
+            .create_operator_snapshot_writer::<i32, isize>(
+                persistent_id,
+                OperatorSnapshotLayout::Streams,
+            )?;
+        let key = i32::try_from(worker_id)?;
+        writer.persist(Timestamp(2), vec![(key, 1), (key + 10, 1)]);
+        let futures = writer.flush(TotalFrontier::At(Timestamp(10)));
+        futures::executor::block_on(futures::future::try_join_all(futures)).unwrap();
+    }
+    for metadata_accessor in &mut metadata_accessors {
+        metadata_accessor.accept_finalized_timestamp(TotalFrontier::At(Timestamp(10)));
+        metadata_accessor.save_current_state()?;
+    }
+
+    // In the second run, the first of two workers gets the entries of its shards
+    let mut config = persistence_config(root_path, 0, 2);
+    let metadata_accessor = config.create_metadata_storage()?;
+    assert_eq!(metadata_accessor.past_runs_total_workers(), Some(4));
+    assert_eq!(
+        metadata_accessor.past_runs_operator_snapshot_layout(),
+        OperatorSnapshotLayout::Streams
+    );
+    assert_eq!(
+        metadata_accessor.operator_snapshot_layout(),
+        OperatorSnapshotLayout::Resharded { total_workers: 2 }
+    );
+    let (mut reader, merger) = config.create_operator_snapshot_readers::<i32, isize>(
+        persistent_id,
+        metadata_accessor.past_runs_threshold_time(),
+        metadata_accessor.past_runs_operator_snapshot_layout(),
+        metadata_accessor.operator_snapshot_layout(),
+    )?;
+    let mut state: Vec<(i32, isize)> = reader.load_persisted()?;
+    state.sort_unstable();
+    assert_eq!(state, vec![(0, 1), (2, 1), (10, 1), (12, 1)]);
+
+    // The re-sharded state is stored separately and compacted as the other snapshots
+    let resharded_backend = FilesystemKVStorage::new(
+        &root_path.join(format!("operator-snapshots/2/0/{persistent_id}")),
+    )?;
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !resharded_backend
+        .list_keys()?
+        .contains(&"1-0-4".to_string())
+    {
+        assert!(
+            Instant::now() < deadline,
+            "the re-sharded snapshot hasn't been compacted"
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+    drop(merger);
+
+    // The snapshots of the first run are left intact
+    let former_worker_backend =
+        FilesystemKVStorage::new(&root_path.join(format!("streams/2/{persistent_id}")))?;
+    assert_eq!(
+        former_worker_backend.list_keys()?,
+        vec!["0-2-2".to_string()]
+    );
+    Ok(())
+}
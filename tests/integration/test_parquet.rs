@@ -0,0 +1,175 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+use std::fs::write;
+use std::sync::Arc;
+
+use deltalake::arrow::array::{
+    ArrayRef, Float32Array, Int32Array, Int64Array, RecordBatch, StringArray,
+};
+use deltalake::arrow::datatypes::{DataType, Field, Schema};
+use deltalake::parquet::arrow::ArrowWriter;
+use tempfile::tempdir;
+
+use pathway_engine::connectors::data_format::{
+    InnerSchemaField, ParquetParser, ParseError, ParsedEvent, Parser,
+};
+use pathway_engine::connectors::data_storage::{ConnectorMode, ReadMethod};
+use pathway_engine::connectors::{DataEventType, ReaderContext, SessionType};
+use pathway_engine::engine::{Type, Value};
+
+use crate::helpers::{new_filesystem_reader, read_data_from_reader, ReplaceErrors};
+
+fn pets_parquet() -> eyre::Result<Vec<u8>> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+        Field::new("weight", DataType::Float32, false),
+        Field::new("owner", DataType::Utf8, true),
+        Field::new("chip_id", DataType::Int64, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec!["Rex", "Tom"])),
+        Arc::new(Int32Array::from(vec![3, 5])),
+        Arc::new(Float32Array::from(vec![12.5, 4.25])),
+        Arc::new(StringArray::from(vec![Some("Alice"), None])),
+        Arc::new(Int64Array::from(vec![1001, 1002])),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+    let mut writer = ArrowWriter::try_new(Vec::new(), schema, None)?;
+    writer.write(&batch)?;
+    Ok(writer.into_inner()?)
+}
+
+fn pets_parser(schema: Vec<(&str, InnerSchemaField)>) -> eyre::Result<ParquetParser> {
+    let value_field_names = schema
+        .iter()
+        .map(|(name, _)| (*name).to_string())
+        .filter(|name| name != "name")
+        .collect();
+    let schema: HashMap<_, _> = schema
+        .into_iter()
+        .map(|(name, field)| (name.to_string(), field))
+        .collect();
+    Ok(ParquetParser::new(
+        Some(vec!["name".to_string()]),
+        value_field_names,
+        schema,
+        SessionType::Native,
+    )?)
+}
+
+fn parse(parser: &mut ParquetParser, context: &ReaderContext) -> eyre::Result<Vec<ParsedEvent>> {
+    Ok(parser
+        .parse(context)
+        .map_err(ParseError::from)?
+        .into_iter()
+        .map(ReplaceErrors::replace_errors)
+        .collect())
+}
+
+#[test]
+fn test_parquet_file_read() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let path = test_storage.path().join("pets.parquet");
+    write(&path, pets_parquet()?)?;
+
+    let reader = new_filesystem_reader(
+        path.to_str().unwrap(),
+        ConnectorMode::Static,
+        ReadMethod::Full,
+        "*",
+        false,
+    )?;
+    let parser = pets_parser(vec![
+        ("name", InnerSchemaField::new(Type::String, None)),
+        ("age", InnerSchemaField::new(Type::Int, None)),
+        ("weight", InnerSchemaField::new(Type::Float, None)),
+        (
+            "owner",
+            InnerSchemaField::new(Type::Optional(Type::String.into()), None),
+        ),
+    ])?;
+    let entries = read_data_from_reader(Box::new(reader), Box::new(parser))?;
+
+    assert_eq!(
+        entries,
+        vec![
+            ParsedEvent::Insert((
+                Some(vec![Value::from("Rex")]),
+                vec![Value::Int(3), Value::from(12.5), Value::from("Alice")]
+            )),
+            ParsedEvent::Insert((
+                Some(vec![Value::from("Tom")]),
+                vec![Value::Int(5), Value::from(4.25), Value::None]
+            )),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parquet_deletion() -> eyre::Result<()> {
+    let mut parser = pets_parser(vec![
+        ("name", InnerSchemaField::new(Type::String, None)),
+        ("chip_id", InnerSchemaField::new(Type::Int, None)),
+    ])?;
+    let context = ReaderContext::from_raw_bytes(DataEventType::Delete, pets_parquet()?);
+    assert_eq!(
+        parse(&mut parser, &context)?,
+        vec![
+            ParsedEvent::Delete((Some(vec![Value::from("Rex")]), vec![Value::Int(1001)])),
+            ParsedEvent::Delete((Some(vec![Value::from("Tom")]), vec![Value::Int(1002)])),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parquet_missing_column_uses_default() -> eyre::Result<()> {
+    let mut parser = pets_parser(vec![
+        ("name", InnerSchemaField::new(Type::String, None)),
+        (
+            "color",
+            InnerSchemaField::new(Type::String, Some("black".into())),
+        ),
+    ])?;
+    let context = ReaderContext::from_raw_bytes(DataEventType::Insert, pets_parquet()?);
+    assert_eq!(
+        parse(&mut parser, &context)?,
+        vec![
+            ParsedEvent::Insert((Some(vec![Value::from("Rex")]), vec![Value::from("black")])),
+            ParsedEvent::Insert((Some(vec![Value::from("Tom")]), vec![Value::from("black")])),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parquet_type_mismatch() -> eyre::Result<()> {
+    let mut parser = pets_parser(vec![
+        ("name", InnerSchemaField::new(Type::String, None)),
+        ("owner", InnerSchemaField::new(Type::Int, None)),
+    ])?;
+    let context = ReaderContext::from_raw_bytes(DataEventType::Insert, pets_parquet()?);
+    assert_eq!(
+        parse(&mut parser, &context)?,
+        vec![
+            ParsedEvent::Insert((Some(vec![Value::from("Rex")]), vec![Value::Error])),
+            ParsedEvent::Insert((Some(vec![Value::from("Tom")]), vec![Value::Error])),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parquet_malformed_file() -> eyre::Result<()> {
+    let mut parser = pets_parser(vec![("name", InnerSchemaField::new(Type::String, None))])?;
+    let context = ReaderContext::from_raw_bytes(DataEventType::Insert, b"name,age\n".to_vec());
+    let error = parser.parse(&context).unwrap_err();
+    assert!(error.to_string().starts_with("failed to read parquet file"));
+
+    let empty = ReaderContext::from_raw_bytes(DataEventType::Insert, Vec::new());
+    assert_eq!(parse(&mut parser, &empty)?, vec![]);
+    Ok(())
+}
@@ -0,0 +1,189 @@
+// Copyright © 2024 Pathway
+
+use std::fs::{self, File};
+use std::path::Path;
+
+use deltalake::parquet::file::reader::{FileReader, SerializedFileReader};
+use deltalake::parquet::record::RowAccessor;
+
+use pathway_engine::connectors::data_format::FormatterContext;
+use pathway_engine::connectors::data_storage::Writer;
+use pathway_engine::connectors::file_rotation::{
+    FileRotationConfig, NameTemplate, SUCCESS_MARKER_NAME,
+};
+use pathway_engine::connectors::parquet_output::ParquetFileWriter;
+use pathway_engine::engine::{Key, Timestamp, Type, Value};
+
+fn config(max_file_size: Option<u64>) -> FileRotationConfig {
+    FileRotationConfig {
+        name_template: NameTemplate::parse("{table}-{part}.{ext}").unwrap(),
+        table_name: "orders".to_string(),
+        extension: "parquet".to_string(),
+        max_file_size,
+        rotation_interval: None,
+        write_success_markers: true,
+        repeat_header: false,
+    }
+}
+
+fn new_writer(directory: &Path, max_file_size: Option<u64>) -> eyre::Result<ParquetFileWriter> {
+    Ok(ParquetFileWriter::new(
+        directory.to_path_buf(),
+        config(max_file_size),
+        &["item".to_string(), "amount".to_string()],
+        &[Type::String, Type::Optional(Type::Int.into())],
+        2,
+    )?)
+}
+
+fn row(item: &str, amount: Option<i64>, time: u64, diff: isize) -> FormatterContext {
+    FormatterContext::new(
+        Vec::<Vec<u8>>::new(),
+        Key::random(),
+        vec![Value::from(item), amount.map_or(Value::None, Value::Int)],
+        Timestamp(time),
+        diff,
+    )
+}
+
+fn directory_contents(directory: &Path) -> eyre::Result<Vec<String>> {
+    let mut names: Vec<_> = fs::read_dir(directory)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().to_string()))
+        .collect::<eyre::Result<_>>()?;
+    names.sort();
+    Ok(names)
+}
+
+type OutputRow = (String, Option<i64>, i64, i64);
+
+fn read_rows(path: &Path) -> eyre::Result<(usize, Vec<OutputRow>)> {
+    let reader = SerializedFileReader::new(File::open(path)?)?;
+    let row_groups = reader.metadata().num_row_groups();
+    let mut rows = Vec::new();
+    for row in reader {
+        let row = row?;
+        let amount = row.get_long(1).ok();
+        rows.push((
+            row.get_string(0)?.clone(),
+            amount,
+            row.get_long(2)?,
+            row.get_long(3)?,
+        ));
+    }
+    Ok((row_groups, rows))
+}
+
+#[test]
+fn test_parquet_output_single_file() -> eyre::Result<()> {
+    let directory = tempfile::tempdir()?;
+    let mut writer = new_writer(directory.path(), None)?;
+    writer.write(row("apple", Some(3), 0, 1))?;
+    writer.write(row("pear", None, 0, 1))?;
+    writer.flush(true)?;
+    writer.write(row("apple", Some(3), 2, -1))?;
+    writer.flush(true)?;
+
+    // The footer isn't written yet, so the file still has a temporary name
+    assert_eq!(
+        directory_contents(directory.path())?,
+        vec![".orders-00000.parquet.inprogress"]
+    );
+
+    writer.on_time_committed(None)?;
+    assert_eq!(
+        directory_contents(directory.path())?,
+        vec![SUCCESS_MARKER_NAME, "orders-00000.parquet"]
+    );
+    let (row_groups, rows) = read_rows(&directory.path().join("orders-00000.parquet"))?;
+    assert_eq!(row_groups, 2);
+    assert_eq!(
+        rows,
+        vec![
+            ("apple".to_string(), Some(3), 0, 1),
+            ("pear".to_string(), None, 0, 1),
+            ("apple".to_string(), Some(3), 2, -1),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parquet_output_rotated_by_size() -> eyre::Result<()> {
+    let directory = tempfile::tempdir()?;
+    let mut writer = new_writer(directory.path(), Some(1))?;
+    writer.write(row("apple", Some(3), 0, 1))?;
+    writer.flush(true)?;
+    writer.write(row("pear", Some(5), 2, 1))?;
+    writer.flush(true)?;
+
+    // Each of the files reaches the size limit right away, so both are complete
+    assert_eq!(
+        directory_contents(directory.path())?,
+        vec!["orders-00000.parquet", "orders-00001.parquet"]
+    );
+    writer.on_time_committed(None)?;
+    assert_eq!(
+        directory_contents(directory.path())?,
+        vec![
+            SUCCESS_MARKER_NAME,
+            "orders-00000.parquet",
+            "orders-00001.parquet"
+        ]
+    );
+    assert_eq!(
+        read_rows(&directory.path().join("orders-00000.parquet"))?.1,
+        vec![("apple".to_string(), Some(3), 0, 1)]
+    );
+    assert_eq!(
+        read_rows(&directory.path().join("orders-00001.parquet"))?.1,
+        vec![("pear".to_string(), Some(5), 2, 1)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parquet_output_subdirectory_published_at_end() -> eyre::Result<()> {
+    let directory = tempfile::tempdir()?;
+    let mut writer = ParquetFileWriter::new(
+        directory.path().to_path_buf(),
+        FileRotationConfig {
+            name_template: NameTemplate::parse("{table}/{part}.{ext}").unwrap(),
+            ..config(None)
+        },
+        &["item".to_string(), "amount".to_string()],
+        &[Type::String, Type::Optional(Type::Int.into())],
+        2,
+    )?;
+    writer.write(row("apple", Some(3), 0, 1))?;
+    writer.flush(true)?;
+    assert!(directory_contents(directory.path())?
+        .iter()
+        .all(|name| name.starts_with('.')));
+
+    writer.on_time_committed(None)?;
+    assert_eq!(directory_contents(directory.path())?, vec!["orders"]);
+    assert_eq!(
+        directory_contents(&directory.path().join("orders"))?,
+        vec!["00000.parquet", SUCCESS_MARKER_NAME]
+    );
+    assert_eq!(
+        read_rows(&directory.path().join("orders/00000.parquet"))?.1,
+        vec![("apple".to_string(), Some(3), 0, 1)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parquet_output_type_mismatch() -> eyre::Result<()> {
+    let directory = tempfile::tempdir()?;
+    let mut writer = new_writer(directory.path(), None)?;
+    writer.write(FormatterContext::new(
+        Vec::<Vec<u8>>::new(),
+        Key::random(),
+        vec![Value::Int(1), Value::Int(2)],
+        Timestamp(0),
+        1,
+    ))?;
+    assert!(writer.flush(true).is_err());
+    Ok(())
+}
@@ -0,0 +1,78 @@
+// Copyright © 2024 Pathway
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use assert_matches::assert_matches;
+
+use pathway_engine::connectors::pausing::{
+    connector_states, set_connector_paused, shared_pause_switch, ConnectorKind, ConnectorState,
+    PauseError,
+};
+
+#[test]
+fn test_pause_switch_is_shared_by_name() -> eyre::Result<()> {
+    let first = shared_pause_switch("pausing-shared", ConnectorKind::Source);
+    let second = shared_pause_switch("pausing-shared", ConnectorKind::Source);
+    let state = set_connector_paused("pausing-shared", true)?;
+    assert_eq!(
+        state,
+        ConnectorState {
+            name: "pausing-shared".to_string(),
+            kind: ConnectorKind::Source,
+            paused: true,
+        }
+    );
+    assert!(first.is_paused());
+    assert!(second.is_paused());
+    assert!(connector_states().contains(&state));
+
+    drop(first);
+    drop(second);
+    assert_matches!(
+        set_connector_paused("pausing-shared", false),
+        Err(PauseError::UnknownConnector(name)) if name == "pausing-shared"
+    );
+    assert!(!connector_states()
+        .iter()
+        .any(|state| state.name == "pausing-shared"));
+    Ok(())
+}
+
+#[test]
+fn test_paused_connector_waits_for_resume() -> eyre::Result<()> {
+    let pause_switch = shared_pause_switch("pausing-resume", ConnectorKind::Sink);
+    set_connector_paused("pausing-resume", true)?;
+    let waiter = {
+        let pause_switch = pause_switch.clone();
+        thread::spawn(move || pause_switch.wait_while_paused(|| false))
+    };
+    thread::sleep(Duration::from_millis(200));
+    assert!(!waiter.is_finished());
+
+    set_connector_paused("pausing-resume", false)?;
+    waiter.join().unwrap();
+    assert!(!pause_switch.is_paused());
+    Ok(())
+}
+
+#[test]
+fn test_paused_connector_stops_waiting_on_request() -> eyre::Result<()> {
+    let pause_switch = shared_pause_switch("pausing-stop", ConnectorKind::Source);
+    set_connector_paused("pausing-stop", true)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let waiter = {
+        let pause_switch = pause_switch.clone();
+        let stop = stop.clone();
+        thread::spawn(move || pause_switch.wait_while_paused(|| stop.load(Ordering::SeqCst)))
+    };
+    thread::sleep(Duration::from_millis(200));
+    assert!(!waiter.is_finished());
+
+    stop.store(true, Ordering::SeqCst);
+    waiter.join().unwrap();
+    assert!(pause_switch.is_paused());
+    Ok(())
+}
@@ -0,0 +1,56 @@
+// Copyright © 2024 Pathway
+
+use std::fs::File;
+use std::io::{ErrorKind, Read, Write};
+
+use pathway_engine::pipe::{pipe, read, ReaderType, WriterType};
+
+#[test]
+fn test_blocking_pipe_passes_data() -> eyre::Result<()> {
+    let pipe = pipe(ReaderType::Blocking, WriterType::Blocking)?;
+    let mut reader = File::from(pipe.reader);
+    let mut writer = File::from(pipe.writer);
+    writer.write_all(b"wakeup")?;
+    drop(writer);
+    let mut buffer = Vec::new();
+    reader.read_to_end(&mut buffer)?;
+    assert_eq!(buffer, b"wakeup");
+    Ok(())
+}
+
+#[test]
+fn test_non_blocking_reader_does_not_wait() -> eyre::Result<()> {
+    let pipe = pipe(ReaderType::NonBlocking, WriterType::Blocking)?;
+    let mut reader = File::from(pipe.reader);
+    let mut writer = File::from(pipe.writer);
+
+    let error = read(&mut reader, &mut [0; 16]).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::WouldBlock);
+
+    writer.write_all(b"wakeup")?;
+    let mut buffer = [0; 16];
+    let amount = read(&mut reader, &mut buffer)?;
+    assert_eq!(&buffer[..amount], b"wakeup");
+    Ok(())
+}
+
+#[test]
+fn test_non_blocking_writer_does_not_wait_when_full() -> eyre::Result<()> {
+    let pipe = pipe(ReaderType::Blocking, WriterType::NonBlocking)?;
+    let mut writer = File::from(pipe.writer);
+    let chunk = [0; 4096];
+    let mut written = 0;
+    // a blocking writer would hang once the buffer of the pipe is full
+    loop {
+        match writer.write(&chunk) {
+            Ok(0) => break,
+            Ok(amount) => written += amount,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            Err(e) => return Err(e.into()),
+        }
+        assert!(written < 64 * 1024 * 1024, "the pipe never got full");
+    }
+    assert!(written > 0);
+    drop(pipe.reader);
+    Ok(())
+}
@@ -0,0 +1,65 @@
+// Copyright © 2024 Pathway
+
+use opentelemetry::metrics::MeterProvider;
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+
+use pathway_engine::engine::prometheus::Reader;
+
+fn provider_with_reader() -> (SdkMeterProvider, Reader) {
+    let reader = Reader::new();
+    let provider = SdkMeterProvider::builder()
+        .with_reader(reader.clone())
+        .build();
+    (provider, reader)
+}
+
+#[test]
+fn test_encodes_gauges_with_unit_suffix() {
+    let (provider, reader) = provider_with_reader();
+    let meter = provider.meter("test");
+    meter
+        .u64_observable_gauge("latency.input")
+        .with_unit("ms")
+        .with_description("Input latency")
+        .with_callback(|observer| observer.observe(42, &[]))
+        .build();
+
+    let text = reader.encode().unwrap();
+    assert!(text.contains("# HELP latency_input_milliseconds Input latency\n"));
+    assert!(text.contains("# TYPE latency_input_milliseconds gauge\n"));
+    assert!(text.contains("latency_input_milliseconds 42\n"));
+}
+
+#[test]
+fn test_encodes_counters_with_labels() {
+    let (provider, reader) = provider_with_reader();
+    let counter = provider.meter("test").u64_counter("rows").build();
+    counter.add(3, &[KeyValue::new("connector", "in\"put")]);
+    counter.add(2, &[KeyValue::new("connector", "in\"put")]);
+
+    let text = reader.encode().unwrap();
+    assert!(text.contains("# TYPE rows_total counter\n"));
+    assert!(text.contains("rows_total{connector=\"in\\\"put\"} 5\n"));
+}
+
+#[test]
+fn test_encodes_histograms_cumulatively() {
+    let (provider, reader) = provider_with_reader();
+    let histogram = provider
+        .meter("test")
+        .f64_histogram("duration")
+        .with_boundaries(vec![1.0, 10.0])
+        .build();
+    histogram.record(0.5, &[]);
+    histogram.record(5.0, &[]);
+    histogram.record(50.0, &[]);
+
+    let text = reader.encode().unwrap();
+    assert!(text.contains("# TYPE duration histogram\n"));
+    assert!(text.contains("duration_bucket{le=\"1\"} 1\n"));
+    assert!(text.contains("duration_bucket{le=\"10\"} 2\n"));
+    assert!(text.contains("duration_bucket{le=\"+Inf\"} 3\n"));
+    assert!(text.contains("duration_sum 55.5\n"));
+    assert!(text.contains("duration_count 3\n"));
+}
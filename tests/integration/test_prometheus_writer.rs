@@ -0,0 +1,219 @@
+// Copyright © 2024 Pathway
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+
+use prost::Message;
+
+use pathway_engine::connectors::data_format::FormatterContext;
+use pathway_engine::connectors::data_storage::{WriteError, Writer};
+use pathway_engine::connectors::prometheus::{PrometheusError, PrometheusWriter};
+use pathway_engine::engine::{DateTimeUtc, Key, Timestamp, Value};
+
+#[derive(Clone, PartialEq, Message)]
+struct WriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    timeseries: Vec<TimeSeries>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TimeSeries {
+    #[prost(message, repeated, tag = "1")]
+    labels: Vec<Label>,
+    #[prost(message, repeated, tag = "2")]
+    samples: Vec<Sample>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Label {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    value: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Sample {
+    #[prost(double, tag = "1")]
+    value: f64,
+    #[prost(int64, tag = "2")]
+    timestamp: i64,
+}
+
+/// Accepts a single request, responds to it with the given status line and returns its
+/// headers and body.
+fn serve_once(status_line: &'static str) -> (String, mpsc::Receiver<(Vec<String>, Vec<u8>)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/api/v1/write", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end().to_lowercase();
+            if line.is_empty() {
+                break;
+            }
+            headers.push(line);
+        }
+        let content_length: usize = headers
+            .iter()
+            .find_map(|header| header.strip_prefix("content-length: "))
+            .unwrap()
+            .parse()
+            .unwrap();
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let mut stream = stream;
+        write!(stream, "{status_line}\r\ncontent-length: 0\r\n\r\n").unwrap();
+        sender.send((headers, body)).unwrap();
+    });
+    (url, receiver)
+}
+
+fn row(values: Vec<Value>, diff: isize) -> FormatterContext {
+    FormatterContext::new(
+        Vec::<Vec<u8>>::new(),
+        Key::random(),
+        values,
+        Timestamp(0),
+        diff,
+    )
+}
+
+fn new_writer(url: String) -> eyre::Result<PrometheusWriter> {
+    Ok(PrometheusWriter::new(
+        url,
+        &[("X-Scope-OrgID".to_string(), "tenant".to_string())],
+        vec![
+            ("shop_orders".to_string(), 1),
+            ("shop_revenue".to_string(), 2),
+        ],
+        vec![("region".to_string(), 0)],
+        Some(3),
+    )?)
+}
+
+#[test]
+fn test_samples_are_grouped_into_series() -> eyre::Result<()> {
+    let (url, receiver) = serve_once("HTTP/1.1 204 No Content");
+    let mut writer = new_writer(url)?;
+    let time = |ms: i64| Value::DateTimeUtc(DateTimeUtc::new(ms * 1_000_000));
+    writer.write(row(
+        vec![
+            "eu".into(),
+            Value::Int(2),
+            Value::Float(15.5.into()),
+            time(2000),
+        ],
+        1,
+    ))?;
+    writer.write(row(
+        vec![
+            "eu".into(),
+            Value::Int(1),
+            Value::Float(10.5.into()),
+            time(1000),
+        ],
+        1,
+    ))?;
+    // Deletions are not sent and samples without values are skipped
+    writer.write(row(
+        vec![
+            "eu".into(),
+            Value::Int(7),
+            Value::Float(7.0.into()),
+            time(500),
+        ],
+        -1,
+    ))?;
+    writer.write(row(
+        vec!["us".into(), Value::Int(3), Value::None, time(1000)],
+        1,
+    ))?;
+    writer.flush(true)?;
+
+    let (headers, body) = receiver.recv()?;
+    assert!(headers.contains(&"content-encoding: snappy".to_string()));
+    assert!(headers.contains(&"x-scope-orgid: tenant".to_string()));
+    let request =
+        WriteRequest::decode(snap::raw::Decoder::new().decompress_vec(&body)?.as_slice())?;
+
+    let series: Vec<_> = request
+        .timeseries
+        .into_iter()
+        .map(|series| {
+            let labels: Vec<_> = series
+                .labels
+                .into_iter()
+                .map(|label| format!("{}={}", label.name, label.value))
+                .collect();
+            let samples: Vec<_> = series
+                .samples
+                .into_iter()
+                .map(|sample| (sample.timestamp, sample.value))
+                .collect();
+            (labels.join(","), samples)
+        })
+        .collect();
+    assert_eq!(
+        series,
+        vec![
+            (
+                "__name__=shop_orders,region=eu".to_string(),
+                vec![(1000, 1.0), (2000, 2.0)]
+            ),
+            (
+                "__name__=shop_orders,region=us".to_string(),
+                vec![(1000, 3.0)]
+            ),
+            (
+                "__name__=shop_revenue,region=eu".to_string(),
+                vec![(1000, 10.5), (2000, 15.5)]
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_rejected_samples_are_not_retried() -> eyre::Result<()> {
+    let (url, receiver) = serve_once("HTTP/1.1 400 Bad Request");
+    let mut writer = new_writer(url)?;
+    writer.write(row(
+        vec![
+            "eu".into(),
+            Value::Int(1),
+            Value::Float(1.0.into()),
+            Value::Int(1000),
+        ],
+        1,
+    ))?;
+    let error = writer.flush(true).unwrap_err();
+    receiver.recv()?;
+    assert!(matches!(
+        error,
+        WriteError::Prometheus(PrometheusError::Rejected { .. })
+    ));
+    assert!(!error.is_retryable());
+    Ok(())
+}
+
+#[test]
+fn test_non_numeric_metric_value() -> eyre::Result<()> {
+    let mut writer = new_writer("http://127.0.0.1:1/api/v1/write".to_string())?;
+    let result = writer.write(row(
+        vec!["eu".into(), "many".into(), Value::None, Value::Int(1000)],
+        1,
+    ));
+    assert!(matches!(
+        result,
+        Err(WriteError::Prometheus(PrometheusError::NotANumber(..)))
+    ));
+    Ok(())
+}
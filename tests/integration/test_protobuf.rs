@@ -0,0 +1,343 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+
+use prost::Message;
+use prost_types::field_descriptor_proto::{Label, Type as FieldType};
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+    FileDescriptorProto, FileDescriptorSet, MessageOptions, OneofDescriptorProto,
+};
+use serde_json::json;
+
+use pathway_engine::connectors::data_format::{
+    InnerSchemaField, ParseError, ParsedEvent, Parser, ProtobufParser,
+};
+use pathway_engine::connectors::data_storage::{DataEventType, ReaderContext};
+use pathway_engine::connectors::SessionType;
+use pathway_engine::engine::{DateTimeUtc, Type, Value};
+
+use crate::helpers::ReplaceErrors;
+
+#[derive(Clone, PartialEq, Message)]
+struct Timestamp {
+    #[prost(int64, tag = "1")]
+    seconds: i64,
+    #[prost(int32, tag = "2")]
+    nanos: i32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Customer {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Order {
+    #[prost(int64, tag = "1")]
+    id: i64,
+    #[prost(string, tag = "2")]
+    item: String,
+    #[prost(sint32, tag = "3")]
+    delta: i32,
+    #[prost(int32, tag = "4")]
+    status: i32,
+    #[prost(message, optional, tag = "5")]
+    customer: Option<Customer>,
+    #[prost(int32, repeated, tag = "6")]
+    quantities: Vec<i32>,
+    #[prost(map = "string, int64", tag = "7")]
+    tags: HashMap<String, i64>,
+    #[prost(message, optional, tag = "8")]
+    created_at: Option<Timestamp>,
+    #[prost(string, optional, tag = "9")]
+    note: Option<String>,
+    #[prost(bytes = "vec", tag = "10")]
+    payload: Vec<u8>,
+    #[prost(double, tag = "11")]
+    price: f64,
+}
+
+/// The same message with a field added in a newer version of the schema.
+#[derive(Clone, PartialEq, Message)]
+struct OrderV2 {
+    #[prost(int64, tag = "1")]
+    id: i64,
+    #[prost(string, tag = "20")]
+    warehouse: String,
+}
+
+fn field(name: &str, number: i32, type_: FieldType, label: Label) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: Some(name.to_string()),
+        number: Some(number),
+        r#type: Some(type_.into()),
+        label: Some(label.into()),
+        ..Default::default()
+    }
+}
+
+fn typed_field(name: &str, number: i32, type_: FieldType, type_name: &str) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        type_name: Some(type_name.to_string()),
+        ..field(name, number, type_, Label::Optional)
+    }
+}
+
+fn descriptor_set() -> Vec<u8> {
+    let timestamp_file = FileDescriptorProto {
+        name: Some("google/protobuf/timestamp.proto".to_string()),
+        package: Some("google.protobuf".to_string()),
+        syntax: Some("proto3".to_string()),
+        message_type: vec![DescriptorProto {
+            name: Some("Timestamp".to_string()),
+            field: vec![
+                field("seconds", 1, FieldType::Int64, Label::Optional),
+                field("nanos", 2, FieldType::Int32, Label::Optional),
+            ],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let tags_entry = DescriptorProto {
+        name: Some("TagsEntry".to_string()),
+        field: vec![
+            field("key", 1, FieldType::String, Label::Optional),
+            field("value", 2, FieldType::Int64, Label::Optional),
+        ],
+        options: Some(MessageOptions {
+            map_entry: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let order = DescriptorProto {
+        name: Some("Order".to_string()),
+        field: vec![
+            field("id", 1, FieldType::Int64, Label::Optional),
+            field("item", 2, FieldType::String, Label::Optional),
+            field("delta", 3, FieldType::Sint32, Label::Optional),
+            typed_field("status", 4, FieldType::Enum, ".shop.Status"),
+            typed_field("customer", 5, FieldType::Message, ".shop.Customer"),
+            field("quantities", 6, FieldType::Int32, Label::Repeated),
+            FieldDescriptorProto {
+                label: Some(Label::Repeated.into()),
+                ..typed_field("tags", 7, FieldType::Message, ".shop.Order.TagsEntry")
+            },
+            typed_field(
+                "created_at",
+                8,
+                FieldType::Message,
+                ".google.protobuf.Timestamp",
+            ),
+            FieldDescriptorProto {
+                oneof_index: Some(0),
+                proto3_optional: Some(true),
+                ..field("note", 9, FieldType::String, Label::Optional)
+            },
+            field("payload", 10, FieldType::Bytes, Label::Optional),
+            field("price", 11, FieldType::Double, Label::Optional),
+        ],
+        nested_type: vec![tags_entry],
+        oneof_decl: vec![OneofDescriptorProto {
+            name: Some("_note".to_string()),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let shop_file = FileDescriptorProto {
+        name: Some("shop.proto".to_string()),
+        package: Some("shop".to_string()),
+        syntax: Some("proto3".to_string()),
+        dependency: vec!["google/protobuf/timestamp.proto".to_string()],
+        message_type: vec![
+            order,
+            DescriptorProto {
+                name: Some("Customer".to_string()),
+                field: vec![field("name", 1, FieldType::String, Label::Optional)],
+                ..Default::default()
+            },
+        ],
+        enum_type: vec![EnumDescriptorProto {
+            name: Some("Status".to_string()),
+            value: vec![
+                EnumValueDescriptorProto {
+                    name: Some("UNKNOWN".to_string()),
+                    number: Some(0),
+                    ..Default::default()
+                },
+                EnumValueDescriptorProto {
+                    name: Some("SHIPPED".to_string()),
+                    number: Some(1),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    FileDescriptorSet {
+        file: vec![timestamp_file, shop_file],
+    }
+    .encode_to_vec()
+}
+
+fn order_columns() -> Vec<(String, InnerSchemaField)> {
+    let optional_json = Type::Optional(Type::Json.into());
+    [
+        ("id", Type::Int),
+        ("item", Type::String),
+        ("delta", Type::Int),
+        ("status", Type::String),
+        ("customer", optional_json.clone()),
+        ("quantities", Type::Json),
+        ("tags", Type::Json),
+        ("created_at", Type::Optional(Type::DateTimeUtc.into())),
+        ("note", Type::Optional(Type::String.into())),
+        ("payload", Type::Bytes),
+        ("price", Type::Float),
+    ]
+    .into_iter()
+    .map(|(name, type_)| (name.to_string(), InnerSchemaField::new(type_, None)))
+    .collect()
+}
+
+fn new_parser(columns: Vec<(String, InnerSchemaField)>) -> eyre::Result<ProtobufParser> {
+    Ok(ProtobufParser::new(
+        None,
+        columns.iter().map(|(name, _)| name.clone()).collect(),
+        columns.into_iter().collect(),
+        SessionType::Native,
+        &descriptor_set(),
+        "shop.Order",
+    )?)
+}
+
+fn parse_message(parser: &mut ProtobufParser, message: Vec<u8>) -> Result<Vec<Value>, ParseError> {
+    let context = ReaderContext::RawBytes(DataEventType::Insert, message);
+    let mut events = parser.parse(&context).map_err(ParseError::from)?;
+    assert_eq!(events.len(), 1);
+    let ParsedEvent::Insert((_, values)) = events.remove(0).replace_errors() else {
+        panic!("an insertion is expected");
+    };
+    Ok(values)
+}
+
+#[test]
+fn test_protobuf_all_field_types() -> eyre::Result<()> {
+    let mut parser = new_parser(order_columns())?;
+    let order = Order {
+        id: 42,
+        item: "lamp".to_string(),
+        delta: -3,
+        status: 1,
+        customer: Some(Customer {
+            name: "Alice".to_string(),
+        }),
+        quantities: vec![1, 2, 3],
+        tags: HashMap::from([("color".to_string(), 7)]),
+        created_at: Some(Timestamp {
+            seconds: 1,
+            nanos: 500,
+        }),
+        note: Some("fragile".to_string()),
+        payload: vec![1, 2],
+        price: 2.5,
+    };
+    assert_eq!(
+        parse_message(&mut parser, order.encode_to_vec())?,
+        vec![
+            Value::Int(42),
+            Value::from("lamp"),
+            Value::Int(-3),
+            Value::from("SHIPPED"),
+            Value::from(json!({"name": "Alice"})),
+            Value::from(json!([1, 2, 3])),
+            Value::from(json!({"color": 7})),
+            Value::DateTimeUtc(DateTimeUtc::new(1_000_000_500)),
+            Value::from("fragile"),
+            Value::Bytes(vec![1, 2].into()),
+            Value::Float(2.5.into()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_protobuf_missing_fields() -> eyre::Result<()> {
+    let mut parser = new_parser(order_columns())?;
+    // The fields without explicit presence get their default values, the others are absent
+    assert_eq!(
+        parse_message(&mut parser, Vec::new())?,
+        vec![
+            Value::Int(0),
+            Value::from(""),
+            Value::Int(0),
+            Value::from("UNKNOWN"),
+            Value::None,
+            Value::from(json!([])),
+            Value::from(json!({})),
+            Value::None,
+            Value::None,
+            Value::Bytes(Vec::new().into()),
+            Value::Float(0.0.into()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_protobuf_unknown_fields_skipped() -> eyre::Result<()> {
+    let mut parser = new_parser(vec![(
+        "id".to_string(),
+        InnerSchemaField::new(Type::Int, None),
+    )])?;
+    let order = OrderV2 {
+        id: 5,
+        warehouse: "north".to_string(),
+    };
+    assert_eq!(
+        parse_message(&mut parser, order.encode_to_vec())?,
+        vec![Value::Int(5)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_protobuf_truncated_message() -> eyre::Result<()> {
+    let mut parser = new_parser(order_columns())?;
+    let mut message = Order {
+        item: "lamp".to_string(),
+        ..Default::default()
+    }
+    .encode_to_vec();
+    message.pop();
+    assert!(matches!(
+        parse_message(&mut parser, message),
+        Err(ParseError::MalformedProtobufMessage(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_protobuf_unknown_message_type() {
+    let result = ProtobufParser::new(
+        None,
+        vec![],
+        HashMap::new(),
+        SessionType::Native,
+        &descriptor_set(),
+        "shop.Invoice",
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_protobuf_column_not_in_message() {
+    let result = new_parser(vec![(
+        "discount".to_string(),
+        InnerSchemaField::new(Type::Float, None),
+    )]);
+    assert!(result.is_err());
+}
@@ -0,0 +1,110 @@
+// Copyright © 2024 Pathway
+
+use assert_matches::assert_matches;
+use serde_json::json;
+
+use pathway_engine::connectors::query_endpoint::{MaterializedTable, QueryError};
+use pathway_engine::engine::{Key, Value};
+
+fn parameters(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+        .collect()
+}
+
+fn pets() -> (MaterializedTable, Key, Key) {
+    let mut table = MaterializedTable::new(vec!["owner".to_string(), "age".to_string()]);
+    let alice = Key::random();
+    let bob = Key::random();
+    table.apply(vec![
+        (alice, vec![Value::from("Alice"), Value::Int(3)], 1),
+        (bob, vec![Value::from("Bob"), Value::Int(5)], 1),
+    ]);
+    (table, alice, bob)
+}
+
+#[test]
+fn test_query_all_rows() -> eyre::Result<()> {
+    let (table, alice, bob) = pets();
+    let rows = table.query(&[])?;
+    let (first, second) = if alice < bob {
+        (alice, bob)
+    } else {
+        (bob, alice)
+    };
+    let ids: Vec<_> = rows
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| row["id"].clone())
+        .collect();
+    assert_eq!(
+        ids,
+        vec![json!(first.to_string()), json!(second.to_string())]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_query_by_id_and_column() -> eyre::Result<()> {
+    let (table, alice, _) = pets();
+    let expected = json!([{"id": alice.to_string(), "owner": "Alice", "age": 3}]);
+    assert_eq!(
+        table.query(&parameters(&[("id", &alice.to_string())]))?,
+        expected
+    );
+    assert_eq!(table.query(&parameters(&[("owner", "Alice")]))?, expected);
+    assert_eq!(table.query(&parameters(&[("age", "3")]))?, expected);
+    assert_eq!(
+        table.query(&parameters(&[("owner", "Alice"), ("age", "5")]))?,
+        json!([])
+    );
+    assert_eq!(
+        table
+            .query(&parameters(&[("limit", "1")]))?
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+    Ok(())
+}
+
+#[test]
+fn test_update_replaces_row() -> eyre::Result<()> {
+    let (mut table, alice, bob) = pets();
+    // the insertion comes before the deletion it replaces
+    table.apply(vec![
+        (alice, vec![Value::from("Alice"), Value::Int(4)], 1),
+        (alice, vec![Value::from("Alice"), Value::Int(3)], -1),
+        (bob, vec![Value::from("Bob"), Value::Int(5)], -1),
+    ]);
+    assert_eq!(table.len(), 1);
+    assert_eq!(
+        table.query(&[])?,
+        json!([{"id": alice.to_string(), "owner": "Alice", "age": 4}])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_invalid_parameters() {
+    let (table, _, _) = pets();
+    assert_matches!(
+        table.query(&parameters(&[("id", "")])),
+        Err(QueryError::InvalidId(_))
+    );
+    assert_matches!(
+        table.query(&parameters(&[("id", "^xyz")])),
+        Err(QueryError::InvalidId(_))
+    );
+    assert_matches!(
+        table.query(&parameters(&[("limit", "-1")])),
+        Err(QueryError::InvalidLimit(_))
+    );
+    assert_matches!(
+        table.query(&parameters(&[("color", "black")])),
+        Err(QueryError::UnknownColumn(_))
+    );
+}
@@ -0,0 +1,131 @@
+// Copyright © 2024 Pathway
+
+use std::thread;
+
+use pathway_engine::engine::reload::{self, Error, ReloadableSettings};
+use pathway_engine::engine::stateless_pool;
+use pathway_engine::env::ConfigFile;
+use pathway_engine::retry::global_retry_budget;
+
+#[test]
+fn test_settings_from_config_file() -> eyre::Result<()> {
+    let config_file = ConfigFile::from_toml_str(
+        r#"
+        [runtime]
+        log_level = "warn"
+        max_batch_size = 100
+        stateless_threads = 8
+        threads = 4
+
+        [telemetry]
+        metrics_reader_interval_seconds = 120
+        "#,
+    )?;
+    let settings = ReloadableSettings::from_config_file(Some(&config_file))?;
+    assert_eq!(
+        settings,
+        ReloadableSettings {
+            log_level: Some("warn".to_string()),
+            retry_budget_per_minute: None,
+            max_batch_size: Some(100),
+            metrics_export_interval_seconds: Some(120),
+            stateless_threads: Some(8),
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_merge_keeps_unset_fields() {
+    let settings = ReloadableSettings {
+        log_level: Some("info".to_string()),
+        max_batch_size: Some(100),
+        ..Default::default()
+    };
+    let update = ReloadableSettings {
+        max_batch_size: Some(10),
+        retry_budget_per_minute: Some(5),
+        ..Default::default()
+    };
+    assert_eq!(
+        settings.merge(&update),
+        ReloadableSettings {
+            log_level: Some("info".to_string()),
+            retry_budget_per_minute: Some(5),
+            max_batch_size: Some(10),
+            metrics_export_interval_seconds: None,
+            stateless_threads: None,
+        }
+    );
+}
+
+// the settings are global, so they are changed in a single test
+#[test]
+fn test_update_from_json() -> eyre::Result<()> {
+    reload::apply(ReloadableSettings::default())?;
+    assert_eq!(reload::max_batch_size(Some(1000)), Some(1000));
+
+    let settings = reload::update_from_json(br#"{"max_batch_size": 10, "log_level": "info"}"#)?;
+    assert_eq!(settings.max_batch_size, Some(10));
+    assert_eq!(reload::max_batch_size(Some(1000)), Some(10));
+    assert_eq!(reload::max_batch_size(None), Some(10));
+    assert_eq!(log::max_level(), log::LevelFilter::Info);
+
+    let settings = reload::update_from_json(br#"{"retry_budget_per_minute": 3}"#)?;
+    assert_eq!(settings.log_level.as_deref(), Some("info"));
+    let budget = global_retry_budget().expect("budget should be set");
+    for _ in 0..3 {
+        assert!(budget.try_acquire());
+    }
+    assert!(!budget.try_acquire());
+
+    // the stateless operators keep the order of the results on any number of threads
+    let items: Vec<usize> = (0..10_000).collect();
+    let doubled: Vec<usize> = items.iter().map(|item| item * 2).collect();
+    let double = |chunk: &[usize]| -> Vec<usize> { chunk.iter().map(|item| item * 2).collect() };
+    assert_eq!(stateless_pool::current_threads(), None);
+    assert_eq!(
+        stateless_pool::map_chunks(&items, 100, false, double),
+        doubled
+    );
+    reload::update_from_json(br#"{"stateless_threads": 4}"#)?;
+    assert_eq!(stateless_pool::current_threads(), Some(4));
+    assert_eq!(
+        stateless_pool::map_chunks(&items, 100, false, double),
+        doubled
+    );
+    reload::update_from_json(br#"{"stateless_threads": 2}"#)?;
+    assert_eq!(stateless_pool::current_threads(), Some(2));
+    assert_eq!(
+        stateless_pool::map_chunks(&items, 100, false, double),
+        doubled
+    );
+
+    // the expressions that have to stay on the worker don't use the pool
+    let worker = thread::current().id();
+    let threads = stateless_pool::map_chunks(&items, 100, true, |chunk| {
+        vec![thread::current().id(); chunk.len()]
+    });
+    assert!(threads.iter().all(|thread| *thread == worker));
+
+    // invalid updates are rejected as a whole
+    assert!(matches!(
+        reload::update_from_json(br#"{"max_batch_size": 5, "log_level": "loud"}"#),
+        Err(Error::InvalidValue("log_level", _))
+    ));
+    assert!(matches!(
+        reload::update_from_json(br#"{"stateless_threads": 0}"#),
+        Err(Error::InvalidValue("stateless_threads", _))
+    ));
+    assert!(matches!(
+        reload::update_from_json(br#"{"batch_size": 5}"#),
+        Err(Error::MalformedJson(_))
+    ));
+    assert_eq!(reload::current().max_batch_size, Some(10));
+
+    reload::apply(ReloadableSettings::default())?;
+    assert_eq!(log::max_level(), log::LevelFilter::Debug);
+    assert!(global_retry_budget().is_none());
+    assert_eq!(stateless_pool::current_threads(), None);
+    Ok(())
+}
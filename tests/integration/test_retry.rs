@@ -0,0 +1,316 @@
+// Copyright © 2024 Pathway
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use s3::error::S3Error;
+
+use pathway_engine::connectors::scanner::s3::is_retryable_s3_error;
+use pathway_engine::engine::dataflow::operators::external_index::Index;
+use pathway_engine::engine::error::{DynError, DynResult, Trace};
+use pathway_engine::engine::report_error::LogError;
+use pathway_engine::engine::{DataError, Key, Value};
+use pathway_engine::external_integration::{
+    AddDataEntry, ExternalIndex, IndexDerivedImpl, QueryEntry,
+};
+use pathway_engine::retry::{
+    execute_with_circuit_breaker, execute_with_retries_if, shared_circuit_breaker, CircuitBreaker,
+    CircuitState, RetryBudget, RetryConfig, RetryPolicy,
+};
+
+fn fast_retries() -> RetryConfig {
+    RetryConfig::new(Duration::from_millis(1), 1.0, Duration::ZERO).with_budget(None)
+}
+
+fn count_attempts(
+    retry_config: RetryConfig,
+    is_retryable: impl Fn(&i32) -> bool,
+    max_retries: usize,
+) -> usize {
+    let attempts = Cell::new(0);
+    let result: Result<(), i32> = execute_with_retries_if(
+        || {
+            attempts.set(attempts.get() + 1);
+            Err(1)
+        },
+        is_retryable,
+        retry_config,
+        max_retries,
+    );
+    assert_eq!(result, Err(1));
+    attempts.get()
+}
+
+#[test]
+fn test_retries_until_limit() {
+    assert_eq!(count_attempts(fast_retries(), |_| true, 3), 4);
+}
+
+#[test]
+fn test_non_retryable_error_is_returned_immediately() {
+    assert_eq!(count_attempts(fast_retries(), |_| false, 3), 1);
+}
+
+#[test]
+fn test_max_elapsed_time() {
+    let retry_config = RetryConfig::new(Duration::from_millis(100), 1.0, Duration::ZERO)
+        .with_budget(None)
+        .with_max_elapsed_time(Duration::from_millis(250));
+    // retries start after 100ms and 200ms, the next one would start after 300ms
+    assert_eq!(count_attempts(retry_config, |_| true, 10), 3);
+}
+
+#[test]
+fn test_full_jitter_delays_are_within_backoff() {
+    let mut retry_config = RetryConfig::new(Duration::from_millis(100), 2.0, Duration::ZERO)
+        .with_budget(None)
+        .with_full_jitter();
+    for max_delay in [100, 200, 400, 800] {
+        let delay = retry_config.next_delay().unwrap();
+        assert!(delay <= Duration::from_millis(max_delay));
+    }
+}
+
+#[test]
+fn test_budget_is_shared() {
+    let budget = Arc::new(RetryBudget::new(3, Duration::from_secs(60)));
+    let retry_config = || fast_retries().with_budget(Some(budget.clone()));
+
+    assert_eq!(count_attempts(retry_config(), |_| true, 2), 3);
+    // only one retry is left in the budget
+    assert_eq!(count_attempts(retry_config(), |_| true, 2), 2);
+    assert_eq!(count_attempts(retry_config(), |_| true, 2), 1);
+}
+
+#[test]
+fn test_budget_window_is_renewed() {
+    let budget = RetryBudget::new(1, Duration::from_millis(10));
+    assert!(budget.try_acquire());
+    assert!(!budget.try_acquire());
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(budget.try_acquire());
+}
+
+#[test]
+fn test_retryable_s3_errors() {
+    assert!(is_retryable_s3_error(&S3Error::HttpFailWithBody(
+        503,
+        String::new()
+    )));
+    assert!(is_retryable_s3_error(&S3Error::HttpFailWithBody(
+        429,
+        String::new()
+    )));
+    assert!(!is_retryable_s3_error(&S3Error::HttpFailWithBody(
+        404,
+        String::new()
+    )));
+    assert!(!is_retryable_s3_error(&S3Error::HttpFailWithBody(
+        403,
+        String::new()
+    )));
+}
+
+#[test]
+fn test_circuit_breaker_opens_after_consecutive_failures() {
+    let circuit_breaker = CircuitBreaker::new("test", 2, Duration::from_secs(60));
+    assert!(circuit_breaker.try_acquire());
+    circuit_breaker.on_failure();
+    circuit_breaker.on_success();
+    circuit_breaker.on_failure();
+    assert_eq!(circuit_breaker.stats().state, CircuitState::Closed);
+    circuit_breaker.on_failure();
+    assert_eq!(circuit_breaker.stats().state, CircuitState::Open);
+
+    assert!(!circuit_breaker.try_acquire());
+    let stats = circuit_breaker.stats();
+    assert_eq!(stats.times_opened, 1);
+    assert_eq!(stats.rejected_calls, 1);
+}
+
+#[test]
+fn test_circuit_breaker_half_open() {
+    let circuit_breaker = CircuitBreaker::new("test", 1, Duration::from_millis(10));
+    circuit_breaker.on_failure();
+    std::thread::sleep(Duration::from_millis(20));
+
+    // only a single trial call is allowed
+    assert!(circuit_breaker.try_acquire());
+    assert!(!circuit_breaker.try_acquire());
+    assert_eq!(circuit_breaker.stats().state, CircuitState::HalfOpen);
+
+    circuit_breaker.on_failure();
+    assert_eq!(circuit_breaker.stats().state, CircuitState::Open);
+    assert_eq!(circuit_breaker.stats().times_opened, 2);
+
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(circuit_breaker.try_acquire());
+    circuit_breaker.on_success();
+    assert_eq!(circuit_breaker.stats().state, CircuitState::Closed);
+}
+
+#[test]
+fn test_open_circuit_fails_fast() {
+    let circuit_breaker = CircuitBreaker::new("test", 2, Duration::from_secs(60));
+    let attempts = Cell::new(0);
+    let call = || -> Result<(), DynError> {
+        execute_with_circuit_breaker(
+            &circuit_breaker,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err(DynError::from("unavailable"))
+            },
+            |_| true,
+            fast_retries(),
+            5,
+        )
+    };
+
+    // the circuit opens on the second attempt, so the remaining retries are not made
+    let error = call().unwrap_err();
+    assert_eq!(attempts.get(), 2);
+    assert!(error
+        .to_string()
+        .contains("circuit breaker \"test\" is open"));
+
+    call().unwrap_err();
+    assert_eq!(attempts.get(), 2);
+}
+
+#[test]
+fn test_non_retryable_errors_dont_open_circuit() {
+    let circuit_breaker = CircuitBreaker::new("test", 1, Duration::from_secs(60));
+    for _ in 0..3 {
+        let result: Result<(), DynError> = execute_with_circuit_breaker(
+            &circuit_breaker,
+            || Err(DynError::from("invalid entry")),
+            |_| false,
+            fast_retries(),
+            5,
+        );
+        assert_eq!(result.unwrap_err().to_string(), "invalid entry");
+    }
+    assert_eq!(circuit_breaker.stats().state, CircuitState::Closed);
+}
+
+#[test]
+fn test_shared_circuit_breaker() {
+    let first = shared_circuit_breaker("test_shared_circuit_breaker");
+    let second = shared_circuit_breaker("test_shared_circuit_breaker");
+    assert!(Arc::ptr_eq(&first, &second));
+    for _ in 0..5 {
+        first.on_failure();
+    }
+    assert_eq!(second.stats().state, CircuitState::Open);
+    drop(first);
+    drop(second);
+
+    // dropped with the last user, so a new one starts closed
+    let third = shared_circuit_breaker("test_shared_circuit_breaker");
+    assert_eq!(third.stats().state, CircuitState::Closed);
+}
+
+#[test]
+fn test_retry_policy_overrides_defaults() {
+    assert_eq!(RetryPolicy::default().max_retries_or(2), 2);
+
+    let retry_policy = RetryPolicy {
+        max_retries: Some(4),
+        initial_delay: Some(Duration::from_millis(1)),
+        backoff_factor: Some(1.0),
+        jitter: Some(Duration::ZERO),
+        max_elapsed_time: None,
+    };
+    assert_eq!(retry_policy.max_retries_or(2), 4);
+    let retry_config = retry_policy.retry_config().with_budget(None);
+    assert_eq!(
+        count_attempts(retry_config, |_| true, retry_policy.max_retries_or(2)),
+        5
+    );
+}
+
+#[test]
+fn test_retry_policy_max_elapsed_time() {
+    let retry_policy = RetryPolicy {
+        initial_delay: Some(Duration::from_millis(100)),
+        backoff_factor: Some(1.0),
+        jitter: Some(Duration::ZERO),
+        max_elapsed_time: Some(Duration::from_millis(50)),
+        ..RetryPolicy::default()
+    };
+    let retry_config = retry_policy.retry_config().with_budget(None);
+    assert_eq!(count_attempts(retry_config, |_| true, 5), 1);
+}
+
+struct UnavailableIndex {
+    calls: Rc<Cell<usize>>,
+}
+
+impl ExternalIndex for UnavailableIndex {
+    fn add(&mut self, add_data: Vec<AddDataEntry>) -> Vec<(Key, DynResult<()>)> {
+        unimplemented!("{} entries", add_data.len())
+    }
+
+    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+        unimplemented!("{} keys", keys.len())
+    }
+
+    fn search(&self, query_data: &[QueryEntry]) -> Vec<(Key, DynResult<Value>)> {
+        self.calls.set(self.calls.get() + 1);
+        (0..query_data.len())
+            .map(|_| (Key::random(), Err("index unavailable".into())))
+            .collect()
+    }
+}
+
+struct CountingLogger {
+    errors: Rc<Cell<usize>>,
+}
+
+impl LogError for CountingLogger {
+    fn log_error(&self, _error: DataError) {
+        self.errors.set(self.errors.get() + 1);
+    }
+
+    fn log_error_with_trace(&self, _error: DynError, _trace: &Trace) {
+        self.errors.set(self.errors.get() + 1);
+    }
+}
+
+#[test]
+fn test_circuit_breaker_stops_external_index_calls() {
+    let calls = Rc::new(Cell::new(0));
+    let errors = Rc::new(Cell::new(0));
+    let circuit_breaker = Arc::new(CircuitBreaker::new("index", 2, Duration::from_secs(60)));
+    let index = IndexDerivedImpl::new(
+        Box::new(UnavailableIndex {
+            calls: calls.clone(),
+        }),
+        circuit_breaker.clone(),
+        Box::new(CountingLogger {
+            errors: errors.clone(),
+        }),
+        Box::new(Value::clone),
+        Box::new(|_| None),
+        Box::new(Value::clone),
+        Box::new(|_| None),
+        Box::new(|_| None),
+    );
+
+    for _ in 0..4 {
+        let results = Index::<Key, Value, isize, Key, Value, Value>::search(
+            &index,
+            vec![(Key::random(), Value::Int(1), 1)],
+        );
+        let [(_key, Value::Tuple(result), 1)] = results.as_slice() else {
+            panic!("unexpected search results: {results:?}");
+        };
+        assert_eq!(result[1], Value::Error);
+    }
+    assert_eq!(calls.get(), 2);
+    assert_eq!(errors.get(), 4);
+    assert_eq!(circuit_breaker.stats().state, CircuitState::Open);
+    assert_eq!(circuit_breaker.stats().rejected_calls, 2);
+}
@@ -0,0 +1,104 @@
+// Copyright © 2024 Pathway
+
+use pathway_engine::connectors::schema_drift::{
+    json_value_kind, FieldObservation, SchemaDriftEvent, SchemaDriftKind, SchemaDriftTracker,
+};
+use pathway_engine::engine::Type;
+
+fn tracker() -> SchemaDriftTracker {
+    SchemaDriftTracker::new(
+        "events".to_string(),
+        vec!["user_id".to_string(), "amount".to_string()],
+        ["user_id".to_string(), "amount".to_string()],
+    )
+}
+
+fn kinds_and_fields(events: &[SchemaDriftEvent]) -> Vec<(SchemaDriftKind, &str)> {
+    events
+        .iter()
+        .map(|event| (event.kind, event.field.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_new_field_is_reported_once() {
+    let mut tracker = tracker();
+    assert!(tracker
+        .observe_field_names(["user_id", "amount"])
+        .is_empty());
+    let events = tracker.observe_field_names(["user_id", "amount", "currency"]);
+    assert_eq!(
+        kinds_and_fields(&events),
+        [(SchemaDriftKind::NewField, "currency")]
+    );
+    assert_eq!(events[0].source, "events");
+    assert!(tracker
+        .observe_field_names(["currency", "user_id"])
+        .is_empty());
+}
+
+#[test]
+fn test_type_conflict_is_reported_once_per_kind_of_value() {
+    let mut tracker = tracker();
+    let string_in_amount = || {
+        [
+            FieldObservation::Present,
+            FieldObservation::TypeConflict {
+                expected: Type::Float,
+                observed: json_value_kind(&serde_json::json!("12.5")).to_string(),
+            },
+        ]
+    };
+    let events = tracker.observe_entry(string_in_amount());
+    assert_eq!(
+        kinds_and_fields(&events),
+        [(SchemaDriftKind::TypeConflict, "amount")]
+    );
+    assert_eq!(
+        events[0].details,
+        "expected a value of type float, got a string"
+    );
+    assert!(tracker.observe_entry(string_in_amount()).is_empty());
+
+    let events = tracker.observe_entry([
+        FieldObservation::Present,
+        FieldObservation::TypeConflict {
+            expected: Type::Float,
+            observed: json_value_kind(&serde_json::json!([1])).to_string(),
+        },
+    ]);
+    assert_eq!(
+        kinds_and_fields(&events),
+        [(SchemaDriftKind::TypeConflict, "amount")]
+    );
+}
+
+#[test]
+fn test_null_rate_change_between_windows() {
+    let mut tracker = tracker().with_null_rate_window(4, 0.5);
+    let mut observe = |missing_amounts: usize| {
+        let mut events = Vec::new();
+        for index in 0..4 {
+            let amount = if index < missing_amounts {
+                FieldObservation::Missing
+            } else {
+                FieldObservation::Present
+            };
+            events.extend(tracker.observe_entry([FieldObservation::Present, amount]));
+        }
+        events
+    };
+    // The first window only sets the baseline
+    assert!(observe(0).is_empty());
+    assert!(observe(1).is_empty());
+    let events = observe(3);
+    assert_eq!(
+        kinds_and_fields(&events),
+        [(SchemaDriftKind::NullRateChange, "amount")]
+    );
+    assert_eq!(
+        events[0].details,
+        "share of missing values changed from 25.0% to 75.0%"
+    );
+    assert!(observe(3).is_empty());
+}
@@ -0,0 +1,111 @@
+// Copyright © 2024 Pathway
+
+use std::fs;
+
+use pathway_engine::connectors::data_format::FormatterContext;
+use pathway_engine::connectors::data_storage::{
+    ReadError, ReadResult, Reader, ReaderContext, WriteError,
+};
+use pathway_engine::connectors::sdk::template::{FileLinesSink, FileLinesSource};
+use pathway_engine::connectors::sdk::testing::{check_resume, read_all, write_all, HarnessError};
+use pathway_engine::connectors::sdk::{ConnectorError, Source, SourceReader, SourceRecord};
+use pathway_engine::engine::{Key, Timestamp, Value};
+
+/// A source that can't be replayed, which ignores the position it's resumed from.
+struct CountingSource {
+    next: u8,
+    limit: u8,
+}
+
+impl Source for CountingSource {
+    fn next_record(&mut self) -> Result<Option<SourceRecord>, ConnectorError> {
+        if self.next == self.limit {
+            return Ok(None);
+        }
+        self.next += 1;
+        Ok(Some(
+            SourceRecord::new(vec![self.next], vec![self.next]).with_key(b"key".to_vec()),
+        ))
+    }
+}
+
+#[test]
+fn test_template_source() -> eyre::Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = directory.path().join("input.txt");
+    fs::write(&path, "foo\nbar\n\nbaz")?;
+    assert_eq!(
+        read_all(FileLinesSource::new(&path))?,
+        vec![
+            b"foo".to_vec(),
+            b"bar".to_vec(),
+            Vec::new(),
+            b"baz".to_vec()
+        ]
+    );
+    check_resume(|| FileLinesSource::new(&path))?;
+    Ok(())
+}
+
+#[test]
+fn test_template_source_invalid_position() {
+    let mut source = FileLinesSource::new("unused.txt");
+    assert!(matches!(
+        source.resume(b"abc"),
+        Err(ConnectorError::InvalidPosition(position)) if position == b"abc"
+    ));
+}
+
+#[test]
+fn test_source_reader_passes_keys() -> eyre::Result<()> {
+    let mut reader = SourceReader::new(CountingSource { next: 0, limit: 1 });
+    let ReadResult::Data(context, _) = reader.read()? else {
+        panic!("a record is expected");
+    };
+    assert!(matches!(
+        context,
+        ReaderContext::KeyValue((Some(key), Some(value))) if key == b"key" && value == [1]
+    ));
+    assert!(matches!(reader.read()?, ReadResult::Finished));
+    Ok(())
+}
+
+#[test]
+fn test_check_resume_detects_replay() {
+    let result = check_resume(|| CountingSource { next: 0, limit: 3 });
+    assert!(matches!(
+        result,
+        Err(HarnessError::ResumeMismatch { index: 0, .. })
+    ));
+}
+
+#[test]
+fn test_template_sink() -> eyre::Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = directory.path().join("output.txt");
+    let entry = |payload: &str, time: u64| {
+        FormatterContext::new(
+            vec![payload.as_bytes().to_vec()],
+            Key::random(),
+            vec![Value::from(payload)],
+            Timestamp(time),
+            1,
+        )
+    };
+    write_all(
+        FileLinesSink::new(&path)?,
+        vec![entry("foo", 0), entry("bar", 0), entry("baz", 2)],
+    )?;
+    assert_eq!(fs::read_to_string(&path)?, "foo\nbar\nbaz\n");
+    Ok(())
+}
+
+#[test]
+fn test_connector_errors_retryability() {
+    let transient: WriteError = ConnectorError::transient("timed out").into();
+    assert!(transient.is_retryable());
+    let fatal: WriteError = ConnectorError::other("access denied").into();
+    assert!(!fatal.is_retryable());
+    let read_error: ReadError = ConnectorError::other("access denied").into();
+    assert_eq!(read_error.to_string(), "access denied");
+}
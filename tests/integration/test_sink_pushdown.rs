@@ -0,0 +1,107 @@
+// Copyright © 2024 Pathway
+
+use pathway_engine::connectors::data_format::{Formatter, IdentityFormatter};
+use pathway_engine::connectors::masking::{ColumnMasking, MaskingFormatter, MaskingMethod};
+use pathway_engine::connectors::pushdown::{ComparisonOp, PushdownFormatter, SinkPredicate};
+use pathway_engine::engine::{Key, Timestamp, Value};
+
+fn compare(column_index: usize, op: ComparisonOp, value: Value) -> SinkPredicate {
+    SinkPredicate::Compare {
+        column_index,
+        op,
+        value,
+    }
+}
+
+fn row(name: &str, age: Value) -> Vec<Value> {
+    vec![Value::from(name), age]
+}
+
+#[test]
+fn test_comparisons() -> eyre::Result<()> {
+    let adult = compare(1, ComparisonOp::Ge, Value::Int(18));
+    assert!(adult.matches(&row("Alice", Value::Int(30)))?);
+    assert!(adult.matches(&row("Bob", Value::Int(18)))?);
+    assert!(!adult.matches(&row("Carol", Value::Int(17)))?);
+    // The integers and the floats are compared by value
+    assert!(adult.matches(&row("Dave", Value::from(18.5)))?);
+    assert!(!adult.matches(&row("Eve", Value::from(17.5)))?);
+    // The values of the other kinds are not comparable
+    assert!(!adult.matches(&row("Frank", Value::None))?);
+    assert!(!adult.matches(&row("Grace", Value::from("30")))?);
+
+    let not_thirty = compare(1, ComparisonOp::Ne, Value::Int(30));
+    assert!(!not_thirty.matches(&row("Alice", Value::Int(30)))?);
+    assert!(not_thirty.matches(&row("Frank", Value::None))?);
+    Ok(())
+}
+
+#[test]
+fn test_null_checks_and_combinations() -> eyre::Result<()> {
+    let predicate = SinkPredicate::Or(vec![
+        SinkPredicate::IsNone(1),
+        SinkPredicate::And(vec![
+            compare(0, ComparisonOp::Eq, Value::from("Alice")),
+            SinkPredicate::Not(Box::new(compare(1, ComparisonOp::Lt, Value::Int(18)))),
+        ]),
+    ]);
+    assert!(predicate.matches(&row("Bob", Value::None))?);
+    assert!(predicate.matches(&row("Alice", Value::Int(30)))?);
+    assert!(!predicate.matches(&row("Alice", Value::Int(10)))?);
+    assert!(!predicate.matches(&row("Bob", Value::Int(30)))?);
+    Ok(())
+}
+
+#[test]
+fn test_pushdown_formatter() -> eyre::Result<()> {
+    let mut formatter = PushdownFormatter::new(
+        Box::new(IdentityFormatter::new()),
+        Some(compare(1, ComparisonOp::Gt, Value::Int(20))),
+        Some(vec![1, 0]),
+    );
+    assert!(formatter.is_filtered_out(&row("Bob", Value::Int(17)))?);
+    assert!(!formatter.is_filtered_out(&row("Alice", Value::Int(30)))?);
+
+    let context = formatter.format(
+        &Key::random(),
+        &row("Alice", Value::Int(30)),
+        Timestamp(0),
+        1,
+    )?;
+    assert_eq!(context.values, [Value::Int(30), Value::from("Alice")]);
+    Ok(())
+}
+
+#[test]
+fn test_pushdown_formatter_without_predicate() -> eyre::Result<()> {
+    let formatter = PushdownFormatter::new(Box::new(IdentityFormatter::new()), None, Some(vec![0]));
+    assert!(!formatter.is_filtered_out(&row("Bob", Value::Int(17)))?);
+    Ok(())
+}
+
+#[test]
+fn test_masking_refers_to_projected_columns() -> eyre::Result<()> {
+    let masking_formatter = MaskingFormatter::new(
+        Box::new(IdentityFormatter::new()),
+        &["name".to_string()],
+        &[],
+        &[ColumnMasking {
+            column_name: "name".to_string(),
+            method: MaskingMethod::Hash,
+        }],
+    )?;
+    let mut formatter = PushdownFormatter::new(Box::new(masking_formatter), None, Some(vec![0]));
+    let context = formatter.format(
+        &Key::random(),
+        &row("Alice", Value::Int(30)),
+        Timestamp(0),
+        1,
+    )?;
+    assert_eq!(
+        context.values,
+        [Value::from(
+            "3bc51062973c458d5a6f2d8d64a023246354ad7e064b1e4e009ec8a0699a3043"
+        )]
+    );
+    Ok(())
+}
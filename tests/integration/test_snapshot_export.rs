@@ -0,0 +1,212 @@
+// Copyright © 2024 Pathway
+
+use std::fs::{read_to_string, File};
+use std::thread;
+use std::time::Duration;
+
+use assert_matches::assert_matches;
+use deltalake::parquet::file::reader::{FileReader, SerializedFileReader};
+use tempfile::tempdir;
+
+use pathway_engine::connectors::data_format::{Formatter, IdentityFormatter};
+use pathway_engine::connectors::data_storage::Writer;
+use pathway_engine::connectors::snapshot_export::{
+    export_snapshot, ExportError, ExportFormat, ExportedSnapshot, SnapshotExportWriter,
+};
+use pathway_engine::engine::{Key, Timestamp, Type, Value};
+
+fn pets_writer(name: &str) -> Result<SnapshotExportWriter, ExportError> {
+    SnapshotExportWriter::new(
+        name.to_string(),
+        vec!["owner".to_string(), "age".to_string()],
+        vec![Type::String, Type::Int],
+        true,
+    )
+}
+
+fn write(
+    writer: &mut SnapshotExportWriter,
+    key: Key,
+    values: &[Value],
+    time: u64,
+    diff: isize,
+) -> eyre::Result<()> {
+    let context = IdentityFormatter::new().format(&key, values, Timestamp(time), diff)?;
+    writer.write(context)?;
+    Ok(())
+}
+
+#[test]
+fn test_export_contains_committed_times() -> eyre::Result<()> {
+    let directory = tempdir()?;
+    let path = directory.path().join("pets.csv");
+    let mut writer = pets_writer("test_export_contains_committed_times")?;
+    let key = Key::random();
+    write(
+        &mut writer,
+        key,
+        &[Value::from("Alice"), Value::Int(3)],
+        2,
+        1,
+    )?;
+    write(
+        &mut writer,
+        key,
+        &[Value::from("Alice"), Value::Int(3)],
+        4,
+        -1,
+    )?;
+    write(
+        &mut writer,
+        key,
+        &[Value::from("Alice"), Value::Int(4)],
+        4,
+        1,
+    )?;
+    writer.on_time_committed(Some(Timestamp(4)))?;
+
+    let snapshot = export_snapshot(
+        "test_export_contains_committed_times",
+        &path,
+        ExportFormat::Csv,
+        None,
+        None,
+    )?;
+    assert_eq!(
+        snapshot,
+        ExportedSnapshot {
+            rows: 1,
+            time: Some(Timestamp(4)),
+        }
+    );
+    assert_eq!(
+        read_to_string(&path)?,
+        format!("owner,age,id\nAlice,3,{key}\n")
+    );
+
+    writer.on_time_committed(Some(Timestamp(6)))?;
+    export_snapshot(
+        "test_export_contains_committed_times",
+        &path,
+        ExportFormat::Csv,
+        None,
+        None,
+    )?;
+    assert_eq!(
+        read_to_string(&path)?,
+        format!("owner,age,id\nAlice,4,{key}\n")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_export_row_deleted_at_later_time() -> eyre::Result<()> {
+    let directory = tempdir()?;
+    let path = directory.path().join("pets.csv");
+    let mut writer = pets_writer("test_export_row_deleted_at_later_time")?;
+    let key = Key::random();
+    let values = [Value::from("Bob"), Value::Int(5)];
+    write(&mut writer, key, &values, 2, 1)?;
+    write(&mut writer, key, &values, 4, -1)?;
+    writer.on_time_committed(None)?;
+
+    let snapshot = export_snapshot(
+        "test_export_row_deleted_at_later_time",
+        &path,
+        ExportFormat::Csv,
+        None,
+        None,
+    )?;
+    assert_eq!(
+        snapshot,
+        ExportedSnapshot {
+            rows: 0,
+            time: None
+        }
+    );
+    assert_eq!(read_to_string(&path)?, "owner,age,id\n");
+    Ok(())
+}
+
+#[test]
+fn test_export_waits_for_time() -> eyre::Result<()> {
+    let directory = tempdir()?;
+    let path = directory.path().join("pets.parquet");
+    let mut writer = pets_writer("test_export_waits_for_time")?;
+    write(
+        &mut writer,
+        Key::random(),
+        &[Value::from("Alice"), Value::Int(3)],
+        2,
+        1,
+    )?;
+    write(
+        &mut writer,
+        Key::random(),
+        &[Value::from("Bob"), Value::Int(5)],
+        4,
+        1,
+    )?;
+    writer.on_time_committed(Some(Timestamp(4)))?;
+
+    let committer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        writer.on_time_committed(Some(Timestamp(6))).unwrap();
+        writer
+    });
+    let snapshot = export_snapshot(
+        "test_export_waits_for_time",
+        &path,
+        ExportFormat::Parquet,
+        Some(Timestamp(4)),
+        Some(Duration::from_secs(10)),
+    )?;
+    assert_eq!(
+        snapshot,
+        ExportedSnapshot {
+            rows: 2,
+            time: Some(Timestamp(6)),
+        }
+    );
+    let reader = SerializedFileReader::new(File::open(&path)?)?;
+    assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+    committer.join().unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_export_timeout() -> eyre::Result<()> {
+    let directory = tempdir()?;
+    let _writer = pets_writer("test_export_timeout")?;
+    let result = export_snapshot(
+        "test_export_timeout",
+        &directory.path().join("pets.csv"),
+        ExportFormat::Csv,
+        Some(Timestamp(2)),
+        Some(Duration::from_millis(10)),
+    );
+    assert_matches!(result, Err(ExportError::Timeout { .. }));
+    assert!(!directory.path().join("pets.csv").exists());
+    Ok(())
+}
+
+#[test]
+fn test_registration() -> eyre::Result<()> {
+    let directory = tempdir()?;
+    let path = directory.path().join("pets.csv");
+    let writer = pets_writer("test_registration")?;
+    assert_matches!(
+        pets_writer("test_registration").err(),
+        Some(ExportError::AlreadyRegistered(_))
+    );
+    drop(writer);
+    assert_matches!(
+        export_snapshot("test_registration", &path, ExportFormat::Csv, None, None),
+        Err(ExportError::UnknownTable(_))
+    );
+    assert_matches!(
+        "json".parse::<ExportFormat>(),
+        Err(ExportError::UnknownFormat(_))
+    );
+    Ok(())
+}
@@ -0,0 +1,70 @@
+// Copyright © 2024 Pathway
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use pathway_engine::engine::spans;
+use pathway_engine::engine::telemetry::{
+    Config as TelemetryConfig, Sampling, Signals, TelemetryEnabled,
+};
+
+fn telemetry_config(tracing_servers: Vec<String>) -> TelemetryConfig {
+    TelemetryConfig::Enabled(Box::new(TelemetryEnabled {
+        telemetry_server: None,
+        monitoring_server: None,
+        logging_servers: vec![],
+        tracing_servers,
+        metrics_servers: vec![],
+        service_name: "pathway".to_string(),
+        service_version: "0.0.0".to_string(),
+        service_namespace: "test".to_string(),
+        service_instance_id: "test".to_string(),
+        run_id: "run".to_string(),
+        trace_parent: Some("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01".to_string()),
+        license_key: String::new(),
+        periodic_reader_interval: Duration::from_secs(60),
+        prometheus_port: None,
+        resource_attributes: BTreeMap::new(),
+        metric_attributes: BTreeMap::new(),
+        sampling: Sampling::default(),
+        export_dir: None,
+        signals: Signals::default(),
+    }))
+}
+
+#[test]
+fn test_spans_follow_telemetry_config() {
+    spans::configure(&TelemetryConfig::Disabled, Duration::ZERO);
+    assert!(!spans::is_enabled());
+    let result = spans::trace(spans::CHECKPOINT, || 42, |_| panic!("span is not exported"));
+    assert_eq!(result, 42);
+
+    // without tracing servers there is nowhere to export the spans to
+    spans::configure(&telemetry_config(vec![]), Duration::ZERO);
+    assert!(!spans::is_enabled());
+
+    spans::configure(
+        &telemetry_config(vec!["http://localhost:4317".to_string()]),
+        Duration::from_secs(3600),
+    );
+    assert!(spans::is_enabled());
+    // shorter than the minimum duration, the attributes are not even computed
+    let result = spans::trace_if_slow(spans::CONNECTOR_POLL, || 7, |_| panic!("too short"));
+    assert_eq!(result, 7);
+    let mut attributes_computed = false;
+    let result = spans::trace(
+        spans::CHECKPOINT,
+        || 8,
+        |result| {
+            attributes_computed = true;
+            vec![opentelemetry::KeyValue::new(
+                spans::CHECKPOINT_TIMESTAMP,
+                *result,
+            )]
+        },
+    );
+    assert_eq!(result, 8);
+    assert!(attributes_computed);
+
+    spans::configure(&TelemetryConfig::Disabled, Duration::ZERO);
+}
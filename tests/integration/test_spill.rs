@@ -0,0 +1,55 @@
+// Copyright © 2024 Pathway
+
+use tempfile::tempdir;
+
+use pathway_engine::engine::dataflow::operators::spill::{SpillConfig, SpillableMap};
+
+#[test]
+fn test_in_memory_without_config() -> eyre::Result<()> {
+    let mut map: SpillableMap<u64, String> = SpillableMap::new(None);
+    for key in 0..100 {
+        map.insert(key, key.to_string())?;
+    }
+    assert_eq!(map.len(), 100);
+    assert_eq!(map.spilled_len(), 0);
+    assert_eq!(map.remove(&42)?, Some("42".to_string()));
+    assert_eq!(map.remove(&42)?, None);
+    Ok(())
+}
+
+#[test]
+fn test_cold_values_are_spilled_and_read_back() -> eyre::Result<()> {
+    let spill_directory = tempdir()?;
+    let mut map: SpillableMap<u64, Vec<String>> = SpillableMap::new(Some(SpillConfig {
+        directory: spill_directory.path().join("spill"),
+        hot_entries: 10,
+    }));
+    for key in 0..100 {
+        map.insert(key, vec![key.to_string(); 3])?;
+    }
+    assert_eq!(map.len(), 100);
+    assert!(map.spilled_len() >= 90);
+
+    for key in 0..100 {
+        assert_eq!(map.remove(&key)?, Some(vec![key.to_string(); 3]));
+    }
+    assert!(map.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_reinserted_value_replaces_spilled_one() -> eyre::Result<()> {
+    let spill_directory = tempdir()?;
+    let mut map: SpillableMap<u64, u64> = SpillableMap::new(Some(SpillConfig {
+        directory: spill_directory.path().to_path_buf(),
+        hot_entries: 2,
+    }));
+    for key in 0..10 {
+        map.insert(key, key)?;
+    }
+    map.insert(0, 100)?;
+    assert_eq!(map.len(), 10);
+    assert_eq!(map.remove(&0)?, Some(100));
+    assert_eq!(map.remove(&0)?, None);
+    Ok(())
+}
@@ -0,0 +1,209 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use assert_matches::assert_matches;
+
+use pathway_engine::engine::sql::{plan_query, SqlError, TableSchema};
+use pathway_engine::engine::Type;
+
+fn schemas() -> HashMap<String, TableSchema> {
+    HashMap::from([
+        (
+            "orders".to_string(),
+            TableSchema::new(vec![
+                ("id".to_string(), Type::Int),
+                ("customer".to_string(), Type::String),
+                ("amount".to_string(), Type::Float),
+                ("quantity".to_string(), Type::Int),
+                ("ts".to_string(), Type::Int),
+                ("created_at".to_string(), Type::DateTimeUtc),
+            ]),
+        ),
+        (
+            "customers".to_string(),
+            TableSchema::new(vec![
+                ("name".to_string(), Type::String),
+                ("country".to_string(), Type::String),
+            ]),
+        ),
+    ])
+}
+
+fn optional(dtype: Type) -> Type {
+    Type::Optional(Arc::new(dtype))
+}
+
+#[test]
+fn test_select_where() -> eyre::Result<()> {
+    let plan = plan_query(
+        "SELECT customer, amount * quantity AS total, quantity / 2 FROM orders WHERE amount > 10",
+        &schemas(),
+    )?;
+    assert_eq!(plan.column_names(), ["customer", "total", "_col_2"]);
+    assert_eq!(plan.dtypes(), [Type::String, Type::Float, Type::Float]);
+    assert_eq!(plan.tables(), ["orders"]);
+    Ok(())
+}
+
+#[test]
+fn test_select_wildcard() -> eyre::Result<()> {
+    let plan = plan_query("SELECT * FROM customers AS c", &schemas())?;
+    assert_eq!(plan.column_names(), ["name", "country"]);
+    assert_eq!(plan.dtypes(), [Type::String, Type::String]);
+    Ok(())
+}
+
+#[test]
+fn test_group_by() -> eyre::Result<()> {
+    let plan = plan_query(
+        "SELECT customer, COUNT(*) AS orders, SUM(quantity) AS items, AVG(amount) AS average, \
+         MAX(amount) AS largest FROM orders GROUP BY customer HAVING COUNT(*) > 1",
+        &schemas(),
+    )?;
+    assert_eq!(
+        plan.column_names(),
+        ["customer", "orders", "items", "average", "largest"]
+    );
+    assert_eq!(
+        plan.dtypes(),
+        [Type::String, Type::Int, Type::Int, Type::Float, Type::Float]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_global_aggregate() -> eyre::Result<()> {
+    let plan = plan_query(
+        "SELECT COUNT(DISTINCT customer) AS customers FROM orders",
+        &schemas(),
+    )?;
+    assert_eq!(plan.column_names(), ["customers"]);
+    assert_eq!(plan.dtypes(), [Type::Int]);
+    Ok(())
+}
+
+#[test]
+fn test_tumbling_windows() -> eyre::Result<()> {
+    let plan = plan_query(
+        "SELECT TUMBLE(ts, 10) AS window_start, TUMBLE_END(ts, 10) AS window_end, \
+         SUM(amount) AS total FROM orders GROUP BY TUMBLE(ts, 10), TUMBLE_END(ts, 10)",
+        &schemas(),
+    )?;
+    assert_eq!(plan.dtypes(), [Type::Int, Type::Int, Type::Float]);
+
+    let plan = plan_query(
+        "SELECT customer, TUMBLE(created_at, INTERVAL '5' MINUTE) AS window_start, \
+         COUNT(*) AS orders FROM orders \
+         GROUP BY customer, TUMBLE(created_at, INTERVAL '5' MINUTE)",
+        &schemas(),
+    )?;
+    assert_eq!(plan.dtypes(), [Type::String, Type::DateTimeUtc, Type::Int]);
+    Ok(())
+}
+
+#[test]
+fn test_joins() -> eyre::Result<()> {
+    let plan = plan_query(
+        "SELECT o.id, c.country FROM orders AS o JOIN customers AS c ON o.customer = c.name",
+        &schemas(),
+    )?;
+    assert_eq!(plan.column_names(), ["id", "country"]);
+    assert_eq!(plan.dtypes(), [Type::Int, Type::String]);
+    assert_eq!(plan.tables(), ["orders", "customers"]);
+
+    let plan = plan_query(
+        "SELECT id, country, amount + 1 AS amount FROM orders \
+         LEFT JOIN customers ON name = customer WHERE country = 'PL'",
+        &schemas(),
+    )?;
+    assert_eq!(
+        plan.dtypes(),
+        [Type::Int, optional(Type::String), Type::Float]
+    );
+
+    let plan = plan_query(
+        "SELECT country, COUNT(*) AS orders FROM orders \
+         FULL JOIN customers ON customer = name GROUP BY country",
+        &schemas(),
+    )?;
+    assert_eq!(plan.dtypes(), [optional(Type::String), Type::Int]);
+    Ok(())
+}
+
+#[test]
+fn test_invalid_queries() {
+    let schemas = schemas();
+    assert_matches!(
+        plan_query("SELECT price FROM orders", &schemas),
+        Err(SqlError::UnknownColumn(column)) if column == "price"
+    );
+    assert_matches!(
+        plan_query("SELECT * FROM products", &schemas),
+        Err(SqlError::UnknownTable(table)) if table == "products"
+    );
+    assert_matches!(
+        plan_query("SELECT customer, amount FROM orders GROUP BY customer", &schemas),
+        Err(SqlError::NotGrouped(column)) if column == "amount"
+    );
+    assert_matches!(
+        plan_query("SELECT id FROM orders WHERE COUNT(*) > 1", &schemas),
+        Err(SqlError::MisplacedAggregate(_))
+    );
+    assert_matches!(
+        plan_query("SELECT customer + 1 FROM orders", &schemas),
+        Err(SqlError::InvalidTypes { .. })
+    );
+    assert_matches!(
+        plan_query("SELECT id FROM orders ORDER BY id", &schemas),
+        Err(SqlError::Unsupported(_))
+    );
+    assert_matches!(
+        plan_query(
+            "SELECT id FROM orders JOIN customers ON amount > 1",
+            &schemas
+        ),
+        Err(SqlError::Unsupported(_))
+    );
+    assert_matches!(
+        plan_query("SELECT id, id FROM orders", &schemas),
+        Err(SqlError::DuplicateColumn(column)) if column == "id"
+    );
+    assert_matches!(
+        plan_query("DELETE FROM orders", &schemas),
+        Err(SqlError::NotAQuery)
+    );
+    assert_matches!(
+        plan_query("SELECT FROM WHERE", &schemas),
+        Err(SqlError::Parse(_))
+    );
+}
+
+#[test]
+fn test_unsupported_windows() {
+    let schemas = schemas();
+    for query in [
+        "SELECT id, SUM(amount) OVER (PARTITION BY customer) FROM orders",
+        "SELECT id, ROW_NUMBER() OVER (ORDER BY id) + 1 FROM orders",
+        "SELECT id FROM orders QUALIFY id > 1",
+        "SELECT HOP(ts, 2, 10), COUNT(*) FROM orders GROUP BY HOP(ts, 2, 10)",
+        "SELECT SESSION_END(ts, 5), COUNT(*) FROM orders GROUP BY SESSION_END(ts, 5)",
+    ] {
+        assert_matches!(
+            plan_query(query, &schemas),
+            Err(SqlError::UnsupportedWindow(_)),
+            "{query}"
+        );
+    }
+    let error = plan_query(
+        "SELECT id, SUM(amount) OVER (PARTITION BY customer) FROM orders",
+        &schemas,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "window functions like SUM with OVER are not supported, only tumbling windows with \
+         TUMBLE(time, size) are"
+    );
+}
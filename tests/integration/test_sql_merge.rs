@@ -0,0 +1,130 @@
+// Copyright © 2024 Pathway
+
+use pathway_engine::connectors::data_format::FormatterContext;
+use pathway_engine::connectors::sql_merge::{SqlRow, SqlSinkBuffer, SqlSinkError, SqlSinkMode};
+use pathway_engine::connectors::{mssql, oracle};
+use pathway_engine::engine::{Key, Timestamp, Type, Value};
+use pathway_engine::python_api::ValueField;
+
+fn field_names() -> Vec<String> {
+    vec!["id".to_string(), "name".to_string()]
+}
+
+fn snapshot_mode() -> SqlSinkMode {
+    SqlSinkMode::Snapshot {
+        key_field_names: vec!["id".to_string()],
+    }
+}
+
+fn change(id: i64, name: &str, time: u64, diff: isize) -> FormatterContext {
+    FormatterContext::new_single_payload(
+        Vec::new(),
+        Key::random(),
+        vec![Value::Int(id), Value::from(name)],
+        Timestamp(time),
+        diff,
+    )
+}
+
+fn row(id: i64, name: &str, time: u64, diff: isize) -> SqlRow {
+    SqlRow {
+        values: vec![Value::Int(id), Value::from(name)],
+        time: Timestamp(time),
+        diff,
+    }
+}
+
+#[test]
+fn test_stream_of_changes_keeps_all_rows() -> eyre::Result<()> {
+    let mut buffer = SqlSinkBuffer::new(
+        "users".to_string(),
+        field_names(),
+        &SqlSinkMode::StreamOfChanges,
+    )?;
+    buffer.push(change(1, "a", 2, 1));
+    buffer.push(change(1, "a", 4, -1));
+    buffer.push(change(1, "b", 4, 1));
+    assert_eq!(
+        buffer.take_rows(),
+        [row(1, "a", 2, 1), row(1, "a", 4, -1), row(1, "b", 4, 1)]
+    );
+    assert!(buffer.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_keeps_final_change_of_each_key() -> eyre::Result<()> {
+    let mut buffer = SqlSinkBuffer::new("users".to_string(), field_names(), &snapshot_mode())?;
+    buffer.push(change(1, "a", 2, 1));
+    buffer.push(change(2, "x", 2, 1));
+    // The insertion of the new value may come before the deletion of the old one
+    buffer.push(change(1, "b", 4, 1));
+    buffer.push(change(1, "a", 4, -1));
+    buffer.push(change(2, "x", 6, -1));
+    assert_eq!(buffer.take_rows(), [row(1, "b", 4, 1), row(2, "x", 6, -1)]);
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_requires_known_key() {
+    let mode = SqlSinkMode::Snapshot {
+        key_field_names: vec!["email".to_string()],
+    };
+    assert!(matches!(
+        SqlSinkBuffer::new("users".to_string(), field_names(), &mode),
+        Err(SqlSinkError::UnknownKey(key)) if key == "email"
+    ));
+}
+
+#[test]
+fn test_mssql_statements() -> eyre::Result<()> {
+    let value_fields = [
+        ValueField {
+            name: "id".to_string(),
+            type_: Type::Int,
+            default: None,
+            metadata: None,
+        },
+        ValueField {
+            name: "name".to_string(),
+            type_: Type::Optional(Type::String.into()),
+            default: None,
+            metadata: None,
+        },
+    ];
+    assert_eq!(
+        mssql::create_staging_table_statement(&value_fields)?,
+        "CREATE TABLE #pathway_staging (id BIGINT NULL,name NVARCHAR(MAX) NULL,time BIGINT NOT NULL,diff BIGINT NOT NULL)"
+    );
+    assert_eq!(
+        mssql::insert_statement("users", &field_names()),
+        "INSERT INTO users (id,name,time,diff) SELECT id,name,time,diff FROM #pathway_staging"
+    );
+    assert_eq!(
+        mssql::merge_statement("users", &field_names(), &["id"]),
+        "MERGE INTO users AS target USING #pathway_staging AS source ON target.id=source.id \
+         WHEN MATCHED AND source.diff<0 THEN DELETE \
+         WHEN MATCHED THEN UPDATE SET target.name=source.name,target.time=source.time,target.diff=source.diff \
+         WHEN NOT MATCHED BY TARGET AND source.diff>0 THEN \
+         INSERT (id,name,time,diff) VALUES (source.id,source.name,source.time,source.diff);"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_oracle_statements() {
+    assert_eq!(
+        oracle::insert_statement("users", &field_names()),
+        "INSERT INTO users (id,name,time,diff) VALUES (:1,:2,:3,:4)"
+    );
+    assert_eq!(
+        oracle::merge_statement("users", &field_names(), &["id"]),
+        "MERGE INTO users target \
+         USING (SELECT :1 AS id,:2 AS name,:3 AS time,:4 AS diff FROM dual) source \
+         ON (target.id=source.id) \
+         WHEN MATCHED THEN UPDATE SET target.name=source.name,target.time=source.time,target.diff=source.diff \
+         DELETE WHERE source.diff<0 \
+         WHEN NOT MATCHED THEN INSERT (id,name,time,diff) \
+         VALUES (source.id,source.name,source.time,source.diff) WHERE source.diff>0"
+    );
+}
@@ -0,0 +1,182 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use pathway_engine::connectors::data_storage::{
+    ReadError, ReadResult, Reader, ReaderContext, ValuesMap,
+};
+use pathway_engine::connectors::sql_polling::{
+    SqlPollingClient, SqlPollingConfig, SqlPollingError, SqlPollingReader,
+};
+use pathway_engine::connectors::{OffsetKey, OffsetValue};
+use pathway_engine::engine::{Type, Value};
+use pathway_engine::persistence::frontier::OffsetAntichain;
+
+/// A table with an auto-incremented `id` column, answering the query
+/// `SELECT id, name FROM table WHERE id > $1 ORDER BY id`.
+#[derive(Clone, Default)]
+struct FakeTable {
+    rows: Arc<Mutex<Vec<(i64, Value)>>>,
+    bound_cursors: Arc<Mutex<Vec<Value>>>,
+}
+
+impl FakeTable {
+    fn insert(&self, name: Value) {
+        let mut rows = self.rows.lock().unwrap();
+        let id = i64::try_from(rows.len()).unwrap() + 1;
+        rows.push((id, name));
+    }
+}
+
+impl SqlPollingClient for FakeTable {
+    fn fetch_rows(
+        &mut self,
+        _query: &str,
+        cursor: &Value,
+        _schema: &[(String, Type)],
+    ) -> Result<Vec<ValuesMap>, SqlPollingError> {
+        self.bound_cursors.lock().unwrap().push(cursor.clone());
+        let Value::Int(cursor) = cursor else {
+            panic!("unexpected cursor: {cursor:?}");
+        };
+        Ok(self
+            .rows
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(id, _)| id > cursor)
+            .map(|(id, name)| {
+                HashMap::from([
+                    ("id".to_string(), Ok(Value::Int(*id))),
+                    ("name".to_string(), Ok(name.clone())),
+                ])
+                .into()
+            })
+            .collect())
+    }
+
+    fn database_name(&self) -> &'static str {
+        "Fake"
+    }
+}
+
+fn config(start_from: i64) -> SqlPollingConfig {
+    SqlPollingConfig {
+        query: "SELECT id, name FROM table WHERE id > $1 ORDER BY id".to_string(),
+        cursor_column: "id".to_string(),
+        start_from: Value::Int(start_from),
+        poll_interval: Duration::ZERO,
+    }
+}
+
+fn schema() -> Vec<(String, Type)> {
+    vec![
+        ("id".to_string(), Type::Int),
+        ("name".to_string(), Type::String),
+    ]
+}
+
+/// Reads the rows returned by a single poll, along with the last offset.
+fn read_poll(
+    reader: &mut SqlPollingReader<FakeTable>,
+) -> eyre::Result<(Vec<Value>, OffsetAntichain)> {
+    let mut names = Vec::new();
+    let mut frontier = OffsetAntichain::new();
+    loop {
+        match reader.read()? {
+            ReadResult::Data(ReaderContext::Diff((_, _, values)), (offset_key, offset_value)) => {
+                names.push(values.get("name").unwrap().clone().unwrap());
+                frontier.advance_offset(offset_key, offset_value);
+            }
+            ReadResult::FinishedSource { .. } => return Ok((names, frontier)),
+            other => panic!("unexpected read result: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_sql_polling_reads_new_rows() -> eyre::Result<()> {
+    let table = FakeTable::default();
+    table.insert(Value::from("a"));
+    table.insert(Value::from("b"));
+    let mut reader = SqlPollingReader::new(table.clone(), config(0), schema())?;
+
+    let (names, _) = read_poll(&mut reader)?;
+    assert_eq!(names, [Value::from("a"), Value::from("b")]);
+
+    table.insert(Value::from("c"));
+    let (names, frontier) = read_poll(&mut reader)?;
+    assert_eq!(names, [Value::from("c")]);
+    assert_eq!(
+        frontier.get_offset(&OffsetKey::Empty),
+        Some(&OffsetValue::SqlPollingCursor {
+            high_water_mark: Value::Int(3),
+            total_entries_read: 3,
+        })
+    );
+
+    let bound_cursors = table.bound_cursors.lock().unwrap().clone();
+    assert_eq!(bound_cursors, [Value::Int(0), Value::Int(2)]);
+    Ok(())
+}
+
+#[test]
+fn test_sql_polling_starts_from_given_value() -> eyre::Result<()> {
+    let table = FakeTable::default();
+    for name in ["a", "b", "c"] {
+        table.insert(Value::from(name));
+    }
+    let mut reader = SqlPollingReader::new(table, config(2), schema())?;
+    let (names, _) = read_poll(&mut reader)?;
+    assert_eq!(names, [Value::from("c")]);
+    Ok(())
+}
+
+#[test]
+fn test_sql_polling_continues_from_high_water_mark() -> eyre::Result<()> {
+    let table = FakeTable::default();
+    table.insert(Value::from("a"));
+    table.insert(Value::from("b"));
+    let mut reader = SqlPollingReader::new(table.clone(), config(0), schema())?;
+    let (_, frontier) = read_poll(&mut reader)?;
+
+    table.insert(Value::from("c"));
+    let mut restarted_reader = SqlPollingReader::new(table, config(0), schema())?;
+    restarted_reader.seek(&frontier)?;
+    let (names, frontier) = read_poll(&mut restarted_reader)?;
+    assert_eq!(names, [Value::from("c")]);
+    assert_eq!(
+        frontier.get_offset(&OffsetKey::Empty),
+        Some(&OffsetValue::SqlPollingCursor {
+            high_water_mark: Value::Int(3),
+            total_entries_read: 3,
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn test_sql_polling_rejects_null_cursor() -> eyre::Result<()> {
+    let table = FakeTable::default();
+    table.insert(Value::None);
+    let mut config = config(0);
+    config.cursor_column = "name".to_string();
+    let mut reader = SqlPollingReader::new(table, config, schema())?;
+    assert!(matches!(
+        reader.read(),
+        Err(ReadError::SqlPolling(SqlPollingError::InvalidCursor(..)))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_sql_polling_requires_cursor_in_schema() {
+    let mut config = config(0);
+    config.cursor_column = "created_at".to_string();
+    assert!(matches!(
+        SqlPollingReader::new(FakeTable::default(), config, schema()),
+        Err(SqlPollingError::UnknownCursorColumn(_))
+    ));
+}
@@ -1,10 +1,12 @@
 // Copyright © 2024 Pathway
 
 use super::helpers::create_persistence_manager;
+use super::helpers::create_rewinding_persistence_manager;
 use super::helpers::get_entries_in_receiver;
 
 use assert_matches::assert_matches;
 use pathway_engine::engine::Timestamp;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
@@ -62,7 +64,19 @@ fn read_persistent_buffer_full(
     persistent_id: PersistentId,
     persistence_mode: PersistenceMode,
 ) -> Vec<SnapshotEvent> {
-    let tracker = create_persistence_manager(chunks_root, false);
+    read_persistent_buffer_rewound(chunks_root, persistent_id, persistence_mode, None)
+}
+
+fn read_persistent_buffer_rewound(
+    chunks_root: &Path,
+    persistent_id: PersistentId,
+    persistence_mode: PersistenceMode,
+    rewind_threshold: Option<Timestamp>,
+) -> Vec<SnapshotEvent> {
+    let rewind_thresholds = rewind_threshold
+        .map(|threshold| HashMap::from([(persistent_id, threshold)]))
+        .unwrap_or_default();
+    let tracker = create_rewinding_persistence_manager(chunks_root, rewind_thresholds);
     let (sender, receiver) = channel::unbounded();
     Connector::rewind_from_disk_snapshot(persistent_id, &tracker, &sender, persistence_mode)
         .expect("Snapshot rewind failure breaks data integrity");
@@ -397,3 +411,52 @@ fn test_stream_snapshot_stateless() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_buffer_rewind_to_past_time() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    let tracker = create_persistence_manager(test_storage_path, true);
+    let mock_sink_id = tracker.lock().unwrap().register_sink();
+    let buffer = tracker
+        .lock()
+        .unwrap()
+        .create_snapshot_writer(42, SnapshotMode::Full)
+        .unwrap();
+
+    let event1 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]);
+    let event2 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(2)]);
+    let event3 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(3)]);
+    for (event, time) in [(&event1, 2), (&event2, 4), (&event3, 6)] {
+        buffer.lock().unwrap().write(event);
+        buffer.lock().unwrap().write(&SnapshotEvent::AdvanceTime(
+            Timestamp(time),
+            OffsetAntichain::new(),
+        ));
+    }
+    flush_snapshot_writer_blocking(&mut buffer.lock().unwrap());
+    tracker
+        .lock()
+        .unwrap()
+        .update_sink_finalized_time(mock_sink_id, Some(Timestamp(7)))?;
+
+    // the batch in progress at the rewind time is kept, the later ones are not
+    assert_eq!(
+        read_persistent_buffer_rewound(
+            test_storage_path,
+            42,
+            PersistenceMode::Batch,
+            Some(Timestamp(3))
+        ),
+        vec![event1.clone(), event2.clone()]
+    );
+
+    // the data read after the rewind time is removed from the snapshot
+    assert_eq!(
+        read_persistent_buffer_full(test_storage_path, 42, PersistenceMode::Batch),
+        vec![event1, event2]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use pathway_engine::connectors::data_format::FormatterContext;
+use pathway_engine::connectors::data_storage::{
+    ReadError, ReadResult, Reader, ReaderContext, Writer,
+};
+use pathway_engine::connectors::subprocess::{
+    RestartPolicy, SubprocessCommand, SubprocessError, SubprocessReader, SubprocessWriter,
+};
+use pathway_engine::engine::{Key, Timestamp};
+
+fn shell_command(script: &str, restart_policy: RestartPolicy) -> SubprocessCommand {
+    SubprocessCommand {
+        program: "sh".to_string(),
+        args: vec!["-c".to_string(), script.to_string()],
+        env: HashMap::new(),
+        working_directory: None,
+        restart_policy,
+        max_restarts: Some(2),
+        restart_delay: Duration::ZERO,
+    }
+}
+
+fn read_lines(reader: &mut SubprocessReader) -> Result<Vec<String>, ReadError> {
+    let mut lines = Vec::new();
+    loop {
+        match reader.read()? {
+            ReadResult::Data(ReaderContext::RawBytes(_, bytes), _) => {
+                lines.push(String::from_utf8(bytes).unwrap());
+            }
+            ReadResult::Finished => return Ok(lines),
+            other => panic!("unexpected read result: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_subprocess_reader_reads_lines() -> eyre::Result<()> {
+    let mut reader = SubprocessReader::new(shell_command(
+        "printf 'foo\\nbar\\r\\nbaz'",
+        RestartPolicy::Never,
+    ));
+    assert_eq!(read_lines(&mut reader)?, ["foo", "bar", "baz"]);
+    Ok(())
+}
+
+#[test]
+fn test_subprocess_reader_passes_env() -> eyre::Result<()> {
+    let mut command = shell_command("echo \"$GREETING\"", RestartPolicy::Never);
+    command
+        .env
+        .insert("GREETING".to_string(), "hello".to_string());
+    let mut reader = SubprocessReader::new(command);
+    assert_eq!(read_lines(&mut reader)?, ["hello"]);
+    Ok(())
+}
+
+#[test]
+fn test_subprocess_reader_restarts_always() -> eyre::Result<()> {
+    let mut reader = SubprocessReader::new(shell_command("echo run", RestartPolicy::Always));
+    assert_eq!(read_lines(&mut reader)?, ["run", "run", "run"]);
+    Ok(())
+}
+
+#[test]
+fn test_subprocess_reader_fails_after_restarts() {
+    let mut reader =
+        SubprocessReader::new(shell_command("echo run; exit 3", RestartPolicy::OnFailure));
+    let mut lines = 0;
+    let error = loop {
+        match reader.read() {
+            Ok(ReadResult::Data(..)) => lines += 1,
+            Ok(other) => panic!("unexpected read result: {other:?}"),
+            Err(error) => break error,
+        }
+    };
+    assert_eq!(lines, 3);
+    assert!(matches!(
+        error,
+        ReadError::Subprocess(SubprocessError::TooManyRestarts(_, 2, _))
+    ));
+    assert!(matches!(reader.read(), Ok(ReadResult::Finished)));
+}
+
+#[test]
+fn test_subprocess_reader_fails_without_restart() {
+    let mut reader = SubprocessReader::new(shell_command("exit 3", RestartPolicy::Never));
+    assert!(matches!(
+        reader.read(),
+        Err(ReadError::Subprocess(SubprocessError::Failed(..)))
+    ));
+}
+
+#[test]
+fn test_subprocess_reader_missing_program() {
+    let mut command = shell_command("", RestartPolicy::Never);
+    command.program = "pathway-nonexistent-program".to_string();
+    let mut reader = SubprocessReader::new(command);
+    assert!(matches!(
+        reader.read(),
+        Err(ReadError::Subprocess(SubprocessError::SpawnFailed(..)))
+    ));
+}
+
+#[test]
+fn test_subprocess_writer_writes_lines() -> eyre::Result<()> {
+    let output = tempfile::NamedTempFile::new()?;
+    let script = format!("cat > '{}'", output.path().display());
+    let mut writer = SubprocessWriter::new(shell_command(&script, RestartPolicy::Never));
+    for (payload, time) in [("foo", 2), ("bar", 4)] {
+        writer.write(FormatterContext::new_single_payload(
+            payload.as_bytes().to_vec(),
+            Key::random(),
+            Vec::new(),
+            Timestamp(time),
+            1,
+        ))?;
+    }
+    writer.flush(true)?;
+    // the command finishes when its input is closed
+    drop(writer);
+    assert_eq!(std::fs::read_to_string(output.path())?, "foo\nbar\n");
+    Ok(())
+}
@@ -0,0 +1,93 @@
+// Copyright © 2024 Pathway
+
+use std::collections::BTreeMap;
+
+use pathway_engine::engine::license::License;
+use pathway_engine::engine::telemetry::{Config, Sampling, Signals};
+
+fn metric_filter(allowlist: &[&str], denylist: &[&str]) -> Sampling {
+    Sampling {
+        metric_allowlist: allowlist.iter().map(ToString::to_string).collect(),
+        metric_denylist: denylist.iter().map(ToString::to_string).collect(),
+        ..Sampling::default()
+    }
+}
+
+#[test]
+fn test_all_metrics_enabled_by_default() {
+    let sampling = Sampling::default();
+    assert!(sampling.is_metric_enabled("latency.input"));
+    assert!(sampling.is_metric_enabled("process.memory.usage"));
+}
+
+#[test]
+fn test_metric_allowlist() {
+    let sampling = metric_filter(&["latency.input", "process.cpu.*"], &[]);
+    assert!(sampling.is_metric_enabled("latency.input"));
+    assert!(!sampling.is_metric_enabled("latency.output"));
+    assert!(sampling.is_metric_enabled("process.cpu.utime"));
+    assert!(sampling.is_metric_enabled("process.cpu.stime"));
+    assert!(!sampling.is_metric_enabled("process.memory.usage"));
+}
+
+#[test]
+fn test_metric_denylist() {
+    let sampling = metric_filter(&[], &["latency.*"]);
+    assert!(!sampling.is_metric_enabled("latency.input"));
+    assert!(!sampling.is_metric_enabled("latency.output"));
+    assert!(sampling.is_metric_enabled("process.memory.usage"));
+}
+
+#[test]
+fn test_metric_denylist_overrides_allowlist() {
+    let sampling = metric_filter(&["process.*"], &["process.cpu.stime"]);
+    assert!(sampling.is_metric_enabled("process.cpu.utime"));
+    assert!(!sampling.is_metric_enabled("process.cpu.stime"));
+}
+
+fn local_export_config(signals: Signals) -> Config {
+    Config::create(
+        &License::NoLicenseKey,
+        Some("run".to_string()),
+        None,
+        None,
+        None,
+        None,
+        BTreeMap::new(),
+        BTreeMap::new(),
+        Sampling::default(),
+        Some("/tmp/pathway-telemetry".into()),
+        signals,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_all_signals_enabled_by_default() {
+    let Config::Enabled(config) = local_export_config(Signals::default()) else {
+        panic!("telemetry should be enabled");
+    };
+    assert!(config.trace_export_dir().is_some());
+    assert!(config.metric_export_dir().is_some());
+}
+
+#[test]
+fn test_signals_disabled_independently() {
+    let Config::Enabled(config) = local_export_config(Signals {
+        traces: false,
+        ..Signals::default()
+    }) else {
+        panic!("telemetry should be enabled");
+    };
+    assert!(config.trace_export_dir().is_none());
+    assert!(config.metric_export_dir().is_some());
+
+    let Config::Enabled(config) = local_export_config(Signals {
+        metrics: false,
+        ..Signals::default()
+    }) else {
+        panic!("telemetry should be enabled");
+    };
+    assert!(config.trace_export_dir().is_some());
+    assert!(config.metric_export_dir().is_none());
+}
@@ -0,0 +1,189 @@
+// Copyright © 2024 Pathway
+
+use std::cell::Cell;
+use std::thread::sleep;
+use std::time::Duration;
+
+use tempfile::tempdir;
+
+use pathway_engine::engine::error::DynResult;
+use pathway_engine::engine::{UdfCache, UdfCacheConfig, Value};
+use pathway_engine::persistence::backends::{FilesystemKVStorage, PersistenceBackend};
+
+fn double(input: &[&[Value]]) -> Vec<DynResult<Value>> {
+    input
+        .iter()
+        .map(|args| Ok(Value::Int(args[0].as_int()? * 2)))
+        .collect()
+}
+
+#[test]
+fn test_cache_computes_only_missing() {
+    let cache = UdfCache::new("double", UdfCacheConfig::default(), None);
+    let computed = Cell::new(0);
+    let run = |values: &[i64]| -> Vec<i64> {
+        let rows: Vec<Vec<Value>> = values.iter().map(|v| vec![Value::Int(*v)]).collect();
+        let input: Vec<&[Value]> = rows.iter().map(Vec::as_slice).collect();
+        cache
+            .apply(&input, |missing| {
+                computed.set(computed.get() + missing.len());
+                double(missing)
+            })
+            .into_iter()
+            .map(|result| result.unwrap().as_int().unwrap())
+            .collect()
+    };
+    assert_eq!(run(&[1, 2]), vec![2, 4]);
+    assert_eq!(computed.get(), 2);
+    assert_eq!(run(&[2, 3, 1]), vec![4, 6, 2]);
+    assert_eq!(computed.get(), 3);
+}
+
+#[test]
+fn test_cache_computes_repeated_calls_once() {
+    let cache = UdfCache::new("repeated", UdfCacheConfig::default(), None);
+    let computed = Cell::new(0);
+    let rows: Vec<Vec<Value>> = [1, 2, 1, 1].iter().map(|v| vec![Value::Int(*v)]).collect();
+    let input: Vec<&[Value]> = rows.iter().map(Vec::as_slice).collect();
+    let results: Vec<i64> = cache
+        .apply(&input, |missing| {
+            computed.set(computed.get() + missing.len());
+            double(missing)
+        })
+        .into_iter()
+        .map(|result| result.unwrap().as_int().unwrap())
+        .collect();
+    assert_eq!(results, vec![2, 4, 2, 2]);
+    assert_eq!(computed.get(), 2);
+}
+
+#[test]
+fn test_cache_size_bound() {
+    let config = UdfCacheConfig {
+        max_entries: Some(2),
+        ttl: None,
+    };
+    let cache = UdfCache::new("bounded", config, None);
+    cache.put(&[Value::Int(1)], Value::Int(10));
+    cache.put(&[Value::Int(2)], Value::Int(20));
+    // using the first entry makes the second one the least recently used
+    assert_eq!(cache.get(&[Value::Int(1)]), Some(Value::Int(10)));
+    cache.put(&[Value::Int(3)], Value::Int(30));
+    assert_eq!(cache.get(&[Value::Int(1)]), Some(Value::Int(10)));
+    assert_eq!(cache.get(&[Value::Int(2)]), None);
+    assert_eq!(cache.get(&[Value::Int(3)]), Some(Value::Int(30)));
+}
+
+#[test]
+fn test_cache_ttl() {
+    let config = UdfCacheConfig {
+        max_entries: None,
+        ttl: Some(Duration::from_millis(50)),
+    };
+    let cache = UdfCache::new("expiring", config, None);
+    cache.put(&[Value::from("a")], Value::Int(1));
+    assert_eq!(cache.get(&[Value::from("a")]), Some(Value::Int(1)));
+    sleep(Duration::from_millis(100));
+    assert_eq!(cache.get(&[Value::from("a")]), None);
+}
+
+#[test]
+fn test_cache_persistence() -> eyre::Result<()> {
+    let storage = tempdir()?;
+    let cache = UdfCache::new(
+        "persisted",
+        UdfCacheConfig::default(),
+        Some(Box::new(FilesystemKVStorage::new(storage.path())?)),
+    );
+    cache.put(&[Value::Int(7)], Value::from("seven"));
+
+    let restored = UdfCache::new(
+        "persisted",
+        UdfCacheConfig::default(),
+        Some(Box::new(FilesystemKVStorage::new(storage.path())?)),
+    );
+    let result = restored.get(&[Value::Int(7)]);
+    assert_eq!(result, Some(Value::from("seven")));
+
+    let other = UdfCache::new(
+        "other",
+        UdfCacheConfig::default(),
+        Some(Box::new(FilesystemKVStorage::new(storage.path())?)),
+    );
+    assert_eq!(other.get(&[Value::Int(7)]), None);
+    Ok(())
+}
+
+#[test]
+fn test_cache_persisted_size_bound() -> eyre::Result<()> {
+    let storage = tempdir()?;
+    let config = UdfCacheConfig {
+        max_entries: Some(2),
+        ttl: None,
+    };
+    let persisted_keys = || -> eyre::Result<usize> {
+        Ok(FilesystemKVStorage::new(storage.path())?.list_keys()?.len())
+    };
+
+    let cache = UdfCache::new(
+        "bounded",
+        config.clone(),
+        Some(Box::new(FilesystemKVStorage::new(storage.path())?)),
+    );
+    for i in 0..3 {
+        cache.put(&[Value::Int(i)], Value::Int(i * 10));
+        // the entries are evicted in the order of their creation times
+        sleep(Duration::from_millis(2));
+    }
+    assert_eq!(persisted_keys()?, 2);
+
+    let restored = UdfCache::new(
+        "bounded",
+        config,
+        Some(Box::new(FilesystemKVStorage::new(storage.path())?)),
+    );
+    assert_eq!(restored.get(&[Value::Int(0)]), None);
+    assert_eq!(restored.get(&[Value::Int(1)]), Some(Value::Int(10)));
+    restored.put(&[Value::Int(3)], Value::Int(30));
+    assert_eq!(persisted_keys()?, 2);
+
+    // a smaller bound removes the oldest of the restored entries
+    let shrunk = UdfCache::new(
+        "bounded",
+        UdfCacheConfig {
+            max_entries: Some(1),
+            ttl: None,
+        },
+        Some(Box::new(FilesystemKVStorage::new(storage.path())?)),
+    );
+    assert_eq!(persisted_keys()?, 1);
+    assert_eq!(shrunk.get(&[Value::Int(2)]), None);
+    assert_eq!(shrunk.get(&[Value::Int(3)]), Some(Value::Int(30)));
+    Ok(())
+}
+
+#[test]
+fn test_cache_expired_persisted_entries_removed() -> eyre::Result<()> {
+    let storage = tempdir()?;
+    let config = UdfCacheConfig {
+        max_entries: None,
+        ttl: Some(Duration::from_millis(50)),
+    };
+    let cache = UdfCache::new(
+        "expiring",
+        config.clone(),
+        Some(Box::new(FilesystemKVStorage::new(storage.path())?)),
+    );
+    cache.put(&[Value::Int(1)], Value::Int(1));
+    sleep(Duration::from_millis(100));
+
+    let _restored = UdfCache::new(
+        "expiring",
+        config,
+        Some(Box::new(FilesystemKVStorage::new(storage.path())?)),
+    );
+    assert!(FilesystemKVStorage::new(storage.path())?
+        .list_keys()?
+        .is_empty());
+    Ok(())
+}
@@ -0,0 +1,155 @@
+// Copyright © 2024 Pathway
+
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use assert_matches::assert_matches;
+
+use pathway_engine::engine::error::{DataError, DynError, DynResult};
+use pathway_engine::engine::{UdfErrorAction, UdfErrorPolicy, Value};
+
+fn policy(on_error: UdfErrorAction, max_retries: usize) -> UdfErrorPolicy {
+    UdfErrorPolicy {
+        max_retries,
+        retry_delay: Duration::from_millis(1),
+        backoff_factor: 1.0,
+        jitter: Duration::ZERO,
+        on_error,
+        default_value: Value::Int(-1),
+    }
+}
+
+fn block_on<T>(future: impl Future<Output = T>) -> T {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+fn failing_until(attempts: &Cell<usize>, successful_attempt: usize) -> DynResult<Value> {
+    attempts.set(attempts.get() + 1);
+    if attempts.get() < successful_attempt {
+        Err(DynError::from("too early"))
+    } else {
+        Ok(Value::Int(attempts.get().try_into().unwrap()))
+    }
+}
+
+#[test]
+fn test_retries_until_success() {
+    let attempts = Cell::new(0);
+    let result = block_on(
+        policy(UdfErrorAction::Propagate, 3).retry(|| async { failing_until(&attempts, 3) }),
+    );
+    assert_eq!(result.unwrap(), Value::Int(3));
+    assert_eq!(attempts.get(), 3);
+}
+
+#[test]
+fn test_retries_exhausted() {
+    let attempts = Cell::new(0);
+    let result = block_on(
+        policy(UdfErrorAction::Propagate, 2).retry(|| async { failing_until(&attempts, 10) }),
+    );
+    assert_eq!(result.unwrap_err().to_string(), "too early");
+    assert_eq!(attempts.get(), 3);
+}
+
+#[test]
+fn test_batch_retries_only_failed_rows() {
+    let calls = RefCell::new(Vec::new());
+    let attempts = Cell::new(0);
+    let rows = [Value::Int(1), Value::Int(2), Value::Int(3)];
+    let input: Vec<&[Value]> = rows.iter().map(std::slice::from_ref).collect();
+    let results = policy(UdfErrorAction::Propagate, 3).retry_batch(&input, |batch| {
+        calls.borrow_mut().push(batch.len());
+        batch
+            .iter()
+            .map(|row| match row[0] {
+                // the second row succeeds at the third attempt, the third one never does
+                Value::Int(2) => failing_until(&attempts, 3),
+                Value::Int(3) => Err(DynError::from("always failing")),
+                _ => Ok(row[0].clone()),
+            })
+            .collect()
+    });
+    assert_eq!(results[0].as_ref().unwrap(), &Value::Int(1));
+    assert_eq!(results[1].as_ref().unwrap(), &Value::Int(3));
+    assert_eq!(
+        results[2].as_ref().unwrap_err().to_string(),
+        "always failing"
+    );
+    assert_eq!(*calls.borrow(), [3, 2, 2, 1]);
+}
+
+#[test]
+fn test_retry_does_not_block_other_calls() {
+    let slow_policy = UdfErrorPolicy {
+        retry_delay: Duration::from_millis(200),
+        ..policy(UdfErrorAction::Propagate, 1)
+    };
+    let attempts = Cell::new(0);
+    let start = Instant::now();
+    let (retried, other) = block_on(async {
+        futures::join!(
+            async {
+                slow_policy
+                    .retry(|| async { failing_until(&attempts, 2) })
+                    .await
+                    .unwrap();
+                start.elapsed()
+            },
+            async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                start.elapsed()
+            }
+        )
+    });
+    assert!(retried >= Duration::from_millis(200));
+    assert!(other < Duration::from_millis(200));
+}
+
+#[test]
+fn test_errors_in_arguments_not_retried() {
+    let attempts = Cell::new(0);
+    let policy = policy(UdfErrorAction::ReturnDefault, 3);
+    let result = block_on(policy.retry(|| async {
+        attempts.set(attempts.get() + 1);
+        Err(DataError::ErrorInValue.into())
+    }));
+    assert_eq!(attempts.get(), 1);
+    let error: DataError = policy.handle(result).unwrap_err().into();
+    assert_matches!(error, DataError::ErrorInValue);
+}
+
+#[test]
+fn test_error_actions() {
+    let failed = || Err(DynError::from("too small"));
+
+    let result = policy(UdfErrorAction::ReturnDefault, 0).handle(failed());
+    assert_eq!(result.unwrap(), Value::Int(-1));
+
+    let result = policy(UdfErrorAction::ReturnDefault, 0).handle(Ok(Value::Int(5)));
+    assert_eq!(result.unwrap(), Value::Int(5));
+
+    let error: DataError = policy(UdfErrorAction::Propagate, 0)
+        .handle(failed())
+        .unwrap_err()
+        .into();
+    assert_matches!(error, DataError::Other(_));
+
+    let error: DataError = policy(UdfErrorAction::SendToErrorLog, 0)
+        .handle(failed())
+        .unwrap_err()
+        .into();
+    assert_matches!(error, DataError::ToErrorLog(_));
+
+    let error: DataError = policy(UdfErrorAction::Fail, 0)
+        .handle(failed())
+        .unwrap_err()
+        .into();
+    assert_matches!(error, DataError::Fatal(_));
+    assert_eq!(error.to_string(), "too small");
+}
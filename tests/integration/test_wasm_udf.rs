@@ -0,0 +1,100 @@
+// Copyright © 2024 Pathway
+
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tempfile::TempDir;
+
+use pathway_engine::engine::{Value, WasmUdfLimits, WasmUdfModule};
+
+const TEST_MODULE: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "42")
+  (global $next (mut i32) (i32.const 1024))
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "answer") (param i32 i32) (result i64)
+    (i64.const 2))
+  (func (export "echo") (param $ptr i32) (param $len i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (local.get $len))))
+  (func (export "spin") (param i32 i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0))
+  (func (export "grow") (param i32 i32) (result i64)
+    (if (i32.lt_s (memory.grow (i32.const 100)) (i32.const 0)) (then unreachable))
+    (i64.const 2)))
+"#;
+
+fn load_test_module(dir: &TempDir) -> Arc<WasmUdfModule> {
+    let path = dir.path().join("udf.wat");
+    fs::write(&path, TEST_MODULE).unwrap();
+    WasmUdfModule::load(
+        &path,
+        WasmUdfLimits {
+            max_memory_bytes: 1024 * 1024,
+            timeout: Duration::from_millis(100),
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_wasm_udf_results() {
+    let dir = tempfile::tempdir().unwrap();
+    let module = load_test_module(&dir);
+
+    let answer = module.function("answer").unwrap();
+    assert_eq!(answer.call(&[]).unwrap(), Value::Int(42));
+
+    let echo = module.function("echo").unwrap();
+    let args = [Value::Int(1), Value::from("a"), Value::None];
+    assert_eq!(
+        echo.call(&args).unwrap(),
+        Value::Tuple(Arc::from(args.as_slice()))
+    );
+}
+
+#[test]
+fn test_wasm_udf_limits() {
+    let dir = tempfile::tempdir().unwrap();
+    let module = load_test_module(&dir);
+
+    let spin = module.function("spin").unwrap();
+    let error = spin.call(&[]).unwrap_err();
+    assert!(error.to_string().contains("time limit"), "{error}");
+
+    let grow = module.function("grow").unwrap();
+    assert!(grow.call(&[]).is_err());
+}
+
+#[test]
+fn test_wasm_udf_missing_function() {
+    let dir = tempfile::tempdir().unwrap();
+    let module = load_test_module(&dir);
+    assert!(module.function("missing").is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_wasm_udf_shares_epoch_ticker() {
+    let dir = tempfile::tempdir().unwrap();
+    for _ in 0..3 {
+        let module = load_test_module(&dir);
+        let answer = module.function("answer").unwrap();
+        assert_eq!(answer.call(&[]).unwrap(), Value::Int(42));
+    }
+    // thread names are truncated to 15 bytes by the kernel
+    let tickers = fs::read_dir("/proc/self/task")
+        .unwrap()
+        .filter_map(|task| fs::read_to_string(task.ok()?.path().join("comm")).ok())
+        .filter(|comm| comm.starts_with("pathway:wasm_ud"))
+        .count();
+    assert_eq!(tickers, 1);
+}